@@ -2,12 +2,17 @@ pub struct Window {}
 impl win32::Window for Window {
     fn set_title(&mut self, _title: &str) {}
     fn set_size(&mut self, _width: u32, _height: u32) {}
+    fn show(&mut self, _visible: bool) {}
+    fn set_alpha(&mut self, _alpha: u8) {}
     fn fullscreen(&mut self) {}
 }
 
 pub struct Surface {}
 impl win32::Surface for Surface {
     fn write_pixels(&mut self, _pixels: &[[u8; 4]]) {}
+    fn read_pixels(&mut self) -> Vec<[u8; 4]> {
+        Vec::new()
+    }
     fn show(&mut self) {}
     fn bit_blt(
         &mut self,
@@ -56,7 +61,11 @@ impl GUI {
             }
             true
         } else {
-            unimplemented!();
+            // There is no input to wait for; see EnvRef::block.
+            std::thread::sleep(std::time::Duration::from_millis(
+                crate::host::IDLE_MS as u64,
+            ));
+            true
         }
     }
 
@@ -64,7 +73,11 @@ impl GUI {
         Box::new(Window {})
     }
 
-    pub fn create_surface(&mut self, _opts: &win32::SurfaceOptions) -> Box<dyn win32::Surface> {
+    pub fn create_surface(
+        &mut self,
+        _hwnd: u32,
+        _opts: &win32::SurfaceOptions,
+    ) -> Box<dyn win32::Surface> {
         Box::new(Surface {})
     }
 
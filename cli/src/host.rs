@@ -4,7 +4,11 @@ use crate::headless::GUI;
 use crate::sdl::GUI;
 use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
-use std::{cell::RefCell, io::Write, rc::Rc};
+use std::{
+    cell::RefCell,
+    io::{Read, Write},
+    rc::Rc,
+};
 use typed_path::{UnixPath, WindowsPath, WindowsPathBuf};
 use win32::{FileOptions, ReadDir, Stat, ERROR};
 
@@ -22,6 +26,18 @@ impl win32::File for File {
         self.f.set_len(len)?;
         Ok(())
     }
+
+    fn set_times(&self, atime: Option<i64>, mtime: Option<i64>) -> Result<(), ERROR> {
+        let mut times = std::fs::FileTimes::new();
+        if let Some(atime) = atime {
+            times = times.set_accessed(nanos_to_system_time(atime));
+        }
+        if let Some(mtime) = mtime {
+            times = times.set_modified(nanos_to_system_time(mtime));
+        }
+        self.f.set_times(times)?;
+        Ok(())
+    }
 }
 
 impl std::io::Read for File {
@@ -90,11 +106,19 @@ impl ReadDir for ReadDirFile {
 
 pub struct Env {
     gui: Option<GUI>,
+    /// In console mode, time and blocking are handled without a GUI, and the
+    /// GUI is only initialized if the program goes on to create a window.
+    console: bool,
+    start: std::time::Instant,
 }
 
 impl Env {
-    pub fn new() -> Self {
-        Env { gui: None }
+    pub fn new(console: bool) -> Self {
+        Env {
+            gui: None,
+            console,
+            start: std::time::Instant::now(),
+        }
     }
 
     pub fn ensure_gui(&mut self) -> anyhow::Result<&mut GUI> {
@@ -105,12 +129,18 @@ impl Env {
     }
 }
 
+/// How long to sleep when blocked with nothing that could wake us.
+pub const IDLE_MS: u32 = 10;
+
 #[derive(Clone)]
 pub struct EnvRef(pub Rc<RefCell<Env>>);
 
 impl win32::Host for EnvRef {
     fn ticks(&self) -> u32 {
         let mut env = self.0.borrow_mut();
+        if env.console {
+            return env.start.elapsed().as_millis() as u32;
+        }
         let gui = env.ensure_gui().unwrap();
         gui.time()
     }
@@ -121,12 +151,30 @@ impl win32::Host for EnvRef {
 
     fn get_message(&self) -> Option<win32::Message> {
         let mut env = self.0.borrow_mut();
-        let gui = env.gui.as_mut().unwrap();
+        let gui = env.gui.as_mut()?;
         gui.get_message()
     }
 
     fn block(&self, wait: Option<u32>) -> bool {
         let mut env = self.0.borrow_mut();
+        if env.console {
+            let now = env.start.elapsed().as_millis() as u32;
+            if let Some(gui) = env.gui.as_mut() {
+                // The program went on to create windows, so their input can wake us too.
+                // The deadline is in our ticks, which count from a different start.
+                let wait = wait.map(|wait| gui.time() + wait.saturating_sub(now));
+                return gui.block(wait);
+            }
+            // Nothing but a timeout can wake us without a GUI.  Without one, as for a
+            // console program waiting on a message that never comes, idle briefly and
+            // let the caller recheck rather than spin.
+            let remaining = match wait {
+                Some(wait) => wait.saturating_sub(now),
+                None => IDLE_MS,
+            };
+            std::thread::sleep(std::time::Duration::from_millis(remaining as u64));
+            return true;
+        }
         let gui = env.gui.as_mut().unwrap();
         gui.block(wait)
     }
@@ -200,8 +248,45 @@ impl win32::Host for EnvRef {
         Ok(())
     }
 
+    fn rename(&self, from: &WindowsPath, to: &WindowsPath) -> Result<(), ERROR> {
+        std::fs::rename(windows_to_host_path(from), windows_to_host_path(to))?;
+        Ok(())
+    }
+
+    fn set_readonly(&self, path: &WindowsPath, readonly: bool) -> Result<(), ERROR> {
+        let path = windows_to_host_path(path);
+        let mut perms = std::fs::metadata(&path)?.permissions();
+        perms.set_readonly(readonly);
+        std::fs::set_permissions(path, perms)?;
+        Ok(())
+    }
+
     fn log(&self, buf: &[u8]) {
-        std::io::stdout().lock().write_all(buf).unwrap();
+        let mut stdout = std::io::stdout().lock();
+        stdout.write_all(buf).unwrap();
+        stdout.flush().unwrap();
+    }
+
+    fn debug_string(&self, ticks: u32, thread_id: u32, msg: &str) {
+        // Kept off stdout so it doesn't mix into a console program's output.
+        eprintln!(
+            "[{:4}.{:03}s thread {thread_id}] {msg}",
+            ticks / 1000,
+            ticks % 1000
+        );
+    }
+
+    fn read_stdin(&self, buf: &mut [u8]) -> Result<usize, ERROR> {
+        Ok(std::io::stdin().lock().read(buf)?)
+    }
+
+    fn message_box(&self, _id: u32, msgbox: &win32::MessageBox) -> Result<usize, ERROR> {
+        // The message was already logged; there's no one to ask, so take the default.
+        Ok(msgbox.default)
+    }
+
+    fn clone_host(&self) -> Box<dyn win32::Host> {
+        Box::new(self.clone())
     }
 
     fn create_window(&mut self, hwnd: u32) -> Box<dyn win32::Window> {
@@ -212,12 +297,12 @@ impl win32::Host for EnvRef {
 
     fn create_surface(
         &mut self,
-        _hwnd: u32,
+        hwnd: u32,
         opts: &win32::SurfaceOptions,
     ) -> Box<dyn win32::Surface> {
         let mut env = self.0.borrow_mut();
         let gui = env.ensure_gui().unwrap();
-        gui.create_surface(opts)
+        gui.create_surface(hwnd, opts)
     }
 
     fn init_audio(&mut self, sample_rate: u32) -> Box<dyn win32::Audio> {
@@ -227,8 +312,8 @@ impl win32::Host for EnvRef {
     }
 }
 
-pub fn new_host() -> EnvRef {
-    EnvRef(Rc::new(RefCell::new(Env::new())))
+pub fn new_host(console: bool) -> EnvRef {
+    EnvRef(Rc::new(RefCell::new(Env::new(console))))
 }
 
 /// Convert a `SystemTime` to nanoseconds relative to the Unix epoch.
@@ -239,6 +324,15 @@ fn system_time_to_nanos(t: SystemTime) -> i64 {
     }
 }
 
+fn nanos_to_system_time(nanos: i64) -> SystemTime {
+    let d = std::time::Duration::from_nanos(nanos.unsigned_abs());
+    if nanos >= 0 {
+        UNIX_EPOCH + d
+    } else {
+        UNIX_EPOCH - d
+    }
+}
+
 fn metadata_to_stat(meta: &std::fs::Metadata) -> Stat {
     let kind = if meta.is_dir() {
         win32::StatKind::Directory
@@ -253,6 +347,7 @@ fn metadata_to_stat(meta: &std::fs::Metadata) -> Stat {
         atime: meta.accessed().map_or(0, system_time_to_nanos),
         ctime: meta.created().map_or(0, system_time_to_nanos),
         mtime: meta.modified().map_or(0, system_time_to_nanos),
+        readonly: meta.permissions().readonly(),
     }
 }
 
@@ -271,7 +366,7 @@ fn windows_to_host_path(mut path: &WindowsPath) -> PathBuf {
 }
 
 #[cfg(unix)]
-fn host_to_windows_path(path: &Path) -> WindowsPathBuf {
+pub fn host_to_windows_path(path: &Path) -> WindowsPathBuf {
     let unix_path = UnixPath::new(path.as_os_str().as_encoded_bytes());
     let windows_path = unix_path.with_windows_encoding();
     if unix_path.is_absolute() {
@@ -289,6 +384,6 @@ fn windows_to_host_path(path: &WindowsPath) -> PathBuf {
 
 #[cfg(windows)]
 #[inline]
-fn host_to_windows_path(path: &Path) -> WindowsPathBuf {
+pub fn host_to_windows_path(path: &Path) -> WindowsPathBuf {
     WindowsPathBuf::from(path.as_os_str().as_encoded_bytes())
 }
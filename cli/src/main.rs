@@ -12,7 +12,7 @@ mod resv32;
 use anyhow::anyhow;
 use std::borrow::Cow;
 use std::process::ExitCode;
-use win32::Host;
+use win32::{winapi::kernel32::DriveKind, Host};
 
 #[derive(argh::FromArgs)]
 /// win32 emulator.
@@ -25,6 +25,75 @@ struct Args {
     #[argh(option)]
     external_dll: Vec<String>,
 
+    /// map a guest path prefix to a host directory, as GUEST=HOST, e.g. C:=./sandbox
+    #[argh(option)]
+    mount: Vec<String>,
+
+    /// like --mount, but read-only
+    #[argh(option)]
+    mount_ro: Vec<String>,
+
+    /// like --mount-ro, but appearing as a CD-ROM drive
+    #[argh(option)]
+    mount_cd: Vec<String>,
+
+    /// load the registry from a regedit .reg file, and save it back there on exit
+    #[argh(option)]
+    registry: Option<String>,
+
+    /// set the volume label and hex serial number of a mount, as GUEST=LABEL[,SERIAL]
+    #[argh(option)]
+    volume: Vec<String>,
+
+    /// pin the guest clock to start at this date, as YYYY-MM-DDTHH:MM:SS, for deterministic runs
+    #[argh(option)]
+    date: Option<chrono::NaiveDateTime>,
+
+    /// ANSI code page, 1252 (default) or 932 for Japanese
+    #[argh(option)]
+    codepage: Option<u32>,
+
+    /// size of the monitor, as WIDTHxHEIGHT or WIDTHxHEIGHTxBPP (default 640x480x32);
+    /// the display starts in this mode and returns to it when a program resets it
+    #[argh(option)]
+    monitor: Option<win32::winapi::user32::DisplayMode>,
+
+    /// processor the guest sees via CPUID and GetSystemInfo: pentium, pentium-mmx (default),
+    /// pentium2, pentium3 (with SSE), pentium4 (with SSE2) or k6
+    #[argh(option)]
+    #[cfg(feature = "x86-emu")]
+    cpu: Option<x86::CpuProfile>,
+
+    /// with multiple threads, the number of instructions a thread runs before another may
+    /// take over (default 512)
+    #[argh(option)]
+    #[cfg(feature = "x86-emu")]
+    quantum: Option<u32>,
+
+    /// translate hot code to host code rather than interpreting it all; only x86-64 hosts
+    /// other than Windows support this, and others keep to the interpreter
+    #[argh(switch)]
+    #[cfg(feature = "x86-emu")]
+    jit: bool,
+
+    /// megabytes of guest address space to reserve (default 2048, at most 4095)
+    #[argh(option)]
+    #[cfg(feature = "x86-emu")]
+    memory: Option<usize>,
+
+    /// configure what the guest sees of the machine, as KEY=VALUE: cpus=N, cpu=386..686,
+    /// features=LIST of PF_ names, page_size=N, computer=NAME or user=NAME
+    #[argh(option)]
+    system: Vec<String>,
+
+    /// guest clock rate: real (default), scale:RATE, or instrs:N for one ms per N instructions
+    #[argh(option)]
+    clock: Option<win32::clock::ClockMode>,
+
+    /// rate of the RDTSC counter in MHz, by default that of the --cpu chip
+    #[argh(option)]
+    tsc_mhz: Option<u32>,
+
     /// winapi systems to trace; see trace.rs for docs
     #[argh(option)]
     win32_trace: Option<String>,
@@ -34,6 +103,28 @@ struct Args {
     #[cfg(feature = "x86-emu")]
     trace_blocks: bool,
 
+    /// write a structured trace to this file, as JSON lines: the CPU state at each block,
+    /// and winapi calls of the systems enabled by --win32-trace
+    #[argh(option)]
+    #[cfg(feature = "x86-emu")]
+    trace_out: Option<String>,
+
+    /// with --trace-out, record the CPU state at each instruction rather than each block
+    #[argh(switch)]
+    #[cfg(feature = "x86-emu")]
+    trace_instrs: bool,
+
+    /// with --trace-out, only record CPU state within these hex address ranges,
+    /// as START-END[,START-END...]
+    #[argh(option, from_str_fn(parse_trace_ranges))]
+    #[cfg(feature = "x86-emu")]
+    trace_range: Option<Vec<std::ops::Range<u32>>>,
+
+    /// with --trace-out, only record winapi calls into these DLLs, as NAME[,NAME...]
+    #[argh(option, from_str_fn(parse_trace_dlls))]
+    #[cfg(feature = "x86-emu")]
+    trace_dll: Option<Vec<String>>,
+
     /// log CPU state first time each point reached
     #[argh(option, from_str_fn(parse_trace_points))]
     trace_points: Option<std::collections::VecDeque<u32>>,
@@ -43,10 +134,60 @@ struct Args {
     #[cfg(feature = "x86-emu")]
     exit_after: Option<usize>,
 
+    /// stop a guest that makes no API calls within a budget: an instruction count,
+    /// or a time like "10s" or "500ms"
+    #[argh(option)]
+    #[cfg(feature = "x86-emu")]
+    watchdog: Option<win32::watchdog::Budget>,
+
+    /// resume from a save state written by --save-state, rather than from the exe's start;
+    /// the exe and mounts must still be given as they were
+    #[argh(option)]
+    #[cfg(feature = "x86-emu")]
+    load_state: Option<String>,
+
+    /// write a save state to this file, at the first point after --save-state-at instructions
+    /// where the machine can be saved
+    #[argh(option)]
+    #[cfg(feature = "x86-emu")]
+    save_state: Option<String>,
+
+    /// with --save-state, the instruction count to save at (default 0, as soon as possible)
+    #[argh(option)]
+    #[cfg(feature = "x86-emu")]
+    save_state_at: Option<usize>,
+
+    /// record every input from the host (time, window events, file contents) to this file,
+    /// so that --replay can repeat the run exactly
+    #[argh(option)]
+    record: Option<String>,
+
+    /// replay a run recorded with --record, taking inputs from the file rather than from
+    /// the outside world; the exe and flags must be as they were when recording
+    #[argh(option)]
+    replay: Option<String>,
+
+    /// when a win32 API hits an unimplemented path, fail the call with
+    /// ERROR_CALL_NOT_IMPLEMENTED and keep going rather than stopping
+    #[argh(switch)]
+    #[cfg(feature = "x86-emu")]
+    lenient: bool,
+
     /// enable debug logging
     #[argh(switch)]
     debug: bool,
 
+    /// run the DOS part of the exe in real mode first: its MZ stub, or a DOS program's
+    /// code; a stub that exits with code 0 then hands off to the PE
+    #[argh(switch)]
+    #[cfg(feature = "x86-emu")]
+    dos: bool,
+
+    /// run without a GUI, on stdin/stdout only, giving even GUI exes a console
+    /// (default for console-subsystem exes)
+    #[argh(switch)]
+    console: bool,
+
     /// command line to run
     #[argh(positional, greedy)]
     cmdline: Vec<String>,
@@ -103,6 +244,53 @@ fn parse_trace_points(param: &str) -> Result<std::collections::VecDeque<u32>, St
     Ok(trace_points)
 }
 
+#[cfg(feature = "x86-emu")]
+fn parse_trace_ranges(param: &str) -> Result<Vec<std::ops::Range<u32>>, String> {
+    let mut ranges = Vec::new();
+    for range in param.split(",") {
+        if range.is_empty() {
+            continue;
+        }
+        let parse =
+            |addr: &str| u32::from_str_radix(addr, 16).map_err(|_| format!("bad addr {addr:?}"));
+        let (start, end) = range
+            .split_once('-')
+            .ok_or_else(|| format!("bad range {range:?}, expected START-END"))?;
+        ranges.push(parse(start)?..parse(end)?);
+    }
+    Ok(ranges)
+}
+
+#[cfg(feature = "x86-emu")]
+fn parse_trace_dlls(param: &str) -> Result<Vec<String>, String> {
+    Ok(param
+        .split(',')
+        .filter(|dll| !dll.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// The per-process host directory backing the guest's temp dir.  The emulator empties it
+/// when the guest exits, but a run can also end in an error, the watchdog, or a failed
+/// load, so the directory and anything left in it are removed on drop.
+struct TempDir(std::path::PathBuf);
+
+impl TempDir {
+    fn create() -> anyhow::Result<Self> {
+        let path = std::env::temp_dir().join(format!("retrowin32-{}", std::process::id()));
+        std::fs::create_dir_all(&path).map_err(|err| anyhow!("{}: {}", path.display(), err))?;
+        Ok(TempDir(path))
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        if let Err(err) = std::fs::remove_dir_all(&self.0) {
+            log::warn!("{}: {}", self.0.display(), err);
+        }
+    }
+}
+
 fn main() -> anyhow::Result<ExitCode> {
     #[cfg(feature = "x86-64")]
     unsafe {
@@ -121,6 +309,19 @@ fn main() -> anyhow::Result<ExitCode> {
     }
 
     win32::trace::set_scheme(args.win32_trace.as_deref().unwrap_or("-"));
+    #[cfg(feature = "x86-emu")]
+    if let Some(path) = &args.trace_out {
+        let file = std::fs::File::create(path).map_err(|err| anyhow!("{path}: {err}"))?;
+        win32::trace::set_output(
+            Box::new(std::io::BufWriter::new(file)),
+            args.trace_range.clone().unwrap_or_default(),
+            args.trace_dll.as_deref().unwrap_or_default(),
+        );
+    }
+
+    if args.record.is_some() && args.replay.is_some() {
+        return Err(anyhow!("--record and --replay can't be used together"));
+    }
 
     let exe = args
         .cmdline
@@ -128,7 +329,11 @@ fn main() -> anyhow::Result<ExitCode> {
         .ok_or_else(|| anyhow!("missing command line"))?;
     let exe = std::fs::canonicalize(exe).map_err(|err| anyhow!("{}: {}", exe, err))?;
     let buf = std::fs::read(&exe).map_err(|err| anyhow!("{}: {}", exe.display(), err))?;
-    let host = host::new_host();
+    let console =
+        args.console || win32::pe::parse(&buf).map_or(false, |file| file.opt_header.is_console());
+    #[cfg(feature = "x86-emu")]
+    let console = console || args.dos;
+    let host = host::new_host(console);
 
     let mut cmdline = args.cmdline.clone();
     let cwd = host
@@ -144,13 +349,142 @@ fn main() -> anyhow::Result<ExitCode> {
         .map(|s| escape_arg(s))
         .collect::<Vec<_>>()
         .join(" ");
-    let mut machine = win32::Machine::new(Box::new(host.clone()), cmdline);
+    let mut wrapped: Box<dyn win32::Host> = Box::new(host.clone());
+    let mut replay = None;
+    if let Some(path) = &args.record {
+        let out = std::io::BufWriter::new(std::fs::File::create(path)?);
+        wrapped = Box::new(win32::replay::Recorder::new(wrapped, Box::new(out))?);
+    } else if let Some(path) = &args.replay {
+        let input = std::io::BufReader::new(std::fs::File::open(path)?);
+        let replayer = win32::replay::Replayer::new(wrapped, Box::new(input))?;
+        replay = Some(replayer.status());
+        wrapped = Box::new(replayer);
+    }
+    #[cfg(feature = "x86-emu")]
+    let mut machine = match args.memory {
+        Some(mb) => win32::Machine::with_memory_size(wrapped, cmdline, mb << 20)?,
+        None => win32::Machine::new(wrapped, cmdline),
+    };
+    #[cfg(not(feature = "x86-emu"))]
+    let mut machine = win32::Machine::new(wrapped, cmdline);
     machine.set_external_dlls(&args.external_dll);
+    let mounts = (args.mount.iter().map(|m| (m, false, DriveKind::Fixed)))
+        .chain(args.mount_ro.iter().map(|m| (m, true, DriveKind::Fixed)))
+        .chain(args.mount_cd.iter().map(|m| (m, true, DriveKind::CdRom)));
+    for (mount, readonly, kind) in mounts {
+        let (guest, dir) = mount
+            .split_once('=')
+            .ok_or_else(|| anyhow!("invalid mount {mount:?}, expected GUEST=HOST"))?;
+        let dir = std::fs::canonicalize(dir).map_err(|err| anyhow!("{}: {}", dir, err))?;
+        let vfs = &mut machine.state.kernel32.vfs;
+        vfs.mount(guest, host::host_to_windows_path(&dir), readonly);
+        vfs.volume_mut(guest).unwrap().kind = kind;
+    }
+    for volume in &args.volume {
+        let (guest, label) = volume
+            .split_once('=')
+            .ok_or_else(|| anyhow!("invalid volume {volume:?}, expected GUEST=LABEL[,SERIAL]"))?;
+        let (label, serial) = match label.split_once(',') {
+            Some((label, serial)) => (
+                label,
+                u32::from_str_radix(serial, 16)
+                    .map_err(|err| anyhow!("invalid serial {serial:?}: {err}"))?,
+            ),
+            None => (label, 0),
+        };
+        let info = machine
+            .state
+            .kernel32
+            .vfs
+            .volume_mut(guest)
+            .ok_or_else(|| anyhow!("invalid volume {volume:?}: no mount at {guest:?}"))?;
+        info.label = label.to_string();
+        info.serial = serial;
+    }
+    if let Some(path) = &args.registry {
+        match std::fs::read_to_string(path) {
+            Ok(text) => {
+                machine.state.advapi32.hive = win32::winapi::advapi32::Hive::parse(&text)
+                    .map_err(|err| anyhow!("{path}: {err}"))?;
+            }
+            // Start with an empty registry, to be created on exit.
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(anyhow!("{path}: {err}")),
+        }
+    }
+    if let Some(id) = args.codepage {
+        machine.state.kernel32.code_page = win32::winapi::kernel32::CodePage::from_id(id)
+            .ok_or_else(|| anyhow!("unsupported code page {id}"))?;
+    }
+    if let Some(mode) = args.monitor {
+        win32::winapi::user32::set_desktop_mode(&mut machine, mode);
+    }
+    #[cfg(feature = "x86-emu")]
+    if let Some(profile) = args.cpu {
+        machine.set_cpu_profile(profile);
+    }
+    for setting in &args.system {
+        machine
+            .state
+            .kernel32
+            .system
+            .configure(setting)
+            .map_err(|err| anyhow!("--system: {err}"))?;
+    }
+    if let Some(mode) = args.clock {
+        machine.set_clock_mode(mode);
+    }
+    if let Some(mhz) = args.tsc_mhz {
+        machine.clock.tsc_mhz = mhz;
+    }
+    if let Some(date) = args.date {
+        win32::winapi::kernel32::pin_clock(&mut machine, date);
+    }
+    #[cfg(feature = "x86-emu")]
+    {
+        machine.emu.lenient = args.lenient;
+        if let Some(quantum) = args.quantum {
+            machine.emu.x86.quantum = quantum.max(1);
+        }
+        if args.jit && !machine.emu.x86.enable_jit() {
+            log::warn!("--jit isn't supported here, interpreting instead");
+        }
+    }
+    // A scratch temp directory, removed along with its contents however the run ends.
+    let temp_dir = TempDir::create()?;
+    machine
+        .state
+        .kernel32
+        .vfs
+        .mount_temp(host::host_to_windows_path(&temp_dir.0));
+
+    #[cfg(feature = "x86-emu")]
+    if args.dos && !win32::dos::run(&mut machine, &buf) {
+        let exit_code = match &machine.status {
+            win32::Status::Exit(code) => *code,
+            win32::Status::Error { message } => {
+                log::error!("{}", message);
+                1
+            }
+            _ => unreachable!(),
+        };
+        return Ok(ExitCode::from(exit_code as u8));
+    }
 
     let addrs = machine
         .load_exe(&buf, &exe, None)
         .map_err(|err| anyhow!("loading {}: {}", exe.display(), err))?;
     _ = addrs;
+    if args.console {
+        // Give GUI programs a console too, so their std handle output is visible.
+        machine.state.kernel32.console = win32::winapi::kernel32::Console::default();
+    }
+
+    #[cfg(feature = "x86-emu")]
+    if let Some(path) = &args.load_state {
+        let buf = std::fs::read(path).map_err(|err| anyhow!("{path}: {err}"))?;
+        win32::snapshot::load(&mut machine, &buf).map_err(|err| anyhow!("{path}: {err:#}"))?;
+    }
 
     let exit_code: u32;
 
@@ -191,11 +525,46 @@ fn main() -> anyhow::Result<ExitCode> {
                     }
                 }
                 machine.clear_breakpoint(next_trace);
+                machine.unblock();
 
                 print_trace(&machine);
             }
         } else {
-            while machine.run() {
+            let mut watchdog = args.watchdog.map(win32::watchdog::Watchdog::new);
+            let mut save_state = args.save_state.as_ref();
+            loop {
+                if args.trace_out.is_some() && machine.emu.x86.cpu().state.is_running() {
+                    if args.trace_instrs {
+                        machine.emu.x86.single_step_next_block();
+                    }
+                    win32::trace::record_cpu(machine.emu.x86.cpu());
+                }
+                if !machine.run() {
+                    break;
+                }
+                if let Some(err) = replay.as_ref().and_then(|replay| replay.error()) {
+                    machine.status = win32::Status::Error { message: err };
+                    break;
+                }
+                if let Some(watchdog) = &mut watchdog {
+                    if let Some(report) = watchdog.check(&machine) {
+                        eprint!("{report}");
+                        machine.status = win32::Status::Error {
+                            message: "watchdog: guest hung".into(),
+                        };
+                        break;
+                    }
+                }
+                if let Some(path) = save_state {
+                    if machine.emu.x86.instr_count >= args.save_state_at.unwrap_or(0) {
+                        // Not every point can be saved; keep trying until one can.
+                        if let Ok(buf) = win32::snapshot::save(&mut machine) {
+                            std::fs::write(path, buf).map_err(|err| anyhow!("{path}: {err}"))?;
+                            log::info!("saved state to {path}");
+                            save_state = None;
+                        }
+                    }
+                }
                 if let Some(exit_after) = args.exit_after {
                     if machine.emu.x86.instr_count >= exit_after {
                         machine.status = win32::Status::Exit(0);
@@ -205,6 +574,10 @@ fn main() -> anyhow::Result<ExitCode> {
             }
         }
 
+        // A replay that ran out or diverged can still reach an exit, but not the recorded one.
+        if let Some(err) = replay.as_ref().and_then(|replay| replay.error()) {
+            machine.status = win32::Status::Error { message: err };
+        }
         match &machine.status {
             win32::Status::Exit(code) => {
                 exit_code = *code;
@@ -214,8 +587,14 @@ fn main() -> anyhow::Result<ExitCode> {
                 machine.dump_state(0);
                 exit_code = 1;
             }
+            win32::Status::DebugBreak => {
+                log::error!("debug break");
+                machine.dump_state(0);
+                exit_code = 1;
+            }
             _ => unreachable!(),
         }
+        win32::trace::flush_output();
 
         let millis = start.elapsed().as_millis() as usize;
         if millis > 0 {
@@ -225,7 +604,7 @@ fn main() -> anyhow::Result<ExitCode> {
                 millis,
                 (machine.emu.x86.instr_count / millis) / 1000
             );
-            eprintln!("icache: {}", machine.emu.x86.icache.stats());
+            eprintln!("{}", machine.emu.x86.backend_stats());
         }
     }
 
@@ -249,25 +628,44 @@ fn main() -> anyhow::Result<ExitCode> {
         }
     }
 
+    if let Some(path) = &args.registry {
+        std::fs::write(path, machine.state.advapi32.hive.to_reg())
+            .map_err(|err| anyhow!("{path}: {err}"))?;
+    }
+    #[cfg(not(feature = "x86-emu"))]
+    if let Some(err) = replay.and_then(|replay| replay.error()) {
+        log::error!("{err}");
+        return Ok(ExitCode::from(1));
+    }
     Ok(ExitCode::from(exit_code as u8))
 }
 
+/// Quote an argument such that CommandLineToArgvW will parse it back.
 fn escape_arg(arg: &str) -> Cow<str> {
-    if arg.contains(['"', ' ', '\t', '\n'].as_ref()) {
-        let mut escaped = String::with_capacity(arg.len() + 2);
-        escaped.push('"');
-        for c in arg.chars() {
-            match c {
-                '"' => {
-                    escaped.push('\\');
-                    escaped.push(c);
-                }
-                _ => escaped.push(c),
+    if !arg.is_empty() && !arg.contains(['"', ' ', '\t', '\n'].as_ref()) {
+        return Cow::Borrowed(arg);
+    }
+    let mut escaped = String::with_capacity(arg.len() + 2);
+    escaped.push('"');
+    // Backslashes are only special when followed by a quote, in which case they
+    // must be doubled.
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            '"' => {
+                escaped.extend(std::iter::repeat('\\').take(backslashes * 2 + 1));
+                escaped.push(c);
+                backslashes = 0;
+            }
+            _ => {
+                escaped.extend(std::iter::repeat('\\').take(backslashes));
+                escaped.push(c);
+                backslashes = 0;
             }
         }
-        escaped.push('"');
-        Cow::Owned(escaped)
-    } else {
-        Cow::Borrowed(arg)
     }
+    escaped.extend(std::iter::repeat('\\').take(backslashes * 2));
+    escaped.push('"');
+    Cow::Owned(escaped)
 }
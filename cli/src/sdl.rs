@@ -12,16 +12,27 @@ fn map_button(b: sdl2::mouse::MouseButton) -> Option<win32::MouseButton> {
     })
 }
 
-fn message_from_event(hwnd: u32, event: sdl2::event::Event) -> Option<win32::Message> {
-    let (time, detail) = match event {
-        sdl2::event::Event::Quit { timestamp } => (timestamp, win32::MessageDetail::Quit),
+/// Find the hwnd of the window that an SDL event was delivered to, or 0 if unknown.
+fn hwnd_for_window_id(windows: &[WindowRef], window_id: u32) -> u32 {
+    windows
+        .iter()
+        .map(|w| w.0.borrow())
+        .find(|w| w.canvas.window().id() == window_id)
+        .map_or(0, |w| w.hwnd)
+}
+
+fn message_from_event(windows: &[WindowRef], event: sdl2::event::Event) -> Option<win32::Message> {
+    let (hwnd, time, detail) = match event {
+        sdl2::event::Event::Quit { timestamp } => (0, timestamp, win32::MessageDetail::Quit),
         sdl2::event::Event::MouseButtonDown {
             timestamp,
+            window_id,
             mouse_btn,
             x,
             y,
             ..
         } => (
+            hwnd_for_window_id(windows, window_id),
             timestamp,
             win32::MessageDetail::Mouse(win32::MouseMessage {
                 down: true,
@@ -32,11 +43,13 @@ fn message_from_event(hwnd: u32, event: sdl2::event::Event) -> Option<win32::Mes
         ),
         sdl2::event::Event::MouseButtonUp {
             timestamp,
+            window_id,
             mouse_btn,
             x,
             y,
             ..
         } => (
+            hwnd_for_window_id(windows, window_id),
             timestamp,
             win32::MessageDetail::Mouse(win32::MouseMessage {
                 down: false,
@@ -46,8 +59,13 @@ fn message_from_event(hwnd: u32, event: sdl2::event::Event) -> Option<win32::Mes
             }),
         ),
         sdl2::event::Event::MouseMotion {
-            timestamp, x, y, ..
+            timestamp,
+            window_id,
+            x,
+            y,
+            ..
         } => (
+            hwnd_for_window_id(windows, window_id),
             timestamp,
             win32::MessageDetail::Mouse(win32::MouseMessage {
                 down: false,
@@ -56,6 +74,17 @@ fn message_from_event(hwnd: u32, event: sdl2::event::Event) -> Option<win32::Mes
                 y: y as u32,
             }),
         ),
+        sdl2::event::Event::Window {
+            timestamp,
+            window_id,
+            win_event:
+                win_event
+                @ (sdl2::event::WindowEvent::FocusGained | sdl2::event::WindowEvent::FocusLost),
+        } => (
+            hwnd_for_window_id(windows, window_id),
+            timestamp,
+            win32::MessageDetail::Focus(win_event == sdl2::event::WindowEvent::FocusGained),
+        ),
         _ => {
             // log::warn!("unhandled event: {:?}", event);
             return None;
@@ -65,12 +94,12 @@ fn message_from_event(hwnd: u32, event: sdl2::event::Event) -> Option<win32::Mes
 }
 
 fn message_from_events(
-    hwnd: u32,
+    windows: &[WindowRef],
     mut f: impl FnMut() -> Option<sdl2::event::Event>,
 ) -> Option<win32::Message> {
     loop {
         let event = f()?;
-        let msg = message_from_event(hwnd, event);
+        let msg = message_from_event(windows, event);
         if msg.is_some() {
             return msg;
         }
@@ -82,7 +111,8 @@ pub struct GUI {
     video: sdl2::VideoSubsystem,
     pump: sdl2::EventPump,
     timer: sdl2::TimerSubsystem,
-    win: Option<WindowRef>,
+    /// All windows created, in creation order.
+    windows: Vec<WindowRef>,
     msg_queue: Option<win32::Message>,
 }
 
@@ -99,7 +129,7 @@ impl GUI {
             video,
             pump,
             timer,
-            win: None,
+            windows: Vec::new(),
             msg_queue: None,
         })
     }
@@ -112,21 +142,13 @@ impl GUI {
         if let Some(msg) = self.msg_queue.take() {
             return Some(msg);
         }
-        let hwnd = match &self.win {
-            Some(w) => w.0.borrow().hwnd,
-            None => 0,
-        };
-        message_from_events(hwnd, || self.pump.poll_event())
+        message_from_events(&self.windows, || self.pump.poll_event())
     }
 
     pub fn block(&mut self, wait: Option<u32>) -> bool {
-        let hwnd = match &self.win {
-            Some(w) => w.0.borrow().hwnd,
-            None => 0,
-        };
         let msg = match wait {
-            Some(until) => message_from_events(hwnd, || {
-                let now = self.time();
+            Some(until) => message_from_events(&self.windows, || {
+                let now = self.timer.ticks();
                 if now >= until {
                     return None;
                 }
@@ -134,7 +156,7 @@ impl GUI {
                 self.pump.wait_event_timeout(delta)
             }),
             None => loop {
-                let msg = message_from_event(hwnd, self.pump.wait_event());
+                let msg = message_from_event(&self.windows, self.pump.wait_event());
                 if msg.is_some() {
                     break msg;
                 }
@@ -148,16 +170,24 @@ impl GUI {
     pub fn create_window(&mut self, hwnd: u32) -> Box<dyn win32::Window> {
         let win = Window::new(&self.video, hwnd);
         let win_ref = WindowRef(Rc::new(RefCell::new(win)));
-        if self.win.is_some() {
-            log::warn!("TODO: handle multiple windows");
-        } else {
-            self.win = Some(win_ref.clone());
-        }
+        self.windows.push(win_ref.clone());
         Box::new(win_ref)
     }
 
-    pub fn create_surface(&mut self, opts: &win32::SurfaceOptions) -> Box<dyn win32::Surface> {
-        Box::new(Texture::new(self.win.as_ref().unwrap(), opts))
+    pub fn create_surface(
+        &mut self,
+        hwnd: u32,
+        opts: &win32::SurfaceOptions,
+    ) -> Box<dyn win32::Surface> {
+        // Surfaces not tied to a known window (e.g. DirectDraw before SetCooperativeLevel)
+        // are drawn into the first window.
+        let win = self
+            .windows
+            .iter()
+            .find(|w| w.0.borrow().hwnd == hwnd)
+            .or(self.windows.first())
+            .expect("surface created with no windows");
+        Box::new(Texture::new(win, opts))
     }
 
     pub fn init_audio(&mut self, sample_rate: u32) -> Box<dyn win32::Audio> {
@@ -171,7 +201,11 @@ struct Window {
 }
 impl Window {
     fn new(video: &sdl2::VideoSubsystem, hwnd: u32) -> Self {
-        let win = video.window("retrowin32", 640, 480).build().unwrap();
+        let win = video
+            .window("retrowin32", 640, 480)
+            .hidden()
+            .build()
+            .unwrap();
         let canvas = win.into_canvas().build().unwrap();
         Window { hwnd, canvas }
     }
@@ -198,6 +232,24 @@ impl win32::Window for WindowRef {
             .unwrap();
     }
 
+    fn show(&mut self, visible: bool) {
+        let mut win = self.0.borrow_mut();
+        let window = win.canvas.window_mut();
+        if visible {
+            window.show();
+            window.raise();
+        } else {
+            window.hide();
+        }
+    }
+
+    fn set_alpha(&mut self, alpha: u8) {
+        let mut win = self.0.borrow_mut();
+        if let Err(err) = win.canvas.window_mut().set_opacity(alpha as f32 / 255.0) {
+            log::warn!("set_opacity: {err}");
+        }
+    }
+
     fn fullscreen(&mut self) {
         log::info!("fullscreen request ignored for debugging ease");
         // self.0
@@ -248,8 +300,29 @@ impl win32::Surface for Texture {
             .unwrap();
     }
 
+    fn read_pixels(&mut self) -> Vec<[u8; 4]> {
+        let mut pixels = Vec::new();
+        self.window
+            .0
+            .borrow_mut()
+            .canvas
+            .with_texture_canvas(&mut self.texture, |canvas| {
+                let bytes = canvas
+                    .read_pixels(None, sdl2::pixels::PixelFormatEnum::ABGR8888)
+                    .unwrap();
+                pixels = bytes
+                    .chunks_exact(4)
+                    .map(|p| [p[0], p[1], p[2], p[3]])
+                    .collect();
+            })
+            .unwrap();
+        pixels
+    }
+
     fn show(&mut self) {
         let canvas = &mut self.window.0.borrow_mut().canvas;
+        // Clear first, as transparent (color-keyed) pixels are blended onto what's underneath.
+        canvas.clear();
         // Passing None/None for the src/dst rects means to do a scaling full copy,
         // which is what we want for the fullscreen case in particular.
         canvas.copy(&self.texture, None, None).unwrap();
@@ -0,0 +1,51 @@
+// BitBlt raster operations, checked against a 32bpp DIB section.
+
+#include "test.h"
+
+struct Surface {
+  HDC dc;
+  uint32_t *pixels;
+};
+
+static Surface create_surface(HDC screen) {
+  BITMAPINFO bmi = {};
+  bmi.bmiHeader.biSize = sizeof(bmi.bmiHeader);
+  bmi.bmiHeader.biWidth = 1;
+  bmi.bmiHeader.biHeight = 1;
+  bmi.bmiHeader.biPlanes = 1;
+  bmi.bmiHeader.biBitCount = 32;
+  bmi.bmiHeader.biCompression = BI_RGB;
+  Surface s;
+  void *bits = nullptr;
+  auto bitmap = CreateDIBSection(screen, &bmi, DIB_RGB_COLORS, &bits, nullptr, 0);
+  s.dc = CreateCompatibleDC(screen);
+  SelectObject(s.dc, bitmap);
+  s.pixels = (uint32_t *)bits;
+  return s;
+}
+
+static uint32_t blit(Surface &dst, Surface &src, uint32_t d, uint32_t s,
+                     DWORD rop) {
+  dst.pixels[0] = d;
+  src.pixels[0] = s;
+  BitBlt(dst.dc, 0, 0, 1, 1, src.dc, 0, 0, rop);
+  GdiFlush();
+  return dst.pixels[0] & 0xffffff;
+}
+
+extern "C" void mainCRTStartup() {
+  HDC screen = GetDC(nullptr);
+  Surface dst = create_surface(screen);
+  Surface src = create_surface(screen);
+
+  const uint32_t d = 0x00ff00f0, s = 0x000f0ff0;
+  check_eq(blit(dst, src, d, s, SRCCOPY), s, "SRCCOPY");
+  check_eq(blit(dst, src, d, s, NOTSRCCOPY), ~s & 0xffffff, "NOTSRCCOPY");
+  check_eq(blit(dst, src, d, s, SRCAND), d & s, "SRCAND");
+  check_eq(blit(dst, src, d, s, SRCPAINT), d | s, "SRCPAINT");
+  check_eq(blit(dst, src, d, s, SRCINVERT), d ^ s, "SRCINVERT");
+  check_eq(blit(dst, src, d, s, DSTINVERT), ~d & 0xffffff, "DSTINVERT");
+  check_eq(blit(dst, src, d, s, BLACKNESS), 0, "BLACKNESS");
+  check_eq(blit(dst, src, d, s, WHITENESS), 0xffffff, "WHITENESS");
+  done();
+}
@@ -0,0 +1,24 @@
+#!/bin/sh
+
+# Builds each test program into its own .exe.
+
+set -e
+
+XWIN="${XWIN:-$HOME/.xwin-cache/splat}"
+clang_flags="-fuse-ld=lld -target i686-pc-windows-msvc -mno-sse"
+warning_flags="/W4"
+# reproducible builds, optimize for size, no security cookies
+cflags="/Brepro /Os /GS- /std:c++17 $warning_flags"
+sdk_flags="/vctoolsdir $XWIN/crt /winsdkdir $XWIN/sdk"
+link_flags="/nodefaultlib kernel32.lib user32.lib gdi32.lib"
+
+tests="${@:-heap messages blit fpu}"
+for t in $tests; do
+  # Tests of windows and their message loops are GUI programs, as they would be on
+  # Windows; test.cc attaches them to the console to print results.
+  case $t in
+  messages) subsystem="/subsystem:windows /entry:mainCRTStartup" ;;
+  *) subsystem="/subsystem:console" ;;
+  esac
+  clang-cl $clang_flags $cflags $sdk_flags $t.cc test.cc /Fe:$t.exe /link $link_flags $subsystem
+done
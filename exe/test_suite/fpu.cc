@@ -0,0 +1,56 @@
+// x87 results that depend on exact rounding behavior.
+
+#include "test.h"
+
+extern "C" int _fltused = 0;
+
+static int32_t fistp(double x) {
+  int32_t out;
+  __asm {
+    fld x
+    fistp out
+  }
+  return out;
+}
+
+static double fsqrt(double x) {
+  double out;
+  __asm {
+    fld x
+    fsqrt
+    fstp out
+  }
+  return out;
+}
+
+static double fprem(double a, double b) {
+  double out;
+  __asm {
+    fld b
+    fld a
+    fprem
+    fstp out
+    fstp st(0)
+  }
+  return out;
+}
+
+// Compare doubles bitwise, to catch off-by-one-ulp errors.
+static void check_double(double actual, double expected, const char *name) {
+  auto a = (uint32_t *)&actual;
+  auto e = (uint32_t *)&expected;
+  check(a[0] == e[0] && a[1] == e[1], name);
+}
+
+extern "C" void mainCRTStartup() {
+  // Default rounding mode is round-to-nearest-even.
+  check_eq(fistp(2.5), 2, "fistp 2.5 rounds to even");
+  check_eq(fistp(3.5), 4, "fistp 3.5 rounds to even");
+  check_eq(fistp(-2.5), (uint32_t)-2, "fistp -2.5 rounds to even");
+  check_double(fsqrt(2.0), 1.4142135623730951, "fsqrt 2");
+  check_double(fsqrt(16.0), 4.0, "fsqrt 16");
+  check_double(fprem(10.0, 3.0), 1.0, "fprem 10,3");
+  check_double(fprem(-10.0, 3.0), -1.0, "fprem -10,3");
+  check_double(1.0 / 3.0, 0.3333333333333333, "fdiv 1/3");
+  done();
+}
@@ -0,0 +1,64 @@
+// Heap API edge cases.
+
+#include "test.h"
+
+static void test_zero_size() {
+  auto heap = GetProcessHeap();
+  void *p = HeapAlloc(heap, 0, 0);
+  check(p != nullptr, "zero-size alloc succeeds");
+  check_eq(HeapSize(heap, 0, p), 0, "zero-size alloc has size 0");
+  check(HeapFree(heap, 0, p), "free zero-size alloc");
+}
+
+static void test_zero_memory() {
+  auto heap = GetProcessHeap();
+  // Dirty some memory first so a reused block isn't zero by accident.
+  auto p = (uint8_t *)HeapAlloc(heap, 0, 64);
+  for (int i = 0; i < 64; i++)
+    p[i] = 0xcc;
+  HeapFree(heap, 0, p);
+
+  p = (uint8_t *)HeapAlloc(heap, HEAP_ZERO_MEMORY, 64);
+  bool zeroed = true;
+  for (int i = 0; i < 64; i++)
+    zeroed &= p[i] == 0;
+  check(zeroed, "HEAP_ZERO_MEMORY zeroes");
+  HeapFree(heap, 0, p);
+}
+
+static void test_realloc() {
+  auto heap = GetProcessHeap();
+  auto p = (uint8_t *)HeapAlloc(heap, 0, 16);
+  for (int i = 0; i < 16; i++)
+    p[i] = (uint8_t)i;
+  p = (uint8_t *)HeapReAlloc(heap, 0, p, 4096);
+  check(p != nullptr, "realloc grow");
+  bool kept = true;
+  for (int i = 0; i < 16; i++)
+    kept &= p[i] == i;
+  check(kept, "realloc preserves contents");
+  check_eq(HeapSize(heap, 0, p), 4096, "realloc size");
+  HeapFree(heap, 0, p);
+}
+
+static void test_free_null() {
+  check(HeapFree(GetProcessHeap(), 0, nullptr), "free null");
+}
+
+static void test_private_heap() {
+  auto heap = HeapCreate(0, 0x1000, 0);
+  check(heap != nullptr, "HeapCreate");
+  void *a = HeapAlloc(heap, 0, 100);
+  void *b = HeapAlloc(heap, 0, 100);
+  check(a != nullptr && b != nullptr && a != b, "distinct allocs");
+  check(HeapDestroy(heap), "HeapDestroy");
+}
+
+extern "C" void mainCRTStartup() {
+  test_zero_size();
+  test_zero_memory();
+  test_realloc();
+  test_free_null();
+  test_private_heap();
+  done();
+}
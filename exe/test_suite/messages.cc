@@ -0,0 +1,89 @@
+// Message queue ordering and SendMessage semantics.
+
+#include "test.h"
+
+static UINT order[8];
+static int order_len;
+static int nested_depth;
+
+static LRESULT CALLBACK wndproc(HWND hwnd, UINT msg, WPARAM wParam,
+                                LPARAM lParam) {
+  switch (msg) {
+  case WM_USER:
+    return 0x1234;
+  case WM_USER + 1:
+    // Send another message from within a wndproc.
+    nested_depth++;
+    return SendMessageA(hwnd, WM_USER, 0, 0) + 1;
+  case WM_PAINT: {
+    PAINTSTRUCT ps;
+    BeginPaint(hwnd, &ps);
+    EndPaint(hwnd, &ps);
+    return 0;
+  }
+  }
+  return DefWindowProcA(hwnd, msg, wParam, lParam);
+}
+
+static HWND create_window() {
+  WNDCLASSA wc = {};
+  wc.lpfnWndProc = wndproc;
+  wc.lpszClassName = "test";
+  RegisterClassA(&wc);
+  return CreateWindowExA(0, "test", "test", WS_OVERLAPPEDWINDOW, 0, 0, 100, 100,
+                         nullptr, nullptr, nullptr, nullptr);
+}
+
+static void test_send_message(HWND hwnd) {
+  check_eq(SendMessageA(hwnd, WM_USER, 0, 0), 0x1234, "SendMessage result");
+  check_eq(SendMessageA(hwnd, WM_USER + 1, 0, 0), 0x1235,
+           "nested SendMessage result");
+  check_eq(nested_depth, 1, "nested SendMessage ran once");
+}
+
+static void test_priority(HWND hwnd) {
+  // Drain anything pending from window creation.
+  MSG msg;
+  while (PeekMessageA(&msg, nullptr, 0, 0, PM_REMOVE))
+    DispatchMessageA(&msg);
+
+  InvalidateRect(hwnd, nullptr, FALSE);
+  SetTimer(hwnd, 1, 10, nullptr);
+  Sleep(20);
+  PostMessageA(hwnd, WM_USER, 0, 0);
+
+  while (order_len < 3 && GetMessageA(&msg, nullptr, 0, 0)) {
+    if (msg.message == WM_USER || msg.message == WM_TIMER ||
+        msg.message == WM_PAINT) {
+      order[order_len++] = msg.message;
+    }
+    if (msg.message == WM_TIMER)
+      KillTimer(hwnd, 1);
+    DispatchMessageA(&msg);
+  }
+  check_eq(order[0], WM_USER, "posted message first");
+  check_eq(order[1], WM_TIMER, "then timer");
+  check_eq(order[2], WM_PAINT, "then paint");
+}
+
+static void test_paint_once(HWND hwnd) {
+  InvalidateRect(hwnd, nullptr, FALSE);
+  InvalidateRect(hwnd, nullptr, FALSE);
+  int paints = 0;
+  MSG msg;
+  while (PeekMessageA(&msg, nullptr, 0, 0, PM_REMOVE)) {
+    if (msg.message == WM_PAINT)
+      paints++;
+    DispatchMessageA(&msg);
+  }
+  check_eq(paints, 1, "WM_PAINT coalesced");
+}
+
+extern "C" void mainCRTStartup() {
+  HWND hwnd = create_window();
+  check(hwnd != nullptr, "CreateWindow");
+  test_send_message(hwnd);
+  test_priority(hwnd);
+  test_paint_once(hwnd);
+  done();
+}
@@ -0,0 +1,30 @@
+#!/bin/bash
+
+# Runs each test program under retrowin32.
+# Test programs print "ok <name>" or "FAIL <name>" per check and exit with
+# the number of failures.
+
+set -o pipefail
+cd "$(dirname "$0")"
+
+cargo build -q -p retrowin32 -F x86-emu --release || exit 1
+retrowin32=../../target/release/retrowin32
+
+if ! ls *.exe >/dev/null 2>&1; then
+  echo "no test programs; run build.sh first"
+  exit 1
+fi
+
+failed=0
+for exe in *.exe; do
+  out=$($retrowin32 $exe 2>&1 | tr -d '\r')
+  status=$?
+  if [ $status -ne 0 ] || grep -q '^FAIL ' <<<"$out"; then
+    echo "$exe: FAILED (exit $status)"
+    grep -v '^ok ' <<<"$out"
+    failed=1
+  else
+    echo "$exe: $(grep -c '^ok ' <<<"$out") ok"
+  fi
+done
+exit $failed
@@ -0,0 +1,50 @@
+#include "test.h"
+
+static uint32_t failures;
+
+void print(const char *s) {
+  static HANDLE out;
+  if (!out) {
+    // GUI test programs start without a console; use the one of whoever ran them.
+    if (!GetStdHandle(STD_OUTPUT_HANDLE))
+      AttachConsole(ATTACH_PARENT_PROCESS);
+    out = GetStdHandle(STD_OUTPUT_HANDLE);
+  }
+  DWORD len = 0;
+  while (s[len])
+    len++;
+  WriteFile(out, s, len, &len, nullptr);
+}
+
+void print_hex(uint32_t x) {
+  char buf[11];
+  buf[0] = '0';
+  buf[1] = 'x';
+  for (int i = 0; i < 8; i++) {
+    auto nybble = (char)((x >> (28 - i * 4)) & 0xf);
+    buf[2 + i] = nybble < 0xa ? '0' + nybble : 'a' + (nybble - 0xa);
+  }
+  buf[10] = 0;
+  print(buf);
+}
+
+void check(bool ok, const char *name) {
+  if (!ok)
+    failures++;
+  print(ok ? "ok " : "FAIL ");
+  print(name);
+  print("\n");
+}
+
+void check_eq(uint32_t actual, uint32_t expected, const char *name) {
+  check(actual == expected, name);
+  if (actual != expected) {
+    print("  expected ");
+    print_hex(expected);
+    print(", got ");
+    print_hex(actual);
+    print("\n");
+  }
+}
+
+void done() { ExitProcess(failures); }
@@ -0,0 +1,18 @@
+#pragma once
+
+// Minimal self-checking test support, with no C runtime.
+// Each check prints "ok <name>" or "FAIL <name>" and the program exits
+// with the count of failures, which run.sh inspects.
+
+#include <stdint.h>
+#include <windows.h>
+
+void print(const char *s);
+void print_hex(uint32_t x);
+
+void check(bool ok, const char *name);
+// Like check(), but prints the expected and actual values on failure.
+void check_eq(uint32_t actual, uint32_t expected, const char *name);
+
+// Exits the process, reporting the failure count.
+[[noreturn]] void done();
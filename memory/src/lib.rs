@@ -1,5 +1,12 @@
 mod mem;
 mod pod;
+mod reserve;
+pub mod simd;
 
-pub use mem::{Extensions, ExtensionsMut, Iterator, Mem};
+pub use mem::{
+    page_table, take_builtin_writes, take_out_of_bounds, Extensions, ExtensionsMut, Iterator, Mem,
+    OutOfBounds, PAGE_COUNT, PAGE_SHIFT, PAGE_TRAPS_NO_ACCESS, PAGE_TRAPS_RECORD, PAGE_TRAP_CODE,
+    PAGE_TRAP_GUARD, PAGE_TRAP_READ, PAGE_TRAP_WATCH, PAGE_TRAP_WRITE,
+};
 pub use pod::Pod;
+pub use reserve::Reservation;
@@ -48,6 +48,23 @@ pub trait Extensions<'m>: Sized {
         self.sub32(ofs, count * size_of::<T>() as u32)
             .into_iter_pod()
     }
+
+    // The accessors below are the checked counterparts of the ones above: rather than
+    // panicking on a bad range, they return it, so that builtins handed a bad pointer
+    // can fail the call as Windows does.  They also honor the guest's page protections,
+    // so the unchecked ones remain for the emulator's accesses to memory it manages
+    // itself, like zeroing freshly allocated pages.
+
+    fn try_sub32(self, ofs: u32, len: u32) -> Result<&'m [u8], OutOfBounds>;
+
+    fn read_pod<T: Clone + Pod>(self, ofs: u32) -> Result<T, OutOfBounds> {
+        let buf = self.try_sub32(ofs, size_of::<T>() as u32)?;
+        Ok(unsafe { std::ptr::read_unaligned(buf.as_ptr() as *const T) })
+    }
+
+    /// The nul-terminated string at ofs, without the nul, looking at no more than
+    /// max_len bytes; a string that doesn't end within those is cut off there.
+    fn strz(self, ofs: u32, max_len: u32) -> Result<&'m [u8], OutOfBounds>;
 }
 
 pub trait ExtensionsMut<'m>: Sized {
@@ -58,6 +75,27 @@ pub trait ExtensionsMut<'m>: Sized {
             std::ptr::write_unaligned(self.get_ptr_mut::<T>(ofs), val);
         }
     }
+
+    // Checked counterparts, as in Extensions.
+
+    fn try_sub32_mut(self, ofs: u32, len: u32) -> Result<&'m mut [u8], OutOfBounds>;
+
+    fn write_pod<T: Clone + Pod>(self, ofs: u32, val: T) -> Result<(), OutOfBounds> {
+        let buf = self.try_sub32_mut(ofs, size_of::<T>() as u32)?;
+        unsafe { std::ptr::write_unaligned(buf.as_mut_ptr() as *mut T, val) };
+        Ok(())
+    }
+}
+
+/// The string at the start of buf, per Extensions::strz, where buf is the rest of
+/// memory from ofs.
+fn strz_in(buf: &[u8], ofs: u32, max_len: u32) -> Result<&[u8], OutOfBounds> {
+    let buf = &buf[..buf.len().min(max_len as usize)];
+    match buf.iter().position(|&c| c == 0) {
+        Some(nul) => Ok(&buf[..nul]),
+        None if buf.len() == max_len as usize => Ok(buf),
+        None => Err(OutOfBounds::read(ofs, buf.len() as u32 + 1)),
+    }
 }
 
 /// See iter_pod.
@@ -86,7 +124,7 @@ impl<'m> Extensions<'m> for &'m [u8] {
     fn get_ptr<T: Pod>(self, ofs: u32) -> *const T {
         unsafe {
             if ofs as usize + size_of::<T>() > self.len() {
-                oob_panic(ofs, size_of::<T>());
+                panic!("oob at {ofs:x}+{:x}", size_of::<T>());
             }
             self.as_ptr().add(ofs as usize) as *const T
         }
@@ -102,6 +140,17 @@ impl<'m> Extensions<'m> for &'m [u8] {
         let nul = slice.iter().position(|&c| c == 0).unwrap();
         &slice[..nul]
     }
+
+    fn try_sub32(self, ofs: u32, len: u32) -> Result<&'m [u8], OutOfBounds> {
+        self.get(ofs as usize..)
+            .and_then(|buf| buf.get(..len as usize))
+            .ok_or(OutOfBounds::read(ofs, len))
+    }
+
+    fn strz(self, ofs: u32, max_len: u32) -> Result<&'m [u8], OutOfBounds> {
+        let buf = self.get(ofs as usize..).ok_or(OutOfBounds::read(ofs, 1))?;
+        strz_in(buf, ofs, max_len)
+    }
 }
 
 impl<'m> ExtensionsMut<'m> for &'m mut [u8] {
@@ -111,6 +160,11 @@ impl<'m> ExtensionsMut<'m> for &'m mut [u8] {
     fn sub32_mut(self, ofs: u32, len: u32) -> &'m mut [u8] {
         &mut self[ofs as usize..][..len as usize]
     }
+    fn try_sub32_mut(self, ofs: u32, len: u32) -> Result<&'m mut [u8], OutOfBounds> {
+        self.get_mut(ofs as usize..)
+            .and_then(|buf| buf.get_mut(..len as usize))
+            .ok_or(OutOfBounds::write(ofs, len))
+    }
 }
 
 /// A view into the x86 memory.
@@ -127,14 +181,49 @@ impl<'m> ExtensionsMut<'m> for &'m mut [u8] {
 pub struct Mem<'m> {
     ptr: *mut u8,
     end: *mut u8,
+    /// Per-page trap bits (PAGE_TRAP_*), one byte for each page of the address space, or
+    /// null if only the bounds of memory are checked.
+    pages: *const u8,
     _marker: std::marker::PhantomData<&'m u8>,
 }
 
+/// Log2 of the size of the pages that protection applies to.
+pub const PAGE_SHIFT: u32 = 12;
+/// Page trap bit: writes to the page fault.
+pub const PAGE_TRAP_WRITE: u8 = 1 << 0;
+/// Page trap bit: the first access to the page faults, as with Windows PAGE_GUARD.
+pub const PAGE_TRAP_GUARD: u8 = 1 << 1;
+/// Page trap bit: the page holds cached decoded code, so writes to it must discard
+/// that cache.  Unlike the above, this one doesn't fault.
+pub const PAGE_TRAP_CODE: u8 = 1 << 2;
+/// Page trap bit: the debugger is tracking writes to the page.  Doesn't fault.
+pub const PAGE_TRAP_WATCH: u8 = 1 << 3;
+/// Page trap bit: any access to the page faults, as with Windows PAGE_NOACCESS and the
+/// null page.  Such pages have PAGE_TRAP_WRITE too, so writes need only check that.
+pub const PAGE_TRAP_READ: u8 = 1 << 4;
+/// The trap bits that only record writes, rather than faulting.
+pub const PAGE_TRAPS_RECORD: u8 = PAGE_TRAP_CODE | PAGE_TRAP_WATCH;
+/// The trap bits of a page that faults on any access.
+pub const PAGE_TRAPS_NO_ACCESS: u8 = PAGE_TRAP_READ | PAGE_TRAP_WRITE;
+/// The number of pages in the 32-bit address space, and so of entries in a page table.
+pub const PAGE_COUNT: usize = 1 << (32 - PAGE_SHIFT);
+
+/// A table of page trap bits for memory of len bytes, for Mem::with_pages().  It covers
+/// the whole address space, with the pages past the end of memory inaccessible: those
+/// are guard pages, so that page_traps() also catches accesses out of bounds.
+pub fn page_table(len: u32) -> Box<[u8]> {
+    let mut pages = vec![0; PAGE_COUNT].into_boxed_slice();
+    let end = (len as usize).div_ceil(1 << PAGE_SHIFT);
+    pages[end..].fill(PAGE_TRAPS_NO_ACCESS);
+    pages
+}
+
 impl<'m> Mem<'m> {
     pub fn from_ptrs(range: std::ops::Range<*const u8>) -> Mem<'m> {
         Mem {
             ptr: range.start as *mut u8,
             end: range.end as *mut u8,
+            pages: std::ptr::null(),
             _marker: std::marker::PhantomData::default(),
         }
     }
@@ -142,17 +231,110 @@ impl<'m> Mem<'m> {
         Mem::from_ptrs(s.as_ptr_range())
     }
 
+    /// Attach a table of per-page trap bits, covering the whole address space; see
+    /// page_table().
+    pub fn with_pages(self, pages: &'m [u8]) -> Mem<'m> {
+        assert_eq!(pages.len(), PAGE_COUNT);
+        Mem {
+            pages: pages.as_ptr(),
+            ..self
+        }
+    }
+
+    /// The trap bits of the pages touched by an access of len bytes at addr.  An access
+    /// that leaves memory touches a guard page, so zero means it's in bounds.
+    #[inline]
+    pub fn page_traps(&self, addr: u32, len: u32) -> u8 {
+        let last = addr.wrapping_add(len - 1);
+        if last < addr {
+            // Wraps around the top of the address space.
+            return PAGE_TRAPS_NO_ACCESS;
+        }
+        if self.pages.is_null() {
+            return if last >= self.len() {
+                PAGE_TRAPS_NO_ACCESS
+            } else {
+                0
+            };
+        }
+        // Safety: the table covers every u32 address, per with_pages().
+        unsafe {
+            *self.pages.add((addr >> PAGE_SHIFT) as usize)
+                | *self.pages.add((last >> PAGE_SHIFT) as usize)
+        }
+    }
+
+    /// The trap bits of all the pages covering [addr, addr+len), for bulk accesses that
+    /// can span more than two pages.
+    pub fn page_traps_span(&self, addr: u32, len: u32) -> u8 {
+        if self.pages.is_null() || len == 0 {
+            return 0;
+        }
+        let first = (addr >> PAGE_SHIFT) as usize;
+        let last = ((addr.saturating_add(len - 1)).min(self.len() - 1) >> PAGE_SHIFT) as usize;
+        // Safety: the table covers all of memory, per with_pages().
+        (first..=last).fold(0, |traps, page| traps | unsafe { *self.pages.add(page) })
+    }
+
+    /// Set (or with set=false, clear) trap bits on the pages covering [addr, addr+len).
+    pub fn set_page_traps(&self, addr: u32, len: u32, bits: u8, set: bool) {
+        if self.pages.is_null() || len == 0 {
+            return;
+        }
+        let first = (addr >> PAGE_SHIFT) as usize;
+        let last = ((addr.saturating_add(len - 1)).min(self.len() - 1) >> PAGE_SHIFT) as usize;
+        for page in first..=last {
+            // Safety: the table covers all of memory, per with_pages().
+            unsafe {
+                let traps = self.pages.add(page) as *mut u8;
+                if set {
+                    *traps |= bits;
+                } else {
+                    *traps &= !bits;
+                }
+            }
+        }
+    }
+
+    /// The table of page trap bits from with_pages(), or null if there is none, for code
+    /// generated to do its own page_traps() lookups.
+    pub fn page_table_ptr(&self) -> *const u8 {
+        self.pages
+    }
+
+    #[inline]
     pub fn is_oob<T>(&self, addr: u32) -> bool {
-        addr + size_of::<T>() as u32 > self.len()
+        addr as usize + size_of::<T>() > self.len() as usize
     }
 
+    #[inline]
     fn get_ptr_unchecked(&self, ofs: u32) -> *mut u8 {
         // Avoid using self.ptr.add here, because when self.ptr is 0 (for native Mems)
         // a later bounds check gets optimized out into always panicking.
         ((self.ptr as usize) + ofs as usize) as *mut u8
     }
 
+    // The accessors below skip the bounds check of get_ptr() and friends, for the
+    // interpreter's operand accesses, which check page_traps() instead: it faults in the
+    // guest rather than panicking, and is needed for page protection anyway.
+
+    /// Pointer to a T at addr, for which page_traps() has returned no faulting bits.
+    #[inline]
+    pub fn get_ptr_mut_unchecked<T: Pod>(&self, addr: u32) -> *mut T {
+        self.get_ptr_unchecked(addr) as *mut T
+    }
+
+    /// Read a T at addr.
+    ///
+    /// # Safety
+    /// page_traps() must have returned no faulting bits for the access.
+    #[inline]
+    pub unsafe fn get_pod_unchecked<T: Clone + Pod>(&self, addr: u32) -> T {
+        std::ptr::read_unaligned(self.get_ptr_mut_unchecked::<T>(addr))
+    }
+
     pub fn copy(&self, src: u32, dst: u32, len: u32) {
+        self.note_write(dst, len);
         unsafe {
             let src = self.get_ptr::<u8>(src);
             let dst = self.get_ptr_mut::<u8>(dst);
@@ -160,8 +342,9 @@ impl<'m> Mem<'m> {
         }
     }
 
+    #[inline]
     pub fn len(&self) -> u32 {
-        unsafe { self.end.offset_from(self.ptr) as u32 }
+        (self.end as usize - self.ptr as usize) as u32
     }
 
     pub fn slice(&self, b: impl std::ops::RangeBounds<u32>) -> &'m [u8] {
@@ -179,21 +362,115 @@ impl<'m> Mem<'m> {
             let ptr = self.get_ptr_unchecked(bstart);
             let end = self.get_ptr_unchecked(bend);
             if !(self.ptr..self.end).contains(&ptr) || !(self.ptr..self.end.add(1)).contains(&end) {
-                panic!("oob slice: {bstart:x?}..{bend:x?}",);
+                oob_panic(bstart, bend.wrapping_sub(bstart) as usize, false);
             }
             std::slice::from_raw_parts(ptr, end.offset_from(ptr) as usize)
         }
     }
 
+    /// Check an access by a builtin of len bytes at addr, which mustn't leave memory
+    /// or touch an inaccessible page.
+    fn check_access(&self, addr: u32, len: u32, write: bool) -> Result<(), OutOfBounds> {
+        let oob = OutOfBounds { addr, len, write };
+        if addr as u64 + len as u64 > self.len() as u64 {
+            return Err(oob);
+        }
+        if self.page_traps_span(addr, len) & PAGE_TRAP_READ != 0 {
+            return Err(oob);
+        }
+        Ok(())
+    }
+
+    /// Remember a write by a builtin to pages with PAGE_TRAPS_RECORD bits; see
+    /// take_builtin_writes().
+    #[inline]
+    fn note_write(&self, addr: u32, len: u32) {
+        if len > 0 && self.page_traps_span(addr, len) & PAGE_TRAPS_RECORD != 0 {
+            let end = addr.saturating_add(len);
+            BUILTIN_WRITES.set(Some(match BUILTIN_WRITES.get() {
+                Some((start, prev_end)) => (start.min(addr), prev_end.max(end)),
+                None => (addr, end),
+            }));
+        }
+    }
+
     /// Create a new Mem with arbitrary lifetime.  Very unsafe, used in stack_args codegen.
     pub unsafe fn detach<'a, 'b>(&'a self) -> Mem<'b> {
         std::mem::transmute(*self)
     }
 }
 
+/// A guest memory access outside of memory, or for the checked accessors, to a page
+/// that doesn't allow any access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutOfBounds {
+    pub addr: u32,
+    pub len: u32,
+    pub write: bool,
+}
+
+impl OutOfBounds {
+    fn read(addr: u32, len: u32) -> Self {
+        OutOfBounds {
+            addr,
+            len,
+            write: false,
+        }
+    }
+
+    fn write(addr: u32, len: u32) -> Self {
+        OutOfBounds {
+            addr,
+            len,
+            write: true,
+        }
+    }
+
+    /// Panic with this access, for callers that have no way to return it, just as the
+    /// unchecked accessors do; see take_out_of_bounds.
+    pub fn raise(self) -> ! {
+        oob_panic(self.addr, self.len as usize, self.write)
+    }
+}
+
+impl std::fmt::Display for OutOfBounds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let access = if self.write { "write" } else { "read" };
+        write!(f, "oob {access} at {:x}+{:x}", self.addr, self.len)
+    }
+}
+
+thread_local! {
+    static LAST_OOB: std::cell::Cell<Option<OutOfBounds>> = const { std::cell::Cell::new(None) };
+    static BUILTIN_WRITES: std::cell::Cell<Option<(u32, u32)>> = const { std::cell::Cell::new(None) };
+}
+
+/// The range [start, end) covering the writes through the ExtensionsMut accessors to
+/// pages with PAGE_TRAPS_RECORD bits since last asked, clearing it.  The interpreter
+/// tracks its own writes as it makes them, but builtins like memcpy or ReadFile write
+/// to guest memory directly, and the CPU must still drop any code cached from there.
+pub fn take_builtin_writes() -> Option<(u32, u32)> {
+    BUILTIN_WRITES.take()
+}
+
+/// The out of bounds access behind the most recent panic from a Mem accessor, if any,
+/// clearing it.  Accessors that can't return an error panic on a bad access, with the
+/// usual message; builtins make such accesses on behalf of the guest with pointers it
+/// gave them, so the emulator catches the panic and uses this to raise an access
+/// violation in the guest instead.
+pub fn take_out_of_bounds() -> Option<OutOfBounds> {
+    LAST_OOB.take()
+}
+
 #[inline(never)]
-fn oob_panic(ofs: u32, size: usize) -> ! {
-    panic!("oob at {ofs:x}+{size:x}");
+fn oob_panic(addr: u32, len: usize, write: bool) -> ! {
+    let oob = OutOfBounds {
+        addr,
+        len: len as u32,
+        write,
+    };
+    LAST_OOB.set(Some(oob));
+    panic!("{oob}");
 }
 
 impl<'m> Extensions<'m> for Mem<'m> {
@@ -201,20 +478,42 @@ impl<'m> Extensions<'m> for Mem<'m> {
         unimplemented!()
     }
 
+    #[inline]
     fn get_ptr<T: Pod>(self, ofs: u32) -> *const T {
-        let ptr = self.get_ptr_unchecked(ofs) as *const T;
-        unsafe {
-            if ptr.add(1) as *const _ > self.end {
-                oob_panic(ofs, size_of::<T>());
-            }
+        if self.is_oob::<T>(ofs) {
+            oob_panic(ofs, size_of::<T>(), false);
         }
-        ptr
+        self.get_ptr_unchecked(ofs) as *const T
+    }
+
+    fn get_aligned_ref_mut<T: Pod>(self, ofs: u32) -> &'m mut T {
+        let ptr = self.get_ptr::<T>(ofs);
+        check_aligned(ptr);
+        self.note_write(ofs, size_of::<T>() as u32);
+        unsafe { &mut *(ptr as *mut T) }
     }
 
     fn sub32(self, ofs: u32, len: u32) -> &'m [u8] {
         self.slice(ofs..(ofs + len))
     }
 
+    fn try_sub32(self, ofs: u32, len: u32) -> Result<&'m [u8], OutOfBounds> {
+        self.check_access(ofs, len, false)?;
+        Ok(unsafe { std::slice::from_raw_parts(self.get_ptr_unchecked(ofs), len as usize) })
+    }
+
+    fn strz(self, ofs: u32, max_len: u32) -> Result<&'m [u8], OutOfBounds> {
+        if max_len == 0 {
+            return Ok(&[]);
+        }
+        self.check_access(ofs, 1, false)?;
+        let buf = strz_in(self.slice(ofs..), ofs, max_len)?;
+        // Also covers the nul, if any; it's in bounds, as strz_in found it.
+        let len = (buf.len() as u32 + 1).min(max_len);
+        self.check_access(ofs, len, false)?;
+        Ok(buf)
+    }
+
     fn slicez(self, ofs: u32) -> &'m [u8] {
         unsafe {
             let start = self.ptr.add(ofs as usize);
@@ -229,10 +528,100 @@ impl<'m> Extensions<'m> for Mem<'m> {
 
 impl<'m> ExtensionsMut<'m> for Mem<'m> {
     fn get_ptr_mut<T: Pod>(self, ofs: u32) -> *mut T {
-        self.get_ptr::<T>(ofs) as *mut T
+        if self.is_oob::<T>(ofs) {
+            oob_panic(ofs, size_of::<T>(), true);
+        }
+        self.note_write(ofs, size_of::<T>() as u32);
+        self.get_ptr_unchecked(ofs) as *mut T
     }
     fn sub32_mut(self, ofs: u32, len: u32) -> &'m mut [u8] {
-        assert!(ofs + len <= self.len());
+        if ofs as u64 + len as u64 > self.len() as u64 {
+            oob_panic(ofs, len as usize, true);
+        }
+        self.note_write(ofs, len);
         unsafe { std::slice::from_raw_parts_mut(self.ptr.add(ofs as usize), len as usize) }
     }
+    fn try_sub32_mut(self, ofs: u32, len: u32) -> Result<&'m mut [u8], OutOfBounds> {
+        self.check_access(ofs, len, true)?;
+        self.note_write(ofs, len);
+        Ok(unsafe { std::slice::from_raw_parts_mut(self.get_ptr_unchecked(ofs), len as usize) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Three pages of memory, the last of them inaccessible.
+    fn with_mem(f: impl FnOnce(Mem)) {
+        let buf = vec![0u8; 3 << PAGE_SHIFT];
+        let mut pages = page_table(buf.len() as u32);
+        pages[2] = PAGE_TRAPS_NO_ACCESS;
+        f(Mem::from_slice(&buf).with_pages(&pages));
+    }
+
+    #[test]
+    fn try_sub32_checks_bounds_and_pages() {
+        with_mem(|mem| {
+            assert_eq!(mem.try_sub32(0x10, 4).unwrap().len(), 4);
+            assert_eq!(mem.try_sub32(0x2000, 0).unwrap().len(), 0);
+            // Crosses into the inaccessible page.
+            assert_eq!(mem.try_sub32(0x1FFE, 4), Err(OutOfBounds::read(0x1FFE, 4)));
+            // Past the end, and wrapping around the address space.
+            assert!(mem.try_sub32(0x3000, 1).is_err());
+            assert!(mem.try_sub32(0xFFFF_FFF0, 0x20).is_err());
+
+            mem.try_sub32_mut(0x10, 4).unwrap().fill(7);
+            assert_eq!(mem.try_sub32(0x10, 4).unwrap(), &[7; 4]);
+            assert_eq!(
+                mem.try_sub32_mut(0x2000, 1).unwrap_err(),
+                OutOfBounds::write(0x2000, 1)
+            );
+        });
+    }
+
+    #[test]
+    fn read_write_pod() {
+        with_mem(|mem| {
+            mem.write_pod::<u32>(0x1FFB, 0x1234_5678).unwrap();
+            assert_eq!(mem.read_pod::<u32>(0x1FFB), Ok(0x1234_5678));
+            assert!(mem.read_pod::<u32>(0x1FFE).is_err());
+            assert!(mem.write_pod::<u16>(0x2FFF, 0).is_err());
+        });
+    }
+
+    #[test]
+    fn strz() {
+        with_mem(|mem| {
+            mem.try_sub32_mut(0x100, 4)
+                .unwrap()
+                .copy_from_slice(b"abc\0");
+            assert_eq!(mem.strz(0x100, u32::MAX), Ok(&b"abc"[..]));
+            // Cut off at max_len.
+            assert_eq!(mem.strz(0x100, 2), Ok(&b"ab"[..]));
+            assert_eq!(mem.strz(0x100, 3), Ok(&b"abc"[..]));
+
+            // A string running into the inaccessible page.
+            mem.try_sub32_mut(0x1FFE, 2).unwrap().copy_from_slice(b"xy");
+            assert!(mem.strz(0x1FFE, u32::MAX).is_err());
+            assert_eq!(mem.strz(0x1FFE, 2), Ok(&b"xy"[..]));
+            assert!(mem.strz(0x3000, u32::MAX).is_err());
+        });
+
+        // Without page traps, a string running off the end of memory.
+        let buf = b"abc";
+        assert!(Mem::from_slice(buf).strz(0, u32::MAX).is_err());
+        assert_eq!(
+            buf.as_slice().strz(1, u32::MAX),
+            Err(OutOfBounds::read(1, 3))
+        );
+    }
+
+    #[test]
+    fn raise_records_access() {
+        let oob = OutOfBounds::write(0x1234, 4);
+        assert!(std::panic::catch_unwind(|| oob.raise()).is_err());
+        assert_eq!(take_out_of_bounds(), Some(oob));
+        assert_eq!(take_out_of_bounds(), None);
+    }
 }
@@ -21,11 +21,15 @@ pub unsafe trait Pod: 'static + Sized {
 // See discussion of endianness in doc/design_notes.md.
 unsafe impl Pod for u8 {}
 unsafe impl Pod for [u8; 4] {} // pixels
+unsafe impl Pod for [u8; 10] {} // x87 extended precision floats
+unsafe impl Pod for [u8; 28] {} // x87 environment, as FNSTENV stores
+unsafe impl Pod for [u8; 108] {} // x87 state, as FNSAVE stores
 unsafe impl Pod for u16 {}
 unsafe impl Pod for i16 {}
 unsafe impl Pod for u32 {}
 unsafe impl Pod for i32 {}
 unsafe impl Pod for u64 {}
 unsafe impl Pod for i64 {}
+unsafe impl Pod for u128 {} // SSE registers
 unsafe impl Pod for f32 {}
 unsafe impl Pod for f64 {}
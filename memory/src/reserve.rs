@@ -0,0 +1,127 @@
+//! Backing store for guest memory, reserved all at once for the lifetime of the machine.
+//!
+//! Guest memory is one contiguous buffer indexed by guest address, so it can never move:
+//! Mems and raw pointers into it are handed out freely.  Rather than guess a size and
+//! hope the program fits, we reserve the whole address space the guest may use up front
+//! and let the host commit pages as they're first touched.  On unix that's an anonymous
+//! mmap, where untouched pages cost nothing.  Elsewhere it's a zeroed allocation, which
+//! the allocator satisfies with fresh pages from the system that it doesn't need to
+//! clear.  On wasm those come from memory.grow, and engines back linear memory with
+//! reserved address space that is only committed as it is touched, so a large
+//! reservation there costs address space within the 4GB of linear memory rather than
+//! real memory.
+
+/// A fixed-size, zero-initialized region of host memory.
+pub struct Reservation {
+    ptr: *mut u8,
+    len: usize,
+}
+
+// Safety: the reservation is uniquely owned, like a Box<[u8]>.
+unsafe impl Send for Reservation {}
+
+impl Reservation {
+    #[cfg(unix)]
+    pub fn new(len: usize) -> std::io::Result<Self> {
+        // Safety: a fresh anonymous mapping doesn't alias anything.
+        let ptr = unsafe { map_zeroed(std::ptr::null_mut(), len, 0) }?;
+        Ok(Reservation { ptr, len })
+    }
+
+    #[cfg(not(unix))]
+    pub fn new(len: usize) -> std::io::Result<Self> {
+        let ptr = match Self::layout(len) {
+            // Safety: the layout has a nonzero size.
+            Some(layout) => unsafe { std::alloc::alloc_zeroed(layout) },
+            None => std::ptr::NonNull::dangling().as_ptr(),
+        };
+        if ptr.is_null() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::OutOfMemory,
+                format!("allocating {len:#x} bytes failed"),
+            ));
+        }
+        Ok(Reservation { ptr, len })
+    }
+
+    /// The layout of the allocation behind a reservation of len bytes, if any.
+    #[cfg(not(unix))]
+    fn layout(len: usize) -> Option<std::alloc::Layout> {
+        if len == 0 {
+            return None;
+        }
+        std::alloc::Layout::from_size_align(len, 1 << crate::PAGE_SHIFT).ok()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Zero all of the memory, releasing the host pages behind it where possible.
+    #[cfg(unix)]
+    pub fn clear(&mut self) {
+        // Mapping fresh zero pages over the old ones drops them; unlike madvise, this
+        // zeroes them on every unix.
+        // Safety: replaces our own mapping in place, which we hold &mut to.
+        if let Err(err) = unsafe { map_zeroed(self.ptr, self.len, libc::MAP_FIXED) } {
+            log::warn!("remapping guest memory: {err}");
+            self.fill(0);
+        }
+    }
+
+    #[cfg(not(unix))]
+    pub fn clear(&mut self) {
+        self.fill(0);
+    }
+}
+
+/// mmap len bytes of anonymous zero pages, which the host only commits when touched.
+#[cfg(unix)]
+unsafe fn map_zeroed(addr: *mut u8, len: usize, flags: libc::c_int) -> std::io::Result<*mut u8> {
+    #[cfg(target_os = "linux")]
+    let flags = flags | libc::MAP_NORESERVE;
+    let ptr = libc::mmap(
+        addr as *mut libc::c_void,
+        len,
+        libc::PROT_READ | libc::PROT_WRITE,
+        libc::MAP_PRIVATE | libc::MAP_ANONYMOUS | flags,
+        -1,
+        0,
+    );
+    if ptr == libc::MAP_FAILED {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(ptr as *mut u8)
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        // Safety: we own the region, and nothing borrows it past our lifetime.
+        #[cfg(unix)]
+        unsafe {
+            libc::munmap(self.ptr as *mut libc::c_void, self.len);
+        }
+        #[cfg(not(unix))]
+        if let Some(layout) = Self::layout(self.len) {
+            unsafe { std::alloc::dealloc(self.ptr, layout) };
+        }
+    }
+}
+
+impl std::ops::Deref for Reservation {
+    type Target = [u8];
+    fn deref(&self) -> &[u8] {
+        // Safety: the region is initialized (zeroed) and lives as long as self.
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+impl std::ops::DerefMut for Reservation {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr, self.len) }
+    }
+}
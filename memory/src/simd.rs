@@ -0,0 +1,267 @@
+//! Hot loops over pixels and guest memory, with wasm SIMD versions.
+//!
+//! These are the inner loops of blits, surface flushes and rep stos, which dominate the
+//! profile of games that draw a lot.  Natively the compiler vectorizes the plain loops
+//! well enough, but a wasm module can only use SIMD if it's compiled with simd128, and
+//! then fails to load at all in a browser without it.  So web/glue/build.sh builds the
+//! module twice and web.tsx picks one at load time; in the SIMD build the functions here
+//! process four pixels (16 bytes) at a time with core::arch::wasm32, falling back to the
+//! plain loop for the remainder.
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+use core::arch::wasm32::*;
+
+/// An RGBA pixel, as in host::Surface.
+pub type Pixel = [u8; 4];
+
+/// The bytes of each pixel other than alpha, as a little-endian u32.
+const RGB_MASK: u32 = 0x00FF_FFFF;
+const ALPHA_MASK: u32 = 0xFF00_0000;
+
+/// View bytes as pixels, ignoring any trailing partial pixel.
+pub fn pixels(bytes: &[u8]) -> &[Pixel] {
+    // Safety: Pixel has the size of four bytes and an alignment of one.
+    unsafe { std::slice::from_raw_parts(bytes.as_ptr() as *const Pixel, bytes.len() / 4) }
+}
+
+fn pixels_as_bytes_mut(pixels: &mut [Pixel]) -> &mut [u8] {
+    unsafe { std::slice::from_raw_parts_mut(pixels.as_mut_ptr() as *mut u8, pixels.len() * 4) }
+}
+
+/// Set dst[i] = scalar(dst[i], src[i]) over the shorter of the two, using vector on
+/// groups of four pixels where SIMD is available.
+#[inline(always)]
+fn zip_pixels(
+    dst: &mut [Pixel],
+    src: &[Pixel],
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))] vector: impl Fn(v128, v128) -> v128,
+    scalar: impl Fn(u32, u32) -> u32,
+) {
+    let len = dst.len().min(src.len());
+    #[allow(unused_mut)]
+    let mut i = 0;
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    while i + 4 <= len {
+        // Safety: in bounds per the loop condition; wasm loads need no alignment.
+        unsafe {
+            let d = dst.as_mut_ptr().add(i) as *mut v128;
+            let s = src.as_ptr().add(i) as *const v128;
+            v128_store(d, vector(v128_load(d), v128_load(s)));
+        }
+        i += 4;
+    }
+    for (d, s) in dst[i..len].iter_mut().zip(&src[i..len]) {
+        *d = scalar(u32::from_le_bytes(*d), u32::from_le_bytes(*s)).to_le_bytes();
+    }
+}
+
+/// Copy src to dst, making every pixel opaque.
+pub fn copy_opaque(dst: &mut [Pixel], src: &[Pixel]) {
+    zip_pixels(
+        dst,
+        src,
+        #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+        |_, s| v128_or(s, u32x4_splat(ALPHA_MASK)),
+        |_, s| s | ALPHA_MASK,
+    );
+}
+
+/// Make every pixel of dst opaque.
+pub fn set_opaque(dst: &mut [Pixel]) {
+    #[allow(unused_mut)]
+    let mut i = 0;
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    while i + 4 <= dst.len() {
+        // Safety: as in zip_pixels.
+        unsafe {
+            let d = dst.as_mut_ptr().add(i) as *mut v128;
+            v128_store(d, v128_or(v128_load(d), u32x4_splat(ALPHA_MASK)));
+        }
+        i += 4;
+    }
+    for d in &mut dst[i..] {
+        d[3] = 0xFF;
+    }
+}
+
+/// Copy src to dst with the color channels inverted, as for NOTSRCCOPY.
+pub fn copy_inverted(dst: &mut [Pixel], src: &[Pixel]) {
+    zip_pixels(
+        dst,
+        src,
+        #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+        |_, s| v128_xor(s, u32x4_splat(RGB_MASK)),
+        |_, s| s ^ RGB_MASK,
+    );
+}
+
+/// dst &= src, as for SRCAND.
+pub fn and(dst: &mut [Pixel], src: &[Pixel]) {
+    zip_pixels(
+        dst,
+        src,
+        #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+        |d, s| v128_and(d, s),
+        |d, s| d & s,
+    );
+}
+
+/// Copy src to dst, making pixels whose color matches key transparent and the rest opaque.
+pub fn copy_color_keyed(dst: &mut [Pixel], src: &[Pixel], key: Pixel) {
+    let key = u32::from_le_bytes(key) & RGB_MASK;
+    zip_pixels(
+        dst,
+        src,
+        #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+        |_, s| {
+            let rgb = v128_and(s, u32x4_splat(RGB_MASK));
+            let keyed = u32x4_eq(rgb, u32x4_splat(key));
+            v128_or(rgb, v128_andnot(u32x4_splat(ALPHA_MASK), keyed))
+        },
+        |_, s| {
+            let rgb = s & RGB_MASK;
+            if rgb == key {
+                rgb
+            } else {
+                rgb | ALPHA_MASK
+            }
+        },
+    );
+}
+
+/// Fill buf with copies of the little-endian bytes of pattern, where the last copy may
+/// be cut short.  For rep stos, and for filling pixels.
+pub fn fill_repeat(buf: &mut [u8], pattern: u32) {
+    #[allow(unused_mut)]
+    let mut i = 0;
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    {
+        let v = u32x4_splat(pattern);
+        while i + 16 <= buf.len() {
+            // Safety: in bounds per the loop condition; wasm stores need no alignment.
+            unsafe { v128_store(buf.as_mut_ptr().add(i) as *mut v128, v) };
+            i += 16;
+        }
+    }
+    let bytes = pattern.to_le_bytes();
+    let mut chunks = buf[i..].chunks_exact_mut(4);
+    for chunk in &mut chunks {
+        chunk.copy_from_slice(&bytes);
+    }
+    let tail = chunks.into_remainder();
+    let n = tail.len();
+    tail.copy_from_slice(&bytes[..n]);
+}
+
+/// Set every pixel of dst to color.
+pub fn fill_pixels(dst: &mut [Pixel], color: Pixel) {
+    fill_repeat(pixels_as_bytes_mut(dst), u32::from_le_bytes(color));
+}
+
+#[cfg(test)]
+mod tests {
+    //! Each function against a pixel-at-a-time reference.  Natively these only cover the
+    //! plain loops; CI also runs them on wasm32 with simd128 (see ci.yml), where the
+    //! lengths and offsets below cover whole vectors, remainders and unaligned slices.
+
+    use super::*;
+
+    const KEY: Pixel = [0x12, 0x34, 0x56, 0x00];
+
+    /// Pixels of deterministic noise, with every third one of color KEY.
+    fn noise(len: usize, seed: u32) -> Vec<Pixel> {
+        let mut x = seed | 1;
+        (0..len)
+            .map(|i| {
+                x ^= x << 13;
+                x ^= x >> 17;
+                x ^= x << 5;
+                let mut p = x.to_le_bytes();
+                if i % 3 == 0 {
+                    p[..3].copy_from_slice(&KEY[..3]);
+                }
+                p
+            })
+            .collect()
+    }
+
+    /// Run f and reference on the same inputs over a range of lengths and offsets, with
+    /// src shorter, equal and longer than dst.
+    fn check(f: impl Fn(&mut [Pixel], &[Pixel]), reference: impl Fn(&mut Pixel, Pixel)) {
+        for len in 0..=13usize {
+            for offset in 0..4 {
+                for src_len in [len.saturating_sub(1), len, len + 3] {
+                    let mut dst = noise(len + offset, 1);
+                    let src = noise(src_len + offset, 2);
+                    let mut want = dst.clone();
+                    for (d, &s) in want[offset..].iter_mut().zip(&src[offset..]) {
+                        reference(d, s);
+                    }
+                    f(&mut dst[offset..], &src[offset..]);
+                    assert_eq!(dst, want, "len {len} offset {offset} src_len {src_len}");
+                }
+            }
+        }
+    }
+
+    /// Like check, for functions of dst alone.
+    fn check_fill(f: impl Fn(&mut [Pixel]), reference: impl Fn(&mut Pixel)) {
+        for len in 0..=13 {
+            for offset in 0..4 {
+                let mut dst = noise(len + offset, 1);
+                let mut want = dst.clone();
+                want[offset..].iter_mut().for_each(&reference);
+                f(&mut dst[offset..]);
+                assert_eq!(dst, want, "len {len} offset {offset}");
+            }
+        }
+    }
+
+    #[test]
+    fn copy_opaque_matches() {
+        check(copy_opaque, |d, s| *d = [s[0], s[1], s[2], 0xFF]);
+    }
+
+    #[test]
+    fn set_opaque_matches() {
+        check_fill(set_opaque, |d| d[3] = 0xFF);
+    }
+
+    #[test]
+    fn copy_inverted_matches() {
+        check(copy_inverted, |d, s| *d = [!s[0], !s[1], !s[2], s[3]]);
+    }
+
+    #[test]
+    fn and_matches() {
+        check(and, |d, s| {
+            for (d, s) in d.iter_mut().zip(s) {
+                *d &= s;
+            }
+        });
+    }
+
+    #[test]
+    fn copy_color_keyed_matches() {
+        check(
+            |d, s| copy_color_keyed(d, s, KEY),
+            |d, s| {
+                let alpha = if s[..3] == KEY[..3] { 0 } else { 0xFF };
+                *d = [s[0], s[1], s[2], alpha];
+            },
+        );
+    }
+
+    #[test]
+    fn fill_matches() {
+        for len in 0..=37 {
+            let mut buf = vec![0xAAu8; len + 1];
+            fill_repeat(&mut buf[1..], 0x44332211);
+            let want: Vec<u8> = std::iter::once(0xAA)
+                .chain([0x11, 0x22, 0x33, 0x44].into_iter().cycle().take(len))
+                .collect();
+            assert_eq!(buf, want, "len {len}");
+        }
+        check_fill(|d| fill_pixels(d, [1, 2, 3, 4]), |d| *d = [1, 2, 3, 4]);
+    }
+}
@@ -0,0 +1,76 @@
+#!/usr/bin/env python3
+
+"""
+Compare two traces as written by retrowin32 --trace-out and report the first
+point where they diverge, e.g. between two versions of the emulator.
+
+  misc/trace-diff.py [--ignore flags] [--no-calls] [--context 5] a.jsonl b.jsonl
+"""
+
+import argparse
+import json
+import sys
+
+
+def read_trace(path, ignore, calls):
+    with open(path) as f:
+        for lineno, line in enumerate(f, 1):
+            record = json.loads(line)
+            if 'call' in record and not calls:
+                continue
+            for field in ignore:
+                record.pop(field, None)
+            yield lineno, record
+
+
+def format_record(record):
+    if 'call' in record:
+        args = ', '.join(f'{k}:{v}' for k, v in record['args'].items())
+        return f"{record['module']}/{record['call']}({args}) -> {record['ret']}"
+    return ' '.join(f'{k}={v}' for k, v in record.items())
+
+
+def main():
+    parser = argparse.ArgumentParser(description='diff two retrowin32 traces')
+    parser.add_argument('a')
+    parser.add_argument('b')
+    parser.add_argument('--ignore', action='append', default=[],
+                        help='field to leave out of the comparison, e.g. flags')
+    parser.add_argument('--no-calls', action='store_true',
+                        help='compare only CPU state, not win32 calls')
+    parser.add_argument('--context', type=int, default=5,
+                        help='matching records to show before the divergence')
+    args = parser.parse_args()
+
+    a = read_trace(args.a, args.ignore, not args.no_calls)
+    b = read_trace(args.b, args.ignore, not args.no_calls)
+    history = []
+    count = 0
+    while True:
+        ra = next(a, None)
+        rb = next(b, None)
+        if ra is None and rb is None:
+            print(f'traces match ({count} records)')
+            return 0
+        if ra is not None and rb is not None and ra[1] == rb[1]:
+            history.append(ra[1])
+            history = history[-args.context:] if args.context > 0 else []
+            count += 1
+            continue
+
+        print(f'traces diverge after {count} records')
+        for record in history:
+            print(f'  {format_record(record)}')
+        for name, path, r in (('-', args.a, ra), ('+', args.b, rb)):
+            if r is None:
+                print(f'{name} {path}: end of trace')
+            else:
+                print(f'{name} {path}:{r[0]}: {format_record(r[1])}')
+        if ra is not None and rb is not None and 'call' not in ra[1] and 'call' not in rb[1]:
+            fields = [k for k in ra[1] if ra[1].get(k) != rb[1].get(k)]
+            print(f'differing: {" ".join(fields)}')
+        return 1
+
+
+if __name__ == '__main__':
+    sys.exit(main())
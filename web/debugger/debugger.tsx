@@ -1,16 +1,18 @@
 import * as preact from 'preact';
 import { Fragment, h } from 'preact';
-import { Emulator, EmulatorHost } from '../emulator';
+import { DebugString, Emulator, EmulatorHost } from '../emulator';
 import { Instruction } from '../glue/pkg/glue';
 import { EmulatorComponent, loadEmulator } from '../web';
 import { BreakpointsComponent } from './break';
 import { Code } from './code';
+import { Heaps } from './heaps';
 import { Labels, parseCSV } from './labels';
 import { Mappings } from './mappings';
 import { Memory, MemoryView, Number } from './memory';
 import { RegistersComponent } from './registers';
 import { Stack } from './stack';
 import { Tabs } from './tabs';
+import { Threads } from './threads';
 import { hex } from './util';
 
 namespace StartStop {
@@ -55,6 +57,8 @@ namespace Debugger {
   export interface State {
     emulator?: Emulator;
     stdout?: string;
+    /** Messages from OutputDebugString. */
+    debugStrings: DebugString[];
     error: string;
     /** Initial address to show in memory pane. */
     memBase: number;
@@ -67,7 +71,13 @@ namespace Debugger {
   }
 }
 export class Debugger extends preact.Component<Debugger.Props, Debugger.State> {
-  state: Debugger.State = { error: '', memBase: 0, labels: new Labels(), selectedTab: 'output' };
+  state: Debugger.State = {
+    debugStrings: [],
+    error: '',
+    memBase: 0,
+    labels: new Labels(),
+    selectedTab: 'output',
+  };
 
   private async load() {
     this.print('Loading...\n');
@@ -95,6 +105,9 @@ export class Debugger extends preact.Component<Debugger.Props, Debugger.State> {
       onStdOut: (stdout: string) => {
         this.print(stdout);
       },
+      onDebugString: (str: DebugString) => {
+        this.setState((state) => ({ debugStrings: [...state.debugStrings, str] }));
+      },
       onStopped: () => {
         this.stop();
       },
@@ -229,6 +242,17 @@ export class Debugger extends preact.Component<Debugger.Props, Debugger.State> {
           style={{ width: '80ex', flex: 1, minHeight: 0, display: 'flex', flexDirection: 'column' }}
           tabs={{
             output: () => output,
+            'debug output': () => (
+              <div style={{ flex: 1, overflow: 'auto' }}>
+                {this.state.debugStrings.map(({ ticks, threadId, msg }) => (
+                  <div>
+                    <code>
+                      [{(ticks / 1000).toFixed(3)}s thread {threadId}] {msg}
+                    </code>
+                  </div>
+                ))}
+              </div>
+            ),
 
             memory: () => (
               <Memory
@@ -245,6 +269,18 @@ export class Debugger extends preact.Component<Debugger.Props, Debugger.State> {
                 {...this.memoryView}
               />
             ),
+            heaps: () => (
+              <Heaps
+                heaps={emulator.heaps()}
+                {...this.memoryView}
+              />
+            ),
+            threads: () => (
+              <Threads
+                threads={emulator.threads()}
+                {...this.memoryView}
+              />
+            ),
 
             imports: () => {
               const labels = emulator.labels();
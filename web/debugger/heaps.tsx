@@ -0,0 +1,62 @@
+import * as preact from 'preact';
+import { h } from 'preact';
+import * as wasm from '../glue/pkg/glue';
+import { MemoryView, Number } from './memory';
+import { hex } from './util';
+
+namespace Heaps {
+  export interface Props extends MemoryView {
+    heaps: wasm.HeapStats[];
+  }
+}
+export class Heaps extends preact.Component<Heaps.Props> {
+  render() {
+    const sections = this.props.heaps.map(heap => {
+      const callers = heap.top_callers.map(([caller, blocks, bytes]) => (
+        <tr>
+          <td>
+            <code>
+              <Number digits={8} {...this.props}>{caller}</Number>
+            </code>
+          </td>
+          <td style={{ textAlign: 'right', padding: '0 2ex' }}>{blocks}</td>
+          <td style={{ textAlign: 'right' }}>
+            <code>{hex(bytes)}</code>
+          </td>
+        </tr>
+      ));
+      return (
+        <div style={{ marginBottom: '2ex' }}>
+          <div>
+            <code>
+              heap <Number digits={8} {...this.props}>{heap.addr}</Number>
+            </code>{' '}
+            used <code>{hex(heap.used)}</code>/<code>{hex(heap.size)}</code> in {heap.busy_blocks} blocks,{' '}
+            {heap.free_blocks} free blocks
+          </div>
+          {heap.corrupt != null
+            ? (
+              <div style={{ color: 'red' }}>
+                corrupt block header at{' '}
+                <code>
+                  <Number digits={8} {...this.props}>{heap.corrupt}</Number>
+                </code>
+              </div>
+            )
+            : null}
+          <table>
+            <thead>
+              <tr>
+                <th>caller</th>
+                <th>blocks</th>
+                <th>bytes</th>
+              </tr>
+            </thead>
+            <tbody>{callers}</tbody>
+          </table>
+        </div>
+      );
+    });
+    return <section style={{ flex: 1, minHeight: 0, overflow: 'auto' }}>{sections}</section>;
+  }
+}
@@ -0,0 +1,51 @@
+import * as preact from 'preact';
+import { h } from 'preact';
+import * as wasm from '../glue/pkg/glue';
+import { MemoryView, Number } from './memory';
+import { hex } from './util';
+
+namespace Threads {
+  export interface Props extends MemoryView {
+    threads: wasm.ThreadInfo[];
+  }
+}
+export class Threads extends preact.Component<Threads.Props> {
+  render() {
+    const rows = this.props.threads.map(thread => {
+      let state = thread.state;
+      if (thread.waiting_on.length > 0) {
+        state += ' on ' + thread.waiting_on.map(handle => hex(handle)).join(', ');
+      }
+      if (thread.deadline != null) {
+        state += ` until ${thread.deadline}`;
+      }
+      return (
+        <tr class={thread.current ? 'highlight' : undefined}>
+          <td style={{ textAlign: 'right', padding: '0 2ex' }}>{thread.id}</td>
+          <td>
+            <code>
+              <Number digits={8} {...this.props}>{thread.eip}</Number>
+            </code>
+          </td>
+          <td style={{ textAlign: 'right', padding: '0 2ex' }}>{thread.priority}</td>
+          <td>{state}</td>
+        </tr>
+      );
+    });
+    return (
+      <section style={{ flex: 1, minHeight: 0, display: 'flex' }}>
+        <table style={{ display: 'block', overflow: 'auto', position: 'relative' }}>
+          <thead>
+            <tr>
+              <th>id</th>
+              <th>eip</th>
+              <th>priority</th>
+              <th>state</th>
+            </tr>
+          </thead>
+          <tbody>{rows}</tbody>
+        </table>
+      </section>
+    );
+  }
+}
@@ -1,14 +1,24 @@
 import { Breakpoints } from './debugger/break';
 import * as wasm from './glue/pkg/glue';
 import { FileSet, JsHost } from './host';
+import { saveRegistry } from './registry';
 
 /** Functions the emulator may need to call. */
+/** A message the program passed to OutputDebugString. */
+export interface DebugString {
+  /** Emulated time in milliseconds. */
+  ticks: number;
+  threadId: number;
+  msg: string;
+}
+
 export interface EmulatorHost {
   exit(code: number): void;
   onWindowChanged(): void;
   showTab(name: string): void;
   onError(msg: string): void;
   onStdOut(stdout: string): void;
+  onDebugString(str: DebugString): void;
   onStopped(): void;
 }
 
@@ -17,6 +27,8 @@ export class Emulator extends JsHost {
   readonly emu: wasm.Emulator;
   /** True when the emulator is actively trying to loop and executing instructions, false when stopped or blocked. */
   running = false;
+  /** True when stopped because every thread is blocked, for wake() to resume. */
+  private blocked = false;
   breakpoints: Breakpoints;
   channel = new MessageChannel();
 
@@ -33,6 +45,8 @@ export class Emulator extends JsHost {
     this.emu = wasm.new_emulator(this, cmdLine);
     this.emu.set_external_dlls(externalDLLs);
     this.emu.load_exe(exePath, bytes, relocate);
+    // Pause into the debugger rather than silently spinning on a hung guest.
+    this.emu.set_watchdog('10s');
     this.breakpoints = new Breakpoints(exePath);
 
     this.channel.port2.onmessage = () => this.loop();
@@ -88,22 +102,32 @@ export class Emulator extends JsHost {
             this.emuHost.showTab('breakpoints');
           }
           this.emuHost.onStopped();
+        } else {
+          // Paused by the watchdog; the report is in the log.
+          this.emuHost.onStopped();
         }
         return false;
       }
       case wasm.Status.Blocked:
+        this.blocked = true;
+        this.emuHost.onStopped();
+        return false;
       case wasm.Status.Error:
         this.emuHost.onStopped();
         return false;
-      case wasm.Status.Exit:
+      case wasm.Status.Exit: {
         this.emuHost.onStopped();
+        const report = this.emu.crash_report();
+        if (report) this.emuHost.onError(report);
         this.emuHost.exit(this.emu.exit_code);
         return false;
+      }
     }
   }
 
   start() {
     if (this.running) return;
+    this.blocked = false;
     this.emu.unblock(); // Attempt to resume any blocked threads.
     // Advance past the current breakpoint, if any.
     if (this.breakpoints.isAtBreakpoint(this.emu.eip)) {
@@ -114,6 +138,13 @@ export class Emulator extends JsHost {
     this.channel.port1.postMessage(null);
   }
 
+  wake() {
+    // Only threads waiting on the host: a thread stopped in the debugger stays stopped,
+    // and so does an emulator that the user stopped rather than one that ran out of work.
+    this.emu.wake();
+    if (this.blocked) this.start();
+  }
+
   /** Runs a batch of instructions; called in a loop. */
   private loop() {
     if (!this.running) return;
@@ -126,12 +157,36 @@ export class Emulator extends JsHost {
 
   stop() {
     this.running = false;
+    saveRegistry(this.exePath, this.emu.save_registry()).catch((e) => console.error('saving registry:', e));
+  }
+
+  /** Saves the whole machine, for loadState() to resume from later. */
+  saveState(): Uint8Array {
+    return this.emu.save_state();
+  }
+
+  /** Replaces the machine with a save state, recreating its windows. */
+  loadState(bytes: Uint8Array) {
+    const running = this.running;
+    this.running = false;
+    this.windows = [];
+    this.emu.load_state(bytes);
+    this.emuHost.onWindowChanged();
+    if (running) this.start();
   }
 
   mappings(): wasm.Mapping[] {
     return JSON.parse(this.emu.mappings_json()) as wasm.Mapping[];
   }
 
+  heaps(): wasm.HeapStats[] {
+    return JSON.parse(this.emu.heaps_json()) as wasm.HeapStats[];
+  }
+
+  threads(): wasm.ThreadInfo[] {
+    return JSON.parse(this.emu.threads_json()) as wasm.ThreadInfo[];
+  }
+
   labels(): Array<[number, string]> {
     const obj = JSON.parse(this.emu.labels()) as Record<number, string>;
     return Object.entries(obj).map(([addr, label]) => [parseInt(addr, 10), label]);
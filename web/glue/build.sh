@@ -10,25 +10,37 @@ cd "$(dirname "$0")"
 
 profile="${profile:-release}"
 
-case $profile in
-debug)
-  cargo build --target wasm32-unknown-unknown --profile dev
-  wasm-bindgen --out-dir pkg --typescript --target web --reference-types \
-    "../../target/wasm32-unknown-unknown/debug/glue.wasm"
-  ;;
-release)
-  cargo build --target wasm32-unknown-unknown --profile release
-  wasm-bindgen --out-dir pkg --typescript --target web --reference-types \
-    "../../target/wasm32-unknown-unknown/$profile/glue.wasm"
-  ;;
-lto)
-  cargo build --target wasm32-unknown-unknown --profile lto
-  wasm-bindgen --out-dir pkg --typescript --target web --reference-types \
-    "../../target/wasm32-unknown-unknown/$profile/glue.wasm"
-  wasm-opt -O --enable-reference-types pkg/glue_bg.wasm -o pkg/glue_bg.wasm-opt
-  mv pkg/glue_bg.wasm-opt pkg/glue_bg.wasm
-  ;;
-*)
-  echo "error: profile=debug or release"
+# Build the wasm and its bindings into $out.  Extra RUSTFLAGS go to a separate target
+# dir, so the two builds below don't keep invalidating each other.
+build() {
+  local out=$1 target_dir=$2
+  local cargo_profile=$profile dir=$profile
+  case $profile in
+  debug)
+    cargo_profile=dev
+    ;;
+  release|lto)
+    ;;
+  *)
+    echo "error: profile=debug or release"
+    exit 1
+  esac
+  CARGO_TARGET_DIR=$target_dir cargo build --target wasm32-unknown-unknown --profile $cargo_profile
+  wasm-bindgen --out-dir $out --typescript --target web --reference-types \
+    "$target_dir/wasm32-unknown-unknown/$dir/glue.wasm"
+  if [[ $profile == lto ]]; then
+    wasm-opt -O --enable-reference-types $out/glue_bg.wasm -o $out/glue_bg.wasm-opt
+    mv $out/glue_bg.wasm-opt $out/glue_bg.wasm
+  fi
+}
+
+build pkg ../../target
+
+# A second module using wasm SIMD (see memory/src/simd.rs), which web.tsx loads instead
+# when the browser supports it.  It shares the JS bindings of the first.
+RUSTFLAGS="-C target-feature=+simd128" build pkg-simd ../../target/simd
+if ! cmp -s pkg/glue.js pkg-simd/glue.js; then
+  echo "error: SIMD build produced different bindings"
   exit 1
-esac
+fi
+cp pkg-simd/glue_bg.wasm pkg/glue_bg_simd.wasm
@@ -6,9 +6,20 @@ fn err_from_anyhow(err: anyhow::Error) -> JsError {
     JsError::new(&err.to_string())
 }
 
+fn hw_breakpoint(addr: u32, len: u32, on: &str) -> JsResult<x86::HwBreakpoint> {
+    let on = match on {
+        "x" => x86::BreakOn::Execute,
+        "w" => x86::BreakOn::Write,
+        "rw" => x86::BreakOn::ReadWrite,
+        _ => return Err(JsError::new(&format!("bad breakpoint kind {on:?}"))),
+    };
+    Ok(x86::HwBreakpoint { addr, len, on })
+}
+
 #[wasm_bindgen]
 pub struct Emulator {
     machine: win32::Machine,
+    watchdog: Option<win32::watchdog::Watchdog>,
 }
 
 #[wasm_bindgen]
@@ -84,6 +95,23 @@ impl Emulator {
 
     pub fn unblock(&mut self) {
         self.machine.unblock_all();
+        if let Some(watchdog) = &mut self.watchdog {
+            watchdog.reset(&self.machine);
+        }
+    }
+
+    /// Wake threads blocked on the host, unlike unblock() leaving any thread stopped in
+    /// the debugger stopped.
+    pub fn wake(&mut self) {
+        self.machine.wake_blocked();
+    }
+
+    /// Pause into the debugger when the guest runs for the given budget (see
+    /// win32::watchdog::Budget) without making progress.
+    pub fn set_watchdog(&mut self, budget: &str) -> JsResult<()> {
+        let budget = budget.parse().map_err(|err: String| JsError::new(&err))?;
+        self.watchdog = Some(win32::watchdog::Watchdog::new(budget));
+        Ok(())
     }
 
     /// Run code until at least count instructions have run.
@@ -99,12 +127,19 @@ impl Emulator {
                 if !self.machine.run() {
                     break;
                 }
+                if let Some(watchdog) = &mut self.watchdog {
+                    if let Some(report) = watchdog.check(&self.machine) {
+                        log::error!("{report}");
+                        return Ok(Status::DebugBreak);
+                    }
+                }
             }
         }
 
         Ok(match &self.machine.status {
             win32::Status::Running => Status::Running,
             win32::Status::Blocked => Status::Blocked,
+            win32::Status::DebugBreak => Status::DebugBreak,
             win32::Status::Error { message } => return Err(JsError::new(message)),
             win32::Status::Exit(_code) => {
                 // TODO: use exit code
@@ -113,6 +148,27 @@ impl Emulator {
         })
     }
 
+    /// Save the whole machine, to resume later with load_state(); see win32::snapshot.
+    /// This fails while a thread is inside a blocking call, so retry after running more.
+    pub fn save_state(&mut self) -> JsResult<Vec<u8>> {
+        win32::snapshot::save(&mut self.machine).map_err(err_from_anyhow)
+    }
+
+    /// Replace the running machine with a save state from save_state().
+    pub fn load_state(&mut self, buf: &[u8]) -> JsResult<()> {
+        win32::snapshot::load(&mut self.machine, buf).map_err(err_from_anyhow)?;
+        if let Some(watchdog) = &mut self.watchdog {
+            watchdog.reset(&self.machine);
+        }
+        Ok(())
+    }
+
+    /// The report of the exception that killed the process, if one did.
+    pub fn crash_report(&self) -> Option<String> {
+        let report = self.machine.state.kernel32.crash_report.as_ref()?;
+        Some(report.to_string())
+    }
+
     pub fn breakpoint_add(&mut self, addr: u32) {
         self.machine.add_breakpoint(addr);
     }
@@ -120,18 +176,119 @@ impl Emulator {
         self.machine.clear_breakpoint(addr);
     }
 
+    pub fn watchpoint_add(&mut self, addr: u32, len: u32) {
+        self.machine.add_watchpoint(addr, len);
+    }
+    pub fn watchpoint_clear(&mut self, addr: u32, len: u32) {
+        self.machine.clear_watchpoint(addr, len);
+    }
+
+    /// Add a hardware breakpoint, where `on` is "x" (execute), "w" (write), or "rw".
+    pub fn hw_breakpoint_add(&mut self, addr: u32, len: u32, on: &str) -> JsResult<()> {
+        let bp = hw_breakpoint(addr, len, on)?;
+        self.machine.add_hw_breakpoint(bp);
+        Ok(())
+    }
+    pub fn hw_breakpoint_clear(&mut self, addr: u32, len: u32, on: &str) -> JsResult<()> {
+        let bp = hw_breakpoint(addr, len, on)?;
+        self.machine.clear_hw_breakpoint(&bp);
+        Ok(())
+    }
+
     pub fn mappings_json(&self) -> String {
         serde_json::to_string(&self.machine.state.kernel32.mappings.vec()).unwrap_throw()
     }
 
+    pub fn heaps_json(&self) -> String {
+        let stats = self.machine.state.kernel32.heap_stats(self.machine.mem());
+        serde_json::to_string(&stats).unwrap_throw()
+    }
+
+    pub fn threads_json(&self) -> String {
+        serde_json::to_string(&win32::winapi::kernel32::thread_infos(&self.machine)).unwrap_throw()
+    }
+
+    /// Set the guest clock mode, as "real", "scale:RATE" or "instrs:N".
+    pub fn set_clock(&mut self, mode: &str) -> JsResult<()> {
+        let mode = mode.parse().map_err(|err: String| JsError::new(&err))?;
+        self.machine.set_clock_mode(mode);
+        Ok(())
+    }
+
+    /// Select the processor the guest sees; see x86::CpuProfile for the names.
+    pub fn set_cpu(&mut self, profile: &str) -> JsResult<()> {
+        let profile = profile.parse().map_err(|err: String| JsError::new(&err))?;
+        self.machine.set_cpu_profile(profile);
+        Ok(())
+    }
+
+    /// Set the size of the monitor, as "WIDTHxHEIGHT" or "WIDTHxHEIGHTxBPP".
+    pub fn set_monitor(&mut self, mode: &str) -> JsResult<()> {
+        let mode = mode.parse().map_err(|err: String| JsError::new(&err))?;
+        win32::winapi::user32::set_desktop_mode(&mut self.machine, mode);
+        Ok(())
+    }
+
+    /// Set the rate of the RDTSC counter, overriding that of the CPU profile.
+    pub fn set_tsc_mhz(&mut self, mhz: u32) {
+        self.machine.clock.tsc_mhz = mhz;
+    }
+
     pub fn set_tracing_scheme(&self, scheme: &str) {
         win32::trace::set_scheme(scheme);
     }
+
+    /// Map the guest path prefix `guest` to `dir`, a directory within the loaded files.
+    pub fn mount(&mut self, guest: &str, dir: &str, readonly: bool) {
+        let dir = win32::WindowsPathBuf::from(dir.to_string());
+        self.machine.state.kernel32.vfs.mount(guest, dir, readonly);
+    }
+
+    /// Replace the registry with the contents of a regedit .reg file.
+    pub fn load_registry(&mut self, text: &str) -> JsResult<()> {
+        let hive = win32::winapi::advapi32::Hive::parse(text).map_err(|err| JsError::new(&err))?;
+        self.machine.state.advapi32.hive = hive;
+        Ok(())
+    }
+
+    /// The registry, as the contents of a regedit .reg file.
+    pub fn save_registry(&self) -> String {
+        self.machine.state.advapi32.hive.to_reg()
+    }
+
+    /// Configure the volume of the mount at `guest`; returns false if there's no such mount.
+    pub fn set_volume(&mut self, guest: &str, cdrom: bool, label: &str, serial: u32) -> bool {
+        let Some(volume) = self.machine.state.kernel32.vfs.volume_mut(guest) else {
+            return false;
+        };
+        volume.kind = if cdrom {
+            win32::winapi::kernel32::DriveKind::CdRom
+        } else {
+            win32::winapi::kernel32::DriveKind::Fixed
+        };
+        volume.label = label.to_string();
+        volume.serial = serial;
+        true
+    }
 }
 
 #[wasm_bindgen]
 pub fn new_emulator(host: JsHost, cmdline: String) -> Emulator {
     crate::log::init(host.clone().unchecked_into());
-    let machine = win32::Machine::new(Box::new(host), cmdline);
-    Emulator { machine }
+    let mut machine = win32::Machine::new(Box::new(host), cmdline);
+    // Files are loaded relative to the current directory, Z:\.
+    machine
+        .state
+        .kernel32
+        .vfs
+        .mount("Z:\\", win32::WindowsPathBuf::new(), false);
+    machine
+        .state
+        .kernel32
+        .vfs
+        .mount_temp(win32::WindowsPathBuf::from("temp"));
+    Emulator {
+        machine,
+        watchdog: None,
+    }
 }
@@ -8,6 +8,7 @@ struct WebSurface {
     _hwnd: u32,
     canvas: web_sys::HtmlCanvasElement,
     width: u32,
+    height: u32,
     ctx: web_sys::CanvasRenderingContext2d,
     screen: web_sys::CanvasRenderingContext2d,
 }
@@ -40,6 +41,7 @@ impl WebSurface {
             _hwnd: hwnd,
             canvas,
             width: opts.width,
+            height: opts.height,
             ctx,
             screen,
         }
@@ -56,7 +58,22 @@ impl win32::Surface for WebSurface {
         self.ctx.put_image_data(&image_data, 0.0, 0.0).unwrap();
     }
 
+    fn read_pixels(&mut self) -> Vec<[u8; 4]> {
+        let image_data = self
+            .ctx
+            .get_image_data(0.0, 0.0, self.width as f64, self.height as f64)
+            .unwrap();
+        image_data
+            .data()
+            .chunks_exact(4)
+            .map(|p| [p[0], p[1], p[2], p[3]])
+            .collect()
+    }
+
     fn show(&mut self) {
+        // Clear first, as transparent (color-keyed) pixels are blended onto what's underneath.
+        self.screen
+            .clear_rect(0.0, 0.0, self.width as f64, self.height as f64);
         self.screen
             .draw_image_with_html_canvas_element(&self.canvas, 0.0, 0.0)
             .unwrap();
@@ -97,6 +114,8 @@ const JSWINDOW_TS: &'static str = r#"
 export interface JsWindow {
   title: string;
   set_size(width: number, height: number): void;
+  show(visible: boolean): void;
+  set_alpha(alpha: number): void;
 }"#;
 
 #[wasm_bindgen]
@@ -106,6 +125,10 @@ extern "C" {
     fn set_title(this: &JsWindow, title: &str);
     #[wasm_bindgen(method)]
     fn set_size(this: &JsWindow, width: u32, height: u32);
+    #[wasm_bindgen(method)]
+    fn show(this: &JsWindow, visible: bool);
+    #[wasm_bindgen(method)]
+    fn set_alpha(this: &JsWindow, alpha: u8);
 }
 
 impl win32::Window for JsWindow {
@@ -117,6 +140,14 @@ impl win32::Window for JsWindow {
         JsWindow::set_size(self, width, height);
     }
 
+    fn show(&mut self, visible: bool) {
+        JsWindow::show(self, visible);
+    }
+
+    fn set_alpha(&mut self, alpha: u8) {
+        JsWindow::set_alpha(self, alpha);
+    }
+
     fn fullscreen(&mut self) {
         log::warn!("todo: fullscreen");
     }
@@ -126,9 +157,11 @@ impl win32::Window for JsWindow {
 const JSFILE_TS: &'static str = r#"
 export interface JsFile {
   info(): number;
+  tell(): number;
   seek(ofs: number): boolean;
   read(buf: Uint8Array): number;
   write(buf: Uint8Array): number;
+  set_len(len: number): void;
 }"#;
 
 #[wasm_bindgen]
@@ -137,11 +170,15 @@ extern "C" {
     #[wasm_bindgen(method)]
     fn info(this: &JsFile) -> u64;
     #[wasm_bindgen(method)]
+    fn tell(this: &JsFile) -> u32;
+    #[wasm_bindgen(method)]
     fn seek(this: &JsFile, ofs: u32) -> bool;
     #[wasm_bindgen(method)]
     fn read(this: &JsFile, buf: &mut [u8]) -> u32;
     #[wasm_bindgen(method)]
     fn write(this: &JsFile, buf: &[u8]) -> u32;
+    #[wasm_bindgen(method, js_name = set_len)]
+    fn js_set_len(this: &JsFile, len: u32);
 }
 
 impl win32::File for JsFile {
@@ -152,11 +189,17 @@ impl win32::File for JsFile {
             atime: 0,
             ctime: 0,
             mtime: 0,
+            readonly: false,
         })
     }
 
     fn set_len(&self, len: u64) -> Result<(), win32::ERROR> {
-        todo!("set_len {len}")
+        JsFile::js_set_len(self, len as u32);
+        Ok(())
+    }
+
+    fn set_times(&self, _atime: Option<i64>, _mtime: Option<i64>) -> Result<(), win32::ERROR> {
+        Ok(()) // the in-memory files don't have times
     }
 }
 
@@ -174,23 +217,21 @@ impl std::io::Write for JsFile {
     }
 
     fn flush(&mut self) -> std::io::Result<()> {
-        todo!()
+        Ok(()) // writes go directly to the in-memory file
     }
 }
 
 impl std::io::Seek for JsFile {
     fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
-        match pos {
-            std::io::SeekFrom::Start(ofs) => {
-                if JsFile::seek(self, ofs as u32) {
-                    Ok(ofs)
-                } else {
-                    todo!();
-                }
-            }
-            std::io::SeekFrom::End(_) => todo!(),
-            std::io::SeekFrom::Current(_) => todo!(),
+        let ofs = match pos {
+            std::io::SeekFrom::Start(ofs) => ofs as i64,
+            std::io::SeekFrom::End(delta) => JsFile::info(self) as i64 + delta,
+            std::io::SeekFrom::Current(delta) => JsFile::tell(self) as i64 + delta,
+        };
+        if ofs < 0 || !JsFile::seek(self, ofs as u32) {
+            return Err(std::io::ErrorKind::InvalidInput.into());
         }
+        Ok(ofs as u64)
     }
 }
 
@@ -231,6 +272,8 @@ fn message_from_event(event: web_sys::Event) -> anyhow::Result<win32::Message> {
             event.down = false;
             win32::MessageDetail::Mouse(event)
         }
+        "focus" => win32::MessageDetail::Focus(true),
+        "blur" => win32::MessageDetail::Focus(false),
         ty => bail!("unhandled event type {ty}"),
     };
     log::info!("msg: {:?}", detail);
@@ -252,16 +295,23 @@ export interface JsHost {
   ensure_timer(when: number): void;
   get_event(): Event | undefined;
   
-  open(path: string, access: {}): JsFile|null;
+  open(path: string, options: FileOptions): JsFile|null;
+  stat(path: string): number;
+  remove_file(path: string): boolean;
+  rename(from: string, to: string): boolean;
   stdout(buf: Uint8Array): void;
+  debug_string(ticks: number, thread_id: number, msg: string): void;
+  read_stdin(buf: Uint8Array): number;
+  message_box(id: number, caption: string, text: string, buttons: string, def: number): number;
   
   create_window(hwnd: number): JsWindow;
-  screen(): CanvasRenderingContext2D;
+  screen(hwnd: number): CanvasRenderingContext2D;
   audio(buf: Int16Array): void;
 }"#;
 
 #[wasm_bindgen]
 extern "C" {
+    #[derive(Clone)]
     pub type JsHost;
 
     #[wasm_bindgen(method)]
@@ -275,14 +325,33 @@ extern "C" {
 
     #[wasm_bindgen(method)]
     fn open(this: &JsHost, path: &str, options: win32::FileOptions) -> Option<JsFile>;
+    #[wasm_bindgen(method, js_name = stat)]
+    fn js_stat(this: &JsHost, path: &str) -> i32;
+    #[wasm_bindgen(method, js_name = remove_file)]
+    fn js_remove_file(this: &JsHost, path: &str) -> bool;
+    #[wasm_bindgen(method, js_name = rename)]
+    fn js_rename(this: &JsHost, from: &str, to: &str) -> bool;
     #[wasm_bindgen(method)]
     fn stdout(this: &JsHost, buf: &[u8]);
+    #[wasm_bindgen(method, js_name = debug_string)]
+    fn js_debug_string(this: &JsHost, ticks: u32, thread_id: u32, msg: &str);
+    #[wasm_bindgen(method, js_name = read_stdin)]
+    fn js_read_stdin(this: &JsHost, buf: &mut [u8]) -> i32;
+    #[wasm_bindgen(method, js_name = message_box)]
+    fn js_message_box(
+        this: &JsHost,
+        id: u32,
+        caption: &str,
+        text: &str,
+        buttons: &str,
+        def: u32,
+    ) -> i32;
 
     #[wasm_bindgen(method)]
     fn create_window(this: &JsHost, hwnd: u32) -> JsWindow;
 
     #[wasm_bindgen(method)]
-    fn screen(this: &JsHost) -> web_sys::CanvasRenderingContext2d;
+    fn screen(this: &JsHost, hwnd: u32) -> web_sys::CanvasRenderingContext2d;
 
     #[wasm_bindgen(method)]
     fn audio(this: &JsHost, buf: &[i16]);
@@ -320,14 +389,31 @@ impl win32::Host for JsHost {
         path: &WindowsPath,
         options: win32::FileOptions,
     ) -> Result<Box<dyn win32::File>, win32::ERROR> {
-        match JsHost::open(self, &path.to_string_lossy(), options) {
+        let path = path.to_string_lossy();
+        let create_new = options.create_new;
+        match JsHost::open(self, &path, options) {
             Some(file) => Ok(Box::new(file)),
+            None if create_new && JsHost::js_stat(self, &path) >= 0 => {
+                Err(win32::ERROR::FILE_EXISTS)
+            }
             None => Err(win32::ERROR::FILE_NOT_FOUND),
         }
     }
 
     fn stat(&self, path: &WindowsPath) -> Result<Stat, win32::ERROR> {
-        todo!("stat {path}")
+        // Returns the file size, or -1 if there's no such file.
+        let size = JsHost::js_stat(self, &path.to_string_lossy());
+        if size < 0 {
+            return Err(win32::ERROR::FILE_NOT_FOUND);
+        }
+        Ok(Stat {
+            kind: StatKind::File,
+            size: size as u64,
+            atime: 0,
+            ctime: 0,
+            mtime: 0,
+            readonly: false,
+        })
     }
 
     fn read_dir(&self, _path: &WindowsPath) -> Result<Box<dyn win32::ReadDir>, win32::ERROR> {
@@ -338,6 +424,32 @@ impl win32::Host for JsHost {
         JsHost::stdout(self, buf)
     }
 
+    fn debug_string(&self, ticks: u32, thread_id: u32, msg: &str) {
+        JsHost::js_debug_string(self, ticks, thread_id, msg)
+    }
+
+    fn read_stdin(&self, buf: &mut [u8]) -> Result<usize, win32::ERROR> {
+        match JsHost::js_read_stdin(self, buf) {
+            // Nothing typed yet; the caller polls again later.
+            -1 => Err(win32::ERROR::IO_PENDING),
+            n => Ok(n as usize),
+        }
+    }
+
+    fn message_box(&self, id: u32, msgbox: &win32::MessageBox) -> Result<usize, win32::ERROR> {
+        let buttons = msgbox.buttons.join("\n");
+        let def = msgbox.default as u32;
+        match JsHost::js_message_box(self, id, &msgbox.caption, &msgbox.text, &buttons, def) {
+            // Not answered yet; the host unblocks us once it is.
+            -1 => Err(win32::ERROR::IO_PENDING),
+            n => Ok(n as usize),
+        }
+    }
+
+    fn clone_host(&self) -> Box<dyn win32::Host> {
+        Box::new(self.clone())
+    }
+
     fn create_window(&mut self, hwnd: u32) -> Box<dyn win32::Window> {
         let window = JsHost::create_window(self, hwnd);
         Box::new(window)
@@ -348,7 +460,7 @@ impl win32::Host for JsHost {
         hwnd: u32,
         opts: &win32::SurfaceOptions,
     ) -> Box<dyn win32::Surface> {
-        Box::new(WebSurface::new(hwnd, opts, JsHost::screen(self)))
+        Box::new(WebSurface::new(hwnd, opts, JsHost::screen(self, hwnd)))
     }
 
     fn current_dir(&self) -> Result<win32::WindowsPathBuf, win32::ERROR> {
@@ -360,13 +472,27 @@ impl win32::Host for JsHost {
     }
 
     fn remove_file(&self, path: &WindowsPath) -> Result<(), win32::ERROR> {
-        todo!("remove_file {path}")
+        if !JsHost::js_remove_file(self, &path.to_string_lossy()) {
+            return Err(win32::ERROR::FILE_NOT_FOUND);
+        }
+        Ok(())
     }
 
     fn remove_dir(&self, path: &WindowsPath) -> Result<(), win32::ERROR> {
         todo!("remove_dir {path}")
     }
 
+    fn rename(&self, from: &WindowsPath, to: &WindowsPath) -> Result<(), win32::ERROR> {
+        if !JsHost::js_rename(self, &from.to_string_lossy(), &to.to_string_lossy()) {
+            return Err(win32::ERROR::FILE_NOT_FOUND);
+        }
+        Ok(())
+    }
+
+    fn set_readonly(&self, _path: &WindowsPath, _readonly: bool) -> Result<(), win32::ERROR> {
+        Ok(()) // the in-memory files don't have permissions
+    }
+
     fn init_audio(&mut self, _sample_rate: u32) -> Box<dyn win32::Audio> {
         todo!()
     }
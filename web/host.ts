@@ -18,6 +18,10 @@ class Window implements glue.JsWindow {
     this.canvas.onmousedown = stashEvent;
     this.canvas.onmouseup = stashEvent;
     this.canvas.onmousemove = stashEvent;
+    // Make the canvas focusable so that activation can follow it.
+    this.canvas.tabIndex = 0;
+    this.canvas.onfocus = stashEvent;
+    this.canvas.onblur = stashEvent;
     this.canvas.oncontextmenu = (ev) => {
       return false;
     };
@@ -25,6 +29,16 @@ class Window implements glue.JsWindow {
 
   title: string = '';
   canvas: HTMLCanvasElement = document.createElement('canvas');
+  visible = false;
+
+  set_title(title: string) {
+    this.title = title;
+    const windows = this.jsHost.windows;
+    if (windows[windows.length - 1] === this) {
+      document.title = title;
+    }
+    this.jsHost.emuHost.onWindowChanged();
+  }
 
   set_size(w: number, h: number) {
     // Note: the canvas must be sized to the size of physical pixels,
@@ -44,39 +58,83 @@ class Window implements glue.JsWindow {
 
     this.jsHost.emuHost.onWindowChanged();
   }
+
+  set_alpha(alpha: number) {
+    this.canvas.style.opacity = `${alpha / 255}`;
+  }
+
+  show(visible: boolean) {
+    this.visible = visible;
+    if (visible) {
+      // Move to the end of the list so it renders on top.
+      const windows = this.jsHost.windows;
+      windows.splice(windows.indexOf(this), 1);
+      windows.push(this);
+      document.title = this.title;
+    }
+    this.jsHost.emuHost.onWindowChanged();
+  }
 }
 
+/** An open file in a FileSet.  Writes are kept in memory, visible to later opens. */
 class File implements glue.JsFile {
   ofs = 0;
 
-  constructor(readonly path: string, readonly bytes: Uint8Array) {
+  constructor(readonly files: FileSet, readonly path: string, readonly writable: boolean) {
+  }
+
+  get bytes(): Uint8Array {
+    return this.files.get(this.path)!;
   }
 
   info(): number {
     return this.bytes.length;
   }
 
+  tell(): number {
+    return this.ofs;
+  }
+
   seek(ofs: number): boolean {
     this.ofs = ofs;
     return true;
   }
 
   read(buf: Uint8Array): number {
-    const n = Math.min(buf.length, this.bytes.length - this.ofs);
+    const n = Math.max(0, Math.min(buf.length, this.bytes.length - this.ofs));
     buf.set(this.bytes.subarray(this.ofs, this.ofs + n));
     this.ofs += n;
     return n;
   }
 
   write(buf: Uint8Array): number {
-    console.warn('ignoring write');
+    if (!this.writable) {
+      return 0;
+    }
+    const end = this.ofs + buf.length;
+    if (end > this.bytes.length) {
+      this.set_len(end);
+    }
+    this.bytes.set(buf, this.ofs);
+    this.ofs = end;
     return buf.length;
   }
+
+  set_len(len: number) {
+    const bytes = new Uint8Array(len);
+    bytes.set(this.bytes.subarray(0, len));
+    this.files.set(this.path, bytes);
+  }
 }
 
 /** A set of (pre)loaded files; a temporary hack until the emulator can load files itself. */
 export type FileSet = Map<string, Uint8Array>;
 
+/** The FileSet key for a path from the emulator, which uses backslash separators. */
+function fileKey(path: string): string {
+  return path.replace(/\\/g, '/');
+}
+
 export async function fetchFileSet(files: string[], dir: string = ''): Promise<FileSet> {
   const fileset: FileSet = new Map();
   for (const file of files) {
@@ -123,6 +181,8 @@ export abstract class JsHost implements glue.JsHost, glue.JsLogger {
   }
 
   abstract start(): void;
+  /** Resume threads blocked on something the host just delivered, like console input. */
+  abstract wake(): void;
 
   enqueueEvent(event: Event) {
     this.events.push(event);
@@ -149,13 +209,36 @@ export abstract class JsHost implements glue.JsHost, glue.JsLogger {
     return this.events.shift();
   }
 
-  open(path: string): glue.JsFile | null {
+  open(path: string, options: glue.FileOptions): glue.JsFile | null {
     // TODO: async file loading.
-    let bytes = this.files.get(path);
-    if (!bytes) {
+    path = fileKey(path);
+    const exists = this.files.has(path);
+    if (exists ? options.create_new : !(options.create || options.create_new)) {
       return null;
     }
-    return new File(path, bytes);
+    if (!exists || options.truncate) {
+      this.files.set(path, new Uint8Array());
+    }
+    return new File(this.files, path, options.write);
+  }
+
+  stat(path: string): number {
+    const bytes = this.files.get(fileKey(path));
+    return bytes ? bytes.length : -1;
+  }
+
+  remove_file(path: string): boolean {
+    return this.files.delete(fileKey(path));
+  }
+
+  rename(from: string, to: string): boolean {
+    const bytes = this.files.get(fileKey(from));
+    if (!bytes) {
+      return false;
+    }
+    this.files.delete(fileKey(from));
+    this.files.set(fileKey(to), bytes);
+    return true;
   }
 
   stdout(buf: Uint8Array) {
@@ -163,6 +246,65 @@ export abstract class JsHost implements glue.JsHost, glue.JsLogger {
     this.emuHost.onStdOut(text);
   }
 
+  debug_string(ticks: number, thread_id: number, msg: string) {
+    this.emuHost.onDebugString({ ticks, threadId: thread_id, msg });
+  }
+
+  /** Console input typed by the user but not yet read by the emulator. */
+  private stdin = new Uint8Array(0);
+
+  /** Queue a line of console input, e.g. from the page's console panel. */
+  writeStdin(text: string) {
+    const bytes = new TextEncoder().encode(text);
+    const stdin = new Uint8Array(this.stdin.length + bytes.length);
+    stdin.set(this.stdin);
+    stdin.set(bytes, this.stdin.length);
+    this.stdin = stdin;
+    this.wake();
+  }
+
+  read_stdin(buf: Uint8Array): number {
+    if (this.stdin.length === 0) {
+      return -1;
+    }
+    const n = Math.min(buf.length, this.stdin.length);
+    buf.set(this.stdin.subarray(0, n));
+    this.stdin = this.stdin.slice(n);
+    return n;
+  }
+
+  /** Answers to message boxes by id, or undefined while one is still showing. */
+  private messageBoxes = new Map<number, number | undefined>();
+
+  message_box(id: number, caption: string, text: string, buttons: string, def: number): number {
+    if (!this.messageBoxes.has(id)) {
+      this.messageBoxes.set(id, undefined);
+      const labels = buttons.split('\n');
+      // Shown from a timer rather than here, so the emulator loop isn't held up meanwhile.
+      // The browser only offers OK or OK/Cancel, so the latter chooses between the default
+      // button and the set's negative answer.
+      const negative = ['Cancel', 'No', 'Abort'].map((label) => labels.indexOf(label)).find((i) => i >= 0)
+        ?? labels.length - 1;
+      setTimeout(() => {
+        const message = `${caption}\n\n${text}`;
+        let answer = def;
+        if (labels.length === 1) {
+          window.alert(message);
+        } else if (!window.confirm(`${message}\n\n(OK: ${labels[def]}, Cancel: ${labels[negative]})`)) {
+          answer = negative;
+        }
+        this.messageBoxes.set(id, answer);
+        this.wake();
+      });
+    }
+    const answer = this.messageBoxes.get(id);
+    if (answer === undefined) {
+      return -1;
+    }
+    this.messageBoxes.delete(id);
+    return answer;
+  }
+
   windows: Window[] = [];
   create_window(hwnd: number): glue.JsWindow {
     let window = new Window(this, hwnd);
@@ -171,11 +313,11 @@ export abstract class JsHost implements glue.JsHost, glue.JsLogger {
     return window;
   }
 
-  screen() {
-    // XXX how to tie surface and window together?
-    // The DirectDraw calls SetCooperativeLevel() on the hwnd, and then CreateSurface with primary,
-    // but how to plumb that info across JS boundary?
-    return this.windows[this.windows.length - 1].canvas.getContext('2d')!;
+  screen(hwnd: number) {
+    // Surfaces not tied to a known window (e.g. DirectDraw before SetCooperativeLevel)
+    // fall back to the most recently shown window.
+    const win = this.windows.find((w) => w.hwnd === hwnd) ?? this.windows[this.windows.length - 1];
+    return win.canvas.getContext('2d')!;
   }
 
   audio(buf: Int16Array) {
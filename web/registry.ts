@@ -0,0 +1,32 @@
+/** Persists each program's registry in IndexedDB, as the text of a regedit .reg file. */
+
+const DB_NAME = 'retrowin32';
+const STORE = 'registry';
+
+function openDB(): Promise<IDBDatabase> {
+  return new Promise((resolve, reject) => {
+    const req = indexedDB.open(DB_NAME, 1);
+    req.onupgradeneeded = () => req.result.createObjectStore(STORE);
+    req.onsuccess = () => resolve(req.result);
+    req.onerror = () => reject(req.error);
+  });
+}
+
+function request<T>(mode: IDBTransactionMode, f: (store: IDBObjectStore) => IDBRequest<T>): Promise<T> {
+  return openDB().then((db) =>
+    new Promise((resolve, reject) => {
+      const req = f(db.transaction(STORE, mode).objectStore(STORE));
+      req.onsuccess = () => resolve(req.result);
+      req.onerror = () => reject(req.error);
+    })
+  );
+}
+
+/** Loads the saved registry of the program at exePath, if any. */
+export function loadRegistry(exePath: string): Promise<string | undefined> {
+  return request('readonly', (store) => store.get(exePath));
+}
+
+export function saveRegistry(exePath: string, text: string): Promise<IDBValidKey> {
+  return request('readwrite', (store) => store.put(text, exePath));
+}
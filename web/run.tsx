@@ -13,6 +13,36 @@ class Panel extends preact.Component<{ emulator?: Emulator }, { status?: Status
     window.location.pathname = window.location.pathname.replace('/run.html', '/debugger.html');
   }
 
+  private saveState = () => {
+    const emulator = this.props.emulator;
+    if (!emulator) return;
+    let bytes;
+    try {
+      bytes = emulator.saveState();
+    } catch (e) {
+      alert(`save state: ${e}`);
+      return;
+    }
+    const a = document.createElement('a');
+    a.href = URL.createObjectURL(new Blob([bytes]));
+    a.download = `${emulator.exePath.split(/[\\/]/).pop()}.state`;
+    a.click();
+    URL.revokeObjectURL(a.href);
+  };
+
+  private stateInput?: HTMLInputElement;
+  private loadState = async (ev: Event) => {
+    const input = ev.currentTarget as HTMLInputElement;
+    const file = input.files?.[0];
+    input.value = '';
+    if (!file || !this.props.emulator) return;
+    try {
+      this.props.emulator.loadState(new Uint8Array(await file.arrayBuffer()));
+    } catch (e) {
+      alert(`load state: ${e}`);
+    }
+  };
+
   private updateStatus = () => {
     if (!this.props.emulator) return;
 
@@ -51,6 +81,19 @@ class Panel extends preact.Component<{ emulator?: Emulator }, { status?: Status
         <button onClick={this.debugger}>
           view in debugger
         </button>
+        <div style='width: 2ex'></div>
+        <button onClick={this.saveState} disabled={!this.props.emulator}>
+          save state
+        </button>
+        <button onClick={() => this.stateInput?.click()} disabled={!this.props.emulator}>
+          load state
+        </button>
+        <input
+          type='file'
+          style={{ display: 'none' }}
+          ref={(input) => this.stateInput = input ?? undefined}
+          onChange={this.loadState}
+        />
         <div style={{ flex: '1' }} />
         {status}
       </header>
@@ -83,6 +126,9 @@ class Page extends preact.Component<{}, Page.State> {
       onStdOut: (stdout) => {
         this.print(stdout);
       },
+      onDebugString: ({ ticks, threadId, msg }) => {
+        console.log(`[${(ticks / 1000).toFixed(3)}s thread ${threadId}] ${msg}`);
+      },
       onStopped: () => {
         // TODO
       },
@@ -97,6 +143,16 @@ class Page extends preact.Component<{}, Page.State> {
     this.setState((state) => ({ output: (state.output ?? '') + text }));
   };
 
+  private onInput = (ev: KeyboardEvent) => {
+    if (ev.key !== 'Enter') return;
+    const input = ev.currentTarget as HTMLInputElement;
+    const line = input.value + '\r\n';
+    input.value = '';
+    // Echo the line, as a line-mode console would.
+    this.print(line.replace('\r', ''));
+    this.state.emulator?.writeStdin(line);
+  };
+
   componentDidMount(): void {
     this.load().catch((e) => this.print(e.stack ?? e.toString()));
   }
@@ -107,6 +163,7 @@ class Page extends preact.Component<{}, Page.State> {
         <Panel emulator={this.state.emulator} />
         <main>
           {this.state.output ? <pre class='stdout'>{this.state.output}</pre> : null}
+          {this.state.emulator ? <input class='stdin' placeholder='console input' onKeyDown={this.onInput} /> : null}
           {this.state.emulator ? <EmulatorComponent emulator={this.state.emulator} /> : null}
         </main>
       </>
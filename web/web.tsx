@@ -3,6 +3,7 @@ import { Fragment, h } from 'preact';
 import { Emulator, EmulatorHost } from './emulator';
 import * as wasm from './glue/pkg/glue';
 import { fetchFileSet } from './host';
+import { loadRegistry } from './registry';
 
 namespace WindowComponent {
   export interface Props {
@@ -69,7 +70,7 @@ namespace EmulatorComponent {
 }
 export class EmulatorComponent extends preact.Component<EmulatorComponent.Props> {
   render() {
-    return this.props.emulator.windows.map((window) => {
+    return this.props.emulator.windows.filter((window) => window.visible).map((window) => {
       return (
         <WindowComponent
           key={window.hwnd}
@@ -94,6 +95,28 @@ interface URLParams {
   relocate?: boolean;
   /** Command line to pass to executable. */
   cmdLine?: string;
+  /** Arguments to pass to the executable, if cmdLine isn't given. */
+  args: string[];
+  /** Guest path prefixes mapped to directories of the loaded files, as GUEST=DIR. */
+  mounts: string[];
+  /** Like mounts, but read-only. */
+  readonlyMounts: string[];
+  /** Like readonlyMounts, but appearing as CD-ROM drives, as GUEST=DIR[,LABEL]. */
+  cdMounts: string[];
+  /** Processor for the guest to see, e.g. pentium3; see x86::CpuProfile. */
+  cpu?: string;
+  /** Rate of the RDTSC counter in MHz, overriding that of the cpu. */
+  tscMhz?: number;
+  /** Size of the monitor, as WIDTHxHEIGHT[xBPP]. */
+  monitor?: string;
+}
+
+/** Quotes an argument such that CommandLineToArgvW will parse it back. */
+function quoteArg(arg: string): string {
+  if (arg !== '' && !/[\s"]/.test(arg)) return arg;
+  // Backslashes are only special when followed by a quote, in which case they must be doubled.
+  const escaped = arg.replace(/(\\*)"/g, '$1$1\\"').replace(/(\\+)$/, '$1$1');
+  return `"${escaped}"`;
 }
 
 function parseURL(): URLParams | undefined {
@@ -105,10 +128,40 @@ function parseURL(): URLParams | undefined {
   const files = query.getAll('file');
   const relocate = query.has('relocate');
   const cmdLine = query.get('cmdline') || undefined;
-  const params: URLParams = { dir, exe, externalDLLs, files, relocate, cmdLine };
+  const args = query.getAll('arg');
+  const mounts = query.getAll('mount');
+  const readonlyMounts = query.getAll('mount-ro');
+  const cdMounts = query.getAll('mount-cd');
+  const cpu = query.get('cpu') || undefined;
+  const tscMhz = query.has('tsc-mhz') ? Number(query.get('tsc-mhz')) : undefined;
+  const monitor = query.get('monitor') || undefined;
+  const params: URLParams = {
+    dir,
+    exe,
+    externalDLLs,
+    files,
+    relocate,
+    cmdLine,
+    args,
+    mounts,
+    readonlyMounts,
+    cdMounts,
+    cpu,
+    tscMhz,
+    monitor,
+  };
   return params;
 }
 
+/** Whether the browser supports wasm SIMD, by validating a module that uses it. */
+function hasWasmSIMD(): boolean {
+  // A function returning i8x16.popcnt(i8x16.splat(0)); from wasm-feature-detect.
+  const module = new Uint8Array([
+    0, 97, 115, 109, 1, 0, 0, 0, 1, 5, 1, 96, 0, 1, 123, 3, 2, 1, 0, 10, 10, 1, 8, 0, 65, 0, 253, 15, 253, 98, 11,
+  ]);
+  return WebAssembly.validate(module);
+}
+
 export async function loadEmulator(host: EmulatorHost) {
   const params = parseURL();
   if (!params) {
@@ -117,11 +170,13 @@ export async function loadEmulator(host: EmulatorHost) {
 
   const fileset = await fetchFileSet([params.exe, ...params.files], params.dir);
 
-  await wasm.default(new URL('wasm.wasm', document.location.href));
+  // See memory/src/simd.rs for the SIMD build.
+  const module = hasWasmSIMD() ? 'wasm-simd.wasm' : 'wasm.wasm';
+  await wasm.default(new URL(module, document.location.href));
 
-  const cmdLine = params.cmdLine ?? params.exe;
+  const cmdLine = params.cmdLine ?? [params.exe, ...params.args].map(quoteArg).join(' ');
   const exePath = (params.dir ?? '') + params.exe;
-  return new Emulator(
+  const emulator = new Emulator(
     host,
     fileset,
     exePath,
@@ -130,4 +185,38 @@ export async function loadEmulator(host: EmulatorHost) {
     fileset.get(params.exe)!,
     params.relocate ?? false,
   );
+  if (params.cpu) {
+    emulator.emu.set_cpu(params.cpu);
+  }
+  if (params.tscMhz !== undefined) {
+    emulator.emu.set_tsc_mhz(params.tscMhz);
+  }
+  if (params.monitor) {
+    emulator.emu.set_monitor(params.monitor);
+  }
+  const mounts = [
+    ...params.mounts.map((m) => [m, false] as const),
+    ...params.readonlyMounts.map((m) => [m, true] as const),
+  ];
+  for (const [mount, readonly] of mounts) {
+    const [guest, dir] = mount.split('=', 2);
+    if (dir === undefined) {
+      throw new Error(`invalid mount ${mount}, expected GUEST=DIR`);
+    }
+    emulator.emu.mount(guest, dir, readonly);
+  }
+  for (const mount of params.cdMounts) {
+    const [guest, spec] = mount.split('=', 2);
+    if (spec === undefined) {
+      throw new Error(`invalid mount ${mount}, expected GUEST=DIR[,LABEL]`);
+    }
+    const [dir, label = ''] = spec.split(',', 2);
+    emulator.emu.mount(guest, dir, true);
+    emulator.emu.set_volume(guest, true, label, 0);
+  }
+  const registry = await loadRegistry(exePath);
+  if (registry !== undefined) {
+    emulator.emu.load_registry(registry);
+  }
+  return emulator;
 }
@@ -70,6 +70,10 @@ fn generate_def(module_name: &str, dllexports: &parse::DllExports) -> anyhow::Re
     for data in &dllexports.data {
         writeln!(f, "  {} DATA", data.name)?;
     }
+    // Forwards are left for the linker to number, after all the fns.
+    for forward in &dllexports.forwards {
+        writeln!(f, "  {} = {}", forward.name, forward.target)?;
+    }
     Ok(f)
 }
 
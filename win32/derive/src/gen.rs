@@ -26,8 +26,23 @@ fn fn_wrapper(module: TokenStream, dllexport: &parse::DllExport) -> (TokenStream
     let mut fetch_args = quote! {
         let mem = machine.mem().detach();
     };
+    if dllexport
+        .args
+        .iter()
+        .any(|arg| matches!(arg.stack, parse::ArgumentStack::Register(_)))
+    {
+        fetch_args.extend(quote! {
+            let register_args = machine.register_args();
+        });
+    }
     let mut stack_offset = 0u32;
     for parse::Argument { name, ty, stack } in dllexport.args.iter() {
+        if let parse::ArgumentStack::Register(reg) = stack {
+            fetch_args.extend(quote! {
+                let #name = <#ty>::from_arg(mem, register_args[#reg]);
+            });
+            continue;
+        }
         // We expect all the stack_offset math to be inlined by the compiler into plain constants.
         // TODO: reading the args in reverse would produce fewer bounds checks...
         fetch_args.extend(quote! {
@@ -99,11 +114,13 @@ fn fn_wrapper(module: TokenStream, dllexport: &parse::DllExport) -> (TokenStream
         )
     };
 
+    let stack_consumed = dllexport.stack_consumed();
     (
         defn,
         quote!(Shim {
             name: #name_str,
             func: #func,
+            stack_consumed: #stack_consumed,
         }),
     )
 }
@@ -5,7 +5,23 @@ use syn::parse::Parser;
 #[derive(Clone, Copy)]
 pub enum CallConv {
     Stdcall,
+    /// Caller cleans the stack, as for C runtime functions.
     Cdecl,
+    /// The first two arguments that fit in a register are passed in ecx and edx.
+    Fastcall,
+    /// The first argument (this) is passed in ecx, as for C++ methods.
+    Thiscall,
+}
+
+impl CallConv {
+    /// How many arguments are passed in registers, ecx then edx.
+    fn register_args(&self) -> usize {
+        match self {
+            CallConv::Stdcall | CallConv::Cdecl => 0,
+            CallConv::Fastcall => 2,
+            CallConv::Thiscall => 1,
+        }
+    }
 }
 
 pub struct DllExportMeta {
@@ -29,12 +45,12 @@ pub struct DllExport<'a> {
 impl<'a> DllExport<'a> {
     pub fn stack_consumed(&self) -> u32 {
         match self.meta.callconv {
-            CallConv::Stdcall => self
+            CallConv::Cdecl => 0, // caller cleaned
+            _ => self
                 .args
                 .iter()
                 .map(|arg| arg.stack.consumed())
                 .sum::<u32>(),
-            CallConv::Cdecl => 0, // caller cleaned
         }
     }
 }
@@ -43,6 +59,14 @@ pub struct DllExportData<'a> {
     pub name: &'a syn::Ident,
 }
 
+/// A dllexport of a function implemented by another DLL, e.g. `pub use ntdll::RtlUnwind`.
+pub struct DllExportForward {
+    /// Name as exported from this DLL.
+    pub name: String,
+    /// Target module and function, e.g. "ntdll.RtlUnwind".
+    pub target: String,
+}
+
 pub struct Vtable {
     pub name: syn::Ident,
     pub fns: Vec<(syn::Ident, Option<String>)>,
@@ -52,6 +76,7 @@ pub struct Vtable {
 pub struct DllExports<'a> {
     pub fns: Vec<DllExport<'a>>,
     pub data: Vec<DllExportData<'a>>,
+    pub forwards: Vec<DllExportForward>,
     pub vtables: Vec<Vtable>,
 }
 
@@ -78,6 +103,12 @@ fn parse_dllexport(attr: &syn::Attribute) -> syn::Result<Option<DllExportMeta>>
             } else if meta.path.is_ident("cdecl") {
                 callconv = CallConv::Cdecl;
                 Ok(())
+            } else if meta.path.is_ident("fastcall") {
+                callconv = CallConv::Fastcall;
+                Ok(())
+            } else if meta.path.is_ident("thiscall") {
+                callconv = CallConv::Thiscall;
+                Ok(())
             } else {
                 Err(meta.error("bad path {path:?}"))
             }
@@ -106,13 +137,15 @@ pub enum ArgumentStack {
     /// Value is amount of stack the argument uses in stdcall.
     Ordinary(u32),
     VarArgs,
+    /// Passed in a register rather than on the stack; value is 0 for ecx, 1 for edx.
+    Register(usize),
 }
 
 impl ArgumentStack {
     pub fn consumed(&self) -> u32 {
         match self {
             ArgumentStack::Ordinary(ofs) => *ofs,
-            ArgumentStack::VarArgs => 0,
+            ArgumentStack::VarArgs | ArgumentStack::Register(_) => 0,
         }
     }
 }
@@ -128,7 +161,7 @@ pub fn parse_argument_stack(ty: &syn::Type) -> ArgumentStack {
     }
 
     let name = &ty.path.segments[0].ident;
-    if name == "ArrayWithSize" || name == "ArrayWithSizeMut" || name == "POINT" {
+    if name == "ArrayWithSize" || name == "ArrayWithSizeMut" || name == "POINT" || name == "u64" {
         ArgumentStack::Ordinary(8)
     } else if name == "VarArgs" {
         ArgumentStack::VarArgs
@@ -164,6 +197,22 @@ fn parse_fn<'a>(
         args.push(Argument { name, ty, stack });
     }
 
+    let register_args = args
+        .iter_mut()
+        .filter(|arg| matches!(arg.stack, ArgumentStack::Ordinary(4)))
+        .take(meta.callconv.register_args());
+    for (reg, arg) in register_args.enumerate() {
+        arg.stack = ArgumentStack::Register(reg);
+    }
+    if matches!(meta.callconv, CallConv::Thiscall)
+        && !matches!(args.first(), Some(arg) if matches!(arg.stack, ArgumentStack::Register(0)))
+    {
+        return Err(syn::Error::new_spanned(
+            func,
+            "thiscall functions take this as their first argument",
+        ));
+    }
+
     if args
         .iter()
         .any(|arg| matches!(arg.stack, ArgumentStack::VarArgs))
@@ -281,6 +330,35 @@ fn parse_const(item: &syn::ItemConst) -> syn::Result<Option<DllExportData>> {
     Ok(Some(DllExportData { name: &item.ident }))
 }
 
+/// Parse a `use` of another DLL's function, where the module just before the function
+/// name is the target DLL.
+fn parse_use(item: &syn::ItemUse) -> syn::Result<Option<DllExportForward>> {
+    if find_dllexport(&item.attrs)?.is_none() {
+        return Ok(None);
+    }
+
+    let mut path = Vec::new();
+    let mut tree = &item.tree;
+    let (func, name) = loop {
+        match tree {
+            syn::UseTree::Path(p) => {
+                path.push(&p.ident);
+                tree = &p.tree;
+            }
+            syn::UseTree::Name(n) => break (&n.ident, &n.ident),
+            syn::UseTree::Rename(r) => break (&r.ident, &r.rename),
+            _ => return Err(syn::Error::new_spanned(item, "expected a single function")),
+        }
+    };
+    let Some(module) = path.last() else {
+        return Err(syn::Error::new_spanned(item, "expected a dll module"));
+    };
+    Ok(Some(DllExportForward {
+        name: name.to_string(),
+        target: format!("{module}.{func}"),
+    }))
+}
+
 /// Gather all the dllexports in a list of syn::Items (module contents).
 pub fn gather_dllexports<'a>(
     trace_module: &'a str,
@@ -305,8 +383,82 @@ pub fn gather_dllexports<'a>(
                     out.data.push(item);
                 }
             }
+            syn::Item::Use(item) => {
+                if let Some(item) = parse_use(item)? {
+                    out.forwards.push(item);
+                }
+            }
             _ => continue,
         }
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stacks(func: &syn::ItemFn) -> Vec<String> {
+        let dllexport = parse_fn("test", func).unwrap().unwrap();
+        dllexport
+            .args
+            .iter()
+            .map(|arg| match arg.stack {
+                ArgumentStack::Ordinary(n) => format!("stack{n}"),
+                ArgumentStack::VarArgs => "varargs".into(),
+                ArgumentStack::Register(0) => "ecx".into(),
+                ArgumentStack::Register(_) => "edx".into(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fastcall_registers() {
+        let func: syn::ItemFn = syn::parse_quote! {
+            #[win32_derive::dllexport(fastcall)]
+            fn f(machine: &mut Machine, a: u32, b: u64, c: u32, d: u32) {}
+        };
+        // b doesn't fit in a register, so c takes edx instead.
+        assert_eq!(stacks(&func), ["ecx", "stack8", "edx", "stack4"]);
+        let dllexport = parse_fn("test", &func).unwrap().unwrap();
+        assert_eq!(dllexport.stack_consumed(), 12);
+    }
+
+    #[test]
+    fn thiscall_registers() {
+        let func: syn::ItemFn = syn::parse_quote! {
+            #[win32_derive::dllexport(thiscall)]
+            fn f(machine: &mut Machine, this: u32, a: u32, b: u32) {}
+        };
+        assert_eq!(stacks(&func), ["ecx", "stack4", "stack4"]);
+
+        let func: syn::ItemFn = syn::parse_quote! {
+            #[win32_derive::dllexport(thiscall)]
+            fn f(machine: &mut Machine, this: u64) {}
+        };
+        assert!(parse_fn("test", &func).is_err());
+    }
+
+    #[test]
+    fn stdcall_and_cdecl_use_stack() {
+        let func: syn::ItemFn = syn::parse_quote! {
+            #[win32_derive::dllexport]
+            fn f(machine: &mut Machine, a: u32, b: u32) {}
+        };
+        assert_eq!(stacks(&func), ["stack4", "stack4"]);
+        assert_eq!(
+            parse_fn("test", &func).unwrap().unwrap().stack_consumed(),
+            8
+        );
+
+        let func: syn::ItemFn = syn::parse_quote! {
+            #[win32_derive::dllexport(cdecl)]
+            fn f(machine: &mut Machine, a: u32, args: VarArgs) {}
+        };
+        assert_eq!(stacks(&func), ["stack4", "varargs"]);
+        assert_eq!(
+            parse_fn("test", &func).unwrap().unwrap().stack_consumed(),
+            0
+        );
+    }
+}
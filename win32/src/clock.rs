@@ -0,0 +1,205 @@
+//! The emulated clock behind GetTickCount, QueryPerformanceCounter, timers and timeouts.
+//!
+//! By default it follows the host's clock, but it can also run at a scaled rate
+//! (fast-forward or slow motion), or be derived purely from the number of executed
+//! instructions, which makes runs reproducible regardless of host speed.
+
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClockMode {
+    /// Follow the host clock.
+    Real,
+    /// Follow the host clock, running this many times as fast.
+    Scaled(f64),
+    /// Advance one millisecond per this many executed instructions.  When every thread
+    /// is waiting on a timeout, time skips ahead to it instead of waiting.
+    Instructions(u64),
+}
+
+impl FromStr for ClockMode {
+    type Err = String;
+
+    /// Parses "real", "scale:RATE" or "instrs:N".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mode = match s.split_once(':') {
+            None if s == "real" => Some(ClockMode::Real),
+            Some(("scale", rate)) => rate
+                .parse::<f64>()
+                .ok()
+                .filter(|&rate| rate > 0.0)
+                .map(ClockMode::Scaled),
+            Some(("instrs", n)) => n
+                .parse::<u64>()
+                .ok()
+                .filter(|&n| n > 0)
+                .map(ClockMode::Instructions),
+            _ => None,
+        };
+        mode.ok_or_else(|| format!("invalid clock {s:?}, expected real, scale:RATE or instrs:N"))
+    }
+}
+
+pub struct Clock {
+    mode: ClockMode,
+    /// Emulated time in microseconds as of the last rebase, along with the host ticks
+    /// and the instruction count at that moment.
+    base_us: u64,
+    base_host: u32,
+    base_instrs: usize,
+    /// Rate of the RDTSC time stamp counter, in counts per microsecond.
+    pub tsc_mhz: u32,
+    /// The last value RDTSC returned.
+    last_tsc: u64,
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Clock {
+            mode: ClockMode::Real,
+            base_us: 0,
+            base_host: 0,
+            base_instrs: 0,
+            // A Pentium MMX 200, like the default CPUID.
+            tsc_mhz: 200,
+            last_tsc: 0,
+        }
+    }
+}
+
+/// The emulated time as recorded in a save state.  The mode isn't saved, as it's up to
+/// whoever loads the state.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct SavedClock {
+    us: u64,
+    tsc_mhz: u32,
+    last_tsc: u64,
+}
+
+impl Clock {
+    pub fn save(&self, host_ticks: u32, instrs: usize) -> SavedClock {
+        SavedClock {
+            us: self.now_us(host_ticks, instrs),
+            tsc_mhz: self.tsc_mhz,
+            last_tsc: self.last_tsc,
+        }
+    }
+
+    /// Continue from the time of a save(), in the current mode.
+    pub fn restore(&mut self, saved: &SavedClock, host_ticks: u32, instrs: usize) {
+        self.base_us = saved.us;
+        self.base_host = host_ticks;
+        self.base_instrs = instrs;
+        self.tsc_mhz = saved.tsc_mhz;
+        self.last_tsc = saved.last_tsc;
+    }
+
+    pub fn mode(&self) -> ClockMode {
+        self.mode
+    }
+
+    /// Switch modes, continuing from the current time.
+    pub fn set_mode(&mut self, mode: ClockMode, host_ticks: u32, instrs: usize) {
+        self.rebase(host_ticks, instrs);
+        self.mode = mode;
+    }
+
+    fn rebase(&mut self, host_ticks: u32, instrs: usize) {
+        self.base_us = self.now_us(host_ticks, instrs);
+        self.base_host = host_ticks;
+        self.base_instrs = instrs;
+    }
+
+    /// Current emulated time in microseconds.
+    pub fn now_us(&self, host_ticks: u32, instrs: usize) -> u64 {
+        match self.mode {
+            ClockMode::Real => self.base_us + host_ticks.wrapping_sub(self.base_host) as u64 * 1000,
+            ClockMode::Scaled(rate) => {
+                let elapsed = host_ticks.wrapping_sub(self.base_host) as f64 * 1000.0 * rate;
+                self.base_us + elapsed as u64
+            }
+            ClockMode::Instructions(per_ms) => {
+                let elapsed = instrs.wrapping_sub(self.base_instrs) as u64;
+                self.base_us + elapsed * 1000 / per_ms
+            }
+        }
+    }
+
+    /// Current emulated time in milliseconds, as returned by GetTickCount.
+    pub fn ticks(&self, host_ticks: u32, instrs: usize) -> u32 {
+        (self.now_us(host_ticks, instrs) / 1000) as u32
+    }
+
+    /// The time stamp counter, as read by RDTSC.  It follows emulated time, but the
+    /// clock only changes every millisecond, so successive reads within one still
+    /// advance by a count each to keep deltas from being zero.
+    pub fn tsc(&mut self, host_ticks: u32, instrs: usize) -> u64 {
+        let tsc = self.now_us(host_ticks, instrs) * self.tsc_mhz as u64;
+        self.last_tsc = tsc.max(self.last_tsc + 1);
+        self.last_tsc
+    }
+
+    /// Convert a deadline in emulated ticks into one in host ticks, for Host::block().
+    /// In Instructions mode nothing should actually wait, so time instead jumps ahead to
+    /// the deadline and the returned deadline is the current host time.
+    pub fn host_deadline(&mut self, deadline: u32, host_ticks: u32, instrs: usize) -> u32 {
+        let now = self.ticks(host_ticks, instrs);
+        let remaining = match deadline.wrapping_sub(now) as i32 {
+            ..=0 => return host_ticks,
+            remaining => remaining as u32,
+        };
+        match self.mode {
+            ClockMode::Real => host_ticks + remaining,
+            ClockMode::Scaled(rate) => host_ticks + (remaining as f64 / rate).ceil() as u32,
+            ClockMode::Instructions(_) => {
+                self.rebase(host_ticks, instrs);
+                self.base_us += remaining as u64 * 1000;
+                host_ticks
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scaled() {
+        let mut clock = Clock::default();
+        assert_eq!(clock.ticks(100, 0), 100);
+        clock.set_mode(ClockMode::Scaled(2.0), 100, 0);
+        assert_eq!(clock.ticks(150, 0), 200);
+        // 50 emulated ms from now is 25 host ms away.
+        assert_eq!(clock.host_deadline(250, 150, 0), 175);
+    }
+
+    #[test]
+    fn instructions() {
+        let mut clock = Clock::default();
+        clock.set_mode(ClockMode::Instructions(1000), 0, 0);
+        assert_eq!(clock.ticks(12345, 5000), 5);
+        // Waiting skips ahead rather than blocking on the host.
+        assert_eq!(clock.host_deadline(100, 12345, 5000), 12345);
+        assert_eq!(clock.ticks(99999, 5000), 100);
+        assert_eq!(clock.ticks(99999, 6000), 101);
+    }
+
+    #[test]
+    fn tsc() {
+        let mut clock = Clock::default();
+        clock.tsc_mhz = 100;
+        assert_eq!(clock.tsc(5, 0), 500_000);
+        // Within the same millisecond the count still moves forward.
+        assert_eq!(clock.tsc(5, 0), 500_001);
+        assert_eq!(clock.tsc(6, 0), 600_000);
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!("real".parse(), Ok(ClockMode::Real));
+        assert_eq!("scale:0.5".parse(), Ok(ClockMode::Scaled(0.5)));
+        assert_eq!("instrs:1000".parse(), Ok(ClockMode::Instructions(1000)));
+        assert!("instrs:0".parse::<ClockMode>().is_err());
+    }
+}
@@ -0,0 +1,206 @@
+//! Windows-style crash reports, for exceptions the guest doesn't handle.
+
+use crate::{
+    winapi::kernel32::{self, CONTEXT, EXCEPTION_RECORD},
+    Machine,
+};
+use memory::Extensions;
+
+/// Frames beyond this many are left out of the stack trace.
+const MAX_FRAMES: usize = 32;
+
+pub struct CrashReport {
+    pub code: u32,
+    pub address: u32,
+    pub params: Vec<u32>,
+    pub thread_id: u32,
+    /// Register values by name, in display order.
+    pub regs: Vec<(&'static str, u32)>,
+    /// Return addresses found by walking the ebp chain, starting with the faulting eip,
+    /// each with a description of where it is if known.
+    pub frames: Vec<(u32, Option<String>)>,
+}
+
+impl CrashReport {
+    pub fn new(
+        machine: &Machine,
+        thread_id: u32,
+        record: &EXCEPTION_RECORD,
+        context: &CONTEXT,
+    ) -> Self {
+        let regs = vec![
+            ("eax", context.Eax),
+            ("ebx", context.Ebx),
+            ("ecx", context.Ecx),
+            ("edx", context.Edx),
+            ("esi", context.Esi),
+            ("edi", context.Edi),
+            ("eip", context.Eip),
+            ("esp", context.Esp),
+            ("ebp", context.Ebp),
+            ("efl", context.EFlags),
+        ];
+        let frames = stack_trace(machine, record.ExceptionAddress, context.Ebp)
+            .into_iter()
+            .map(|addr| (addr, describe_address(machine, addr)))
+            .collect();
+        CrashReport {
+            code: record.ExceptionCode,
+            address: record.ExceptionAddress,
+            params: record.ExceptionInformation[..record.NumberParameters as usize].to_vec(),
+            thread_id,
+            regs,
+            frames,
+        }
+    }
+}
+
+/// Walk the chain of saved ebps, as a debugger does without symbols.  Code built without
+/// frame pointers stops the walk early or adds a bogus frame, but it's usually close.
+fn stack_trace(machine: &Machine, eip: u32, ebp: u32) -> Vec<u32> {
+    let mem = machine.mem();
+    let mut frames = vec![eip];
+    let mut ebp = ebp;
+    while frames.len() < MAX_FRAMES {
+        if ebp == 0 || ebp & 3 != 0 || mem.is_oob::<[u32; 2]>(ebp) {
+            break;
+        }
+        let ret = mem.get_pod::<u32>(ebp + 4);
+        if ret == 0 {
+            break;
+        }
+        frames.push(ret);
+        let next = mem.get_pod::<u32>(ebp);
+        // The stack grows down, so callers' frames are at higher addresses.
+        if next <= ebp {
+            break;
+        }
+        ebp = next;
+    }
+    frames
+}
+
+/// Describe a code address by its nearest label, else by its offset into its module.
+pub fn describe_address(machine: &Machine, addr: u32) -> Option<String> {
+    if let Some(label) = nearest_label(machine, addr) {
+        return Some(label);
+    }
+    let hmodule = kernel32::module_from_address(machine, addr)?;
+    let module = kernel32::modules(machine)
+        .into_iter()
+        .find(|module| module.hmodule == hmodule)?;
+    Some(format!("{}+{:x}", module.name, addr - hmodule.to_raw()))
+}
+
+/// Find the closest label at or before addr, rendered like "sub_401000+12".
+pub fn nearest_label(machine: &Machine, addr: u32) -> Option<String> {
+    let (&base, name) = machine
+        .labels
+        .iter()
+        .filter(|(&base, _)| base <= addr && addr - base < 0x1000)
+        .max_by_key(|(&base, _)| base)?;
+    Some(if base == addr {
+        name.clone()
+    } else {
+        format!("{name}+{:x}", addr - base)
+    })
+}
+
+fn exception_name(code: u32) -> Option<&'static str> {
+    Some(match code {
+        0x8000_0001 => "STATUS_GUARD_PAGE_VIOLATION",
+        0x8000_0003 => "STATUS_BREAKPOINT",
+        0x8000_0004 => "STATUS_SINGLE_STEP",
+        0xC000_0005 => "STATUS_ACCESS_VIOLATION",
+        0xC000_0017 => "STATUS_NO_MEMORY",
+        0xC000_001D => "STATUS_ILLEGAL_INSTRUCTION",
+        0xC000_0025 => "STATUS_NONCONTINUABLE_EXCEPTION",
+        0xC000_008C => "STATUS_ARRAY_BOUNDS_EXCEEDED",
+        0xC000_008D => "STATUS_FLOAT_DENORMAL_OPERAND",
+        0xC000_008E => "STATUS_FLOAT_DIVIDE_BY_ZERO",
+        0xC000_008F => "STATUS_FLOAT_INEXACT_RESULT",
+        0xC000_0090 => "STATUS_FLOAT_INVALID_OPERATION",
+        0xC000_0091 => "STATUS_FLOAT_OVERFLOW",
+        0xC000_0092 => "STATUS_FLOAT_STACK_CHECK",
+        0xC000_0093 => "STATUS_FLOAT_UNDERFLOW",
+        0xC000_0094 => "STATUS_INTEGER_DIVIDE_BY_ZERO",
+        0xC000_0095 => "STATUS_INTEGER_OVERFLOW",
+        0xC000_0096 => "STATUS_PRIVILEGED_INSTRUCTION",
+        0xC000_00FD => "STATUS_STACK_OVERFLOW",
+        0xE06D_7363 => "C++ exception",
+        _ => return None,
+    })
+}
+
+impl std::fmt::Display for CrashReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unhandled exception {:08x}", self.code)?;
+        if let Some(name) = exception_name(self.code) {
+            write!(f, " ({name})")?;
+        }
+        write!(f, " at {:08x}", self.address)?;
+        if let Some((_, Some(location))) = self.frames.first() {
+            write!(f, " {location}")?;
+        }
+        writeln!(f, " in thread {}", self.thread_id)?;
+        // Access violations say what was accessed, like the Windows crash dialog.
+        if let (0xC000_0005 | 0x8000_0001, &[write, addr, ..]) = (self.code, self.params.as_slice())
+        {
+            let access = match write {
+                0 => "read",
+                8 => "execute",
+                _ => "write",
+            };
+            writeln!(f, "  {access} of address {addr:08x}")?;
+        } else if !self.params.is_empty() {
+            write!(f, "  parameters:")?;
+            for param in &self.params {
+                write!(f, " {param:08x}")?;
+            }
+            writeln!(f)?;
+        }
+        for (i, (name, value)) in self.regs.iter().enumerate() {
+            let sep = if i % 6 == 5 || i == self.regs.len() - 1 {
+                "\n"
+            } else {
+                " "
+            };
+            write!(f, "{name}={value:08x}{sep}")?;
+        }
+        writeln!(f, "stack trace:")?;
+        for (addr, location) in &self.frames {
+            write!(f, "  {addr:08x}")?;
+            if let Some(location) = location {
+                write!(f, " {location}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display() {
+        let report = CrashReport {
+            code: 0xC000_0005,
+            address: 0x401234,
+            params: vec![1, 0x10],
+            thread_id: 1,
+            regs: vec![("eax", 0x10), ("eip", 0x401234)],
+            frames: vec![(0x401234, Some("sub_401200+34".into())), (0x401050, None)],
+        };
+        assert_eq!(
+            report.to_string(),
+            "unhandled exception c0000005 (STATUS_ACCESS_VIOLATION) at 00401234 sub_401200+34 in thread 1\n\
+             \x20 write of address 00000010\n\
+             eax=00000010 eip=00401234\n\
+             stack trace:\n\
+             \x20 00401234 sub_401200+34\n\
+             \x20 00401050\n"
+        );
+    }
+}
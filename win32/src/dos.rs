@@ -0,0 +1,589 @@
+//! Running the DOS part of an executable in real mode: the MZ stub in front of a PE, or
+//! a small DOS program on its own.
+//!
+//! Only the services such programs commonly use are here, mostly from INT 21h.  Files
+//! and the console go through kernel32, so paths resolve through the same VFS the
+//! Windows program would see.
+
+use crate::{
+    machine::{Machine, Status},
+    pe::IMAGE_DOS_HEADER,
+    winapi::{
+        kernel32::{self, FileFlags, FileHandle, KernelObject, HOBJECT},
+        types::HFILE,
+        ERROR,
+    },
+    FileOptions,
+};
+use memory::Extensions;
+use typed_path::WindowsPath;
+use x86::{real::RealModeCPU, Flags, Register};
+
+/// Segment of the environment block.
+const ENV_SEGMENT: u16 = 0x0080;
+/// Segment of the program segment prefix, which the program is loaded after.
+const PSP_SEGMENT: u16 = 0x0100;
+/// Segment where conventional memory ends.
+const MEM_TOP: u16 = 0xA000;
+
+/// DOS handles 3 and 4 are the AUX and PRN devices, which we don't have.
+const FIRST_FILE_HANDLE: usize = 5;
+
+struct Dos {
+    /// Open files, by DOS handle; the first FIRST_FILE_HANDLE are the standard devices.
+    handles: Vec<Option<HFILE>>,
+    /// Disk transfer area, as segment and offset.
+    dta: (u16, u16),
+    /// End of the memory block holding the program; memory past it is free.
+    mem_end: u16,
+}
+
+/// Run the DOS part of exe until it exits.
+///
+/// A loader that exits successfully from in front of a PE image hands off to it: run()
+/// returns true, leaving machine.status alone, and the caller goes on to load the PE.
+/// Otherwise run() sets machine.status to the exit or error and returns false.
+pub fn run(machine: &mut Machine, exe: &[u8]) -> bool {
+    let mut cpu = RealModeCPU::new();
+    let mem_end = match load(machine, &mut cpu, exe) {
+        Ok(end) => end,
+        Err(message) => {
+            machine.status = Status::Error { message };
+            return false;
+        }
+    };
+    let mut dos = Dos {
+        handles: vec![
+            Some(kernel32::STDIN_HFILE),
+            Some(kernel32::STDOUT_HFILE),
+            Some(kernel32::STDERR_HFILE),
+            None,
+            None,
+        ],
+        dta: (PSP_SEGMENT, 0x80),
+        mem_end,
+    };
+
+    loop {
+        let n = match cpu.run() {
+            Ok(n) => n,
+            Err(message) => {
+                machine.status = Status::Error { message };
+                return false;
+            }
+        };
+        let exit_code = match n {
+            0x00 => {
+                machine.status = Status::Error {
+                    message: "divide error".into(),
+                };
+                return false;
+            }
+            0x10 => {
+                bios_video(machine, &mut cpu);
+                None
+            }
+            0x20 => Some(0),
+            0x21 => dos.int21(machine, &mut cpu),
+            _ => {
+                log::warn!("dos: unhandled int {n:#x}");
+                None
+            }
+        };
+        if let Some(code) = exit_code {
+            if code == 0 && has_pe(exe) {
+                return true;
+            }
+            machine.exit(code);
+            return false;
+        }
+    }
+}
+
+/// Whether the MZ header of exe points at a PE image.
+fn has_pe(exe: &[u8]) -> bool {
+    if exe.len() < std::mem::size_of::<IMAGE_DOS_HEADER>() || !exe.starts_with(b"MZ") {
+        return false;
+    }
+    let offset = exe.get_pod::<IMAGE_DOS_HEADER>(0).e_lfanew as usize;
+    exe.get(offset..)
+        .is_some_and(|pe| pe.starts_with(b"PE\0\0"))
+}
+
+/// Load the program, as an MZ executable or else a .COM file, and set up its PSP and
+/// registers.  Returns the segment just past the memory given to the program.
+fn load(machine: &Machine, cpu: &mut RealModeCPU, exe: &[u8]) -> Result<u16, String> {
+    use Register::{CS, DS, ES, SP, SS};
+    let load_seg = PSP_SEGMENT + 0x10;
+    let max_len = ((MEM_TOP - load_seg) as usize) << 4;
+
+    let is_mz = exe.len() >= std::mem::size_of::<IMAGE_DOS_HEADER>()
+        && (exe.starts_with(b"MZ") || exe.starts_with(b"ZM"));
+    let mem_end = if is_mz {
+        let header = exe.get_pod::<IMAGE_DOS_HEADER>(0);
+        let header_len = header.e_cparhdr as usize * 16;
+        let mut file_len = header.e_cp as usize * 512;
+        if header.e_cblp != 0 {
+            file_len = file_len.saturating_sub(512 - header.e_cblp as usize);
+        }
+        let image = exe
+            .get(header_len..file_len.min(exe.len()))
+            .ok_or_else(|| "dos: invalid MZ header".to_string())?;
+        if image.len() > max_len {
+            return Err(format!("dos: image of {:#x} bytes too large", image.len()));
+        }
+        cpu.slice_mut(load_seg, 0, image.len())
+            .copy_from_slice(image);
+
+        for i in 0..header.e_crlc as u32 {
+            let entry = header.e_lfarlc as u32 + i * 4;
+            if entry as usize + 4 > exe.len() {
+                return Err("dos: relocation table past end of file".into());
+            }
+            let off = exe.get_pod::<u16>(entry);
+            let seg = load_seg.wrapping_add(exe.get_pod::<u16>(entry + 2));
+            let value = cpu.get16(seg, off);
+            cpu.put16(seg, off, value.wrapping_add(load_seg));
+        }
+
+        cpu.regs.set16(CS, load_seg.wrapping_add(header.e_cs));
+        cpu.regs.eip = header.e_ip as u32;
+        cpu.regs.set16(SS, load_seg.wrapping_add(header.e_ss));
+        cpu.regs.set16(SP, header.e_sp);
+        // The program gets the memory it asks for beyond its image, or all of it.
+        let paras = (image.len() as u32).div_ceil(16) + 0x10;
+        let wanted = match header.e_maxalloc {
+            0xFFFF => MEM_TOP as u32,
+            max => PSP_SEGMENT as u32 + paras + max as u32,
+        };
+        wanted.min(MEM_TOP as u32) as u16
+    } else {
+        // A .COM file is loaded at offset 0x100 of a single 64kb segment.
+        if exe.len() > 0xFF00 {
+            return Err(format!(
+                "dos: .COM file of {:#x} bytes too large",
+                exe.len()
+            ));
+        }
+        cpu.slice_mut(PSP_SEGMENT, 0x100, exe.len())
+            .copy_from_slice(exe);
+        cpu.regs.set16(CS, PSP_SEGMENT);
+        cpu.regs.eip = 0x100;
+        cpu.regs.set16(SS, PSP_SEGMENT);
+        cpu.regs.set16(SP, 0xFFFE);
+        // Returning from the program reaches the int 20h at the start of the PSP.
+        cpu.push16(0);
+        MEM_TOP
+    };
+    cpu.regs.set16(DS, PSP_SEGMENT);
+    cpu.regs.set16(ES, PSP_SEGMENT);
+
+    // The environment: no variables beyond PATH, then the path of the program.
+    let mut env = b"PATH=C:\\\0\0\x01\0".to_vec();
+    env.extend_from_slice(kernel32::exe_path(machine).as_bytes());
+    env.push(0);
+    let env_len = ((PSP_SEGMENT - ENV_SEGMENT) as usize) << 4;
+    env.truncate(env_len);
+    cpu.slice_mut(ENV_SEGMENT, 0, env.len())
+        .copy_from_slice(&env);
+
+    // The program segment prefix.
+    cpu.slice_mut(PSP_SEGMENT, 0, 2)
+        .copy_from_slice(&[0xCD, 0x20]); // int 20h
+    cpu.put16(PSP_SEGMENT, 0x02, mem_end);
+    cpu.put16(PSP_SEGMENT, 0x2C, ENV_SEGMENT);
+    let args = machine.state.kernel32.cmdline.args[1..].join(" ");
+    let mut tail = Vec::from(args.as_bytes());
+    if !tail.is_empty() {
+        tail.insert(0, b' ');
+    }
+    tail.truncate(126);
+    cpu.put8(PSP_SEGMENT, 0x80, tail.len() as u8);
+    tail.push(b'\r');
+    cpu.slice_mut(PSP_SEGMENT, 0x81, tail.len())
+        .copy_from_slice(&tail);
+
+    Ok(mem_end)
+}
+
+/// The nul-terminated string at seg:off.
+fn read_asciiz(cpu: &RealModeCPU, seg: u16, off: u16) -> String {
+    let bytes = (0..=0xFFFFu16)
+        .map(|i| cpu.get8(seg, off.wrapping_add(i)))
+        .take_while(|&b| b != 0)
+        .collect::<Vec<_>>();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Report success of a DOS call, via CF.
+fn ok(cpu: &mut RealModeCPU) {
+    cpu.flags.remove(Flags::CF);
+}
+
+/// Report failure of a DOS call, via CF and the error code in AX.  DOS error codes are
+/// the first of the Windows ones.
+fn fail(cpu: &mut RealModeCPU, err: ERROR) {
+    cpu.flags.insert(Flags::CF);
+    cpu.regs.set16(Register::AX, u32::from(err) as u16);
+}
+
+fn write_console(machine: &mut Machine, buf: &[u8]) {
+    _ = kernel32::write_file(machine, kernel32::STDOUT_HFILE, buf, None);
+}
+
+fn read_console(machine: &mut Machine, echo: bool) -> u8 {
+    let mut buf = [0u8];
+    let c = match machine.host.read_stdin(&mut buf) {
+        Ok(1) => buf[0],
+        _ => 0x1A, // ^Z, end of file
+    };
+    if echo {
+        write_console(machine, &buf);
+    }
+    c
+}
+
+/// INT 10h, BIOS video services.
+fn bios_video(machine: &mut Machine, cpu: &mut RealModeCPU) {
+    match cpu.regs.get8(Register::AH) {
+        0x0E => {
+            // teletype output
+            let c = cpu.regs.get8(Register::AL);
+            write_console(machine, &[c]);
+        }
+        ah => log::warn!("dos: unhandled int 10h function {ah:#x}"),
+    }
+}
+
+impl Dos {
+    fn file(&self, handle: u16) -> Result<HFILE, ERROR> {
+        match self.handles.get(handle as usize) {
+            Some(Some(hfile)) => Ok(*hfile),
+            _ => Err(ERROR::INVALID_HANDLE),
+        }
+    }
+
+    /// Open a file and give it a DOS handle.
+    fn open(
+        &mut self,
+        machine: &mut Machine,
+        name: String,
+        options: FileOptions,
+    ) -> Result<u16, ERROR> {
+        let (read, write) = (options.read, options.write);
+        let file = kernel32::vfs(machine).open(WindowsPath::new(&name), options)?;
+        let handle = machine
+            .state
+            .kernel32
+            .objects
+            .add(KernelObject::File(FileHandle {
+                file,
+                path: name,
+                read,
+                write,
+                share_mode: 0,
+                flags: FileFlags::empty(),
+            }));
+        let hfile = HFILE::from_raw(handle.to_raw());
+        let slot = match self.handles[FIRST_FILE_HANDLE..]
+            .iter()
+            .position(|h| h.is_none())
+        {
+            Some(i) => FIRST_FILE_HANDLE + i,
+            None => {
+                self.handles.push(None);
+                self.handles.len() - 1
+            }
+        };
+        self.handles[slot] = Some(hfile);
+        Ok(slot as u16)
+    }
+
+    /// INT 21h, DOS services.  Returns an exit code if the program exited.
+    fn int21(&mut self, machine: &mut Machine, cpu: &mut RealModeCPU) -> Option<u32> {
+        use Register::{AH, AL, AX, BX, CX, DL, DS, DX, ES};
+        let ds = cpu.regs.get16(DS);
+        let dx = cpu.regs.get16(DX);
+        let ah = cpu.regs.get8(AH);
+        match ah {
+            0x00 => return Some(0),
+            0x01 => {
+                let c = read_console(machine, true);
+                cpu.regs.set8(AL, c);
+            }
+            0x02 => write_console(machine, &[cpu.regs.get8(DL)]),
+            0x06 => match cpu.regs.get8(DL) {
+                0xFF => {
+                    // No input is ever waiting.
+                    cpu.flags.insert(Flags::ZF);
+                    cpu.regs.set8(AL, 0);
+                }
+                c => write_console(machine, &[c]),
+            },
+            0x07 | 0x08 => {
+                let c = read_console(machine, false);
+                cpu.regs.set8(AL, c);
+            }
+            0x09 => {
+                let text = (0..=0xFFFFu16)
+                    .map(|i| cpu.get8(ds, dx.wrapping_add(i)))
+                    .take_while(|&b| b != b'$')
+                    .collect::<Vec<_>>();
+                write_console(machine, &text);
+            }
+            0x0E => {
+                // select disk: report the number of drive letters
+                cpu.regs.set8(AL, 26);
+            }
+            0x19 => {
+                let drive = kernel32::vfs(machine)
+                    .current_dir()
+                    .ok()
+                    .and_then(|dir| dir.as_bytes().first().copied())
+                    .map_or(2, |letter| letter.to_ascii_uppercase().wrapping_sub(b'A'));
+                cpu.regs.set8(AL, drive);
+            }
+            0x1A => self.dta = (ds, dx),
+            0x2F => {
+                cpu.regs.set16(ES, self.dta.0);
+                cpu.regs.set16(BX, self.dta.1);
+            }
+            0x25 => {
+                let n = cpu.regs.get8(AL) as u16;
+                cpu.put16(0, n * 4, dx);
+                cpu.put16(0, n * 4 + 2, ds);
+            }
+            0x35 => {
+                let n = cpu.regs.get8(AL) as u16;
+                cpu.regs.set16(BX, cpu.get16(0, n * 4));
+                cpu.regs.set16(ES, cpu.get16(0, n * 4 + 2));
+            }
+            0x30 => {
+                // DOS 5.0
+                cpu.regs.set16(AX, 0x0005);
+                cpu.regs.set16(BX, 0);
+                cpu.regs.set16(CX, 0);
+            }
+            0x3C | 0x3D => {
+                let name = read_asciiz(cpu, ds, dx);
+                let options = if ah == 0x3C {
+                    FileOptions {
+                        read: true,
+                        write: true,
+                        truncate: true,
+                        create: true,
+                        create_new: false,
+                    }
+                } else {
+                    let mode = cpu.regs.get8(AL) & 3;
+                    FileOptions {
+                        read: mode != 1,
+                        write: mode != 0,
+                        ..Default::default()
+                    }
+                };
+                match self.open(machine, name.clone(), options) {
+                    Ok(handle) => {
+                        cpu.regs.set16(AX, handle);
+                        ok(cpu);
+                    }
+                    Err(err) => {
+                        log::debug!("dos: open {name:?} failed: {err:?}");
+                        fail(cpu, err);
+                    }
+                }
+            }
+            0x3E => {
+                let handle = cpu.regs.get16(BX);
+                match self.file(handle) {
+                    Ok(hfile) => {
+                        self.handles[handle as usize] = None;
+                        if handle as usize >= FIRST_FILE_HANDLE {
+                            machine
+                                .state
+                                .kernel32
+                                .objects
+                                .close(HOBJECT::from_raw(hfile.to_raw()));
+                        }
+                        ok(cpu);
+                    }
+                    Err(err) => fail(cpu, err),
+                }
+            }
+            0x3F | 0x40 => {
+                let len = cpu.regs.get16(CX) as usize;
+                let result = self.file(cpu.regs.get16(BX)).and_then(|hfile| {
+                    let buf = cpu.slice_mut(ds, dx, len);
+                    if ah == 0x40 {
+                        kernel32::write_file(machine, hfile, buf, None)
+                    } else if hfile == kernel32::STDIN_HFILE {
+                        machine.host.read_stdin(buf)
+                    } else {
+                        kernel32::read_file(machine, hfile, buf, None)
+                    }
+                });
+                match result {
+                    Ok(n) => {
+                        cpu.regs.set16(AX, n as u16);
+                        ok(cpu);
+                    }
+                    Err(err) => fail(cpu, err),
+                }
+            }
+            0x41 => {
+                let name = read_asciiz(cpu, ds, dx);
+                match kernel32::vfs(machine).remove_file(WindowsPath::new(&name)) {
+                    Ok(()) => ok(cpu),
+                    Err(err) => fail(cpu, err),
+                }
+            }
+            0x42 => {
+                let distance = ((cpu.regs.get16(CX) as u32) << 16 | dx as u32) as i32 as i64;
+                let pos = match cpu.regs.get8(AL) {
+                    0 => Some(std::io::SeekFrom::Start(distance as u32 as u64)),
+                    1 => Some(std::io::SeekFrom::Current(distance)),
+                    2 => Some(std::io::SeekFrom::End(distance)),
+                    _ => None,
+                };
+                let result = match pos {
+                    Some(pos) => self
+                        .file(cpu.regs.get16(BX))
+                        .and_then(|hfile| kernel32::seek(machine, hfile, pos)),
+                    None => Err(ERROR::INVALID_FUNCTION),
+                };
+                match result {
+                    Ok(pos) => {
+                        cpu.regs.set16(AX, pos as u16);
+                        cpu.regs.set16(DX, (pos >> 16) as u16);
+                        ok(cpu);
+                    }
+                    Err(err) => fail(cpu, err),
+                }
+            }
+            0x44 if cpu.regs.get8(AL) == 0 => {
+                // IOCTL get device information: the console handles are character
+                // devices, and anything else a file on drive C.
+                let handle = cpu.regs.get16(BX);
+                match self.file(handle) {
+                    Ok(_) => {
+                        let info = if (handle as usize) < FIRST_FILE_HANDLE {
+                            0x80D3
+                        } else {
+                            0x0002
+                        };
+                        cpu.regs.set16(DX, info);
+                        ok(cpu);
+                    }
+                    Err(err) => fail(cpu, err),
+                }
+            }
+            0x48 => {
+                // Allocate memory past the program's block.
+                let paras = cpu.regs.get16(BX);
+                match self.mem_end.checked_add(paras) {
+                    Some(end) if end <= MEM_TOP => {
+                        cpu.regs.set16(AX, self.mem_end);
+                        self.mem_end = end;
+                        ok(cpu);
+                    }
+                    _ => {
+                        fail(cpu, ERROR::NOT_ENOUGH_MEMORY);
+                        cpu.regs.set16(BX, MEM_TOP - self.mem_end);
+                    }
+                }
+            }
+            0x49 => {
+                // Freed memory isn't reused.
+                ok(cpu);
+            }
+            0x4A => {
+                let seg = cpu.regs.get16(ES);
+                let paras = cpu.regs.get16(BX);
+                if seg != PSP_SEGMENT {
+                    // Blocks from 48h can't grow, but shrinking succeeds.
+                    ok(cpu);
+                } else {
+                    match seg.checked_add(paras) {
+                        Some(end) if end <= MEM_TOP => {
+                            self.mem_end = end;
+                            ok(cpu);
+                        }
+                        _ => {
+                            fail(cpu, ERROR::NOT_ENOUGH_MEMORY);
+                            cpu.regs.set16(BX, MEM_TOP - seg);
+                        }
+                    }
+                }
+            }
+            0x4C => return Some(cpu.regs.get8(AL) as u32),
+            0x51 | 0x62 => cpu.regs.set16(BX, PSP_SEGMENT),
+            _ => {
+                log::warn!("dos: unhandled int 21h function {ah:#x}");
+                fail(cpu, ERROR::INVALID_FUNCTION);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fake_host::FakeHost;
+
+    fn new_machine() -> (Machine, FakeHost) {
+        let host = FakeHost::default();
+        let machine = Machine::new(Box::new(host.clone()), "test.exe".into());
+        (machine, host)
+    }
+
+    /// An MZ stub whose code is `mov ax, 4Cxxh; int 21h`, followed by a PE signature.
+    fn stub(exit_code: u8) -> Vec<u8> {
+        let mut exe = vec![0u8; 0x4C];
+        exe[0..2].copy_from_slice(b"MZ");
+        exe[2..4].copy_from_slice(&0x4Cu16.to_le_bytes()); // e_cblp
+        exe[4..6].copy_from_slice(&1u16.to_le_bytes()); // e_cp
+        exe[8..10].copy_from_slice(&4u16.to_le_bytes()); // e_cparhdr
+        exe[12..14].copy_from_slice(&0xFFFFu16.to_le_bytes()); // e_maxalloc
+        exe[16..18].copy_from_slice(&0x100u16.to_le_bytes()); // e_sp
+        exe[60..64].copy_from_slice(&0x48u32.to_le_bytes()); // e_lfanew
+        exe[0x40..0x45].copy_from_slice(&[0xB8, exit_code, 0x4C, 0xCD, 0x21]);
+        exe[0x48..0x4C].copy_from_slice(b"PE\0\0");
+        exe
+    }
+
+    #[test]
+    fn com_prints_and_exits() {
+        let (mut machine, host) = new_machine();
+        #[rustfmt::skip]
+        let com = [
+            0xB4, 0x09,             // mov ah, 09h
+            0xBA, 0x0C, 0x01,       // mov dx, msg
+            0xCD, 0x21,             // int 21h
+            0xB8, 0x05, 0x4C,       // mov ax, 4C05h
+            0xCD, 0x21,             // int 21h
+            b'h', b'i', b'$',       // msg
+        ];
+        assert!(!run(&mut machine, &com));
+        assert!(matches!(machine.status, Status::Exit(5)));
+        assert_eq!(host.take_output(), b"hi");
+    }
+
+    #[test]
+    fn com_returns_to_psp() {
+        let (mut machine, _host) = new_machine();
+        assert!(!run(&mut machine, &[0xC3])); // ret
+        assert!(matches!(machine.status, Status::Exit(0)));
+    }
+
+    #[test]
+    fn stub_hands_off_to_pe() {
+        let (mut machine, _host) = new_machine();
+        assert!(run(&mut machine, &stub(0)));
+        assert!(matches!(machine.status, Status::Running));
+
+        let (mut machine, _host) = new_machine();
+        assert!(!run(&mut machine, &stub(1)));
+        assert!(matches!(machine.status, Status::Exit(1)));
+    }
+}
@@ -0,0 +1,277 @@
+//! A Host for tests: a clock that only moves when block() waits, an in-memory
+//! filesystem, and console output collected for inspection.
+
+use crate::{
+    host::{self, Message, ReadDirEntry, Stat, StatKind, WindowsPath, WindowsPathBuf, ERROR},
+    snapshot::Detached,
+};
+use std::{
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, VecDeque},
+    io::{Read, Seek, SeekFrom, Write},
+    rc::Rc,
+};
+
+#[derive(Default)]
+struct Shared {
+    ticks: Cell<u32>,
+    messages: RefCell<VecDeque<Message>>,
+    stdin: RefCell<VecDeque<u8>>,
+    /// Everything written to the console.
+    output: RefCell<Vec<u8>>,
+    /// Files by path; the only directory is the root.
+    files: RefCell<BTreeMap<String, Rc<RefCell<Vec<u8>>>>>,
+    /// Button indexes for message boxes to pick, in turn.
+    answers: RefCell<VecDeque<usize>>,
+    /// While set, message boxes stay open: the host reports them as still pending.
+    hold_answers: Cell<bool>,
+}
+
+#[derive(Clone, Default)]
+pub struct FakeHost(Rc<Shared>);
+
+impl FakeHost {
+    /// Take what has been written to the console so far.
+    pub fn take_output(&self) -> Vec<u8> {
+        std::mem::take(&mut *self.0.output.borrow_mut())
+    }
+
+    /// Queue a button index for the next message box to pick.
+    pub fn answer(&self, button: usize) {
+        self.0.answers.borrow_mut().push_back(button);
+    }
+
+    /// Keep message boxes open, as if the user hadn't answered yet, until released.
+    pub fn hold_answers(&self, hold: bool) {
+        self.0.hold_answers.set(hold);
+    }
+}
+
+fn stat(size: u64) -> Stat {
+    Stat {
+        kind: StatKind::File,
+        size,
+        atime: 0,
+        ctime: 0,
+        mtime: 0,
+        readonly: false,
+    }
+}
+
+struct FakeFile {
+    data: Rc<RefCell<Vec<u8>>>,
+    pos: usize,
+}
+
+impl Read for FakeFile {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let data = self.data.borrow();
+        let n = buf.len().min(data.len().saturating_sub(self.pos));
+        buf[..n].copy_from_slice(&data[self.pos..][..n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+impl Write for FakeFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut data = self.data.borrow_mut();
+        let end = self.pos + buf.len();
+        if data.len() < end {
+            data.resize(end, 0);
+        }
+        data[self.pos..end].copy_from_slice(buf);
+        self.pos = end;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for FakeFile {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let len = self.data.borrow().len() as i64;
+        let pos = match pos {
+            SeekFrom::Start(pos) => pos as i64,
+            SeekFrom::End(delta) => len + delta,
+            SeekFrom::Current(delta) => self.pos as i64 + delta,
+        };
+        if pos < 0 {
+            return Err(std::io::ErrorKind::InvalidInput.into());
+        }
+        self.pos = pos as usize;
+        Ok(pos as u64)
+    }
+}
+
+impl host::File for FakeFile {
+    fn stat(&self) -> Result<Stat, ERROR> {
+        Ok(stat(self.data.borrow().len() as u64))
+    }
+
+    fn set_len(&self, len: u64) -> Result<(), ERROR> {
+        self.data.borrow_mut().resize(len as usize, 0);
+        Ok(())
+    }
+
+    fn set_times(&self, _atime: Option<i64>, _mtime: Option<i64>) -> Result<(), ERROR> {
+        Ok(())
+    }
+}
+
+struct FakeReadDir(VecDeque<ReadDirEntry>);
+
+impl host::ReadDir for FakeReadDir {
+    fn next(&mut self) -> Result<Option<ReadDirEntry>, ERROR> {
+        Ok(self.0.pop_front())
+    }
+}
+
+impl host::Host for FakeHost {
+    fn ticks(&self) -> u32 {
+        self.0.ticks.get()
+    }
+
+    fn system_time(&self) -> chrono::DateTime<chrono::Local> {
+        chrono::DateTime::from_timestamp(self.0.ticks.get() as i64 / 1000, 0)
+            .unwrap()
+            .into()
+    }
+
+    fn get_message(&self) -> Option<Message> {
+        self.0.messages.borrow_mut().pop_front()
+    }
+
+    fn block(&self, wait: Option<u32>) -> bool {
+        match wait {
+            Some(deadline) => {
+                self.0.ticks.set(self.0.ticks.get().max(deadline));
+                true
+            }
+            // Nothing will ever arrive.
+            None => false,
+        }
+    }
+
+    fn current_dir(&self) -> Result<WindowsPathBuf, ERROR> {
+        Ok(WindowsPathBuf::from("C:\\"))
+    }
+
+    fn open(
+        &self,
+        path: &WindowsPath,
+        options: host::FileOptions,
+    ) -> Result<Box<dyn host::File>, ERROR> {
+        let path = path.to_string_lossy().into_owned();
+        let mut files = self.0.files.borrow_mut();
+        let data = match files.get(&path) {
+            Some(_) if options.create_new => return Err(ERROR::FILE_EXISTS),
+            Some(data) => data.clone(),
+            None if options.create || options.create_new => {
+                let data = Rc::new(RefCell::new(Vec::new()));
+                files.insert(path, data.clone());
+                data
+            }
+            None => return Err(ERROR::FILE_NOT_FOUND),
+        };
+        if options.truncate {
+            data.borrow_mut().clear();
+        }
+        Ok(Box::new(FakeFile { data, pos: 0 }))
+    }
+
+    fn stat(&self, path: &WindowsPath) -> Result<Stat, ERROR> {
+        let path = path.to_string_lossy();
+        match self.0.files.borrow().get(&*path) {
+            Some(data) => Ok(stat(data.borrow().len() as u64)),
+            None => Err(ERROR::FILE_NOT_FOUND),
+        }
+    }
+
+    fn read_dir(&self, _path: &WindowsPath) -> Result<Box<dyn host::ReadDir>, ERROR> {
+        let files = self.0.files.borrow();
+        let entries = files.iter().map(|(path, data)| ReadDirEntry {
+            name: path.trim_start_matches("C:\\").to_string(),
+            stat: stat(data.borrow().len() as u64),
+        });
+        Ok(Box::new(FakeReadDir(entries.collect())))
+    }
+
+    fn create_dir(&self, _path: &WindowsPath) -> Result<(), ERROR> {
+        Err(ERROR::ACCESS_DENIED)
+    }
+
+    fn remove_file(&self, path: &WindowsPath) -> Result<(), ERROR> {
+        let path = path.to_string_lossy();
+        match self.0.files.borrow_mut().remove(&*path) {
+            Some(_) => Ok(()),
+            None => Err(ERROR::FILE_NOT_FOUND),
+        }
+    }
+
+    fn remove_dir(&self, _path: &WindowsPath) -> Result<(), ERROR> {
+        Err(ERROR::ACCESS_DENIED)
+    }
+
+    fn rename(&self, from: &WindowsPath, to: &WindowsPath) -> Result<(), ERROR> {
+        let mut files = self.0.files.borrow_mut();
+        let data = files
+            .remove(&*from.to_string_lossy())
+            .ok_or(ERROR::FILE_NOT_FOUND)?;
+        files.insert(to.to_string_lossy().into_owned(), data);
+        Ok(())
+    }
+
+    fn set_readonly(&self, _path: &WindowsPath, _readonly: bool) -> Result<(), ERROR> {
+        Ok(())
+    }
+
+    fn log(&self, buf: &[u8]) {
+        self.0.output.borrow_mut().extend_from_slice(buf);
+    }
+
+    fn debug_string(&self, _ticks: u32, _thread_id: u32, _msg: &str) {}
+
+    fn read_stdin(&self, buf: &mut [u8]) -> Result<usize, ERROR> {
+        let mut stdin = self.0.stdin.borrow_mut();
+        let n = buf.len().min(stdin.len());
+        for (dst, src) in buf.iter_mut().zip(stdin.drain(..n)) {
+            *dst = src;
+        }
+        Ok(n)
+    }
+
+    fn message_box(&self, _id: u32, msgbox: &host::MessageBox) -> Result<usize, ERROR> {
+        if self.0.hold_answers.get() {
+            return Err(ERROR::IO_PENDING);
+        }
+        Ok(self
+            .0
+            .answers
+            .borrow_mut()
+            .pop_front()
+            .unwrap_or(msgbox.default))
+    }
+
+    fn clone_host(&self) -> Box<dyn host::Host> {
+        Box::new(self.clone())
+    }
+
+    fn create_window(&mut self, _hwnd: u32) -> Box<dyn host::Window> {
+        Box::new(Detached)
+    }
+
+    fn create_surface(
+        &mut self,
+        _hwnd: u32,
+        _opts: &host::SurfaceOptions,
+    ) -> Box<dyn host::Surface> {
+        Box::new(Detached)
+    }
+
+    fn init_audio(&mut self, _sample_rate: u32) -> Box<dyn host::Audio> {
+        Box::new(Detached)
+    }
+}
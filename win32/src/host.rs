@@ -9,6 +9,10 @@ pub trait Surface {
     /// Used for copying an image to the surface via GDI calls, and for Lock/Unlock pixel writes.
     fn write_pixels(&mut self, pixels: &[[u8; 4]]);
 
+    /// Read back the RGBA pixel data, as for a save state.  Hosts that don't keep
+    /// pixels may return nothing.
+    fn read_pixels(&mut self) -> Vec<[u8; 4]>;
+
     /// Show the this surface as the foreground.  Called by ::Flip().
     fn show(&mut self);
 
@@ -34,10 +38,14 @@ impl Default for SurfaceOptions {
     }
 }
 
-/// Floating window.
+/// Floating window.  Windows are created hidden.
 pub trait Window {
     fn set_title(&mut self, title: &str);
     fn set_size(&mut self, width: u32, height: u32);
+    /// Show or hide the window.  Showing a window also raises it above other windows.
+    fn show(&mut self, visible: bool);
+    /// Set the constant opacity of the window, as used by layered windows.
+    fn set_alpha(&mut self, alpha: u8);
     fn fullscreen(&mut self);
 }
 
@@ -68,19 +76,21 @@ impl FileOptions {
 pub trait File: std::io::Read + std::io::Write + std::io::Seek {
     fn stat(&self) -> Result<Stat, ERROR>;
     fn set_len(&self, len: u64) -> Result<(), ERROR>;
+    /// Set the access and/or modification times, in nanoseconds relative to the Unix epoch.
+    fn set_times(&self, atime: Option<i64>, mtime: Option<i64>) -> Result<(), ERROR>;
 }
 
 pub trait ReadDir {
     fn next(&mut self) -> Result<Option<ReadDirEntry>, ERROR>;
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ReadDirEntry {
     pub name: String,
     pub stat: Stat,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub enum StatKind {
     File,
     Directory,
@@ -88,16 +98,17 @@ pub enum StatKind {
 }
 
 // Times are in nanoseconds relative to the Unix epoch.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Stat {
     pub kind: StatKind,
     pub size: u64,
     pub atime: i64,
     pub ctime: i64,
     pub mtime: i64,
+    pub readonly: bool,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
 pub enum MouseButton {
     None,
     Left,
@@ -105,7 +116,7 @@ pub enum MouseButton {
     Right,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct MouseMessage {
     pub down: bool,
     pub button: MouseButton,
@@ -113,19 +124,32 @@ pub struct MouseMessage {
     pub y: u32,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum MessageDetail {
     Quit,
     Mouse(MouseMessage),
+    /// The window gained (true) or lost (false) input focus.
+    Focus(bool),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Message {
     pub hwnd: u32,
     pub detail: MessageDetail,
     pub time: u32, // in units of Host::time()
 }
 
+/// A message box a guest thread is waiting on the user to answer.
+#[derive(Debug, Clone)]
+pub struct MessageBox {
+    pub caption: String,
+    pub text: String,
+    /// Button labels, in order, like "OK" and "Cancel".
+    pub buttons: Vec<&'static str>,
+    /// Index of the button that Enter picks.
+    pub default: usize,
+}
+
 pub trait Audio {
     fn write(&mut self, buf: &[u8]);
 }
@@ -158,7 +182,25 @@ pub trait Host {
     fn remove_file(&self, path: &WindowsPath) -> Result<(), ERROR>;
     /// Remove a directory at the given (Windows-style) path.
     fn remove_dir(&self, path: &WindowsPath) -> Result<(), ERROR>;
+    /// Move a file or directory, replacing any existing file at the destination.
+    fn rename(&self, from: &WindowsPath, to: &WindowsPath) -> Result<(), ERROR>;
+    /// Mark a file as read-only or writable.
+    fn set_readonly(&self, path: &WindowsPath, readonly: bool) -> Result<(), ERROR>;
     fn log(&self, buf: &[u8]);
+    /// Show a message the program passed to OutputDebugString, as a debugger would.
+    /// `ticks` is the emulated time in milliseconds.
+    fn debug_string(&self, ticks: u32, thread_id: u32, msg: &str);
+    /// Read from the host's standard input, as used by console programs.
+    /// Returns Ok(0) at end of input.
+    fn read_stdin(&self, buf: &mut [u8]) -> Result<usize, ERROR>;
+    /// Show a message box, returning the index of the button the user picked.
+    /// `id` distinguishes message boxes, as a host that can't block while the user answers
+    /// returns Err(IO_PENDING) instead, calls unblock() once answered, and is then asked
+    /// again with the same id.
+    fn message_box(&self, id: u32, msgbox: &MessageBox) -> Result<usize, ERROR>;
+
+    /// Another reference to this host, for the Machine of a child process.
+    fn clone_host(&self) -> Box<dyn Host>;
 
     fn create_window(&mut self, hwnd: u32) -> Box<dyn Window>;
     fn create_surface(&mut self, hwnd: u32, opts: &SurfaceOptions) -> Box<dyn Surface>;
@@ -1,14 +1,26 @@
+pub mod clock;
+pub mod crash;
+#[cfg(test)]
+mod fake_host;
 mod host;
 mod machine;
 pub mod pe;
+pub mod replay;
 mod segments;
 pub mod shims;
+pub mod snapshot;
 pub mod str16;
 pub mod trace;
 pub mod winapi;
 
+#[cfg(feature = "x86-emu")]
+pub mod dos;
 #[cfg(feature = "x86-emu")]
 mod machine_emu;
+#[cfg(all(test, feature = "x86-emu"))]
+mod test_guest;
+#[cfg(feature = "x86-emu")]
+pub mod watchdog;
 
 #[cfg(feature = "x86-64")]
 mod ldt;
@@ -1,4 +1,8 @@
-use crate::{host, winapi};
+use crate::{
+    clock::{Clock, ClockMode},
+    host,
+    winapi::{self, stack_args::ToX86Args},
+};
 use std::{collections::HashMap, path::PathBuf};
 
 #[cfg(feature = "x86-emu")]
@@ -17,6 +21,7 @@ pub struct LoadedAddrs {
 pub struct MachineX<Emu> {
     pub emu: Emu,
     pub host: Box<dyn host::Host>,
+    pub clock: Clock,
     pub state: winapi::State,
     pub labels: HashMap<u32, String>,
     pub exe_path: PathBuf,
@@ -33,6 +38,39 @@ impl<Emu> MachineX<Emu> {
     }
 }
 
+impl Machine {
+    /// The current emulated time in milliseconds; see clock.rs.
+    /// All guest-visible time should come from here rather than the host.
+    pub fn ticks(&self) -> u32 {
+        self.clock.ticks(self.host.ticks(), self.instr_count())
+    }
+
+    pub fn set_clock_mode(&mut self, mode: ClockMode) {
+        let host_ticks = self.host.ticks();
+        let instrs = self.instr_count();
+        self.clock.set_mode(mode, host_ticks, instrs);
+    }
+
+    /// Host::block(), with the deadline given in emulated ticks.
+    pub fn block_host(&mut self, wait: Option<u32>) -> bool {
+        let host_ticks = self.host.ticks();
+        let instrs = self.instr_count();
+        let wait = wait.map(|deadline| self.clock.host_deadline(deadline, host_ticks, instrs));
+        self.host.block(wait)
+    }
+
+    /// Call a guest function pointer, like a window procedure or an enumeration callback,
+    /// with the arguments given as a tuple of ToX86 values, returning its eax.
+    /// The callee may be stdcall or cdecl; a null func is logged and returns 0.
+    pub async fn call_guest(&mut self, func: u32, args: impl ToX86Args) -> u32 {
+        if func == 0 {
+            log::warn!("call to null guest callback, skipping");
+            return 0;
+        }
+        self.call_x86(func, args.to_raw_args()).await
+    }
+}
+
 /// Status of the machine/process.  Separate from CPU state because multiple threads
 /// can be in different states.
 #[derive(Default)]
@@ -42,6 +80,8 @@ pub enum Status {
     Running,
     /// All threads are blocked awaiting results.
     Blocked,
+    /// A thread stopped for the debugger, at a breakpoint or watchpoint.
+    DebugBreak,
     /// CPU error.
     Error {
         message: String,
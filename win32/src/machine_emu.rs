@@ -4,37 +4,138 @@ use crate::{
     host,
     machine::{LoadedAddrs, MachineX, Status},
     pe,
-    shims::{Handler, Shims},
-    winapi,
+    shims::{Handler, Shim, Shims},
+    winapi::{self, kernel32::ChildProcesses},
 };
 use memory::{Extensions, ExtensionsMut, Mem};
-use std::{collections::HashMap, path::Path};
+use std::{
+    any::Any,
+    collections::HashMap,
+    future::Future,
+    panic::AssertUnwindSafe,
+    path::Path,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// How much guest address space to reserve by default: the 2GB of user address space a
+/// Windows process gets, or on the web, where it must share the 4GB of wasm linear memory
+/// with the emulator itself, half that.
+pub const DEFAULT_MEMORY_SIZE: usize = if cfg!(target_arch = "wasm32") {
+    1 << 30
+} else {
+    2 << 30
+};
+
+/// The smallest reservation Machine::new() falls back to when the host refuses the default.
+const MIN_MEMORY_SIZE: usize = 64 << 20;
 
-pub struct BoxMem(Box<[u8]>);
+/// The largest size we can reserve: guest addresses are u32, and Windows never maps
+/// anything in the top 64kb anyway.
+pub const MAX_MEMORY_SIZE: usize = 0xFFFF_0000;
+
+pub struct BoxMem {
+    /// All of guest memory, reserved at startup so that it never moves.
+    buf: memory::Reservation,
+    /// Trap bits of each page of the address space, as set by page protection, with
+    /// the pages past the end of buf inaccessible; see Mem::page_traps.
+    pages: Box<[u8]>,
+}
 
 impl BoxMem {
-    fn new(size: usize) -> Self {
-        let mut buf = Vec::<u8>::with_capacity(size);
-        unsafe {
-            buf.set_len(size);
-        }
-        Self(buf.into_boxed_slice())
+    fn new(size: usize) -> std::io::Result<Self> {
+        let size = size.min(MAX_MEMORY_SIZE) & !((1 << memory::PAGE_SHIFT) - 1);
+        Ok(Self {
+            buf: memory::Reservation::new(size)?,
+            pages: memory::page_table(size as u32),
+        })
     }
 
-    // TODO: we can support growing under wasm by using a custom allocator that
-    // ensures this is the last thing in the heap.
-    // pub fn grow();
+    /// The trap bits of the pages of buf, leaving out the guard pages past it.
+    fn mapped_pages(&self) -> &[u8] {
+        &self.pages[..self.buf.len() >> memory::PAGE_SHIFT]
+    }
 
     pub fn len(&self) -> u32 {
-        self.0.len() as u32
+        self.buf.len() as u32
     }
 
     pub fn mem(&self) -> Mem {
-        Mem::from_slice(&self.0)
+        Mem::from_slice(&self.buf).with_pages(&self.pages)
     }
 
     pub fn as_ptr(&self) -> *const u8 {
-        self.0.as_ptr()
+        self.buf.as_ptr()
+    }
+
+    /// The contents of the given ranges, and the protection of every page, for a save state.
+    pub fn save(&self, ranges: impl Iterator<Item = (u32, u32)>) -> SavedMemory {
+        SavedMemory {
+            regions: ranges
+                .map(|(addr, size)| (addr, self.mem().slice(addr..addr + size).to_vec()))
+                .collect(),
+            pages: self
+                .mapped_pages()
+                .iter()
+                .map(|&page| page & !memory::PAGE_TRAPS_RECORD)
+                .collect(),
+        }
+    }
+
+    /// Replace all of memory with a save(), leaving unsaved memory zeroed.
+    /// The bits that only record writes (PAGE_TRAPS_RECORD) are kept as they were.
+    pub fn restore(&mut self, saved: &SavedMemory) -> anyhow::Result<()> {
+        let count = self.mapped_pages().len();
+        if saved.pages.len() != count {
+            anyhow::bail!("saved memory is {} pages, not {}", saved.pages.len(), count);
+        }
+        self.buf.clear();
+        for (addr, data) in &saved.regions {
+            let start = *addr as usize;
+            let Some(dst) = self.buf.get_mut(start..start + data.len()) else {
+                anyhow::bail!("saved region at {addr:x} out of bounds");
+            };
+            dst.copy_from_slice(data);
+        }
+        for (page, &traps) in self.pages[..count].iter_mut().zip(&saved.pages) {
+            *page = (*page & memory::PAGE_TRAPS_RECORD) | traps;
+        }
+        Ok(())
+    }
+
+    /// Set the protection trap bits of the pages covering [addr, addr+size), which
+    /// must be within memory.
+    /// The bits that only record writes (PAGE_TRAPS_RECORD) are kept as they were.
+    pub fn set_page_traps(&mut self, addr: u32, size: u32, traps: u8) {
+        let first = (addr >> memory::PAGE_SHIFT) as usize;
+        let last = ((addr + size - 1) >> memory::PAGE_SHIFT) as usize;
+        let count = self.mapped_pages().len();
+        for page in &mut self.pages[..count][first..=last] {
+            *page = (*page & memory::PAGE_TRAPS_RECORD) | traps;
+        }
+    }
+}
+
+/// Guest memory as recorded in a save state.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct SavedMemory {
+    /// Contents of the mapped ranges, by address.
+    regions: Vec<(u32, Vec<u8>)>,
+    /// Protection trap bits of every page.
+    pages: Vec<u8>,
+}
+
+impl SavedMemory {
+    /// Overwrite a byte of the saved contents.
+    fn patch(&mut self, addr: u32, byte: u8) {
+        for (start, data) in &mut self.regions {
+            if let Some(b) = addr
+                .checked_sub(*start)
+                .and_then(|ofs| data.get_mut(ofs as usize))
+            {
+                *b = byte;
+            }
+        }
     }
 }
 
@@ -43,19 +144,80 @@ pub struct Emulator {
     pub memory: BoxMem,
     pub shims: Shims,
 
+    /// Count of win32 API calls, used as a measure of guest progress.
+    pub syscall_count: usize,
+
+    /// When set, a builtin function that panics, as on hitting a todo!() or an unwrap()
+    /// in some unimplemented path, fails just that call with ERROR_CALL_NOT_IMPLEMENTED
+    /// rather than stopping the emulator.  Needs panic=unwind, so not on the web.
+    pub lenient: bool,
+
     /// Places where we've patched out the instruction with an int3.
     /// The map values are the bytes from before the breakpoint.
     breakpoints: HashMap<u32, u8>,
 }
 
+impl Emulator {
+    /// Memory for a save state, as it is without our breakpoints' int3s.
+    pub fn save_memory(&self, ranges: impl Iterator<Item = (u32, u32)>) -> SavedMemory {
+        let mut saved = self.memory.save(ranges);
+        for (&addr, &byte) in &self.breakpoints {
+            saved.patch(addr, byte);
+        }
+        saved
+    }
+
+    /// Load memory from a save state, patching our breakpoints back in.
+    pub fn restore_memory(&mut self, saved: &SavedMemory) -> anyhow::Result<()> {
+        self.memory.restore(saved)?;
+        let mem = self.memory.mem();
+        for (&addr, byte) in self.breakpoints.iter_mut() {
+            *byte = mem.get_pod::<u8>(addr);
+            mem.put_pod::<u8>(addr, 0xcc); // int3
+        }
+        Ok(())
+    }
+}
+
 pub type MemImpl = BoxMem;
 pub type Machine = MachineX<Emulator>;
 
 impl MachineX<Emulator> {
+    /// A machine with the default amount of guest address space, or if the host can't
+    /// reserve that much, as much as it can.
     pub fn new(host: Box<dyn host::Host>, cmdline: String) -> Self {
-        let mut memory = BoxMem::new(256 << 20);
+        let mut size = DEFAULT_MEMORY_SIZE;
+        let memory = loop {
+            match BoxMem::new(size) {
+                Ok(memory) => break memory,
+                Err(err) if size > MIN_MEMORY_SIZE => {
+                    log::warn!("reserving {}mb of guest memory: {err}", size >> 20);
+                    size /= 2;
+                }
+                Err(err) => panic!("reserving {}mb of guest memory: {err}", size >> 20),
+            }
+        };
+        Self::with_memory(host, cmdline, memory)
+    }
+
+    /// Like new(), reserving memory_size bytes of guest address space rather than the
+    /// default.  Nothing can be mapped past it.
+    pub fn with_memory_size(
+        host: Box<dyn host::Host>,
+        cmdline: String,
+        memory_size: usize,
+    ) -> anyhow::Result<Self> {
+        let memory = BoxMem::new(memory_size).map_err(|err| {
+            anyhow::anyhow!("reserving {}mb of guest memory: {err}", memory_size >> 20)
+        })?;
+        Ok(Self::with_memory(host, cmdline, memory))
+    }
+
+    fn with_memory(host: Box<dyn host::Host>, cmdline: String, mut memory: BoxMem) -> Self {
         let retrowin32_syscall = b"\x0f\x34\xc3".as_slice(); // sysenter; ret
         let kernel32 = winapi::kernel32::State::new(&mut memory, cmdline, retrowin32_syscall);
+        // The null page, which Mappings reserves, faults on any access.
+        memory.set_page_traps(0, 0x1000, memory::PAGE_TRAPS_NO_ACCESS);
         let shims = Shims::default();
         let state = winapi::State::new(&mut memory, kernel32);
 
@@ -64,9 +226,12 @@ impl MachineX<Emulator> {
                 x86: x86::X86::new(),
                 memory,
                 shims,
+                syscall_count: 0,
+                lenient: false,
                 breakpoints: Default::default(),
             },
             host,
+            clock: Default::default(),
             state,
             labels: HashMap::new(),
             exe_path: Default::default(),
@@ -75,19 +240,60 @@ impl MachineX<Emulator> {
         }
     }
 
+    /// Select the processor the guest sees, both via CPUID and via GetSystemInfo and
+    /// IsProcessorFeaturePresent.  This also sets the RDTSC rate to the chip's.
+    pub fn set_cpu_profile(&mut self, profile: x86::CpuProfile) {
+        use winapi::kernel32::ProcessorFeature;
+        use x86::EDXFeatures;
+        self.emu.x86.set_cpu_profile(profile);
+        self.clock.tsc_mhz = profile.mhz();
+        let edx = profile.features();
+        let features = [
+            (
+                EDXFeatures::TSC,
+                ProcessorFeature::RDTSC_INSTRUCTION_AVAILABLE,
+            ),
+            (EDXFeatures::CX8, ProcessorFeature::COMPARE_EXCHANGE_DOUBLE),
+            (
+                EDXFeatures::MMX,
+                ProcessorFeature::MMX_INSTRUCTIONS_AVAILABLE,
+            ),
+            (
+                EDXFeatures::SSE,
+                ProcessorFeature::XMMI_INSTRUCTIONS_AVAILABLE,
+            ),
+            (
+                EDXFeatures::SSE2,
+                ProcessorFeature::XMMI64_INSTRUCTIONS_AVAILABLE,
+            ),
+        ];
+        let system = &mut self.state.kernel32.system;
+        system.processor = profile.family().min(6) * 100 + 86;
+        system.features = features
+            .iter()
+            .filter(|(flag, _)| edx.contains(*flag))
+            .fold(0, |bits, &(_, feature)| bits | 1 << feature as u32);
+    }
+
+    /// Instructions executed so far, for the instruction-driven clock.
+    pub fn instr_count(&self) -> usize {
+        self.emu.x86.instr_count
+    }
+
     pub fn mem(&self) -> Mem {
         self.emu.memory.mem()
     }
 
-    /// Initialize a memory mapping for the stack and return the initial stack pointer.
-    pub fn create_stack(&mut self, desc: String, stack_size: u32) -> u32 {
+    /// Initialize a memory mapping for the stack and return the initial stack pointer,
+    /// or None if there's no room for it.
+    pub fn create_stack(&mut self, desc: String, stack_size: u32) -> Option<u32> {
         let stack = self
             .state
             .kernel32
             .mappings
-            .alloc(stack_size, desc, &mut self.emu.memory);
+            .alloc(stack_size, desc, &mut self.emu.memory)?;
         let stack_pointer = stack.addr + stack.size - 4;
-        stack_pointer
+        Some(stack_pointer)
     }
 
     pub fn load_exe(
@@ -98,7 +304,16 @@ impl MachineX<Emulator> {
     ) -> anyhow::Result<LoadedAddrs> {
         let exe = pe::load_exe(self, buf, path, relocate)?;
 
-        let stack_pointer = self.create_stack("stack".into(), exe.stack_size);
+        // Pre-label functions found by static analysis, for traces and the debugger.
+        for addr in x86::analysis::find_functions(self.mem(), &exe.code, &[exe.entry_point]) {
+            self.labels
+                .entry(addr)
+                .or_insert_with(|| format!("sub_{addr:x}"));
+        }
+
+        let stack_pointer = self
+            .create_stack("stack".into(), exe.stack_size)
+            .ok_or_else(|| anyhow::anyhow!("no room for a {:#x} byte stack", exe.stack_size))?;
         let regs = &mut self.emu.x86.cpu_mut().regs;
         regs.set32(x86::Register::ESP, stack_pointer);
         regs.set32(x86::Register::EBP, stack_pointer);
@@ -111,11 +326,14 @@ impl MachineX<Emulator> {
         regs.set32(x86::Register::ESI, exe.entry_point);
         regs.set32(x86::Register::EDI, exe.entry_point);
 
-        let retrowin32_main = winapi::kernel32::get_kernel32_builtin(self, "retrowin32_main");
+        // retrowin32_start returns into the entry point, which returns into retrowin32_exit.
+        let retrowin32_start = winapi::kernel32::get_kernel32_builtin(self, "retrowin32_start");
+        let retrowin32_exit = winapi::kernel32::get_kernel32_builtin(self, "retrowin32_exit");
         let cpu = self.emu.x86.cpu_mut();
+        cpu.fpu.control = winapi::kernel32::INITIAL_FPU_CONTROL;
+        x86::ops::push(cpu, self.emu.memory.mem(), retrowin32_exit);
         x86::ops::push(cpu, self.emu.memory.mem(), exe.entry_point);
-        x86::ops::push(cpu, self.emu.memory.mem(), 0); // return address
-        cpu.regs.eip = retrowin32_main;
+        cpu.regs.eip = retrowin32_start;
 
         self.exe_path = path.to_path_buf();
         Ok(LoadedAddrs {
@@ -125,7 +343,7 @@ impl MachineX<Emulator> {
     }
 
     pub fn single_step(&mut self) {
-        self.emu.x86.single_step_next_block(self.emu.memory.mem());
+        self.emu.x86.single_step_next_block();
         self.run();
     }
 
@@ -143,26 +361,108 @@ impl MachineX<Emulator> {
 
     pub fn unblock(&mut self) {
         let cpu = self.emu.x86.cpu_mut();
-        if matches!(
-            cpu.state,
-            x86::CPUState::Blocked(_) | x86::CPUState::DebugBreak
-        ) {
+        if matches!(cpu.state, x86::CPUState::DebugBreak) {
             cpu.state = x86::CPUState::Running;
             self.status = Status::Running;
         }
+        // Any blocked thread may have been waiting for the message or timeout,
+        // so wake them all to recheck their wait conditions.
+        self.wake_blocked();
+    }
+
+    /// Wake the blocked threads to recheck their wait conditions, e.g. after the host
+    /// has an answer for one, leaving any thread stopped in the debugger stopped.
+    pub fn wake_blocked(&mut self) {
+        for cpu in self.emu.x86.cpus.iter_mut() {
+            if matches!(cpu.state, x86::CPUState::Blocked(_)) {
+                cpu.state = x86::CPUState::Running;
+                if matches!(self.status, Status::Blocked) {
+                    self.status = Status::Running;
+                }
+            }
+        }
+    }
+
+    /// If every thread is blocked, the host ticks the soonest of them waits until, or
+    /// Some(None) if none has a deadline.  Child processes of our own count as work.
+    pub fn idle_until(&mut self) -> Option<Option<u32>> {
+        if self.state.kernel32.has_child_processes() {
+            return None;
+        }
+        self.emu.x86.schedule();
+        let x86::CPUState::Blocked(wait) = self.emu.x86.cpu().state else {
+            return None;
+        };
+        let host_ticks = self.host.ticks();
+        let instrs = self.instr_count();
+        Some(wait.map(|deadline| self.clock.host_deadline(deadline, host_ticks, instrs)))
     }
 
     pub fn run(&mut self) -> bool {
+        let children = winapi::kernel32::run_child_processes(self);
         self.emu.x86.schedule();
+        let blocked = matches!(self.emu.x86.cpu().state, x86::CPUState::Blocked(_));
+        if children != ChildProcesses::None && !blocked {
+            self.state.kernel32.wake_child_processes();
+        }
         match &self.emu.x86.cpu().state {
             x86::CPUState::Running => self.execute_block(),
             x86::CPUState::SysCall => self.syscall(),
+            x86::CPUState::ReadTsc => {
+                let tsc = self.clock.tsc(self.host.ticks(), self.instr_count());
+                let cpu = self.emu.x86.cpu_mut();
+                x86::set_edx_eax(cpu, tsc);
+                cpu.state = x86::CPUState::Running;
+            }
             x86::CPUState::Blocked(wait) => {
                 let wait = *wait;
-                if self.host.block(wait) {
-                    self.unblock();
+                match children {
+                    ChildProcesses::None => {
+                        if self.block_host(wait) {
+                            self.unblock();
+                        } else {
+                            self.status = Status::Blocked;
+                        }
+                    }
+                    ChildProcesses::Running => {
+                        // Keep the child processes running rather than waiting on the
+                        // host; their exit wakes us, and otherwise only our deadline can.
+                        if wait.is_some_and(|deadline| self.ticks() >= deadline) {
+                            self.unblock();
+                        }
+                    }
+                    ChildProcesses::Blocked(child_wait) => {
+                        // Everyone is idle, so block the host until the soonest of our
+                        // deadlines, then have everyone recheck.
+                        let host_ticks = self.host.ticks();
+                        let instrs = self.instr_count();
+                        let wait = wait
+                            .map(|deadline| self.clock.host_deadline(deadline, host_ticks, instrs));
+                        let wait = match (wait, child_wait) {
+                            (Some(a), Some(b)) => Some(a.min(b)),
+                            (a, b) => a.or(b),
+                        };
+                        self.state.kernel32.wake_child_processes();
+                        if self.host.block(wait) {
+                            self.unblock();
+                        } else {
+                            self.status = Status::Blocked;
+                        }
+                    }
+                }
+            }
+            x86::CPUState::DebugBreak => {
+                self.status = Status::DebugBreak;
+            }
+            x86::CPUState::Fault(fault) => {
+                let fault = fault.clone();
+                let eip = self.emu.x86.cpu().regs.eip;
+                if fault == x86::Fault::Breakpoint && self.emu.breakpoints.contains_key(&eip) {
+                    // One of add_breakpoint()'s, rather than the guest's own int3.
+                    self.emu.x86.cpu_mut().state = x86::CPUState::DebugBreak;
+                    self.status = Status::DebugBreak;
                 } else {
-                    self.status = Status::Blocked;
+                    winapi::kernel32::dispatch_fault(self, fault);
                 }
             }
             x86::CPUState::Error(message) => {
@@ -181,6 +481,7 @@ impl MachineX<Emulator> {
 
     fn syscall(&mut self) {
         self.emu.x86.cpu_mut().state = x86::CPUState::Running;
+        self.emu.syscall_count += 1;
 
         // See doc/shims.md for the state of the stack when we get here.
 
@@ -195,28 +496,120 @@ impl MachineX<Emulator> {
 
         let shim = match self.emu.shims.get(shim_addr) {
             Ok(shim) => shim,
+            Err(name) if self.emu.lenient => {
+                // The stub that called us continues with a ret that pops any arguments.
+                log::error!("{name}: unimplemented; failing the call");
+                winapi::kernel32::set_last_error(self, winapi::ERROR::CALL_NOT_IMPLEMENTED);
+                self.emu.x86.cpu_mut().regs.set32(x86::Register::EAX, 0);
+                return;
+            }
             Err(name) => unimplemented!("{}", name),
         };
 
         let stack_args = esp + 8;
-        match shim.func {
+        let ret = match shim.func {
             Handler::Sync(func) => {
-                let ret = unsafe { func(self, stack_args) };
-                let regs = &mut self.emu.x86.cpu_mut().regs;
-                regs.set32(x86::Register::EAX, ret);
-
-                // Clear registers to make traces clean.
-                // eax holds return value; other registers are callee-saved per ABI.
-                regs.set32(x86::Register::ECX, 0);
-                regs.set32(x86::Register::EDX, 0);
+                let call = AssertUnwindSafe(|| unsafe { func(self, stack_args) });
+                std::panic::catch_unwind(call).unwrap_or_else(|panic| self.failed_call(shim, panic))
             }
 
             Handler::Async(func) => {
                 let eip = regs.eip; // return address
-                let future = unsafe { func(self, stack_args) };
-                self.emu.x86.cpu_mut().call_async(future, eip);
+                                    // The wrapper reads the arguments before returning the future.
+                let call = AssertUnwindSafe(|| unsafe { func(self, stack_args) });
+                match std::panic::catch_unwind(call) {
+                    Ok(future) => {
+                        let machine: *mut Machine = self;
+                        let future = Box::pin(async move {
+                            match CatchPanic(future).await {
+                                Ok(ret) => ret,
+                                Err(panic) => {
+                                    let machine = unsafe { &mut *machine };
+                                    machine.failed_async_call(shim, esp, panic).await
+                                }
+                            }
+                        });
+                        self.emu.x86.cpu_mut().call_async(future, eip);
+                        return;
+                    }
+                    Err(panic) => self.failed_call(shim, panic),
+                }
             }
+        };
+
+        let regs = &mut self.emu.x86.cpu_mut().regs;
+        regs.set32(x86::Register::EAX, ret);
+
+        // Clear registers to make traces clean.
+        // eax holds return value; other registers are callee-saved per ABI.
+        regs.set32(x86::Register::ECX, 0);
+        regs.set32(x86::Register::EDX, 0);
+    }
+
+    /// Handle a builtin call that panicked before returning or suspending.
+    /// A bad pointer from the guest becomes an access violation in the guest, as if
+    /// the builtin's own code had faulted; see memory::take_out_of_bounds.  Other panics
+    /// fail the call in lenient mode, and otherwise carry on unwinding.
+    fn failed_call(&mut self, shim: &Shim, panic: Box<dyn Any + Send>) -> u32 {
+        if let Some(oob) = memory::take_out_of_bounds() {
+            log::warn!("{}: {oob}; raising an access violation", shim.name);
+            let params = [oob.write as u32, oob.addr];
+            winapi::kernel32::raise_from_builtin(
+                self,
+                winapi::kernel32::STATUS_ACCESS_VIOLATION,
+                shim.stack_consumed,
+                &params,
+            );
+            return 0;
+        }
+        if !self.emu.lenient {
+            std::panic::resume_unwind(panic);
+        }
+        self.unimplemented_call(shim.name, panic)
+    }
+
+    /// Like failed_call, for an async builtin that panicked while being polled, where
+    /// esp is as on entry to it.
+    async fn failed_async_call(
+        &mut self,
+        shim: &Shim,
+        esp: u32,
+        panic: Box<dyn Any + Send>,
+    ) -> u32 {
+        if let Some(oob) = memory::take_out_of_bounds() {
+            log::warn!("{}: {oob}; raising an access violation", shim.name);
+            // Drop anything the builtin left on the stack, so the fault appears to come
+            // from its call.
+            let regs = &mut self.emu.x86.cpu_mut().regs;
+            regs.set32(x86::Register::ESP, esp);
+            let params = [oob.write as u32, oob.addr];
+            winapi::kernel32::raise_from_async_builtin(
+                self,
+                winapi::kernel32::STATUS_ACCESS_VIOLATION,
+                shim.stack_consumed,
+                &params,
+            )
+            .await;
+            return 0;
+        }
+        if !self.emu.lenient {
+            std::panic::resume_unwind(panic);
         }
+        self.unimplemented_call(shim.name, panic)
+    }
+
+    /// Fail a builtin call that panicked, for lenient mode.
+    fn unimplemented_call(&mut self, name: &str, panic: Box<dyn Any + Send>) -> u32 {
+        let message = if let Some(message) = panic.downcast_ref::<&str>() {
+            message
+        } else if let Some(message) = panic.downcast_ref::<String>() {
+            message.as_str()
+        } else {
+            "unknown panic"
+        };
+        log::error!("{name}: {message}; failing the call");
+        winapi::kernel32::set_last_error(self, winapi::ERROR::CALL_NOT_IMPLEMENTED);
+        0
     }
 
     pub async fn call_x86(&mut self, func: u32, args: Vec<u32>) -> u32 {
@@ -227,6 +620,41 @@ impl MachineX<Emulator> {
             .await
     }
 
+    /// The ecx and edx of the current call into a builtin, where fastcall and thiscall
+    /// builtins find their first arguments.
+    pub fn register_args(&self) -> [u32; 2] {
+        let regs = &self.emu.x86.cpu().regs;
+        [
+            regs.get32(x86::Register::ECX),
+            regs.get32(x86::Register::EDX),
+        ]
+    }
+
+    /// Like call_x86, but entering func with the given register values, as expected by
+    /// thiscall methods (ecx) and MSVC exception funclets (ebp).
+    pub async fn call_x86_with_regs(
+        &mut self,
+        func: u32,
+        args: Vec<u32>,
+        regs: &[(x86::Register, u32)],
+    ) -> u32 {
+        let cpu = self.emu.x86.cpu_mut();
+        let saved = regs
+            .iter()
+            .map(|&(reg, _)| (reg, cpu.regs.get32(reg)))
+            .collect::<Vec<_>>();
+        let future = cpu.call_x86(self.emu.memory.mem(), func, args);
+        for &(reg, value) in regs {
+            cpu.regs.set32(reg, value);
+        }
+        let ret = future.await;
+        let cpu = self.emu.x86.cpu_mut();
+        for (reg, value) in saved {
+            cpu.regs.set32(reg, value);
+        }
+        ret
+    }
+
     pub fn dump_stack(&self) {
         let esp = self.emu.x86.cpu().regs.get32(x86::Register::ESP);
         for addr in ((esp - 0x10)..(esp + 0x10)).step_by(4) {
@@ -243,6 +671,24 @@ impl MachineX<Emulator> {
         x86::debug::dump_state(self.emu.x86.cpu(), self.mem(), &self.labels, eip_offset);
         println!("stack:");
         self.dump_stack();
+        let threads = winapi::kernel32::thread_infos(self);
+        if threads.len() > 1 {
+            println!("threads:");
+            for t in threads {
+                let mark = if t.current { '*' } else { ' ' };
+                print!(
+                    "{mark}{} {:08x} prio {} {}",
+                    t.id, t.eip, t.priority, t.state
+                );
+                if !t.waiting_on.is_empty() {
+                    print!(" on {:x?}", t.waiting_on);
+                }
+                if let Some(deadline) = t.deadline {
+                    print!(" until {deadline}");
+                }
+                println!();
+            }
+        }
     }
 
     /// Patch in an int3 over the instruction at that addr, backing up the current one.
@@ -253,7 +699,7 @@ impl MachineX<Emulator> {
                 let mem = self.emu.memory.mem();
                 entry.insert(mem.get_pod::<u8>(addr));
                 mem.put_pod::<u8>(addr, 0xcc); // int3
-                self.emu.x86.icache.clear_cache(addr);
+                self.emu.x86.clear_cache(mem, addr, 1);
                 true
             }
         }
@@ -263,15 +709,54 @@ impl MachineX<Emulator> {
     pub fn clear_breakpoint(&mut self, addr: u32) -> bool {
         match self.emu.breakpoints.remove(&addr) {
             Some(prev) => {
-                self.emu.x86.icache.clear_cache(addr);
-                self.mem().put_pod::<u8>(addr, prev);
+                let mem = self.emu.memory.mem();
+                self.emu.x86.clear_cache(mem, addr, 1);
+                mem.put_pod::<u8>(addr, prev);
                 true
             }
             None => false,
         }
     }
 
+    /// Stop with a debug break after any instruction that writes to [addr, addr+len).
+    pub fn add_watchpoint(&mut self, addr: u32, len: u32) -> bool {
+        self.emu.x86.add_watch(self.emu.memory.mem(), addr, len)
+    }
+
+    /// Undo an add_watchpoint().
+    pub fn clear_watchpoint(&mut self, addr: u32, len: u32) -> bool {
+        self.emu.x86.clear_watch(self.emu.memory.mem(), addr, len)
+    }
+
+    /// Stop with a debug break on a hardware breakpoint, which unlike a watchpoint can catch
+    /// reads and execution, but slows the emulator down while any is set.
+    pub fn add_hw_breakpoint(&mut self, bp: x86::HwBreakpoint) -> bool {
+        self.emu.x86.add_hw_breakpoint(bp)
+    }
+
+    /// Undo an add_hw_breakpoint().
+    pub fn clear_hw_breakpoint(&mut self, bp: &x86::HwBreakpoint) -> bool {
+        self.emu.x86.clear_hw_breakpoint(bp)
+    }
+
     pub fn exit(&mut self, exit_code: u32) {
+        winapi::kernel32::vfs(self).remove_temp_files();
         self.status = Status::Exit(exit_code);
     }
 }
+
+/// Polls a builtin's future, catching any panic within it; see Machine::failed_async_call.
+struct CatchPanic(Pin<Box<dyn Future<Output = u32>>>);
+
+impl Future for CatchPanic {
+    type Output = Result<u32, Box<dyn Any + Send>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let future = self.0.as_mut();
+        match std::panic::catch_unwind(AssertUnwindSafe(|| future.poll(cx))) {
+            Ok(Poll::Ready(ret)) => Poll::Ready(Ok(ret)),
+            Ok(Poll::Pending) => Poll::Pending,
+            Err(panic) => Poll::Ready(Err(panic)),
+        }
+    }
+}
@@ -39,6 +39,7 @@ impl MachineX<Emulator> {
         Machine {
             emu: Emulator { shims, memory },
             host,
+            clock: Default::default(),
             state,
             labels: HashMap::new(),
             exe_path: Default::default(),
@@ -47,6 +48,11 @@ impl MachineX<Emulator> {
         }
     }
 
+    /// Instructions executed so far, for the instruction-driven clock.
+    pub fn instr_count(&self) -> usize {
+        0 // not counted by this backend
+    }
+
     pub fn mem(&self) -> Mem {
         self.emu.memory.mem()
     }
@@ -60,11 +66,12 @@ impl MachineX<Emulator> {
     ) -> anyhow::Result<LoadedAddrs> {
         let exe = pe::load_exe(self, buf, path, relocate)?;
 
-        let stack = self.state.kernel32.mappings.alloc(
-            exe.stack_size,
-            "stack".into(),
-            &mut self.emu.memory,
-        );
+        let stack = self
+            .state
+            .kernel32
+            .mappings
+            .alloc(exe.stack_size, "stack".into(), &mut self.emu.memory)
+            .ok_or_else(|| anyhow::anyhow!("no room for a {:#x} byte stack", exe.stack_size))?;
         let stack_pointer = stack.addr + stack.size - 4;
 
         Ok(LoadedAddrs {
@@ -77,6 +84,11 @@ impl MachineX<Emulator> {
         crate::shims_raw::call_x86(self, func, args).await
     }
 
+    /// See Machine::register_args in machine_emu.
+    pub fn register_args(&self) -> [u32; 2] {
+        crate::shims_raw::register_args()
+    }
+
     /// Transfer control to the executable's entry point.
     /// Needs to switch code segments to enter compatibility mode, stacks, etc.
     #[inline(never)] // aid in debugging
@@ -96,6 +108,7 @@ impl MachineX<Emulator> {
     }
 
     pub fn exit(&mut self, exit_code: u32) {
+        winapi::kernel32::vfs(self).remove_temp_files();
         self.status = Status::Exit(exit_code);
     }
 }
@@ -93,6 +93,7 @@ impl MachineX<Emulator> {
                 futures: Default::default(),
             },
             host,
+            clock: Default::default(),
             state,
             labels: HashMap::new(),
             exe_path: Default::default(),
@@ -101,6 +102,11 @@ impl MachineX<Emulator> {
         }
     }
 
+    /// Instructions executed so far, for the instruction-driven clock.
+    pub fn instr_count(&self) -> usize {
+        0 // not counted by this backend
+    }
+
     pub fn mem(&self) -> Mem {
         self.emu.memory.mem()
     }
@@ -256,12 +262,10 @@ impl MachineX<Emulator> {
     /// Poll the current future, removing it from the queue if it's done.
     fn async_executor(&mut self) {
         let future = self.emu.futures.last_mut().unwrap();
-        // TODO: we don't use the waker at all.  Rust doesn't like us passing a random null pointer
-        // here but it seems like nothing accesses it(?).
-        //let c = unsafe { std::task::Context::from_waker(&Waker::from_raw(std::task::RawWaker::)) };
-        #[allow(deref_nullptr)]
-        let context: &mut std::task::Context = unsafe { &mut *std::ptr::null_mut() };
-        let poll = future.as_mut().poll(context);
+        // We don't use the waker at all: futures are polled again whenever the CPU gets
+        // back to them.
+        let mut context = std::task::Context::from_waker(std::task::Waker::noop());
+        let poll = future.as_mut().poll(&mut context);
         match poll {
             std::task::Poll::Ready(()) => {
                 self.emu.futures.pop();
@@ -363,6 +367,15 @@ impl MachineX<Emulator> {
         }
     }
 
+    /// See Machine::register_args in machine_emu.
+    pub fn register_args(&self) -> [u32; 2] {
+        let unicorn = &self.emu.unicorn;
+        [
+            unicorn.reg_read(RegisterX86::ECX).unwrap() as u32,
+            unicorn.reg_read(RegisterX86::EDX).unwrap() as u32,
+        ]
+    }
+
     pub fn dump_stack(&self) {
         let esp = self.emu.unicorn.reg_read(RegisterX86::ESP).unwrap() as u32;
         for addr in ((esp - 0x8)..(esp + 0x18)).step_by(4) {
@@ -402,6 +415,7 @@ impl MachineX<Emulator> {
     }
 
     pub fn exit(&mut self, exit_code: u32) {
+        winapi::kernel32::vfs(self).remove_temp_files();
         self.status = Status::Exit(exit_code);
     }
 }
@@ -10,6 +10,40 @@ use memory::Extensions;
 // https://docs.microsoft.com/en-us/previous-versions/ms809762(v=msdn.10)
 // https://learn.microsoft.com/en-us/windows/win32/debug/pe-format
 
+/// The header of the DOS (MZ) executable that starts a PE file.
+#[derive(Debug, Default, Clone)]
+#[repr(C)]
+pub struct IMAGE_DOS_HEADER {
+    pub e_magic: WORD,
+    /// Bytes in the last 512-byte page.
+    pub e_cblp: WORD,
+    /// 512-byte pages in the file.
+    pub e_cp: WORD,
+    /// Number of relocations.
+    pub e_crlc: WORD,
+    /// Size of the header in 16-byte paragraphs.
+    pub e_cparhdr: WORD,
+    pub e_minalloc: WORD,
+    pub e_maxalloc: WORD,
+    /// Initial SS, relative to the load segment.
+    pub e_ss: WORD,
+    pub e_sp: WORD,
+    pub e_csum: WORD,
+    pub e_ip: WORD,
+    /// Initial CS, relative to the load segment.
+    pub e_cs: WORD,
+    /// File offset of the relocation table.
+    pub e_lfarlc: WORD,
+    pub e_ovno: WORD,
+    pub e_res: [WORD; 4],
+    pub e_oemid: WORD,
+    pub e_oeminfo: WORD,
+    pub e_res2: [WORD; 10],
+    /// File offset of the PE header.
+    pub e_lfanew: DWORD,
+}
+unsafe impl memory::Pod for IMAGE_DOS_HEADER {}
+
 fn dos_header<'m>(r: &mut Reader<'m>) -> anyhow::Result<u32> {
     r.expect("MZ")?;
     r.skip(0x3a)?;
@@ -82,7 +116,7 @@ pub struct IMAGE_OPTIONAL_HEADER32 {
 unsafe impl memory::Pod for IMAGE_OPTIONAL_HEADER32 {}
 
 #[repr(C)]
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
 pub struct IMAGE_DATA_DIRECTORY {
     pub VirtualAddress: DWORD,
     pub Size: DWORD,
@@ -116,6 +150,22 @@ pub enum IMAGE_DIRECTORY_ENTRY {
     COM_DESCRIPTOR = 14,
 }
 
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IMAGE_SUBSYSTEM {
+    UNKNOWN = 0,
+    NATIVE = 1,
+    WINDOWS_GUI = 2,
+    WINDOWS_CUI = 3,
+}
+
+impl IMAGE_OPTIONAL_HEADER32 {
+    /// Whether the executable expects to run in a console rather than create windows.
+    pub fn is_console(&self) -> bool {
+        self.Subsystem == IMAGE_SUBSYSTEM::WINDOWS_CUI as u16
+    }
+}
+
 fn pe_header<'m>(r: &mut Reader<'m>) -> anyhow::Result<IMAGE_FILE_HEADER> {
     r.expect("PE\0\0")?;
 
@@ -157,7 +207,7 @@ impl IMAGE_SECTION_HEADER {
 }
 
 bitflags! {
-    #[derive(serde::Serialize)]
+    #[derive(serde::Serialize, serde::Deserialize)]
     pub struct ImageSectionFlags: u32 {
         const CODE = 0x20;
         const INITIALIZED_DATA = 0x40;
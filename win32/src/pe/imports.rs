@@ -38,6 +38,18 @@ impl IMAGE_IMPORT_DESCRIPTOR {
         expect_ascii(image.slicez(self.Name))
     }
 
+    /// Whether the IAT was filled in ahead of time by binding against some version of the
+    /// DLL.  We always resolve imports ourselves, so the binding is ignored.
+    pub fn is_bound(&self) -> bool {
+        self.TimeDateStamp != 0
+    }
+
+    /// Whether the imported symbols can't be determined, because there's no ILT and the
+    /// IAT holds bound addresses rather than names.
+    pub fn is_unnamed(&self) -> bool {
+        self.OriginalFirstThunk == 0 && self.is_bound()
+    }
+
     pub fn ilt<'m>(&self, image: &'m [u8]) -> impl Iterator<Item = ILTEntry> + 'm {
         // Officially OriginalFirstThunk (ILT) should have all the data, but in one
         // executable they're all 0, possibly a Borland compiler thing.
@@ -49,9 +61,10 @@ impl IMAGE_IMPORT_DESCRIPTOR {
         };
 
         // Import Lookup Table (section 6.4.2)
+        let unnamed = self.is_unnamed();
         image[addr as usize..]
             .into_iter_pod::<ILTEntry>()
-            .take_while(|entry| entry.0 != 0)
+            .take_while(move |entry| !unnamed && entry.0 != 0)
     }
 
     pub fn iat_offset(&self) -> u32 {
@@ -64,6 +77,68 @@ pub fn read_imports<'m>(buf: &'m [u8]) -> impl Iterator<Item = IMAGE_IMPORT_DESC
         .take_while(|desc| desc.Name != 0)
 }
 
+// Delay-loaded imports are resolved by a helper linked into the executable
+// (__delayLoadHelper2 from delayimp.lib), which calls LoadLibrary/GetProcAddress the
+// first time each import is called.  The IAT initially points at thunks that call the
+// helper, so the loader has nothing to patch; we only read the tables for labels.
+
+/// Delay Load Import Table (section 5.8.1), ImgDelayDescr in delayimp.h.
+#[derive(Clone, Debug, Default)]
+#[repr(C)]
+pub struct IMAGE_DELAYLOAD_DESCRIPTOR {
+    /// If the dlattrRva bit is clear, as produced by VC6, the "RVA" fields below are
+    /// actually virtual addresses.
+    Attributes: DWORD,
+    DllNameRVA: DWORD,
+    ModuleHandleRVA: DWORD,
+    ImportAddressTableRVA: DWORD,
+    ImportNameTableRVA: DWORD,
+    BoundImportAddressTableRVA: DWORD,
+    UnloadInformationTableRVA: DWORD,
+    TimeDateStamp: DWORD,
+}
+unsafe impl memory::Pod for IMAGE_DELAYLOAD_DESCRIPTOR {}
+
+impl IMAGE_DELAYLOAD_DESCRIPTOR {
+    /// Convert one of the address fields to an RVA, given the loaded image base.
+    fn rva(&self, addr: u32, base: u32) -> u32 {
+        const DLATTR_RVA: u32 = 1;
+        if self.Attributes & DLATTR_RVA != 0 {
+            addr
+        } else {
+            addr.wrapping_sub(base)
+        }
+    }
+
+    pub fn image_name<'m>(&self, image: &'m [u8], base: u32) -> &'m str {
+        expect_ascii(image.slicez(self.rva(self.DllNameRVA, base)))
+    }
+
+    /// The Import Name Table, in the same format as an ILT.
+    pub fn int<'m>(&self, image: &'m [u8], base: u32) -> impl Iterator<Item = ILTEntry> + 'm {
+        let addr = self.rva(self.ImportNameTableRVA, base);
+        let desc = self.clone();
+        image[addr as usize..]
+            .into_iter_pod::<u32>()
+            .take_while(|&entry| entry != 0)
+            .map(move |entry| match entry & (1 << 31) {
+                0 => ILTEntry(desc.rva(entry, base)),
+                _ => ILTEntry(entry),
+            })
+    }
+
+    pub fn iat_offset(&self, base: u32) -> u32 {
+        self.rva(self.ImportAddressTableRVA, base)
+    }
+}
+
+pub fn read_delay_imports<'m>(
+    buf: &'m [u8],
+) -> impl Iterator<Item = IMAGE_DELAYLOAD_DESCRIPTOR> + 'm {
+    buf.into_iter_pod::<IMAGE_DELAYLOAD_DESCRIPTOR>()
+        .take_while(|desc| desc.DllNameRVA != 0)
+}
+
 #[repr(transparent)]
 #[derive(Clone)]
 pub struct ILTEntry(u32);
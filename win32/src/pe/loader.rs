@@ -1,7 +1,7 @@
 #![allow(non_snake_case)]
 
 use super::{apply_relocs, IMAGE_DATA_DIRECTORY, IMAGE_SECTION_HEADER};
-use crate::{machine::Machine, pe, winapi};
+use crate::{machine::Machine, pe, str16::expect_ascii, winapi};
 use memory::{Extensions, ExtensionsMut};
 use std::{collections::HashMap, path::Path};
 
@@ -49,6 +49,9 @@ fn load_image(
             size: first_page_size as u32,
             desc: filename.into(),
             flags: pe::ImageSectionFlags::MEM_READ,
+            kind: winapi::kernel32::MappingKind::Image { base: addr },
+            alloc_protect: winapi::kernel32::PAGE::EXECUTE_WRITECOPY,
+            protect: Vec::new(),
         },
         Some(&buf[..first_page_size]),
     );
@@ -95,6 +98,9 @@ fn load_section(
             flags
         ),
         flags,
+        kind: winapi::kernel32::MappingKind::Image { base },
+        alloc_protect: winapi::kernel32::PAGE::EXECUTE_WRITECOPY,
+        protect: Vec::new(),
     };
 
     map_memory(
@@ -120,22 +126,22 @@ fn patch_iat(machine: &mut Machine, base: u32, imports_data: &IMAGE_DATA_DIRECTO
     };
     for dll_imports in pe::read_imports(section) {
         let dll_name = dll_imports.image_name(image).to_ascii_lowercase();
+        if dll_imports.is_unnamed() {
+            log::warn!("{dll_name}: imports are bound without names, keeping bound addresses");
+        }
         let hmodule = winapi::kernel32::load_library(machine, &dll_name);
-        let mut dll = machine.state.kernel32.dlls.get_mut(&hmodule);
         for (i, entry) in dll_imports.ilt(image).enumerate() {
             let sym = entry.as_import_symbol(image);
             let name = format!("{}!{}", dll_name, sym.to_string());
             let iat_addr = base + dll_imports.iat_offset() + (i as u32 * 4);
             machine.labels.insert(iat_addr, format!("{}@IAT", name));
 
-            let resolved_addr = if let Some(dll) = dll.as_mut() {
-                if let Some(sym) = dll.resolve(&sym) {
-                    Some(sym)
-                } else {
-                    log::warn!("missing symbol {name}");
-                    None
-                }
+            let resolved_addr = if !machine.state.kernel32.dlls.contains_key(&hmodule) {
+                None
+            } else if let Some(addr) = winapi::kernel32::resolve_export(machine, hmodule, &sym) {
+                Some(addr)
             } else {
+                log::warn!("missing symbol {name}");
                 None
             };
 
@@ -152,6 +158,29 @@ fn patch_iat(machine: &mut Machine, base: u32, imports_data: &IMAGE_DATA_DIRECTO
     }
 }
 
+/// Label the delay-load IAT entries and the thunks they initially point to.
+fn label_delay_imports(machine: &mut Machine, base: u32, delay_imports: &IMAGE_DATA_DIRECTORY) {
+    let mut labels = Vec::new();
+
+    let image = machine.mem().slice(base..);
+    let Some(section) = delay_imports.as_slice(image) else {
+        return;
+    };
+    for dll_imports in pe::read_delay_imports(section) {
+        let dll_name = dll_imports.image_name(image, base).to_ascii_lowercase();
+        for (i, entry) in dll_imports.int(image, base).enumerate() {
+            let sym = entry.as_import_symbol(image);
+            let name = format!("{}!{}", dll_name, sym.to_string());
+            let iat_offset = dll_imports.iat_offset(base) + (i as u32 * 4);
+            let thunk = image.get_pod::<u32>(iat_offset);
+            labels.push((base + iat_offset, format!("{}@DelayIAT", name)));
+            labels.push((thunk, format!("{}@DelayThunk", name)));
+        }
+    }
+
+    machine.labels.extend(labels);
+}
+
 fn load_pe(
     machine: &mut Machine,
     filename: &str,
@@ -191,6 +220,16 @@ fn load_pe(
     if let Some(imports) = file.get_data_directory(pe::IMAGE_DIRECTORY_ENTRY::IMPORT) {
         patch_iat(machine, base, imports);
     }
+    if let Some(delay_imports) = file.get_data_directory(pe::IMAGE_DIRECTORY_ENTRY::DELAY_IMPORT) {
+        label_delay_imports(machine, base, delay_imports);
+    }
+
+    if let Some(tls) = file.get_data_directory(pe::IMAGE_DIRECTORY_ENTRY::TLS) {
+        let dir = machine
+            .mem()
+            .get_pod::<pe::IMAGE_TLS_DIRECTORY>(base + tls.VirtualAddress);
+        winapi::kernel32::register_tls(machine, base, &dir);
+    }
 
     Ok(base)
 }
@@ -198,6 +237,8 @@ fn load_pe(
 pub struct EXEFields {
     pub entry_point: u32,
     pub stack_size: u32,
+    /// Address ranges of the sections containing code.
+    pub code: Vec<std::ops::Range<u32>>,
 }
 
 pub fn load_exe(
@@ -212,6 +253,13 @@ pub fn load_exe(
     let base = load_pe(machine, &filename, buf, &file, relocate)?;
     machine.state.kernel32.image_base = base;
 
+    // Like Windows, only console-subsystem programs start out with a console.
+    machine.state.kernel32.console = if file.opt_header.is_console() {
+        winapi::kernel32::Console::default()
+    } else {
+        winapi::kernel32::Console::detached()
+    };
+
     if let Some(res_data) = file
         .data_directory
         .get(pe::IMAGE_DIRECTORY_ENTRY::RESOURCE as usize)
@@ -221,14 +269,29 @@ pub fn load_exe(
 
     let entry_point = base + file.opt_header.AddressOfEntryPoint;
 
+    let code = file
+        .sections
+        .iter()
+        .filter(|sec| {
+            sec.characteristics().map_or(false, |flags| {
+                flags.intersects(pe::ImageSectionFlags::CODE | pe::ImageSectionFlags::MEM_EXECUTE)
+            })
+        })
+        .map(|sec| {
+            let start = base + sec.VirtualAddress;
+            start..start + sec.VirtualSize
+        })
+        .collect();
+
     let addrs = EXEFields {
         entry_point,
         stack_size: file.opt_header.SizeOfStackReserve,
+        code,
     };
     Ok(addrs)
 }
 
-#[derive(Debug)]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct DLL {
     /// Image base address.
     pub base: u32,
@@ -240,6 +303,9 @@ pub struct DLL {
     pub ordinal_base: u32,
     pub fns: Vec<u32>,
 
+    /// Forwarded exports: address in fns => target, e.g. "NTDLL.RtlUnwind".
+    pub forwards: HashMap<u32, String>,
+
     pub resources: Option<IMAGE_DATA_DIRECTORY>,
 
     /// Address of DllMain() entry point.
@@ -260,13 +326,20 @@ pub fn load_dll(machine: &mut Machine, filename: &str, buf: &[u8]) -> anyhow::Re
     let mut ordinal_base = 1;
     let mut fns = Vec::new();
     let mut names = HashMap::new();
+    let mut forwards = HashMap::new();
     if let Some(dir) = file.get_data_directory(pe::IMAGE_DIRECTORY_ENTRY::EXPORT) {
         let section = dir
             .as_slice(image)
             .ok_or_else(|| anyhow::anyhow!("invalid exports"))?;
+        let exports = dir.VirtualAddress..dir.VirtualAddress + dir.Size;
         let dir = pe::read_exports(section);
         ordinal_base = dir.Base;
         for addr in dir.fns(image) {
+            // An address within the export section is a forwarder string.
+            if exports.contains(&addr) {
+                let target = expect_ascii(image.slicez(addr));
+                forwards.insert(base + addr, target.to_string());
+            }
             fns.push(base + addr);
         }
         for (name, i) in dir.names(image) {
@@ -284,6 +357,7 @@ pub fn load_dll(machine: &mut Machine, filename: &str, buf: &[u8]) -> anyhow::Re
         names,
         ordinal_base,
         fns,
+        forwards,
         resources,
         entry_point,
     })
@@ -52,6 +52,7 @@ unsafe impl memory::Pod for IMAGE_RESOURCE_DIRECTORY_ENTRY {}
 pub enum RT {
     BITMAP = 2,
     STRING = 6,
+    MESSAGETABLE = 11,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -132,3 +133,24 @@ pub fn find_resource(
     };
     Some(data.OffsetToData..(data.OffsetToData + data.Size))
 }
+
+/// String resources (RT_STRING) are stored in bundles of 16 consecutive strings,
+/// where resource id N holds string ids (N-1)*16 through N*16-1.
+/// Each string is a u16 count of UTF-16 code units followed by those code units.
+/// Returns the byte range within the bundle of the string at the given index.
+pub fn find_string_in_bundle(bundle: &[u8], index: u32) -> Option<Range<u32>> {
+    let mut ofs = 0;
+    for i in 0..=index {
+        let len = u16::from_le_bytes(bundle.get(ofs..ofs + 2)?.try_into().unwrap()) as usize;
+        let start = ofs + 2;
+        let end = start + len * 2;
+        if end > bundle.len() {
+            return None;
+        }
+        if i == index {
+            return Some(start as u32..end as u32);
+        }
+        ofs = end;
+    }
+    unreachable!()
+}
@@ -0,0 +1,19 @@
+#![allow(non_snake_case)]
+
+/// The TLS directory, describing a module's implicit (__declspec(thread)) TLS.
+/// Unlike most PE structures, the addresses here are virtual addresses, not RVAs.
+#[derive(Debug, Clone)]
+#[repr(C)]
+pub struct IMAGE_TLS_DIRECTORY {
+    /// Template for the initial contents of each thread's TLS block.
+    pub StartAddressOfRawData: u32,
+    pub EndAddressOfRawData: u32,
+    /// Where the loader stores the module's TLS index.
+    pub AddressOfIndex: u32,
+    /// Null-terminated array of callback addresses.
+    pub AddressOfCallBacks: u32,
+    /// Count of zero bytes following the template in each block.
+    pub SizeOfZeroFill: u32,
+    pub Characteristics: u32,
+}
+unsafe impl memory::Pod for IMAGE_TLS_DIRECTORY {}
@@ -0,0 +1,710 @@
+//! Record and replay: logging every input the host gives the emulator, so that a run can
+//! be repeated exactly, e.g. for a TAS-style run or to share a reproduction of a bug.
+//!
+//! The guest only learns about the outside world through the Host trait, and otherwise
+//! the emulator is deterministic: the clock only reads the host's time through it (see
+//! clock.rs), and threads switch after fixed numbers of instructions.  Recorder wraps a
+//! Host, passing each call through and logging its result: the time, input events,
+//! console input, message box answers, and the results of all filesystem calls including
+//! the bytes read.  Replayer answers those calls from the log in turn instead, and so
+//! touches no files; output (windows, surfaces, audio, logging) still goes to a real
+//! host, to watch.
+//!
+//! A replay must run the same exe with the same flags as the recording, starting from
+//! the same save state if any.  If the emulator asks the host something the log doesn't
+//! have next, or the log runs out, the replay has diverged: from then on every call
+//! fails, and ReplayStatus reports the event where it went wrong so the caller can stop
+//! the machine with an error.  Guest-visible state is kept in ordered maps (BTreeMap,
+//! not HashMap) so that nothing the guest sees depends on per-run hashing.
+
+use crate::host::{self, Message, ReadDirEntry, Stat, WindowsPath, WindowsPathBuf, ERROR};
+use anyhow::bail;
+use serde::{Deserialize, Serialize};
+use std::{
+    cell::{Cell, RefCell},
+    io::{Read, Write},
+    rc::Rc,
+};
+
+const MAGIC: &[u8; 8] = b"RW32RPLY";
+/// Bump when the format changes in a way that would otherwise misparse.
+const VERSION: u32 = 1;
+
+/// One result from the host, in the order the emulator asked for them.
+#[derive(Debug, Serialize, Deserialize)]
+enum Event {
+    Ticks(u32),
+    SystemTime(chrono::DateTime<chrono::Local>),
+    Message(Option<Message>),
+    Block(bool),
+    /// The current directory's path, as bytes.
+    CurrentDir(Result<Vec<u8>, ERROR>),
+    /// The result of any call that only succeeds or fails, like remove_file or set_len.
+    Done(Result<(), ERROR>),
+    Open(Result<(), ERROR>),
+    Stat(Result<Stat, ERROR>),
+    ReadDir(Result<(), ERROR>),
+    ReadDirNext(Result<Option<ReadDirEntry>, ERROR>),
+    ReadStdin(Result<Vec<u8>, ERROR>),
+    Read(Result<Vec<u8>, ERROR>),
+    Write(Result<usize, ERROR>),
+    Seek(Result<u64, ERROR>),
+    MessageBox(Result<usize, ERROR>),
+}
+
+/// An io::Error that converts back into the ERROR it was recorded as.
+fn io_error(err: ERROR) -> std::io::Error {
+    use std::io::ErrorKind;
+    let kind = match err {
+        ERROR::FILE_NOT_FOUND => ErrorKind::NotFound,
+        ERROR::ACCESS_DENIED => ErrorKind::PermissionDenied,
+        ERROR::INVALID_DATA => ErrorKind::InvalidData,
+        ERROR::FILE_EXISTS => ErrorKind::AlreadyExists,
+        ERROR::INVALID_ACCESS => ErrorKind::InvalidInput,
+        ERROR::DIRECTORY => ErrorKind::IsADirectory,
+        _ => ErrorKind::Other,
+    };
+    std::io::Error::new(kind, format!("{err:?}"))
+}
+
+/// The log being written, shared by a Recorder and the files it opened.
+struct RecordLog {
+    out: RefCell<Box<dyn Write>>,
+}
+
+impl RecordLog {
+    fn record(&self, event: Event) {
+        bincode::serialize_into(&mut *self.out.borrow_mut(), &event).expect("writing replay log");
+    }
+}
+
+impl Drop for RecordLog {
+    fn drop(&mut self) {
+        if let Err(err) = self.out.borrow_mut().flush() {
+            log::error!("writing replay log: {err}");
+        }
+    }
+}
+
+/// A Host that logs what the wrapped host returns, for Replayer to play back.
+pub struct Recorder {
+    host: Box<dyn host::Host>,
+    log: Rc<RecordLog>,
+}
+
+impl Recorder {
+    pub fn new(host: Box<dyn host::Host>, mut out: Box<dyn Write>) -> anyhow::Result<Self> {
+        out.write_all(MAGIC)?;
+        out.write_all(&VERSION.to_le_bytes())?;
+        Ok(Recorder {
+            host,
+            log: Rc::new(RecordLog {
+                out: RefCell::new(out),
+            }),
+        })
+    }
+
+    fn record<T: Clone>(&self, result: T, event: impl FnOnce(T) -> Event) -> T {
+        self.log.record(event(result.clone()));
+        result
+    }
+}
+
+struct RecordedFile {
+    file: Box<dyn host::File>,
+    log: Rc<RecordLog>,
+}
+
+impl Read for RecordedFile {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self.file.read(buf) {
+            Ok(n) => {
+                self.log.record(Event::Read(Ok(buf[..n].to_vec())));
+                Ok(n)
+            }
+            Err(err) => {
+                let err = ERROR::from(err);
+                self.log.record(Event::Read(Err(err)));
+                Err(io_error(err))
+            }
+        }
+    }
+}
+
+impl Write for RecordedFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let result = self.file.write(buf).map_err(ERROR::from);
+        self.log.record(Event::Write(result));
+        result.map_err(io_error)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+impl std::io::Seek for RecordedFile {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        let result = self.file.seek(pos).map_err(ERROR::from);
+        self.log.record(Event::Seek(result));
+        result.map_err(io_error)
+    }
+}
+
+impl host::File for RecordedFile {
+    fn stat(&self) -> Result<Stat, ERROR> {
+        let result = self.file.stat();
+        self.log.record(Event::Stat(result.clone()));
+        result
+    }
+
+    fn set_len(&self, len: u64) -> Result<(), ERROR> {
+        let result = self.file.set_len(len);
+        self.log.record(Event::Done(result));
+        result
+    }
+
+    fn set_times(&self, atime: Option<i64>, mtime: Option<i64>) -> Result<(), ERROR> {
+        let result = self.file.set_times(atime, mtime);
+        self.log.record(Event::Done(result));
+        result
+    }
+}
+
+struct RecordedReadDir {
+    read_dir: Box<dyn host::ReadDir>,
+    log: Rc<RecordLog>,
+}
+
+impl host::ReadDir for RecordedReadDir {
+    fn next(&mut self) -> Result<Option<ReadDirEntry>, ERROR> {
+        let result = self.read_dir.next();
+        self.log.record(Event::ReadDirNext(result.clone()));
+        result
+    }
+}
+
+impl host::Host for Recorder {
+    fn ticks(&self) -> u32 {
+        self.record(self.host.ticks(), Event::Ticks)
+    }
+
+    fn system_time(&self) -> chrono::DateTime<chrono::Local> {
+        self.record(self.host.system_time(), Event::SystemTime)
+    }
+
+    fn get_message(&self) -> Option<Message> {
+        let msg = self.host.get_message();
+        self.log.record(Event::Message(msg.clone()));
+        msg
+    }
+
+    fn block(&self, wait: Option<u32>) -> bool {
+        self.record(self.host.block(wait), Event::Block)
+    }
+
+    fn current_dir(&self) -> Result<WindowsPathBuf, ERROR> {
+        let result = self.host.current_dir();
+        let bytes = result.as_ref().map(|dir| dir.as_bytes().to_vec());
+        self.log
+            .record(Event::CurrentDir(bytes.map_err(|err| *err)));
+        result
+    }
+
+    fn open(
+        &self,
+        path: &WindowsPath,
+        options: host::FileOptions,
+    ) -> Result<Box<dyn host::File>, ERROR> {
+        let result = self.host.open(path, options);
+        self.log
+            .record(Event::Open(result.as_ref().map(|_| ()).map_err(|err| *err)));
+        let file = result?;
+        Ok(Box::new(RecordedFile {
+            file,
+            log: self.log.clone(),
+        }))
+    }
+
+    fn stat(&self, path: &WindowsPath) -> Result<Stat, ERROR> {
+        self.record(self.host.stat(path), Event::Stat)
+    }
+
+    fn read_dir(&self, path: &WindowsPath) -> Result<Box<dyn host::ReadDir>, ERROR> {
+        let result = self.host.read_dir(path);
+        self.log.record(Event::ReadDir(
+            result.as_ref().map(|_| ()).map_err(|err| *err),
+        ));
+        let read_dir = result?;
+        Ok(Box::new(RecordedReadDir {
+            read_dir,
+            log: self.log.clone(),
+        }))
+    }
+
+    fn create_dir(&self, path: &WindowsPath) -> Result<(), ERROR> {
+        self.record(self.host.create_dir(path), Event::Done)
+    }
+
+    fn remove_file(&self, path: &WindowsPath) -> Result<(), ERROR> {
+        self.record(self.host.remove_file(path), Event::Done)
+    }
+
+    fn remove_dir(&self, path: &WindowsPath) -> Result<(), ERROR> {
+        self.record(self.host.remove_dir(path), Event::Done)
+    }
+
+    fn rename(&self, from: &WindowsPath, to: &WindowsPath) -> Result<(), ERROR> {
+        self.record(self.host.rename(from, to), Event::Done)
+    }
+
+    fn set_readonly(&self, path: &WindowsPath, readonly: bool) -> Result<(), ERROR> {
+        self.record(self.host.set_readonly(path, readonly), Event::Done)
+    }
+
+    fn log(&self, buf: &[u8]) {
+        self.host.log(buf)
+    }
+
+    fn debug_string(&self, ticks: u32, thread_id: u32, msg: &str) {
+        self.host.debug_string(ticks, thread_id, msg)
+    }
+
+    fn read_stdin(&self, buf: &mut [u8]) -> Result<usize, ERROR> {
+        let result = self.host.read_stdin(buf);
+        let bytes = result.map(|n| buf[..n].to_vec());
+        self.log.record(Event::ReadStdin(bytes));
+        result
+    }
+
+    fn message_box(&self, id: u32, msgbox: &host::MessageBox) -> Result<usize, ERROR> {
+        self.record(self.host.message_box(id, msgbox), Event::MessageBox)
+    }
+
+    fn clone_host(&self) -> Box<dyn host::Host> {
+        Box::new(Recorder {
+            host: self.host.clone_host(),
+            log: self.log.clone(),
+        })
+    }
+
+    fn create_window(&mut self, hwnd: u32) -> Box<dyn host::Window> {
+        self.host.create_window(hwnd)
+    }
+
+    fn create_surface(&mut self, hwnd: u32, opts: &host::SurfaceOptions) -> Box<dyn host::Surface> {
+        self.host.create_surface(hwnd, opts)
+    }
+
+    fn init_audio(&mut self, sample_rate: u32) -> Box<dyn host::Audio> {
+        self.host.init_audio(sample_rate)
+    }
+}
+
+/// What host calls fail with once a replay has diverged.
+const DIVERGED: ERROR = ERROR::GEN_FAILURE;
+
+/// The log being played back, shared by a Replayer and the files it opened.
+struct ReplayLog {
+    input: RefCell<Box<dyn Read>>,
+    /// Number of events read so far, to say where a replay diverged.
+    count: Cell<usize>,
+    /// Where the replay diverged, after which no more events are read.
+    error: RefCell<Option<String>>,
+}
+
+impl ReplayLog {
+    fn next(&self, call: &str) -> Option<Event> {
+        if self.error.borrow().is_some() {
+            return None;
+        }
+        let n = self.count.get();
+        self.count.set(n + 1);
+        match bincode::deserialize_from(&mut *self.input.borrow_mut()) {
+            Ok(event) => Some(event),
+            Err(err) => {
+                self.fail(format!(
+                    "replay: log ends at event {n}, at a {call} call: {err}"
+                ));
+                None
+            }
+        }
+    }
+
+    fn diverged(&self, call: &str, event: Event) {
+        let mut event = format!("{event:?}");
+        event.truncate(80);
+        self.fail(format!(
+            "replay: diverged at event {}: a {call} call, but the log has {event}",
+            self.count.get() - 1
+        ));
+    }
+
+    fn fail(&self, message: String) {
+        log::error!("{message}");
+        self.error.borrow_mut().get_or_insert(message);
+    }
+}
+
+/// Take the next event from the log, which must match the pattern, or else give the
+/// fallback value.
+macro_rules! replay {
+    ($log:expr, $call:literal, $pat:pat => $val:expr, else $fallback:expr) => {
+        match $log.next($call) {
+            Some($pat) => $val,
+            Some(event) => {
+                $log.diverged($call, event);
+                $fallback
+            }
+            None => $fallback,
+        }
+    };
+}
+
+/// Whether a replay has diverged from its log, checked by the caller between runs.
+#[derive(Clone)]
+pub struct ReplayStatus(Rc<ReplayLog>);
+
+impl ReplayStatus {
+    /// Describes where the replay diverged, if it has.
+    pub fn error(&self) -> Option<String> {
+        self.0.error.borrow().clone()
+    }
+}
+
+/// A Host that answers from a log written by Recorder, passing only output through to
+/// the wrapped host.
+pub struct Replayer {
+    host: Box<dyn host::Host>,
+    log: Rc<ReplayLog>,
+}
+
+impl Replayer {
+    pub fn new(host: Box<dyn host::Host>, mut input: Box<dyn Read>) -> anyhow::Result<Self> {
+        let mut header = [0u8; 12];
+        input.read_exact(&mut header)?;
+        if &header[..8] != MAGIC {
+            bail!("not a replay log");
+        }
+        if header[8..] != VERSION.to_le_bytes() {
+            bail!("replay log from an incompatible version");
+        }
+        Ok(Replayer {
+            host,
+            log: Rc::new(ReplayLog {
+                input: RefCell::new(input),
+                count: Cell::new(0),
+                error: RefCell::new(None),
+            }),
+        })
+    }
+
+    pub fn status(&self) -> ReplayStatus {
+        ReplayStatus(self.log.clone())
+    }
+}
+
+struct ReplayedFile {
+    log: Rc<ReplayLog>,
+}
+
+impl Read for ReplayedFile {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let data = replay!(self.log, "read", Event::Read(result) => result, else Err(DIVERGED))
+            .map_err(io_error)?;
+        if data.len() > buf.len() {
+            self.log.diverged("read", Event::Read(Ok(data)));
+            return Err(io_error(DIVERGED));
+        }
+        buf[..data.len()].copy_from_slice(&data);
+        Ok(data.len())
+    }
+}
+
+impl Write for ReplayedFile {
+    fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+        replay!(self.log, "write", Event::Write(result) => result, else Err(DIVERGED))
+            .map_err(io_error)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl std::io::Seek for ReplayedFile {
+    fn seek(&mut self, _pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        replay!(self.log, "seek", Event::Seek(result) => result, else Err(DIVERGED))
+            .map_err(io_error)
+    }
+}
+
+impl host::File for ReplayedFile {
+    fn stat(&self) -> Result<Stat, ERROR> {
+        replay!(self.log, "stat", Event::Stat(result) => result, else Err(DIVERGED))
+    }
+
+    fn set_len(&self, _len: u64) -> Result<(), ERROR> {
+        replay!(self.log, "set_len", Event::Done(result) => result, else Err(DIVERGED))
+    }
+
+    fn set_times(&self, _atime: Option<i64>, _mtime: Option<i64>) -> Result<(), ERROR> {
+        replay!(self.log, "set_times", Event::Done(result) => result, else Err(DIVERGED))
+    }
+}
+
+struct ReplayedReadDir {
+    log: Rc<ReplayLog>,
+}
+
+impl host::ReadDir for ReplayedReadDir {
+    fn next(&mut self) -> Result<Option<ReadDirEntry>, ERROR> {
+        replay!(self.log, "read_dir next", Event::ReadDirNext(result) => result, else Err(DIVERGED))
+    }
+}
+
+impl host::Host for Replayer {
+    fn ticks(&self) -> u32 {
+        replay!(self.log, "ticks", Event::Ticks(ticks) => ticks, else self.host.ticks())
+    }
+
+    fn system_time(&self) -> chrono::DateTime<chrono::Local> {
+        replay!(self.log, "system_time", Event::SystemTime(time) => time, else self.host.system_time())
+    }
+
+    fn get_message(&self) -> Option<Message> {
+        replay!(self.log, "get_message", Event::Message(msg) => msg, else None)
+    }
+
+    fn block(&self, _wait: Option<u32>) -> bool {
+        // The wait already happened while recording, so don't wait again.
+        replay!(self.log, "block", Event::Block(blocked) => blocked, else false)
+    }
+
+    fn current_dir(&self) -> Result<WindowsPathBuf, ERROR> {
+        replay!(self.log, "current_dir", Event::CurrentDir(result) => result, else Err(DIVERGED))
+            .map(WindowsPathBuf::from)
+    }
+
+    fn open(
+        &self,
+        _path: &WindowsPath,
+        _options: host::FileOptions,
+    ) -> Result<Box<dyn host::File>, ERROR> {
+        replay!(self.log, "open", Event::Open(result) => result, else Err(DIVERGED))?;
+        Ok(Box::new(ReplayedFile {
+            log: self.log.clone(),
+        }))
+    }
+
+    fn stat(&self, _path: &WindowsPath) -> Result<Stat, ERROR> {
+        replay!(self.log, "stat", Event::Stat(result) => result, else Err(DIVERGED))
+    }
+
+    fn read_dir(&self, _path: &WindowsPath) -> Result<Box<dyn host::ReadDir>, ERROR> {
+        replay!(self.log, "read_dir", Event::ReadDir(result) => result, else Err(DIVERGED))?;
+        Ok(Box::new(ReplayedReadDir {
+            log: self.log.clone(),
+        }))
+    }
+
+    fn create_dir(&self, _path: &WindowsPath) -> Result<(), ERROR> {
+        replay!(self.log, "create_dir", Event::Done(result) => result, else Err(DIVERGED))
+    }
+
+    fn remove_file(&self, _path: &WindowsPath) -> Result<(), ERROR> {
+        replay!(self.log, "remove_file", Event::Done(result) => result, else Err(DIVERGED))
+    }
+
+    fn remove_dir(&self, _path: &WindowsPath) -> Result<(), ERROR> {
+        replay!(self.log, "remove_dir", Event::Done(result) => result, else Err(DIVERGED))
+    }
+
+    fn rename(&self, _from: &WindowsPath, _to: &WindowsPath) -> Result<(), ERROR> {
+        replay!(self.log, "rename", Event::Done(result) => result, else Err(DIVERGED))
+    }
+
+    fn set_readonly(&self, _path: &WindowsPath, _readonly: bool) -> Result<(), ERROR> {
+        replay!(self.log, "set_readonly", Event::Done(result) => result, else Err(DIVERGED))
+    }
+
+    fn log(&self, buf: &[u8]) {
+        self.host.log(buf)
+    }
+
+    fn debug_string(&self, ticks: u32, thread_id: u32, msg: &str) {
+        self.host.debug_string(ticks, thread_id, msg)
+    }
+
+    fn read_stdin(&self, buf: &mut [u8]) -> Result<usize, ERROR> {
+        let data = replay!(self.log, "read_stdin", Event::ReadStdin(result) => result, else Err(DIVERGED))?;
+        if data.len() > buf.len() {
+            self.log.diverged("read_stdin", Event::ReadStdin(Ok(data)));
+            return Err(DIVERGED);
+        }
+        buf[..data.len()].copy_from_slice(&data);
+        Ok(data.len())
+    }
+
+    fn message_box(&self, _id: u32, _msgbox: &host::MessageBox) -> Result<usize, ERROR> {
+        replay!(self.log, "message_box", Event::MessageBox(result) => result, else Err(DIVERGED))
+    }
+
+    fn clone_host(&self) -> Box<dyn host::Host> {
+        Box::new(Replayer {
+            host: self.host.clone_host(),
+            log: self.log.clone(),
+        })
+    }
+
+    fn create_window(&mut self, hwnd: u32) -> Box<dyn host::Window> {
+        self.host.create_window(hwnd)
+    }
+
+    fn create_surface(&mut self, hwnd: u32, opts: &host::SurfaceOptions) -> Box<dyn host::Surface> {
+        self.host.create_surface(hwnd, opts)
+    }
+
+    fn init_audio(&mut self, sample_rate: u32) -> Box<dyn host::Audio> {
+        self.host.init_audio(sample_rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fake_host::FakeHost, host::Host};
+    use std::io::{Seek, SeekFrom};
+
+    /// A log that stays readable after the Recorder writing it is dropped.
+    #[derive(Clone, Default)]
+    struct SharedLog(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedLog {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Exercise each kind of host call, returning a transcript of what came back.
+    fn session(host: &dyn Host) -> Vec<String> {
+        let mut out = Vec::new();
+        let path = WindowsPath::new("C:\\a.txt");
+        let options = host::FileOptions {
+            read: true,
+            write: true,
+            create: true,
+            ..Default::default()
+        };
+        out.push(format!("{}", host.ticks()));
+        out.push(format!("{}", host.block(Some(500))));
+        out.push(format!("{}", host.ticks()));
+        out.push(format!("{}", host.system_time()));
+        out.push(format!("{:?}", host.get_message()));
+        out.push(format!(
+            "{:?}",
+            host.current_dir()
+                .map(|dir| dir.to_string_lossy().into_owned())
+        ));
+        out.push(format!("{:?}", host.stat(path).err()));
+        match host.open(path, options) {
+            Ok(mut file) => {
+                out.push(format!("{:?}", file.write(b"hello").ok()));
+                out.push(format!("{:?}", file.seek(SeekFrom::Start(1)).ok()));
+                let mut buf = [0u8; 8];
+                let n = file.read(&mut buf).unwrap_or(0);
+                out.push(String::from_utf8_lossy(&buf[..n]).into_owned());
+                out.push(format!("{:?}", file.stat().map(|stat| stat.size)));
+            }
+            Err(err) => out.push(format!("{err:?}")),
+        }
+        match host.read_dir(WindowsPath::new("C:\\")) {
+            Ok(mut dir) => {
+                while let Ok(Some(entry)) = dir.next() {
+                    out.push(entry.name);
+                }
+            }
+            Err(err) => out.push(format!("{err:?}")),
+        }
+        out.push(format!("{:?}", host.create_dir(WindowsPath::new("C:\\d"))));
+        out.push(format!("{:?}", host.remove_file(path)));
+        let mut buf = [0u8; 4];
+        out.push(format!("{:?}", host.read_stdin(&mut buf)));
+        let msgbox = host::MessageBox {
+            caption: "caption".into(),
+            text: "text".into(),
+            buttons: vec!["OK", "Cancel"],
+            default: 1,
+        };
+        out.push(format!("{:?}", host.message_box(1, &msgbox)));
+        out
+    }
+
+    fn record() -> (Vec<String>, Vec<u8>) {
+        let log = SharedLog::default();
+        let recorder = Recorder::new(Box::new(FakeHost::default()), Box::new(log.clone())).unwrap();
+        let transcript = session(&recorder);
+        drop(recorder);
+        let bytes = log.0.borrow().clone();
+        (transcript, bytes)
+    }
+
+    fn replayer(log: Vec<u8>) -> Replayer {
+        Replayer::new(
+            Box::new(FakeHost::default()),
+            Box::new(std::io::Cursor::new(log)),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn round_trip() {
+        let (recorded, log) = record();
+        assert!(recorded.contains(&"ello".to_string()));
+        let replayer = replayer(log);
+        assert_eq!(session(&replayer), recorded);
+        assert_eq!(replayer.status().error(), None);
+    }
+
+    #[test]
+    fn truncated_log() {
+        let (_, mut log) = record();
+        log.truncate(log.len() - 4);
+        let replayer = replayer(log);
+        let replayed = session(&replayer);
+        assert_eq!(replayed.last().unwrap(), "Err(GEN_FAILURE)");
+        let err = replayer.status().error().unwrap();
+        assert!(err.contains("log ends"), "{err}");
+    }
+
+    #[test]
+    fn divergence() {
+        let (_, log) = record();
+        let replayer = replayer(log);
+        // The log starts with ticks, not a message.
+        assert!(replayer.get_message().is_none());
+        let err = replayer.status().error().unwrap();
+        assert!(
+            err.contains("diverged at event 0: a get_message call"),
+            "{err}"
+        );
+        // Once diverged, every call fails rather than reading on.
+        assert!(matches!(
+            replayer.stat(WindowsPath::new("C:\\a.txt")),
+            Err(ERROR::GEN_FAILURE)
+        ));
+        assert!(!replayer.block(None));
+        assert_eq!(replayer.status().error().unwrap(), err);
+    }
+
+    #[test]
+    fn not_a_log() {
+        let input = Box::new(std::io::Cursor::new(b"something else".to_vec()));
+        assert!(Replayer::new(Box::new(FakeHost::default()), input).is_err());
+    }
+}
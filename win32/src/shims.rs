@@ -22,10 +22,21 @@ pub enum Handler {
     Async(AsyncHandler),
 }
 
+impl Handler {
+    fn addr(&self) -> usize {
+        match *self {
+            Handler::Sync(func) => func as usize,
+            Handler::Async(func) => func as usize,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Shim {
     pub name: &'static str,
     pub func: Handler,
+    /// Bytes of arguments the shim's stub pops on return; 0 for cdecl.
+    pub stack_consumed: u32,
 }
 
 #[derive(Default)]
@@ -38,20 +49,70 @@ impl Shims {
         self.shims.insert(addr, shim);
     }
 
-    pub fn get(&self, addr: u32) -> Result<&Shim, &str> {
+    /// Where a shim is found among the builtin DLLs, by file name and index, which unlike
+    /// its address in our own binary stays the same across runs.
+    /// The shim arrays are consts, which may be copied, so compare shims by their handlers.
+    fn locate(shim: &'static Shim) -> Option<(&'static str, usize)> {
+        crate::winapi::DLLS.iter().find_map(|dll| {
+            let index = dll
+                .shims
+                .iter()
+                .position(|s| s.func.addr() == shim.func.addr())?;
+            Some((dll.file_name, index))
+        })
+    }
+
+    pub fn get(&self, addr: u32) -> Result<&'static Shim, &str> {
         match self.shims.get(&addr) {
-            Some(Ok(shim)) => Ok(shim),
+            Some(Ok(shim)) => Ok(*shim),
             Some(Err(name)) => Err(name),
             None => panic!("unknown import reference at {:x}", addr),
         }
     }
 }
 
+/// Save states record each shim by where locate() finds it.
+impl serde::Serialize for Shims {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let mut shims = Vec::with_capacity(self.shims.len());
+        for (&addr, shim) in &self.shims {
+            let saved = match shim {
+                Ok(shim) => Ok(Self::locate(shim).ok_or_else(|| {
+                    serde::ser::Error::custom(format!("shim {} not builtin", shim.name))
+                })?),
+                Err(name) => Err(name.as_str()),
+            };
+            shims.push((addr, saved));
+        }
+        s.collect_seq(shims)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Shims {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let saved = Vec::<(u32, Result<(String, usize), String>)>::deserialize(d)?;
+        let mut shims = Shims::default();
+        for (addr, shim) in saved {
+            let shim = match shim {
+                Ok((file_name, index)) => Ok(crate::winapi::DLLS
+                    .iter()
+                    .find(|dll| dll.file_name == file_name)
+                    .and_then(|dll| dll.shims.get(index))
+                    .ok_or_else(|| {
+                        serde::de::Error::custom(format!("no shim {file_name}:{index}"))
+                    })?),
+                Err(name) => Err(name),
+            };
+            shims.register(addr, shim);
+        }
+        Ok(shims)
+    }
+}
+
 /// Synchronously evaluate a Future, under the assumption that it is always immediately Ready.
-#[allow(deref_nullptr)]
 pub fn call_sync<T>(future: std::pin::Pin<&mut impl std::future::Future<Output = T>>) -> T {
-    let context: &mut std::task::Context = unsafe { &mut *std::ptr::null_mut() };
-    match future.poll(context) {
+    let mut context = std::task::Context::from_waker(std::task::Waker::noop());
+    match future.poll(&mut context) {
         std::task::Poll::Pending => unreachable!(),
         std::task::Poll::Ready(t) => t,
     }
@@ -15,6 +15,13 @@ use memory::ExtensionsMut;
 static mut MACHINE: *mut Machine = std::ptr::null_mut();
 static mut STACK32: u32 = 0;
 static mut STACK64: u64 = 0;
+/// ecx and edx on entry to trans64, which the switch to 64-bit code clobbers.
+static mut REGISTER_ARGS: [u32; 2] = [0; 2];
+
+/// The ecx and edx of the current call into a builtin, for fastcall and thiscall.
+pub fn register_args() -> [u32; 2] {
+    unsafe { REGISTER_ARGS }
+}
 
 unsafe extern "C" fn call64() -> u32 {
     let machine: &mut Machine = &mut *MACHINE;
@@ -51,11 +58,13 @@ unsafe extern "C" fn call64() -> u32 {
 // It's responsible for switching to the 64-bit stack and backing up the appropriate
 // registers to transition from stdcall ABI to SysV AMD64 ABI.
 // See "Calling conventions" in doc/x86-64.md; the summary is we only need to preserve
-// ESI/EDI.
+// ESI/EDI, and save ECX/EDX for builtins that take arguments in them.
 #[cfg(target_arch = "x86_64")]
 std::arch::global_asm!(
     "_trans64:",
     "movl %esp, {stack32}(%rip)",  // save 32-bit stack
+    "movl %ecx, {regs}(%rip)",     // save fastcall/thiscall args
+    "movl %edx, {regs}+4(%rip)",
     "movq {stack64}(%rip), %rsp",  // switch to 64-bit stack
     "pushq %rdi",                  // preserve edi
     "pushq %rsi",                  // preserve esi
@@ -74,6 +83,7 @@ std::arch::global_asm!(
     options(att_syntax),
     stack32 = sym STACK32,
     stack64 = sym STACK64,
+    regs = sym REGISTER_ARGS,
     call64 = sym call64,
 );
 
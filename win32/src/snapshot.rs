@@ -0,0 +1,593 @@
+//! Save states: the whole machine written to a file, to resume from later.
+//!
+//! A save state holds the CPUs, guest memory, and the state of all the DLLs we
+//! implement, serialized via serde with bincode.  Some things can't be saved directly:
+//!
+//! - Host objects (windows, surfaces, open files, audio) are left out and deserialize as
+//!   Detached placeholders, which reattach() replaces with fresh ones from the host.
+//!   The pixels of surfaces are saved separately so that reattach() can restore them.
+//! - Rust futures can't be serialized, so saving is only possible while no thread is
+//!   partway through an async call into retrowin32, such as a blocking wait.
+//! - Child processes aren't saved, so neither is a process that has any.
+//!
+//! Save states are only good for the build that wrote them, as their layout follows
+//! the layout of the state structs; VERSION guards against the obvious mismatches.
+
+use crate::{host, machine::Machine, winapi};
+use anyhow::{bail, Context};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    any::Any,
+    cell::RefCell,
+    collections::HashMap,
+    rc::{Rc, Weak},
+};
+
+const MAGIC: &[u8; 8] = b"RW32SAVE";
+/// Bump when the format changes in a way that would otherwise misparse.
+const VERSION: u32 = 1;
+
+/// Stands in for a host object in a freshly loaded save state, until reattach()
+/// replaces it.  Anything that outlives reattach(), like a directory listing that can't
+/// be resumed, stays detached and acts as if empty or closed.
+pub struct Detached;
+
+impl host::Surface for Detached {
+    fn write_pixels(&mut self, _pixels: &[[u8; 4]]) {}
+    fn read_pixels(&mut self) -> Vec<[u8; 4]> {
+        Vec::new()
+    }
+    fn show(&mut self) {}
+    fn bit_blt(
+        &mut self,
+        _dx: u32,
+        _dy: u32,
+        _src: &dyn host::Surface,
+        _sx: u32,
+        _sy: u32,
+        _w: u32,
+        _h: u32,
+    ) {
+    }
+}
+
+impl host::Window for Detached {
+    fn set_title(&mut self, _title: &str) {}
+    fn set_size(&mut self, _width: u32, _height: u32) {}
+    fn show(&mut self, _visible: bool) {}
+    fn set_alpha(&mut self, _alpha: u8) {}
+    fn fullscreen(&mut self) {}
+}
+
+impl host::Audio for Detached {
+    fn write(&mut self, _buf: &[u8]) {}
+}
+
+fn detached_io_error() -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::NotConnected, "file not reopened")
+}
+
+impl std::io::Read for Detached {
+    fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+        Err(detached_io_error())
+    }
+}
+
+impl std::io::Write for Detached {
+    fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+        Err(detached_io_error())
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl std::io::Seek for Detached {
+    fn seek(&mut self, _pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        Err(detached_io_error())
+    }
+}
+
+impl host::File for Detached {
+    fn stat(&self) -> Result<host::Stat, host::ERROR> {
+        Err(host::ERROR::INVALID_HANDLE)
+    }
+    fn set_len(&self, _len: u64) -> Result<(), host::ERROR> {
+        Err(host::ERROR::INVALID_HANDLE)
+    }
+    fn set_times(&self, _atime: Option<i64>, _mtime: Option<i64>) -> Result<(), host::ERROR> {
+        Err(host::ERROR::INVALID_HANDLE)
+    }
+}
+
+impl host::ReadDir for Detached {
+    fn next(&mut self) -> Result<Option<host::ReadDirEntry>, host::ERROR> {
+        Ok(None)
+    }
+}
+
+/// Host object fields are skipped by serde and deserialize as detached; use as
+/// `#[serde(skip, default = "crate::snapshot::Detach::detached")]`.
+pub trait Detach {
+    fn detached() -> Self;
+}
+
+impl Detach for Box<dyn host::Surface> {
+    fn detached() -> Self {
+        Box::new(Detached)
+    }
+}
+
+impl Detach for Box<dyn host::Window> {
+    fn detached() -> Self {
+        Box::new(Detached)
+    }
+}
+
+impl Detach for Box<dyn host::File> {
+    fn detached() -> Self {
+        Box::new(Detached)
+    }
+}
+
+impl Detach for Box<dyn host::ReadDir> {
+    fn detached() -> Self {
+        Box::new(Detached)
+    }
+}
+
+/// For an optional host file, saving only whether there is one.
+pub mod optional_file {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        file: &Option<Box<dyn host::File>>,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        file.is_some().serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        d: D,
+    ) -> Result<Option<Box<dyn host::File>>, D::Error> {
+        Ok(bool::deserialize(d)?.then(Detach::detached))
+    }
+}
+
+thread_local! {
+    /// While saving, the index of each Rc written so far, by address.
+    static SAVED_RCS: RefCell<HashMap<usize, u32>> = RefCell::new(HashMap::new());
+    /// While loading, the Rcs read so far, by index.
+    static LOADED_RCS: RefCell<Vec<Rc<dyn Any>>> = RefCell::new(Vec::new());
+}
+
+/// Clears the Rc tables when saving or loading is done, however it ends.
+/// Saving must write in a single pass, e.g. bincode::serialize_into() rather than
+/// bincode::serialize(), which first measures the output with a pass of its own.
+struct RcScope;
+
+impl RcScope {
+    fn new() -> Self {
+        SAVED_RCS.with(|rcs| rcs.borrow_mut().clear());
+        LOADED_RCS.with(|rcs| rcs.borrow_mut().clear());
+        RcScope
+    }
+}
+
+impl Drop for RcScope {
+    fn drop(&mut self) {
+        SAVED_RCS.with(|rcs| rcs.borrow_mut().clear());
+        LOADED_RCS.with(|rcs| rcs.borrow_mut().clear());
+    }
+}
+
+/// An Rc shared between several owners.  The first time it's written it's written in
+/// full, and afterwards only by index, so that loading shares it again rather than
+/// making copies.  Indices count Rcs in the order they're finished, which is the same
+/// for saving and loading even when one Rc holds others.
+struct SharedRef<'a, T>(&'a Rc<T>);
+
+impl<'a, T: Serialize> Serialize for SharedRef<'a, T> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let addr = Rc::as_ptr(self.0) as *const () as usize;
+        if let Some(index) = SAVED_RCS.with(|rcs| rcs.borrow().get(&addr).copied()) {
+            return Err::<&T, u32>(index).serialize(s);
+        }
+        let ok = Ok::<&T, u32>(&**self.0).serialize(s)?;
+        SAVED_RCS.with(|rcs| {
+            let mut rcs = rcs.borrow_mut();
+            let index = rcs.len() as u32;
+            rcs.insert(addr, index);
+        });
+        Ok(ok)
+    }
+}
+
+fn load_shared<'de, T: Deserialize<'de> + 'static, D: Deserializer<'de>>(
+    d: D,
+) -> Result<Rc<T>, D::Error> {
+    let saved = Result::<T, u32>::deserialize(d)?;
+    LOADED_RCS.with(|rcs| {
+        let mut rcs = rcs.borrow_mut();
+        match saved {
+            Ok(value) => {
+                let rc = Rc::new(value);
+                rcs.push(rc.clone());
+                Ok(rc)
+            }
+            Err(index) => rcs
+                .get(index as usize)
+                .and_then(|rc| rc.clone().downcast::<T>().ok())
+                .ok_or_else(|| serde::de::Error::custom("bad shared reference")),
+        }
+    })
+}
+
+/// For Rc fields whose sharing matters, as `#[serde(with = "crate::snapshot::shared")]`.
+pub mod shared {
+    use super::*;
+
+    pub fn serialize<S: Serializer, T: Serialize>(rc: &Rc<T>, s: S) -> Result<S::Ok, S::Error> {
+        SharedRef(rc).serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, T: Deserialize<'de> + 'static>(
+        d: D,
+    ) -> Result<Rc<T>, D::Error> {
+        load_shared(d)
+    }
+}
+
+struct LoadShared<T>(Rc<T>);
+
+impl<'de, T: Deserialize<'de> + 'static> Deserialize<'de> for LoadShared<T> {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        load_shared(d).map(LoadShared)
+    }
+}
+
+/// Like shared, for a Vec of Rcs.
+pub mod shared_vec {
+    use super::*;
+
+    pub fn serialize<S: Serializer, T: Serialize>(rcs: &[Rc<T>], s: S) -> Result<S::Ok, S::Error> {
+        s.collect_seq(rcs.iter().map(SharedRef))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, T: Deserialize<'de> + 'static>(
+        d: D,
+    ) -> Result<Vec<Rc<T>>, D::Error> {
+        let rcs = Vec::<LoadShared<T>>::deserialize(d)?;
+        Ok(rcs.into_iter().map(|rc| rc.0).collect())
+    }
+}
+
+/// Like shared, for a Vec of Weaks; those whose target is gone load as dangling.
+pub mod weak_vec {
+    use super::*;
+
+    pub fn serialize<S: Serializer, T: Serialize>(
+        weaks: &[Weak<T>],
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        let rcs: Vec<Option<Rc<T>>> = weaks.iter().map(Weak::upgrade).collect();
+        s.collect_seq(rcs.iter().map(|rc| rc.as_ref().map(SharedRef)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, T: Deserialize<'de> + 'static>(
+        d: D,
+    ) -> Result<Vec<Weak<T>>, D::Error> {
+        let rcs = Vec::<Option<LoadShared<T>>>::deserialize(d)?;
+        Ok(rcs
+            .into_iter()
+            .map(|rc| rc.map_or_else(Weak::new, |rc| Rc::downgrade(&rc.0)))
+            .collect())
+    }
+}
+
+#[cfg(feature = "x86-emu")]
+pub use save_state::{load, save};
+
+/// Saving and loading whole machines, which so far only the x86 emulator supports.
+#[cfg(feature = "x86-emu")]
+mod save_state {
+    use super::*;
+    use crate::{
+        clock::SavedClock,
+        machine_emu::SavedMemory,
+        shims::Shims,
+        winapi::{
+            kernel32::{reopen_mapped_files, vfs, KernelObject, ObjectId},
+            user32::{ShowState, WindowStyle, WindowType},
+        },
+        FileOptions, Status, SurfaceOptions,
+    };
+    use std::{
+        io::{Seek, SeekFrom},
+        path::PathBuf,
+    };
+    use typed_path::WindowsPath;
+
+    /// What a save state holds, borrowed from the Machine for writing.
+    /// Must match Saved field for field.
+    #[derive(Serialize)]
+    struct SaveRef<'a> {
+        exe_path: &'a PathBuf,
+        x86: &'a x86::X86,
+        memory: SavedMemory,
+        shims: &'a Shims,
+        state: &'a winapi::State,
+        clock: SavedClock,
+        /// Positions of open files, which live in the host objects.
+        file_positions: HashMap<ObjectId, u64>,
+        /// Contents of DirectDraw surfaces, keyed by surface address.
+        surface_pixels: HashMap<u32, Vec<[u8; 4]>>,
+        labels: &'a HashMap<u32, String>,
+        syscall_count: usize,
+    }
+
+    /// What a save state holds, as read back.
+    #[derive(Deserialize)]
+    struct Saved {
+        exe_path: PathBuf,
+        x86: x86::X86,
+        memory: SavedMemory,
+        shims: Shims,
+        state: winapi::State,
+        clock: SavedClock,
+        file_positions: HashMap<ObjectId, u64>,
+        surface_pixels: HashMap<u32, Vec<[u8; 4]>>,
+        labels: HashMap<u32, String>,
+        syscall_count: usize,
+    }
+
+    /// Write a save state of the machine, which must be stopped between blocks.
+    pub fn save(machine: &mut Machine) -> anyhow::Result<Vec<u8>> {
+        if machine.emu.x86.has_futures() {
+            bail!("a thread is in a blocking call, try again once it returns");
+        }
+        if machine.state.kernel32.has_child_processes() {
+            bail!("child processes can't be saved");
+        }
+
+        let objects = &mut machine.state.kernel32.objects;
+        let files: Vec<ObjectId> = objects
+            .iter_ids()
+            .filter(|(_, obj)| matches!(obj, KernelObject::File(_)))
+            .map(|(id, _)| id)
+            .collect();
+        let mut file_positions = HashMap::new();
+        for id in files {
+            let Some(KernelObject::File(file)) = objects.object_mut(id) else {
+                unreachable!()
+            };
+            file_positions.insert(id, file.file.stream_position()?);
+        }
+        let surface_pixels = machine
+            .state
+            .ddraw
+            .surfaces
+            .iter_mut()
+            .map(|(&addr, surface)| (addr, surface.host.read_pixels()))
+            .collect();
+
+        let mappings = machine.state.kernel32.mappings.vec();
+        let saved = SaveRef {
+            exe_path: &machine.exe_path,
+            x86: &machine.emu.x86,
+            memory: machine
+                .emu
+                .save_memory(mappings.iter().map(|m| (m.addr, m.size))),
+            shims: &machine.emu.shims,
+            state: &machine.state,
+            clock: machine
+                .clock
+                .save(machine.host.ticks(), machine.instr_count()),
+            file_positions,
+            surface_pixels,
+            labels: &machine.labels,
+            syscall_count: machine.emu.syscall_count,
+        };
+
+        let mut buf = MAGIC.to_vec();
+        buf.extend_from_slice(&VERSION.to_le_bytes());
+        let _scope = RcScope::new();
+        bincode::serialize_into(&mut buf, &saved)?;
+        Ok(buf)
+    }
+
+    /// Replace the running machine with a save state.  The machine keeps its host and
+    /// its setup that describes the host, like mounts and debugger breakpoints.
+    pub fn load(machine: &mut Machine, buf: &[u8]) -> anyhow::Result<()> {
+        let Some(buf) = buf.strip_prefix(MAGIC) else {
+            bail!("not a save state");
+        };
+        let (version, buf) = buf.split_at(4.min(buf.len()));
+        if version != VERSION.to_le_bytes() {
+            bail!("save state from an incompatible version");
+        }
+        let saved: Saved = {
+            let _scope = RcScope::new();
+            bincode::deserialize(buf).context("reading save state")?
+        };
+
+        machine.emu.restore_memory(&saved.memory)?;
+        machine.emu.x86.restore(machine.emu.memory.mem(), saved.x86);
+        machine.emu.shims = saved.shims;
+        machine.emu.syscall_count = saved.syscall_count;
+        let current = std::mem::replace(&mut machine.state, saved.state);
+        machine.state.kernel32.vfs.keep_mounts(current.kernel32.vfs);
+        machine
+            .clock
+            .restore(&saved.clock, machine.host.ticks(), machine.instr_count());
+        machine.labels = saved.labels;
+        machine.exe_path = saved.exe_path;
+        machine.status = Status::Running;
+
+        reattach(machine, &saved.file_positions, saved.surface_pixels);
+        Ok(())
+    }
+
+    /// Replace the Detached host objects of a loaded state with new ones.
+    fn reattach(
+        machine: &mut Machine,
+        file_positions: &HashMap<ObjectId, u64>,
+        surface_pixels: HashMap<u32, Vec<[u8; 4]>>,
+    ) {
+        let mem = machine.emu.memory.mem();
+        for window in machine.state.user32.windows.iter_mut() {
+            let WindowType::TopLevel(top) = &mut window.typ else {
+                continue;
+            };
+            let hwnd = window.hwnd.to_raw();
+            top.host = machine.host.create_window(hwnd);
+            top.host.set_title(&window.text);
+            top.host.set_size(window.width, window.height);
+            if let Some(layered) = &top.layered {
+                top.host.set_alpha(layered.alpha);
+            }
+            top.surface = machine.host.create_surface(
+                hwnd,
+                &SurfaceOptions {
+                    width: window.width,
+                    height: window.height,
+                    primary: true,
+                },
+            );
+            if window.style.contains(WindowStyle::VISIBLE)
+                && window.show_state != ShowState::Minimized
+            {
+                top.host.show(true);
+            }
+            top.flush_pixels(mem);
+        }
+
+        machine
+            .state
+            .ddraw
+            .reattach(&mut *machine.host, surface_pixels);
+
+        // Files that can't be reopened stay detached, failing reads and writes.
+        let files: Vec<(ObjectId, String, FileOptions)> = machine
+            .state
+            .kernel32
+            .objects
+            .iter_ids()
+            .filter_map(|(id, obj)| match obj {
+                KernelObject::File(file) => Some((
+                    id,
+                    file.path.clone(),
+                    FileOptions {
+                        read: file.read,
+                        write: file.write,
+                        ..Default::default()
+                    },
+                )),
+                _ => None,
+            })
+            .collect();
+        for (id, path, options) in files {
+            let mut host_file = match vfs(machine).open(WindowsPath::new(&path), options) {
+                Ok(file) => file,
+                Err(err) => {
+                    log::warn!("save state: reopening {path}: {err:?}");
+                    continue;
+                }
+            };
+            if let Some(&pos) = file_positions.get(&id) {
+                if let Err(err) = host_file.seek(SeekFrom::Start(pos)) {
+                    log::warn!("save state: seeking {path}: {err}");
+                }
+            }
+            if let Some(KernelObject::File(file)) = machine.state.kernel32.objects.object_mut(id) {
+                file.file = host_file;
+            }
+        }
+        if let Err(err) = reopen_mapped_files(machine) {
+            log::warn!("save state: reopening mapped files: {err:?}");
+        }
+
+        if machine.state.winmm.audio_rate != 0 {
+            machine.state.winmm.audio =
+                Some(machine.host.init_audio(machine.state.winmm.audio_rate));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    struct Owners {
+        #[serde(with = "shared")]
+        a: Rc<RefCell<u32>>,
+        #[serde(with = "shared_vec")]
+        b: Vec<Rc<RefCell<u32>>>,
+        #[serde(with = "weak_vec")]
+        c: Vec<Weak<RefCell<u32>>>,
+    }
+
+    #[test]
+    fn shared_rcs_stay_shared() {
+        let rc = Rc::new(RefCell::new(1));
+        let gone = Rc::new(RefCell::new(2));
+        let owners = Owners {
+            a: rc.clone(),
+            b: vec![rc.clone(), Rc::new(RefCell::new(3))],
+            c: vec![Rc::downgrade(&rc), Rc::downgrade(&gone)],
+        };
+        drop(gone);
+
+        let mut buf = Vec::new();
+        {
+            let _scope = RcScope::new();
+            bincode::serialize_into(&mut buf, &owners).unwrap();
+        }
+        let loaded: Owners = {
+            let _scope = RcScope::new();
+            bincode::deserialize(&buf).unwrap()
+        };
+
+        *loaded.a.borrow_mut() = 4;
+        assert_eq!(*loaded.b[0].borrow(), 4);
+        assert_eq!(*loaded.b[1].borrow(), 3);
+        assert_eq!(*loaded.c[0].upgrade().unwrap().borrow(), 4);
+        assert!(loaded.c[1].upgrade().is_none());
+    }
+
+    #[cfg(feature = "x86-emu")]
+    #[test]
+    fn machine_round_trip() {
+        use crate::{fake_host::FakeHost, winapi::kernel32};
+        use memory::{Extensions, ExtensionsMut};
+
+        let mut machine = Machine::new(Box::new(FakeHost::default()), "test.exe".into());
+        let addr = machine
+            .state
+            .kernel32
+            .mappings
+            .alloc(0x1000, "test".into(), &mut machine.emu.memory)
+            .unwrap()
+            .addr;
+        machine.emu.memory.mem().put_pod::<u32>(addr, 0x1234_5678);
+        machine.emu.x86.cpu_mut().regs.set32(x86::Register::EAX, 42);
+        let event = kernel32::CreateEventA(&mut machine, 0, false, false, Some("ev"));
+        let event = kernel32::HOBJECT::from_raw(event.to_raw());
+        let buf = save(&mut machine).unwrap();
+
+        machine.emu.memory.mem().put_pod::<u32>(addr, 0);
+        machine.emu.x86.cpu_mut().regs.set32(x86::Register::EAX, 0);
+        kernel32::CloseHandle(&mut machine, event);
+        assert!(machine.state.kernel32.objects.get(event).is_none());
+
+        load(&mut machine, &buf).unwrap();
+        assert_eq!(machine.emu.memory.mem().get_pod::<u32>(addr), 0x1234_5678);
+        assert_eq!(machine.emu.x86.cpu().regs.get32(x86::Register::EAX), 42);
+        assert!(matches!(
+            machine.state.kernel32.objects.get(event),
+            Some(kernel32::KernelObject::Event(ev)) if ev.name == "ev"
+        ));
+        assert!(load(&mut machine, b"RW32SAVE\xff\xff\xff\xff").is_err());
+    }
+}
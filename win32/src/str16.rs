@@ -48,16 +48,7 @@ impl Str16 {
     }
 
     pub fn to_string(&self) -> String {
-        self.0
-            .iter()
-            .map(|&c| {
-                if c > 0xFF {
-                    // TODO
-                    panic!("unhandled non-ascii {:?}", char::from_u32(c as u32));
-                }
-                c as u8 as char
-            })
-            .collect()
+        String::from_utf16_lossy(&self.0)
     }
 }
 
@@ -94,16 +85,7 @@ impl String16 {
     }
 
     pub fn from(str: &str) -> Self {
-        String16(
-            str.chars()
-                .map(|c| {
-                    if c as u16 > 0x7f {
-                        panic!("unhandled non-ascii {:?}", c);
-                    }
-                    c as u16
-                })
-                .collect(),
-        )
+        String16(str.encode_utf16().collect())
     }
 }
 
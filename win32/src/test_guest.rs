@@ -0,0 +1,159 @@
+//! Running snippets of guest code in tests, without an exe: a machine with a stack and
+//! the builtin DLLs to call, and a little help laying out machine code by hand.
+
+use crate::{fake_host::FakeHost, machine::Status, winapi::kernel32, Machine};
+use memory::{Extensions, ExtensionsMut};
+
+/// A machine ready to run code from alloc_code(), on a main thread with a stack.
+pub fn new_machine() -> (Machine, FakeHost) {
+    let host = FakeHost::default();
+    let mut machine = Machine::new(Box::new(host.clone()), "test.exe".into());
+    let esp = machine.create_stack("stack".into(), 0x10000).unwrap();
+    let teb = machine.state.kernel32.teb;
+    let cpu = machine.emu.x86.cpu_mut();
+    cpu.regs.set32(x86::Register::ESP, esp);
+    cpu.regs.set32(x86::Register::EBP, esp);
+    cpu.regs.fs_addr = teb;
+    cpu.fpu.control = kernel32::INITIAL_FPU_CONTROL;
+    (machine, host)
+}
+
+/// The address of an export of a builtin DLL.
+pub fn builtin(machine: &mut Machine, dll: &str, name: &str) -> u32 {
+    kernel32::get_symbol(machine, dll, name)
+}
+
+/// Run until the machine stops, which a test's code must arrange by calling
+/// ExitProcess, and return the exit code.
+pub fn run(machine: &mut Machine) -> u32 {
+    for _ in 0..100_000 {
+        if !machine.run() {
+            break;
+        }
+    }
+    match &machine.status {
+        Status::Exit(code) => *code,
+        Status::Error { message } => panic!("guest failed: {message}"),
+        _ => panic!("guest didn't exit"),
+    }
+}
+
+/// Memory that guest code stores results into: recorder() functions append their arguments,
+/// after a cursor word pointing at the next free entry.
+pub struct Log {
+    pub addr: u32,
+}
+
+impl Log {
+    pub fn alloc(machine: &mut Machine, len: u32) -> Log {
+        let addr = machine
+            .state
+            .kernel32
+            .mappings
+            .alloc(len, "test log".into(), &mut machine.emu.memory)
+            .unwrap()
+            .addr;
+        machine.mem().put_pod::<u32>(addr, addr + 4);
+        Log { addr }
+    }
+
+    /// The entries appended so far.
+    pub fn read(&self, machine: &Machine) -> Vec<u32> {
+        let mem = machine.mem();
+        let end = mem.get_pod::<u32>(self.addr);
+        (self.addr + 4..end)
+            .step_by(4)
+            .map(|addr| mem.get_pod::<u32>(addr))
+            .collect()
+    }
+}
+
+/// Machine code under construction, to be placed at base.
+pub struct Code {
+    pub base: u32,
+    pub bytes: Vec<u8>,
+}
+
+impl Code {
+    /// Reserve memory for up to len bytes of code.
+    pub fn alloc(machine: &mut Machine, len: u32) -> Code {
+        let base = machine
+            .state
+            .kernel32
+            .mappings
+            .alloc(len, "test code".into(), &mut machine.emu.memory)
+            .unwrap()
+            .addr;
+        Code {
+            base,
+            bytes: Vec::new(),
+        }
+    }
+
+    /// Pad with int3 up to offset, for code at a known address.
+    pub fn at(&mut self, offset: u32) -> &mut Self {
+        assert!(offset as usize >= self.bytes.len());
+        self.bytes.resize(offset as usize, 0xCC);
+        self
+    }
+
+    pub fn emit(&mut self, bytes: &[u8]) -> &mut Self {
+        self.bytes.extend_from_slice(bytes);
+        self
+    }
+
+    pub fn emit32(&mut self, value: u32) -> &mut Self {
+        self.emit(&value.to_le_bytes())
+    }
+
+    /// push imm32
+    pub fn push(&mut self, value: u32) -> &mut Self {
+        self.emit(&[0x68]).emit32(value)
+    }
+
+    /// push dword [addr]
+    pub fn push_from(&mut self, addr: u32) -> &mut Self {
+        self.emit(&[0xFF, 0x35]).emit32(addr)
+    }
+
+    /// mov [addr], eax
+    pub fn save_eax(&mut self, addr: u32) -> &mut Self {
+        self.emit(&[0xA3]).emit32(addr)
+    }
+
+    /// mov eax, func; call eax
+    pub fn call(&mut self, func: u32) -> &mut Self {
+        self.emit(&[0xB8]).emit32(func).emit(&[0xFF, 0xD0])
+    }
+
+    /// A stdcall function of argc arguments that appends them to log and returns ret.
+    pub fn recorder(&mut self, log: &Log, argc: u8, ret: u32) -> &mut Self {
+        self.emit(&[0xA1]).emit32(log.addr); // mov eax, [cursor]
+        for i in 0..argc {
+            self.emit(&[0x8B, 0x54, 0x24, 4 + 4 * i]) // mov edx, [esp+4+4*i]
+                .emit(&[0x89, 0x50, 4 * i]); // mov [eax+4*i], edx
+        }
+        self.emit(&[0x83, 0x05]) // add dword [cursor], 4*argc
+            .emit32(log.addr)
+            .emit(&[4 * argc])
+            .emit(&[0xB8]) // mov eax, ret
+            .emit32(ret)
+            .emit(&[0xC2, 4 * argc, 0]) // ret 4*argc
+    }
+
+    /// Call ExitProcess with the value of a register, given as the low 3 bits of its
+    /// encoding (0 for eax, 3 for ebx, ...).
+    pub fn exit_with(&mut self, machine: &mut Machine, reg: u8) -> &mut Self {
+        let exit = builtin(machine, "kernel32.dll", "ExitProcess");
+        self.emit(&[0x50 + reg]).call(exit)
+    }
+
+    /// Copy the code into memory and start the CPU at its base.
+    pub fn start(&self, machine: &mut Machine) {
+        machine
+            .mem()
+            .sub32_mut(self.base, self.bytes.len() as u32)
+            .copy_from_slice(&self.bytes);
+        machine.emu.x86.cpu_mut().regs.eip = self.base;
+    }
+}
@@ -4,6 +4,11 @@
 //! matching, and a "-" suppresses, so e.g.
 //!   --win32-trace=kernel32/,-kernel32/file
 //! Pass '*' to enable all.
+//!
+//! Separately, set_output() directs a structured trace to a file: one JSON object per
+//! line, recording the CPU state at each traced block or instruction (see record_cpu)
+//! and each traced winapi call, with its function, module, arguments and return value
+//! as separate fields, for comparing runs with misc/trace-diff.py.
 
 use std::cell::UnsafeCell;
 use std::collections::HashMap;
@@ -78,32 +83,177 @@ pub fn enabled(context: &'static str) -> bool {
     unsafe {
         match STATE.get_mut() {
             None => return false,
-            Some(state) => state.lookup(context),
+            Some(state) => {
+                state.lookup(context) && output().map_or(true, |output| output.traces(context))
+            }
         }
     }
 }
 
-#[inline(never)]
-pub fn trace_begin(context: &str, func: &str, args: &[(&str, &dyn std::fmt::Debug)]) -> String {
-    let mut msg = format!("{}/{}(", context, func);
-    for (i, arg) in args.iter().enumerate() {
-        if i > 0 {
-            msg.push_str(", ");
+/// A traced winapi call, from trace_begin() until trace_return().
+pub struct Call {
+    /// The trace context, e.g. "kernel32/file", whose first part names the DLL.
+    context: &'static str,
+    func: &'static str,
+    args: Vec<(&'static str, String)>,
+}
+
+impl std::fmt::Display for Call {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}(", self.context, self.func)?;
+        for (i, (name, value)) in self.args.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{name}:{value}")?;
         }
-        write!(&mut msg, "{}:{:x?}", arg.0, arg.1).unwrap();
+        f.write_str(")")
     }
-    msg.push_str(")");
-    msg
 }
 
 #[inline(never)]
-pub fn trace_return(context: &str, file: &'static str, line: u32, ret: &dyn std::fmt::Debug) {
+pub fn trace_begin(
+    context: &'static str,
+    func: &'static str,
+    args: &[(&'static str, &dyn std::fmt::Debug)],
+) -> Call {
+    Call {
+        context,
+        func,
+        args: args
+            .iter()
+            .map(|(name, value)| (*name, format!("{value:x?}")))
+            .collect(),
+    }
+}
+
+#[inline(never)]
+pub fn trace_return(call: &Call, file: &'static str, line: u32, ret: &dyn std::fmt::Debug) {
+    if let Some(output) = output() {
+        let mut line = format!(
+            "{{\"call\":\"{}\",\"module\":\"{}\",\"args\":{{",
+            call.func,
+            json_escape(call.context)
+        );
+        for (i, (name, value)) in call.args.iter().enumerate() {
+            if i > 0 {
+                line.push(',');
+            }
+            write!(&mut line, "\"{}\":\"{}\"", name, json_escape(value)).unwrap();
+        }
+        write!(
+            &mut line,
+            "}},\"ret\":\"{}\"}}\n",
+            json_escape(&format!("{ret:x?}"))
+        )
+        .unwrap();
+        output.write(line.as_bytes());
+        return;
+    }
     log::logger().log(
         &log::Record::builder()
             .level(log::Level::Info)
             .file(Some(file))
             .line(Some(line))
-            .args(format_args!("{context} -> {ret:x?}"))
+            .args(format_args!("{call} -> {ret:x?}"))
             .build(),
     );
 }
+
+/// Destination of the structured trace.
+struct Output {
+    out: Box<dyn std::io::Write>,
+    /// Address ranges to record CPU state in; empty means everywhere.
+    ranges: Vec<std::ops::Range<u32>>,
+    /// DLLs to record winapi calls into, lowercase and without extension;
+    /// empty means all.
+    dlls: Vec<String>,
+}
+
+impl Output {
+    /// Whether calls in a trace context like "kernel32/file" are recorded.
+    fn traces(&self, context: &str) -> bool {
+        let dll = context.split('/').next().unwrap_or(context);
+        self.dlls.is_empty() || self.dlls.iter().any(|d| d == dll)
+    }
+
+    fn write(&mut self, buf: &[u8]) {
+        if let Err(err) = self.out.write_all(buf) {
+            log::error!("trace output: {err}");
+        }
+    }
+}
+
+static mut OUTPUT: UnsafeCell<Option<Output>> = UnsafeCell::new(None);
+
+fn output() -> Option<&'static mut Output> {
+    unsafe { (*std::ptr::addr_of_mut!(OUTPUT)).get_mut().as_mut() }
+}
+
+/// Write the structured trace to out, recording CPU state only within ranges if any.
+/// winapi calls are recorded for the modules enabled by set_scheme(), and only for
+/// the given DLLs (e.g. "kernel32" or "USER32.DLL") if any.
+pub fn set_output(
+    out: Box<dyn std::io::Write>,
+    ranges: Vec<std::ops::Range<u32>>,
+    dlls: &[String],
+) {
+    let dlls = dlls
+        .iter()
+        .map(|dll| {
+            let dll = dll.to_ascii_lowercase();
+            dll.strip_suffix(".dll").map(str::to_string).unwrap_or(dll)
+        })
+        .collect();
+    let output = Output { out, ranges, dlls };
+    unsafe { *(*std::ptr::addr_of_mut!(OUTPUT)).get_mut() = Some(output) };
+}
+
+/// Flush the structured trace, before exiting.
+pub fn flush_output() {
+    if let Some(output) = output() {
+        if let Err(err) = output.out.flush() {
+            log::error!("trace output: {err}");
+        }
+    }
+}
+
+/// Record the state of a CPU about to execute at its eip, if eip is in a traced range.
+#[cfg(feature = "x86-emu")]
+pub fn record_cpu(cpu: &x86::CPU) {
+    use x86::Register::*;
+    let Some(output) = output() else {
+        return;
+    };
+    let regs = &cpu.regs;
+    if !output.ranges.is_empty() && !output.ranges.iter().any(|r| r.contains(&regs.eip)) {
+        return;
+    }
+    let line = format!(
+        "{{\"eip\":\"{:x}\",\"eax\":\"{:x}\",\"ebx\":\"{:x}\",\"ecx\":\"{:x}\",\"edx\":\"{:x}\",\"esi\":\"{:x}\",\"edi\":\"{:x}\",\"esp\":\"{:x}\",\"ebp\":\"{:x}\",\"flags\":\"{:x}\"}}\n",
+        regs.eip,
+        regs.get32(EAX),
+        regs.get32(EBX),
+        regs.get32(ECX),
+        regs.get32(EDX),
+        regs.get32(ESI),
+        regs.get32(EDI),
+        regs.get32(ESP),
+        regs.get32(EBP),
+        cpu.flags.bits(),
+    );
+    output.write(line.as_bytes());
+}
+
+fn json_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => write!(&mut out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out
+}
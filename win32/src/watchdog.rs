@@ -0,0 +1,171 @@
+//! Detects a guest that has stopped making progress, e.g. stuck in an infinite loop.
+//!
+//! "Progress" is any win32 API call (which includes anything that draws a frame)
+//! or the guest blocking on the host, e.g. in Sleep or GetMessage; time spent blocked
+//! doesn't count against the budget.  A guest that executes code for longer than the
+//! budget without either is considered hung.
+
+use crate::{crash::nearest_label, machine::Status, Machine};
+use memory::Extensions;
+use std::collections::HashMap;
+
+/// How long a guest may run without progress before it's considered hung.
+#[derive(Debug, Clone, Copy)]
+pub enum Budget {
+    Instructions(usize),
+    /// Milliseconds as measured by Host::ticks().
+    Millis(u32),
+}
+
+impl std::str::FromStr for Budget {
+    type Err = String;
+
+    /// Parses "10s" or "500ms" as a time budget, and a bare number as an instruction count.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = |_| format!("bad watchdog budget {s:?}");
+        if let Some(ms) = s.strip_suffix("ms") {
+            Ok(Budget::Millis(ms.parse().map_err(err)?))
+        } else if let Some(secs) = s.strip_suffix("s") {
+            let secs = secs.parse::<u32>().map_err(err)?;
+            Ok(Budget::Millis(secs.saturating_mul(1000)))
+        } else {
+            Ok(Budget::Instructions(s.parse().map_err(err)?))
+        }
+    }
+}
+
+/// Only look at the clock and sample EIP every this many checks, to keep check() cheap.
+const SAMPLE_INTERVAL: usize = 1024;
+
+pub struct Watchdog {
+    budget: Budget,
+    checks: usize,
+    /// Syscall count, instruction count, and time at last progress.
+    syscalls: usize,
+    instrs: usize,
+    ticks: Option<u32>,
+    /// Histogram of sampled EIP values since last progress.
+    samples: HashMap<u32, usize>,
+}
+
+impl Watchdog {
+    pub fn new(budget: Budget) -> Self {
+        Watchdog {
+            budget,
+            checks: 0,
+            syscalls: 0,
+            instrs: 0,
+            ticks: None,
+            samples: HashMap::new(),
+        }
+    }
+
+    /// Note progress, e.g. when resuming after the user has paused execution.
+    pub fn reset(&mut self, machine: &Machine) {
+        self.syscalls = machine.emu.syscall_count;
+        self.instrs = machine.emu.x86.instr_count;
+        self.ticks = None;
+        self.samples.clear();
+    }
+
+    /// Call regularly while running the machine, e.g. after each Machine::run().
+    /// Returns a report if the guest has exceeded its budget without progress.
+    pub fn check(&mut self, machine: &Machine) -> Option<StallReport> {
+        let blocked = matches!(machine.emu.x86.cpu().state, x86::CPUState::Blocked(_));
+        if machine.emu.syscall_count != self.syscalls
+            || blocked
+            || !matches!(machine.status, Status::Running)
+        {
+            self.reset(machine);
+            return None;
+        }
+
+        let stalled = match self.budget {
+            Budget::Instructions(n) => machine.emu.x86.instr_count.wrapping_sub(self.instrs) > n,
+            Budget::Millis(_) => false,
+        };
+
+        self.checks = self.checks.wrapping_add(1);
+        if self.checks % SAMPLE_INTERVAL != 0 && !stalled {
+            return None;
+        }
+        *self
+            .samples
+            .entry(machine.emu.x86.cpu().regs.eip)
+            .or_default() += 1;
+
+        let stalled = stalled
+            || match self.budget {
+                Budget::Millis(ms) => {
+                    let now = machine.host.ticks();
+                    let start = *self.ticks.get_or_insert(now);
+                    now.wrapping_sub(start) > ms
+                }
+                _ => false,
+            };
+        if !stalled {
+            return None;
+        }
+
+        let report = StallReport::new(machine, &self.samples);
+        self.reset(machine);
+        Some(report)
+    }
+}
+
+/// Diagnostics about where a hung guest was spending its time.
+pub struct StallReport {
+    pub eip: u32,
+    /// Most frequently sampled EIPs, with their labels if known, most frequent first.
+    pub hot: Vec<(u32, Option<String>, usize)>,
+    /// Top of the stack, as (address, value) pairs.
+    pub stack: Vec<(u32, u32)>,
+}
+
+impl StallReport {
+    fn new(machine: &Machine, samples: &HashMap<u32, usize>) -> Self {
+        let mut hot = samples
+            .iter()
+            .map(|(&addr, &count)| (addr, count))
+            .collect::<Vec<_>>();
+        hot.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        let hot = hot
+            .into_iter()
+            .take(10)
+            .map(|(addr, count)| (addr, nearest_label(machine, addr), count))
+            .collect();
+
+        let esp = machine.emu.x86.cpu().regs.get32(x86::Register::ESP);
+        let mem = machine.mem();
+        let stack = (0..8)
+            .map(|i| esp + i * 4)
+            .take_while(|&addr| addr + 4 <= mem.len())
+            .map(|addr| (addr, mem.get_pod::<u32>(addr)))
+            .collect();
+
+        StallReport {
+            eip: machine.emu.x86.cpu().regs.eip,
+            hot,
+            stack,
+        }
+    }
+}
+
+impl std::fmt::Display for StallReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "guest made no progress; eip={:08x}", self.eip)?;
+        writeln!(f, "hot addresses:")?;
+        for (addr, label, count) in &self.hot {
+            write!(f, "  {addr:08x} {count:>6}")?;
+            if let Some(label) = label {
+                write!(f, " {label}")?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f, "stack:")?;
+        for (addr, value) in &self.stack {
+            writeln!(f, "  {addr:08x} {value:08x}")?;
+        }
+        Ok(())
+    }
+}
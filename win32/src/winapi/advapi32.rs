@@ -1,101 +0,0 @@
-#![allow(non_snake_case)]
-
-use super::types::Str16;
-use crate::machine::Machine;
-
-pub type HKEY = u32;
-
-#[win32_derive::dllexport]
-pub fn RegCreateKeyA(
-    _machine: &mut Machine,
-    hKey: HKEY,
-    lpSubKey: Option<&str>,
-    phkResult: Option<&mut u32>,
-) -> u32 {
-    0
-}
-
-#[win32_derive::dllexport]
-pub fn RegCreateKeyExW(
-    _machine: &mut Machine,
-    hKey: HKEY,
-    lpSubKey: Option<&Str16>,
-    Reserved: u32,
-    lpClass: Option<&Str16>,
-    dwOptions: u32,
-    samDesired: u32,
-    lpSecurityAttributes: u32,
-    phkResult: Option<&mut u32>,
-    lpdwDisposition: Option<&mut u32>,
-) -> u32 {
-    0 // success
-}
-
-#[win32_derive::dllexport]
-pub fn RegOpenKeyExA(
-    _machine: &mut Machine,
-    hKey: HKEY,
-    lpSubKey: Option<&str>,
-    ulOptions: u32,
-    samDesired: u32,
-    phkResult: Option<&mut HKEY>,
-) -> u32 {
-    todo!();
-}
-
-#[win32_derive::dllexport]
-pub fn RegCloseKey(_machine: &mut Machine, hKey: HKEY) -> u32 {
-    0 // success
-}
-
-#[win32_derive::dllexport]
-pub fn RegQueryValueExA(
-    _machine: &mut Machine,
-    hKey: HKEY,
-    lpValueName: Option<&str>,
-    lpReserved: u32,
-    lpType: Option<&mut u32>,
-    lpData: u32,
-    lpcbData: Option<&mut u32>,
-) -> u32 {
-    2 // ERROR_FILE_NOT_FOUND
-}
-
-#[win32_derive::dllexport]
-pub fn RegQueryValueExW(
-    _machine: &mut Machine,
-    hKey: HKEY,
-    lpValueName: Option<&Str16>,
-    lpReserved: u32,
-    lpType: Option<&mut u32>,
-    lpData: u32,
-    lpcbData: Option<&mut u32>,
-) -> u32 {
-    2 // ERROR_FILE_NOT_FOUND
-}
-
-#[win32_derive::dllexport]
-pub fn RegSetValueExA(
-    _machine: &mut Machine,
-    hKey: HKEY,
-    lpValueName: Option<&str>,
-    Reserved: u32,
-    dwType: u32,
-    lpData: u32,
-    cbData: u32,
-) -> u32 {
-    0 // success
-}
-
-#[win32_derive::dllexport]
-pub fn RegSetValueExW(
-    _machine: &mut Machine,
-    hKey: HKEY,
-    lpValueName: Option<&Str16>,
-    Reserved: u32,
-    dwType: u32,
-    lpData: u32,
-    cbData: u32,
-) -> u32 {
-    0 // success
-}
@@ -0,0 +1,362 @@
+//! The contents of the registry, and their serialization as regedit's .reg files,
+//! which is how hosts seed and persist the registry across runs.
+
+use std::collections::BTreeMap;
+
+pub const REG_NONE: u32 = 0;
+pub const REG_SZ: u32 = 1;
+pub const REG_EXPAND_SZ: u32 = 2;
+pub const REG_BINARY: u32 = 3;
+pub const REG_DWORD: u32 = 4;
+pub const REG_MULTI_SZ: u32 = 7;
+
+/// The predefined top-level keys.
+pub const ROOT_KEYS: [&str; 4] = [
+    "HKEY_CLASSES_ROOT",
+    "HKEY_CURRENT_USER",
+    "HKEY_LOCAL_MACHINE",
+    "HKEY_USERS",
+];
+
+/// Whether values of a type hold strings.  We store these as UTF-8, converting
+/// to UTF-16 for the W APIs.
+pub fn is_string_type(kind: u32) -> bool {
+    matches!(kind, REG_SZ | REG_EXPAND_SZ | REG_MULTI_SZ)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Value {
+    pub name: String,
+    pub kind: u32,
+    pub data: Vec<u8>,
+}
+
+/// A registry key.  Names are case-insensitive, so the maps are keyed by lowercased
+/// names, while the entries keep their original case.
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct Key {
+    pub name: String,
+    subkeys: BTreeMap<String, Key>,
+    values: BTreeMap<String, Value>,
+}
+
+impl Key {
+    fn new(name: &str) -> Self {
+        Key {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn subkey(&self, name: &str) -> Option<&Key> {
+        self.subkeys.get(&name.to_lowercase())
+    }
+
+    pub fn subkey_mut(&mut self, name: &str) -> Option<&mut Key> {
+        self.subkeys.get_mut(&name.to_lowercase())
+    }
+
+    /// Get a subkey, creating it if needed.  Also returns whether it was created.
+    pub fn create_subkey(&mut self, name: &str) -> (&mut Key, bool) {
+        let mut created = false;
+        let key = self.subkeys.entry(name.to_lowercase()).or_insert_with(|| {
+            created = true;
+            Key::new(name)
+        });
+        (key, created)
+    }
+
+    pub fn remove_subkey(&mut self, name: &str) -> Option<Key> {
+        self.subkeys.remove(&name.to_lowercase())
+    }
+
+    /// Subkeys, in a stable order.
+    pub fn subkeys(&self) -> impl Iterator<Item = &Key> {
+        self.subkeys.values()
+    }
+
+    /// Look up a value; the empty name is the key's default value.
+    pub fn value(&self, name: &str) -> Option<&Value> {
+        self.values.get(&name.to_lowercase())
+    }
+
+    pub fn set_value(&mut self, value: Value) {
+        self.values.insert(value.name.to_lowercase(), value);
+    }
+
+    pub fn remove_value(&mut self, name: &str) -> Option<Value> {
+        self.values.remove(&name.to_lowercase())
+    }
+
+    /// Values, in a stable order.
+    pub fn values(&self) -> impl Iterator<Item = &Value> {
+        self.values.values()
+    }
+}
+
+/// Split a registry path like `Software\Foo` into its components.
+pub fn split_path(path: &str) -> impl Iterator<Item = &str> {
+    path.split('\\').filter(|name| !name.is_empty())
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Hive {
+    /// The unnamed key above the root keys.
+    root: Key,
+}
+
+impl Default for Hive {
+    fn default() -> Self {
+        let mut root = Key::default();
+        for name in ROOT_KEYS {
+            root.create_subkey(name);
+        }
+        Hive { root }
+    }
+}
+
+impl Hive {
+    /// Find the key at a path of key names, starting with a root key name.
+    pub fn key<S: AsRef<str>>(&self, path: &[S]) -> Option<&Key> {
+        path.iter()
+            .try_fold(&self.root, |key, name| key.subkey(name.as_ref()))
+    }
+
+    pub fn key_mut<S: AsRef<str>>(&mut self, path: &[S]) -> Option<&mut Key> {
+        path.iter()
+            .try_fold(&mut self.root, |key, name| key.subkey_mut(name.as_ref()))
+    }
+
+    /// Parse the contents of a .reg file, in either the REGEDIT4 or the
+    /// "Windows Registry Editor Version 5.00" format.
+    pub fn parse(text: &str) -> Result<Hive, String> {
+        let mut hive = Hive::default();
+        // Version 5 files store hex(N) strings as UTF-16.
+        let mut utf16 = false;
+        let mut path: Option<Vec<String>> = None;
+
+        let mut lines = text.lines().enumerate();
+        while let Some((index, line)) = lines.next() {
+            let err = |msg: &str| format!("line {}: {}", index + 1, msg);
+            let mut line = line.trim().to_string();
+            // Hex data continues across lines ending in a backslash.
+            while line.ends_with('\\') {
+                line.pop();
+                match lines.next() {
+                    Some((_, next)) => line.push_str(next.trim()),
+                    None => break,
+                }
+            }
+
+            if line.is_empty() || line.starts_with(';') || line == "REGEDIT4" {
+                continue;
+            }
+            if line == "Windows Registry Editor Version 5.00" {
+                utf16 = true;
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[') {
+                let name = name.strip_suffix(']').ok_or_else(|| err("expected ]"))?;
+                if let Some(name) = name.strip_prefix('-') {
+                    let names = split_path(name).collect::<Vec<_>>();
+                    if let Some((last, parent)) = names.split_last() {
+                        if let Some(parent) = hive.key_mut(parent) {
+                            parent.remove_subkey(last);
+                        }
+                    }
+                    path = None;
+                    continue;
+                }
+                let names = split_path(name).map(str::to_string).collect::<Vec<_>>();
+                match names.first() {
+                    Some(root) if ROOT_KEYS.iter().any(|r| r.eq_ignore_ascii_case(root)) => {}
+                    _ => return Err(err("unknown root key")),
+                }
+                let mut key = &mut hive.root;
+                for name in &names {
+                    key = key.create_subkey(name).0;
+                }
+                path = Some(names);
+                continue;
+            }
+
+            let Some(path) = &path else {
+                return Err(err("value outside of a key"));
+            };
+            let (name, rest) = parse_value_name(&line).ok_or_else(|| err("bad value name"))?;
+            let data = rest
+                .strip_prefix('=')
+                .ok_or_else(|| err("expected ="))?
+                .trim();
+            let key = hive.key_mut(path).unwrap();
+            if data == "-" {
+                key.remove_value(&name);
+                continue;
+            }
+            let (kind, data) = parse_value_data(data, utf16).ok_or_else(|| err("bad value"))?;
+            key.set_value(Value { name, kind, data });
+        }
+        Ok(hive)
+    }
+
+    /// Serialize as a REGEDIT4 .reg file.
+    pub fn to_reg(&self) -> String {
+        let mut out = String::from("REGEDIT4\n");
+        for key in self.root.subkeys() {
+            write_key(&mut out, &key.name, key);
+        }
+        out
+    }
+}
+
+fn write_key(out: &mut String, path: &str, key: &Key) {
+    out.push_str(&format!("\n[{path}]\n"));
+    for value in key.values() {
+        match value.name.as_str() {
+            "" => out.push('@'),
+            name => out.push_str(&quote(name)),
+        }
+        out.push('=');
+        match value.kind {
+            REG_SZ if is_plain_string(&value.data) => {
+                let str = std::str::from_utf8(&value.data[..value.data.len() - 1]).unwrap();
+                out.push_str(&quote(str));
+            }
+            REG_DWORD if value.data.len() == 4 => {
+                let dword = u32::from_le_bytes(value.data[..].try_into().unwrap());
+                out.push_str(&format!("dword:{dword:08x}"));
+            }
+            kind => {
+                match kind {
+                    REG_BINARY => out.push_str("hex:"),
+                    kind => out.push_str(&format!("hex({kind:x}):")),
+                }
+                let bytes = value
+                    .data
+                    .iter()
+                    .map(|b| format!("{b:02x}"))
+                    .collect::<Vec<_>>();
+                out.push_str(&bytes.join(","));
+            }
+        }
+        out.push('\n');
+    }
+    for subkey in key.subkeys() {
+        write_key(out, &format!("{path}\\{}", subkey.name), subkey);
+    }
+}
+
+/// Whether string data can be written as a quoted string: UTF-8 with just a trailing nul.
+fn is_plain_string(data: &[u8]) -> bool {
+    match data.split_last() {
+        Some((0, str)) => !str.contains(&0) && std::str::from_utf8(str).is_ok(),
+        _ => false,
+    }
+}
+
+fn quote(str: &str) -> String {
+    format!("\"{}\"", str.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Parse a quoted string at the start of `text`, returning it and the text after it.
+fn parse_quoted(text: &str) -> Option<(String, &str)> {
+    let mut chars = text.strip_prefix('"')?.char_indices();
+    let mut str = String::new();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((str, &text[i + 2..])),
+            '\\' => str.push(chars.next()?.1),
+            c => str.push(c),
+        }
+    }
+    None
+}
+
+fn parse_value_name(line: &str) -> Option<(String, &str)> {
+    match line.strip_prefix('@') {
+        Some(rest) => Some((String::new(), rest.trim_start())),
+        None => {
+            let (name, rest) = parse_quoted(line)?;
+            Some((name, rest.trim_start()))
+        }
+    }
+}
+
+fn parse_value_data(data: &str, utf16: bool) -> Option<(u32, Vec<u8>)> {
+    if data.starts_with('"') {
+        let (str, rest) = parse_quoted(data)?;
+        if !rest.trim().is_empty() {
+            return None;
+        }
+        let mut bytes = str.into_bytes();
+        bytes.push(0);
+        return Some((REG_SZ, bytes));
+    }
+    if let Some(hex) = data.strip_prefix("dword:") {
+        let dword = u32::from_str_radix(hex, 16).ok()?;
+        return Some((REG_DWORD, dword.to_le_bytes().to_vec()));
+    }
+    let (kind, hex) = match data.strip_prefix("hex:") {
+        Some(hex) => (REG_BINARY, hex),
+        None => {
+            let (kind, hex) = data.strip_prefix("hex(")?.split_once("):")?;
+            (u32::from_str_radix(kind, 16).ok()?, hex)
+        }
+    };
+    let mut bytes = hex
+        .split(',')
+        .map(str::trim)
+        .filter(|b| !b.is_empty())
+        .map(|b| u8::from_str_radix(b, 16).ok())
+        .collect::<Option<Vec<_>>>()?;
+    if utf16 && is_string_type(kind) {
+        let chars = bytes
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect::<Vec<_>>();
+        bytes = String::from_utf16_lossy(&chars).into_bytes();
+    }
+    Some((kind, bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reg_roundtrip() {
+        let text = r#"REGEDIT4
+
+[HKEY_CURRENT_USER]
+
+[HKEY_CURRENT_USER\Software]
+
+[HKEY_CURRENT_USER\Software\Game]
+@="default"
+"Blob"=hex:01,02,\
+  ff
+"List"=hex(7):61,00,62,00,00
+"Path"="C:\\Games\\Game"
+"Quote"="say \"hi\""
+"Runs"=dword:0000002a
+"#;
+        let hive = Hive::parse(text).unwrap();
+        let key = hive
+            .key(&["hkey_current_user", "SOFTWARE", "game"])
+            .unwrap();
+        assert_eq!(key.name, "Game");
+        assert_eq!(key.value("").unwrap().data, b"default\0");
+        assert_eq!(key.value("path").unwrap().data, b"C:\\Games\\Game\0");
+        assert_eq!(key.value("Quote").unwrap().data, b"say \"hi\"\0");
+        assert_eq!(key.value("Runs").unwrap().data, 42u32.to_le_bytes());
+        assert_eq!(key.value("Blob").unwrap().data, [1, 2, 0xff]);
+        assert_eq!(key.value("List").unwrap().kind, REG_MULTI_SZ);
+
+        let text = text.replace("01,02,\\\n  ff", "01,02,ff");
+        let out = hive.to_reg();
+        assert!(out.starts_with("REGEDIT4\n\n[HKEY_CLASSES_ROOT]\n"));
+        assert!(out.contains(&text["REGEDIT4\n".len()..]));
+        assert_eq!(Hive::parse(&out).unwrap().to_reg(), out);
+    }
+}
@@ -0,0 +1,19 @@
+#![allow(non_snake_case)]
+
+mod hive;
+mod registry;
+mod user;
+
+pub use hive::*;
+pub use registry::*;
+pub use user::*;
+
+use super::handle::Handles;
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+pub struct State {
+    /// The registry's contents.
+    pub hive: Hive,
+    /// Open registry keys, as paths from the hive root.
+    keys: Handles<HKEY, Vec<String>>,
+}
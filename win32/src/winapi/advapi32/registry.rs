@@ -0,0 +1,472 @@
+//! The registry APIs, over the in-memory hive in hive.rs.
+
+use super::{is_string_type, split_path, Value, ROOT_KEYS};
+use crate::{
+    machine::Machine,
+    winapi::{handle::HANDLE, stack_args::ArrayWithSize, types::Str16, ERROR},
+};
+use memory::ExtensionsMut;
+
+pub struct HKEYT;
+pub type HKEY = HANDLE<HKEYT>;
+
+/// The predefined keys, HKEY_CLASSES_ROOT etc., in the order of ROOT_KEYS.
+const HKEY_CLASSES_ROOT: u32 = 0x8000_0000;
+
+const REG_CREATED_NEW_KEY: u32 = 1;
+const REG_OPENED_EXISTING_KEY: u32 = 2;
+
+/// Get the path of an open key.
+fn key_path(machine: &Machine, hKey: HKEY) -> Result<Vec<String>, ERROR> {
+    let raw = hKey.to_raw();
+    if let Some(root) = raw
+        .checked_sub(HKEY_CLASSES_ROOT)
+        .and_then(|i| ROOT_KEYS.get(i as usize))
+    {
+        return Ok(vec![root.to_string()]);
+    }
+    let path = machine
+        .state
+        .advapi32
+        .keys
+        .get(hKey)
+        .ok_or(ERROR::INVALID_HANDLE)?;
+    if machine.state.advapi32.hive.key(path).is_none() {
+        return Err(ERROR::KEY_DELETED);
+    }
+    Ok(path.clone())
+}
+
+/// Get the path of a subkey of an open key.
+fn subkey_path(machine: &Machine, hKey: HKEY, sub_key: Option<&str>) -> Result<Vec<String>, ERROR> {
+    let mut path = key_path(machine, hKey)?;
+    path.extend(split_path(sub_key.unwrap_or_default()).map(str::to_string));
+    Ok(path)
+}
+
+fn create_key(
+    machine: &mut Machine,
+    hKey: HKEY,
+    sub_key: Option<&str>,
+) -> Result<(HKEY, bool), ERROR> {
+    let path = subkey_path(machine, hKey, sub_key)?;
+    let (root, names) = path.split_first().unwrap();
+    let mut key = machine.state.advapi32.hive.key_mut(&[root]).unwrap();
+    let mut created = false;
+    for name in names {
+        (key, created) = key.create_subkey(name);
+    }
+    Ok((machine.state.advapi32.keys.add(path), created))
+}
+
+fn open_key(machine: &mut Machine, hKey: HKEY, sub_key: Option<&str>) -> Result<HKEY, ERROR> {
+    let path = subkey_path(machine, hKey, sub_key)?;
+    if machine.state.advapi32.hive.key(&path).is_none() {
+        return Err(ERROR::FILE_NOT_FOUND);
+    }
+    Ok(machine.state.advapi32.keys.add(path))
+}
+
+#[win32_derive::dllexport]
+pub fn RegCreateKeyA(
+    machine: &mut Machine,
+    hKey: HKEY,
+    lpSubKey: Option<&str>,
+    phkResult: Option<&mut HKEY>,
+) -> ERROR {
+    match create_key(machine, hKey, lpSubKey) {
+        Ok((key, _)) => {
+            *phkResult.unwrap() = key;
+            ERROR::SUCCESS
+        }
+        Err(err) => err,
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn RegCreateKeyExA(
+    machine: &mut Machine,
+    hKey: HKEY,
+    lpSubKey: Option<&str>,
+    Reserved: u32,
+    lpClass: Option<&str>,
+    dwOptions: u32,
+    samDesired: u32,
+    lpSecurityAttributes: u32,
+    phkResult: Option<&mut HKEY>,
+    lpdwDisposition: Option<&mut u32>,
+) -> ERROR {
+    match create_key(machine, hKey, lpSubKey) {
+        Ok((key, created)) => {
+            *phkResult.unwrap() = key;
+            if let Some(disposition) = lpdwDisposition {
+                *disposition = if created {
+                    REG_CREATED_NEW_KEY
+                } else {
+                    REG_OPENED_EXISTING_KEY
+                };
+            }
+            ERROR::SUCCESS
+        }
+        Err(err) => err,
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn RegCreateKeyExW(
+    machine: &mut Machine,
+    hKey: HKEY,
+    lpSubKey: Option<&Str16>,
+    Reserved: u32,
+    lpClass: Option<&Str16>,
+    dwOptions: u32,
+    samDesired: u32,
+    lpSecurityAttributes: u32,
+    phkResult: Option<&mut HKEY>,
+    lpdwDisposition: Option<&mut u32>,
+) -> ERROR {
+    RegCreateKeyExA(
+        machine,
+        hKey,
+        lpSubKey.map(|f| f.to_string()).as_deref(),
+        Reserved,
+        None,
+        dwOptions,
+        samDesired,
+        lpSecurityAttributes,
+        phkResult,
+        lpdwDisposition,
+    )
+}
+
+#[win32_derive::dllexport]
+pub fn RegOpenKeyA(
+    machine: &mut Machine,
+    hKey: HKEY,
+    lpSubKey: Option<&str>,
+    phkResult: Option<&mut HKEY>,
+) -> ERROR {
+    RegOpenKeyExA(machine, hKey, lpSubKey, 0, 0, phkResult)
+}
+
+#[win32_derive::dllexport]
+pub fn RegOpenKeyExA(
+    machine: &mut Machine,
+    hKey: HKEY,
+    lpSubKey: Option<&str>,
+    ulOptions: u32,
+    samDesired: u32,
+    phkResult: Option<&mut HKEY>,
+) -> ERROR {
+    match open_key(machine, hKey, lpSubKey) {
+        Ok(key) => {
+            *phkResult.unwrap() = key;
+            ERROR::SUCCESS
+        }
+        Err(err) => err,
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn RegOpenKeyExW(
+    machine: &mut Machine,
+    hKey: HKEY,
+    lpSubKey: Option<&Str16>,
+    ulOptions: u32,
+    samDesired: u32,
+    phkResult: Option<&mut HKEY>,
+) -> ERROR {
+    RegOpenKeyExA(
+        machine,
+        hKey,
+        lpSubKey.map(|f| f.to_string()).as_deref(),
+        ulOptions,
+        samDesired,
+        phkResult,
+    )
+}
+
+#[win32_derive::dllexport]
+pub fn RegCloseKey(machine: &mut Machine, hKey: HKEY) -> ERROR {
+    if hKey.to_raw() >= HKEY_CLASSES_ROOT {
+        return ERROR::SUCCESS; // predefined key
+    }
+    match machine.state.advapi32.keys.remove(hKey) {
+        Some(_) => ERROR::SUCCESS,
+        None => ERROR::INVALID_HANDLE,
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn RegDeleteKeyA(machine: &mut Machine, hKey: HKEY, lpSubKey: Option<&str>) -> ERROR {
+    let path = match subkey_path(machine, hKey, lpSubKey) {
+        Ok(path) => path,
+        Err(err) => return err,
+    };
+    let hive = &mut machine.state.advapi32.hive;
+    match hive.key(&path) {
+        None => return ERROR::FILE_NOT_FOUND,
+        // "The subkey to be deleted must not have subkeys."
+        Some(key) if key.subkeys().next().is_some() => return ERROR::ACCESS_DENIED,
+        // Root keys can't be deleted.
+        Some(_) if path.len() == 1 => return ERROR::ACCESS_DENIED,
+        Some(_) => {}
+    }
+    let (name, parent) = path.split_last().unwrap();
+    hive.key_mut(parent).unwrap().remove_subkey(name);
+    ERROR::SUCCESS
+}
+
+#[win32_derive::dllexport]
+pub fn RegDeleteKeyW(machine: &mut Machine, hKey: HKEY, lpSubKey: Option<&Str16>) -> ERROR {
+    RegDeleteKeyA(machine, hKey, lpSubKey.map(|f| f.to_string()).as_deref())
+}
+
+#[win32_derive::dllexport]
+pub fn RegDeleteValueA(machine: &mut Machine, hKey: HKEY, lpValueName: Option<&str>) -> ERROR {
+    let path = match key_path(machine, hKey) {
+        Ok(path) => path,
+        Err(err) => return err,
+    };
+    let key = machine.state.advapi32.hive.key_mut(&path).unwrap();
+    match key.remove_value(lpValueName.unwrap_or_default()) {
+        Some(_) => ERROR::SUCCESS,
+        None => ERROR::FILE_NOT_FOUND,
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn RegDeleteValueW(machine: &mut Machine, hKey: HKEY, lpValueName: Option<&Str16>) -> ERROR {
+    RegDeleteValueA(machine, hKey, lpValueName.map(|f| f.to_string()).as_deref())
+}
+
+/// Look up a value, with its data converted for the A or W APIs.
+fn query_value(
+    machine: &Machine,
+    hKey: HKEY,
+    name: Option<&str>,
+    wide: bool,
+) -> Result<(u32, Vec<u8>), ERROR> {
+    let path = key_path(machine, hKey)?;
+    let key = machine.state.advapi32.hive.key(&path).unwrap();
+    let value = key
+        .value(name.unwrap_or_default())
+        .ok_or(ERROR::FILE_NOT_FOUND)?;
+    let data = if wide && is_string_type(value.kind) {
+        String::from_utf8_lossy(&value.data)
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect()
+    } else {
+        value.data.clone()
+    };
+    Ok((value.kind, data))
+}
+
+/// Copy out a queried value, following the RegQueryValueEx protocol where a null
+/// buffer or a too-small size yields the needed size.
+fn copy_value(
+    machine: &mut Machine,
+    value: Result<(u32, Vec<u8>), ERROR>,
+    lpType: Option<&mut u32>,
+    lpData: u32,
+    lpcbData: Option<&mut u32>,
+) -> ERROR {
+    let (kind, data) = match value {
+        Ok(value) => value,
+        Err(err) => return err,
+    };
+    if let Some(ty) = lpType {
+        *ty = kind;
+    }
+    let Some(size) = lpcbData else {
+        return if lpData == 0 {
+            ERROR::SUCCESS
+        } else {
+            ERROR::INVALID_PARAMETER
+        };
+    };
+    let len = data.len() as u32;
+    if lpData == 0 {
+        *size = len;
+        return ERROR::SUCCESS;
+    }
+    if *size < len {
+        *size = len;
+        return ERROR::MORE_DATA;
+    }
+    match machine.mem().try_sub32_mut(lpData, len) {
+        Ok(buf) => buf.copy_from_slice(&data),
+        Err(err) => return err.into(),
+    }
+    *size = len;
+    ERROR::SUCCESS
+}
+
+#[win32_derive::dllexport]
+pub fn RegQueryValueExA(
+    machine: &mut Machine,
+    hKey: HKEY,
+    lpValueName: Option<&str>,
+    lpReserved: u32,
+    lpType: Option<&mut u32>,
+    lpData: u32,
+    lpcbData: Option<&mut u32>,
+) -> ERROR {
+    let value = query_value(machine, hKey, lpValueName, false);
+    copy_value(machine, value, lpType, lpData, lpcbData)
+}
+
+#[win32_derive::dllexport]
+pub fn RegQueryValueExW(
+    machine: &mut Machine,
+    hKey: HKEY,
+    lpValueName: Option<&Str16>,
+    lpReserved: u32,
+    lpType: Option<&mut u32>,
+    lpData: u32,
+    lpcbData: Option<&mut u32>,
+) -> ERROR {
+    let name = lpValueName.map(|f| f.to_string());
+    let value = query_value(machine, hKey, name.as_deref(), true);
+    copy_value(machine, value, lpType, lpData, lpcbData)
+}
+
+fn set_value(
+    machine: &mut Machine,
+    hKey: HKEY,
+    name: Option<&str>,
+    kind: u32,
+    data: &[u8],
+    wide: bool,
+) -> ERROR {
+    let path = match key_path(machine, hKey) {
+        Ok(path) => path,
+        Err(err) => return err,
+    };
+    let data = if wide && is_string_type(kind) {
+        let chars = data
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect::<Vec<_>>();
+        String::from_utf16_lossy(&chars).into_bytes()
+    } else {
+        data.to_vec()
+    };
+    let key = machine.state.advapi32.hive.key_mut(&path).unwrap();
+    key.set_value(Value {
+        name: name.unwrap_or_default().to_string(),
+        kind,
+        data,
+    });
+    ERROR::SUCCESS
+}
+
+#[win32_derive::dllexport]
+pub fn RegSetValueExA(
+    machine: &mut Machine,
+    hKey: HKEY,
+    lpValueName: Option<&str>,
+    Reserved: u32,
+    dwType: u32,
+    lpData: ArrayWithSize<u8>,
+) -> ERROR {
+    let data = lpData.unwrap_or_default();
+    set_value(machine, hKey, lpValueName, dwType, data, false)
+}
+
+#[win32_derive::dllexport]
+pub fn RegSetValueExW(
+    machine: &mut Machine,
+    hKey: HKEY,
+    lpValueName: Option<&Str16>,
+    Reserved: u32,
+    dwType: u32,
+    lpData: ArrayWithSize<u8>,
+) -> ERROR {
+    let name = lpValueName.map(|f| f.to_string());
+    let data = lpData.unwrap_or_default();
+    set_value(machine, hKey, name.as_deref(), dwType, data, true)
+}
+
+/// Get the name of the index'th subkey of an open key.
+fn enum_key(machine: &Machine, hKey: HKEY, index: u32) -> Result<String, ERROR> {
+    let path = key_path(machine, hKey)?;
+    let key = machine.state.advapi32.hive.key(&path).unwrap();
+    let subkey = key
+        .subkeys()
+        .nth(index as usize)
+        .ok_or(ERROR::NO_MORE_ITEMS)?;
+    Ok(subkey.name.clone())
+}
+
+#[win32_derive::dllexport]
+pub fn RegEnumKeyExA(
+    machine: &mut Machine,
+    hKey: HKEY,
+    dwIndex: u32,
+    lpName: u32,
+    lpcchName: Option<&mut u32>,
+    lpReserved: u32,
+    lpClass: u32,
+    lpcchClass: Option<&mut u32>,
+    lpftLastWriteTime: u32,
+) -> ERROR {
+    let name = match enum_key(machine, hKey, dwIndex) {
+        Ok(name) => name,
+        Err(err) => return err,
+    };
+    let size = lpcchName.unwrap();
+    let bytes = name.as_bytes();
+    if (*size as usize) < bytes.len() + 1 {
+        return ERROR::MORE_DATA;
+    }
+    let buf = match machine.mem().try_sub32_mut(lpName, bytes.len() as u32 + 1) {
+        Ok(buf) => buf,
+        Err(err) => return err.into(),
+    };
+    buf[..bytes.len()].copy_from_slice(bytes);
+    buf[bytes.len()] = 0;
+    *size = bytes.len() as u32;
+    if let Some(size) = lpcchClass {
+        *size = 0;
+    }
+    ERROR::SUCCESS
+}
+
+#[win32_derive::dllexport]
+pub fn RegEnumKeyExW(
+    machine: &mut Machine,
+    hKey: HKEY,
+    dwIndex: u32,
+    lpName: u32,
+    lpcchName: Option<&mut u32>,
+    lpReserved: u32,
+    lpClass: u32,
+    lpcchClass: Option<&mut u32>,
+    lpftLastWriteTime: u32,
+) -> ERROR {
+    let name = match enum_key(machine, hKey, dwIndex) {
+        Ok(name) => name,
+        Err(err) => return err,
+    };
+    let size = lpcchName.unwrap();
+    let chars = name.encode_utf16().collect::<Vec<_>>();
+    if (*size as usize) < chars.len() + 1 {
+        return ERROR::MORE_DATA;
+    }
+    let buf = match machine
+        .mem()
+        .try_sub32_mut(lpName, (chars.len() as u32 + 1) * 2)
+    {
+        Ok(buf) => Str16::from_bytes_mut(buf),
+        Err(err) => return err.into(),
+    };
+    buf[..chars.len()].copy_from_slice(&chars);
+    buf[chars.len()] = 0;
+    *size = chars.len() as u32;
+    if let Some(size) = lpcchClass {
+        *size = 0;
+    }
+    ERROR::SUCCESS
+}
@@ -0,0 +1,24 @@
+//! The logged-on user.
+
+use crate::{
+    machine::Machine,
+    winapi::{kernel32, ERROR},
+};
+
+#[win32_derive::dllexport]
+pub fn GetUserNameA(machine: &mut Machine, lpBuffer: u32, pcbBuffer: Option<&mut u32>) -> bool {
+    let Some(size) = pcbBuffer else {
+        kernel32::set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return false;
+    };
+    kernel32::get_user_name_ansi(machine, lpBuffer, size)
+}
+
+#[win32_derive::dllexport]
+pub fn GetUserNameW(machine: &mut Machine, lpBuffer: u32, pcbBuffer: Option<&mut u32>) -> bool {
+    let Some(size) = pcbBuffer else {
+        kernel32::set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return false;
+    };
+    kernel32::get_user_name_wide(machine, lpBuffer, size)
+}
@@ -154,6 +154,7 @@ pub trait Bitmap {
     fn height(&self) -> u32;
 }
 
+#[derive(serde::Serialize, serde::Deserialize)]
 pub enum PixelData<T> {
     Owned(Box<[T]>),
     Ptr(u32, u32),
@@ -174,7 +175,7 @@ impl<T: memory::Pod> PixelData<T> {
         match self {
             PixelData::Owned(b) => &*b,
             &PixelData::Ptr(addr, len) => {
-                let bytes = mem.sub32(addr, len);
+                let bytes = mem.try_sub32(addr, len).unwrap_or_else(|err| err.raise());
                 transmute_pixels::<T>(bytes)
             }
         }
@@ -188,6 +189,7 @@ impl<T: memory::Pod> PixelData<T> {
     }
 }
 
+#[derive(serde::Serialize, serde::Deserialize)]
 pub struct BitmapRGBA32 {
     pub width: u32,
     pub height: u32,
@@ -316,6 +318,7 @@ impl Bitmap for BitmapRGBA32 {
     }
 }
 
+#[derive(serde::Serialize, serde::Deserialize)]
 pub struct BitmapMono {
     pub width: u32,
     pub height: u32,
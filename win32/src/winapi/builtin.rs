@@ -20,12 +20,60 @@ pub mod advapi32 {
         };
         use memory::Extensions;
         use winapi::advapi32::*;
+        pub unsafe fn GetUserNameA(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpBuffer = <u32>::from_stack(mem, stack_args + 0u32);
+            let pcbBuffer = <Option<&mut u32>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("advapi32/user") {
+                Some(crate::trace::trace_begin(
+                    "advapi32/user",
+                    "GetUserNameA",
+                    &[("lpBuffer", &lpBuffer), ("pcbBuffer", &pcbBuffer)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::advapi32::GetUserNameA(machine, lpBuffer, pcbBuffer);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::advapi32::GetUserNameA_pos.0,
+                    winapi::advapi32::GetUserNameA_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn GetUserNameW(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpBuffer = <u32>::from_stack(mem, stack_args + 0u32);
+            let pcbBuffer = <Option<&mut u32>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("advapi32/user") {
+                Some(crate::trace::trace_begin(
+                    "advapi32/user",
+                    "GetUserNameW",
+                    &[("lpBuffer", &lpBuffer), ("pcbBuffer", &pcbBuffer)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::advapi32::GetUserNameW(machine, lpBuffer, pcbBuffer);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::advapi32::GetUserNameW_pos.0,
+                    winapi::advapi32::GetUserNameW_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
         pub unsafe fn RegCloseKey(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
             let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("advapi32") {
+            let __trace_context = if crate::trace::enabled("advapi32/registry") {
                 Some(crate::trace::trace_begin(
-                    "advapi32",
+                    "advapi32/registry",
                     "RegCloseKey",
                     &[("hKey", &hKey)],
                 ))
@@ -47,10 +95,10 @@ pub mod advapi32 {
             let mem = machine.mem().detach();
             let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
             let lpSubKey = <Option<&str>>::from_stack(mem, stack_args + 4u32);
-            let phkResult = <Option<&mut u32>>::from_stack(mem, stack_args + 8u32);
-            let __trace_context = if crate::trace::enabled("advapi32") {
+            let phkResult = <Option<&mut HKEY>>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("advapi32/registry") {
                 Some(crate::trace::trace_begin(
-                    "advapi32",
+                    "advapi32/registry",
                     "RegCreateKeyA",
                     &[
                         ("hKey", &hKey),
@@ -72,6 +120,58 @@ pub mod advapi32 {
             }
             result.to_raw()
         }
+        pub unsafe fn RegCreateKeyExA(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
+            let lpSubKey = <Option<&str>>::from_stack(mem, stack_args + 4u32);
+            let Reserved = <u32>::from_stack(mem, stack_args + 8u32);
+            let lpClass = <Option<&str>>::from_stack(mem, stack_args + 12u32);
+            let dwOptions = <u32>::from_stack(mem, stack_args + 16u32);
+            let samDesired = <u32>::from_stack(mem, stack_args + 20u32);
+            let lpSecurityAttributes = <u32>::from_stack(mem, stack_args + 24u32);
+            let phkResult = <Option<&mut HKEY>>::from_stack(mem, stack_args + 28u32);
+            let lpdwDisposition = <Option<&mut u32>>::from_stack(mem, stack_args + 32u32);
+            let __trace_context = if crate::trace::enabled("advapi32/registry") {
+                Some(crate::trace::trace_begin(
+                    "advapi32/registry",
+                    "RegCreateKeyExA",
+                    &[
+                        ("hKey", &hKey),
+                        ("lpSubKey", &lpSubKey),
+                        ("Reserved", &Reserved),
+                        ("lpClass", &lpClass),
+                        ("dwOptions", &dwOptions),
+                        ("samDesired", &samDesired),
+                        ("lpSecurityAttributes", &lpSecurityAttributes),
+                        ("phkResult", &phkResult),
+                        ("lpdwDisposition", &lpdwDisposition),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::advapi32::RegCreateKeyExA(
+                machine,
+                hKey,
+                lpSubKey,
+                Reserved,
+                lpClass,
+                dwOptions,
+                samDesired,
+                lpSecurityAttributes,
+                phkResult,
+                lpdwDisposition,
+            );
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::advapi32::RegCreateKeyExA_pos.0,
+                    winapi::advapi32::RegCreateKeyExA_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
         pub unsafe fn RegCreateKeyExW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
             let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
@@ -81,11 +181,11 @@ pub mod advapi32 {
             let dwOptions = <u32>::from_stack(mem, stack_args + 16u32);
             let samDesired = <u32>::from_stack(mem, stack_args + 20u32);
             let lpSecurityAttributes = <u32>::from_stack(mem, stack_args + 24u32);
-            let phkResult = <Option<&mut u32>>::from_stack(mem, stack_args + 28u32);
+            let phkResult = <Option<&mut HKEY>>::from_stack(mem, stack_args + 28u32);
             let lpdwDisposition = <Option<&mut u32>>::from_stack(mem, stack_args + 32u32);
-            let __trace_context = if crate::trace::enabled("advapi32") {
+            let __trace_context = if crate::trace::enabled("advapi32/registry") {
                 Some(crate::trace::trace_begin(
-                    "advapi32",
+                    "advapi32/registry",
                     "RegCreateKeyExW",
                     &[
                         ("hKey", &hKey),
@@ -124,6 +224,229 @@ pub mod advapi32 {
             }
             result.to_raw()
         }
+        pub unsafe fn RegDeleteKeyA(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
+            let lpSubKey = <Option<&str>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("advapi32/registry") {
+                Some(crate::trace::trace_begin(
+                    "advapi32/registry",
+                    "RegDeleteKeyA",
+                    &[("hKey", &hKey), ("lpSubKey", &lpSubKey)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::advapi32::RegDeleteKeyA(machine, hKey, lpSubKey);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::advapi32::RegDeleteKeyA_pos.0,
+                    winapi::advapi32::RegDeleteKeyA_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn RegDeleteKeyW(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
+            let lpSubKey = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("advapi32/registry") {
+                Some(crate::trace::trace_begin(
+                    "advapi32/registry",
+                    "RegDeleteKeyW",
+                    &[("hKey", &hKey), ("lpSubKey", &lpSubKey)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::advapi32::RegDeleteKeyW(machine, hKey, lpSubKey);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::advapi32::RegDeleteKeyW_pos.0,
+                    winapi::advapi32::RegDeleteKeyW_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn RegDeleteValueA(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
+            let lpValueName = <Option<&str>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("advapi32/registry") {
+                Some(crate::trace::trace_begin(
+                    "advapi32/registry",
+                    "RegDeleteValueA",
+                    &[("hKey", &hKey), ("lpValueName", &lpValueName)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::advapi32::RegDeleteValueA(machine, hKey, lpValueName);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::advapi32::RegDeleteValueA_pos.0,
+                    winapi::advapi32::RegDeleteValueA_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn RegDeleteValueW(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
+            let lpValueName = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("advapi32/registry") {
+                Some(crate::trace::trace_begin(
+                    "advapi32/registry",
+                    "RegDeleteValueW",
+                    &[("hKey", &hKey), ("lpValueName", &lpValueName)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::advapi32::RegDeleteValueW(machine, hKey, lpValueName);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::advapi32::RegDeleteValueW_pos.0,
+                    winapi::advapi32::RegDeleteValueW_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn RegEnumKeyExA(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
+            let dwIndex = <u32>::from_stack(mem, stack_args + 4u32);
+            let lpName = <u32>::from_stack(mem, stack_args + 8u32);
+            let lpcchName = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
+            let lpReserved = <u32>::from_stack(mem, stack_args + 16u32);
+            let lpClass = <u32>::from_stack(mem, stack_args + 20u32);
+            let lpcchClass = <Option<&mut u32>>::from_stack(mem, stack_args + 24u32);
+            let lpftLastWriteTime = <u32>::from_stack(mem, stack_args + 28u32);
+            let __trace_context = if crate::trace::enabled("advapi32/registry") {
+                Some(crate::trace::trace_begin(
+                    "advapi32/registry",
+                    "RegEnumKeyExA",
+                    &[
+                        ("hKey", &hKey),
+                        ("dwIndex", &dwIndex),
+                        ("lpName", &lpName),
+                        ("lpcchName", &lpcchName),
+                        ("lpReserved", &lpReserved),
+                        ("lpClass", &lpClass),
+                        ("lpcchClass", &lpcchClass),
+                        ("lpftLastWriteTime", &lpftLastWriteTime),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::advapi32::RegEnumKeyExA(
+                machine,
+                hKey,
+                dwIndex,
+                lpName,
+                lpcchName,
+                lpReserved,
+                lpClass,
+                lpcchClass,
+                lpftLastWriteTime,
+            );
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::advapi32::RegEnumKeyExA_pos.0,
+                    winapi::advapi32::RegEnumKeyExA_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn RegEnumKeyExW(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
+            let dwIndex = <u32>::from_stack(mem, stack_args + 4u32);
+            let lpName = <u32>::from_stack(mem, stack_args + 8u32);
+            let lpcchName = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
+            let lpReserved = <u32>::from_stack(mem, stack_args + 16u32);
+            let lpClass = <u32>::from_stack(mem, stack_args + 20u32);
+            let lpcchClass = <Option<&mut u32>>::from_stack(mem, stack_args + 24u32);
+            let lpftLastWriteTime = <u32>::from_stack(mem, stack_args + 28u32);
+            let __trace_context = if crate::trace::enabled("advapi32/registry") {
+                Some(crate::trace::trace_begin(
+                    "advapi32/registry",
+                    "RegEnumKeyExW",
+                    &[
+                        ("hKey", &hKey),
+                        ("dwIndex", &dwIndex),
+                        ("lpName", &lpName),
+                        ("lpcchName", &lpcchName),
+                        ("lpReserved", &lpReserved),
+                        ("lpClass", &lpClass),
+                        ("lpcchClass", &lpcchClass),
+                        ("lpftLastWriteTime", &lpftLastWriteTime),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::advapi32::RegEnumKeyExW(
+                machine,
+                hKey,
+                dwIndex,
+                lpName,
+                lpcchName,
+                lpReserved,
+                lpClass,
+                lpcchClass,
+                lpftLastWriteTime,
+            );
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::advapi32::RegEnumKeyExW_pos.0,
+                    winapi::advapi32::RegEnumKeyExW_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn RegOpenKeyA(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
+            let lpSubKey = <Option<&str>>::from_stack(mem, stack_args + 4u32);
+            let phkResult = <Option<&mut HKEY>>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("advapi32/registry") {
+                Some(crate::trace::trace_begin(
+                    "advapi32/registry",
+                    "RegOpenKeyA",
+                    &[
+                        ("hKey", &hKey),
+                        ("lpSubKey", &lpSubKey),
+                        ("phkResult", &phkResult),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::advapi32::RegOpenKeyA(machine, hKey, lpSubKey, phkResult);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::advapi32::RegOpenKeyA_pos.0,
+                    winapi::advapi32::RegOpenKeyA_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
         pub unsafe fn RegOpenKeyExA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
             let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
@@ -131,9 +454,9 @@ pub mod advapi32 {
             let ulOptions = <u32>::from_stack(mem, stack_args + 8u32);
             let samDesired = <u32>::from_stack(mem, stack_args + 12u32);
             let phkResult = <Option<&mut HKEY>>::from_stack(mem, stack_args + 16u32);
-            let __trace_context = if crate::trace::enabled("advapi32") {
+            let __trace_context = if crate::trace::enabled("advapi32/registry") {
                 Some(crate::trace::trace_begin(
-                    "advapi32",
+                    "advapi32/registry",
                     "RegOpenKeyExA",
                     &[
                         ("hKey", &hKey),
@@ -159,6 +482,41 @@ pub mod advapi32 {
             }
             result.to_raw()
         }
+        pub unsafe fn RegOpenKeyExW(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
+            let lpSubKey = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
+            let ulOptions = <u32>::from_stack(mem, stack_args + 8u32);
+            let samDesired = <u32>::from_stack(mem, stack_args + 12u32);
+            let phkResult = <Option<&mut HKEY>>::from_stack(mem, stack_args + 16u32);
+            let __trace_context = if crate::trace::enabled("advapi32/registry") {
+                Some(crate::trace::trace_begin(
+                    "advapi32/registry",
+                    "RegOpenKeyExW",
+                    &[
+                        ("hKey", &hKey),
+                        ("lpSubKey", &lpSubKey),
+                        ("ulOptions", &ulOptions),
+                        ("samDesired", &samDesired),
+                        ("phkResult", &phkResult),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::advapi32::RegOpenKeyExW(
+                machine, hKey, lpSubKey, ulOptions, samDesired, phkResult,
+            );
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::advapi32::RegOpenKeyExW_pos.0,
+                    winapi::advapi32::RegOpenKeyExW_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
         pub unsafe fn RegQueryValueExA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
             let hKey = <HKEY>::from_stack(mem, stack_args + 0u32);
@@ -167,9 +525,9 @@ pub mod advapi32 {
             let lpType = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
             let lpData = <u32>::from_stack(mem, stack_args + 16u32);
             let lpcbData = <Option<&mut u32>>::from_stack(mem, stack_args + 20u32);
-            let __trace_context = if crate::trace::enabled("advapi32") {
+            let __trace_context = if crate::trace::enabled("advapi32/registry") {
                 Some(crate::trace::trace_begin(
-                    "advapi32",
+                    "advapi32/registry",
                     "RegQueryValueExA",
                     &[
                         ("hKey", &hKey),
@@ -210,9 +568,9 @@ pub mod advapi32 {
             let lpType = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
             let lpData = <u32>::from_stack(mem, stack_args + 16u32);
             let lpcbData = <Option<&mut u32>>::from_stack(mem, stack_args + 20u32);
-            let __trace_context = if crate::trace::enabled("advapi32") {
+            let __trace_context = if crate::trace::enabled("advapi32/registry") {
                 Some(crate::trace::trace_begin(
-                    "advapi32",
+                    "advapi32/registry",
                     "RegQueryValueExW",
                     &[
                         ("hKey", &hKey),
@@ -251,11 +609,10 @@ pub mod advapi32 {
             let lpValueName = <Option<&str>>::from_stack(mem, stack_args + 4u32);
             let Reserved = <u32>::from_stack(mem, stack_args + 8u32);
             let dwType = <u32>::from_stack(mem, stack_args + 12u32);
-            let lpData = <u32>::from_stack(mem, stack_args + 16u32);
-            let cbData = <u32>::from_stack(mem, stack_args + 20u32);
-            let __trace_context = if crate::trace::enabled("advapi32") {
+            let lpData = <ArrayWithSize<u8>>::from_stack(mem, stack_args + 16u32);
+            let __trace_context = if crate::trace::enabled("advapi32/registry") {
                 Some(crate::trace::trace_begin(
-                    "advapi32",
+                    "advapi32/registry",
                     "RegSetValueExA",
                     &[
                         ("hKey", &hKey),
@@ -263,7 +620,6 @@ pub mod advapi32 {
                         ("Reserved", &Reserved),
                         ("dwType", &dwType),
                         ("lpData", &lpData),
-                        ("cbData", &cbData),
                     ],
                 ))
             } else {
@@ -276,7 +632,6 @@ pub mod advapi32 {
                 Reserved,
                 dwType,
                 lpData,
-                cbData,
             );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
@@ -294,11 +649,10 @@ pub mod advapi32 {
             let lpValueName = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
             let Reserved = <u32>::from_stack(mem, stack_args + 8u32);
             let dwType = <u32>::from_stack(mem, stack_args + 12u32);
-            let lpData = <u32>::from_stack(mem, stack_args + 16u32);
-            let cbData = <u32>::from_stack(mem, stack_args + 20u32);
-            let __trace_context = if crate::trace::enabled("advapi32") {
+            let lpData = <ArrayWithSize<u8>>::from_stack(mem, stack_args + 16u32);
+            let __trace_context = if crate::trace::enabled("advapi32/registry") {
                 Some(crate::trace::trace_begin(
-                    "advapi32",
+                    "advapi32/registry",
                     "RegSetValueExW",
                     &[
                         ("hKey", &hKey),
@@ -306,7 +660,6 @@ pub mod advapi32 {
                         ("Reserved", &Reserved),
                         ("dwType", &dwType),
                         ("lpData", &lpData),
-                        ("cbData", &cbData),
                     ],
                 ))
             } else {
@@ -319,7 +672,6 @@ pub mod advapi32 {
                 Reserved,
                 dwType,
                 lpData,
-                cbData,
             );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
@@ -332,38 +684,101 @@ pub mod advapi32 {
             result.to_raw()
         }
     }
-    const SHIMS: [Shim; 8usize] = [
+    const SHIMS: [Shim; 19usize] = [
+        Shim {
+            name: "GetUserNameA",
+            func: Handler::Sync(wrappers::GetUserNameA),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "GetUserNameW",
+            func: Handler::Sync(wrappers::GetUserNameW),
+            stack_consumed: 8u32,
+        },
         Shim {
             name: "RegCloseKey",
             func: Handler::Sync(wrappers::RegCloseKey),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "RegCreateKeyA",
             func: Handler::Sync(wrappers::RegCreateKeyA),
+            stack_consumed: 12u32,
+        },
+        Shim {
+            name: "RegCreateKeyExA",
+            func: Handler::Sync(wrappers::RegCreateKeyExA),
+            stack_consumed: 36u32,
         },
         Shim {
             name: "RegCreateKeyExW",
             func: Handler::Sync(wrappers::RegCreateKeyExW),
+            stack_consumed: 36u32,
         },
         Shim {
-            name: "RegOpenKeyExA",
-            func: Handler::Sync(wrappers::RegOpenKeyExA),
+            name: "RegDeleteKeyA",
+            func: Handler::Sync(wrappers::RegDeleteKeyA),
+            stack_consumed: 8u32,
         },
         Shim {
-            name: "RegQueryValueExA",
-            func: Handler::Sync(wrappers::RegQueryValueExA),
+            name: "RegDeleteKeyW",
+            func: Handler::Sync(wrappers::RegDeleteKeyW),
+            stack_consumed: 8u32,
         },
         Shim {
-            name: "RegQueryValueExW",
-            func: Handler::Sync(wrappers::RegQueryValueExW),
+            name: "RegDeleteValueA",
+            func: Handler::Sync(wrappers::RegDeleteValueA),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "RegDeleteValueW",
+            func: Handler::Sync(wrappers::RegDeleteValueW),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "RegEnumKeyExA",
+            func: Handler::Sync(wrappers::RegEnumKeyExA),
+            stack_consumed: 32u32,
+        },
+        Shim {
+            name: "RegEnumKeyExW",
+            func: Handler::Sync(wrappers::RegEnumKeyExW),
+            stack_consumed: 32u32,
+        },
+        Shim {
+            name: "RegOpenKeyA",
+            func: Handler::Sync(wrappers::RegOpenKeyA),
+            stack_consumed: 12u32,
+        },
+        Shim {
+            name: "RegOpenKeyExA",
+            func: Handler::Sync(wrappers::RegOpenKeyExA),
+            stack_consumed: 20u32,
+        },
+        Shim {
+            name: "RegOpenKeyExW",
+            func: Handler::Sync(wrappers::RegOpenKeyExW),
+            stack_consumed: 20u32,
+        },
+        Shim {
+            name: "RegQueryValueExA",
+            func: Handler::Sync(wrappers::RegQueryValueExA),
+            stack_consumed: 24u32,
+        },
+        Shim {
+            name: "RegQueryValueExW",
+            func: Handler::Sync(wrappers::RegQueryValueExW),
+            stack_consumed: 24u32,
         },
         Shim {
             name: "RegSetValueExA",
             func: Handler::Sync(wrappers::RegSetValueExA),
+            stack_consumed: 24u32,
         },
         Shim {
             name: "RegSetValueExW",
             func: Handler::Sync(wrappers::RegSetValueExW),
+            stack_consumed: 24u32,
         },
     ];
     pub const DLL: BuiltinDLL = BuiltinDLL {
@@ -561,30 +976,37 @@ pub mod bass {
         Shim {
             name: "BASS_ChannelGetPosition",
             func: Handler::Sync(wrappers::BASS_ChannelGetPosition),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "BASS_Free",
             func: Handler::Sync(wrappers::BASS_Free),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "BASS_Init",
             func: Handler::Sync(wrappers::BASS_Init),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "BASS_MusicLoad",
             func: Handler::Sync(wrappers::BASS_MusicLoad),
+            stack_consumed: 20u32,
         },
         Shim {
             name: "BASS_MusicPlay",
             func: Handler::Sync(wrappers::BASS_MusicPlay),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "BASS_MusicSetPositionScaler",
             func: Handler::Sync(wrappers::BASS_MusicSetPositionScaler),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "BASS_Start",
             func: Handler::Sync(wrappers::BASS_Start),
+            stack_consumed: 0u32,
         },
     ];
     pub const DLL: BuiltinDLL = BuiltinDLL {
@@ -2136,210 +2558,262 @@ pub mod ddraw {
         Shim {
             name: "DirectDrawCreate",
             func: Handler::Sync(wrappers::DirectDrawCreate),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "DirectDrawCreateClipper",
             func: Handler::Sync(wrappers::DirectDrawCreateClipper),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "DirectDrawCreateEx",
             func: Handler::Sync(wrappers::DirectDrawCreateEx),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "IDirectDraw2::CreateSurface",
             func: Handler::Sync(wrappers::IDirectDraw2_CreateSurface),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "IDirectDraw2::EnumDisplayModes",
             func: Handler::Async(wrappers::IDirectDraw2_EnumDisplayModes),
+            stack_consumed: 20u32,
         },
         Shim {
             name: "IDirectDraw2::GetDisplayMode",
             func: Handler::Sync(wrappers::IDirectDraw2_GetDisplayMode),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "IDirectDraw2::QueryInterface",
             func: Handler::Sync(wrappers::IDirectDraw2_QueryInterface),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "IDirectDraw2::Release",
             func: Handler::Sync(wrappers::IDirectDraw2_Release),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "IDirectDraw2::SetDisplayMode",
             func: Handler::Sync(wrappers::IDirectDraw2_SetDisplayMode),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "IDirectDraw7::CreatePalette",
             func: Handler::Sync(wrappers::IDirectDraw7_CreatePalette),
+            stack_consumed: 20u32,
         },
         Shim {
             name: "IDirectDraw7::CreateSurface",
             func: Handler::Sync(wrappers::IDirectDraw7_CreateSurface),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "IDirectDraw7::EnumDisplayModes",
             func: Handler::Async(wrappers::IDirectDraw7_EnumDisplayModes),
+            stack_consumed: 20u32,
         },
         Shim {
             name: "IDirectDraw7::GetDisplayMode",
             func: Handler::Sync(wrappers::IDirectDraw7_GetDisplayMode),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "IDirectDraw7::Release",
             func: Handler::Sync(wrappers::IDirectDraw7_Release),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "IDirectDraw7::RestoreDisplayMode",
             func: Handler::Sync(wrappers::IDirectDraw7_RestoreDisplayMode),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "IDirectDraw7::SetCooperativeLevel",
             func: Handler::Sync(wrappers::IDirectDraw7_SetCooperativeLevel),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "IDirectDraw7::SetDisplayMode",
             func: Handler::Sync(wrappers::IDirectDraw7_SetDisplayMode),
+            stack_consumed: 24u32,
         },
         Shim {
             name: "IDirectDraw7::WaitForVerticalBlank",
             func: Handler::Sync(wrappers::IDirectDraw7_WaitForVerticalBlank),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "IDirectDrawClipper::Release",
             func: Handler::Sync(wrappers::IDirectDrawClipper_Release),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "IDirectDrawClipper::SetHWnd",
             func: Handler::Sync(wrappers::IDirectDrawClipper_SetHWnd),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "IDirectDrawPalette::Release",
             func: Handler::Sync(wrappers::IDirectDrawPalette_Release),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "IDirectDrawPalette::SetEntries",
             func: Handler::Sync(wrappers::IDirectDrawPalette_SetEntries),
+            stack_consumed: 20u32,
         },
         Shim {
             name: "IDirectDrawSurface2::GetAttachedSurface",
             func: Handler::Sync(wrappers::IDirectDrawSurface2_GetAttachedSurface),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "IDirectDrawSurface2::GetCaps",
             func: Handler::Sync(wrappers::IDirectDrawSurface2_GetCaps),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "IDirectDrawSurface2::GetSurfaceDesc",
             func: Handler::Sync(wrappers::IDirectDrawSurface2_GetSurfaceDesc),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "IDirectDrawSurface2::Lock",
             func: Handler::Sync(wrappers::IDirectDrawSurface2_Lock),
+            stack_consumed: 20u32,
         },
         Shim {
             name: "IDirectDrawSurface2::Release",
             func: Handler::Sync(wrappers::IDirectDrawSurface2_Release),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "IDirectDrawSurface2::Unlock",
             func: Handler::Sync(wrappers::IDirectDrawSurface2_Unlock),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "IDirectDrawSurface7::Blt",
             func: Handler::Sync(wrappers::IDirectDrawSurface7_Blt),
+            stack_consumed: 24u32,
         },
         Shim {
             name: "IDirectDrawSurface7::BltFast",
             func: Handler::Sync(wrappers::IDirectDrawSurface7_BltFast),
+            stack_consumed: 24u32,
         },
         Shim {
             name: "IDirectDrawSurface7::Flip",
             func: Handler::Sync(wrappers::IDirectDrawSurface7_Flip),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "IDirectDrawSurface7::GetAttachedSurface",
             func: Handler::Sync(wrappers::IDirectDrawSurface7_GetAttachedSurface),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "IDirectDrawSurface7::GetCaps",
             func: Handler::Sync(wrappers::IDirectDrawSurface7_GetCaps),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "IDirectDrawSurface7::GetDC",
             func: Handler::Sync(wrappers::IDirectDrawSurface7_GetDC),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "IDirectDrawSurface7::GetPixelFormat",
             func: Handler::Sync(wrappers::IDirectDrawSurface7_GetPixelFormat),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "IDirectDrawSurface7::GetSurfaceDesc",
             func: Handler::Sync(wrappers::IDirectDrawSurface7_GetSurfaceDesc),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "IDirectDrawSurface7::Lock",
             func: Handler::Sync(wrappers::IDirectDrawSurface7_Lock),
+            stack_consumed: 20u32,
         },
         Shim {
             name: "IDirectDrawSurface7::Release",
             func: Handler::Sync(wrappers::IDirectDrawSurface7_Release),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "IDirectDrawSurface7::ReleaseDC",
             func: Handler::Sync(wrappers::IDirectDrawSurface7_ReleaseDC),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "IDirectDrawSurface7::Restore",
             func: Handler::Sync(wrappers::IDirectDrawSurface7_Restore),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "IDirectDrawSurface7::SetClipper",
             func: Handler::Sync(wrappers::IDirectDrawSurface7_SetClipper),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "IDirectDrawSurface7::SetPalette",
             func: Handler::Sync(wrappers::IDirectDrawSurface7_SetPalette),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "IDirectDrawSurface7::Unlock",
             func: Handler::Sync(wrappers::IDirectDrawSurface7_Unlock),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "IDirectDrawSurface::GetAttachedSurface",
             func: Handler::Sync(wrappers::IDirectDrawSurface_GetAttachedSurface),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "IDirectDrawSurface::GetCaps",
             func: Handler::Sync(wrappers::IDirectDrawSurface_GetCaps),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "IDirectDrawSurface::Lock",
             func: Handler::Sync(wrappers::IDirectDrawSurface_Lock),
+            stack_consumed: 20u32,
         },
         Shim {
             name: "IDirectDrawSurface::Release",
             func: Handler::Sync(wrappers::IDirectDrawSurface_Release),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "IDirectDrawSurface::Unlock",
             func: Handler::Sync(wrappers::IDirectDrawSurface_Unlock),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "IDirectDraw::CreateSurface",
             func: Handler::Sync(wrappers::IDirectDraw_CreateSurface),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "IDirectDraw::QueryInterface",
             func: Handler::Sync(wrappers::IDirectDraw_QueryInterface),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "IDirectDraw::Release",
             func: Handler::Sync(wrappers::IDirectDraw_Release),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "IDirectDraw::SetDisplayMode",
             func: Handler::Sync(wrappers::IDirectDraw_SetDisplayMode),
+            stack_consumed: 16u32,
         },
     ];
     pub const DLL: BuiltinDLL = BuiltinDLL {
@@ -2745,50 +3219,62 @@ pub mod dsound {
         Shim {
             name: "DirectSoundCreate",
             func: Handler::Sync(wrappers::DirectSoundCreate),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "DirectSoundEnumerateA",
             func: Handler::Sync(wrappers::DirectSoundEnumerateA),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "IDirectSoundBuffer::GetCurrentPosition",
             func: Handler::Sync(wrappers::IDirectSoundBuffer_GetCurrentPosition),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "IDirectSoundBuffer::GetStatus",
             func: Handler::Sync(wrappers::IDirectSoundBuffer_GetStatus),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "IDirectSoundBuffer::Lock",
             func: Handler::Sync(wrappers::IDirectSoundBuffer_Lock),
+            stack_consumed: 32u32,
         },
         Shim {
             name: "IDirectSoundBuffer::Play",
             func: Handler::Sync(wrappers::IDirectSoundBuffer_Play),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "IDirectSoundBuffer::Release",
             func: Handler::Sync(wrappers::IDirectSoundBuffer_Release),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "IDirectSoundBuffer::SetFormat",
             func: Handler::Sync(wrappers::IDirectSoundBuffer_SetFormat),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "IDirectSoundBuffer::Unlock",
             func: Handler::Sync(wrappers::IDirectSoundBuffer_Unlock),
+            stack_consumed: 20u32,
         },
         Shim {
             name: "IDirectSound::CreateSoundBuffer",
             func: Handler::Sync(wrappers::IDirectSound_CreateSoundBuffer),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "IDirectSound::Release",
             func: Handler::Sync(wrappers::IDirectSound_Release),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "IDirectSound::SetCooperativeLevel",
             func: Handler::Sync(wrappers::IDirectSound_SetCooperativeLevel),
+            stack_consumed: 12u32,
         },
     ];
     pub const DLL: BuiltinDLL = BuiltinDLL {
@@ -3150,6 +3636,88 @@ pub mod gdi32 {
             }
             result.to_raw()
         }
+        pub unsafe fn EnumFontFamiliesA(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let hdc = <HDC>::from_stack(mem, stack_args + 0u32);
+            let lpLogfont = <Option<&str>>::from_stack(mem, stack_args + 4u32);
+            let lpProc = <u32>::from_stack(mem, stack_args + 8u32);
+            let lParam = <u32>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("gdi32/text") {
+                Some(crate::trace::trace_begin(
+                    "gdi32/text",
+                    "EnumFontFamiliesA",
+                    &[
+                        ("hdc", &hdc),
+                        ("lpLogfont", &lpLogfont),
+                        ("lpProc", &lpProc),
+                        ("lParam", &lParam),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result =
+                    winapi::gdi32::EnumFontFamiliesA(machine, hdc, lpLogfont, lpProc, lParam).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::gdi32::EnumFontFamiliesA_pos.0,
+                        winapi::gdi32::EnumFontFamiliesA_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn EnumFontFamiliesExA(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let hdc = <HDC>::from_stack(mem, stack_args + 0u32);
+            let lpLogfont = <Option<&LOGFONTA>>::from_stack(mem, stack_args + 4u32);
+            let lpProc = <u32>::from_stack(mem, stack_args + 8u32);
+            let lParam = <u32>::from_stack(mem, stack_args + 12u32);
+            let dwFlags = <u32>::from_stack(mem, stack_args + 16u32);
+            let __trace_context = if crate::trace::enabled("gdi32/text") {
+                Some(crate::trace::trace_begin(
+                    "gdi32/text",
+                    "EnumFontFamiliesExA",
+                    &[
+                        ("hdc", &hdc),
+                        ("lpLogfont", &lpLogfont),
+                        ("lpProc", &lpProc),
+                        ("lParam", &lParam),
+                        ("dwFlags", &dwFlags),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::gdi32::EnumFontFamiliesExA(
+                    machine, hdc, lpLogfont, lpProc, lParam, dwFlags,
+                )
+                .await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::gdi32::EnumFontFamiliesExA_pos.0,
+                        winapi::gdi32::EnumFontFamiliesExA_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
         pub unsafe fn GetDCOrgEx(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
             let hdc = <HDC>::from_stack(mem, stack_args + 0u32);
@@ -3404,7 +3972,10 @@ pub mod gdi32 {
             }
             result.to_raw()
         }
-        pub unsafe fn LineDDA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn LineDDA(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
             let mem = machine.mem().detach();
             let xStart = <i32>::from_stack(mem, stack_args + 0u32);
             let yStart = <i32>::from_stack(mem, stack_args + 4u32);
@@ -3428,16 +3999,21 @@ pub mod gdi32 {
             } else {
                 None
             };
-            let result = winapi::gdi32::LineDDA(machine, xStart, yStart, xEnd, yEnd, lpProc, data);
-            if let Some(__trace_context) = __trace_context {
-                crate::trace::trace_return(
-                    &__trace_context,
-                    winapi::gdi32::LineDDA_pos.0,
-                    winapi::gdi32::LineDDA_pos.1,
-                    &result,
-                );
-            }
-            result.to_raw()
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result =
+                    winapi::gdi32::LineDDA(machine, xStart, yStart, xEnd, yEnd, lpProc, data).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::gdi32::LineDDA_pos.0,
+                        winapi::gdi32::LineDDA_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
         }
         pub unsafe fn LineTo(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
@@ -3972,166 +4548,216 @@ pub mod gdi32 {
             result.to_raw()
         }
     }
-    const SHIMS: [Shim; 40usize] = [
+    const SHIMS: [Shim; 42usize] = [
         Shim {
             name: "BitBlt",
             func: Handler::Sync(wrappers::BitBlt),
+            stack_consumed: 36u32,
         },
         Shim {
             name: "CreateBitmap",
             func: Handler::Sync(wrappers::CreateBitmap),
+            stack_consumed: 20u32,
         },
         Shim {
             name: "CreateCompatibleBitmap",
             func: Handler::Sync(wrappers::CreateCompatibleBitmap),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "CreateCompatibleDC",
             func: Handler::Sync(wrappers::CreateCompatibleDC),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "CreateDIBSection",
             func: Handler::Sync(wrappers::CreateDIBSection),
+            stack_consumed: 24u32,
         },
         Shim {
             name: "CreateFontA",
             func: Handler::Sync(wrappers::CreateFontA),
+            stack_consumed: 56u32,
         },
         Shim {
             name: "CreatePalette",
             func: Handler::Sync(wrappers::CreatePalette),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "CreatePen",
             func: Handler::Sync(wrappers::CreatePen),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "CreateSolidBrush",
             func: Handler::Sync(wrappers::CreateSolidBrush),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "DeleteDC",
             func: Handler::Sync(wrappers::DeleteDC),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "DeleteObject",
             func: Handler::Sync(wrappers::DeleteObject),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "EnumFontFamiliesA",
+            func: Handler::Async(wrappers::EnumFontFamiliesA),
+            stack_consumed: 16u32,
+        },
+        Shim {
+            name: "EnumFontFamiliesExA",
+            func: Handler::Async(wrappers::EnumFontFamiliesExA),
+            stack_consumed: 20u32,
         },
         Shim {
             name: "GetDCOrgEx",
             func: Handler::Sync(wrappers::GetDCOrgEx),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "GetDeviceCaps",
             func: Handler::Sync(wrappers::GetDeviceCaps),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "GetLayout",
             func: Handler::Sync(wrappers::GetLayout),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "GetObjectA",
             func: Handler::Sync(wrappers::GetObjectA),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "GetPixel",
             func: Handler::Sync(wrappers::GetPixel),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "GetStockObject",
             func: Handler::Sync(wrappers::GetStockObject),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "GetTextExtentPoint32A",
             func: Handler::Sync(wrappers::GetTextExtentPoint32A),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "GetTextExtentPoint32W",
             func: Handler::Sync(wrappers::GetTextExtentPoint32W),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "GetTextMetricsA",
             func: Handler::Sync(wrappers::GetTextMetricsA),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "GetTextMetricsW",
             func: Handler::Sync(wrappers::GetTextMetricsW),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "LineDDA",
-            func: Handler::Sync(wrappers::LineDDA),
+            func: Handler::Async(wrappers::LineDDA),
+            stack_consumed: 24u32,
         },
         Shim {
             name: "LineTo",
             func: Handler::Sync(wrappers::LineTo),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "MoveToEx",
             func: Handler::Sync(wrappers::MoveToEx),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "PatBlt",
             func: Handler::Sync(wrappers::PatBlt),
+            stack_consumed: 24u32,
         },
         Shim {
             name: "PtVisible",
             func: Handler::Sync(wrappers::PtVisible),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "SelectObject",
             func: Handler::Sync(wrappers::SelectObject),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "SetBkColor",
             func: Handler::Sync(wrappers::SetBkColor),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "SetBkMode",
             func: Handler::Sync(wrappers::SetBkMode),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "SetBrushOrgEx",
             func: Handler::Sync(wrappers::SetBrushOrgEx),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "SetDIBitsToDevice",
             func: Handler::Sync(wrappers::SetDIBitsToDevice),
+            stack_consumed: 48u32,
         },
         Shim {
             name: "SetLayout",
             func: Handler::Sync(wrappers::SetLayout),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "SetPixel",
             func: Handler::Sync(wrappers::SetPixel),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "SetROP2",
             func: Handler::Sync(wrappers::SetROP2),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "SetTextAlign",
             func: Handler::Sync(wrappers::SetTextAlign),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "SetTextColor",
             func: Handler::Sync(wrappers::SetTextColor),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "StretchBlt",
             func: Handler::Sync(wrappers::StretchBlt),
+            stack_consumed: 44u32,
         },
         Shim {
             name: "StretchDIBits",
             func: Handler::Sync(wrappers::StretchDIBits),
+            stack_consumed: 52u32,
         },
         Shim {
             name: "TextOutA",
             func: Handler::Sync(wrappers::TextOutA),
+            stack_consumed: 20u32,
         },
         Shim {
             name: "TextOutW",
             func: Handler::Sync(wrappers::TextOutW),
+            stack_consumed: 20u32,
         },
     ];
     pub const DLL: BuiltinDLL = BuiltinDLL {
@@ -4219,213 +4845,160 @@ pub mod kernel32 {
             }
             result.to_raw()
         }
-        pub unsafe fn CloseHandle(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn AllocConsole(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hObject = <HFILE>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/misc") {
+            let __trace_context = if crate::trace::enabled("kernel32/console") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/misc",
-                    "CloseHandle",
-                    &[("hObject", &hObject)],
+                    "kernel32/console",
+                    "AllocConsole",
+                    &[],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::CloseHandle(machine, hObject);
+            let result = winapi::kernel32::AllocConsole(machine);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::CloseHandle_pos.0,
-                    winapi::kernel32::CloseHandle_pos.1,
+                    winapi::kernel32::AllocConsole_pos.0,
+                    winapi::kernel32::AllocConsole_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn CreateDirectoryA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn AttachConsole(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpPathName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
-            let lpSecurityAttributes = <u32>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/file") {
+            let dwProcessId = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/console") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/file",
-                    "CreateDirectoryA",
-                    &[
-                        ("lpPathName", &lpPathName),
-                        ("lpSecurityAttributes", &lpSecurityAttributes),
-                    ],
+                    "kernel32/console",
+                    "AttachConsole",
+                    &[("dwProcessId", &dwProcessId)],
                 ))
             } else {
                 None
             };
-            let result =
-                winapi::kernel32::CreateDirectoryA(machine, lpPathName, lpSecurityAttributes);
+            let result = winapi::kernel32::AttachConsole(machine, dwProcessId);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::CreateDirectoryA_pos.0,
-                    winapi::kernel32::CreateDirectoryA_pos.1,
+                    winapi::kernel32::AttachConsole_pos.0,
+                    winapi::kernel32::AttachConsole_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn CreateEventA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CloseHandle(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpEventAttributes = <u32>::from_stack(mem, stack_args + 0u32);
-            let bManualReset = <bool>::from_stack(mem, stack_args + 4u32);
-            let bInitialState = <bool>::from_stack(mem, stack_args + 8u32);
-            let lpName = <Option<&str>>::from_stack(mem, stack_args + 12u32);
-            let __trace_context = if crate::trace::enabled("kernel32/sync") {
+            let hObject = <HOBJECT>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/misc") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/sync",
-                    "CreateEventA",
-                    &[
-                        ("lpEventAttributes", &lpEventAttributes),
-                        ("bManualReset", &bManualReset),
-                        ("bInitialState", &bInitialState),
-                        ("lpName", &lpName),
-                    ],
+                    "kernel32/misc",
+                    "CloseHandle",
+                    &[("hObject", &hObject)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::CreateEventA(
-                machine,
-                lpEventAttributes,
-                bManualReset,
-                bInitialState,
-                lpName,
-            );
+            let result = winapi::kernel32::CloseHandle(machine, hObject);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::CreateEventA_pos.0,
-                    winapi::kernel32::CreateEventA_pos.1,
+                    winapi::kernel32::CloseHandle_pos.0,
+                    winapi::kernel32::CloseHandle_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn CreateFileA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CompareStringA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpFileName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
-            let dwDesiredAccess = <u32>::from_stack(mem, stack_args + 4u32);
-            let dwShareMode = <u32>::from_stack(mem, stack_args + 8u32);
-            let lpSecurityAttributes = <u32>::from_stack(mem, stack_args + 12u32);
-            let dwCreationDisposition =
-                <Result<CreationDisposition, u32>>::from_stack(mem, stack_args + 16u32);
-            let dwFlagsAndAttributes =
-                <Result<FileAttribute, u32>>::from_stack(mem, stack_args + 20u32);
-            let hTemplateFile = <HFILE>::from_stack(mem, stack_args + 24u32);
-            let __trace_context = if crate::trace::enabled("kernel32/file") {
+            let Locale = <LCID>::from_stack(mem, stack_args + 0u32);
+            let dwCmpFlags = <u32>::from_stack(mem, stack_args + 4u32);
+            let lpString1 = <u32>::from_stack(mem, stack_args + 8u32);
+            let cchCount1 = <i32>::from_stack(mem, stack_args + 12u32);
+            let lpString2 = <u32>::from_stack(mem, stack_args + 16u32);
+            let cchCount2 = <i32>::from_stack(mem, stack_args + 20u32);
+            let __trace_context = if crate::trace::enabled("kernel32/nls") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/file",
-                    "CreateFileA",
+                    "kernel32/nls",
+                    "CompareStringA",
                     &[
-                        ("lpFileName", &lpFileName),
-                        ("dwDesiredAccess", &dwDesiredAccess),
-                        ("dwShareMode", &dwShareMode),
-                        ("lpSecurityAttributes", &lpSecurityAttributes),
-                        ("dwCreationDisposition", &dwCreationDisposition),
-                        ("dwFlagsAndAttributes", &dwFlagsAndAttributes),
-                        ("hTemplateFile", &hTemplateFile),
+                        ("Locale", &Locale),
+                        ("dwCmpFlags", &dwCmpFlags),
+                        ("lpString1", &lpString1),
+                        ("cchCount1", &cchCount1),
+                        ("lpString2", &lpString2),
+                        ("cchCount2", &cchCount2),
                     ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::CreateFileA(
-                machine,
-                lpFileName,
-                dwDesiredAccess,
-                dwShareMode,
-                lpSecurityAttributes,
-                dwCreationDisposition,
-                dwFlagsAndAttributes,
-                hTemplateFile,
+            let result = winapi::kernel32::CompareStringA(
+                machine, Locale, dwCmpFlags, lpString1, cchCount1, lpString2, cchCount2,
             );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::CreateFileA_pos.0,
-                    winapi::kernel32::CreateFileA_pos.1,
+                    winapi::kernel32::CompareStringA_pos.0,
+                    winapi::kernel32::CompareStringA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn CreateFileW(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CompareStringW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpFileName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
-            let dwDesiredAccess = <u32>::from_stack(mem, stack_args + 4u32);
-            let dwShareMode = <u32>::from_stack(mem, stack_args + 8u32);
-            let lpSecurityAttributes = <u32>::from_stack(mem, stack_args + 12u32);
-            let dwCreationDisposition =
-                <Result<CreationDisposition, u32>>::from_stack(mem, stack_args + 16u32);
-            let dwFlagsAndAttributes =
-                <Result<FileAttribute, u32>>::from_stack(mem, stack_args + 20u32);
-            let hTemplateFile = <HFILE>::from_stack(mem, stack_args + 24u32);
-            let __trace_context = if crate::trace::enabled("kernel32/file") {
+            let Locale = <LCID>::from_stack(mem, stack_args + 0u32);
+            let dwCmpFlags = <u32>::from_stack(mem, stack_args + 4u32);
+            let lpString1 = <u32>::from_stack(mem, stack_args + 8u32);
+            let cchCount1 = <i32>::from_stack(mem, stack_args + 12u32);
+            let lpString2 = <u32>::from_stack(mem, stack_args + 16u32);
+            let cchCount2 = <i32>::from_stack(mem, stack_args + 20u32);
+            let __trace_context = if crate::trace::enabled("kernel32/nls") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/file",
-                    "CreateFileW",
+                    "kernel32/nls",
+                    "CompareStringW",
                     &[
-                        ("lpFileName", &lpFileName),
-                        ("dwDesiredAccess", &dwDesiredAccess),
-                        ("dwShareMode", &dwShareMode),
-                        ("lpSecurityAttributes", &lpSecurityAttributes),
-                        ("dwCreationDisposition", &dwCreationDisposition),
-                        ("dwFlagsAndAttributes", &dwFlagsAndAttributes),
-                        ("hTemplateFile", &hTemplateFile),
+                        ("Locale", &Locale),
+                        ("dwCmpFlags", &dwCmpFlags),
+                        ("lpString1", &lpString1),
+                        ("cchCount1", &cchCount1),
+                        ("lpString2", &lpString2),
+                        ("cchCount2", &cchCount2),
                     ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::CreateFileW(
-                machine,
-                lpFileName,
-                dwDesiredAccess,
-                dwShareMode,
-                lpSecurityAttributes,
-                dwCreationDisposition,
-                dwFlagsAndAttributes,
-                hTemplateFile,
+            let result = winapi::kernel32::CompareStringW(
+                machine, Locale, dwCmpFlags, lpString1, cchCount1, lpString2, cchCount2,
             );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::CreateFileW_pos.0,
-                    winapi::kernel32::CreateFileW_pos.1,
+                    winapi::kernel32::CompareStringW_pos.0,
+                    winapi::kernel32::CompareStringW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn CreateThread(
+        pub unsafe fn ConnectNamedPipe(
             machine: &mut Machine,
             stack_args: u32,
         ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
             let mem = machine.mem().detach();
-            let lpThreadAttributes = <u32>::from_stack(mem, stack_args + 0u32);
-            let dwStackSize = <u32>::from_stack(mem, stack_args + 4u32);
-            let lpStartAddress = <u32>::from_stack(mem, stack_args + 8u32);
-            let lpParameter = <u32>::from_stack(mem, stack_args + 12u32);
-            let dwCreationFlags = <u32>::from_stack(mem, stack_args + 16u32);
-            let lpThreadId = <u32>::from_stack(mem, stack_args + 20u32);
-            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+            let hNamedPipe = <HFILE>::from_stack(mem, stack_args + 0u32);
+            let lpOverlapped = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/pipe") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/thread",
-                    "CreateThread",
-                    &[
-                        ("lpThreadAttributes", &lpThreadAttributes),
-                        ("dwStackSize", &dwStackSize),
-                        ("lpStartAddress", &lpStartAddress),
-                        ("lpParameter", &lpParameter),
-                        ("dwCreationFlags", &dwCreationFlags),
-                        ("lpThreadId", &lpThreadId),
-                    ],
+                    "kernel32/pipe",
+                    "ConnectNamedPipe",
+                    &[("hNamedPipe", &hNamedPipe), ("lpOverlapped", &lpOverlapped)],
                 ))
             } else {
                 None
@@ -4433,5075 +5006,10580 @@ pub mod kernel32 {
             let machine: *mut Machine = machine;
             Box::pin(async move {
                 let machine = unsafe { &mut *machine };
-                let result = winapi::kernel32::CreateThread(
-                    machine,
-                    lpThreadAttributes,
-                    dwStackSize,
-                    lpStartAddress,
-                    lpParameter,
-                    dwCreationFlags,
-                    lpThreadId,
-                )
-                .await;
+                let result =
+                    winapi::kernel32::ConnectNamedPipe(machine, hNamedPipe, lpOverlapped).await;
                 if let Some(__trace_context) = __trace_context {
                     crate::trace::trace_return(
                         &__trace_context,
-                        winapi::kernel32::CreateThread_pos.0,
-                        winapi::kernel32::CreateThread_pos.1,
+                        winapi::kernel32::ConnectNamedPipe_pos.0,
+                        winapi::kernel32::ConnectNamedPipe_pos.1,
                         &result,
                     );
                 }
                 result.to_raw()
             })
         }
-        pub unsafe fn DebugBreak(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn ConvertFiberToThread(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let __trace_context = if crate::trace::enabled("kernel32/misc") {
+            let __trace_context = if crate::trace::enabled("kernel32/fiber") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/misc",
-                    "DebugBreak",
+                    "kernel32/fiber",
+                    "ConvertFiberToThread",
                     &[],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::DebugBreak(machine);
+            let result = winapi::kernel32::ConvertFiberToThread(machine);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::DebugBreak_pos.0,
-                    winapi::kernel32::DebugBreak_pos.1,
+                    winapi::kernel32::ConvertFiberToThread_pos.0,
+                    winapi::kernel32::ConvertFiberToThread_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn DeleteCriticalSection(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn ConvertThreadToFiber(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpCriticalSection = <u32>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+            let lpParameter = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/fiber") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/thread",
-                    "DeleteCriticalSection",
-                    &[("lpCriticalSection", &lpCriticalSection)],
+                    "kernel32/fiber",
+                    "ConvertThreadToFiber",
+                    &[("lpParameter", &lpParameter)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::DeleteCriticalSection(machine, lpCriticalSection);
+            let result = winapi::kernel32::ConvertThreadToFiber(machine, lpParameter);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::DeleteCriticalSection_pos.0,
-                    winapi::kernel32::DeleteCriticalSection_pos.1,
+                    winapi::kernel32::ConvertThreadToFiber_pos.0,
+                    winapi::kernel32::ConvertThreadToFiber_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn DeleteFileA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn ConvertThreadToFiberEx(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpFileName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/file") {
+            let lpParameter = <u32>::from_stack(mem, stack_args + 0u32);
+            let dwFlags = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/fiber") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/file",
-                    "DeleteFileA",
-                    &[("lpFileName", &lpFileName)],
+                    "kernel32/fiber",
+                    "ConvertThreadToFiberEx",
+                    &[("lpParameter", &lpParameter), ("dwFlags", &dwFlags)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::DeleteFileA(machine, lpFileName);
+            let result = winapi::kernel32::ConvertThreadToFiberEx(machine, lpParameter, dwFlags);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::DeleteFileA_pos.0,
-                    winapi::kernel32::DeleteFileA_pos.1,
+                    winapi::kernel32::ConvertThreadToFiberEx_pos.0,
+                    winapi::kernel32::ConvertThreadToFiberEx_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn DisableThreadLibraryCalls(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CopyFileA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hLibModule = <HMODULE>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/dll") {
+            let lpExistingFileName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+            let lpNewFileName = <Option<&str>>::from_stack(mem, stack_args + 4u32);
+            let bFailIfExists = <bool>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/dll",
-                    "DisableThreadLibraryCalls",
-                    &[("hLibModule", &hLibModule)],
+                    "kernel32/file",
+                    "CopyFileA",
+                    &[
+                        ("lpExistingFileName", &lpExistingFileName),
+                        ("lpNewFileName", &lpNewFileName),
+                        ("bFailIfExists", &bFailIfExists),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::DisableThreadLibraryCalls(machine, hLibModule);
+            let result = winapi::kernel32::CopyFileA(
+                machine,
+                lpExistingFileName,
+                lpNewFileName,
+                bFailIfExists,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::DisableThreadLibraryCalls_pos.0,
-                    winapi::kernel32::DisableThreadLibraryCalls_pos.1,
+                    winapi::kernel32::CopyFileA_pos.0,
+                    winapi::kernel32::CopyFileA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn EnterCriticalSection(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CopyFileW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpCriticalSection = <u32>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+            let lpExistingFileName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+            let lpNewFileName = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
+            let bFailIfExists = <bool>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/thread",
-                    "EnterCriticalSection",
-                    &[("lpCriticalSection", &lpCriticalSection)],
+                    "kernel32/file",
+                    "CopyFileW",
+                    &[
+                        ("lpExistingFileName", &lpExistingFileName),
+                        ("lpNewFileName", &lpNewFileName),
+                        ("bFailIfExists", &bFailIfExists),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::EnterCriticalSection(machine, lpCriticalSection);
+            let result = winapi::kernel32::CopyFileW(
+                machine,
+                lpExistingFileName,
+                lpNewFileName,
+                bFailIfExists,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::EnterCriticalSection_pos.0,
-                    winapi::kernel32::EnterCriticalSection_pos.1,
+                    winapi::kernel32::CopyFileW_pos.0,
+                    winapi::kernel32::CopyFileW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn ExitProcess(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CreateDirectoryA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let uExitCode = <u32>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/misc") {
+            let lpPathName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+            let lpSecurityAttributes = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/misc",
-                    "ExitProcess",
-                    &[("uExitCode", &uExitCode)],
+                    "kernel32/file",
+                    "CreateDirectoryA",
+                    &[
+                        ("lpPathName", &lpPathName),
+                        ("lpSecurityAttributes", &lpSecurityAttributes),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::ExitProcess(machine, uExitCode);
+            let result =
+                winapi::kernel32::CreateDirectoryA(machine, lpPathName, lpSecurityAttributes);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::ExitProcess_pos.0,
-                    winapi::kernel32::ExitProcess_pos.1,
+                    winapi::kernel32::CreateDirectoryA_pos.0,
+                    winapi::kernel32::CreateDirectoryA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn ExitThread(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CreateEventA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let dwExitCode = <u32>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+            let lpEventAttributes = <u32>::from_stack(mem, stack_args + 0u32);
+            let bManualReset = <bool>::from_stack(mem, stack_args + 4u32);
+            let bInitialState = <bool>::from_stack(mem, stack_args + 8u32);
+            let lpName = <Option<&str>>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("kernel32/sync") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/thread",
-                    "ExitThread",
-                    &[("dwExitCode", &dwExitCode)],
+                    "kernel32/sync",
+                    "CreateEventA",
+                    &[
+                        ("lpEventAttributes", &lpEventAttributes),
+                        ("bManualReset", &bManualReset),
+                        ("bInitialState", &bInitialState),
+                        ("lpName", &lpName),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::ExitThread(machine, dwExitCode);
+            let result = winapi::kernel32::CreateEventA(
+                machine,
+                lpEventAttributes,
+                bManualReset,
+                bInitialState,
+                lpName,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::ExitThread_pos.0,
-                    winapi::kernel32::ExitThread_pos.1,
+                    winapi::kernel32::CreateEventA_pos.0,
+                    winapi::kernel32::CreateEventA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn FileTimeToSystemTime(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CreateEventW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpFileTime = <Option<&FILETIME>>::from_stack(mem, stack_args + 0u32);
-            let lpSystemTime = <Option<&mut SYSTEMTIME>>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/time") {
+            let lpEventAttributes = <u32>::from_stack(mem, stack_args + 0u32);
+            let bManualReset = <bool>::from_stack(mem, stack_args + 4u32);
+            let bInitialState = <bool>::from_stack(mem, stack_args + 8u32);
+            let lpName = <Option<&Str16>>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("kernel32/sync") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/time",
-                    "FileTimeToSystemTime",
-                    &[("lpFileTime", &lpFileTime), ("lpSystemTime", &lpSystemTime)],
+                    "kernel32/sync",
+                    "CreateEventW",
+                    &[
+                        ("lpEventAttributes", &lpEventAttributes),
+                        ("bManualReset", &bManualReset),
+                        ("bInitialState", &bInitialState),
+                        ("lpName", &lpName),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::FileTimeToSystemTime(machine, lpFileTime, lpSystemTime);
+            let result = winapi::kernel32::CreateEventW(
+                machine,
+                lpEventAttributes,
+                bManualReset,
+                bInitialState,
+                lpName,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::FileTimeToSystemTime_pos.0,
-                    winapi::kernel32::FileTimeToSystemTime_pos.1,
+                    winapi::kernel32::CreateEventW_pos.0,
+                    winapi::kernel32::CreateEventW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn FindClose(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CreateFiber(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hFindFile = <HFIND>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/file") {
+            let dwStackSize = <u32>::from_stack(mem, stack_args + 0u32);
+            let lpStartAddress = <u32>::from_stack(mem, stack_args + 4u32);
+            let lpParameter = <u32>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/fiber") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/file",
-                    "FindClose",
-                    &[("hFindFile", &hFindFile)],
+                    "kernel32/fiber",
+                    "CreateFiber",
+                    &[
+                        ("dwStackSize", &dwStackSize),
+                        ("lpStartAddress", &lpStartAddress),
+                        ("lpParameter", &lpParameter),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::FindClose(machine, hFindFile);
+            let result =
+                winapi::kernel32::CreateFiber(machine, dwStackSize, lpStartAddress, lpParameter);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::FindClose_pos.0,
-                    winapi::kernel32::FindClose_pos.1,
+                    winapi::kernel32::CreateFiber_pos.0,
+                    winapi::kernel32::CreateFiber_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn FindFirstFileA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CreateFiberEx(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpFileName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
-            let lpFindFileData =
-                <Option<&mut WIN32_FIND_DATAA>>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/file") {
+            let dwStackCommitSize = <u32>::from_stack(mem, stack_args + 0u32);
+            let dwStackReserveSize = <u32>::from_stack(mem, stack_args + 4u32);
+            let dwFlags = <u32>::from_stack(mem, stack_args + 8u32);
+            let lpStartAddress = <u32>::from_stack(mem, stack_args + 12u32);
+            let lpParameter = <u32>::from_stack(mem, stack_args + 16u32);
+            let __trace_context = if crate::trace::enabled("kernel32/fiber") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/file",
-                    "FindFirstFileA",
+                    "kernel32/fiber",
+                    "CreateFiberEx",
                     &[
-                        ("lpFileName", &lpFileName),
-                        ("lpFindFileData", &lpFindFileData),
+                        ("dwStackCommitSize", &dwStackCommitSize),
+                        ("dwStackReserveSize", &dwStackReserveSize),
+                        ("dwFlags", &dwFlags),
+                        ("lpStartAddress", &lpStartAddress),
+                        ("lpParameter", &lpParameter),
                     ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::FindFirstFileA(machine, lpFileName, lpFindFileData);
+            let result = winapi::kernel32::CreateFiberEx(
+                machine,
+                dwStackCommitSize,
+                dwStackReserveSize,
+                dwFlags,
+                lpStartAddress,
+                lpParameter,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::FindFirstFileA_pos.0,
-                    winapi::kernel32::FindFirstFileA_pos.1,
+                    winapi::kernel32::CreateFiberEx_pos.0,
+                    winapi::kernel32::CreateFiberEx_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn FindNextFileA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CreateFileA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hFindFile = <HFIND>::from_stack(mem, stack_args + 0u32);
-            let lpFindFileData =
-                <Option<&mut WIN32_FIND_DATAA>>::from_stack(mem, stack_args + 4u32);
+            let lpFileName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+            let dwDesiredAccess = <u32>::from_stack(mem, stack_args + 4u32);
+            let dwShareMode = <u32>::from_stack(mem, stack_args + 8u32);
+            let lpSecurityAttributes = <u32>::from_stack(mem, stack_args + 12u32);
+            let dwCreationDisposition =
+                <Result<CreationDisposition, u32>>::from_stack(mem, stack_args + 16u32);
+            let dwFlagsAndAttributes = <u32>::from_stack(mem, stack_args + 20u32);
+            let hTemplateFile = <HFILE>::from_stack(mem, stack_args + 24u32);
             let __trace_context = if crate::trace::enabled("kernel32/file") {
                 Some(crate::trace::trace_begin(
                     "kernel32/file",
-                    "FindNextFileA",
+                    "CreateFileA",
                     &[
-                        ("hFindFile", &hFindFile),
-                        ("lpFindFileData", &lpFindFileData),
+                        ("lpFileName", &lpFileName),
+                        ("dwDesiredAccess", &dwDesiredAccess),
+                        ("dwShareMode", &dwShareMode),
+                        ("lpSecurityAttributes", &lpSecurityAttributes),
+                        ("dwCreationDisposition", &dwCreationDisposition),
+                        ("dwFlagsAndAttributes", &dwFlagsAndAttributes),
+                        ("hTemplateFile", &hTemplateFile),
                     ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::FindNextFileA(machine, hFindFile, lpFindFileData);
+            let result = winapi::kernel32::CreateFileA(
+                machine,
+                lpFileName,
+                dwDesiredAccess,
+                dwShareMode,
+                lpSecurityAttributes,
+                dwCreationDisposition,
+                dwFlagsAndAttributes,
+                hTemplateFile,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::FindNextFileA_pos.0,
-                    winapi::kernel32::FindNextFileA_pos.1,
+                    winapi::kernel32::CreateFileA_pos.0,
+                    winapi::kernel32::CreateFileA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn FindResourceA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CreateFileMappingA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hModule = <HMODULE>::from_stack(mem, stack_args + 0u32);
-            let lpName = <ResourceKey<&str>>::from_stack(mem, stack_args + 4u32);
-            let lpType = <ResourceKey<&str>>::from_stack(mem, stack_args + 8u32);
-            let __trace_context = if crate::trace::enabled("kernel32/resource") {
-                Some(crate::trace::trace_begin(
-                    "kernel32/resource",
-                    "FindResourceA",
+            let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
+            let lpFileMappingAttributes = <u32>::from_stack(mem, stack_args + 4u32);
+            let flProtect = <u32>::from_stack(mem, stack_args + 8u32);
+            let dwMaximumSizeHigh = <u32>::from_stack(mem, stack_args + 12u32);
+            let dwMaximumSizeLow = <u32>::from_stack(mem, stack_args + 16u32);
+            let lpName = <Option<&str>>::from_stack(mem, stack_args + 20u32);
+            let __trace_context = if crate::trace::enabled("kernel32/mapping") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/mapping",
+                    "CreateFileMappingA",
                     &[
-                        ("hModule", &hModule),
+                        ("hFile", &hFile),
+                        ("lpFileMappingAttributes", &lpFileMappingAttributes),
+                        ("flProtect", &flProtect),
+                        ("dwMaximumSizeHigh", &dwMaximumSizeHigh),
+                        ("dwMaximumSizeLow", &dwMaximumSizeLow),
                         ("lpName", &lpName),
-                        ("lpType", &lpType),
                     ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::FindResourceA(machine, hModule, lpName, lpType);
+            let result = winapi::kernel32::CreateFileMappingA(
+                machine,
+                hFile,
+                lpFileMappingAttributes,
+                flProtect,
+                dwMaximumSizeHigh,
+                dwMaximumSizeLow,
+                lpName,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::FindResourceA_pos.0,
-                    winapi::kernel32::FindResourceA_pos.1,
+                    winapi::kernel32::CreateFileMappingA_pos.0,
+                    winapi::kernel32::CreateFileMappingA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn FindResourceW(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CreateFileMappingW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hModule = <HMODULE>::from_stack(mem, stack_args + 0u32);
-            let lpName = <ResourceKey<&Str16>>::from_stack(mem, stack_args + 4u32);
-            let lpType = <ResourceKey<&Str16>>::from_stack(mem, stack_args + 8u32);
-            let __trace_context = if crate::trace::enabled("kernel32/resource") {
-                Some(crate::trace::trace_begin(
-                    "kernel32/resource",
-                    "FindResourceW",
+            let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
+            let lpFileMappingAttributes = <u32>::from_stack(mem, stack_args + 4u32);
+            let flProtect = <u32>::from_stack(mem, stack_args + 8u32);
+            let dwMaximumSizeHigh = <u32>::from_stack(mem, stack_args + 12u32);
+            let dwMaximumSizeLow = <u32>::from_stack(mem, stack_args + 16u32);
+            let lpName = <Option<&Str16>>::from_stack(mem, stack_args + 20u32);
+            let __trace_context = if crate::trace::enabled("kernel32/mapping") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/mapping",
+                    "CreateFileMappingW",
                     &[
-                        ("hModule", &hModule),
+                        ("hFile", &hFile),
+                        ("lpFileMappingAttributes", &lpFileMappingAttributes),
+                        ("flProtect", &flProtect),
+                        ("dwMaximumSizeHigh", &dwMaximumSizeHigh),
+                        ("dwMaximumSizeLow", &dwMaximumSizeLow),
                         ("lpName", &lpName),
-                        ("lpType", &lpType),
                     ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::FindResourceW(machine, hModule, lpName, lpType);
+            let result = winapi::kernel32::CreateFileMappingW(
+                machine,
+                hFile,
+                lpFileMappingAttributes,
+                flProtect,
+                dwMaximumSizeHigh,
+                dwMaximumSizeLow,
+                lpName,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::FindResourceW_pos.0,
-                    winapi::kernel32::FindResourceW_pos.1,
+                    winapi::kernel32::CreateFileMappingW_pos.0,
+                    winapi::kernel32::CreateFileMappingW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn FlushFileBuffers(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CreateFileW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/file") {
-                Some(crate::trace::trace_begin(
-                    "kernel32/file",
-                    "FlushFileBuffers",
-                    &[("hFile", &hFile)],
-                ))
-            } else {
-                None
-            };
-            let result = winapi::kernel32::FlushFileBuffers(machine, hFile);
-            if let Some(__trace_context) = __trace_context {
-                crate::trace::trace_return(
-                    &__trace_context,
-                    winapi::kernel32::FlushFileBuffers_pos.0,
-                    winapi::kernel32::FlushFileBuffers_pos.1,
-                    &result,
-                );
-            }
-            result.to_raw()
-        }
-        pub unsafe fn FormatMessageA(machine: &mut Machine, stack_args: u32) -> u32 {
-            let mem = machine.mem().detach();
-            let dwFlags = <u32>::from_stack(mem, stack_args + 0u32);
-            let lpSource = <u32>::from_stack(mem, stack_args + 4u32);
-            let dwMessageId = <u32>::from_stack(mem, stack_args + 8u32);
-            let dwLanguageId = <u32>::from_stack(mem, stack_args + 12u32);
-            let lpBuffer = <u32>::from_stack(mem, stack_args + 16u32);
-            let nSize = <u32>::from_stack(mem, stack_args + 20u32);
-            let args = <u32>::from_stack(mem, stack_args + 24u32);
-            let __trace_context = if crate::trace::enabled("kernel32/misc") {
+            let lpFileName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+            let dwDesiredAccess = <u32>::from_stack(mem, stack_args + 4u32);
+            let dwShareMode = <u32>::from_stack(mem, stack_args + 8u32);
+            let lpSecurityAttributes = <u32>::from_stack(mem, stack_args + 12u32);
+            let dwCreationDisposition =
+                <Result<CreationDisposition, u32>>::from_stack(mem, stack_args + 16u32);
+            let dwFlagsAndAttributes = <u32>::from_stack(mem, stack_args + 20u32);
+            let hTemplateFile = <HFILE>::from_stack(mem, stack_args + 24u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/misc",
-                    "FormatMessageA",
+                    "kernel32/file",
+                    "CreateFileW",
                     &[
-                        ("dwFlags", &dwFlags),
-                        ("lpSource", &lpSource),
-                        ("dwMessageId", &dwMessageId),
-                        ("dwLanguageId", &dwLanguageId),
-                        ("lpBuffer", &lpBuffer),
-                        ("nSize", &nSize),
-                        ("args", &args),
+                        ("lpFileName", &lpFileName),
+                        ("dwDesiredAccess", &dwDesiredAccess),
+                        ("dwShareMode", &dwShareMode),
+                        ("lpSecurityAttributes", &lpSecurityAttributes),
+                        ("dwCreationDisposition", &dwCreationDisposition),
+                        ("dwFlagsAndAttributes", &dwFlagsAndAttributes),
+                        ("hTemplateFile", &hTemplateFile),
                     ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::FormatMessageA(
+            let result = winapi::kernel32::CreateFileW(
                 machine,
-                dwFlags,
-                lpSource,
-                dwMessageId,
-                dwLanguageId,
-                lpBuffer,
-                nSize,
-                args,
+                lpFileName,
+                dwDesiredAccess,
+                dwShareMode,
+                lpSecurityAttributes,
+                dwCreationDisposition,
+                dwFlagsAndAttributes,
+                hTemplateFile,
             );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::FormatMessageA_pos.0,
-                    winapi::kernel32::FormatMessageA_pos.1,
+                    winapi::kernel32::CreateFileW_pos.0,
+                    winapi::kernel32::CreateFileW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn FormatMessageW(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CreateMutexA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let dwFlags = <Result<FormatMessageFlags, u32>>::from_stack(mem, stack_args + 0u32);
-            let lpSource = <u32>::from_stack(mem, stack_args + 4u32);
-            let dwMessageId = <u32>::from_stack(mem, stack_args + 8u32);
-            let dwLanguageId = <u32>::from_stack(mem, stack_args + 12u32);
-            let lpBuffer = <u32>::from_stack(mem, stack_args + 16u32);
-            let nSize = <u32>::from_stack(mem, stack_args + 20u32);
-            let args = <u32>::from_stack(mem, stack_args + 24u32);
-            let __trace_context = if crate::trace::enabled("kernel32/misc") {
+            let lpMutexAttributes = <u32>::from_stack(mem, stack_args + 0u32);
+            let bInitialOwner = <bool>::from_stack(mem, stack_args + 4u32);
+            let lpName = <Option<&str>>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/sync") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/misc",
-                    "FormatMessageW",
+                    "kernel32/sync",
+                    "CreateMutexA",
                     &[
-                        ("dwFlags", &dwFlags),
-                        ("lpSource", &lpSource),
-                        ("dwMessageId", &dwMessageId),
-                        ("dwLanguageId", &dwLanguageId),
-                        ("lpBuffer", &lpBuffer),
-                        ("nSize", &nSize),
-                        ("args", &args),
+                        ("lpMutexAttributes", &lpMutexAttributes),
+                        ("bInitialOwner", &bInitialOwner),
+                        ("lpName", &lpName),
                     ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::FormatMessageW(
-                machine,
-                dwFlags,
-                lpSource,
-                dwMessageId,
-                dwLanguageId,
-                lpBuffer,
-                nSize,
-                args,
-            );
+            let result =
+                winapi::kernel32::CreateMutexA(machine, lpMutexAttributes, bInitialOwner, lpName);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::FormatMessageW_pos.0,
-                    winapi::kernel32::FormatMessageW_pos.1,
+                    winapi::kernel32::CreateMutexA_pos.0,
+                    winapi::kernel32::CreateMutexA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn FreeEnvironmentStringsA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CreateMutexW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let _penv = <u32>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/env") {
+            let lpMutexAttributes = <u32>::from_stack(mem, stack_args + 0u32);
+            let bInitialOwner = <bool>::from_stack(mem, stack_args + 4u32);
+            let lpName = <Option<&Str16>>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/sync") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/env",
-                    "FreeEnvironmentStringsA",
-                    &[("penv", &_penv)],
+                    "kernel32/sync",
+                    "CreateMutexW",
+                    &[
+                        ("lpMutexAttributes", &lpMutexAttributes),
+                        ("bInitialOwner", &bInitialOwner),
+                        ("lpName", &lpName),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::FreeEnvironmentStringsA(machine, _penv);
+            let result =
+                winapi::kernel32::CreateMutexW(machine, lpMutexAttributes, bInitialOwner, lpName);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::FreeEnvironmentStringsA_pos.0,
-                    winapi::kernel32::FreeEnvironmentStringsA_pos.1,
+                    winapi::kernel32::CreateMutexW_pos.0,
+                    winapi::kernel32::CreateMutexW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn FreeEnvironmentStringsW(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CreateNamedPipeA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let __trace_context = if crate::trace::enabled("kernel32/env") {
+            let lpName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+            let dwOpenMode = <u32>::from_stack(mem, stack_args + 4u32);
+            let dwPipeMode = <u32>::from_stack(mem, stack_args + 8u32);
+            let nMaxInstances = <u32>::from_stack(mem, stack_args + 12u32);
+            let nOutBufferSize = <u32>::from_stack(mem, stack_args + 16u32);
+            let nInBufferSize = <u32>::from_stack(mem, stack_args + 20u32);
+            let nDefaultTimeOut = <u32>::from_stack(mem, stack_args + 24u32);
+            let lpSecurityAttributes = <u32>::from_stack(mem, stack_args + 28u32);
+            let __trace_context = if crate::trace::enabled("kernel32/pipe") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/env",
-                    "FreeEnvironmentStringsW",
-                    &[],
+                    "kernel32/pipe",
+                    "CreateNamedPipeA",
+                    &[
+                        ("lpName", &lpName),
+                        ("dwOpenMode", &dwOpenMode),
+                        ("dwPipeMode", &dwPipeMode),
+                        ("nMaxInstances", &nMaxInstances),
+                        ("nOutBufferSize", &nOutBufferSize),
+                        ("nInBufferSize", &nInBufferSize),
+                        ("nDefaultTimeOut", &nDefaultTimeOut),
+                        ("lpSecurityAttributes", &lpSecurityAttributes),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::FreeEnvironmentStringsW(machine);
+            let result = winapi::kernel32::CreateNamedPipeA(
+                machine,
+                lpName,
+                dwOpenMode,
+                dwPipeMode,
+                nMaxInstances,
+                nOutBufferSize,
+                nInBufferSize,
+                nDefaultTimeOut,
+                lpSecurityAttributes,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::FreeEnvironmentStringsW_pos.0,
-                    winapi::kernel32::FreeEnvironmentStringsW_pos.1,
+                    winapi::kernel32::CreateNamedPipeA_pos.0,
+                    winapi::kernel32::CreateNamedPipeA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn FreeLibrary(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CreateNamedPipeW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hLibModule = <HMODULE>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/dll") {
+            let lpName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+            let dwOpenMode = <u32>::from_stack(mem, stack_args + 4u32);
+            let dwPipeMode = <u32>::from_stack(mem, stack_args + 8u32);
+            let nMaxInstances = <u32>::from_stack(mem, stack_args + 12u32);
+            let nOutBufferSize = <u32>::from_stack(mem, stack_args + 16u32);
+            let nInBufferSize = <u32>::from_stack(mem, stack_args + 20u32);
+            let nDefaultTimeOut = <u32>::from_stack(mem, stack_args + 24u32);
+            let lpSecurityAttributes = <u32>::from_stack(mem, stack_args + 28u32);
+            let __trace_context = if crate::trace::enabled("kernel32/pipe") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/dll",
-                    "FreeLibrary",
-                    &[("hLibModule", &hLibModule)],
+                    "kernel32/pipe",
+                    "CreateNamedPipeW",
+                    &[
+                        ("lpName", &lpName),
+                        ("dwOpenMode", &dwOpenMode),
+                        ("dwPipeMode", &dwPipeMode),
+                        ("nMaxInstances", &nMaxInstances),
+                        ("nOutBufferSize", &nOutBufferSize),
+                        ("nInBufferSize", &nInBufferSize),
+                        ("nDefaultTimeOut", &nDefaultTimeOut),
+                        ("lpSecurityAttributes", &lpSecurityAttributes),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::FreeLibrary(machine, hLibModule);
-            if let Some(__trace_context) = __trace_context {
-                crate::trace::trace_return(
-                    &__trace_context,
-                    winapi::kernel32::FreeLibrary_pos.0,
-                    winapi::kernel32::FreeLibrary_pos.1,
-                    &result,
-                );
-            }
-            result.to_raw()
-        }
-        pub unsafe fn GetACP(machine: &mut Machine, stack_args: u32) -> u32 {
-            let mem = machine.mem().detach();
-            let __trace_context = if crate::trace::enabled("kernel32/nls") {
-                Some(crate::trace::trace_begin("kernel32/nls", "GetACP", &[]))
-            } else {
-                None
-            };
-            let result = winapi::kernel32::GetACP(machine);
+            let result = winapi::kernel32::CreateNamedPipeW(
+                machine,
+                lpName,
+                dwOpenMode,
+                dwPipeMode,
+                nMaxInstances,
+                nOutBufferSize,
+                nInBufferSize,
+                nDefaultTimeOut,
+                lpSecurityAttributes,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetACP_pos.0,
-                    winapi::kernel32::GetACP_pos.1,
+                    winapi::kernel32::CreateNamedPipeW_pos.0,
+                    winapi::kernel32::CreateNamedPipeW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetCPInfo(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CreateProcessA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let _CodePage = <u32>::from_stack(mem, stack_args + 0u32);
-            let _lpCPInfo = <u32>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/nls") {
+            let lpApplicationName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+            let lpCommandLine = <Option<&str>>::from_stack(mem, stack_args + 4u32);
+            let lpProcessAttributes = <u32>::from_stack(mem, stack_args + 8u32);
+            let lpThreadAttributes = <u32>::from_stack(mem, stack_args + 12u32);
+            let bInheritHandles = <bool>::from_stack(mem, stack_args + 16u32);
+            let dwCreationFlags = <u32>::from_stack(mem, stack_args + 20u32);
+            let lpEnvironment = <u32>::from_stack(mem, stack_args + 24u32);
+            let lpCurrentDirectory = <Option<&str>>::from_stack(mem, stack_args + 28u32);
+            let lpStartupInfo = <u32>::from_stack(mem, stack_args + 32u32);
+            let lpProcessInformation =
+                <Option<&mut PROCESS_INFORMATION>>::from_stack(mem, stack_args + 36u32);
+            let __trace_context = if crate::trace::enabled("kernel32/process") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/nls",
-                    "GetCPInfo",
-                    &[("CodePage", &_CodePage), ("lpCPInfo", &_lpCPInfo)],
+                    "kernel32/process",
+                    "CreateProcessA",
+                    &[
+                        ("lpApplicationName", &lpApplicationName),
+                        ("lpCommandLine", &lpCommandLine),
+                        ("lpProcessAttributes", &lpProcessAttributes),
+                        ("lpThreadAttributes", &lpThreadAttributes),
+                        ("bInheritHandles", &bInheritHandles),
+                        ("dwCreationFlags", &dwCreationFlags),
+                        ("lpEnvironment", &lpEnvironment),
+                        ("lpCurrentDirectory", &lpCurrentDirectory),
+                        ("lpStartupInfo", &lpStartupInfo),
+                        ("lpProcessInformation", &lpProcessInformation),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetCPInfo(machine, _CodePage, _lpCPInfo);
+            let result = winapi::kernel32::CreateProcessA(
+                machine,
+                lpApplicationName,
+                lpCommandLine,
+                lpProcessAttributes,
+                lpThreadAttributes,
+                bInheritHandles,
+                dwCreationFlags,
+                lpEnvironment,
+                lpCurrentDirectory,
+                lpStartupInfo,
+                lpProcessInformation,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetCPInfo_pos.0,
-                    winapi::kernel32::GetCPInfo_pos.1,
+                    winapi::kernel32::CreateProcessA_pos.0,
+                    winapi::kernel32::CreateProcessA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetCommandLineA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CreateProcessW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let __trace_context = if crate::trace::enabled("kernel32/init") {
+            let lpApplicationName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+            let lpCommandLine = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
+            let lpProcessAttributes = <u32>::from_stack(mem, stack_args + 8u32);
+            let lpThreadAttributes = <u32>::from_stack(mem, stack_args + 12u32);
+            let bInheritHandles = <bool>::from_stack(mem, stack_args + 16u32);
+            let dwCreationFlags = <u32>::from_stack(mem, stack_args + 20u32);
+            let lpEnvironment = <u32>::from_stack(mem, stack_args + 24u32);
+            let lpCurrentDirectory = <Option<&Str16>>::from_stack(mem, stack_args + 28u32);
+            let lpStartupInfo = <u32>::from_stack(mem, stack_args + 32u32);
+            let lpProcessInformation =
+                <Option<&mut PROCESS_INFORMATION>>::from_stack(mem, stack_args + 36u32);
+            let __trace_context = if crate::trace::enabled("kernel32/process") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/init",
-                    "GetCommandLineA",
-                    &[],
+                    "kernel32/process",
+                    "CreateProcessW",
+                    &[
+                        ("lpApplicationName", &lpApplicationName),
+                        ("lpCommandLine", &lpCommandLine),
+                        ("lpProcessAttributes", &lpProcessAttributes),
+                        ("lpThreadAttributes", &lpThreadAttributes),
+                        ("bInheritHandles", &bInheritHandles),
+                        ("dwCreationFlags", &dwCreationFlags),
+                        ("lpEnvironment", &lpEnvironment),
+                        ("lpCurrentDirectory", &lpCurrentDirectory),
+                        ("lpStartupInfo", &lpStartupInfo),
+                        ("lpProcessInformation", &lpProcessInformation),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetCommandLineA(machine);
+            let result = winapi::kernel32::CreateProcessW(
+                machine,
+                lpApplicationName,
+                lpCommandLine,
+                lpProcessAttributes,
+                lpThreadAttributes,
+                bInheritHandles,
+                dwCreationFlags,
+                lpEnvironment,
+                lpCurrentDirectory,
+                lpStartupInfo,
+                lpProcessInformation,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetCommandLineA_pos.0,
-                    winapi::kernel32::GetCommandLineA_pos.1,
+                    winapi::kernel32::CreateProcessW_pos.0,
+                    winapi::kernel32::CreateProcessW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetCommandLineW(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CreateSemaphoreA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let __trace_context = if crate::trace::enabled("kernel32/init") {
+            let lpSemaphoreAttributes = <u32>::from_stack(mem, stack_args + 0u32);
+            let lInitialCount = <i32>::from_stack(mem, stack_args + 4u32);
+            let lMaximumCount = <i32>::from_stack(mem, stack_args + 8u32);
+            let lpName = <Option<&str>>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("kernel32/sync") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/init",
-                    "GetCommandLineW",
-                    &[],
+                    "kernel32/sync",
+                    "CreateSemaphoreA",
+                    &[
+                        ("lpSemaphoreAttributes", &lpSemaphoreAttributes),
+                        ("lInitialCount", &lInitialCount),
+                        ("lMaximumCount", &lMaximumCount),
+                        ("lpName", &lpName),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetCommandLineW(machine);
+            let result = winapi::kernel32::CreateSemaphoreA(
+                machine,
+                lpSemaphoreAttributes,
+                lInitialCount,
+                lMaximumCount,
+                lpName,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetCommandLineW_pos.0,
-                    winapi::kernel32::GetCommandLineW_pos.1,
+                    winapi::kernel32::CreateSemaphoreA_pos.0,
+                    winapi::kernel32::CreateSemaphoreA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetConsoleMode(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CreateSemaphoreW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hConsoleHandle = <HFILE>::from_stack(mem, stack_args + 0u32);
-            let lpMode = <Option<&mut u32>>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/file") {
+            let lpSemaphoreAttributes = <u32>::from_stack(mem, stack_args + 0u32);
+            let lInitialCount = <i32>::from_stack(mem, stack_args + 4u32);
+            let lMaximumCount = <i32>::from_stack(mem, stack_args + 8u32);
+            let lpName = <Option<&Str16>>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("kernel32/sync") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/file",
-                    "GetConsoleMode",
-                    &[("hConsoleHandle", &hConsoleHandle), ("lpMode", &lpMode)],
+                    "kernel32/sync",
+                    "CreateSemaphoreW",
+                    &[
+                        ("lpSemaphoreAttributes", &lpSemaphoreAttributes),
+                        ("lInitialCount", &lInitialCount),
+                        ("lMaximumCount", &lMaximumCount),
+                        ("lpName", &lpName),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetConsoleMode(machine, hConsoleHandle, lpMode);
+            let result = winapi::kernel32::CreateSemaphoreW(
+                machine,
+                lpSemaphoreAttributes,
+                lInitialCount,
+                lMaximumCount,
+                lpName,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetConsoleMode_pos.0,
-                    winapi::kernel32::GetConsoleMode_pos.1,
+                    winapi::kernel32::CreateSemaphoreW_pos.0,
+                    winapi::kernel32::CreateSemaphoreW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetConsoleScreenBufferInfo(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CreateThread(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
             let mem = machine.mem().detach();
-            let _hConsoleOutput = <HANDLE<()>>::from_stack(mem, stack_args + 0u32);
-            let lpConsoleScreenBufferInfo =
-                <Option<&mut CONSOLE_SCREEN_BUFFER_INFO>>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/console") {
+            let lpThreadAttributes = <u32>::from_stack(mem, stack_args + 0u32);
+            let dwStackSize = <u32>::from_stack(mem, stack_args + 4u32);
+            let lpStartAddress = <u32>::from_stack(mem, stack_args + 8u32);
+            let lpParameter = <u32>::from_stack(mem, stack_args + 12u32);
+            let dwCreationFlags = <u32>::from_stack(mem, stack_args + 16u32);
+            let lpThreadId = <Option<&mut u32>>::from_stack(mem, stack_args + 20u32);
+            let __trace_context = if crate::trace::enabled("kernel32/thread") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/console",
-                    "GetConsoleScreenBufferInfo",
+                    "kernel32/thread",
+                    "CreateThread",
                     &[
-                        ("hConsoleOutput", &_hConsoleOutput),
-                        ("lpConsoleScreenBufferInfo", &lpConsoleScreenBufferInfo),
+                        ("lpThreadAttributes", &lpThreadAttributes),
+                        ("dwStackSize", &dwStackSize),
+                        ("lpStartAddress", &lpStartAddress),
+                        ("lpParameter", &lpParameter),
+                        ("dwCreationFlags", &dwCreationFlags),
+                        ("lpThreadId", &lpThreadId),
                     ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetConsoleScreenBufferInfo(
-                machine,
-                _hConsoleOutput,
-                lpConsoleScreenBufferInfo,
-            );
-            if let Some(__trace_context) = __trace_context {
-                crate::trace::trace_return(
-                    &__trace_context,
-                    winapi::kernel32::GetConsoleScreenBufferInfo_pos.0,
-                    winapi::kernel32::GetConsoleScreenBufferInfo_pos.1,
-                    &result,
-                );
-            }
-            result.to_raw()
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::kernel32::CreateThread(
+                    machine,
+                    lpThreadAttributes,
+                    dwStackSize,
+                    lpStartAddress,
+                    lpParameter,
+                    dwCreationFlags,
+                    lpThreadId,
+                )
+                .await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::kernel32::CreateThread_pos.0,
+                        winapi::kernel32::CreateThread_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
         }
-        pub unsafe fn GetCurrentDirectoryA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn DebugBreak(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let nBufferLength = <u32>::from_stack(mem, stack_args + 0u32);
-            let lpBuffer = <u32>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/file") {
+            let __trace_context = if crate::trace::enabled("kernel32/misc") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/file",
-                    "GetCurrentDirectoryA",
-                    &[("nBufferLength", &nBufferLength), ("lpBuffer", &lpBuffer)],
+                    "kernel32/misc",
+                    "DebugBreak",
+                    &[],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetCurrentDirectoryA(machine, nBufferLength, lpBuffer);
+            let result = winapi::kernel32::DebugBreak(machine);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetCurrentDirectoryA_pos.0,
-                    winapi::kernel32::GetCurrentDirectoryA_pos.1,
+                    winapi::kernel32::DebugBreak_pos.0,
+                    winapi::kernel32::DebugBreak_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetCurrentProcess(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn DeleteCriticalSection(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let __trace_context = if crate::trace::enabled("kernel32/misc") {
+            let lpCriticalSection =
+                <Option<&mut CRITICAL_SECTION>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/thread") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/misc",
-                    "GetCurrentProcess",
-                    &[],
+                    "kernel32/thread",
+                    "DeleteCriticalSection",
+                    &[("lpCriticalSection", &lpCriticalSection)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetCurrentProcess(machine);
+            let result = winapi::kernel32::DeleteCriticalSection(machine, lpCriticalSection);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetCurrentProcess_pos.0,
-                    winapi::kernel32::GetCurrentProcess_pos.1,
+                    winapi::kernel32::DeleteCriticalSection_pos.0,
+                    winapi::kernel32::DeleteCriticalSection_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetCurrentProcessId(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn DeleteFiber(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let __trace_context = if crate::trace::enabled("kernel32/misc") {
+            let lpFiber = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/fiber") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/misc",
-                    "GetCurrentProcessId",
-                    &[],
+                    "kernel32/fiber",
+                    "DeleteFiber",
+                    &[("lpFiber", &lpFiber)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetCurrentProcessId(machine);
+            let result = winapi::kernel32::DeleteFiber(machine, lpFiber);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetCurrentProcessId_pos.0,
-                    winapi::kernel32::GetCurrentProcessId_pos.1,
+                    winapi::kernel32::DeleteFiber_pos.0,
+                    winapi::kernel32::DeleteFiber_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetCurrentThread(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn DeleteFileA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+            let lpFileName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/thread",
-                    "GetCurrentThread",
-                    &[],
+                    "kernel32/file",
+                    "DeleteFileA",
+                    &[("lpFileName", &lpFileName)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetCurrentThread(machine);
+            let result = winapi::kernel32::DeleteFileA(machine, lpFileName);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetCurrentThread_pos.0,
-                    winapi::kernel32::GetCurrentThread_pos.1,
+                    winapi::kernel32::DeleteFileA_pos.0,
+                    winapi::kernel32::DeleteFileA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetCurrentThreadId(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn DeleteFileW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+            let lpFileName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/thread",
-                    "GetCurrentThreadId",
-                    &[],
+                    "kernel32/file",
+                    "DeleteFileW",
+                    &[("lpFileName", &lpFileName)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetCurrentThreadId(machine);
+            let result = winapi::kernel32::DeleteFileW(machine, lpFileName);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetCurrentThreadId_pos.0,
-                    winapi::kernel32::GetCurrentThreadId_pos.1,
+                    winapi::kernel32::DeleteFileW_pos.0,
+                    winapi::kernel32::DeleteFileW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetEnvironmentStrings(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn DisableThreadLibraryCalls(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let __trace_context = if crate::trace::enabled("kernel32/env") {
+            let hLibModule = <HMODULE>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/dll") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/env",
-                    "GetEnvironmentStrings",
-                    &[],
+                    "kernel32/dll",
+                    "DisableThreadLibraryCalls",
+                    &[("hLibModule", &hLibModule)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetEnvironmentStrings(machine);
+            let result = winapi::kernel32::DisableThreadLibraryCalls(machine, hLibModule);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetEnvironmentStrings_pos.0,
-                    winapi::kernel32::GetEnvironmentStrings_pos.1,
+                    winapi::kernel32::DisableThreadLibraryCalls_pos.0,
+                    winapi::kernel32::DisableThreadLibraryCalls_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetEnvironmentStringsW(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn DisconnectNamedPipe(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let __trace_context = if crate::trace::enabled("kernel32/env") {
+            let hNamedPipe = <HFILE>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/pipe") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/env",
-                    "GetEnvironmentStringsW",
-                    &[],
+                    "kernel32/pipe",
+                    "DisconnectNamedPipe",
+                    &[("hNamedPipe", &hNamedPipe)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetEnvironmentStringsW(machine);
+            let result = winapi::kernel32::DisconnectNamedPipe(machine, hNamedPipe);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetEnvironmentStringsW_pos.0,
-                    winapi::kernel32::GetEnvironmentStringsW_pos.1,
+                    winapi::kernel32::DisconnectNamedPipe_pos.0,
+                    winapi::kernel32::DisconnectNamedPipe_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetEnvironmentVariableA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn DosDateTimeToFileTime(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let name = <Option<&str>>::from_stack(mem, stack_args + 0u32);
-            let buf = <ArrayWithSize<u8>>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/env") {
+            let wFatDate = <u16>::from_stack(mem, stack_args + 0u32);
+            let wFatTime = <u16>::from_stack(mem, stack_args + 4u32);
+            let lpFileTime = <Option<&mut FILETIME>>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/time") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/env",
-                    "GetEnvironmentVariableA",
-                    &[("name", &name), ("buf", &buf)],
+                    "kernel32/time",
+                    "DosDateTimeToFileTime",
+                    &[
+                        ("wFatDate", &wFatDate),
+                        ("wFatTime", &wFatTime),
+                        ("lpFileTime", &lpFileTime),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetEnvironmentVariableA(machine, name, buf);
+            let result =
+                winapi::kernel32::DosDateTimeToFileTime(machine, wFatDate, wFatTime, lpFileTime);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetEnvironmentVariableA_pos.0,
-                    winapi::kernel32::GetEnvironmentVariableA_pos.1,
+                    winapi::kernel32::DosDateTimeToFileTime_pos.0,
+                    winapi::kernel32::DosDateTimeToFileTime_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetEnvironmentVariableW(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn DuplicateHandle(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let name = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
-            let buf = <ArrayWithSize<u16>>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/env") {
+            let hSourceProcessHandle = <HPROCESS>::from_stack(mem, stack_args + 0u32);
+            let hSourceHandle = <HOBJECT>::from_stack(mem, stack_args + 4u32);
+            let hTargetProcessHandle = <HPROCESS>::from_stack(mem, stack_args + 8u32);
+            let lpTargetHandle = <Option<&mut HOBJECT>>::from_stack(mem, stack_args + 12u32);
+            let dwDesiredAccess = <u32>::from_stack(mem, stack_args + 16u32);
+            let bInheritHandle = <bool>::from_stack(mem, stack_args + 20u32);
+            let dwOptions = <u32>::from_stack(mem, stack_args + 24u32);
+            let __trace_context = if crate::trace::enabled("kernel32/object") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/env",
-                    "GetEnvironmentVariableW",
-                    &[("name", &name), ("buf", &buf)],
+                    "kernel32/object",
+                    "DuplicateHandle",
+                    &[
+                        ("hSourceProcessHandle", &hSourceProcessHandle),
+                        ("hSourceHandle", &hSourceHandle),
+                        ("hTargetProcessHandle", &hTargetProcessHandle),
+                        ("lpTargetHandle", &lpTargetHandle),
+                        ("dwDesiredAccess", &dwDesiredAccess),
+                        ("bInheritHandle", &bInheritHandle),
+                        ("dwOptions", &dwOptions),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetEnvironmentVariableW(machine, name, buf);
+            let result = winapi::kernel32::DuplicateHandle(
+                machine,
+                hSourceProcessHandle,
+                hSourceHandle,
+                hTargetProcessHandle,
+                lpTargetHandle,
+                dwDesiredAccess,
+                bInheritHandle,
+                dwOptions,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetEnvironmentVariableW_pos.0,
-                    winapi::kernel32::GetEnvironmentVariableW_pos.1,
+                    winapi::kernel32::DuplicateHandle_pos.0,
+                    winapi::kernel32::DuplicateHandle_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetFileAttributesA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn EnterCriticalSection(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
             let mem = machine.mem().detach();
-            let lpFileName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/file") {
+            let lpCriticalSection = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/thread") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/file",
-                    "GetFileAttributesA",
-                    &[("lpFileName", &lpFileName)],
+                    "kernel32/thread",
+                    "EnterCriticalSection",
+                    &[("lpCriticalSection", &lpCriticalSection)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetFileAttributesA(machine, lpFileName);
-            if let Some(__trace_context) = __trace_context {
-                crate::trace::trace_return(
-                    &__trace_context,
-                    winapi::kernel32::GetFileAttributesA_pos.0,
-                    winapi::kernel32::GetFileAttributesA_pos.1,
-                    &result,
-                );
-            }
-            result.to_raw()
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result =
+                    winapi::kernel32::EnterCriticalSection(machine, lpCriticalSection).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::kernel32::EnterCriticalSection_pos.0,
+                        winapi::kernel32::EnterCriticalSection_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
         }
-        pub unsafe fn GetFileInformationByHandle(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn ExitProcess(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
             let mem = machine.mem().detach();
-            let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
-            let lpFileInformation =
-                <Option<&mut BY_HANDLE_FILE_INFORMATION>>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/file") {
+            let uExitCode = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/misc") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/file",
-                    "GetFileInformationByHandle",
-                    &[("hFile", &hFile), ("lpFileInformation", &lpFileInformation)],
+                    "kernel32/misc",
+                    "ExitProcess",
+                    &[("uExitCode", &uExitCode)],
                 ))
             } else {
                 None
             };
-            let result =
-                winapi::kernel32::GetFileInformationByHandle(machine, hFile, lpFileInformation);
-            if let Some(__trace_context) = __trace_context {
-                crate::trace::trace_return(
-                    &__trace_context,
-                    winapi::kernel32::GetFileInformationByHandle_pos.0,
-                    winapi::kernel32::GetFileInformationByHandle_pos.1,
-                    &result,
-                );
-            }
-            result.to_raw()
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::kernel32::ExitProcess(machine, uExitCode).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::kernel32::ExitProcess_pos.0,
+                        winapi::kernel32::ExitProcess_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
         }
-        pub unsafe fn GetFileSize(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn ExitThread(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
             let mem = machine.mem().detach();
-            let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
-            let lpFileSizeHigh = <Option<&mut u32>>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/file") {
+            let dwExitCode = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/thread") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/file",
-                    "GetFileSize",
-                    &[("hFile", &hFile), ("lpFileSizeHigh", &lpFileSizeHigh)],
+                    "kernel32/thread",
+                    "ExitThread",
+                    &[("dwExitCode", &dwExitCode)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetFileSize(machine, hFile, lpFileSizeHigh);
-            if let Some(__trace_context) = __trace_context {
-                crate::trace::trace_return(
-                    &__trace_context,
-                    winapi::kernel32::GetFileSize_pos.0,
-                    winapi::kernel32::GetFileSize_pos.1,
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::kernel32::ExitThread(machine, dwExitCode).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::kernel32::ExitThread_pos.0,
+                        winapi::kernel32::ExitThread_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn FileTimeToDosDateTime(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpFileTime = <Option<&FILETIME>>::from_stack(mem, stack_args + 0u32);
+            let lpFatDate = <Option<&mut u16>>::from_stack(mem, stack_args + 4u32);
+            let lpFatTime = <Option<&mut u16>>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/time") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/time",
+                    "FileTimeToDosDateTime",
+                    &[
+                        ("lpFileTime", &lpFileTime),
+                        ("lpFatDate", &lpFatDate),
+                        ("lpFatTime", &lpFatTime),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result =
+                winapi::kernel32::FileTimeToDosDateTime(machine, lpFileTime, lpFatDate, lpFatTime);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::FileTimeToDosDateTime_pos.0,
+                    winapi::kernel32::FileTimeToDosDateTime_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetFileTime(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn FileTimeToLocalFileTime(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
-            let lpCreationTime = <Option<&mut FILETIME>>::from_stack(mem, stack_args + 4u32);
-            let lpLastAccessTime = <Option<&mut FILETIME>>::from_stack(mem, stack_args + 8u32);
-            let lpLastWriteTime = <Option<&mut FILETIME>>::from_stack(mem, stack_args + 12u32);
-            let __trace_context = if crate::trace::enabled("kernel32/file") {
+            let lpFileTime = <Option<&FILETIME>>::from_stack(mem, stack_args + 0u32);
+            let lpLocalFileTime = <Option<&mut FILETIME>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/time") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/file",
-                    "GetFileTime",
+                    "kernel32/time",
+                    "FileTimeToLocalFileTime",
                     &[
-                        ("hFile", &hFile),
-                        ("lpCreationTime", &lpCreationTime),
-                        ("lpLastAccessTime", &lpLastAccessTime),
-                        ("lpLastWriteTime", &lpLastWriteTime),
+                        ("lpFileTime", &lpFileTime),
+                        ("lpLocalFileTime", &lpLocalFileTime),
                     ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetFileTime(
-                machine,
-                hFile,
-                lpCreationTime,
-                lpLastAccessTime,
-                lpLastWriteTime,
-            );
+            let result =
+                winapi::kernel32::FileTimeToLocalFileTime(machine, lpFileTime, lpLocalFileTime);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetFileTime_pos.0,
-                    winapi::kernel32::GetFileTime_pos.1,
+                    winapi::kernel32::FileTimeToLocalFileTime_pos.0,
+                    winapi::kernel32::FileTimeToLocalFileTime_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetFileType(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn FileTimeToSystemTime(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/file") {
+            let lpFileTime = <Option<&FILETIME>>::from_stack(mem, stack_args + 0u32);
+            let lpSystemTime = <Option<&mut SYSTEMTIME>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/time") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/file",
-                    "GetFileType",
-                    &[("hFile", &hFile)],
+                    "kernel32/time",
+                    "FileTimeToSystemTime",
+                    &[("lpFileTime", &lpFileTime), ("lpSystemTime", &lpSystemTime)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetFileType(machine, hFile);
+            let result = winapi::kernel32::FileTimeToSystemTime(machine, lpFileTime, lpSystemTime);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetFileType_pos.0,
-                    winapi::kernel32::GetFileType_pos.1,
+                    winapi::kernel32::FileTimeToSystemTime_pos.0,
+                    winapi::kernel32::FileTimeToSystemTime_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetFullPathNameA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn FindClose(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpFileName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
-            let nBufferLength = <u32>::from_stack(mem, stack_args + 4u32);
-            let lpBuffer = <u32>::from_stack(mem, stack_args + 8u32);
-            let lpFilePart = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
+            let hFindFile = <HFIND>::from_stack(mem, stack_args + 0u32);
             let __trace_context = if crate::trace::enabled("kernel32/file") {
                 Some(crate::trace::trace_begin(
                     "kernel32/file",
-                    "GetFullPathNameA",
-                    &[
-                        ("lpFileName", &lpFileName),
-                        ("nBufferLength", &nBufferLength),
-                        ("lpBuffer", &lpBuffer),
-                        ("lpFilePart", &lpFilePart),
-                    ],
+                    "FindClose",
+                    &[("hFindFile", &hFindFile)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetFullPathNameA(
-                machine,
-                lpFileName,
-                nBufferLength,
-                lpBuffer,
-                lpFilePart,
-            );
+            let result = winapi::kernel32::FindClose(machine, hFindFile);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetFullPathNameA_pos.0,
-                    winapi::kernel32::GetFullPathNameA_pos.1,
+                    winapi::kernel32::FindClose_pos.0,
+                    winapi::kernel32::FindClose_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetFullPathNameW(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn FindFirstFileA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpFileName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
-            let nBufferLength = <u32>::from_stack(mem, stack_args + 4u32);
-            let lpBuffer = <u32>::from_stack(mem, stack_args + 8u32);
-            let lpFilePart = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
+            let lpFileName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+            let lpFindFileData =
+                <Option<&mut WIN32_FIND_DATAA>>::from_stack(mem, stack_args + 4u32);
             let __trace_context = if crate::trace::enabled("kernel32/file") {
                 Some(crate::trace::trace_begin(
                     "kernel32/file",
-                    "GetFullPathNameW",
+                    "FindFirstFileA",
                     &[
                         ("lpFileName", &lpFileName),
-                        ("nBufferLength", &nBufferLength),
-                        ("lpBuffer", &lpBuffer),
-                        ("lpFilePart", &lpFilePart),
+                        ("lpFindFileData", &lpFindFileData),
                     ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetFullPathNameW(
-                machine,
-                lpFileName,
-                nBufferLength,
-                lpBuffer,
-                lpFilePart,
-            );
+            let result = winapi::kernel32::FindFirstFileA(machine, lpFileName, lpFindFileData);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetFullPathNameW_pos.0,
-                    winapi::kernel32::GetFullPathNameW_pos.1,
+                    winapi::kernel32::FindFirstFileA_pos.0,
+                    winapi::kernel32::FindFirstFileA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetLastError(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn FindNextFileA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let __trace_context = if crate::trace::enabled("kernel32/misc") {
+            let hFindFile = <HFIND>::from_stack(mem, stack_args + 0u32);
+            let lpFindFileData =
+                <Option<&mut WIN32_FIND_DATAA>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/misc",
-                    "GetLastError",
-                    &[],
+                    "kernel32/file",
+                    "FindNextFileA",
+                    &[
+                        ("hFindFile", &hFindFile),
+                        ("lpFindFileData", &lpFindFileData),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetLastError(machine);
+            let result = winapi::kernel32::FindNextFileA(machine, hFindFile, lpFindFileData);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetLastError_pos.0,
-                    winapi::kernel32::GetLastError_pos.1,
+                    winapi::kernel32::FindNextFileA_pos.0,
+                    winapi::kernel32::FindNextFileA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetLocalTime(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn FindResourceA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpSystemTime = <Option<&mut SYSTEMTIME>>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/time") {
+            let hModule = <HMODULE>::from_stack(mem, stack_args + 0u32);
+            let lpName = <ResourceKey<&str>>::from_stack(mem, stack_args + 4u32);
+            let lpType = <ResourceKey<&str>>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/resource") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/time",
-                    "GetLocalTime",
-                    &[("lpSystemTime", &lpSystemTime)],
+                    "kernel32/resource",
+                    "FindResourceA",
+                    &[
+                        ("hModule", &hModule),
+                        ("lpName", &lpName),
+                        ("lpType", &lpType),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetLocalTime(machine, lpSystemTime);
+            let result = winapi::kernel32::FindResourceA(machine, hModule, lpName, lpType);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetLocalTime_pos.0,
-                    winapi::kernel32::GetLocalTime_pos.1,
+                    winapi::kernel32::FindResourceA_pos.0,
+                    winapi::kernel32::FindResourceA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetModuleFileNameA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn FindResourceW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
             let hModule = <HMODULE>::from_stack(mem, stack_args + 0u32);
-            let filename = <ArrayWithSizeMut<u8>>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/dll") {
+            let lpName = <ResourceKey<&Str16>>::from_stack(mem, stack_args + 4u32);
+            let lpType = <ResourceKey<&Str16>>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/resource") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/dll",
-                    "GetModuleFileNameA",
-                    &[("hModule", &hModule), ("filename", &filename)],
+                    "kernel32/resource",
+                    "FindResourceW",
+                    &[
+                        ("hModule", &hModule),
+                        ("lpName", &lpName),
+                        ("lpType", &lpType),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetModuleFileNameA(machine, hModule, filename);
+            let result = winapi::kernel32::FindResourceW(machine, hModule, lpName, lpType);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetModuleFileNameA_pos.0,
-                    winapi::kernel32::GetModuleFileNameA_pos.1,
+                    winapi::kernel32::FindResourceW_pos.0,
+                    winapi::kernel32::FindResourceW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetModuleFileNameW(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn FlushConsoleInputBuffer(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hModule = <HMODULE>::from_stack(mem, stack_args + 0u32);
-            let _lpFilename = <u32>::from_stack(mem, stack_args + 4u32);
-            let _nSize = <u32>::from_stack(mem, stack_args + 8u32);
-            let __trace_context = if crate::trace::enabled("kernel32/dll") {
+            let hConsoleInput = <HFILE>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/console") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/dll",
-                    "GetModuleFileNameW",
-                    &[
-                        ("hModule", &hModule),
-                        ("lpFilename", &_lpFilename),
-                        ("nSize", &_nSize),
-                    ],
+                    "kernel32/console",
+                    "FlushConsoleInputBuffer",
+                    &[("hConsoleInput", &hConsoleInput)],
                 ))
             } else {
                 None
             };
-            let result =
-                winapi::kernel32::GetModuleFileNameW(machine, hModule, _lpFilename, _nSize);
+            let result = winapi::kernel32::FlushConsoleInputBuffer(machine, hConsoleInput);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetModuleFileNameW_pos.0,
-                    winapi::kernel32::GetModuleFileNameW_pos.1,
+                    winapi::kernel32::FlushConsoleInputBuffer_pos.0,
+                    winapi::kernel32::FlushConsoleInputBuffer_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetModuleHandleA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn FlushFileBuffers(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpModuleName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/dll") {
+            let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/dll",
-                    "GetModuleHandleA",
-                    &[("lpModuleName", &lpModuleName)],
+                    "kernel32/file",
+                    "FlushFileBuffers",
+                    &[("hFile", &hFile)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetModuleHandleA(machine, lpModuleName);
+            let result = winapi::kernel32::FlushFileBuffers(machine, hFile);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetModuleHandleA_pos.0,
-                    winapi::kernel32::GetModuleHandleA_pos.1,
+                    winapi::kernel32::FlushFileBuffers_pos.0,
+                    winapi::kernel32::FlushFileBuffers_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetModuleHandleExW(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn FlushInstructionCache(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let dwFlags = <u32>::from_stack(mem, stack_args + 0u32);
-            let lpModuleName = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
-            let hModule = <Option<&mut HMODULE>>::from_stack(mem, stack_args + 8u32);
-            let __trace_context = if crate::trace::enabled("kernel32/dll") {
+            let hProcess = <HPROCESS>::from_stack(mem, stack_args + 0u32);
+            let lpBaseAddress = <u32>::from_stack(mem, stack_args + 4u32);
+            let dwSize = <u32>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/memory") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/dll",
-                    "GetModuleHandleExW",
+                    "kernel32/memory",
+                    "FlushInstructionCache",
                     &[
-                        ("dwFlags", &dwFlags),
-                        ("lpModuleName", &lpModuleName),
-                        ("hModule", &hModule),
+                        ("hProcess", &hProcess),
+                        ("lpBaseAddress", &lpBaseAddress),
+                        ("dwSize", &dwSize),
                     ],
                 ))
             } else {
                 None
             };
             let result =
-                winapi::kernel32::GetModuleHandleExW(machine, dwFlags, lpModuleName, hModule);
+                winapi::kernel32::FlushInstructionCache(machine, hProcess, lpBaseAddress, dwSize);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetModuleHandleExW_pos.0,
-                    winapi::kernel32::GetModuleHandleExW_pos.1,
+                    winapi::kernel32::FlushInstructionCache_pos.0,
+                    winapi::kernel32::FlushInstructionCache_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetModuleHandleW(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn FlushViewOfFile(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpModuleName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/dll") {
+            let lpBaseAddress = <u32>::from_stack(mem, stack_args + 0u32);
+            let dwNumberOfBytesToFlush = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/mapping") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/dll",
-                    "GetModuleHandleW",
-                    &[("lpModuleName", &lpModuleName)],
+                    "kernel32/mapping",
+                    "FlushViewOfFile",
+                    &[
+                        ("lpBaseAddress", &lpBaseAddress),
+                        ("dwNumberOfBytesToFlush", &dwNumberOfBytesToFlush),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetModuleHandleW(machine, lpModuleName);
+            let result =
+                winapi::kernel32::FlushViewOfFile(machine, lpBaseAddress, dwNumberOfBytesToFlush);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetModuleHandleW_pos.0,
-                    winapi::kernel32::GetModuleHandleW_pos.1,
+                    winapi::kernel32::FlushViewOfFile_pos.0,
+                    winapi::kernel32::FlushViewOfFile_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetOEMCP(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn FormatMessageA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let __trace_context = if crate::trace::enabled("kernel32/nls") {
-                Some(crate::trace::trace_begin("kernel32/nls", "GetOEMCP", &[]))
+            let dwFlags = <Result<FormatMessageFlags, u32>>::from_stack(mem, stack_args + 0u32);
+            let lpSource = <u32>::from_stack(mem, stack_args + 4u32);
+            let dwMessageId = <u32>::from_stack(mem, stack_args + 8u32);
+            let dwLanguageId = <u32>::from_stack(mem, stack_args + 12u32);
+            let lpBuffer = <u32>::from_stack(mem, stack_args + 16u32);
+            let nSize = <u32>::from_stack(mem, stack_args + 20u32);
+            let args = <u32>::from_stack(mem, stack_args + 24u32);
+            let __trace_context = if crate::trace::enabled("kernel32/message") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/message",
+                    "FormatMessageA",
+                    &[
+                        ("dwFlags", &dwFlags),
+                        ("lpSource", &lpSource),
+                        ("dwMessageId", &dwMessageId),
+                        ("dwLanguageId", &dwLanguageId),
+                        ("lpBuffer", &lpBuffer),
+                        ("nSize", &nSize),
+                        ("args", &args),
+                    ],
+                ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetOEMCP(machine);
+            let result = winapi::kernel32::FormatMessageA(
+                machine,
+                dwFlags,
+                lpSource,
+                dwMessageId,
+                dwLanguageId,
+                lpBuffer,
+                nSize,
+                args,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetOEMCP_pos.0,
-                    winapi::kernel32::GetOEMCP_pos.1,
+                    winapi::kernel32::FormatMessageA_pos.0,
+                    winapi::kernel32::FormatMessageA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetPrivateProfileIntW(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn FormatMessageW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpAppName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
-            let lpKeyName = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
-            let nDefault = <u32>::from_stack(mem, stack_args + 8u32);
-            let lpFileName = <Option<&Str16>>::from_stack(mem, stack_args + 12u32);
-            let __trace_context = if crate::trace::enabled("kernel32/ini") {
+            let dwFlags = <Result<FormatMessageFlags, u32>>::from_stack(mem, stack_args + 0u32);
+            let lpSource = <u32>::from_stack(mem, stack_args + 4u32);
+            let dwMessageId = <u32>::from_stack(mem, stack_args + 8u32);
+            let dwLanguageId = <u32>::from_stack(mem, stack_args + 12u32);
+            let lpBuffer = <u32>::from_stack(mem, stack_args + 16u32);
+            let nSize = <u32>::from_stack(mem, stack_args + 20u32);
+            let args = <u32>::from_stack(mem, stack_args + 24u32);
+            let __trace_context = if crate::trace::enabled("kernel32/message") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/ini",
-                    "GetPrivateProfileIntW",
+                    "kernel32/message",
+                    "FormatMessageW",
                     &[
-                        ("lpAppName", &lpAppName),
-                        ("lpKeyName", &lpKeyName),
-                        ("nDefault", &nDefault),
-                        ("lpFileName", &lpFileName),
+                        ("dwFlags", &dwFlags),
+                        ("lpSource", &lpSource),
+                        ("dwMessageId", &dwMessageId),
+                        ("dwLanguageId", &dwLanguageId),
+                        ("lpBuffer", &lpBuffer),
+                        ("nSize", &nSize),
+                        ("args", &args),
                     ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetPrivateProfileIntW(
-                machine, lpAppName, lpKeyName, nDefault, lpFileName,
+            let result = winapi::kernel32::FormatMessageW(
+                machine,
+                dwFlags,
+                lpSource,
+                dwMessageId,
+                dwLanguageId,
+                lpBuffer,
+                nSize,
+                args,
             );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetPrivateProfileIntW_pos.0,
-                    winapi::kernel32::GetPrivateProfileIntW_pos.1,
+                    winapi::kernel32::FormatMessageW_pos.0,
+                    winapi::kernel32::FormatMessageW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetPrivateProfileStringW(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn FreeConsole(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpAppName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
-            let lpKeyName = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
-            let lpDefault = <Option<&Str16>>::from_stack(mem, stack_args + 8u32);
-            let lpReturnedString = <ArrayWithSizeMut<u16>>::from_stack(mem, stack_args + 12u32);
-            let lpFileName = <Option<&Str16>>::from_stack(mem, stack_args + 20u32);
-            let __trace_context = if crate::trace::enabled("kernel32/ini") {
+            let __trace_context = if crate::trace::enabled("kernel32/console") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/ini",
-                    "GetPrivateProfileStringW",
-                    &[
-                        ("lpAppName", &lpAppName),
-                        ("lpKeyName", &lpKeyName),
-                        ("lpDefault", &lpDefault),
-                        ("lpReturnedString", &lpReturnedString),
-                        ("lpFileName", &lpFileName),
-                    ],
+                    "kernel32/console",
+                    "FreeConsole",
+                    &[],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetPrivateProfileStringW(
-                machine,
-                lpAppName,
-                lpKeyName,
-                lpDefault,
-                lpReturnedString,
-                lpFileName,
-            );
+            let result = winapi::kernel32::FreeConsole(machine);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetPrivateProfileStringW_pos.0,
-                    winapi::kernel32::GetPrivateProfileStringW_pos.1,
+                    winapi::kernel32::FreeConsole_pos.0,
+                    winapi::kernel32::FreeConsole_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetProcAddress(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn FreeEnvironmentStringsA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hModule = <HMODULE>::from_stack(mem, stack_args + 0u32);
-            let lpProcName = <GetProcAddressArg>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/dll") {
+            let _penv = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/env") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/dll",
-                    "GetProcAddress",
-                    &[("hModule", &hModule), ("lpProcName", &lpProcName)],
+                    "kernel32/env",
+                    "FreeEnvironmentStringsA",
+                    &[("penv", &_penv)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetProcAddress(machine, hModule, lpProcName);
+            let result = winapi::kernel32::FreeEnvironmentStringsA(machine, _penv);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetProcAddress_pos.0,
-                    winapi::kernel32::GetProcAddress_pos.1,
+                    winapi::kernel32::FreeEnvironmentStringsA_pos.0,
+                    winapi::kernel32::FreeEnvironmentStringsA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetProcessHeap(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn FreeEnvironmentStringsW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+            let __trace_context = if crate::trace::enabled("kernel32/env") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/memory",
-                    "GetProcessHeap",
+                    "kernel32/env",
+                    "FreeEnvironmentStringsW",
                     &[],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetProcessHeap(machine);
+            let result = winapi::kernel32::FreeEnvironmentStringsW(machine);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetProcessHeap_pos.0,
-                    winapi::kernel32::GetProcessHeap_pos.1,
+                    winapi::kernel32::FreeEnvironmentStringsW_pos.0,
+                    winapi::kernel32::FreeEnvironmentStringsW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetProfileIntW(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn FreeLibrary(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
             let mem = machine.mem().detach();
-            let lpAppName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
-            let lpKeyName = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
-            let nDefault = <i32>::from_stack(mem, stack_args + 8u32);
-            let __trace_context = if crate::trace::enabled("kernel32/ini") {
+            let hLibModule = <HMODULE>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/dll") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/ini",
-                    "GetProfileIntW",
-                    &[
-                        ("lpAppName", &lpAppName),
-                        ("lpKeyName", &lpKeyName),
-                        ("nDefault", &nDefault),
-                    ],
+                    "kernel32/dll",
+                    "FreeLibrary",
+                    &[("hLibModule", &hLibModule)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetProfileIntW(machine, lpAppName, lpKeyName, nDefault);
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::kernel32::FreeLibrary(machine, hLibModule).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::kernel32::FreeLibrary_pos.0,
+                        winapi::kernel32::FreeLibrary_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn GetACP(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let __trace_context = if crate::trace::enabled("kernel32/nls") {
+                Some(crate::trace::trace_begin("kernel32/nls", "GetACP", &[]))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::GetACP(machine);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetProfileIntW_pos.0,
-                    winapi::kernel32::GetProfileIntW_pos.1,
+                    winapi::kernel32::GetACP_pos.0,
+                    winapi::kernel32::GetACP_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetProfileStringW(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetCPInfo(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpAppName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
-            let lpKeyName = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
-            let lpDefault = <Option<&Str16>>::from_stack(mem, stack_args + 8u32);
-            let lpReturnedString = <ArrayWithSizeMut<u16>>::from_stack(mem, stack_args + 12u32);
-            let __trace_context = if crate::trace::enabled("kernel32/ini") {
+            let CodePage = <u32>::from_stack(mem, stack_args + 0u32);
+            let lpCPInfo = <Option<&mut CPINFO>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/nls") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/ini",
-                    "GetProfileStringW",
-                    &[
-                        ("lpAppName", &lpAppName),
-                        ("lpKeyName", &lpKeyName),
-                        ("lpDefault", &lpDefault),
-                        ("lpReturnedString", &lpReturnedString),
-                    ],
+                    "kernel32/nls",
+                    "GetCPInfo",
+                    &[("CodePage", &CodePage), ("lpCPInfo", &lpCPInfo)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetProfileStringW(
-                machine,
-                lpAppName,
-                lpKeyName,
-                lpDefault,
-                lpReturnedString,
-            );
+            let result = winapi::kernel32::GetCPInfo(machine, CodePage, lpCPInfo);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetProfileStringW_pos.0,
-                    winapi::kernel32::GetProfileStringW_pos.1,
+                    winapi::kernel32::GetCPInfo_pos.0,
+                    winapi::kernel32::GetCPInfo_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetStartupInfoA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetCommandLineA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpStartupInfo = <Option<&mut STARTUPINFOA>>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/dll") {
+            let __trace_context = if crate::trace::enabled("kernel32/init") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/dll",
-                    "GetStartupInfoA",
-                    &[("lpStartupInfo", &lpStartupInfo)],
+                    "kernel32/init",
+                    "GetCommandLineA",
+                    &[],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetStartupInfoA(machine, lpStartupInfo);
+            let result = winapi::kernel32::GetCommandLineA(machine);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetStartupInfoA_pos.0,
-                    winapi::kernel32::GetStartupInfoA_pos.1,
+                    winapi::kernel32::GetCommandLineA_pos.0,
+                    winapi::kernel32::GetCommandLineA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetStartupInfoW(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetCommandLineW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpStartupInfo = <Option<&mut STARTUPINFOA>>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/dll") {
+            let __trace_context = if crate::trace::enabled("kernel32/init") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/dll",
-                    "GetStartupInfoW",
-                    &[("lpStartupInfo", &lpStartupInfo)],
+                    "kernel32/init",
+                    "GetCommandLineW",
+                    &[],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetStartupInfoW(machine, lpStartupInfo);
+            let result = winapi::kernel32::GetCommandLineW(machine);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetStartupInfoW_pos.0,
-                    winapi::kernel32::GetStartupInfoW_pos.1,
+                    winapi::kernel32::GetCommandLineW_pos.0,
+                    winapi::kernel32::GetCommandLineW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetStdHandle(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetComputerNameA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let nStdHandle = <Result<STD, u32>>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/file") {
+            let lpBuffer = <u32>::from_stack(mem, stack_args + 0u32);
+            let nSize = <Option<&mut u32>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/sysinfo") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/file",
-                    "GetStdHandle",
-                    &[("nStdHandle", &nStdHandle)],
+                    "kernel32/sysinfo",
+                    "GetComputerNameA",
+                    &[("lpBuffer", &lpBuffer), ("nSize", &nSize)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetStdHandle(machine, nStdHandle);
+            let result = winapi::kernel32::GetComputerNameA(machine, lpBuffer, nSize);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetStdHandle_pos.0,
-                    winapi::kernel32::GetStdHandle_pos.1,
+                    winapi::kernel32::GetComputerNameA_pos.0,
+                    winapi::kernel32::GetComputerNameA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetStringTypeA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetComputerNameW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let Locale = <LCID>::from_stack(mem, stack_args + 0u32);
-            let dwInfoType = <u32>::from_stack(mem, stack_args + 4u32);
-            let lpSrcStr = <u32>::from_stack(mem, stack_args + 8u32);
-            let cchSrc = <i32>::from_stack(mem, stack_args + 12u32);
-            let lpCharType = <Option<&mut u32>>::from_stack(mem, stack_args + 16u32);
-            let __trace_context = if crate::trace::enabled("kernel32/nls") {
+            let lpBuffer = <u32>::from_stack(mem, stack_args + 0u32);
+            let nSize = <Option<&mut u32>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/sysinfo") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/nls",
-                    "GetStringTypeA",
-                    &[
-                        ("Locale", &Locale),
-                        ("dwInfoType", &dwInfoType),
-                        ("lpSrcStr", &lpSrcStr),
-                        ("cchSrc", &cchSrc),
-                        ("lpCharType", &lpCharType),
-                    ],
+                    "kernel32/sysinfo",
+                    "GetComputerNameW",
+                    &[("lpBuffer", &lpBuffer), ("nSize", &nSize)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetStringTypeA(
-                machine, Locale, dwInfoType, lpSrcStr, cchSrc, lpCharType,
-            );
+            let result = winapi::kernel32::GetComputerNameW(machine, lpBuffer, nSize);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetStringTypeA_pos.0,
-                    winapi::kernel32::GetStringTypeA_pos.1,
+                    winapi::kernel32::GetComputerNameW_pos.0,
+                    winapi::kernel32::GetComputerNameW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetStringTypeW(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetConsoleCP(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let dwInfoType = <u32>::from_stack(mem, stack_args + 0u32);
-            let lpSrcStr = <u32>::from_stack(mem, stack_args + 4u32);
-            let cchSrc = <i32>::from_stack(mem, stack_args + 8u32);
-            let lpCharType = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
-            let __trace_context = if crate::trace::enabled("kernel32/nls") {
+            let __trace_context = if crate::trace::enabled("kernel32/console") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/nls",
-                    "GetStringTypeW",
-                    &[
-                        ("dwInfoType", &dwInfoType),
-                        ("lpSrcStr", &lpSrcStr),
-                        ("cchSrc", &cchSrc),
-                        ("lpCharType", &lpCharType),
-                    ],
+                    "kernel32/console",
+                    "GetConsoleCP",
+                    &[],
                 ))
             } else {
                 None
             };
-            let result =
-                winapi::kernel32::GetStringTypeW(machine, dwInfoType, lpSrcStr, cchSrc, lpCharType);
+            let result = winapi::kernel32::GetConsoleCP(machine);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetStringTypeW_pos.0,
-                    winapi::kernel32::GetStringTypeW_pos.1,
+                    winapi::kernel32::GetConsoleCP_pos.0,
+                    winapi::kernel32::GetConsoleCP_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetSystemDirectoryA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetConsoleMode(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpBuffer = <u32>::from_stack(mem, stack_args + 0u32);
-            let uSize = <u32>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/misc") {
+            let hConsoleHandle = <HFILE>::from_stack(mem, stack_args + 0u32);
+            let lpMode = <Option<&mut u32>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/console") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/misc",
-                    "GetSystemDirectoryA",
-                    &[("lpBuffer", &lpBuffer), ("uSize", &uSize)],
+                    "kernel32/console",
+                    "GetConsoleMode",
+                    &[("hConsoleHandle", &hConsoleHandle), ("lpMode", &lpMode)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetSystemDirectoryA(machine, lpBuffer, uSize);
+            let result = winapi::kernel32::GetConsoleMode(machine, hConsoleHandle, lpMode);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetSystemDirectoryA_pos.0,
-                    winapi::kernel32::GetSystemDirectoryA_pos.1,
+                    winapi::kernel32::GetConsoleMode_pos.0,
+                    winapi::kernel32::GetConsoleMode_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetSystemTime(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetConsoleOutputCP(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpSystemTime = <Option<&mut SYSTEMTIME>>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/time") {
+            let __trace_context = if crate::trace::enabled("kernel32/console") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/time",
-                    "GetSystemTime",
-                    &[("lpSystemTime", &lpSystemTime)],
+                    "kernel32/console",
+                    "GetConsoleOutputCP",
+                    &[],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetSystemTime(machine, lpSystemTime);
+            let result = winapi::kernel32::GetConsoleOutputCP(machine);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetSystemTime_pos.0,
-                    winapi::kernel32::GetSystemTime_pos.1,
+                    winapi::kernel32::GetConsoleOutputCP_pos.0,
+                    winapi::kernel32::GetConsoleOutputCP_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetSystemTimeAsFileTime(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetConsoleScreenBufferInfo(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpSystemTimeAsFileTime =
-                <Option<&mut FILETIME>>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/time") {
+            let _hConsoleOutput = <HANDLE<()>>::from_stack(mem, stack_args + 0u32);
+            let lpConsoleScreenBufferInfo =
+                <Option<&mut CONSOLE_SCREEN_BUFFER_INFO>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/console") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/time",
-                    "GetSystemTimeAsFileTime",
-                    &[("lpSystemTimeAsFileTime", &lpSystemTimeAsFileTime)],
+                    "kernel32/console",
+                    "GetConsoleScreenBufferInfo",
+                    &[
+                        ("hConsoleOutput", &_hConsoleOutput),
+                        ("lpConsoleScreenBufferInfo", &lpConsoleScreenBufferInfo),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetSystemTimeAsFileTime(machine, lpSystemTimeAsFileTime);
+            let result = winapi::kernel32::GetConsoleScreenBufferInfo(
+                machine,
+                _hConsoleOutput,
+                lpConsoleScreenBufferInfo,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetSystemTimeAsFileTime_pos.0,
-                    winapi::kernel32::GetSystemTimeAsFileTime_pos.1,
+                    winapi::kernel32::GetConsoleScreenBufferInfo_pos.0,
+                    winapi::kernel32::GetConsoleScreenBufferInfo_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetTickCount(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetConsoleTitleA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let __trace_context = if crate::trace::enabled("kernel32/time") {
+            let lpConsoleTitle = <ArrayWithSizeMut<u8>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/console") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/time",
-                    "GetTickCount",
-                    &[],
+                    "kernel32/console",
+                    "GetConsoleTitleA",
+                    &[("lpConsoleTitle", &lpConsoleTitle)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetTickCount(machine);
+            let result = winapi::kernel32::GetConsoleTitleA(machine, lpConsoleTitle);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetTickCount_pos.0,
-                    winapi::kernel32::GetTickCount_pos.1,
+                    winapi::kernel32::GetConsoleTitleA_pos.0,
+                    winapi::kernel32::GetConsoleTitleA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetTimeZoneInformation(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetConsoleTitleW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpTimeZoneInformation =
-                <Option<&mut TIME_ZONE_INFORMATION>>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/time") {
+            let lpConsoleTitle = <ArrayWithSizeMut<u16>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/console") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/time",
-                    "GetTimeZoneInformation",
-                    &[("lpTimeZoneInformation", &lpTimeZoneInformation)],
+                    "kernel32/console",
+                    "GetConsoleTitleW",
+                    &[("lpConsoleTitle", &lpConsoleTitle)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetTimeZoneInformation(machine, lpTimeZoneInformation);
+            let result = winapi::kernel32::GetConsoleTitleW(machine, lpConsoleTitle);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetTimeZoneInformation_pos.0,
-                    winapi::kernel32::GetTimeZoneInformation_pos.1,
+                    winapi::kernel32::GetConsoleTitleW_pos.0,
+                    winapi::kernel32::GetConsoleTitleW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetVersion(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetConsoleWindow(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let __trace_context = if crate::trace::enabled("kernel32/misc") {
+            let __trace_context = if crate::trace::enabled("kernel32/console") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/misc",
-                    "GetVersion",
+                    "kernel32/console",
+                    "GetConsoleWindow",
                     &[],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetVersion(machine);
+            let result = winapi::kernel32::GetConsoleWindow(machine);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetVersion_pos.0,
-                    winapi::kernel32::GetVersion_pos.1,
+                    winapi::kernel32::GetConsoleWindow_pos.0,
+                    winapi::kernel32::GetConsoleWindow_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetVersionExA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetCurrentDirectoryA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpVersionInformation =
-                <Option<&mut OSVERSIONINFO>>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/misc") {
+            let nBufferLength = <u32>::from_stack(mem, stack_args + 0u32);
+            let lpBuffer = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/misc",
-                    "GetVersionExA",
-                    &[("lpVersionInformation", &lpVersionInformation)],
+                    "kernel32/file",
+                    "GetCurrentDirectoryA",
+                    &[("nBufferLength", &nBufferLength), ("lpBuffer", &lpBuffer)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetVersionExA(machine, lpVersionInformation);
+            let result = winapi::kernel32::GetCurrentDirectoryA(machine, nBufferLength, lpBuffer);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetVersionExA_pos.0,
-                    winapi::kernel32::GetVersionExA_pos.1,
+                    winapi::kernel32::GetCurrentDirectoryA_pos.0,
+                    winapi::kernel32::GetCurrentDirectoryA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GetWindowsDirectoryA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetCurrentDirectoryW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpBuffer = <u32>::from_stack(mem, stack_args + 0u32);
-            let uSize = <u32>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/misc") {
+            let nBufferLength = <u32>::from_stack(mem, stack_args + 0u32);
+            let lpBuffer = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/misc",
-                    "GetWindowsDirectoryA",
-                    &[("lpBuffer", &lpBuffer), ("uSize", &uSize)],
+                    "kernel32/file",
+                    "GetCurrentDirectoryW",
+                    &[("nBufferLength", &nBufferLength), ("lpBuffer", &lpBuffer)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GetWindowsDirectoryA(machine, lpBuffer, uSize);
+            let result = winapi::kernel32::GetCurrentDirectoryW(machine, nBufferLength, lpBuffer);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GetWindowsDirectoryA_pos.0,
-                    winapi::kernel32::GetWindowsDirectoryA_pos.1,
+                    winapi::kernel32::GetCurrentDirectoryW_pos.0,
+                    winapi::kernel32::GetCurrentDirectoryW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GlobalAlloc(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetCurrentProcess(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let uFlags = <GMEM>::from_stack(mem, stack_args + 0u32);
-            let dwBytes = <u32>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+            let __trace_context = if crate::trace::enabled("kernel32/process") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/memory",
-                    "GlobalAlloc",
-                    &[("uFlags", &uFlags), ("dwBytes", &dwBytes)],
+                    "kernel32/process",
+                    "GetCurrentProcess",
+                    &[],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GlobalAlloc(machine, uFlags, dwBytes);
+            let result = winapi::kernel32::GetCurrentProcess(machine);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GlobalAlloc_pos.0,
-                    winapi::kernel32::GlobalAlloc_pos.1,
+                    winapi::kernel32::GetCurrentProcess_pos.0,
+                    winapi::kernel32::GetCurrentProcess_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GlobalFlags(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetCurrentProcessId(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hMem = <u32>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+            let __trace_context = if crate::trace::enabled("kernel32/process") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/memory",
-                    "GlobalFlags",
-                    &[("hMem", &hMem)],
+                    "kernel32/process",
+                    "GetCurrentProcessId",
+                    &[],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GlobalFlags(machine, hMem);
+            let result = winapi::kernel32::GetCurrentProcessId(machine);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GlobalFlags_pos.0,
-                    winapi::kernel32::GlobalFlags_pos.1,
+                    winapi::kernel32::GetCurrentProcessId_pos.0,
+                    winapi::kernel32::GetCurrentProcessId_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GlobalFree(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetCurrentThread(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hMem = <u32>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+            let __trace_context = if crate::trace::enabled("kernel32/thread") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/memory",
-                    "GlobalFree",
-                    &[("hMem", &hMem)],
+                    "kernel32/thread",
+                    "GetCurrentThread",
+                    &[],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GlobalFree(machine, hMem);
+            let result = winapi::kernel32::GetCurrentThread(machine);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GlobalFree_pos.0,
-                    winapi::kernel32::GlobalFree_pos.1,
+                    winapi::kernel32::GetCurrentThread_pos.0,
+                    winapi::kernel32::GetCurrentThread_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn GlobalReAlloc(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetCurrentThreadId(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hMem = <u32>::from_stack(mem, stack_args + 0u32);
-            let dwBytes = <u32>::from_stack(mem, stack_args + 4u32);
-            let uFlags = <GMEM>::from_stack(mem, stack_args + 8u32);
-            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+            let __trace_context = if crate::trace::enabled("kernel32/thread") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/memory",
-                    "GlobalReAlloc",
-                    &[("hMem", &hMem), ("dwBytes", &dwBytes), ("uFlags", &uFlags)],
+                    "kernel32/thread",
+                    "GetCurrentThreadId",
+                    &[],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::GlobalReAlloc(machine, hMem, dwBytes, uFlags);
+            let result = winapi::kernel32::GetCurrentThreadId(machine);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::GlobalReAlloc_pos.0,
-                    winapi::kernel32::GlobalReAlloc_pos.1,
+                    winapi::kernel32::GetCurrentThreadId_pos.0,
+                    winapi::kernel32::GetCurrentThreadId_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn HeapAlloc(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetDiskFreeSpaceA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hHeap = <u32>::from_stack(mem, stack_args + 0u32);
-            let dwFlags = <Result<HeapAllocFlags, u32>>::from_stack(mem, stack_args + 4u32);
-            let dwBytes = <u32>::from_stack(mem, stack_args + 8u32);
-            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+            let lpRootPathName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+            let lpSectorsPerCluster = <Option<&mut u32>>::from_stack(mem, stack_args + 4u32);
+            let lpBytesPerSector = <Option<&mut u32>>::from_stack(mem, stack_args + 8u32);
+            let lpNumberOfFreeClusters = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
+            let lpTotalNumberOfClusters = <Option<&mut u32>>::from_stack(mem, stack_args + 16u32);
+            let __trace_context = if crate::trace::enabled("kernel32/volume") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/memory",
-                    "HeapAlloc",
+                    "kernel32/volume",
+                    "GetDiskFreeSpaceA",
                     &[
-                        ("hHeap", &hHeap),
-                        ("dwFlags", &dwFlags),
-                        ("dwBytes", &dwBytes),
+                        ("lpRootPathName", &lpRootPathName),
+                        ("lpSectorsPerCluster", &lpSectorsPerCluster),
+                        ("lpBytesPerSector", &lpBytesPerSector),
+                        ("lpNumberOfFreeClusters", &lpNumberOfFreeClusters),
+                        ("lpTotalNumberOfClusters", &lpTotalNumberOfClusters),
                     ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::HeapAlloc(machine, hHeap, dwFlags, dwBytes);
+            let result = winapi::kernel32::GetDiskFreeSpaceA(
+                machine,
+                lpRootPathName,
+                lpSectorsPerCluster,
+                lpBytesPerSector,
+                lpNumberOfFreeClusters,
+                lpTotalNumberOfClusters,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::HeapAlloc_pos.0,
-                    winapi::kernel32::HeapAlloc_pos.1,
+                    winapi::kernel32::GetDiskFreeSpaceA_pos.0,
+                    winapi::kernel32::GetDiskFreeSpaceA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn HeapCreate(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetDiskFreeSpaceExA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let flOptions = <Result<HeapCreateFlags, u32>>::from_stack(mem, stack_args + 0u32);
-            let dwInitialSize = <u32>::from_stack(mem, stack_args + 4u32);
-            let dwMaximumSize = <u32>::from_stack(mem, stack_args + 8u32);
-            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+            let lpDirectoryName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+            let lpFreeBytesAvailableToCaller =
+                <Option<&mut u64>>::from_stack(mem, stack_args + 4u32);
+            let lpTotalNumberOfBytes = <Option<&mut u64>>::from_stack(mem, stack_args + 8u32);
+            let lpTotalNumberOfFreeBytes = <Option<&mut u64>>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("kernel32/volume") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/memory",
-                    "HeapCreate",
+                    "kernel32/volume",
+                    "GetDiskFreeSpaceExA",
                     &[
-                        ("flOptions", &flOptions),
-                        ("dwInitialSize", &dwInitialSize),
-                        ("dwMaximumSize", &dwMaximumSize),
+                        ("lpDirectoryName", &lpDirectoryName),
+                        (
+                            "lpFreeBytesAvailableToCaller",
+                            &lpFreeBytesAvailableToCaller,
+                        ),
+                        ("lpTotalNumberOfBytes", &lpTotalNumberOfBytes),
+                        ("lpTotalNumberOfFreeBytes", &lpTotalNumberOfFreeBytes),
                     ],
                 ))
             } else {
                 None
             };
-            let result =
-                winapi::kernel32::HeapCreate(machine, flOptions, dwInitialSize, dwMaximumSize);
+            let result = winapi::kernel32::GetDiskFreeSpaceExA(
+                machine,
+                lpDirectoryName,
+                lpFreeBytesAvailableToCaller,
+                lpTotalNumberOfBytes,
+                lpTotalNumberOfFreeBytes,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::HeapCreate_pos.0,
-                    winapi::kernel32::HeapCreate_pos.1,
+                    winapi::kernel32::GetDiskFreeSpaceExA_pos.0,
+                    winapi::kernel32::GetDiskFreeSpaceExA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn HeapDestroy(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetDiskFreeSpaceExW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hHeap = <u32>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+            let lpDirectoryName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+            let lpFreeBytesAvailableToCaller =
+                <Option<&mut u64>>::from_stack(mem, stack_args + 4u32);
+            let lpTotalNumberOfBytes = <Option<&mut u64>>::from_stack(mem, stack_args + 8u32);
+            let lpTotalNumberOfFreeBytes = <Option<&mut u64>>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("kernel32/volume") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/memory",
-                    "HeapDestroy",
-                    &[("hHeap", &hHeap)],
+                    "kernel32/volume",
+                    "GetDiskFreeSpaceExW",
+                    &[
+                        ("lpDirectoryName", &lpDirectoryName),
+                        (
+                            "lpFreeBytesAvailableToCaller",
+                            &lpFreeBytesAvailableToCaller,
+                        ),
+                        ("lpTotalNumberOfBytes", &lpTotalNumberOfBytes),
+                        ("lpTotalNumberOfFreeBytes", &lpTotalNumberOfFreeBytes),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::HeapDestroy(machine, hHeap);
+            let result = winapi::kernel32::GetDiskFreeSpaceExW(
+                machine,
+                lpDirectoryName,
+                lpFreeBytesAvailableToCaller,
+                lpTotalNumberOfBytes,
+                lpTotalNumberOfFreeBytes,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::HeapDestroy_pos.0,
-                    winapi::kernel32::HeapDestroy_pos.1,
+                    winapi::kernel32::GetDiskFreeSpaceExW_pos.0,
+                    winapi::kernel32::GetDiskFreeSpaceExW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn HeapFree(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetDiskFreeSpaceW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hHeap = <u32>::from_stack(mem, stack_args + 0u32);
-            let dwFlags = <u32>::from_stack(mem, stack_args + 4u32);
-            let lpMem = <u32>::from_stack(mem, stack_args + 8u32);
-            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+            let lpRootPathName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+            let lpSectorsPerCluster = <Option<&mut u32>>::from_stack(mem, stack_args + 4u32);
+            let lpBytesPerSector = <Option<&mut u32>>::from_stack(mem, stack_args + 8u32);
+            let lpNumberOfFreeClusters = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
+            let lpTotalNumberOfClusters = <Option<&mut u32>>::from_stack(mem, stack_args + 16u32);
+            let __trace_context = if crate::trace::enabled("kernel32/volume") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/memory",
-                    "HeapFree",
-                    &[("hHeap", &hHeap), ("dwFlags", &dwFlags), ("lpMem", &lpMem)],
+                    "kernel32/volume",
+                    "GetDiskFreeSpaceW",
+                    &[
+                        ("lpRootPathName", &lpRootPathName),
+                        ("lpSectorsPerCluster", &lpSectorsPerCluster),
+                        ("lpBytesPerSector", &lpBytesPerSector),
+                        ("lpNumberOfFreeClusters", &lpNumberOfFreeClusters),
+                        ("lpTotalNumberOfClusters", &lpTotalNumberOfClusters),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::HeapFree(machine, hHeap, dwFlags, lpMem);
+            let result = winapi::kernel32::GetDiskFreeSpaceW(
+                machine,
+                lpRootPathName,
+                lpSectorsPerCluster,
+                lpBytesPerSector,
+                lpNumberOfFreeClusters,
+                lpTotalNumberOfClusters,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::HeapFree_pos.0,
-                    winapi::kernel32::HeapFree_pos.1,
+                    winapi::kernel32::GetDiskFreeSpaceW_pos.0,
+                    winapi::kernel32::GetDiskFreeSpaceW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn HeapReAlloc(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetDriveTypeA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hHeap = <u32>::from_stack(mem, stack_args + 0u32);
-            let dwFlags = <u32>::from_stack(mem, stack_args + 4u32);
-            let lpMem = <u32>::from_stack(mem, stack_args + 8u32);
-            let dwBytes = <u32>::from_stack(mem, stack_args + 12u32);
-            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+            let lpRootPathName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/volume") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/memory",
-                    "HeapReAlloc",
-                    &[
-                        ("hHeap", &hHeap),
-                        ("dwFlags", &dwFlags),
-                        ("lpMem", &lpMem),
-                        ("dwBytes", &dwBytes),
-                    ],
+                    "kernel32/volume",
+                    "GetDriveTypeA",
+                    &[("lpRootPathName", &lpRootPathName)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::HeapReAlloc(machine, hHeap, dwFlags, lpMem, dwBytes);
+            let result = winapi::kernel32::GetDriveTypeA(machine, lpRootPathName);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::HeapReAlloc_pos.0,
-                    winapi::kernel32::HeapReAlloc_pos.1,
+                    winapi::kernel32::GetDriveTypeA_pos.0,
+                    winapi::kernel32::GetDriveTypeA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn HeapSetInformation(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetDriveTypeW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let HeapHandle = <u32>::from_stack(mem, stack_args + 0u32);
-            let HeapInformationClass = <u32>::from_stack(mem, stack_args + 4u32);
-            let HeapInformation = <u32>::from_stack(mem, stack_args + 8u32);
-            let HeapInformationLength = <u32>::from_stack(mem, stack_args + 12u32);
-            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+            let lpRootPathName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/volume") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/memory",
-                    "HeapSetInformation",
-                    &[
-                        ("HeapHandle", &HeapHandle),
-                        ("HeapInformationClass", &HeapInformationClass),
-                        ("HeapInformation", &HeapInformation),
-                        ("HeapInformationLength", &HeapInformationLength),
-                    ],
+                    "kernel32/volume",
+                    "GetDriveTypeW",
+                    &[("lpRootPathName", &lpRootPathName)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::HeapSetInformation(
-                machine,
-                HeapHandle,
-                HeapInformationClass,
-                HeapInformation,
-                HeapInformationLength,
-            );
+            let result = winapi::kernel32::GetDriveTypeW(machine, lpRootPathName);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::HeapSetInformation_pos.0,
-                    winapi::kernel32::HeapSetInformation_pos.1,
+                    winapi::kernel32::GetDriveTypeW_pos.0,
+                    winapi::kernel32::GetDriveTypeW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn HeapSize(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetEnvironmentStrings(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hHeap = <u32>::from_stack(mem, stack_args + 0u32);
-            let dwFlags = <u32>::from_stack(mem, stack_args + 4u32);
-            let lpMem = <u32>::from_stack(mem, stack_args + 8u32);
-            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+            let __trace_context = if crate::trace::enabled("kernel32/env") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/memory",
-                    "HeapSize",
-                    &[("hHeap", &hHeap), ("dwFlags", &dwFlags), ("lpMem", &lpMem)],
+                    "kernel32/env",
+                    "GetEnvironmentStrings",
+                    &[],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::HeapSize(machine, hHeap, dwFlags, lpMem);
+            let result = winapi::kernel32::GetEnvironmentStrings(machine);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::HeapSize_pos.0,
-                    winapi::kernel32::HeapSize_pos.1,
+                    winapi::kernel32::GetEnvironmentStrings_pos.0,
+                    winapi::kernel32::GetEnvironmentStrings_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn HeapValidate(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetEnvironmentStringsW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hHeap = <u32>::from_stack(mem, stack_args + 0u32);
-            let dwFlags = <u32>::from_stack(mem, stack_args + 4u32);
-            let lpMem = <u32>::from_stack(mem, stack_args + 8u32);
-            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+            let __trace_context = if crate::trace::enabled("kernel32/env") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/memory",
-                    "HeapValidate",
-                    &[("hHeap", &hHeap), ("dwFlags", &dwFlags), ("lpMem", &lpMem)],
+                    "kernel32/env",
+                    "GetEnvironmentStringsW",
+                    &[],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::HeapValidate(machine, hHeap, dwFlags, lpMem);
+            let result = winapi::kernel32::GetEnvironmentStringsW(machine);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::HeapValidate_pos.0,
-                    winapi::kernel32::HeapValidate_pos.1,
+                    winapi::kernel32::GetEnvironmentStringsW_pos.0,
+                    winapi::kernel32::GetEnvironmentStringsW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn InitOnceBeginInitialize(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetEnvironmentVariableA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpInitOnce = <Option<&mut INIT_ONCE>>::from_stack(mem, stack_args + 0u32);
-            let dwFlags = <u32>::from_stack(mem, stack_args + 4u32);
-            let fPending = <Option<&mut u32>>::from_stack(mem, stack_args + 8u32);
-            let lpContext = <u32>::from_stack(mem, stack_args + 12u32);
-            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+            let name = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+            let buf = <ArrayWithSizeMut<u8>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/env") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/thread",
-                    "InitOnceBeginInitialize",
-                    &[
-                        ("lpInitOnce", &lpInitOnce),
-                        ("dwFlags", &dwFlags),
-                        ("fPending", &fPending),
-                        ("lpContext", &lpContext),
-                    ],
+                    "kernel32/env",
+                    "GetEnvironmentVariableA",
+                    &[("name", &name), ("buf", &buf)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::InitOnceBeginInitialize(
-                machine, lpInitOnce, dwFlags, fPending, lpContext,
-            );
+            let result = winapi::kernel32::GetEnvironmentVariableA(machine, name, buf);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::InitOnceBeginInitialize_pos.0,
-                    winapi::kernel32::InitOnceBeginInitialize_pos.1,
+                    winapi::kernel32::GetEnvironmentVariableA_pos.0,
+                    winapi::kernel32::GetEnvironmentVariableA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn InitOnceComplete(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetEnvironmentVariableW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpInitOnce = <Option<&mut INIT_ONCE>>::from_stack(mem, stack_args + 0u32);
-            let dwFlags = <u32>::from_stack(mem, stack_args + 4u32);
-            let lpContext = <u32>::from_stack(mem, stack_args + 8u32);
-            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+            let name = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+            let buf = <ArrayWithSizeMut<u16>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/env") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/thread",
-                    "InitOnceComplete",
-                    &[
-                        ("lpInitOnce", &lpInitOnce),
-                        ("dwFlags", &dwFlags),
-                        ("lpContext", &lpContext),
-                    ],
+                    "kernel32/env",
+                    "GetEnvironmentVariableW",
+                    &[("name", &name), ("buf", &buf)],
                 ))
             } else {
                 None
             };
-            let result =
-                winapi::kernel32::InitOnceComplete(machine, lpInitOnce, dwFlags, lpContext);
+            let result = winapi::kernel32::GetEnvironmentVariableW(machine, name, buf);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::InitOnceComplete_pos.0,
-                    winapi::kernel32::InitOnceComplete_pos.1,
+                    winapi::kernel32::GetEnvironmentVariableW_pos.0,
+                    winapi::kernel32::GetEnvironmentVariableW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn InitializeCriticalSection(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetExitCodeProcess(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpCriticalSection = <u32>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+            let hProcess = <HPROCESS>::from_stack(mem, stack_args + 0u32);
+            let lpExitCode = <Option<&mut u32>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/process") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/thread",
-                    "InitializeCriticalSection",
-                    &[("lpCriticalSection", &lpCriticalSection)],
+                    "kernel32/process",
+                    "GetExitCodeProcess",
+                    &[("hProcess", &hProcess), ("lpExitCode", &lpExitCode)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::InitializeCriticalSection(machine, lpCriticalSection);
+            let result = winapi::kernel32::GetExitCodeProcess(machine, hProcess, lpExitCode);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::InitializeCriticalSection_pos.0,
-                    winapi::kernel32::InitializeCriticalSection_pos.1,
+                    winapi::kernel32::GetExitCodeProcess_pos.0,
+                    winapi::kernel32::GetExitCodeProcess_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn InitializeCriticalSectionAndSpinCount(
-            machine: &mut Machine,
-            stack_args: u32,
-        ) -> u32 {
+        pub unsafe fn GetExitCodeThread(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpCriticalSection = <u32>::from_stack(mem, stack_args + 0u32);
-            let dwSpinCount = <u32>::from_stack(mem, stack_args + 4u32);
+            let hThread = <HTHREAD>::from_stack(mem, stack_args + 0u32);
+            let lpExitCode = <Option<&mut u32>>::from_stack(mem, stack_args + 4u32);
             let __trace_context = if crate::trace::enabled("kernel32/thread") {
                 Some(crate::trace::trace_begin(
                     "kernel32/thread",
-                    "InitializeCriticalSectionAndSpinCount",
-                    &[
-                        ("lpCriticalSection", &lpCriticalSection),
-                        ("dwSpinCount", &dwSpinCount),
-                    ],
+                    "GetExitCodeThread",
+                    &[("hThread", &hThread), ("lpExitCode", &lpExitCode)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::InitializeCriticalSectionAndSpinCount(
-                machine,
-                lpCriticalSection,
-                dwSpinCount,
-            );
+            let result = winapi::kernel32::GetExitCodeThread(machine, hThread, lpExitCode);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::InitializeCriticalSectionAndSpinCount_pos.0,
-                    winapi::kernel32::InitializeCriticalSectionAndSpinCount_pos.1,
+                    winapi::kernel32::GetExitCodeThread_pos.0,
+                    winapi::kernel32::GetExitCodeThread_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn InitializeCriticalSectionEx(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetFileAttributesA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpCriticalSection = <u32>::from_stack(mem, stack_args + 0u32);
-            let dwSpinCount = <u32>::from_stack(mem, stack_args + 4u32);
-            let flags = <u32>::from_stack(mem, stack_args + 8u32);
-            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+            let lpFileName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/thread",
-                    "InitializeCriticalSectionEx",
-                    &[
-                        ("lpCriticalSection", &lpCriticalSection),
-                        ("dwSpinCount", &dwSpinCount),
-                        ("flags", &flags),
-                    ],
+                    "kernel32/file",
+                    "GetFileAttributesA",
+                    &[("lpFileName", &lpFileName)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::InitializeCriticalSectionEx(
-                machine,
-                lpCriticalSection,
-                dwSpinCount,
-                flags,
-            );
+            let result = winapi::kernel32::GetFileAttributesA(machine, lpFileName);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::InitializeCriticalSectionEx_pos.0,
-                    winapi::kernel32::InitializeCriticalSectionEx_pos.1,
+                    winapi::kernel32::GetFileAttributesA_pos.0,
+                    winapi::kernel32::GetFileAttributesA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn InitializeSListHead(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetFileAttributesExA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let ListHead = <Option<&mut SLIST_HEADER>>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/misc") {
+            let lpFileName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+            let fInfoLevelId =
+                <Result<GET_FILEEX_INFO_LEVELS, u32>>::from_stack(mem, stack_args + 4u32);
+            let lpFileInformation =
+                <Option<&mut WIN32_FILE_ATTRIBUTE_DATA>>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/misc",
-                    "InitializeSListHead",
-                    &[("ListHead", &ListHead)],
+                    "kernel32/file",
+                    "GetFileAttributesExA",
+                    &[
+                        ("lpFileName", &lpFileName),
+                        ("fInfoLevelId", &fInfoLevelId),
+                        ("lpFileInformation", &lpFileInformation),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::InitializeSListHead(machine, ListHead);
+            let result = winapi::kernel32::GetFileAttributesExA(
+                machine,
+                lpFileName,
+                fInfoLevelId,
+                lpFileInformation,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::InitializeSListHead_pos.0,
-                    winapi::kernel32::InitializeSListHead_pos.1,
+                    winapi::kernel32::GetFileAttributesExA_pos.0,
+                    winapi::kernel32::GetFileAttributesExA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn InterlockedDecrement(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetFileAttributesExW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let addend = <Option<&mut u32>>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+            let lpFileName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+            let fInfoLevelId =
+                <Result<GET_FILEEX_INFO_LEVELS, u32>>::from_stack(mem, stack_args + 4u32);
+            let lpFileInformation =
+                <Option<&mut WIN32_FILE_ATTRIBUTE_DATA>>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/thread",
-                    "InterlockedDecrement",
-                    &[("addend", &addend)],
+                    "kernel32/file",
+                    "GetFileAttributesExW",
+                    &[
+                        ("lpFileName", &lpFileName),
+                        ("fInfoLevelId", &fInfoLevelId),
+                        ("lpFileInformation", &lpFileInformation),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::InterlockedDecrement(machine, addend);
+            let result = winapi::kernel32::GetFileAttributesExW(
+                machine,
+                lpFileName,
+                fInfoLevelId,
+                lpFileInformation,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::InterlockedDecrement_pos.0,
-                    winapi::kernel32::InterlockedDecrement_pos.1,
+                    winapi::kernel32::GetFileAttributesExW_pos.0,
+                    winapi::kernel32::GetFileAttributesExW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn InterlockedIncrement(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetFileAttributesW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let addend = <Option<&mut u32>>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+            let lpFileName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/thread",
-                    "InterlockedIncrement",
-                    &[("addend", &addend)],
+                    "kernel32/file",
+                    "GetFileAttributesW",
+                    &[("lpFileName", &lpFileName)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::InterlockedIncrement(machine, addend);
+            let result = winapi::kernel32::GetFileAttributesW(machine, lpFileName);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::InterlockedIncrement_pos.0,
-                    winapi::kernel32::InterlockedIncrement_pos.1,
+                    winapi::kernel32::GetFileAttributesW_pos.0,
+                    winapi::kernel32::GetFileAttributesW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn IsBadCodePtr(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetFileInformationByHandle(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpfn = <u32>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+            let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
+            let lpFileInformation =
+                <Option<&mut BY_HANDLE_FILE_INFORMATION>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/memory",
-                    "IsBadCodePtr",
-                    &[("lpfn", &lpfn)],
+                    "kernel32/file",
+                    "GetFileInformationByHandle",
+                    &[("hFile", &hFile), ("lpFileInformation", &lpFileInformation)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::IsBadCodePtr(machine, lpfn);
+            let result =
+                winapi::kernel32::GetFileInformationByHandle(machine, hFile, lpFileInformation);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::IsBadCodePtr_pos.0,
-                    winapi::kernel32::IsBadCodePtr_pos.1,
+                    winapi::kernel32::GetFileInformationByHandle_pos.0,
+                    winapi::kernel32::GetFileInformationByHandle_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn IsBadReadPtr(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetFileSize(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lp = <u32>::from_stack(mem, stack_args + 0u32);
-            let ucb = <u32>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+            let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
+            let lpFileSizeHigh = <Option<&mut u32>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/memory",
-                    "IsBadReadPtr",
-                    &[("lp", &lp), ("ucb", &ucb)],
+                    "kernel32/file",
+                    "GetFileSize",
+                    &[("hFile", &hFile), ("lpFileSizeHigh", &lpFileSizeHigh)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::IsBadReadPtr(machine, lp, ucb);
+            let result = winapi::kernel32::GetFileSize(machine, hFile, lpFileSizeHigh);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::IsBadReadPtr_pos.0,
-                    winapi::kernel32::IsBadReadPtr_pos.1,
+                    winapi::kernel32::GetFileSize_pos.0,
+                    winapi::kernel32::GetFileSize_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn IsBadWritePtr(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetFileSizeEx(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lp = <u32>::from_stack(mem, stack_args + 0u32);
-            let ucb = <u32>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+            let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
+            let lpFileSize = <Option<&mut u64>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/memory",
-                    "IsBadWritePtr",
-                    &[("lp", &lp), ("ucb", &ucb)],
+                    "kernel32/file",
+                    "GetFileSizeEx",
+                    &[("hFile", &hFile), ("lpFileSize", &lpFileSize)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::IsBadWritePtr(machine, lp, ucb);
+            let result = winapi::kernel32::GetFileSizeEx(machine, hFile, lpFileSize);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::IsBadWritePtr_pos.0,
-                    winapi::kernel32::IsBadWritePtr_pos.1,
+                    winapi::kernel32::GetFileSizeEx_pos.0,
+                    winapi::kernel32::GetFileSizeEx_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn IsDBCSLeadByte(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetFileTime(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let _TestChar = <u8>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/nls") {
+            let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
+            let lpCreationTime = <Option<&mut FILETIME>>::from_stack(mem, stack_args + 4u32);
+            let lpLastAccessTime = <Option<&mut FILETIME>>::from_stack(mem, stack_args + 8u32);
+            let lpLastWriteTime = <Option<&mut FILETIME>>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/nls",
-                    "IsDBCSLeadByte",
-                    &[("TestChar", &_TestChar)],
+                    "kernel32/file",
+                    "GetFileTime",
+                    &[
+                        ("hFile", &hFile),
+                        ("lpCreationTime", &lpCreationTime),
+                        ("lpLastAccessTime", &lpLastAccessTime),
+                        ("lpLastWriteTime", &lpLastWriteTime),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::IsDBCSLeadByte(machine, _TestChar);
+            let result = winapi::kernel32::GetFileTime(
+                machine,
+                hFile,
+                lpCreationTime,
+                lpLastAccessTime,
+                lpLastWriteTime,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::IsDBCSLeadByte_pos.0,
-                    winapi::kernel32::IsDBCSLeadByte_pos.1,
+                    winapi::kernel32::GetFileTime_pos.0,
+                    winapi::kernel32::GetFileTime_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn IsDBCSLeadByteEx(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetFileType(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let _TestChar = <u8>::from_stack(mem, stack_args + 0u32);
-            let _CodePage = <u32>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/nls") {
+            let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/nls",
-                    "IsDBCSLeadByteEx",
-                    &[("TestChar", &_TestChar), ("CodePage", &_CodePage)],
+                    "kernel32/file",
+                    "GetFileType",
+                    &[("hFile", &hFile)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::IsDBCSLeadByteEx(machine, _TestChar, _CodePage);
+            let result = winapi::kernel32::GetFileType(machine, hFile);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::IsDBCSLeadByteEx_pos.0,
-                    winapi::kernel32::IsDBCSLeadByteEx_pos.1,
+                    winapi::kernel32::GetFileType_pos.0,
+                    winapi::kernel32::GetFileType_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn IsDebuggerPresent(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetFullPathNameA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let __trace_context = if crate::trace::enabled("kernel32/misc") {
+            let lpFileName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+            let nBufferLength = <u32>::from_stack(mem, stack_args + 4u32);
+            let lpBuffer = <u32>::from_stack(mem, stack_args + 8u32);
+            let lpFilePart = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/misc",
-                    "IsDebuggerPresent",
-                    &[],
+                    "kernel32/file",
+                    "GetFullPathNameA",
+                    &[
+                        ("lpFileName", &lpFileName),
+                        ("nBufferLength", &nBufferLength),
+                        ("lpBuffer", &lpBuffer),
+                        ("lpFilePart", &lpFilePart),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::IsDebuggerPresent(machine);
+            let result = winapi::kernel32::GetFullPathNameA(
+                machine,
+                lpFileName,
+                nBufferLength,
+                lpBuffer,
+                lpFilePart,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::IsDebuggerPresent_pos.0,
-                    winapi::kernel32::IsDebuggerPresent_pos.1,
+                    winapi::kernel32::GetFullPathNameA_pos.0,
+                    winapi::kernel32::GetFullPathNameA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn IsProcessorFeaturePresent(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetFullPathNameW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let feature = <Result<ProcessorFeature, u32>>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/misc") {
+            let lpFileName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+            let nBufferLength = <u32>::from_stack(mem, stack_args + 4u32);
+            let lpBuffer = <u32>::from_stack(mem, stack_args + 8u32);
+            let lpFilePart = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/misc",
-                    "IsProcessorFeaturePresent",
-                    &[("feature", &feature)],
+                    "kernel32/file",
+                    "GetFullPathNameW",
+                    &[
+                        ("lpFileName", &lpFileName),
+                        ("nBufferLength", &nBufferLength),
+                        ("lpBuffer", &lpBuffer),
+                        ("lpFilePart", &lpFilePart),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::IsProcessorFeaturePresent(machine, feature);
+            let result = winapi::kernel32::GetFullPathNameW(
+                machine,
+                lpFileName,
+                nBufferLength,
+                lpBuffer,
+                lpFilePart,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::IsProcessorFeaturePresent_pos.0,
-                    winapi::kernel32::IsProcessorFeaturePresent_pos.1,
+                    winapi::kernel32::GetFullPathNameW_pos.0,
+                    winapi::kernel32::GetFullPathNameW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn IsValidCodePage(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetLastError(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let CodePage = <u32>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/nls") {
+            let __trace_context = if crate::trace::enabled("kernel32/misc") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/nls",
-                    "IsValidCodePage",
-                    &[("CodePage", &CodePage)],
+                    "kernel32/misc",
+                    "GetLastError",
+                    &[],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::IsValidCodePage(machine, CodePage);
+            let result = winapi::kernel32::GetLastError(machine);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::IsValidCodePage_pos.0,
-                    winapi::kernel32::IsValidCodePage_pos.1,
+                    winapi::kernel32::GetLastError_pos.0,
+                    winapi::kernel32::GetLastError_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn LCMapStringA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetLocalTime(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let locale = <LCID>::from_stack(mem, stack_args + 0u32);
-            let dwMapFlags = <u32>::from_stack(mem, stack_args + 4u32);
-            let lpSrcStr = <u32>::from_stack(mem, stack_args + 8u32);
-            let cchSrc = <i32>::from_stack(mem, stack_args + 12u32);
-            let lpDestStr = <ArrayWithSizeMut<u8>>::from_stack(mem, stack_args + 16u32);
-            let __trace_context = if crate::trace::enabled("kernel32/nls") {
+            let lpSystemTime = <Option<&mut SYSTEMTIME>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/time") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/nls",
-                    "LCMapStringA",
-                    &[
-                        ("locale", &locale),
-                        ("dwMapFlags", &dwMapFlags),
-                        ("lpSrcStr", &lpSrcStr),
-                        ("cchSrc", &cchSrc),
-                        ("lpDestStr", &lpDestStr),
-                    ],
+                    "kernel32/time",
+                    "GetLocalTime",
+                    &[("lpSystemTime", &lpSystemTime)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::LCMapStringA(
-                machine, locale, dwMapFlags, lpSrcStr, cchSrc, lpDestStr,
-            );
+            let result = winapi::kernel32::GetLocalTime(machine, lpSystemTime);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::LCMapStringA_pos.0,
-                    winapi::kernel32::LCMapStringA_pos.1,
+                    winapi::kernel32::GetLocalTime_pos.0,
+                    winapi::kernel32::GetLocalTime_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn LCMapStringW(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetLogicalDrives(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let locale = <LCID>::from_stack(mem, stack_args + 0u32);
-            let dwMapFlags = <u32>::from_stack(mem, stack_args + 4u32);
-            let lpSrcStr = <u32>::from_stack(mem, stack_args + 8u32);
-            let cchSrc = <i32>::from_stack(mem, stack_args + 12u32);
-            let lpDestStr = <ArrayWithSizeMut<u16>>::from_stack(mem, stack_args + 16u32);
-            let __trace_context = if crate::trace::enabled("kernel32/nls") {
+            let __trace_context = if crate::trace::enabled("kernel32/volume") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/nls",
-                    "LCMapStringW",
-                    &[
-                        ("locale", &locale),
-                        ("dwMapFlags", &dwMapFlags),
-                        ("lpSrcStr", &lpSrcStr),
-                        ("cchSrc", &cchSrc),
-                        ("lpDestStr", &lpDestStr),
-                    ],
+                    "kernel32/volume",
+                    "GetLogicalDrives",
+                    &[],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::LCMapStringW(
-                machine, locale, dwMapFlags, lpSrcStr, cchSrc, lpDestStr,
-            );
+            let result = winapi::kernel32::GetLogicalDrives(machine);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::LCMapStringW_pos.0,
-                    winapi::kernel32::LCMapStringW_pos.1,
+                    winapi::kernel32::GetLogicalDrives_pos.0,
+                    winapi::kernel32::GetLogicalDrives_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn LeaveCriticalSection(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetModuleFileNameA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpCriticalSection = <u32>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+            let hModule = <HMODULE>::from_stack(mem, stack_args + 0u32);
+            let filename = <ArrayWithSizeMut<u8>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/dll") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/thread",
-                    "LeaveCriticalSection",
-                    &[("lpCriticalSection", &lpCriticalSection)],
+                    "kernel32/dll",
+                    "GetModuleFileNameA",
+                    &[("hModule", &hModule), ("filename", &filename)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::LeaveCriticalSection(machine, lpCriticalSection);
+            let result = winapi::kernel32::GetModuleFileNameA(machine, hModule, filename);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::LeaveCriticalSection_pos.0,
-                    winapi::kernel32::LeaveCriticalSection_pos.1,
+                    winapi::kernel32::GetModuleFileNameA_pos.0,
+                    winapi::kernel32::GetModuleFileNameA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn LoadLibraryA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetModuleFileNameW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let filename = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+            let hModule = <HMODULE>::from_stack(mem, stack_args + 0u32);
+            let lpFilename = <ArrayWithSizeMut<u16>>::from_stack(mem, stack_args + 4u32);
             let __trace_context = if crate::trace::enabled("kernel32/dll") {
                 Some(crate::trace::trace_begin(
                     "kernel32/dll",
-                    "LoadLibraryA",
-                    &[("filename", &filename)],
+                    "GetModuleFileNameW",
+                    &[("hModule", &hModule), ("lpFilename", &lpFilename)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::LoadLibraryA(machine, filename);
+            let result = winapi::kernel32::GetModuleFileNameW(machine, hModule, lpFilename);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::LoadLibraryA_pos.0,
-                    winapi::kernel32::LoadLibraryA_pos.1,
+                    winapi::kernel32::GetModuleFileNameW_pos.0,
+                    winapi::kernel32::GetModuleFileNameW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn LoadLibraryExW(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetModuleHandleA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpLibFileName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
-            let hFile = <HFILE>::from_stack(mem, stack_args + 4u32);
-            let dwFlags = <u32>::from_stack(mem, stack_args + 8u32);
+            let lpModuleName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
             let __trace_context = if crate::trace::enabled("kernel32/dll") {
                 Some(crate::trace::trace_begin(
                     "kernel32/dll",
-                    "LoadLibraryExW",
-                    &[
-                        ("lpLibFileName", &lpLibFileName),
-                        ("hFile", &hFile),
-                        ("dwFlags", &dwFlags),
-                    ],
+                    "GetModuleHandleA",
+                    &[("lpModuleName", &lpModuleName)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::LoadLibraryExW(machine, lpLibFileName, hFile, dwFlags);
+            let result = winapi::kernel32::GetModuleHandleA(machine, lpModuleName);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::LoadLibraryExW_pos.0,
-                    winapi::kernel32::LoadLibraryExW_pos.1,
+                    winapi::kernel32::GetModuleHandleA_pos.0,
+                    winapi::kernel32::GetModuleHandleA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn LoadResource(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetModuleHandleExA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hModule = <HMODULE>::from_stack(mem, stack_args + 0u32);
-            let hResInfo = <HRSRC>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/resource") {
+            let dwFlags = <u32>::from_stack(mem, stack_args + 0u32);
+            let lpModuleName = <u32>::from_stack(mem, stack_args + 4u32);
+            let phModule = <Option<&mut HMODULE>>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/dll") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/resource",
-                    "LoadResource",
-                    &[("hModule", &hModule), ("hResInfo", &hResInfo)],
+                    "kernel32/dll",
+                    "GetModuleHandleExA",
+                    &[
+                        ("dwFlags", &dwFlags),
+                        ("lpModuleName", &lpModuleName),
+                        ("phModule", &phModule),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::LoadResource(machine, hModule, hResInfo);
+            let result =
+                winapi::kernel32::GetModuleHandleExA(machine, dwFlags, lpModuleName, phModule);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::LoadResource_pos.0,
-                    winapi::kernel32::LoadResource_pos.1,
+                    winapi::kernel32::GetModuleHandleExA_pos.0,
+                    winapi::kernel32::GetModuleHandleExA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn LocalAlloc(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetModuleHandleExW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let uFlags = <GMEM>::from_stack(mem, stack_args + 0u32);
-            let dwBytes = <u32>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+            let dwFlags = <u32>::from_stack(mem, stack_args + 0u32);
+            let lpModuleName = <u32>::from_stack(mem, stack_args + 4u32);
+            let phModule = <Option<&mut HMODULE>>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/dll") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/memory",
-                    "LocalAlloc",
-                    &[("uFlags", &uFlags), ("dwBytes", &dwBytes)],
+                    "kernel32/dll",
+                    "GetModuleHandleExW",
+                    &[
+                        ("dwFlags", &dwFlags),
+                        ("lpModuleName", &lpModuleName),
+                        ("phModule", &phModule),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::LocalAlloc(machine, uFlags, dwBytes);
+            let result =
+                winapi::kernel32::GetModuleHandleExW(machine, dwFlags, lpModuleName, phModule);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::LocalAlloc_pos.0,
-                    winapi::kernel32::LocalAlloc_pos.1,
+                    winapi::kernel32::GetModuleHandleExW_pos.0,
+                    winapi::kernel32::GetModuleHandleExW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn LocalFree(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetModuleHandleW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hMem = <u32>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+            let lpModuleName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/dll") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/memory",
-                    "LocalFree",
-                    &[("hMem", &hMem)],
+                    "kernel32/dll",
+                    "GetModuleHandleW",
+                    &[("lpModuleName", &lpModuleName)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::LocalFree(machine, hMem);
+            let result = winapi::kernel32::GetModuleHandleW(machine, lpModuleName);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::LocalFree_pos.0,
-                    winapi::kernel32::LocalFree_pos.1,
+                    winapi::kernel32::GetModuleHandleW_pos.0,
+                    winapi::kernel32::GetModuleHandleW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn LockResource(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetNativeSystemInfo(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hResData = <HRSRC>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/resource") {
+            let lpSystemInfo = <Option<&mut SYSTEM_INFO>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/sysinfo") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/resource",
-                    "LockResource",
-                    &[("hResData", &hResData)],
+                    "kernel32/sysinfo",
+                    "GetNativeSystemInfo",
+                    &[("lpSystemInfo", &lpSystemInfo)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::LockResource(machine, hResData);
+            let result = winapi::kernel32::GetNativeSystemInfo(machine, lpSystemInfo);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::LockResource_pos.0,
-                    winapi::kernel32::LockResource_pos.1,
+                    winapi::kernel32::GetNativeSystemInfo_pos.0,
+                    winapi::kernel32::GetNativeSystemInfo_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn MulDiv(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetNumberOfConsoleInputEvents(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let nNumber = <i32>::from_stack(mem, stack_args + 0u32);
-            let nNumerator = <i32>::from_stack(mem, stack_args + 4u32);
-            let nDenominator = <i32>::from_stack(mem, stack_args + 8u32);
-            let __trace_context = if crate::trace::enabled("kernel32/misc") {
+            let hConsoleInput = <HFILE>::from_stack(mem, stack_args + 0u32);
+            let lpNumberOfEvents = <Option<&mut u32>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/console") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/misc",
-                    "MulDiv",
+                    "kernel32/console",
+                    "GetNumberOfConsoleInputEvents",
                     &[
-                        ("nNumber", &nNumber),
-                        ("nNumerator", &nNumerator),
-                        ("nDenominator", &nDenominator),
+                        ("hConsoleInput", &hConsoleInput),
+                        ("lpNumberOfEvents", &lpNumberOfEvents),
                     ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::MulDiv(machine, nNumber, nNumerator, nDenominator);
+            let result = winapi::kernel32::GetNumberOfConsoleInputEvents(
+                machine,
+                hConsoleInput,
+                lpNumberOfEvents,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::MulDiv_pos.0,
-                    winapi::kernel32::MulDiv_pos.1,
+                    winapi::kernel32::GetNumberOfConsoleInputEvents_pos.0,
+                    winapi::kernel32::GetNumberOfConsoleInputEvents_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn MultiByteToWideChar(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetOEMCP(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let CodePage = <Result<CP, u32>>::from_stack(mem, stack_args + 0u32);
-            let dwFlags = <u32>::from_stack(mem, stack_args + 4u32);
-            let lpMultiByteStr = <u32>::from_stack(mem, stack_args + 8u32);
-            let cbMultiByte = <i32>::from_stack(mem, stack_args + 12u32);
-            let lpWideCharStr = <ArrayWithSizeMut<u16>>::from_stack(mem, stack_args + 16u32);
             let __trace_context = if crate::trace::enabled("kernel32/nls") {
-                Some(crate::trace::trace_begin(
-                    "kernel32/nls",
-                    "MultiByteToWideChar",
-                    &[
-                        ("CodePage", &CodePage),
-                        ("dwFlags", &dwFlags),
-                        ("lpMultiByteStr", &lpMultiByteStr),
-                        ("cbMultiByte", &cbMultiByte),
-                        ("lpWideCharStr", &lpWideCharStr),
-                    ],
-                ))
+                Some(crate::trace::trace_begin("kernel32/nls", "GetOEMCP", &[]))
             } else {
                 None
             };
-            let result = winapi::kernel32::MultiByteToWideChar(
-                machine,
-                CodePage,
-                dwFlags,
-                lpMultiByteStr,
-                cbMultiByte,
-                lpWideCharStr,
-            );
+            let result = winapi::kernel32::GetOEMCP(machine);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::MultiByteToWideChar_pos.0,
-                    winapi::kernel32::MultiByteToWideChar_pos.1,
+                    winapi::kernel32::GetOEMCP_pos.0,
+                    winapi::kernel32::GetOEMCP_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn NtCurrentTeb(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetOverlappedResult(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
             let mem = machine.mem().detach();
-            let __trace_context = if crate::trace::enabled("kernel32/misc") {
+            let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
+            let lpOverlapped = <u32>::from_stack(mem, stack_args + 4u32);
+            let lpNumberOfBytesTransferred = <Option<&mut u32>>::from_stack(mem, stack_args + 8u32);
+            let bWait = <bool>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/misc",
-                    "NtCurrentTeb",
-                    &[],
+                    "kernel32/file",
+                    "GetOverlappedResult",
+                    &[
+                        ("hFile", &hFile),
+                        ("lpOverlapped", &lpOverlapped),
+                        ("lpNumberOfBytesTransferred", &lpNumberOfBytesTransferred),
+                        ("bWait", &bWait),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::NtCurrentTeb(machine);
-            if let Some(__trace_context) = __trace_context {
-                crate::trace::trace_return(
-                    &__trace_context,
-                    winapi::kernel32::NtCurrentTeb_pos.0,
-                    winapi::kernel32::NtCurrentTeb_pos.1,
-                    &result,
-                );
-            }
-            result.to_raw()
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::kernel32::GetOverlappedResult(
+                    machine,
+                    hFile,
+                    lpOverlapped,
+                    lpNumberOfBytesTransferred,
+                    bWait,
+                )
+                .await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::kernel32::GetOverlappedResult_pos.0,
+                        winapi::kernel32::GetOverlappedResult_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
         }
-        pub unsafe fn OutputDebugStringA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetPrivateProfileIntW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let msg = <Option<&str>>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/misc") {
+            let lpAppName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+            let lpKeyName = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
+            let nDefault = <u32>::from_stack(mem, stack_args + 8u32);
+            let lpFileName = <Option<&Str16>>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("kernel32/ini") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/misc",
-                    "OutputDebugStringA",
-                    &[("msg", &msg)],
+                    "kernel32/ini",
+                    "GetPrivateProfileIntW",
+                    &[
+                        ("lpAppName", &lpAppName),
+                        ("lpKeyName", &lpKeyName),
+                        ("nDefault", &nDefault),
+                        ("lpFileName", &lpFileName),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::OutputDebugStringA(machine, msg);
+            let result = winapi::kernel32::GetPrivateProfileIntW(
+                machine, lpAppName, lpKeyName, nDefault, lpFileName,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::OutputDebugStringA_pos.0,
-                    winapi::kernel32::OutputDebugStringA_pos.1,
+                    winapi::kernel32::GetPrivateProfileIntW_pos.0,
+                    winapi::kernel32::GetPrivateProfileIntW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn QueryPerformanceCounter(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetPrivateProfileStringW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpPerformanceCount =
-                <Option<&mut LARGE_INTEGER>>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/time") {
+            let lpAppName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+            let lpKeyName = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
+            let lpDefault = <Option<&Str16>>::from_stack(mem, stack_args + 8u32);
+            let lpReturnedString = <ArrayWithSizeMut<u16>>::from_stack(mem, stack_args + 12u32);
+            let lpFileName = <Option<&Str16>>::from_stack(mem, stack_args + 20u32);
+            let __trace_context = if crate::trace::enabled("kernel32/ini") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/time",
-                    "QueryPerformanceCounter",
-                    &[("lpPerformanceCount", &lpPerformanceCount)],
+                    "kernel32/ini",
+                    "GetPrivateProfileStringW",
+                    &[
+                        ("lpAppName", &lpAppName),
+                        ("lpKeyName", &lpKeyName),
+                        ("lpDefault", &lpDefault),
+                        ("lpReturnedString", &lpReturnedString),
+                        ("lpFileName", &lpFileName),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::QueryPerformanceCounter(machine, lpPerformanceCount);
+            let result = winapi::kernel32::GetPrivateProfileStringW(
+                machine,
+                lpAppName,
+                lpKeyName,
+                lpDefault,
+                lpReturnedString,
+                lpFileName,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::QueryPerformanceCounter_pos.0,
-                    winapi::kernel32::QueryPerformanceCounter_pos.1,
+                    winapi::kernel32::GetPrivateProfileStringW_pos.0,
+                    winapi::kernel32::GetPrivateProfileStringW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn QueryPerformanceFrequency(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetProcAddress(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpFrequency = <u32>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/time") {
+            let hModule = <HMODULE>::from_stack(mem, stack_args + 0u32);
+            let lpProcName = <GetProcAddressArg>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/dll") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/time",
-                    "QueryPerformanceFrequency",
-                    &[("lpFrequency", &lpFrequency)],
+                    "kernel32/dll",
+                    "GetProcAddress",
+                    &[("hModule", &hModule), ("lpProcName", &lpProcName)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::QueryPerformanceFrequency(machine, lpFrequency);
+            let result = winapi::kernel32::GetProcAddress(machine, hModule, lpProcName);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::QueryPerformanceFrequency_pos.0,
-                    winapi::kernel32::QueryPerformanceFrequency_pos.1,
+                    winapi::kernel32::GetProcAddress_pos.0,
+                    winapi::kernel32::GetProcAddress_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn RaiseException(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetProcessHeap(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let dwExceptionCode = <u32>::from_stack(mem, stack_args + 0u32);
-            let dwExceptionFlags = <u32>::from_stack(mem, stack_args + 4u32);
-            let nNumberOfArguments = <u32>::from_stack(mem, stack_args + 8u32);
-            let lpArguments = <u32>::from_stack(mem, stack_args + 12u32);
-            let __trace_context = if crate::trace::enabled("kernel32/misc") {
+            let __trace_context = if crate::trace::enabled("kernel32/memory") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/misc",
-                    "RaiseException",
-                    &[
-                        ("dwExceptionCode", &dwExceptionCode),
-                        ("dwExceptionFlags", &dwExceptionFlags),
-                        ("nNumberOfArguments", &nNumberOfArguments),
-                        ("lpArguments", &lpArguments),
-                    ],
+                    "kernel32/memory",
+                    "GetProcessHeap",
+                    &[],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::RaiseException(
-                machine,
-                dwExceptionCode,
-                dwExceptionFlags,
-                nNumberOfArguments,
-                lpArguments,
-            );
+            let result = winapi::kernel32::GetProcessHeap(machine);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::RaiseException_pos.0,
-                    winapi::kernel32::RaiseException_pos.1,
+                    winapi::kernel32::GetProcessHeap_pos.0,
+                    winapi::kernel32::GetProcessHeap_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn ReadFile(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetProcessHeaps(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
-            let lpBuffer = <ArrayWithSizeMut<u8>>::from_stack(mem, stack_args + 4u32);
-            let lpNumberOfBytesRead = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
-            let lpOverlapped = <u32>::from_stack(mem, stack_args + 16u32);
-            let __trace_context = if crate::trace::enabled("kernel32/file") {
+            let NumberOfHeaps = <u32>::from_stack(mem, stack_args + 0u32);
+            let ProcessHeaps = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/memory") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/file",
-                    "ReadFile",
+                    "kernel32/memory",
+                    "GetProcessHeaps",
                     &[
-                        ("hFile", &hFile),
-                        ("lpBuffer", &lpBuffer),
-                        ("lpNumberOfBytesRead", &lpNumberOfBytesRead),
-                        ("lpOverlapped", &lpOverlapped),
+                        ("NumberOfHeaps", &NumberOfHeaps),
+                        ("ProcessHeaps", &ProcessHeaps),
                     ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::ReadFile(
-                machine,
-                hFile,
-                lpBuffer,
-                lpNumberOfBytesRead,
-                lpOverlapped,
-            );
+            let result = winapi::kernel32::GetProcessHeaps(machine, NumberOfHeaps, ProcessHeaps);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::ReadFile_pos.0,
-                    winapi::kernel32::ReadFile_pos.1,
+                    winapi::kernel32::GetProcessHeaps_pos.0,
+                    winapi::kernel32::GetProcessHeaps_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn ReleaseSRWLockExclusive(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetProcessId(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let SRWLock = <Option<&mut SRWLOCK>>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+            let Process = <HPROCESS>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/process") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/thread",
-                    "ReleaseSRWLockExclusive",
-                    &[("SRWLock", &SRWLock)],
+                    "kernel32/process",
+                    "GetProcessId",
+                    &[("Process", &Process)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::ReleaseSRWLockExclusive(machine, SRWLock);
+            let result = winapi::kernel32::GetProcessId(machine, Process);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::ReleaseSRWLockExclusive_pos.0,
-                    winapi::kernel32::ReleaseSRWLockExclusive_pos.1,
+                    winapi::kernel32::GetProcessId_pos.0,
+                    winapi::kernel32::GetProcessId_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn ReleaseSRWLockShared(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetProfileIntW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let SRWLock = <Option<&mut SRWLOCK>>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+            let lpAppName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+            let lpKeyName = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
+            let nDefault = <i32>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/ini") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/thread",
-                    "ReleaseSRWLockShared",
-                    &[("SRWLock", &SRWLock)],
+                    "kernel32/ini",
+                    "GetProfileIntW",
+                    &[
+                        ("lpAppName", &lpAppName),
+                        ("lpKeyName", &lpKeyName),
+                        ("nDefault", &nDefault),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::ReleaseSRWLockShared(machine, SRWLock);
+            let result = winapi::kernel32::GetProfileIntW(machine, lpAppName, lpKeyName, nDefault);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::ReleaseSRWLockShared_pos.0,
-                    winapi::kernel32::ReleaseSRWLockShared_pos.1,
+                    winapi::kernel32::GetProfileIntW_pos.0,
+                    winapi::kernel32::GetProfileIntW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn RemoveDirectoryA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetProfileStringW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpPathName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/file") {
+            let lpAppName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+            let lpKeyName = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
+            let lpDefault = <Option<&Str16>>::from_stack(mem, stack_args + 8u32);
+            let lpReturnedString = <ArrayWithSizeMut<u16>>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("kernel32/ini") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/file",
-                    "RemoveDirectoryA",
-                    &[("lpPathName", &lpPathName)],
+                    "kernel32/ini",
+                    "GetProfileStringW",
+                    &[
+                        ("lpAppName", &lpAppName),
+                        ("lpKeyName", &lpKeyName),
+                        ("lpDefault", &lpDefault),
+                        ("lpReturnedString", &lpReturnedString),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::RemoveDirectoryA(machine, lpPathName);
+            let result = winapi::kernel32::GetProfileStringW(
+                machine,
+                lpAppName,
+                lpKeyName,
+                lpDefault,
+                lpReturnedString,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::RemoveDirectoryA_pos.0,
-                    winapi::kernel32::RemoveDirectoryA_pos.1,
+                    winapi::kernel32::GetProfileStringW_pos.0,
+                    winapi::kernel32::GetProfileStringW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn ResumeThread(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetStartupInfoA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hThread = <HTHREAD>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+            let lpStartupInfo = <Option<&mut STARTUPINFOA>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/dll") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/thread",
-                    "ResumeThread",
-                    &[("hThread", &hThread)],
+                    "kernel32/dll",
+                    "GetStartupInfoA",
+                    &[("lpStartupInfo", &lpStartupInfo)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::ResumeThread(machine, hThread);
+            let result = winapi::kernel32::GetStartupInfoA(machine, lpStartupInfo);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::ResumeThread_pos.0,
-                    winapi::kernel32::ResumeThread_pos.1,
+                    winapi::kernel32::GetStartupInfoA_pos.0,
+                    winapi::kernel32::GetStartupInfoA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn RtlUnwind(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetStartupInfoW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let TargetFrame = <u32>::from_stack(mem, stack_args + 0u32);
-            let TargetIp = <u32>::from_stack(mem, stack_args + 4u32);
-            let ExceptionRecord = <u32>::from_stack(mem, stack_args + 8u32);
-            let ReturnValue = <u32>::from_stack(mem, stack_args + 12u32);
-            let __trace_context = if crate::trace::enabled("kernel32/misc") {
+            let lpStartupInfo = <Option<&mut STARTUPINFOA>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/dll") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/misc",
-                    "RtlUnwind",
-                    &[
-                        ("TargetFrame", &TargetFrame),
-                        ("TargetIp", &TargetIp),
-                        ("ExceptionRecord", &ExceptionRecord),
-                        ("ReturnValue", &ReturnValue),
-                    ],
+                    "kernel32/dll",
+                    "GetStartupInfoW",
+                    &[("lpStartupInfo", &lpStartupInfo)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::RtlUnwind(
-                machine,
-                TargetFrame,
-                TargetIp,
-                ExceptionRecord,
-                ReturnValue,
-            );
+            let result = winapi::kernel32::GetStartupInfoW(machine, lpStartupInfo);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::RtlUnwind_pos.0,
-                    winapi::kernel32::RtlUnwind_pos.1,
+                    winapi::kernel32::GetStartupInfoW_pos.0,
+                    winapi::kernel32::GetStartupInfoW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn SetConsoleCtrlHandler(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetStdHandle(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let _handlerRoutine = <DWORD>::from_stack(mem, stack_args + 0u32);
-            let _add = <u32>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/console") {
+            let nStdHandle = <Result<STD, u32>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/console",
-                    "SetConsoleCtrlHandler",
-                    &[("handlerRoutine", &_handlerRoutine), ("add", &_add)],
+                    "kernel32/file",
+                    "GetStdHandle",
+                    &[("nStdHandle", &nStdHandle)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::SetConsoleCtrlHandler(machine, _handlerRoutine, _add);
+            let result = winapi::kernel32::GetStdHandle(machine, nStdHandle);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::SetConsoleCtrlHandler_pos.0,
-                    winapi::kernel32::SetConsoleCtrlHandler_pos.1,
+                    winapi::kernel32::GetStdHandle_pos.0,
+                    winapi::kernel32::GetStdHandle_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn SetEndOfFile(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetStringTypeA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/file") {
+            let Locale = <LCID>::from_stack(mem, stack_args + 0u32);
+            let dwInfoType = <u32>::from_stack(mem, stack_args + 4u32);
+            let lpSrcStr = <u32>::from_stack(mem, stack_args + 8u32);
+            let cchSrc = <i32>::from_stack(mem, stack_args + 12u32);
+            let lpCharType = <Option<&mut u32>>::from_stack(mem, stack_args + 16u32);
+            let __trace_context = if crate::trace::enabled("kernel32/nls") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/file",
-                    "SetEndOfFile",
-                    &[("hFile", &hFile)],
+                    "kernel32/nls",
+                    "GetStringTypeA",
+                    &[
+                        ("Locale", &Locale),
+                        ("dwInfoType", &dwInfoType),
+                        ("lpSrcStr", &lpSrcStr),
+                        ("cchSrc", &cchSrc),
+                        ("lpCharType", &lpCharType),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::SetEndOfFile(machine, hFile);
+            let result = winapi::kernel32::GetStringTypeA(
+                machine, Locale, dwInfoType, lpSrcStr, cchSrc, lpCharType,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::SetEndOfFile_pos.0,
-                    winapi::kernel32::SetEndOfFile_pos.1,
+                    winapi::kernel32::GetStringTypeA_pos.0,
+                    winapi::kernel32::GetStringTypeA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn SetEnvironmentVariableA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetStringTypeW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let name = <Option<&str>>::from_stack(mem, stack_args + 0u32);
-            let value = <Option<&str>>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/env") {
+            let dwInfoType = <u32>::from_stack(mem, stack_args + 0u32);
+            let lpSrcStr = <u32>::from_stack(mem, stack_args + 4u32);
+            let cchSrc = <i32>::from_stack(mem, stack_args + 8u32);
+            let lpCharType = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("kernel32/nls") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/env",
-                    "SetEnvironmentVariableA",
-                    &[("name", &name), ("value", &value)],
+                    "kernel32/nls",
+                    "GetStringTypeW",
+                    &[
+                        ("dwInfoType", &dwInfoType),
+                        ("lpSrcStr", &lpSrcStr),
+                        ("cchSrc", &cchSrc),
+                        ("lpCharType", &lpCharType),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::SetEnvironmentVariableA(machine, name, value);
+            let result =
+                winapi::kernel32::GetStringTypeW(machine, dwInfoType, lpSrcStr, cchSrc, lpCharType);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::SetEnvironmentVariableA_pos.0,
-                    winapi::kernel32::SetEnvironmentVariableA_pos.1,
+                    winapi::kernel32::GetStringTypeW_pos.0,
+                    winapi::kernel32::GetStringTypeW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn SetEvent(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetSystemDirectoryA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hEvent = <HEVENT>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/sync") {
+            let lpBuffer = <u32>::from_stack(mem, stack_args + 0u32);
+            let uSize = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/misc") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/sync",
-                    "SetEvent",
-                    &[("hEvent", &hEvent)],
+                    "kernel32/misc",
+                    "GetSystemDirectoryA",
+                    &[("lpBuffer", &lpBuffer), ("uSize", &uSize)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::SetEvent(machine, hEvent);
+            let result = winapi::kernel32::GetSystemDirectoryA(machine, lpBuffer, uSize);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::SetEvent_pos.0,
-                    winapi::kernel32::SetEvent_pos.1,
+                    winapi::kernel32::GetSystemDirectoryA_pos.0,
+                    winapi::kernel32::GetSystemDirectoryA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn SetFileAttributesA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetSystemInfo(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpFileName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
-            let dwFileAttributes = <Result<FileAttribute, u32>>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/file") {
+            let lpSystemInfo = <Option<&mut SYSTEM_INFO>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/sysinfo") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/file",
-                    "SetFileAttributesA",
-                    &[
-                        ("lpFileName", &lpFileName),
-                        ("dwFileAttributes", &dwFileAttributes),
-                    ],
+                    "kernel32/sysinfo",
+                    "GetSystemInfo",
+                    &[("lpSystemInfo", &lpSystemInfo)],
                 ))
             } else {
                 None
             };
-            let result =
-                winapi::kernel32::SetFileAttributesA(machine, lpFileName, dwFileAttributes);
+            let result = winapi::kernel32::GetSystemInfo(machine, lpSystemInfo);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::SetFileAttributesA_pos.0,
-                    winapi::kernel32::SetFileAttributesA_pos.1,
+                    winapi::kernel32::GetSystemInfo_pos.0,
+                    winapi::kernel32::GetSystemInfo_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn SetFilePointer(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetSystemTime(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
-            let lDistanceToMove = <i32>::from_stack(mem, stack_args + 4u32);
-            let lpDistanceToMoveHigh = <Option<&mut i32>>::from_stack(mem, stack_args + 8u32);
-            let dwMoveMethod = <Result<FILE, u32>>::from_stack(mem, stack_args + 12u32);
+            let lpSystemTime = <Option<&mut SYSTEMTIME>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/time") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/time",
+                    "GetSystemTime",
+                    &[("lpSystemTime", &lpSystemTime)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::GetSystemTime(machine, lpSystemTime);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::GetSystemTime_pos.0,
+                    winapi::kernel32::GetSystemTime_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn GetSystemTimeAsFileTime(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpSystemTimeAsFileTime =
+                <Option<&mut FILETIME>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/time") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/time",
+                    "GetSystemTimeAsFileTime",
+                    &[("lpSystemTimeAsFileTime", &lpSystemTimeAsFileTime)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::GetSystemTimeAsFileTime(machine, lpSystemTimeAsFileTime);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::GetSystemTimeAsFileTime_pos.0,
+                    winapi::kernel32::GetSystemTimeAsFileTime_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn GetTempFileNameA(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpPathName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+            let lpPrefixString = <Option<&str>>::from_stack(mem, stack_args + 4u32);
+            let uUnique = <u32>::from_stack(mem, stack_args + 8u32);
+            let lpTempFileName = <u32>::from_stack(mem, stack_args + 12u32);
             let __trace_context = if crate::trace::enabled("kernel32/file") {
                 Some(crate::trace::trace_begin(
                     "kernel32/file",
-                    "SetFilePointer",
+                    "GetTempFileNameA",
                     &[
-                        ("hFile", &hFile),
-                        ("lDistanceToMove", &lDistanceToMove),
-                        ("lpDistanceToMoveHigh", &lpDistanceToMoveHigh),
-                        ("dwMoveMethod", &dwMoveMethod),
+                        ("lpPathName", &lpPathName),
+                        ("lpPrefixString", &lpPrefixString),
+                        ("uUnique", &uUnique),
+                        ("lpTempFileName", &lpTempFileName),
                     ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::SetFilePointer(
+            let result = winapi::kernel32::GetTempFileNameA(
                 machine,
-                hFile,
-                lDistanceToMove,
-                lpDistanceToMoveHigh,
-                dwMoveMethod,
+                lpPathName,
+                lpPrefixString,
+                uUnique,
+                lpTempFileName,
             );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::SetFilePointer_pos.0,
-                    winapi::kernel32::SetFilePointer_pos.1,
+                    winapi::kernel32::GetTempFileNameA_pos.0,
+                    winapi::kernel32::GetTempFileNameA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn SetFileTime(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetTempFileNameW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
-            let lpCreationTime = <Option<&FILETIME>>::from_stack(mem, stack_args + 4u32);
-            let lpLastAccessTime = <Option<&FILETIME>>::from_stack(mem, stack_args + 8u32);
-            let lpLastWriteTime = <Option<&FILETIME>>::from_stack(mem, stack_args + 12u32);
+            let lpPathName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+            let lpPrefixString = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
+            let uUnique = <u32>::from_stack(mem, stack_args + 8u32);
+            let lpTempFileName = <u32>::from_stack(mem, stack_args + 12u32);
             let __trace_context = if crate::trace::enabled("kernel32/file") {
                 Some(crate::trace::trace_begin(
                     "kernel32/file",
-                    "SetFileTime",
+                    "GetTempFileNameW",
                     &[
-                        ("hFile", &hFile),
-                        ("lpCreationTime", &lpCreationTime),
-                        ("lpLastAccessTime", &lpLastAccessTime),
-                        ("lpLastWriteTime", &lpLastWriteTime),
+                        ("lpPathName", &lpPathName),
+                        ("lpPrefixString", &lpPrefixString),
+                        ("uUnique", &uUnique),
+                        ("lpTempFileName", &lpTempFileName),
                     ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::SetFileTime(
+            let result = winapi::kernel32::GetTempFileNameW(
                 machine,
-                hFile,
-                lpCreationTime,
-                lpLastAccessTime,
-                lpLastWriteTime,
+                lpPathName,
+                lpPrefixString,
+                uUnique,
+                lpTempFileName,
             );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::SetFileTime_pos.0,
-                    winapi::kernel32::SetFileTime_pos.1,
+                    winapi::kernel32::GetTempFileNameW_pos.0,
+                    winapi::kernel32::GetTempFileNameW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn SetHandleCount(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetTempPathA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let uNumber = <u32>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/misc") {
+            let nBufferLength = <u32>::from_stack(mem, stack_args + 0u32);
+            let lpBuffer = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/misc",
-                    "SetHandleCount",
-                    &[("uNumber", &uNumber)],
+                    "kernel32/file",
+                    "GetTempPathA",
+                    &[("nBufferLength", &nBufferLength), ("lpBuffer", &lpBuffer)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::SetHandleCount(machine, uNumber);
+            let result = winapi::kernel32::GetTempPathA(machine, nBufferLength, lpBuffer);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::SetHandleCount_pos.0,
-                    winapi::kernel32::SetHandleCount_pos.1,
+                    winapi::kernel32::GetTempPathA_pos.0,
+                    winapi::kernel32::GetTempPathA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn SetLastError(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetTempPathW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let dwErrCode = <u32>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/misc") {
+            let nBufferLength = <u32>::from_stack(mem, stack_args + 0u32);
+            let lpBuffer = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/misc",
-                    "SetLastError",
-                    &[("dwErrCode", &dwErrCode)],
+                    "kernel32/file",
+                    "GetTempPathW",
+                    &[("nBufferLength", &nBufferLength), ("lpBuffer", &lpBuffer)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::SetLastError(machine, dwErrCode);
+            let result = winapi::kernel32::GetTempPathW(machine, nBufferLength, lpBuffer);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::SetLastError_pos.0,
-                    winapi::kernel32::SetLastError_pos.1,
+                    winapi::kernel32::GetTempPathW_pos.0,
+                    winapi::kernel32::GetTempPathW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn SetPriorityClass(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetThreadContext(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hProcess = <HANDLE<()>>::from_stack(mem, stack_args + 0u32);
-            let dwPriorityClass = <u32>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/misc") {
+            let hThread = <HTHREAD>::from_stack(mem, stack_args + 0u32);
+            let lpContext = <Option<&mut CONTEXT>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/exception") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/misc",
-                    "SetPriorityClass",
-                    &[
-                        ("hProcess", &hProcess),
-                        ("dwPriorityClass", &dwPriorityClass),
-                    ],
+                    "kernel32/exception",
+                    "GetThreadContext",
+                    &[("hThread", &hThread), ("lpContext", &lpContext)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::SetPriorityClass(machine, hProcess, dwPriorityClass);
+            let result = winapi::kernel32::GetThreadContext(machine, hThread, lpContext);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::SetPriorityClass_pos.0,
-                    winapi::kernel32::SetPriorityClass_pos.1,
+                    winapi::kernel32::GetThreadContext_pos.0,
+                    winapi::kernel32::GetThreadContext_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn SetStdHandle(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetThreadPriority(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let nStdHandle = <Result<STD, u32>>::from_stack(mem, stack_args + 0u32);
-            let hHandle = <u32>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/file") {
+            let hThread = <HTHREAD>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/thread") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/file",
-                    "SetStdHandle",
-                    &[("nStdHandle", &nStdHandle), ("hHandle", &hHandle)],
+                    "kernel32/thread",
+                    "GetThreadPriority",
+                    &[("hThread", &hThread)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::SetStdHandle(machine, nStdHandle, hHandle);
+            let result = winapi::kernel32::GetThreadPriority(machine, hThread);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::SetStdHandle_pos.0,
-                    winapi::kernel32::SetStdHandle_pos.1,
+                    winapi::kernel32::GetThreadPriority_pos.0,
+                    winapi::kernel32::GetThreadPriority_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn SetThreadDescription(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetTickCount(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hThread = <HTHREAD>::from_stack(mem, stack_args + 0u32);
-            let lpThreadDescription = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+            let __trace_context = if crate::trace::enabled("kernel32/time") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/thread",
-                    "SetThreadDescription",
-                    &[
-                        ("hThread", &hThread),
-                        ("lpThreadDescription", &lpThreadDescription),
-                    ],
+                    "kernel32/time",
+                    "GetTickCount",
+                    &[],
                 ))
             } else {
                 None
             };
-            let result =
-                winapi::kernel32::SetThreadDescription(machine, hThread, lpThreadDescription);
+            let result = winapi::kernel32::GetTickCount(machine);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::SetThreadDescription_pos.0,
-                    winapi::kernel32::SetThreadDescription_pos.1,
+                    winapi::kernel32::GetTickCount_pos.0,
+                    winapi::kernel32::GetTickCount_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn SetThreadPriority(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetTimeZoneInformation(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hThread = <HTHREAD>::from_stack(mem, stack_args + 0u32);
-            let nPriority = <u32>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+            let lpTimeZoneInformation =
+                <Option<&mut TIME_ZONE_INFORMATION>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/time") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/thread",
-                    "SetThreadPriority",
-                    &[("hThread", &hThread), ("nPriority", &nPriority)],
+                    "kernel32/time",
+                    "GetTimeZoneInformation",
+                    &[("lpTimeZoneInformation", &lpTimeZoneInformation)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::SetThreadPriority(machine, hThread, nPriority);
+            let result = winapi::kernel32::GetTimeZoneInformation(machine, lpTimeZoneInformation);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::SetThreadPriority_pos.0,
-                    winapi::kernel32::SetThreadPriority_pos.1,
+                    winapi::kernel32::GetTimeZoneInformation_pos.0,
+                    winapi::kernel32::GetTimeZoneInformation_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn SetThreadStackGuarantee(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetVersion(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let StackSizeInBytes = <Option<&mut u32>>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+            let __trace_context = if crate::trace::enabled("kernel32/misc") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/thread",
-                    "SetThreadStackGuarantee",
-                    &[("StackSizeInBytes", &StackSizeInBytes)],
+                    "kernel32/misc",
+                    "GetVersion",
+                    &[],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::SetThreadStackGuarantee(machine, StackSizeInBytes);
+            let result = winapi::kernel32::GetVersion(machine);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::SetThreadStackGuarantee_pos.0,
-                    winapi::kernel32::SetThreadStackGuarantee_pos.1,
+                    winapi::kernel32::GetVersion_pos.0,
+                    winapi::kernel32::GetVersion_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn SetUnhandledExceptionFilter(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetVersionExA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let _lpTopLevelExceptionFilter = <u32>::from_stack(mem, stack_args + 0u32);
+            let lpVersionInformation =
+                <Option<&mut OSVERSIONINFO>>::from_stack(mem, stack_args + 0u32);
             let __trace_context = if crate::trace::enabled("kernel32/misc") {
                 Some(crate::trace::trace_begin(
                     "kernel32/misc",
-                    "SetUnhandledExceptionFilter",
-                    &[("lpTopLevelExceptionFilter", &_lpTopLevelExceptionFilter)],
+                    "GetVersionExA",
+                    &[("lpVersionInformation", &lpVersionInformation)],
                 ))
             } else {
                 None
             };
-            let result =
-                winapi::kernel32::SetUnhandledExceptionFilter(machine, _lpTopLevelExceptionFilter);
+            let result = winapi::kernel32::GetVersionExA(machine, lpVersionInformation);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::SetUnhandledExceptionFilter_pos.0,
-                    winapi::kernel32::SetUnhandledExceptionFilter_pos.1,
+                    winapi::kernel32::GetVersionExA_pos.0,
+                    winapi::kernel32::GetVersionExA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn SizeofResource(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetVolumeInformationA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hModule = <HMODULE>::from_stack(mem, stack_args + 0u32);
-            let hResInfo = <HRSRC>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/resource") {
+            let lpRootPathName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+            let lpVolumeNameBuffer = <ArrayWithSizeMut<u8>>::from_stack(mem, stack_args + 4u32);
+            let lpVolumeSerialNumber = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
+            let lpMaximumComponentLength = <Option<&mut u32>>::from_stack(mem, stack_args + 16u32);
+            let lpFileSystemFlags = <Option<&mut u32>>::from_stack(mem, stack_args + 20u32);
+            let lpFileSystemNameBuffer =
+                <ArrayWithSizeMut<u8>>::from_stack(mem, stack_args + 24u32);
+            let __trace_context = if crate::trace::enabled("kernel32/volume") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/resource",
-                    "SizeofResource",
-                    &[("hModule", &hModule), ("hResInfo", &hResInfo)],
+                    "kernel32/volume",
+                    "GetVolumeInformationA",
+                    &[
+                        ("lpRootPathName", &lpRootPathName),
+                        ("lpVolumeNameBuffer", &lpVolumeNameBuffer),
+                        ("lpVolumeSerialNumber", &lpVolumeSerialNumber),
+                        ("lpMaximumComponentLength", &lpMaximumComponentLength),
+                        ("lpFileSystemFlags", &lpFileSystemFlags),
+                        ("lpFileSystemNameBuffer", &lpFileSystemNameBuffer),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::SizeofResource(machine, hModule, hResInfo);
+            let result = winapi::kernel32::GetVolumeInformationA(
+                machine,
+                lpRootPathName,
+                lpVolumeNameBuffer,
+                lpVolumeSerialNumber,
+                lpMaximumComponentLength,
+                lpFileSystemFlags,
+                lpFileSystemNameBuffer,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::SizeofResource_pos.0,
-                    winapi::kernel32::SizeofResource_pos.1,
+                    winapi::kernel32::GetVolumeInformationA_pos.0,
+                    winapi::kernel32::GetVolumeInformationA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn Sleep(
-            machine: &mut Machine,
-            stack_args: u32,
-        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+        pub unsafe fn GetVolumeInformationW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let dwMilliseconds = <u32>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/time") {
+            let lpRootPathName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+            let lpVolumeNameBuffer = <ArrayWithSizeMut<u16>>::from_stack(mem, stack_args + 4u32);
+            let lpVolumeSerialNumber = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
+            let lpMaximumComponentLength = <Option<&mut u32>>::from_stack(mem, stack_args + 16u32);
+            let lpFileSystemFlags = <Option<&mut u32>>::from_stack(mem, stack_args + 20u32);
+            let lpFileSystemNameBuffer =
+                <ArrayWithSizeMut<u16>>::from_stack(mem, stack_args + 24u32);
+            let __trace_context = if crate::trace::enabled("kernel32/volume") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/time",
-                    "Sleep",
-                    &[("dwMilliseconds", &dwMilliseconds)],
+                    "kernel32/volume",
+                    "GetVolumeInformationW",
+                    &[
+                        ("lpRootPathName", &lpRootPathName),
+                        ("lpVolumeNameBuffer", &lpVolumeNameBuffer),
+                        ("lpVolumeSerialNumber", &lpVolumeSerialNumber),
+                        ("lpMaximumComponentLength", &lpMaximumComponentLength),
+                        ("lpFileSystemFlags", &lpFileSystemFlags),
+                        ("lpFileSystemNameBuffer", &lpFileSystemNameBuffer),
+                    ],
                 ))
             } else {
                 None
             };
-            let machine: *mut Machine = machine;
-            Box::pin(async move {
-                let machine = unsafe { &mut *machine };
-                let result = winapi::kernel32::Sleep(machine, dwMilliseconds).await;
-                if let Some(__trace_context) = __trace_context {
-                    crate::trace::trace_return(
-                        &__trace_context,
-                        winapi::kernel32::Sleep_pos.0,
-                        winapi::kernel32::Sleep_pos.1,
-                        &result,
-                    );
-                }
-                result.to_raw()
-            })
-        }
-        pub unsafe fn SystemTimeToFileTime(machine: &mut Machine, stack_args: u32) -> u32 {
-            let mem = machine.mem().detach();
-            let lpSystemTime = <Option<&SYSTEMTIME>>::from_stack(mem, stack_args + 0u32);
-            let lpFileTime = <Option<&mut FILETIME>>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/time") {
-                Some(crate::trace::trace_begin(
-                    "kernel32/time",
-                    "SystemTimeToFileTime",
-                    &[("lpSystemTime", &lpSystemTime), ("lpFileTime", &lpFileTime)],
-                ))
-            } else {
-                None
-            };
-            let result = winapi::kernel32::SystemTimeToFileTime(machine, lpSystemTime, lpFileTime);
+            let result = winapi::kernel32::GetVolumeInformationW(
+                machine,
+                lpRootPathName,
+                lpVolumeNameBuffer,
+                lpVolumeSerialNumber,
+                lpMaximumComponentLength,
+                lpFileSystemFlags,
+                lpFileSystemNameBuffer,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::SystemTimeToFileTime_pos.0,
-                    winapi::kernel32::SystemTimeToFileTime_pos.1,
+                    winapi::kernel32::GetVolumeInformationW_pos.0,
+                    winapi::kernel32::GetVolumeInformationW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn TerminateProcess(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GetWindowsDirectoryA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hProcess = <u32>::from_stack(mem, stack_args + 0u32);
-            let uExitCode = <u32>::from_stack(mem, stack_args + 4u32);
+            let lpBuffer = <u32>::from_stack(mem, stack_args + 0u32);
+            let uSize = <u32>::from_stack(mem, stack_args + 4u32);
             let __trace_context = if crate::trace::enabled("kernel32/misc") {
                 Some(crate::trace::trace_begin(
                     "kernel32/misc",
-                    "TerminateProcess",
-                    &[("hProcess", &hProcess), ("uExitCode", &uExitCode)],
+                    "GetWindowsDirectoryA",
+                    &[("lpBuffer", &lpBuffer), ("uSize", &uSize)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::TerminateProcess(machine, hProcess, uExitCode);
+            let result = winapi::kernel32::GetWindowsDirectoryA(machine, lpBuffer, uSize);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::TerminateProcess_pos.0,
-                    winapi::kernel32::TerminateProcess_pos.1,
+                    winapi::kernel32::GetWindowsDirectoryA_pos.0,
+                    winapi::kernel32::GetWindowsDirectoryA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn TlsAlloc(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GlobalAlloc(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+            let uFlags = <GMEM>::from_stack(mem, stack_args + 0u32);
+            let dwBytes = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/memory") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/thread",
-                    "TlsAlloc",
-                    &[],
+                    "kernel32/memory",
+                    "GlobalAlloc",
+                    &[("uFlags", &uFlags), ("dwBytes", &dwBytes)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::TlsAlloc(machine);
+            let result = winapi::kernel32::GlobalAlloc(machine, uFlags, dwBytes);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::TlsAlloc_pos.0,
-                    winapi::kernel32::TlsAlloc_pos.1,
+                    winapi::kernel32::GlobalAlloc_pos.0,
+                    winapi::kernel32::GlobalAlloc_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn TlsFree(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GlobalFlags(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let dwTlsIndex = <u32>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+            let hMem = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/memory") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/thread",
-                    "TlsFree",
-                    &[("dwTlsIndex", &dwTlsIndex)],
+                    "kernel32/memory",
+                    "GlobalFlags",
+                    &[("hMem", &hMem)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::TlsFree(machine, dwTlsIndex);
+            let result = winapi::kernel32::GlobalFlags(machine, hMem);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::TlsFree_pos.0,
-                    winapi::kernel32::TlsFree_pos.1,
+                    winapi::kernel32::GlobalFlags_pos.0,
+                    winapi::kernel32::GlobalFlags_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn TlsGetValue(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GlobalFree(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let dwTlsIndex = <u32>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+            let hMem = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/memory") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/thread",
-                    "TlsGetValue",
-                    &[("dwTlsIndex", &dwTlsIndex)],
+                    "kernel32/memory",
+                    "GlobalFree",
+                    &[("hMem", &hMem)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::TlsGetValue(machine, dwTlsIndex);
+            let result = winapi::kernel32::GlobalFree(machine, hMem);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::TlsGetValue_pos.0,
-                    winapi::kernel32::TlsGetValue_pos.1,
+                    winapi::kernel32::GlobalFree_pos.0,
+                    winapi::kernel32::GlobalFree_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn TlsSetValue(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GlobalHandle(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let dwTlsIndex = <u32>::from_stack(mem, stack_args + 0u32);
-            let lpTlsValue = <u32>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+            let pMem = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/memory") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/thread",
-                    "TlsSetValue",
-                    &[("dwTlsIndex", &dwTlsIndex), ("lpTlsValue", &lpTlsValue)],
+                    "kernel32/memory",
+                    "GlobalHandle",
+                    &[("pMem", &pMem)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::TlsSetValue(machine, dwTlsIndex, lpTlsValue);
+            let result = winapi::kernel32::GlobalHandle(machine, pMem);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::TlsSetValue_pos.0,
-                    winapi::kernel32::TlsSetValue_pos.1,
+                    winapi::kernel32::GlobalHandle_pos.0,
+                    winapi::kernel32::GlobalHandle_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn TryAcquireSRWLockExclusive(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GlobalLock(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let SRWLock = <Option<&mut SRWLOCK>>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+            let hMem = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/memory") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/thread",
-                    "TryAcquireSRWLockExclusive",
-                    &[("SRWLock", &SRWLock)],
+                    "kernel32/memory",
+                    "GlobalLock",
+                    &[("hMem", &hMem)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::TryAcquireSRWLockExclusive(machine, SRWLock);
+            let result = winapi::kernel32::GlobalLock(machine, hMem);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::TryAcquireSRWLockExclusive_pos.0,
-                    winapi::kernel32::TryAcquireSRWLockExclusive_pos.1,
+                    winapi::kernel32::GlobalLock_pos.0,
+                    winapi::kernel32::GlobalLock_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn UnhandledExceptionFilter(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GlobalReAlloc(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let _exceptionInfo = <u32>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/misc") {
+            let hMem = <u32>::from_stack(mem, stack_args + 0u32);
+            let dwBytes = <u32>::from_stack(mem, stack_args + 4u32);
+            let uFlags = <GMEM>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/memory") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/misc",
-                    "UnhandledExceptionFilter",
-                    &[("exceptionInfo", &_exceptionInfo)],
+                    "kernel32/memory",
+                    "GlobalReAlloc",
+                    &[("hMem", &hMem), ("dwBytes", &dwBytes), ("uFlags", &uFlags)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::UnhandledExceptionFilter(machine, _exceptionInfo);
+            let result = winapi::kernel32::GlobalReAlloc(machine, hMem, dwBytes, uFlags);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::UnhandledExceptionFilter_pos.0,
-                    winapi::kernel32::UnhandledExceptionFilter_pos.1,
+                    winapi::kernel32::GlobalReAlloc_pos.0,
+                    winapi::kernel32::GlobalReAlloc_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn VirtualAlloc(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GlobalSize(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpAddress = <u32>::from_stack(mem, stack_args + 0u32);
-            let dwSize = <u32>::from_stack(mem, stack_args + 4u32);
-            let flAllocationType = <Result<MEM, u32>>::from_stack(mem, stack_args + 8u32);
-            let flProtec = <Result<PAGE, u32>>::from_stack(mem, stack_args + 12u32);
+            let hMem = <u32>::from_stack(mem, stack_args + 0u32);
             let __trace_context = if crate::trace::enabled("kernel32/memory") {
                 Some(crate::trace::trace_begin(
                     "kernel32/memory",
-                    "VirtualAlloc",
-                    &[
-                        ("lpAddress", &lpAddress),
-                        ("dwSize", &dwSize),
-                        ("flAllocationType", &flAllocationType),
-                        ("flProtec", &flProtec),
-                    ],
+                    "GlobalSize",
+                    &[("hMem", &hMem)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::VirtualAlloc(
-                machine,
-                lpAddress,
-                dwSize,
-                flAllocationType,
-                flProtec,
-            );
+            let result = winapi::kernel32::GlobalSize(machine, hMem);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::VirtualAlloc_pos.0,
-                    winapi::kernel32::VirtualAlloc_pos.1,
+                    winapi::kernel32::GlobalSize_pos.0,
+                    winapi::kernel32::GlobalSize_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn VirtualFree(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn GlobalUnlock(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpAddress = <u32>::from_stack(mem, stack_args + 0u32);
-            let dwSize = <u32>::from_stack(mem, stack_args + 4u32);
-            let dwFreeType = <u32>::from_stack(mem, stack_args + 8u32);
+            let hMem = <u32>::from_stack(mem, stack_args + 0u32);
             let __trace_context = if crate::trace::enabled("kernel32/memory") {
                 Some(crate::trace::trace_begin(
                     "kernel32/memory",
-                    "VirtualFree",
-                    &[
-                        ("lpAddress", &lpAddress),
-                        ("dwSize", &dwSize),
-                        ("dwFreeType", &dwFreeType),
-                    ],
+                    "GlobalUnlock",
+                    &[("hMem", &hMem)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::VirtualFree(machine, lpAddress, dwSize, dwFreeType);
+            let result = winapi::kernel32::GlobalUnlock(machine, hMem);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::VirtualFree_pos.0,
-                    winapi::kernel32::VirtualFree_pos.1,
+                    winapi::kernel32::GlobalUnlock_pos.0,
+                    winapi::kernel32::GlobalUnlock_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn VirtualProtect(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn HeapAlloc(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpAddress = <u32>::from_stack(mem, stack_args + 0u32);
-            let dwSize = <u32>::from_stack(mem, stack_args + 4u32);
-            let flNewProtect = <u32>::from_stack(mem, stack_args + 8u32);
-            let lpflOldProtect = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
+            let hHeap = <u32>::from_stack(mem, stack_args + 0u32);
+            let dwFlags = <Result<HeapAllocFlags, u32>>::from_stack(mem, stack_args + 4u32);
+            let dwBytes = <u32>::from_stack(mem, stack_args + 8u32);
             let __trace_context = if crate::trace::enabled("kernel32/memory") {
                 Some(crate::trace::trace_begin(
                     "kernel32/memory",
-                    "VirtualProtect",
+                    "HeapAlloc",
                     &[
-                        ("lpAddress", &lpAddress),
-                        ("dwSize", &dwSize),
-                        ("flNewProtect", &flNewProtect),
-                        ("lpflOldProtect", &lpflOldProtect),
+                        ("hHeap", &hHeap),
+                        ("dwFlags", &dwFlags),
+                        ("dwBytes", &dwBytes),
                     ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::VirtualProtect(
-                machine,
-                lpAddress,
-                dwSize,
-                flNewProtect,
-                lpflOldProtect,
-            );
+            let result = winapi::kernel32::HeapAlloc(machine, hHeap, dwFlags, dwBytes);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::VirtualProtect_pos.0,
-                    winapi::kernel32::VirtualProtect_pos.1,
+                    winapi::kernel32::HeapAlloc_pos.0,
+                    winapi::kernel32::HeapAlloc_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn VirtualQuery(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn HeapCreate(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpAddress = <u32>::from_stack(mem, stack_args + 0u32);
-            let lpBuffer =
-                <Option<&mut MEMORY_BASIC_INFORMATION>>::from_stack(mem, stack_args + 4u32);
-            let dwLength = <u32>::from_stack(mem, stack_args + 8u32);
+            let flOptions = <Result<HeapCreateFlags, u32>>::from_stack(mem, stack_args + 0u32);
+            let dwInitialSize = <u32>::from_stack(mem, stack_args + 4u32);
+            let dwMaximumSize = <u32>::from_stack(mem, stack_args + 8u32);
             let __trace_context = if crate::trace::enabled("kernel32/memory") {
                 Some(crate::trace::trace_begin(
                     "kernel32/memory",
-                    "VirtualQuery",
+                    "HeapCreate",
                     &[
-                        ("lpAddress", &lpAddress),
-                        ("lpBuffer", &lpBuffer),
-                        ("dwLength", &dwLength),
+                        ("flOptions", &flOptions),
+                        ("dwInitialSize", &dwInitialSize),
+                        ("dwMaximumSize", &dwMaximumSize),
                     ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::VirtualQuery(machine, lpAddress, lpBuffer, dwLength);
+            let result =
+                winapi::kernel32::HeapCreate(machine, flOptions, dwInitialSize, dwMaximumSize);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::VirtualQuery_pos.0,
-                    winapi::kernel32::VirtualQuery_pos.1,
+                    winapi::kernel32::HeapCreate_pos.0,
+                    winapi::kernel32::HeapCreate_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn WaitForSingleObject(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn HeapDestroy(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hHandle = <HEVENT>::from_stack(mem, stack_args + 0u32);
-            let dwMilliseconds = <u32>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/sync") {
+            let hHeap = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/memory") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/sync",
-                    "WaitForSingleObject",
-                    &[("hHandle", &hHandle), ("dwMilliseconds", &dwMilliseconds)],
+                    "kernel32/memory",
+                    "HeapDestroy",
+                    &[("hHeap", &hHeap)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::WaitForSingleObject(machine, hHandle, dwMilliseconds);
+            let result = winapi::kernel32::HeapDestroy(machine, hHeap);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::WaitForSingleObject_pos.0,
-                    winapi::kernel32::WaitForSingleObject_pos.1,
+                    winapi::kernel32::HeapDestroy_pos.0,
+                    winapi::kernel32::HeapDestroy_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn WideCharToMultiByte(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn HeapFree(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let CodePage = <Result<CP, u32>>::from_stack(mem, stack_args + 0u32);
+            let hHeap = <u32>::from_stack(mem, stack_args + 0u32);
             let dwFlags = <u32>::from_stack(mem, stack_args + 4u32);
-            let lpWideCharStr = <u32>::from_stack(mem, stack_args + 8u32);
-            let cchWideChar = <i32>::from_stack(mem, stack_args + 12u32);
-            let lpMultiByteStr = <u32>::from_stack(mem, stack_args + 16u32);
-            let cbMultiByte = <i32>::from_stack(mem, stack_args + 20u32);
-            let lpUsedDefaultChar = <Option<&mut u32>>::from_stack(mem, stack_args + 24u32);
-            let __trace_context = if crate::trace::enabled("kernel32/nls") {
+            let lpMem = <u32>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/memory") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/nls",
-                    "WideCharToMultiByte",
-                    &[
-                        ("CodePage", &CodePage),
-                        ("dwFlags", &dwFlags),
-                        ("lpWideCharStr", &lpWideCharStr),
-                        ("cchWideChar", &cchWideChar),
-                        ("lpMultiByteStr", &lpMultiByteStr),
-                        ("cbMultiByte", &cbMultiByte),
-                        ("lpUsedDefaultChar", &lpUsedDefaultChar),
-                    ],
+                    "kernel32/memory",
+                    "HeapFree",
+                    &[("hHeap", &hHeap), ("dwFlags", &dwFlags), ("lpMem", &lpMem)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::WideCharToMultiByte(
-                machine,
-                CodePage,
-                dwFlags,
-                lpWideCharStr,
-                cchWideChar,
-                lpMultiByteStr,
-                cbMultiByte,
-                lpUsedDefaultChar,
-            );
+            let result = winapi::kernel32::HeapFree(machine, hHeap, dwFlags, lpMem);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::WideCharToMultiByte_pos.0,
-                    winapi::kernel32::WideCharToMultiByte_pos.1,
+                    winapi::kernel32::HeapFree_pos.0,
+                    winapi::kernel32::HeapFree_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn WriteConsoleA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn HeapReAlloc(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hConsoleOutput = <HANDLE<()>>::from_stack(mem, stack_args + 0u32);
-            let lpBuffer = <ArrayWithSize<u8>>::from_stack(mem, stack_args + 4u32);
-            let lpNumberOfCharsWritten = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
-            let lpReserved = <u32>::from_stack(mem, stack_args + 16u32);
-            let __trace_context = if crate::trace::enabled("kernel32/console") {
+            let hHeap = <u32>::from_stack(mem, stack_args + 0u32);
+            let dwFlags = <Result<HeapAllocFlags, u32>>::from_stack(mem, stack_args + 4u32);
+            let lpMem = <u32>::from_stack(mem, stack_args + 8u32);
+            let dwBytes = <u32>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("kernel32/memory") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/console",
-                    "WriteConsoleA",
+                    "kernel32/memory",
+                    "HeapReAlloc",
                     &[
-                        ("hConsoleOutput", &hConsoleOutput),
-                        ("lpBuffer", &lpBuffer),
-                        ("lpNumberOfCharsWritten", &lpNumberOfCharsWritten),
-                        ("lpReserved", &lpReserved),
+                        ("hHeap", &hHeap),
+                        ("dwFlags", &dwFlags),
+                        ("lpMem", &lpMem),
+                        ("dwBytes", &dwBytes),
                     ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::WriteConsoleA(
-                machine,
-                hConsoleOutput,
-                lpBuffer,
-                lpNumberOfCharsWritten,
-                lpReserved,
-            );
+            let result = winapi::kernel32::HeapReAlloc(machine, hHeap, dwFlags, lpMem, dwBytes);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::WriteConsoleA_pos.0,
-                    winapi::kernel32::WriteConsoleA_pos.1,
+                    winapi::kernel32::HeapReAlloc_pos.0,
+                    winapi::kernel32::HeapReAlloc_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn WriteConsoleW(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn HeapSetInformation(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hConsoleOutput = <HFILE>::from_stack(mem, stack_args + 0u32);
-            let lpBuffer = <ArrayWithSize<u16>>::from_stack(mem, stack_args + 4u32);
-            let lpNumberOfCharsWritten = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
-            let _lpReserved = <u32>::from_stack(mem, stack_args + 16u32);
-            let __trace_context = if crate::trace::enabled("kernel32/console") {
+            let HeapHandle = <u32>::from_stack(mem, stack_args + 0u32);
+            let HeapInformationClass = <u32>::from_stack(mem, stack_args + 4u32);
+            let HeapInformation = <u32>::from_stack(mem, stack_args + 8u32);
+            let HeapInformationLength = <u32>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("kernel32/memory") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/console",
-                    "WriteConsoleW",
+                    "kernel32/memory",
+                    "HeapSetInformation",
                     &[
-                        ("hConsoleOutput", &hConsoleOutput),
-                        ("lpBuffer", &lpBuffer),
-                        ("lpNumberOfCharsWritten", &lpNumberOfCharsWritten),
-                        ("lpReserved", &_lpReserved),
+                        ("HeapHandle", &HeapHandle),
+                        ("HeapInformationClass", &HeapInformationClass),
+                        ("HeapInformation", &HeapInformation),
+                        ("HeapInformationLength", &HeapInformationLength),
                     ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::WriteConsoleW(
+            let result = winapi::kernel32::HeapSetInformation(
                 machine,
-                hConsoleOutput,
-                lpBuffer,
-                lpNumberOfCharsWritten,
-                _lpReserved,
+                HeapHandle,
+                HeapInformationClass,
+                HeapInformation,
+                HeapInformationLength,
             );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::WriteConsoleW_pos.0,
-                    winapi::kernel32::WriteConsoleW_pos.1,
+                    winapi::kernel32::HeapSetInformation_pos.0,
+                    winapi::kernel32::HeapSetInformation_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn WriteFile(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn HeapSize(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
-            let lpBuffer = <ArrayWithSize<u8>>::from_stack(mem, stack_args + 4u32);
-            let lpNumberOfBytesWritten = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
-            let lpOverlapped = <u32>::from_stack(mem, stack_args + 16u32);
-            let __trace_context = if crate::trace::enabled("kernel32/file") {
+            let hHeap = <u32>::from_stack(mem, stack_args + 0u32);
+            let dwFlags = <u32>::from_stack(mem, stack_args + 4u32);
+            let lpMem = <u32>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/memory") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/file",
-                    "WriteFile",
-                    &[
-                        ("hFile", &hFile),
-                        ("lpBuffer", &lpBuffer),
-                        ("lpNumberOfBytesWritten", &lpNumberOfBytesWritten),
-                        ("lpOverlapped", &lpOverlapped),
-                    ],
+                    "kernel32/memory",
+                    "HeapSize",
+                    &[("hHeap", &hHeap), ("dwFlags", &dwFlags), ("lpMem", &lpMem)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::WriteFile(
-                machine,
-                hFile,
-                lpBuffer,
-                lpNumberOfBytesWritten,
-                lpOverlapped,
-            );
+            let result = winapi::kernel32::HeapSize(machine, hHeap, dwFlags, lpMem);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::WriteFile_pos.0,
-                    winapi::kernel32::WriteFile_pos.1,
+                    winapi::kernel32::HeapSize_pos.0,
+                    winapi::kernel32::HeapSize_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn WriteProfileStringW(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn HeapValidate(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpAppName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
-            let lpKeyName = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
-            let lpString = <Option<&Str16>>::from_stack(mem, stack_args + 8u32);
-            let __trace_context = if crate::trace::enabled("kernel32/ini") {
+            let hHeap = <u32>::from_stack(mem, stack_args + 0u32);
+            let dwFlags = <u32>::from_stack(mem, stack_args + 4u32);
+            let lpMem = <u32>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/memory") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/ini",
-                    "WriteProfileStringW",
-                    &[
-                        ("lpAppName", &lpAppName),
-                        ("lpKeyName", &lpKeyName),
-                        ("lpString", &lpString),
-                    ],
+                    "kernel32/memory",
+                    "HeapValidate",
+                    &[("hHeap", &hHeap), ("dwFlags", &dwFlags), ("lpMem", &lpMem)],
                 ))
             } else {
                 None
             };
-            let result =
-                winapi::kernel32::WriteProfileStringW(machine, lpAppName, lpKeyName, lpString);
+            let result = winapi::kernel32::HeapValidate(machine, hHeap, dwFlags, lpMem);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::WriteProfileStringW_pos.0,
-                    winapi::kernel32::WriteProfileStringW_pos.1,
+                    winapi::kernel32::HeapValidate_pos.0,
+                    winapi::kernel32::HeapValidate_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn _lclose(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn HeapWalk(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/file16") {
+            let hHeap = <u32>::from_stack(mem, stack_args + 0u32);
+            let lpEntry = <Option<&mut PROCESS_HEAP_ENTRY>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/memory") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/file16",
-                    "_lclose",
-                    &[("hFile", &hFile)],
+                    "kernel32/memory",
+                    "HeapWalk",
+                    &[("hHeap", &hHeap), ("lpEntry", &lpEntry)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::_lclose(machine, hFile);
+            let result = winapi::kernel32::HeapWalk(machine, hHeap, lpEntry);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::_lclose_pos.0,
-                    winapi::kernel32::_lclose_pos.1,
+                    winapi::kernel32::HeapWalk_pos.0,
+                    winapi::kernel32::HeapWalk_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn _llseek(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn InitOnceBeginInitialize(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
-            let lOffset = <i32>::from_stack(mem, stack_args + 4u32);
-            let iOrigin = <i32>::from_stack(mem, stack_args + 8u32);
-            let __trace_context = if crate::trace::enabled("kernel32/file16") {
+            let lpInitOnce = <Option<&mut INIT_ONCE>>::from_stack(mem, stack_args + 0u32);
+            let dwFlags = <u32>::from_stack(mem, stack_args + 4u32);
+            let fPending = <Option<&mut u32>>::from_stack(mem, stack_args + 8u32);
+            let lpContext = <u32>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("kernel32/thread") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/file16",
-                    "_llseek",
+                    "kernel32/thread",
+                    "InitOnceBeginInitialize",
                     &[
-                        ("hFile", &hFile),
-                        ("lOffset", &lOffset),
-                        ("iOrigin", &iOrigin),
+                        ("lpInitOnce", &lpInitOnce),
+                        ("dwFlags", &dwFlags),
+                        ("fPending", &fPending),
+                        ("lpContext", &lpContext),
                     ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::_llseek(machine, hFile, lOffset, iOrigin);
+            let result = winapi::kernel32::InitOnceBeginInitialize(
+                machine, lpInitOnce, dwFlags, fPending, lpContext,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::_llseek_pos.0,
-                    winapi::kernel32::_llseek_pos.1,
+                    winapi::kernel32::InitOnceBeginInitialize_pos.0,
+                    winapi::kernel32::InitOnceBeginInitialize_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn _lopen(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn InitOnceComplete(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpPathName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
-            let iReadWrite = <i32>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/file16") {
+            let lpInitOnce = <Option<&mut INIT_ONCE>>::from_stack(mem, stack_args + 0u32);
+            let dwFlags = <u32>::from_stack(mem, stack_args + 4u32);
+            let lpContext = <u32>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/thread") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/file16",
-                    "_lopen",
-                    &[("lpPathName", &lpPathName), ("iReadWrite", &iReadWrite)],
+                    "kernel32/thread",
+                    "InitOnceComplete",
+                    &[
+                        ("lpInitOnce", &lpInitOnce),
+                        ("dwFlags", &dwFlags),
+                        ("lpContext", &lpContext),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::_lopen(machine, lpPathName, iReadWrite);
+            let result =
+                winapi::kernel32::InitOnceComplete(machine, lpInitOnce, dwFlags, lpContext);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::_lopen_pos.0,
-                    winapi::kernel32::_lopen_pos.1,
+                    winapi::kernel32::InitOnceComplete_pos.0,
+                    winapi::kernel32::InitOnceComplete_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn _lread(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn InitializeCriticalSection(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
-            let lpBuffer = <ArrayWithSize<u8>>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/file16") {
+            let lpCriticalSection =
+                <Option<&mut CRITICAL_SECTION>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/thread") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/file16",
-                    "_lread",
-                    &[("hFile", &hFile), ("lpBuffer", &lpBuffer)],
+                    "kernel32/thread",
+                    "InitializeCriticalSection",
+                    &[("lpCriticalSection", &lpCriticalSection)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::_lread(machine, hFile, lpBuffer);
+            let result = winapi::kernel32::InitializeCriticalSection(machine, lpCriticalSection);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::_lread_pos.0,
-                    winapi::kernel32::_lread_pos.1,
+                    winapi::kernel32::InitializeCriticalSection_pos.0,
+                    winapi::kernel32::InitializeCriticalSection_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn lstrcmpiA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn InitializeCriticalSectionAndSpinCount(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> u32 {
             let mem = machine.mem().detach();
-            let lpString1 = <Option<&str>>::from_stack(mem, stack_args + 0u32);
-            let lpString2 = <Option<&str>>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/libc") {
+            let lpCriticalSection =
+                <Option<&mut CRITICAL_SECTION>>::from_stack(mem, stack_args + 0u32);
+            let dwSpinCount = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/thread") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/libc",
-                    "lstrcmpiA",
-                    &[("lpString1", &lpString1), ("lpString2", &lpString2)],
+                    "kernel32/thread",
+                    "InitializeCriticalSectionAndSpinCount",
+                    &[
+                        ("lpCriticalSection", &lpCriticalSection),
+                        ("dwSpinCount", &dwSpinCount),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::lstrcmpiA(machine, lpString1, lpString2);
-            if let Some(__trace_context) = __trace_context {
-                crate::trace::trace_return(
-                    &__trace_context,
-                    winapi::kernel32::lstrcmpiA_pos.0,
-                    winapi::kernel32::lstrcmpiA_pos.1,
+            let result = winapi::kernel32::InitializeCriticalSectionAndSpinCount(
+                machine,
+                lpCriticalSection,
+                dwSpinCount,
+            );
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::InitializeCriticalSectionAndSpinCount_pos.0,
+                    winapi::kernel32::InitializeCriticalSectionAndSpinCount_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn lstrcpyA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn InitializeCriticalSectionEx(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpString1 = <u32>::from_stack(mem, stack_args + 0u32);
-            let lpString2 = <Option<&str>>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/libc") {
+            let lpCriticalSection =
+                <Option<&mut CRITICAL_SECTION>>::from_stack(mem, stack_args + 0u32);
+            let dwSpinCount = <u32>::from_stack(mem, stack_args + 4u32);
+            let flags = <u32>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/thread") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/libc",
-                    "lstrcpyA",
-                    &[("lpString1", &lpString1), ("lpString2", &lpString2)],
+                    "kernel32/thread",
+                    "InitializeCriticalSectionEx",
+                    &[
+                        ("lpCriticalSection", &lpCriticalSection),
+                        ("dwSpinCount", &dwSpinCount),
+                        ("flags", &flags),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::lstrcpyA(machine, lpString1, lpString2);
+            let result = winapi::kernel32::InitializeCriticalSectionEx(
+                machine,
+                lpCriticalSection,
+                dwSpinCount,
+                flags,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::lstrcpyA_pos.0,
-                    winapi::kernel32::lstrcpyA_pos.1,
+                    winapi::kernel32::InitializeCriticalSectionEx_pos.0,
+                    winapi::kernel32::InitializeCriticalSectionEx_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn lstrcpyW(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn InitializeSListHead(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpString1 = <u32>::from_stack(mem, stack_args + 0u32);
-            let lpString2 = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/libc") {
+            let ListHead = <Option<&mut SLIST_HEADER>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/interlocked") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/libc",
-                    "lstrcpyW",
-                    &[("lpString1", &lpString1), ("lpString2", &lpString2)],
+                    "kernel32/interlocked",
+                    "InitializeSListHead",
+                    &[("ListHead", &ListHead)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::lstrcpyW(machine, lpString1, lpString2);
+            let result = winapi::kernel32::InitializeSListHead(machine, ListHead);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::lstrcpyW_pos.0,
-                    winapi::kernel32::lstrcpyW_pos.1,
+                    winapi::kernel32::InitializeSListHead_pos.0,
+                    winapi::kernel32::InitializeSListHead_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn lstrlenA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn InterlockedCompareExchange(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpString = <Option<&str>>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/libc") {
+            let Destination = <Option<&mut i32>>::from_stack(mem, stack_args + 0u32);
+            let Exchange = <i32>::from_stack(mem, stack_args + 4u32);
+            let Comperand = <i32>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/interlocked") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/libc",
-                    "lstrlenA",
-                    &[("lpString", &lpString)],
+                    "kernel32/interlocked",
+                    "InterlockedCompareExchange",
+                    &[
+                        ("Destination", &Destination),
+                        ("Exchange", &Exchange),
+                        ("Comperand", &Comperand),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::lstrlenA(machine, lpString);
+            let result = winapi::kernel32::InterlockedCompareExchange(
+                machine,
+                Destination,
+                Exchange,
+                Comperand,
+            );
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::lstrlenA_pos.0,
-                    winapi::kernel32::lstrlenA_pos.1,
+                    winapi::kernel32::InterlockedCompareExchange_pos.0,
+                    winapi::kernel32::InterlockedCompareExchange_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn lstrlenW(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn InterlockedDecrement(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpString = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/libc") {
+            let Addend = <Option<&mut i32>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/interlocked") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/libc",
-                    "lstrlenW",
-                    &[("lpString", &lpString)],
+                    "kernel32/interlocked",
+                    "InterlockedDecrement",
+                    &[("Addend", &Addend)],
                 ))
             } else {
                 None
             };
-            let result = winapi::kernel32::lstrlenW(machine, lpString);
+            let result = winapi::kernel32::InterlockedDecrement(machine, Addend);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::kernel32::lstrlenW_pos.0,
-                    winapi::kernel32::lstrlenW_pos.1,
+                    winapi::kernel32::InterlockedDecrement_pos.0,
+                    winapi::kernel32::InterlockedDecrement_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn retrowin32_main(
-            machine: &mut Machine,
-            stack_args: u32,
-        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+        pub unsafe fn InterlockedExchange(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let entry_point = <u32>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("kernel32/init") {
+            let Target = <Option<&mut i32>>::from_stack(mem, stack_args + 0u32);
+            let Value = <i32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/interlocked") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/init",
-                    "retrowin32_main",
-                    &[("entry_point", &entry_point)],
+                    "kernel32/interlocked",
+                    "InterlockedExchange",
+                    &[("Target", &Target), ("Value", &Value)],
                 ))
             } else {
                 None
             };
-            let machine: *mut Machine = machine;
-            Box::pin(async move {
-                let machine = unsafe { &mut *machine };
-                let result = winapi::kernel32::retrowin32_main(machine, entry_point).await;
-                if let Some(__trace_context) = __trace_context {
-                    crate::trace::trace_return(
-                        &__trace_context,
-                        winapi::kernel32::retrowin32_main_pos.0,
-                        winapi::kernel32::retrowin32_main_pos.1,
-                        &result,
-                    );
-                }
-                result.to_raw()
-            })
+            let result = winapi::kernel32::InterlockedExchange(machine, Target, Value);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::InterlockedExchange_pos.0,
+                    winapi::kernel32::InterlockedExchange_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
         }
-        pub unsafe fn retrowin32_thread_main(
-            machine: &mut Machine,
-            stack_args: u32,
-        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+        pub unsafe fn InterlockedExchangeAdd(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let entry_point = <u32>::from_stack(mem, stack_args + 0u32);
-            let param = <u32>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("kernel32/init") {
+            let Addend = <Option<&mut i32>>::from_stack(mem, stack_args + 0u32);
+            let Value = <i32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/interlocked") {
                 Some(crate::trace::trace_begin(
-                    "kernel32/init",
-                    "retrowin32_thread_main",
-                    &[("entry_point", &entry_point), ("param", &param)],
+                    "kernel32/interlocked",
+                    "InterlockedExchangeAdd",
+                    &[("Addend", &Addend), ("Value", &Value)],
                 ))
             } else {
                 None
             };
-            let machine: *mut Machine = machine;
-            Box::pin(async move {
-                let machine = unsafe { &mut *machine };
-                let result =
-                    winapi::kernel32::retrowin32_thread_main(machine, entry_point, param).await;
-                if let Some(__trace_context) = __trace_context {
-                    crate::trace::trace_return(
-                        &__trace_context,
-                        winapi::kernel32::retrowin32_thread_main_pos.0,
-                        winapi::kernel32::retrowin32_thread_main_pos.1,
-                        &result,
-                    );
-                }
-                result.to_raw()
-            })
+            let result = winapi::kernel32::InterlockedExchangeAdd(machine, Addend, Value);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::InterlockedExchangeAdd_pos.0,
+                    winapi::kernel32::InterlockedExchangeAdd_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
         }
-    }
-    const SHIMS: [Shim; 173usize] = [
-        Shim {
-            name: "AcquireSRWLockExclusive",
-            func: Handler::Sync(wrappers::AcquireSRWLockExclusive),
-        },
-        Shim {
-            name: "AcquireSRWLockShared",
-            func: Handler::Sync(wrappers::AcquireSRWLockShared),
-        },
-        Shim {
-            name: "AddVectoredExceptionHandler",
-            func: Handler::Sync(wrappers::AddVectoredExceptionHandler),
-        },
-        Shim {
-            name: "CloseHandle",
-            func: Handler::Sync(wrappers::CloseHandle),
-        },
-        Shim {
-            name: "CreateDirectoryA",
-            func: Handler::Sync(wrappers::CreateDirectoryA),
-        },
-        Shim {
-            name: "CreateEventA",
-            func: Handler::Sync(wrappers::CreateEventA),
-        },
-        Shim {
-            name: "CreateFileA",
-            func: Handler::Sync(wrappers::CreateFileA),
-        },
-        Shim {
-            name: "CreateFileW",
-            func: Handler::Sync(wrappers::CreateFileW),
-        },
-        Shim {
-            name: "CreateThread",
-            func: Handler::Async(wrappers::CreateThread),
-        },
-        Shim {
-            name: "DebugBreak",
-            func: Handler::Sync(wrappers::DebugBreak),
-        },
-        Shim {
-            name: "DeleteCriticalSection",
-            func: Handler::Sync(wrappers::DeleteCriticalSection),
-        },
-        Shim {
-            name: "DeleteFileA",
-            func: Handler::Sync(wrappers::DeleteFileA),
-        },
-        Shim {
-            name: "DisableThreadLibraryCalls",
-            func: Handler::Sync(wrappers::DisableThreadLibraryCalls),
-        },
-        Shim {
-            name: "EnterCriticalSection",
-            func: Handler::Sync(wrappers::EnterCriticalSection),
-        },
-        Shim {
-            name: "ExitProcess",
-            func: Handler::Sync(wrappers::ExitProcess),
-        },
-        Shim {
-            name: "ExitThread",
-            func: Handler::Sync(wrappers::ExitThread),
-        },
-        Shim {
-            name: "FileTimeToSystemTime",
-            func: Handler::Sync(wrappers::FileTimeToSystemTime),
-        },
-        Shim {
-            name: "FindClose",
-            func: Handler::Sync(wrappers::FindClose),
-        },
-        Shim {
-            name: "FindFirstFileA",
-            func: Handler::Sync(wrappers::FindFirstFileA),
-        },
-        Shim {
-            name: "FindNextFileA",
-            func: Handler::Sync(wrappers::FindNextFileA),
-        },
-        Shim {
-            name: "FindResourceA",
-            func: Handler::Sync(wrappers::FindResourceA),
-        },
-        Shim {
-            name: "FindResourceW",
-            func: Handler::Sync(wrappers::FindResourceW),
-        },
-        Shim {
-            name: "FlushFileBuffers",
-            func: Handler::Sync(wrappers::FlushFileBuffers),
-        },
-        Shim {
-            name: "FormatMessageA",
-            func: Handler::Sync(wrappers::FormatMessageA),
-        },
-        Shim {
-            name: "FormatMessageW",
-            func: Handler::Sync(wrappers::FormatMessageW),
-        },
-        Shim {
-            name: "FreeEnvironmentStringsA",
-            func: Handler::Sync(wrappers::FreeEnvironmentStringsA),
-        },
-        Shim {
-            name: "FreeEnvironmentStringsW",
-            func: Handler::Sync(wrappers::FreeEnvironmentStringsW),
-        },
-        Shim {
-            name: "FreeLibrary",
-            func: Handler::Sync(wrappers::FreeLibrary),
-        },
-        Shim {
-            name: "GetACP",
-            func: Handler::Sync(wrappers::GetACP),
-        },
-        Shim {
-            name: "GetCPInfo",
-            func: Handler::Sync(wrappers::GetCPInfo),
-        },
-        Shim {
+        pub unsafe fn InterlockedFlushSList(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let ListHead = <Option<&mut SLIST_HEADER>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/interlocked") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/interlocked",
+                    "InterlockedFlushSList",
+                    &[("ListHead", &ListHead)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::InterlockedFlushSList(machine, ListHead);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::InterlockedFlushSList_pos.0,
+                    winapi::kernel32::InterlockedFlushSList_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn InterlockedIncrement(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let Addend = <Option<&mut i32>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/interlocked") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/interlocked",
+                    "InterlockedIncrement",
+                    &[("Addend", &Addend)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::InterlockedIncrement(machine, Addend);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::InterlockedIncrement_pos.0,
+                    winapi::kernel32::InterlockedIncrement_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn InterlockedPopEntrySList(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let ListHead = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/interlocked") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/interlocked",
+                    "InterlockedPopEntrySList",
+                    &[("ListHead", &ListHead)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::InterlockedPopEntrySList(machine, ListHead);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::InterlockedPopEntrySList_pos.0,
+                    winapi::kernel32::InterlockedPopEntrySList_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn InterlockedPushEntrySList(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let ListHead = <u32>::from_stack(mem, stack_args + 0u32);
+            let ListEntry = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/interlocked") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/interlocked",
+                    "InterlockedPushEntrySList",
+                    &[("ListHead", &ListHead), ("ListEntry", &ListEntry)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::InterlockedPushEntrySList(machine, ListHead, ListEntry);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::InterlockedPushEntrySList_pos.0,
+                    winapi::kernel32::InterlockedPushEntrySList_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn IsBadCodePtr(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpfn = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/memory",
+                    "IsBadCodePtr",
+                    &[("lpfn", &lpfn)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::IsBadCodePtr(machine, lpfn);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::IsBadCodePtr_pos.0,
+                    winapi::kernel32::IsBadCodePtr_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn IsBadReadPtr(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lp = <u32>::from_stack(mem, stack_args + 0u32);
+            let ucb = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/memory",
+                    "IsBadReadPtr",
+                    &[("lp", &lp), ("ucb", &ucb)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::IsBadReadPtr(machine, lp, ucb);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::IsBadReadPtr_pos.0,
+                    winapi::kernel32::IsBadReadPtr_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn IsBadStringPtrA(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpsz = <u32>::from_stack(mem, stack_args + 0u32);
+            let ucchMax = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/memory",
+                    "IsBadStringPtrA",
+                    &[("lpsz", &lpsz), ("ucchMax", &ucchMax)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::IsBadStringPtrA(machine, lpsz, ucchMax);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::IsBadStringPtrA_pos.0,
+                    winapi::kernel32::IsBadStringPtrA_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn IsBadWritePtr(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lp = <u32>::from_stack(mem, stack_args + 0u32);
+            let ucb = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/memory",
+                    "IsBadWritePtr",
+                    &[("lp", &lp), ("ucb", &ucb)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::IsBadWritePtr(machine, lp, ucb);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::IsBadWritePtr_pos.0,
+                    winapi::kernel32::IsBadWritePtr_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn IsDBCSLeadByte(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let TestChar = <u8>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/nls") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/nls",
+                    "IsDBCSLeadByte",
+                    &[("TestChar", &TestChar)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::IsDBCSLeadByte(machine, TestChar);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::IsDBCSLeadByte_pos.0,
+                    winapi::kernel32::IsDBCSLeadByte_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn IsDBCSLeadByteEx(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let TestChar = <u8>::from_stack(mem, stack_args + 0u32);
+            let CodePage = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/nls") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/nls",
+                    "IsDBCSLeadByteEx",
+                    &[("TestChar", &TestChar), ("CodePage", &CodePage)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::IsDBCSLeadByteEx(machine, TestChar, CodePage);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::IsDBCSLeadByteEx_pos.0,
+                    winapi::kernel32::IsDBCSLeadByteEx_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn IsDebuggerPresent(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let __trace_context = if crate::trace::enabled("kernel32/misc") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/misc",
+                    "IsDebuggerPresent",
+                    &[],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::IsDebuggerPresent(machine);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::IsDebuggerPresent_pos.0,
+                    winapi::kernel32::IsDebuggerPresent_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn IsProcessorFeaturePresent(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let feature = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/sysinfo") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/sysinfo",
+                    "IsProcessorFeaturePresent",
+                    &[("feature", &feature)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::IsProcessorFeaturePresent(machine, feature);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::IsProcessorFeaturePresent_pos.0,
+                    winapi::kernel32::IsProcessorFeaturePresent_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn IsThreadAFiber(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let __trace_context = if crate::trace::enabled("kernel32/fiber") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/fiber",
+                    "IsThreadAFiber",
+                    &[],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::IsThreadAFiber(machine);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::IsThreadAFiber_pos.0,
+                    winapi::kernel32::IsThreadAFiber_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn IsValidCodePage(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let CodePage = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/nls") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/nls",
+                    "IsValidCodePage",
+                    &[("CodePage", &CodePage)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::IsValidCodePage(machine, CodePage);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::IsValidCodePage_pos.0,
+                    winapi::kernel32::IsValidCodePage_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn LCMapStringA(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let locale = <LCID>::from_stack(mem, stack_args + 0u32);
+            let dwMapFlags = <u32>::from_stack(mem, stack_args + 4u32);
+            let lpSrcStr = <u32>::from_stack(mem, stack_args + 8u32);
+            let cchSrc = <i32>::from_stack(mem, stack_args + 12u32);
+            let lpDestStr = <ArrayWithSizeMut<u8>>::from_stack(mem, stack_args + 16u32);
+            let __trace_context = if crate::trace::enabled("kernel32/nls") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/nls",
+                    "LCMapStringA",
+                    &[
+                        ("locale", &locale),
+                        ("dwMapFlags", &dwMapFlags),
+                        ("lpSrcStr", &lpSrcStr),
+                        ("cchSrc", &cchSrc),
+                        ("lpDestStr", &lpDestStr),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::LCMapStringA(
+                machine, locale, dwMapFlags, lpSrcStr, cchSrc, lpDestStr,
+            );
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::LCMapStringA_pos.0,
+                    winapi::kernel32::LCMapStringA_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn LCMapStringW(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let locale = <LCID>::from_stack(mem, stack_args + 0u32);
+            let dwMapFlags = <u32>::from_stack(mem, stack_args + 4u32);
+            let lpSrcStr = <u32>::from_stack(mem, stack_args + 8u32);
+            let cchSrc = <i32>::from_stack(mem, stack_args + 12u32);
+            let lpDestStr = <ArrayWithSizeMut<u16>>::from_stack(mem, stack_args + 16u32);
+            let __trace_context = if crate::trace::enabled("kernel32/nls") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/nls",
+                    "LCMapStringW",
+                    &[
+                        ("locale", &locale),
+                        ("dwMapFlags", &dwMapFlags),
+                        ("lpSrcStr", &lpSrcStr),
+                        ("cchSrc", &cchSrc),
+                        ("lpDestStr", &lpDestStr),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::LCMapStringW(
+                machine, locale, dwMapFlags, lpSrcStr, cchSrc, lpDestStr,
+            );
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::LCMapStringW_pos.0,
+                    winapi::kernel32::LCMapStringW_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn LeaveCriticalSection(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpCriticalSection =
+                <Option<&mut CRITICAL_SECTION>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/thread",
+                    "LeaveCriticalSection",
+                    &[("lpCriticalSection", &lpCriticalSection)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::LeaveCriticalSection(machine, lpCriticalSection);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::LeaveCriticalSection_pos.0,
+                    winapi::kernel32::LeaveCriticalSection_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn LoadLibraryA(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let lpLibFileName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/dll") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/dll",
+                    "LoadLibraryA",
+                    &[("lpLibFileName", &lpLibFileName)],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::kernel32::LoadLibraryA(machine, lpLibFileName).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::kernel32::LoadLibraryA_pos.0,
+                        winapi::kernel32::LoadLibraryA_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn LoadLibraryExA(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let lpLibFileName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+            let hFile = <HFILE>::from_stack(mem, stack_args + 4u32);
+            let dwFlags = <u32>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/dll") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/dll",
+                    "LoadLibraryExA",
+                    &[
+                        ("lpLibFileName", &lpLibFileName),
+                        ("hFile", &hFile),
+                        ("dwFlags", &dwFlags),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result =
+                    winapi::kernel32::LoadLibraryExA(machine, lpLibFileName, hFile, dwFlags).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::kernel32::LoadLibraryExA_pos.0,
+                        winapi::kernel32::LoadLibraryExA_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn LoadLibraryExW(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let lpLibFileName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+            let hFile = <HFILE>::from_stack(mem, stack_args + 4u32);
+            let dwFlags = <u32>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/dll") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/dll",
+                    "LoadLibraryExW",
+                    &[
+                        ("lpLibFileName", &lpLibFileName),
+                        ("hFile", &hFile),
+                        ("dwFlags", &dwFlags),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result =
+                    winapi::kernel32::LoadLibraryExW(machine, lpLibFileName, hFile, dwFlags).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::kernel32::LoadLibraryExW_pos.0,
+                        winapi::kernel32::LoadLibraryExW_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn LoadLibraryW(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let lpLibFileName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/dll") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/dll",
+                    "LoadLibraryW",
+                    &[("lpLibFileName", &lpLibFileName)],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::kernel32::LoadLibraryW(machine, lpLibFileName).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::kernel32::LoadLibraryW_pos.0,
+                        winapi::kernel32::LoadLibraryW_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn LoadResource(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hModule = <HMODULE>::from_stack(mem, stack_args + 0u32);
+            let hResInfo = <HRSRC>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/resource") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/resource",
+                    "LoadResource",
+                    &[("hModule", &hModule), ("hResInfo", &hResInfo)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::LoadResource(machine, hModule, hResInfo);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::LoadResource_pos.0,
+                    winapi::kernel32::LoadResource_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn LocalAlloc(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let uFlags = <GMEM>::from_stack(mem, stack_args + 0u32);
+            let dwBytes = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/memory",
+                    "LocalAlloc",
+                    &[("uFlags", &uFlags), ("dwBytes", &dwBytes)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::LocalAlloc(machine, uFlags, dwBytes);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::LocalAlloc_pos.0,
+                    winapi::kernel32::LocalAlloc_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn LocalFileTimeToFileTime(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpLocalFileTime = <Option<&FILETIME>>::from_stack(mem, stack_args + 0u32);
+            let lpFileTime = <Option<&mut FILETIME>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/time") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/time",
+                    "LocalFileTimeToFileTime",
+                    &[
+                        ("lpLocalFileTime", &lpLocalFileTime),
+                        ("lpFileTime", &lpFileTime),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result =
+                winapi::kernel32::LocalFileTimeToFileTime(machine, lpLocalFileTime, lpFileTime);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::LocalFileTimeToFileTime_pos.0,
+                    winapi::kernel32::LocalFileTimeToFileTime_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn LocalFlags(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hMem = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/memory",
+                    "LocalFlags",
+                    &[("hMem", &hMem)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::LocalFlags(machine, hMem);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::LocalFlags_pos.0,
+                    winapi::kernel32::LocalFlags_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn LocalFree(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hMem = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/memory",
+                    "LocalFree",
+                    &[("hMem", &hMem)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::LocalFree(machine, hMem);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::LocalFree_pos.0,
+                    winapi::kernel32::LocalFree_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn LocalHandle(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let pMem = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/memory",
+                    "LocalHandle",
+                    &[("pMem", &pMem)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::LocalHandle(machine, pMem);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::LocalHandle_pos.0,
+                    winapi::kernel32::LocalHandle_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn LocalLock(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hMem = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/memory",
+                    "LocalLock",
+                    &[("hMem", &hMem)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::LocalLock(machine, hMem);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::LocalLock_pos.0,
+                    winapi::kernel32::LocalLock_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn LocalReAlloc(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hMem = <u32>::from_stack(mem, stack_args + 0u32);
+            let uBytes = <u32>::from_stack(mem, stack_args + 4u32);
+            let uFlags = <GMEM>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/memory",
+                    "LocalReAlloc",
+                    &[("hMem", &hMem), ("uBytes", &uBytes), ("uFlags", &uFlags)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::LocalReAlloc(machine, hMem, uBytes, uFlags);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::LocalReAlloc_pos.0,
+                    winapi::kernel32::LocalReAlloc_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn LocalSize(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hMem = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/memory",
+                    "LocalSize",
+                    &[("hMem", &hMem)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::LocalSize(machine, hMem);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::LocalSize_pos.0,
+                    winapi::kernel32::LocalSize_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn LocalUnlock(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hMem = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/memory",
+                    "LocalUnlock",
+                    &[("hMem", &hMem)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::LocalUnlock(machine, hMem);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::LocalUnlock_pos.0,
+                    winapi::kernel32::LocalUnlock_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn LockResource(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hResData = <HRSRC>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/resource") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/resource",
+                    "LockResource",
+                    &[("hResData", &hResData)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::LockResource(machine, hResData);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::LockResource_pos.0,
+                    winapi::kernel32::LockResource_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn MapViewOfFile(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hFileMappingObject = <HOBJECT>::from_stack(mem, stack_args + 0u32);
+            let dwDesiredAccess = <u32>::from_stack(mem, stack_args + 4u32);
+            let dwFileOffsetHigh = <u32>::from_stack(mem, stack_args + 8u32);
+            let dwFileOffsetLow = <u32>::from_stack(mem, stack_args + 12u32);
+            let dwNumberOfBytesToMap = <u32>::from_stack(mem, stack_args + 16u32);
+            let __trace_context = if crate::trace::enabled("kernel32/mapping") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/mapping",
+                    "MapViewOfFile",
+                    &[
+                        ("hFileMappingObject", &hFileMappingObject),
+                        ("dwDesiredAccess", &dwDesiredAccess),
+                        ("dwFileOffsetHigh", &dwFileOffsetHigh),
+                        ("dwFileOffsetLow", &dwFileOffsetLow),
+                        ("dwNumberOfBytesToMap", &dwNumberOfBytesToMap),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::MapViewOfFile(
+                machine,
+                hFileMappingObject,
+                dwDesiredAccess,
+                dwFileOffsetHigh,
+                dwFileOffsetLow,
+                dwNumberOfBytesToMap,
+            );
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::MapViewOfFile_pos.0,
+                    winapi::kernel32::MapViewOfFile_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn MoveFileA(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpExistingFileName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+            let lpNewFileName = <Option<&str>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/file",
+                    "MoveFileA",
+                    &[
+                        ("lpExistingFileName", &lpExistingFileName),
+                        ("lpNewFileName", &lpNewFileName),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::MoveFileA(machine, lpExistingFileName, lpNewFileName);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::MoveFileA_pos.0,
+                    winapi::kernel32::MoveFileA_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn MoveFileExA(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpExistingFileName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+            let lpNewFileName = <Option<&str>>::from_stack(mem, stack_args + 4u32);
+            let dwFlags = <Result<MOVEFILE, u32>>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/file",
+                    "MoveFileExA",
+                    &[
+                        ("lpExistingFileName", &lpExistingFileName),
+                        ("lpNewFileName", &lpNewFileName),
+                        ("dwFlags", &dwFlags),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result =
+                winapi::kernel32::MoveFileExA(machine, lpExistingFileName, lpNewFileName, dwFlags);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::MoveFileExA_pos.0,
+                    winapi::kernel32::MoveFileExA_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn MoveFileExW(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpExistingFileName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+            let lpNewFileName = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
+            let dwFlags = <Result<MOVEFILE, u32>>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/file",
+                    "MoveFileExW",
+                    &[
+                        ("lpExistingFileName", &lpExistingFileName),
+                        ("lpNewFileName", &lpNewFileName),
+                        ("dwFlags", &dwFlags),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result =
+                winapi::kernel32::MoveFileExW(machine, lpExistingFileName, lpNewFileName, dwFlags);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::MoveFileExW_pos.0,
+                    winapi::kernel32::MoveFileExW_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn MoveFileW(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpExistingFileName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+            let lpNewFileName = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/file",
+                    "MoveFileW",
+                    &[
+                        ("lpExistingFileName", &lpExistingFileName),
+                        ("lpNewFileName", &lpNewFileName),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::MoveFileW(machine, lpExistingFileName, lpNewFileName);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::MoveFileW_pos.0,
+                    winapi::kernel32::MoveFileW_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn MulDiv(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let nNumber = <i32>::from_stack(mem, stack_args + 0u32);
+            let nNumerator = <i32>::from_stack(mem, stack_args + 4u32);
+            let nDenominator = <i32>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/misc") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/misc",
+                    "MulDiv",
+                    &[
+                        ("nNumber", &nNumber),
+                        ("nNumerator", &nNumerator),
+                        ("nDenominator", &nDenominator),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::MulDiv(machine, nNumber, nNumerator, nDenominator);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::MulDiv_pos.0,
+                    winapi::kernel32::MulDiv_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn MultiByteToWideChar(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let CodePage = <u32>::from_stack(mem, stack_args + 0u32);
+            let dwFlags = <u32>::from_stack(mem, stack_args + 4u32);
+            let lpMultiByteStr = <u32>::from_stack(mem, stack_args + 8u32);
+            let cbMultiByte = <i32>::from_stack(mem, stack_args + 12u32);
+            let lpWideCharStr = <ArrayWithSizeMut<u16>>::from_stack(mem, stack_args + 16u32);
+            let __trace_context = if crate::trace::enabled("kernel32/nls") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/nls",
+                    "MultiByteToWideChar",
+                    &[
+                        ("CodePage", &CodePage),
+                        ("dwFlags", &dwFlags),
+                        ("lpMultiByteStr", &lpMultiByteStr),
+                        ("cbMultiByte", &cbMultiByte),
+                        ("lpWideCharStr", &lpWideCharStr),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::MultiByteToWideChar(
+                machine,
+                CodePage,
+                dwFlags,
+                lpMultiByteStr,
+                cbMultiByte,
+                lpWideCharStr,
+            );
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::MultiByteToWideChar_pos.0,
+                    winapi::kernel32::MultiByteToWideChar_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn NtCurrentTeb(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let __trace_context = if crate::trace::enabled("kernel32/misc") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/misc",
+                    "NtCurrentTeb",
+                    &[],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::NtCurrentTeb(machine);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::NtCurrentTeb_pos.0,
+                    winapi::kernel32::NtCurrentTeb_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn OpenFileMappingA(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let dwDesiredAccess = <u32>::from_stack(mem, stack_args + 0u32);
+            let bInheritHandle = <bool>::from_stack(mem, stack_args + 4u32);
+            let lpName = <Option<&str>>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/mapping") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/mapping",
+                    "OpenFileMappingA",
+                    &[
+                        ("dwDesiredAccess", &dwDesiredAccess),
+                        ("bInheritHandle", &bInheritHandle),
+                        ("lpName", &lpName),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::OpenFileMappingA(
+                machine,
+                dwDesiredAccess,
+                bInheritHandle,
+                lpName,
+            );
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::OpenFileMappingA_pos.0,
+                    winapi::kernel32::OpenFileMappingA_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn OpenFileMappingW(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let dwDesiredAccess = <u32>::from_stack(mem, stack_args + 0u32);
+            let bInheritHandle = <bool>::from_stack(mem, stack_args + 4u32);
+            let lpName = <Option<&Str16>>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/mapping") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/mapping",
+                    "OpenFileMappingW",
+                    &[
+                        ("dwDesiredAccess", &dwDesiredAccess),
+                        ("bInheritHandle", &bInheritHandle),
+                        ("lpName", &lpName),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::OpenFileMappingW(
+                machine,
+                dwDesiredAccess,
+                bInheritHandle,
+                lpName,
+            );
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::OpenFileMappingW_pos.0,
+                    winapi::kernel32::OpenFileMappingW_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn OutputDebugStringA(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpOutputString = <Option<&CStr>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/misc") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/misc",
+                    "OutputDebugStringA",
+                    &[("lpOutputString", &lpOutputString)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::OutputDebugStringA(machine, lpOutputString);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::OutputDebugStringA_pos.0,
+                    winapi::kernel32::OutputDebugStringA_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn OutputDebugStringW(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpOutputString = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/misc") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/misc",
+                    "OutputDebugStringW",
+                    &[("lpOutputString", &lpOutputString)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::OutputDebugStringW(machine, lpOutputString);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::OutputDebugStringW_pos.0,
+                    winapi::kernel32::OutputDebugStringW_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn PeekNamedPipe(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hNamedPipe = <HFILE>::from_stack(mem, stack_args + 0u32);
+            let lpBuffer = <ArrayWithSizeMut<u8>>::from_stack(mem, stack_args + 4u32);
+            let lpBytesRead = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
+            let lpTotalBytesAvail = <Option<&mut u32>>::from_stack(mem, stack_args + 16u32);
+            let lpBytesLeftThisMessage = <Option<&mut u32>>::from_stack(mem, stack_args + 20u32);
+            let __trace_context = if crate::trace::enabled("kernel32/pipe") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/pipe",
+                    "PeekNamedPipe",
+                    &[
+                        ("hNamedPipe", &hNamedPipe),
+                        ("lpBuffer", &lpBuffer),
+                        ("lpBytesRead", &lpBytesRead),
+                        ("lpTotalBytesAvail", &lpTotalBytesAvail),
+                        ("lpBytesLeftThisMessage", &lpBytesLeftThisMessage),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::PeekNamedPipe(
+                machine,
+                hNamedPipe,
+                lpBuffer,
+                lpBytesRead,
+                lpTotalBytesAvail,
+                lpBytesLeftThisMessage,
+            );
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::PeekNamedPipe_pos.0,
+                    winapi::kernel32::PeekNamedPipe_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn PulseEvent(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hEvent = <HEVENT>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/sync") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/sync",
+                    "PulseEvent",
+                    &[("hEvent", &hEvent)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::PulseEvent(machine, hEvent);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::PulseEvent_pos.0,
+                    winapi::kernel32::PulseEvent_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn QueryDepthSList(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let ListHead = <Option<&mut SLIST_HEADER>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/interlocked") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/interlocked",
+                    "QueryDepthSList",
+                    &[("ListHead", &ListHead)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::QueryDepthSList(machine, ListHead);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::QueryDepthSList_pos.0,
+                    winapi::kernel32::QueryDepthSList_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn QueryPerformanceCounter(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpPerformanceCount =
+                <Option<&mut LARGE_INTEGER>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/time") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/time",
+                    "QueryPerformanceCounter",
+                    &[("lpPerformanceCount", &lpPerformanceCount)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::QueryPerformanceCounter(machine, lpPerformanceCount);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::QueryPerformanceCounter_pos.0,
+                    winapi::kernel32::QueryPerformanceCounter_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn QueryPerformanceFrequency(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpFrequency = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/time") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/time",
+                    "QueryPerformanceFrequency",
+                    &[("lpFrequency", &lpFrequency)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::QueryPerformanceFrequency(machine, lpFrequency);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::QueryPerformanceFrequency_pos.0,
+                    winapi::kernel32::QueryPerformanceFrequency_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn QueueUserAPC(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let pfnAPC = <u32>::from_stack(mem, stack_args + 0u32);
+            let hThread = <HTHREAD>::from_stack(mem, stack_args + 4u32);
+            let dwData = <u32>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/thread",
+                    "QueueUserAPC",
+                    &[
+                        ("pfnAPC", &pfnAPC),
+                        ("hThread", &hThread),
+                        ("dwData", &dwData),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::QueueUserAPC(machine, pfnAPC, hThread, dwData);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::QueueUserAPC_pos.0,
+                    winapi::kernel32::QueueUserAPC_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn RaiseException(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let dwExceptionCode = <u32>::from_stack(mem, stack_args + 0u32);
+            let dwExceptionFlags = <u32>::from_stack(mem, stack_args + 4u32);
+            let nNumberOfArguments = <u32>::from_stack(mem, stack_args + 8u32);
+            let lpArguments = <u32>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("kernel32/exception") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/exception",
+                    "RaiseException",
+                    &[
+                        ("dwExceptionCode", &dwExceptionCode),
+                        ("dwExceptionFlags", &dwExceptionFlags),
+                        ("nNumberOfArguments", &nNumberOfArguments),
+                        ("lpArguments", &lpArguments),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::kernel32::RaiseException(
+                    machine,
+                    dwExceptionCode,
+                    dwExceptionFlags,
+                    nNumberOfArguments,
+                    lpArguments,
+                )
+                .await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::kernel32::RaiseException_pos.0,
+                        winapi::kernel32::RaiseException_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn ReadConsoleA(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let hConsoleInput = <HFILE>::from_stack(mem, stack_args + 0u32);
+            let lpBuffer = <ArrayWithSizeMut<'_, u8>>::from_stack(mem, stack_args + 4u32);
+            let lpNumberOfCharsRead = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
+            let pInputControl = <u32>::from_stack(mem, stack_args + 16u32);
+            let __trace_context = if crate::trace::enabled("kernel32/console") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/console",
+                    "ReadConsoleA",
+                    &[
+                        ("hConsoleInput", &hConsoleInput),
+                        ("lpBuffer", &lpBuffer),
+                        ("lpNumberOfCharsRead", &lpNumberOfCharsRead),
+                        ("pInputControl", &pInputControl),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::kernel32::ReadConsoleA(
+                    machine,
+                    hConsoleInput,
+                    lpBuffer,
+                    lpNumberOfCharsRead,
+                    pInputControl,
+                )
+                .await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::kernel32::ReadConsoleA_pos.0,
+                        winapi::kernel32::ReadConsoleA_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn ReadConsoleW(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let hConsoleInput = <HFILE>::from_stack(mem, stack_args + 0u32);
+            let lpBuffer = <ArrayWithSizeMut<'_, u16>>::from_stack(mem, stack_args + 4u32);
+            let lpNumberOfCharsRead = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
+            let pInputControl = <u32>::from_stack(mem, stack_args + 16u32);
+            let __trace_context = if crate::trace::enabled("kernel32/console") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/console",
+                    "ReadConsoleW",
+                    &[
+                        ("hConsoleInput", &hConsoleInput),
+                        ("lpBuffer", &lpBuffer),
+                        ("lpNumberOfCharsRead", &lpNumberOfCharsRead),
+                        ("pInputControl", &pInputControl),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::kernel32::ReadConsoleW(
+                    machine,
+                    hConsoleInput,
+                    lpBuffer,
+                    lpNumberOfCharsRead,
+                    pInputControl,
+                )
+                .await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::kernel32::ReadConsoleW_pos.0,
+                        winapi::kernel32::ReadConsoleW_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn ReadFile(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
+            let lpBuffer = <ArrayWithSizeMut<'_, u8>>::from_stack(mem, stack_args + 4u32);
+            let lpNumberOfBytesRead = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
+            let lpOverlapped = <u32>::from_stack(mem, stack_args + 16u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/file",
+                    "ReadFile",
+                    &[
+                        ("hFile", &hFile),
+                        ("lpBuffer", &lpBuffer),
+                        ("lpNumberOfBytesRead", &lpNumberOfBytesRead),
+                        ("lpOverlapped", &lpOverlapped),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::kernel32::ReadFile(
+                    machine,
+                    hFile,
+                    lpBuffer,
+                    lpNumberOfBytesRead,
+                    lpOverlapped,
+                )
+                .await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::kernel32::ReadFile_pos.0,
+                        winapi::kernel32::ReadFile_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn ReadFileEx(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
+            let lpBuffer = <ArrayWithSizeMut<u8>>::from_stack(mem, stack_args + 4u32);
+            let lpOverlapped = <u32>::from_stack(mem, stack_args + 12u32);
+            let lpCompletionRoutine = <u32>::from_stack(mem, stack_args + 16u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/file",
+                    "ReadFileEx",
+                    &[
+                        ("hFile", &hFile),
+                        ("lpBuffer", &lpBuffer),
+                        ("lpOverlapped", &lpOverlapped),
+                        ("lpCompletionRoutine", &lpCompletionRoutine),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::ReadFileEx(
+                machine,
+                hFile,
+                lpBuffer,
+                lpOverlapped,
+                lpCompletionRoutine,
+            );
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::ReadFileEx_pos.0,
+                    winapi::kernel32::ReadFileEx_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn ReleaseMutex(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hMutex = <HOBJECT>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/sync") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/sync",
+                    "ReleaseMutex",
+                    &[("hMutex", &hMutex)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::ReleaseMutex(machine, hMutex);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::ReleaseMutex_pos.0,
+                    winapi::kernel32::ReleaseMutex_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn ReleaseSRWLockExclusive(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let SRWLock = <Option<&mut SRWLOCK>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/thread",
+                    "ReleaseSRWLockExclusive",
+                    &[("SRWLock", &SRWLock)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::ReleaseSRWLockExclusive(machine, SRWLock);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::ReleaseSRWLockExclusive_pos.0,
+                    winapi::kernel32::ReleaseSRWLockExclusive_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn ReleaseSRWLockShared(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let SRWLock = <Option<&mut SRWLOCK>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/thread",
+                    "ReleaseSRWLockShared",
+                    &[("SRWLock", &SRWLock)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::ReleaseSRWLockShared(machine, SRWLock);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::ReleaseSRWLockShared_pos.0,
+                    winapi::kernel32::ReleaseSRWLockShared_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn ReleaseSemaphore(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hSemaphore = <HOBJECT>::from_stack(mem, stack_args + 0u32);
+            let lReleaseCount = <i32>::from_stack(mem, stack_args + 4u32);
+            let lpPreviousCount = <Option<&mut i32>>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/sync") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/sync",
+                    "ReleaseSemaphore",
+                    &[
+                        ("hSemaphore", &hSemaphore),
+                        ("lReleaseCount", &lReleaseCount),
+                        ("lpPreviousCount", &lpPreviousCount),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::ReleaseSemaphore(
+                machine,
+                hSemaphore,
+                lReleaseCount,
+                lpPreviousCount,
+            );
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::ReleaseSemaphore_pos.0,
+                    winapi::kernel32::ReleaseSemaphore_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn RemoveDirectoryA(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpPathName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/file",
+                    "RemoveDirectoryA",
+                    &[("lpPathName", &lpPathName)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::RemoveDirectoryA(machine, lpPathName);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::RemoveDirectoryA_pos.0,
+                    winapi::kernel32::RemoveDirectoryA_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn ResetEvent(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hEvent = <HEVENT>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/sync") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/sync",
+                    "ResetEvent",
+                    &[("hEvent", &hEvent)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::ResetEvent(machine, hEvent);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::ResetEvent_pos.0,
+                    winapi::kernel32::ResetEvent_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn ResumeThread(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hThread = <HTHREAD>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/thread",
+                    "ResumeThread",
+                    &[("hThread", &hThread)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::ResumeThread(machine, hThread);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::ResumeThread_pos.0,
+                    winapi::kernel32::ResumeThread_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn SetConsoleCtrlHandler(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let _handlerRoutine = <DWORD>::from_stack(mem, stack_args + 0u32);
+            let _add = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/console") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/console",
+                    "SetConsoleCtrlHandler",
+                    &[("handlerRoutine", &_handlerRoutine), ("add", &_add)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::SetConsoleCtrlHandler(machine, _handlerRoutine, _add);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::SetConsoleCtrlHandler_pos.0,
+                    winapi::kernel32::SetConsoleCtrlHandler_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn SetConsoleMode(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hConsoleHandle = <HFILE>::from_stack(mem, stack_args + 0u32);
+            let dwMode = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/console") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/console",
+                    "SetConsoleMode",
+                    &[("hConsoleHandle", &hConsoleHandle), ("dwMode", &dwMode)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::SetConsoleMode(machine, hConsoleHandle, dwMode);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::SetConsoleMode_pos.0,
+                    winapi::kernel32::SetConsoleMode_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn SetConsoleTitleA(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpConsoleTitle = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/console") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/console",
+                    "SetConsoleTitleA",
+                    &[("lpConsoleTitle", &lpConsoleTitle)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::SetConsoleTitleA(machine, lpConsoleTitle);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::SetConsoleTitleA_pos.0,
+                    winapi::kernel32::SetConsoleTitleA_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn SetConsoleTitleW(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpConsoleTitle = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/console") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/console",
+                    "SetConsoleTitleW",
+                    &[("lpConsoleTitle", &lpConsoleTitle)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::SetConsoleTitleW(machine, lpConsoleTitle);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::SetConsoleTitleW_pos.0,
+                    winapi::kernel32::SetConsoleTitleW_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn SetCurrentDirectoryA(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpPathName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/file",
+                    "SetCurrentDirectoryA",
+                    &[("lpPathName", &lpPathName)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::SetCurrentDirectoryA(machine, lpPathName);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::SetCurrentDirectoryA_pos.0,
+                    winapi::kernel32::SetCurrentDirectoryA_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn SetCurrentDirectoryW(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpPathName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/file",
+                    "SetCurrentDirectoryW",
+                    &[("lpPathName", &lpPathName)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::SetCurrentDirectoryW(machine, lpPathName);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::SetCurrentDirectoryW_pos.0,
+                    winapi::kernel32::SetCurrentDirectoryW_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn SetEndOfFile(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/file",
+                    "SetEndOfFile",
+                    &[("hFile", &hFile)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::SetEndOfFile(machine, hFile);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::SetEndOfFile_pos.0,
+                    winapi::kernel32::SetEndOfFile_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn SetEnvironmentVariableA(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let name = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+            let value = <Option<&str>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/env") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/env",
+                    "SetEnvironmentVariableA",
+                    &[("name", &name), ("value", &value)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::SetEnvironmentVariableA(machine, name, value);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::SetEnvironmentVariableA_pos.0,
+                    winapi::kernel32::SetEnvironmentVariableA_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn SetEvent(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hEvent = <HEVENT>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/sync") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/sync",
+                    "SetEvent",
+                    &[("hEvent", &hEvent)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::SetEvent(machine, hEvent);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::SetEvent_pos.0,
+                    winapi::kernel32::SetEvent_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn SetFileAttributesA(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpFileName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+            let dwFileAttributes = <Result<FileAttribute, u32>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/file",
+                    "SetFileAttributesA",
+                    &[
+                        ("lpFileName", &lpFileName),
+                        ("dwFileAttributes", &dwFileAttributes),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result =
+                winapi::kernel32::SetFileAttributesA(machine, lpFileName, dwFileAttributes);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::SetFileAttributesA_pos.0,
+                    winapi::kernel32::SetFileAttributesA_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn SetFileAttributesW(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpFileName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+            let dwFileAttributes = <Result<FileAttribute, u32>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/file",
+                    "SetFileAttributesW",
+                    &[
+                        ("lpFileName", &lpFileName),
+                        ("dwFileAttributes", &dwFileAttributes),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result =
+                winapi::kernel32::SetFileAttributesW(machine, lpFileName, dwFileAttributes);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::SetFileAttributesW_pos.0,
+                    winapi::kernel32::SetFileAttributesW_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn SetFilePointer(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
+            let lDistanceToMove = <i32>::from_stack(mem, stack_args + 4u32);
+            let lpDistanceToMoveHigh = <Option<&mut i32>>::from_stack(mem, stack_args + 8u32);
+            let dwMoveMethod = <Result<FILE, u32>>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/file",
+                    "SetFilePointer",
+                    &[
+                        ("hFile", &hFile),
+                        ("lDistanceToMove", &lDistanceToMove),
+                        ("lpDistanceToMoveHigh", &lpDistanceToMoveHigh),
+                        ("dwMoveMethod", &dwMoveMethod),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::SetFilePointer(
+                machine,
+                hFile,
+                lDistanceToMove,
+                lpDistanceToMoveHigh,
+                dwMoveMethod,
+            );
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::SetFilePointer_pos.0,
+                    winapi::kernel32::SetFilePointer_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn SetFilePointerEx(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
+            let liDistanceToMove = <u64>::from_stack(mem, stack_args + 4u32);
+            let lpNewFilePointer = <Option<&mut u64>>::from_stack(mem, stack_args + 12u32);
+            let dwMoveMethod = <Result<FILE, u32>>::from_stack(mem, stack_args + 16u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/file",
+                    "SetFilePointerEx",
+                    &[
+                        ("hFile", &hFile),
+                        ("liDistanceToMove", &liDistanceToMove),
+                        ("lpNewFilePointer", &lpNewFilePointer),
+                        ("dwMoveMethod", &dwMoveMethod),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::SetFilePointerEx(
+                machine,
+                hFile,
+                liDistanceToMove,
+                lpNewFilePointer,
+                dwMoveMethod,
+            );
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::SetFilePointerEx_pos.0,
+                    winapi::kernel32::SetFilePointerEx_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn SetFileTime(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
+            let lpCreationTime = <Option<&FILETIME>>::from_stack(mem, stack_args + 4u32);
+            let lpLastAccessTime = <Option<&FILETIME>>::from_stack(mem, stack_args + 8u32);
+            let lpLastWriteTime = <Option<&FILETIME>>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/file",
+                    "SetFileTime",
+                    &[
+                        ("hFile", &hFile),
+                        ("lpCreationTime", &lpCreationTime),
+                        ("lpLastAccessTime", &lpLastAccessTime),
+                        ("lpLastWriteTime", &lpLastWriteTime),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::SetFileTime(
+                machine,
+                hFile,
+                lpCreationTime,
+                lpLastAccessTime,
+                lpLastWriteTime,
+            );
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::SetFileTime_pos.0,
+                    winapi::kernel32::SetFileTime_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn SetHandleCount(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let uNumber = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/misc") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/misc",
+                    "SetHandleCount",
+                    &[("uNumber", &uNumber)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::SetHandleCount(machine, uNumber);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::SetHandleCount_pos.0,
+                    winapi::kernel32::SetHandleCount_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn SetLastError(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let dwErrCode = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/misc") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/misc",
+                    "SetLastError",
+                    &[("dwErrCode", &dwErrCode)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::SetLastError(machine, dwErrCode);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::SetLastError_pos.0,
+                    winapi::kernel32::SetLastError_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn SetLocalTime(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpSystemTime = <Option<&SYSTEMTIME>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/time") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/time",
+                    "SetLocalTime",
+                    &[("lpSystemTime", &lpSystemTime)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::SetLocalTime(machine, lpSystemTime);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::SetLocalTime_pos.0,
+                    winapi::kernel32::SetLocalTime_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn SetNamedPipeHandleState(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hNamedPipe = <HFILE>::from_stack(mem, stack_args + 0u32);
+            let lpMode = <Option<&u32>>::from_stack(mem, stack_args + 4u32);
+            let lpMaxCollectionCount = <u32>::from_stack(mem, stack_args + 8u32);
+            let lpCollectDataTimeout = <u32>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("kernel32/pipe") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/pipe",
+                    "SetNamedPipeHandleState",
+                    &[
+                        ("hNamedPipe", &hNamedPipe),
+                        ("lpMode", &lpMode),
+                        ("lpMaxCollectionCount", &lpMaxCollectionCount),
+                        ("lpCollectDataTimeout", &lpCollectDataTimeout),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::SetNamedPipeHandleState(
+                machine,
+                hNamedPipe,
+                lpMode,
+                lpMaxCollectionCount,
+                lpCollectDataTimeout,
+            );
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::SetNamedPipeHandleState_pos.0,
+                    winapi::kernel32::SetNamedPipeHandleState_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn SetPriorityClass(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hProcess = <HANDLE<()>>::from_stack(mem, stack_args + 0u32);
+            let dwPriorityClass = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/misc") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/misc",
+                    "SetPriorityClass",
+                    &[
+                        ("hProcess", &hProcess),
+                        ("dwPriorityClass", &dwPriorityClass),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::SetPriorityClass(machine, hProcess, dwPriorityClass);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::SetPriorityClass_pos.0,
+                    winapi::kernel32::SetPriorityClass_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn SetStdHandle(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let nStdHandle = <Result<STD, u32>>::from_stack(mem, stack_args + 0u32);
+            let hHandle = <HFILE>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/file",
+                    "SetStdHandle",
+                    &[("nStdHandle", &nStdHandle), ("hHandle", &hHandle)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::SetStdHandle(machine, nStdHandle, hHandle);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::SetStdHandle_pos.0,
+                    winapi::kernel32::SetStdHandle_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn SetSystemTime(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpSystemTime = <Option<&SYSTEMTIME>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/time") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/time",
+                    "SetSystemTime",
+                    &[("lpSystemTime", &lpSystemTime)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::SetSystemTime(machine, lpSystemTime);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::SetSystemTime_pos.0,
+                    winapi::kernel32::SetSystemTime_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn SetThreadContext(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hThread = <HTHREAD>::from_stack(mem, stack_args + 0u32);
+            let lpContext = <Option<&CONTEXT>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/exception") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/exception",
+                    "SetThreadContext",
+                    &[("hThread", &hThread), ("lpContext", &lpContext)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::SetThreadContext(machine, hThread, lpContext);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::SetThreadContext_pos.0,
+                    winapi::kernel32::SetThreadContext_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn SetThreadDescription(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hThread = <HTHREAD>::from_stack(mem, stack_args + 0u32);
+            let lpThreadDescription = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/thread",
+                    "SetThreadDescription",
+                    &[
+                        ("hThread", &hThread),
+                        ("lpThreadDescription", &lpThreadDescription),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result =
+                winapi::kernel32::SetThreadDescription(machine, hThread, lpThreadDescription);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::SetThreadDescription_pos.0,
+                    winapi::kernel32::SetThreadDescription_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn SetThreadPriority(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hThread = <HTHREAD>::from_stack(mem, stack_args + 0u32);
+            let nPriority = <i32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/thread",
+                    "SetThreadPriority",
+                    &[("hThread", &hThread), ("nPriority", &nPriority)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::SetThreadPriority(machine, hThread, nPriority);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::SetThreadPriority_pos.0,
+                    winapi::kernel32::SetThreadPriority_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn SetThreadStackGuarantee(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let StackSizeInBytes = <Option<&mut u32>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/thread",
+                    "SetThreadStackGuarantee",
+                    &[("StackSizeInBytes", &StackSizeInBytes)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::SetThreadStackGuarantee(machine, StackSizeInBytes);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::SetThreadStackGuarantee_pos.0,
+                    winapi::kernel32::SetThreadStackGuarantee_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn SetUnhandledExceptionFilter(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpTopLevelExceptionFilter = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/exception") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/exception",
+                    "SetUnhandledExceptionFilter",
+                    &[("lpTopLevelExceptionFilter", &lpTopLevelExceptionFilter)],
+                ))
+            } else {
+                None
+            };
+            let result =
+                winapi::kernel32::SetUnhandledExceptionFilter(machine, lpTopLevelExceptionFilter);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::SetUnhandledExceptionFilter_pos.0,
+                    winapi::kernel32::SetUnhandledExceptionFilter_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn SizeofResource(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hModule = <HMODULE>::from_stack(mem, stack_args + 0u32);
+            let hResInfo = <HRSRC>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/resource") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/resource",
+                    "SizeofResource",
+                    &[("hModule", &hModule), ("hResInfo", &hResInfo)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::SizeofResource(machine, hModule, hResInfo);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::SizeofResource_pos.0,
+                    winapi::kernel32::SizeofResource_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn Sleep(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let dwMilliseconds = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/time") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/time",
+                    "Sleep",
+                    &[("dwMilliseconds", &dwMilliseconds)],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::kernel32::Sleep(machine, dwMilliseconds).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::kernel32::Sleep_pos.0,
+                        winapi::kernel32::Sleep_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn SleepEx(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let dwMilliseconds = <u32>::from_stack(mem, stack_args + 0u32);
+            let bAlertable = <bool>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/time") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/time",
+                    "SleepEx",
+                    &[
+                        ("dwMilliseconds", &dwMilliseconds),
+                        ("bAlertable", &bAlertable),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::kernel32::SleepEx(machine, dwMilliseconds, bAlertable).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::kernel32::SleepEx_pos.0,
+                        winapi::kernel32::SleepEx_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn SuspendThread(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hThread = <HTHREAD>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/thread",
+                    "SuspendThread",
+                    &[("hThread", &hThread)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::SuspendThread(machine, hThread);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::SuspendThread_pos.0,
+                    winapi::kernel32::SuspendThread_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn SwitchToFiber(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpFiber = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/fiber") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/fiber",
+                    "SwitchToFiber",
+                    &[("lpFiber", &lpFiber)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::SwitchToFiber(machine, lpFiber);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::SwitchToFiber_pos.0,
+                    winapi::kernel32::SwitchToFiber_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn SwitchToThread(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/thread",
+                    "SwitchToThread",
+                    &[],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::kernel32::SwitchToThread(machine).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::kernel32::SwitchToThread_pos.0,
+                        winapi::kernel32::SwitchToThread_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn SystemTimeToFileTime(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpSystemTime = <Option<&SYSTEMTIME>>::from_stack(mem, stack_args + 0u32);
+            let lpFileTime = <Option<&mut FILETIME>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/time") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/time",
+                    "SystemTimeToFileTime",
+                    &[("lpSystemTime", &lpSystemTime), ("lpFileTime", &lpFileTime)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::SystemTimeToFileTime(machine, lpSystemTime, lpFileTime);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::SystemTimeToFileTime_pos.0,
+                    winapi::kernel32::SystemTimeToFileTime_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn TerminateProcess(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hProcess = <HPROCESS>::from_stack(mem, stack_args + 0u32);
+            let uExitCode = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/process") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/process",
+                    "TerminateProcess",
+                    &[("hProcess", &hProcess), ("uExitCode", &uExitCode)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::TerminateProcess(machine, hProcess, uExitCode);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::TerminateProcess_pos.0,
+                    winapi::kernel32::TerminateProcess_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn TlsAlloc(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/thread",
+                    "TlsAlloc",
+                    &[],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::TlsAlloc(machine);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::TlsAlloc_pos.0,
+                    winapi::kernel32::TlsAlloc_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn TlsFree(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let dwTlsIndex = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/thread",
+                    "TlsFree",
+                    &[("dwTlsIndex", &dwTlsIndex)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::TlsFree(machine, dwTlsIndex);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::TlsFree_pos.0,
+                    winapi::kernel32::TlsFree_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn TlsGetValue(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let dwTlsIndex = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/thread",
+                    "TlsGetValue",
+                    &[("dwTlsIndex", &dwTlsIndex)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::TlsGetValue(machine, dwTlsIndex);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::TlsGetValue_pos.0,
+                    winapi::kernel32::TlsGetValue_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn TlsSetValue(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let dwTlsIndex = <u32>::from_stack(mem, stack_args + 0u32);
+            let lpTlsValue = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/thread",
+                    "TlsSetValue",
+                    &[("dwTlsIndex", &dwTlsIndex), ("lpTlsValue", &lpTlsValue)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::TlsSetValue(machine, dwTlsIndex, lpTlsValue);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::TlsSetValue_pos.0,
+                    winapi::kernel32::TlsSetValue_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn TryAcquireSRWLockExclusive(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let SRWLock = <Option<&mut SRWLOCK>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/thread",
+                    "TryAcquireSRWLockExclusive",
+                    &[("SRWLock", &SRWLock)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::TryAcquireSRWLockExclusive(machine, SRWLock);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::TryAcquireSRWLockExclusive_pos.0,
+                    winapi::kernel32::TryAcquireSRWLockExclusive_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn TryEnterCriticalSection(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpCriticalSection =
+                <Option<&mut CRITICAL_SECTION>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/thread") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/thread",
+                    "TryEnterCriticalSection",
+                    &[("lpCriticalSection", &lpCriticalSection)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::TryEnterCriticalSection(machine, lpCriticalSection);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::TryEnterCriticalSection_pos.0,
+                    winapi::kernel32::TryEnterCriticalSection_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn UnhandledExceptionFilter(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let ExceptionInfo = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/exception") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/exception",
+                    "UnhandledExceptionFilter",
+                    &[("ExceptionInfo", &ExceptionInfo)],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result =
+                    winapi::kernel32::UnhandledExceptionFilter(machine, ExceptionInfo).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::kernel32::UnhandledExceptionFilter_pos.0,
+                        winapi::kernel32::UnhandledExceptionFilter_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn UnmapViewOfFile(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpBaseAddress = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/mapping") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/mapping",
+                    "UnmapViewOfFile",
+                    &[("lpBaseAddress", &lpBaseAddress)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::UnmapViewOfFile(machine, lpBaseAddress);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::UnmapViewOfFile_pos.0,
+                    winapi::kernel32::UnmapViewOfFile_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn VirtualAlloc(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpAddress = <u32>::from_stack(mem, stack_args + 0u32);
+            let dwSize = <u32>::from_stack(mem, stack_args + 4u32);
+            let flAllocationType = <Result<MEM, u32>>::from_stack(mem, stack_args + 8u32);
+            let flProtect = <Result<PAGE, u32>>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/memory",
+                    "VirtualAlloc",
+                    &[
+                        ("lpAddress", &lpAddress),
+                        ("dwSize", &dwSize),
+                        ("flAllocationType", &flAllocationType),
+                        ("flProtect", &flProtect),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::VirtualAlloc(
+                machine,
+                lpAddress,
+                dwSize,
+                flAllocationType,
+                flProtect,
+            );
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::VirtualAlloc_pos.0,
+                    winapi::kernel32::VirtualAlloc_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn VirtualFree(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpAddress = <u32>::from_stack(mem, stack_args + 0u32);
+            let dwSize = <u32>::from_stack(mem, stack_args + 4u32);
+            let dwFreeType = <Result<MEM, u32>>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/memory",
+                    "VirtualFree",
+                    &[
+                        ("lpAddress", &lpAddress),
+                        ("dwSize", &dwSize),
+                        ("dwFreeType", &dwFreeType),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::VirtualFree(machine, lpAddress, dwSize, dwFreeType);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::VirtualFree_pos.0,
+                    winapi::kernel32::VirtualFree_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn VirtualProtect(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpAddress = <u32>::from_stack(mem, stack_args + 0u32);
+            let dwSize = <u32>::from_stack(mem, stack_args + 4u32);
+            let flNewProtect = <Result<PAGE, u32>>::from_stack(mem, stack_args + 8u32);
+            let lpflOldProtect = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/memory",
+                    "VirtualProtect",
+                    &[
+                        ("lpAddress", &lpAddress),
+                        ("dwSize", &dwSize),
+                        ("flNewProtect", &flNewProtect),
+                        ("lpflOldProtect", &lpflOldProtect),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::VirtualProtect(
+                machine,
+                lpAddress,
+                dwSize,
+                flNewProtect,
+                lpflOldProtect,
+            );
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::VirtualProtect_pos.0,
+                    winapi::kernel32::VirtualProtect_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn VirtualQuery(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpAddress = <u32>::from_stack(mem, stack_args + 0u32);
+            let lpBuffer =
+                <Option<&mut MEMORY_BASIC_INFORMATION>>::from_stack(mem, stack_args + 4u32);
+            let dwLength = <u32>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/memory",
+                    "VirtualQuery",
+                    &[
+                        ("lpAddress", &lpAddress),
+                        ("lpBuffer", &lpBuffer),
+                        ("dwLength", &dwLength),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::VirtualQuery(machine, lpAddress, lpBuffer, dwLength);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::VirtualQuery_pos.0,
+                    winapi::kernel32::VirtualQuery_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn VirtualQueryEx(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hProcess = <HANDLE<()>>::from_stack(mem, stack_args + 0u32);
+            let lpAddress = <u32>::from_stack(mem, stack_args + 4u32);
+            let lpBuffer =
+                <Option<&mut MEMORY_BASIC_INFORMATION>>::from_stack(mem, stack_args + 8u32);
+            let dwLength = <u32>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("kernel32/memory") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/memory",
+                    "VirtualQueryEx",
+                    &[
+                        ("hProcess", &hProcess),
+                        ("lpAddress", &lpAddress),
+                        ("lpBuffer", &lpBuffer),
+                        ("dwLength", &dwLength),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result =
+                winapi::kernel32::VirtualQueryEx(machine, hProcess, lpAddress, lpBuffer, dwLength);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::VirtualQueryEx_pos.0,
+                    winapi::kernel32::VirtualQueryEx_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn WaitForMultipleObjects(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let nCount = <u32>::from_stack(mem, stack_args + 0u32);
+            let lpHandles = <u32>::from_stack(mem, stack_args + 4u32);
+            let bWaitAll = <bool>::from_stack(mem, stack_args + 8u32);
+            let dwMilliseconds = <u32>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("kernel32/sync") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/sync",
+                    "WaitForMultipleObjects",
+                    &[
+                        ("nCount", &nCount),
+                        ("lpHandles", &lpHandles),
+                        ("bWaitAll", &bWaitAll),
+                        ("dwMilliseconds", &dwMilliseconds),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::kernel32::WaitForMultipleObjects(
+                    machine,
+                    nCount,
+                    lpHandles,
+                    bWaitAll,
+                    dwMilliseconds,
+                )
+                .await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::kernel32::WaitForMultipleObjects_pos.0,
+                        winapi::kernel32::WaitForMultipleObjects_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn WaitForMultipleObjectsEx(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let nCount = <u32>::from_stack(mem, stack_args + 0u32);
+            let lpHandles = <u32>::from_stack(mem, stack_args + 4u32);
+            let bWaitAll = <bool>::from_stack(mem, stack_args + 8u32);
+            let dwMilliseconds = <u32>::from_stack(mem, stack_args + 12u32);
+            let bAlertable = <bool>::from_stack(mem, stack_args + 16u32);
+            let __trace_context = if crate::trace::enabled("kernel32/sync") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/sync",
+                    "WaitForMultipleObjectsEx",
+                    &[
+                        ("nCount", &nCount),
+                        ("lpHandles", &lpHandles),
+                        ("bWaitAll", &bWaitAll),
+                        ("dwMilliseconds", &dwMilliseconds),
+                        ("bAlertable", &bAlertable),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::kernel32::WaitForMultipleObjectsEx(
+                    machine,
+                    nCount,
+                    lpHandles,
+                    bWaitAll,
+                    dwMilliseconds,
+                    bAlertable,
+                )
+                .await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::kernel32::WaitForMultipleObjectsEx_pos.0,
+                        winapi::kernel32::WaitForMultipleObjectsEx_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn WaitForSingleObject(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let hHandle = <HOBJECT>::from_stack(mem, stack_args + 0u32);
+            let dwMilliseconds = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/sync") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/sync",
+                    "WaitForSingleObject",
+                    &[("hHandle", &hHandle), ("dwMilliseconds", &dwMilliseconds)],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result =
+                    winapi::kernel32::WaitForSingleObject(machine, hHandle, dwMilliseconds).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::kernel32::WaitForSingleObject_pos.0,
+                        winapi::kernel32::WaitForSingleObject_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn WaitForSingleObjectEx(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let hHandle = <HOBJECT>::from_stack(mem, stack_args + 0u32);
+            let dwMilliseconds = <u32>::from_stack(mem, stack_args + 4u32);
+            let bAlertable = <bool>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/sync") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/sync",
+                    "WaitForSingleObjectEx",
+                    &[
+                        ("hHandle", &hHandle),
+                        ("dwMilliseconds", &dwMilliseconds),
+                        ("bAlertable", &bAlertable),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::kernel32::WaitForSingleObjectEx(
+                    machine,
+                    hHandle,
+                    dwMilliseconds,
+                    bAlertable,
+                )
+                .await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::kernel32::WaitForSingleObjectEx_pos.0,
+                        winapi::kernel32::WaitForSingleObjectEx_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn WaitNamedPipeA(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let lpNamedPipeName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+            let nTimeOut = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/pipe") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/pipe",
+                    "WaitNamedPipeA",
+                    &[
+                        ("lpNamedPipeName", &lpNamedPipeName),
+                        ("nTimeOut", &nTimeOut),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result =
+                    winapi::kernel32::WaitNamedPipeA(machine, lpNamedPipeName, nTimeOut).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::kernel32::WaitNamedPipeA_pos.0,
+                        winapi::kernel32::WaitNamedPipeA_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn WaitNamedPipeW(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let lpNamedPipeName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+            let nTimeOut = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/pipe") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/pipe",
+                    "WaitNamedPipeW",
+                    &[
+                        ("lpNamedPipeName", &lpNamedPipeName),
+                        ("nTimeOut", &nTimeOut),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result =
+                    winapi::kernel32::WaitNamedPipeW(machine, lpNamedPipeName, nTimeOut).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::kernel32::WaitNamedPipeW_pos.0,
+                        winapi::kernel32::WaitNamedPipeW_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn WideCharToMultiByte(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let CodePage = <u32>::from_stack(mem, stack_args + 0u32);
+            let dwFlags = <u32>::from_stack(mem, stack_args + 4u32);
+            let lpWideCharStr = <u32>::from_stack(mem, stack_args + 8u32);
+            let cchWideChar = <i32>::from_stack(mem, stack_args + 12u32);
+            let lpMultiByteStr = <ArrayWithSizeMut<u8>>::from_stack(mem, stack_args + 16u32);
+            let lpDefaultChar = <u32>::from_stack(mem, stack_args + 24u32);
+            let lpUsedDefaultChar = <Option<&mut u32>>::from_stack(mem, stack_args + 28u32);
+            let __trace_context = if crate::trace::enabled("kernel32/nls") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/nls",
+                    "WideCharToMultiByte",
+                    &[
+                        ("CodePage", &CodePage),
+                        ("dwFlags", &dwFlags),
+                        ("lpWideCharStr", &lpWideCharStr),
+                        ("cchWideChar", &cchWideChar),
+                        ("lpMultiByteStr", &lpMultiByteStr),
+                        ("lpDefaultChar", &lpDefaultChar),
+                        ("lpUsedDefaultChar", &lpUsedDefaultChar),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::WideCharToMultiByte(
+                machine,
+                CodePage,
+                dwFlags,
+                lpWideCharStr,
+                cchWideChar,
+                lpMultiByteStr,
+                lpDefaultChar,
+                lpUsedDefaultChar,
+            );
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::WideCharToMultiByte_pos.0,
+                    winapi::kernel32::WideCharToMultiByte_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn WriteConsoleA(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hConsoleOutput = <HFILE>::from_stack(mem, stack_args + 0u32);
+            let lpBuffer = <ArrayWithSize<u8>>::from_stack(mem, stack_args + 4u32);
+            let lpNumberOfCharsWritten = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
+            let _lpReserved = <u32>::from_stack(mem, stack_args + 16u32);
+            let __trace_context = if crate::trace::enabled("kernel32/console") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/console",
+                    "WriteConsoleA",
+                    &[
+                        ("hConsoleOutput", &hConsoleOutput),
+                        ("lpBuffer", &lpBuffer),
+                        ("lpNumberOfCharsWritten", &lpNumberOfCharsWritten),
+                        ("lpReserved", &_lpReserved),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::WriteConsoleA(
+                machine,
+                hConsoleOutput,
+                lpBuffer,
+                lpNumberOfCharsWritten,
+                _lpReserved,
+            );
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::WriteConsoleA_pos.0,
+                    winapi::kernel32::WriteConsoleA_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn WriteConsoleW(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hConsoleOutput = <HFILE>::from_stack(mem, stack_args + 0u32);
+            let lpBuffer = <ArrayWithSize<u16>>::from_stack(mem, stack_args + 4u32);
+            let lpNumberOfCharsWritten = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
+            let _lpReserved = <u32>::from_stack(mem, stack_args + 16u32);
+            let __trace_context = if crate::trace::enabled("kernel32/console") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/console",
+                    "WriteConsoleW",
+                    &[
+                        ("hConsoleOutput", &hConsoleOutput),
+                        ("lpBuffer", &lpBuffer),
+                        ("lpNumberOfCharsWritten", &lpNumberOfCharsWritten),
+                        ("lpReserved", &_lpReserved),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::WriteConsoleW(
+                machine,
+                hConsoleOutput,
+                lpBuffer,
+                lpNumberOfCharsWritten,
+                _lpReserved,
+            );
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::WriteConsoleW_pos.0,
+                    winapi::kernel32::WriteConsoleW_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn WriteFile(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
+            let lpBuffer = <ArrayWithSize<u8>>::from_stack(mem, stack_args + 4u32);
+            let lpNumberOfBytesWritten = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
+            let lpOverlapped = <u32>::from_stack(mem, stack_args + 16u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/file",
+                    "WriteFile",
+                    &[
+                        ("hFile", &hFile),
+                        ("lpBuffer", &lpBuffer),
+                        ("lpNumberOfBytesWritten", &lpNumberOfBytesWritten),
+                        ("lpOverlapped", &lpOverlapped),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::WriteFile(
+                machine,
+                hFile,
+                lpBuffer,
+                lpNumberOfBytesWritten,
+                lpOverlapped,
+            );
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::WriteFile_pos.0,
+                    winapi::kernel32::WriteFile_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn WriteFileEx(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
+            let lpBuffer = <ArrayWithSize<u8>>::from_stack(mem, stack_args + 4u32);
+            let lpOverlapped = <u32>::from_stack(mem, stack_args + 12u32);
+            let lpCompletionRoutine = <u32>::from_stack(mem, stack_args + 16u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/file",
+                    "WriteFileEx",
+                    &[
+                        ("hFile", &hFile),
+                        ("lpBuffer", &lpBuffer),
+                        ("lpOverlapped", &lpOverlapped),
+                        ("lpCompletionRoutine", &lpCompletionRoutine),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::WriteFileEx(
+                machine,
+                hFile,
+                lpBuffer,
+                lpOverlapped,
+                lpCompletionRoutine,
+            );
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::WriteFileEx_pos.0,
+                    winapi::kernel32::WriteFileEx_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn WriteProfileStringW(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpAppName = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+            let lpKeyName = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
+            let lpString = <Option<&Str16>>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/ini") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/ini",
+                    "WriteProfileStringW",
+                    &[
+                        ("lpAppName", &lpAppName),
+                        ("lpKeyName", &lpKeyName),
+                        ("lpString", &lpString),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result =
+                winapi::kernel32::WriteProfileStringW(machine, lpAppName, lpKeyName, lpString);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::WriteProfileStringW_pos.0,
+                    winapi::kernel32::WriteProfileStringW_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn _lclose(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file16") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/file16",
+                    "_lclose",
+                    &[("hFile", &hFile)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::_lclose(machine, hFile);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::_lclose_pos.0,
+                    winapi::kernel32::_lclose_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn _llseek(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
+            let lOffset = <i32>::from_stack(mem, stack_args + 4u32);
+            let iOrigin = <i32>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file16") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/file16",
+                    "_llseek",
+                    &[
+                        ("hFile", &hFile),
+                        ("lOffset", &lOffset),
+                        ("iOrigin", &iOrigin),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::_llseek(machine, hFile, lOffset, iOrigin);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::_llseek_pos.0,
+                    winapi::kernel32::_llseek_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn _lopen(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpPathName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+            let iReadWrite = <i32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file16") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/file16",
+                    "_lopen",
+                    &[("lpPathName", &lpPathName), ("iReadWrite", &iReadWrite)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::_lopen(machine, lpPathName, iReadWrite);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::_lopen_pos.0,
+                    winapi::kernel32::_lopen_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn _lread(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hFile = <HFILE>::from_stack(mem, stack_args + 0u32);
+            let lpBuffer = <ArrayWithSize<u8>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/file16") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/file16",
+                    "_lread",
+                    &[("hFile", &hFile), ("lpBuffer", &lpBuffer)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::_lread(machine, hFile, lpBuffer);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::_lread_pos.0,
+                    winapi::kernel32::_lread_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn lstrcmpA(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpString1 = <Option<&CStr>>::from_stack(mem, stack_args + 0u32);
+            let lpString2 = <Option<&CStr>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/libc") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/libc",
+                    "lstrcmpA",
+                    &[("lpString1", &lpString1), ("lpString2", &lpString2)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::lstrcmpA(machine, lpString1, lpString2);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::lstrcmpA_pos.0,
+                    winapi::kernel32::lstrcmpA_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn lstrcmpW(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpString1 = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+            let lpString2 = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/libc") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/libc",
+                    "lstrcmpW",
+                    &[("lpString1", &lpString1), ("lpString2", &lpString2)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::lstrcmpW(machine, lpString1, lpString2);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::lstrcmpW_pos.0,
+                    winapi::kernel32::lstrcmpW_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn lstrcmpiA(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpString1 = <Option<&CStr>>::from_stack(mem, stack_args + 0u32);
+            let lpString2 = <Option<&CStr>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/libc") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/libc",
+                    "lstrcmpiA",
+                    &[("lpString1", &lpString1), ("lpString2", &lpString2)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::lstrcmpiA(machine, lpString1, lpString2);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::lstrcmpiA_pos.0,
+                    winapi::kernel32::lstrcmpiA_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn lstrcmpiW(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpString1 = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+            let lpString2 = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/libc") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/libc",
+                    "lstrcmpiW",
+                    &[("lpString1", &lpString1), ("lpString2", &lpString2)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::lstrcmpiW(machine, lpString1, lpString2);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::lstrcmpiW_pos.0,
+                    winapi::kernel32::lstrcmpiW_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn lstrcpyA(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpString1 = <u32>::from_stack(mem, stack_args + 0u32);
+            let lpString2 = <Option<&CStr>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/libc") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/libc",
+                    "lstrcpyA",
+                    &[("lpString1", &lpString1), ("lpString2", &lpString2)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::lstrcpyA(machine, lpString1, lpString2);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::lstrcpyA_pos.0,
+                    winapi::kernel32::lstrcpyA_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn lstrcpyW(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpString1 = <u32>::from_stack(mem, stack_args + 0u32);
+            let lpString2 = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/libc") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/libc",
+                    "lstrcpyW",
+                    &[("lpString1", &lpString1), ("lpString2", &lpString2)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::lstrcpyW(machine, lpString1, lpString2);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::lstrcpyW_pos.0,
+                    winapi::kernel32::lstrcpyW_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn lstrlenA(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpString = <Option<&CStr>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/libc") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/libc",
+                    "lstrlenA",
+                    &[("lpString", &lpString)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::lstrlenA(machine, lpString);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::lstrlenA_pos.0,
+                    winapi::kernel32::lstrlenA_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn lstrlenW(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpString = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/libc") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/libc",
+                    "lstrlenW",
+                    &[("lpString", &lpString)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::kernel32::lstrlenW(machine, lpString);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::kernel32::lstrlenW_pos.0,
+                    winapi::kernel32::lstrlenW_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn retrowin32_exit(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let __trace_context = if crate::trace::enabled("kernel32/init") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/init",
+                    "retrowin32_exit",
+                    &[],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::kernel32::retrowin32_exit(machine).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::kernel32::retrowin32_exit_pos.0,
+                        winapi::kernel32::retrowin32_exit_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn retrowin32_fiber_main(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let start = <u32>::from_stack(mem, stack_args + 0u32);
+            let param = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("kernel32/fiber") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/fiber",
+                    "retrowin32_fiber_main",
+                    &[("start", &start), ("param", &param)],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::kernel32::retrowin32_fiber_main(machine, start, param).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::kernel32::retrowin32_fiber_main_pos.0,
+                        winapi::kernel32::retrowin32_fiber_main_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn retrowin32_main(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let entry_point = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("kernel32/init") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/init",
+                    "retrowin32_main",
+                    &[("entry_point", &entry_point)],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::kernel32::retrowin32_main(machine, entry_point).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::kernel32::retrowin32_main_pos.0,
+                        winapi::kernel32::retrowin32_main_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn retrowin32_start(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let __trace_context = if crate::trace::enabled("kernel32/init") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/init",
+                    "retrowin32_start",
+                    &[],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::kernel32::retrowin32_start(machine).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::kernel32::retrowin32_start_pos.0,
+                        winapi::kernel32::retrowin32_start_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn retrowin32_thread_exit(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let __trace_context = if crate::trace::enabled("kernel32/init") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/init",
+                    "retrowin32_thread_exit",
+                    &[],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::kernel32::retrowin32_thread_exit(machine).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::kernel32::retrowin32_thread_exit_pos.0,
+                        winapi::kernel32::retrowin32_thread_exit_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn retrowin32_thread_start(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let __trace_context = if crate::trace::enabled("kernel32/init") {
+                Some(crate::trace::trace_begin(
+                    "kernel32/init",
+                    "retrowin32_thread_start",
+                    &[],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::kernel32::retrowin32_thread_start(machine).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::kernel32::retrowin32_thread_start_pos.0,
+                        winapi::kernel32::retrowin32_thread_start_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+    }
+    const SHIMS: [Shim; 313usize] = [
+        Shim {
+            name: "AcquireSRWLockExclusive",
+            func: Handler::Sync(wrappers::AcquireSRWLockExclusive),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "AcquireSRWLockShared",
+            func: Handler::Sync(wrappers::AcquireSRWLockShared),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "AddVectoredExceptionHandler",
+            func: Handler::Sync(wrappers::AddVectoredExceptionHandler),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "AllocConsole",
+            func: Handler::Sync(wrappers::AllocConsole),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "AttachConsole",
+            func: Handler::Sync(wrappers::AttachConsole),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "CloseHandle",
+            func: Handler::Sync(wrappers::CloseHandle),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "CompareStringA",
+            func: Handler::Sync(wrappers::CompareStringA),
+            stack_consumed: 24u32,
+        },
+        Shim {
+            name: "CompareStringW",
+            func: Handler::Sync(wrappers::CompareStringW),
+            stack_consumed: 24u32,
+        },
+        Shim {
+            name: "ConnectNamedPipe",
+            func: Handler::Async(wrappers::ConnectNamedPipe),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "ConvertFiberToThread",
+            func: Handler::Sync(wrappers::ConvertFiberToThread),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "ConvertThreadToFiber",
+            func: Handler::Sync(wrappers::ConvertThreadToFiber),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "ConvertThreadToFiberEx",
+            func: Handler::Sync(wrappers::ConvertThreadToFiberEx),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "CopyFileA",
+            func: Handler::Sync(wrappers::CopyFileA),
+            stack_consumed: 12u32,
+        },
+        Shim {
+            name: "CopyFileW",
+            func: Handler::Sync(wrappers::CopyFileW),
+            stack_consumed: 12u32,
+        },
+        Shim {
+            name: "CreateDirectoryA",
+            func: Handler::Sync(wrappers::CreateDirectoryA),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "CreateEventA",
+            func: Handler::Sync(wrappers::CreateEventA),
+            stack_consumed: 16u32,
+        },
+        Shim {
+            name: "CreateEventW",
+            func: Handler::Sync(wrappers::CreateEventW),
+            stack_consumed: 16u32,
+        },
+        Shim {
+            name: "CreateFiber",
+            func: Handler::Sync(wrappers::CreateFiber),
+            stack_consumed: 12u32,
+        },
+        Shim {
+            name: "CreateFiberEx",
+            func: Handler::Sync(wrappers::CreateFiberEx),
+            stack_consumed: 20u32,
+        },
+        Shim {
+            name: "CreateFileA",
+            func: Handler::Sync(wrappers::CreateFileA),
+            stack_consumed: 28u32,
+        },
+        Shim {
+            name: "CreateFileMappingA",
+            func: Handler::Sync(wrappers::CreateFileMappingA),
+            stack_consumed: 24u32,
+        },
+        Shim {
+            name: "CreateFileMappingW",
+            func: Handler::Sync(wrappers::CreateFileMappingW),
+            stack_consumed: 24u32,
+        },
+        Shim {
+            name: "CreateFileW",
+            func: Handler::Sync(wrappers::CreateFileW),
+            stack_consumed: 28u32,
+        },
+        Shim {
+            name: "CreateMutexA",
+            func: Handler::Sync(wrappers::CreateMutexA),
+            stack_consumed: 12u32,
+        },
+        Shim {
+            name: "CreateMutexW",
+            func: Handler::Sync(wrappers::CreateMutexW),
+            stack_consumed: 12u32,
+        },
+        Shim {
+            name: "CreateNamedPipeA",
+            func: Handler::Sync(wrappers::CreateNamedPipeA),
+            stack_consumed: 32u32,
+        },
+        Shim {
+            name: "CreateNamedPipeW",
+            func: Handler::Sync(wrappers::CreateNamedPipeW),
+            stack_consumed: 32u32,
+        },
+        Shim {
+            name: "CreateProcessA",
+            func: Handler::Sync(wrappers::CreateProcessA),
+            stack_consumed: 40u32,
+        },
+        Shim {
+            name: "CreateProcessW",
+            func: Handler::Sync(wrappers::CreateProcessW),
+            stack_consumed: 40u32,
+        },
+        Shim {
+            name: "CreateSemaphoreA",
+            func: Handler::Sync(wrappers::CreateSemaphoreA),
+            stack_consumed: 16u32,
+        },
+        Shim {
+            name: "CreateSemaphoreW",
+            func: Handler::Sync(wrappers::CreateSemaphoreW),
+            stack_consumed: 16u32,
+        },
+        Shim {
+            name: "CreateThread",
+            func: Handler::Async(wrappers::CreateThread),
+            stack_consumed: 24u32,
+        },
+        Shim {
+            name: "DebugBreak",
+            func: Handler::Sync(wrappers::DebugBreak),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "DeleteCriticalSection",
+            func: Handler::Sync(wrappers::DeleteCriticalSection),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "DeleteFiber",
+            func: Handler::Sync(wrappers::DeleteFiber),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "DeleteFileA",
+            func: Handler::Sync(wrappers::DeleteFileA),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "DeleteFileW",
+            func: Handler::Sync(wrappers::DeleteFileW),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "DisableThreadLibraryCalls",
+            func: Handler::Sync(wrappers::DisableThreadLibraryCalls),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "DisconnectNamedPipe",
+            func: Handler::Sync(wrappers::DisconnectNamedPipe),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "DosDateTimeToFileTime",
+            func: Handler::Sync(wrappers::DosDateTimeToFileTime),
+            stack_consumed: 12u32,
+        },
+        Shim {
+            name: "DuplicateHandle",
+            func: Handler::Sync(wrappers::DuplicateHandle),
+            stack_consumed: 28u32,
+        },
+        Shim {
+            name: "EnterCriticalSection",
+            func: Handler::Async(wrappers::EnterCriticalSection),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "ExitProcess",
+            func: Handler::Async(wrappers::ExitProcess),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "ExitThread",
+            func: Handler::Async(wrappers::ExitThread),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "FileTimeToDosDateTime",
+            func: Handler::Sync(wrappers::FileTimeToDosDateTime),
+            stack_consumed: 12u32,
+        },
+        Shim {
+            name: "FileTimeToLocalFileTime",
+            func: Handler::Sync(wrappers::FileTimeToLocalFileTime),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "FileTimeToSystemTime",
+            func: Handler::Sync(wrappers::FileTimeToSystemTime),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "FindClose",
+            func: Handler::Sync(wrappers::FindClose),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "FindFirstFileA",
+            func: Handler::Sync(wrappers::FindFirstFileA),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "FindNextFileA",
+            func: Handler::Sync(wrappers::FindNextFileA),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "FindResourceA",
+            func: Handler::Sync(wrappers::FindResourceA),
+            stack_consumed: 12u32,
+        },
+        Shim {
+            name: "FindResourceW",
+            func: Handler::Sync(wrappers::FindResourceW),
+            stack_consumed: 12u32,
+        },
+        Shim {
+            name: "FlushConsoleInputBuffer",
+            func: Handler::Sync(wrappers::FlushConsoleInputBuffer),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "FlushFileBuffers",
+            func: Handler::Sync(wrappers::FlushFileBuffers),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "FlushInstructionCache",
+            func: Handler::Sync(wrappers::FlushInstructionCache),
+            stack_consumed: 12u32,
+        },
+        Shim {
+            name: "FlushViewOfFile",
+            func: Handler::Sync(wrappers::FlushViewOfFile),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "FormatMessageA",
+            func: Handler::Sync(wrappers::FormatMessageA),
+            stack_consumed: 28u32,
+        },
+        Shim {
+            name: "FormatMessageW",
+            func: Handler::Sync(wrappers::FormatMessageW),
+            stack_consumed: 28u32,
+        },
+        Shim {
+            name: "FreeConsole",
+            func: Handler::Sync(wrappers::FreeConsole),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "FreeEnvironmentStringsA",
+            func: Handler::Sync(wrappers::FreeEnvironmentStringsA),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "FreeEnvironmentStringsW",
+            func: Handler::Sync(wrappers::FreeEnvironmentStringsW),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "FreeLibrary",
+            func: Handler::Async(wrappers::FreeLibrary),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "GetACP",
+            func: Handler::Sync(wrappers::GetACP),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "GetCPInfo",
+            func: Handler::Sync(wrappers::GetCPInfo),
+            stack_consumed: 8u32,
+        },
+        Shim {
             name: "GetCommandLineA",
             func: Handler::Sync(wrappers::GetCommandLineA),
+            stack_consumed: 0u32,
         },
         Shim {
             name: "GetCommandLineW",
             func: Handler::Sync(wrappers::GetCommandLineW),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "GetComputerNameA",
+            func: Handler::Sync(wrappers::GetComputerNameA),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "GetComputerNameW",
+            func: Handler::Sync(wrappers::GetComputerNameW),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "GetConsoleCP",
+            func: Handler::Sync(wrappers::GetConsoleCP),
+            stack_consumed: 0u32,
         },
         Shim {
             name: "GetConsoleMode",
             func: Handler::Sync(wrappers::GetConsoleMode),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "GetConsoleOutputCP",
+            func: Handler::Sync(wrappers::GetConsoleOutputCP),
+            stack_consumed: 0u32,
         },
         Shim {
             name: "GetConsoleScreenBufferInfo",
             func: Handler::Sync(wrappers::GetConsoleScreenBufferInfo),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "GetConsoleTitleA",
+            func: Handler::Sync(wrappers::GetConsoleTitleA),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "GetConsoleTitleW",
+            func: Handler::Sync(wrappers::GetConsoleTitleW),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "GetConsoleWindow",
+            func: Handler::Sync(wrappers::GetConsoleWindow),
+            stack_consumed: 0u32,
         },
         Shim {
             name: "GetCurrentDirectoryA",
             func: Handler::Sync(wrappers::GetCurrentDirectoryA),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "GetCurrentDirectoryW",
+            func: Handler::Sync(wrappers::GetCurrentDirectoryW),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "GetCurrentProcess",
             func: Handler::Sync(wrappers::GetCurrentProcess),
+            stack_consumed: 0u32,
         },
         Shim {
             name: "GetCurrentProcessId",
             func: Handler::Sync(wrappers::GetCurrentProcessId),
+            stack_consumed: 0u32,
         },
         Shim {
             name: "GetCurrentThread",
             func: Handler::Sync(wrappers::GetCurrentThread),
+            stack_consumed: 0u32,
         },
         Shim {
             name: "GetCurrentThreadId",
             func: Handler::Sync(wrappers::GetCurrentThreadId),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "GetDiskFreeSpaceA",
+            func: Handler::Sync(wrappers::GetDiskFreeSpaceA),
+            stack_consumed: 20u32,
+        },
+        Shim {
+            name: "GetDiskFreeSpaceExA",
+            func: Handler::Sync(wrappers::GetDiskFreeSpaceExA),
+            stack_consumed: 16u32,
+        },
+        Shim {
+            name: "GetDiskFreeSpaceExW",
+            func: Handler::Sync(wrappers::GetDiskFreeSpaceExW),
+            stack_consumed: 16u32,
+        },
+        Shim {
+            name: "GetDiskFreeSpaceW",
+            func: Handler::Sync(wrappers::GetDiskFreeSpaceW),
+            stack_consumed: 20u32,
+        },
+        Shim {
+            name: "GetDriveTypeA",
+            func: Handler::Sync(wrappers::GetDriveTypeA),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "GetDriveTypeW",
+            func: Handler::Sync(wrappers::GetDriveTypeW),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "GetEnvironmentStrings",
             func: Handler::Sync(wrappers::GetEnvironmentStrings),
+            stack_consumed: 0u32,
         },
         Shim {
             name: "GetEnvironmentStringsW",
             func: Handler::Sync(wrappers::GetEnvironmentStringsW),
+            stack_consumed: 0u32,
         },
         Shim {
             name: "GetEnvironmentVariableA",
             func: Handler::Sync(wrappers::GetEnvironmentVariableA),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "GetEnvironmentVariableW",
             func: Handler::Sync(wrappers::GetEnvironmentVariableW),
+            stack_consumed: 12u32,
+        },
+        Shim {
+            name: "GetExitCodeProcess",
+            func: Handler::Sync(wrappers::GetExitCodeProcess),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "GetExitCodeThread",
+            func: Handler::Sync(wrappers::GetExitCodeThread),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "GetFileAttributesA",
             func: Handler::Sync(wrappers::GetFileAttributesA),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "GetFileAttributesExA",
+            func: Handler::Sync(wrappers::GetFileAttributesExA),
+            stack_consumed: 12u32,
+        },
+        Shim {
+            name: "GetFileAttributesExW",
+            func: Handler::Sync(wrappers::GetFileAttributesExW),
+            stack_consumed: 12u32,
+        },
+        Shim {
+            name: "GetFileAttributesW",
+            func: Handler::Sync(wrappers::GetFileAttributesW),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "GetFileInformationByHandle",
             func: Handler::Sync(wrappers::GetFileInformationByHandle),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "GetFileSize",
             func: Handler::Sync(wrappers::GetFileSize),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "GetFileSizeEx",
+            func: Handler::Sync(wrappers::GetFileSizeEx),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "GetFileTime",
             func: Handler::Sync(wrappers::GetFileTime),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "GetFileType",
             func: Handler::Sync(wrappers::GetFileType),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "GetFullPathNameA",
             func: Handler::Sync(wrappers::GetFullPathNameA),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "GetFullPathNameW",
             func: Handler::Sync(wrappers::GetFullPathNameW),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "GetLastError",
             func: Handler::Sync(wrappers::GetLastError),
+            stack_consumed: 0u32,
         },
         Shim {
             name: "GetLocalTime",
             func: Handler::Sync(wrappers::GetLocalTime),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "GetLogicalDrives",
+            func: Handler::Sync(wrappers::GetLogicalDrives),
+            stack_consumed: 0u32,
         },
         Shim {
             name: "GetModuleFileNameA",
             func: Handler::Sync(wrappers::GetModuleFileNameA),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "GetModuleFileNameW",
             func: Handler::Sync(wrappers::GetModuleFileNameW),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "GetModuleHandleA",
             func: Handler::Sync(wrappers::GetModuleHandleA),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "GetModuleHandleExA",
+            func: Handler::Sync(wrappers::GetModuleHandleExA),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "GetModuleHandleExW",
             func: Handler::Sync(wrappers::GetModuleHandleExW),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "GetModuleHandleW",
             func: Handler::Sync(wrappers::GetModuleHandleW),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "GetNativeSystemInfo",
+            func: Handler::Sync(wrappers::GetNativeSystemInfo),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "GetNumberOfConsoleInputEvents",
+            func: Handler::Sync(wrappers::GetNumberOfConsoleInputEvents),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "GetOEMCP",
             func: Handler::Sync(wrappers::GetOEMCP),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "GetOverlappedResult",
+            func: Handler::Async(wrappers::GetOverlappedResult),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "GetPrivateProfileIntW",
             func: Handler::Sync(wrappers::GetPrivateProfileIntW),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "GetPrivateProfileStringW",
             func: Handler::Sync(wrappers::GetPrivateProfileStringW),
+            stack_consumed: 24u32,
         },
         Shim {
             name: "GetProcAddress",
             func: Handler::Sync(wrappers::GetProcAddress),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "GetProcessHeap",
             func: Handler::Sync(wrappers::GetProcessHeap),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "GetProcessHeaps",
+            func: Handler::Sync(wrappers::GetProcessHeaps),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "GetProcessId",
+            func: Handler::Sync(wrappers::GetProcessId),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "GetProfileIntW",
             func: Handler::Sync(wrappers::GetProfileIntW),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "GetProfileStringW",
             func: Handler::Sync(wrappers::GetProfileStringW),
+            stack_consumed: 20u32,
         },
         Shim {
             name: "GetStartupInfoA",
             func: Handler::Sync(wrappers::GetStartupInfoA),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "GetStartupInfoW",
             func: Handler::Sync(wrappers::GetStartupInfoW),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "GetStdHandle",
             func: Handler::Sync(wrappers::GetStdHandle),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "GetStringTypeA",
             func: Handler::Sync(wrappers::GetStringTypeA),
+            stack_consumed: 20u32,
         },
         Shim {
             name: "GetStringTypeW",
             func: Handler::Sync(wrappers::GetStringTypeW),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "GetSystemDirectoryA",
             func: Handler::Sync(wrappers::GetSystemDirectoryA),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "GetSystemInfo",
+            func: Handler::Sync(wrappers::GetSystemInfo),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "GetSystemTime",
             func: Handler::Sync(wrappers::GetSystemTime),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "GetSystemTimeAsFileTime",
             func: Handler::Sync(wrappers::GetSystemTimeAsFileTime),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "GetTempFileNameA",
+            func: Handler::Sync(wrappers::GetTempFileNameA),
+            stack_consumed: 16u32,
+        },
+        Shim {
+            name: "GetTempFileNameW",
+            func: Handler::Sync(wrappers::GetTempFileNameW),
+            stack_consumed: 16u32,
+        },
+        Shim {
+            name: "GetTempPathA",
+            func: Handler::Sync(wrappers::GetTempPathA),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "GetTempPathW",
+            func: Handler::Sync(wrappers::GetTempPathW),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "GetThreadContext",
+            func: Handler::Sync(wrappers::GetThreadContext),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "GetThreadPriority",
+            func: Handler::Sync(wrappers::GetThreadPriority),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "GetTickCount",
             func: Handler::Sync(wrappers::GetTickCount),
+            stack_consumed: 0u32,
         },
         Shim {
             name: "GetTimeZoneInformation",
             func: Handler::Sync(wrappers::GetTimeZoneInformation),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "GetVersion",
             func: Handler::Sync(wrappers::GetVersion),
+            stack_consumed: 0u32,
         },
         Shim {
             name: "GetVersionExA",
             func: Handler::Sync(wrappers::GetVersionExA),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "GetVolumeInformationA",
+            func: Handler::Sync(wrappers::GetVolumeInformationA),
+            stack_consumed: 32u32,
+        },
+        Shim {
+            name: "GetVolumeInformationW",
+            func: Handler::Sync(wrappers::GetVolumeInformationW),
+            stack_consumed: 32u32,
         },
         Shim {
             name: "GetWindowsDirectoryA",
             func: Handler::Sync(wrappers::GetWindowsDirectoryA),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "GlobalAlloc",
             func: Handler::Sync(wrappers::GlobalAlloc),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "GlobalFlags",
             func: Handler::Sync(wrappers::GlobalFlags),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "GlobalFree",
             func: Handler::Sync(wrappers::GlobalFree),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "GlobalHandle",
+            func: Handler::Sync(wrappers::GlobalHandle),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "GlobalLock",
+            func: Handler::Sync(wrappers::GlobalLock),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "GlobalReAlloc",
             func: Handler::Sync(wrappers::GlobalReAlloc),
+            stack_consumed: 12u32,
+        },
+        Shim {
+            name: "GlobalSize",
+            func: Handler::Sync(wrappers::GlobalSize),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "GlobalUnlock",
+            func: Handler::Sync(wrappers::GlobalUnlock),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "HeapAlloc",
             func: Handler::Sync(wrappers::HeapAlloc),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "HeapCreate",
             func: Handler::Sync(wrappers::HeapCreate),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "HeapDestroy",
             func: Handler::Sync(wrappers::HeapDestroy),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "HeapFree",
             func: Handler::Sync(wrappers::HeapFree),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "HeapReAlloc",
             func: Handler::Sync(wrappers::HeapReAlloc),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "HeapSetInformation",
             func: Handler::Sync(wrappers::HeapSetInformation),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "HeapSize",
             func: Handler::Sync(wrappers::HeapSize),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "HeapValidate",
             func: Handler::Sync(wrappers::HeapValidate),
+            stack_consumed: 12u32,
+        },
+        Shim {
+            name: "HeapWalk",
+            func: Handler::Sync(wrappers::HeapWalk),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "InitOnceBeginInitialize",
             func: Handler::Sync(wrappers::InitOnceBeginInitialize),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "InitOnceComplete",
             func: Handler::Sync(wrappers::InitOnceComplete),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "InitializeCriticalSection",
             func: Handler::Sync(wrappers::InitializeCriticalSection),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "InitializeCriticalSectionAndSpinCount",
             func: Handler::Sync(wrappers::InitializeCriticalSectionAndSpinCount),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "InitializeCriticalSectionEx",
             func: Handler::Sync(wrappers::InitializeCriticalSectionEx),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "InitializeSListHead",
             func: Handler::Sync(wrappers::InitializeSListHead),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "InterlockedCompareExchange",
+            func: Handler::Sync(wrappers::InterlockedCompareExchange),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "InterlockedDecrement",
             func: Handler::Sync(wrappers::InterlockedDecrement),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "InterlockedExchange",
+            func: Handler::Sync(wrappers::InterlockedExchange),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "InterlockedExchangeAdd",
+            func: Handler::Sync(wrappers::InterlockedExchangeAdd),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "InterlockedFlushSList",
+            func: Handler::Sync(wrappers::InterlockedFlushSList),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "InterlockedIncrement",
             func: Handler::Sync(wrappers::InterlockedIncrement),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "InterlockedPopEntrySList",
+            func: Handler::Sync(wrappers::InterlockedPopEntrySList),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "InterlockedPushEntrySList",
+            func: Handler::Sync(wrappers::InterlockedPushEntrySList),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "IsBadCodePtr",
             func: Handler::Sync(wrappers::IsBadCodePtr),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "IsBadReadPtr",
             func: Handler::Sync(wrappers::IsBadReadPtr),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "IsBadStringPtrA",
+            func: Handler::Sync(wrappers::IsBadStringPtrA),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "IsBadWritePtr",
             func: Handler::Sync(wrappers::IsBadWritePtr),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "IsDBCSLeadByte",
             func: Handler::Sync(wrappers::IsDBCSLeadByte),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "IsDBCSLeadByteEx",
             func: Handler::Sync(wrappers::IsDBCSLeadByteEx),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "IsDebuggerPresent",
             func: Handler::Sync(wrappers::IsDebuggerPresent),
+            stack_consumed: 0u32,
         },
         Shim {
             name: "IsProcessorFeaturePresent",
             func: Handler::Sync(wrappers::IsProcessorFeaturePresent),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "IsThreadAFiber",
+            func: Handler::Sync(wrappers::IsThreadAFiber),
+            stack_consumed: 0u32,
         },
         Shim {
             name: "IsValidCodePage",
             func: Handler::Sync(wrappers::IsValidCodePage),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "LCMapStringA",
             func: Handler::Sync(wrappers::LCMapStringA),
+            stack_consumed: 24u32,
         },
         Shim {
             name: "LCMapStringW",
             func: Handler::Sync(wrappers::LCMapStringW),
+            stack_consumed: 24u32,
         },
         Shim {
             name: "LeaveCriticalSection",
             func: Handler::Sync(wrappers::LeaveCriticalSection),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "LoadLibraryA",
-            func: Handler::Sync(wrappers::LoadLibraryA),
+            func: Handler::Async(wrappers::LoadLibraryA),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "LoadLibraryExA",
+            func: Handler::Async(wrappers::LoadLibraryExA),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "LoadLibraryExW",
-            func: Handler::Sync(wrappers::LoadLibraryExW),
+            func: Handler::Async(wrappers::LoadLibraryExW),
+            stack_consumed: 12u32,
+        },
+        Shim {
+            name: "LoadLibraryW",
+            func: Handler::Async(wrappers::LoadLibraryW),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "LoadResource",
             func: Handler::Sync(wrappers::LoadResource),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "LocalAlloc",
             func: Handler::Sync(wrappers::LocalAlloc),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "LocalFileTimeToFileTime",
+            func: Handler::Sync(wrappers::LocalFileTimeToFileTime),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "LocalFlags",
+            func: Handler::Sync(wrappers::LocalFlags),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "LocalFree",
             func: Handler::Sync(wrappers::LocalFree),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "LocalHandle",
+            func: Handler::Sync(wrappers::LocalHandle),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "LocalLock",
+            func: Handler::Sync(wrappers::LocalLock),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "LocalReAlloc",
+            func: Handler::Sync(wrappers::LocalReAlloc),
+            stack_consumed: 12u32,
+        },
+        Shim {
+            name: "LocalSize",
+            func: Handler::Sync(wrappers::LocalSize),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "LocalUnlock",
+            func: Handler::Sync(wrappers::LocalUnlock),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "LockResource",
             func: Handler::Sync(wrappers::LockResource),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "MapViewOfFile",
+            func: Handler::Sync(wrappers::MapViewOfFile),
+            stack_consumed: 20u32,
+        },
+        Shim {
+            name: "MoveFileA",
+            func: Handler::Sync(wrappers::MoveFileA),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "MoveFileExA",
+            func: Handler::Sync(wrappers::MoveFileExA),
+            stack_consumed: 12u32,
+        },
+        Shim {
+            name: "MoveFileExW",
+            func: Handler::Sync(wrappers::MoveFileExW),
+            stack_consumed: 12u32,
+        },
+        Shim {
+            name: "MoveFileW",
+            func: Handler::Sync(wrappers::MoveFileW),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "MulDiv",
             func: Handler::Sync(wrappers::MulDiv),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "MultiByteToWideChar",
             func: Handler::Sync(wrappers::MultiByteToWideChar),
+            stack_consumed: 24u32,
         },
         Shim {
             name: "NtCurrentTeb",
             func: Handler::Sync(wrappers::NtCurrentTeb),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "OpenFileMappingA",
+            func: Handler::Sync(wrappers::OpenFileMappingA),
+            stack_consumed: 12u32,
+        },
+        Shim {
+            name: "OpenFileMappingW",
+            func: Handler::Sync(wrappers::OpenFileMappingW),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "OutputDebugStringA",
             func: Handler::Sync(wrappers::OutputDebugStringA),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "OutputDebugStringW",
+            func: Handler::Sync(wrappers::OutputDebugStringW),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "PeekNamedPipe",
+            func: Handler::Sync(wrappers::PeekNamedPipe),
+            stack_consumed: 24u32,
+        },
+        Shim {
+            name: "PulseEvent",
+            func: Handler::Sync(wrappers::PulseEvent),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "QueryDepthSList",
+            func: Handler::Sync(wrappers::QueryDepthSList),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "QueryPerformanceCounter",
             func: Handler::Sync(wrappers::QueryPerformanceCounter),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "QueryPerformanceFrequency",
             func: Handler::Sync(wrappers::QueryPerformanceFrequency),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "QueueUserAPC",
+            func: Handler::Sync(wrappers::QueueUserAPC),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "RaiseException",
-            func: Handler::Sync(wrappers::RaiseException),
+            func: Handler::Async(wrappers::RaiseException),
+            stack_consumed: 16u32,
+        },
+        Shim {
+            name: "ReadConsoleA",
+            func: Handler::Async(wrappers::ReadConsoleA),
+            stack_consumed: 20u32,
+        },
+        Shim {
+            name: "ReadConsoleW",
+            func: Handler::Async(wrappers::ReadConsoleW),
+            stack_consumed: 20u32,
         },
         Shim {
             name: "ReadFile",
-            func: Handler::Sync(wrappers::ReadFile),
+            func: Handler::Async(wrappers::ReadFile),
+            stack_consumed: 20u32,
+        },
+        Shim {
+            name: "ReadFileEx",
+            func: Handler::Sync(wrappers::ReadFileEx),
+            stack_consumed: 20u32,
+        },
+        Shim {
+            name: "ReleaseMutex",
+            func: Handler::Sync(wrappers::ReleaseMutex),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "ReleaseSRWLockExclusive",
             func: Handler::Sync(wrappers::ReleaseSRWLockExclusive),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "ReleaseSRWLockShared",
             func: Handler::Sync(wrappers::ReleaseSRWLockShared),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "ReleaseSemaphore",
+            func: Handler::Sync(wrappers::ReleaseSemaphore),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "RemoveDirectoryA",
             func: Handler::Sync(wrappers::RemoveDirectoryA),
+            stack_consumed: 4u32,
         },
         Shim {
-            name: "ResumeThread",
-            func: Handler::Sync(wrappers::ResumeThread),
+            name: "ResetEvent",
+            func: Handler::Sync(wrappers::ResetEvent),
+            stack_consumed: 4u32,
         },
         Shim {
-            name: "RtlUnwind",
-            func: Handler::Sync(wrappers::RtlUnwind),
+            name: "ResumeThread",
+            func: Handler::Sync(wrappers::ResumeThread),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "SetConsoleCtrlHandler",
             func: Handler::Sync(wrappers::SetConsoleCtrlHandler),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "SetConsoleMode",
+            func: Handler::Sync(wrappers::SetConsoleMode),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "SetConsoleTitleA",
+            func: Handler::Sync(wrappers::SetConsoleTitleA),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "SetConsoleTitleW",
+            func: Handler::Sync(wrappers::SetConsoleTitleW),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "SetCurrentDirectoryA",
+            func: Handler::Sync(wrappers::SetCurrentDirectoryA),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "SetCurrentDirectoryW",
+            func: Handler::Sync(wrappers::SetCurrentDirectoryW),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "SetEndOfFile",
             func: Handler::Sync(wrappers::SetEndOfFile),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "SetEnvironmentVariableA",
             func: Handler::Sync(wrappers::SetEnvironmentVariableA),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "SetEvent",
             func: Handler::Sync(wrappers::SetEvent),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "SetFileAttributesA",
             func: Handler::Sync(wrappers::SetFileAttributesA),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "SetFileAttributesW",
+            func: Handler::Sync(wrappers::SetFileAttributesW),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "SetFilePointer",
             func: Handler::Sync(wrappers::SetFilePointer),
+            stack_consumed: 16u32,
+        },
+        Shim {
+            name: "SetFilePointerEx",
+            func: Handler::Sync(wrappers::SetFilePointerEx),
+            stack_consumed: 20u32,
         },
         Shim {
             name: "SetFileTime",
             func: Handler::Sync(wrappers::SetFileTime),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "SetHandleCount",
             func: Handler::Sync(wrappers::SetHandleCount),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "SetLastError",
             func: Handler::Sync(wrappers::SetLastError),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "SetLocalTime",
+            func: Handler::Sync(wrappers::SetLocalTime),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "SetNamedPipeHandleState",
+            func: Handler::Sync(wrappers::SetNamedPipeHandleState),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "SetPriorityClass",
             func: Handler::Sync(wrappers::SetPriorityClass),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "SetStdHandle",
             func: Handler::Sync(wrappers::SetStdHandle),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "SetSystemTime",
+            func: Handler::Sync(wrappers::SetSystemTime),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "SetThreadContext",
+            func: Handler::Sync(wrappers::SetThreadContext),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "SetThreadDescription",
             func: Handler::Sync(wrappers::SetThreadDescription),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "SetThreadPriority",
             func: Handler::Sync(wrappers::SetThreadPriority),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "SetThreadStackGuarantee",
             func: Handler::Sync(wrappers::SetThreadStackGuarantee),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "SetUnhandledExceptionFilter",
             func: Handler::Sync(wrappers::SetUnhandledExceptionFilter),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "SizeofResource",
             func: Handler::Sync(wrappers::SizeofResource),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "Sleep",
             func: Handler::Async(wrappers::Sleep),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "SleepEx",
+            func: Handler::Async(wrappers::SleepEx),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "SuspendThread",
+            func: Handler::Sync(wrappers::SuspendThread),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "SwitchToFiber",
+            func: Handler::Sync(wrappers::SwitchToFiber),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "SwitchToThread",
+            func: Handler::Async(wrappers::SwitchToThread),
+            stack_consumed: 0u32,
         },
         Shim {
             name: "SystemTimeToFileTime",
             func: Handler::Sync(wrappers::SystemTimeToFileTime),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "TerminateProcess",
             func: Handler::Sync(wrappers::TerminateProcess),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "TlsAlloc",
             func: Handler::Sync(wrappers::TlsAlloc),
+            stack_consumed: 0u32,
         },
         Shim {
             name: "TlsFree",
             func: Handler::Sync(wrappers::TlsFree),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "TlsGetValue",
             func: Handler::Sync(wrappers::TlsGetValue),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "TlsSetValue",
             func: Handler::Sync(wrappers::TlsSetValue),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "TryAcquireSRWLockExclusive",
             func: Handler::Sync(wrappers::TryAcquireSRWLockExclusive),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "TryEnterCriticalSection",
+            func: Handler::Sync(wrappers::TryEnterCriticalSection),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "UnhandledExceptionFilter",
-            func: Handler::Sync(wrappers::UnhandledExceptionFilter),
+            func: Handler::Async(wrappers::UnhandledExceptionFilter),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "UnmapViewOfFile",
+            func: Handler::Sync(wrappers::UnmapViewOfFile),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "VirtualAlloc",
             func: Handler::Sync(wrappers::VirtualAlloc),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "VirtualFree",
             func: Handler::Sync(wrappers::VirtualFree),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "VirtualProtect",
             func: Handler::Sync(wrappers::VirtualProtect),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "VirtualQuery",
             func: Handler::Sync(wrappers::VirtualQuery),
+            stack_consumed: 12u32,
+        },
+        Shim {
+            name: "VirtualQueryEx",
+            func: Handler::Sync(wrappers::VirtualQueryEx),
+            stack_consumed: 16u32,
+        },
+        Shim {
+            name: "WaitForMultipleObjects",
+            func: Handler::Async(wrappers::WaitForMultipleObjects),
+            stack_consumed: 16u32,
+        },
+        Shim {
+            name: "WaitForMultipleObjectsEx",
+            func: Handler::Async(wrappers::WaitForMultipleObjectsEx),
+            stack_consumed: 20u32,
         },
         Shim {
             name: "WaitForSingleObject",
-            func: Handler::Sync(wrappers::WaitForSingleObject),
+            func: Handler::Async(wrappers::WaitForSingleObject),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "WaitForSingleObjectEx",
+            func: Handler::Async(wrappers::WaitForSingleObjectEx),
+            stack_consumed: 12u32,
+        },
+        Shim {
+            name: "WaitNamedPipeA",
+            func: Handler::Async(wrappers::WaitNamedPipeA),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "WaitNamedPipeW",
+            func: Handler::Async(wrappers::WaitNamedPipeW),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "WideCharToMultiByte",
             func: Handler::Sync(wrappers::WideCharToMultiByte),
+            stack_consumed: 32u32,
         },
         Shim {
             name: "WriteConsoleA",
             func: Handler::Sync(wrappers::WriteConsoleA),
+            stack_consumed: 20u32,
         },
         Shim {
             name: "WriteConsoleW",
             func: Handler::Sync(wrappers::WriteConsoleW),
+            stack_consumed: 20u32,
         },
         Shim {
             name: "WriteFile",
             func: Handler::Sync(wrappers::WriteFile),
+            stack_consumed: 20u32,
+        },
+        Shim {
+            name: "WriteFileEx",
+            func: Handler::Sync(wrappers::WriteFileEx),
+            stack_consumed: 20u32,
         },
         Shim {
             name: "WriteProfileStringW",
             func: Handler::Sync(wrappers::WriteProfileStringW),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "_lclose",
             func: Handler::Sync(wrappers::_lclose),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "_llseek",
             func: Handler::Sync(wrappers::_llseek),
+            stack_consumed: 12u32,
+        },
+        Shim {
+            name: "_lopen",
+            func: Handler::Sync(wrappers::_lopen),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "_lread",
+            func: Handler::Sync(wrappers::_lread),
+            stack_consumed: 12u32,
+        },
+        Shim {
+            name: "lstrcmpA",
+            func: Handler::Sync(wrappers::lstrcmpA),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "lstrcmpW",
+            func: Handler::Sync(wrappers::lstrcmpW),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "lstrcmpiA",
+            func: Handler::Sync(wrappers::lstrcmpiA),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "lstrcmpiW",
+            func: Handler::Sync(wrappers::lstrcmpiW),
+            stack_consumed: 8u32,
         },
         Shim {
-            name: "_lopen",
-            func: Handler::Sync(wrappers::_lopen),
+            name: "lstrcpyA",
+            func: Handler::Sync(wrappers::lstrcpyA),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "lstrcpyW",
+            func: Handler::Sync(wrappers::lstrcpyW),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "lstrlenA",
+            func: Handler::Sync(wrappers::lstrlenA),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "lstrlenW",
+            func: Handler::Sync(wrappers::lstrlenW),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "retrowin32_exit",
+            func: Handler::Async(wrappers::retrowin32_exit),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "retrowin32_fiber_main",
+            func: Handler::Async(wrappers::retrowin32_fiber_main),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "retrowin32_main",
+            func: Handler::Async(wrappers::retrowin32_main),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "retrowin32_start",
+            func: Handler::Async(wrappers::retrowin32_start),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "retrowin32_thread_exit",
+            func: Handler::Async(wrappers::retrowin32_thread_exit),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "retrowin32_thread_start",
+            func: Handler::Async(wrappers::retrowin32_thread_start),
+            stack_consumed: 0u32,
+        },
+    ];
+    pub const DLL: BuiltinDLL = BuiltinDLL {
+        file_name: "kernel32.dll",
+        shims: &SHIMS,
+        raw: std::include_bytes!("../../dll/kernel32.dll"),
+    };
+}
+pub mod ntdll {
+    use super::*;
+    mod wrappers {
+        use crate::{
+            machine::Machine,
+            winapi::{self, stack_args::*, types::*},
+        };
+        use memory::Extensions;
+        use winapi::ntdll::*;
+        pub unsafe fn NtReadFile(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let FileHandle = <HFILE>::from_stack(mem, stack_args + 0u32);
+            let Event = <u32>::from_stack(mem, stack_args + 4u32);
+            let ApcRoutine = <u32>::from_stack(mem, stack_args + 8u32);
+            let ApcContext = <u32>::from_stack(mem, stack_args + 12u32);
+            let IoStatusBlock = <Option<&mut IO_STATUS_BLOCK>>::from_stack(mem, stack_args + 16u32);
+            let Buffer = <ArrayWithSizeMut<u8>>::from_stack(mem, stack_args + 20u32);
+            let ByteOffset = <Option<&mut u64>>::from_stack(mem, stack_args + 28u32);
+            let Key = <u32>::from_stack(mem, stack_args + 32u32);
+            let __trace_context = if crate::trace::enabled("ntdll") {
+                Some(crate::trace::trace_begin(
+                    "ntdll",
+                    "NtReadFile",
+                    &[
+                        ("FileHandle", &FileHandle),
+                        ("Event", &Event),
+                        ("ApcRoutine", &ApcRoutine),
+                        ("ApcContext", &ApcContext),
+                        ("IoStatusBlock", &IoStatusBlock),
+                        ("Buffer", &Buffer),
+                        ("ByteOffset", &ByteOffset),
+                        ("Key", &Key),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::ntdll::NtReadFile(
+                machine,
+                FileHandle,
+                Event,
+                ApcRoutine,
+                ApcContext,
+                IoStatusBlock,
+                Buffer,
+                ByteOffset,
+                Key,
+            );
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::ntdll::NtReadFile_pos.0,
+                    winapi::ntdll::NtReadFile_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn RtlExitUserProcess(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let exit_code = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("ntdll") {
+                Some(crate::trace::trace_begin(
+                    "ntdll",
+                    "RtlExitUserProcess",
+                    &[("exit_code", &exit_code)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::ntdll::RtlExitUserProcess(machine, exit_code);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::ntdll::RtlExitUserProcess_pos.0,
+                    winapi::ntdll::RtlExitUserProcess_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn RtlUlongByteSwap(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let register_args = machine.register_args();
+            let Source = <u32>::from_arg(mem, register_args[0usize]);
+            let __trace_context = if crate::trace::enabled("ntdll") {
+                Some(crate::trace::trace_begin(
+                    "ntdll",
+                    "RtlUlongByteSwap",
+                    &[("Source", &Source)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::ntdll::RtlUlongByteSwap(machine, Source);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::ntdll::RtlUlongByteSwap_pos.0,
+                    winapi::ntdll::RtlUlongByteSwap_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn RtlUnwind(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let TargetFrame = <u32>::from_stack(mem, stack_args + 0u32);
+            let TargetIp = <u32>::from_stack(mem, stack_args + 4u32);
+            let ExceptionRecord = <u32>::from_stack(mem, stack_args + 8u32);
+            let ReturnValue = <u32>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("ntdll") {
+                Some(crate::trace::trace_begin(
+                    "ntdll",
+                    "RtlUnwind",
+                    &[
+                        ("TargetFrame", &TargetFrame),
+                        ("TargetIp", &TargetIp),
+                        ("ExceptionRecord", &ExceptionRecord),
+                        ("ReturnValue", &ReturnValue),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::ntdll::RtlUnwind(
+                    machine,
+                    TargetFrame,
+                    TargetIp,
+                    ExceptionRecord,
+                    ReturnValue,
+                )
+                .await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::ntdll::RtlUnwind_pos.0,
+                        winapi::ntdll::RtlUnwind_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn RtlUshortByteSwap(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let register_args = machine.register_args();
+            let Source = <u16>::from_arg(mem, register_args[0usize]);
+            let __trace_context = if crate::trace::enabled("ntdll") {
+                Some(crate::trace::trace_begin(
+                    "ntdll",
+                    "RtlUshortByteSwap",
+                    &[("Source", &Source)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::ntdll::RtlUshortByteSwap(machine, Source);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::ntdll::RtlUshortByteSwap_pos.0,
+                    winapi::ntdll::RtlUshortByteSwap_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+    }
+    const SHIMS: [Shim; 5usize] = [
+        Shim {
+            name: "NtReadFile",
+            func: Handler::Sync(wrappers::NtReadFile),
+            stack_consumed: 36u32,
         },
         Shim {
-            name: "_lread",
-            func: Handler::Sync(wrappers::_lread),
+            name: "RtlExitUserProcess",
+            func: Handler::Sync(wrappers::RtlExitUserProcess),
+            stack_consumed: 4u32,
         },
         Shim {
-            name: "lstrcmpiA",
-            func: Handler::Sync(wrappers::lstrcmpiA),
+            name: "RtlUlongByteSwap",
+            func: Handler::Sync(wrappers::RtlUlongByteSwap),
+            stack_consumed: 0u32,
         },
         Shim {
-            name: "lstrcpyA",
-            func: Handler::Sync(wrappers::lstrcpyA),
+            name: "RtlUnwind",
+            func: Handler::Async(wrappers::RtlUnwind),
+            stack_consumed: 16u32,
         },
         Shim {
-            name: "lstrcpyW",
-            func: Handler::Sync(wrappers::lstrcpyW),
+            name: "RtlUshortByteSwap",
+            func: Handler::Sync(wrappers::RtlUshortByteSwap),
+            stack_consumed: 0u32,
         },
+    ];
+    pub const DLL: BuiltinDLL = BuiltinDLL {
+        file_name: "ntdll.dll",
+        shims: &SHIMS,
+        raw: std::include_bytes!("../../dll/ntdll.dll"),
+    };
+}
+pub mod ole32 {
+    use super::*;
+    mod wrappers {
+        use crate::{
+            machine::Machine,
+            winapi::{self, stack_args::*, types::*},
+        };
+        use memory::Extensions;
+        use winapi::ole32::*;
+        pub unsafe fn CoCreateInstance(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let rclsid = <u32>::from_stack(mem, stack_args + 0u32);
+            let pUnkOuter = <u32>::from_stack(mem, stack_args + 4u32);
+            let dwClsContext = <u32>::from_stack(mem, stack_args + 8u32);
+            let riid = <u32>::from_stack(mem, stack_args + 12u32);
+            let ppv = <u32>::from_stack(mem, stack_args + 16u32);
+            let __trace_context = if crate::trace::enabled("ole32") {
+                Some(crate::trace::trace_begin(
+                    "ole32",
+                    "CoCreateInstance",
+                    &[
+                        ("rclsid", &rclsid),
+                        ("pUnkOuter", &pUnkOuter),
+                        ("dwClsContext", &dwClsContext),
+                        ("riid", &riid),
+                        ("ppv", &ppv),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::ole32::CoCreateInstance(
+                machine,
+                rclsid,
+                pUnkOuter,
+                dwClsContext,
+                riid,
+                ppv,
+            );
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::ole32::CoCreateInstance_pos.0,
+                    winapi::ole32::CoCreateInstance_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn CoInitialize(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let pvReserved = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("ole32") {
+                Some(crate::trace::trace_begin(
+                    "ole32",
+                    "CoInitialize",
+                    &[("pvReserved", &pvReserved)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::ole32::CoInitialize(machine, pvReserved);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::ole32::CoInitialize_pos.0,
+                    winapi::ole32::CoInitialize_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn CoUninitialize(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let __trace_context = if crate::trace::enabled("ole32") {
+                Some(crate::trace::trace_begin("ole32", "CoUninitialize", &[]))
+            } else {
+                None
+            };
+            let result = winapi::ole32::CoUninitialize(machine);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::ole32::CoUninitialize_pos.0,
+                    winapi::ole32::CoUninitialize_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn OleInitialize(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let _pvReserved = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("ole32") {
+                Some(crate::trace::trace_begin(
+                    "ole32",
+                    "OleInitialize",
+                    &[("pvReserved", &_pvReserved)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::ole32::OleInitialize(machine, _pvReserved);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::ole32::OleInitialize_pos.0,
+                    winapi::ole32::OleInitialize_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+    }
+    const SHIMS: [Shim; 4usize] = [
         Shim {
-            name: "lstrlenA",
-            func: Handler::Sync(wrappers::lstrlenA),
+            name: "CoCreateInstance",
+            func: Handler::Sync(wrappers::CoCreateInstance),
+            stack_consumed: 20u32,
         },
         Shim {
-            name: "lstrlenW",
-            func: Handler::Sync(wrappers::lstrlenW),
+            name: "CoInitialize",
+            func: Handler::Sync(wrappers::CoInitialize),
+            stack_consumed: 4u32,
         },
         Shim {
-            name: "retrowin32_main",
-            func: Handler::Async(wrappers::retrowin32_main),
+            name: "CoUninitialize",
+            func: Handler::Sync(wrappers::CoUninitialize),
+            stack_consumed: 0u32,
         },
         Shim {
-            name: "retrowin32_thread_main",
-            func: Handler::Async(wrappers::retrowin32_thread_main),
+            name: "OleInitialize",
+            func: Handler::Sync(wrappers::OleInitialize),
+            stack_consumed: 4u32,
         },
     ];
     pub const DLL: BuiltinDLL = BuiltinDLL {
-        file_name: "kernel32.dll",
+        file_name: "ole32.dll",
         shims: &SHIMS,
-        raw: std::include_bytes!("../../dll/kernel32.dll"),
+        raw: std::include_bytes!("../../dll/ole32.dll"),
     };
 }
-pub mod ntdll {
+pub mod oleaut32 {
     use super::*;
     mod wrappers {
         use crate::{
@@ -9509,272 +15587,862 @@ pub mod ntdll {
             winapi::{self, stack_args::*, types::*},
         };
         use memory::Extensions;
-        use winapi::ntdll::*;
-        pub unsafe fn NtReadFile(machine: &mut Machine, stack_args: u32) -> u32 {
+        use winapi::oleaut32::*;
+    }
+    const SHIMS: [Shim; 0usize] = [];
+    pub const DLL: BuiltinDLL = BuiltinDLL {
+        file_name: "oleaut32.dll",
+        shims: &SHIMS,
+        raw: std::include_bytes!("../../dll/oleaut32.dll"),
+    };
+}
+pub mod retrowin32_test {
+    use super::*;
+    mod wrappers {
+        use crate::{
+            machine::Machine,
+            winapi::{self, stack_args::*, types::*},
+        };
+        use memory::Extensions;
+        use winapi::retrowin32_test::*;
+        pub unsafe fn retrowin32_test_callback1(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let func = <u32>::from_stack(mem, stack_args + 0u32);
+            let data = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("retrowin32_test") {
+                Some(crate::trace::trace_begin(
+                    "retrowin32_test",
+                    "retrowin32_test_callback1",
+                    &[("func", &func), ("data", &data)],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result =
+                    winapi::retrowin32_test::retrowin32_test_callback1(machine, func, data).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::retrowin32_test::retrowin32_test_callback1_pos.0,
+                        winapi::retrowin32_test::retrowin32_test_callback1_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+    }
+    const SHIMS: [Shim; 1usize] = [Shim {
+        name: "retrowin32_test_callback1",
+        func: Handler::Async(wrappers::retrowin32_test_callback1),
+        stack_consumed: 8u32,
+    }];
+    pub const DLL: BuiltinDLL = BuiltinDLL {
+        file_name: "retrowin32_test.dll",
+        shims: &SHIMS,
+        raw: std::include_bytes!("../../dll/retrowin32_test.dll"),
+    };
+}
+pub mod shell32 {
+    use super::*;
+    mod wrappers {
+        use crate::{
+            machine::Machine,
+            winapi::{self, stack_args::*, types::*},
+        };
+        use memory::Extensions;
+        use winapi::shell32::*;
+        pub unsafe fn CommandLineToArgvW(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpCmdLine = <Option<&Str16>>::from_stack(mem, stack_args + 0u32);
+            let pNumArgs = <Option<&mut u32>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("shell32") {
+                Some(crate::trace::trace_begin(
+                    "shell32",
+                    "CommandLineToArgvW",
+                    &[("lpCmdLine", &lpCmdLine), ("pNumArgs", &pNumArgs)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::shell32::CommandLineToArgvW(machine, lpCmdLine, pNumArgs);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::shell32::CommandLineToArgvW_pos.0,
+                    winapi::shell32::CommandLineToArgvW_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+    }
+    const SHIMS: [Shim; 1usize] = [Shim {
+        name: "CommandLineToArgvW",
+        func: Handler::Sync(wrappers::CommandLineToArgvW),
+        stack_consumed: 8u32,
+    }];
+    pub const DLL: BuiltinDLL = BuiltinDLL {
+        file_name: "shell32.dll",
+        shims: &SHIMS,
+        raw: std::include_bytes!("../../dll/shell32.dll"),
+    };
+}
+pub mod ucrtbase {
+    use super::*;
+    mod wrappers {
+        use crate::{
+            machine::Machine,
+            winapi::{self, stack_args::*, types::*},
+        };
+        use memory::Extensions;
+        use winapi::ucrtbase::*;
+        pub unsafe fn _XcptFilter(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let xcptnum = <u32>::from_stack(mem, stack_args + 0u32);
+            let pxcptinfoptrs = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("ucrtbase") {
+                Some(crate::trace::trace_begin(
+                    "ucrtbase",
+                    "_XcptFilter",
+                    &[("xcptnum", &xcptnum), ("pxcptinfoptrs", &pxcptinfoptrs)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::ucrtbase::_XcptFilter(machine, xcptnum, pxcptinfoptrs);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::ucrtbase::_XcptFilter_pos.0,
+                    winapi::ucrtbase::_XcptFilter_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn __control87_2(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let new = <u32>::from_stack(mem, stack_args + 0u32);
+            let mask = <u32>::from_stack(mem, stack_args + 4u32);
+            let x86_cw = <Option<&mut u32>>::from_stack(mem, stack_args + 8u32);
+            let sse2_cw = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("ucrtbase") {
+                Some(crate::trace::trace_begin(
+                    "ucrtbase",
+                    "__control87_2",
+                    &[
+                        ("new", &new),
+                        ("mask", &mask),
+                        ("x86_cw", &x86_cw),
+                        ("sse2_cw", &sse2_cw),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::ucrtbase::__control87_2(machine, new, mask, x86_cw, sse2_cw);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::ucrtbase::__control87_2_pos.0,
+                    winapi::ucrtbase::__control87_2_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn __dllonexit(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let func = <u32>::from_stack(mem, stack_args + 0u32);
+            let d = <u32>::from_stack(mem, stack_args + 4u32);
+            let f = <u32>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("ucrtbase") {
+                Some(crate::trace::trace_begin(
+                    "ucrtbase",
+                    "__dllonexit",
+                    &[("func", &func), ("d", &d), ("f", &f)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::ucrtbase::__dllonexit(machine, func, d, f);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::ucrtbase::__dllonexit_pos.0,
+                    winapi::ucrtbase::__dllonexit_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn __getmainargs(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let argc = <Option<&mut u32>>::from_stack(mem, stack_args + 0u32);
+            let argv = <Option<&mut u32>>::from_stack(mem, stack_args + 4u32);
+            let env = <Option<&mut u32>>::from_stack(mem, stack_args + 8u32);
+            let doWildCard = <u32>::from_stack(mem, stack_args + 12u32);
+            let startInfo = <u32>::from_stack(mem, stack_args + 16u32);
+            let __trace_context = if crate::trace::enabled("ucrtbase") {
+                Some(crate::trace::trace_begin(
+                    "ucrtbase",
+                    "__getmainargs",
+                    &[
+                        ("argc", &argc),
+                        ("argv", &argv),
+                        ("env", &env),
+                        ("doWildCard", &doWildCard),
+                        ("startInfo", &startInfo),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result =
+                winapi::ucrtbase::__getmainargs(machine, argc, argv, env, doWildCard, startInfo);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::ucrtbase::__getmainargs_pos.0,
+                    winapi::ucrtbase::__getmainargs_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn __p___argc(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let __trace_context = if crate::trace::enabled("ucrtbase") {
+                Some(crate::trace::trace_begin("ucrtbase", "__p___argc", &[]))
+            } else {
+                None
+            };
+            let result = winapi::ucrtbase::__p___argc(machine);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::ucrtbase::__p___argc_pos.0,
+                    winapi::ucrtbase::__p___argc_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn __p___argv(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let __trace_context = if crate::trace::enabled("ucrtbase") {
+                Some(crate::trace::trace_begin("ucrtbase", "__p___argv", &[]))
+            } else {
+                None
+            };
+            let result = winapi::ucrtbase::__p___argv(machine);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::ucrtbase::__p___argv_pos.0,
+                    winapi::ucrtbase::__p___argv_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn __p__commode(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let __trace_context = if crate::trace::enabled("ucrtbase") {
+                Some(crate::trace::trace_begin("ucrtbase", "__p__commode", &[]))
+            } else {
+                None
+            };
+            let result = winapi::ucrtbase::__p__commode(machine);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::ucrtbase::__p__commode_pos.0,
+                    winapi::ucrtbase::__p__commode_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn __p__fmode(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let __trace_context = if crate::trace::enabled("ucrtbase") {
+                Some(crate::trace::trace_begin("ucrtbase", "__p__fmode", &[]))
+            } else {
+                None
+            };
+            let result = winapi::ucrtbase::__p__fmode(machine);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::ucrtbase::__p__fmode_pos.0,
+                    winapi::ucrtbase::__p__fmode_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn __set_app_type(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let _app_type = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("ucrtbase") {
+                Some(crate::trace::trace_begin(
+                    "ucrtbase",
+                    "__set_app_type",
+                    &[("app_type", &_app_type)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::ucrtbase::__set_app_type(machine, _app_type);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::ucrtbase::__set_app_type_pos.0,
+                    winapi::ucrtbase::__set_app_type_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn __setusermatherr(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let pf = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("ucrtbase") {
+                Some(crate::trace::trace_begin(
+                    "ucrtbase",
+                    "__setusermatherr",
+                    &[("pf", &pf)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::ucrtbase::__setusermatherr(machine, pf);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::ucrtbase::__setusermatherr_pos.0,
+                    winapi::ucrtbase::__setusermatherr_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn __stdio_common_vsprintf(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let options = <u64>::from_stack(mem, stack_args + 0u32);
+            let buffer = <u32>::from_stack(mem, stack_args + 8u32);
+            let buffer_count = <u32>::from_stack(mem, stack_args + 12u32);
+            let format = <Option<&CStr>>::from_stack(mem, stack_args + 16u32);
+            let locale = <u32>::from_stack(mem, stack_args + 20u32);
+            let arglist = <u32>::from_stack(mem, stack_args + 24u32);
+            let __trace_context = if crate::trace::enabled("ucrtbase") {
+                Some(crate::trace::trace_begin(
+                    "ucrtbase",
+                    "__stdio_common_vsprintf",
+                    &[
+                        ("options", &options),
+                        ("buffer", &buffer),
+                        ("buffer_count", &buffer_count),
+                        ("format", &format),
+                        ("locale", &locale),
+                        ("arglist", &arglist),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::ucrtbase::__stdio_common_vsprintf(
+                machine,
+                options,
+                buffer,
+                buffer_count,
+                format,
+                locale,
+                arglist,
+            );
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::ucrtbase::__stdio_common_vsprintf_pos.0,
+                    winapi::ucrtbase::__stdio_common_vsprintf_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn __stdio_common_vswprintf(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let options = <u64>::from_stack(mem, stack_args + 0u32);
+            let buffer = <u32>::from_stack(mem, stack_args + 8u32);
+            let buffer_count = <u32>::from_stack(mem, stack_args + 12u32);
+            let format = <Option<&Str16>>::from_stack(mem, stack_args + 16u32);
+            let locale = <u32>::from_stack(mem, stack_args + 20u32);
+            let arglist = <u32>::from_stack(mem, stack_args + 24u32);
+            let __trace_context = if crate::trace::enabled("ucrtbase") {
+                Some(crate::trace::trace_begin(
+                    "ucrtbase",
+                    "__stdio_common_vswprintf",
+                    &[
+                        ("options", &options),
+                        ("buffer", &buffer),
+                        ("buffer_count", &buffer_count),
+                        ("format", &format),
+                        ("locale", &locale),
+                        ("arglist", &arglist),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::ucrtbase::__stdio_common_vswprintf(
+                machine,
+                options,
+                buffer,
+                buffer_count,
+                format,
+                locale,
+                arglist,
+            );
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::ucrtbase::__stdio_common_vswprintf_pos.0,
+                    winapi::ucrtbase::__stdio_common_vswprintf_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn _clearfp(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let __trace_context = if crate::trace::enabled("ucrtbase") {
+                Some(crate::trace::trace_begin("ucrtbase", "_clearfp", &[]))
+            } else {
+                None
+            };
+            let result = winapi::ucrtbase::_clearfp(machine);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::ucrtbase::_clearfp_pos.0,
+                    winapi::ucrtbase::_clearfp_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn _configthreadlocale(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let per_thread_locale_type = <i32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("ucrtbase") {
+                Some(crate::trace::trace_begin(
+                    "ucrtbase",
+                    "_configthreadlocale",
+                    &[("per_thread_locale_type", &per_thread_locale_type)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::ucrtbase::_configthreadlocale(machine, per_thread_locale_type);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::ucrtbase::_configthreadlocale_pos.0,
+                    winapi::ucrtbase::_configthreadlocale_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn _configure_narrow_argv(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let _mode = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("ucrtbase") {
+                Some(crate::trace::trace_begin(
+                    "ucrtbase",
+                    "_configure_narrow_argv",
+                    &[("mode", &_mode)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::ucrtbase::_configure_narrow_argv(machine, _mode);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::ucrtbase::_configure_narrow_argv_pos.0,
+                    winapi::ucrtbase::_configure_narrow_argv_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn _control87(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let new = <u32>::from_stack(mem, stack_args + 0u32);
+            let mask = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("ucrtbase") {
+                Some(crate::trace::trace_begin(
+                    "ucrtbase",
+                    "_control87",
+                    &[("new", &new), ("mask", &mask)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::ucrtbase::_control87(machine, new, mask);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::ucrtbase::_control87_pos.0,
+                    winapi::ucrtbase::_control87_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn _controlfp(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let new = <u32>::from_stack(mem, stack_args + 0u32);
+            let mask = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("ucrtbase") {
+                Some(crate::trace::trace_begin(
+                    "ucrtbase",
+                    "_controlfp",
+                    &[("new", &new), ("mask", &mask)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::ucrtbase::_controlfp(machine, new, mask);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::ucrtbase::_controlfp_pos.0,
+                    winapi::ucrtbase::_controlfp_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn _controlfp_s(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let FileHandle = <HFILE>::from_stack(mem, stack_args + 0u32);
-            let Event = <u32>::from_stack(mem, stack_args + 4u32);
-            let ApcRoutine = <u32>::from_stack(mem, stack_args + 8u32);
-            let ApcContext = <u32>::from_stack(mem, stack_args + 12u32);
-            let IoStatusBlock = <Option<&mut IO_STATUS_BLOCK>>::from_stack(mem, stack_args + 16u32);
-            let Buffer = <ArrayWithSizeMut<u8>>::from_stack(mem, stack_args + 20u32);
-            let ByteOffset = <Option<&mut u64>>::from_stack(mem, stack_args + 28u32);
-            let Key = <u32>::from_stack(mem, stack_args + 32u32);
-            let __trace_context = if crate::trace::enabled("ntdll") {
+            let currentControl = <Option<&mut u32>>::from_stack(mem, stack_args + 0u32);
+            let newControl = <u32>::from_stack(mem, stack_args + 4u32);
+            let mask = <u32>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("ucrtbase") {
                 Some(crate::trace::trace_begin(
-                    "ntdll",
-                    "NtReadFile",
+                    "ucrtbase",
+                    "_controlfp_s",
                     &[
-                        ("FileHandle", &FileHandle),
-                        ("Event", &Event),
-                        ("ApcRoutine", &ApcRoutine),
-                        ("ApcContext", &ApcContext),
-                        ("IoStatusBlock", &IoStatusBlock),
-                        ("Buffer", &Buffer),
-                        ("ByteOffset", &ByteOffset),
-                        ("Key", &Key),
+                        ("currentControl", &currentControl),
+                        ("newControl", &newControl),
+                        ("mask", &mask),
                     ],
                 ))
             } else {
                 None
             };
-            let result = winapi::ntdll::NtReadFile(
-                machine,
-                FileHandle,
-                Event,
-                ApcRoutine,
-                ApcContext,
-                IoStatusBlock,
-                Buffer,
-                ByteOffset,
-                Key,
-            );
+            let result = winapi::ucrtbase::_controlfp_s(machine, currentControl, newControl, mask);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::ntdll::NtReadFile_pos.0,
-                    winapi::ntdll::NtReadFile_pos.1,
+                    winapi::ucrtbase::_controlfp_s_pos.0,
+                    winapi::ucrtbase::_controlfp_s_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn RtlExitUserProcess(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn _crt_atexit(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let exit_code = <u32>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("ntdll") {
+            let _function = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("ucrtbase") {
                 Some(crate::trace::trace_begin(
-                    "ntdll",
-                    "RtlExitUserProcess",
-                    &[("exit_code", &exit_code)],
+                    "ucrtbase",
+                    "_crt_atexit",
+                    &[("function", &_function)],
                 ))
             } else {
                 None
             };
-            let result = winapi::ntdll::RtlExitUserProcess(machine, exit_code);
+            let result = winapi::ucrtbase::_crt_atexit(machine, _function);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::ntdll::RtlExitUserProcess_pos.0,
-                    winapi::ntdll::RtlExitUserProcess_pos.1,
+                    winapi::ucrtbase::_crt_atexit_pos.0,
+                    winapi::ucrtbase::_crt_atexit_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-    }
-    const SHIMS: [Shim; 2usize] = [
-        Shim {
-            name: "NtReadFile",
-            func: Handler::Sync(wrappers::NtReadFile),
-        },
-        Shim {
-            name: "RtlExitUserProcess",
-            func: Handler::Sync(wrappers::RtlExitUserProcess),
-        },
-    ];
-    pub const DLL: BuiltinDLL = BuiltinDLL {
-        file_name: "ntdll.dll",
-        shims: &SHIMS,
-        raw: std::include_bytes!("../../dll/ntdll.dll"),
-    };
-}
-pub mod ole32 {
-    use super::*;
-    mod wrappers {
-        use crate::{
-            machine::Machine,
-            winapi::{self, stack_args::*, types::*},
-        };
-        use memory::Extensions;
-        use winapi::ole32::*;
-        pub unsafe fn CoCreateInstance(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn _errno(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let rclsid = <u32>::from_stack(mem, stack_args + 0u32);
-            let pUnkOuter = <u32>::from_stack(mem, stack_args + 4u32);
-            let dwClsContext = <u32>::from_stack(mem, stack_args + 8u32);
-            let riid = <u32>::from_stack(mem, stack_args + 12u32);
-            let ppv = <u32>::from_stack(mem, stack_args + 16u32);
-            let __trace_context = if crate::trace::enabled("ole32") {
+            let __trace_context = if crate::trace::enabled("ucrtbase") {
+                Some(crate::trace::trace_begin("ucrtbase", "_errno", &[]))
+            } else {
+                None
+            };
+            let result = winapi::ucrtbase::_errno(machine);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::ucrtbase::_errno_pos.0,
+                    winapi::ucrtbase::_errno_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn _except_handler3(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let exception_record = <u32>::from_stack(mem, stack_args + 0u32);
+            let registration = <u32>::from_stack(mem, stack_args + 4u32);
+            let context = <u32>::from_stack(mem, stack_args + 8u32);
+            let _dispatcher = <u32>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("ucrtbase") {
                 Some(crate::trace::trace_begin(
-                    "ole32",
-                    "CoCreateInstance",
+                    "ucrtbase",
+                    "_except_handler3",
                     &[
-                        ("rclsid", &rclsid),
-                        ("pUnkOuter", &pUnkOuter),
-                        ("dwClsContext", &dwClsContext),
-                        ("riid", &riid),
-                        ("ppv", &ppv),
+                        ("exception_record", &exception_record),
+                        ("registration", &registration),
+                        ("context", &context),
+                        ("dispatcher", &_dispatcher),
                     ],
                 ))
             } else {
                 None
             };
-            let result = winapi::ole32::CoCreateInstance(
-                machine,
-                rclsid,
-                pUnkOuter,
-                dwClsContext,
-                riid,
-                ppv,
-            );
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::ucrtbase::_except_handler3(
+                    machine,
+                    exception_record,
+                    registration,
+                    context,
+                    _dispatcher,
+                )
+                .await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::ucrtbase::_except_handler3_pos.0,
+                        winapi::ucrtbase::_except_handler3_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn _exit(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let status = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("ucrtbase") {
+                Some(crate::trace::trace_begin(
+                    "ucrtbase",
+                    "_exit",
+                    &[("status", &status)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::ucrtbase::_exit(machine, status);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::ole32::CoCreateInstance_pos.0,
-                    winapi::ole32::CoCreateInstance_pos.1,
+                    winapi::ucrtbase::_exit_pos.0,
+                    winapi::ucrtbase::_exit_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn CoInitialize(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn _fpreset(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let pvReserved = <u32>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("ole32") {
+            let __trace_context = if crate::trace::enabled("ucrtbase") {
+                Some(crate::trace::trace_begin("ucrtbase", "_fpreset", &[]))
+            } else {
+                None
+            };
+            let result = winapi::ucrtbase::_fpreset(machine);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::ucrtbase::_fpreset_pos.0,
+                    winapi::ucrtbase::_fpreset_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn _get_initial_narrow_environment(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> u32 {
+            let mem = machine.mem().detach();
+            let __trace_context = if crate::trace::enabled("ucrtbase") {
                 Some(crate::trace::trace_begin(
-                    "ole32",
-                    "CoInitialize",
-                    &[("pvReserved", &pvReserved)],
+                    "ucrtbase",
+                    "_get_initial_narrow_environment",
+                    &[],
                 ))
             } else {
                 None
             };
-            let result = winapi::ole32::CoInitialize(machine, pvReserved);
+            let result = winapi::ucrtbase::_get_initial_narrow_environment(machine);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::ole32::CoInitialize_pos.0,
-                    winapi::ole32::CoInitialize_pos.1,
+                    winapi::ucrtbase::_get_initial_narrow_environment_pos.0,
+                    winapi::ucrtbase::_get_initial_narrow_environment_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn CoUninitialize(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn _global_unwind2(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
             let mem = machine.mem().detach();
-            let __trace_context = if crate::trace::enabled("ole32") {
-                Some(crate::trace::trace_begin("ole32", "CoUninitialize", &[]))
+            let registration = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("ucrtbase") {
+                Some(crate::trace::trace_begin(
+                    "ucrtbase",
+                    "_global_unwind2",
+                    &[("registration", &registration)],
+                ))
             } else {
                 None
             };
-            let result = winapi::ole32::CoUninitialize(machine);
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::ucrtbase::_global_unwind2(machine, registration).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::ucrtbase::_global_unwind2_pos.0,
+                        winapi::ucrtbase::_global_unwind2_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn _initialize_narrow_environment(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> u32 {
+            let mem = machine.mem().detach();
+            let __trace_context = if crate::trace::enabled("ucrtbase") {
+                Some(crate::trace::trace_begin(
+                    "ucrtbase",
+                    "_initialize_narrow_environment",
+                    &[],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::ucrtbase::_initialize_narrow_environment(machine);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::ole32::CoUninitialize_pos.0,
-                    winapi::ole32::CoUninitialize_pos.1,
+                    winapi::ucrtbase::_initialize_narrow_environment_pos.0,
+                    winapi::ucrtbase::_initialize_narrow_environment_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn OleInitialize(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn _initterm(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let start = <u32>::from_stack(mem, stack_args + 0u32);
+            let end = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("ucrtbase") {
+                Some(crate::trace::trace_begin(
+                    "ucrtbase",
+                    "_initterm",
+                    &[("start", &start), ("end", &end)],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::ucrtbase::_initterm(machine, start, end).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::ucrtbase::_initterm_pos.0,
+                        winapi::ucrtbase::_initterm_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn _initterm_e(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
             let mem = machine.mem().detach();
-            let _pvReserved = <u32>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("ole32") {
+            let start = <u32>::from_stack(mem, stack_args + 0u32);
+            let end = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("ucrtbase") {
                 Some(crate::trace::trace_begin(
-                    "ole32",
-                    "OleInitialize",
-                    &[("pvReserved", &_pvReserved)],
+                    "ucrtbase",
+                    "_initterm_e",
+                    &[("start", &start), ("end", &end)],
                 ))
             } else {
                 None
             };
-            let result = winapi::ole32::OleInitialize(machine, _pvReserved);
-            if let Some(__trace_context) = __trace_context {
-                crate::trace::trace_return(
-                    &__trace_context,
-                    winapi::ole32::OleInitialize_pos.0,
-                    winapi::ole32::OleInitialize_pos.1,
-                    &result,
-                );
-            }
-            result.to_raw()
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::ucrtbase::_initterm_e(machine, start, end).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::ucrtbase::_initterm_e_pos.0,
+                        winapi::ucrtbase::_initterm_e_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
         }
-    }
-    const SHIMS: [Shim; 4usize] = [
-        Shim {
-            name: "CoCreateInstance",
-            func: Handler::Sync(wrappers::CoCreateInstance),
-        },
-        Shim {
-            name: "CoInitialize",
-            func: Handler::Sync(wrappers::CoInitialize),
-        },
-        Shim {
-            name: "CoUninitialize",
-            func: Handler::Sync(wrappers::CoUninitialize),
-        },
-        Shim {
-            name: "OleInitialize",
-            func: Handler::Sync(wrappers::OleInitialize),
-        },
-    ];
-    pub const DLL: BuiltinDLL = BuiltinDLL {
-        file_name: "ole32.dll",
-        shims: &SHIMS,
-        raw: std::include_bytes!("../../dll/ole32.dll"),
-    };
-}
-pub mod oleaut32 {
-    use super::*;
-    mod wrappers {
-        use crate::{
-            machine::Machine,
-            winapi::{self, stack_args::*, types::*},
-        };
-        use memory::Extensions;
-        use winapi::oleaut32::*;
-    }
-    const SHIMS: [Shim; 0usize] = [];
-    pub const DLL: BuiltinDLL = BuiltinDLL {
-        file_name: "oleaut32.dll",
-        shims: &SHIMS,
-        raw: std::include_bytes!("../../dll/oleaut32.dll"),
-    };
-}
-pub mod retrowin32_test {
-    use super::*;
-    mod wrappers {
-        use crate::{
-            machine::Machine,
-            winapi::{self, stack_args::*, types::*},
-        };
-        use memory::Extensions;
-        use winapi::retrowin32_test::*;
-        pub unsafe fn retrowin32_test_callback1(
+        pub unsafe fn _local_unwind2(
             machine: &mut Machine,
             stack_args: u32,
         ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
             let mem = machine.mem().detach();
-            let func = <u32>::from_stack(mem, stack_args + 0u32);
-            let data = <u32>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("retrowin32_test") {
+            let registration = <u32>::from_stack(mem, stack_args + 0u32);
+            let stop_level = <i32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("ucrtbase") {
                 Some(crate::trace::trace_begin(
-                    "retrowin32_test",
-                    "retrowin32_test_callback1",
-                    &[("func", &func), ("data", &data)],
+                    "ucrtbase",
+                    "_local_unwind2",
+                    &[("registration", &registration), ("stop_level", &stop_level)],
                 ))
             } else {
                 None
@@ -9783,484 +16451,766 @@ pub mod retrowin32_test {
             Box::pin(async move {
                 let machine = unsafe { &mut *machine };
                 let result =
-                    winapi::retrowin32_test::retrowin32_test_callback1(machine, func, data).await;
+                    winapi::ucrtbase::_local_unwind2(machine, registration, stop_level).await;
                 if let Some(__trace_context) = __trace_context {
                     crate::trace::trace_return(
                         &__trace_context,
-                        winapi::retrowin32_test::retrowin32_test_callback1_pos.0,
-                        winapi::retrowin32_test::retrowin32_test_callback1_pos.1,
+                        winapi::ucrtbase::_local_unwind2_pos.0,
+                        winapi::ucrtbase::_local_unwind2_pos.1,
                         &result,
                     );
                 }
                 result.to_raw()
             })
         }
-    }
-    const SHIMS: [Shim; 1usize] = [Shim {
-        name: "retrowin32_test_callback1",
-        func: Handler::Async(wrappers::retrowin32_test_callback1),
-    }];
-    pub const DLL: BuiltinDLL = BuiltinDLL {
-        file_name: "retrowin32_test.dll",
-        shims: &SHIMS,
-        raw: std::include_bytes!("../../dll/retrowin32_test.dll"),
-    };
-}
-pub mod ucrtbase {
-    use super::*;
-    mod wrappers {
-        use crate::{
-            machine::Machine,
-            winapi::{self, stack_args::*, types::*},
-        };
-        use memory::Extensions;
-        use winapi::ucrtbase::*;
-        pub unsafe fn _XcptFilter(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn _lock(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let xcptnum = <u32>::from_stack(mem, stack_args + 0u32);
-            let pxcptinfoptrs = <u32>::from_stack(mem, stack_args + 4u32);
+            let locknum = <u32>::from_stack(mem, stack_args + 0u32);
             let __trace_context = if crate::trace::enabled("ucrtbase") {
                 Some(crate::trace::trace_begin(
                     "ucrtbase",
-                    "_XcptFilter",
-                    &[("xcptnum", &xcptnum), ("pxcptinfoptrs", &pxcptinfoptrs)],
+                    "_lock",
+                    &[("locknum", &locknum)],
                 ))
             } else {
                 None
             };
-            let result = winapi::ucrtbase::_XcptFilter(machine, xcptnum, pxcptinfoptrs);
+            let result = winapi::ucrtbase::_lock(machine, locknum);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::ucrtbase::_XcptFilter_pos.0,
-                    winapi::ucrtbase::_XcptFilter_pos.1,
+                    winapi::ucrtbase::_lock_pos.0,
+                    winapi::ucrtbase::_lock_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn __dllonexit(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn _set_app_type(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let func = <u32>::from_stack(mem, stack_args + 0u32);
-            let d = <u32>::from_stack(mem, stack_args + 4u32);
-            let f = <u32>::from_stack(mem, stack_args + 8u32);
+            let _app_type = <u32>::from_stack(mem, stack_args + 0u32);
             let __trace_context = if crate::trace::enabled("ucrtbase") {
                 Some(crate::trace::trace_begin(
                     "ucrtbase",
-                    "__dllonexit",
-                    &[("func", &func), ("d", &d), ("f", &f)],
+                    "_set_app_type",
+                    &[("app_type", &_app_type)],
                 ))
             } else {
                 None
             };
-            let result = winapi::ucrtbase::__dllonexit(machine, func, d, f);
+            let result = winapi::ucrtbase::_set_app_type(machine, _app_type);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::ucrtbase::__dllonexit_pos.0,
-                    winapi::ucrtbase::__dllonexit_pos.1,
+                    winapi::ucrtbase::_set_app_type_pos.0,
+                    winapi::ucrtbase::_set_app_type_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn __getmainargs(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn _set_fmode(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let argc = <Option<&mut u32>>::from_stack(mem, stack_args + 0u32);
-            let argv = <Option<&mut u32>>::from_stack(mem, stack_args + 4u32);
-            let env = <Option<&mut u32>>::from_stack(mem, stack_args + 8u32);
-            let doWildCard = <u32>::from_stack(mem, stack_args + 12u32);
-            let startInfo = <u32>::from_stack(mem, stack_args + 16u32);
+            let _mode = <u32>::from_stack(mem, stack_args + 0u32);
             let __trace_context = if crate::trace::enabled("ucrtbase") {
                 Some(crate::trace::trace_begin(
                     "ucrtbase",
-                    "__getmainargs",
-                    &[
-                        ("argc", &argc),
-                        ("argv", &argv),
-                        ("env", &env),
-                        ("doWildCard", &doWildCard),
-                        ("startInfo", &startInfo),
-                    ],
+                    "_set_fmode",
+                    &[("mode", &_mode)],
                 ))
             } else {
                 None
             };
-            let result =
-                winapi::ucrtbase::__getmainargs(machine, argc, argv, env, doWildCard, startInfo);
+            let result = winapi::ucrtbase::_set_fmode(machine, _mode);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::ucrtbase::__getmainargs_pos.0,
-                    winapi::ucrtbase::__getmainargs_pos.1,
+                    winapi::ucrtbase::_set_fmode_pos.0,
+                    winapi::ucrtbase::_set_fmode_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn __p___argc(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn _set_new_mode(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
+            let newhandlermode = <u32>::from_stack(mem, stack_args + 0u32);
             let __trace_context = if crate::trace::enabled("ucrtbase") {
-                Some(crate::trace::trace_begin("ucrtbase", "__p___argc", &[]))
+                Some(crate::trace::trace_begin(
+                    "ucrtbase",
+                    "_set_new_mode",
+                    &[("newhandlermode", &newhandlermode)],
+                ))
             } else {
                 None
             };
-            let result = winapi::ucrtbase::__p___argc(machine);
+            let result = winapi::ucrtbase::_set_new_mode(machine, newhandlermode);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::ucrtbase::__p___argc_pos.0,
-                    winapi::ucrtbase::__p___argc_pos.1,
+                    winapi::ucrtbase::_set_new_mode_pos.0,
+                    winapi::ucrtbase::_set_new_mode_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn __p___argv(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn _snprintf(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
+            let buffer = <u32>::from_stack(mem, stack_args + 0u32);
+            let count = <u32>::from_stack(mem, stack_args + 4u32);
+            let format = <Option<&CStr>>::from_stack(mem, stack_args + 8u32);
+            let args = <VarArgs>::from_stack(mem, stack_args + 12u32);
             let __trace_context = if crate::trace::enabled("ucrtbase") {
-                Some(crate::trace::trace_begin("ucrtbase", "__p___argv", &[]))
+                Some(crate::trace::trace_begin(
+                    "ucrtbase",
+                    "_snprintf",
+                    &[
+                        ("buffer", &buffer),
+                        ("count", &count),
+                        ("format", &format),
+                        ("args", &args),
+                    ],
+                ))
             } else {
                 None
             };
-            let result = winapi::ucrtbase::__p___argv(machine);
+            let result = winapi::ucrtbase::_snprintf(machine, buffer, count, format, args);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::ucrtbase::__p___argv_pos.0,
-                    winapi::ucrtbase::__p___argv_pos.1,
+                    winapi::ucrtbase::_snprintf_pos.0,
+                    winapi::ucrtbase::_snprintf_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn __p__commode(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn _statusfp(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
             let __trace_context = if crate::trace::enabled("ucrtbase") {
-                Some(crate::trace::trace_begin("ucrtbase", "__p__commode", &[]))
+                Some(crate::trace::trace_begin("ucrtbase", "_statusfp", &[]))
             } else {
                 None
             };
-            let result = winapi::ucrtbase::__p__commode(machine);
+            let result = winapi::ucrtbase::_statusfp(machine);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::ucrtbase::__p__commode_pos.0,
-                    winapi::ucrtbase::__p__commode_pos.1,
+                    winapi::ucrtbase::_statusfp_pos.0,
+                    winapi::ucrtbase::_statusfp_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn __p__fmode(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn _time64(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
+            let destTime = <Option<&mut u64>>::from_stack(mem, stack_args + 0u32);
             let __trace_context = if crate::trace::enabled("ucrtbase") {
-                Some(crate::trace::trace_begin("ucrtbase", "__p__fmode", &[]))
+                Some(crate::trace::trace_begin(
+                    "ucrtbase",
+                    "_time64",
+                    &[("destTime", &destTime)],
+                ))
             } else {
                 None
             };
-            let result = winapi::ucrtbase::__p__fmode(machine);
+            let result = winapi::ucrtbase::_time64(machine, destTime);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::ucrtbase::_time64_pos.0,
+                    winapi::ucrtbase::_time64_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn _unlock(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let locknum = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("ucrtbase") {
+                Some(crate::trace::trace_begin(
+                    "ucrtbase",
+                    "_unlock",
+                    &[("locknum", &locknum)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::ucrtbase::_unlock(machine, locknum);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::ucrtbase::__p__fmode_pos.0,
-                    winapi::ucrtbase::__p__fmode_pos.1,
+                    winapi::ucrtbase::_unlock_pos.0,
+                    winapi::ucrtbase::_unlock_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn __set_app_type(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn _vsnprintf(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let _app_type = <u32>::from_stack(mem, stack_args + 0u32);
+            let buffer = <u32>::from_stack(mem, stack_args + 0u32);
+            let count = <u32>::from_stack(mem, stack_args + 4u32);
+            let format = <Option<&CStr>>::from_stack(mem, stack_args + 8u32);
+            let arglist = <u32>::from_stack(mem, stack_args + 12u32);
             let __trace_context = if crate::trace::enabled("ucrtbase") {
                 Some(crate::trace::trace_begin(
                     "ucrtbase",
-                    "__set_app_type",
-                    &[("app_type", &_app_type)],
+                    "_vsnprintf",
+                    &[
+                        ("buffer", &buffer),
+                        ("count", &count),
+                        ("format", &format),
+                        ("arglist", &arglist),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::ucrtbase::__set_app_type(machine, _app_type);
+            let result = winapi::ucrtbase::_vsnprintf(machine, buffer, count, format, arglist);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::ucrtbase::__set_app_type_pos.0,
-                    winapi::ucrtbase::__set_app_type_pos.1,
+                    winapi::ucrtbase::_vsnprintf_pos.0,
+                    winapi::ucrtbase::_vsnprintf_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn __setusermatherr(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn exit(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let pf = <u32>::from_stack(mem, stack_args + 0u32);
+            let status = <u32>::from_stack(mem, stack_args + 0u32);
             let __trace_context = if crate::trace::enabled("ucrtbase") {
                 Some(crate::trace::trace_begin(
                     "ucrtbase",
-                    "__setusermatherr",
-                    &[("pf", &pf)],
+                    "exit",
+                    &[("status", &status)],
                 ))
             } else {
                 None
             };
-            let result = winapi::ucrtbase::__setusermatherr(machine, pf);
+            let result = winapi::ucrtbase::exit(machine, status);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::ucrtbase::__setusermatherr_pos.0,
-                    winapi::ucrtbase::__setusermatherr_pos.1,
+                    winapi::ucrtbase::exit_pos.0,
+                    winapi::ucrtbase::exit_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn _configthreadlocale(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn free(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let per_thread_locale_type = <i32>::from_stack(mem, stack_args + 0u32);
+            let ptr = <u32>::from_stack(mem, stack_args + 0u32);
             let __trace_context = if crate::trace::enabled("ucrtbase") {
                 Some(crate::trace::trace_begin(
                     "ucrtbase",
-                    "_configthreadlocale",
-                    &[("per_thread_locale_type", &per_thread_locale_type)],
+                    "free",
+                    &[("ptr", &ptr)],
                 ))
             } else {
                 None
             };
-            let result = winapi::ucrtbase::_configthreadlocale(machine, per_thread_locale_type);
+            let result = winapi::ucrtbase::free(machine, ptr);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::ucrtbase::_configthreadlocale_pos.0,
-                    winapi::ucrtbase::_configthreadlocale_pos.1,
+                    winapi::ucrtbase::free_pos.0,
+                    winapi::ucrtbase::free_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn _configure_narrow_argv(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn malloc(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let _mode = <u32>::from_stack(mem, stack_args + 0u32);
+            let size = <u32>::from_stack(mem, stack_args + 0u32);
             let __trace_context = if crate::trace::enabled("ucrtbase") {
                 Some(crate::trace::trace_begin(
                     "ucrtbase",
-                    "_configure_narrow_argv",
-                    &[("mode", &_mode)],
+                    "malloc",
+                    &[("size", &size)],
                 ))
             } else {
                 None
             };
-            let result = winapi::ucrtbase::_configure_narrow_argv(machine, _mode);
+            let result = winapi::ucrtbase::malloc(machine, size);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::ucrtbase::_configure_narrow_argv_pos.0,
-                    winapi::ucrtbase::_configure_narrow_argv_pos.1,
+                    winapi::ucrtbase::malloc_pos.0,
+                    winapi::ucrtbase::malloc_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn _controlfp(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn rand(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let _new = <u32>::from_stack(mem, stack_args + 0u32);
-            let _mask = <u32>::from_stack(mem, stack_args + 4u32);
             let __trace_context = if crate::trace::enabled("ucrtbase") {
-                Some(crate::trace::trace_begin(
-                    "ucrtbase",
-                    "_controlfp",
-                    &[("new", &_new), ("mask", &_mask)],
-                ))
+                Some(crate::trace::trace_begin("ucrtbase", "rand", &[]))
             } else {
                 None
             };
-            let result = winapi::ucrtbase::_controlfp(machine, _new, _mask);
+            let result = winapi::ucrtbase::rand(machine);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::ucrtbase::_controlfp_pos.0,
-                    winapi::ucrtbase::_controlfp_pos.1,
+                    winapi::ucrtbase::rand_pos.0,
+                    winapi::ucrtbase::rand_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn _controlfp_s(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn sprintf(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let _currentControl = <u32>::from_stack(mem, stack_args + 0u32);
-            let _newControl = <u32>::from_stack(mem, stack_args + 4u32);
-            let _mask = <u32>::from_stack(mem, stack_args + 8u32);
+            let buffer = <u32>::from_stack(mem, stack_args + 0u32);
+            let format = <Option<&CStr>>::from_stack(mem, stack_args + 4u32);
+            let args = <VarArgs>::from_stack(mem, stack_args + 8u32);
             let __trace_context = if crate::trace::enabled("ucrtbase") {
                 Some(crate::trace::trace_begin(
                     "ucrtbase",
-                    "_controlfp_s",
-                    &[
-                        ("currentControl", &_currentControl),
-                        ("newControl", &_newControl),
-                        ("mask", &_mask),
-                    ],
+                    "sprintf",
+                    &[("buffer", &buffer), ("format", &format), ("args", &args)],
                 ))
             } else {
                 None
             };
-            let result =
-                winapi::ucrtbase::_controlfp_s(machine, _currentControl, _newControl, _mask);
+            let result = winapi::ucrtbase::sprintf(machine, buffer, format, args);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::ucrtbase::_controlfp_s_pos.0,
-                    winapi::ucrtbase::_controlfp_s_pos.1,
+                    winapi::ucrtbase::sprintf_pos.0,
+                    winapi::ucrtbase::sprintf_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn _crt_atexit(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn srand(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let _function = <u32>::from_stack(mem, stack_args + 0u32);
+            let seed = <u32>::from_stack(mem, stack_args + 0u32);
             let __trace_context = if crate::trace::enabled("ucrtbase") {
                 Some(crate::trace::trace_begin(
                     "ucrtbase",
-                    "_crt_atexit",
-                    &[("function", &_function)],
+                    "srand",
+                    &[("seed", &seed)],
                 ))
             } else {
                 None
             };
-            let result = winapi::ucrtbase::_crt_atexit(machine, _function);
+            let result = winapi::ucrtbase::srand(machine, seed);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::ucrtbase::_crt_atexit_pos.0,
-                    winapi::ucrtbase::_crt_atexit_pos.1,
+                    winapi::ucrtbase::srand_pos.0,
+                    winapi::ucrtbase::srand_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn _except_handler3(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn time(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let exception_record = <u32>::from_stack(mem, stack_args + 0u32);
-            let registration = <u32>::from_stack(mem, stack_args + 4u32);
-            let context = <u32>::from_stack(mem, stack_args + 8u32);
-            let dispatcher = <u32>::from_stack(mem, stack_args + 12u32);
+            let destTime = <Option<&mut u64>>::from_stack(mem, stack_args + 0u32);
             let __trace_context = if crate::trace::enabled("ucrtbase") {
                 Some(crate::trace::trace_begin(
                     "ucrtbase",
-                    "_except_handler3",
-                    &[
-                        ("exception_record", &exception_record),
-                        ("registration", &registration),
-                        ("context", &context),
-                        ("dispatcher", &dispatcher),
-                    ],
+                    "time",
+                    &[("destTime", &destTime)],
                 ))
             } else {
                 None
             };
-            let result = winapi::ucrtbase::_except_handler3(
-                machine,
-                exception_record,
-                registration,
-                context,
-                dispatcher,
-            );
+            let result = winapi::ucrtbase::time(machine, destTime);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::ucrtbase::_except_handler3_pos.0,
-                    winapi::ucrtbase::_except_handler3_pos.1,
+                    winapi::ucrtbase::time_pos.0,
+                    winapi::ucrtbase::time_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn _exit(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn vsprintf(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let status = <u32>::from_stack(mem, stack_args + 0u32);
+            let buffer = <u32>::from_stack(mem, stack_args + 0u32);
+            let format = <Option<&CStr>>::from_stack(mem, stack_args + 4u32);
+            let arglist = <u32>::from_stack(mem, stack_args + 8u32);
             let __trace_context = if crate::trace::enabled("ucrtbase") {
                 Some(crate::trace::trace_begin(
                     "ucrtbase",
-                    "_exit",
-                    &[("status", &status)],
+                    "vsprintf",
+                    &[
+                        ("buffer", &buffer),
+                        ("format", &format),
+                        ("arglist", &arglist),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::ucrtbase::_exit(machine, status);
+            let result = winapi::ucrtbase::vsprintf(machine, buffer, format, arglist);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::ucrtbase::_exit_pos.0,
-                    winapi::ucrtbase::_exit_pos.1,
+                    winapi::ucrtbase::vsprintf_pos.0,
+                    winapi::ucrtbase::vsprintf_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn _get_initial_narrow_environment(
+    }
+    const SHIMS: [Shim; 46usize] = [
+        Shim {
+            name: "_XcptFilter",
+            func: Handler::Sync(wrappers::_XcptFilter),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "__control87_2",
+            func: Handler::Sync(wrappers::__control87_2),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "__dllonexit",
+            func: Handler::Sync(wrappers::__dllonexit),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "__getmainargs",
+            func: Handler::Sync(wrappers::__getmainargs),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "__p___argc",
+            func: Handler::Sync(wrappers::__p___argc),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "__p___argv",
+            func: Handler::Sync(wrappers::__p___argv),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "__p__commode",
+            func: Handler::Sync(wrappers::__p__commode),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "__p__fmode",
+            func: Handler::Sync(wrappers::__p__fmode),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "__set_app_type",
+            func: Handler::Sync(wrappers::__set_app_type),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "__setusermatherr",
+            func: Handler::Sync(wrappers::__setusermatherr),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "__stdio_common_vsprintf",
+            func: Handler::Sync(wrappers::__stdio_common_vsprintf),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "__stdio_common_vswprintf",
+            func: Handler::Sync(wrappers::__stdio_common_vswprintf),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "_clearfp",
+            func: Handler::Sync(wrappers::_clearfp),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "_configthreadlocale",
+            func: Handler::Sync(wrappers::_configthreadlocale),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "_configure_narrow_argv",
+            func: Handler::Sync(wrappers::_configure_narrow_argv),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "_control87",
+            func: Handler::Sync(wrappers::_control87),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "_controlfp",
+            func: Handler::Sync(wrappers::_controlfp),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "_controlfp_s",
+            func: Handler::Sync(wrappers::_controlfp_s),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "_crt_atexit",
+            func: Handler::Sync(wrappers::_crt_atexit),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "_errno",
+            func: Handler::Sync(wrappers::_errno),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "_except_handler3",
+            func: Handler::Async(wrappers::_except_handler3),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "_exit",
+            func: Handler::Sync(wrappers::_exit),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "_fpreset",
+            func: Handler::Sync(wrappers::_fpreset),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "_get_initial_narrow_environment",
+            func: Handler::Sync(wrappers::_get_initial_narrow_environment),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "_global_unwind2",
+            func: Handler::Async(wrappers::_global_unwind2),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "_initialize_narrow_environment",
+            func: Handler::Sync(wrappers::_initialize_narrow_environment),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "_initterm",
+            func: Handler::Async(wrappers::_initterm),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "_initterm_e",
+            func: Handler::Async(wrappers::_initterm_e),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "_local_unwind2",
+            func: Handler::Async(wrappers::_local_unwind2),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "_lock",
+            func: Handler::Sync(wrappers::_lock),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "_set_app_type",
+            func: Handler::Sync(wrappers::_set_app_type),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "_set_fmode",
+            func: Handler::Sync(wrappers::_set_fmode),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "_set_new_mode",
+            func: Handler::Sync(wrappers::_set_new_mode),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "_snprintf",
+            func: Handler::Sync(wrappers::_snprintf),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "_statusfp",
+            func: Handler::Sync(wrappers::_statusfp),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "_time64",
+            func: Handler::Sync(wrappers::_time64),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "_unlock",
+            func: Handler::Sync(wrappers::_unlock),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "_vsnprintf",
+            func: Handler::Sync(wrappers::_vsnprintf),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "exit",
+            func: Handler::Sync(wrappers::exit),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "free",
+            func: Handler::Sync(wrappers::free),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "malloc",
+            func: Handler::Sync(wrappers::malloc),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "rand",
+            func: Handler::Sync(wrappers::rand),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "sprintf",
+            func: Handler::Sync(wrappers::sprintf),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "srand",
+            func: Handler::Sync(wrappers::srand),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "time",
+            func: Handler::Sync(wrappers::time),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "vsprintf",
+            func: Handler::Sync(wrappers::vsprintf),
+            stack_consumed: 0u32,
+        },
+    ];
+    pub const DLL: BuiltinDLL = BuiltinDLL {
+        file_name: "ucrtbase.dll",
+        shims: &SHIMS,
+        raw: std::include_bytes!("../../dll/ucrtbase.dll"),
+    };
+}
+pub mod vcruntime140 {
+    use super::*;
+    mod wrappers {
+        use crate::{
+            machine::Machine,
+            winapi::{self, stack_args::*, types::*},
+        };
+        use memory::Extensions;
+        use winapi::vcruntime140::*;
+        pub unsafe fn _CxxThrowException(
             machine: &mut Machine,
             stack_args: u32,
-        ) -> u32 {
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
             let mem = machine.mem().detach();
-            let __trace_context = if crate::trace::enabled("ucrtbase") {
+            let pExceptionObject = <u32>::from_stack(mem, stack_args + 0u32);
+            let pThrowInfo = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("vcruntime140") {
                 Some(crate::trace::trace_begin(
-                    "ucrtbase",
-                    "_get_initial_narrow_environment",
-                    &[],
+                    "vcruntime140",
+                    "_CxxThrowException",
+                    &[
+                        ("pExceptionObject", &pExceptionObject),
+                        ("pThrowInfo", &pThrowInfo),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::ucrtbase::_get_initial_narrow_environment(machine);
-            if let Some(__trace_context) = __trace_context {
-                crate::trace::trace_return(
-                    &__trace_context,
-                    winapi::ucrtbase::_get_initial_narrow_environment_pos.0,
-                    winapi::ucrtbase::_get_initial_narrow_environment_pos.1,
-                    &result,
-                );
-            }
-            result.to_raw()
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result =
+                    winapi::vcruntime140::_CxxThrowException(machine, pExceptionObject, pThrowInfo)
+                        .await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::vcruntime140::_CxxThrowException_pos.0,
+                        winapi::vcruntime140::_CxxThrowException_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
         }
-        pub unsafe fn _initialize_narrow_environment(
+        pub unsafe fn __CxxFrameHandler(
             machine: &mut Machine,
             stack_args: u32,
-        ) -> u32 {
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
             let mem = machine.mem().detach();
-            let __trace_context = if crate::trace::enabled("ucrtbase") {
+            let pExcept = <u32>::from_stack(mem, stack_args + 0u32);
+            let pRN = <u32>::from_stack(mem, stack_args + 4u32);
+            let _pContext = <u32>::from_stack(mem, stack_args + 8u32);
+            let _pDC = <u32>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("vcruntime140") {
                 Some(crate::trace::trace_begin(
-                    "ucrtbase",
-                    "_initialize_narrow_environment",
-                    &[],
+                    "vcruntime140",
+                    "__CxxFrameHandler",
+                    &[
+                        ("pExcept", &pExcept),
+                        ("pRN", &pRN),
+                        ("pContext", &_pContext),
+                        ("pDC", &_pDC),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::ucrtbase::_initialize_narrow_environment(machine);
-            if let Some(__trace_context) = __trace_context {
-                crate::trace::trace_return(
-                    &__trace_context,
-                    winapi::ucrtbase::_initialize_narrow_environment_pos.0,
-                    winapi::ucrtbase::_initialize_narrow_environment_pos.1,
-                    &result,
-                );
-            }
-            result.to_raw()
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result =
+                    winapi::vcruntime140::__CxxFrameHandler(machine, pExcept, pRN, _pContext, _pDC)
+                        .await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::vcruntime140::__CxxFrameHandler_pos.0,
+                        winapi::vcruntime140::__CxxFrameHandler_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
         }
-        pub unsafe fn _initterm(
+        pub unsafe fn __CxxFrameHandler3(
             machine: &mut Machine,
             stack_args: u32,
         ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
             let mem = machine.mem().detach();
-            let start = <u32>::from_stack(mem, stack_args + 0u32);
-            let end = <u32>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("ucrtbase") {
+            let pExcept = <u32>::from_stack(mem, stack_args + 0u32);
+            let pRN = <u32>::from_stack(mem, stack_args + 4u32);
+            let pContext = <u32>::from_stack(mem, stack_args + 8u32);
+            let pDC = <u32>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("vcruntime140") {
                 Some(crate::trace::trace_begin(
-                    "ucrtbase",
-                    "_initterm",
-                    &[("start", &start), ("end", &end)],
+                    "vcruntime140",
+                    "__CxxFrameHandler3",
+                    &[
+                        ("pExcept", &pExcept),
+                        ("pRN", &pRN),
+                        ("pContext", &pContext),
+                        ("pDC", &pDC),
+                    ],
                 ))
             } else {
                 None
@@ -10268,30 +17218,43 @@ pub mod ucrtbase {
             let machine: *mut Machine = machine;
             Box::pin(async move {
                 let machine = unsafe { &mut *machine };
-                let result = winapi::ucrtbase::_initterm(machine, start, end).await;
+                let result =
+                    winapi::vcruntime140::__CxxFrameHandler3(machine, pExcept, pRN, pContext, pDC)
+                        .await;
                 if let Some(__trace_context) = __trace_context {
                     crate::trace::trace_return(
                         &__trace_context,
-                        winapi::ucrtbase::_initterm_pos.0,
-                        winapi::ucrtbase::_initterm_pos.1,
+                        winapi::vcruntime140::__CxxFrameHandler3_pos.0,
+                        winapi::vcruntime140::__CxxFrameHandler3_pos.1,
                         &result,
                     );
                 }
                 result.to_raw()
             })
         }
-        pub unsafe fn _initterm_e(
+        pub unsafe fn _except_handler4_common(
             machine: &mut Machine,
             stack_args: u32,
         ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
             let mem = machine.mem().detach();
-            let start = <u32>::from_stack(mem, stack_args + 0u32);
-            let end = <u32>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("ucrtbase") {
+            let CookiePointer = <u32>::from_stack(mem, stack_args + 0u32);
+            let _CookieCheckFunction = <u32>::from_stack(mem, stack_args + 4u32);
+            let ExceptionRecord = <u32>::from_stack(mem, stack_args + 8u32);
+            let EstablisherFrame = <u32>::from_stack(mem, stack_args + 12u32);
+            let ContextRecord = <u32>::from_stack(mem, stack_args + 16u32);
+            let _DispatcherContext = <u32>::from_stack(mem, stack_args + 20u32);
+            let __trace_context = if crate::trace::enabled("vcruntime140") {
                 Some(crate::trace::trace_begin(
-                    "ucrtbase",
-                    "_initterm_e",
-                    &[("start", &start), ("end", &end)],
+                    "vcruntime140",
+                    "_except_handler4_common",
+                    &[
+                        ("CookiePointer", &CookiePointer),
+                        ("CookieCheckFunction", &_CookieCheckFunction),
+                        ("ExceptionRecord", &ExceptionRecord),
+                        ("EstablisherFrame", &EstablisherFrame),
+                        ("ContextRecord", &ContextRecord),
+                        ("DispatcherContext", &_DispatcherContext),
+                    ],
                 ))
             } else {
                 None
@@ -10299,731 +17262,736 @@ pub mod ucrtbase {
             let machine: *mut Machine = machine;
             Box::pin(async move {
                 let machine = unsafe { &mut *machine };
-                let result = winapi::ucrtbase::_initterm_e(machine, start, end).await;
+                let result = winapi::vcruntime140::_except_handler4_common(
+                    machine,
+                    CookiePointer,
+                    _CookieCheckFunction,
+                    ExceptionRecord,
+                    EstablisherFrame,
+                    ContextRecord,
+                    _DispatcherContext,
+                )
+                .await;
                 if let Some(__trace_context) = __trace_context {
                     crate::trace::trace_return(
                         &__trace_context,
-                        winapi::ucrtbase::_initterm_e_pos.0,
-                        winapi::ucrtbase::_initterm_e_pos.1,
+                        winapi::vcruntime140::_except_handler4_common_pos.0,
+                        winapi::vcruntime140::_except_handler4_common_pos.1,
                         &result,
                     );
                 }
                 result.to_raw()
             })
         }
-        pub unsafe fn _lock(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn memcmp(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lhs = <u32>::from_stack(mem, stack_args + 0u32);
+            let rhs = <u32>::from_stack(mem, stack_args + 4u32);
+            let len = <u32>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("vcruntime140") {
+                Some(crate::trace::trace_begin(
+                    "vcruntime140",
+                    "memcmp",
+                    &[("lhs", &lhs), ("rhs", &rhs), ("len", &len)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::vcruntime140::memcmp(machine, lhs, rhs, len);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::vcruntime140::memcmp_pos.0,
+                    winapi::vcruntime140::memcmp_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn memcpy(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let dst = <u32>::from_stack(mem, stack_args + 0u32);
+            let src = <u32>::from_stack(mem, stack_args + 4u32);
+            let len = <u32>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("vcruntime140") {
+                Some(crate::trace::trace_begin(
+                    "vcruntime140",
+                    "memcpy",
+                    &[("dst", &dst), ("src", &src), ("len", &len)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::vcruntime140::memcpy(machine, dst, src, len);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::vcruntime140::memcpy_pos.0,
+                    winapi::vcruntime140::memcpy_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn memset(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let dst = <u32>::from_stack(mem, stack_args + 0u32);
+            let val = <u32>::from_stack(mem, stack_args + 4u32);
+            let len = <u32>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("vcruntime140") {
+                Some(crate::trace::trace_begin(
+                    "vcruntime140",
+                    "memset",
+                    &[("dst", &dst), ("val", &val), ("len", &len)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::vcruntime140::memset(machine, dst, val, len);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::vcruntime140::memset_pos.0,
+                    winapi::vcruntime140::memset_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+    }
+    const SHIMS: [Shim; 7usize] = [
+        Shim {
+            name: "_CxxThrowException",
+            func: Handler::Async(wrappers::_CxxThrowException),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "__CxxFrameHandler",
+            func: Handler::Async(wrappers::__CxxFrameHandler),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "__CxxFrameHandler3",
+            func: Handler::Async(wrappers::__CxxFrameHandler3),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "_except_handler4_common",
+            func: Handler::Async(wrappers::_except_handler4_common),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "memcmp",
+            func: Handler::Sync(wrappers::memcmp),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "memcpy",
+            func: Handler::Sync(wrappers::memcpy),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "memset",
+            func: Handler::Sync(wrappers::memset),
+            stack_consumed: 0u32,
+        },
+    ];
+    pub const DLL: BuiltinDLL = BuiltinDLL {
+        file_name: "vcruntime140.dll",
+        shims: &SHIMS,
+        raw: std::include_bytes!("../../dll/vcruntime140.dll"),
+    };
+}
+pub mod version {
+    use super::*;
+    mod wrappers {
+        use crate::{
+            machine::Machine,
+            winapi::{self, stack_args::*, types::*},
+        };
+        use memory::Extensions;
+        use winapi::version::*;
+        pub unsafe fn GetFileVersionInfoSizeA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let locknum = <u32>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("ucrtbase") {
+            let lptstrFilename = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+            let lpdwHandle = <Option<&mut u32>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("version") {
                 Some(crate::trace::trace_begin(
-                    "ucrtbase",
-                    "_lock",
-                    &[("locknum", &locknum)],
+                    "version",
+                    "GetFileVersionInfoSizeA",
+                    &[
+                        ("lptstrFilename", &lptstrFilename),
+                        ("lpdwHandle", &lpdwHandle),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::ucrtbase::_lock(machine, locknum);
+            let result =
+                winapi::version::GetFileVersionInfoSizeA(machine, lptstrFilename, lpdwHandle);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::ucrtbase::_lock_pos.0,
-                    winapi::ucrtbase::_lock_pos.1,
+                    winapi::version::GetFileVersionInfoSizeA_pos.0,
+                    winapi::version::GetFileVersionInfoSizeA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn _set_app_type(machine: &mut Machine, stack_args: u32) -> u32 {
+    }
+    const SHIMS: [Shim; 1usize] = [Shim {
+        name: "GetFileVersionInfoSizeA",
+        func: Handler::Sync(wrappers::GetFileVersionInfoSizeA),
+        stack_consumed: 8u32,
+    }];
+    pub const DLL: BuiltinDLL = BuiltinDLL {
+        file_name: "version.dll",
+        shims: &SHIMS,
+        raw: std::include_bytes!("../../dll/version.dll"),
+    };
+}
+pub mod user32 {
+    use super::*;
+    mod wrappers {
+        use crate::{
+            machine::Machine,
+            winapi::{self, stack_args::*, types::*},
+        };
+        use memory::Extensions;
+        use winapi::user32::*;
+        pub unsafe fn AdjustWindowRect(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let _app_type = <u32>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("ucrtbase") {
+            let lpRect = <Option<&mut RECT>>::from_stack(mem, stack_args + 0u32);
+            let dwStyle = <Result<WindowStyle, u32>>::from_stack(mem, stack_args + 4u32);
+            let bMenu = <bool>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("user32/window") {
                 Some(crate::trace::trace_begin(
-                    "ucrtbase",
-                    "_set_app_type",
-                    &[("app_type", &_app_type)],
+                    "user32/window",
+                    "AdjustWindowRect",
+                    &[
+                        ("lpRect", &lpRect),
+                        ("dwStyle", &dwStyle),
+                        ("bMenu", &bMenu),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::ucrtbase::_set_app_type(machine, _app_type);
+            let result = winapi::user32::AdjustWindowRect(machine, lpRect, dwStyle, bMenu);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::ucrtbase::_set_app_type_pos.0,
-                    winapi::ucrtbase::_set_app_type_pos.1,
+                    winapi::user32::AdjustWindowRect_pos.0,
+                    winapi::user32::AdjustWindowRect_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn _set_fmode(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn AdjustWindowRectEx(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let _mode = <u32>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("ucrtbase") {
+            let lpRect = <Option<&mut RECT>>::from_stack(mem, stack_args + 0u32);
+            let dwStyle = <Result<WindowStyle, u32>>::from_stack(mem, stack_args + 4u32);
+            let bMenu = <bool>::from_stack(mem, stack_args + 8u32);
+            let dwExStyle = <Result<WindowStyleEx, u32>>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("user32/window") {
                 Some(crate::trace::trace_begin(
-                    "ucrtbase",
-                    "_set_fmode",
-                    &[("mode", &_mode)],
+                    "user32/window",
+                    "AdjustWindowRectEx",
+                    &[
+                        ("lpRect", &lpRect),
+                        ("dwStyle", &dwStyle),
+                        ("bMenu", &bMenu),
+                        ("dwExStyle", &dwExStyle),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::ucrtbase::_set_fmode(machine, _mode);
+            let result =
+                winapi::user32::AdjustWindowRectEx(machine, lpRect, dwStyle, bMenu, dwExStyle);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::ucrtbase::_set_fmode_pos.0,
-                    winapi::ucrtbase::_set_fmode_pos.1,
+                    winapi::user32::AdjustWindowRectEx_pos.0,
+                    winapi::user32::AdjustWindowRectEx_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn _set_new_mode(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn AppendMenuA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let newhandlermode = <u32>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("ucrtbase") {
+            let hMenu = <HMENU>::from_stack(mem, stack_args + 0u32);
+            let uFlags = <u32>::from_stack(mem, stack_args + 4u32);
+            let uIDNewItem = <u32>::from_stack(mem, stack_args + 8u32);
+            let lpNewItem = <Option<&str>>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("user32/menu") {
                 Some(crate::trace::trace_begin(
-                    "ucrtbase",
-                    "_set_new_mode",
-                    &[("newhandlermode", &newhandlermode)],
+                    "user32/menu",
+                    "AppendMenuA",
+                    &[
+                        ("hMenu", &hMenu),
+                        ("uFlags", &uFlags),
+                        ("uIDNewItem", &uIDNewItem),
+                        ("lpNewItem", &lpNewItem),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::ucrtbase::_set_new_mode(machine, newhandlermode);
+            let result = winapi::user32::AppendMenuA(machine, hMenu, uFlags, uIDNewItem, lpNewItem);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::ucrtbase::_set_new_mode_pos.0,
-                    winapi::ucrtbase::_set_new_mode_pos.1,
+                    winapi::user32::AppendMenuA_pos.0,
+                    winapi::user32::AppendMenuA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn _time64(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn BeginPaint(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
             let mem = machine.mem().detach();
-            let destTime = <Option<&mut u64>>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("ucrtbase") {
+            let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+            let lpPaint = <Option<&mut PAINTSTRUCT>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("user32/paint") {
                 Some(crate::trace::trace_begin(
-                    "ucrtbase",
-                    "_time64",
-                    &[("destTime", &destTime)],
+                    "user32/paint",
+                    "BeginPaint",
+                    &[("hWnd", &hWnd), ("lpPaint", &lpPaint)],
                 ))
             } else {
                 None
             };
-            let result = winapi::ucrtbase::_time64(machine, destTime);
-            if let Some(__trace_context) = __trace_context {
-                crate::trace::trace_return(
-                    &__trace_context,
-                    winapi::ucrtbase::_time64_pos.0,
-                    winapi::ucrtbase::_time64_pos.1,
-                    &result,
-                );
-            }
-            result.to_raw()
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::user32::BeginPaint(machine, hWnd, lpPaint).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::user32::BeginPaint_pos.0,
+                        winapi::user32::BeginPaint_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
         }
-        pub unsafe fn _unlock(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CallNextHookEx(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
             let mem = machine.mem().detach();
-            let locknum = <u32>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("ucrtbase") {
+            let hhk = <HHOOK>::from_stack(mem, stack_args + 0u32);
+            let nCode = <i32>::from_stack(mem, stack_args + 4u32);
+            let wParam = <u32>::from_stack(mem, stack_args + 8u32);
+            let lParam = <u32>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("user32/hook") {
                 Some(crate::trace::trace_begin(
-                    "ucrtbase",
-                    "_unlock",
-                    &[("locknum", &locknum)],
+                    "user32/hook",
+                    "CallNextHookEx",
+                    &[
+                        ("hhk", &hhk),
+                        ("nCode", &nCode),
+                        ("wParam", &wParam),
+                        ("lParam", &lParam),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::ucrtbase::_unlock(machine, locknum);
-            if let Some(__trace_context) = __trace_context {
-                crate::trace::trace_return(
-                    &__trace_context,
-                    winapi::ucrtbase::_unlock_pos.0,
-                    winapi::ucrtbase::_unlock_pos.1,
-                    &result,
-                );
-            }
-            result.to_raw()
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result =
+                    winapi::user32::CallNextHookEx(machine, hhk, nCode, wParam, lParam).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::user32::CallNextHookEx_pos.0,
+                        winapi::user32::CallNextHookEx_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
         }
-        pub unsafe fn exit(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn ChangeDisplaySettingsA(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
             let mem = machine.mem().detach();
-            let status = <u32>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("ucrtbase") {
+            let lpDevMode = <Option<&DEVMODEA>>::from_stack(mem, stack_args + 0u32);
+            let dwFlags = <Result<CDS, u32>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("user32/display") {
                 Some(crate::trace::trace_begin(
-                    "ucrtbase",
-                    "exit",
-                    &[("status", &status)],
+                    "user32/display",
+                    "ChangeDisplaySettingsA",
+                    &[("lpDevMode", &lpDevMode), ("dwFlags", &dwFlags)],
                 ))
             } else {
                 None
             };
-            let result = winapi::ucrtbase::exit(machine, status);
-            if let Some(__trace_context) = __trace_context {
-                crate::trace::trace_return(
-                    &__trace_context,
-                    winapi::ucrtbase::exit_pos.0,
-                    winapi::ucrtbase::exit_pos.1,
-                    &result,
-                );
-            }
-            result.to_raw()
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result =
+                    winapi::user32::ChangeDisplaySettingsA(machine, lpDevMode, dwFlags).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::user32::ChangeDisplaySettingsA_pos.0,
+                        winapi::user32::ChangeDisplaySettingsA_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
         }
-        pub unsafe fn free(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CharLowerA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let ptr = <u32>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("ucrtbase") {
+            let lpsz = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("user32/string") {
                 Some(crate::trace::trace_begin(
-                    "ucrtbase",
-                    "free",
-                    &[("ptr", &ptr)],
+                    "user32/string",
+                    "CharLowerA",
+                    &[("lpsz", &lpsz)],
                 ))
             } else {
                 None
             };
-            let result = winapi::ucrtbase::free(machine, ptr);
+            let result = winapi::user32::CharLowerA(machine, lpsz);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::ucrtbase::free_pos.0,
-                    winapi::ucrtbase::free_pos.1,
+                    winapi::user32::CharLowerA_pos.0,
+                    winapi::user32::CharLowerA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn malloc(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CharLowerBuffA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let size = <u32>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("ucrtbase") {
+            let lpsz = <u32>::from_stack(mem, stack_args + 0u32);
+            let cchLength = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("user32/string") {
                 Some(crate::trace::trace_begin(
-                    "ucrtbase",
-                    "malloc",
-                    &[("size", &size)],
+                    "user32/string",
+                    "CharLowerBuffA",
+                    &[("lpsz", &lpsz), ("cchLength", &cchLength)],
                 ))
             } else {
                 None
             };
-            let result = winapi::ucrtbase::malloc(machine, size);
-            if let Some(__trace_context) = __trace_context {
-                crate::trace::trace_return(
-                    &__trace_context,
-                    winapi::ucrtbase::malloc_pos.0,
-                    winapi::ucrtbase::malloc_pos.1,
-                    &result,
-                );
-            }
-            result.to_raw()
-        }
-        pub unsafe fn rand(machine: &mut Machine, stack_args: u32) -> u32 {
-            let mem = machine.mem().detach();
-            let __trace_context = if crate::trace::enabled("ucrtbase") {
-                Some(crate::trace::trace_begin("ucrtbase", "rand", &[]))
-            } else {
-                None
-            };
-            let result = winapi::ucrtbase::rand(machine);
+            let result = winapi::user32::CharLowerBuffA(machine, lpsz, cchLength);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::ucrtbase::rand_pos.0,
-                    winapi::ucrtbase::rand_pos.1,
+                    winapi::user32::CharLowerBuffA_pos.0,
+                    winapi::user32::CharLowerBuffA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn srand(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CharLowerBuffW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let seed = <u32>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("ucrtbase") {
+            let lpsz = <u32>::from_stack(mem, stack_args + 0u32);
+            let cchLength = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("user32/string") {
                 Some(crate::trace::trace_begin(
-                    "ucrtbase",
-                    "srand",
-                    &[("seed", &seed)],
+                    "user32/string",
+                    "CharLowerBuffW",
+                    &[("lpsz", &lpsz), ("cchLength", &cchLength)],
                 ))
             } else {
                 None
             };
-            let result = winapi::ucrtbase::srand(machine, seed);
+            let result = winapi::user32::CharLowerBuffW(machine, lpsz, cchLength);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::ucrtbase::srand_pos.0,
-                    winapi::ucrtbase::srand_pos.1,
+                    winapi::user32::CharLowerBuffW_pos.0,
+                    winapi::user32::CharLowerBuffW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn time(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CharLowerW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let destTime = <Option<&mut u64>>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("ucrtbase") {
+            let lpsz = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("user32/string") {
                 Some(crate::trace::trace_begin(
-                    "ucrtbase",
-                    "time",
-                    &[("destTime", &destTime)],
+                    "user32/string",
+                    "CharLowerW",
+                    &[("lpsz", &lpsz)],
                 ))
             } else {
                 None
             };
-            let result = winapi::ucrtbase::time(machine, destTime);
+            let result = winapi::user32::CharLowerW(machine, lpsz);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::ucrtbase::time_pos.0,
-                    winapi::ucrtbase::time_pos.1,
+                    winapi::user32::CharLowerW_pos.0,
+                    winapi::user32::CharLowerW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-    }
-    const SHIMS: [Shim; 32usize] = [
-        Shim {
-            name: "_XcptFilter",
-            func: Handler::Sync(wrappers::_XcptFilter),
-        },
-        Shim {
-            name: "__dllonexit",
-            func: Handler::Sync(wrappers::__dllonexit),
-        },
-        Shim {
-            name: "__getmainargs",
-            func: Handler::Sync(wrappers::__getmainargs),
-        },
-        Shim {
-            name: "__p___argc",
-            func: Handler::Sync(wrappers::__p___argc),
-        },
-        Shim {
-            name: "__p___argv",
-            func: Handler::Sync(wrappers::__p___argv),
-        },
-        Shim {
-            name: "__p__commode",
-            func: Handler::Sync(wrappers::__p__commode),
-        },
-        Shim {
-            name: "__p__fmode",
-            func: Handler::Sync(wrappers::__p__fmode),
-        },
-        Shim {
-            name: "__set_app_type",
-            func: Handler::Sync(wrappers::__set_app_type),
-        },
-        Shim {
-            name: "__setusermatherr",
-            func: Handler::Sync(wrappers::__setusermatherr),
-        },
-        Shim {
-            name: "_configthreadlocale",
-            func: Handler::Sync(wrappers::_configthreadlocale),
-        },
-        Shim {
-            name: "_configure_narrow_argv",
-            func: Handler::Sync(wrappers::_configure_narrow_argv),
-        },
-        Shim {
-            name: "_controlfp",
-            func: Handler::Sync(wrappers::_controlfp),
-        },
-        Shim {
-            name: "_controlfp_s",
-            func: Handler::Sync(wrappers::_controlfp_s),
-        },
-        Shim {
-            name: "_crt_atexit",
-            func: Handler::Sync(wrappers::_crt_atexit),
-        },
-        Shim {
-            name: "_except_handler3",
-            func: Handler::Sync(wrappers::_except_handler3),
-        },
-        Shim {
-            name: "_exit",
-            func: Handler::Sync(wrappers::_exit),
-        },
-        Shim {
-            name: "_get_initial_narrow_environment",
-            func: Handler::Sync(wrappers::_get_initial_narrow_environment),
-        },
-        Shim {
-            name: "_initialize_narrow_environment",
-            func: Handler::Sync(wrappers::_initialize_narrow_environment),
-        },
-        Shim {
-            name: "_initterm",
-            func: Handler::Async(wrappers::_initterm),
-        },
-        Shim {
-            name: "_initterm_e",
-            func: Handler::Async(wrappers::_initterm_e),
-        },
-        Shim {
-            name: "_lock",
-            func: Handler::Sync(wrappers::_lock),
-        },
-        Shim {
-            name: "_set_app_type",
-            func: Handler::Sync(wrappers::_set_app_type),
-        },
-        Shim {
-            name: "_set_fmode",
-            func: Handler::Sync(wrappers::_set_fmode),
-        },
-        Shim {
-            name: "_set_new_mode",
-            func: Handler::Sync(wrappers::_set_new_mode),
-        },
-        Shim {
-            name: "_time64",
-            func: Handler::Sync(wrappers::_time64),
-        },
-        Shim {
-            name: "_unlock",
-            func: Handler::Sync(wrappers::_unlock),
-        },
-        Shim {
-            name: "exit",
-            func: Handler::Sync(wrappers::exit),
-        },
-        Shim {
-            name: "free",
-            func: Handler::Sync(wrappers::free),
-        },
-        Shim {
-            name: "malloc",
-            func: Handler::Sync(wrappers::malloc),
-        },
-        Shim {
-            name: "rand",
-            func: Handler::Sync(wrappers::rand),
-        },
-        Shim {
-            name: "srand",
-            func: Handler::Sync(wrappers::srand),
-        },
-        Shim {
-            name: "time",
-            func: Handler::Sync(wrappers::time),
-        },
-    ];
-    pub const DLL: BuiltinDLL = BuiltinDLL {
-        file_name: "ucrtbase.dll",
-        shims: &SHIMS,
-        raw: std::include_bytes!("../../dll/ucrtbase.dll"),
-    };
-}
-pub mod vcruntime140 {
-    use super::*;
-    mod wrappers {
-        use crate::{
-            machine::Machine,
-            winapi::{self, stack_args::*, types::*},
-        };
-        use memory::Extensions;
-        use winapi::vcruntime140::*;
-        pub unsafe fn _CxxThrowException(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CharNextA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let pExceptionObject = <u32>::from_stack(mem, stack_args + 0u32);
-            let pThrowInfo = <u32>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("vcruntime140") {
+            let lpsz = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("user32/string") {
                 Some(crate::trace::trace_begin(
-                    "vcruntime140",
-                    "_CxxThrowException",
-                    &[
-                        ("pExceptionObject", &pExceptionObject),
-                        ("pThrowInfo", &pThrowInfo),
-                    ],
+                    "user32/string",
+                    "CharNextA",
+                    &[("lpsz", &lpsz)],
                 ))
             } else {
                 None
             };
-            let result =
-                winapi::vcruntime140::_CxxThrowException(machine, pExceptionObject, pThrowInfo);
+            let result = winapi::user32::CharNextA(machine, lpsz);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::vcruntime140::_CxxThrowException_pos.0,
-                    winapi::vcruntime140::_CxxThrowException_pos.1,
+                    winapi::user32::CharNextA_pos.0,
+                    winapi::user32::CharNextA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn memcmp(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CharNextExA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lhs = <u32>::from_stack(mem, stack_args + 0u32);
-            let rhs = <u32>::from_stack(mem, stack_args + 4u32);
-            let len = <u32>::from_stack(mem, stack_args + 8u32);
-            let __trace_context = if crate::trace::enabled("vcruntime140") {
+            let CodePage = <u16>::from_stack(mem, stack_args + 0u32);
+            let lpCurrentChar = <u32>::from_stack(mem, stack_args + 4u32);
+            let dwFlags = <u32>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("user32/string") {
                 Some(crate::trace::trace_begin(
-                    "vcruntime140",
-                    "memcmp",
-                    &[("lhs", &lhs), ("rhs", &rhs), ("len", &len)],
+                    "user32/string",
+                    "CharNextExA",
+                    &[
+                        ("CodePage", &CodePage),
+                        ("lpCurrentChar", &lpCurrentChar),
+                        ("dwFlags", &dwFlags),
+                    ],
                 ))
             } else {
                 None
             };
-            let result = winapi::vcruntime140::memcmp(machine, lhs, rhs, len);
+            let result = winapi::user32::CharNextExA(machine, CodePage, lpCurrentChar, dwFlags);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::vcruntime140::memcmp_pos.0,
-                    winapi::vcruntime140::memcmp_pos.1,
+                    winapi::user32::CharNextExA_pos.0,
+                    winapi::user32::CharNextExA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn memcpy(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CharNextW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let dst = <u32>::from_stack(mem, stack_args + 0u32);
-            let src = <u32>::from_stack(mem, stack_args + 4u32);
-            let len = <u32>::from_stack(mem, stack_args + 8u32);
-            let __trace_context = if crate::trace::enabled("vcruntime140") {
+            let lpsz = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("user32/string") {
                 Some(crate::trace::trace_begin(
-                    "vcruntime140",
-                    "memcpy",
-                    &[("dst", &dst), ("src", &src), ("len", &len)],
+                    "user32/string",
+                    "CharNextW",
+                    &[("lpsz", &lpsz)],
                 ))
             } else {
                 None
             };
-            let result = winapi::vcruntime140::memcpy(machine, dst, src, len);
+            let result = winapi::user32::CharNextW(machine, lpsz);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::vcruntime140::memcpy_pos.0,
-                    winapi::vcruntime140::memcpy_pos.1,
+                    winapi::user32::CharNextW_pos.0,
+                    winapi::user32::CharNextW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn memset(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CharPrevA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let dst = <u32>::from_stack(mem, stack_args + 0u32);
-            let val = <u32>::from_stack(mem, stack_args + 4u32);
-            let len = <u32>::from_stack(mem, stack_args + 8u32);
-            let __trace_context = if crate::trace::enabled("vcruntime140") {
+            let lpszStart = <u32>::from_stack(mem, stack_args + 0u32);
+            let lpszCurrent = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("user32/string") {
                 Some(crate::trace::trace_begin(
-                    "vcruntime140",
-                    "memset",
-                    &[("dst", &dst), ("val", &val), ("len", &len)],
+                    "user32/string",
+                    "CharPrevA",
+                    &[("lpszStart", &lpszStart), ("lpszCurrent", &lpszCurrent)],
                 ))
             } else {
                 None
             };
-            let result = winapi::vcruntime140::memset(machine, dst, val, len);
+            let result = winapi::user32::CharPrevA(machine, lpszStart, lpszCurrent);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::vcruntime140::memset_pos.0,
-                    winapi::vcruntime140::memset_pos.1,
+                    winapi::user32::CharPrevA_pos.0,
+                    winapi::user32::CharPrevA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-    }
-    const SHIMS: [Shim; 4usize] = [
-        Shim {
-            name: "_CxxThrowException",
-            func: Handler::Sync(wrappers::_CxxThrowException),
-        },
-        Shim {
-            name: "memcmp",
-            func: Handler::Sync(wrappers::memcmp),
-        },
-        Shim {
-            name: "memcpy",
-            func: Handler::Sync(wrappers::memcpy),
-        },
-        Shim {
-            name: "memset",
-            func: Handler::Sync(wrappers::memset),
-        },
-    ];
-    pub const DLL: BuiltinDLL = BuiltinDLL {
-        file_name: "vcruntime140.dll",
-        shims: &SHIMS,
-        raw: std::include_bytes!("../../dll/vcruntime140.dll"),
-    };
-}
-pub mod version {
-    use super::*;
-    mod wrappers {
-        use crate::{
-            machine::Machine,
-            winapi::{self, stack_args::*, types::*},
-        };
-        use memory::Extensions;
-        use winapi::version::*;
-        pub unsafe fn GetFileVersionInfoSizeA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CharPrevExA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lptstrFilename = <Option<&str>>::from_stack(mem, stack_args + 0u32);
-            let lpdwHandle = <Option<&mut u32>>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("version") {
+            let CodePage = <u16>::from_stack(mem, stack_args + 0u32);
+            let lpStart = <u32>::from_stack(mem, stack_args + 4u32);
+            let lpCurrentChar = <u32>::from_stack(mem, stack_args + 8u32);
+            let dwFlags = <u32>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("user32/string") {
                 Some(crate::trace::trace_begin(
-                    "version",
-                    "GetFileVersionInfoSizeA",
-                    &[
-                        ("lptstrFilename", &lptstrFilename),
-                        ("lpdwHandle", &lpdwHandle),
+                    "user32/string",
+                    "CharPrevExA",
+                    &[
+                        ("CodePage", &CodePage),
+                        ("lpStart", &lpStart),
+                        ("lpCurrentChar", &lpCurrentChar),
+                        ("dwFlags", &dwFlags),
                     ],
                 ))
             } else {
                 None
             };
             let result =
-                winapi::version::GetFileVersionInfoSizeA(machine, lptstrFilename, lpdwHandle);
+                winapi::user32::CharPrevExA(machine, CodePage, lpStart, lpCurrentChar, dwFlags);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::version::GetFileVersionInfoSizeA_pos.0,
-                    winapi::version::GetFileVersionInfoSizeA_pos.1,
+                    winapi::user32::CharPrevExA_pos.0,
+                    winapi::user32::CharPrevExA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-    }
-    const SHIMS: [Shim; 1usize] = [Shim {
-        name: "GetFileVersionInfoSizeA",
-        func: Handler::Sync(wrappers::GetFileVersionInfoSizeA),
-    }];
-    pub const DLL: BuiltinDLL = BuiltinDLL {
-        file_name: "version.dll",
-        shims: &SHIMS,
-        raw: std::include_bytes!("../../dll/version.dll"),
-    };
-}
-pub mod user32 {
-    use super::*;
-    mod wrappers {
-        use crate::{
-            machine::Machine,
-            winapi::{self, stack_args::*, types::*},
-        };
-        use memory::Extensions;
-        use winapi::user32::*;
-        pub unsafe fn AdjustWindowRect(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CharPrevW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpRect = <Option<&mut RECT>>::from_stack(mem, stack_args + 0u32);
-            let dwStyle = <Result<WindowStyle, u32>>::from_stack(mem, stack_args + 4u32);
-            let bMenu = <bool>::from_stack(mem, stack_args + 8u32);
-            let __trace_context = if crate::trace::enabled("user32/window") {
+            let lpszStart = <u32>::from_stack(mem, stack_args + 0u32);
+            let lpszCurrent = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("user32/string") {
                 Some(crate::trace::trace_begin(
-                    "user32/window",
-                    "AdjustWindowRect",
-                    &[
-                        ("lpRect", &lpRect),
-                        ("dwStyle", &dwStyle),
-                        ("bMenu", &bMenu),
-                    ],
+                    "user32/string",
+                    "CharPrevW",
+                    &[("lpszStart", &lpszStart), ("lpszCurrent", &lpszCurrent)],
                 ))
             } else {
                 None
             };
-            let result = winapi::user32::AdjustWindowRect(machine, lpRect, dwStyle, bMenu);
+            let result = winapi::user32::CharPrevW(machine, lpszStart, lpszCurrent);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::user32::AdjustWindowRect_pos.0,
-                    winapi::user32::AdjustWindowRect_pos.1,
+                    winapi::user32::CharPrevW_pos.0,
+                    winapi::user32::CharPrevW_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn AdjustWindowRectEx(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CharUpperA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let lpRect = <Option<&mut RECT>>::from_stack(mem, stack_args + 0u32);
-            let dwStyle = <Result<WindowStyle, u32>>::from_stack(mem, stack_args + 4u32);
-            let bMenu = <bool>::from_stack(mem, stack_args + 8u32);
-            let dwExStyle = <Result<WindowStyleEx, u32>>::from_stack(mem, stack_args + 12u32);
-            let __trace_context = if crate::trace::enabled("user32/window") {
+            let lpsz = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("user32/string") {
                 Some(crate::trace::trace_begin(
-                    "user32/window",
-                    "AdjustWindowRectEx",
-                    &[
-                        ("lpRect", &lpRect),
-                        ("dwStyle", &dwStyle),
-                        ("bMenu", &bMenu),
-                        ("dwExStyle", &dwExStyle),
-                    ],
+                    "user32/string",
+                    "CharUpperA",
+                    &[("lpsz", &lpsz)],
                 ))
             } else {
                 None
             };
-            let result =
-                winapi::user32::AdjustWindowRectEx(machine, lpRect, dwStyle, bMenu, dwExStyle);
+            let result = winapi::user32::CharUpperA(machine, lpsz);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::user32::AdjustWindowRectEx_pos.0,
-                    winapi::user32::AdjustWindowRectEx_pos.1,
+                    winapi::user32::CharUpperA_pos.0,
+                    winapi::user32::CharUpperA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn AppendMenuA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CharUpperBuffA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hMenu = <HMENU>::from_stack(mem, stack_args + 0u32);
-            let uFlags = <u32>::from_stack(mem, stack_args + 4u32);
-            let uIDNewItem = <u32>::from_stack(mem, stack_args + 8u32);
-            let lpNewItem = <Option<&str>>::from_stack(mem, stack_args + 12u32);
-            let __trace_context = if crate::trace::enabled("user32/menu") {
+            let lpsz = <u32>::from_stack(mem, stack_args + 0u32);
+            let cchLength = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("user32/string") {
                 Some(crate::trace::trace_begin(
-                    "user32/menu",
-                    "AppendMenuA",
-                    &[
-                        ("hMenu", &hMenu),
-                        ("uFlags", &uFlags),
-                        ("uIDNewItem", &uIDNewItem),
-                        ("lpNewItem", &lpNewItem),
-                    ],
+                    "user32/string",
+                    "CharUpperBuffA",
+                    &[("lpsz", &lpsz), ("cchLength", &cchLength)],
                 ))
             } else {
                 None
             };
-            let result = winapi::user32::AppendMenuA(machine, hMenu, uFlags, uIDNewItem, lpNewItem);
+            let result = winapi::user32::CharUpperBuffA(machine, lpsz, cchLength);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::user32::AppendMenuA_pos.0,
-                    winapi::user32::AppendMenuA_pos.1,
+                    winapi::user32::CharUpperBuffA_pos.0,
+                    winapi::user32::CharUpperBuffA_pos.1,
                     &result,
                 );
             }
             result.to_raw()
         }
-        pub unsafe fn BeginPaint(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn CharUpperBuffW(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
-            let lpPaint = <Option<&mut PAINTSTRUCT>>::from_stack(mem, stack_args + 4u32);
-            let __trace_context = if crate::trace::enabled("user32/paint") {
+            let lpsz = <u32>::from_stack(mem, stack_args + 0u32);
+            let cchLength = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("user32/string") {
                 Some(crate::trace::trace_begin(
-                    "user32/paint",
-                    "BeginPaint",
-                    &[("hWnd", &hWnd), ("lpPaint", &lpPaint)],
+                    "user32/string",
+                    "CharUpperBuffW",
+                    &[("lpsz", &lpsz), ("cchLength", &cchLength)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::user32::CharUpperBuffW(machine, lpsz, cchLength);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::user32::CharUpperBuffW_pos.0,
+                    winapi::user32::CharUpperBuffW_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn CharUpperW(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpsz = <u32>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("user32/string") {
+                Some(crate::trace::trace_begin(
+                    "user32/string",
+                    "CharUpperW",
+                    &[("lpsz", &lpsz)],
                 ))
             } else {
                 None
             };
-            let result = winapi::user32::BeginPaint(machine, hWnd, lpPaint);
+            let result = winapi::user32::CharUpperW(machine, lpsz);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::user32::BeginPaint_pos.0,
-                    winapi::user32::BeginPaint_pos.1,
+                    winapi::user32::CharUpperW_pos.0,
+                    winapi::user32::CharUpperW_pos.1,
                     &result,
                 );
             }
@@ -11211,6 +18179,69 @@ pub mod user32 {
             }
             result.to_raw()
         }
+        pub unsafe fn CreateMDIWindowA(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let lpClassName = <u32>::from_stack(mem, stack_args + 0u32);
+            let lpWindowName = <u32>::from_stack(mem, stack_args + 4u32);
+            let dwStyle = <u32>::from_stack(mem, stack_args + 8u32);
+            let X = <u32>::from_stack(mem, stack_args + 12u32);
+            let Y = <u32>::from_stack(mem, stack_args + 16u32);
+            let nWidth = <u32>::from_stack(mem, stack_args + 20u32);
+            let nHeight = <u32>::from_stack(mem, stack_args + 24u32);
+            let hWndParent = <HWND>::from_stack(mem, stack_args + 28u32);
+            let hInstance = <u32>::from_stack(mem, stack_args + 32u32);
+            let lParam = <u32>::from_stack(mem, stack_args + 36u32);
+            let __trace_context = if crate::trace::enabled("user32/mdi") {
+                Some(crate::trace::trace_begin(
+                    "user32/mdi",
+                    "CreateMDIWindowA",
+                    &[
+                        ("lpClassName", &lpClassName),
+                        ("lpWindowName", &lpWindowName),
+                        ("dwStyle", &dwStyle),
+                        ("X", &X),
+                        ("Y", &Y),
+                        ("nWidth", &nWidth),
+                        ("nHeight", &nHeight),
+                        ("hWndParent", &hWndParent),
+                        ("hInstance", &hInstance),
+                        ("lParam", &lParam),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::user32::CreateMDIWindowA(
+                    machine,
+                    lpClassName,
+                    lpWindowName,
+                    dwStyle,
+                    X,
+                    Y,
+                    nWidth,
+                    nHeight,
+                    hWndParent,
+                    hInstance,
+                    lParam,
+                )
+                .await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::user32::CreateMDIWindowA_pos.0,
+                        winapi::user32::CreateMDIWindowA_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
         pub unsafe fn CreatePopupMenu(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
             let __trace_context = if crate::trace::enabled("user32/menu") {
@@ -11364,8 +18395,182 @@ pub mod user32 {
                 if let Some(__trace_context) = __trace_context {
                     crate::trace::trace_return(
                         &__trace_context,
-                        winapi::user32::CreateWindowExW_pos.0,
-                        winapi::user32::CreateWindowExW_pos.1,
+                        winapi::user32::CreateWindowExW_pos.0,
+                        winapi::user32::CreateWindowExW_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn DefFrameProcA(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+            let hWndMDIClient = <HWND>::from_stack(mem, stack_args + 4u32);
+            let uMsg = <Result<WM, u32>>::from_stack(mem, stack_args + 8u32);
+            let wParam = <u32>::from_stack(mem, stack_args + 12u32);
+            let lParam = <u32>::from_stack(mem, stack_args + 16u32);
+            let __trace_context = if crate::trace::enabled("user32/mdi") {
+                Some(crate::trace::trace_begin(
+                    "user32/mdi",
+                    "DefFrameProcA",
+                    &[
+                        ("hWnd", &hWnd),
+                        ("hWndMDIClient", &hWndMDIClient),
+                        ("uMsg", &uMsg),
+                        ("wParam", &wParam),
+                        ("lParam", &lParam),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::user32::DefFrameProcA(
+                    machine,
+                    hWnd,
+                    hWndMDIClient,
+                    uMsg,
+                    wParam,
+                    lParam,
+                )
+                .await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::user32::DefFrameProcA_pos.0,
+                        winapi::user32::DefFrameProcA_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn DefFrameProcW(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+            let hWndMDIClient = <HWND>::from_stack(mem, stack_args + 4u32);
+            let uMsg = <Result<WM, u32>>::from_stack(mem, stack_args + 8u32);
+            let wParam = <u32>::from_stack(mem, stack_args + 12u32);
+            let lParam = <u32>::from_stack(mem, stack_args + 16u32);
+            let __trace_context = if crate::trace::enabled("user32/mdi") {
+                Some(crate::trace::trace_begin(
+                    "user32/mdi",
+                    "DefFrameProcW",
+                    &[
+                        ("hWnd", &hWnd),
+                        ("hWndMDIClient", &hWndMDIClient),
+                        ("uMsg", &uMsg),
+                        ("wParam", &wParam),
+                        ("lParam", &lParam),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::user32::DefFrameProcW(
+                    machine,
+                    hWnd,
+                    hWndMDIClient,
+                    uMsg,
+                    wParam,
+                    lParam,
+                )
+                .await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::user32::DefFrameProcW_pos.0,
+                        winapi::user32::DefFrameProcW_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn DefMDIChildProcA(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+            let uMsg = <Result<WM, u32>>::from_stack(mem, stack_args + 4u32);
+            let wParam = <u32>::from_stack(mem, stack_args + 8u32);
+            let lParam = <u32>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("user32/mdi") {
+                Some(crate::trace::trace_begin(
+                    "user32/mdi",
+                    "DefMDIChildProcA",
+                    &[
+                        ("hWnd", &hWnd),
+                        ("uMsg", &uMsg),
+                        ("wParam", &wParam),
+                        ("lParam", &lParam),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result =
+                    winapi::user32::DefMDIChildProcA(machine, hWnd, uMsg, wParam, lParam).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::user32::DefMDIChildProcA_pos.0,
+                        winapi::user32::DefMDIChildProcA_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn DefMDIChildProcW(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+            let uMsg = <Result<WM, u32>>::from_stack(mem, stack_args + 4u32);
+            let wParam = <u32>::from_stack(mem, stack_args + 8u32);
+            let lParam = <u32>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("user32/mdi") {
+                Some(crate::trace::trace_begin(
+                    "user32/mdi",
+                    "DefMDIChildProcW",
+                    &[
+                        ("hWnd", &hWnd),
+                        ("uMsg", &uMsg),
+                        ("wParam", &wParam),
+                        ("lParam", &lParam),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result =
+                    winapi::user32::DefMDIChildProcW(machine, hWnd, uMsg, wParam, lParam).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::user32::DefMDIChildProcW_pos.0,
+                        winapi::user32::DefMDIChildProcW_pos.1,
                         &result,
                     );
                 }
@@ -11450,7 +18655,10 @@ pub mod user32 {
                 result.to_raw()
             })
         }
-        pub unsafe fn DestroyWindow(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn DestroyWindow(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
             let mem = machine.mem().detach();
             let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
             let __trace_context = if crate::trace::enabled("user32/window") {
@@ -11462,16 +18670,20 @@ pub mod user32 {
             } else {
                 None
             };
-            let result = winapi::user32::DestroyWindow(machine, hWnd);
-            if let Some(__trace_context) = __trace_context {
-                crate::trace::trace_return(
-                    &__trace_context,
-                    winapi::user32::DestroyWindow_pos.0,
-                    winapi::user32::DestroyWindow_pos.1,
-                    &result,
-                );
-            }
-            result.to_raw()
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::user32::DestroyWindow(machine, hWnd).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::user32::DestroyWindow_pos.0,
+                        winapi::user32::DestroyWindow_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
         }
         pub unsafe fn DialogBoxIndirectParamA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
@@ -11787,6 +18999,144 @@ pub mod user32 {
             }
             result.to_raw()
         }
+        pub unsafe fn EnumChildWindows(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let hWndParent = <HWND>::from_stack(mem, stack_args + 0u32);
+            let lpEnumFunc = <u32>::from_stack(mem, stack_args + 4u32);
+            let lParam = <u32>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("user32/window") {
+                Some(crate::trace::trace_begin(
+                    "user32/window",
+                    "EnumChildWindows",
+                    &[
+                        ("hWndParent", &hWndParent),
+                        ("lpEnumFunc", &lpEnumFunc),
+                        ("lParam", &lParam),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result =
+                    winapi::user32::EnumChildWindows(machine, hWndParent, lpEnumFunc, lParam).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::user32::EnumChildWindows_pos.0,
+                        winapi::user32::EnumChildWindows_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn EnumDisplayMonitors(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let hdc = <HDC>::from_stack(mem, stack_args + 0u32);
+            let lprcClip = <Option<&RECT>>::from_stack(mem, stack_args + 4u32);
+            let lpfnEnum = <u32>::from_stack(mem, stack_args + 8u32);
+            let dwData = <u32>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("user32/display") {
+                Some(crate::trace::trace_begin(
+                    "user32/display",
+                    "EnumDisplayMonitors",
+                    &[
+                        ("hdc", &hdc),
+                        ("lprcClip", &lprcClip),
+                        ("lpfnEnum", &lpfnEnum),
+                        ("dwData", &dwData),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result =
+                    winapi::user32::EnumDisplayMonitors(machine, hdc, lprcClip, lpfnEnum, dwData)
+                        .await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::user32::EnumDisplayMonitors_pos.0,
+                        winapi::user32::EnumDisplayMonitors_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn EnumDisplaySettingsA(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lpszDeviceName = <Option<&str>>::from_stack(mem, stack_args + 0u32);
+            let iModeNum = <u32>::from_stack(mem, stack_args + 4u32);
+            let lpDevMode = <Option<&mut DEVMODEA>>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("user32/display") {
+                Some(crate::trace::trace_begin(
+                    "user32/display",
+                    "EnumDisplaySettingsA",
+                    &[
+                        ("lpszDeviceName", &lpszDeviceName),
+                        ("iModeNum", &iModeNum),
+                        ("lpDevMode", &lpDevMode),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result =
+                winapi::user32::EnumDisplaySettingsA(machine, lpszDeviceName, iModeNum, lpDevMode);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::user32::EnumDisplaySettingsA_pos.0,
+                    winapi::user32::EnumDisplaySettingsA_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn EnumWindows(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let lpEnumFunc = <u32>::from_stack(mem, stack_args + 0u32);
+            let lParam = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("user32/window") {
+                Some(crate::trace::trace_begin(
+                    "user32/window",
+                    "EnumWindows",
+                    &[("lpEnumFunc", &lpEnumFunc), ("lParam", &lParam)],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::user32::EnumWindows(machine, lpEnumFunc, lParam).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::user32::EnumWindows_pos.0,
+                        winapi::user32::EnumWindows_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
         pub unsafe fn FillRect(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
             let hDC = <HDC>::from_stack(mem, stack_args + 0u32);
@@ -11866,9 +19216,9 @@ pub mod user32 {
         }
         pub unsafe fn GetActiveWindow(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let __trace_context = if crate::trace::enabled("user32/window") {
+            let __trace_context = if crate::trace::enabled("user32/focus") {
                 Some(crate::trace::trace_begin(
-                    "user32/window",
+                    "user32/focus",
                     "GetActiveWindow",
                     &[],
                 ))
@@ -12064,8 +19414,8 @@ pub mod user32 {
         }
         pub unsafe fn GetFocus(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let __trace_context = if crate::trace::enabled("user32/window") {
-                Some(crate::trace::trace_begin("user32/window", "GetFocus", &[]))
+            let __trace_context = if crate::trace::enabled("user32/focus") {
+                Some(crate::trace::trace_begin("user32/focus", "GetFocus", &[]))
             } else {
                 None
             };
@@ -12082,9 +19432,9 @@ pub mod user32 {
         }
         pub unsafe fn GetForegroundWindow(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
-            let __trace_context = if crate::trace::enabled("user32/window") {
+            let __trace_context = if crate::trace::enabled("user32/focus") {
                 Some(crate::trace::trace_begin(
-                    "user32/window",
+                    "user32/focus",
                     "GetForegroundWindow",
                     &[],
                 ))
@@ -12147,6 +19497,39 @@ pub mod user32 {
             }
             result.to_raw()
         }
+        pub unsafe fn GetLayeredWindowAttributes(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hwnd = <HWND>::from_stack(mem, stack_args + 0u32);
+            let pcrKey = <Option<&mut u32>>::from_stack(mem, stack_args + 4u32);
+            let pbAlpha = <Option<&mut u8>>::from_stack(mem, stack_args + 8u32);
+            let pdwFlags = <Option<&mut u32>>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("user32/window") {
+                Some(crate::trace::trace_begin(
+                    "user32/window",
+                    "GetLayeredWindowAttributes",
+                    &[
+                        ("hwnd", &hwnd),
+                        ("pcrKey", &pcrKey),
+                        ("pbAlpha", &pbAlpha),
+                        ("pdwFlags", &pdwFlags),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::user32::GetLayeredWindowAttributes(
+                machine, hwnd, pcrKey, pbAlpha, pdwFlags,
+            );
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::user32::GetLayeredWindowAttributes_pos.0,
+                    winapi::user32::GetLayeredWindowAttributes_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
         pub unsafe fn GetMenu(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
             let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
@@ -12281,6 +19664,54 @@ pub mod user32 {
                 result.to_raw()
             })
         }
+        pub unsafe fn GetMonitorInfoA(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hMonitor = <HMONITOR>::from_stack(mem, stack_args + 0u32);
+            let lpmi = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("user32/display") {
+                Some(crate::trace::trace_begin(
+                    "user32/display",
+                    "GetMonitorInfoA",
+                    &[("hMonitor", &hMonitor), ("lpmi", &lpmi)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::user32::GetMonitorInfoA(machine, hMonitor, lpmi);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::user32::GetMonitorInfoA_pos.0,
+                    winapi::user32::GetMonitorInfoA_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn GetMonitorInfoW(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hMonitor = <HMONITOR>::from_stack(mem, stack_args + 0u32);
+            let lpmi = <u32>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("user32/display") {
+                Some(crate::trace::trace_begin(
+                    "user32/display",
+                    "GetMonitorInfoW",
+                    &[("hMonitor", &hMonitor), ("lpmi", &lpmi)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::user32::GetMonitorInfoW(machine, hMonitor, lpmi);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::user32::GetMonitorInfoW_pos.0,
+                    winapi::user32::GetMonitorInfoW_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
         pub unsafe fn GetSubMenu(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
             let hMenu = <HMENU>::from_stack(mem, stack_args + 0u32);
@@ -12425,7 +19856,7 @@ pub mod user32 {
         pub unsafe fn GetWindowPlacement(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
             let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
-            let lpwndpl = <Option<&mut u32>>::from_stack(mem, stack_args + 4u32);
+            let lpwndpl = <Option<&mut WINDOWPLACEMENT>>::from_stack(mem, stack_args + 4u32);
             let __trace_context = if crate::trace::enabled("user32/window") {
                 Some(crate::trace::trace_begin(
                     "user32/window",
@@ -12459,12 +19890,116 @@ pub mod user32 {
             } else {
                 None
             };
-            let result = winapi::user32::GetWindowRect(machine, hWnd, lpRect);
+            let result = winapi::user32::GetWindowRect(machine, hWnd, lpRect);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::user32::GetWindowRect_pos.0,
+                    winapi::user32::GetWindowRect_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn GetWindowTextA(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+            let lpString = <u32>::from_stack(mem, stack_args + 4u32);
+            let nMaxCount = <i32>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("user32/window") {
+                Some(crate::trace::trace_begin(
+                    "user32/window",
+                    "GetWindowTextA",
+                    &[
+                        ("hWnd", &hWnd),
+                        ("lpString", &lpString),
+                        ("nMaxCount", &nMaxCount),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::user32::GetWindowTextA(machine, hWnd, lpString, nMaxCount);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::user32::GetWindowTextA_pos.0,
+                    winapi::user32::GetWindowTextA_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn GetWindowTextLengthA(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("user32/window") {
+                Some(crate::trace::trace_begin(
+                    "user32/window",
+                    "GetWindowTextLengthA",
+                    &[("hWnd", &hWnd)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::user32::GetWindowTextLengthA(machine, hWnd);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::user32::GetWindowTextLengthA_pos.0,
+                    winapi::user32::GetWindowTextLengthA_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn GetWindowTextLengthW(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("user32/window") {
+                Some(crate::trace::trace_begin(
+                    "user32/window",
+                    "GetWindowTextLengthW",
+                    &[("hWnd", &hWnd)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::user32::GetWindowTextLengthW(machine, hWnd);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::user32::GetWindowTextLengthW_pos.0,
+                    winapi::user32::GetWindowTextLengthW_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn GetWindowTextW(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+            let lpString = <u32>::from_stack(mem, stack_args + 4u32);
+            let nMaxCount = <i32>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("user32/window") {
+                Some(crate::trace::trace_begin(
+                    "user32/window",
+                    "GetWindowTextW",
+                    &[
+                        ("hWnd", &hWnd),
+                        ("lpString", &lpString),
+                        ("nMaxCount", &nMaxCount),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::user32::GetWindowTextW(machine, hWnd, lpString, nMaxCount);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::user32::GetWindowRect_pos.0,
-                    winapi::user32::GetWindowRect_pos.1,
+                    winapi::user32::GetWindowTextW_pos.0,
+                    winapi::user32::GetWindowTextW_pos.1,
                     &result,
                 );
             }
@@ -12668,6 +20203,29 @@ pub mod user32 {
             }
             result.to_raw()
         }
+        pub unsafe fn IsZoomed(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hwnd = <HWND>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("user32/misc") {
+                Some(crate::trace::trace_begin(
+                    "user32/misc",
+                    "IsZoomed",
+                    &[("hwnd", &hwnd)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::user32::IsZoomed(machine, hwnd);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::user32::IsZoomed_pos.0,
+                    winapi::user32::IsZoomed_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
         pub unsafe fn KillTimer(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
             let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
@@ -12958,8 +20516,7 @@ pub mod user32 {
             let mem = machine.mem().detach();
             let hInstance = <u32>::from_stack(mem, stack_args + 0u32);
             let uID = <u32>::from_stack(mem, stack_args + 4u32);
-            let lpBuffer = <u32>::from_stack(mem, stack_args + 8u32);
-            let cchBufferMax = <u32>::from_stack(mem, stack_args + 12u32);
+            let lpBuffer = <ArrayWithSizeMut<u8>>::from_stack(mem, stack_args + 8u32);
             let __trace_context = if crate::trace::enabled("user32/resource") {
                 Some(crate::trace::trace_begin(
                     "user32/resource",
@@ -12968,14 +20525,12 @@ pub mod user32 {
                         ("hInstance", &hInstance),
                         ("uID", &uID),
                         ("lpBuffer", &lpBuffer),
-                        ("cchBufferMax", &cchBufferMax),
                     ],
                 ))
             } else {
                 None
             };
-            let result =
-                winapi::user32::LoadStringA(machine, hInstance, uID, lpBuffer, cchBufferMax);
+            let result = winapi::user32::LoadStringA(machine, hInstance, uID, lpBuffer);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
@@ -13022,7 +20577,7 @@ pub mod user32 {
             let mem = machine.mem().detach();
             let hWndFrom = <HWND>::from_stack(mem, stack_args + 0u32);
             let hWndTo = <HWND>::from_stack(mem, stack_args + 4u32);
-            let lpPoints = <ArrayWithSize<POINT>>::from_stack(mem, stack_args + 8u32);
+            let lpPoints = <ArrayWithSizeMut<POINT>>::from_stack(mem, stack_args + 8u32);
             let __trace_context = if crate::trace::enabled("user32/window") {
                 Some(crate::trace::trace_begin(
                     "user32/window",
@@ -13047,7 +20602,10 @@ pub mod user32 {
             }
             result.to_raw()
         }
-        pub unsafe fn MessageBoxA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn MessageBoxA(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
             let mem = machine.mem().detach();
             let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
             let lpText = <Option<&str>>::from_stack(mem, stack_args + 4u32);
@@ -13067,18 +20625,26 @@ pub mod user32 {
             } else {
                 None
             };
-            let result = winapi::user32::MessageBoxA(machine, hWnd, lpText, lpCaption, uType);
-            if let Some(__trace_context) = __trace_context {
-                crate::trace::trace_return(
-                    &__trace_context,
-                    winapi::user32::MessageBoxA_pos.0,
-                    winapi::user32::MessageBoxA_pos.1,
-                    &result,
-                );
-            }
-            result.to_raw()
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result =
+                    winapi::user32::MessageBoxA(machine, hWnd, lpText, lpCaption, uType).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::user32::MessageBoxA_pos.0,
+                        winapi::user32::MessageBoxA_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
         }
-        pub unsafe fn MessageBoxW(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn MessageBoxW(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
             let mem = machine.mem().detach();
             let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
             let lpText = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
@@ -13098,12 +20664,89 @@ pub mod user32 {
             } else {
                 None
             };
-            let result = winapi::user32::MessageBoxW(machine, hWnd, lpText, lpCaption, uType);
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result =
+                    winapi::user32::MessageBoxW(machine, hWnd, lpText, lpCaption, uType).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::user32::MessageBoxW_pos.0,
+                        winapi::user32::MessageBoxW_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn MonitorFromPoint(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let pt = <POINT>::from_stack(mem, stack_args + 0u32);
+            let dwFlags = <Result<MONITOR, u32>>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("user32/display") {
+                Some(crate::trace::trace_begin(
+                    "user32/display",
+                    "MonitorFromPoint",
+                    &[("pt", &pt), ("dwFlags", &dwFlags)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::user32::MonitorFromPoint(machine, pt, dwFlags);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::user32::MonitorFromPoint_pos.0,
+                    winapi::user32::MonitorFromPoint_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn MonitorFromRect(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let lprc = <Option<&RECT>>::from_stack(mem, stack_args + 0u32);
+            let dwFlags = <Result<MONITOR, u32>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("user32/display") {
+                Some(crate::trace::trace_begin(
+                    "user32/display",
+                    "MonitorFromRect",
+                    &[("lprc", &lprc), ("dwFlags", &dwFlags)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::user32::MonitorFromRect(machine, lprc, dwFlags);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::user32::MonitorFromRect_pos.0,
+                    winapi::user32::MonitorFromRect_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn MonitorFromWindow(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hwnd = <HWND>::from_stack(mem, stack_args + 0u32);
+            let dwFlags = <Result<MONITOR, u32>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("user32/display") {
+                Some(crate::trace::trace_begin(
+                    "user32/display",
+                    "MonitorFromWindow",
+                    &[("hwnd", &hwnd), ("dwFlags", &dwFlags)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::user32::MonitorFromWindow(machine, hwnd, dwFlags);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::user32::MessageBoxW_pos.0,
-                    winapi::user32::MessageBoxW_pos.1,
+                    winapi::user32::MonitorFromWindow_pos.0,
+                    winapi::user32::MonitorFromWindow_pos.1,
                     &result,
                 );
             }
@@ -13144,7 +20787,10 @@ pub mod user32 {
             }
             result.to_raw()
         }
-        pub unsafe fn MsgWaitForMultipleObjects(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn MsgWaitForMultipleObjects(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
             let mem = machine.mem().detach();
             let nCount = <u32>::from_stack(mem, stack_args + 0u32);
             let pHandles = <u32>::from_stack(mem, stack_args + 4u32);
@@ -13166,23 +20812,76 @@ pub mod user32 {
             } else {
                 None
             };
-            let result = winapi::user32::MsgWaitForMultipleObjects(
-                machine,
-                nCount,
-                pHandles,
-                fWaitAll,
-                dwMilliseconds,
-                dwWakeMask,
-            );
-            if let Some(__trace_context) = __trace_context {
-                crate::trace::trace_return(
-                    &__trace_context,
-                    winapi::user32::MsgWaitForMultipleObjects_pos.0,
-                    winapi::user32::MsgWaitForMultipleObjects_pos.1,
-                    &result,
-                );
-            }
-            result.to_raw()
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::user32::MsgWaitForMultipleObjects(
+                    machine,
+                    nCount,
+                    pHandles,
+                    fWaitAll,
+                    dwMilliseconds,
+                    dwWakeMask,
+                )
+                .await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::user32::MsgWaitForMultipleObjects_pos.0,
+                        winapi::user32::MsgWaitForMultipleObjects_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn MsgWaitForMultipleObjectsEx(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let nCount = <u32>::from_stack(mem, stack_args + 0u32);
+            let pHandles = <u32>::from_stack(mem, stack_args + 4u32);
+            let dwMilliseconds = <u32>::from_stack(mem, stack_args + 8u32);
+            let dwWakeMask = <u32>::from_stack(mem, stack_args + 12u32);
+            let dwFlags = <u32>::from_stack(mem, stack_args + 16u32);
+            let __trace_context = if crate::trace::enabled("user32/message") {
+                Some(crate::trace::trace_begin(
+                    "user32/message",
+                    "MsgWaitForMultipleObjectsEx",
+                    &[
+                        ("nCount", &nCount),
+                        ("pHandles", &pHandles),
+                        ("dwMilliseconds", &dwMilliseconds),
+                        ("dwWakeMask", &dwWakeMask),
+                        ("dwFlags", &dwFlags),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::user32::MsgWaitForMultipleObjectsEx(
+                    machine,
+                    nCount,
+                    pHandles,
+                    dwMilliseconds,
+                    dwWakeMask,
+                    dwFlags,
+                )
+                .await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::user32::MsgWaitForMultipleObjectsEx_pos.0,
+                        winapi::user32::MsgWaitForMultipleObjectsEx_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
         }
         pub unsafe fn PeekMessageA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
@@ -13503,6 +21202,30 @@ pub mod user32 {
             }
             result.to_raw()
         }
+        pub unsafe fn ScreenToClient(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+            let lpPoint = <Option<&mut POINT>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("user32/window") {
+                Some(crate::trace::trace_begin(
+                    "user32/window",
+                    "ScreenToClient",
+                    &[("hWnd", &hWnd), ("lpPoint", &lpPoint)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::user32::ScreenToClient(machine, hWnd, lpPoint);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::user32::ScreenToClient_pos.0,
+                    winapi::user32::ScreenToClient_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
         pub unsafe fn SendMessageA(
             machine: &mut Machine,
             stack_args: u32,
@@ -13579,6 +21302,36 @@ pub mod user32 {
                 result.to_raw()
             })
         }
+        pub unsafe fn SetActiveWindow(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("user32/focus") {
+                Some(crate::trace::trace_begin(
+                    "user32/focus",
+                    "SetActiveWindow",
+                    &[("hWnd", &hWnd)],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::user32::SetActiveWindow(machine, hWnd).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::user32::SetActiveWindow_pos.0,
+                        winapi::user32::SetActiveWindow_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
         pub unsafe fn SetCapture(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
             let hwnd = <HWND>::from_stack(mem, stack_args + 0u32);
@@ -13738,47 +21491,93 @@ pub mod user32 {
             }
             result.to_raw()
         }
-        pub unsafe fn SetFocus(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn SetFocus(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
             let mem = machine.mem().detach();
             let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("user32/window") {
+            let __trace_context = if crate::trace::enabled("user32/focus") {
                 Some(crate::trace::trace_begin(
-                    "user32/window",
+                    "user32/focus",
                     "SetFocus",
                     &[("hWnd", &hWnd)],
                 ))
             } else {
                 None
             };
-            let result = winapi::user32::SetFocus(machine, hWnd);
-            if let Some(__trace_context) = __trace_context {
-                crate::trace::trace_return(
-                    &__trace_context,
-                    winapi::user32::SetFocus_pos.0,
-                    winapi::user32::SetFocus_pos.1,
-                    &result,
-                );
-            }
-            result.to_raw()
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::user32::SetFocus(machine, hWnd).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::user32::SetFocus_pos.0,
+                        winapi::user32::SetFocus_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
         }
-        pub unsafe fn SetForegroundWindow(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn SetForegroundWindow(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
             let mem = machine.mem().detach();
             let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
-            let __trace_context = if crate::trace::enabled("user32/window") {
+            let __trace_context = if crate::trace::enabled("user32/focus") {
                 Some(crate::trace::trace_begin(
-                    "user32/window",
+                    "user32/focus",
                     "SetForegroundWindow",
                     &[("hWnd", &hWnd)],
                 ))
             } else {
                 None
             };
-            let result = winapi::user32::SetForegroundWindow(machine, hWnd);
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::user32::SetForegroundWindow(machine, hWnd).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::user32::SetForegroundWindow_pos.0,
+                        winapi::user32::SetForegroundWindow_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn SetLayeredWindowAttributes(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hwnd = <HWND>::from_stack(mem, stack_args + 0u32);
+            let crKey = <COLORREF>::from_stack(mem, stack_args + 4u32);
+            let bAlpha = <u8>::from_stack(mem, stack_args + 8u32);
+            let dwFlags = <Result<LWA, u32>>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("user32/window") {
+                Some(crate::trace::trace_begin(
+                    "user32/window",
+                    "SetLayeredWindowAttributes",
+                    &[
+                        ("hwnd", &hwnd),
+                        ("crKey", &crKey),
+                        ("bAlpha", &bAlpha),
+                        ("dwFlags", &dwFlags),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result =
+                winapi::user32::SetLayeredWindowAttributes(machine, hwnd, crKey, bAlpha, dwFlags);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::user32::SetForegroundWindow_pos.0,
-                    winapi::user32::SetForegroundWindow_pos.1,
+                    winapi::user32::SetLayeredWindowAttributes_pos.0,
+                    winapi::user32::SetLayeredWindowAttributes_pos.1,
                     &result,
                 );
             }
@@ -13926,6 +21725,37 @@ pub mod user32 {
             }
             result.to_raw()
         }
+        pub unsafe fn SetWindowPlacement(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+            let lpwndpl = <Option<&WINDOWPLACEMENT>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("user32/window") {
+                Some(crate::trace::trace_begin(
+                    "user32/window",
+                    "SetWindowPlacement",
+                    &[("hWnd", &hWnd), ("lpwndpl", &lpwndpl)],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::user32::SetWindowPlacement(machine, hWnd, lpwndpl).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::user32::SetWindowPlacement_pos.0,
+                        winapi::user32::SetWindowPlacement_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
         pub unsafe fn SetWindowPos(
             machine: &mut Machine,
             stack_args: u32,
@@ -13980,7 +21810,10 @@ pub mod user32 {
                 result.to_raw()
             })
         }
-        pub unsafe fn SetWindowTextA(machine: &mut Machine, stack_args: u32) -> u32 {
+        pub unsafe fn SetWindowTextA(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
             let mem = machine.mem().detach();
             let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
             let lpString = <Option<&str>>::from_stack(mem, stack_args + 4u32);
@@ -13993,12 +21826,109 @@ pub mod user32 {
             } else {
                 None
             };
-            let result = winapi::user32::SetWindowTextA(machine, hWnd, lpString);
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::user32::SetWindowTextA(machine, hWnd, lpString).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::user32::SetWindowTextA_pos.0,
+                        winapi::user32::SetWindowTextA_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn SetWindowTextW(
+            machine: &mut Machine,
+            stack_args: u32,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32>>> {
+            let mem = machine.mem().detach();
+            let hWnd = <HWND>::from_stack(mem, stack_args + 0u32);
+            let lpString = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("user32/window") {
+                Some(crate::trace::trace_begin(
+                    "user32/window",
+                    "SetWindowTextW",
+                    &[("hWnd", &hWnd), ("lpString", &lpString)],
+                ))
+            } else {
+                None
+            };
+            let machine: *mut Machine = machine;
+            Box::pin(async move {
+                let machine = unsafe { &mut *machine };
+                let result = winapi::user32::SetWindowTextW(machine, hWnd, lpString).await;
+                if let Some(__trace_context) = __trace_context {
+                    crate::trace::trace_return(
+                        &__trace_context,
+                        winapi::user32::SetWindowTextW_pos.0,
+                        winapi::user32::SetWindowTextW_pos.1,
+                        &result,
+                    );
+                }
+                result.to_raw()
+            })
+        }
+        pub unsafe fn SetWindowsHookExA(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let idHook = <i32>::from_stack(mem, stack_args + 0u32);
+            let lpfn = <u32>::from_stack(mem, stack_args + 4u32);
+            let hmod = <HINSTANCE>::from_stack(mem, stack_args + 8u32);
+            let dwThreadId = <u32>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("user32/hook") {
+                Some(crate::trace::trace_begin(
+                    "user32/hook",
+                    "SetWindowsHookExA",
+                    &[
+                        ("idHook", &idHook),
+                        ("lpfn", &lpfn),
+                        ("hmod", &hmod),
+                        ("dwThreadId", &dwThreadId),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::user32::SetWindowsHookExA(machine, idHook, lpfn, hmod, dwThreadId);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::user32::SetWindowsHookExA_pos.0,
+                    winapi::user32::SetWindowsHookExA_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn SetWindowsHookExW(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let idHook = <i32>::from_stack(mem, stack_args + 0u32);
+            let lpfn = <u32>::from_stack(mem, stack_args + 4u32);
+            let hmod = <HINSTANCE>::from_stack(mem, stack_args + 8u32);
+            let dwThreadId = <u32>::from_stack(mem, stack_args + 12u32);
+            let __trace_context = if crate::trace::enabled("user32/hook") {
+                Some(crate::trace::trace_begin(
+                    "user32/hook",
+                    "SetWindowsHookExW",
+                    &[
+                        ("idHook", &idHook),
+                        ("lpfn", &lpfn),
+                        ("hmod", &hmod),
+                        ("dwThreadId", &dwThreadId),
+                    ],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::user32::SetWindowsHookExW(machine, idHook, lpfn, hmod, dwThreadId);
             if let Some(__trace_context) = __trace_context {
                 crate::trace::trace_return(
                     &__trace_context,
-                    winapi::user32::SetWindowTextA_pos.0,
-                    winapi::user32::SetWindowTextA_pos.1,
+                    winapi::user32::SetWindowsHookExW_pos.0,
+                    winapi::user32::SetWindowsHookExW_pos.1,
                     &result,
                 );
             }
@@ -14087,6 +22017,30 @@ pub mod user32 {
             }
             result.to_raw()
         }
+        pub unsafe fn TranslateMDISysAccel(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hWndClient = <HWND>::from_stack(mem, stack_args + 0u32);
+            let lpMsg = <Option<&MSG>>::from_stack(mem, stack_args + 4u32);
+            let __trace_context = if crate::trace::enabled("user32/mdi") {
+                Some(crate::trace::trace_begin(
+                    "user32/mdi",
+                    "TranslateMDISysAccel",
+                    &[("hWndClient", &hWndClient), ("lpMsg", &lpMsg)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::user32::TranslateMDISysAccel(machine, hWndClient, lpMsg);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::user32::TranslateMDISysAccel_pos.0,
+                    winapi::user32::TranslateMDISysAccel_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
         pub unsafe fn TranslateMessage(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
             let lpMsg = <Option<&MSG>>::from_stack(mem, stack_args + 0u32);
@@ -14110,6 +22064,29 @@ pub mod user32 {
             }
             result.to_raw()
         }
+        pub unsafe fn UnhookWindowsHookEx(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let hhk = <HHOOK>::from_stack(mem, stack_args + 0u32);
+            let __trace_context = if crate::trace::enabled("user32/hook") {
+                Some(crate::trace::trace_begin(
+                    "user32/hook",
+                    "UnhookWindowsHookEx",
+                    &[("hhk", &hhk)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::user32::UnhookWindowsHookEx(machine, hhk);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::user32::UnhookWindowsHookEx_pos.0,
+                    winapi::user32::UnhookWindowsHookEx_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
         pub unsafe fn UpdateWindow(
             machine: &mut Machine,
             stack_args: u32,
@@ -14227,7 +22204,7 @@ pub mod user32 {
         pub unsafe fn wsprintfA(machine: &mut Machine, stack_args: u32) -> u32 {
             let mem = machine.mem().detach();
             let buf = <u32>::from_stack(mem, stack_args + 0u32);
-            let fmt = <Option<&str>>::from_stack(mem, stack_args + 4u32);
+            let fmt = <Option<&CStr>>::from_stack(mem, stack_args + 4u32);
             let args = <VarArgs>::from_stack(mem, stack_args + 8u32);
             let __trace_context = if crate::trace::enabled("user32/misc") {
                 Some(crate::trace::trace_begin(
@@ -14274,479 +22251,887 @@ pub mod user32 {
             }
             result.to_raw()
         }
+        pub unsafe fn wvsprintfA(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let buf = <u32>::from_stack(mem, stack_args + 0u32);
+            let fmt = <Option<&CStr>>::from_stack(mem, stack_args + 4u32);
+            let arglist = <u32>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("user32/misc") {
+                Some(crate::trace::trace_begin(
+                    "user32/misc",
+                    "wvsprintfA",
+                    &[("buf", &buf), ("fmt", &fmt), ("arglist", &arglist)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::user32::wvsprintfA(machine, buf, fmt, arglist);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::user32::wvsprintfA_pos.0,
+                    winapi::user32::wvsprintfA_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
+        pub unsafe fn wvsprintfW(machine: &mut Machine, stack_args: u32) -> u32 {
+            let mem = machine.mem().detach();
+            let buf = <u32>::from_stack(mem, stack_args + 0u32);
+            let fmt = <Option<&Str16>>::from_stack(mem, stack_args + 4u32);
+            let arglist = <u32>::from_stack(mem, stack_args + 8u32);
+            let __trace_context = if crate::trace::enabled("user32/misc") {
+                Some(crate::trace::trace_begin(
+                    "user32/misc",
+                    "wvsprintfW",
+                    &[("buf", &buf), ("fmt", &fmt), ("arglist", &arglist)],
+                ))
+            } else {
+                None
+            };
+            let result = winapi::user32::wvsprintfW(machine, buf, fmt, arglist);
+            if let Some(__trace_context) = __trace_context {
+                crate::trace::trace_return(
+                    &__trace_context,
+                    winapi::user32::wvsprintfW_pos.0,
+                    winapi::user32::wvsprintfW_pos.1,
+                    &result,
+                );
+            }
+            result.to_raw()
+        }
     }
-    const SHIMS: [Shim; 118usize] = [
+    const SHIMS: [Shim; 166usize] = [
         Shim {
             name: "AdjustWindowRect",
             func: Handler::Sync(wrappers::AdjustWindowRect),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "AdjustWindowRectEx",
             func: Handler::Sync(wrappers::AdjustWindowRectEx),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "AppendMenuA",
             func: Handler::Sync(wrappers::AppendMenuA),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "BeginPaint",
-            func: Handler::Sync(wrappers::BeginPaint),
+            func: Handler::Async(wrappers::BeginPaint),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "CallNextHookEx",
+            func: Handler::Async(wrappers::CallNextHookEx),
+            stack_consumed: 16u32,
+        },
+        Shim {
+            name: "ChangeDisplaySettingsA",
+            func: Handler::Async(wrappers::ChangeDisplaySettingsA),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "CharLowerA",
+            func: Handler::Sync(wrappers::CharLowerA),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "CharLowerBuffA",
+            func: Handler::Sync(wrappers::CharLowerBuffA),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "CharLowerBuffW",
+            func: Handler::Sync(wrappers::CharLowerBuffW),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "CharLowerW",
+            func: Handler::Sync(wrappers::CharLowerW),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "CharNextA",
+            func: Handler::Sync(wrappers::CharNextA),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "CharNextExA",
+            func: Handler::Sync(wrappers::CharNextExA),
+            stack_consumed: 12u32,
+        },
+        Shim {
+            name: "CharNextW",
+            func: Handler::Sync(wrappers::CharNextW),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "CharPrevA",
+            func: Handler::Sync(wrappers::CharPrevA),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "CharPrevExA",
+            func: Handler::Sync(wrappers::CharPrevExA),
+            stack_consumed: 16u32,
+        },
+        Shim {
+            name: "CharPrevW",
+            func: Handler::Sync(wrappers::CharPrevW),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "CharUpperA",
+            func: Handler::Sync(wrappers::CharUpperA),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "CharUpperBuffA",
+            func: Handler::Sync(wrappers::CharUpperBuffA),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "CharUpperBuffW",
+            func: Handler::Sync(wrappers::CharUpperBuffW),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "CharUpperW",
+            func: Handler::Sync(wrappers::CharUpperW),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "CheckDlgButton",
             func: Handler::Sync(wrappers::CheckDlgButton),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "CheckMenuItem",
             func: Handler::Sync(wrappers::CheckMenuItem),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "CheckRadioButton",
             func: Handler::Sync(wrappers::CheckRadioButton),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "ClientToScreen",
             func: Handler::Sync(wrappers::ClientToScreen),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "CopyRect",
             func: Handler::Sync(wrappers::CopyRect),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "CreateCursor",
             func: Handler::Sync(wrappers::CreateCursor),
+            stack_consumed: 28u32,
+        },
+        Shim {
+            name: "CreateMDIWindowA",
+            func: Handler::Async(wrappers::CreateMDIWindowA),
+            stack_consumed: 40u32,
         },
         Shim {
             name: "CreatePopupMenu",
             func: Handler::Sync(wrappers::CreatePopupMenu),
+            stack_consumed: 0u32,
         },
         Shim {
             name: "CreateWindowExA",
             func: Handler::Async(wrappers::CreateWindowExA),
+            stack_consumed: 48u32,
         },
         Shim {
             name: "CreateWindowExW",
             func: Handler::Async(wrappers::CreateWindowExW),
+            stack_consumed: 48u32,
+        },
+        Shim {
+            name: "DefFrameProcA",
+            func: Handler::Async(wrappers::DefFrameProcA),
+            stack_consumed: 20u32,
+        },
+        Shim {
+            name: "DefFrameProcW",
+            func: Handler::Async(wrappers::DefFrameProcW),
+            stack_consumed: 20u32,
+        },
+        Shim {
+            name: "DefMDIChildProcA",
+            func: Handler::Async(wrappers::DefMDIChildProcA),
+            stack_consumed: 16u32,
+        },
+        Shim {
+            name: "DefMDIChildProcW",
+            func: Handler::Async(wrappers::DefMDIChildProcW),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "DefWindowProcA",
             func: Handler::Async(wrappers::DefWindowProcA),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "DefWindowProcW",
             func: Handler::Async(wrappers::DefWindowProcW),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "DestroyWindow",
-            func: Handler::Sync(wrappers::DestroyWindow),
+            func: Handler::Async(wrappers::DestroyWindow),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "DialogBoxIndirectParamA",
             func: Handler::Sync(wrappers::DialogBoxIndirectParamA),
+            stack_consumed: 20u32,
         },
         Shim {
             name: "DialogBoxParamA",
             func: Handler::Sync(wrappers::DialogBoxParamA),
+            stack_consumed: 20u32,
         },
         Shim {
             name: "DialogBoxParamW",
             func: Handler::Sync(wrappers::DialogBoxParamW),
+            stack_consumed: 20u32,
         },
         Shim {
             name: "DispatchMessageA",
             func: Handler::Async(wrappers::DispatchMessageA),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "DispatchMessageW",
             func: Handler::Async(wrappers::DispatchMessageW),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "DrawTextW",
             func: Handler::Sync(wrappers::DrawTextW),
+            stack_consumed: 20u32,
         },
         Shim {
             name: "EnableMenuItem",
             func: Handler::Sync(wrappers::EnableMenuItem),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "EnableWindow",
             func: Handler::Sync(wrappers::EnableWindow),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "EndDialog",
             func: Handler::Sync(wrappers::EndDialog),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "EndPaint",
             func: Handler::Sync(wrappers::EndPaint),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "EnumChildWindows",
+            func: Handler::Async(wrappers::EnumChildWindows),
+            stack_consumed: 12u32,
+        },
+        Shim {
+            name: "EnumDisplayMonitors",
+            func: Handler::Async(wrappers::EnumDisplayMonitors),
+            stack_consumed: 16u32,
+        },
+        Shim {
+            name: "EnumDisplaySettingsA",
+            func: Handler::Sync(wrappers::EnumDisplaySettingsA),
+            stack_consumed: 12u32,
+        },
+        Shim {
+            name: "EnumWindows",
+            func: Handler::Async(wrappers::EnumWindows),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "FillRect",
             func: Handler::Sync(wrappers::FillRect),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "FindWindowA",
             func: Handler::Sync(wrappers::FindWindowA),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "FrameRect",
             func: Handler::Sync(wrappers::FrameRect),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "GetActiveWindow",
             func: Handler::Sync(wrappers::GetActiveWindow),
+            stack_consumed: 0u32,
         },
         Shim {
             name: "GetCapture",
             func: Handler::Sync(wrappers::GetCapture),
+            stack_consumed: 0u32,
         },
         Shim {
             name: "GetClientRect",
             func: Handler::Sync(wrappers::GetClientRect),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "GetDC",
             func: Handler::Sync(wrappers::GetDC),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "GetDesktopWindow",
             func: Handler::Sync(wrappers::GetDesktopWindow),
+            stack_consumed: 0u32,
         },
         Shim {
             name: "GetDlgItem",
             func: Handler::Sync(wrappers::GetDlgItem),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "GetDlgItemInt",
             func: Handler::Sync(wrappers::GetDlgItemInt),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "GetDlgItemTextW",
             func: Handler::Sync(wrappers::GetDlgItemTextW),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "GetFocus",
             func: Handler::Sync(wrappers::GetFocus),
+            stack_consumed: 0u32,
         },
         Shim {
             name: "GetForegroundWindow",
             func: Handler::Sync(wrappers::GetForegroundWindow),
+            stack_consumed: 0u32,
         },
         Shim {
             name: "GetKeyState",
             func: Handler::Sync(wrappers::GetKeyState),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "GetLastActivePopup",
             func: Handler::Sync(wrappers::GetLastActivePopup),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "GetLayeredWindowAttributes",
+            func: Handler::Sync(wrappers::GetLayeredWindowAttributes),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "GetMenu",
             func: Handler::Sync(wrappers::GetMenu),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "GetMenuItemRect",
             func: Handler::Sync(wrappers::GetMenuItemRect),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "GetMessageA",
             func: Handler::Async(wrappers::GetMessageA),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "GetMessageW",
             func: Handler::Async(wrappers::GetMessageW),
+            stack_consumed: 16u32,
+        },
+        Shim {
+            name: "GetMonitorInfoA",
+            func: Handler::Sync(wrappers::GetMonitorInfoA),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "GetMonitorInfoW",
+            func: Handler::Sync(wrappers::GetMonitorInfoW),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "GetSubMenu",
             func: Handler::Sync(wrappers::GetSubMenu),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "GetSysColor",
             func: Handler::Sync(wrappers::GetSysColor),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "GetSystemMenu",
             func: Handler::Sync(wrappers::GetSystemMenu),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "GetSystemMetrics",
             func: Handler::Sync(wrappers::GetSystemMetrics),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "GetWindowDC",
             func: Handler::Sync(wrappers::GetWindowDC),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "GetWindowLongA",
             func: Handler::Sync(wrappers::GetWindowLongA),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "GetWindowPlacement",
             func: Handler::Sync(wrappers::GetWindowPlacement),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "GetWindowRect",
             func: Handler::Sync(wrappers::GetWindowRect),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "GetWindowTextA",
+            func: Handler::Sync(wrappers::GetWindowTextA),
+            stack_consumed: 12u32,
+        },
+        Shim {
+            name: "GetWindowTextLengthA",
+            func: Handler::Sync(wrappers::GetWindowTextLengthA),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "GetWindowTextLengthW",
+            func: Handler::Sync(wrappers::GetWindowTextLengthW),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "GetWindowTextW",
+            func: Handler::Sync(wrappers::GetWindowTextW),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "InflateRect",
             func: Handler::Sync(wrappers::InflateRect),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "IntersectRect",
             func: Handler::Sync(wrappers::IntersectRect),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "InvalidateRect",
             func: Handler::Sync(wrappers::InvalidateRect),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "InvalidateRgn",
             func: Handler::Sync(wrappers::InvalidateRgn),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "InvertRect",
             func: Handler::Sync(wrappers::InvertRect),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "IsDlgButtonChecked",
             func: Handler::Sync(wrappers::IsDlgButtonChecked),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "IsIconic",
             func: Handler::Sync(wrappers::IsIconic),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "IsRectEmpty",
             func: Handler::Sync(wrappers::IsRectEmpty),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "IsZoomed",
+            func: Handler::Sync(wrappers::IsZoomed),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "KillTimer",
             func: Handler::Sync(wrappers::KillTimer),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "LoadAcceleratorsW",
             func: Handler::Sync(wrappers::LoadAcceleratorsW),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "LoadBitmapA",
             func: Handler::Sync(wrappers::LoadBitmapA),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "LoadCursorA",
             func: Handler::Sync(wrappers::LoadCursorA),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "LoadCursorW",
             func: Handler::Sync(wrappers::LoadCursorW),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "LoadIconA",
             func: Handler::Sync(wrappers::LoadIconA),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "LoadIconW",
             func: Handler::Sync(wrappers::LoadIconW),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "LoadImageA",
             func: Handler::Sync(wrappers::LoadImageA),
+            stack_consumed: 24u32,
         },
         Shim {
             name: "LoadImageW",
             func: Handler::Sync(wrappers::LoadImageW),
+            stack_consumed: 24u32,
         },
         Shim {
             name: "LoadMenuA",
             func: Handler::Sync(wrappers::LoadMenuA),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "LoadMenuW",
             func: Handler::Sync(wrappers::LoadMenuW),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "LoadStringA",
             func: Handler::Sync(wrappers::LoadStringA),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "LoadStringW",
             func: Handler::Sync(wrappers::LoadStringW),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "MapWindowPoints",
             func: Handler::Sync(wrappers::MapWindowPoints),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "MessageBoxA",
-            func: Handler::Sync(wrappers::MessageBoxA),
+            func: Handler::Async(wrappers::MessageBoxA),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "MessageBoxW",
-            func: Handler::Sync(wrappers::MessageBoxW),
+            func: Handler::Async(wrappers::MessageBoxW),
+            stack_consumed: 16u32,
+        },
+        Shim {
+            name: "MonitorFromPoint",
+            func: Handler::Sync(wrappers::MonitorFromPoint),
+            stack_consumed: 12u32,
+        },
+        Shim {
+            name: "MonitorFromRect",
+            func: Handler::Sync(wrappers::MonitorFromRect),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "MonitorFromWindow",
+            func: Handler::Sync(wrappers::MonitorFromWindow),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "MoveWindow",
             func: Handler::Sync(wrappers::MoveWindow),
+            stack_consumed: 24u32,
         },
         Shim {
             name: "MsgWaitForMultipleObjects",
-            func: Handler::Sync(wrappers::MsgWaitForMultipleObjects),
+            func: Handler::Async(wrappers::MsgWaitForMultipleObjects),
+            stack_consumed: 20u32,
+        },
+        Shim {
+            name: "MsgWaitForMultipleObjectsEx",
+            func: Handler::Async(wrappers::MsgWaitForMultipleObjectsEx),
+            stack_consumed: 20u32,
         },
         Shim {
             name: "PeekMessageA",
             func: Handler::Sync(wrappers::PeekMessageA),
+            stack_consumed: 20u32,
         },
         Shim {
             name: "PeekMessageW",
             func: Handler::Sync(wrappers::PeekMessageW),
+            stack_consumed: 20u32,
         },
         Shim {
             name: "PostMessageW",
             func: Handler::Sync(wrappers::PostMessageW),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "PostQuitMessage",
             func: Handler::Sync(wrappers::PostQuitMessage),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "PtInRect",
             func: Handler::Sync(wrappers::PtInRect),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "RegisterClassA",
             func: Handler::Sync(wrappers::RegisterClassA),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "RegisterClassExA",
             func: Handler::Sync(wrappers::RegisterClassExA),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "RegisterClassExW",
             func: Handler::Sync(wrappers::RegisterClassExW),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "RegisterClassW",
             func: Handler::Sync(wrappers::RegisterClassW),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "RegisterWindowMessageW",
             func: Handler::Sync(wrappers::RegisterWindowMessageW),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "ReleaseCapture",
             func: Handler::Sync(wrappers::ReleaseCapture),
+            stack_consumed: 0u32,
         },
         Shim {
             name: "ReleaseDC",
             func: Handler::Sync(wrappers::ReleaseDC),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "ScreenToClient",
+            func: Handler::Sync(wrappers::ScreenToClient),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "SendMessageA",
             func: Handler::Async(wrappers::SendMessageA),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "SendMessageW",
             func: Handler::Async(wrappers::SendMessageW),
+            stack_consumed: 16u32,
+        },
+        Shim {
+            name: "SetActiveWindow",
+            func: Handler::Async(wrappers::SetActiveWindow),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "SetCapture",
             func: Handler::Sync(wrappers::SetCapture),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "SetCursor",
             func: Handler::Sync(wrappers::SetCursor),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "SetCursorPos",
             func: Handler::Sync(wrappers::SetCursorPos),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "SetDlgItemInt",
             func: Handler::Sync(wrappers::SetDlgItemInt),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "SetDlgItemTextA",
             func: Handler::Sync(wrappers::SetDlgItemTextA),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "SetDlgItemTextW",
             func: Handler::Sync(wrappers::SetDlgItemTextW),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "SetFocus",
-            func: Handler::Sync(wrappers::SetFocus),
+            func: Handler::Async(wrappers::SetFocus),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "SetForegroundWindow",
-            func: Handler::Sync(wrappers::SetForegroundWindow),
+            func: Handler::Async(wrappers::SetForegroundWindow),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "SetLayeredWindowAttributes",
+            func: Handler::Sync(wrappers::SetLayeredWindowAttributes),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "SetMenu",
             func: Handler::Sync(wrappers::SetMenu),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "SetMenuItemInfoA",
             func: Handler::Sync(wrappers::SetMenuItemInfoA),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "SetRect",
             func: Handler::Sync(wrappers::SetRect),
+            stack_consumed: 20u32,
         },
         Shim {
             name: "SetRectEmpty",
             func: Handler::Sync(wrappers::SetRectEmpty),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "SetTimer",
             func: Handler::Sync(wrappers::SetTimer),
+            stack_consumed: 16u32,
+        },
+        Shim {
+            name: "SetWindowPlacement",
+            func: Handler::Async(wrappers::SetWindowPlacement),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "SetWindowPos",
             func: Handler::Async(wrappers::SetWindowPos),
+            stack_consumed: 28u32,
         },
         Shim {
             name: "SetWindowTextA",
-            func: Handler::Sync(wrappers::SetWindowTextA),
+            func: Handler::Async(wrappers::SetWindowTextA),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "SetWindowTextW",
+            func: Handler::Async(wrappers::SetWindowTextW),
+            stack_consumed: 8u32,
+        },
+        Shim {
+            name: "SetWindowsHookExA",
+            func: Handler::Sync(wrappers::SetWindowsHookExA),
+            stack_consumed: 16u32,
+        },
+        Shim {
+            name: "SetWindowsHookExW",
+            func: Handler::Sync(wrappers::SetWindowsHookExW),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "ShowCursor",
             func: Handler::Sync(wrappers::ShowCursor),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "ShowWindow",
             func: Handler::Async(wrappers::ShowWindow),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "TranslateAcceleratorW",
             func: Handler::Sync(wrappers::TranslateAcceleratorW),
+            stack_consumed: 12u32,
+        },
+        Shim {
+            name: "TranslateMDISysAccel",
+            func: Handler::Sync(wrappers::TranslateMDISysAccel),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "TranslateMessage",
             func: Handler::Sync(wrappers::TranslateMessage),
+            stack_consumed: 4u32,
+        },
+        Shim {
+            name: "UnhookWindowsHookEx",
+            func: Handler::Sync(wrappers::UnhookWindowsHookEx),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "UpdateWindow",
             func: Handler::Async(wrappers::UpdateWindow),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "ValidateRect",
             func: Handler::Sync(wrappers::ValidateRect),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "WaitMessage",
             func: Handler::Async(wrappers::WaitMessage),
+            stack_consumed: 0u32,
         },
         Shim {
             name: "WinHelpW",
             func: Handler::Sync(wrappers::WinHelpW),
+            stack_consumed: 16u32,
         },
         Shim {
             name: "wsprintfA",
             func: Handler::Sync(wrappers::wsprintfA),
+            stack_consumed: 0u32,
         },
         Shim {
             name: "wsprintfW",
             func: Handler::Sync(wrappers::wsprintfW),
+            stack_consumed: 0u32,
+        },
+        Shim {
+            name: "wvsprintfA",
+            func: Handler::Sync(wrappers::wvsprintfA),
+            stack_consumed: 12u32,
+        },
+        Shim {
+            name: "wvsprintfW",
+            func: Handler::Sync(wrappers::wvsprintfW),
+            stack_consumed: 12u32,
         },
     ];
     pub const DLL: BuiltinDLL = BuiltinDLL {
@@ -14808,6 +23193,7 @@ pub mod wininet {
     const SHIMS: [Shim; 1usize] = [Shim {
         name: "InternetOpenA",
         func: Handler::Sync(wrappers::InternetOpenA),
+        stack_consumed: 20u32,
     }];
     pub const DLL: BuiltinDLL = BuiltinDLL {
         file_name: "wininet.dll",
@@ -15451,98 +23837,122 @@ pub mod winmm {
         Shim {
             name: "PlaySoundW",
             func: Handler::Sync(wrappers::PlaySoundW),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "mciSendCommandA",
             func: Handler::Sync(wrappers::mciSendCommandA),
+            stack_consumed: 0u32,
         },
         Shim {
             name: "mixerClose",
             func: Handler::Sync(wrappers::mixerClose),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "mixerGetControlDetailsA",
             func: Handler::Sync(wrappers::mixerGetControlDetailsA),
+            stack_consumed: 0u32,
         },
         Shim {
             name: "mixerGetLineControlsA",
             func: Handler::Sync(wrappers::mixerGetLineControlsA),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "mixerGetLineInfoA",
             func: Handler::Sync(wrappers::mixerGetLineInfoA),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "mixerOpen",
             func: Handler::Sync(wrappers::mixerOpen),
+            stack_consumed: 20u32,
         },
         Shim {
             name: "mixerSetControlDetails",
             func: Handler::Sync(wrappers::mixerSetControlDetails),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "timeBeginPeriod",
             func: Handler::Sync(wrappers::timeBeginPeriod),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "timeEndPeriod",
             func: Handler::Sync(wrappers::timeEndPeriod),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "timeGetTime",
             func: Handler::Sync(wrappers::timeGetTime),
+            stack_consumed: 0u32,
         },
         Shim {
             name: "timeKillEvent",
             func: Handler::Sync(wrappers::timeKillEvent),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "timeSetEvent",
             func: Handler::Sync(wrappers::timeSetEvent),
+            stack_consumed: 20u32,
         },
         Shim {
             name: "waveOutClose",
             func: Handler::Sync(wrappers::waveOutClose),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "waveOutGetDevCapsA",
             func: Handler::Sync(wrappers::waveOutGetDevCapsA),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "waveOutGetNumDevs",
             func: Handler::Sync(wrappers::waveOutGetNumDevs),
+            stack_consumed: 0u32,
         },
         Shim {
             name: "waveOutGetPosition",
             func: Handler::Sync(wrappers::waveOutGetPosition),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "waveOutGetVolume",
             func: Handler::Sync(wrappers::waveOutGetVolume),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "waveOutOpen",
             func: Handler::Sync(wrappers::waveOutOpen),
+            stack_consumed: 24u32,
         },
         Shim {
             name: "waveOutPrepareHeader",
             func: Handler::Sync(wrappers::waveOutPrepareHeader),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "waveOutReset",
             func: Handler::Sync(wrappers::waveOutReset),
+            stack_consumed: 4u32,
         },
         Shim {
             name: "waveOutSetVolume",
             func: Handler::Sync(wrappers::waveOutSetVolume),
+            stack_consumed: 8u32,
         },
         Shim {
             name: "waveOutUnprepareHeader",
             func: Handler::Sync(wrappers::waveOutUnprepareHeader),
+            stack_consumed: 12u32,
         },
         Shim {
             name: "waveOutWrite",
             func: Handler::Sync(wrappers::waveOutWrite),
+            stack_consumed: 12u32,
         },
     ];
     pub const DLL: BuiltinDLL = BuiltinDLL {
@@ -137,7 +137,7 @@ pub mod IDirectDraw2 {
         mem.put_pod::<DDSURFACEDESC>(desc_addr, desc);
 
         machine
-            .call_x86(lpEnumCallback, vec![desc_addr, lpContext])
+            .call_guest(lpEnumCallback, (desc_addr, lpContext))
             .await;
 
         machine
@@ -3,7 +3,7 @@
 use super::{palette::IDirectDrawPalette, types::*, DD_OK};
 pub use crate::winapi::com::GUID;
 use crate::{
-    winapi::{com::vtable, ddraw, kernel32::get_symbol, types::*},
+    winapi::{com::vtable, ddraw, kernel32::get_symbol, types::*, user32},
     Machine,
 };
 use bitflags::bitflags;
@@ -202,7 +202,7 @@ pub mod IDirectDraw7 {
         mem.put_pod::<DDSURFACEDESC2>(desc_addr, desc);
 
         machine
-            .call_x86(lpEnumCallback, vec![desc_addr, lpContext])
+            .call_guest(lpEnumCallback, (desc_addr, lpContext))
             .await;
 
         machine
@@ -243,7 +243,10 @@ pub mod IDirectDraw7 {
         let flags = flags.unwrap();
         if flags.contains(DDSCL::EXCLUSIVE) {
             let window = machine.state.user32.windows.get_mut(hwnd).unwrap();
-            window.expect_toplevel_mut().host.fullscreen();
+            let host = &mut window.expect_toplevel_mut().host;
+            // Exclusive mode owns the display whether or not the window was shown.
+            host.show(true);
+            host.fullscreen();
         }
         DD_OK
     }
@@ -267,6 +270,7 @@ pub mod IDirectDraw7 {
             wnd.set_client_size(&mut *machine.host, width, height);
         }
         machine.state.ddraw.bytes_per_pixel = bpp / 8;
+        machine.state.user32.display = user32::DisplayMode { width, height, bpp };
         DD_OK
     }
 
@@ -583,9 +587,10 @@ pub mod IDirectDrawSurface7 {
                     .emu
                     .memory
                     .mem()
-                    .iter_pod::<[u8; 4]>(surf.pixels, surf.width * surf.height);
+                    .sub32(surf.pixels, surf.width * surf.height * 4);
                 // Ignore alpha channel in input; output is always opaque.
-                let pixels32: Vec<_> = pixels.map(|[r, g, b, _a]| [r, g, b, 0xff]).collect();
+                let mut pixels32 = vec![[0; 4]; (surf.width * surf.height) as usize];
+                memory::simd::copy_opaque(&mut pixels32, memory::simd::pixels(pixels));
                 surf.host.write_pixels(&pixels32);
             }
             bpp => todo!("Unlock for {bpp}bpp"),
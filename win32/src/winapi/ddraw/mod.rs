@@ -20,10 +20,14 @@ use crate::{host, machine::Machine, SurfaceOptions};
 use std::collections::HashMap;
 pub use types::*;
 
+#[derive(serde::Serialize, serde::Deserialize)]
 pub struct Surface {
+    #[serde(skip, default = "crate::snapshot::Detach::detached")]
     pub host: Box<dyn host::Surface>,
     pub width: u32,
     pub height: u32,
+    /// Whether this is the primary surface, for recreating the host surface.
+    primary: bool,
     pub palette: u32, // same as key in palettes
     /// x86 address to pixel buffer, or 0 if unused.
     pixels: u32,
@@ -40,6 +44,7 @@ impl Surface {
             host: machine.host.create_surface(hwnd.to_raw(), &opts),
             width: opts.width,
             height: opts.height,
+            primary: opts.primary,
             palette: 0,
             pixels: 0,
             attached: 0,
@@ -86,6 +91,7 @@ impl Surface {
     }
 }
 
+#[derive(serde::Serialize, serde::Deserialize)]
 pub struct State {
     heap: Heap,
 
@@ -111,6 +117,26 @@ impl State {
         );
         ddraw
     }
+
+    /// After loading a save state, recreate the host surfaces and give them back the
+    /// pixels they had when saved, keyed by surface address.
+    pub fn reattach(&mut self, host: &mut dyn host::Host, mut pixels: HashMap<u32, Vec<[u8; 4]>>) {
+        for (addr, surface) in self.surfaces.iter_mut() {
+            let opts = SurfaceOptions {
+                width: surface.width,
+                height: surface.height,
+                primary: surface.primary,
+            };
+            surface.host = host.create_surface(self.hwnd.to_raw(), &opts);
+            let pixels = pixels.remove(addr).unwrap_or_default();
+            if pixels.len() == (surface.width * surface.height) as usize {
+                surface.host.write_pixels(&pixels);
+                if surface.primary {
+                    surface.host.show();
+                }
+            }
+        }
+    }
 }
 
 impl Default for State {
@@ -394,7 +394,7 @@ pub struct DDPIXELFORMAT {
 unsafe impl memory::Pod for DDPIXELFORMAT {}
 
 #[repr(C)]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct PALETTEENTRY {
     pub peRed: u8,
     pub peGreen: u8,
@@ -26,7 +26,7 @@ const fn make_dhsresult(code: u32) -> u32 {
     (1 << 31) | (0x878 << 16) | code
 }
 
-#[derive(Default)]
+#[derive(Default, serde::Serialize, serde::Deserialize)]
 pub struct State {
     heap: Heap,
     buffers: HashMap<u32, Buffer>,
@@ -44,13 +44,14 @@ impl State {
     }
 }
 
-#[derive(Default)]
+#[derive(Default, serde::Serialize, serde::Deserialize)]
 struct Buffer {
     addr: u32,
     size: u32,
     lock: Option<Lock>,
 }
 
+#[derive(serde::Serialize, serde::Deserialize)]
 struct Lock {
     addr: u32,
     size: u32,
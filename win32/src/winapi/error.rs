@@ -2,19 +2,65 @@
 
 /// Windows error codes.
 #[allow(non_camel_case_types)]
-#[derive(Debug, win32_derive::TryFromEnum)]
+#[derive(Clone, Copy, Debug, win32_derive::TryFromEnum, serde::Serialize, serde::Deserialize)]
 pub enum ERROR {
     SUCCESS = 0,
+    INVALID_FUNCTION = 1,
     FILE_NOT_FOUND = 2,
+    PATH_NOT_FOUND = 3,
     ACCESS_DENIED = 5,
     INVALID_HANDLE = 6,
+    NOT_ENOUGH_MEMORY = 8,
     INVALID_ACCESS = 12,
     INVALID_DATA = 13,
+    BAD_LENGTH = 24,
     OUT_OF_PAPER = 28,
+    GEN_FAILURE = 31,
+    HANDLE_EOF = 38,
+    NOT_SUPPORTED = 50,
     FILE_EXISTS = 80,
+    INVALID_PARAMETER = 87,
+    BROKEN_PIPE = 109,
     OPEN_FAILED = 110,
+    BUFFER_OVERFLOW = 111,
+    CALL_NOT_IMPLEMENTED = 120,
+    SEM_TIMEOUT = 121,
+    INSUFFICIENT_BUFFER = 122,
+    INVALID_NAME = 123,
     MOD_NOT_FOUND = 126,
+    PROC_NOT_FOUND = 127,
+    DISCARDED = 157,
+    NOT_LOCKED = 158,
     ALREADY_EXISTS = 183,
+    BAD_EXE_FORMAT = 193,
+    ENVVAR_NOT_FOUND = 203,
+    PIPE_BUSY = 231,
+    NO_DATA = 232,
+    PIPE_NOT_CONNECTED = 233,
+    MORE_DATA = 234,
+    NO_MORE_ITEMS = 259,
+    DIRECTORY = 267,
+    NOT_OWNER = 288,
+    TOO_MANY_POSTS = 298,
+    MR_MID_NOT_FOUND = 317,
+    INVALID_ADDRESS = 487,
+    PIPE_CONNECTED = 535,
+    PIPE_LISTENING = 536,
+    IO_INCOMPLETE = 996,
+    IO_PENDING = 997,
+    NOACCESS = 998,
+    INVALID_FLAGS = 1004,
+    FILE_INVALID = 1006,
+    KEY_DELETED = 1018,
+    NO_UNICODE_TRANSLATION = 1113,
+    DLL_INIT_FAILED = 1114,
+    MAPPED_ALIGNMENT = 1132,
+    ALREADY_FIBER = 1280,
+    ALREADY_THREAD = 1281,
+    INVALID_WINDOW_HANDLE = 1400,
+    CANNOT_FIND_WND_CLASS = 1407,
+    RESOURCE_DATA_NOT_FOUND = 1812,
+    RESOURCE_NAME_NOT_FOUND = 1814,
 }
 
 impl From<std::io::Error> for ERROR {
@@ -25,11 +71,28 @@ impl From<std::io::Error> for ERROR {
             std::io::ErrorKind::InvalidData => ERROR::INVALID_DATA,
             std::io::ErrorKind::AlreadyExists => ERROR::FILE_EXISTS,
             std::io::ErrorKind::InvalidInput => ERROR::INVALID_ACCESS,
-            _ => unimplemented!(),
+            std::io::ErrorKind::IsADirectory => ERROR::DIRECTORY,
+            _ => {
+                log::warn!("no Windows error for {err}");
+                ERROR::GEN_FAILURE
+            }
         }
     }
 }
 
+/// A bad pointer passed to a builtin, per the checked accessors of memory::Extensions.
+impl From<memory::OutOfBounds> for ERROR {
+    fn from(_err: memory::OutOfBounds) -> Self {
+        ERROR::NOACCESS
+    }
+}
+
+impl crate::winapi::stack_args::ToX86 for ERROR {
+    fn to_raw(&self) -> u32 {
+        *self as u32
+    }
+}
+
 impl From<ERROR> for u32 {
     fn from(err: ERROR) -> u32 {
         err as u32
@@ -6,6 +6,7 @@ use crate::{
         kernel32,
     },
 };
+use memory::simd;
 use std::cmp::min;
 
 #[derive(Clone)]
@@ -66,28 +67,12 @@ fn bit_blt(
             RasterOp::SRCCOPY => {
                 dst_row.copy_from_slice(src_row);
             }
-            RasterOp::NOTSRCCOPY => {
-                for (d, s) in dst_row.iter_mut().zip(src_row.iter()) {
-                    d[0] = !s[0];
-                    d[1] = !s[1];
-                    d[2] = !s[2];
-                    d[3] = s[3];
-                }
-            }
-            RasterOp::SRCAND => {
-                for (d, s) in dst_row.iter_mut().zip(src_row.iter()) {
-                    d[0] &= s[0];
-                    d[1] &= s[1];
-                    d[2] &= s[2];
-                    d[3] &= s[3];
-                }
-            }
+            RasterOp::NOTSRCCOPY => simd::copy_inverted(dst_row, src_row),
+            RasterOp::SRCAND => simd::and(dst_row, src_row),
             _ => todo!("unimplemented BitBlt with rop={rop:?}"),
         }
         if flush_alpha {
-            for p in dst_row {
-                p[3] = 0xFF;
-            }
+            simd::set_opaque(dst_row);
         }
     }
 }
@@ -120,12 +105,8 @@ fn pat_blt(
         }
         let dst_row = &mut dst[dst_off as usize..][..w as usize];
         match rop {
-            RasterOp::PATCOPY => {
-                dst_row.fill(color);
-            }
-            RasterOp::BLACKNESS => {
-                dst_row.fill([0, 0, 0, 0xFF]);
-            }
+            RasterOp::PATCOPY => simd::fill_pixels(dst_row, color),
+            RasterOp::BLACKNESS => simd::fill_pixels(dst_row, [0, 0, 0, 0xFF]),
             _ => todo!("unimplemented PatBlt with rop={rop:?}"),
         }
     }
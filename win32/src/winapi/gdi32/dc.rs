@@ -11,14 +11,14 @@ use crate::{
 pub type HDC = HANDLE<DC>;
 
 /// Target device for a DC.
-#[derive(Debug)]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub enum DCTarget {
     Memory(HGDIOBJ), // aka Bitmap
     Window(HWND),
     DirectDrawSurface(u32),
 }
 
-#[derive(Debug)]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct DC {
     // TODO: it's unclear to me what the representation of a DC ought to be.
     // DirectDraw can also create a DC, and DirectDraw (as a DLL that came
@@ -123,16 +123,37 @@ pub enum GetDeviceCapsArg {
 }
 
 #[win32_derive::dllexport]
-pub fn GetDeviceCaps(
-    _machine: &mut Machine,
-    hdc: HDC,
-    index: Result<GetDeviceCapsArg, u32>,
-) -> u32 {
+pub fn GetDeviceCaps(machine: &mut Machine, hdc: HDC, index: Result<GetDeviceCapsArg, u32>) -> u32 {
+    let display = &machine.state.user32.display;
+    let palettized = display.bpp <= 8;
     match index.unwrap() {
-        GetDeviceCapsArg::NUMCOLORS => -1i32 as u32, // true color
-        GetDeviceCapsArg::HORZRES => 640,
-        GetDeviceCapsArg::VERTRES => 480,
-        GetDeviceCapsArg::RASTERCAPS => 0, // none
+        GetDeviceCapsArg::NUMCOLORS => {
+            if palettized {
+                20 // static colors in the system palette
+            } else {
+                -1i32 as u32 // true color
+            }
+        }
+        GetDeviceCapsArg::HORZRES | GetDeviceCapsArg::DESKTOPHORZRES => display.width,
+        GetDeviceCapsArg::VERTRES | GetDeviceCapsArg::DESKTOPVERTRES => display.height,
+        GetDeviceCapsArg::BITSPIXEL => display.bpp,
+        GetDeviceCapsArg::PLANES => 1,
+        GetDeviceCapsArg::SIZEPALETTE => {
+            if palettized {
+                1 << display.bpp
+            } else {
+                0
+            }
+        }
+        GetDeviceCapsArg::VREFRESH => 60,
+        GetDeviceCapsArg::RASTERCAPS => {
+            const RC_PALETTE: u32 = 0x0100;
+            if palettized {
+                RC_PALETTE
+            } else {
+                0 // none
+            }
+        }
         _ => unimplemented!(),
     }
 }
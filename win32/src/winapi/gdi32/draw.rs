@@ -8,7 +8,7 @@ use crate::{
 
 /// COLORREF is a u32 containing RGB0, modeled specially here because there is the
 /// invalid marker value CLR_INVALID=0xffffffff.
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct COLORREF(u32);
 
 impl COLORREF {
@@ -49,12 +49,12 @@ impl crate::winapi::stack_args::ToX86 for COLORREF {
 
 pub const CLR_INVALID: COLORREF = COLORREF(0xffff_ffff);
 
-#[derive(Debug)]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct Pen {
     pub color: COLORREF,
 }
 
-#[derive(Debug)]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct Brush {
     pub color: Option<COLORREF>,
 }
@@ -147,7 +147,7 @@ pub fn LineTo(machine: &mut Machine, hdc: HDC, x: u32, y: u32) -> bool {
     false // fail
 }
 
-#[derive(Debug, Default, win32_derive::TryFromEnum)]
+#[derive(Debug, Default, win32_derive::TryFromEnum, serde::Serialize, serde::Deserialize)]
 pub enum R2 {
     #[default]
     COPYPEN = 13,
@@ -250,9 +250,11 @@ pub fn PtVisible(_machine: &mut Machine, hdc: HDC, x: i32, y: i32) -> bool {
     true // stub
 }
 
+/// Call lpProc(x, y, data) for each point of the line from start to end, excluding the
+/// end point, stepping as Bresenham does along the line's longer axis.
 #[win32_derive::dllexport]
-pub fn LineDDA(
-    _machine: &mut Machine,
+pub async fn LineDDA(
+    machine: &mut Machine,
     xStart: i32,
     yStart: i32,
     xEnd: i32,
@@ -260,5 +262,85 @@ pub fn LineDDA(
     lpProc: u32,
     data: u32,
 ) -> bool {
-    true // stub
+    let (mut x, mut y) = (xStart, yStart);
+    let (dx, dy) = ((xEnd - xStart).abs(), (yEnd - yStart).abs());
+    let (xstep, ystep) = ((xEnd - xStart).signum(), (yEnd - yStart).signum());
+    // Walk the major axis one step at a time, stepping the minor axis when err says so.
+    let (major, minor) = if dx > dy { (dx, dy) } else { (dy, dx) };
+    let mut err = 2 * minor - major;
+    for _ in 0..major {
+        machine.call_guest(lpProc, (x, y, data)).await;
+        let minor_step = err > 0;
+        if minor_step {
+            err += 2 * (minor - major);
+        } else {
+            err += 2 * minor;
+        }
+        if dx > dy {
+            x += xstep;
+            if minor_step {
+                y += ystep;
+            }
+        } else {
+            y += ystep;
+            if minor_step {
+                x += xstep;
+            }
+        }
+    }
+    true
+}
+
+#[cfg(all(test, feature = "x86-emu"))]
+mod tests {
+    use crate::test_guest::{builtin, new_machine, run, Code, Log};
+
+    /// The points LineDDA passes to its callback for the line from start to end.
+    fn line_dda(start: (i32, i32), end: (i32, i32)) -> Vec<(i32, i32)> {
+        let (mut machine, _host) = new_machine();
+        let line_dda = builtin(&mut machine, "gdi32.dll", "LineDDA");
+        let log = Log::alloc(&mut machine, 0x1000);
+        let mut code = Code::alloc(&mut machine, 0x100);
+        let proc = code.base + 0x80;
+        code.push(0x1234)
+            .push(proc)
+            .push(end.1 as u32)
+            .push(end.0 as u32)
+            .push(start.1 as u32)
+            .push(start.0 as u32)
+            .call(line_dda)
+            .exit_with(&mut machine, 0)
+            .at(0x80)
+            .recorder(&log, 3, 0);
+        code.start(&mut machine);
+        assert_eq!(run(&mut machine), 1);
+        log.read(&machine)
+            .chunks(3)
+            .map(|point| {
+                assert_eq!(point[2], 0x1234);
+                (point[0] as i32, point[1] as i32)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn shallow_line() {
+        assert_eq!(
+            line_dda((0, 0), (5, 2)),
+            [(0, 0), (1, 0), (2, 1), (3, 1), (4, 2)]
+        );
+    }
+
+    #[test]
+    fn steep_backwards_line() {
+        assert_eq!(
+            line_dda((10, 10), (9, 6)),
+            [(10, 10), (10, 9), (10, 8), (9, 7)]
+        );
+    }
+
+    #[test]
+    fn empty_line() {
+        assert_eq!(line_dda((3, 4), (3, 4)), []);
+    }
 }
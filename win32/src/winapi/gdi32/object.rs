@@ -8,7 +8,7 @@ use crate::{
 };
 use memory::ExtensionsMut;
 
-#[derive(Debug)]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub enum BitmapType {
     RGBA32(BitmapRGBA32),
     Mono(BitmapMono),
@@ -24,7 +24,7 @@ impl BitmapType {
 }
 
 /// GDI Object, as identified by HANDLEs.
-#[derive(Debug)]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub enum Object {
     Brush(Brush),
     Bitmap(BitmapType),
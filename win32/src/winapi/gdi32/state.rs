@@ -1,6 +1,7 @@
 use super::{DCTarget, Object, DC, HDC, HGDIOBJ};
 use crate::winapi::{handle::Handles, types::HWND};
 
+#[derive(serde::Serialize, serde::Deserialize)]
 pub struct State {
     pub dcs: Handles<HDC, DC>,
     pub screen_dc: HDC,
@@ -3,7 +3,7 @@ use crate::{
     winapi::{gdi32::COLORREF, stack_args::ArrayWithSize, types::HANDLE},
     Machine,
 };
-use memory::Pod;
+use memory::{ExtensionsMut, Pod};
 
 pub struct FONT {}
 pub type HFONT = HANDLE<FONT>;
@@ -63,7 +63,7 @@ pub fn TextOutW(
 }
 
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct TEXTMETRICA {
     pub tmHeight: u32,
     pub tmAscent: u32,
@@ -171,3 +171,218 @@ pub fn GetTextExtentPoint32W(
     };
     true
 }
+
+#[repr(C)]
+#[derive(Clone, Debug)]
+pub struct LOGFONTA {
+    pub lfHeight: i32,
+    pub lfWidth: i32,
+    pub lfEscapement: i32,
+    pub lfOrientation: i32,
+    pub lfWeight: i32,
+    pub lfItalic: u8,
+    pub lfUnderline: u8,
+    pub lfStrikeOut: u8,
+    pub lfCharSet: u8,
+    pub lfOutPrecision: u8,
+    pub lfClipPrecision: u8,
+    pub lfQuality: u8,
+    pub lfPitchAndFamily: u8,
+    pub lfFaceName: [u8; 32],
+}
+unsafe impl memory::Pod for LOGFONTA {}
+
+/// Also serves as the ENUMLOGFONTA of EnumFontFamilies, which it begins with.
+#[repr(C)]
+#[derive(Clone, Debug)]
+pub struct ENUMLOGFONTEXA {
+    pub elfLogFont: LOGFONTA,
+    pub elfFullName: [u8; 64],
+    pub elfStyle: [u8; 32],
+    pub elfScript: [u8; 32],
+}
+unsafe impl memory::Pod for ENUMLOGFONTEXA {}
+
+/// NEWTEXTMETRICEXA, which begins with the TEXTMETRICA given to older callbacks.
+#[repr(C)]
+#[derive(Clone, Debug)]
+pub struct NEWTEXTMETRICEXA {
+    pub ntmTm: TEXTMETRICA,
+    pub ntmFlags: u32,
+    pub ntmSizeEM: u32,
+    pub ntmCellHeight: u32,
+    pub ntmAvgWidth: u32,
+    pub ntmFontSig: [u32; 6],
+}
+unsafe impl memory::Pod for NEWTEXTMETRICEXA {}
+
+const ANSI_CHARSET: u8 = 0;
+const DEFAULT_CHARSET: u8 = 1;
+const RASTER_FONTTYPE: u32 = 1;
+
+/// The one font there is, sized as GetTextExtentPoint32 measures text.
+const FONT_FACE: &str = "System";
+
+/// Call lpProc(logfont, textmetric, FontType, lParam) for each font matching the face and
+/// charset, returning the last value it returned.
+async fn enum_fonts(
+    machine: &mut Machine,
+    face: Option<&str>,
+    charset: u8,
+    lpProc: u32,
+    lParam: u32,
+) -> i32 {
+    if face.is_some_and(|face| !face.eq_ignore_ascii_case(FONT_FACE)) {
+        return 1;
+    }
+    if charset != DEFAULT_CHARSET && charset != ANSI_CHARSET {
+        return 1;
+    }
+
+    let mut logfont = ENUMLOGFONTEXA::zeroed();
+    logfont.elfLogFont.lfHeight = 12;
+    logfont.elfLogFont.lfWidth = 10;
+    logfont.elfLogFont.lfWeight = 400; // FW_NORMAL
+    logfont.elfLogFont.lfCharSet = ANSI_CHARSET;
+    logfont.elfLogFont.lfPitchAndFamily = 0x31; // FIXED_PITCH | FF_MODERN
+    logfont.elfLogFont.lfFaceName[..FONT_FACE.len()].copy_from_slice(FONT_FACE.as_bytes());
+    logfont.elfFullName[..FONT_FACE.len()].copy_from_slice(FONT_FACE.as_bytes());
+    logfont.elfStyle[..7].copy_from_slice(b"Regular");
+    logfont.elfScript[..7].copy_from_slice(b"Western");
+
+    let mut metrics = NEWTEXTMETRICEXA::zeroed();
+    let tm = &mut metrics.ntmTm;
+    tm.tmHeight = 12;
+    tm.tmAscent = 10;
+    tm.tmDescent = 2;
+    tm.tmAveCharWidth = 10;
+    tm.tmMaxCharWidth = 10;
+    tm.tmWeight = 400;
+    tm.tmDigitizedAspectX = 96;
+    tm.tmDigitizedAspectY = 96;
+    tm.tmFirstChar = 0x20;
+    tm.tmLastChar = 0xFF;
+    tm.tmDefaultChar = 0x80;
+    tm.tmBreakChar = 0x20;
+    // For TEXTMETRIC, a clear FIXED_PITCH bit means fixed pitch.
+    tm.tmPitchAndFamily = 0x30; // FF_MODERN
+    tm.tmCharSet = ANSI_CHARSET;
+    metrics.ntmCellHeight = 12;
+    metrics.ntmAvgWidth = 10;
+
+    let mem = machine.emu.memory.mem();
+    let logfont_addr = machine
+        .state
+        .scratch
+        .alloc(mem, std::mem::size_of::<ENUMLOGFONTEXA>() as u32);
+    mem.put_pod::<ENUMLOGFONTEXA>(logfont_addr, logfont);
+    let metrics_addr = machine
+        .state
+        .scratch
+        .alloc(mem, std::mem::size_of::<NEWTEXTMETRICEXA>() as u32);
+    mem.put_pod::<NEWTEXTMETRICEXA>(metrics_addr, metrics);
+
+    let ret = machine
+        .call_guest(
+            lpProc,
+            (logfont_addr, metrics_addr, RASTER_FONTTYPE, lParam),
+        )
+        .await;
+
+    let mem = machine.emu.memory.mem();
+    machine.state.scratch.free(mem, metrics_addr);
+    machine.state.scratch.free(mem, logfont_addr);
+    ret as i32
+}
+
+#[win32_derive::dllexport]
+pub async fn EnumFontFamiliesA(
+    machine: &mut Machine,
+    hdc: HDC,
+    lpLogfont: Option<&str>,
+    lpProc: u32,
+    lParam: u32,
+) -> i32 {
+    enum_fonts(machine, lpLogfont, DEFAULT_CHARSET, lpProc, lParam).await
+}
+
+#[win32_derive::dllexport]
+pub async fn EnumFontFamiliesExA(
+    machine: &mut Machine,
+    hdc: HDC,
+    lpLogfont: Option<&LOGFONTA>,
+    lpProc: u32,
+    lParam: u32,
+    dwFlags: u32,
+) -> i32 {
+    let logfont = lpLogfont.unwrap();
+    let name = &logfont.lfFaceName;
+    let name = &name[..name.iter().position(|&c| c == 0).unwrap_or(name.len())];
+    let face = std::str::from_utf8(name)
+        .ok()
+        .filter(|face| !face.is_empty());
+    let charset = logfont.lfCharSet;
+    enum_fonts(machine, face, charset, lpProc, lParam).await
+}
+
+#[cfg(all(test, feature = "x86-emu"))]
+mod tests {
+    use crate::test_guest::{builtin, new_machine, run, Code, Log};
+    use memory::Extensions;
+
+    /// Call EnumFontFamiliesA for the given face, returning its result, the callback's
+    /// arguments, and the face name and height of each font as the callback saw them.
+    fn enum_fonts(face: &[u8]) -> (u32, Vec<u32>, Vec<u8>) {
+        let (mut machine, _host) = new_machine();
+        let enum_fonts = builtin(&mut machine, "gdi32.dll", "EnumFontFamiliesA");
+        let log = Log::alloc(&mut machine, 0x1000);
+        let mut code = Code::alloc(&mut machine, 0x200);
+        let (proc, seen, face_addr) = (code.base + 0x100, code.base + 0x180, code.base + 0x1C0);
+        code.push(9)
+            .push(proc)
+            .push(if face.is_empty() { 0 } else { face_addr })
+            .push(0)
+            .call(enum_fonts)
+            .exit_with(&mut machine, 0)
+            .at(0x100)
+            // Copy the start of lfFaceName and tmHeight, which only live during the call.
+            .emit(&[0x8B, 0x44, 0x24, 0x04]) // mov eax, [esp+4]
+            .emit(&[0x8B, 0x48, 28]) // mov ecx, [eax+28]
+            .emit(&[0x89, 0x0D]) // mov [seen], ecx
+            .emit32(seen)
+            .emit(&[0x8B, 0x48, 32]) // mov ecx, [eax+32]
+            .emit(&[0x89, 0x0D]) // mov [seen+4], ecx
+            .emit32(seen + 4)
+            .emit(&[0x8B, 0x44, 0x24, 0x08]) // mov eax, [esp+8]
+            .emit(&[0x8B, 0x08]) // mov ecx, [eax]
+            .emit(&[0x89, 0x0D]) // mov [seen+8], ecx
+            .emit32(seen + 8)
+            .recorder(&log, 4, 7)
+            .at(0x1C0)
+            .emit(face)
+            .emit(&[0]);
+        code.start(&mut machine);
+        let ret = run(&mut machine);
+        let seen = machine.mem().sub32(seen, 12).to_vec();
+        (ret, log.read(&machine), seen)
+    }
+
+    #[test]
+    fn enumerates_system_font() {
+        let (ret, args, seen) = enum_fonts(b"");
+        // The callback's return value is passed back.
+        assert_eq!(ret, 7);
+        assert_eq!(args[2..], [1, 9]); // RASTER_FONTTYPE, lParam
+        assert_eq!(&seen[..8], b"System\0\0");
+        assert_eq!(seen[8..], 12u32.to_le_bytes());
+    }
+
+    #[test]
+    fn face_filters_fonts() {
+        let (_, args, _) = enum_fonts(b"system");
+        assert_eq!(args.len(), 4);
+        let (ret, args, _) = enum_fonts(b"Arial");
+        assert_eq!(ret, 1);
+        assert_eq!(args, []);
+    }
+}
@@ -1,6 +1,6 @@
 //! Structures for working with HWND, HDC, etc. Windows handles.
 
-use std::{collections::HashMap, marker::PhantomData};
+use std::{collections::BTreeMap, marker::PhantomData};
 
 /// A more type-safe wrapper for HWND, HDC, etc. Windows handles.
 /// The <T> parameter is purely a nominal type and not used for carrying any data.
@@ -78,6 +78,18 @@ impl<T> std::fmt::Debug for HANDLE<T> {
     }
 }
 
+impl<T> serde::Serialize for HANDLE<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.raw.serialize(serializer)
+    }
+}
+
+impl<'de, T> serde::Deserialize<'de> for HANDLE<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self::from_raw(u32::deserialize(deserializer)?))
+    }
+}
+
 impl<T> std::fmt::LowerHex for HANDLE<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         self.raw.fmt(f)
@@ -110,8 +122,13 @@ impl<T> Handle for HANDLE<T> {
 }
 
 /// Maintains a mapping of HANDLE -> V, vending out new handles.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(bound(
+    serialize = "H: serde::Serialize, V: serde::Serialize",
+    deserialize = "H: serde::Deserialize<'de>, V: serde::Deserialize<'de>"
+))]
 pub struct Handles<H: Handle, V> {
-    map: HashMap<u32, V>,
+    map: BTreeMap<u32, V>,
     next: H,
 }
 
@@ -124,7 +141,7 @@ impl<H: Handle, V> Default for Handles<H, V> {
 impl<H: Handle, V> Handles<H, V> {
     pub fn new(start: u32) -> Self {
         Handles {
-            map: HashMap::default(),
+            map: BTreeMap::new(),
             next: H::from_raw(start),
         }
     }
@@ -157,6 +174,18 @@ impl<H: Handle, V> Handles<H, V> {
         self.map.values()
     }
 
+    /// Find the handle of the first value matching `pred`.
+    pub fn find(&self, pred: impl Fn(&V) -> bool) -> Option<H> {
+        self.map
+            .iter()
+            .find(|(_, v)| pred(v))
+            .map(|(&raw, _)| H::from_raw(raw))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut V> {
+        self.map.values_mut()
+    }
+
     pub fn remove(&mut self, handle: H) -> Option<V> {
         self.map.remove(&handle.to_raw())
     }
@@ -1,118 +1,456 @@
 //! Heap memory allocator. Used for win32 API HeapCreate() etc. implementation
 //! and also for win32-visible allocations created by other calls (like in
 //! DirectDraw).
+//!
+//! Each block starts with a BlockHeader in guest memory, followed by the caller's data.
+//! Free blocks are tracked on the host side, both by address (to coalesce neighbors)
+//! and in size-class bins (to find a fit without scanning the whole heap).
+//! Headers carry a canary so that guest code scribbling over them is caught by
+//! HeapValidate or the next free, rather than silently corrupting the free lists.
+
+use std::collections::{BTreeMap, BTreeSet};
 
 use super::alloc::align_to;
 use memory::{Extensions, ExtensionsMut, Mem};
 
-#[derive(Default)]
+/// Header preceding each block handed out by the heap.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+struct BlockHeader {
+    /// Size of the whole block including this header.
+    size: u32,
+    /// Size the caller asked for, as reported by HeapSize.
+    requested: u32,
+    /// Guest address of the code that allocated the block, for diagnostics.
+    caller: u32,
+    /// BLOCK_MAGIC xor the header's own address.
+    magic: u32,
+}
+unsafe impl memory::Pod for BlockHeader {}
+
+pub const HEADER_SIZE: u32 = std::mem::size_of::<BlockHeader>() as u32;
+const BLOCK_MAGIC: u32 = 0xB10C_4EA9;
+
+/// Blocks are 8-byte aligned, like the Windows heap.
+const ALIGN: u32 = 8;
+
+/// A block in the heap, as seen when walking it.
+#[derive(Debug, Clone, Copy)]
+pub struct Block {
+    /// Address of the block's data, just past its header.
+    pub addr: u32,
+    /// Bytes available at addr: the requested size for busy blocks, all of it for free ones.
+    pub size: u32,
+    pub busy: bool,
+    pub caller: u32,
+}
+
+/// Usage summary of a heap, shown in the debugger.
+#[derive(Debug, Default, serde::Serialize)]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+pub struct HeapStats {
+    pub addr: u32,
+    pub size: u32,
+    pub used: u32,
+    pub busy_blocks: u32,
+    pub free_blocks: u32,
+    /// Address of the first corrupt block header, if any.
+    pub corrupt: Option<u32>,
+    /// The call sites holding the most memory: (caller, blocks, bytes).
+    pub top_callers: Vec<(u32, u32, u32)>,
+}
+
+/// Smallest block worth splitting off as free space.
+const MIN_BLOCK: u32 = HEADER_SIZE + ALIGN;
+
+/// Blocks up to this size get a bin per ALIGN-sized step; larger ones share a bin per power of two.
+const SMALL_LIMIT: u32 = 512;
+const SMALL_BINS: usize = (SMALL_LIMIT / ALIGN) as usize;
+const BINS: usize = SMALL_BINS + 32;
+
+fn bin_for(size: u32) -> usize {
+    if size < SMALL_LIMIT {
+        (size / ALIGN) as usize
+    } else {
+        SMALL_BINS + (size.ilog2() - SMALL_LIMIT.ilog2()) as usize
+    }
+}
+
+/// The block size needed to hold a request of `size` bytes.
+fn block_size(size: u32) -> Option<u32> {
+    let size = size.checked_add(HEADER_SIZE + ALIGN - 1)? & !(ALIGN - 1);
+    Some(size.max(MIN_BLOCK))
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
 pub struct Heap {
     pub addr: u32,
     pub size: u32,
-    freelist: Vec<FreeNode>,
+    /// Free blocks, address => size.
+    free: BTreeMap<u32, u32>,
+    /// Free block addresses, by size class.
+    bins: Vec<BTreeSet<u32>>,
+    /// Whether failed allocations raise an exception, per HeapCreate's
+    /// HEAP_GENERATE_EXCEPTIONS, rather than just returning NULL.
+    pub generate_exceptions: bool,
+}
+
+impl Default for Heap {
+    fn default() -> Self {
+        Heap {
+            addr: 0,
+            size: 0,
+            free: BTreeMap::new(),
+            bins: vec![BTreeSet::new(); BINS],
+            generate_exceptions: false,
+        }
+    }
 }
 
 impl Heap {
     pub fn new(addr: u32, size: u32) -> Self {
-        let freelist = vec![FreeNode { addr, size }];
-        Heap {
+        let mut heap = Heap {
             addr,
             size,
-            freelist,
+            ..Default::default()
+        };
+        let start = align_to(addr, ALIGN as usize);
+        let end = (addr + size) & !(ALIGN - 1);
+        if end > start {
+            heap.insert_free(start, end - start);
         }
+        heap
     }
 
     fn range(&self) -> std::ops::Range<u32> {
         self.addr..self.addr + self.size
     }
 
+    fn insert_free(&mut self, addr: u32, size: u32) {
+        self.free.insert(addr, size);
+        self.bins[bin_for(size)].insert(addr);
+    }
+
+    fn remove_free(&mut self, addr: u32) -> u32 {
+        let size = self.free.remove(&addr).unwrap();
+        self.bins[bin_for(size)].remove(&addr);
+        size
+    }
+
+    /// Find the smallest-class free block that fits `size`.
+    fn find_fit(&self, size: u32) -> Option<u32> {
+        let bin = bin_for(size);
+        for bin in &self.bins[bin..] {
+            if let Some(&addr) = bin.iter().find(|addr| self.free[addr] >= size) {
+                return Some(addr);
+            }
+        }
+        None
+    }
+
+    /// Read the header of the block at `addr`, if it's intact.
+    fn header(&self, mem: Mem, addr: u32) -> Option<BlockHeader> {
+        if !self.range().contains(&addr) || addr + HEADER_SIZE > self.addr + self.size {
+            return None;
+        }
+        let header = mem.get_pod::<BlockHeader>(addr);
+        let ok = header.magic == BLOCK_MAGIC ^ addr
+            && header.size >= MIN_BLOCK
+            && header.size % ALIGN == 0
+            && header.requested <= header.size - HEADER_SIZE
+            && header.size <= self.addr + self.size - addr;
+        ok.then_some(header)
+    }
+
+    /// Carve a block of `size` bytes out of the block at `addr` of `avail` bytes,
+    /// returning the leftover to the free lists.
+    fn split(&mut self, mem: Mem, addr: u32, avail: u32, size: u32, requested: u32, caller: u32) {
+        let size = if avail - size >= MIN_BLOCK {
+            self.insert_free(addr + size, avail - size);
+            size
+        } else {
+            avail
+        };
+        let magic = BLOCK_MAGIC ^ addr;
+        let header = BlockHeader {
+            size,
+            requested,
+            caller,
+            magic,
+        };
+        mem.put_pod::<BlockHeader>(addr, header);
+    }
+
+    /// Allocate `size` bytes, returning 0 if the heap is exhausted.
     pub fn alloc(&mut self, mem: Mem, size: u32) -> u32 {
-        let size = align_to(size, 4) + 4;
-        let i = self
-            .freelist
-            .iter()
-            .position(|f| f.size >= size)
-            .unwrap_or_else(|| panic!("heap size {:x} oom {:x}", self.size, size));
-        let free = &mut self.freelist[i];
-        let addr = free.addr;
-        free.size -= size;
-        free.addr += size;
-        if free.size == 0 {
-            self.freelist.remove(i);
-        }
-        mem.put_pod::<u32>(addr, size);
-        addr + 4
+        let Some(block) = block_size(size) else {
+            return 0;
+        };
+        let Some(addr) = self.find_fit(block) else {
+            log::warn!("heap {:x} size {:x} oom {:x}", self.addr, self.size, size);
+            return 0;
+        };
+        let avail = self.remove_free(addr);
+        self.split(mem, addr, avail, block, size, 0);
+        addr + HEADER_SIZE
+    }
+
+    /// Record the guest code responsible for the block at `addr`.
+    pub fn set_caller(&self, mem: Mem, addr: u32, caller: u32) {
+        let header = addr - HEADER_SIZE;
+        if let Some(mut block) = self.header(mem, header) {
+            block.caller = caller;
+            mem.put_pod::<BlockHeader>(header, block);
+        }
     }
 
     pub fn size(&self, mem: Mem, addr: u32) -> u32 {
-        mem.get_pod::<u32>(addr - 4) - 4
+        mem.get_pod::<BlockHeader>(addr - HEADER_SIZE).requested
     }
 
     pub fn free(&mut self, mem: Mem, addr: u32) {
-        let addr = addr - 4;
-        let size = mem.get_pod::<u32>(addr);
-
+        if addr == 0 {
+            return;
+        }
+        let mut addr = addr - HEADER_SIZE;
         if !self.range().contains(&addr) {
             panic!("free of addr not on heap");
         }
-
-        let mut insert_index = 0;
-        for (i, node) in self.freelist.iter().enumerate() {
-            if node.range().contains(&addr) {
+        if let Some((&prev, &prev_size)) = self.free.range(..=addr).next_back() {
+            if prev + prev_size > addr {
                 // address is within already free block
                 log::warn!("ignoring double free");
                 return;
             }
-            if node.addr > addr {
-                insert_index = i;
-                break;
-            }
         }
+        let Some(header) = self.header(mem, addr) else {
+            log::error!(
+                "heap {:x}: ignoring free of corrupt block {addr:x}",
+                self.addr
+            );
+            return;
+        };
+        let mut size = header.size;
 
-        let mut joined = false;
-        if insert_index > 0 {
-            // Check if merging with earlier block.
-            let prev_i = insert_index - 1;
-            let prev = &mut self.freelist[prev_i];
-            if prev.addr + prev.size == addr {
-                prev.size += size;
-                joined = true;
+        // Coalesce with free neighbors on either side.
+        if let Some((&prev, &prev_size)) = self.free.range(..addr).next_back() {
+            if prev + prev_size == addr {
+                self.remove_free(prev);
+                addr = prev;
+                size += prev_size;
             }
         }
+        if self.free.contains_key(&(addr + size)) {
+            size += self.remove_free(addr + size);
+        }
+        self.insert_free(addr, size);
+    }
 
-        if insert_index < self.freelist.len() {
-            // Check if merging with later block.
-            let next = &mut self.freelist[insert_index];
-            if addr + size == next.addr {
-                if joined {
-                    let next_size = next.size;
-                    let prev = &mut self.freelist[insert_index - 1];
-                    prev.size += next_size;
-                    self.freelist.remove(insert_index);
-                } else {
-                    next.addr -= size;
-                    next.size += size;
-                    joined = true;
-                }
+    /// Resize the block at `addr` without moving it, returning false if that isn't possible.
+    pub fn resize_in_place(&mut self, mem: Mem, addr: u32, size: u32) -> bool {
+        let Some(want) = block_size(size) else {
+            return false;
+        };
+        let header = addr - HEADER_SIZE;
+        let Some(BlockHeader {
+            size: cur, caller, ..
+        }) = self.header(mem, header)
+        else {
+            log::error!(
+                "heap {:x}: cannot resize corrupt block {header:x}",
+                self.addr
+            );
+            return false;
+        };
+        if want <= cur {
+            if cur - want < MIN_BLOCK {
+                self.split(mem, header, cur, cur, size, caller);
+                return true;
+            }
+            // Give back the tail, merging it with any free block after it.
+            self.split(mem, header, want, want, size, caller);
+            let tail = header + want;
+            let mut tail_size = cur - want;
+            if self.free.contains_key(&(tail + tail_size)) {
+                tail_size += self.remove_free(tail + tail_size);
+            }
+            self.insert_free(tail, tail_size);
+            return true;
+        }
+        match self.free.get(&(header + cur)) {
+            Some(&next_size) if cur + next_size >= want => {
+                self.remove_free(header + cur);
+                self.split(mem, header, cur + next_size, want, size, caller);
+                true
             }
+            _ => false,
         }
+    }
 
-        if !joined {
-            let free = FreeNode { addr, size };
-            self.freelist.insert(insert_index, free);
+    /// Resize the block at `addr`, moving it if it can't grow in place.
+    /// Returns the new address, or 0 if the heap is exhausted.
+    pub fn realloc(&mut self, mem: Mem, addr: u32, size: u32) -> u32 {
+        if self.resize_in_place(mem, addr, size) {
+            return addr;
+        }
+        let Some(old) = self.header(mem, addr - HEADER_SIZE) else {
+            return 0; // corrupt, already logged by resize_in_place
+        };
+        let new_addr = self.alloc(mem, size);
+        if new_addr == 0 {
+            return 0;
         }
+        mem.copy(addr, new_addr, old.requested.min(size));
+        self.set_caller(mem, new_addr, old.caller);
+        self.free(mem, addr);
+        new_addr
     }
-}
 
-/// Entry in the FreeList.
-#[derive(Debug)]
-struct FreeNode {
-    addr: u32,
-    size: u32,
+    /// The block whose header is at `addr`, along with the size of the whole block.
+    /// Fails with `addr` if the header is corrupt.
+    fn block_at(&self, mem: Mem, addr: u32) -> Result<(Block, u32), u32> {
+        if let Some(&size) = self.free.get(&addr) {
+            let block = Block {
+                addr: addr + HEADER_SIZE,
+                size: size - HEADER_SIZE,
+                busy: false,
+                caller: 0,
+            };
+            return Ok((block, size));
+        }
+        let header = self.header(mem, addr).ok_or(addr)?;
+        let block = Block {
+            addr: addr + HEADER_SIZE,
+            size: header.requested,
+            busy: true,
+            caller: header.caller,
+        };
+        Ok((block, header.size))
+    }
+
+    /// The block following the one whose data is at `prev`, or the first block if None.
+    pub fn next_block(&self, mem: Mem, prev: Option<u32>) -> Option<Result<Block, u32>> {
+        let addr = match prev {
+            None => align_to(self.addr, ALIGN as usize),
+            Some(prev) => match self.block_at(mem, prev - HEADER_SIZE) {
+                Ok((_, size)) => prev - HEADER_SIZE + size,
+                Err(addr) => return Some(Err(addr)),
+            },
+        };
+        if addr >= (self.addr + self.size) & !(ALIGN - 1) {
+            return None;
+        }
+        Some(self.block_at(mem, addr).map(|(block, _)| block))
+    }
+
+    /// Walk the blocks of the heap in address order, stopping early at a corrupt header,
+    /// whose address is then returned as the error.
+    pub fn walk(&self, mem: Mem, mut f: impl FnMut(Block)) -> Result<(), u32> {
+        let mut prev = None;
+        while let Some(block) = self.next_block(mem, prev) {
+            let block = block?;
+            f(block);
+            prev = Some(block.addr);
+        }
+        Ok(())
+    }
+
+    /// Check the block at `addr`, or the whole heap if None.
+    pub fn validate(&self, mem: Mem, addr: Option<u32>) -> bool {
+        match addr {
+            Some(addr) => addr >= HEADER_SIZE && self.header(mem, addr - HEADER_SIZE).is_some(),
+            None => self.walk(mem, |_| {}).is_ok(),
+        }
+    }
+
+    pub fn stats(&self, mem: Mem) -> HeapStats {
+        let mut stats = HeapStats {
+            addr: self.addr,
+            size: self.size,
+            ..Default::default()
+        };
+        let mut callers = std::collections::HashMap::<u32, (u32, u32)>::new();
+        let walked = self.walk(mem, |block| {
+            if block.busy {
+                stats.busy_blocks += 1;
+                stats.used += block.size;
+                let entry = callers.entry(block.caller).or_default();
+                entry.0 += 1;
+                entry.1 += block.size;
+            } else {
+                stats.free_blocks += 1;
+            }
+        });
+        stats.corrupt = walked.err();
+        let mut callers: Vec<_> = callers.into_iter().map(|(c, (n, b))| (c, n, b)).collect();
+        callers.sort_by_key(|&(_, _, bytes)| std::cmp::Reverse(bytes));
+        callers.truncate(10);
+        stats.top_callers = callers;
+        stats
+    }
 }
 
-impl FreeNode {
-    fn range(&self) -> std::ops::Range<u32> {
-        self.addr..self.addr + self.size
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn heap(buf: &mut [u8]) -> (Heap, Mem) {
+        let ptrs = buf.as_mut_ptr_range();
+        let mem = Mem::from_ptrs(ptrs.start as *const u8..ptrs.end as *const u8);
+        (Heap::new(0, buf.len() as u32), mem)
+    }
+
+    #[test]
+    fn reuse_and_coalesce() {
+        let mut buf = vec![0u8; 0x1000];
+        let (mut heap, mem) = heap(&mut buf);
+        let a = heap.alloc(mem, 10);
+        let b = heap.alloc(mem, 20);
+        let c = heap.alloc(mem, 30);
+        assert_eq!(heap.size(mem, b), 20);
+        heap.free(mem, a);
+        heap.free(mem, b);
+        // a and b coalesced, so a larger block fits where they were.
+        assert_eq!(heap.alloc(mem, 40), a);
+        heap.free(mem, c);
+    }
+
+    #[test]
+    fn realloc_in_place() {
+        let mut buf = vec![0u8; 0x1000];
+        let (mut heap, mem) = heap(&mut buf);
+        let a = heap.alloc(mem, 16);
+        let b = heap.alloc(mem, 16);
+        heap.free(mem, b);
+        assert_eq!(heap.realloc(mem, a, 100), a);
+        assert_eq!(heap.size(mem, a), 100);
+
+        let c = heap.alloc(mem, 16);
+        mem.put_pod::<u32>(a, 0x1234);
+        let moved = heap.realloc(mem, a, 200);
+        assert_ne!(moved, a);
+        assert_eq!(mem.get_pod::<u32>(moved), 0x1234);
+        heap.free(mem, c);
+    }
+
+    #[test]
+    fn detect_corruption() {
+        let mut buf = vec![0u8; 0x1000];
+        let (mut heap, mem) = heap(&mut buf);
+        let a = heap.alloc(mem, 16);
+        let b = heap.alloc(mem, 16);
+        assert!(heap.validate(mem, None));
+        // Overrun a's data into b's header.
+        mem.sub32_mut(a, 24).fill(0xCC);
+        assert!(heap.validate(mem, Some(a)));
+        assert!(!heap.validate(mem, Some(b)));
+        assert_eq!(heap.stats(mem).corrupt, Some(b - HEADER_SIZE));
+    }
+
+    #[test]
+    fn oom() {
+        let mut buf = vec![0u8; 0x100];
+        let (mut heap, mem) = heap(&mut buf);
+        assert_eq!(heap.alloc(mem, 0x200), 0);
     }
 }
@@ -1,10 +1,291 @@
+use super::{set_last_error, STDERR_HFILE, STDIN_HFILE, STDOUT_HFILE};
 use crate::str16::Str16;
 use crate::winapi::handle::HANDLE;
 use crate::winapi::kernel32::WriteFile;
-use crate::winapi::stack_args::ArrayWithSize;
-use crate::winapi::types::{DWORD, HFILE, WORD};
+use crate::winapi::stack_args::{ArrayWithSize, ArrayWithSizeMut};
+use crate::winapi::types::{DWORD, HFILE, HWND, WORD};
+use crate::winapi::ERROR;
 use crate::Machine;
 
+/// ENABLE_PROCESSED_INPUT | ENABLE_LINE_INPUT | ENABLE_ECHO_INPUT | ENABLE_MOUSE_INPUT
+/// | ENABLE_INSERT_MODE | ENABLE_QUICK_EDIT_MODE | ENABLE_EXTENDED_FLAGS
+/// | ENABLE_AUTO_POSITION, as a fresh cmd.exe console reports.
+const DEFAULT_INPUT_MODE: u32 = 0x1F7;
+/// ENABLE_PROCESSED_OUTPUT | ENABLE_WRAP_AT_EOL_OUTPUT
+const DEFAULT_OUTPUT_MODE: u32 = 0x3;
+
+/// The process's console, backed by the host's stdin/stdout.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Console {
+    /// Whether the process has a console at all; AllocConsole fails if it already does.
+    pub attached: bool,
+    /// Handles returned by GetStdHandle, indexed by -10 - STD_*_HANDLE.
+    pub std_handles: [HFILE; 3],
+    pub input_mode: u32,
+    pub output_mode: u32,
+    pub title: String,
+}
+
+impl Default for Console {
+    fn default() -> Self {
+        Console {
+            attached: true,
+            std_handles: [STDIN_HFILE, STDOUT_HFILE, STDERR_HFILE],
+            input_mode: DEFAULT_INPUT_MODE,
+            output_mode: DEFAULT_OUTPUT_MODE,
+            title: String::new(),
+        }
+    }
+}
+
+impl Console {
+    /// The state of a GUI-subsystem process, which starts without a console and so
+    /// with null std handles until it calls AllocConsole.
+    pub fn detached() -> Self {
+        Console {
+            attached: false,
+            std_handles: [HFILE::null(); 3],
+            ..Default::default()
+        }
+    }
+}
+
+fn is_console(machine: &Machine, handle: HFILE) -> bool {
+    machine.state.kernel32.console.attached
+        && matches!(handle, STDIN_HFILE | STDOUT_HFILE | STDERR_HFILE)
+}
+
+/// Read console input, blocking the calling thread until some is available.
+/// Like the real console in line mode, this returns as soon as any input arrives.
+pub async fn read_console(machine: &mut Machine, buf: &mut [u8]) -> Result<usize, ERROR> {
+    // The host may have no input yet (e.g. the web console panel), and wakes us when it does.
+    super::block_until(machine, |machine| match machine.host.read_stdin(buf) {
+        Err(ERROR::IO_PENDING) => None,
+        res => Some(res),
+    })
+    .await
+}
+
+#[win32_derive::dllexport]
+pub fn AllocConsole(machine: &mut Machine) -> bool {
+    let console = &mut machine.state.kernel32.console;
+    if console.attached {
+        set_last_error(machine, ERROR::ACCESS_DENIED);
+        return false;
+    }
+    *console = Console::default();
+    true
+}
+
+#[win32_derive::dllexport]
+pub fn AttachConsole(machine: &mut Machine, dwProcessId: u32) -> bool {
+    // There is no parent process console to attach to, but we can behave as if there were.
+    AllocConsole(machine)
+}
+
+#[win32_derive::dllexport]
+pub fn FreeConsole(machine: &mut Machine) -> bool {
+    machine.state.kernel32.console = Console::detached();
+    true
+}
+
+#[win32_derive::dllexport]
+pub fn GetConsoleWindow(_machine: &mut Machine) -> HWND {
+    HWND::null() // the console isn't a window we manage
+}
+
+#[win32_derive::dllexport]
+pub fn GetConsoleMode(
+    machine: &mut Machine,
+    hConsoleHandle: HFILE,
+    lpMode: Option<&mut u32>,
+) -> bool {
+    if !is_console(machine, hConsoleHandle) {
+        set_last_error(machine, ERROR::INVALID_HANDLE);
+        return false;
+    }
+    let console = &machine.state.kernel32.console;
+    let mode = if hConsoleHandle == STDIN_HFILE {
+        console.input_mode
+    } else {
+        console.output_mode
+    };
+    match lpMode {
+        Some(out) => *out = mode,
+        None => {
+            set_last_error(machine, ERROR::INVALID_PARAMETER);
+            return false;
+        }
+    }
+    true
+}
+
+#[win32_derive::dllexport]
+pub fn SetConsoleMode(machine: &mut Machine, hConsoleHandle: HFILE, dwMode: u32) -> bool {
+    if !is_console(machine, hConsoleHandle) {
+        set_last_error(machine, ERROR::INVALID_HANDLE);
+        return false;
+    }
+    let console = &mut machine.state.kernel32.console;
+    if hConsoleHandle == STDIN_HFILE {
+        console.input_mode = dwMode;
+    } else {
+        console.output_mode = dwMode;
+    }
+    true
+}
+
+#[win32_derive::dllexport]
+pub fn GetConsoleCP(_machine: &mut Machine) -> u32 {
+    437 // OEM United States
+}
+
+#[win32_derive::dllexport]
+pub fn GetConsoleOutputCP(_machine: &mut Machine) -> u32 {
+    437 // OEM United States
+}
+
+#[win32_derive::dllexport]
+pub fn SetConsoleTitleA(machine: &mut Machine, lpConsoleTitle: Option<&str>) -> bool {
+    machine.state.kernel32.console.title = lpConsoleTitle.unwrap_or_default().to_string();
+    true
+}
+
+#[win32_derive::dllexport]
+pub fn SetConsoleTitleW(machine: &mut Machine, lpConsoleTitle: Option<&Str16>) -> bool {
+    machine.state.kernel32.console.title =
+        lpConsoleTitle.map(|s| s.to_string()).unwrap_or_default();
+    true
+}
+
+#[win32_derive::dllexport]
+pub fn GetConsoleTitleA(machine: &mut Machine, lpConsoleTitle: ArrayWithSizeMut<u8>) -> u32 {
+    let title = machine.state.kernel32.console.title.as_bytes();
+    let Some(buf) = lpConsoleTitle.to_option() else {
+        return 0;
+    };
+    if buf.is_empty() {
+        return 0;
+    }
+    let n = title.len().min(buf.len() - 1);
+    buf[..n].copy_from_slice(&title[..n]);
+    buf[n] = 0;
+    n as u32
+}
+
+#[win32_derive::dllexport]
+pub fn GetConsoleTitleW(machine: &mut Machine, lpConsoleTitle: ArrayWithSizeMut<u16>) -> u32 {
+    let title: Vec<u16> = machine
+        .state
+        .kernel32
+        .console
+        .title
+        .encode_utf16()
+        .collect();
+    let Some(buf) = lpConsoleTitle.to_option() else {
+        return 0;
+    };
+    if buf.is_empty() {
+        return 0;
+    }
+    let n = title.len().min(buf.len() - 1);
+    buf[..n].copy_from_slice(&title[..n]);
+    buf[n] = 0;
+    n as u32
+}
+
+#[win32_derive::dllexport]
+pub fn GetNumberOfConsoleInputEvents(
+    machine: &mut Machine,
+    hConsoleInput: HFILE,
+    lpNumberOfEvents: Option<&mut u32>,
+) -> bool {
+    if hConsoleInput != STDIN_HFILE || !is_console(machine, hConsoleInput) {
+        set_last_error(machine, ERROR::INVALID_HANDLE);
+        return false;
+    }
+    // Input only arrives as whole lines via ReadConsole, so there are never pending events.
+    if let Some(n) = lpNumberOfEvents {
+        *n = 0;
+    }
+    true
+}
+
+#[win32_derive::dllexport]
+pub fn FlushConsoleInputBuffer(machine: &mut Machine, hConsoleInput: HFILE) -> bool {
+    if hConsoleInput != STDIN_HFILE || !is_console(machine, hConsoleInput) {
+        set_last_error(machine, ERROR::INVALID_HANDLE);
+        return false;
+    }
+    true
+}
+
+#[win32_derive::dllexport]
+pub async fn ReadConsoleA(
+    machine: &mut Machine,
+    hConsoleInput: HFILE,
+    lpBuffer: ArrayWithSizeMut<'_, u8>,
+    lpNumberOfCharsRead: Option<&mut u32>,
+    pInputControl: u32,
+) -> bool {
+    if hConsoleInput != STDIN_HFILE || !is_console(machine, hConsoleInput) {
+        set_last_error(machine, ERROR::INVALID_HANDLE);
+        return false;
+    }
+    let Some(buf) = lpBuffer.to_option() else {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return false;
+    };
+    let n = match read_console(machine, buf).await {
+        Ok(n) => n,
+        Err(err) => {
+            set_last_error(machine, err);
+            return false;
+        }
+    };
+    if let Some(read) = lpNumberOfCharsRead {
+        *read = n as u32;
+    }
+    true
+}
+
+#[win32_derive::dllexport]
+pub async fn ReadConsoleW(
+    machine: &mut Machine,
+    hConsoleInput: HFILE,
+    lpBuffer: ArrayWithSizeMut<'_, u16>,
+    lpNumberOfCharsRead: Option<&mut u32>,
+    pInputControl: u32,
+) -> bool {
+    if hConsoleInput != STDIN_HFILE || !is_console(machine, hConsoleInput) {
+        set_last_error(machine, ERROR::INVALID_HANDLE);
+        return false;
+    }
+    let Some(buf) = lpBuffer.to_option() else {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return false;
+    };
+    // Read at most as many bytes as we have room for chars, so nothing gets dropped
+    // when the input is ASCII.
+    let mut bytes = vec![0u8; buf.len()];
+    let n = match read_console(machine, &mut bytes).await {
+        Ok(n) => n,
+        Err(err) => {
+            set_last_error(machine, err);
+            return false;
+        }
+    };
+    let text: Vec<u16> = String::from_utf8_lossy(&bytes[..n])
+        .encode_utf16()
+        .collect();
+    let n = text.len().min(buf.len());
+    buf[..n].copy_from_slice(&text[..n]);
+    if let Some(read) = lpNumberOfCharsRead {
+        *read = n as u32;
+    }
+    true
+}
+
 #[win32_derive::dllexport]
 pub fn SetConsoleCtrlHandler(_machine: &mut Machine, _handlerRoutine: DWORD, _add: u32) -> bool {
     true // succeed
@@ -62,18 +343,17 @@ pub fn GetConsoleScreenBufferInfo(
 
 #[win32_derive::dllexport]
 pub fn WriteConsoleA(
-    _machine: &mut Machine,
-    hConsoleOutput: HANDLE<()>,
+    machine: &mut Machine,
+    hConsoleOutput: HFILE,
     lpBuffer: ArrayWithSize<u8>,
     lpNumberOfCharsWritten: Option<&mut u32>,
-    lpReserved: u32,
+    _lpReserved: u32,
 ) -> bool {
-    let msg = std::str::from_utf8(lpBuffer.unwrap()).unwrap();
-    log::debug!("WriteConsoleA: {:?}", msg);
-    if let Some(w) = lpNumberOfCharsWritten {
-        *w = msg.len() as u32;
+    if !is_console(machine, hConsoleOutput) {
+        set_last_error(machine, ERROR::INVALID_HANDLE);
+        return false;
     }
-    true // success
+    WriteFile(machine, hConsoleOutput, lpBuffer, lpNumberOfCharsWritten, 0)
 }
 
 #[win32_derive::dllexport]
@@ -84,6 +364,10 @@ pub fn WriteConsoleW(
     lpNumberOfCharsWritten: Option<&mut u32>,
     _lpReserved: u32,
 ) -> bool {
+    if !is_console(machine, hConsoleOutput) {
+        set_last_error(machine, ERROR::INVALID_HANDLE);
+        return false;
+    }
     let buf = Str16::from_buffer(lpBuffer.unwrap()).to_string();
     let mut bytes_written = 0;
     if !WriteFile(
@@ -1,4 +1,4 @@
-use crate::winapi::kernel32::set_last_error;
+use crate::winapi::kernel32::{set_last_error, vfs};
 use memory::{Extensions, Pod};
 
 use crate::{
@@ -8,7 +8,6 @@ use crate::{
     str16::expect_ascii,
     winapi::{self, stack_args::ArrayWithSizeMut, types::*, ImportSymbol},
 };
-use std::io::Write;
 use typed_path::WindowsPath;
 
 // HMODULE must be the address of the loaded DLL image.
@@ -17,25 +16,58 @@ use typed_path::WindowsPath;
 pub struct HMODULET;
 pub type HMODULE = HANDLE<HMODULET>;
 
+#[derive(serde::Serialize, serde::Deserialize)]
 pub struct DLL {
+    /// Module name: the lowercased file name.
     pub name: String,
+    /// Full guest path of the file it was loaded from, as reported by GetModuleFileName.
+    pub path: String,
 
     pub dll: pe::DLL,
+
+    /// Number of loads not yet matched by FreeLibrary; imports count as a load.
+    pub ref_count: u32,
+    /// Whether DllMain gets DLL_THREAD_ATTACH/DETACH, cleared by DisableThreadLibraryCalls.
+    pub thread_calls: bool,
 }
 
 impl DLL {
     pub fn resolve(&mut self, sym: &ImportSymbol) -> Option<u32> {
         match *sym {
             ImportSymbol::Name(name) => self.dll.names.get(name).copied(),
-            ImportSymbol::Ordinal(ord) => self
-                .dll
-                .fns
-                .get((ord - self.dll.ordinal_base) as usize)
-                .copied(),
+            ImportSymbol::Ordinal(ord) => {
+                let index = ord.checked_sub(self.dll.ordinal_base)?;
+                // Unused ordinals have a zero address, i.e. the image base.
+                let addr = *self.dll.fns.get(index as usize)?;
+                if addr == self.dll.base {
+                    return None;
+                }
+                Some(addr)
+            }
         }
     }
 }
 
+/// Look up an export of a loaded DLL, following forwarded exports to the DLLs they name.
+pub fn resolve_export(machine: &mut Machine, hmodule: HMODULE, sym: &ImportSymbol) -> Option<u32> {
+    let dll = machine.state.kernel32.dlls.get_mut(&hmodule)?;
+    let addr = dll.resolve(sym)?;
+    let Some(target) = dll.dll.forwards.get(&addr).cloned() else {
+        return Some(addr);
+    };
+    // The target is "DLL.Name" or "DLL.#ordinal".
+    let Some((module, func)) = target.rsplit_once('.') else {
+        log::warn!("{}: bad forwarded export {target:?}", dll.name);
+        return None;
+    };
+    let hmodule = load_library(machine, module);
+    let sym = match func.strip_prefix('#') {
+        Some(ordinal) => ImportSymbol::Ordinal(ordinal.parse().ok()?),
+        None => ImportSymbol::Name(func),
+    };
+    resolve_export(machine, hmodule, &sym)
+}
+
 pub fn normalize_module_name(name: &str) -> String {
     let mut name = name.to_ascii_lowercase();
     if !name.ends_with(".dll") && !name.ends_with(".") {
@@ -44,27 +76,89 @@ pub fn normalize_module_name(name: &str) -> String {
     name
 }
 
-#[win32_derive::dllexport]
-pub fn GetModuleHandleA(machine: &mut Machine, lpModuleName: Option<&str>) -> HMODULE {
-    let name = match lpModuleName {
-        None => return HMODULE::from_raw(machine.state.kernel32.image_base),
-        Some(name) => name,
-    };
+/// Directory that builtin DLLs appear to be loaded from.
+const SYSTEM_DIR: &str = "C:\\Windows\\System32";
 
-    let name = normalize_module_name(name);
+/// Full guest path of the exe, as reported by GetModuleFileName(NULL).
+pub fn exe_path(machine: &Machine) -> String {
+    let exe = machine.state.kernel32.cmdline.args.first().unwrap();
+    match vfs(machine).full_path(WindowsPath::new(exe)) {
+        Ok(path) => path.to_string_lossy().into_owned(),
+        Err(_) => exe.clone(),
+    }
+}
 
-    if let Some((hmodule, _)) = machine
+/// A loaded module: the exe or a DLL.
+pub struct Module {
+    pub hmodule: HMODULE,
+    pub name: String,
+    pub path: String,
+}
+
+/// All loaded modules, the exe first.
+pub fn modules(machine: &Machine) -> Vec<Module> {
+    let path = exe_path(machine);
+    let exe = Module {
+        hmodule: HMODULE::from_raw(machine.state.kernel32.image_base),
+        name: path.rsplit('\\').next().unwrap().to_ascii_lowercase(),
+        path,
+    };
+    let mut dlls = machine
         .state
         .kernel32
         .dlls
         .iter()
-        .find(|(_, dll)| dll.name == name)
-    {
-        return *hmodule;
+        // The retrowin32 syscall stub isn't a real module.
+        .filter(|(hmodule, _)| !hmodule.is_null())
+        .map(|(&hmodule, dll)| Module {
+            hmodule,
+            name: dll.name.clone(),
+            path: dll.path.clone(),
+        })
+        .collect::<Vec<_>>();
+    dlls.sort_by_key(|module| module.hmodule.to_raw());
+    std::iter::once(exe).chain(dlls).collect()
+}
+
+/// Find a loaded module by name, which may include a directory and may omit the .dll extension.
+fn find_module(machine: &Machine, name: &str) -> Option<HMODULE> {
+    let filename = name.rsplit(['\\', '/']).next().unwrap();
+    let lower = filename.to_ascii_lowercase();
+    let normalized = normalize_module_name(filename);
+    modules(machine)
+        .into_iter()
+        .find(|module| module.name == lower || module.name == normalized)
+        .map(|module| module.hmodule)
+}
+
+/// Find the module whose image contains the address.
+pub fn module_from_address(machine: &Machine, addr: u32) -> Option<HMODULE> {
+    let mapping = machine
+        .state
+        .kernel32
+        .mappings
+        .vec()
+        .iter()
+        .find(|mapping| mapping.addr <= addr && addr - mapping.addr < mapping.size)?;
+    match mapping.kind {
+        super::MappingKind::Image { base } => Some(HMODULE::from_raw(base)),
+        _ => None,
     }
+}
 
-    set_last_error(machine, winapi::ERROR::MOD_NOT_FOUND);
-    return HMODULE::null();
+#[win32_derive::dllexport]
+pub fn GetModuleHandleA(machine: &mut Machine, lpModuleName: Option<&str>) -> HMODULE {
+    let name = match lpModuleName {
+        None => return HMODULE::from_raw(machine.state.kernel32.image_base),
+        Some(name) => name,
+    };
+    match find_module(machine, name) {
+        Some(hmodule) => hmodule,
+        None => {
+            set_last_error(machine, winapi::ERROR::MOD_NOT_FOUND);
+            HMODULE::null()
+        }
+    }
 }
 
 #[win32_derive::dllexport]
@@ -73,64 +167,165 @@ pub fn GetModuleHandleW(machine: &mut Machine, lpModuleName: Option<&Str16>) ->
     GetModuleHandleA(machine, ascii.as_deref())
 }
 
+const GET_MODULE_HANDLE_EX_FLAG_PIN: u32 = 0x1;
+const GET_MODULE_HANDLE_EX_FLAG_UNCHANGED_REFCOUNT: u32 = 0x2;
+const GET_MODULE_HANDLE_EX_FLAG_FROM_ADDRESS: u32 = 0x4;
+
+fn get_module_handle_ex(
+    machine: &mut Machine,
+    flags: u32,
+    name: Option<String>,
+    addr: u32,
+) -> HMODULE {
+    let hmodule = if flags & GET_MODULE_HANDLE_EX_FLAG_FROM_ADDRESS != 0 {
+        module_from_address(machine, addr).unwrap_or_else(|| {
+            set_last_error(machine, winapi::ERROR::MOD_NOT_FOUND);
+            HMODULE::null()
+        })
+    } else {
+        GetModuleHandleA(machine, name.as_deref())
+    };
+    if flags & (GET_MODULE_HANDLE_EX_FLAG_PIN | GET_MODULE_HANDLE_EX_FLAG_UNCHANGED_REFCOUNT) == 0 {
+        if let Some(dll) = machine.state.kernel32.dlls.get_mut(&hmodule) {
+            dll.ref_count += 1;
+        }
+    }
+    hmodule
+}
+
+#[win32_derive::dllexport]
+pub fn GetModuleHandleExA(
+    machine: &mut Machine,
+    dwFlags: u32,
+    lpModuleName: u32,
+    phModule: Option<&mut HMODULE>,
+) -> bool {
+    let name = if dwFlags & GET_MODULE_HANDLE_EX_FLAG_FROM_ADDRESS == 0 && lpModuleName != 0 {
+        match machine.mem().strz(lpModuleName, u32::MAX) {
+            Ok(name) => Some(expect_ascii(name).to_string()),
+            Err(err) => {
+                set_last_error(machine, err.into());
+                return false;
+            }
+        }
+    } else {
+        None
+    };
+    let hmodule = get_module_handle_ex(machine, dwFlags, name, lpModuleName);
+    if let Some(out) = phModule {
+        *out = hmodule;
+    }
+    !hmodule.is_null()
+}
+
 #[win32_derive::dllexport]
 pub fn GetModuleHandleExW(
     machine: &mut Machine,
     dwFlags: u32,
-    lpModuleName: Option<&Str16>,
-    hModule: Option<&mut HMODULE>,
+    lpModuleName: u32,
+    phModule: Option<&mut HMODULE>,
 ) -> bool {
-    if dwFlags != 0 {
-        unimplemented!("GetModuleHandleExW flags {dwFlags:x}");
+    let name = if dwFlags & GET_MODULE_HANDLE_EX_FLAG_FROM_ADDRESS == 0 && lpModuleName != 0 {
+        unsafe { Str16::from_nul_term_ptr(machine.mem(), lpModuleName) }.map(|s| s.to_string())
+    } else {
+        None
+    };
+    let hmodule = get_module_handle_ex(machine, dwFlags, name, lpModuleName);
+    if let Some(out) = phModule {
+        *out = hmodule;
+    }
+    !hmodule.is_null()
+}
+
+/// The path GetModuleFileName reports for a module, where null means the exe.
+fn module_path(machine: &Machine, hmodule: HMODULE) -> Option<String> {
+    if hmodule.is_null() {
+        return Some(exe_path(machine));
+    }
+    modules(machine)
+        .into_iter()
+        .find(|module| module.hmodule == hmodule)
+        .map(|module| module.path)
+}
+
+/// Copy a module path into a GetModuleFileName buffer, which like XP truncates
+/// (still nul-terminating) and returns the buffer size when it doesn't fit.
+fn copy_module_path<T: Copy + Default>(machine: &mut Machine, path: &[T], buf: &mut [T]) -> u32 {
+    if buf.is_empty() {
+        set_last_error(machine, winapi::ERROR::INSUFFICIENT_BUFFER);
+        return 0;
     }
-    let hMod = GetModuleHandleW(machine, lpModuleName);
-    if let Some(out) = hModule {
-        *out = hMod;
+    let n = path.len().min(buf.len() - 1);
+    buf[..n].copy_from_slice(&path[..n]);
+    buf[n] = T::default();
+    if n < path.len() {
+        set_last_error(machine, winapi::ERROR::INSUFFICIENT_BUFFER);
+        return buf.len() as u32;
     }
-    return !hMod.is_null();
+    set_last_error(machine, winapi::ERROR::SUCCESS);
+    n as u32
 }
 
 #[win32_derive::dllexport]
 pub fn GetModuleFileNameA(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     hModule: HMODULE,
     filename: ArrayWithSizeMut<u8>,
 ) -> u32 {
-    assert!(hModule.is_null());
-    match filename.unwrap().write(b"TODO.exe\0") {
-        Ok(n) => n as u32,
-        Err(err) => {
-            log::warn!("GetModuleFileNameA(): {}", err);
-            0
-        }
-    }
+    let Some(path) = module_path(machine, hModule) else {
+        set_last_error(machine, winapi::ERROR::MOD_NOT_FOUND);
+        return 0;
+    };
+    let Some(buf) = filename.to_option() else {
+        set_last_error(machine, winapi::ERROR::INVALID_PARAMETER);
+        return 0;
+    };
+    copy_module_path(machine, path.as_bytes(), buf)
 }
 
 #[win32_derive::dllexport]
 pub fn GetModuleFileNameW(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     hModule: HMODULE,
-    _lpFilename: u32,
-    _nSize: u32,
+    lpFilename: ArrayWithSizeMut<u16>,
 ) -> u32 {
-    if !hModule.is_null() {
-        log::error!("unimplemented: GetModuleFileNameW(non-null)")
-    }
-    0 // fail
+    let Some(path) = module_path(machine, hModule) else {
+        set_last_error(machine, winapi::ERROR::MOD_NOT_FOUND);
+        return 0;
+    };
+    let Some(buf) = lpFilename.to_option() else {
+        set_last_error(machine, winapi::ERROR::INVALID_PARAMETER);
+        return 0;
+    };
+    let path = path.encode_utf16().collect::<Vec<_>>();
+    copy_module_path(machine, &path, buf)
 }
 
-pub fn load_library(machine: &mut Machine, filename: &str) -> HMODULE {
+pub const DLL_PROCESS_DETACH: u32 = 0;
+pub const DLL_PROCESS_ATTACH: u32 = 1;
+pub const DLL_THREAD_ATTACH: u32 = 2;
+pub const DLL_THREAD_DETACH: u32 = 3;
+
+/// Load a DLL and the DLLs it imports, without running their DllMains; see attach_dlls.
+/// The path may include a directory, which is otherwise searched for.
+pub fn load_library(machine: &mut Machine, path: &str) -> HMODULE {
+    // The module name is just the file name.
+    let (dir, filename) = match path.rfind(['\\', '/']) {
+        Some(i) => (Some(&path[..i]), &path[i + 1..]),
+        None => (None, path),
+    };
     let mut filename = normalize_module_name(filename);
 
     // See if already loaded.
-    if let Some((hmodule, _)) = machine
+    if let Some((&hmodule, dll)) = machine
         .state
         .kernel32
         .dlls
-        .iter()
+        .iter_mut()
         .find(|(_, dll)| dll.name == filename)
     {
-        return *hmodule;
+        dll.ref_count += 1;
+        return hmodule;
     }
 
     if filename.starts_with("api-") {
@@ -150,22 +345,33 @@ pub fn load_library(machine: &mut Machine, filename: &str) -> HMODULE {
         winapi::DLLS.iter().find(|&dll| dll.file_name == filename)
     };
     let mut buf = Vec::new();
+    // Builtins appear to live in the system directory.
+    let mut found_path = format!("{SYSTEM_DIR}\\{filename}");
 
     let contents = {
         if let Some(builtin) = builtin {
             builtin.raw
         } else {
-            let exe = machine.state.kernel32.cmdline.args.first().unwrap();
-            let exe_dir = exe.rsplitn(2, '\\').last().unwrap();
-            let dll_paths = [format!("{exe_dir}\\{filename}"), filename.to_string()];
+            let dll_paths = match dir {
+                Some(dir) => vec![format!("{dir}\\{filename}")],
+                None => {
+                    // Search the exe's directory, then the current directory.
+                    let exe = machine.state.kernel32.cmdline.args.first().unwrap();
+                    let exe_dir = exe.rsplitn(2, '\\').last().unwrap();
+                    vec![format!("{exe_dir}\\{filename}"), filename.to_string()]
+                }
+            };
             for path in &dll_paths {
                 let path = WindowsPath::new(path);
-                let mut file = match machine.host.open(path, host::FileOptions::read()) {
+                let mut file = match vfs(machine).open(path, host::FileOptions::read()) {
                     Ok(file) => file,
                     Err(_) => continue,
                 };
                 file.read_to_end(&mut buf).unwrap();
                 // TODO: close file.
+                if let Ok(full) = vfs(machine).full_path(path) {
+                    found_path = full.to_string_lossy().into_owned();
+                }
                 break;
             }
             &buf
@@ -173,51 +379,190 @@ pub fn load_library(machine: &mut Machine, filename: &str) -> HMODULE {
     };
 
     if contents.is_empty() {
-        log::warn!("load_library({filename:?}): not found");
+        log::warn!("load_library({path:?}): not found");
+        set_last_error(machine, winapi::ERROR::MOD_NOT_FOUND);
         return HMODULE::null();
     }
 
-    let dll = pe::load_dll(machine, &filename, contents).unwrap();
+    let dll = match pe::load_dll(machine, &filename, contents) {
+        Ok(dll) => dll,
+        Err(err) => {
+            log::warn!("load_library({path:?}): {err}");
+            set_last_error(machine, winapi::ERROR::BAD_EXE_FORMAT);
+            return HMODULE::null();
+        }
+    };
 
     // For builtins, register all the exports as known symbols.
     // It is critical that the DLL's exports match up to the shims array;
     // this is ensured by both being generated by the same generator.
     if let Some(builtin) = builtin {
-        for (&addr, shim) in dll.fns.iter().zip(builtin.shims) {
+        let fns = dll
+            .fns
+            .iter()
+            .filter(|&addr| *addr != dll.base && !dll.forwards.contains_key(addr));
+        for (&addr, shim) in fns.zip(builtin.shims) {
             machine.emu.shims.register(addr, Ok(shim));
         }
     }
 
     let hmodule = HMODULE::from_raw(dll.base);
+    if builtin.is_none() {
+        // Imported DLLs were loaded first, so they'll be attached first.
+        machine.state.kernel32.dlls_to_attach.push(hmodule);
+    }
     machine.state.kernel32.dlls.insert(
         hmodule,
         DLL {
             name: filename,
+            path: found_path,
             dll,
+            ref_count: 1,
+            thread_calls: true,
         },
     );
     hmodule
 }
 
+/// Call DllMain(DLL_PROCESS_ATTACH) of the DLLs loaded since the last call.
+/// Returns false if any of them failed to initialize.
+pub async fn attach_dlls(machine: &mut Machine) -> bool {
+    let mut ok = true;
+    // A DllMain may load further DLLs, which are attached after it.
+    while !machine.state.kernel32.dlls_to_attach.is_empty() {
+        let hmodule = machine.state.kernel32.dlls_to_attach.remove(0);
+        if call_dll_main(machine, hmodule, DLL_PROCESS_ATTACH, 0).await == 0 {
+            let name = &machine.state.kernel32.dlls[&hmodule].name;
+            log::warn!("{name}: DllMain(DLL_PROCESS_ATTACH) failed");
+            ok = false;
+        } else {
+            machine.state.kernel32.attached_dlls.push(hmodule);
+        }
+    }
+    ok
+}
+
+/// Call a DLL's TLS callbacks and then its DllMain, returning DllMain's result.
+async fn call_dll_main(machine: &mut Machine, hmodule: HMODULE, reason: u32, reserved: u32) -> u32 {
+    super::call_tls_callbacks(machine, hmodule.to_raw(), reason, reserved).await;
+    let Some(entry_point) = machine
+        .state
+        .kernel32
+        .dlls
+        .get(&hmodule)
+        .and_then(|dll| dll.dll.entry_point)
+    else {
+        return 1;
+    };
+    machine
+        .call_x86(entry_point, vec![hmodule.to_raw(), reason, reserved])
+        .await
+}
+
+/// Call DllMain(DLL_PROCESS_DETACH) of all attached DLLs as the process exits,
+/// in the reverse of the order they were attached.
+pub async fn detach_dlls(machine: &mut Machine) {
+    // The exe was initialized last, so its TLS callbacks go first.
+    let exe = machine.state.kernel32.image_base;
+    super::call_tls_callbacks(machine, exe, DLL_PROCESS_DETACH, 1).await;
+    while let Some(hmodule) = machine.state.kernel32.attached_dlls.pop() {
+        // Nonzero lpvReserved means the process is terminating.
+        call_dll_main(machine, hmodule, DLL_PROCESS_DETACH, 1).await;
+    }
+}
+
+/// Call DllMain(DLL_THREAD_ATTACH/DETACH) of the attached DLLs, on the thread that is
+/// starting or exiting.  Detach goes in the reverse of attach order.
+pub async fn notify_dlls_thread(machine: &mut Machine, attach: bool) {
+    let exe = machine.state.kernel32.image_base;
+    let mut dlls = machine.state.kernel32.attached_dlls.clone();
+    let reason = if attach {
+        DLL_THREAD_ATTACH
+    } else {
+        super::call_tls_callbacks(machine, exe, DLL_THREAD_DETACH, 0).await;
+        dlls.reverse();
+        DLL_THREAD_DETACH
+    };
+    for hmodule in dlls {
+        if machine.state.kernel32.dlls[&hmodule].thread_calls {
+            call_dll_main(machine, hmodule, reason, 0).await;
+        } else {
+            // DisableThreadLibraryCalls doesn't affect TLS callbacks.
+            super::call_tls_callbacks(machine, hmodule.to_raw(), reason, 0).await;
+        }
+    }
+    if attach {
+        super::call_tls_callbacks(machine, exe, DLL_THREAD_ATTACH, 0).await;
+    }
+}
+
+#[win32_derive::dllexport]
+pub async fn LoadLibraryA(machine: &mut Machine, lpLibFileName: Option<&str>) -> HMODULE {
+    let Some(filename) = lpLibFileName else {
+        set_last_error(machine, winapi::ERROR::INVALID_PARAMETER);
+        return HMODULE::null();
+    };
+    let hmodule = load_library(machine, filename);
+    // A DLL freed down to zero references is still loaded, but needs attaching again.
+    if let Some(dll) = machine.state.kernel32.dlls.get(&hmodule) {
+        if dll.dll.entry_point.is_some()
+            && !machine.state.kernel32.attached_dlls.contains(&hmodule)
+            && !machine.state.kernel32.dlls_to_attach.contains(&hmodule)
+        {
+            machine.state.kernel32.dlls_to_attach.push(hmodule);
+        }
+    }
+    if !attach_dlls(machine).await {
+        set_last_error(machine, winapi::ERROR::DLL_INIT_FAILED);
+        return HMODULE::null();
+    }
+    hmodule
+}
+
 #[win32_derive::dllexport]
-pub fn LoadLibraryA(machine: &mut Machine, filename: Option<&str>) -> HMODULE {
-    load_library(machine, filename.unwrap())
+pub async fn LoadLibraryW(machine: &mut Machine, lpLibFileName: Option<&Str16>) -> HMODULE {
+    let filename = lpLibFileName.map(|f| f.to_string());
+    LoadLibraryA(machine, filename.as_deref()).await
 }
 
 #[win32_derive::dllexport]
-pub fn LoadLibraryExW(
+pub async fn LoadLibraryExA(
+    machine: &mut Machine,
+    lpLibFileName: Option<&str>,
+    hFile: HFILE,
+    dwFlags: u32,
+) -> HMODULE {
+    LoadLibraryA(machine, lpLibFileName).await
+}
+
+#[win32_derive::dllexport]
+pub async fn LoadLibraryExW(
     machine: &mut Machine,
     lpLibFileName: Option<&Str16>,
     hFile: HFILE,
     dwFlags: u32,
 ) -> HMODULE {
     let filename = lpLibFileName.map(|f| f.to_string());
-    LoadLibraryA(machine, filename.as_deref())
+    LoadLibraryA(machine, filename.as_deref()).await
 }
 
 #[win32_derive::dllexport]
-pub fn FreeLibrary(_machine: &mut Machine, hLibModule: HMODULE) -> bool {
-    true // success
+pub async fn FreeLibrary(machine: &mut Machine, hLibModule: HMODULE) -> bool {
+    let Some(dll) = machine.state.kernel32.dlls.get_mut(&hLibModule) else {
+        set_last_error(machine, winapi::ERROR::MOD_NOT_FOUND);
+        return false;
+    };
+    dll.ref_count = dll.ref_count.saturating_sub(1);
+    if dll.ref_count > 0 {
+        return true;
+    }
+    // TODO: unmap the DLL; for now it stays loaded, and is only detached.
+    let attached = &mut machine.state.kernel32.attached_dlls;
+    if let Some(index) = attached.iter().position(|&h| h == hLibModule) {
+        attached.remove(index);
+        call_dll_main(machine, hLibModule, DLL_PROCESS_DETACH, 0).await;
+    }
+    true
 }
 
 /// The argument to GetProcAddress is an ImportSymbol stuffed into a u32.
@@ -230,7 +575,10 @@ impl<'a> winapi::stack_args::FromStack<'a> for GetProcAddressArg<'a> {
         if lpProcName & 0xFFFF_0000 == 0 {
             GetProcAddressArg(ImportSymbol::Ordinal(lpProcName))
         } else {
-            let proc_name = expect_ascii(mem.slicez(lpProcName));
+            let proc_name = mem
+                .strz(lpProcName, u32::MAX)
+                .unwrap_or_else(|err| err.raise());
+            let proc_name = expect_ascii(proc_name);
             GetProcAddressArg(ImportSymbol::Name(proc_name))
         }
     }
@@ -252,13 +600,24 @@ pub fn GetProcAddress(
     hModule: HMODULE,
     lpProcName: GetProcAddressArg,
 ) -> u32 {
-    let dll = machine.state.kernel32.dlls.get_mut(&hModule).unwrap();
-    let Some(addr) = dll.resolve(&lpProcName.0) else {
-        log::warn!("GetProcAddress({:?}, {:?}) failed", dll.name, lpProcName);
-        return 0; // fail
+    let Some(dll) = machine.state.kernel32.dlls.get(&hModule) else {
+        let exe = HMODULE::from_raw(machine.state.kernel32.image_base);
+        if hModule.is_null() || hModule == exe {
+            // TODO: exports of the exe itself.
+            log::warn!("GetProcAddress(exe, {}) failed", lpProcName.0.to_string());
+            set_last_error(machine, winapi::ERROR::PROC_NOT_FOUND);
+        } else {
+            set_last_error(machine, winapi::ERROR::MOD_NOT_FOUND);
+        }
+        return 0;
     };
-
     let name = format!("{}!{}", dll.name, lpProcName.0.to_string());
+    let Some(addr) = resolve_export(machine, hModule, &lpProcName.0) else {
+        log::warn!("GetProcAddress({name}) failed");
+        set_last_error(machine, winapi::ERROR::PROC_NOT_FOUND);
+        return 0;
+    };
+
     machine.labels.insert(addr, name);
     return addr;
 }
@@ -306,6 +665,11 @@ pub fn GetStartupInfoW(machine: &mut Machine, lpStartupInfo: Option<&mut STARTUP
 }
 
 #[win32_derive::dllexport]
-pub fn DisableThreadLibraryCalls(_machine: &mut Machine, hLibModule: HMODULE) -> bool {
-    true // succeed
+pub fn DisableThreadLibraryCalls(machine: &mut Machine, hLibModule: HMODULE) -> bool {
+    let Some(dll) = machine.state.kernel32.dlls.get_mut(&hLibModule) else {
+        set_last_error(machine, winapi::ERROR::INVALID_HANDLE);
+        return false;
+    };
+    dll.thread_calls = false;
+    true
 }
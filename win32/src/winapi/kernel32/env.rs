@@ -1,4 +1,10 @@
-use crate::{str16::Str16, winapi::stack_args::ArrayWithSize, Machine};
+use super::set_last_error;
+use crate::{
+    str16::{Str16, String16},
+    winapi::{stack_args::ArrayWithSizeMut, ERROR},
+    Machine,
+};
+use memory::Extensions;
 
 #[win32_derive::dllexport]
 pub fn GetEnvironmentStrings(machine: &mut Machine) -> u32 {
@@ -21,22 +27,62 @@ pub fn FreeEnvironmentStringsW(_machine: &mut Machine) -> bool {
     true // success
 }
 
+/// Look up a variable in the environment block, whose names are case-insensitive.
+fn get_env(machine: &Machine, name: &str) -> Option<String> {
+    let mut addr = machine.state.kernel32.env;
+    loop {
+        let entry = machine.mem().slicez(addr);
+        if entry.is_empty() {
+            return None;
+        }
+        addr += entry.len() as u32 + 1;
+        let entry = std::str::from_utf8(entry).ok()?;
+        if let Some((key, value)) = entry.split_once('=') {
+            if key.eq_ignore_ascii_case(name) {
+                return Some(value.to_string());
+            }
+        }
+    }
+}
+
+/// Copy a variable's value out, returning its length if it fits and otherwise the
+/// size of buffer needed.
+fn copy_env<T: Copy + Default>(value: &[T], buf: Option<&mut [T]>) -> u32 {
+    match buf {
+        Some(buf) if buf.len() > value.len() => {
+            buf[..value.len()].copy_from_slice(value);
+            buf[value.len()] = T::default();
+            value.len() as u32
+        }
+        _ => value.len() as u32 + 1,
+    }
+}
+
 #[win32_derive::dllexport]
 pub fn GetEnvironmentVariableA(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     name: Option<&str>,
-    buf: ArrayWithSize<u8>,
-) -> bool {
-    false
+    buf: ArrayWithSizeMut<u8>,
+) -> u32 {
+    let Some(value) = name.and_then(|name| get_env(machine, name)) else {
+        set_last_error(machine, ERROR::ENVVAR_NOT_FOUND);
+        return 0;
+    };
+    copy_env(value.as_bytes(), buf.to_option())
 }
 
 #[win32_derive::dllexport]
 pub fn GetEnvironmentVariableW(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     name: Option<&Str16>,
-    buf: ArrayWithSize<u16>,
-) -> bool {
-    false
+    buf: ArrayWithSizeMut<u16>,
+) -> u32 {
+    let Some(value) = name.and_then(|name| get_env(machine, &name.to_string())) else {
+        set_last_error(machine, ERROR::ENVVAR_NOT_FOUND);
+        return 0;
+    };
+    let value = String16::from(value.as_str());
+    copy_env(&value.0, buf.to_option())
 }
 
 #[win32_derive::dllexport]
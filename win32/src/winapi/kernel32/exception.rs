@@ -0,0 +1,912 @@
+//! Structured exception handling, via the chain of handlers at FS:[0].
+//!
+//! As in KiUserExceptionDispatcher, an exception is dispatched by copying its
+//! EXCEPTION_RECORD and CONTEXT onto the stack of the thread that raised it, then calling
+//! each registered handler in turn.  A handler that takes the exception typically calls
+//! RtlUnwind and then jumps into its __except block, never returning to us.
+
+#![allow(non_upper_case_globals)]
+
+use super::HTHREAD;
+use crate::machine::Machine;
+use memory::{Extensions, ExtensionsMut, Pod};
+
+pub const STATUS_GUARD_PAGE_VIOLATION: u32 = 0x8000_0001;
+pub const STATUS_BREAKPOINT: u32 = 0x8000_0003;
+pub const STATUS_SINGLE_STEP: u32 = 0x8000_0004;
+pub const STATUS_ACCESS_VIOLATION: u32 = 0xC000_0005;
+pub const STATUS_NO_MEMORY: u32 = 0xC000_0017;
+pub const STATUS_ILLEGAL_INSTRUCTION: u32 = 0xC000_001D;
+pub const STATUS_NONCONTINUABLE_EXCEPTION: u32 = 0xC000_0025;
+pub const STATUS_UNWIND: u32 = 0xC000_0027;
+pub const STATUS_ARRAY_BOUNDS_EXCEEDED: u32 = 0xC000_008C;
+pub const STATUS_FLOAT_DENORMAL_OPERAND: u32 = 0xC000_008D;
+pub const STATUS_FLOAT_DIVIDE_BY_ZERO: u32 = 0xC000_008E;
+pub const STATUS_FLOAT_INEXACT_RESULT: u32 = 0xC000_008F;
+pub const STATUS_FLOAT_INVALID_OPERATION: u32 = 0xC000_0090;
+pub const STATUS_FLOAT_OVERFLOW: u32 = 0xC000_0091;
+pub const STATUS_FLOAT_STACK_CHECK: u32 = 0xC000_0092;
+pub const STATUS_FLOAT_UNDERFLOW: u32 = 0xC000_0093;
+pub const STATUS_INTEGER_DIVIDE_BY_ZERO: u32 = 0xC000_0094;
+pub const STATUS_INTEGER_OVERFLOW: u32 = 0xC000_0095;
+
+/// EXCEPTION_RECORD.ExceptionFlags bits.
+pub const EXCEPTION_NONCONTINUABLE: u32 = 0x1;
+pub const EXCEPTION_UNWINDING: u32 = 0x2;
+pub const EXCEPTION_EXIT_UNWIND: u32 = 0x4;
+
+/// Handler return values (EXCEPTION_DISPOSITION).
+pub const ExceptionContinueExecution: u32 = 0;
+pub const ExceptionContinueSearch: u32 = 1;
+
+/// Exception filter return values.
+pub const EXCEPTION_CONTINUE_SEARCH: i32 = 0;
+pub const EXCEPTION_CONTINUE_EXECUTION: i32 = -1;
+pub const EXCEPTION_EXECUTE_HANDLER: i32 = 1;
+
+/// Handler of the outermost record of the main thread's chain, which we never call.
+/// See init_teb().
+pub const SEH_SENTINEL_HANDLER: u32 = 0xFF5E_5EFF; // Hopefully easier to spot.
+
+/// End of the chain of EXCEPTION_REGISTRATION_RECORDs.
+const SEH_CHAIN_END: u32 = 0xFFFF_FFFF;
+
+const EXCEPTION_MAXIMUM_PARAMETERS: usize = 15;
+
+#[repr(C)]
+#[derive(Clone, Debug)]
+pub struct EXCEPTION_RECORD {
+    pub ExceptionCode: u32,
+    pub ExceptionFlags: u32,
+    /// A nested exception record, for exceptions raised while handling another.
+    pub ExceptionRecord: u32,
+    pub ExceptionAddress: u32,
+    pub NumberParameters: u32,
+    pub ExceptionInformation: [u32; EXCEPTION_MAXIMUM_PARAMETERS],
+}
+unsafe impl Pod for EXCEPTION_RECORD {}
+
+#[repr(C)]
+#[derive(Clone)]
+pub struct FLOATING_SAVE_AREA {
+    pub ControlWord: u32,
+    pub StatusWord: u32,
+    pub TagWord: u32,
+    pub ErrorOffset: u32,
+    pub ErrorSelector: u32,
+    pub DataOffset: u32,
+    pub DataSelector: u32,
+    pub RegisterArea: [u8; 80],
+    pub Cr0NpxState: u32,
+}
+unsafe impl Pod for FLOATING_SAVE_AREA {}
+
+#[cfg(feature = "x86-emu")]
+impl FLOATING_SAVE_AREA {
+    /// All but Cr0NpxState is laid out as the 108 bytes fnsave stores.
+    fn from_fnsave(image: &[u8; 108]) -> Self {
+        let word = |i: usize| u32::from_le_bytes(image[i * 4..][..4].try_into().unwrap());
+        FLOATING_SAVE_AREA {
+            ControlWord: word(0),
+            StatusWord: word(1),
+            TagWord: word(2),
+            ErrorOffset: word(3),
+            ErrorSelector: word(4),
+            DataOffset: word(5),
+            DataSelector: word(6),
+            RegisterArea: image[28..].try_into().unwrap(),
+            Cr0NpxState: 0,
+        }
+    }
+
+    fn to_fnsave(&self) -> [u8; 108] {
+        let mut image = [0; 108];
+        let words = [
+            self.ControlWord,
+            self.StatusWord,
+            self.TagWord,
+            self.ErrorOffset,
+            self.ErrorSelector,
+            self.DataOffset,
+            self.DataSelector,
+        ];
+        for (chunk, word) in image.chunks_exact_mut(4).zip(words) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        image[28..].copy_from_slice(&self.RegisterArea);
+        image
+    }
+}
+
+/// CONTEXT.ContextFlags bits, selecting groups of registers.
+const CONTEXT_CONTROL: u32 = 0x1_0001;
+const CONTEXT_INTEGER: u32 = 0x1_0002;
+const CONTEXT_SEGMENTS: u32 = 0x1_0004;
+const CONTEXT_FLOATING_POINT: u32 = 0x1_0008;
+const CONTEXT_DEBUG_REGISTERS: u32 = 0x1_0010;
+const CONTEXT_FULL: u32 = CONTEXT_CONTROL | CONTEXT_INTEGER | CONTEXT_SEGMENTS;
+
+/// The i386 register state.
+#[repr(C)]
+#[derive(Clone)]
+pub struct CONTEXT {
+    pub ContextFlags: u32,
+    pub Dr0: u32,
+    pub Dr1: u32,
+    pub Dr2: u32,
+    pub Dr3: u32,
+    pub Dr6: u32,
+    pub Dr7: u32,
+    pub FloatSave: FLOATING_SAVE_AREA,
+    pub SegGs: u32,
+    pub SegFs: u32,
+    pub SegEs: u32,
+    pub SegDs: u32,
+    pub Edi: u32,
+    pub Esi: u32,
+    pub Ebx: u32,
+    pub Edx: u32,
+    pub Ecx: u32,
+    pub Eax: u32,
+    pub Ebp: u32,
+    pub Eip: u32,
+    pub SegCs: u32,
+    pub EFlags: u32,
+    pub Esp: u32,
+    pub SegSs: u32,
+    pub ExtendedRegisters: [u8; 512],
+}
+unsafe impl Pod for CONTEXT {}
+
+impl std::fmt::Debug for CONTEXT {
+    // All of it would swamp a trace.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CONTEXT")
+            .field("ContextFlags", &format_args!("{:#x}", self.ContextFlags))
+            .field("Eip", &format_args!("{:#x}", self.Eip))
+            .field("Esp", &format_args!("{:#x}", self.Esp))
+            .field("Dr7", &format_args!("{:#x}", self.Dr7))
+            .finish_non_exhaustive()
+    }
+}
+
+#[repr(C)]
+#[derive(Clone)]
+pub struct EXCEPTION_POINTERS {
+    pub ExceptionRecord: u32,
+    pub ContextRecord: u32,
+}
+unsafe impl Pod for EXCEPTION_POINTERS {}
+
+#[repr(C)]
+#[derive(Clone)]
+pub struct EXCEPTION_REGISTRATION_RECORD {
+    pub Prev: u32,
+    pub Handler: u32,
+}
+unsafe impl Pod for EXCEPTION_REGISTRATION_RECORD {}
+
+impl EXCEPTION_RECORD {
+    pub fn new(code: u32, flags: u32, address: u32, params: &[u32]) -> Self {
+        let mut record = EXCEPTION_RECORD::zeroed();
+        record.ExceptionCode = code;
+        record.ExceptionFlags = flags;
+        record.ExceptionAddress = address;
+        let count = params.len().min(EXCEPTION_MAXIMUM_PARAMETERS);
+        record.NumberParameters = count as u32;
+        record.ExceptionInformation[..count].copy_from_slice(&params[..count]);
+        record
+    }
+}
+
+/// Capture the current CPU state.
+#[cfg(feature = "x86-emu")]
+fn capture_context(machine: &Machine) -> CONTEXT {
+    cpu_context(machine.emu.x86.cpu())
+}
+
+#[cfg(feature = "x86-emu")]
+fn cpu_context(cpu: &x86::CPU) -> CONTEXT {
+    use x86::Register::*;
+    let regs = &cpu.regs;
+    let mut context = CONTEXT::zeroed();
+    context.ContextFlags = CONTEXT_FULL | CONTEXT_FLOATING_POINT | CONTEXT_DEBUG_REGISTERS;
+    context.FloatSave = FLOATING_SAVE_AREA::from_fnsave(&cpu.fpu.save());
+    [context.Dr0, context.Dr1, context.Dr2, context.Dr3] = cpu.debug.dr;
+    context.Dr6 = cpu.debug.dr6;
+    context.Dr7 = cpu.debug.dr7;
+    context.SegGs = regs.get16(GS) as u32;
+    context.SegFs = regs.get16(FS) as u32;
+    context.SegEs = regs.get16(ES) as u32;
+    context.SegDs = regs.get16(DS) as u32;
+    context.Edi = regs.get32(EDI);
+    context.Esi = regs.get32(ESI);
+    context.Ebx = regs.get32(EBX);
+    context.Edx = regs.get32(EDX);
+    context.Ecx = regs.get32(ECX);
+    context.Eax = regs.get32(EAX);
+    context.Ebp = regs.get32(EBP);
+    context.Eip = regs.eip;
+    context.SegCs = regs.get16(CS) as u32;
+    // Bit 1 is reserved and always set.
+    context.EFlags = cpu.flags.bits() | 0x2;
+    context.Esp = regs.get32(ESP);
+    context.SegSs = regs.get16(SS) as u32;
+    context
+}
+
+/// Resume execution at the state in a (possibly handler-modified) CONTEXT.
+#[cfg(feature = "x86-emu")]
+fn restore_context(machine: &mut Machine, context: &CONTEXT) {
+    set_cpu_context(machine.emu.x86.cpu_mut(), context);
+}
+
+/// Load the register groups that context.ContextFlags selects into a CPU.
+/// The segment registers are left alone, as Windows would only accept the values they
+/// already have.
+#[cfg(feature = "x86-emu")]
+fn set_cpu_context(cpu: &mut x86::CPU, context: &CONTEXT) {
+    use x86::Register::*;
+    let has = |group: u32| context.ContextFlags & group == group;
+    if has(CONTEXT_INTEGER) {
+        let regs = &mut cpu.regs;
+        regs.set32(EDI, context.Edi);
+        regs.set32(ESI, context.Esi);
+        regs.set32(EBX, context.Ebx);
+        regs.set32(EDX, context.Edx);
+        regs.set32(ECX, context.Ecx);
+        regs.set32(EAX, context.Eax);
+    }
+    if has(CONTEXT_CONTROL) {
+        cpu.regs.set32(EBP, context.Ebp);
+        cpu.regs.set32(ESP, context.Esp);
+        cpu.regs.eip = context.Eip;
+        cpu.flags = x86::Flags::from_bits_truncate(context.EFlags).into();
+    }
+    if has(CONTEXT_FLOATING_POINT) {
+        cpu.fpu.restore(&context.FloatSave.to_fnsave());
+    }
+    if has(CONTEXT_DEBUG_REGISTERS) {
+        let debug = &mut cpu.debug;
+        for (n, dr) in [context.Dr0, context.Dr1, context.Dr2, context.Dr3]
+            .into_iter()
+            .enumerate()
+        {
+            debug.set(n, dr);
+        }
+        debug.set(6, context.Dr6);
+        debug.set(7, context.Dr7);
+    }
+}
+
+/// The context of the caller of the current builtin function, as if it had just returned.
+/// args_size is the size of the arguments popped by the (stdcall) function.
+#[cfg(feature = "x86-emu")]
+pub fn caller_context(machine: &Machine, args_size: u32) -> CONTEXT {
+    // See doc/shims.md for the state of the stack within a shim.
+    let mut context = capture_context(machine);
+    context.Eip = machine.mem().get_pod::<u32>(context.Esp + 4);
+    context.Esp += 8 + args_size;
+    context
+}
+
+/// Reserve space for a T on the guest stack and copy it there.
+#[cfg(feature = "x86-emu")]
+pub fn push_pod<T: Clone + Pod>(machine: &mut Machine, val: T) -> u32 {
+    let esp = machine.emu.x86.cpu().regs.get32(x86::Register::ESP);
+    let addr = (esp - std::mem::size_of::<T>() as u32) & !3;
+    machine
+        .emu
+        .x86
+        .cpu_mut()
+        .regs
+        .set32(x86::Register::ESP, addr);
+    machine.mem().put_pod::<T>(addr, val);
+    addr
+}
+
+/// Call the handlers in the current thread's SEH chain, from the innermost outward,
+/// until one claims the exception.  Returns whether one did.
+#[cfg(feature = "x86-emu")]
+async fn call_handlers(machine: &mut Machine, record_addr: u32, context_addr: u32) -> bool {
+    let mut frame = super::teb(machine).Tib.ExceptionList;
+    while frame != SEH_CHAIN_END {
+        let reg = machine
+            .mem()
+            .get_pod::<EXCEPTION_REGISTRATION_RECORD>(frame);
+        if reg.Handler == SEH_SENTINEL_HANDLER {
+            break;
+        }
+        let disposition = machine
+            .call_x86(reg.Handler, vec![record_addr, frame, context_addr, 0])
+            .await;
+        match disposition {
+            ExceptionContinueExecution => return true,
+            ExceptionContinueSearch => {}
+            _ => log::warn!("exception handler {:x} returned {disposition}", reg.Handler),
+        }
+        frame = reg.Prev;
+    }
+    false
+}
+
+/// Deliver an exception to the current thread, resuming at the handler's choice of
+/// context or terminating the process if no handler takes it.
+#[cfg(feature = "x86-emu")]
+pub async fn dispatch_exception(machine: &mut Machine, record: EXCEPTION_RECORD, context: CONTEXT) {
+    let code = record.ExceptionCode;
+    let address = record.ExceptionAddress;
+    let noncontinuable = record.ExceptionFlags & EXCEPTION_NONCONTINUABLE != 0;
+    let context_addr = push_pod(machine, context.clone());
+    let record_addr = push_pod(machine, record.clone());
+
+    if call_handlers(machine, record_addr, context_addr).await {
+        if noncontinuable {
+            // Windows raises STATUS_NONCONTINUABLE_EXCEPTION here, which nothing handles.
+            log::error!("handler continued noncontinuable exception {code:08x} at {address:x}");
+        } else {
+            let context = machine.mem().get_pod::<CONTEXT>(context_addr);
+            restore_context(machine, &context);
+            return;
+        }
+    } else {
+        let filter = machine.state.kernel32.unhandled_exception_filter;
+        if filter != 0 {
+            let pointers = push_pod(
+                machine,
+                EXCEPTION_POINTERS {
+                    ExceptionRecord: record_addr,
+                    ContextRecord: context_addr,
+                },
+            );
+            let ret = machine.call_x86(filter, vec![pointers]).await as i32;
+            if ret == EXCEPTION_CONTINUE_EXECUTION && !noncontinuable {
+                let context = machine.mem().get_pod::<CONTEXT>(context_addr);
+                restore_context(machine, &context);
+                return;
+            }
+            if ret == EXCEPTION_EXECUTE_HANDLER {
+                // The filter dealt with it (say, by writing its own crash log), and
+                // asks for the process to be terminated without further fuss.
+                log::info!("exception {code:08x} at {address:x} terminated the process");
+                machine.exit(code);
+                return;
+            }
+        }
+        let thread_id = super::current_thread(machine).id;
+        let report = crate::crash::CrashReport::new(machine, thread_id, &record, &context);
+        log::error!("{report}");
+        machine.state.kernel32.crash_report = Some(report);
+    }
+
+    // The process exits with the exception code as its exit code.
+    machine.exit(code);
+}
+
+/// Deliver a CPU fault to the current thread as an exception.
+#[cfg(feature = "x86-emu")]
+pub fn dispatch_fault(machine: &mut Machine, fault: x86::Fault) {
+    let eip = machine.emu.x86.cpu().regs.eip;
+    let record = match fault {
+        x86::Fault::DivideError { overflow: false } => {
+            EXCEPTION_RECORD::new(STATUS_INTEGER_DIVIDE_BY_ZERO, 0, eip, &[])
+        }
+        x86::Fault::DivideError { overflow: true } => {
+            EXCEPTION_RECORD::new(STATUS_INTEGER_OVERFLOW, 0, eip, &[])
+        }
+        x86::Fault::InvalidOpcode => EXCEPTION_RECORD::new(STATUS_ILLEGAL_INSTRUCTION, 0, eip, &[]),
+        x86::Fault::BoundRange => EXCEPTION_RECORD::new(STATUS_ARRAY_BOUNDS_EXCEEDED, 0, eip, &[]),
+        x86::Fault::AccessViolation { addr, write } => {
+            EXCEPTION_RECORD::new(STATUS_ACCESS_VIOLATION, 0, eip, &[write as u32, addr])
+        }
+        // Windows reports #GP as an access violation at an unknown address.
+        x86::Fault::GeneralProtection => {
+            EXCEPTION_RECORD::new(STATUS_ACCESS_VIOLATION, 0, eip, &[0, 0xFFFF_FFFF])
+        }
+        x86::Fault::GuardPage { addr, write } => {
+            // A guard page only fires once, becoming an ordinary page.
+            let page = addr & !0xFFF;
+            if let Some(mapping) = machine.state.kernel32.mappings.find_mut(page) {
+                let protect = mapping.protect[((page - mapping.addr) >> 12) as usize];
+                super::protect_pages(machine, page, 0x1000, protect - super::PAGE::GUARD);
+            }
+            EXCEPTION_RECORD::new(STATUS_GUARD_PAGE_VIOLATION, 0, eip, &[write as u32, addr])
+        }
+        // The parameter is BREAKPOINT_BREAK, as opposed to the kernel debugger's services.
+        x86::Fault::Breakpoint => EXCEPTION_RECORD::new(STATUS_BREAKPOINT, 0, eip, &[0]),
+        x86::Fault::SingleStep => EXCEPTION_RECORD::new(STATUS_SINGLE_STEP, 0, eip, &[]),
+        x86::Fault::FloatingPoint => {
+            let code = float_exception_code(machine.emu.x86.cpu().fpu.unmasked_exceptions());
+            EXCEPTION_RECORD::new(code, 0, eip, &[])
+        }
+    };
+    log::debug!("fault {fault:?} at {eip:x}");
+    let mut context = capture_context(machine);
+    if fault == x86::Fault::SingleStep {
+        // Windows clears TF for the handler, which must set it again to keep stepping.
+        context.EFlags &= !x86::Flags::TF.bits();
+    }
+    dispatch_later(machine, record, context);
+}
+
+/// The exception code for the most important of the pending unmasked FPU exceptions.
+#[cfg(feature = "x86-emu")]
+fn float_exception_code(pending: x86::FPUStatus) -> u32 {
+    use x86::FPUStatus as Status;
+    [
+        (Status::SF, STATUS_FLOAT_STACK_CHECK),
+        (Status::IE, STATUS_FLOAT_INVALID_OPERATION),
+        (Status::DE, STATUS_FLOAT_DENORMAL_OPERAND),
+        (Status::ZE, STATUS_FLOAT_DIVIDE_BY_ZERO),
+        (Status::OE, STATUS_FLOAT_OVERFLOW),
+        (Status::UE, STATUS_FLOAT_UNDERFLOW),
+    ]
+    .into_iter()
+    .find(|&(flag, _)| pending.contains(flag))
+    .map_or(STATUS_FLOAT_INEXACT_RESULT, |(_, code)| code)
+}
+
+/// Raise an exception from a synchronous builtin, as if its caller had called RaiseException.
+/// args_size is as in caller_context; a handler that continues execution returns 0 from
+/// the call.
+#[cfg(feature = "x86-emu")]
+pub fn raise_from_builtin(machine: &mut Machine, code: u32, args_size: u32, params: &[u32]) {
+    let mut context = caller_context(machine, args_size);
+    context.Eax = 0;
+    let record = EXCEPTION_RECORD::new(code, 0, context.Eip, params);
+    dispatch_later(machine, record, context);
+}
+
+/// Like raise_from_builtin, from within an async builtin, where the handlers can run
+/// directly.
+#[cfg(feature = "x86-emu")]
+pub async fn raise_from_async_builtin(
+    machine: &mut Machine,
+    code: u32,
+    args_size: u32,
+    params: &[u32],
+) {
+    let mut context = caller_context(machine, args_size);
+    context.Eax = 0;
+    let record = EXCEPTION_RECORD::new(code, 0, context.Eip, params);
+    dispatch_exception(machine, record, context).await;
+}
+
+#[cfg(not(feature = "x86-emu"))]
+pub fn raise_from_builtin(_machine: &mut Machine, code: u32, _args_size: u32, _params: &[u32]) {
+    log::error!("exception {code:08x} not raised");
+}
+
+/// Arrange for dispatch_exception to run as soon as the CPU resumes, as it must be
+/// awaited but the callers here aren't async.
+#[cfg(feature = "x86-emu")]
+fn dispatch_later(machine: &mut Machine, record: EXCEPTION_RECORD, context: CONTEXT) {
+    let eip = machine.emu.x86.cpu().regs.eip;
+    let machine_ptr: *mut Machine = machine;
+    let cpu = machine.emu.x86.cpu_mut();
+    cpu.state = x86::CPUState::Running;
+    // Handlers don't run single-stepped.
+    cpu.flags.remove(x86::Flags::TF);
+    cpu.call_async(
+        Box::pin(async move {
+            let machine = unsafe { &mut *machine_ptr };
+            dispatch_exception(machine, record, context).await;
+            0
+        }),
+        eip,
+    );
+}
+
+/// Call the handlers of the records from the head of the SEH chain up to (not including)
+/// target_frame, or the whole chain if it is 0, and pop them off the chain.
+pub async fn unwind(machine: &mut Machine, target_frame: u32, record_addr: u32) {
+    #[cfg(feature = "x86-emu")]
+    {
+        let mut record = if record_addr != 0 {
+            machine.mem().get_pod::<EXCEPTION_RECORD>(record_addr)
+        } else {
+            let eip = machine.emu.x86.cpu().regs.eip;
+            EXCEPTION_RECORD::new(STATUS_UNWIND, 0, eip, &[])
+        };
+        record.ExceptionFlags |= EXCEPTION_UNWINDING;
+        if target_frame == 0 {
+            record.ExceptionFlags |= EXCEPTION_EXIT_UNWIND;
+        }
+
+        // Handlers see a copy of the record, and a context that isn't meaningful here.
+        let esp = machine.emu.x86.cpu().regs.get32(x86::Register::ESP);
+        let context = capture_context(machine);
+        let context_addr = push_pod(machine, context);
+        let record_addr = push_pod(machine, record);
+
+        let mut frame = super::teb(machine).Tib.ExceptionList;
+        while frame != SEH_CHAIN_END && frame != target_frame {
+            let reg = machine
+                .mem()
+                .get_pod::<EXCEPTION_REGISTRATION_RECORD>(frame);
+            if reg.Handler == SEH_SENTINEL_HANDLER {
+                break;
+            }
+            machine
+                .call_x86(reg.Handler, vec![record_addr, frame, context_addr, 0])
+                .await;
+            frame = reg.Prev;
+            super::teb_mut(machine).Tib.ExceptionList = frame;
+        }
+
+        machine
+            .emu
+            .x86
+            .cpu_mut()
+            .regs
+            .set32(x86::Register::ESP, esp);
+    }
+
+    #[cfg(not(feature = "x86-emu"))]
+    {
+        _ = (machine, record_addr);
+        log::warn!("unwind to {target_frame:x}: unimplemented");
+    }
+}
+
+#[win32_derive::dllexport]
+pub async fn RaiseException(
+    machine: &mut Machine,
+    dwExceptionCode: u32,
+    dwExceptionFlags: u32,
+    nNumberOfArguments: u32,
+    lpArguments: u32,
+) {
+    #[cfg(feature = "x86-emu")]
+    {
+        let params = match lpArguments {
+            0 => Vec::new(),
+            addr => machine
+                .mem()
+                .iter_pod::<u32>(
+                    addr,
+                    nNumberOfArguments.min(EXCEPTION_MAXIMUM_PARAMETERS as u32),
+                )
+                .collect(),
+        };
+
+        // The exception appears to come from our caller, and continuing it returns there.
+        let context = caller_context(machine, 4 * 4);
+        let record = EXCEPTION_RECORD::new(
+            dwExceptionCode,
+            dwExceptionFlags & EXCEPTION_NONCONTINUABLE,
+            context.Eip,
+            &params,
+        );
+        dispatch_exception(machine, record, context).await;
+    }
+
+    #[cfg(not(feature = "x86-emu"))]
+    {
+        _ = (machine, nNumberOfArguments, lpArguments);
+        log::warn!("RaiseException({dwExceptionCode:x}, {dwExceptionFlags:x}): unimplemented");
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn SetUnhandledExceptionFilter(machine: &mut Machine, lpTopLevelExceptionFilter: u32) -> u32 {
+    std::mem::replace(
+        &mut machine.state.kernel32.unhandled_exception_filter,
+        lpTopLevelExceptionFilter,
+    )
+}
+
+#[win32_derive::dllexport]
+pub async fn UnhandledExceptionFilter(machine: &mut Machine, ExceptionInfo: u32) -> i32 {
+    let filter = machine.state.kernel32.unhandled_exception_filter;
+    if filter != 0 {
+        let ret = machine.call_x86(filter, vec![ExceptionInfo]).await as i32;
+        if ret != EXCEPTION_CONTINUE_SEARCH {
+            return ret;
+        }
+    }
+    // "The process is being debugged, so the exception should be passed (as second chance) to the application's debugger."
+    EXCEPTION_CONTINUE_SEARCH
+}
+
+/// Copy the register groups selected by flags from one CONTEXT to another.
+/// Callers may pass the smaller CONTEXT of old headers, without ExtendedRegisters,
+/// so this must not touch what they didn't ask for.
+#[cfg(feature = "x86-emu")]
+fn copy_context(dst: &mut CONTEXT, src: &CONTEXT, flags: u32) {
+    let has = |group: u32| flags & group == group;
+    if has(CONTEXT_CONTROL) {
+        dst.Ebp = src.Ebp;
+        dst.Eip = src.Eip;
+        dst.SegCs = src.SegCs;
+        dst.EFlags = src.EFlags;
+        dst.Esp = src.Esp;
+        dst.SegSs = src.SegSs;
+    }
+    if has(CONTEXT_INTEGER) {
+        dst.Edi = src.Edi;
+        dst.Esi = src.Esi;
+        dst.Ebx = src.Ebx;
+        dst.Edx = src.Edx;
+        dst.Ecx = src.Ecx;
+        dst.Eax = src.Eax;
+    }
+    if has(CONTEXT_SEGMENTS) {
+        dst.SegGs = src.SegGs;
+        dst.SegFs = src.SegFs;
+        dst.SegEs = src.SegEs;
+        dst.SegDs = src.SegDs;
+    }
+    if has(CONTEXT_FLOATING_POINT) {
+        dst.FloatSave = src.FloatSave.clone();
+    }
+    if has(CONTEXT_DEBUG_REGISTERS) {
+        dst.Dr0 = src.Dr0;
+        dst.Dr1 = src.Dr1;
+        dst.Dr2 = src.Dr2;
+        dst.Dr3 = src.Dr3;
+        dst.Dr6 = src.Dr6;
+        dst.Dr7 = src.Dr7;
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn GetThreadContext(
+    machine: &mut Machine,
+    hThread: HTHREAD,
+    lpContext: Option<&mut CONTEXT>,
+) -> bool {
+    let Some(cpu) = super::get_thread(machine, hThread).map(|thread| thread.cpu) else {
+        super::set_last_error(machine, crate::winapi::ERROR::INVALID_HANDLE);
+        return false;
+    };
+    let Some(lpContext) = lpContext else {
+        super::set_last_error(machine, crate::winapi::ERROR::INVALID_PARAMETER);
+        return false;
+    };
+
+    #[cfg(feature = "x86-emu")]
+    {
+        // The calling thread sees itself as it will be on return.
+        let context = if cpu == machine.emu.x86.cur_cpu {
+            caller_context(machine, 2 * 4)
+        } else {
+            cpu_context(&machine.emu.x86.cpus[cpu])
+        };
+        copy_context(lpContext, &context, lpContext.ContextFlags);
+        true
+    }
+
+    #[cfg(not(feature = "x86-emu"))]
+    {
+        _ = (cpu, lpContext);
+        log::warn!("GetThreadContext: unimplemented");
+        super::set_last_error(machine, crate::winapi::ERROR::CALL_NOT_IMPLEMENTED);
+        false
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn SetThreadContext(
+    machine: &mut Machine,
+    hThread: HTHREAD,
+    lpContext: Option<&CONTEXT>,
+) -> bool {
+    let Some(cpu) = super::get_thread(machine, hThread).map(|thread| thread.cpu) else {
+        super::set_last_error(machine, crate::winapi::ERROR::INVALID_HANDLE);
+        return false;
+    };
+    let Some(lpContext) = lpContext else {
+        super::set_last_error(machine, crate::winapi::ERROR::INVALID_PARAMETER);
+        return false;
+    };
+
+    #[cfg(feature = "x86-emu")]
+    {
+        let mut context = lpContext.clone();
+        if cpu == machine.emu.x86.cur_cpu {
+            // Setting our own control and integer registers would return into the
+            // middle of nowhere; the useful part is setting hardware breakpoints.
+            context.ContextFlags &= CONTEXT_DEBUG_REGISTERS;
+        }
+        set_cpu_context(&mut machine.emu.x86.cpus[cpu], &context);
+        true
+    }
+
+    #[cfg(not(feature = "x86-emu"))]
+    {
+        _ = (cpu, lpContext);
+        log::warn!("SetThreadContext: unimplemented");
+        super::set_last_error(machine, crate::winapi::ERROR::CALL_NOT_IMPLEMENTED);
+        false
+    }
+}
+
+#[cfg(all(test, feature = "x86-emu"))]
+mod tests {
+    use super::*;
+    use crate::test_guest::{builtin, new_machine, run, Code};
+    use std::mem::offset_of;
+
+    /// Run `trigger` inside the equivalent of __try { trigger } __except(1) { ... },
+    /// with a handler that catches everything, and return the exit code: the caught
+    /// exception's code, or 1 if nothing was raised.
+    fn try_except(trigger: impl FnOnce(&mut Machine, &mut Code)) -> u32 {
+        let (mut machine, _host) = new_machine();
+        let mut code = Code::alloc(&mut machine, 0x1000);
+        let except_block = code.base + 0x100;
+        let handler = code.base + 0x200;
+
+        // Register the handler, as the __try prologue does.
+        code.push(handler)
+            .emit(&[0x64, 0xFF, 0x35, 0, 0, 0, 0]) // push dword fs:[0]
+            .emit(&[0x64, 0x89, 0x25, 0, 0, 0, 0]); // mov fs:[0], esp
+        trigger(&mut machine, &mut code);
+        code.emit(&[0xBB, 1, 0, 0, 0]) // mov ebx, 1
+            .exit_with(&mut machine, 3);
+
+        // The __except block: unregister the handler, and exit with the code.
+        code.at(0x100)
+            .emit(&[0x64, 0x8F, 0x05, 0, 0, 0, 0]) // pop dword fs:[0]
+            .emit(&[0x83, 0xC4, 0x04]) // add esp, 4
+            .exit_with(&mut machine, 3);
+
+        // The handler: catch by resuming in the __except block, with the stack as it
+        // was on registering, and the exception code in ebx.
+        let ctx = |offset: usize| offset as u32;
+        code.at(0x200)
+            .emit(&[0x8B, 0x44, 0x24, 0x04]) // mov eax, [esp+4]  (record)
+            .emit(&[0x8B, 0x00]) // mov eax, [eax]  (ExceptionCode)
+            .emit(&[0x8B, 0x4C, 0x24, 0x0C]) // mov ecx, [esp+12]  (context)
+            .emit(&[0x89, 0x81]) // mov [ecx+Ebx], eax
+            .emit32(ctx(offset_of!(CONTEXT, Ebx)))
+            .emit(&[0x8B, 0x54, 0x24, 0x08]) // mov edx, [esp+8]  (frame)
+            .emit(&[0x89, 0x91]) // mov [ecx+Esp], edx
+            .emit32(ctx(offset_of!(CONTEXT, Esp)))
+            .emit(&[0xC7, 0x81]) // mov dword [ecx+Eip], except_block
+            .emit32(ctx(offset_of!(CONTEXT, Eip)))
+            .emit32(except_block)
+            .emit(&[0x31, 0xC0]) // xor eax, eax  (ExceptionContinueExecution)
+            .emit(&[0xC3]); // ret
+
+        code.start(&mut machine);
+        run(&mut machine)
+    }
+
+    #[test]
+    fn catch_raised_exception() {
+        let code = try_except(|machine, code| {
+            let raise = builtin(machine, "kernel32.dll", "RaiseException");
+            code.push(0).push(0).push(0).push(0xE000_1234).call(raise);
+        });
+        assert_eq!(code, 0xE000_1234);
+    }
+
+    #[test]
+    fn catch_fault() {
+        let code = try_except(|_machine, code| {
+            code.emit(&[0x31, 0xC9]) // xor ecx, ecx
+                .emit(&[0xF7, 0xF1]); // div ecx
+        });
+        assert_eq!(code, STATUS_INTEGER_DIVIDE_BY_ZERO);
+
+        let code = try_except(|_machine, code| {
+            code.emit(&[0xA1, 0x10, 0, 0, 0]); // mov eax, [10h]
+        });
+        assert_eq!(code, STATUS_ACCESS_VIOLATION);
+    }
+
+    #[test]
+    fn nothing_raised() {
+        assert_eq!(try_except(|_, _| {}), 1);
+    }
+
+    #[test]
+    fn catch_null_page_access() {
+        let code = try_except(|_machine, code| {
+            code.emit(&[0x31, 0xC0]) // xor eax, eax
+                .emit(&[0x89, 0x00]); // mov [eax], eax
+        });
+        assert_eq!(code, STATUS_ACCESS_VIOLATION);
+
+        // From a builtin, given a pointer into the null page.
+        let code = try_except(|machine, code| {
+            let memset = builtin(machine, "vcruntime140.dll", "memset");
+            code.push(4).push(0).push(8).call(memset);
+        });
+        assert_eq!(code, STATUS_ACCESS_VIOLATION);
+    }
+
+    /// Run `trigger` with a handler that continues every exception as it was raised, and
+    /// return the number of exceptions and how far esp moved across it, which is 0 if a
+    /// faulting call popped just what it would have on returning normally.
+    fn continue_exceptions(trigger: impl FnOnce(&mut Machine, &mut Code)) -> (u32, u32) {
+        let (mut machine, _host) = new_machine();
+        let mut code = Code::alloc(&mut machine, 0x1000);
+        let handler = code.base + 0x200;
+        let count = code.base + 0x300;
+        code.push(handler)
+            .emit(&[0x64, 0xFF, 0x35, 0, 0, 0, 0]) // push dword fs:[0]
+            .emit(&[0x64, 0x89, 0x25, 0, 0, 0, 0]) // mov fs:[0], esp
+            .emit(&[0x89, 0xE6]); // mov esi, esp
+        trigger(&mut machine, &mut code);
+        code.emit(&[0x29, 0xE6]) // sub esi, esp
+            .emit(&[0xA1]) // mov eax, [count]
+            .emit32(count)
+            .emit(&[0xC1, 0xE0, 0x10]) // shl eax, 16
+            .emit(&[0x01, 0xC6]) // add esi, eax
+            .exit_with(&mut machine, 6);
+        code.at(0x200)
+            .emit(&[0xFF, 0x05]) // inc dword [count]
+            .emit32(count)
+            .emit(&[0x31, 0xC0]) // xor eax, eax  (ExceptionContinueExecution)
+            .emit(&[0xC3]); // ret
+        code.at(0x304);
+        code.start(&mut machine);
+        machine.mem().put_pod::<u32>(count, 0);
+        let exit = run(&mut machine);
+        (exit >> 16, exit & 0xFFFF)
+    }
+
+    #[test]
+    fn bad_pointers_in_builtins_fail_the_call() {
+        let bad = 0xFFFF_0000;
+        // stdcall
+        let moved = continue_exceptions(|machine, code| {
+            let func = builtin(machine, "kernel32.dll", "GetSystemTimeAsFileTime");
+            code.push(bad).call(func);
+        });
+        assert_eq!(moved, (1, 0));
+        // cdecl, where the caller pops the arguments.
+        let moved = continue_exceptions(|machine, code| {
+            let memset = builtin(machine, "vcruntime140.dll", "memset");
+            code.push(4).push(0).push(8).call(memset);
+            code.emit(&[0x83, 0xC4, 0x0C]); // add esp, 12
+        });
+        assert_eq!(moved, (1, 0));
+        // async
+        let moved = continue_exceptions(|machine, code| {
+            let func = builtin(machine, "user32.dll", "DispatchMessageA");
+            code.push(bad).call(func);
+        });
+        assert_eq!(moved, (1, 0));
+    }
+
+    #[test]
+    fn debug_registers_round_trip() {
+        let (mut machine, _host) = new_machine();
+        let cpu = machine.emu.x86.cpu_mut();
+        cpu.debug.dr = [0x1000, 0x2000, 0x3000, 0x4000];
+        cpu.debug.set(7, 0b1);
+
+        let context = cpu_context(machine.emu.x86.cpu());
+        assert_eq!(
+            [context.Dr0, context.Dr1, context.Dr2, context.Dr3],
+            [0x1000, 0x2000, 0x3000, 0x4000]
+        );
+        assert_eq!(context.Dr7 & 0xFF, 0b1);
+
+        // Only the debug registers, as a debugger setting a breakpoint would.
+        let mut update = CONTEXT::zeroed();
+        update.ContextFlags = CONTEXT_DEBUG_REGISTERS;
+        update.Dr1 = 0x5000;
+        update.Dr7 = 0b100;
+        update.Eip = 0xDEAD;
+        let mut context = context;
+        copy_context(&mut context, &update, CONTEXT_DEBUG_REGISTERS);
+        context.ContextFlags = CONTEXT_DEBUG_REGISTERS;
+        let eip = machine.emu.x86.cpu().regs.eip;
+        set_cpu_context(machine.emu.x86.cpu_mut(), &context);
+
+        let cpu = machine.emu.x86.cpu();
+        assert_eq!(cpu.regs.eip, eip);
+        assert_eq!(cpu.debug.dr, [0, 0x5000, 0, 0]);
+        assert_eq!(cpu.debug.dr7 & 0xFF, 0b100);
+        assert!(cpu.debug.breakpoint(1).is_some());
+        let round = cpu_context(cpu);
+        assert_eq!(
+            (round.Dr1, round.Dr6, round.Dr7),
+            (0x5000, cpu.debug.dr6, cpu.debug.dr7)
+        );
+    }
+}
@@ -0,0 +1,341 @@
+//! Fibers: execution contexts that share their thread, and which the application switches
+//! between explicitly with SwitchToFiber.
+
+use super::{set_last_error, teb_mut};
+use crate::{machine::Machine, winapi::ERROR};
+use memory::{Extensions, ExtensionsMut};
+
+/// The guest-visible fiber data, whose address is the fiber "handle".
+/// GetFiberData() and GetCurrentFiber() are macros that read this and TEB.Tib.FiberData directly.
+#[repr(C)]
+#[derive(Clone, Debug, Default)]
+pub struct FIBER {
+    pub lpParameter: u32,
+    /// TIB fields, saved here while the fiber isn't running.
+    pub ExceptionList: u32,
+    pub StackBase: u32,
+    pub StackLimit: u32,
+}
+unsafe impl memory::Pod for FIBER {}
+
+/// Host-side state of a fiber.
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+pub struct Fiber {
+    /// CPU state saved when the fiber was switched away from, or for a new fiber, the
+    /// state it starts with.
+    #[cfg(feature = "x86-emu")]
+    context: Option<(x86::Registers, x86::LazyFlags)>,
+    /// Address of the stack mapping, for fibers created by CreateFiber.
+    stack: Option<u32>,
+}
+
+const DEFAULT_STACK_SIZE: u32 = 1 << 20;
+
+/// Address of the running fiber, if the current thread has been converted to a fiber.
+fn current_fiber(machine: &mut Machine) -> Option<u32> {
+    let addr = teb_mut(machine).Tib.FiberData;
+    if machine.state.kernel32.fibers.contains_key(&addr) {
+        Some(addr)
+    } else {
+        None
+    }
+}
+
+fn alloc_fiber(machine: &mut Machine, fiber: FIBER) -> u32 {
+    let heap = machine
+        .state
+        .kernel32
+        .get_process_heap(&mut machine.emu.memory);
+    let addr = heap.alloc(
+        machine.emu.memory.mem(),
+        std::mem::size_of::<FIBER>() as u32,
+    );
+    machine.mem().put_pod::<FIBER>(addr, fiber);
+    addr
+}
+
+fn convert_thread_to_fiber(machine: &mut Machine, lpParameter: u32) -> u32 {
+    if current_fiber(machine).is_some() {
+        set_last_error(machine, ERROR::ALREADY_FIBER);
+        return 0;
+    }
+    let tib = &teb_mut(machine).Tib;
+    let fiber = FIBER {
+        lpParameter,
+        ExceptionList: tib.ExceptionList,
+        StackBase: tib.StackBase,
+        StackLimit: tib.StackLimit,
+    };
+    let addr = alloc_fiber(machine, fiber);
+    machine.state.kernel32.fibers.insert(addr, Fiber::default());
+    teb_mut(machine).Tib.FiberData = addr;
+    addr
+}
+
+#[win32_derive::dllexport]
+pub fn ConvertThreadToFiber(machine: &mut Machine, lpParameter: u32) -> u32 {
+    convert_thread_to_fiber(machine, lpParameter)
+}
+
+#[win32_derive::dllexport]
+pub fn ConvertThreadToFiberEx(machine: &mut Machine, lpParameter: u32, dwFlags: u32) -> u32 {
+    convert_thread_to_fiber(machine, lpParameter)
+}
+
+#[win32_derive::dllexport]
+pub fn ConvertFiberToThread(machine: &mut Machine) -> bool {
+    let Some(addr) = current_fiber(machine) else {
+        set_last_error(machine, ERROR::ALREADY_THREAD);
+        return false;
+    };
+    machine.state.kernel32.fibers.remove(&addr);
+    teb_mut(machine).Tib.FiberData = 0;
+    let heap = machine
+        .state
+        .kernel32
+        .get_process_heap(&mut machine.emu.memory);
+    heap.free(machine.emu.memory.mem(), addr);
+    true
+}
+
+fn create_fiber(machine: &mut Machine, stack_size: u32, start: u32, param: u32) -> u32 {
+    let fiber_main = super::get_kernel32_builtin(machine, "retrowin32_fiber_main");
+
+    #[cfg(feature = "x86-emu")]
+    {
+        let stack_size = match stack_size {
+            0 => DEFAULT_STACK_SIZE,
+            size => size,
+        };
+        let Some(stack) = machine.state.kernel32.mappings.alloc(
+            stack_size,
+            "fiber stack".into(),
+            &mut machine.emu.memory,
+        ) else {
+            set_last_error(machine, ERROR::NOT_ENOUGH_MEMORY);
+            return 0;
+        };
+        let (stack, stack_base) = (stack.addr, stack.addr + stack.size);
+        let stack_pointer = stack_base - 4;
+        let addr = alloc_fiber(
+            machine,
+            FIBER {
+                lpParameter: param,
+                ExceptionList: 0xFFFF_FFFF, // end of SEH chain
+                StackBase: stack_base,
+                StackLimit: stack,
+            },
+        );
+
+        // The fiber starts by calling retrowin32_fiber_main(start, param),
+        // as if from a function that never returns.
+        let mem = machine.mem();
+        let esp = stack_pointer - 8;
+        mem.put_pod::<u32>(esp, 0); // return address
+        mem.put_pod::<u32>(esp + 4, start);
+        mem.put_pod::<u32>(esp + 8, param);
+        let mut regs = x86::Registers::default();
+        regs.set32(x86::Register::ESP, esp);
+        regs.eip = fiber_main;
+
+        machine.state.kernel32.fibers.insert(
+            addr,
+            Fiber {
+                context: Some((regs, x86::LazyFlags::default())),
+                stack: Some(stack),
+            },
+        );
+        addr
+    }
+
+    #[cfg(not(feature = "x86-emu"))]
+    {
+        _ = (stack_size, start, param, fiber_main, DEFAULT_STACK_SIZE);
+        log::error!("CreateFiber: not implemented for this CPU backend");
+        set_last_error(machine, ERROR::CALL_NOT_IMPLEMENTED);
+        0
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn CreateFiber(
+    machine: &mut Machine,
+    dwStackSize: u32,
+    lpStartAddress: u32,
+    lpParameter: u32,
+) -> u32 {
+    create_fiber(machine, dwStackSize, lpStartAddress, lpParameter)
+}
+
+#[win32_derive::dllexport]
+pub fn CreateFiberEx(
+    machine: &mut Machine,
+    dwStackCommitSize: u32,
+    dwStackReserveSize: u32,
+    dwFlags: u32,
+    lpStartAddress: u32,
+    lpParameter: u32,
+) -> u32 {
+    let stack_size = std::cmp::max(dwStackCommitSize, dwStackReserveSize);
+    create_fiber(machine, stack_size, lpStartAddress, lpParameter)
+}
+
+/// Entry point of fibers created by CreateFiber.
+#[win32_derive::dllexport]
+pub async fn retrowin32_fiber_main(machine: &mut Machine, start: u32, param: u32) {
+    machine.call_x86(start, vec![param]).await;
+    // "If the fiber function returns, the thread running the fiber exits."
+    super::exit_thread(machine, 0);
+}
+
+#[win32_derive::dllexport]
+pub fn SwitchToFiber(machine: &mut Machine, lpFiber: u32) {
+    let Some(cur) = current_fiber(machine) else {
+        log::error!("SwitchToFiber from a thread that isn't a fiber");
+        return;
+    };
+    if cur == lpFiber {
+        return;
+    }
+    let Some(next) = machine.state.kernel32.fibers.get_mut(&lpFiber) else {
+        log::error!("SwitchToFiber({lpFiber:x}): unknown fiber");
+        return;
+    };
+    #[cfg(feature = "x86-emu")]
+    let Some((regs, flags)) = next.context.take() else {
+        log::error!("SwitchToFiber({lpFiber:x}): fiber is running on another thread");
+        return;
+    };
+    #[cfg(not(feature = "x86-emu"))]
+    let _ = next;
+
+    // Swap the fiber-specific TIB fields.
+    let teb_addr = teb_mut(machine).Tib._Self;
+    let mem = machine.mem();
+    let tib = &mut mem.get_aligned_ref_mut::<super::TEB>(teb_addr).Tib;
+    let prev = mem.get_aligned_ref_mut::<FIBER>(cur);
+    prev.ExceptionList = tib.ExceptionList;
+    prev.StackBase = tib.StackBase;
+    prev.StackLimit = tib.StackLimit;
+    let next = mem.get_aligned_ref::<FIBER>(lpFiber);
+    tib.ExceptionList = next.ExceptionList;
+    tib.StackBase = next.StackBase;
+    tib.StackLimit = next.StackLimit;
+    tib.FiberData = lpFiber;
+
+    #[cfg(feature = "x86-emu")]
+    {
+        // Swap CPU state.  We are within the syscall of SwitchToFiber, so the saved state
+        // resumes by returning from SwitchToFiber.
+        // TODO: pending async calls on the CPU are not per-fiber.
+        let cpu = machine.emu.x86.cpu_mut();
+        let fs_addr = cpu.regs.fs_addr;
+        let prev_regs = std::mem::replace(&mut cpu.regs, regs);
+        let prev_flags = std::mem::replace(&mut cpu.flags, flags);
+        // The fiber may have been created on another thread.
+        cpu.regs.fs_addr = fs_addr;
+        machine.state.kernel32.fibers.get_mut(&cur).unwrap().context =
+            Some((prev_regs, prev_flags));
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn DeleteFiber(machine: &mut Machine, lpFiber: u32) {
+    if current_fiber(machine) == Some(lpFiber) {
+        // "If the currently running fiber calls DeleteFiber, its thread calls ExitThread."
+        super::exit_thread(machine, 0);
+        return;
+    }
+    let Some(fiber) = machine.state.kernel32.fibers.remove(&lpFiber) else {
+        log::warn!("DeleteFiber of unknown fiber {lpFiber:x}");
+        return;
+    };
+    if let Some(stack) = fiber.stack {
+        super::release_mapping(machine, stack);
+    }
+    let heap = machine
+        .state
+        .kernel32
+        .get_process_heap(&mut machine.emu.memory);
+    heap.free(machine.emu.memory.mem(), lpFiber);
+}
+
+#[win32_derive::dllexport]
+pub fn IsThreadAFiber(machine: &mut Machine) -> bool {
+    current_fiber(machine).is_some()
+}
+
+#[cfg(all(test, feature = "x86-emu"))]
+mod tests {
+    use crate::test_guest::{builtin, new_machine, run, Code, Log};
+
+    /// Switch back and forth between the main thread, converted to a fiber, and a created
+    /// fiber, each logging as it goes; then delete the created fiber.
+    #[test]
+    fn switch_between_fibers() {
+        let (mut machine, _host) = new_machine();
+        let [convert, create, switch, delete, is_fiber] = [
+            "ConvertThreadToFiber",
+            "CreateFiber",
+            "SwitchToFiber",
+            "DeleteFiber",
+            "IsThreadAFiber",
+        ]
+        .map(|name| builtin(&mut machine, "kernel32.dll", name));
+        let log = Log::alloc(&mut machine, 0x1000);
+        let mut code = Code::alloc(&mut machine, 0x200);
+        let (fiber_proc, record) = (code.base + 0x100, code.base + 0x140);
+        let (main_fiber, fiber) = (code.base + 0x180, code.base + 0x184);
+        code.push(0x11)
+            .call(convert)
+            .save_eax(main_fiber)
+            .push(0x22)
+            .push(fiber_proc)
+            .push(0)
+            .call(create)
+            .save_eax(fiber)
+            .push(1)
+            .call(record)
+            .push_from(fiber)
+            .call(switch)
+            .push(3)
+            .call(record)
+            .push_from(fiber)
+            .call(switch)
+            // An unknown fiber is ignored.
+            .push(0xDEAD)
+            .call(switch)
+            .push(5)
+            .call(record)
+            .push_from(fiber)
+            .call(delete)
+            .call(is_fiber)
+            .emit(&[0x50]) // push eax
+            .call(record)
+            .exit_with(&mut machine, 0)
+            .at(0x100)
+            .emit(&[0xFF, 0x74, 0x24, 0x04]) // push [esp+4]
+            .call(record)
+            .push(2)
+            .call(record)
+            .push_from(main_fiber)
+            .call(switch)
+            .push(4)
+            .call(record)
+            .push_from(main_fiber)
+            .call(switch)
+            .at(0x140)
+            .recorder(&log, 1, 0);
+        code.start(&mut machine);
+        run(&mut machine);
+        assert_eq!(log.read(&machine), [1, 0x22, 2, 3, 4, 5, 1]);
+
+        let kernel32 = &machine.state.kernel32;
+        assert_eq!(kernel32.fibers.len(), 1);
+        assert!(!kernel32
+            .mappings
+            .vec()
+            .iter()
+            .any(|m| m.desc == "fiber stack"));
+    }
+}
@@ -1,7 +1,10 @@
 use crate::str16::String16;
-use crate::winapi::kernel32::set_last_error;
+use crate::winapi::kernel32::{
+    block_thread, current_thread, out_buf, set_last_error, vfs, wake_waiters, Apc, KernelObject,
+    HOBJECT,
+};
 use crate::winapi::stack_args::ToX86;
-use crate::winapi::types::{DWORD, HFIND, MAX_PATH};
+use crate::winapi::types::{DWORD, HEVENT, HFIND, MAX_PATH};
 use crate::winapi::ERROR;
 use crate::{
     machine::Machine,
@@ -12,7 +15,7 @@ use crate::{
     FileOptions, ReadDir, ReadDirEntry, Stat, StatKind,
 };
 use bitflags::bitflags;
-use memory::ExtensionsMut;
+use memory::{Extensions, ExtensionsMut};
 use typed_path::WindowsPath;
 
 #[derive(Debug)]
@@ -21,6 +24,17 @@ pub enum STD {
     OUTPUT_HANDLE = -11,
     ERROR_HANDLE = -12,
 }
+impl STD {
+    /// Index into Console::std_handles.
+    pub fn index(&self) -> usize {
+        match self {
+            STD::INPUT_HANDLE => 0,
+            STD::OUTPUT_HANDLE => 1,
+            STD::ERROR_HANDLE => 2,
+        }
+    }
+}
+
 impl TryFrom<u32> for STD {
     type Error = u32;
 
@@ -40,18 +54,28 @@ pub const STDOUT_HFILE: HFILE = HFILE::from_raw(0xF11E_0101);
 pub const STDERR_HFILE: HFILE = HFILE::from_raw(0xF11E_0102);
 
 #[win32_derive::dllexport]
-pub fn GetStdHandle(_machine: &mut Machine, nStdHandle: Result<STD, u32>) -> HFILE {
+pub fn GetStdHandle(machine: &mut Machine, nStdHandle: Result<STD, u32>) -> HFILE {
     match nStdHandle {
-        Ok(STD::INPUT_HANDLE) => STDIN_HFILE,
-        Ok(STD::OUTPUT_HANDLE) => STDOUT_HFILE,
-        Ok(STD::ERROR_HANDLE) => STDERR_HFILE,
-        _ => HFILE::invalid(),
+        Ok(std) => machine.state.kernel32.console.std_handles[std.index()],
+        Err(_) => {
+            set_last_error(machine, ERROR::INVALID_HANDLE);
+            HFILE::invalid()
+        }
     }
 }
 
 #[win32_derive::dllexport]
-pub fn SetStdHandle(_machine: &mut Machine, nStdHandle: Result<STD, u32>, hHandle: u32) -> bool {
-    true // succees
+pub fn SetStdHandle(machine: &mut Machine, nStdHandle: Result<STD, u32>, hHandle: HFILE) -> bool {
+    match nStdHandle {
+        Ok(std) => {
+            machine.state.kernel32.console.std_handles[std.index()] = hHandle;
+            true
+        }
+        Err(_) => {
+            set_last_error(machine, ERROR::INVALID_HANDLE);
+            false
+        }
+    }
 }
 
 // https://learn.microsoft.com/en-us/windows/win32/api/fileapi/nf-fileapi-createfilea
@@ -106,6 +130,11 @@ impl From<&Stat> for FileAttribute {
                 attr |= FileAttribute::REPARSE_POINT;
             }
         }
+        if stat.readonly {
+            // NORMAL is only valid alone.
+            attr.remove(FileAttribute::NORMAL);
+            attr |= FileAttribute::READONLY;
+        }
         attr
     }
 }
@@ -138,6 +167,41 @@ impl TryFrom<u32> for GENERIC {
     }
 }
 
+// Specific access rights, which may be used in place of GENERIC_*.
+const FILE_READ_DATA: u32 = 0x1;
+const FILE_WRITE_DATA: u32 = 0x2;
+const FILE_APPEND_DATA: u32 = 0x4;
+
+bitflags! {
+    /// The FILE_FLAG_* half of CreateFile's dwFlagsAndAttributes.
+    #[derive(serde::Serialize, serde::Deserialize)]
+    pub struct FileFlags: u32 {
+        const WRITE_THROUGH = 0x80000000;
+        const OVERLAPPED = 0x40000000;
+        const NO_BUFFERING = 0x20000000;
+        const RANDOM_ACCESS = 0x10000000;
+        const SEQUENTIAL_SCAN = 0x08000000;
+        const DELETE_ON_CLOSE = 0x04000000;
+        const BACKUP_SEMANTICS = 0x02000000;
+        const POSIX_SEMANTICS = 0x01000000;
+    }
+}
+
+/// An open file, as referenced by an HFILE.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct FileHandle {
+    #[serde(skip, default = "crate::snapshot::Detach::detached")]
+    pub file: Box<dyn crate::host::File>,
+    /// The path the file was opened with.
+    pub path: String,
+    /// Access the file was opened with.
+    pub read: bool,
+    pub write: bool,
+    /// FILE_SHARE_* flags.  Sharing isn't enforced; we only record it.
+    pub share_mode: u32,
+    pub flags: FileFlags,
+}
+
 #[win32_derive::dllexport]
 pub fn CreateFileA(
     machine: &mut Machine,
@@ -146,7 +210,7 @@ pub fn CreateFileA(
     dwShareMode: u32,
     lpSecurityAttributes: u32,
     dwCreationDisposition: Result<CreationDisposition, u32>,
-    dwFlagsAndAttributes: Result<FileAttribute, u32>,
+    dwFlagsAndAttributes: u32,
     hTemplateFile: HFILE,
 ) -> HFILE {
     let Some(file_name) = lpFileName else {
@@ -155,8 +219,11 @@ pub fn CreateFileA(
         return HFILE::invalid();
     };
 
-    // If this from_bits fails, it's due to more complex access bits; see ACCESS_MASK in MSDN docs.
-    let generic_access = GENERIC::from_bits(dwDesiredAccess).unwrap();
+    let generic_access = GENERIC::from_bits_truncate(dwDesiredAccess);
+    let read = generic_access.intersects(GENERIC::READ | GENERIC::ALL)
+        || dwDesiredAccess & FILE_READ_DATA != 0;
+    let write = generic_access.intersects(GENERIC::WRITE | GENERIC::ALL)
+        || dwDesiredAccess & (FILE_WRITE_DATA | FILE_APPEND_DATA) != 0;
     let creation_disposition = match dwCreationDisposition {
         Ok(value) => value,
         Err(value) => {
@@ -168,8 +235,9 @@ pub fn CreateFileA(
 
     // https://learn.microsoft.com/en-us/windows/win32/api/fileapi/nf-fileapi-createfilea
     let file_options = FileOptions {
-        read: generic_access.contains(GENERIC::READ),
-        write: generic_access.contains(GENERIC::WRITE),
+        // Opening with no access is permitted, for querying metadata.
+        read: read || !write,
+        write,
         truncate: matches!(
             creation_disposition,
             CreationDisposition::CREATE_ALWAYS | CreationDisposition::TRUNCATE_EXISTING
@@ -181,20 +249,45 @@ pub fn CreateFileA(
         create_new: creation_disposition == CreationDisposition::CREATE_NEW,
     };
 
-    let attr = dwFlagsAndAttributes.unwrap();
-    if !(attr & !FileAttribute::NORMAL).is_empty() {
-        unimplemented!("dwFlagsAndAttributes {attr:?}");
+    if let Some(name) = super::pipe_name(file_name) {
+        return super::open_pipe(machine, &name, read, write);
+    }
+
+    // Attributes only matter when creating a file, and we don't store them.
+    let flags = FileFlags::from_bits_truncate(dwFlagsAndAttributes);
+    if flags.contains(FileFlags::DELETE_ON_CLOSE) {
+        log::warn!("CreateFileA({file_name:?}): FILE_FLAG_DELETE_ON_CLOSE not implemented");
     }
 
     if !hTemplateFile.is_null() {
-        unimplemented!("hTemplateFile {hTemplateFile:?}");
+        log::warn!("CreateFileA({file_name:?}): ignoring hTemplateFile");
     }
 
     let path = WindowsPath::new(file_name);
-    match machine.host.open(path, file_options) {
+    // CREATE_ALWAYS and OPEN_ALWAYS succeed either way, but report whether the file
+    // was already there.
+    let existed = file_options.create && vfs(machine).stat(path).is_ok();
+    match vfs(machine).open(path, file_options) {
         Ok(file) => {
-            set_last_error(machine, ERROR::SUCCESS);
-            machine.state.kernel32.files.add(file)
+            let err = if existed {
+                ERROR::ALREADY_EXISTS
+            } else {
+                ERROR::SUCCESS
+            };
+            set_last_error(machine, err);
+            let handle = machine
+                .state
+                .kernel32
+                .objects
+                .add(KernelObject::File(FileHandle {
+                    file,
+                    path: file_name.to_string(),
+                    read,
+                    write,
+                    share_mode: dwShareMode,
+                    flags,
+                }));
+            HFILE::from_raw(handle.to_raw())
         }
         Err(err) => {
             log::debug!("CreateFileA({file_name:?}) failed: {err:?}",);
@@ -212,15 +305,12 @@ pub fn CreateFileW(
     dwShareMode: u32,
     lpSecurityAttributes: u32,
     dwCreationDisposition: Result<CreationDisposition, u32>,
-    dwFlagsAndAttributes: Result<FileAttribute, u32>,
+    dwFlagsAndAttributes: u32,
     hTemplateFile: HFILE,
 ) -> HFILE {
     CreateFileA(
         machine,
-        lpFileName
-            .map(|f| f.to_string())
-            .as_ref()
-            .map(|f| f.as_str()),
+        lpFileName.map(|f| f.to_string()).as_deref(),
         dwDesiredAccess,
         dwShareMode,
         lpSecurityAttributes,
@@ -232,14 +322,19 @@ pub fn CreateFileW(
 
 #[win32_derive::dllexport]
 pub fn GetFileType(machine: &mut Machine, hFile: HFILE) -> u32 {
+    let FILE_TYPE_DISK = 0x1;
     let FILE_TYPE_CHAR = 0x2;
+    let FILE_TYPE_PIPE = 0x3;
     let FILE_TYPE_UNKNOWN = 0x8;
     match hFile {
         STDIN_HFILE | STDOUT_HFILE | STDERR_HFILE => return FILE_TYPE_CHAR,
         _ => {}
     }
-    if machine.state.kernel32.files.get(hFile).is_some() {
-        return FILE_TYPE_CHAR;
+    if machine.state.kernel32.objects.file(hFile).is_some() {
+        return FILE_TYPE_DISK;
+    }
+    if machine.state.kernel32.objects.pipe_mut(hFile).is_some() {
+        return FILE_TYPE_PIPE;
     }
 
     log::error!("GetFileType({hFile:?}) unknown handle");
@@ -326,7 +421,7 @@ pub fn GetFileInformationByHandle(
     hFile: HFILE,
     lpFileInformation: Option<&mut BY_HANDLE_FILE_INFORMATION>,
 ) -> bool {
-    let file = match machine.state.kernel32.files.get(hFile) {
+    let file = match machine.state.kernel32.objects.file(hFile) {
         Some(f) => f,
         None => {
             log::debug!("GetFileInformationByHandle({hFile:?}) unknown handle");
@@ -335,7 +430,7 @@ pub fn GetFileInformationByHandle(
         }
     };
 
-    let stat = match file.stat() {
+    let stat = match file.file.stat() {
         Ok(stat) => stat,
         Err(err) => {
             log::debug!("GetFileInformationByHandle({hFile:?}) failed: {err:?}",);
@@ -359,6 +454,25 @@ pub enum FILE {
     END = 2,
 }
 
+pub fn seek(machine: &mut Machine, hFile: HFILE, pos: std::io::SeekFrom) -> Result<u64, ERROR> {
+    let Some(file) = machine.state.kernel32.objects.file_mut(hFile) else {
+        log::debug!("seek({hFile:?}) unknown handle");
+        return Err(ERROR::INVALID_HANDLE);
+    };
+    file.file.seek(pos).map_err(|err| {
+        log::debug!("seek({hFile:?}) failed: {:?}", err);
+        ERROR::from(err)
+    })
+}
+
+fn seek_from(distance: i64, method: FILE) -> std::io::SeekFrom {
+    match method {
+        FILE::BEGIN => std::io::SeekFrom::Start(distance as u64),
+        FILE::CURRENT => std::io::SeekFrom::Current(distance),
+        FILE::END => std::io::SeekFrom::End(distance),
+    }
+}
+
 #[win32_derive::dllexport]
 pub fn SetFilePointer(
     machine: &mut Machine,
@@ -369,23 +483,16 @@ pub fn SetFilePointer(
 ) -> u32 {
     let mut lDistanceToMove = lDistanceToMove as i64;
     if let Some(high) = &mut lpDistanceToMoveHigh {
-        lDistanceToMove |= (**high as i64) << 32;
+        lDistanceToMove = (lDistanceToMove & 0xFFFF_FFFF) | ((**high as i64) << 32);
     }
-    let Some(file) = machine.state.kernel32.files.get_mut(hFile) else {
-        log::debug!("SetFilePointer({hFile:?}) unknown handle");
-        set_last_error(machine, ERROR::INVALID_HANDLE);
-        return u32::MAX;
-    };
-    let seek = match dwMoveMethod.unwrap() {
-        FILE::BEGIN => std::io::SeekFrom::Start(lDistanceToMove as u64),
-        FILE::CURRENT => std::io::SeekFrom::Current(lDistanceToMove),
-        FILE::END => std::io::SeekFrom::End(lDistanceToMove),
-    };
-    let pos = match file.seek(seek) {
+    let pos = match seek(
+        machine,
+        hFile,
+        seek_from(lDistanceToMove, dwMoveMethod.unwrap()),
+    ) {
         Ok(pos) => pos,
         Err(err) => {
-            log::debug!("SetFilePointer({hFile:?}) failed: {:?}", err);
-            set_last_error(machine, ERROR::from(err));
+            set_last_error(machine, err);
             return u32::MAX;
         }
     };
@@ -401,54 +508,211 @@ pub fn SetFilePointer(
 }
 
 #[win32_derive::dllexport]
-pub fn ReadFile(
+pub fn SetFilePointerEx(
     machine: &mut Machine,
     hFile: HFILE,
-    lpBuffer: ArrayWithSizeMut<u8>,
-    mut lpNumberOfBytesRead: Option<&mut u32>,
-    lpOverlapped: u32,
+    liDistanceToMove: u64,
+    lpNewFilePointer: Option<&mut u64>,
+    dwMoveMethod: Result<FILE, u32>,
 ) -> bool {
-    // "ReadFile sets this value to zero before doing any work or error checking."
-    if let Some(bytes) = lpNumberOfBytesRead.as_deref_mut() {
-        *bytes = 0;
+    let distance = liDistanceToMove as i64;
+    match seek(machine, hFile, seek_from(distance, dwMoveMethod.unwrap())) {
+        Ok(pos) => {
+            if let Some(new_pos) = lpNewFilePointer {
+                *new_pos = pos;
+            }
+            set_last_error(machine, ERROR::SUCCESS);
+            true
+        }
+        Err(err) => {
+            set_last_error(machine, err);
+            false
+        }
     }
-    let Some(file) = (match hFile {
-        STDIN_HFILE => unimplemented!("ReadFile(stdin)"),
-        _ => machine.state.kernel32.files.get_mut(hFile),
-    }) else {
-        log::debug!("ReadFile({hFile:?}) unknown handle");
-        set_last_error(machine, ERROR::INVALID_HANDLE);
-        return false;
-    };
-    if lpOverlapped != 0 {
-        unimplemented!("ReadFile overlapped");
+}
+
+/// Asynchronous I/O state, passed to ReadFile etc. for files opened with FILE_FLAG_OVERLAPPED.
+#[repr(C)]
+#[derive(Debug)]
+pub struct OVERLAPPED {
+    /// NTSTATUS of the operation.
+    pub Internal: u32,
+    /// Number of bytes transferred.
+    pub InternalHigh: u32,
+    pub Offset: u32,
+    pub OffsetHigh: u32,
+    pub hEvent: HEVENT,
+}
+unsafe impl memory::Pod for OVERLAPPED {}
+
+const STATUS_SUCCESS: u32 = 0;
+const STATUS_PENDING: u32 = 0x103;
+
+/// For an I/O with an OVERLAPPED, the file offset it starts at.
+fn overlapped_offset(machine: &mut Machine, lpOverlapped: u32) -> Option<u64> {
+    if lpOverlapped == 0 {
+        return None;
     }
-    let Some(mut buf) = lpBuffer.to_option() else {
-        log::debug!("ReadFile({hFile:?}) failed: null lpBuffer");
-        set_last_error(machine, ERROR::INVALID_DATA);
-        return false;
+    let ov = machine.mem().get_aligned_ref::<OVERLAPPED>(lpOverlapped);
+    Some((ov.OffsetHigh as u64) << 32 | ov.Offset as u64)
+}
+
+/// Record the completion of an I/O in its OVERLAPPED, signaling its event if `signal`.
+pub(super) fn complete_overlapped(
+    machine: &mut Machine,
+    lpOverlapped: u32,
+    transferred: usize,
+    signal: bool,
+) {
+    let ov = machine
+        .mem()
+        .get_aligned_ref_mut::<OVERLAPPED>(lpOverlapped);
+    ov.Internal = STATUS_SUCCESS;
+    ov.InternalHigh = transferred as u32;
+    // The low bit of hEvent is a flag to skip I/O completion ports.
+    let event = HEVENT::from_raw(ov.hEvent.to_raw() & !1);
+    if signal && !event.is_null() {
+        super::SetEvent(machine, event);
+    }
+    wake_waiters(machine);
+}
+
+/// Look up a file for ReadFile/WriteFile, checking the handle was opened with the needed access.
+/// `overlapped` is whether the call passed an OVERLAPPED, which overlapped handles require.
+fn file_for_io(
+    machine: &mut Machine,
+    hFile: HFILE,
+    write: bool,
+    overlapped: bool,
+) -> Result<&mut FileHandle, ERROR> {
+    let Some(file) = machine.state.kernel32.objects.file_mut(hFile) else {
+        log::debug!("I/O on unknown handle {hFile:?}");
+        return Err(ERROR::INVALID_HANDLE);
     };
+    if !(if write { file.write } else { file.read }) {
+        return Err(ERROR::ACCESS_DENIED);
+    }
+    if file.flags.contains(FileFlags::OVERLAPPED) && !overlapped {
+        return Err(ERROR::INVALID_PARAMETER);
+    }
+    Ok(file)
+}
 
+/// Read until `buf` is full or the end of the file, starting at `offset` if given.
+pub fn read_file(
+    machine: &mut Machine,
+    hFile: HFILE,
+    mut buf: &mut [u8],
+    offset: Option<u64>,
+) -> Result<usize, ERROR> {
+    let file = file_for_io(machine, hFile, false, offset.is_some())?;
+    if let Some(offset) = offset {
+        file.file.seek(std::io::SeekFrom::Start(offset))?;
+    }
     let mut read = 0;
     while !buf.is_empty() {
-        match file.read(buf) {
-            Ok(0) => break,
-            Ok(n) => {
+        match file.file.read(buf)? {
+            0 => break,
+            n => {
                 read += n;
                 buf = &mut buf[n..];
             }
-            Err(err) => {
-                log::debug!("ReadFile({hFile:?}) failed: {:?}", err);
-                set_last_error(machine, ERROR::from(err));
-                return false;
+        }
+    }
+    if read == 0 && !buf.is_empty() && offset.is_some() {
+        // Overlapped reads report the end of the file as an error.
+        return Err(ERROR::HANDLE_EOF);
+    }
+    Ok(read)
+}
+
+/// Write all of `buf`, starting at `offset` if given.
+pub fn write_file(
+    machine: &mut Machine,
+    hFile: HFILE,
+    mut buf: &[u8],
+    offset: Option<u64>,
+) -> Result<usize, ERROR> {
+    if hFile == STDOUT_HFILE || hFile == STDERR_HFILE {
+        machine.host.log(buf);
+        return Ok(buf.len());
+    }
+    if machine.state.kernel32.objects.pipe_mut(hFile).is_some() {
+        return super::write_pipe(machine, hFile, buf);
+    }
+    let file = file_for_io(machine, hFile, true, offset.is_some())?;
+    if let Some(offset) = offset {
+        file.file.seek(std::io::SeekFrom::Start(offset))?;
+    }
+    let mut written = 0;
+    while !buf.is_empty() {
+        match file.file.write(buf)? {
+            0 => break,
+            n => {
+                written += n;
+                buf = &buf[n..];
             }
         }
     }
+    Ok(written)
+}
 
-    set_last_error(machine, ERROR::SUCCESS);
+#[win32_derive::dllexport]
+pub async fn ReadFile(
+    machine: &mut Machine,
+    hFile: HFILE,
+    lpBuffer: ArrayWithSizeMut<'_, u8>,
+    mut lpNumberOfBytesRead: Option<&mut u32>,
+    lpOverlapped: u32,
+) -> bool {
+    // "ReadFile sets this value to zero before doing any work or error checking."
+    if let Some(bytes) = lpNumberOfBytesRead.as_deref_mut() {
+        *bytes = 0;
+    }
+    let Some(buf) = lpBuffer.to_option() else {
+        log::debug!("ReadFile({hFile:?}) failed: null lpBuffer");
+        set_last_error(machine, ERROR::INVALID_DATA);
+        return false;
+    };
+
+    // Set when a message from a pipe didn't fit in buf.
+    let mut more_data = false;
+    let read = if hFile == STDIN_HFILE {
+        // Console reads return as soon as any input is available, rather than filling buf.
+        super::read_console(machine, buf).await
+    } else if machine.state.kernel32.objects.pipe_mut(hFile).is_some() {
+        super::read_pipe(machine, hFile, buf)
+            .await
+            .map(|(n, more)| {
+                more_data = more;
+                n
+            })
+    } else {
+        // Overlapped reads always complete synchronously.
+        let offset = overlapped_offset(machine, lpOverlapped);
+        read_file(machine, hFile, buf, offset)
+    };
+    let read = match read {
+        Ok(n) => n,
+        Err(err) => {
+            log::debug!("ReadFile({hFile:?}) failed: {:?}", err);
+            set_last_error(machine, err);
+            return false;
+        }
+    };
+
+    if lpOverlapped != 0 {
+        complete_overlapped(machine, lpOverlapped, read, true);
+    }
     if let Some(bytes) = lpNumberOfBytesRead {
         *bytes = read as u32;
     }
+    if more_data {
+        // The rest of the message is left for another read.
+        set_last_error(machine, ERROR::MORE_DATA);
+        return false;
+    }
+    set_last_error(machine, ERROR::SUCCESS);
     true
 }
 
@@ -464,45 +728,26 @@ pub fn WriteFile(
     if let Some(bytes) = lpNumberOfBytesWritten.as_deref_mut() {
         *bytes = 0;
     }
-    if lpOverlapped != 0 {
-        unimplemented!("ReadFile overlapped");
-    }
-    let Some(mut buf) = lpBuffer else {
+    let Some(buf) = lpBuffer else {
         log::debug!("WriteFile({hFile:?}) failed: null lpBuffer");
         set_last_error(machine, ERROR::INVALID_DATA);
         return false;
     };
 
-    let n = match hFile {
-        STDOUT_HFILE | STDERR_HFILE => {
-            machine.host.log(buf);
-            buf.len()
-        }
-        _ => {
-            let Some(file) = machine.state.kernel32.files.get_mut(hFile) else {
-                log::debug!("WriteFile({hFile:?}) unknown handle");
-                set_last_error(machine, ERROR::INVALID_HANDLE);
-                return false;
-            };
-            let mut written = 0;
-            while !buf.is_empty() {
-                match file.write(buf) {
-                    Ok(0) => break,
-                    Ok(n) => {
-                        written += n;
-                        buf = &buf[n..];
-                    }
-                    Err(err) => {
-                        log::debug!("WriteFile({hFile:?}) failed: {:?}", err);
-                        set_last_error(machine, ERROR::from(err));
-                        return false;
-                    }
-                }
-            }
-            written
+    // Overlapped writes always complete synchronously.
+    let offset = overlapped_offset(machine, lpOverlapped);
+    let n = match write_file(machine, hFile, buf, offset) {
+        Ok(n) => n,
+        Err(err) => {
+            log::debug!("WriteFile({hFile:?}) failed: {:?}", err);
+            set_last_error(machine, err);
+            return false;
         }
     };
 
+    if lpOverlapped != 0 {
+        complete_overlapped(machine, lpOverlapped, n, true);
+    }
     set_last_error(machine, ERROR::SUCCESS);
     if let Some(written) = lpNumberOfBytesWritten {
         *written = n as u32;
@@ -510,13 +755,81 @@ pub fn WriteFile(
     true
 }
 
+/// Queue the completion routine of a ReadFileEx/WriteFileEx on the current thread.
+fn queue_completion(machine: &mut Machine, routine: u32, transferred: usize, lpOverlapped: u32) {
+    complete_overlapped(machine, lpOverlapped, transferred, false);
+    current_thread(machine).apcs.push(Apc {
+        func: routine,
+        args: vec![ERROR::SUCCESS.into(), transferred as u32, lpOverlapped],
+    });
+}
+
+#[win32_derive::dllexport]
+pub fn ReadFileEx(
+    machine: &mut Machine,
+    hFile: HFILE,
+    lpBuffer: ArrayWithSizeMut<u8>,
+    lpOverlapped: u32,
+    lpCompletionRoutine: u32,
+) -> bool {
+    let offset = overlapped_offset(machine, lpOverlapped);
+    let read = match read_file(machine, hFile, lpBuffer.unwrap(), offset) {
+        Ok(n) => n,
+        Err(err) => {
+            log::debug!("ReadFileEx({hFile:?}) failed: {:?}", err);
+            set_last_error(machine, err);
+            return false;
+        }
+    };
+    queue_completion(machine, lpCompletionRoutine, read, lpOverlapped);
+    set_last_error(machine, ERROR::SUCCESS);
+    true
+}
+
 #[win32_derive::dllexport]
-pub fn GetConsoleMode(
-    _machine: &mut Machine,
-    hConsoleHandle: HFILE,
-    lpMode: Option<&mut u32>,
+pub fn WriteFileEx(
+    machine: &mut Machine,
+    hFile: HFILE,
+    lpBuffer: ArrayWithSize<u8>,
+    lpOverlapped: u32,
+    lpCompletionRoutine: u32,
+) -> bool {
+    let offset = overlapped_offset(machine, lpOverlapped);
+    let written = match write_file(machine, hFile, lpBuffer.unwrap(), offset) {
+        Ok(n) => n,
+        Err(err) => {
+            log::debug!("WriteFileEx({hFile:?}) failed: {:?}", err);
+            set_last_error(machine, err);
+            return false;
+        }
+    };
+    queue_completion(machine, lpCompletionRoutine, written, lpOverlapped);
+    set_last_error(machine, ERROR::SUCCESS);
+    true
+}
+
+#[win32_derive::dllexport]
+pub async fn GetOverlappedResult(
+    machine: &mut Machine,
+    hFile: HFILE,
+    lpOverlapped: u32,
+    lpNumberOfBytesTransferred: Option<&mut u32>,
+    bWait: bool,
 ) -> bool {
-    *lpMode.unwrap() = 0;
+    loop {
+        let ov = machine.mem().get_aligned_ref::<OVERLAPPED>(lpOverlapped);
+        if ov.Internal != STATUS_PENDING {
+            break;
+        }
+        if !bWait {
+            set_last_error(machine, ERROR::IO_INCOMPLETE);
+            return false;
+        }
+        // Completion wakes waiting threads.
+        block_thread(machine, None).await;
+    }
+    let ov = machine.mem().get_aligned_ref::<OVERLAPPED>(lpOverlapped);
+    *lpNumberOfBytesTransferred.unwrap() = ov.InternalHigh;
     true
 }
 
@@ -534,7 +847,7 @@ pub fn GetFullPathNameA(
         return 0;
     };
 
-    let cwd = match machine.host.current_dir() {
+    let out_path = match vfs(machine).full_path(WindowsPath::new(file_name)) {
         Ok(value) => value,
         Err(err) => {
             log::debug!("GetFullPathNameA({file_name:?}) failed: {err:?}",);
@@ -542,12 +855,18 @@ pub fn GetFullPathNameA(
             return 0;
         }
     };
-    let out_path = cwd.join(file_name).normalize();
     let out_bytes = out_path.as_bytes();
 
     set_last_error(machine, ERROR::SUCCESS);
 
-    let buf = machine.mem().sub32_mut(lpBuffer, nBufferLength);
+    let Some(buf) = out_buf(
+        machine,
+        lpBuffer,
+        nBufferLength,
+        format_args!("GetFullPathNameA({file_name:?})"),
+    ) else {
+        return 0;
+    };
     if let Some(part) = lpFilePart {
         if let Some(i) = out_bytes.iter().rposition(|&b| b == b'\\') {
             if i == out_bytes.len() - 1 {
@@ -590,7 +909,7 @@ pub fn GetFullPathNameW(
     };
 
     let file_name = file_name.to_string();
-    let cwd = match machine.host.current_dir() {
+    let out_path = match vfs(machine).full_path(WindowsPath::new(&file_name)) {
         Ok(value) => value,
         Err(err) => {
             log::debug!("GetFullPathNameW({file_name:?}) failed: {err:?}",);
@@ -598,12 +917,19 @@ pub fn GetFullPathNameW(
             return 0;
         }
     };
-    let out_path = cwd.join(&file_name).normalize();
     let out_bytes = String16::from(out_path.to_string_lossy().as_ref()).0;
 
     set_last_error(machine, ERROR::SUCCESS);
 
-    let buf = Str16::from_bytes_mut(machine.mem().sub32_mut(lpBuffer, nBufferLength * 2));
+    let Some(buf) = out_buf(
+        machine,
+        lpBuffer,
+        nBufferLength * 2,
+        format_args!("GetFullPathNameW({file_name:?})"),
+    ) else {
+        return 0;
+    };
+    let buf = Str16::from_bytes_mut(buf);
     if let Some(part) = lpFilePart {
         if let Some(i) = out_bytes.iter().rposition(|&b| b == b'\\' as u16) {
             if i == out_bytes.len() - 1 {
@@ -640,7 +966,7 @@ pub fn DeleteFileA(machine: &mut Machine, lpFileName: Option<&str>) -> bool {
     };
 
     let path = WindowsPath::new(file_name);
-    match machine.host.remove_file(path) {
+    match vfs(machine).remove_file(path) {
         Ok(()) => {
             set_last_error(machine, ERROR::SUCCESS);
             true
@@ -653,6 +979,172 @@ pub fn DeleteFileA(machine: &mut Machine, lpFileName: Option<&str>) -> bool {
     }
 }
 
+#[win32_derive::dllexport]
+pub fn DeleteFileW(machine: &mut Machine, lpFileName: Option<&Str16>) -> bool {
+    DeleteFileA(machine, lpFileName.map(|f| f.to_string()).as_deref())
+}
+
+bitflags! {
+    pub struct MOVEFILE: u32 {
+        const REPLACE_EXISTING = 0x1;
+        const COPY_ALLOWED = 0x2;
+        const DELAY_UNTIL_REBOOT = 0x4;
+        const WRITE_THROUGH = 0x8;
+    }
+}
+impl TryFrom<u32> for MOVEFILE {
+    type Error = u32;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        MOVEFILE::from_bits(value).ok_or(value)
+    }
+}
+
+fn move_file(machine: &mut Machine, from: &str, to: &str, flags: MOVEFILE) -> Result<(), ERROR> {
+    let from = WindowsPath::new(from);
+    let to = WindowsPath::new(to);
+    // Hosts replace existing files when renaming, but MoveFile only does with the flag.
+    if !flags.contains(MOVEFILE::REPLACE_EXISTING) && vfs(machine).stat(to).is_ok() {
+        return Err(ERROR::ALREADY_EXISTS);
+    }
+    vfs(machine).rename(from, to)
+}
+
+#[win32_derive::dllexport]
+pub fn MoveFileExA(
+    machine: &mut Machine,
+    lpExistingFileName: Option<&str>,
+    lpNewFileName: Option<&str>,
+    dwFlags: Result<MOVEFILE, u32>,
+) -> bool {
+    let (Some(from), Some(to)) = (lpExistingFileName, lpNewFileName) else {
+        // A null lpNewFileName with DELAY_UNTIL_REBOOT deletes the file on reboot.
+        log::debug!("MoveFileExA failed: null file name");
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return false;
+    };
+    let flags = dwFlags.unwrap_or_else(|value| {
+        log::warn!("MoveFileExA: invalid flags {value:x}");
+        MOVEFILE::from_bits_truncate(value)
+    });
+    match move_file(machine, from, to, flags) {
+        Ok(()) => {
+            set_last_error(machine, ERROR::SUCCESS);
+            true
+        }
+        Err(err) => {
+            log::debug!("MoveFileExA({from:?}, {to:?}) failed: {err:?}");
+            set_last_error(machine, err);
+            false
+        }
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn MoveFileExW(
+    machine: &mut Machine,
+    lpExistingFileName: Option<&Str16>,
+    lpNewFileName: Option<&Str16>,
+    dwFlags: Result<MOVEFILE, u32>,
+) -> bool {
+    MoveFileExA(
+        machine,
+        lpExistingFileName.map(|f| f.to_string()).as_deref(),
+        lpNewFileName.map(|f| f.to_string()).as_deref(),
+        dwFlags,
+    )
+}
+
+#[win32_derive::dllexport]
+pub fn MoveFileA(
+    machine: &mut Machine,
+    lpExistingFileName: Option<&str>,
+    lpNewFileName: Option<&str>,
+) -> bool {
+    MoveFileExA(
+        machine,
+        lpExistingFileName,
+        lpNewFileName,
+        Ok(MOVEFILE::COPY_ALLOWED),
+    )
+}
+
+#[win32_derive::dllexport]
+pub fn MoveFileW(
+    machine: &mut Machine,
+    lpExistingFileName: Option<&Str16>,
+    lpNewFileName: Option<&Str16>,
+) -> bool {
+    MoveFileExW(
+        machine,
+        lpExistingFileName,
+        lpNewFileName,
+        Ok(MOVEFILE::COPY_ALLOWED),
+    )
+}
+
+fn copy_file(
+    machine: &mut Machine,
+    from: &str,
+    to: &str,
+    fail_if_exists: bool,
+) -> Result<(), ERROR> {
+    let mut src = vfs(machine).open(WindowsPath::new(from), FileOptions::read())?;
+    let options = FileOptions {
+        write: true,
+        truncate: !fail_if_exists,
+        create: !fail_if_exists,
+        create_new: fail_if_exists,
+        ..Default::default()
+    };
+    let mut dst = vfs(machine).open(WindowsPath::new(to), options)?;
+    std::io::copy(&mut src, &mut dst)?;
+    // The copy keeps the original's modification time.
+    let stat = src.stat()?;
+    dst.set_times(None, Some(stat.mtime))?;
+    Ok(())
+}
+
+#[win32_derive::dllexport]
+pub fn CopyFileA(
+    machine: &mut Machine,
+    lpExistingFileName: Option<&str>,
+    lpNewFileName: Option<&str>,
+    bFailIfExists: bool,
+) -> bool {
+    let (Some(from), Some(to)) = (lpExistingFileName, lpNewFileName) else {
+        log::debug!("CopyFileA failed: null file name");
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return false;
+    };
+    match copy_file(machine, from, to, bFailIfExists) {
+        Ok(()) => {
+            set_last_error(machine, ERROR::SUCCESS);
+            true
+        }
+        Err(err) => {
+            log::debug!("CopyFileA({from:?}, {to:?}) failed: {err:?}");
+            set_last_error(machine, err);
+            false
+        }
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn CopyFileW(
+    machine: &mut Machine,
+    lpExistingFileName: Option<&Str16>,
+    lpNewFileName: Option<&Str16>,
+    bFailIfExists: bool,
+) -> bool {
+    CopyFileA(
+        machine,
+        lpExistingFileName.map(|f| f.to_string()).as_deref(),
+        lpNewFileName.map(|f| f.to_string()).as_deref(),
+        bFailIfExists,
+    )
+}
+
 #[win32_derive::dllexport]
 pub fn RemoveDirectoryA(machine: &mut Machine, lpPathName: Option<&str>) -> bool {
     let Some(path_name) = lpPathName else {
@@ -662,7 +1154,7 @@ pub fn RemoveDirectoryA(machine: &mut Machine, lpPathName: Option<&str>) -> bool
     };
 
     let path = WindowsPath::new(path_name);
-    match machine.host.remove_dir(path) {
+    match vfs(machine).remove_dir(path) {
         Ok(()) => {
             set_last_error(machine, ERROR::SUCCESS);
             true
@@ -684,7 +1176,7 @@ pub fn GetFileAttributesA(machine: &mut Machine, lpFileName: Option<&str>) -> Fi
     };
 
     let path = WindowsPath::new(file_name);
-    let stat = match machine.host.stat(path) {
+    let stat = match vfs(machine).stat(path) {
         Ok(stat) => stat,
         Err(err) => {
             log::debug!("GetFileAttributesA({file_name:?}) failed: {err:?}",);
@@ -694,17 +1186,89 @@ pub fn GetFileAttributesA(machine: &mut Machine, lpFileName: Option<&str>) -> Fi
     };
 
     set_last_error(machine, ERROR::SUCCESS);
+    FileAttribute::from(&stat)
+}
+
+#[win32_derive::dllexport]
+pub fn GetFileAttributesW(machine: &mut Machine, lpFileName: Option<&Str16>) -> FileAttribute {
+    GetFileAttributesA(machine, lpFileName.map(|f| f.to_string()).as_deref())
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct WIN32_FILE_ATTRIBUTE_DATA {
+    pub dwFileAttributes: u32,
+    pub ftCreationTime: FILETIME,
+    pub ftLastAccessTime: FILETIME,
+    pub ftLastWriteTime: FILETIME,
+    pub nFileSizeHigh: u32,
+    pub nFileSizeLow: u32,
+}
+unsafe impl memory::Pod for WIN32_FILE_ATTRIBUTE_DATA {}
+
+#[derive(Debug, win32_derive::TryFromEnum)]
+pub enum GET_FILEEX_INFO_LEVELS {
+    GetFileExInfoStandard = 0,
+}
 
-    match stat.kind {
-        StatKind::File => FileAttribute::NORMAL,
-        StatKind::Directory => FileAttribute::DIRECTORY,
-        StatKind::Symlink => FileAttribute::REPARSE_POINT,
+#[win32_derive::dllexport]
+pub fn GetFileAttributesExA(
+    machine: &mut Machine,
+    lpFileName: Option<&str>,
+    fInfoLevelId: Result<GET_FILEEX_INFO_LEVELS, u32>,
+    lpFileInformation: Option<&mut WIN32_FILE_ATTRIBUTE_DATA>,
+) -> bool {
+    let Some(file_name) = lpFileName else {
+        log::debug!("GetFileAttributesExA failed: null lpFileName");
+        set_last_error(machine, ERROR::INVALID_DATA);
+        return false;
+    };
+    if let Err(level) = fInfoLevelId {
+        log::debug!("GetFileAttributesExA({file_name:?}) failed: invalid info level {level}");
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return false;
     }
+
+    let path = WindowsPath::new(file_name);
+    let stat = match vfs(machine).stat(path) {
+        Ok(stat) => stat,
+        Err(err) => {
+            log::debug!("GetFileAttributesExA({file_name:?}) failed: {err:?}",);
+            set_last_error(machine, err);
+            return false;
+        }
+    };
+
+    *lpFileInformation.unwrap() = WIN32_FILE_ATTRIBUTE_DATA {
+        dwFileAttributes: FileAttribute::from(&stat).bits(),
+        ftCreationTime: FILETIME::from_unix_nanos(stat.ctime),
+        ftLastAccessTime: FILETIME::from_unix_nanos(stat.atime),
+        ftLastWriteTime: FILETIME::from_unix_nanos(stat.mtime),
+        nFileSizeHigh: (stat.size >> 32) as u32,
+        nFileSizeLow: stat.size as u32,
+    };
+    set_last_error(machine, ERROR::SUCCESS);
+    true
+}
+
+#[win32_derive::dllexport]
+pub fn GetFileAttributesExW(
+    machine: &mut Machine,
+    lpFileName: Option<&Str16>,
+    fInfoLevelId: Result<GET_FILEEX_INFO_LEVELS, u32>,
+    lpFileInformation: Option<&mut WIN32_FILE_ATTRIBUTE_DATA>,
+) -> bool {
+    GetFileAttributesExA(
+        machine,
+        lpFileName.map(|f| f.to_string()).as_deref(),
+        fInfoLevelId,
+        lpFileInformation,
+    )
 }
 
 #[win32_derive::dllexport]
 pub fn GetCurrentDirectoryA(machine: &mut Machine, nBufferLength: u32, lpBuffer: u32) -> u32 {
-    let cwd = match machine.host.current_dir() {
+    let cwd = match vfs(machine).current_dir() {
         Ok(value) => value,
         Err(err) => {
             log::debug!("GetCurrentDirectoryA failed: {err:?}");
@@ -714,7 +1278,14 @@ pub fn GetCurrentDirectoryA(machine: &mut Machine, nBufferLength: u32, lpBuffer:
     };
     let out_bytes = cwd.as_bytes();
 
-    let buf = machine.mem().sub32_mut(lpBuffer, nBufferLength);
+    let Some(buf) = out_buf(
+        machine,
+        lpBuffer,
+        nBufferLength,
+        format_args!("GetCurrentDirectoryA"),
+    ) else {
+        return 0;
+    };
 
     if buf.len() < out_bytes.len() + 1 {
         // not enough space
@@ -729,6 +1300,257 @@ pub fn GetCurrentDirectoryA(machine: &mut Machine, nBufferLength: u32, lpBuffer:
     out_bytes.len() as u32
 }
 
+#[win32_derive::dllexport]
+pub fn GetCurrentDirectoryW(machine: &mut Machine, nBufferLength: u32, lpBuffer: u32) -> u32 {
+    let cwd = match vfs(machine).current_dir() {
+        Ok(value) => value,
+        Err(err) => {
+            log::debug!("GetCurrentDirectoryW failed: {err:?}");
+            set_last_error(machine, err);
+            return 0;
+        }
+    };
+    let out_chars = String16::from(cwd.to_string_lossy().as_ref()).0;
+
+    let Some(buf) = out_buf(
+        machine,
+        lpBuffer,
+        nBufferLength * 2,
+        format_args!("GetCurrentDirectoryW"),
+    ) else {
+        return 0;
+    };
+    let buf = Str16::from_bytes_mut(buf);
+
+    if buf.len() < out_chars.len() + 1 {
+        // not enough space
+        log::debug!("GetCurrentDirectoryW -> size {}", out_chars.len() + 1);
+        return out_chars.len() as u32 + 1;
+    }
+
+    buf[..out_chars.len()].copy_from_slice(&out_chars);
+    buf[out_chars.len()] = 0;
+
+    set_last_error(machine, ERROR::SUCCESS);
+    out_chars.len() as u32
+}
+
+pub(super) fn set_current_dir(machine: &mut Machine, path: &str) -> Result<(), ERROR> {
+    let vfs = vfs(machine);
+    let path = vfs.full_path(WindowsPath::new(path))?;
+    if vfs.stat(&path)?.kind != StatKind::Directory {
+        return Err(ERROR::DIRECTORY);
+    }
+    machine.state.kernel32.vfs.set_current_dir(path);
+    Ok(())
+}
+
+#[win32_derive::dllexport]
+pub fn SetCurrentDirectoryA(machine: &mut Machine, lpPathName: Option<&str>) -> bool {
+    let Some(path_name) = lpPathName else {
+        log::debug!("SetCurrentDirectoryA failed: null lpPathName");
+        set_last_error(machine, ERROR::INVALID_DATA);
+        return false;
+    };
+    match set_current_dir(machine, path_name) {
+        Ok(()) => {
+            set_last_error(machine, ERROR::SUCCESS);
+            true
+        }
+        Err(err) => {
+            log::debug!("SetCurrentDirectoryA({path_name:?}) failed: {err:?}");
+            set_last_error(machine, err);
+            false
+        }
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn SetCurrentDirectoryW(machine: &mut Machine, lpPathName: Option<&Str16>) -> bool {
+    SetCurrentDirectoryA(machine, lpPathName.map(|f| f.to_string()).as_deref())
+}
+
+#[win32_derive::dllexport]
+pub fn GetTempPathA(machine: &mut Machine, nBufferLength: u32, lpBuffer: u32) -> u32 {
+    let dir = match vfs(machine).temp_dir() {
+        Ok(dir) => dir,
+        Err(err) => {
+            log::debug!("GetTempPathA failed: {err:?}");
+            set_last_error(machine, err);
+            return 0;
+        }
+    };
+    let out_bytes = dir.as_bytes();
+
+    let Some(buf) = out_buf(
+        machine,
+        lpBuffer,
+        nBufferLength,
+        format_args!("GetTempPathA"),
+    ) else {
+        return 0;
+    };
+    if buf.len() < out_bytes.len() + 1 {
+        // not enough space
+        return out_bytes.len() as u32 + 1;
+    }
+
+    buf[..out_bytes.len()].copy_from_slice(out_bytes);
+    buf[out_bytes.len()] = 0;
+
+    set_last_error(machine, ERROR::SUCCESS);
+    out_bytes.len() as u32
+}
+
+#[win32_derive::dllexport]
+pub fn GetTempPathW(machine: &mut Machine, nBufferLength: u32, lpBuffer: u32) -> u32 {
+    let dir = match vfs(machine).temp_dir() {
+        Ok(dir) => dir,
+        Err(err) => {
+            log::debug!("GetTempPathW failed: {err:?}");
+            set_last_error(machine, err);
+            return 0;
+        }
+    };
+    let out_chars = String16::from(dir.as_str()).0;
+
+    let Some(buf) = out_buf(
+        machine,
+        lpBuffer,
+        nBufferLength * 2,
+        format_args!("GetTempPathW"),
+    ) else {
+        return 0;
+    };
+    let buf = Str16::from_bytes_mut(buf);
+    if buf.len() < out_chars.len() + 1 {
+        // not enough space
+        return out_chars.len() as u32 + 1;
+    }
+
+    buf[..out_chars.len()].copy_from_slice(&out_chars);
+    buf[out_chars.len()] = 0;
+
+    set_last_error(machine, ERROR::SUCCESS);
+    out_chars.len() as u32
+}
+
+/// Build a temp file name of the form `dir\PREuuuu.TMP`.  If `unique` is zero, generate
+/// numbers until one names a file that doesn't exist yet, and create that file.
+/// `len` measures a name in the caller's encoding; names that can't fit in a MAX_PATH
+/// buffer are rejected before anything is created.
+/// Returns the path and the number used.
+fn temp_file_name(
+    machine: &mut Machine,
+    dir: &str,
+    prefix: &str,
+    unique: u32,
+    len: fn(&str) -> usize,
+) -> Result<(String, u32), ERROR> {
+    // Only the first three characters of the prefix are used.
+    let prefix = prefix.chars().take(3).collect::<String>();
+    let mut dir = dir.to_string();
+    if !dir.is_empty() && !dir.ends_with(['\\', '/']) {
+        dir.push('\\');
+    }
+    let name = |unique: u32| format!("{dir}{prefix}{unique:X}.TMP");
+    if len(&name(0xFFFF)) + 1 > MAX_PATH {
+        return Err(ERROR::BUFFER_OVERFLOW);
+    }
+
+    // "If uUnique is not zero ... the function does not create the specified file."
+    if unique != 0 {
+        return Ok((name(unique & 0xFFFF), unique & 0xFFFF));
+    }
+
+    let options = FileOptions {
+        write: true,
+        create_new: true,
+        ..Default::default()
+    };
+    for _ in 0..=u16::MAX {
+        let unique = machine.state.kernel32.vfs.next_temp_unique() as u32;
+        let path = name(unique);
+        match vfs(machine).open(WindowsPath::new(&path), options.clone()) {
+            Ok(_) => return Ok((path, unique)),
+            Err(ERROR::FILE_EXISTS) => continue,
+            Err(err) => return Err(err),
+        }
+    }
+    Err(ERROR::FILE_EXISTS)
+}
+
+#[win32_derive::dllexport]
+pub fn GetTempFileNameA(
+    machine: &mut Machine,
+    lpPathName: Option<&str>,
+    lpPrefixString: Option<&str>,
+    uUnique: u32,
+    lpTempFileName: u32,
+) -> u32 {
+    let (Some(dir), Some(prefix)) = (lpPathName, lpPrefixString) else {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return 0;
+    };
+    // The buffer must hold MAX_PATH characters; check it before creating any file.
+    let call = format_args!("GetTempFileNameA({dir:?}, {prefix:?})");
+    if out_buf(machine, lpTempFileName, MAX_PATH as u32, call).is_none() {
+        return 0;
+    }
+    let (path, unique) = match temp_file_name(machine, dir, prefix, uUnique, str::len) {
+        Ok(value) => value,
+        Err(err) => {
+            log::debug!("{call} failed: {err:?}");
+            set_last_error(machine, err);
+            return 0;
+        }
+    };
+    let out_bytes = path.as_bytes();
+    let buf = machine.mem().sub32_mut(lpTempFileName, MAX_PATH as u32);
+    buf[..out_bytes.len()].copy_from_slice(out_bytes);
+    buf[out_bytes.len()] = 0;
+
+    set_last_error(machine, ERROR::SUCCESS);
+    unique
+}
+
+#[win32_derive::dllexport]
+pub fn GetTempFileNameW(
+    machine: &mut Machine,
+    lpPathName: Option<&Str16>,
+    lpPrefixString: Option<&Str16>,
+    uUnique: u32,
+    lpTempFileName: u32,
+) -> u32 {
+    let (Some(dir), Some(prefix)) = (lpPathName, lpPrefixString) else {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return 0;
+    };
+    let (dir, prefix) = (dir.to_string(), prefix.to_string());
+    // The buffer must hold MAX_PATH characters; check it before creating any file.
+    let call = format_args!("GetTempFileNameW({dir:?}, {prefix:?})");
+    if out_buf(machine, lpTempFileName, MAX_PATH as u32 * 2, call).is_none() {
+        return 0;
+    }
+    let utf16_len = |name: &str| name.encode_utf16().count();
+    let (path, unique) = match temp_file_name(machine, &dir, &prefix, uUnique, utf16_len) {
+        Ok(value) => value,
+        Err(err) => {
+            log::debug!("{call} failed: {err:?}");
+            set_last_error(machine, err);
+            return 0;
+        }
+    };
+    let out_chars = String16::from(path.as_str()).0;
+    let buf = machine.mem().sub32_mut(lpTempFileName, MAX_PATH as u32 * 2);
+    let buf = Str16::from_bytes_mut(buf);
+    buf[..out_chars.len()].copy_from_slice(&out_chars);
+    buf[out_chars.len()] = 0;
+
+    set_last_error(machine, ERROR::SUCCESS);
+    unique
+}
+
 #[repr(C)]
 #[derive(Debug)]
 pub struct WIN32_FIND_DATAA {
@@ -774,8 +1596,11 @@ impl From<&ReadDirEntry> for WIN32_FIND_DATAA {
 }
 unsafe impl memory::Pod for WIN32_FIND_DATAA {}
 
+#[derive(serde::Serialize, serde::Deserialize)]
 pub struct FindHandle {
     pub pattern: String,
+    /// Listings can't be resumed, so after loading a save state this reads as finished.
+    #[serde(skip, default = "crate::snapshot::Detach::detached")]
     pub read_dir: Box<dyn ReadDir>,
 }
 
@@ -810,7 +1635,7 @@ pub fn FindFirstFileA(
         pattern = "*".to_string();
     }
 
-    let mut read_dir = match machine.host.read_dir(parent) {
+    let mut read_dir = match vfs(machine).read_dir(parent) {
         Ok(handle) => handle,
         Err(err) => {
             log::debug!("FindFirstFileA({file_name:?}) failed: {err:?}",);
@@ -844,11 +1669,12 @@ pub fn FindFirstFileA(
     }
 
     set_last_error(machine, ERROR::SUCCESS);
-    machine
+    let handle = machine
         .state
         .kernel32
-        .find_handles
-        .add(FindHandle { pattern, read_dir })
+        .objects
+        .add(KernelObject::Find(FindHandle { pattern, read_dir }));
+    HFIND::from_raw(handle.to_raw())
 }
 
 #[win32_derive::dllexport]
@@ -857,7 +1683,7 @@ pub fn FindNextFileA(
     hFindFile: HFIND,
     lpFindFileData: Option<&mut WIN32_FIND_DATAA>,
 ) -> bool {
-    let handle = match machine.state.kernel32.find_handles.get_mut(hFindFile) {
+    let handle = match machine.state.kernel32.objects.find_handle_mut(hFindFile) {
         Some(handle) => handle,
         None => {
             log::debug!("FindNextFileA({hFindFile:?}) unknown handle");
@@ -895,12 +1721,9 @@ pub fn FindNextFileA(
 
 #[win32_derive::dllexport]
 pub fn FindClose(machine: &mut Machine, hFindFile: HFIND) -> bool {
-    if machine
-        .state
-        .kernel32
-        .find_handles
-        .remove(hFindFile)
-        .is_none()
+    let objects = &mut machine.state.kernel32.objects;
+    if objects.find_handle_mut(hFindFile).is_none()
+        || !objects.close(HOBJECT::from_raw(hFindFile.to_raw()))
     {
         log::debug!("FindClose({hFindFile:?}): unknown handle");
         set_last_error(machine, ERROR::INVALID_HANDLE);
@@ -913,7 +1736,7 @@ pub fn FindClose(machine: &mut Machine, hFindFile: HFIND) -> bool {
 
 #[win32_derive::dllexport]
 pub fn GetFileSize(machine: &mut Machine, hFile: HFILE, lpFileSizeHigh: Option<&mut u32>) -> u32 {
-    let file = match machine.state.kernel32.files.get(hFile) {
+    let file = match machine.state.kernel32.objects.file(hFile) {
         Some(f) => f,
         None => {
             log::debug!("GetFileSize({hFile:?}) unknown handle");
@@ -922,7 +1745,7 @@ pub fn GetFileSize(machine: &mut Machine, hFile: HFILE, lpFileSizeHigh: Option<&
         }
     };
 
-    let stat = match file.stat() {
+    let stat = match file.file.stat() {
         Ok(stat) => stat,
         Err(err) => {
             log::debug!("GetFileSize({hFile:?}) failed: {err:?}");
@@ -942,6 +1765,27 @@ pub fn GetFileSize(machine: &mut Machine, hFile: HFILE, lpFileSizeHigh: Option<&
     stat.size as u32
 }
 
+#[win32_derive::dllexport]
+pub fn GetFileSizeEx(machine: &mut Machine, hFile: HFILE, lpFileSize: Option<&mut u64>) -> bool {
+    let Some(file) = machine.state.kernel32.objects.file(hFile) else {
+        log::debug!("GetFileSizeEx({hFile:?}) unknown handle");
+        set_last_error(machine, ERROR::INVALID_HANDLE);
+        return false;
+    };
+    match file.file.stat() {
+        Ok(stat) => {
+            *lpFileSize.unwrap() = stat.size;
+            set_last_error(machine, ERROR::SUCCESS);
+            true
+        }
+        Err(err) => {
+            log::debug!("GetFileSizeEx({hFile:?}) failed: {err:?}");
+            set_last_error(machine, err);
+            false
+        }
+    }
+}
+
 #[win32_derive::dllexport]
 pub fn GetFileTime(
     machine: &mut Machine,
@@ -950,7 +1794,7 @@ pub fn GetFileTime(
     lpLastAccessTime: Option<&mut FILETIME>,
     lpLastWriteTime: Option<&mut FILETIME>,
 ) -> bool {
-    let file = match machine.state.kernel32.files.get(hFile) {
+    let file = match machine.state.kernel32.objects.file(hFile) {
         Some(f) => f,
         None => {
             log::debug!("GetFileTime({hFile:?}) unknown handle");
@@ -959,7 +1803,7 @@ pub fn GetFileTime(
         }
     };
 
-    let stat = match file.stat() {
+    let stat = match file.file.stat() {
         Ok(stat) => stat,
         Err(error) => {
             log::debug!("GetFileTime({hFile:?}) failed: {error:?}");
@@ -984,7 +1828,7 @@ pub fn GetFileTime(
 
 #[win32_derive::dllexport]
 pub fn SetEndOfFile(machine: &mut Machine, hFile: HFILE) -> bool {
-    let file = match machine.state.kernel32.files.get_mut(hFile) {
+    let file = match machine.state.kernel32.objects.file_mut(hFile) {
         Some(f) => f,
         None => {
             log::debug!("SetEndOfFile({hFile:?}) unknown handle");
@@ -993,7 +1837,7 @@ pub fn SetEndOfFile(machine: &mut Machine, hFile: HFILE) -> bool {
         }
     };
 
-    let len = match file.seek(std::io::SeekFrom::Current(0)) {
+    let len = match file.file.seek(std::io::SeekFrom::Current(0)) {
         Ok(pos) => pos,
         Err(err) => {
             log::debug!("SetEndOfFile({hFile:?}) failed: {:?}", err);
@@ -1001,7 +1845,7 @@ pub fn SetEndOfFile(machine: &mut Machine, hFile: HFILE) -> bool {
             return false;
         }
     };
-    match file.set_len(len) {
+    match file.file.set_len(len) {
         Ok(()) => {
             set_last_error(machine, ERROR::SUCCESS);
             true
@@ -1027,7 +1871,7 @@ pub fn CreateDirectoryA(
     };
 
     let path = WindowsPath::new(path_name);
-    match machine.host.create_dir(path) {
+    match vfs(machine).create_dir(path) {
         Ok(()) => {
             set_last_error(machine, ERROR::SUCCESS);
             true
@@ -1051,11 +1895,45 @@ pub fn SetFileAttributesA(
         set_last_error(machine, ERROR::INVALID_DATA);
         return false;
     };
-    dwFileAttributes.unwrap();
+    let attributes = match dwFileAttributes {
+        Ok(attributes) => attributes,
+        Err(value) => {
+            log::debug!("SetFileAttributesA({file_name:?}) failed: invalid attributes {value:x}");
+            set_last_error(machine, ERROR::INVALID_PARAMETER);
+            return false;
+        }
+    };
+    // Only the read-only attribute is stored by the host.
+    let other = attributes - FileAttribute::READONLY - FileAttribute::NORMAL;
+    if !other.is_empty() {
+        log::debug!("SetFileAttributesA({file_name:?}): ignoring {other:?}");
+    }
 
-    let _ = file_name;
-    log::debug!("SetFileAttributesA stub");
-    true
+    let path = WindowsPath::new(file_name);
+    match vfs(machine).set_readonly(path, attributes.contains(FileAttribute::READONLY)) {
+        Ok(()) => {
+            set_last_error(machine, ERROR::SUCCESS);
+            true
+        }
+        Err(err) => {
+            log::debug!("SetFileAttributesA({file_name:?}) failed: {err:?}");
+            set_last_error(machine, err);
+            false
+        }
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn SetFileAttributesW(
+    machine: &mut Machine,
+    lpFileName: Option<&Str16>,
+    dwFileAttributes: Result<FileAttribute, u32>,
+) -> bool {
+    SetFileAttributesA(
+        machine,
+        lpFileName.map(|f| f.to_string()).as_deref(),
+        dwFileAttributes,
+    )
 }
 
 #[win32_derive::dllexport]
@@ -1066,7 +1944,7 @@ pub fn SetFileTime(
     lpLastAccessTime: Option<&FILETIME>,
     lpLastWriteTime: Option<&FILETIME>,
 ) -> bool {
-    let file = match machine.state.kernel32.files.get_mut(hFile) {
+    let file = match machine.state.kernel32.objects.file_mut(hFile) {
         Some(f) => f,
         None => {
             log::debug!("SetFileTime({hFile:?}) unknown handle");
@@ -1075,28 +1953,22 @@ pub fn SetFileTime(
         }
     };
 
-    let mut stat = match file.stat() {
-        Ok(stat) => stat,
+    if lpCreationTime.is_some() {
+        log::debug!("SetFileTime({hFile:?}): ignoring creation time");
+    }
+    let atime = lpLastAccessTime.map(|time| time.to_unix_nanos());
+    let mtime = lpLastWriteTime.map(|time| time.to_unix_nanos());
+    match file.file.set_times(atime, mtime) {
+        Ok(()) => {
+            set_last_error(machine, ERROR::SUCCESS);
+            true
+        }
         Err(error) => {
             log::debug!("SetFileTime({hFile:?}) failed: {error:?}");
             set_last_error(machine, error);
-            return false;
+            false
         }
-    };
-
-    if let Some(time) = lpCreationTime {
-        stat.ctime = time.to_unix_nanos();
-    }
-    if let Some(time) = lpLastAccessTime {
-        stat.atime = time.to_unix_nanos();
-    }
-    if let Some(time) = lpLastWriteTime {
-        stat.mtime = time.to_unix_nanos();
     }
-
-    let _ = stat;
-    log::debug!("SetFileTime stub");
-    true
 }
 
 /// Matches a string against a glob pattern with `*` and `?` wildcards.
@@ -1160,5 +2032,19 @@ mod tests {
 
 #[win32_derive::dllexport]
 pub fn FlushFileBuffers(machine: &mut Machine, hFile: HFILE) -> bool {
-    todo!();
+    if let STDOUT_HFILE | STDERR_HFILE = hFile {
+        return true;
+    }
+    let Some(file) = machine.state.kernel32.objects.file_mut(hFile) else {
+        log::debug!("FlushFileBuffers({hFile:?}) unknown handle");
+        set_last_error(machine, ERROR::INVALID_HANDLE);
+        return false;
+    };
+    if let Err(err) = file.file.flush() {
+        log::debug!("FlushFileBuffers({hFile:?}) failed: {:?}", err);
+        set_last_error(machine, ERROR::from(err));
+        return false;
+    }
+    set_last_error(machine, ERROR::SUCCESS);
+    true
 }
@@ -1,22 +1,30 @@
 //! Process initialization and startup.
 
 use super::{
-    EventObject, FindHandle, Mappings, ResourceHandle, DLL, HMODULE, STDERR_HFILE, STDOUT_HFILE,
+    Clock, CodePage, Console, Fiber, KernelObject, Mappings, Moveable, ObjectId, ObjectTable,
+    Pipes, ResourceHandle, SystemInfo, Thread, Vfs, View, DLL, HMODULE, MAIN_THREAD_ID,
+    STDERR_HFILE, STDOUT_HFILE,
 };
 use crate::{
     machine::MemImpl,
     pe,
     segments::SegmentDescriptor,
-    winapi::{alloc::Arena, handle::Handles, heap::Heap, types::*},
+    winapi::{
+        alloc::Arena,
+        handle::Handles,
+        heap::{Heap, HeapStats},
+        types::*,
+    },
     Machine,
 };
 use ::memory::Mem;
 use memory::{Extensions, ExtensionsMut};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// Process command line, as exposed in GetCommandLine() and also TEB.
 /// Gross: GetCommandLineA() needs to return a pointer that's never freed,
 /// so we need to hang on to both versions of the command line.
+#[derive(serde::Serialize, serde::Deserialize)]
 pub struct CommandLine {
     /// Command line, split args.
     pub args: Vec<String>,
@@ -63,29 +71,70 @@ impl CommandLine {
     }
 }
 
-// TODO: follow the logic for CommandLineToArgvW
-// https://learn.microsoft.com/en-us/windows/win32/api/shellapi/nf-shellapi-commandlinetoargvw
-fn split_cmdline(cmdline: &str) -> Vec<String> {
+/// Split a command line into arguments, following the rules of CommandLineToArgvW:
+/// https://learn.microsoft.com/en-us/windows/win32/api/shellapi/nf-shellapi-commandlinetoargvw
+/// including its undocumented handling of consecutive quotes, as in Wine.
+pub fn split_cmdline(cmdline: &str) -> Vec<String> {
+    let is_space = |c: &char| *c == ' ' || *c == '\t';
+    let mut chars = cmdline.chars().peekable();
     let mut args = Vec::new();
+
+    // The program name is either quoted, without any escaping, or ends at whitespace.
     let mut arg = String::new();
-    let mut in_quote = false;
-    for c in cmdline.chars() {
-        match c {
-            ' ' if !in_quote => {
-                if !arg.is_empty() {
-                    args.push(arg);
-                    arg = String::new();
+    if chars.next_if_eq(&'"').is_some() {
+        arg.extend(chars.by_ref().take_while(|&c| c != '"'));
+    } else {
+        while let Some(c) = chars.next_if(|c| !is_space(c)) {
+            arg.push(c);
+        }
+    }
+    args.push(arg);
+
+    loop {
+        while chars.next_if(is_space).is_some() {}
+        if chars.peek().is_none() {
+            break;
+        }
+        let mut arg = String::new();
+        // Odd while within quotes.
+        let mut quotes = 0;
+        let mut backslashes = 0;
+        while let Some(c) = chars.next_if(|c| quotes == 1 || !is_space(c)) {
+            match c {
+                '\\' => {
+                    arg.push(c);
+                    backslashes += 1;
+                    continue;
+                }
+                '"' if backslashes % 2 == 0 => {
+                    // 2n backslashes and a quote: n backslashes, and the quote toggles quoting.
+                    arg.truncate(arg.len() - backslashes / 2);
+                    quotes += 1;
+                }
+                '"' => {
+                    // 2n+1 backslashes and a quote: n backslashes and a literal quote.
+                    arg.truncate(arg.len() - backslashes / 2 - 1);
+                    arg.push('"');
+                }
+                c => {
+                    arg.push(c);
+                    backslashes = 0;
+                    continue;
                 }
             }
-            '"' => {
-                in_quote = !in_quote;
+            backslashes = 0;
+            // Every third consecutive quote is a literal quote.
+            while chars.next_if_eq(&'"').is_some() {
+                quotes += 1;
+                if quotes == 3 {
+                    arg.push('"');
+                    quotes = 0;
+                }
             }
-            _ => {
-                arg.push(c);
+            if quotes == 2 {
+                quotes = 0;
             }
         }
-    }
-    if !arg.is_empty() {
         args.push(arg);
     }
     args
@@ -130,13 +179,6 @@ struct RTL_USER_PROCESS_PARAMETERS {
 }
 unsafe impl ::memory::Pod for RTL_USER_PROCESS_PARAMETERS {}
 
-#[repr(C)]
-struct _EXCEPTION_REGISTRATION_RECORD {
-    Prev: DWORD,
-    Handler: DWORD,
-}
-unsafe impl ::memory::Pod for _EXCEPTION_REGISTRATION_RECORD {}
-
 /// Set up TEB, PEB, and other process info.
 /// The FS register points at the TEB (thread info), which points at the PEB (process info).
 fn init_teb(cmdline: &CommandLine, arena: &mut Arena, mem: Mem) -> u32 {
@@ -162,16 +204,16 @@ fn init_teb(cmdline: &CommandLine, arena: &mut Arena, mem: Mem) -> u32 {
     let peb = mem.get_aligned_ref_mut::<PEB>(peb_addr);
     peb.ProcessParameters = params_addr;
     peb.ProcessHeap = 0; // TODO: we use state.process_heap instead
-    peb.TlsCount = 0;
+    peb.TlsBitmapBits = [0; 2];
 
     // SEH chain
     let seh_addr = arena.alloc(
-        std::mem::size_of::<_EXCEPTION_REGISTRATION_RECORD>() as u32,
+        std::mem::size_of::<super::EXCEPTION_REGISTRATION_RECORD>() as u32,
         4,
     );
-    let seh = mem.get_aligned_ref_mut::<_EXCEPTION_REGISTRATION_RECORD>(seh_addr);
+    let seh = mem.get_aligned_ref_mut::<super::EXCEPTION_REGISTRATION_RECORD>(seh_addr);
     seh.Prev = 0xFFFF_FFFF;
-    seh.Handler = 0xFF5E_5EFF; // Hopefully easier to spot.
+    seh.Handler = super::SEH_SENTINEL_HANDLER;
 
     // TEB
     let teb_addr = arena.alloc(std::cmp::max(std::mem::size_of::<TEB>() as u32, 0x100), 4);
@@ -179,6 +221,7 @@ fn init_teb(cmdline: &CommandLine, arena: &mut Arena, mem: Mem) -> u32 {
     teb.Tib.ExceptionList = seh_addr;
     teb.Tib._Self = teb_addr; // Confusing: it points to itself.
     teb.Peb = peb_addr;
+    teb.ClientId_UniqueThread = MAIN_THREAD_ID;
 
     teb_addr
     // log::info!("params {params_addr:x} peb {peb_addr:x} teb {teb_addr:x}");
@@ -194,28 +237,65 @@ pub struct GDTEntries {
     pub ss: u16,
 }
 
+#[derive(serde::Serialize, serde::Deserialize)]
 pub struct State {
     /// Memory for kernel32 data structures.
     arena: Arena,
     /// Address image was loaded at.
     pub image_base: u32,
-    /// Address of TEB (what FS register-relative addresses refer to).
+    /// Address of the main thread's TEB (what FS register-relative addresses refer to).
     pub teb: u32,
     pub mappings: Mappings,
     /// Heaps created by HeapAlloc().
     heaps: HashMap<u32, Heap>,
     pub process_heap: u32,
+    /// GMEM_MOVEABLE blocks of GlobalAlloc/LocalAlloc, keyed by handle.
+    pub(super) moveables: BTreeMap<u32, Moveable>,
 
     pub dlls: HashMap<HMODULE, DLL>,
+    /// DLLs whose DllMain is yet to be called, in load order.
+    pub(super) dlls_to_attach: Vec<HMODULE>,
+    /// DLLs whose DllMain has been called, in the order they were attached.
+    pub(super) attached_dlls: Vec<HMODULE>,
+    /// Implicit TLS of loaded modules, indexed by TLS index.
+    pub(super) tls_modules: Vec<super::ModuleTls>,
 
     pub resources: pe::IMAGE_DATA_DIRECTORY,
     pub resource_handles: Handles<HRSRC, ResourceHandle>,
 
-    pub event_handles: Handles<HEVENT, EventObject>,
-
-    pub files: Handles<HFILE, Box<dyn crate::host::File>>,
-
-    pub find_handles: Handles<HFIND, FindHandle>,
+    /// Files, events, threads, and other kernel objects.
+    pub objects: ObjectTable,
+    pub(super) next_thread_id: u32,
+    /// Our process id; 1 for the initial process, others for CreateProcess children.
+    pub process_id: u32,
+    /// Child processes that are still running.
+    pub(super) child_processes: Vec<ObjectId>,
+    /// Whether blocked child processes should recheck their waits; see
+    /// wake_child_processes().
+    #[serde(skip)]
+    pub(super) wake_children: bool,
+    /// Fibers, keyed by the address of their FIBER.
+    pub(super) fibers: HashMap<u32, Fiber>,
+    /// Mapped views of file mappings, keyed by address.
+    pub(super) views: BTreeMap<u32, View>,
+    /// Top-level exception filter set by SetUnhandledExceptionFilter().
+    pub(super) unhandled_exception_filter: u32,
+    /// Set when the process dies of an exception nothing handled; a dead process is
+    /// never saved.
+    #[serde(skip)]
+    pub crash_report: Option<crate::crash::CrashReport>,
+
+    /// Maps guest paths onto the host filesystem.
+    pub vfs: Vfs,
+    /// Named pipes, shared with child processes.
+    pub pipes: Pipes,
+
+    pub clock: Clock,
+    pub console: Console,
+    /// What GetSystemInfo and friends report.
+    pub system: SystemInfo,
+    /// The ANSI code page, from which the OEM code page follows.
+    pub code_page: CodePage,
 
     pub(super) env: u32,
 
@@ -225,7 +305,9 @@ pub struct State {
 impl State {
     pub fn new(mem: &mut MemImpl, cmdline: String, retrowin32_syscall: &[u8]) -> Self {
         let mut mappings = Mappings::new();
-        let mapping = mappings.alloc(0x1000, "kernel32 data".into(), mem);
+        let mapping = mappings
+            .alloc(0x1000, "kernel32 data".into(), mem)
+            .expect("no room for kernel32 data");
         let mut arena = Arena::new(mapping.addr, mapping.size);
 
         let mut dlls = HashMap::new();
@@ -238,14 +320,18 @@ impl State {
             names.insert("retrowin32_syscall".into(), addr);
             DLL {
                 name: "retrowin32.dll".into(),
+                path: String::new(),
                 dll: pe::DLL {
                     base: 0, // unused
                     names,
                     ordinal_base: 0,         // unused
                     fns: Default::default(), // unused
+                    forwards: Default::default(),
                     resources: None,
                     entry_point: None,
                 },
+                ref_count: 1,
+                thread_calls: false,
             }
         };
         dlls.insert(HMODULE::from_raw(dll.dll.base), dll);
@@ -259,6 +345,8 @@ impl State {
         let cmdline = CommandLine::new(cmdline, &mut arena, mem.mem());
 
         let teb = init_teb(&cmdline, &mut arena, mem.mem());
+        let mut objects = ObjectTable::default();
+        objects.add(KernelObject::Thread(Thread::new(MAIN_THREAD_ID, 0, teb)));
 
         State {
             arena,
@@ -267,10 +355,26 @@ impl State {
             process_heap: 0,
             mappings,
             heaps: HashMap::new(),
+            moveables: BTreeMap::new(),
             dlls,
-            event_handles: Default::default(),
-            files: Default::default(),
-            find_handles: Default::default(),
+            dlls_to_attach: Vec::new(),
+            attached_dlls: Vec::new(),
+            tls_modules: Vec::new(),
+            objects,
+            next_thread_id: MAIN_THREAD_ID + 1,
+            process_id: 1,
+            child_processes: Vec::new(),
+            wake_children: false,
+            fibers: HashMap::new(),
+            views: BTreeMap::new(),
+            unhandled_exception_filter: 0,
+            crash_report: None,
+            vfs: Vfs::default(),
+            pipes: Pipes::default(),
+            clock: Clock::default(),
+            console: Console::default(),
+            system: SystemInfo::default(),
+            code_page: CodePage::Windows1252,
             env: env_addr,
             cmdline,
             resources: Default::default(),
@@ -278,29 +382,62 @@ impl State {
         }
     }
 
+    /// Create a heap for the emulator's own use.  If there's no room for it, the heap is
+    /// empty, and allocations from it fail like those from a full heap.
     pub fn new_private_heap(&mut self, mem: &mut MemImpl, size: usize, desc: String) -> Heap {
-        let mapping = self.mappings.alloc(size as u32, desc, mem);
+        let Some(mapping) = self.mappings.alloc(size as u32, desc.clone(), mem) else {
+            log::warn!("no room for {desc:?}");
+            return Heap::default();
+        };
         Heap::new(mapping.addr, mapping.size)
     }
 
-    pub fn new_heap(&mut self, mem: &mut MemImpl, size: usize, desc: String) -> u32 {
-        let heap = self.new_private_heap(mem, size, desc);
+    /// Create a heap the program can find by its address, or None if there's no room for it.
+    pub fn new_heap(&mut self, mem: &mut MemImpl, size: usize, desc: String) -> Option<u32> {
+        let mapping = self.mappings.alloc(size as u32, desc, mem)?;
+        let heap = Heap::new(mapping.addr, mapping.size);
         let addr = heap.addr;
         self.heaps.insert(addr, heap);
-        addr
+        Some(addr)
     }
 
     pub fn get_heap<'a>(&'a mut self, addr: u32) -> Option<&mut Heap> {
         self.heaps.get_mut(&addr)
     }
 
+    /// Handles of all heaps, in address order.
+    pub fn heap_handles(&self) -> Vec<u32> {
+        let mut handles: Vec<u32> = self.heaps.keys().copied().collect();
+        handles.sort();
+        handles
+    }
+
+    /// Usage summaries of all heaps, for the debugger.
+    pub fn heap_stats(&self, mem: Mem) -> Vec<HeapStats> {
+        self.heap_handles()
+            .into_iter()
+            .map(|addr| self.heaps[&addr].stats(mem))
+            .collect()
+    }
+
+    pub fn remove_heap(&mut self, addr: u32) -> Option<Heap> {
+        self.heaps.remove(&addr)
+    }
+
+    /// The process heap, created on first use.  If there's no room for it, this is an
+    /// empty heap at address 0, which fails every allocation.
     pub fn get_process_heap<'a>(&'a mut self, memory: &mut MemImpl) -> &mut Heap {
         if self.process_heap == 0 {
             let size = 24 << 20;
-            let heap = self.new_heap(memory, size, "process heap".into());
-            self.process_heap = heap;
+            match self.new_heap(memory, size, "process heap".into()) {
+                Some(addr) => {
+                    self.process_heap = addr;
+                    self.heaps.remove(&0);
+                }
+                None => log::warn!("no room for the process heap"),
+            }
         }
-        self.get_heap(self.process_heap).unwrap()
+        self.heaps.entry(self.process_heap).or_default()
     }
 
     pub fn create_gdt(&mut self, mem: Mem) -> GDTEntries {
@@ -383,15 +520,52 @@ impl State {
     }
 }
 
+/// Address of the current thread's TEB.
+pub(super) fn teb_addr(machine: &Machine) -> u32 {
+    #[cfg(feature = "x86-emu")]
+    {
+        // Each thread's FS points at its own TEB, but it's not set up until the exe is loaded.
+        match machine.emu.x86.cpu().regs.fs_addr {
+            0 => machine.state.kernel32.teb,
+            addr => addr,
+        }
+    }
+
+    #[cfg(not(feature = "x86-emu"))]
+    {
+        machine.state.kernel32.teb
+    }
+}
 pub fn teb(machine: &Machine) -> &TEB {
-    machine
-        .mem()
-        .get_aligned_ref::<TEB>(machine.state.kernel32.teb)
+    machine.mem().get_aligned_ref::<TEB>(teb_addr(machine))
 }
 pub fn teb_mut(machine: &mut Machine) -> &mut TEB {
-    machine
-        .mem()
-        .get_aligned_ref_mut::<TEB>(machine.state.kernel32.teb)
+    machine.mem().get_aligned_ref_mut::<TEB>(teb_addr(machine))
+}
+
+/// Allocate a TEB for a new thread, whose stack occupies stack_limit..stack_base,
+/// or return None if there's no room for it.
+#[cfg(feature = "x86-emu")]
+pub fn new_teb(machine: &mut Machine, id: u32, stack_base: u32, stack_limit: u32) -> Option<u32> {
+    let peb_addr = teb(machine).Peb;
+    let size = std::mem::size_of::<TEB>() as u32;
+    let addr = machine
+        .state
+        .kernel32
+        .mappings
+        .alloc(size, format!("thread {id} teb"), &mut machine.emu.memory)?
+        .addr;
+    let mem = machine.mem();
+    mem.sub32_mut(addr, size).fill(0);
+    let teb = mem.get_aligned_ref_mut::<TEB>(addr);
+    teb.Tib.ExceptionList = 0xFFFF_FFFF; // end of SEH chain
+    teb.Tib.StackBase = stack_base;
+    teb.Tib.StackLimit = stack_limit;
+    teb.Tib._Self = addr;
+    teb.Peb = peb_addr;
+    teb.ClientId_UniqueThread = id;
+    super::init_thread_tls(machine, addr);
+    Some(addr)
 }
 pub fn peb_mut(machine: &mut Machine) -> &mut PEB {
     let peb_addr = teb(machine).Peb;
@@ -411,23 +585,32 @@ pub struct PEB {
     pub ProcessParameters: DWORD,
     pub SubSystemData: DWORD,
     pub ProcessHeap: DWORD,
+    pub FastPebLock: DWORD,
+    pub FastPebLockRoutine: DWORD,
+    pub FastPebUnlockRoutine: DWORD,
+    pub EnvironmentUpdateCount: DWORD,
+    pub KernelCallbackTable: DWORD,
+    pub SystemReserved: DWORD,
+    pub AtlThunkSListPtr32: DWORD,
+    pub FreeList: DWORD,
+    pub TlsExpansionCounter: DWORD,
+    pub TlsBitmap: DWORD,
+    /* 0x44 */
+    /// One bit per allocated TLS slot.
+    pub TlsBitmapBits: [DWORD; 2],
     // TODO: more fields
-
-    // This is at the wrong offset, but it shouldn't matter.
-    // TODO: this should be TlsBitmap.
-    pub TlsCount: DWORD,
 }
 unsafe impl ::memory::Pod for PEB {}
 
 #[repr(C)]
 pub struct NT_TIB {
-    ExceptionList: DWORD,
-    StackBase: DWORD,
-    StackLimit: DWORD,
-    SubSystemTib: DWORD,
-    FiberData: DWORD,
-    ArbitraryUserPointer: DWORD,
-    _Self: DWORD,
+    pub ExceptionList: DWORD,
+    pub StackBase: DWORD,
+    pub StackLimit: DWORD,
+    pub SubSystemTib: DWORD,
+    pub FiberData: DWORD,
+    pub ArbitraryUserPointer: DWORD,
+    pub _Self: DWORD,
 }
 unsafe impl ::memory::Pod for NT_TIB {}
 
@@ -468,42 +651,79 @@ pub fn GetCommandLineW(machine: &mut Machine) -> u32 {
 /// This function is not part of the Windows API, but is rather just the entry
 /// point for when retrowin32 starts/stops a process, initializing DLLs and calling main.
 /// It probably has some better name within ntdll.dll.
+///
+/// The x86 emulator instead uses retrowin32_start and retrowin32_exit below, which
+/// don't keep a call of ours pending for as long as the program runs.
 #[win32_derive::dllexport]
 pub async fn retrowin32_main(machine: &mut Machine, entry_point: u32) {
-    struct DllData {
-        base: u32,
-        entry_point: u32,
-    }
-
-    let dlls = machine
-        .state
-        .kernel32
-        .dlls
-        .iter()
-        .filter(|(_, dll)| dll.dll.entry_point.is_some())
-        .map(|(_, dll)| DllData {
-            base: dll.dll.base,
-            entry_point: dll.dll.entry_point.unwrap(),
-        })
-        .collect::<Vec<_>>();
-    // TODO: invoking dllmains can load more dlls.
-    for dll in dlls {
-        let hInstance = dll.base;
-        let fdwReason = 1u32; // DLL_PROCESS_ATTACH
-        let lpvReserved = 0u32;
-        machine
-            .call_x86(dll.entry_point, vec![hInstance, fdwReason, lpvReserved])
-            .await;
-    }
+    super::attach_dlls(machine).await;
+    let exe = machine.state.kernel32.image_base;
+    super::call_tls_callbacks(machine, exe, super::DLL_PROCESS_ATTACH, 0).await;
 
     machine.call_x86(entry_point, vec![]).await;
     // TODO: if the entry point returns, the Windows behavior is to wait for any
     // spawned threads before exiting.
+    super::detach_dlls(machine).await;
     machine.exit(0);
 }
 
+/// Like retrowin32_main, but only the starting half: the stack is set up for this to
+/// return into the entry point, and for the entry point to return into retrowin32_exit.
+/// With no future pending while the program runs, the machine can be saved; see snapshot.rs.
 #[win32_derive::dllexport]
-pub async fn retrowin32_thread_main(machine: &mut Machine, entry_point: u32, param: u32) {
-    machine.call_x86(entry_point, vec![param]).await;
-    machine.emu.x86.cpu_mut().state = x86::CPUState::Free;
+pub async fn retrowin32_start(machine: &mut Machine) {
+    super::attach_dlls(machine).await;
+    let exe = machine.state.kernel32.image_base;
+    super::call_tls_callbacks(machine, exe, super::DLL_PROCESS_ATTACH, 0).await;
+}
+
+/// Where the entry point returns to after a retrowin32_start.
+#[win32_derive::dllexport]
+pub async fn retrowin32_exit(machine: &mut Machine) {
+    // TODO: if the entry point returns, the Windows behavior is to wait for any
+    // spawned threads before exiting.
+    super::detach_dlls(machine).await;
+    machine.exit(0);
+}
+
+/// The retrowin32_start of threads, returning into the thread's start routine,
+/// which returns into retrowin32_thread_exit.
+#[win32_derive::dllexport]
+pub async fn retrowin32_thread_start(machine: &mut Machine) {
+    super::notify_dlls_thread(machine, true).await;
+}
+
+#[win32_derive::dllexport]
+pub async fn retrowin32_thread_exit(machine: &mut Machine) {
+    // The start routine left its exit code in eax.
+    #[cfg(feature = "x86-emu")]
+    let exit_code = machine.emu.x86.cpu().regs.get32(x86::Register::EAX);
+    #[cfg(not(feature = "x86-emu"))]
+    let exit_code = 0;
+    super::notify_dlls_thread(machine, false).await;
+    super::exit_thread(machine, exit_code);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_cmdline;
+
+    #[test]
+    fn test_split_cmdline() {
+        let cases: &[(&str, &[&str])] = &[
+            (r#"a.exe"#, &["a.exe"]),
+            (r#""C:\dir\a b.exe" x  y"#, &[r#"C:\dir\a b.exe"#, "x", "y"]),
+            (r#"a.exe "b c" d"#, &["a.exe", "b c", "d"]),
+            (
+                r#"a.exe a\\b c\\\\"d e" f"#,
+                &["a.exe", r#"a\\b"#, r#"c\\d e"#, "f"],
+            ),
+            (r#"a.exe \"b \\\"c"#, &["a.exe", r#""b"#, r#"\"c"#]),
+            (r#"a.exe "" x"#, &["a.exe", "", "x"]),
+            (r#"a.exe "a""b""#, &["a.exe", r#"a"b"#]),
+        ];
+        for (cmdline, args) in cases {
+            assert_eq!(split_cmdline(cmdline), *args, "{cmdline}");
+        }
+    }
 }
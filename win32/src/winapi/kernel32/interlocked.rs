@@ -0,0 +1,112 @@
+//! The Interlocked* family of atomic operations.
+//!
+//! Guest threads only switch between x86 instructions, and a builtin runs to completion
+//! once called, so these are atomic with respect to the other guest threads as is.
+
+use crate::Machine;
+use memory::{Extensions, ExtensionsMut};
+
+#[win32_derive::dllexport]
+pub fn InterlockedIncrement(_machine: &mut Machine, Addend: Option<&mut i32>) -> i32 {
+    let addend = Addend.unwrap();
+    *addend = addend.wrapping_add(1);
+    *addend
+}
+
+#[win32_derive::dllexport]
+pub fn InterlockedDecrement(_machine: &mut Machine, Addend: Option<&mut i32>) -> i32 {
+    let addend = Addend.unwrap();
+    *addend = addend.wrapping_sub(1);
+    *addend
+}
+
+#[win32_derive::dllexport]
+pub fn InterlockedExchange(_machine: &mut Machine, Target: Option<&mut i32>, Value: i32) -> i32 {
+    std::mem::replace(Target.unwrap(), Value)
+}
+
+#[win32_derive::dllexport]
+pub fn InterlockedExchangeAdd(_machine: &mut Machine, Addend: Option<&mut i32>, Value: i32) -> i32 {
+    let addend = Addend.unwrap();
+    let old = *addend;
+    *addend = old.wrapping_add(Value);
+    old
+}
+
+#[win32_derive::dllexport]
+pub fn InterlockedCompareExchange(
+    _machine: &mut Machine,
+    Destination: Option<&mut i32>,
+    Exchange: i32,
+    Comperand: i32,
+) -> i32 {
+    let dest = Destination.unwrap();
+    let old = *dest;
+    if old == Comperand {
+        *dest = Exchange;
+    }
+    old
+}
+
+/// Head of an interlocked singly-linked list, whose entries each start with a pointer
+/// to the next entry.  The sequence number changes on every update, so that code
+/// manipulating the header directly with cmpxchg8b can detect interference.
+#[repr(C)]
+#[derive(Debug)]
+pub struct SLIST_HEADER {
+    Next: u32,
+    Depth: u16,
+    Sequence: u16,
+}
+unsafe impl ::memory::Pod for SLIST_HEADER {}
+
+impl SLIST_HEADER {
+    fn set(&mut self, next: u32, depth: u16) {
+        self.Next = next;
+        self.Depth = depth;
+        self.Sequence = self.Sequence.wrapping_add(1);
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn InitializeSListHead(_machine: &mut Machine, ListHead: Option<&mut SLIST_HEADER>) {
+    *ListHead.unwrap() = SLIST_HEADER {
+        Next: 0,
+        Depth: 0,
+        Sequence: 0,
+    };
+}
+
+#[win32_derive::dllexport]
+pub fn InterlockedPushEntrySList(machine: &mut Machine, ListHead: u32, ListEntry: u32) -> u32 {
+    let mem = machine.mem();
+    let head = mem.get_aligned_ref_mut::<SLIST_HEADER>(ListHead);
+    let old = head.Next;
+    mem.put_pod::<u32>(ListEntry, old);
+    head.set(ListEntry, head.Depth.wrapping_add(1));
+    old
+}
+
+#[win32_derive::dllexport]
+pub fn InterlockedPopEntrySList(machine: &mut Machine, ListHead: u32) -> u32 {
+    let mem = machine.mem();
+    let head = mem.get_aligned_ref_mut::<SLIST_HEADER>(ListHead);
+    let entry = head.Next;
+    if entry != 0 {
+        head.set(mem.get_pod::<u32>(entry), head.Depth - 1);
+    }
+    entry
+}
+
+#[win32_derive::dllexport]
+pub fn InterlockedFlushSList(_machine: &mut Machine, ListHead: Option<&mut SLIST_HEADER>) -> u32 {
+    let head = ListHead.unwrap();
+    let entry = head.Next;
+    head.set(0, 0);
+    entry
+}
+
+#[win32_derive::dllexport]
+pub fn QueryDepthSList(_machine: &mut Machine, ListHead: Option<&mut SLIST_HEADER>) -> u32 {
+    ListHead.unwrap().Depth as u32
+}
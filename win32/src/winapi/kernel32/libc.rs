@@ -1,14 +1,16 @@
 //! For some reason kernel32 exports functions that I would've expected to find in the libc...
 
+use super::collate;
 use crate::{winapi::types::Str16, Machine};
 use memory::ExtensionsMut;
+use std::ffi::CStr;
 
 #[win32_derive::dllexport]
-pub fn lstrlenA(_machine: &mut Machine, lpString: Option<&str>) -> u32 {
+pub fn lstrlenA(_machine: &mut Machine, lpString: Option<&CStr>) -> u32 {
     match lpString {
         None => 0,
-        // The mapping to str already computes the string length.
-        Some(str) => str.len() as u32,
+        // In bytes, even for double-byte characters.
+        Some(str) => str.to_bytes().len() as u32,
     }
 }
 
@@ -22,11 +24,13 @@ pub fn lstrlenW(_machine: &mut Machine, lpString: Option<&Str16>) -> u32 {
 }
 
 #[win32_derive::dllexport]
-pub fn lstrcpyA(machine: &mut Machine, lpString1: u32, lpString2: Option<&str>) -> u32 {
-    let src = lpString2.unwrap();
-    let dst = machine.mem().sub32_mut(lpString1, (src.len() + 1) as u32);
-    dst[..src.len()].copy_from_slice(src.as_bytes());
-    dst[src.len()] = 0;
+pub fn lstrcpyA(machine: &mut Machine, lpString1: u32, lpString2: Option<&CStr>) -> u32 {
+    let src = lpString2.unwrap().to_bytes_with_nul();
+    // Like Windows, fail rather than fault on a bad destination.
+    let Ok(dst) = machine.mem().try_sub32_mut(lpString1, src.len() as u32) else {
+        return 0;
+    };
+    dst.copy_from_slice(src);
     lpString1
 }
 
@@ -35,30 +39,57 @@ pub fn lstrcpyW(machine: &mut Machine, lpString1: u32, lpString2: Option<&Str16>
     let lpString2 = lpString2.unwrap();
     // lpString1 is a buffer of unspecified size!
     let copy_len = (lpString2.len() + 1) * 2; // include nul
-    let dst = machine.mem().sub32_mut(lpString1, copy_len as u32);
+    let Ok(dst) = machine.mem().try_sub32_mut(lpString1, copy_len as u32) else {
+        return 0;
+    };
     let src =
         unsafe { std::slice::from_raw_parts(lpString2.buf().as_ptr() as *const u8, copy_len) };
     dst.copy_from_slice(src);
     lpString1
 }
 
+/// Compare as the locale collation does, after decoding from the ANSI code page.
+fn lstrcmp_ansi(machine: &Machine, a: Option<&CStr>, b: Option<&CStr>, ignore_case: bool) -> i32 {
+    let cp = machine.state.kernel32.code_page;
+    let decode = |s: Option<&CStr>| cp.decode(s.map_or(&[][..], CStr::to_bytes), false, false);
+    let order = collate(&decode(a).unwrap(), &decode(b).unwrap(), ignore_case);
+    order as i32
+}
+
 #[win32_derive::dllexport]
-pub fn lstrcmpiA(_machine: &mut Machine, lpString1: Option<&str>, lpString2: Option<&str>) -> i32 {
-    let lpString1 = lpString1.unwrap();
-    let lpString2 = lpString2.unwrap();
+pub fn lstrcmpA(machine: &mut Machine, lpString1: Option<&CStr>, lpString2: Option<&CStr>) -> i32 {
+    lstrcmp_ansi(machine, lpString1, lpString2, false)
+}
 
-    for (a, b) in lpString1.bytes().zip(lpString2.bytes()) {
-        // TODO: case insensitive
-        if a < b {
-            return -1;
-        } else if a > b {
-            return 1;
-        }
-    }
+#[win32_derive::dllexport]
+pub fn lstrcmpiA(machine: &mut Machine, lpString1: Option<&CStr>, lpString2: Option<&CStr>) -> i32 {
+    lstrcmp_ansi(machine, lpString1, lpString2, true)
+}
 
-    match lpString1.len().cmp(&lpString2.len()) {
-        std::cmp::Ordering::Less => -1,
-        std::cmp::Ordering::Greater => 1,
-        std::cmp::Ordering::Equal => 0,
-    }
+#[win32_derive::dllexport]
+pub fn lstrcmpW(
+    _machine: &mut Machine,
+    lpString1: Option<&Str16>,
+    lpString2: Option<&Str16>,
+) -> i32 {
+    let order = collate(
+        lpString1.map_or(&[][..], |s| s),
+        lpString2.map_or(&[][..], |s| s),
+        false,
+    );
+    order as i32
+}
+
+#[win32_derive::dllexport]
+pub fn lstrcmpiW(
+    _machine: &mut Machine,
+    lpString1: Option<&Str16>,
+    lpString2: Option<&Str16>,
+) -> i32 {
+    let order = collate(
+        lpString1.map_or(&[][..], |s| s),
+        lpString2.map_or(&[][..], |s| s),
+        true,
+    );
+    order as i32
 }
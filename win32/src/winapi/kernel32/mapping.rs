@@ -0,0 +1,436 @@
+//! File mappings: CreateFileMapping and MapViewOfFile.
+//!
+//! We can't alias guest pages, so each view is a copy of the mapped bytes in its own
+//! Mapping, which is written back to the file (or to the pagefile-backed contents) when
+//! the view is flushed or unmapped.  Views of the same mapping therefore don't see each
+//! other's writes until then.
+
+use super::{
+    set_last_error, sync::create_named_object, vfs, KernelObject, MappingKind, HOBJECT, PAGE,
+};
+use crate::{
+    host,
+    machine::Machine,
+    winapi::{
+        types::{Str16, HFILE},
+        ERROR,
+    },
+    FileOptions,
+};
+use memory::{Extensions, ExtensionsMut, Mem};
+use std::{cell::RefCell, rc::Rc};
+use typed_path::WindowsPath;
+
+/// The contents of a file mapping, shared by the mapping object and its views,
+/// either of which can outlive the other.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Section {
+    /// The mapped file, or None for a pagefile-backed mapping.
+    #[serde(with = "crate::snapshot::optional_file")]
+    file: Option<Box<dyn host::File>>,
+    /// The path and access the file was opened with, to reopen it after loading a
+    /// save state.
+    path: String,
+    writable: bool,
+    /// The contents of a pagefile-backed mapping.
+    data: Vec<u8>,
+    size: u64,
+}
+
+impl Section {
+    fn read(&mut self, offset: u64, buf: &mut [u8]) -> Result<(), ERROR> {
+        let Some(file) = &mut self.file else {
+            let ofs = offset as usize;
+            buf.copy_from_slice(&self.data[ofs..ofs + buf.len()]);
+            return Ok(());
+        };
+        file.seek(std::io::SeekFrom::Start(offset))?;
+        let mut read = 0;
+        while read < buf.len() {
+            match file.read(&mut buf[read..])? {
+                0 => break,
+                n => read += n,
+            }
+        }
+        // Mapped bytes past the end of the file read as zero.
+        buf[read..].fill(0);
+        Ok(())
+    }
+
+    fn write(&mut self, offset: u64, buf: &[u8]) -> Result<(), ERROR> {
+        let Some(file) = &mut self.file else {
+            let ofs = offset as usize;
+            self.data[ofs..ofs + buf.len()].copy_from_slice(buf);
+            return Ok(());
+        };
+        file.seek(std::io::SeekFrom::Start(offset))?;
+        file.write_all(buf)?;
+        Ok(())
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct FileMappingObject {
+    pub name: String,
+    #[serde(with = "crate::snapshot::shared")]
+    section: Rc<RefCell<Section>>,
+    /// Whether views may be mapped for writing.
+    writable: bool,
+}
+
+/// A mapped view of a file mapping.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct View {
+    #[serde(with = "crate::snapshot::shared")]
+    section: Rc<RefCell<Section>>,
+    /// Offset of the view within the mapping.
+    offset: u64,
+    size: u32,
+    /// Whether changes to the view are written back.
+    write: bool,
+}
+
+impl View {
+    /// Write back `len` bytes at `addr` of the view mapped at `base`.
+    fn flush(&self, mem: Mem, base: u32, addr: u32, len: u32) -> Result<(), ERROR> {
+        if !self.write {
+            return Ok(());
+        }
+        let buf = mem.sub32(addr, len);
+        self.section
+            .borrow_mut()
+            .write(self.offset + (addr - base) as u64, buf)
+    }
+}
+
+/// After loading a save state, reopen the files of file-backed mappings.
+pub fn reopen_mapped_files(machine: &mut Machine) -> Result<(), ERROR> {
+    let kernel32 = &machine.state.kernel32;
+    let mappings = kernel32.objects.iter().filter_map(|obj| match obj {
+        KernelObject::FileMapping(mapping) => Some(mapping.section.clone()),
+        _ => None,
+    });
+    let views = kernel32.views.values().map(|view| view.section.clone());
+    // Mappings and their views share sections; reopen each once, in a stable order so
+    // that a replay opens the files in the order they were recorded.
+    let mut sections: Vec<Rc<_>> = Vec::new();
+    for section in mappings.chain(views) {
+        if !sections.iter().any(|s| Rc::ptr_eq(s, &section)) {
+            sections.push(section);
+        }
+    }
+    for section in sections {
+        let mut section = section.borrow_mut();
+        if section.file.is_none() {
+            continue;
+        }
+        let options = FileOptions {
+            read: true,
+            write: section.writable,
+            ..Default::default()
+        };
+        section.file = Some(vfs(machine).open(WindowsPath::new(&section.path), options)?);
+    }
+    Ok(())
+}
+
+impl super::State {
+    /// Find the base address of the view containing `addr`.
+    fn views_base(&self, addr: u32) -> Option<u32> {
+        self.views
+            .iter()
+            .find(|(&base, view)| addr >= base && addr < base + view.size)
+            .map(|(&base, _)| base)
+    }
+}
+
+const PAGE_READWRITE: u32 = 0x04;
+const PAGE_EXECUTE_READWRITE: u32 = 0x40;
+
+const FILE_MAP_COPY: u32 = 0x1;
+const FILE_MAP_WRITE: u32 = 0x2;
+
+/// Views must start at a multiple of the allocation granularity.
+const ALLOCATION_GRANULARITY: u64 = 0x1_0000;
+
+fn create_file_mapping(
+    machine: &mut Machine,
+    hFile: HFILE,
+    protect: u32,
+    max_size: u64,
+    name: Option<String>,
+) -> HOBJECT {
+    // The upper bits are SEC_* flags, which don't affect us.
+    let writable = matches!(protect & 0xFF, PAGE_READWRITE | PAGE_EXECUTE_READWRITE);
+
+    let section = if hFile.is_invalid() {
+        if max_size == 0 {
+            set_last_error(machine, ERROR::INVALID_PARAMETER);
+            return HOBJECT::null();
+        }
+        Section {
+            file: None,
+            path: String::new(),
+            writable: true,
+            data: vec![0; max_size as usize],
+            size: max_size,
+        }
+    } else {
+        let Some(file) = machine.state.kernel32.objects.file(hFile) else {
+            set_last_error(machine, ERROR::INVALID_HANDLE);
+            return HOBJECT::null();
+        };
+        if writable && !file.write {
+            set_last_error(machine, ERROR::ACCESS_DENIED);
+            return HOBJECT::null();
+        }
+        // The mapping keeps the file open even if the application closes its handle,
+        // so it opens the file itself.
+        let path = file.path.clone();
+        let options = FileOptions {
+            read: true,
+            write: writable,
+            ..Default::default()
+        };
+        let file = match vfs(machine).open(WindowsPath::new(&path), options) {
+            Ok(file) => file,
+            Err(err) => {
+                set_last_error(machine, err);
+                return HOBJECT::null();
+            }
+        };
+        let file_size = match file.stat() {
+            Ok(stat) => stat.size,
+            Err(err) => {
+                set_last_error(machine, err);
+                return HOBJECT::null();
+            }
+        };
+        let size = match max_size {
+            0 => file_size,
+            size => size,
+        };
+        if size == 0 {
+            set_last_error(machine, ERROR::FILE_INVALID);
+            return HOBJECT::null();
+        }
+        if size > file_size && writable {
+            // "If [the size] is larger than the size of the file on disk, the file on disk
+            // is increased to match the specified size."
+            if let Err(err) = file.set_len(size) {
+                set_last_error(machine, err);
+                return HOBJECT::null();
+            }
+        }
+        Section {
+            file: Some(file),
+            path,
+            writable,
+            data: Vec::new(),
+            size,
+        }
+    };
+
+    create_named_object(machine, name, |name| {
+        KernelObject::FileMapping(FileMappingObject {
+            name,
+            section: Rc::new(RefCell::new(section)),
+            writable,
+        })
+    })
+}
+
+#[win32_derive::dllexport]
+pub fn CreateFileMappingA(
+    machine: &mut Machine,
+    hFile: HFILE,
+    lpFileMappingAttributes: u32,
+    flProtect: u32,
+    dwMaximumSizeHigh: u32,
+    dwMaximumSizeLow: u32,
+    lpName: Option<&str>,
+) -> HOBJECT {
+    let max_size = (dwMaximumSizeHigh as u64) << 32 | dwMaximumSizeLow as u64;
+    let name = lpName.map(|name| name.to_string());
+    create_file_mapping(machine, hFile, flProtect, max_size, name)
+}
+
+#[win32_derive::dllexport]
+pub fn CreateFileMappingW(
+    machine: &mut Machine,
+    hFile: HFILE,
+    lpFileMappingAttributes: u32,
+    flProtect: u32,
+    dwMaximumSizeHigh: u32,
+    dwMaximumSizeLow: u32,
+    lpName: Option<&Str16>,
+) -> HOBJECT {
+    let max_size = (dwMaximumSizeHigh as u64) << 32 | dwMaximumSizeLow as u64;
+    let name = lpName.map(|name| name.to_string());
+    create_file_mapping(machine, hFile, flProtect, max_size, name)
+}
+
+fn open_file_mapping(machine: &mut Machine, name: Option<String>) -> HOBJECT {
+    let name = name.unwrap_or_default();
+    let objects = &mut machine.state.kernel32.objects;
+    let found = objects.find(|obj| {
+        matches!(obj, KernelObject::FileMapping(_)) && obj.name() == Some(name.as_str())
+    });
+    match found {
+        Some(id) => {
+            let handle = objects.new_handle(id);
+            set_last_error(machine, ERROR::SUCCESS);
+            handle
+        }
+        None => {
+            set_last_error(machine, ERROR::FILE_NOT_FOUND);
+            HOBJECT::null()
+        }
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn OpenFileMappingA(
+    machine: &mut Machine,
+    dwDesiredAccess: u32,
+    bInheritHandle: bool,
+    lpName: Option<&str>,
+) -> HOBJECT {
+    open_file_mapping(machine, lpName.map(|name| name.to_string()))
+}
+
+#[win32_derive::dllexport]
+pub fn OpenFileMappingW(
+    machine: &mut Machine,
+    dwDesiredAccess: u32,
+    bInheritHandle: bool,
+    lpName: Option<&Str16>,
+) -> HOBJECT {
+    open_file_mapping(machine, lpName.map(|name| name.to_string()))
+}
+
+#[win32_derive::dllexport]
+pub fn MapViewOfFile(
+    machine: &mut Machine,
+    hFileMappingObject: HOBJECT,
+    dwDesiredAccess: u32,
+    dwFileOffsetHigh: u32,
+    dwFileOffsetLow: u32,
+    dwNumberOfBytesToMap: u32,
+) -> u32 {
+    let Some(KernelObject::FileMapping(mapping)) =
+        machine.state.kernel32.objects.get(hFileMappingObject)
+    else {
+        set_last_error(machine, ERROR::INVALID_HANDLE);
+        return 0;
+    };
+    // Copy-on-write views are writable, but never written back.
+    let write = dwDesiredAccess & FILE_MAP_WRITE != 0 && dwDesiredAccess & FILE_MAP_COPY == 0;
+    if write && !mapping.writable {
+        set_last_error(machine, ERROR::ACCESS_DENIED);
+        return 0;
+    }
+
+    let offset = (dwFileOffsetHigh as u64) << 32 | dwFileOffsetLow as u64;
+    if offset % ALLOCATION_GRANULARITY != 0 {
+        set_last_error(machine, ERROR::MAPPED_ALIGNMENT);
+        return 0;
+    }
+    let section_size = mapping.section.borrow().size;
+    let size = match dwNumberOfBytesToMap {
+        0 => section_size.saturating_sub(offset),
+        size => size as u64,
+    };
+    if size == 0 || offset + size > section_size || size > u32::MAX as u64 {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return 0;
+    }
+    let size = size as u32;
+
+    let section = mapping.section.clone();
+    let desc = match mapping.name.as_str() {
+        "" => format!("view {hFileMappingObject:x}"),
+        name => format!("view {name:?}"),
+    };
+    let Some(view) = machine
+        .state
+        .kernel32
+        .mappings
+        .alloc(size, desc, &mut machine.emu.memory)
+    else {
+        set_last_error(machine, ERROR::NOT_ENOUGH_MEMORY);
+        return 0;
+    };
+    let addr = view.addr;
+    let view_mapping = machine.state.kernel32.mappings.find_mut(addr).unwrap();
+    view_mapping.kind = MappingKind::Mapped;
+    view_mapping.alloc_protect = match (write, dwDesiredAccess & FILE_MAP_COPY != 0) {
+        (_, true) => PAGE::WRITECOPY,
+        (true, _) => PAGE::READWRITE,
+        _ => PAGE::READONLY,
+    };
+    let buf = machine.emu.memory.mem().sub32_mut(addr, size);
+    if let Err(err) = section.borrow_mut().read(offset, buf) {
+        machine.state.kernel32.mappings.remove(addr);
+        set_last_error(machine, err);
+        return 0;
+    }
+    machine.state.kernel32.views.insert(
+        addr,
+        View {
+            section,
+            offset,
+            size,
+            write,
+        },
+    );
+    set_last_error(machine, ERROR::SUCCESS);
+    addr
+}
+
+#[win32_derive::dllexport]
+pub fn UnmapViewOfFile(machine: &mut Machine, lpBaseAddress: u32) -> bool {
+    let Some(base) = machine.state.kernel32.views_base(lpBaseAddress) else {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return false;
+    };
+    let view = machine.state.kernel32.views.remove(&base).unwrap();
+    if let Err(err) = view.flush(machine.mem(), base, base, view.size) {
+        log::warn!("UnmapViewOfFile({lpBaseAddress:x}): failed to write back: {err:?}");
+    }
+    machine.state.kernel32.mappings.remove(base);
+    true
+}
+
+#[win32_derive::dllexport]
+pub fn FlushViewOfFile(
+    machine: &mut Machine,
+    lpBaseAddress: u32,
+    dwNumberOfBytesToFlush: u32,
+) -> bool {
+    let Some(base) = machine.state.kernel32.views_base(lpBaseAddress) else {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return false;
+    };
+    let view = machine.state.kernel32.views.remove(&base).unwrap();
+    // Zero means flush to the end of the view.
+    let end = base + view.size;
+    let len = match dwNumberOfBytesToFlush {
+        0 => end - lpBaseAddress,
+        len => len.min(end - lpBaseAddress),
+    };
+    let result = view
+        .flush(machine.mem(), base, lpBaseAddress, len)
+        .and_then(|()| match &mut view.section.borrow_mut().file {
+            Some(file) => Ok(file.flush()?),
+            None => Ok(()),
+        });
+    machine.state.kernel32.views.insert(base, view);
+    match result {
+        Ok(()) => true,
+        Err(err) => {
+            set_last_error(machine, err);
+            false
+        }
+    }
+}
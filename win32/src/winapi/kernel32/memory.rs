@@ -1,7 +1,12 @@
 use crate::{
     machine::{Machine, MemImpl},
     pe::ImageSectionFlags,
-    winapi::stack_args,
+    winapi::{
+        self, heap,
+        kernel32::{raise_from_builtin, set_last_error, HPROCESS, STATUS_NO_MEMORY},
+        stack_args,
+        types::HANDLE,
+    },
 };
 use bitflags::bitflags;
 use memory::{Extensions, ExtensionsMut, Mem};
@@ -12,6 +17,8 @@ pub fn round_up_to_page_granularity(size: u32) -> u32 {
 }
 
 /// Memory span as managed by the kernel.  Some come from the exe and others are allocated dynamically.
+/// The Serialize impl is the debugger's view of a mapping; save states go through
+/// Mappings, which saves all of it.
 #[derive(Debug, serde::Serialize)]
 #[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
 pub struct Mapping {
@@ -19,6 +26,27 @@ pub struct Mapping {
     pub size: u32,
     pub desc: String,
     pub flags: ImageSectionFlags,
+    /// What backs the memory, as reported by VirtualQuery.
+    #[serde(skip)]
+    pub kind: MappingKind,
+    /// The protection the mapping was created with.
+    #[serde(skip)]
+    pub alloc_protect: PAGE,
+    /// Protection of each page, filled in as fully accessible when the mapping is added.
+    /// Pages that are reserved but not committed have an empty protection.
+    #[serde(skip)]
+    pub protect: Vec<PAGE>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum MappingKind {
+    /// Memory private to the process: VirtualAlloc, heaps, stacks.
+    #[default]
+    Private,
+    /// Part of a loaded module whose header is at `base`.
+    Image { base: u32 },
+    /// A view of a file mapping.
+    Mapped,
 }
 
 impl Mapping {
@@ -29,20 +57,80 @@ impl Mapping {
 
 /// The set of Mappings managed by the kernel.
 /// These get visualized in the debugger when you hover a pointer.
-#[derive(serde::Serialize, Debug)]
+#[derive(Debug)]
 pub struct Mappings(Vec<Mapping>);
+
+type SavedMapping = (
+    u32,
+    u32,
+    String,
+    ImageSectionFlags,
+    MappingKind,
+    PAGE,
+    Vec<PAGE>,
+);
+
+impl serde::Serialize for Mappings {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_seq(self.0.iter().map(|m| {
+            let Mapping {
+                addr,
+                size,
+                desc,
+                flags,
+                kind,
+                alloc_protect,
+                protect,
+            } = m;
+            (addr, size, desc, flags, kind, alloc_protect, protect)
+        }))
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Mappings {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let saved = <Vec<SavedMapping> as serde::Deserialize>::deserialize(d)?;
+        Ok(Mappings(
+            saved
+                .into_iter()
+                .map(
+                    |(addr, size, desc, flags, kind, alloc_protect, protect)| Mapping {
+                        addr,
+                        size,
+                        desc,
+                        flags,
+                        kind,
+                        alloc_protect,
+                        protect,
+                    },
+                )
+                .collect(),
+        ))
+    }
+}
 impl Mappings {
     pub fn new() -> Self {
-        Mappings(vec![Mapping {
+        let mut mappings = Mappings(Vec::new());
+        mappings.add(Mapping {
             addr: 0,
             size: 0x1000,
             desc: "avoid null pointers".into(),
             flags: ImageSectionFlags::empty(),
-        }])
+            kind: MappingKind::Private,
+            alloc_protect: PAGE::empty(),
+            protect: vec![PAGE::NOACCESS],
+        });
+        mappings
     }
 
     pub fn add(&mut self, mut mapping: Mapping) -> &Mapping {
         mapping.size = round_up_to_page_granularity(mapping.size);
+        // Windows would protect image sections according to their characteristics, but
+        // plenty of old programs write to their own code without asking, so everything
+        // starts out writable until the program asks otherwise.
+        mapping
+            .protect
+            .resize((mapping.size >> 12) as usize, PAGE::EXECUTE_READWRITE);
         let pos = self
             .0
             .iter()
@@ -76,57 +164,84 @@ impl Mappings {
         prev_end
     }
 
-    pub fn alloc(&mut self, size: u32, desc: String, mem: &mut MemImpl) -> &Mapping {
-        let size = round_up_to_page_granularity(size);
-        if size > 32 << 20 {
-            panic!("new mapping {:?} too large: {size:x} bytes", desc);
-        }
+    /// Create a mapping of at least size bytes wherever there's room, or return None if
+    /// there's no room left in the memory reserved for the guest.
+    pub fn alloc(&mut self, size: u32, desc: String, mem: &mut MemImpl) -> Option<&Mapping> {
+        let size = size.checked_next_multiple_of(0x1000)?;
         let addr = self.find_space(size);
-        if addr + size > mem.len() {
-            panic!(
-                "not enough memory reserved, need at least {}mb",
-                (addr + size) >> 20
-            );
+        if addr.checked_add(size).map_or(true, |end| end > mem.len()) {
+            log::warn!("no room for mapping {desc:?} of {size:x} bytes");
+            return None;
         }
-        self.add(Mapping {
+        Some(self.add(Mapping {
             addr,
             size,
             desc,
             flags: ImageSectionFlags::empty(),
-        })
+            kind: MappingKind::Private,
+            alloc_protect: PAGE::EXECUTE_READWRITE,
+            protect: Vec::new(),
+        }))
+    }
+
+    /// Remove the mapping starting at `addr`.
+    pub fn remove(&mut self, addr: u32) -> Option<Mapping> {
+        let pos = self.0.iter().position(|m| m.addr == addr)?;
+        Some(self.0.remove(pos))
     }
 
     pub fn vec(&self) -> &Vec<Mapping> {
         &self.0
     }
 
-    pub fn grow(&mut self, addr: u32, min_growth: u32) -> u32 {
-        let pos = self.0.iter().position(|m| m.addr == addr).unwrap();
-        let mapping = &self.0[pos];
-        let mut new_size = mapping.size;
-        while new_size - mapping.size < min_growth {
-            new_size *= 2;
-        }
+    /// Find the mapping containing addr.
+    pub fn find_mut(&mut self, addr: u32) -> Option<&mut Mapping> {
+        self.0.iter_mut().find(|m| m.contains(addr))
+    }
 
-        // Check if we run into a mapping after this one.
-        if pos + 1 < self.0.len() {
-            let next = &self.0[pos + 1];
-            if mapping.addr + new_size > next.addr {
-                panic!("cannot grow {:?}", mapping);
+    /// Describe the run of pages starting at the page containing addr that share the
+    /// same state, as reported by VirtualQuery.  Addresses outside any mapping are
+    /// reported as free up to the next mapping or `mem_len`.
+    pub fn query(&self, addr: u32, mem_len: u32) -> MEMORY_BASIC_INFORMATION {
+        let base = addr & !0xFFF;
+        let next = self.0.iter().find(|m| m.addr + m.size > base);
+        match next {
+            Some(mapping) if mapping.addr <= base => {
+                let first = ((base - mapping.addr) >> 12) as usize;
+                let protect = mapping.protect[first];
+                let pages = mapping.protect[first..]
+                    .iter()
+                    .take_while(|&&p| p == protect)
+                    .count() as u32;
+                let (alloc_base, alloc_protect, type_) = match mapping.kind {
+                    MappingKind::Private => (mapping.addr, mapping.alloc_protect, MEM_PRIVATE),
+                    MappingKind::Image { base } => (base, PAGE::EXECUTE_WRITECOPY, MEM_IMAGE),
+                    MappingKind::Mapped => (mapping.addr, mapping.alloc_protect, MEM_MAPPED),
+                };
+                MEMORY_BASIC_INFORMATION {
+                    BaseAddress: base,
+                    AllocationBase: alloc_base,
+                    AllocationProtect: alloc_protect.bits(),
+                    RegionSize: pages << 12,
+                    State: if protect.is_empty() {
+                        MEM::RESERVE.bits()
+                    } else {
+                        MEM::COMMIT.bits()
+                    },
+                    Protect: protect.bits(),
+                    Type: type_,
+                }
             }
+            _ => MEMORY_BASIC_INFORMATION {
+                BaseAddress: base,
+                AllocationBase: 0,
+                AllocationProtect: 0,
+                RegionSize: next.map_or(mem_len, |m| m.addr) - base,
+                State: MEM_FREE,
+                Protect: PAGE::NOACCESS.bits(),
+                Type: 0,
+            },
         }
-
-        let mapping = &mut self.0[pos];
-        let growth = new_size - mapping.size;
-        mapping.size = new_size;
-        log::info!(
-            "grew mapping {:?} by {:#x}, new size {:#x}",
-            mapping.desc,
-            growth,
-            new_size
-        );
-        log::warn!("might need to grow backing memory after growth");
-        growth
     }
 
     pub fn dump(&self) {
@@ -157,6 +272,7 @@ bitflags! {
         const HEAP_GENERATE_EXCEPTIONS = 0x4;
         const HEAP_NO_SERIALIZE = 0x1;
         const HEAP_ZERO_MEMORY = 0x8;
+        const HEAP_REALLOC_IN_PLACE_ONLY = 0x10;
     }
 }
 impl TryFrom<u32> for HeapAllocFlags {
@@ -167,6 +283,31 @@ impl TryFrom<u32> for HeapAllocFlags {
     }
 }
 
+/// The guest return address of the current builtin call, used to attribute heap blocks.
+#[cfg(feature = "x86-emu")]
+pub fn guest_caller(machine: &Machine) -> u32 {
+    // See doc/shims.md for the state of the stack within a shim.
+    let esp = machine.emu.x86.cpu().regs.get32(x86::Register::ESP);
+    machine.mem().get_pod::<u32>(esp + 4)
+}
+
+#[cfg(not(feature = "x86-emu"))]
+pub fn guest_caller(_machine: &Machine) -> u32 {
+    0
+}
+
+/// Report a failed HeapAlloc/HeapReAlloc, either by the last error or, for heaps or calls
+/// asking for it with HEAP_GENERATE_EXCEPTIONS, by raising STATUS_NO_MEMORY in the caller.
+/// args_size is the size of the failed call's arguments.
+fn heap_alloc_failed(machine: &mut Machine, generate_exceptions: bool, args_size: u32, size: u32) {
+    if !generate_exceptions {
+        set_last_error(machine, winapi::ERROR::NOT_ENOUGH_MEMORY);
+        return;
+    }
+    // Windows passes the requested size along with the exception.
+    raise_from_builtin(machine, STATUS_NO_MEMORY, args_size, &[size]);
+}
+
 #[win32_derive::dllexport]
 pub fn HeapAlloc(
     machine: &mut Machine,
@@ -178,8 +319,8 @@ pub fn HeapAlloc(
         log::warn!("HeapAlloc invalid flags {dwFlags:x?}");
         HeapAllocFlags::empty()
     });
-    flags.remove(HeapAllocFlags::HEAP_GENERATE_EXCEPTIONS); // todo: OOM
     flags.remove(HeapAllocFlags::HEAP_NO_SERIALIZE); // todo: threads
+    let caller = guest_caller(machine);
     let heap = match machine.state.kernel32.get_heap(hHeap) {
         None => {
             log::error!("HeapAlloc({hHeap:x}): no such heap");
@@ -187,10 +328,17 @@ pub fn HeapAlloc(
         }
         Some(heap) => heap,
     };
-    let addr = heap.alloc(machine.emu.memory.mem(), dwBytes);
+    let generate_exceptions =
+        heap.generate_exceptions || flags.contains(HeapAllocFlags::HEAP_GENERATE_EXCEPTIONS);
+    flags.remove(HeapAllocFlags::HEAP_GENERATE_EXCEPTIONS);
+    let mem = machine.emu.memory.mem();
+    let addr = heap.alloc(mem, dwBytes);
     if addr == 0 {
         log::warn!("HeapAlloc({hHeap:x}) failed");
+        heap_alloc_failed(machine, generate_exceptions, 3 * 4, dwBytes);
+        return 0;
     }
+    heap.set_caller(mem, addr, caller);
     if flags.contains(HeapAllocFlags::HEAP_ZERO_MEMORY) {
         machine.mem().sub32_mut(addr, dwBytes).fill(0);
         flags.remove(HeapAllocFlags::HEAP_ZERO_MEMORY);
@@ -245,26 +393,44 @@ pub fn HeapSetInformation(
 pub fn HeapReAlloc(
     machine: &mut Machine,
     hHeap: u32,
-    dwFlags: u32,
+    dwFlags: Result<HeapAllocFlags, u32>,
     lpMem: u32,
     dwBytes: u32,
 ) -> u32 {
-    if dwFlags != 0 {
-        log::warn!("HeapReAlloc flags: {:x}", dwFlags);
-    }
+    let mut flags = dwFlags.unwrap_or_else(|_| {
+        log::warn!("HeapReAlloc invalid flags {dwFlags:x?}");
+        HeapAllocFlags::empty()
+    });
+    flags.remove(HeapAllocFlags::HEAP_NO_SERIALIZE); // todo: threads
     let heap = match machine.state.kernel32.get_heap(hHeap) {
         None => {
-            log::error!("HeapSize({hHeap:x}): no such heap");
+            log::error!("HeapReAlloc({hHeap:x}): no such heap");
             return 0;
         }
         Some(heap) => heap,
     };
-    let old_size = heap.size(machine.emu.memory.mem(), lpMem);
-    let new_addr = heap.alloc(machine.emu.memory.mem(), dwBytes);
-    assert!(dwBytes >= old_size);
-    heap.free(machine.emu.memory.mem(), lpMem);
-    machine.mem().copy(lpMem, new_addr, old_size);
-    new_addr
+    let generate_exceptions =
+        heap.generate_exceptions || flags.contains(HeapAllocFlags::HEAP_GENERATE_EXCEPTIONS);
+    flags.remove(HeapAllocFlags::HEAP_GENERATE_EXCEPTIONS);
+    let mem = machine.emu.memory.mem();
+    let old_size = heap.size(mem, lpMem);
+    let addr = if flags.contains(HeapAllocFlags::HEAP_REALLOC_IN_PLACE_ONLY) {
+        match heap.resize_in_place(mem, lpMem, dwBytes) {
+            true => lpMem,
+            false => 0,
+        }
+    } else {
+        heap.realloc(mem, lpMem, dwBytes)
+    };
+    if addr == 0 {
+        log::warn!("HeapReAlloc({hHeap:x}) failed");
+        heap_alloc_failed(machine, generate_exceptions, 4 * 4, dwBytes);
+        return 0;
+    }
+    if flags.contains(HeapAllocFlags::HEAP_ZERO_MEMORY) && dwBytes > old_size {
+        mem.sub32_mut(addr + old_size, dwBytes - old_size).fill(0);
+    }
+    addr
 }
 
 bitflags! {
@@ -289,31 +455,146 @@ pub fn HeapCreate(
     dwInitialSize: u32,
     dwMaximumSize: u32,
 ) -> u32 {
-    flOptions.unwrap();
-    // Currently none of the flags will affect behavior, but we might need to revisit this
-    // with exceptions or threads support...
+    let options = flOptions.unwrap_or_else(HeapCreateFlags::from_bits_truncate);
     let size = max(dwInitialSize as usize, 20 << 20);
-    machine
-        .state
-        .kernel32
-        .new_heap(&mut machine.emu.memory, size, "HeapCreate".into())
+    let Some(addr) =
+        machine
+            .state
+            .kernel32
+            .new_heap(&mut machine.emu.memory, size, "HeapCreate".into())
+    else {
+        set_last_error(machine, winapi::ERROR::NOT_ENOUGH_MEMORY);
+        return 0;
+    };
+    let heap = machine.state.kernel32.get_heap(addr).unwrap();
+    heap.generate_exceptions = options.contains(HeapCreateFlags::HEAP_GENERATE_EXCEPTIONS);
+    addr
 }
 
 #[win32_derive::dllexport]
-pub fn HeapDestroy(_machine: &mut Machine, hHeap: u32) -> u32 {
-    log::warn!("HeapDestroy({hHeap:x})");
-    1 // success
+pub fn HeapDestroy(machine: &mut Machine, hHeap: u32) -> bool {
+    let kernel32 = &mut machine.state.kernel32;
+    if hHeap == kernel32.process_heap || kernel32.remove_heap(hHeap).is_none() {
+        set_last_error(machine, winapi::ERROR::INVALID_HANDLE);
+        return false;
+    }
+    release_mapping(machine, hHeap);
+    true
 }
 
 #[win32_derive::dllexport]
-pub fn HeapValidate(_machine: &mut Machine, hHeap: u32, dwFlags: u32, lpMem: u32) -> bool {
-    todo!();
+pub fn HeapValidate(machine: &mut Machine, hHeap: u32, dwFlags: u32, lpMem: u32) -> bool {
+    let mem = machine.emu.memory.mem();
+    let Some(heap) = machine.state.kernel32.get_heap(hHeap) else {
+        return false;
+    };
+    let ok = heap.validate(mem, if lpMem == 0 { None } else { Some(lpMem) });
+    if !ok {
+        log::warn!("HeapValidate({hHeap:x}, {lpMem:x}): heap is corrupt");
+    }
+    ok
+}
+
+const PROCESS_HEAP_REGION: u16 = 0x1;
+const PROCESS_HEAP_ENTRY_BUSY: u16 = 0x4;
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct PROCESS_HEAP_ENTRY {
+    pub lpData: u32,
+    pub cbData: u32,
+    pub cbOverhead: u8,
+    pub iRegionIndex: u8,
+    pub wFlags: u16,
+    // The rest is a union of Block and Region; only Region fields are filled in.
+    pub dwCommittedSize: u32,
+    pub dwUnCommittedSize: u32,
+    pub lpFirstBlock: u32,
+    pub lpLastBlock: u32,
+}
+unsafe impl memory::Pod for PROCESS_HEAP_ENTRY {}
+
+#[win32_derive::dllexport]
+pub fn HeapWalk(
+    machine: &mut Machine,
+    hHeap: u32,
+    lpEntry: Option<&mut PROCESS_HEAP_ENTRY>,
+) -> bool {
+    let mem = machine.emu.memory.mem();
+    let (Some(heap), Some(entry)) = (machine.state.kernel32.get_heap(hHeap), lpEntry) else {
+        set_last_error(machine, winapi::ERROR::INVALID_PARAMETER);
+        return false;
+    };
+    // The walk starts with an entry describing the whole heap, then visits each block.
+    if entry.lpData == 0 {
+        *entry = PROCESS_HEAP_ENTRY {
+            lpData: heap.addr,
+            cbData: heap.size,
+            cbOverhead: 0,
+            iRegionIndex: 0,
+            wFlags: PROCESS_HEAP_REGION,
+            dwCommittedSize: heap.size,
+            dwUnCommittedSize: 0,
+            lpFirstBlock: heap.addr,
+            lpLastBlock: heap.addr + heap.size,
+        };
+        return true;
+    }
+    let prev = if entry.wFlags & PROCESS_HEAP_REGION != 0 {
+        None
+    } else {
+        Some(entry.lpData)
+    };
+    let block = match heap.next_block(mem, prev) {
+        Some(Ok(block)) => block,
+        Some(Err(addr)) => {
+            log::warn!("HeapWalk({hHeap:x}): corrupt block at {addr:x}");
+            set_last_error(machine, winapi::ERROR::INVALID_PARAMETER);
+            return false;
+        }
+        None => {
+            set_last_error(machine, winapi::ERROR::NO_MORE_ITEMS);
+            return false;
+        }
+    };
+    *entry = PROCESS_HEAP_ENTRY {
+        lpData: block.addr,
+        cbData: block.size,
+        cbOverhead: heap::HEADER_SIZE as u8,
+        iRegionIndex: 0,
+        wFlags: if block.busy {
+            PROCESS_HEAP_ENTRY_BUSY
+        } else {
+            0
+        },
+        dwCommittedSize: 0,
+        dwUnCommittedSize: 0,
+        lpFirstBlock: 0,
+        lpLastBlock: 0,
+    };
+    true
+}
+
+#[win32_derive::dllexport]
+pub fn GetProcessHeaps(machine: &mut Machine, NumberOfHeaps: u32, ProcessHeaps: u32) -> u32 {
+    machine
+        .state
+        .kernel32
+        .get_process_heap(&mut machine.emu.memory); // lazy init process_heap
+    let handles = machine.state.kernel32.heap_handles();
+    let mem = machine.mem();
+    for (i, &handle) in handles.iter().take(NumberOfHeaps as usize).enumerate() {
+        mem.put_pod::<u32>(ProcessHeaps + (i as u32 * 4), handle);
+    }
+    handles.len() as u32
 }
 
 bitflags! {
     pub struct MEM: u32 {
         const COMMIT = 0x00001000;
         const RESERVE = 0x00002000;
+        const DECOMMIT = 0x00004000;
+        const RELEASE = 0x00008000;
         const RESET = 0x00080000;
         const RESET_UNDO = 0x1000000;
         const LARGE_PAGES = 0x20000000;
@@ -330,7 +611,14 @@ impl TryFrom<u32> for MEM {
     }
 }
 
+// MEMORY_BASIC_INFORMATION State and Type values not covered by MEM.
+pub const MEM_FREE: u32 = 0x10000;
+pub const MEM_PRIVATE: u32 = 0x20000;
+pub const MEM_MAPPED: u32 = 0x40000;
+pub const MEM_IMAGE: u32 = 0x1000000;
+
 bitflags! {
+    #[derive(serde::Serialize, serde::Deserialize)]
     pub struct PAGE: u32 {
         const EXECUTE = 0x10;
         const EXECUTE_READ = 0x20;
@@ -355,50 +643,115 @@ impl TryFrom<u32> for PAGE {
     }
 }
 
+impl PAGE {
+    pub fn is_writable(self) -> bool {
+        self.intersects(
+            PAGE::READWRITE | PAGE::WRITECOPY | PAGE::EXECUTE_READWRITE | PAGE::EXECUTE_WRITECOPY,
+        )
+    }
+
+    /// The emulated CPU's trap bits for pages with this protection.
+    #[cfg(feature = "x86-emu")]
+    fn traps(self) -> u8 {
+        let mut traps = 0;
+        if self.contains(PAGE::NOACCESS) {
+            traps |= memory::PAGE_TRAP_READ;
+        }
+        if !self.is_writable() {
+            traps |= memory::PAGE_TRAP_WRITE;
+        }
+        if self.contains(PAGE::GUARD) {
+            traps |= memory::PAGE_TRAP_GUARD;
+        }
+        traps
+    }
+}
+
+/// Change the protection of the pages covering [addr, addr+size), which must all lie
+/// within one mapping.  Returns the previous protection of the first page, or None if
+/// the range isn't mapped.
+pub fn protect_pages(machine: &mut Machine, addr: u32, size: u32, protect: PAGE) -> Option<PAGE> {
+    let start = addr & !0xFFF;
+    let end = round_up_to_page_granularity(addr + size.max(1));
+    let mapping = machine.state.kernel32.mappings.find_mut(start)?;
+    if end > mapping.addr + mapping.size {
+        return None;
+    }
+    let first = ((start - mapping.addr) >> 12) as usize;
+    let last = ((end - mapping.addr) >> 12) as usize;
+    let old = mapping.protect[first];
+    mapping.protect[first..last].fill(protect);
+    #[cfg(feature = "x86-emu")]
+    machine
+        .emu
+        .memory
+        .set_page_traps(start, end - start, protect.traps());
+    Some(old)
+}
+
 #[win32_derive::dllexport]
 pub fn VirtualAlloc(
     machine: &mut Machine,
     lpAddress: u32,
     dwSize: u32,
     flAllocationType: Result<MEM, u32>,
-    flProtec: Result<PAGE, u32>,
+    flProtect: Result<PAGE, u32>,
 ) -> u32 {
+    let protect = match flProtect {
+        Ok(protect) => protect,
+        Err(bits) => {
+            log::error!("VirtualAlloc: invalid protection {bits:x}");
+            set_last_error(machine, winapi::ERROR::INVALID_PARAMETER);
+            return 0;
+        }
+    };
+
     if lpAddress != 0 {
-        // Changing flags on an existing address, hopefully.
-        match machine
-            .state
-            .kernel32
-            .mappings
-            .vec()
-            .iter()
-            .find(|&mapping| mapping.contains(lpAddress))
-        {
-            None => {
-                log::error!("failing VirtualAlloc({lpAddress:x}, ...) refers to unknown mapping");
-                return 0;
-            }
-            Some(_) => {
-                // adjusting flags on existing mapping, ignore.
-                return lpAddress;
-            }
+        // Committing or changing the protection of part of an existing mapping, hopefully.
+        if protect_pages(machine, lpAddress, dwSize, protect).is_none() {
+            log::error!("failing VirtualAlloc({lpAddress:x}, ...) refers to unknown mapping");
+            set_last_error(machine, winapi::ERROR::INVALID_ADDRESS);
+            return 0;
         }
+        return lpAddress & !0xFFF;
     }
-    // TODO round dwSize to page boundary
 
-    let mapping = machine.state.kernel32.mappings.alloc(
+    let Some(mapping) = machine.state.kernel32.mappings.alloc(
         dwSize,
         "VirtualAlloc".into(),
         &mut machine.emu.memory,
+    ) else {
+        set_last_error(machine, winapi::ERROR::NOT_ENOUGH_MEMORY);
+        return 0;
+    };
+    let addr = mapping.addr;
+    machine
+        .state
+        .kernel32
+        .mappings
+        .find_mut(addr)
+        .unwrap()
+        .alloc_protect = protect;
+    // Reserving without committing leaves the pages inaccessible until a later commit.
+    let commit = match flAllocationType {
+        Ok(ty) => ty.contains(MEM::COMMIT),
+        Err(_) => true,
+    };
+    protect_pages(
+        machine,
+        addr,
+        dwSize,
+        if commit { protect } else { PAGE::empty() },
     );
-    mapping.addr
+    addr
 }
 
+#[repr(C)]
 #[derive(Debug)]
 pub struct MEMORY_BASIC_INFORMATION {
     pub BaseAddress: u32,
     pub AllocationBase: u32,
     pub AllocationProtect: u32,
-    pub PartitionId: u16,
     pub RegionSize: u32,
     pub State: u32,
     pub Protect: u32,
@@ -408,32 +761,106 @@ unsafe impl memory::Pod for MEMORY_BASIC_INFORMATION {}
 
 #[win32_derive::dllexport]
 pub fn VirtualQuery(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     lpAddress: u32,
     lpBuffer: Option<&mut MEMORY_BASIC_INFORMATION>,
     dwLength: u32,
 ) -> u32 {
-    0 // fail
+    const SIZE: u32 = std::mem::size_of::<MEMORY_BASIC_INFORMATION>() as u32;
+    if dwLength < SIZE {
+        set_last_error(machine, winapi::ERROR::BAD_LENGTH);
+        return 0;
+    }
+    let mem_len = machine.emu.memory.len();
+    let Some(info) = lpBuffer.filter(|_| lpAddress < mem_len) else {
+        set_last_error(machine, winapi::ERROR::INVALID_PARAMETER);
+        return 0;
+    };
+    *info = machine.state.kernel32.mappings.query(lpAddress, mem_len);
+    SIZE
 }
 
 #[win32_derive::dllexport]
-pub fn VirtualFree(_machine: &mut Machine, lpAddress: u32, dwSize: u32, dwFreeType: u32) -> u32 {
-    1 // success
+pub fn VirtualQueryEx(
+    machine: &mut Machine,
+    hProcess: HANDLE<()>,
+    lpAddress: u32,
+    lpBuffer: Option<&mut MEMORY_BASIC_INFORMATION>,
+    dwLength: u32,
+) -> u32 {
+    VirtualQuery(machine, lpAddress, lpBuffer, dwLength)
+}
+
+/// Remove the mapping starting at addr, leaving its pages zeroed and unprotected for
+/// whatever reuses the address space next.
+pub(super) fn release_mapping(machine: &mut Machine, addr: u32) {
+    let Mapping { addr, size, .. } = machine.state.kernel32.mappings.remove(addr).unwrap();
+    machine.mem().sub32_mut(addr, size).fill(0);
+    #[cfg(feature = "x86-emu")]
+    machine.emu.memory.set_page_traps(addr, size, 0);
 }
 
 #[win32_derive::dllexport]
-pub fn IsBadReadPtr(_machine: &mut Machine, lp: u32, ucb: u32) -> bool {
-    false // all pointers are valid
+pub fn VirtualFree(
+    machine: &mut Machine,
+    lpAddress: u32,
+    dwSize: u32,
+    dwFreeType: Result<MEM, u32>,
+) -> bool {
+    let mappings = &mut machine.state.kernel32.mappings;
+    let Some(mapping) = mappings
+        .find_mut(lpAddress)
+        .filter(|m| m.kind == MappingKind::Private)
+    else {
+        set_last_error(machine, winapi::ERROR::INVALID_ADDRESS);
+        return false;
+    };
+    match dwFreeType {
+        Ok(MEM::RELEASE) if lpAddress == mapping.addr && dwSize == 0 => {
+            release_mapping(machine, lpAddress);
+            true
+        }
+        Ok(MEM::DECOMMIT) => {
+            let size = match dwSize {
+                0 if lpAddress == mapping.addr => mapping.size,
+                size => size,
+            };
+            // Decommitted pages stay reserved, so the mapping stays put.
+            if protect_pages(machine, lpAddress, size, PAGE::empty()).is_none() {
+                set_last_error(machine, winapi::ERROR::INVALID_PARAMETER);
+                return false;
+            }
+            // The pages read as zero when they are next committed.
+            let start = lpAddress & !0xFFF;
+            let end = round_up_to_page_granularity(lpAddress + size);
+            machine.mem().sub32_mut(start, end - start).fill(0);
+            true
+        }
+        _ => {
+            set_last_error(machine, winapi::ERROR::INVALID_PARAMETER);
+            false
+        }
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn IsBadReadPtr(machine: &mut Machine, lp: u32, ucb: u32) -> bool {
+    ucb != 0 && machine.mem().try_sub32(lp, ucb).is_err()
 }
 
 #[win32_derive::dllexport]
-pub fn IsBadWritePtr(_machine: &mut Machine, lp: u32, ucb: u32) -> bool {
-    false // all pointers are valid
+pub fn IsBadWritePtr(machine: &mut Machine, lp: u32, ucb: u32) -> bool {
+    ucb != 0 && machine.mem().try_sub32_mut(lp, ucb).is_err()
 }
 
 #[win32_derive::dllexport]
-pub fn IsBadCodePtr(_machine: &mut Machine, lpfn: u32) -> bool {
-    false // all pointers are valid
+pub fn IsBadCodePtr(machine: &mut Machine, lpfn: u32) -> bool {
+    machine.mem().try_sub32(lpfn, 1).is_err()
+}
+
+#[win32_derive::dllexport]
+pub fn IsBadStringPtrA(machine: &mut Machine, lpsz: u32, ucchMax: u32) -> bool {
+    ucchMax != 0 && machine.mem().strz(lpsz, ucchMax).is_err()
 }
 
 bitflags! {
@@ -451,56 +878,220 @@ impl<'a> stack_args::FromArg<'a> for GMEM {
     }
 }
 
-fn alloc(machine: &mut Machine, uFlags: GMEM, dwBytes: u32) -> u32 {
-    if uFlags.contains(GMEM::MOVEABLE) {
-        todo!("GMEM_MOVEABLE");
-    }
+/// GlobalFlags bit for a moveable block with no memory.
+const GMEM_DISCARDED: u32 = 0x4000;
+const GMEM_LOCKCOUNT: u32 = 0xFF;
+
+/// A GMEM_MOVEABLE block.  Its handle is the address of a slot in guest memory
+/// holding the pointer to its data, as on Windows, where some programs dereference
+/// handles directly instead of calling GlobalLock.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Moveable {
+    /// The data, or 0 if the block has none (allocated with size 0, or discarded).
+    addr: u32,
+    locks: u32,
+}
+
+fn heap_alloc(machine: &mut Machine, flags: GMEM, size: u32) -> u32 {
+    let caller = guest_caller(machine);
     let heap = machine
         .state
         .kernel32
         .get_process_heap(&mut machine.emu.memory); // lazy init process_heap
-    let addr = heap.alloc(machine.emu.memory.mem(), dwBytes);
-    if uFlags.contains(GMEM::ZEROINIT) {
-        machine.mem().sub32_mut(addr, dwBytes).fill(0);
+    let addr = heap.alloc(machine.emu.memory.mem(), size);
+    if addr == 0 {
+        set_last_error(machine, winapi::ERROR::NOT_ENOUGH_MEMORY);
+        return 0;
+    }
+    heap.set_caller(machine.emu.memory.mem(), addr, caller);
+    if flags.contains(GMEM::ZEROINIT) {
+        machine.mem().sub32_mut(addr, size).fill(0);
     }
     addr
 }
 
-#[win32_derive::dllexport]
-pub fn GlobalAlloc(machine: &mut Machine, uFlags: GMEM, dwBytes: u32) -> u32 {
-    alloc(machine, uFlags, dwBytes)
+/// Make a handle for a moveable block with the given data.
+fn new_moveable(machine: &mut Machine, addr: u32) -> u32 {
+    let handle = heap_alloc(machine, GMEM::empty(), 4);
+    if handle == 0 {
+        return 0;
+    }
+    machine.mem().put_pod::<u32>(handle, addr);
+    machine
+        .state
+        .kernel32
+        .moveables
+        .insert(handle, Moveable { addr, locks: 0 });
+    handle
 }
 
-#[win32_derive::dllexport]
-pub fn GlobalReAlloc(machine: &mut Machine, hMem: u32, dwBytes: u32, uFlags: GMEM) -> u32 {
-    if uFlags.contains(GMEM::MODIFY) {
-        todo!("GMEM_MODIFY");
+/// Point a moveable block's handle at new data.
+fn set_moveable_addr(machine: &mut Machine, handle: u32, addr: u32) {
+    machine
+        .state
+        .kernel32
+        .moveables
+        .get_mut(&handle)
+        .unwrap()
+        .addr = addr;
+    machine.mem().put_pod::<u32>(handle, addr);
+}
+
+fn alloc(machine: &mut Machine, uFlags: GMEM, dwBytes: u32) -> u32 {
+    if !uFlags.contains(GMEM::MOVEABLE) {
+        return heap_alloc(machine, uFlags, dwBytes);
+    }
+    // A zero-size moveable block starts out discarded, with no memory.
+    let addr = if dwBytes == 0 {
+        0
+    } else {
+        match heap_alloc(machine, uFlags, dwBytes) {
+            0 => return 0,
+            addr => addr,
+        }
+    };
+    new_moveable(machine, addr)
+}
+
+fn size(machine: &mut Machine, hMem: u32) -> u32 {
+    let addr = match machine.state.kernel32.moveables.get(&hMem) {
+        Some(block) => block.addr,
+        None => hMem,
+    };
+    if addr == 0 {
+        return 0;
     }
     let heap = machine
         .state
         .kernel32
         .get_process_heap(&mut machine.emu.memory);
-    let mem = machine.emu.memory.mem();
-    let old_size = heap.size(mem, hMem);
-    if dwBytes <= old_size {
+    heap.size(machine.emu.memory.mem(), addr)
+}
+
+fn realloc(machine: &mut Machine, hMem: u32, dwBytes: u32, uFlags: GMEM) -> u32 {
+    let moveable = machine
+        .state
+        .kernel32
+        .moveables
+        .get(&hMem)
+        .map(|block| block.addr);
+    if uFlags.contains(GMEM::MODIFY) {
+        // Only changes the flags, of which the only one we track is moveability.
+        if moveable.is_none() && uFlags.contains(GMEM::MOVEABLE) {
+            return new_moveable(machine, hMem);
+        }
         return hMem;
     }
-    let addr = heap.alloc(mem, dwBytes);
-    mem.copy(hMem, addr, old_size);
-    heap.free(mem, hMem);
-    if uFlags.contains(GMEM::ZEROINIT) {
-        mem.sub32_mut(addr + old_size, dwBytes - old_size).fill(0);
+
+    let old = moveable.unwrap_or(hMem);
+    let old_size = if old == 0 { 0 } else { size(machine, old) };
+    let addr = match (old, dwBytes) {
+        (0, 0) => 0,
+        (0, _) => heap_alloc(machine, uFlags, dwBytes),
+        (_, 0) if moveable.is_some() => {
+            // Shrinking a moveable block to nothing discards it, keeping the handle.
+            free_block(machine, old);
+            0
+        }
+        _ => {
+            let heap = machine
+                .state
+                .kernel32
+                .get_process_heap(&mut machine.emu.memory);
+            let mem = machine.emu.memory.mem();
+            let addr = heap.realloc(mem, old, dwBytes);
+            if addr != 0 && uFlags.contains(GMEM::ZEROINIT) && dwBytes > old_size {
+                mem.sub32_mut(addr + old_size, dwBytes - old_size).fill(0);
+            }
+            addr
+        }
+    };
+    if moveable.is_none() {
+        return addr;
     }
-    addr
+    if addr == 0 && dwBytes != 0 {
+        return 0;
+    }
+    set_moveable_addr(machine, hMem, addr);
+    hMem
 }
 
-fn free(machine: &mut Machine, hMem: u32) -> u32 {
+fn free_block(machine: &mut Machine, addr: u32) {
     let heap = machine
         .state
         .kernel32
         .get_process_heap(&mut machine.emu.memory);
-    heap.free(machine.emu.memory.mem(), hMem);
-    return 0; // success
+    heap.free(machine.emu.memory.mem(), addr);
+}
+
+fn free(machine: &mut Machine, hMem: u32) -> u32 {
+    if let Some(block) = machine.state.kernel32.moveables.remove(&hMem) {
+        free_block(machine, block.addr);
+    }
+    free_block(machine, hMem);
+    0 // success
+}
+
+fn lock(machine: &mut Machine, hMem: u32) -> u32 {
+    let Some(block) = machine.state.kernel32.moveables.get_mut(&hMem) else {
+        // Fixed blocks are their own pointers.
+        return hMem;
+    };
+    if block.addr == 0 {
+        set_last_error(machine, winapi::ERROR::DISCARDED);
+        return 0;
+    }
+    // The lock count saturates rather than wrapping.
+    if block.locks < GMEM_LOCKCOUNT {
+        block.locks += 1;
+    }
+    block.addr
+}
+
+fn unlock(machine: &mut Machine, hMem: u32) -> bool {
+    let Some(block) = machine.state.kernel32.moveables.get_mut(&hMem) else {
+        return true;
+    };
+    if block.locks == 0 {
+        set_last_error(machine, winapi::ERROR::NOT_LOCKED);
+        return false;
+    }
+    block.locks -= 1;
+    if block.locks == 0 {
+        // Unlocking the last lock "fails" with no error.
+        set_last_error(machine, winapi::ERROR::SUCCESS);
+        return false;
+    }
+    true
+}
+
+fn handle(machine: &mut Machine, pMem: u32) -> u32 {
+    let moveables = &machine.state.kernel32.moveables;
+    if moveables.contains_key(&pMem) {
+        return pMem;
+    }
+    match moveables.iter().find(|(_, block)| block.addr == pMem) {
+        Some((&handle, _)) => handle,
+        None => pMem,
+    }
+}
+
+fn flags(machine: &mut Machine, hMem: u32) -> u32 {
+    match machine.state.kernel32.moveables.get(&hMem) {
+        Some(block) if block.addr == 0 => GMEM_DISCARDED,
+        Some(block) => block.locks,
+        None => 0,
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn GlobalAlloc(machine: &mut Machine, uFlags: GMEM, dwBytes: u32) -> u32 {
+    alloc(machine, uFlags, dwBytes)
+}
+
+#[win32_derive::dllexport]
+pub fn GlobalReAlloc(machine: &mut Machine, hMem: u32, dwBytes: u32, uFlags: GMEM) -> u32 {
+    realloc(machine, hMem, dwBytes, uFlags)
 }
 
 #[win32_derive::dllexport]
@@ -509,30 +1100,124 @@ pub fn GlobalFree(machine: &mut Machine, hMem: u32) -> u32 {
 }
 
 #[win32_derive::dllexport]
-pub fn GlobalFlags(_machine: &mut Machine, hMem: u32) -> u32 {
-    0 // stub
+pub fn GlobalFlags(machine: &mut Machine, hMem: u32) -> u32 {
+    flags(machine, hMem)
+}
+
+#[win32_derive::dllexport]
+pub fn GlobalLock(machine: &mut Machine, hMem: u32) -> u32 {
+    lock(machine, hMem)
+}
+
+#[win32_derive::dllexport]
+pub fn GlobalUnlock(machine: &mut Machine, hMem: u32) -> bool {
+    unlock(machine, hMem)
 }
 
+#[win32_derive::dllexport]
+pub fn GlobalSize(machine: &mut Machine, hMem: u32) -> u32 {
+    size(machine, hMem)
+}
+
+#[win32_derive::dllexport]
+pub fn GlobalHandle(machine: &mut Machine, pMem: u32) -> u32 {
+    handle(machine, pMem)
+}
+
+// The Local* functions take LMEM_* flags, but they are the same as GMEM_*.
+
 #[win32_derive::dllexport]
 pub fn LocalAlloc(machine: &mut Machine, uFlags: GMEM, dwBytes: u32) -> u32 {
-    // In theory this takes LMEM_* flags, but they are the same as GMEM_*.
     alloc(machine, uFlags, dwBytes)
 }
 
+#[win32_derive::dllexport]
+pub fn LocalReAlloc(machine: &mut Machine, hMem: u32, uBytes: u32, uFlags: GMEM) -> u32 {
+    realloc(machine, hMem, uBytes, uFlags)
+}
+
 #[win32_derive::dllexport]
 pub fn LocalFree(machine: &mut Machine, hMem: u32) -> u32 {
     free(machine, hMem)
 }
 
+#[win32_derive::dllexport]
+pub fn LocalFlags(machine: &mut Machine, hMem: u32) -> u32 {
+    flags(machine, hMem)
+}
+
+#[win32_derive::dllexport]
+pub fn LocalLock(machine: &mut Machine, hMem: u32) -> u32 {
+    lock(machine, hMem)
+}
+
+#[win32_derive::dllexport]
+pub fn LocalUnlock(machine: &mut Machine, hMem: u32) -> bool {
+    unlock(machine, hMem)
+}
+
+#[win32_derive::dllexport]
+pub fn LocalSize(machine: &mut Machine, hMem: u32) -> u32 {
+    size(machine, hMem)
+}
+
+#[win32_derive::dllexport]
+pub fn LocalHandle(machine: &mut Machine, pMem: u32) -> u32 {
+    handle(machine, pMem)
+}
+
 #[win32_derive::dllexport]
 pub fn VirtualProtect(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     lpAddress: u32,
     dwSize: u32,
-    flNewProtect: u32,
+    flNewProtect: Result<PAGE, u32>,
     lpflOldProtect: Option<&mut u32>,
 ) -> bool {
-    true // success
+    let Ok(protect) = flNewProtect else {
+        set_last_error(machine, winapi::ERROR::INVALID_PARAMETER);
+        return false;
+    };
+    let Some(lpflOldProtect) = lpflOldProtect else {
+        // "If this parameter is NULL or does not point to a valid variable, the function fails."
+        set_last_error(machine, winapi::ERROR::INVALID_PARAMETER);
+        return false;
+    };
+    match protect_pages(machine, lpAddress, dwSize, protect) {
+        Some(old) => {
+            *lpflOldProtect = old.bits();
+            true
+        }
+        None => {
+            set_last_error(machine, winapi::ERROR::INVALID_ADDRESS);
+            false
+        }
+    }
+}
+
+/// Self-modifying code is noticed as it's written, but programs that generate code
+/// say so here, so discard whatever was cached from that range regardless.
+#[win32_derive::dllexport]
+pub fn FlushInstructionCache(
+    machine: &mut Machine,
+    hProcess: HPROCESS,
+    lpBaseAddress: u32,
+    dwSize: u32,
+) -> bool {
+    #[cfg(feature = "x86-emu")]
+    {
+        let mem = machine.emu.memory.mem();
+        if lpBaseAddress == 0 {
+            machine.emu.x86.clear_cache(mem, 0, mem.len());
+        } else {
+            machine.emu.x86.clear_cache(mem, lpBaseAddress, dwSize);
+        }
+    }
+    #[cfg(not(feature = "x86-emu"))]
+    {
+        _ = (machine, lpBaseAddress, dwSize);
+    }
+    true
 }
 
 #[win32_derive::dllexport]
@@ -543,3 +1228,34 @@ pub fn GetProcessHeap(machine: &mut Machine) -> u32 {
         .get_process_heap(&mut machine.emu.memory); // lazy init process_heap
     machine.state.kernel32.process_heap
 }
+
+#[cfg(all(test, feature = "x86-emu"))]
+mod tests {
+    use super::*;
+    use crate::{fake_host::FakeHost, winapi::kernel32::teb};
+
+    fn alloc(machine: &mut Machine, size: u32) -> u32 {
+        let kind = Ok(MEM::COMMIT | MEM::RESERVE);
+        VirtualAlloc(machine, 0, size, kind, Ok(PAGE::READWRITE))
+    }
+
+    #[test]
+    fn alloc_fails_when_memory_runs_out() {
+        let host = Box::new(FakeHost::default());
+        let mut machine = Machine::with_memory_size(host, "test.exe".into(), 64 << 20).unwrap();
+        // Larger than anything that used to be allowed, but it fits.
+        assert_ne!(alloc(&mut machine, 48 << 20), 0);
+        assert_eq!(alloc(&mut machine, 48 << 20), 0);
+        assert_eq!(
+            teb(&machine).LastErrorValue,
+            winapi::ERROR::NOT_ENOUGH_MEMORY as u32
+        );
+        assert_ne!(alloc(&mut machine, 1 << 20), 0);
+    }
+
+    #[test]
+    fn reservation_failure_is_an_error() {
+        let host = Box::new(FakeHost::default());
+        assert!(Machine::with_memory_size(host, "test.exe".into(), 0).is_err());
+    }
+}
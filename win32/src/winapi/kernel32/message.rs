@@ -0,0 +1,562 @@
+//! FormatMessage, which looks up message strings and expands their %1-style inserts.
+
+use super::{decode_ansi, find_resource, set_last_error, LocalAlloc, ResourceKey, GMEM};
+use crate::{
+    pe,
+    winapi::{types::*, ERROR},
+    Machine,
+};
+use bitflags::bitflags;
+use memory::{Extensions, ExtensionsMut};
+
+bitflags! {
+    pub struct FormatMessageFlags: u32 {
+        const ALLOCATE_BUFFER = 0x00000100;
+        const IGNORE_INSERTS  = 0x00000200;
+        const FROM_STRING     = 0x00000400;
+        const FROM_HMODULE    = 0x00000800;
+        const FROM_SYSTEM     = 0x00001000;
+        const ARGUMENT_ARRAY  = 0x00002000;
+
+        /// The low byte is the maximum output line width; see FormatMessageFlags::max_width.
+        const MAX_WIDTH_MASK  = 0x000000FF;
+    }
+}
+impl TryFrom<u32> for FormatMessageFlags {
+    type Error = u32;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        FormatMessageFlags::from_bits(value).ok_or(value)
+    }
+}
+
+impl FormatMessageFlags {
+    /// 0 keeps the message's line breaks, 0xFF replaces them with spaces, and other
+    /// values also wrap lines at that width.
+    fn max_width(self) -> u32 {
+        self.bits() & Self::MAX_WIDTH_MASK.bits()
+    }
+}
+
+/// Text of the system error messages, as in the system message table.
+fn system_message(id: u32) -> Option<&'static str> {
+    Some(match id {
+        0 => "The operation completed successfully.",
+        1 => "Incorrect function.",
+        2 => "The system cannot find the file specified.",
+        3 => "The system cannot find the path specified.",
+        4 => "The system cannot open the file.",
+        5 => "Access is denied.",
+        6 => "The handle is invalid.",
+        8 => "Not enough storage is available to process this command.",
+        12 => "The access code is invalid.",
+        13 => "The data is invalid.",
+        14 => "Not enough storage is available to complete this operation.",
+        15 => "The system cannot find the drive specified.",
+        18 => "There are no more files.",
+        21 => "The device is not ready.",
+        24 => "The program issued a command but the command length is incorrect.",
+        28 => "The printer is out of paper.",
+        32 => "The process cannot access the file because it is being used by another process.",
+        38 => "Reached the end of the file.",
+        50 => "The request is not supported.",
+        80 => "The file exists.",
+        87 => "The parameter is incorrect.",
+        110 => "The system cannot open the device or file specified.",
+        111 => "The file name is too long.",
+        112 => "There is not enough space on the disk.",
+        122 => "The data area passed to a system call is too small.",
+        123 => "The filename, directory name, or volume label syntax is incorrect.",
+        126 => "The specified module could not be found.",
+        127 => "The specified procedure could not be found.",
+        183 => "Cannot create a file when that file already exists.",
+        193 => "%1 is not a valid Win32 application.",
+        234 => "More data is available.",
+        259 => "No more data is available.",
+        267 => "The directory name is invalid.",
+        288 => "Attempt to release mutex not owned by caller.",
+        298 => "Too many posts were made to a semaphore.",
+        317 => "The system cannot find message text for message number 0x%1 in the message file for %2.",
+        487 => "Attempt to access invalid address.",
+        996 => "Overlapped I/O event is not in a signaled state.",
+        997 => "Overlapped I/O operation is in progress.",
+        1004 => "Invalid flags.",
+        1006 => "The volume for a file has been externally altered so that the opened file is no longer valid.",
+        1018 => "Illegal operation attempted on a registry key that has been marked for deletion.",
+        1113 => "No mapping for the Unicode character exists in the target multi-byte code page.",
+        1114 => "A dynamic link library (DLL) initialization routine failed.",
+        1132 => "The base address or the file offset specified does not have the proper alignment.",
+        1280 => "The current thread has already been converted to a fiber.",
+        1281 => "The current thread has already been converted from a fiber.",
+        1400 => "Invalid window handle.",
+        1813 => "The specified resource type cannot be found in the image file.",
+        1814 => "The specified resource name cannot be found in the image file.",
+        _ => return None,
+    })
+}
+
+/// Look up a message in a module's RT_MESSAGETABLE resource.
+fn module_message(machine: &Machine, hmodule: u32, id: u32) -> Option<String> {
+    let hmodule = match hmodule {
+        0 => machine.state.kernel32.image_base,
+        hmodule => hmodule,
+    };
+    let mem = machine.mem();
+    let table = find_resource(
+        &machine.state.kernel32,
+        mem,
+        hmodule,
+        ResourceKey::Id(pe::RT::MESSAGETABLE as u32),
+        ResourceKey::Id(1),
+    )?;
+    // MESSAGE_RESOURCE_DATA: a count of MESSAGE_RESOURCE_BLOCKs, each covering a range
+    // of ids with an offset to its run of MESSAGE_RESOURCE_ENTRYs.
+    let blocks = mem.get_pod::<u32>(table.start);
+    for i in 0..blocks {
+        let block = table.start + 4 + i * 12;
+        let low = mem.get_pod::<u32>(block);
+        let high = mem.get_pod::<u32>(block + 4);
+        if !(low..=high).contains(&id) {
+            continue;
+        }
+        let mut entry = table.start + mem.get_pod::<u32>(block + 8);
+        for _ in low..id {
+            entry += mem.get_pod::<u16>(entry) as u32;
+        }
+        let len = mem.get_pod::<u16>(entry) as u32;
+        let unicode = mem.get_pod::<u16>(entry + 2) & 1 != 0;
+        let text = mem.sub32(entry + 4, len.saturating_sub(4));
+        // Entries are padded out with nuls.
+        let text = if unicode {
+            Str16::from_bytes(text).to_string()
+        } else {
+            decode_ansi(machine, text)
+        };
+        return Some(text.trim_end_matches('\0').to_string());
+    }
+    None
+}
+
+/// Where the insert arguments come from: either a plain array of values, or a va_list.
+/// Either way each argument takes up 4 bytes.
+struct Args {
+    addr: u32,
+    /// Whether %s means a wide string, i.e. for FormatMessageW.
+    wide: bool,
+}
+
+impl Args {
+    fn get(&self, machine: &Machine, index: u32) -> u32 {
+        machine.mem().get_pod::<u32>(self.addr + index * 4)
+    }
+
+    fn string(machine: &Machine, addr: u32, wide: bool) -> String {
+        if addr == 0 {
+            return "(null)".into();
+        }
+        if wide {
+            unsafe { Str16::from_nul_term_ptr(machine.mem(), addr) }
+                .unwrap()
+                .to_string()
+        } else {
+            let str = machine.mem().strz(addr, u32::MAX);
+            decode_ansi(machine, str.unwrap_or_else(|err| err.raise()))
+        }
+    }
+}
+
+/// Format a single insert according to its printf-style !spec!, given the index of its
+/// argument.  A * width or precision takes that argument, moving the value to the next.
+fn format_insert(machine: &Machine, args: &Args, index: u32, spec: &str) -> String {
+    let mut index = index;
+    let mut spec = spec.chars().peekable();
+
+    let mut left = false;
+    let mut zero = false;
+    let mut plus = false;
+    let mut alt = false;
+    while let Some(&c) = spec.peek() {
+        match c {
+            '-' => left = true,
+            '0' => zero = true,
+            '+' => plus = true,
+            '#' => alt = true,
+            ' ' => {}
+            _ => break,
+        }
+        spec.next();
+    }
+
+    let mut number = |spec: &mut std::iter::Peekable<std::str::Chars>| -> Option<usize> {
+        if spec.peek() == Some(&'*') {
+            spec.next();
+            let n = args.get(machine, index) as usize;
+            index += 1;
+            return Some(n);
+        }
+        let mut n = None;
+        while let Some(d) = spec.peek().and_then(|c| c.to_digit(10)) {
+            n = Some(n.unwrap_or(0) * 10 + d as usize);
+            spec.next();
+        }
+        n
+    };
+    let width = number(&mut spec).unwrap_or(0);
+    let precision = if spec.peek() == Some(&'.') {
+        spec.next();
+        Some(number(&mut spec).unwrap_or(0))
+    } else {
+        None
+    };
+
+    // Size prefixes: h and l/w pick the narrow and wide variants of s and c.
+    let mut wide = None;
+    while let Some(&c) = spec.peek() {
+        match c {
+            'h' => wide = Some(false),
+            'l' | 'w' => wide = Some(true),
+            'I' => {
+                // I32/I64.
+                spec.next();
+                let bits: String = spec.by_ref().take(2).collect();
+                if bits == "64" {
+                    log::warn!("FormatMessage: 64-bit insert {spec:?} unsupported");
+                }
+                continue;
+            }
+            _ => break,
+        }
+        spec.next();
+    }
+
+    let arg = args.get(machine, index);
+    let conv = spec.next().unwrap_or('s');
+    let text = match conv {
+        's' | 'S' => {
+            let wide = wide.unwrap_or(args.wide == (conv == 's'));
+            let mut str = Args::string(machine, arg, wide);
+            if let Some(precision) = precision {
+                str = str.chars().take(precision).collect();
+            }
+            str
+        }
+        'c' | 'C' => {
+            let wide = wide.unwrap_or(args.wide == (conv == 'c'));
+            if wide {
+                char::from_u32(arg & 0xFFFF).unwrap_or(char::REPLACEMENT_CHARACTER)
+            } else {
+                decode_ansi(machine, &[arg as u8]).chars().next().unwrap()
+            }
+            .to_string()
+        }
+        'd' | 'i' | 'u' | 'x' | 'X' | 'o' | 'p' => {
+            let (digits, sign) = match conv {
+                'd' | 'i' => {
+                    let n = arg as i32;
+                    let sign = if n < 0 {
+                        "-"
+                    } else if plus {
+                        "+"
+                    } else {
+                        ""
+                    };
+                    (n.unsigned_abs().to_string(), sign)
+                }
+                'u' => (arg.to_string(), ""),
+                'x' => (format!("{arg:x}"), if alt && arg != 0 { "0x" } else { "" }),
+                'X' => (format!("{arg:X}"), if alt && arg != 0 { "0X" } else { "" }),
+                'o' => (format!("{arg:o}"), if alt && arg != 0 { "0" } else { "" }),
+                'p' => (format!("{arg:08X}"), ""),
+                _ => unreachable!(),
+            };
+            let digits = match precision {
+                Some(precision) => format!("{digits:0>precision$}"),
+                None => digits,
+            };
+            if zero && !left && precision.is_none() {
+                let width = width.saturating_sub(sign.len());
+                return format!("{sign}{digits:0>width$}");
+            }
+            format!("{sign}{digits}")
+        }
+        _ => {
+            log::warn!("FormatMessage: unhandled insert format {conv:?}");
+            String::new()
+        }
+    };
+    if left {
+        format!("{text:<width$}")
+    } else {
+        format!("{text:>width$}")
+    }
+}
+
+/// Expand the escapes and inserts in a message.
+/// `insert` formats insert n with its printf-style spec, if there are arguments.
+fn format_message(
+    msg: &str,
+    flags: FormatMessageFlags,
+    mut insert: Option<&mut dyn FnMut(u32, &str) -> String>,
+) -> Result<String, ERROR> {
+    let ignore_inserts = flags.contains(FormatMessageFlags::IGNORE_INSERTS);
+    let max_width = flags.max_width();
+    let mut out = String::new();
+    let mut chars = msg.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '%' => {}
+            '\r' | '\n' if max_width != 0 => {
+                // Line breaks in the message text become spaces; only %n breaks lines.
+                if c == '\r' && chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                out.push(' ');
+                continue;
+            }
+            c => {
+                out.push(c);
+                continue;
+            }
+        }
+
+        let Some(c) = chars.next() else {
+            break;
+        };
+        match c {
+            '1'..='9' => {
+                let mut n = c.to_digit(10).unwrap();
+                if let Some(d) = chars.peek().and_then(|c| c.to_digit(10)) {
+                    n = n * 10 + d;
+                    chars.next();
+                }
+                let mut spec = None;
+                if chars.peek() == Some(&'!') {
+                    chars.next();
+                    spec = Some(chars.by_ref().take_while(|&c| c != '!').collect::<String>());
+                }
+                if ignore_inserts {
+                    out.push_str(&format!("%{n}"));
+                    if let Some(spec) = spec {
+                        out.push_str(&format!("!{spec}!"));
+                    }
+                    continue;
+                }
+                let Some(insert) = insert.as_mut() else {
+                    return Err(ERROR::INVALID_PARAMETER);
+                };
+                out.push_str(&insert(n, spec.as_deref().unwrap_or("s")));
+            }
+            // Ends the message, without the trailing newline it would otherwise have.
+            '0' => break,
+            'n' => out.push_str("\r\n"),
+            'r' => out.push('\r'),
+            't' => out.push('\t'),
+            // %%, %space, %., %! etc. are the character itself.
+            c => out.push(c),
+        }
+    }
+
+    if max_width != 0 && max_width != 0xFF {
+        out = wrap(&out, max_width as usize);
+    }
+    Ok(out)
+}
+
+/// Break lines longer than width at spaces.
+fn wrap(text: &str, width: usize) -> String {
+    let mut out = String::new();
+    for (i, line) in text.split("\r\n").enumerate() {
+        if i > 0 {
+            out.push_str("\r\n");
+        }
+        let mut len = 0;
+        for (j, word) in line.split(' ').enumerate() {
+            if j > 0 {
+                if len > 0 && len + 1 + word.chars().count() > width {
+                    out.push_str("\r\n");
+                    len = 0;
+                } else {
+                    out.push(' ');
+                    len += 1;
+                }
+            }
+            out.push_str(word);
+            len += word.chars().count();
+        }
+    }
+    out
+}
+
+/// Find the message and format it, for both FormatMessageA and W.
+fn find_and_format(
+    machine: &Machine,
+    flags: FormatMessageFlags,
+    source: u32,
+    message_id: u32,
+    args: u32,
+    wide: bool,
+) -> Result<String, ERROR> {
+    let msg = if flags.contains(FormatMessageFlags::FROM_STRING) {
+        if source == 0 {
+            return Err(ERROR::INVALID_PARAMETER);
+        }
+        Args::string(machine, source, wide)
+    } else {
+        let mut msg = None;
+        if flags.contains(FormatMessageFlags::FROM_HMODULE) {
+            msg = module_message(machine, source, message_id);
+        }
+        if msg.is_none() && flags.contains(FormatMessageFlags::FROM_SYSTEM) {
+            // System messages end in a line break, which %0 can suppress.
+            msg = system_message(message_id).map(|msg| format!("{msg}\r\n"));
+        }
+        match msg {
+            Some(msg) => msg,
+            None => {
+                log::warn!("FormatMessage: no message {message_id:#x}");
+                return Err(ERROR::MR_MID_NOT_FOUND);
+            }
+        }
+    };
+
+    let args = match args {
+        0 => None,
+        addr if flags.contains(FormatMessageFlags::ARGUMENT_ARRAY) => Some(Args { addr, wide }),
+        // Otherwise this is a pointer to a va_list, which itself points at the arguments.
+        addr => Some(Args {
+            addr: machine.mem().get_pod::<u32>(addr),
+            wide,
+        }),
+    };
+    match args {
+        Some(args) => format_message(
+            &msg,
+            flags,
+            Some(&mut |n, spec| format_insert(machine, &args, n - 1, spec)),
+        ),
+        None => format_message(&msg, flags, None),
+    }
+}
+
+/// Store the message into the caller's buffer or, for ALLOCATE_BUFFER, into one we
+/// allocate and store a pointer to.  Returns the length stored, excluding the nul.
+fn store<T: Copy + Default + memory::Pod>(
+    machine: &mut Machine,
+    flags: FormatMessageFlags,
+    msg: &[T],
+    buffer: u32,
+    size: u32,
+) -> u32 {
+    let char_size = std::mem::size_of::<T>() as u32;
+    let len = msg.len() as u32;
+    let addr = if flags.contains(FormatMessageFlags::ALLOCATE_BUFFER) {
+        if buffer == 0 {
+            set_last_error(machine, ERROR::INVALID_PARAMETER);
+            return 0;
+        }
+        let addr = LocalAlloc(machine, GMEM::empty(), (len + 1).max(size) * char_size);
+        machine.mem().put_pod::<u32>(buffer, addr);
+        addr
+    } else {
+        if len + 1 > size {
+            set_last_error(machine, ERROR::INSUFFICIENT_BUFFER);
+            return 0;
+        }
+        buffer
+    };
+    let mem = machine.mem();
+    for (i, &c) in msg.iter().chain(&[T::default()]).enumerate() {
+        mem.put_pod::<T>(addr + i as u32 * char_size, c);
+    }
+    set_last_error(machine, ERROR::SUCCESS);
+    len
+}
+
+#[win32_derive::dllexport]
+pub fn FormatMessageA(
+    machine: &mut Machine,
+    dwFlags: Result<FormatMessageFlags, u32>,
+    lpSource: u32,
+    dwMessageId: u32,
+    dwLanguageId: u32,
+    lpBuffer: u32,
+    nSize: u32,
+    args: u32,
+) -> u32 {
+    let Ok(flags) = dwFlags else {
+        set_last_error(machine, ERROR::INVALID_FLAGS);
+        return 0;
+    };
+    // Only English messages exist, so dwLanguageId is ignored.
+    match find_and_format(machine, flags, lpSource, dwMessageId, args, false) {
+        Ok(msg) => {
+            let chars = msg.encode_utf16().collect::<Vec<_>>();
+            let cp = machine.state.kernel32.code_page;
+            let (bytes, _) = cp.encode(&chars, b"?", false).unwrap();
+            store(machine, flags, &bytes, lpBuffer, nSize)
+        }
+        Err(err) => {
+            set_last_error(machine, err);
+            0
+        }
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn FormatMessageW(
+    machine: &mut Machine,
+    dwFlags: Result<FormatMessageFlags, u32>,
+    lpSource: u32,
+    dwMessageId: u32,
+    dwLanguageId: u32,
+    lpBuffer: u32,
+    nSize: u32,
+    args: u32,
+) -> u32 {
+    let Ok(flags) = dwFlags else {
+        set_last_error(machine, ERROR::INVALID_FLAGS);
+        return 0;
+    };
+    match find_and_format(machine, flags, lpSource, dwMessageId, args, true) {
+        Ok(msg) => {
+            let chars = msg.encode_utf16().collect::<Vec<_>>();
+            store(machine, flags, &chars, lpBuffer, nSize)
+        }
+        Err(err) => {
+            set_last_error(machine, err);
+            0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(msg: &str, flags: u32) -> Result<String, ERROR> {
+        let flags = FormatMessageFlags::from_bits(flags).unwrap();
+        let mut insert = |n: u32, spec: &str| format!("<{n}:{spec}>");
+        format_message(msg, flags, Some(&mut insert))
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(format("a%%b%nc%0d", 0).unwrap(), "a%b\r\nc");
+        assert_eq!(format("%1 and %12!08x!", 0).unwrap(), "<1:s> and <12:08x>");
+        let ignore = FormatMessageFlags::IGNORE_INSERTS.bits();
+        assert_eq!(format("%1 and %2!d!", ignore).unwrap(), "%1 and %2!d!");
+        assert!(format_message("%1", FormatMessageFlags::empty(), None).is_err());
+    }
+
+    #[test]
+    fn line_width() {
+        assert_eq!(
+            format("one\r\ntwo%nthree", 0xFF).unwrap(),
+            "one two\r\nthree"
+        );
+        assert_eq!(
+            format("the quick brown fox", 10).unwrap(),
+            "the quick\r\nbrown fox"
+        );
+    }
+}
@@ -1,18 +1,40 @@
 //! kernel32 API without a better home.
 
-use super::teb_mut;
+use super::{current_thread, decode_ansi, teb_addr, teb_mut, wake_waiters, HOBJECT, TEB};
 use crate::{
+    str16::Str16,
     winapi::{types::*, ERROR},
     Machine,
 };
 use ::memory::Pod;
-use bitflags::bitflags;
-use memory::ExtensionsMut;
+use memory::{Extensions, ExtensionsMut};
+use std::ffi::CStr;
 
 pub fn set_last_error(machine: &mut Machine, err: ERROR) {
     teb_mut(machine).LastErrorValue = err.into();
 }
 
+/// The len bytes at addr, for an API to write its result to.  If they're out of bounds,
+/// logs it as the failure of call and sets the last error, returning None for the caller
+/// to fail with.
+pub fn out_buf<'a>(
+    machine: &'a Machine,
+    addr: u32,
+    len: u32,
+    call: std::fmt::Arguments,
+) -> Option<&'a mut [u8]> {
+    match machine.mem().try_sub32_mut(addr, len) {
+        Ok(buf) => Some(buf),
+        Err(err) => {
+            log::debug!("{call} failed: {err}");
+            let err: ERROR = err.into();
+            let teb = machine.mem().get_aligned_ref_mut::<TEB>(teb_addr(machine));
+            teb.LastErrorValue = err.into();
+            None
+        }
+    }
+}
+
 #[win32_derive::dllexport]
 pub fn SetLastError(machine: &mut Machine, dwErrCode: u32) -> u32 {
     // Avoid set_last_error to allow for unknown error codes.
@@ -26,87 +48,21 @@ pub fn GetLastError(machine: &mut Machine) -> u32 {
 }
 
 #[win32_derive::dllexport]
-pub fn ExitProcess(machine: &mut Machine, uExitCode: u32) {
+pub async fn ExitProcess(machine: &mut Machine, uExitCode: u32) {
+    super::detach_dlls(machine).await;
     machine.exit(uExitCode);
 }
 
-#[win32_derive::dllexport]
-pub fn TerminateProcess(_machine: &mut Machine, hProcess: u32, uExitCode: u32) -> bool {
-    todo!();
-}
-
-#[derive(Debug, win32_derive::TryFromEnum)]
-pub enum ProcessorFeature {
-    FLOATING_POINT_PRECISION_ERRATA = 0,
-    FLOATING_POINT_EMULATED = 1,
-    COMPARE_EXCHANGE_DOUBLE = 2,
-    MMX_INSTRUCTIONS_AVAILABLE = 3,
-    PPC_MOVEMEM_64BIT_OK = 4,
-    ALPHA_BYTE_INSTRUCTIONS = 5,
-    XMMI_INSTRUCTIONS_AVAILABLE = 6,
-    _3DNOW_INSTRUCTIONS_AVAILABLE = 7,
-    RDTSC_INSTRUCTION_AVAILABLE = 8,
-    PAE_ENABLED = 9,
-    XMMI64_INSTRUCTIONS_AVAILABLE = 10,
-    SSE_DAZ_MODE_AVAILABLE = 11,
-    NX_ENABLED = 12,
-    SSE3_INSTRUCTIONS_AVAILABLE = 13,
-    COMPARE_EXCHANGE128 = 14,
-    COMPARE64_EXCHANGE128 = 15,
-    CHANNELS_ENABLED = 16,
-    XSAVE_ENABLED = 17,
-    ARM_VFP_32_REGISTERS_AVAILABLE = 18,
-    ARM_NEON_INSTRUCTIONS_AVAILABLE = 19,
-    SECOND_LEVEL_ADDRESS_TRANSLATION = 20,
-    VIRT_FIRMWARE_ENABLED = 21,
-    RDWRFSGSBASE_AVAILABLE = 22,
-    FASTFAIL_AVAILABLE = 23,
-    ARM_DIVIDE_INSTRUCTION_AVAILABLE = 24,
-    ARM_64BIT_LOADSTORE_ATOMIC = 25,
-    ARM_EXTERNAL_CACHE_AVAILABLE = 26,
-    ARM_FMAC_INSTRUCTIONS_AVAILABLE = 27,
-    RDRAND_INSTRUCTION_AVAILABLE = 28,
-    ARM_V8_INSTRUCTIONS_AVAILABLE = 29,
-    ARM_V8_CRYPTO_INSTRUCTIONS_AVAILABLE = 30,
-    ARM_V8_CRC32_INSTRUCTIONS_AVAILABLE = 31,
-    RDTSCP_INSTRUCTION_AVAILABLE = 32,
-}
-
-#[win32_derive::dllexport]
-pub fn IsProcessorFeaturePresent(
-    _machine: &mut Machine,
-    feature: Result<ProcessorFeature, u32>,
-) -> bool {
-    match feature.unwrap() {
-        ProcessorFeature::FLOATING_POINT_PRECISION_ERRATA => {
-            // We don't emulate floating point errors.
-            false
-        }
-        feature => {
-            log::warn!("IsProcessorFeaturePresent({feature:?}) unhandled, returning false");
-            false
-        }
-    }
-}
-
 #[win32_derive::dllexport]
 pub fn IsDebuggerPresent(_machine: &mut Machine) -> bool {
     true // Might cause a binary to log info via the debug API? Not sure.
 }
 
 #[win32_derive::dllexport]
-pub fn DebugBreak(_machine: &mut Machine) {
-    todo!()
-}
-
-#[win32_derive::dllexport]
-pub fn GetCurrentProcess(_machine: &mut Machine) -> u32 {
-    todo!()
-}
-
-#[win32_derive::dllexport]
-pub fn GetCurrentProcessId(_machine: &mut Machine) -> u32 {
-    1
+pub fn DebugBreak(machine: &mut Machine) {
+    // On Windows this is an int3, which reaches the program's own handlers when
+    // there's no debugger attached.
+    super::raise_from_builtin(machine, super::STATUS_BREAKPOINT, 0, &[0]);
 }
 
 #[win32_derive::dllexport]
@@ -151,32 +107,31 @@ pub fn SetHandleCount(_machine: &mut Machine, uNumber: u32) -> u32 {
     uNumber
 }
 
-#[win32_derive::dllexport]
-pub fn OutputDebugStringA(_machine: &mut Machine, msg: Option<&str>) -> u32 {
-    log::warn!("OutputDebugStringA: {:?}", msg);
-    0
-}
-
-#[win32_derive::dllexport]
-pub fn SetUnhandledExceptionFilter(_machine: &mut Machine, _lpTopLevelExceptionFilter: u32) -> u32 {
-    0 // No current handler.
+fn output_debug_string(machine: &mut Machine, msg: &str) {
+    let thread_id = current_thread(machine).id;
+    let ticks = machine.ticks();
+    // Programs typically end each message with a newline, which the host supplies.
+    let msg = msg.trim_end_matches(['\r', '\n']);
+    machine.host.debug_string(ticks, thread_id, msg);
 }
 
 #[win32_derive::dllexport]
-pub fn UnhandledExceptionFilter(_machine: &mut Machine, _exceptionInfo: u32) -> u32 {
-    // "The process is being debugged, so the exception should be passed (as second chance) to the application's debugger."
-    0 // EXCEPTION_CONTINUE_SEARCH
+pub fn OutputDebugStringA(machine: &mut Machine, lpOutputString: Option<&CStr>) -> u32 {
+    let Some(msg) = lpOutputString else {
+        return 0;
+    };
+    let msg = decode_ansi(machine, msg.to_bytes());
+    output_debug_string(machine, &msg);
+    0
 }
 
 #[win32_derive::dllexport]
-pub fn RaiseException(
-    _machine: &mut Machine,
-    dwExceptionCode: u32,
-    dwExceptionFlags: u32,
-    nNumberOfArguments: u32,
-    lpArguments: u32,
-) {
-    todo!();
+pub fn OutputDebugStringW(machine: &mut Machine, lpOutputString: Option<&Str16>) -> u32 {
+    let Some(msg) = lpOutputString else {
+        return 0;
+    };
+    output_debug_string(machine, &msg.to_string());
+    0
 }
 
 #[win32_derive::dllexport]
@@ -184,22 +139,6 @@ pub fn NtCurrentTeb(machine: &mut Machine) -> u32 {
     machine.state.kernel32.teb
 }
 
-// TODO: this has a bunch of synchronization magic that I haven't implemented,
-// but I did at least make this struct the right size (128 bits).
-#[repr(C)]
-#[derive(Debug)]
-pub struct SLIST_HEADER {
-    Next: u32,
-    todo: [u32; 3],
-}
-unsafe impl ::memory::Pod for SLIST_HEADER {}
-
-#[win32_derive::dllexport]
-pub fn InitializeSListHead(_machine: &mut Machine, ListHead: Option<&mut SLIST_HEADER>) -> u32 {
-    ListHead.unwrap().Next = 0;
-    0
-}
-
 #[win32_derive::dllexport]
 pub fn SetPriorityClass(
     _machine: &mut Machine,
@@ -214,72 +153,17 @@ pub fn AddVectoredExceptionHandler(_machine: &mut Machine, first: u32, handler:
     handler // success
 }
 
-bitflags! {
-    pub struct FormatMessageFlags: u32 {
-        const FROM_STRING    = 0x00000400;
-        const IGNORE_INSERTS = 0x00000200;
-        const FROM_SYSTEM    = 0x00001000;
-
-        // Low 8 bits can be used for line breaking width (!?).
-        // Not sure if this makes bitflags do the right thing...
-        const MAX_WIDTH_MASK = 0xFF;
-    }
-}
-impl TryFrom<u32> for FormatMessageFlags {
-    type Error = u32;
-
-    fn try_from(value: u32) -> Result<Self, Self::Error> {
-        FormatMessageFlags::from_bits(value).ok_or(value)
-    }
-}
-
 #[win32_derive::dllexport]
-pub fn FormatMessageW(
-    machine: &mut Machine,
-    dwFlags: Result<FormatMessageFlags, u32>,
-    lpSource: u32,
-    dwMessageId: u32,
-    dwLanguageId: u32,
-    lpBuffer: u32,
-    nSize: u32,
-    args: u32,
-) -> u32 {
-    // Note args is a va_list*, not a va_list!
-
-    let flags = dwFlags.unwrap();
-
-    if dwLanguageId != 0 {
-        todo!();
-    }
-    let msg = if flags.contains(FormatMessageFlags::FROM_SYSTEM) {
-        match ERROR::try_from(dwMessageId) {
-            Ok(ERROR::FILE_NOT_FOUND) => "The system cannot find the file specified.",
-            Ok(ERROR::OUT_OF_PAPER) => "The printer is out of paper.",
-            err => todo!("system error {err:x?}"),
-        }
-    } else {
-        todo!();
-    };
-
-    let buf: &mut [u16] = unsafe {
-        let mem = machine.mem().sub32_mut(lpBuffer, nSize);
-        std::slice::from_raw_parts_mut(mem.as_mut_ptr() as *mut _, mem.len() / 2)
-    };
-    let msgw = String16::from(msg);
-
-    buf[..msgw.len()].copy_from_slice(msgw.buf());
-    buf[msgw.len()] = 0;
-
-    msgw.len() as u32
-}
-
-#[win32_derive::dllexport]
-pub fn CloseHandle(machine: &mut Machine, hObject: HFILE) -> bool {
-    if machine.state.kernel32.files.remove(hObject).is_none() {
+pub fn CloseHandle(machine: &mut Machine, hObject: HOBJECT) -> bool {
+    if !machine.state.kernel32.objects.close(hObject) {
         log::debug!("CloseHandle({hObject:?}): unknown handle");
         set_last_error(machine, ERROR::INVALID_HANDLE);
         return false;
     }
+    // Closing one end of a pipe breaks it for a thread waiting on the other.
+    if machine.state.kernel32.pipes.changed() {
+        wake_waiters(machine);
+    }
 
     set_last_error(machine, ERROR::SUCCESS);
     true
@@ -294,7 +178,14 @@ pub fn GetSystemDirectoryA(machine: &mut Machine, lpBuffer: u32, uSize: u32) ->
     }
     set_last_error(machine, ERROR::SUCCESS);
     if lpBuffer != 0 {
-        let buf = machine.mem().sub32_mut(lpBuffer, uSize);
+        let Some(buf) = out_buf(
+            machine,
+            lpBuffer,
+            uSize,
+            format_args!("GetSystemDirectoryA"),
+        ) else {
+            return 0;
+        };
         buf[..path_bytes.len()].copy_from_slice(path_bytes);
         buf[path_bytes.len()] = 0;
     }
@@ -310,32 +201,20 @@ pub fn GetWindowsDirectoryA(machine: &mut Machine, lpBuffer: u32, uSize: u32) ->
         return path_bytes.len() as u32 + 1;
     }
     if lpBuffer != 0 {
-        let buf = machine.mem().sub32_mut(lpBuffer, uSize);
+        let Some(buf) = out_buf(
+            machine,
+            lpBuffer,
+            uSize,
+            format_args!("GetWindowsDirectoryA"),
+        ) else {
+            return 0;
+        };
         buf[..path_bytes.len()].copy_from_slice(path_bytes);
         buf[path_bytes.len()] = 0;
     }
     path_bytes.len() as u32
 }
 
-#[win32_derive::dllexport]
-pub fn FormatMessageA(
-    machine: &mut Machine,
-    dwFlags: u32,
-    lpSource: u32,
-    dwMessageId: u32,
-    dwLanguageId: u32,
-    lpBuffer: u32,
-    nSize: u32,
-    args: u32,
-) -> u32 {
-    log::warn!("FormatMessageA: stub");
-    if lpBuffer != 0 && nSize > 0 {
-        let buf = machine.mem().sub32_mut(lpBuffer, nSize);
-        buf[0] = 0;
-    }
-    0
-}
-
 #[win32_derive::dllexport]
 pub fn MulDiv(_machine: &mut Machine, nNumber: i32, nNumerator: i32, nDenominator: i32) -> i32 {
     if nDenominator == 0 {
@@ -367,12 +246,4 @@ pub fn MulDiv(_machine: &mut Machine, nNumber: i32, nNumerator: i32, nDenominato
 }
 
 #[win32_derive::dllexport]
-pub fn RtlUnwind(
-    _machine: &mut Machine,
-    TargetFrame: u32,
-    TargetIp: u32,
-    ExceptionRecord: u32,
-    ReturnValue: u32,
-) {
-    todo!();
-}
+pub use crate::winapi::ntdll::RtlUnwind;
@@ -4,31 +4,55 @@
 mod console;
 mod dll;
 mod env;
+mod exception;
+mod fiber;
 mod file;
 mod file16;
 mod ini;
 mod init;
+mod interlocked;
 mod libc;
+mod mapping;
 mod memory;
+mod message;
 mod misc;
 mod nls;
+mod object;
+mod pipe;
+mod process;
 mod resource;
 mod sync;
+mod sysinfo;
 mod thread;
 mod time;
+mod tls;
+mod vfs;
+mod volume;
 
 pub use self::memory::*;
 pub use console::*;
 pub use dll::*;
 pub use env::*;
+pub use exception::*;
+pub use fiber::*;
 pub use file::*;
 pub use file16::*;
 pub use ini::*;
 pub use init::*;
+pub use interlocked::*;
 pub use libc::*;
+pub use mapping::*;
+pub use message::*;
 pub use misc::*;
 pub use nls::*;
+pub use object::*;
+pub use pipe::*;
+pub use process::*;
 pub use resource::*;
 pub use sync::*;
+pub use sysinfo::*;
 pub use thread::*;
 pub use time::*;
+pub use tls::*;
+pub use vfs::*;
+pub use volume::*;
@@ -1,105 +1,642 @@
 //! "National Language Support", e.g. code page conversions.
 
-use crate::{winapi::stack_args::ArrayWithSizeMut, Machine};
+use super::set_last_error;
+use crate::{
+    str16::Str16,
+    winapi::{stack_args::ArrayWithSizeMut, ERROR},
+    Machine,
+};
 use memory::Extensions;
+use std::cmp::Ordering;
 
-/// Code pages
+/// Code page identifiers, including the CP_* aliases for the current code pages.
 #[derive(Debug, win32_derive::TryFromEnum)]
 pub enum CP {
     /// The system default Windows ANSI code page.
     ACP = 0,
+    /// The system default OEM (console) code page.
     OEMCP = 1,
+    MACCP = 2,
+    /// The current thread's ANSI code page.
+    THREAD_ACP = 3,
+    IBM437 = 437,
+    SHIFT_JIS = 932,
     WINDOWS_1252 = 1252,
     UTF8 = 65001,
 }
 
+/// A code page we can convert to and from UTF-16.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum CodePage {
+    /// OEM United States, the DOS character set.
+    Ibm437,
+    /// Japanese, a double-byte character set.
+    ShiftJis,
+    /// Western European, the usual ANSI code page.
+    Windows1252,
+    Utf8,
+}
+
+/// Unicode for bytes 0x80..=0xFF of code page 437.
+const CP437_HIGH: [u16; 128] = [
+    0x00C7, 0x00FC, 0x00E9, 0x00E2, 0x00E4, 0x00E0, 0x00E5, 0x00E7, //
+    0x00EA, 0x00EB, 0x00E8, 0x00EF, 0x00EE, 0x00EC, 0x00C4, 0x00C5, //
+    0x00C9, 0x00E6, 0x00C6, 0x00F4, 0x00F6, 0x00F2, 0x00FB, 0x00F9, //
+    0x00FF, 0x00D6, 0x00DC, 0x00A2, 0x00A3, 0x00A5, 0x20A7, 0x0192, //
+    0x00E1, 0x00ED, 0x00F3, 0x00FA, 0x00F1, 0x00D1, 0x00AA, 0x00BA, //
+    0x00BF, 0x2310, 0x00AC, 0x00BD, 0x00BC, 0x00A1, 0x00AB, 0x00BB, //
+    0x2591, 0x2592, 0x2593, 0x2502, 0x2524, 0x2561, 0x2562, 0x2556, //
+    0x2555, 0x2563, 0x2551, 0x2557, 0x255D, 0x255C, 0x255B, 0x2510, //
+    0x2514, 0x2534, 0x252C, 0x251C, 0x2500, 0x253C, 0x255E, 0x255F, //
+    0x255A, 0x2554, 0x2569, 0x2566, 0x2560, 0x2550, 0x256C, 0x2567, //
+    0x2568, 0x2564, 0x2565, 0x2559, 0x2558, 0x2552, 0x2553, 0x256B, //
+    0x256A, 0x2518, 0x250C, 0x2588, 0x2584, 0x258C, 0x2590, 0x2580, //
+    0x03B1, 0x00DF, 0x0393, 0x03C0, 0x03A3, 0x03C3, 0x00B5, 0x03C4, //
+    0x03A6, 0x0398, 0x03A9, 0x03B4, 0x221E, 0x03C6, 0x03B5, 0x2229, //
+    0x2261, 0x00B1, 0x2265, 0x2264, 0x2320, 0x2321, 0x00F7, 0x2248, //
+    0x00B0, 0x2219, 0x00B7, 0x221A, 0x207F, 0x00B2, 0x25A0, 0x00A0, //
+];
+
+/// The glyphs code page 437 shows for control characters 0x01..=0x1F, for MB_USEGLYPHCHARS.
+const CP437_GLYPHS: [u16; 31] = [
+    0x263A, 0x263B, 0x2665, 0x2666, 0x2663, 0x2660, 0x2022, 0x25D8, //
+    0x25CB, 0x25D9, 0x2642, 0x2640, 0x266A, 0x266B, 0x263C, 0x25BA, //
+    0x25C4, 0x2195, 0x203C, 0x00B6, 0x00A7, 0x25AC, 0x21A8, 0x2191, //
+    0x2193, 0x2192, 0x2190, 0x221F, 0x2194, 0x25B2, 0x25BC,
+];
+/// The glyph code page 437 shows for 0x7F (DEL).
+const CP437_GLYPH_DEL: u16 = 0x2302;
+
+impl CodePage {
+    /// The code page for an identifier, resolving the CP_* aliases.
+    pub fn resolve(machine: &Machine, id: u32) -> Option<CodePage> {
+        let acp = machine.state.kernel32.code_page;
+        Some(match CP::try_from(id).ok()? {
+            CP::ACP | CP::THREAD_ACP => acp,
+            CP::OEMCP => acp.oem(),
+            CP::MACCP => return None,
+            CP::IBM437 => CodePage::Ibm437,
+            CP::SHIFT_JIS => CodePage::ShiftJis,
+            CP::WINDOWS_1252 => CodePage::Windows1252,
+            CP::UTF8 => CodePage::Utf8,
+        })
+    }
+
+    /// Look up an ANSI code page by number, e.g. from the command line.
+    pub fn from_id(id: u32) -> Option<CodePage> {
+        match id {
+            932 => Some(CodePage::ShiftJis),
+            1252 => Some(CodePage::Windows1252),
+            _ => None,
+        }
+    }
+
+    pub fn id(self) -> u32 {
+        match self {
+            CodePage::Ibm437 => 437,
+            CodePage::ShiftJis => 932,
+            CodePage::Windows1252 => 1252,
+            CodePage::Utf8 => 65001,
+        }
+    }
+
+    /// The OEM code page that goes along with this ANSI code page in its locale.
+    pub fn oem(self) -> CodePage {
+        match self {
+            CodePage::ShiftJis => CodePage::ShiftJis,
+            _ => CodePage::Ibm437,
+        }
+    }
+
+    /// Maximum bytes per character.
+    pub fn max_char_size(self) -> u32 {
+        match self {
+            CodePage::Ibm437 | CodePage::Windows1252 => 1,
+            CodePage::ShiftJis => 2,
+            CodePage::Utf8 => 4,
+        }
+    }
+
+    /// Whether a byte starts a two-byte character.
+    pub fn is_lead_byte(self, b: u8) -> bool {
+        match self {
+            CodePage::ShiftJis => matches!(b, 0x81..=0x9F | 0xE0..=0xFC),
+            _ => false,
+        }
+    }
+
+    /// The offset of the character following the one at `i`, treating a lead byte
+    /// followed by the end of the string as a single character.
+    pub fn next_char(self, s: &[u8], i: usize) -> usize {
+        if i >= s.len() {
+            return s.len();
+        }
+        if self.is_lead_byte(s[i]) && i + 1 < s.len() {
+            i + 2
+        } else {
+            i + 1
+        }
+    }
+
+    /// The offset of the character preceding the one at `i`.  Whether a byte is a lead
+    /// or trail byte depends on what comes before it, so this scans from the start.
+    pub fn prev_char(self, s: &[u8], i: usize) -> usize {
+        let mut prev = 0;
+        let mut pos = 0;
+        while pos < i {
+            prev = pos;
+            pos = self.next_char(s, pos);
+        }
+        prev
+    }
+
+    /// Upper- or lowercase a string in place, leaving double-byte characters alone.
+    pub fn change_case(self, s: &mut [u8], upper: bool) {
+        let mut i = 0;
+        while i < s.len() {
+            let next = self.next_char(s, i);
+            if next == i + 1 {
+                s[i] = self.change_case_byte(s[i], upper);
+            }
+            i = next;
+        }
+    }
+
+    fn change_case_byte(self, b: u8, upper: bool) -> u8 {
+        if b < 0x80 {
+            return if upper {
+                b.to_ascii_uppercase()
+            } else {
+                b.to_ascii_lowercase()
+            };
+        }
+        let Some(&[c]) = self.decode(&[b], true, false).as_deref() else {
+            return b;
+        };
+        let Some(c) = char::from_u32(c as u32) else {
+            return b;
+        };
+        let mapped: String = if upper {
+            c.to_uppercase().collect()
+        } else {
+            c.to_lowercase().collect()
+        };
+        let mapped = mapped.encode_utf16().collect::<Vec<_>>();
+        match self.encode(&mapped, b"", true) {
+            Some((bytes, false)) if bytes.len() == 1 => bytes[0],
+            _ => b,
+        }
+    }
+
+    /// Lead byte ranges as reported by GetCPInfo, inclusive.
+    fn lead_byte_ranges(self) -> &'static [(u8, u8)] {
+        match self {
+            CodePage::ShiftJis => &[(0x81, 0x9F), (0xE0, 0xFC)],
+            _ => &[],
+        }
+    }
+
+    fn encoding(self) -> Option<&'static encoding_rs::Encoding> {
+        match self {
+            CodePage::ShiftJis => Some(encoding_rs::SHIFT_JIS),
+            CodePage::Windows1252 => Some(encoding_rs::WINDOWS_1252),
+            CodePage::Ibm437 | CodePage::Utf8 => None,
+        }
+    }
+
+    /// Decode to UTF-16.  Invalid sequences become U+FFFD, or with `strict` fail the decode.
+    /// `glyphs` shows control characters as the glyphs the code page draws for them.
+    pub fn decode(self, bytes: &[u8], strict: bool, glyphs: bool) -> Option<Vec<u16>> {
+        match self {
+            CodePage::Ibm437 => Some(
+                bytes
+                    .iter()
+                    .map(|&b| match b {
+                        0x80.. => CP437_HIGH[b as usize - 0x80],
+                        0x01..=0x1F if glyphs => CP437_GLYPHS[b as usize - 1],
+                        0x7F if glyphs => CP437_GLYPH_DEL,
+                        _ => b as u16,
+                    })
+                    .collect(),
+            ),
+            CodePage::Utf8 => {
+                let str = if strict {
+                    std::borrow::Cow::Borrowed(std::str::from_utf8(bytes).ok()?)
+                } else {
+                    String::from_utf8_lossy(bytes)
+                };
+                Some(str.encode_utf16().collect())
+            }
+            _ => {
+                let encoding = self.encoding().unwrap();
+                let str = if strict {
+                    encoding.decode_without_bom_handling_and_without_replacement(bytes)?
+                } else {
+                    encoding.decode_without_bom_handling(bytes).0
+                };
+                Some(str.encode_utf16().collect())
+            }
+        }
+    }
+
+    /// Append the encoding of a character, returning false if the code page has no mapping for it.
+    fn encode_char(self, c: char, out: &mut Vec<u8>) -> bool {
+        match self {
+            CodePage::Ibm437 => {
+                let b = if (c as u32) < 0x80 {
+                    c as u8
+                } else {
+                    match CP437_HIGH.iter().position(|&u| u as u32 == c as u32) {
+                        Some(i) => 0x80 + i as u8,
+                        None => return false,
+                    }
+                };
+                out.push(b);
+                true
+            }
+            CodePage::Utf8 => {
+                out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                true
+            }
+            _ => {
+                let mut buf = [0; 4];
+                let (bytes, _, unmappable) =
+                    self.encoding().unwrap().encode(c.encode_utf8(&mut buf));
+                if unmappable {
+                    return false;
+                }
+                out.extend_from_slice(&bytes);
+                true
+            }
+        }
+    }
+
+    /// Encode from UTF-16, substituting `default` for characters without a mapping.
+    /// Returns the bytes and whether the default was used, or None if `strict` and the
+    /// input had unpaired surrogates.
+    pub fn encode(self, chars: &[u16], default: &[u8], strict: bool) -> Option<(Vec<u8>, bool)> {
+        let mut out = Vec::with_capacity(chars.len());
+        let mut used_default = false;
+        for c in char::decode_utf16(chars.iter().copied()) {
+            let c = match c {
+                Ok(c) => c,
+                Err(_) if strict => return None,
+                Err(_) => char::REPLACEMENT_CHARACTER,
+            };
+            if !self.encode_char(c, &mut out) {
+                out.extend_from_slice(default);
+                used_default = true;
+            }
+        }
+        Some((out, used_default))
+    }
+}
+
+/// Decode a string in the ANSI code page.
+pub fn decode_ansi(machine: &Machine, bytes: &[u8]) -> String {
+    let chars = machine
+        .state
+        .kernel32
+        .code_page
+        .decode(bytes, false, false)
+        .unwrap();
+    String::from_utf16_lossy(&chars)
+}
+
 #[win32_derive::dllexport]
-pub fn GetACP(_machine: &mut Machine) -> u32 {
-    1252 // windows-1252
+pub fn GetACP(machine: &mut Machine) -> u32 {
+    machine.state.kernel32.code_page.id()
 }
 
 #[win32_derive::dllexport]
-pub fn GetOEMCP(_machine: &mut Machine) -> u32 {
-    todo!()
+pub fn GetOEMCP(machine: &mut Machine) -> u32 {
+    machine.state.kernel32.code_page.oem().id()
 }
 
 #[win32_derive::dllexport]
-pub fn IsValidCodePage(_machine: &mut Machine, CodePage: u32) -> bool {
-    CodePage == 1252
+pub fn IsValidCodePage(machine: &mut Machine, CodePage: u32) -> bool {
+    // The CP_* aliases aren't code pages themselves.
+    CodePage > 3 && CodePage::resolve(machine, CodePage).is_some()
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct CPINFO {
+    MaxCharSize: u32,
+    DefaultChar: [u8; 2],
+    LeadByte: [u8; 12],
 }
+unsafe impl ::memory::Pod for CPINFO {}
 
 #[win32_derive::dllexport]
-pub fn GetCPInfo(_machine: &mut Machine, _CodePage: u32, _lpCPInfo: u32) -> u32 {
-    0 // fail
+pub fn GetCPInfo(machine: &mut Machine, CodePage: u32, lpCPInfo: Option<&mut CPINFO>) -> bool {
+    let (Some(cp), Some(info)) = (CodePage::resolve(machine, CodePage), lpCPInfo) else {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return false;
+    };
+    info.MaxCharSize = cp.max_char_size();
+    info.DefaultChar = [b'?', 0];
+    info.LeadByte = [0; 12];
+    for (i, &(lo, hi)) in cp.lead_byte_ranges().iter().enumerate() {
+        info.LeadByte[i * 2] = lo;
+        info.LeadByte[i * 2 + 1] = hi;
+    }
+    true
 }
 
+const MB_PRECOMPOSED: u32 = 0x1;
+const MB_COMPOSITE: u32 = 0x2;
+const MB_USEGLYPHCHARS: u32 = 0x4;
+const MB_ERR_INVALID_CHARS: u32 = 0x8;
+
 #[win32_derive::dllexport]
 pub fn MultiByteToWideChar(
     machine: &mut Machine,
-    CodePage: Result<CP, u32>,
+    CodePage: u32,
     dwFlags: u32,
     lpMultiByteStr: u32,
     cbMultiByte: i32,
     lpWideCharStr: ArrayWithSizeMut<u16>,
 ) -> u32 {
-    match CodePage {
-        Err(value) => unimplemented!("MultiByteToWideChar code page {value}"),
-        _ => {} // treat all others as ansi for now
+    let Some(cp) = CodePage::resolve(machine, CodePage) else {
+        log::warn!("MultiByteToWideChar: unsupported code page {CodePage}");
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return 0;
+    };
+    let known = MB_PRECOMPOSED | MB_COMPOSITE | MB_USEGLYPHCHARS | MB_ERR_INVALID_CHARS;
+    if dwFlags & !known != 0 || (cp == CodePage::Utf8 && dwFlags & !MB_ERR_INVALID_CHARS != 0) {
+        set_last_error(machine, ERROR::INVALID_FLAGS);
+        return 0;
+    }
+    if lpMultiByteStr == 0 || cbMultiByte == 0 || cbMultiByte < -1 {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return 0;
     }
-    // TODO: dwFlags
 
-    let input_len = match cbMultiByte {
-        0 => return 0,                                               // TODO: invalid param
-        -1 => machine.mem().slicez(lpMultiByteStr).len() as u32 + 1, // include nul
-        len => len as u32,
+    let mem = machine.mem();
+    let input = match cbMultiByte {
+        // Include the nul.
+        -1 => mem
+            .strz(lpMultiByteStr, u32::MAX)
+            .and_then(|str| mem.try_sub32(lpMultiByteStr, str.len() as u32 + 1)),
+        len => mem.try_sub32(lpMultiByteStr, len as u32),
     };
-
-    let mut lpWideCharStr = lpWideCharStr.to_option();
-    match lpWideCharStr {
-        Some(buf) if buf.len() == 0 => lpWideCharStr = None,
-        _ => (),
+    let input = match input {
+        Ok(input) => input,
+        Err(err) => {
+            set_last_error(machine, err.into());
+            return 0;
+        }
+    };
+    let strict = dwFlags & MB_ERR_INVALID_CHARS != 0;
+    let Some(chars) = cp.decode(input, strict, dwFlags & MB_USEGLYPHCHARS != 0) else {
+        set_last_error(machine, ERROR::NO_UNICODE_TRANSLATION);
+        return 0;
     };
 
-    match lpWideCharStr {
-        None => input_len,
+    match lpWideCharStr.to_option() {
+        // A zero-sized output buffer queries the required length.
+        None | Some([]) => chars.len() as u32,
+        Some(buf) if buf.len() < chars.len() => {
+            set_last_error(machine, ERROR::INSUFFICIENT_BUFFER);
+            0
+        }
         Some(buf) => {
-            let input = machine.mem().sub32(lpMultiByteStr, input_len);
-            let mut len = 0;
-            for (&c_in, c_out) in std::iter::zip(input, buf) {
-                if c_in > 0x7f {
-                    unimplemented!("unicode");
-                }
-                *c_out = c_in as u16;
-                len += 1;
-            }
-            len
+            buf[..chars.len()].copy_from_slice(&chars);
+            chars.len() as u32
         }
     }
 }
 
+const WC_DEFAULTCHAR: u32 = 0x40;
+const WC_ERR_INVALID_CHARS: u32 = 0x80;
+const WC_COMPOSITECHECK: u32 = 0x200;
+const WC_NO_BEST_FIT_CHARS: u32 = 0x400;
+
 #[win32_derive::dllexport]
 pub fn WideCharToMultiByte(
     machine: &mut Machine,
-    CodePage: Result<CP, u32>,
+    CodePage: u32,
     dwFlags: u32,
     lpWideCharStr: u32,
     cchWideChar: i32,
-    lpMultiByteStr: u32,
-    cbMultiByte: i32,
+    lpMultiByteStr: ArrayWithSizeMut<u8>,
+    lpDefaultChar: u32,
     lpUsedDefaultChar: Option<&mut u32>,
 ) -> u32 {
-    todo!()
+    let Some(cp) = CodePage::resolve(machine, CodePage) else {
+        log::warn!("WideCharToMultiByte: unsupported code page {CodePage}");
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return 0;
+    };
+    let utf8 = cp == CodePage::Utf8;
+    let known = WC_DEFAULTCHAR | WC_ERR_INVALID_CHARS | WC_COMPOSITECHECK | WC_NO_BEST_FIT_CHARS;
+    if dwFlags & !known != 0 || (utf8 && dwFlags & !WC_ERR_INVALID_CHARS != 0) {
+        set_last_error(machine, ERROR::INVALID_FLAGS);
+        return 0;
+    }
+    // UTF-8 can encode everything, so it takes no default char.
+    if utf8 && (lpDefaultChar != 0 || lpUsedDefaultChar.is_some()) {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return 0;
+    }
+    if lpWideCharStr == 0 || cchWideChar == 0 || cchWideChar < -1 {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return 0;
+    }
+
+    let input = match cchWideChar {
+        // Include the nul.
+        -1 => {
+            let str = unsafe { Str16::from_nul_term_ptr(machine.mem(), lpWideCharStr) }.unwrap();
+            let len = str.len() as u32 + 1;
+            machine.mem().try_sub32(lpWideCharStr, len * 2)
+        }
+        len => machine.mem().try_sub32(lpWideCharStr, len as u32 * 2),
+    };
+    let input = match input {
+        Ok(input) => input,
+        Err(err) => {
+            set_last_error(machine, err.into());
+            return 0;
+        }
+    };
+    let chars = Str16::from_bytes(input);
+
+    let default = match lpDefaultChar {
+        0 => &b"?"[..],
+        addr => {
+            // A single character, which may be double-byte.
+            let lead = machine.mem().read_pod::<u8>(addr);
+            let len = lead.map(|lead| if cp.is_lead_byte(lead) { 2 } else { 1 });
+            match len.and_then(|len| machine.mem().try_sub32(addr, len)) {
+                Ok(default) => default,
+                Err(err) => {
+                    set_last_error(machine, err.into());
+                    return 0;
+                }
+            }
+        }
+    };
+    let strict = dwFlags & WC_ERR_INVALID_CHARS != 0;
+    let Some((bytes, used_default)) = cp.encode(chars, default, strict) else {
+        set_last_error(machine, ERROR::NO_UNICODE_TRANSLATION);
+        return 0;
+    };
+    if let Some(used) = lpUsedDefaultChar {
+        *used = used_default as u32;
+    }
+
+    match lpMultiByteStr.to_option() {
+        // A zero-sized output buffer queries the required length.
+        None | Some([]) => bytes.len() as u32,
+        Some(buf) if buf.len() < bytes.len() => {
+            set_last_error(machine, ERROR::INSUFFICIENT_BUFFER);
+            0
+        }
+        Some(buf) => {
+            buf[..bytes.len()].copy_from_slice(&bytes);
+            bytes.len() as u32
+        }
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn IsDBCSLeadByteEx(machine: &mut Machine, TestChar: u8, CodePage: u32) -> bool {
+    match CodePage::resolve(machine, CodePage) {
+        Some(cp) => cp.is_lead_byte(TestChar),
+        None => {
+            set_last_error(machine, ERROR::INVALID_PARAMETER);
+            false
+        }
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn IsDBCSLeadByte(machine: &mut Machine, TestChar: u8) -> bool {
+    machine.state.kernel32.code_page.is_lead_byte(TestChar)
+}
+
+/// Compare strings roughly the way the user locale's collation does, as used by
+/// lstrcmp and CompareString: symbols sort before digits, which sort before letters,
+/// letters compare alphabetically with lowercase ahead of uppercase, and hyphens and
+/// apostrophes only count when the strings are otherwise equal ("word sort").
+pub fn collate(a: &[u16], b: &[u16], ignore_case: bool) -> Ordering {
+    fn chars(s: &[u16]) -> impl Iterator<Item = char> + '_ {
+        char::decode_utf16(s.iter().copied()).map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+    }
+    fn fold(c: char) -> char {
+        c.to_lowercase().next().unwrap()
+    }
+    fn primary(c: char) -> Option<(u8, char)> {
+        if c == '-' || c == '\'' {
+            return None;
+        }
+        let class = if c.is_alphabetic() {
+            2
+        } else if c.is_numeric() {
+            1
+        } else {
+            0
+        };
+        Some((class, fold(c)))
+    }
+    fn is_upper(c: char) -> bool {
+        fold(c) != c
+    }
+
+    let order = chars(a)
+        .filter_map(primary)
+        .cmp(chars(b).filter_map(primary));
+    if ignore_case {
+        order.then_with(|| chars(a).map(fold).cmp(chars(b).map(fold)))
+    } else {
+        order
+            .then_with(|| chars(a).map(is_upper).cmp(chars(b).map(is_upper)))
+            .then_with(|| chars(a).cmp(chars(b)))
+    }
+}
+
+const NORM_IGNORECASE: u32 = 0x1;
+
+/// CompareString result values.
+const CSTR_LESS_THAN: i32 = 1;
+const CSTR_EQUAL: i32 = 2;
+const CSTR_GREATER_THAN: i32 = 3;
+
+fn compare_string(a: &[u16], b: &[u16], flags: u32) -> i32 {
+    if flags & !NORM_IGNORECASE != 0 {
+        log::warn!(
+            "CompareString: ignoring flags {:#x}",
+            flags & !NORM_IGNORECASE
+        );
+    }
+    match collate(a, b, flags & NORM_IGNORECASE != 0) {
+        Ordering::Less => CSTR_LESS_THAN,
+        Ordering::Equal => CSTR_EQUAL,
+        Ordering::Greater => CSTR_GREATER_THAN,
+    }
 }
 
 #[win32_derive::dllexport]
-pub fn IsDBCSLeadByteEx(_machine: &mut Machine, _TestChar: u8, _CodePage: u32) -> bool {
-    // TODO
-    false
+pub fn CompareStringA(
+    machine: &mut Machine,
+    Locale: LCID,
+    dwCmpFlags: u32,
+    lpString1: u32,
+    cchCount1: i32,
+    lpString2: u32,
+    cchCount2: i32,
+) -> i32 {
+    if lpString1 == 0 || lpString2 == 0 {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return 0;
+    }
+    let mem = machine.mem();
+    let read = |addr: u32, len: i32| match len {
+        ..=-1 => mem.strz(addr, u32::MAX),
+        len => mem.try_sub32(addr, len as u32),
+    };
+    let (a, b) = match (read(lpString1, cchCount1), read(lpString2, cchCount2)) {
+        (Ok(a), Ok(b)) => (a, b),
+        (Err(err), _) | (_, Err(err)) => {
+            set_last_error(machine, err.into());
+            return 0;
+        }
+    };
+    let cp = machine.state.kernel32.code_page;
+    let a = cp.decode(a, false, false).unwrap();
+    let b = cp.decode(b, false, false).unwrap();
+    compare_string(&a, &b, dwCmpFlags)
 }
 
 #[win32_derive::dllexport]
-pub fn IsDBCSLeadByte(_machine: &mut Machine, _TestChar: u8) -> bool {
-    false
+pub fn CompareStringW(
+    machine: &mut Machine,
+    Locale: LCID,
+    dwCmpFlags: u32,
+    lpString1: u32,
+    cchCount1: i32,
+    lpString2: u32,
+    cchCount2: i32,
+) -> i32 {
+    if lpString1 == 0 || lpString2 == 0 {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return 0;
+    }
+    let mem = machine.mem();
+    let read = |addr: u32, len: i32| match len {
+        ..=-1 => unsafe { Str16::from_nul_term_ptr(mem, addr) }.unwrap(),
+        len => Str16::from_bytes(
+            mem.try_sub32(addr, len as u32 * 2)
+                .unwrap_or_else(|err| err.raise()),
+        ),
+    };
+    compare_string(
+        read(lpString1, cchCount1),
+        read(lpString2, cchCount2),
+        dwCmpFlags,
+    )
 }
 
 pub type LCID = u32;
@@ -150,3 +687,73 @@ pub fn GetStringTypeW(
 ) -> bool {
     todo!();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16(s: &str) -> Vec<u16> {
+        s.encode_utf16().collect()
+    }
+
+    #[test]
+    fn single_byte() {
+        let cp = CodePage::Windows1252;
+        assert_eq!(cp.decode(b"caf\xE9", false, false), Some(utf16("café")));
+        assert_eq!(
+            cp.encode(&utf16("café"), b"?", false),
+            Some((b"caf\xE9".to_vec(), false))
+        );
+        assert_eq!(
+            cp.encode(&utf16("日"), b"?", false),
+            Some((b"?".to_vec(), true))
+        );
+
+        let cp = CodePage::Ibm437;
+        assert_eq!(
+            cp.decode(b"\xC9\xCD\x01", false, false),
+            Some(utf16("╔═\x01"))
+        );
+        assert_eq!(cp.decode(b"\x01", false, true), Some(utf16("☺")));
+        assert_eq!(
+            cp.encode(&utf16("╔é"), b"?", false),
+            Some((b"\xC9\x82".to_vec(), false))
+        );
+    }
+
+    #[test]
+    fn double_byte() {
+        let cp = CodePage::ShiftJis;
+        let sjis = b"\x93\xFA\x96\x7Ba";
+        assert!(cp.is_lead_byte(sjis[0]) && !cp.is_lead_byte(b'a'));
+        assert_eq!(cp.decode(sjis, false, false), Some(utf16("日本a")));
+        assert_eq!(
+            cp.encode(&utf16("日本a"), b"?", false),
+            Some((sjis.to_vec(), false))
+        );
+        // A truncated double-byte character is invalid.
+        assert_eq!(cp.decode(b"\x93", true, false), None);
+
+        // "ソa\": the trail byte of ソ is a backslash.
+        let mut str = *b"\x83\x5Ca\\";
+        assert_eq!(cp.next_char(&str, 0), 2);
+        assert_eq!(cp.prev_char(&str, 2), 0);
+        assert_eq!(cp.prev_char(&str, 4), 3);
+        cp.change_case(&mut str, false);
+        assert_eq!(&str, b"\x83\x5Ca\\");
+        cp.change_case(&mut str, true);
+        assert_eq!(&str, b"\x83\x5CA\\");
+    }
+
+    #[test]
+    fn collation() {
+        let cmp = |a: &str, b: &str| collate(&utf16(a), &utf16(b), false);
+        assert_eq!(cmp("apple", "Banana"), Ordering::Less);
+        assert_eq!(cmp("apple", "Apple"), Ordering::Less);
+        assert_eq!(cmp("_x", "1"), Ordering::Less);
+        assert_eq!(cmp("9", "a"), Ordering::Less);
+        assert_eq!(cmp("co-op", "coop"), Ordering::Greater);
+        assert_eq!(cmp("co-op", "copy"), Ordering::Less);
+        assert_eq!(collate(&utf16("ABC"), &utf16("abc"), true), Ordering::Equal);
+    }
+}
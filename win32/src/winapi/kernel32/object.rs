@@ -0,0 +1,331 @@
+//! Kernel objects: files, events, threads, etc.
+//!
+//! These share a single handle namespace, so that functions like WaitForSingleObject
+//! and CloseHandle can accept any of them.
+
+use super::{
+    current_thread_object, set_last_error, EventObject, FileHandle, FileMappingObject, FindHandle,
+    MutexObject, PipeEnd, ProcessObject, SemaphoreObject, Thread, CURRENT_PROCESS, CURRENT_THREAD,
+    HPROCESS,
+};
+use crate::{
+    winapi::{
+        types::{HANDLE, HFILE, HFIND},
+        ERROR,
+    },
+    Machine,
+};
+use std::collections::BTreeMap;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct HOBJECTT;
+/// A handle to any kernel object; typed handles like HEVENT share its values.
+pub type HOBJECT = HANDLE<HOBJECTT>;
+
+/// Handles are multiples of 4 from here, like on Windows, which keeps them away from
+/// small integers that a confused program might pass.
+pub const FIRST_OBJECT_HANDLE: u32 = 0x1000;
+/// Handle values keep the generation of their slot in the bits above the slot number.
+const GENERATION_SHIFT: u32 = 20;
+const GENERATION_MASK: u32 = 0xFF;
+const MAX_SLOTS: usize = ((1 << GENERATION_SHIFT) - FIRST_OBJECT_HANDLE as usize) / 4;
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub enum KernelObject {
+    File(FileHandle),
+    Find(FindHandle),
+    Pipe(PipeEnd),
+    Event(EventObject),
+    Thread(Thread),
+    Mutex(MutexObject),
+    Semaphore(SemaphoreObject),
+    FileMapping(FileMappingObject),
+    Process(ProcessObject),
+}
+
+impl KernelObject {
+    /// The name of a named object, which other CreateXXX calls can find it by.
+    pub fn name(&self) -> Option<&str> {
+        let name = match self {
+            KernelObject::Event(ev) => &ev.name,
+            KernelObject::Mutex(mutex) => &mutex.name,
+            KernelObject::Semaphore(sem) => &sem.name,
+            KernelObject::FileMapping(mapping) => &mapping.name,
+            KernelObject::File(_)
+            | KernelObject::Find(_)
+            | KernelObject::Pipe(_)
+            | KernelObject::Thread(_)
+            | KernelObject::Process(_) => return None,
+        };
+        if name.is_empty() {
+            None
+        } else {
+            Some(name)
+        }
+    }
+
+    /// Whether the object must outlive its last handle: a thread runs until it exits,
+    /// and a child process must be kept to run it.
+    fn is_running(&self) -> bool {
+        match self {
+            KernelObject::Thread(thread) => thread.exit_code.is_none(),
+            KernelObject::Process(process) => process.exit_code.is_none(),
+            _ => false,
+        }
+    }
+}
+
+/// Identifies an object independently of its handles, for references that must stay
+/// valid even if the program closes every handle to it.
+#[derive(
+    Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, serde::Serialize, serde::Deserialize,
+)]
+pub struct ObjectId(u32);
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+struct Slot {
+    generation: u32,
+    object: Option<ObjectId>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Entry {
+    object: KernelObject,
+    /// Count of handles referring to the object.
+    handles: u32,
+}
+
+/// The process's handle table.
+///
+/// Each handle is a slot referring to an object, so DuplicateHandle can give an object
+/// more handles and the object lives until the last one is closed.  Closed slots are
+/// reused, but reuse bumps the slot's generation, which is part of the handle value;
+/// a stale handle is then reported rather than finding whatever reused its slot.
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+pub struct ObjectTable {
+    slots: Vec<Slot>,
+    free: Vec<usize>,
+    objects: BTreeMap<ObjectId, Entry>,
+    next_id: u32,
+}
+
+impl ObjectTable {
+    pub fn add(&mut self, object: KernelObject) -> HOBJECT {
+        let id = ObjectId(self.next_id);
+        self.next_id += 1;
+        self.objects.insert(id, Entry { object, handles: 0 });
+        self.new_handle(id)
+    }
+
+    /// Add another handle to an object.
+    pub fn new_handle(&mut self, id: ObjectId) -> HOBJECT {
+        let index = match self.free.pop() {
+            Some(index) => index,
+            None => {
+                // Windows too runs out of handles eventually, but this is likely a leak.
+                assert!(self.slots.len() < MAX_SLOTS, "out of kernel handles");
+                self.slots.push(Slot::default());
+                self.slots.len() - 1
+            }
+        };
+        let slot = &mut self.slots[index];
+        slot.object = Some(id);
+        self.objects.get_mut(&id).unwrap().handles += 1;
+        HOBJECT::from_raw(
+            (slot.generation << GENERATION_SHIFT) | (FIRST_OBJECT_HANDLE + index as u32 * 4),
+        )
+    }
+
+    /// Map a handle to its slot, if it refers to one.  Values that were never handles
+    /// are quietly ignored, but a handle that has been closed is worth a warning.
+    fn slot(&self, handle: HOBJECT) -> Option<usize> {
+        let raw = handle.to_raw();
+        let offset = (raw & ((1 << GENERATION_SHIFT) - 1)).checked_sub(FIRST_OBJECT_HANDLE)?;
+        if offset % 4 != 0 {
+            return None;
+        }
+        let index = offset as usize / 4;
+        let slot = self.slots.get(index)?;
+        if slot.object.is_none() || slot.generation != raw >> GENERATION_SHIFT {
+            log::warn!("use of stale handle {raw:x}");
+            return None;
+        }
+        Some(index)
+    }
+
+    /// The object a handle refers to.
+    pub fn id(&self, handle: HOBJECT) -> Option<ObjectId> {
+        self.slots[self.slot(handle)?].object
+    }
+
+    pub fn get(&self, handle: HOBJECT) -> Option<&KernelObject> {
+        self.object(self.id(handle)?)
+    }
+
+    pub fn get_mut(&mut self, handle: HOBJECT) -> Option<&mut KernelObject> {
+        self.object_mut(self.id(handle)?)
+    }
+
+    pub fn object(&self, id: ObjectId) -> Option<&KernelObject> {
+        self.objects.get(&id).map(|entry| &entry.object)
+    }
+
+    pub fn object_mut(&mut self, id: ObjectId) -> Option<&mut KernelObject> {
+        self.objects.get_mut(&id).map(|entry| &mut entry.object)
+    }
+
+    pub fn file(&self, handle: HFILE) -> Option<&FileHandle> {
+        match self.get(HOBJECT::from_raw(handle.to_raw())) {
+            Some(KernelObject::File(file)) => Some(file),
+            _ => None,
+        }
+    }
+
+    pub fn file_mut(&mut self, handle: HFILE) -> Option<&mut FileHandle> {
+        match self.get_mut(HOBJECT::from_raw(handle.to_raw())) {
+            Some(KernelObject::File(file)) => Some(file),
+            _ => None,
+        }
+    }
+
+    pub fn pipe_mut(&mut self, handle: HFILE) -> Option<&mut PipeEnd> {
+        match self.get_mut(HOBJECT::from_raw(handle.to_raw())) {
+            Some(KernelObject::Pipe(pipe)) => Some(pipe),
+            _ => None,
+        }
+    }
+
+    pub fn find_handle_mut(&mut self, handle: HFIND) -> Option<&mut FindHandle> {
+        match self.get_mut(HOBJECT::from_raw(handle.to_raw())) {
+            Some(KernelObject::Find(find)) => Some(find),
+            _ => None,
+        }
+    }
+
+    /// All objects, each once however many handles it has.
+    pub fn iter(&self) -> impl Iterator<Item = &KernelObject> {
+        self.objects.values().map(|entry| &entry.object)
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut KernelObject> {
+        self.objects.values_mut().map(|entry| &mut entry.object)
+    }
+
+    /// All objects with their ids.
+    pub fn iter_ids(&self) -> impl Iterator<Item = (ObjectId, &KernelObject)> {
+        self.objects.iter().map(|(&id, entry)| (id, &entry.object))
+    }
+
+    /// Find an object matching `pred`.
+    pub fn find(&self, pred: impl Fn(&KernelObject) -> bool) -> Option<ObjectId> {
+        self.objects
+            .iter()
+            .find(|(_, entry)| pred(&entry.object))
+            .map(|(&id, _)| id)
+    }
+
+    /// Close a handle, freeing the object with its last handle unless it's still running.
+    /// Returns false if it wasn't a handle.
+    pub fn close(&mut self, handle: HOBJECT) -> bool {
+        let Some(index) = self.slot(handle) else {
+            return false;
+        };
+        let slot = &mut self.slots[index];
+        let id = slot.object.take().unwrap();
+        slot.generation = (slot.generation + 1) & GENERATION_MASK;
+        self.free.push(index);
+        self.objects.get_mut(&id).unwrap().handles -= 1;
+        self.release(id);
+        true
+    }
+
+    /// Free an object if nothing needs it anymore.  Call this after an object stops
+    /// running, in case the program closed its handles while it still was.
+    pub fn release(&mut self, id: ObjectId) {
+        if let Some(entry) = self.objects.get(&id) {
+            if entry.handles == 0 && !entry.object.is_running() {
+                self.objects.remove(&id);
+            }
+        }
+    }
+}
+
+const DUPLICATE_CLOSE_SOURCE: u32 = 0x1;
+
+#[win32_derive::dllexport]
+pub fn DuplicateHandle(
+    machine: &mut Machine,
+    hSourceProcessHandle: HPROCESS,
+    hSourceHandle: HOBJECT,
+    hTargetProcessHandle: HPROCESS,
+    lpTargetHandle: Option<&mut HOBJECT>,
+    dwDesiredAccess: u32,
+    bInheritHandle: bool,
+    dwOptions: u32,
+) -> bool {
+    if hSourceProcessHandle != CURRENT_PROCESS || hTargetProcessHandle != CURRENT_PROCESS {
+        log::warn!("DuplicateHandle: handles of other processes not implemented");
+        set_last_error(machine, ERROR::NOT_SUPPORTED);
+        return false;
+    }
+    // Duplicating the GetCurrentThread pseudo-handle is how to get a real one.
+    let id = if hSourceHandle.to_raw() == CURRENT_THREAD.to_raw() {
+        Some(current_thread_object(machine))
+    } else {
+        machine.state.kernel32.objects.id(hSourceHandle)
+    };
+    let Some(id) = id else {
+        set_last_error(machine, ERROR::INVALID_HANDLE);
+        return false;
+    };
+
+    // Access rights aren't tracked, so the new handle has the same access regardless.
+    let objects = &mut machine.state.kernel32.objects;
+    let handle = objects.new_handle(id);
+    if dwOptions & DUPLICATE_CLOSE_SOURCE != 0 {
+        objects.close(hSourceHandle);
+    }
+    match lpTargetHandle {
+        Some(target) => *target = handle,
+        // Allowed with DUPLICATE_CLOSE_SOURCE, to close a handle in another process.
+        None => {
+            objects.close(handle);
+        }
+    }
+    set_last_error(machine, ERROR::SUCCESS);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event() -> KernelObject {
+        KernelObject::Event(EventObject::new(String::new(), false, false))
+    }
+
+    #[test]
+    fn stale_handles() {
+        let mut objects = ObjectTable::default();
+        let first = objects.add(event());
+        assert!(objects.close(first));
+        // The slot is reused under a new generation, so the old handle stays dead.
+        let second = objects.add(event());
+        assert_ne!(first, second);
+        assert!(objects.get(first).is_none());
+        assert!(!objects.close(first));
+        assert!(objects.get(second).is_some());
+    }
+
+    #[test]
+    fn duplicate() {
+        let mut objects = ObjectTable::default();
+        let handle = objects.add(event());
+        let id = objects.id(handle).unwrap();
+        let dup = objects.new_handle(id);
+        assert!(objects.close(handle));
+        assert_eq!(objects.id(dup), Some(id));
+        assert!(objects.close(dup));
+        assert!(objects.object(id).is_none());
+    }
+}
@@ -0,0 +1,763 @@
+//! Named pipes.
+//!
+//! Pipes never leave the emulator: each instance of a pipe is a pair of in-memory
+//! queues, one per direction.  The namespace of pipe names is shared by a process and
+//! the children it creates, so a program can talk over a pipe to itself or to a child.
+//!
+//! I/O is synchronous: reads block until there is data, and writes never block.
+
+use super::{block_thread, complete_overlapped, set_last_error, wake_waiters, KernelObject};
+use crate::{
+    machine::Machine,
+    winapi::{
+        stack_args::ArrayWithSizeMut,
+        types::{Str16, HFILE},
+        ERROR,
+    },
+};
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, VecDeque},
+    rc::{Rc, Weak},
+};
+
+const PIPE_PREFIX: &str = r"\\.\pipe\";
+
+const PIPE_ACCESS_INBOUND: u32 = 0x1;
+const PIPE_ACCESS_OUTBOUND: u32 = 0x2;
+const FILE_FLAG_FIRST_PIPE_INSTANCE: u32 = 0x0008_0000;
+
+const PIPE_TYPE_MESSAGE: u32 = 0x4;
+const PIPE_READMODE_MESSAGE: u32 = 0x2;
+const PIPE_NOWAIT: u32 = 0x1;
+
+const PIPE_UNLIMITED_INSTANCES: u32 = 255;
+const NMPWAIT_WAIT_FOREVER: u32 = 0xFFFF_FFFF;
+const NMPWAIT_USE_DEFAULT_WAIT: u32 = 0;
+/// The default timeout for WaitNamedPipe if CreateNamedPipe doesn't give one.
+const DEFAULT_TIMEOUT: u32 = 50;
+
+/// If `path` names a pipe, its name, which like file names is case-insensitive.
+pub fn pipe_name(path: &str) -> Option<String> {
+    let prefix = path.get(..PIPE_PREFIX.len())?;
+    if !prefix.eq_ignore_ascii_case(PIPE_PREFIX) {
+        return None;
+    }
+    Some(path[PIPE_PREFIX.len()..].to_ascii_lowercase())
+}
+
+/// One instance of a named pipe, connecting a server to at most one client.
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+struct Instance {
+    /// Whether writes are kept as separate messages (PIPE_TYPE_MESSAGE).
+    message_type: bool,
+    /// Writes not yet read, client to server and server to client.
+    to_server: VecDeque<Vec<u8>>,
+    to_client: VecDeque<Vec<u8>>,
+    server_open: bool,
+    client_open: bool,
+    /// Whether a client has connected; stays set after the client closes.
+    connected: bool,
+    /// Set when DisconnectNamedPipe cut the client off.
+    disconnected: bool,
+}
+
+impl Instance {
+    fn listening(&self) -> bool {
+        self.server_open && !self.connected
+    }
+}
+
+/// The instances of one pipe name.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct NamedPipe {
+    max_instances: u32,
+    default_timeout: u32,
+    #[serde(with = "crate::snapshot::weak_vec")]
+    instances: Vec<Weak<RefCell<Instance>>>,
+}
+
+impl NamedPipe {
+    /// Instances a server still has open.
+    fn live(&self) -> impl Iterator<Item = Rc<RefCell<Instance>>> + '_ {
+        self.instances
+            .iter()
+            .filter_map(|instance| instance.upgrade())
+            .filter(|instance| instance.borrow().server_open)
+    }
+}
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+struct Namespace {
+    pipes: HashMap<String, NamedPipe>,
+    /// Bumped on every change to any pipe, so processes can tell when to recheck.
+    version: u64,
+}
+
+impl Namespace {
+    /// An instance of the pipe that a client can connect to.
+    fn find_listening(&self, name: &str) -> Result<Rc<RefCell<Instance>>, ERROR> {
+        let pipe = self.pipes.get(name).ok_or(ERROR::FILE_NOT_FOUND)?;
+        let mut live = pipe.live().peekable();
+        if live.peek().is_none() {
+            return Err(ERROR::FILE_NOT_FOUND);
+        }
+        live.find(|instance| instance.borrow().listening())
+            .ok_or(ERROR::PIPE_BUSY)
+    }
+
+    fn register(&mut self, name: &str, instance: &Rc<RefCell<Instance>>) {
+        let pipe = self.pipes.get_mut(name).unwrap();
+        pipe.instances
+            .retain(|instance| instance.strong_count() > 0);
+        pipe.instances.push(Rc::downgrade(instance));
+        self.version += 1;
+    }
+}
+
+/// The pipe namespace, shared between a process and its children.
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+pub struct Pipes {
+    #[serde(with = "crate::snapshot::shared")]
+    namespace: Rc<RefCell<Namespace>>,
+    /// The namespace version this process last saw.
+    seen: Cell<u64>,
+}
+
+impl Pipes {
+    /// The namespace as seen by a child process.
+    pub fn inherit(&self) -> Pipes {
+        Pipes {
+            namespace: self.namespace.clone(),
+            seen: self.seen.clone(),
+        }
+    }
+
+    /// Whether any pipe has changed since the last call, which might have been caused by
+    /// another process (or a handle closing) and so needs our waiting threads woken.
+    pub fn changed(&self) -> bool {
+        let version = self.namespace.borrow().version;
+        self.seen.replace(version) != version
+    }
+}
+
+/// A handle's end of a pipe instance.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct PipeEnd {
+    #[serde(with = "crate::snapshot::shared")]
+    namespace: Rc<RefCell<Namespace>>,
+    name: String,
+    #[serde(with = "crate::snapshot::shared")]
+    instance: Rc<RefCell<Instance>>,
+    server: bool,
+    read: bool,
+    write: bool,
+    /// PIPE_READMODE_MESSAGE: each read returns at most one message.
+    read_messages: bool,
+}
+
+impl PipeEnd {
+    fn peer_open(&self, instance: &Instance) -> bool {
+        if self.server {
+            instance.client_open
+        } else {
+            instance.server_open
+        }
+    }
+
+    /// The error for I/O when there's no peer to talk to, if there isn't.
+    fn check_connected(&self, instance: &Instance) -> Result<(), ERROR> {
+        if self.server && !instance.connected {
+            return Err(ERROR::PIPE_LISTENING);
+        }
+        if !self.server && instance.disconnected {
+            return Err(ERROR::PIPE_NOT_CONNECTED);
+        }
+        Ok(())
+    }
+
+    /// Read what's available, returning None if the read must wait for a write.
+    /// On success, also returns whether a message didn't fit in `buf`.
+    fn try_read(&mut self, buf: &mut [u8]) -> Option<Result<(usize, bool), ERROR>> {
+        if !self.read {
+            return Some(Err(ERROR::ACCESS_DENIED));
+        }
+        let mut instance = self.instance.borrow_mut();
+        if let Err(err) = self.check_connected(&instance) {
+            return Some(Err(err));
+        }
+        let peer_open = self.peer_open(&instance);
+        let queue = if self.server {
+            &mut instance.to_server
+        } else {
+            &mut instance.to_client
+        };
+        if queue.is_empty() {
+            return if peer_open {
+                None
+            } else {
+                Some(Err(ERROR::BROKEN_PIPE))
+            };
+        }
+
+        if self.read_messages {
+            let msg = queue.front_mut().unwrap();
+            let n = msg.len().min(buf.len());
+            buf[..n].copy_from_slice(&msg[..n]);
+            if n < msg.len() {
+                // The rest of the message is left for the next read.
+                msg.drain(..n);
+                return Some(Ok((n, true)));
+            }
+            queue.pop_front();
+            return Some(Ok((n, false)));
+        }
+
+        let mut read = 0;
+        while read < buf.len() {
+            let Some(msg) = queue.front_mut() else {
+                break;
+            };
+            let n = msg.len().min(buf.len() - read);
+            buf[read..read + n].copy_from_slice(&msg[..n]);
+            read += n;
+            if n < msg.len() {
+                msg.drain(..n);
+            } else {
+                queue.pop_front();
+            }
+        }
+        Some(Ok((read, false)))
+    }
+
+    fn write(&mut self, buf: &[u8]) -> Result<usize, ERROR> {
+        if !self.write {
+            return Err(ERROR::ACCESS_DENIED);
+        }
+        let mut instance = self.instance.borrow_mut();
+        self.check_connected(&instance)?;
+        if !self.peer_open(&instance) {
+            return Err(ERROR::NO_DATA);
+        }
+        // In a byte pipe only the bytes matter, so an empty write is nothing.
+        if !buf.is_empty() || instance.message_type {
+            let queue = if self.server {
+                &mut instance.to_client
+            } else {
+                &mut instance.to_server
+            };
+            queue.push_back(buf.to_vec());
+        }
+        drop(instance);
+        self.namespace.borrow_mut().version += 1;
+        Ok(buf.len())
+    }
+
+    /// Copy what a read would return without consuming it, as PeekNamedPipe does.
+    /// Returns the bytes copied, the bytes available, and the bytes of the current
+    /// message left beyond those copied.
+    fn peek(&self, buf: &mut [u8]) -> Result<(usize, usize, usize), ERROR> {
+        let instance = self.instance.borrow();
+        self.check_connected(&instance)?;
+        let queue = if self.server {
+            &instance.to_server
+        } else {
+            &instance.to_client
+        };
+        if queue.is_empty() && !self.peer_open(&instance) {
+            return Err(ERROR::BROKEN_PIPE);
+        }
+        let avail = queue.iter().map(|msg| msg.len()).sum();
+        if instance.message_type {
+            let Some(msg) = queue.front() else {
+                return Ok((0, 0, 0));
+            };
+            let n = msg.len().min(buf.len());
+            buf[..n].copy_from_slice(&msg[..n]);
+            return Ok((n, avail, msg.len() - n));
+        }
+        let mut copied = 0;
+        for byte in queue.iter().flatten().take(buf.len()) {
+            buf[copied] = *byte;
+            copied += 1;
+        }
+        Ok((copied, avail, 0))
+    }
+}
+
+impl Drop for PipeEnd {
+    fn drop(&mut self) {
+        let mut instance = self.instance.borrow_mut();
+        if self.server {
+            instance.server_open = false;
+        } else {
+            instance.client_open = false;
+        }
+        drop(instance);
+        self.namespace.borrow_mut().version += 1;
+    }
+}
+
+fn pipe_end(machine: &mut Machine, handle: HFILE) -> Result<&mut PipeEnd, ERROR> {
+    machine
+        .state
+        .kernel32
+        .objects
+        .pipe_mut(handle)
+        .ok_or(ERROR::INVALID_HANDLE)
+}
+
+/// Like pipe_end, but only for the server end, as for ConnectNamedPipe.
+fn server_end(machine: &mut Machine, handle: HFILE) -> Result<&mut PipeEnd, ERROR> {
+    let end = pipe_end(machine, handle)?;
+    if !end.server {
+        return Err(ERROR::INVALID_PARAMETER);
+    }
+    Ok(end)
+}
+
+/// ReadFile from a pipe, blocking until there's something to read.
+/// Also returns whether a message didn't fit in `buf`.
+pub async fn read_pipe(
+    machine: &mut Machine,
+    handle: HFILE,
+    buf: &mut [u8],
+) -> Result<(usize, bool), ERROR> {
+    loop {
+        if let Some(result) = pipe_end(machine, handle)?.try_read(buf) {
+            return result;
+        }
+        block_thread(machine, None).await;
+    }
+}
+
+/// WriteFile to a pipe.
+pub fn write_pipe(machine: &mut Machine, handle: HFILE, buf: &[u8]) -> Result<usize, ERROR> {
+    let n = pipe_end(machine, handle)?.write(buf)?;
+    wake_waiters(machine);
+    Ok(n)
+}
+
+/// CreateFile of a pipe name, connecting to a listening instance.
+pub fn open_pipe(machine: &mut Machine, name: &str, read: bool, write: bool) -> HFILE {
+    let namespace = machine.state.kernel32.pipes.namespace.clone();
+    let instance = match namespace.borrow().find_listening(name) {
+        Ok(instance) => instance,
+        Err(err) => {
+            set_last_error(machine, err);
+            return HFILE::invalid();
+        }
+    };
+    {
+        let mut instance = instance.borrow_mut();
+        instance.connected = true;
+        instance.client_open = true;
+    }
+    namespace.borrow_mut().version += 1;
+    let end = PipeEnd {
+        namespace,
+        name: name.to_string(),
+        instance,
+        server: false,
+        read,
+        write,
+        // Clients start in byte mode until they ask otherwise.
+        read_messages: false,
+    };
+    let handle = machine.state.kernel32.objects.add(KernelObject::Pipe(end));
+    wake_waiters(machine);
+    set_last_error(machine, ERROR::SUCCESS);
+    HFILE::from_raw(handle.to_raw())
+}
+
+/// Check whether CreateNamedPipe may add another instance of the pipe `name`.
+fn check_new_instance(
+    namespace: &mut Namespace,
+    name: &str,
+    open_mode: u32,
+    max_instances: u32,
+    default_timeout: u32,
+) -> Result<(), ERROR> {
+    let pipe = namespace
+        .pipes
+        .entry(name.to_string())
+        .or_insert_with(|| NamedPipe {
+            max_instances,
+            default_timeout: match default_timeout {
+                0 => DEFAULT_TIMEOUT,
+                timeout => timeout,
+            },
+            instances: Vec::new(),
+        });
+    let live = pipe.live().count() as u32;
+    if live == 0 {
+        // All earlier instances are gone, so this one starts the pipe afresh.
+        pipe.max_instances = max_instances;
+        return Ok(());
+    }
+    if open_mode & FILE_FLAG_FIRST_PIPE_INSTANCE != 0 || max_instances != pipe.max_instances {
+        return Err(ERROR::ACCESS_DENIED);
+    }
+    if pipe.max_instances != PIPE_UNLIMITED_INSTANCES && live >= pipe.max_instances {
+        return Err(ERROR::PIPE_BUSY);
+    }
+    Ok(())
+}
+
+fn create_named_pipe(
+    machine: &mut Machine,
+    name: Option<String>,
+    open_mode: u32,
+    pipe_mode: u32,
+    max_instances: u32,
+    default_timeout: u32,
+) -> HFILE {
+    let Some(name) = name.as_deref().and_then(pipe_name) else {
+        set_last_error(machine, ERROR::INVALID_NAME);
+        return HFILE::invalid();
+    };
+    let message_type = pipe_mode & PIPE_TYPE_MESSAGE != 0;
+    let read_messages = pipe_mode & PIPE_READMODE_MESSAGE != 0;
+    if max_instances == 0
+        || max_instances > PIPE_UNLIMITED_INSTANCES
+        || (read_messages && !message_type)
+    {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return HFILE::invalid();
+    }
+    if pipe_mode & PIPE_NOWAIT != 0 {
+        log::warn!("CreateNamedPipe({name:?}): PIPE_NOWAIT not implemented");
+    }
+
+    let namespace = machine.state.kernel32.pipes.namespace.clone();
+    if let Err(err) = check_new_instance(
+        &mut namespace.borrow_mut(),
+        &name,
+        open_mode,
+        max_instances,
+        default_timeout,
+    ) {
+        set_last_error(machine, err);
+        return HFILE::invalid();
+    }
+
+    let instance = Rc::new(RefCell::new(Instance {
+        message_type,
+        server_open: true,
+        ..Default::default()
+    }));
+    namespace.borrow_mut().register(&name, &instance);
+    let end = PipeEnd {
+        namespace,
+        name,
+        instance,
+        server: true,
+        read: open_mode & PIPE_ACCESS_INBOUND != 0,
+        write: open_mode & PIPE_ACCESS_OUTBOUND != 0,
+        read_messages,
+    };
+    let handle = machine.state.kernel32.objects.add(KernelObject::Pipe(end));
+    set_last_error(machine, ERROR::SUCCESS);
+    HFILE::from_raw(handle.to_raw())
+}
+
+#[win32_derive::dllexport]
+pub fn CreateNamedPipeA(
+    machine: &mut Machine,
+    lpName: Option<&str>,
+    dwOpenMode: u32,
+    dwPipeMode: u32,
+    nMaxInstances: u32,
+    nOutBufferSize: u32,
+    nInBufferSize: u32,
+    nDefaultTimeOut: u32,
+    lpSecurityAttributes: u32,
+) -> HFILE {
+    create_named_pipe(
+        machine,
+        lpName.map(|name| name.to_string()),
+        dwOpenMode,
+        dwPipeMode,
+        nMaxInstances,
+        nDefaultTimeOut,
+    )
+}
+
+#[win32_derive::dllexport]
+pub fn CreateNamedPipeW(
+    machine: &mut Machine,
+    lpName: Option<&Str16>,
+    dwOpenMode: u32,
+    dwPipeMode: u32,
+    nMaxInstances: u32,
+    nOutBufferSize: u32,
+    nInBufferSize: u32,
+    nDefaultTimeOut: u32,
+    lpSecurityAttributes: u32,
+) -> HFILE {
+    create_named_pipe(
+        machine,
+        lpName.map(|name| name.to_string()),
+        dwOpenMode,
+        dwPipeMode,
+        nMaxInstances,
+        nDefaultTimeOut,
+    )
+}
+
+#[win32_derive::dllexport]
+pub async fn ConnectNamedPipe(machine: &mut Machine, hNamedPipe: HFILE, lpOverlapped: u32) -> bool {
+    loop {
+        let end = match server_end(machine, hNamedPipe) {
+            Ok(end) => end,
+            Err(err) => {
+                set_last_error(machine, err);
+                return false;
+            }
+        };
+        // The client got here first, which is still success, or already left.
+        let early = {
+            let instance = end.instance.borrow();
+            instance
+                .connected
+                .then(|| instance.client_open || !instance.to_server.is_empty())
+        };
+        if let Some(still_there) = early {
+            let err = if still_there {
+                ERROR::PIPE_CONNECTED
+            } else {
+                ERROR::NO_DATA
+            };
+            set_last_error(machine, err);
+            return false;
+        }
+        // Like other overlapped I/O, this completes synchronously.
+        block_thread(machine, None).await;
+        if server_end(machine, hNamedPipe).is_ok_and(|end| end.instance.borrow().connected) {
+            break;
+        }
+    }
+    if lpOverlapped != 0 {
+        complete_overlapped(machine, lpOverlapped, 0, true);
+    }
+    set_last_error(machine, ERROR::SUCCESS);
+    true
+}
+
+#[win32_derive::dllexport]
+pub fn DisconnectNamedPipe(machine: &mut Machine, hNamedPipe: HFILE) -> bool {
+    let end = match server_end(machine, hNamedPipe) {
+        Ok(end) => end,
+        Err(err) => {
+            set_last_error(machine, err);
+            return false;
+        }
+    };
+    // The client keeps the old instance, cut off, and the server carries on with a
+    // fresh one for the next client to connect to.
+    let message_type = {
+        let mut old = end.instance.borrow_mut();
+        old.server_open = false;
+        old.disconnected = true;
+        old.to_client.clear();
+        old.message_type
+    };
+    end.instance = Rc::new(RefCell::new(Instance {
+        message_type,
+        server_open: true,
+        ..Default::default()
+    }));
+    end.namespace
+        .borrow_mut()
+        .register(&end.name, &end.instance);
+    wake_waiters(machine);
+    set_last_error(machine, ERROR::SUCCESS);
+    true
+}
+
+async fn wait_named_pipe(machine: &mut Machine, name: Option<String>, timeout: u32) -> bool {
+    let Some(name) = name.as_deref().and_then(pipe_name) else {
+        set_last_error(machine, ERROR::INVALID_NAME);
+        return false;
+    };
+    let mut deadline = None;
+    loop {
+        let (result, default_timeout) = {
+            let namespace = machine.state.kernel32.pipes.namespace.borrow();
+            let result = namespace.find_listening(&name).map(|_| ());
+            let default_timeout = namespace.pipes.get(&name).map(|pipe| pipe.default_timeout);
+            (result, default_timeout)
+        };
+        match result {
+            Ok(_) => break,
+            Err(ERROR::PIPE_BUSY) => {}
+            Err(err) => {
+                set_last_error(machine, err);
+                return false;
+            }
+        }
+
+        let now = machine.ticks();
+        let deadline = *deadline.get_or_insert(match timeout {
+            NMPWAIT_WAIT_FOREVER => None,
+            NMPWAIT_USE_DEFAULT_WAIT => Some(now + default_timeout.unwrap()),
+            ms => Some(now + ms),
+        });
+        if deadline.is_some_and(|deadline| now >= deadline) {
+            set_last_error(machine, ERROR::SEM_TIMEOUT);
+            return false;
+        }
+        block_thread(machine, deadline).await;
+    }
+    set_last_error(machine, ERROR::SUCCESS);
+    true
+}
+
+#[win32_derive::dllexport]
+pub async fn WaitNamedPipeA(
+    machine: &mut Machine,
+    lpNamedPipeName: Option<&str>,
+    nTimeOut: u32,
+) -> bool {
+    let name = lpNamedPipeName.map(|name| name.to_string());
+    wait_named_pipe(machine, name, nTimeOut).await
+}
+
+#[win32_derive::dllexport]
+pub async fn WaitNamedPipeW(
+    machine: &mut Machine,
+    lpNamedPipeName: Option<&Str16>,
+    nTimeOut: u32,
+) -> bool {
+    let name = lpNamedPipeName.map(|name| name.to_string());
+    wait_named_pipe(machine, name, nTimeOut).await
+}
+
+#[win32_derive::dllexport]
+pub fn PeekNamedPipe(
+    machine: &mut Machine,
+    hNamedPipe: HFILE,
+    lpBuffer: ArrayWithSizeMut<u8>,
+    lpBytesRead: Option<&mut u32>,
+    lpTotalBytesAvail: Option<&mut u32>,
+    lpBytesLeftThisMessage: Option<&mut u32>,
+) -> bool {
+    let buf = lpBuffer.to_option().unwrap_or_default();
+    let (copied, avail, left) = match pipe_end(machine, hNamedPipe).and_then(|end| end.peek(buf)) {
+        Ok(result) => result,
+        Err(err) => {
+            set_last_error(machine, err);
+            return false;
+        }
+    };
+    if let Some(out) = lpBytesRead {
+        *out = copied as u32;
+    }
+    if let Some(out) = lpTotalBytesAvail {
+        *out = avail as u32;
+    }
+    if let Some(out) = lpBytesLeftThisMessage {
+        *out = left as u32;
+    }
+    set_last_error(machine, ERROR::SUCCESS);
+    true
+}
+
+#[win32_derive::dllexport]
+pub fn SetNamedPipeHandleState(
+    machine: &mut Machine,
+    hNamedPipe: HFILE,
+    lpMode: Option<&u32>,
+    lpMaxCollectionCount: u32,
+    lpCollectDataTimeout: u32,
+) -> bool {
+    let end = match pipe_end(machine, hNamedPipe) {
+        Ok(end) => end,
+        Err(err) => {
+            set_last_error(machine, err);
+            return false;
+        }
+    };
+    if let Some(&mode) = lpMode {
+        let read_messages = mode & PIPE_READMODE_MESSAGE != 0;
+        if read_messages && !end.instance.borrow().message_type {
+            set_last_error(machine, ERROR::INVALID_PARAMETER);
+            return false;
+        }
+        end.read_messages = read_messages;
+        if mode & PIPE_NOWAIT != 0 {
+            log::warn!("SetNamedPipeHandleState: PIPE_NOWAIT not implemented");
+        }
+    }
+    set_last_error(machine, ERROR::SUCCESS);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A connected server and client end of one instance.
+    fn connect(message_type: bool) -> (PipeEnd, PipeEnd) {
+        let namespace = Rc::new(RefCell::new(Namespace::default()));
+        let instance = Rc::new(RefCell::new(Instance {
+            message_type,
+            server_open: true,
+            client_open: true,
+            connected: true,
+            ..Default::default()
+        }));
+        let end = |server| PipeEnd {
+            namespace: namespace.clone(),
+            name: "test".into(),
+            instance: instance.clone(),
+            server,
+            read: true,
+            write: true,
+            read_messages: message_type,
+        };
+        (end(true), end(false))
+    }
+
+    #[test]
+    fn messages() {
+        let (mut server, mut client) = connect(true);
+        client.write(b"hello").unwrap();
+        client.write(b"world").unwrap();
+        let mut buf = [0u8; 3];
+        // A message too big for the buffer is read in pieces.
+        assert!(matches!(server.try_read(&mut buf), Some(Ok((3, true)))));
+        assert_eq!(&buf, b"hel");
+        assert!(matches!(server.try_read(&mut buf), Some(Ok((2, false)))));
+        assert_eq!(&buf[..2], b"lo");
+        let mut buf = [0u8; 16];
+        assert!(matches!(server.try_read(&mut buf), Some(Ok((5, false)))));
+        assert!(server.try_read(&mut buf).is_none());
+    }
+
+    #[test]
+    fn bytes() {
+        let (mut server, mut client) = connect(false);
+        server.write(b"hello").unwrap();
+        server.write(b"world").unwrap();
+        let mut buf = [0u8; 16];
+        assert!(matches!(client.peek(&mut buf), Ok((10, 10, 0))));
+        assert!(matches!(client.try_read(&mut buf), Some(Ok((10, false)))));
+        assert_eq!(&buf[..10], b"helloworld");
+    }
+
+    #[test]
+    fn broken() {
+        let (mut server, mut client) = connect(false);
+        client.write(b"bye").unwrap();
+        drop(client);
+        let mut buf = [0u8; 16];
+        // Data written before the close can still be read.
+        assert!(matches!(server.try_read(&mut buf), Some(Ok((3, false)))));
+        assert!(matches!(
+            server.try_read(&mut buf),
+            Some(Err(ERROR::BROKEN_PIPE))
+        ));
+        assert!(matches!(server.write(b"hi"), Err(ERROR::NO_DATA)));
+    }
+}
@@ -0,0 +1,425 @@
+//! Child processes.
+//!
+//! CreateProcess loads the child exe into a Machine of its own, sharing our host,
+//! which is then stepped alongside ours by run_child_processes().
+
+use super::{set_last_error, vfs, wake_waiters, KernelObject, HOBJECT, STILL_ACTIVE};
+use crate::{
+    host,
+    str16::Str16,
+    winapi::{handle::HANDLE, types::DWORD, ERROR},
+    Machine,
+};
+use std::sync::atomic::{AtomicU32, Ordering};
+use typed_path::WindowsPath;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct HPROCESST;
+pub type HPROCESS = HANDLE<HPROCESST>;
+
+/// The pseudo-handle returned by GetCurrentProcess.
+pub(super) const CURRENT_PROCESS: HPROCESS = HPROCESS::from_raw(-1i32 as u32);
+
+/// Process ids are unique across all the processes of an emulator, nested or not.
+static NEXT_PROCESS_ID: AtomicU32 = AtomicU32::new(2);
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct ProcessObject {
+    pub id: u32,
+    /// The running child, dropped once it exits.
+    #[serde(skip)]
+    machine: Option<Box<Machine>>,
+    pub exit_code: Option<u32>,
+}
+
+impl ProcessObject {
+    fn finish(&mut self, exit_code: u32) {
+        self.machine = None;
+        self.exit_code = Some(exit_code);
+    }
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct PROCESS_INFORMATION {
+    hProcess: HPROCESS,
+    hThread: DWORD,
+    dwProcessId: DWORD,
+    dwThreadId: DWORD,
+}
+unsafe impl ::memory::Pod for PROCESS_INFORMATION {}
+
+fn get_process(machine: &mut Machine, handle: HPROCESS) -> Option<&mut ProcessObject> {
+    match machine
+        .state
+        .kernel32
+        .objects
+        .get_mut(HOBJECT::from_raw(handle.to_raw()))
+    {
+        Some(KernelObject::Process(process)) => Some(process),
+        _ => None,
+    }
+}
+
+/// Split the exe name off the front of a command line, as CreateProcess does when
+/// it isn't given an application name.
+fn exe_from_cmdline(cmdline: &str) -> String {
+    let cmdline = cmdline.trim_start();
+    let exe = match cmdline.strip_prefix('"') {
+        Some(rest) => rest.split('"').next().unwrap(),
+        None => cmdline.split([' ', '\t']).next().unwrap(),
+    };
+    let name = exe.rsplit('\\').next().unwrap();
+    if name.contains('.') {
+        exe.to_string()
+    } else {
+        format!("{exe}.exe")
+    }
+}
+
+/// Find and read the exe, searching like LoadLibrary does when the path is relative.
+fn read_exe(machine: &Machine, exe: &str) -> Option<Vec<u8>> {
+    let mut paths = vec![exe.to_string()];
+    if !exe.contains('\\') {
+        let ours = machine.state.kernel32.cmdline.args.first().unwrap();
+        let our_dir = ours.rsplitn(2, '\\').last().unwrap();
+        paths.insert(0, format!("{our_dir}\\{exe}"));
+    }
+    for path in &paths {
+        let Ok(mut file) = vfs(machine).open(WindowsPath::new(path), host::FileOptions::read())
+        else {
+            continue;
+        };
+        let mut buf = Vec::new();
+        if file.read_to_end(&mut buf).is_ok() {
+            return Some(buf);
+        }
+    }
+    None
+}
+
+#[cfg(feature = "x86-emu")]
+fn spawn(
+    machine: &mut Machine,
+    exe: &str,
+    buf: &[u8],
+    cmdline: String,
+    cwd: Option<&str>,
+) -> Result<Box<Machine>, ERROR> {
+    let mut child = Box::new(Machine::new(machine.host.clone_host(), cmdline));
+    child.external_dlls = machine.external_dlls.clone();
+    child.set_clock_mode(machine.clock.mode());
+    child.clock.tsc_mhz = machine.clock.tsc_mhz;
+    child.emu.lenient = machine.emu.lenient;
+    child.emu.x86.set_cpu_profile(machine.emu.x86.cpu_profile());
+    child.state.kernel32.vfs = machine.state.kernel32.vfs.inherit();
+    child.state.kernel32.pipes = machine.state.kernel32.pipes.inherit();
+    child.state.kernel32.system = machine.state.kernel32.system.clone();
+    if let Some(cwd) = cwd {
+        super::set_current_dir(&mut child, cwd)?;
+    }
+
+    let name = exe.rsplit('\\').next().unwrap();
+    child
+        .load_exe(buf, std::path::Path::new(name), None)
+        .map_err(|err| {
+            log::warn!("CreateProcess({exe:?}): {err}");
+            ERROR::BAD_EXE_FORMAT
+        })?;
+
+    // A console child shares our console, including any std handles redirected to it.
+    let ours = &machine.state.kernel32.console;
+    let console = &mut child.state.kernel32.console;
+    if ours.attached && console.attached {
+        for (theirs, &ours) in console.std_handles.iter_mut().zip(&ours.std_handles) {
+            if matches!(
+                ours,
+                super::STDIN_HFILE | super::STDOUT_HFILE | super::STDERR_HFILE
+            ) {
+                *theirs = ours;
+            } else {
+                // File handles live in our Machine, so the child can't use them.
+                log::warn!("CreateProcess({exe:?}): not inheriting std handle {ours:?}");
+            }
+        }
+    }
+    Ok(child)
+}
+
+#[cfg(not(feature = "x86-emu"))]
+fn spawn(
+    _machine: &mut Machine,
+    exe: &str,
+    _buf: &[u8],
+    _cmdline: String,
+    _cwd: Option<&str>,
+) -> Result<Box<Machine>, ERROR> {
+    log::warn!("CreateProcess({exe:?}): child processes need the x86-emu backend");
+    Err(ERROR::NOT_SUPPORTED)
+}
+
+fn create_process(
+    machine: &mut Machine,
+    application_name: Option<String>,
+    command_line: Option<String>,
+    creation_flags: u32,
+    current_directory: Option<String>,
+    info: Option<&mut PROCESS_INFORMATION>,
+) -> bool {
+    let exe = match (&application_name, &command_line) {
+        (Some(app), _) => app.clone(),
+        (None, Some(cmdline)) => exe_from_cmdline(cmdline),
+        (None, None) => {
+            set_last_error(machine, ERROR::INVALID_PARAMETER);
+            return false;
+        }
+    };
+    let cmdline = command_line.unwrap_or_else(|| format!("\"{exe}\""));
+    if creation_flags != 0 {
+        log::warn!("CreateProcess({exe:?}): ignoring creation flags {creation_flags:#x}");
+    }
+
+    let Some(buf) = read_exe(machine, &exe) else {
+        log::debug!("CreateProcess({exe:?}): not found");
+        set_last_error(machine, ERROR::FILE_NOT_FOUND);
+        return false;
+    };
+    let mut child = match spawn(machine, &exe, &buf, cmdline, current_directory.as_deref()) {
+        Ok(child) => child,
+        Err(err) => {
+            set_last_error(machine, err);
+            return false;
+        }
+    };
+
+    let id = NEXT_PROCESS_ID.fetch_add(1, Ordering::Relaxed);
+    child.state.kernel32.process_id = id;
+    let objects = &mut machine.state.kernel32.objects;
+    let handle = objects.add(KernelObject::Process(ProcessObject {
+        id,
+        machine: Some(child),
+        exit_code: None,
+    }));
+    let object = objects.id(handle).unwrap();
+    machine.state.kernel32.child_processes.push(object);
+
+    if let Some(info) = info {
+        info.hProcess = HPROCESS::from_raw(handle.to_raw());
+        // There's no separate object for the child's main thread, so hThread is another
+        // handle to the process; waiting on either waits for the child to exit, and
+        // each is closed separately.
+        info.hThread = objects.new_handle(object).to_raw();
+        info.dwProcessId = id;
+        info.dwThreadId = super::MAIN_THREAD_ID;
+    }
+    set_last_error(machine, ERROR::SUCCESS);
+    true
+}
+
+#[win32_derive::dllexport]
+pub fn CreateProcessA(
+    machine: &mut Machine,
+    lpApplicationName: Option<&str>,
+    lpCommandLine: Option<&str>,
+    lpProcessAttributes: u32,
+    lpThreadAttributes: u32,
+    bInheritHandles: bool,
+    dwCreationFlags: u32,
+    lpEnvironment: u32,
+    lpCurrentDirectory: Option<&str>,
+    lpStartupInfo: u32,
+    lpProcessInformation: Option<&mut PROCESS_INFORMATION>,
+) -> bool {
+    create_process(
+        machine,
+        lpApplicationName.map(|s| s.to_string()),
+        lpCommandLine.map(|s| s.to_string()),
+        dwCreationFlags,
+        lpCurrentDirectory.map(|s| s.to_string()),
+        lpProcessInformation,
+    )
+}
+
+#[win32_derive::dllexport]
+pub fn CreateProcessW(
+    machine: &mut Machine,
+    lpApplicationName: Option<&Str16>,
+    lpCommandLine: Option<&Str16>,
+    lpProcessAttributes: u32,
+    lpThreadAttributes: u32,
+    bInheritHandles: bool,
+    dwCreationFlags: u32,
+    lpEnvironment: u32,
+    lpCurrentDirectory: Option<&Str16>,
+    lpStartupInfo: u32,
+    lpProcessInformation: Option<&mut PROCESS_INFORMATION>,
+) -> bool {
+    create_process(
+        machine,
+        lpApplicationName.map(|s| s.to_string()),
+        lpCommandLine.map(|s| s.to_string()),
+        dwCreationFlags,
+        lpCurrentDirectory.map(|s| s.to_string()),
+        lpProcessInformation,
+    )
+}
+
+/// How the child processes stand after a run_child_processes().
+#[derive(Debug, PartialEq, Eq)]
+pub enum ChildProcesses {
+    None,
+    /// At least one child ran.
+    Running,
+    /// Every child is blocked, until the given host ticks or indefinitely.
+    Blocked(Option<u32>),
+}
+
+impl super::State {
+    pub fn has_child_processes(&self) -> bool {
+        !self.child_processes.is_empty()
+    }
+
+    /// Have blocked children recheck what they wait for on their next run, because we
+    /// ran and may have done something they wait for, or because the host woke.
+    pub fn wake_child_processes(&mut self) {
+        self.wake_children = true;
+    }
+}
+
+/// Run a block of each live child process.
+#[cfg(feature = "x86-emu")]
+pub fn run_child_processes(machine: &mut Machine) -> ChildProcesses {
+    if machine.state.kernel32.child_processes.is_empty() {
+        return ChildProcesses::None;
+    }
+    let wake = std::mem::take(&mut machine.state.kernel32.wake_children);
+    let host_ticks = machine.host.ticks();
+    let mut running = false;
+    let mut soonest: Option<u32> = None;
+    let mut exited = Vec::new();
+    let objects = &mut machine.state.kernel32.objects;
+    machine.state.kernel32.child_processes.retain(|&id| {
+        let Some(KernelObject::Process(process)) = objects.object_mut(id) else {
+            return false;
+        };
+        let child = process.machine.as_mut().unwrap();
+        // Rather than letting a blocked child block the host on our behalf, leave it be
+        // until something may have changed for it, and block for it ourselves when all
+        // of us are idle.
+        if wake {
+            child.unblock();
+        }
+        if let Some(deadline) = child.idle_until() {
+            if deadline.is_none_or(|deadline| host_ticks < deadline) {
+                // A wait with no deadline comes after any deadline.
+                soonest = match (soonest, deadline) {
+                    (Some(a), Some(b)) => Some(a.min(b)),
+                    (a, b) => a.or(b),
+                };
+                return true;
+            }
+            child.unblock();
+        }
+        running = true;
+        child.run();
+        let exit_code = match &child.status {
+            crate::Status::Exit(code) => *code,
+            crate::Status::Error { message } => {
+                log::error!("process {}: {message}", process.id);
+                1
+            }
+            _ => return true,
+        };
+        process.finish(exit_code);
+        exited.push(id);
+        false
+    });
+    for &id in &exited {
+        // The program may have closed its handles to the process while it ran.
+        machine.state.kernel32.objects.release(id);
+    }
+    // A child may also have used a pipe that one of our threads is waiting on.
+    if !exited.is_empty() || machine.state.kernel32.pipes.changed() {
+        wake_waiters(machine);
+    }
+    if running {
+        ChildProcesses::Running
+    } else if machine.state.kernel32.child_processes.is_empty() {
+        ChildProcesses::None
+    } else {
+        ChildProcesses::Blocked(soonest)
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn GetCurrentProcess(_machine: &mut Machine) -> HPROCESS {
+    CURRENT_PROCESS
+}
+
+#[win32_derive::dllexport]
+pub fn GetCurrentProcessId(machine: &mut Machine) -> u32 {
+    machine.state.kernel32.process_id
+}
+
+#[win32_derive::dllexport]
+pub fn GetProcessId(machine: &mut Machine, Process: HPROCESS) -> u32 {
+    if Process == CURRENT_PROCESS {
+        return machine.state.kernel32.process_id;
+    }
+    match get_process(machine, Process) {
+        Some(process) => process.id,
+        None => {
+            set_last_error(machine, ERROR::INVALID_HANDLE);
+            0
+        }
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn GetExitCodeProcess(
+    machine: &mut Machine,
+    hProcess: HPROCESS,
+    lpExitCode: Option<&mut u32>,
+) -> bool {
+    let exit_code = if hProcess == CURRENT_PROCESS {
+        STILL_ACTIVE
+    } else {
+        match get_process(machine, hProcess) {
+            Some(process) => process.exit_code.unwrap_or(STILL_ACTIVE),
+            None => {
+                set_last_error(machine, ERROR::INVALID_HANDLE);
+                return false;
+            }
+        }
+    };
+    match lpExitCode {
+        Some(out) => *out = exit_code,
+        None => {
+            set_last_error(machine, ERROR::INVALID_PARAMETER);
+            return false;
+        }
+    }
+    true
+}
+
+#[win32_derive::dllexport]
+pub fn TerminateProcess(machine: &mut Machine, hProcess: HPROCESS, uExitCode: u32) -> bool {
+    if hProcess == CURRENT_PROCESS {
+        machine.exit(uExitCode);
+        return true;
+    }
+    let Some(process) = get_process(machine, hProcess) else {
+        set_last_error(machine, ERROR::INVALID_HANDLE);
+        return false;
+    };
+    if process.exit_code.is_some() {
+        set_last_error(machine, ERROR::ACCESS_DENIED);
+        return false;
+    }
+    process.finish(uExitCode);
+    let kernel32 = &mut machine.state.kernel32;
+    let id = kernel32.objects.id(HOBJECT::from_raw(hProcess.to_raw()));
+    kernel32.child_processes.retain(|&child| Some(child) != id);
+    wake_waiters(machine);
+    true
+}
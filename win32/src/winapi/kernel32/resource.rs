@@ -1,6 +1,6 @@
 #![allow(non_snake_case)]
 
-use crate::winapi::kernel32::HMODULE;
+use crate::winapi::kernel32::{set_last_error, HMODULE};
 use crate::winapi::types::HRSRC;
 use crate::winapi::user32::HINSTANCE;
 use crate::{
@@ -9,6 +9,7 @@ use crate::{
         kernel32,
         stack_args::FromArg,
         types::{Str16, String16},
+        ERROR,
     },
     Machine,
 };
@@ -19,6 +20,7 @@ fn IS_INTRESOURCE(x: u32) -> bool {
     x >> 16 == 0
 }
 
+#[derive(serde::Serialize, serde::Deserialize)]
 pub struct ResourceHandle(Range<u32>);
 
 /// ResourceKey is the type of queries into the Windows resources system, including
@@ -124,7 +126,10 @@ pub fn FindResourceW(
         lpType,
         lpName,
     ) {
-        None => HRSRC::null(),
+        None => {
+            set_last_error(machine, ERROR::RESOURCE_NAME_NOT_FOUND);
+            HRSRC::null()
+        }
         Some(mem) => machine
             .state
             .kernel32
@@ -141,7 +146,10 @@ pub fn LoadResource(machine: &mut Machine, hModule: HMODULE, hResInfo: HRSRC) ->
 #[win32_derive::dllexport]
 pub fn LockResource(machine: &mut Machine, hResData: HRSRC) -> u32 {
     match machine.state.kernel32.resource_handles.get(hResData) {
-        None => 0,
+        None => {
+            set_last_error(machine, ERROR::INVALID_HANDLE);
+            0
+        }
         Some(handle) => handle.0.start,
     }
 }
@@ -149,7 +157,10 @@ pub fn LockResource(machine: &mut Machine, hResData: HRSRC) -> u32 {
 #[win32_derive::dllexport]
 pub fn SizeofResource(machine: &mut Machine, hModule: HMODULE, hResInfo: HRSRC) -> u32 {
     match machine.state.kernel32.resource_handles.get(hResInfo) {
-        None => 0,
+        None => {
+            set_last_error(machine, ERROR::INVALID_HANDLE);
+            0
+        }
         Some(handle) => handle.0.len() as u32,
     }
 }
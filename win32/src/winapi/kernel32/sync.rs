@@ -1,15 +1,394 @@
-//! Synchronization.  Currently all no-ops as we don't support threads.
+//! Synchronization.
 
-use crate::{winapi::types::HEVENT, Machine};
+use super::{
+    block_thread, current_thread, deliver_apcs, set_last_error, wake_waiters, KernelObject, HOBJECT,
+};
+use crate::{
+    winapi::{
+        types::{Str16, HEVENT},
+        ERROR,
+    },
+    Machine,
+};
+use memory::Extensions;
 
+#[derive(serde::Serialize, serde::Deserialize)]
 pub struct EventObject {
-    name: String,
-    state: bool,
+    pub name: String,
+    manual_reset: bool,
+    signaled: bool,
+    /// Ids of the threads currently blocked on the event, in the order they started waiting.
+    waiters: Vec<u32>,
+    /// Ids of the threads PulseEvent released, which the event counts as signaled for until
+    /// their wait next checks it.
+    released: Vec<u32>,
+}
+
+impl EventObject {
+    pub fn new(name: String, manual_reset: bool, signaled: bool) -> Self {
+        EventObject {
+            name,
+            manual_reset,
+            signaled,
+            waiters: Vec::new(),
+            released: Vec::new(),
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct MutexObject {
+    pub name: String,
+    /// Id of the owning thread, or 0 if unowned.
+    owner: u32,
+    /// Number of times the owner has acquired the mutex.
+    count: u32,
+    /// Set when the owner exited without releasing the mutex, until the next acquisition.
+    abandoned: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct SemaphoreObject {
+    pub name: String,
+    count: u32,
+    max: u32,
+}
+
+pub const INFINITE: u32 = 0xFFFF_FFFF;
+pub const MAXIMUM_WAIT_OBJECTS: u32 = 64;
+
+const WAIT_OBJECT_0: u32 = 0;
+const WAIT_ABANDONED_0: u32 = 0x80;
+pub const WAIT_IO_COMPLETION: u32 = 0xC0;
+const WAIT_TIMEOUT: u32 = 0x102;
+pub const WAIT_FAILED: u32 = 0xFFFF_FFFF;
+
+fn event_object(machine: &mut Machine, handle: HEVENT) -> Option<&mut EventObject> {
+    match machine
+        .state
+        .kernel32
+        .objects
+        .get_mut(HOBJECT::from_raw(handle.to_raw()))
+    {
+        Some(KernelObject::Event(ev)) => Some(ev),
+        _ => None,
+    }
+}
+
+/// Whether an object can be waited on at all; waiting on any other kind fails.
+fn is_waitable(obj: &KernelObject) -> bool {
+    !matches!(obj, KernelObject::Find(_) | KernelObject::FileMapping(_))
+}
+
+/// Check whether a waited-on object is signaled for the given thread, without consuming the signal.
+fn is_signaled(obj: &KernelObject, thread_id: u32) -> bool {
+    match obj {
+        KernelObject::Thread(thread) => thread.exit_code.is_some(),
+        KernelObject::Process(process) => process.exit_code.is_some(),
+        KernelObject::Event(ev) => ev.signaled,
+        KernelObject::Mutex(mutex) => mutex.owner == 0 || mutex.owner == thread_id,
+        KernelObject::Semaphore(sem) => sem.count > 0,
+        // I/O is synchronous, so a file is never waiting on any.
+        KernelObject::File(_) | KernelObject::Pipe(_) => true,
+        // Rejected by wait_for_objects.
+        KernelObject::Find(_) | KernelObject::FileMapping(_) => false,
+    }
+}
+
+/// Take a PulseEvent release of the thread from an event, returning whether there was one.
+/// A pulse only counts at the first check after it, so the caller must take these
+/// whether or not the wait ends.
+fn take_release(obj: &mut KernelObject, thread_id: u32) -> bool {
+    let KernelObject::Event(ev) = obj else {
+        return false;
+    };
+    match ev.released.iter().position(|&id| id == thread_id) {
+        Some(index) => {
+            ev.released.remove(index);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Consume the signal of an object that a wait was satisfied by.
+/// Returns true if the object was an abandoned mutex.
+fn acquire(obj: &mut KernelObject, thread_id: u32) -> bool {
+    match obj {
+        KernelObject::Event(ev) if !ev.manual_reset => ev.signaled = false,
+        KernelObject::Mutex(mutex) => {
+            mutex.owner = thread_id;
+            mutex.count += 1;
+            return std::mem::take(&mut mutex.abandoned);
+        }
+        KernelObject::Semaphore(sem) => sem.count -= 1,
+        _ => {}
+    }
+    false
+}
+
+/// Release the mutexes owned by an exiting thread, marking them abandoned.
+pub fn abandon_mutexes(machine: &mut Machine, thread_id: u32) {
+    for obj in machine.state.kernel32.objects.iter_mut() {
+        if let KernelObject::Mutex(mutex) = obj {
+            if mutex.owner == thread_id {
+                mutex.owner = 0;
+                mutex.count = 0;
+                mutex.abandoned = true;
+            }
+        }
+    }
+}
+
+fn add_waiter(obj: &mut KernelObject, thread_id: u32) {
+    if let KernelObject::Event(ev) = obj {
+        ev.waiters.push(thread_id);
+    }
+}
+
+fn remove_waiter(obj: &mut KernelObject, thread_id: u32) {
+    if let KernelObject::Event(ev) = obj {
+        if let Some(index) = ev.waiters.iter().position(|&id| id == thread_id) {
+            ev.waiters.remove(index);
+        }
+    }
+}
+
+/// Read the handle array of a multiple-object wait, failing the call (as the caller must
+/// then do with WAIT_FAILED) if the count isn't within `counts` or the array is unreadable.
+pub fn wait_handles(
+    machine: &mut Machine,
+    count: u32,
+    addr: u32,
+    counts: std::ops::RangeInclusive<u32>,
+) -> Option<Vec<HOBJECT>> {
+    if !counts.contains(&count) {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return None;
+    }
+    match machine.mem().try_sub32(addr, count * 4) {
+        Ok(buf) => Some(buf.into_iter_pod::<HOBJECT>().collect()),
+        Err(err) => {
+            set_last_error(machine, err.into());
+            None
+        }
+    }
+}
+
+/// Checks for window messages in MsgWaitForMultipleObjects: Ok if input is available,
+/// otherwise the time of the next pending timer message, if any.
+pub type MessageCheck = fn(&mut Machine) -> Result<(), Option<u32>>;
+
+/// Block the current thread until one of `handles` is signaled, or all of them for `wait_all`.
+/// With `messages`, queued window messages also end the wait, returning WAIT_OBJECT_0 + handles.len().
+/// An `alertable` wait runs queued APCs instead, returning WAIT_IO_COMPLETION.
+pub async fn wait_for_objects(
+    machine: &mut Machine,
+    handles: &[HOBJECT],
+    wait_all: bool,
+    timeout: u32,
+    alertable: bool,
+    messages: Option<MessageCheck>,
+) -> u32 {
+    let deadline = match timeout {
+        INFINITE => None,
+        ms => Some(machine.ticks() + ms),
+    };
+    let thread_id = current_thread(machine).id;
+    for &handle in handles {
+        if !machine
+            .state
+            .kernel32
+            .objects
+            .get(handle)
+            .is_some_and(is_waitable)
+        {
+            set_last_error(machine, ERROR::INVALID_HANDLE);
+            return WAIT_FAILED;
+        }
+    }
+
+    loop {
+        // Pulses that happened while we were blocked count now or never.
+        let mut pulsed = Vec::with_capacity(handles.len());
+        for &handle in handles {
+            let obj = machine.state.kernel32.objects.get_mut(handle);
+            pulsed.push(obj.is_some_and(|obj| take_release(obj, thread_id)));
+        }
+
+        if alertable && deliver_apcs(machine).await {
+            return WAIT_IO_COMPLETION;
+        }
+
+        let objects = &mut machine.state.kernel32.objects;
+        let mut signaled = Vec::with_capacity(handles.len());
+        for (&handle, &pulsed) in handles.iter().zip(&pulsed) {
+            // The handle may have been closed while we were waiting.
+            let Some(obj) = objects.get(handle) else {
+                set_last_error(machine, ERROR::INVALID_HANDLE);
+                return WAIT_FAILED;
+            };
+            signaled.push(pulsed || is_signaled(obj, thread_id));
+        }
+
+        let mut wait = deadline;
+        let message = match messages {
+            Some(check) => match check(machine) {
+                Ok(()) => true,
+                Err(timer) => {
+                    wait = match (wait, timer) {
+                        (Some(a), Some(b)) => Some(a.min(b)),
+                        (a, b) => a.or(b),
+                    };
+                    false
+                }
+            },
+            None => false,
+        };
+
+        let now = machine.ticks();
+        let objects = &mut machine.state.kernel32.objects;
+        if wait_all {
+            // Both the objects and (if requested) a message are required.
+            if signaled.iter().all(|&s| s) && (messages.is_none() || message) {
+                let mut abandoned = false;
+                for (&handle, &pulsed) in handles.iter().zip(&pulsed) {
+                    // A pulse released the thread without leaving anything to consume.
+                    if !pulsed {
+                        abandoned |= acquire(objects.get_mut(handle).unwrap(), thread_id);
+                    }
+                }
+                return if abandoned {
+                    WAIT_ABANDONED_0
+                } else {
+                    WAIT_OBJECT_0
+                };
+            }
+        } else {
+            if let Some(index) = signaled.iter().position(|&s| s) {
+                if !pulsed[index] && acquire(objects.get_mut(handles[index]).unwrap(), thread_id) {
+                    return WAIT_ABANDONED_0 + index as u32;
+                }
+                return WAIT_OBJECT_0 + index as u32;
+            }
+            if message {
+                return WAIT_OBJECT_0 + handles.len() as u32;
+            }
+        }
+
+        if let Some(deadline) = deadline {
+            if now >= deadline {
+                return WAIT_TIMEOUT;
+            }
+        }
+
+        for &handle in handles {
+            add_waiter(objects.get_mut(handle).unwrap(), thread_id);
+        }
+        current_thread(machine).waiting_on = handles.iter().map(|h| h.to_raw()).collect();
+        block_thread(machine, wait).await;
+        current_thread(machine).waiting_on.clear();
+        for &handle in handles {
+            if let Some(obj) = machine.state.kernel32.objects.get_mut(handle) {
+                remove_waiter(obj, thread_id);
+            }
+        }
+    }
+}
+
+#[win32_derive::dllexport]
+pub async fn WaitForSingleObject(
+    machine: &mut Machine,
+    hHandle: HOBJECT,
+    dwMilliseconds: u32,
+) -> u32 {
+    wait_for_objects(machine, &[hHandle], false, dwMilliseconds, false, None).await
 }
 
 #[win32_derive::dllexport]
-pub fn WaitForSingleObject(_machine: &mut Machine, hHandle: HEVENT, dwMilliseconds: u32) -> u32 {
-    todo!()
+pub async fn WaitForSingleObjectEx(
+    machine: &mut Machine,
+    hHandle: HOBJECT,
+    dwMilliseconds: u32,
+    bAlertable: bool,
+) -> u32 {
+    wait_for_objects(machine, &[hHandle], false, dwMilliseconds, bAlertable, None).await
+}
+
+#[win32_derive::dllexport]
+pub async fn WaitForMultipleObjects(
+    machine: &mut Machine,
+    nCount: u32,
+    lpHandles: u32,
+    bWaitAll: bool,
+    dwMilliseconds: u32,
+) -> u32 {
+    let Some(handles) = wait_handles(machine, nCount, lpHandles, 1..=MAXIMUM_WAIT_OBJECTS) else {
+        return WAIT_FAILED;
+    };
+    wait_for_objects(machine, &handles, bWaitAll, dwMilliseconds, false, None).await
+}
+
+#[win32_derive::dllexport]
+pub async fn WaitForMultipleObjectsEx(
+    machine: &mut Machine,
+    nCount: u32,
+    lpHandles: u32,
+    bWaitAll: bool,
+    dwMilliseconds: u32,
+    bAlertable: bool,
+) -> u32 {
+    let Some(handles) = wait_handles(machine, nCount, lpHandles, 1..=MAXIMUM_WAIT_OBJECTS) else {
+        return WAIT_FAILED;
+    };
+    wait_for_objects(
+        machine,
+        &handles,
+        bWaitAll,
+        dwMilliseconds,
+        bAlertable,
+        None,
+    )
+    .await
+}
+
+/// Create a kernel object, or if an object of the same name exists, return that instead.
+/// Sets the last error to ERROR_ALREADY_EXISTS in that case, which applications check to
+/// detect e.g. another running instance.
+pub(super) fn create_named_object(
+    machine: &mut Machine,
+    name: Option<String>,
+    object: impl FnOnce(String) -> KernelObject,
+) -> HOBJECT {
+    let name = name.unwrap_or_default();
+    let object = object(name.clone());
+    if !name.is_empty() {
+        let objects = &mut machine.state.kernel32.objects;
+        if let Some(id) = objects.find(|obj| obj.name() == Some(&name)) {
+            let existing = objects.object(id).unwrap();
+            if std::mem::discriminant(existing) != std::mem::discriminant(&object) {
+                set_last_error(machine, ERROR::INVALID_HANDLE);
+                return HOBJECT::null();
+            }
+            let handle = objects.new_handle(id);
+            set_last_error(machine, ERROR::ALREADY_EXISTS);
+            return handle;
+        }
+    }
+    set_last_error(machine, ERROR::SUCCESS);
+    machine.state.kernel32.objects.add(object)
+}
+
+fn create_event(
+    machine: &mut Machine,
+    manual_reset: bool,
+    initial_state: bool,
+    name: Option<String>,
+) -> HEVENT {
+    let handle = create_named_object(machine, name, |name| {
+        KernelObject::Event(EventObject::new(name, manual_reset, initial_state))
+    });
+    HEVENT::from_raw(handle.to_raw())
 }
 
 #[win32_derive::dllexport]
@@ -20,38 +399,536 @@ pub fn CreateEventA(
     bInitialState: bool,
     lpName: Option<&str>,
 ) -> HEVENT {
-    let name = if let Some(name) = lpName {
-        if let Some(ev) = machine
-            .state
-            .kernel32
-            .event_handles
-            .iter()
-            .find(|ev| ev.name == name)
-        {
-            todo!("CreateEventA: reusing named event");
+    let name = lpName.map(|name| name.to_string());
+    create_event(machine, bManualReset, bInitialState, name)
+}
+
+#[win32_derive::dllexport]
+pub fn CreateEventW(
+    machine: &mut Machine,
+    lpEventAttributes: u32,
+    bManualReset: bool,
+    bInitialState: bool,
+    lpName: Option<&Str16>,
+) -> HEVENT {
+    let name = lpName.map(|name| name.to_string());
+    create_event(machine, bManualReset, bInitialState, name)
+}
+
+/// Apply `f` to an event, reporting an invalid handle as failure.
+fn update_event(machine: &mut Machine, hEvent: HEVENT, f: impl FnOnce(&mut EventObject)) -> bool {
+    match event_object(machine, hEvent) {
+        Some(ev) => {
+            f(ev);
+            true
+        }
+        None => {
+            log::warn!("invalid event handle {hEvent:?}");
+            set_last_error(machine, ERROR::INVALID_HANDLE);
+            false
         }
-        name.to_string()
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn SetEvent(machine: &mut Machine, hEvent: HEVENT) -> bool {
+    let ok = update_event(machine, hEvent, |ev| ev.signaled = true);
+    wake_waiters(machine);
+    ok
+}
+
+#[win32_derive::dllexport]
+pub fn ResetEvent(machine: &mut Machine, hEvent: HEVENT) -> bool {
+    update_event(machine, hEvent, |ev| ev.signaled = false)
+}
+
+/// Release the threads currently waiting on the event (or the longest waiting of them, for an
+/// auto-reset event), leaving the event unsignaled.
+#[win32_derive::dllexport]
+pub fn PulseEvent(machine: &mut Machine, hEvent: HEVENT) -> bool {
+    let ok = update_event(machine, hEvent, |ev| {
+        let mut waiters = ev.waiters.iter().filter(|id| !ev.released.contains(id));
+        let released: Vec<u32> = if ev.manual_reset {
+            waiters.copied().collect()
+        } else {
+            waiters.next().copied().into_iter().collect()
+        };
+        ev.released.extend(released);
+        ev.signaled = false;
+    });
+    wake_waiters(machine);
+    ok
+}
+
+fn create_mutex(machine: &mut Machine, initial_owner: bool, name: Option<String>) -> HOBJECT {
+    let owner = if initial_owner {
+        current_thread(machine).id
     } else {
-        "".into()
+        0
     };
+    create_named_object(machine, name, |name| {
+        KernelObject::Mutex(MutexObject {
+            name,
+            owner,
+            count: initial_owner as u32,
+            abandoned: false,
+        })
+    })
+}
 
-    machine
-        .state
-        .kernel32
-        .event_handles
-        .add(EventObject { name, state: false })
+#[win32_derive::dllexport]
+pub fn CreateMutexA(
+    machine: &mut Machine,
+    lpMutexAttributes: u32,
+    bInitialOwner: bool,
+    lpName: Option<&str>,
+) -> HOBJECT {
+    create_mutex(machine, bInitialOwner, lpName.map(|name| name.to_string()))
 }
 
 #[win32_derive::dllexport]
-pub fn SetEvent(machine: &mut Machine, hEvent: HEVENT) -> bool {
-    match machine.state.kernel32.event_handles.get_mut(hEvent) {
-        Some(handle) => {
-            handle.state = true;
-            true
+pub fn CreateMutexW(
+    machine: &mut Machine,
+    lpMutexAttributes: u32,
+    bInitialOwner: bool,
+    lpName: Option<&Str16>,
+) -> HOBJECT {
+    create_mutex(machine, bInitialOwner, lpName.map(|name| name.to_string()))
+}
+
+#[win32_derive::dllexport]
+pub fn ReleaseMutex(machine: &mut Machine, hMutex: HOBJECT) -> bool {
+    let thread_id = current_thread(machine).id;
+    let Some(KernelObject::Mutex(mutex)) = machine.state.kernel32.objects.get_mut(hMutex) else {
+        set_last_error(machine, ERROR::INVALID_HANDLE);
+        return false;
+    };
+    if mutex.owner != thread_id {
+        set_last_error(machine, ERROR::NOT_OWNER);
+        return false;
+    }
+    mutex.count -= 1;
+    if mutex.count == 0 {
+        mutex.owner = 0;
+        wake_waiters(machine);
+    }
+    true
+}
+
+fn create_semaphore(
+    machine: &mut Machine,
+    initial_count: i32,
+    maximum_count: i32,
+    name: Option<String>,
+) -> HOBJECT {
+    if maximum_count <= 0 || initial_count < 0 || initial_count > maximum_count {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return HOBJECT::null();
+    }
+    create_named_object(machine, name, |name| {
+        KernelObject::Semaphore(SemaphoreObject {
+            name,
+            count: initial_count as u32,
+            max: maximum_count as u32,
+        })
+    })
+}
+
+#[win32_derive::dllexport]
+pub fn CreateSemaphoreA(
+    machine: &mut Machine,
+    lpSemaphoreAttributes: u32,
+    lInitialCount: i32,
+    lMaximumCount: i32,
+    lpName: Option<&str>,
+) -> HOBJECT {
+    let name = lpName.map(|name| name.to_string());
+    create_semaphore(machine, lInitialCount, lMaximumCount, name)
+}
+
+#[win32_derive::dllexport]
+pub fn CreateSemaphoreW(
+    machine: &mut Machine,
+    lpSemaphoreAttributes: u32,
+    lInitialCount: i32,
+    lMaximumCount: i32,
+    lpName: Option<&Str16>,
+) -> HOBJECT {
+    let name = lpName.map(|name| name.to_string());
+    create_semaphore(machine, lInitialCount, lMaximumCount, name)
+}
+
+#[win32_derive::dllexport]
+pub fn ReleaseSemaphore(
+    machine: &mut Machine,
+    hSemaphore: HOBJECT,
+    lReleaseCount: i32,
+    lpPreviousCount: Option<&mut i32>,
+) -> bool {
+    let Some(KernelObject::Semaphore(sem)) = machine.state.kernel32.objects.get_mut(hSemaphore)
+    else {
+        set_last_error(machine, ERROR::INVALID_HANDLE);
+        return false;
+    };
+    if lReleaseCount <= 0 {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return false;
+    }
+    let prev = sem.count;
+    if prev as u64 + lReleaseCount as u64 > sem.max as u64 {
+        set_last_error(machine, ERROR::TOO_MANY_POSTS);
+        return false;
+    }
+    sem.count += lReleaseCount as u32;
+    if let Some(lpPreviousCount) = lpPreviousCount {
+        *lpPreviousCount = prev as i32;
+    }
+    wake_waiters(machine);
+    true
+}
+
+#[cfg(all(test, feature = "x86-emu"))]
+mod tests {
+    use super::*;
+    use crate::test_guest::{builtin, new_machine, run, Code, Log};
+
+    /// Offsets in a Script's code of the recorder and the variables.
+    const RECORD: u32 = 0x700;
+    const VARS: u32 = 0x740;
+
+    /// An argument of a Script call.
+    #[derive(Clone, Copy)]
+    enum Arg {
+        Imm(u32),
+        /// The value of a variable.
+        Var(u32),
+        /// The address of a variable, e.g. of an array of handles starting there.
+        VarAddr(u32),
+    }
+    use Arg::*;
+
+    /// Guest code calling kernel32 functions, with a few variables to keep handles in
+    /// and a log of results.
+    struct Script {
+        machine: Machine,
+        code: Code,
+        log: Log,
+    }
+
+    impl Script {
+        fn new() -> Self {
+            let (mut machine, _host) = new_machine();
+            let log = Log::alloc(&mut machine, 0x1000);
+            let code = Code::alloc(&mut machine, 0x800);
+            Script { machine, code, log }
         }
-        None => {
-            log::warn!("SetEvent: invalid handle");
-            false
+
+        fn var(&self, index: u32) -> u32 {
+            self.code.base + VARS + 4 * index
+        }
+
+        fn call(&mut self, name: &str, args: &[Arg]) -> &mut Self {
+            let func = builtin(&mut self.machine, "kernel32.dll", name);
+            for &arg in args.iter().rev() {
+                match arg {
+                    Imm(value) => self.code.push(value),
+                    Var(index) => self.code.push_from(self.var(index)),
+                    VarAddr(index) => self.code.push(self.var(index)),
+                };
+            }
+            self.code.call(func);
+            self
+        }
+
+        /// Store the result of the last call in a variable.
+        fn save(&mut self, index: u32) -> &mut Self {
+            self.code.save_eax(self.var(index));
+            self
+        }
+
+        /// Append the result of the last call to the log.
+        fn log(&mut self) -> &mut Self {
+            let record = self.code.base + RECORD;
+            self.code.emit(&[0x50]).call(record); // push eax
+            self
+        }
+
+        /// Append the value of a variable to the log.
+        fn log_var(&mut self, index: u32) -> &mut Self {
+            let record = self.code.base + RECORD;
+            self.code.push_from(self.var(index)).call(record);
+            self
+        }
+
+        fn log_value(&mut self, value: u32) -> &mut Self {
+            let record = self.code.base + RECORD;
+            self.code.push(value).call(record);
+            self
+        }
+
+        /// Append the argument of the current thread procedure to the log.
+        fn log_param(&mut self) -> &mut Self {
+            let record = self.code.base + RECORD;
+            self.code.emit(&[0xFF, 0x74, 0x24, 0x04]).call(record); // push [esp+4]
+            self
+        }
+
+        /// Exit the main code, or with `thread`, return from a thread procedure.
+        fn end(&mut self, thread: bool) -> &mut Self {
+            if thread {
+                self.code.emit(&[0xC2, 4, 0]); // ret 4
+            } else {
+                self.code.exit_with(&mut self.machine, 0);
+            }
+            self
+        }
+
+        /// Continue at an offset, e.g. for a thread procedure.
+        fn at(&mut self, offset: u32) -> &mut Self {
+            self.code.at(offset);
+            self
+        }
+
+        fn run(mut self) -> Vec<u32> {
+            self.code.at(RECORD).recorder(&self.log, 1, 0);
+            self.code.start(&mut self.machine);
+            run(&mut self.machine);
+            self.log.read(&self.machine)
+        }
+    }
+
+    const ERROR_INVALID_HANDLE: u32 = ERROR::INVALID_HANDLE as u32;
+    const ERROR_INVALID_PARAMETER: u32 = ERROR::INVALID_PARAMETER as u32;
+
+    #[test]
+    fn multiple_wait_checks_count_and_handles() {
+        let mut s = Script::new();
+        s.call(
+            "WaitForMultipleObjects",
+            &[Imm(0), VarAddr(0), Imm(0), Imm(0)],
+        )
+        .log()
+        .call("GetLastError", &[])
+        .log()
+        .call(
+            "WaitForMultipleObjects",
+            &[Imm(65), VarAddr(0), Imm(0), Imm(0)],
+        )
+        .log()
+        .call("GetLastError", &[])
+        .log()
+        // A file mapping is a kernel object, but not one that can be waited on.
+        .call(
+            "CreateFileMappingA",
+            &[Imm(u32::MAX), Imm(0), Imm(4), Imm(0), Imm(0x1000), Imm(0)],
+        )
+        .save(0)
+        .call("WaitForSingleObject", &[Var(0), Imm(0)])
+        .log()
+        .call("GetLastError", &[])
+        .log()
+        .end(false);
+        assert_eq!(
+            s.run(),
+            [
+                WAIT_FAILED,
+                ERROR_INVALID_PARAMETER,
+                WAIT_FAILED,
+                ERROR_INVALID_PARAMETER,
+                WAIT_FAILED,
+                ERROR_INVALID_HANDLE,
+            ]
+        );
+    }
+
+    #[test]
+    fn wait_for_any_and_all() {
+        let mut s = Script::new();
+        // A manual-reset event, unsignaled, and a signaled auto-reset one.
+        s.call("CreateEventA", &[Imm(0), Imm(1), Imm(0), Imm(0)])
+            .save(0)
+            .call("CreateEventA", &[Imm(0), Imm(0), Imm(1), Imm(0)])
+            .save(1)
+            // Any: only the second is signaled, and this resets it.
+            .call(
+                "WaitForMultipleObjects",
+                &[Imm(2), VarAddr(0), Imm(0), Imm(0)],
+            )
+            .log()
+            .call(
+                "WaitForMultipleObjects",
+                &[Imm(2), VarAddr(0), Imm(1), Imm(0)],
+            )
+            .log()
+            .call("SetEvent", &[Var(0)])
+            .call("SetEvent", &[Var(1)])
+            // All: both are signaled, and the auto-reset one is reset.
+            .call(
+                "WaitForMultipleObjects",
+                &[Imm(2), VarAddr(0), Imm(1), Imm(0)],
+            )
+            .log()
+            .call("WaitForSingleObject", &[Var(1), Imm(0)])
+            .log()
+            .call("WaitForSingleObject", &[Var(0), Imm(0)])
+            .log()
+            .end(false);
+        assert_eq!(
+            s.run(),
+            [
+                WAIT_OBJECT_0 + 1,
+                WAIT_TIMEOUT,
+                WAIT_OBJECT_0,
+                WAIT_TIMEOUT,
+                WAIT_OBJECT_0
+            ]
+        );
+    }
+
+    #[test]
+    fn mutex_is_recursive_and_abandoned() {
+        let mut s = Script::new();
+        let thread_proc = s.code.base + 0x400;
+        s.call("CreateMutexA", &[Imm(0), Imm(1), Imm(0)])
+            .save(0)
+            // The owner can acquire it again, and must release it as many times.
+            .call("WaitForSingleObject", &[Var(0), Imm(0)])
+            .log()
+            .call("ReleaseMutex", &[Var(0)])
+            .log()
+            .call("ReleaseMutex", &[Var(0)])
+            .log()
+            .call("ReleaseMutex", &[Var(0)])
+            .log()
+            .call("GetLastError", &[])
+            .log()
+            // The thread takes the mutex and exits holding it.
+            .call(
+                "CreateThread",
+                &[Imm(0), Imm(0), Imm(thread_proc), Imm(0), Imm(0), Imm(0)],
+            )
+            .save(1)
+            .call("WaitForSingleObject", &[Var(1), Imm(INFINITE)])
+            .call("WaitForSingleObject", &[Var(0), Imm(0)])
+            .log()
+            .call("ReleaseMutex", &[Var(0)])
+            .log()
+            .end(false)
+            .at(0x400)
+            .call("WaitForSingleObject", &[Var(0), Imm(INFINITE)])
+            .log()
+            .end(true);
+        assert_eq!(
+            s.run(),
+            [
+                WAIT_OBJECT_0,
+                1,
+                1,
+                0,
+                ERROR::NOT_OWNER as u32,
+                WAIT_OBJECT_0,
+                WAIT_ABANDONED_0,
+                1
+            ]
+        );
+    }
+
+    #[test]
+    fn semaphore_counts() {
+        let mut s = Script::new();
+        s.call("CreateSemaphoreA", &[Imm(0), Imm(1), Imm(2), Imm(0)])
+            .save(0)
+            .call("WaitForSingleObject", &[Var(0), Imm(0)])
+            .log()
+            .call("WaitForSingleObject", &[Var(0), Imm(0)])
+            .log()
+            // Past the maximum.
+            .call("ReleaseSemaphore", &[Var(0), Imm(3), Imm(0)])
+            .log()
+            .call("GetLastError", &[])
+            .log()
+            .call("ReleaseSemaphore", &[Var(0), Imm(2), VarAddr(1)])
+            .log()
+            .log_var(1)
+            .call("WaitForSingleObject", &[Var(0), Imm(0)])
+            .log()
+            .call("WaitForSingleObject", &[Var(0), Imm(0)])
+            .log()
+            .call("WaitForSingleObject", &[Var(0), Imm(0)])
+            .log()
+            .end(false);
+        let log = s.run();
+        assert_eq!(
+            log,
+            [
+                WAIT_OBJECT_0,
+                WAIT_TIMEOUT,
+                0,
+                ERROR::TOO_MANY_POSTS as u32,
+                1,
+                0, // the previous count
+                WAIT_OBJECT_0,
+                WAIT_OBJECT_0,
+                WAIT_TIMEOUT
+            ]
+        );
+    }
+
+    #[test]
+    fn pulse_releases_current_waiters() {
+        const MARK: u32 = 99;
+        let mut s = Script::new();
+        let thread_proc = s.code.base + 0x400;
+        // A manual-reset event, and an auto-reset one.
+        s.call("CreateEventA", &[Imm(0), Imm(1), Imm(0), Imm(0)])
+            .save(0)
+            .call("CreateEventA", &[Imm(0), Imm(0), Imm(0), Imm(0)])
+            .save(1);
+        for (param, var) in [(1, 2), (2, 3)] {
+            s.call(
+                "CreateThread",
+                &[Imm(0), Imm(0), Imm(thread_proc), Imm(param), Imm(0), Imm(0)],
+            )
+            .save(var);
         }
+        // Both threads wait on the manual-reset event, and both are released.
+        s.call("Sleep", &[Imm(10)])
+            .call("PulseEvent", &[Var(0)])
+            .save(4)
+            .call("WaitForSingleObject", &[Var(0), Imm(0)])
+            .save(5)
+            .call("Sleep", &[Imm(10)])
+            // Both now wait on the auto-reset event, and only one is released.  The pulse
+            // isn't left for anyone else to take, including the pulsing thread.
+            .call("PulseEvent", &[Var(1)])
+            .call("WaitForSingleObject", &[Var(1), Imm(0)])
+            .save(6)
+            .call("Sleep", &[Imm(10)])
+            .log_value(MARK)
+            .call("PulseEvent", &[Var(1)])
+            .call(
+                "WaitForMultipleObjects",
+                &[Imm(2), VarAddr(2), Imm(1), Imm(INFINITE)],
+            )
+            .log_var(4)
+            .log_var(5)
+            .log_var(6)
+            .end(false)
+            .at(0x400)
+            .call("WaitForSingleObject", &[Var(0), Imm(INFINITE)])
+            .log_param()
+            .call("WaitForSingleObject", &[Var(1), Imm(INFINITE)])
+            .log_param()
+            .end(true);
+        let log = s.run();
+        let [a, b, first, mark, second, pulsed, manual, auto] = log[..] else {
+            panic!("{log:x?}");
+        };
+        assert_eq!(
+            [mark, pulsed, manual, auto],
+            [MARK, 1, WAIT_TIMEOUT, WAIT_TIMEOUT]
+        );
+        assert_eq!(a + b, 3, "{log:x?}");
+        assert_eq!(first + second, 3, "{log:x?}");
     }
 }
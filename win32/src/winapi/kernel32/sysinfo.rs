@@ -0,0 +1,335 @@
+//! What the guest is told about the machine it runs on: GetSystemInfo and friends.
+//!
+//! These are configurable rather than taken from the host, so that programs that key
+//! save files or licensing off them behave the same on any host.
+
+use super::{out_buf, set_last_error};
+use crate::{
+    machine::Machine,
+    winapi::{
+        types::{Str16, String16},
+        ERROR,
+    },
+};
+
+const PROCESSOR_ARCHITECTURE_INTEL: u16 = 0;
+/// Longest name GetComputerName returns, not counting the nul.
+const MAX_COMPUTERNAME_LENGTH: usize = 15;
+
+#[derive(Copy, Clone, Debug, win32_derive::TryFromEnum)]
+pub enum ProcessorFeature {
+    FLOATING_POINT_PRECISION_ERRATA = 0,
+    FLOATING_POINT_EMULATED = 1,
+    COMPARE_EXCHANGE_DOUBLE = 2,
+    MMX_INSTRUCTIONS_AVAILABLE = 3,
+    PPC_MOVEMEM_64BIT_OK = 4,
+    ALPHA_BYTE_INSTRUCTIONS = 5,
+    XMMI_INSTRUCTIONS_AVAILABLE = 6,
+    _3DNOW_INSTRUCTIONS_AVAILABLE = 7,
+    RDTSC_INSTRUCTION_AVAILABLE = 8,
+    PAE_ENABLED = 9,
+    XMMI64_INSTRUCTIONS_AVAILABLE = 10,
+    SSE_DAZ_MODE_AVAILABLE = 11,
+    NX_ENABLED = 12,
+    SSE3_INSTRUCTIONS_AVAILABLE = 13,
+    COMPARE_EXCHANGE128 = 14,
+    COMPARE64_EXCHANGE128 = 15,
+    CHANNELS_ENABLED = 16,
+    XSAVE_ENABLED = 17,
+    ARM_VFP_32_REGISTERS_AVAILABLE = 18,
+    ARM_NEON_INSTRUCTIONS_AVAILABLE = 19,
+    SECOND_LEVEL_ADDRESS_TRANSLATION = 20,
+    VIRT_FIRMWARE_ENABLED = 21,
+    RDWRFSGSBASE_AVAILABLE = 22,
+    FASTFAIL_AVAILABLE = 23,
+    ARM_DIVIDE_INSTRUCTION_AVAILABLE = 24,
+    ARM_64BIT_LOADSTORE_ATOMIC = 25,
+    ARM_EXTERNAL_CACHE_AVAILABLE = 26,
+    ARM_FMAC_INSTRUCTIONS_AVAILABLE = 27,
+    RDRAND_INSTRUCTION_AVAILABLE = 28,
+    ARM_V8_INSTRUCTIONS_AVAILABLE = 29,
+    ARM_V8_CRYPTO_INSTRUCTIONS_AVAILABLE = 30,
+    ARM_V8_CRC32_INSTRUCTIONS_AVAILABLE = 31,
+    RDTSCP_INSTRUCTION_AVAILABLE = 32,
+}
+
+impl ProcessorFeature {
+    fn from_name(name: &str) -> Option<ProcessorFeature> {
+        (0..64)
+            .filter_map(|id| ProcessorFeature::try_from(id).ok())
+            .find(|feature| format!("{feature:?}").eq_ignore_ascii_case(name))
+    }
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct SystemInfo {
+    pub processors: u32,
+    /// The processor family: 386, 486, 586 (Pentium) or 686.
+    pub processor: u32,
+    /// Bits set for each ProcessorFeature present.
+    pub features: u64,
+    pub page_size: u32,
+    pub computer_name: String,
+    pub user_name: String,
+}
+
+impl Default for SystemInfo {
+    fn default() -> Self {
+        // A Pentium MMX, which is what the x86 emulator implements.
+        let features = [
+            ProcessorFeature::COMPARE_EXCHANGE_DOUBLE,
+            ProcessorFeature::MMX_INSTRUCTIONS_AVAILABLE,
+            ProcessorFeature::RDTSC_INSTRUCTION_AVAILABLE,
+        ];
+        SystemInfo {
+            processors: 1,
+            processor: 586,
+            features: features.iter().fold(0, |bits, &f| bits | 1 << f as u32),
+            page_size: 0x1000,
+            computer_name: "RETROWIN32".into(),
+            user_name: "user".into(),
+        }
+    }
+}
+
+impl SystemInfo {
+    /// Apply a setting given as KEY=VALUE, where KEY is one of cpus, cpu (386, 486,
+    /// 586 or 686), features (a comma-separated list of PF_ names without the PF_,
+    /// replacing the defaults), page_size, computer or user.
+    pub fn configure(&mut self, setting: &str) -> Result<(), String> {
+        let (key, value) = setting
+            .split_once('=')
+            .ok_or_else(|| format!("invalid setting {setting:?}, expected KEY=VALUE"))?;
+        let invalid = || format!("invalid {key} {value:?}");
+        match key {
+            "cpus" => {
+                self.processors = value
+                    .parse()
+                    .ok()
+                    .filter(|&n| n > 0 && n <= 32)
+                    .ok_or_else(invalid)?;
+            }
+            "cpu" => {
+                self.processor = value
+                    .parse()
+                    .ok()
+                    .filter(|n| matches!(n, 386 | 486 | 586 | 686))
+                    .ok_or_else(invalid)?;
+            }
+            "features" => {
+                let mut features = 0;
+                for name in value.split(',').filter(|name| !name.is_empty()) {
+                    let name = name.strip_prefix("PF_").unwrap_or(name);
+                    let feature = ProcessorFeature::from_name(name)
+                        .ok_or_else(|| format!("unknown processor feature {name:?}"))?;
+                    features |= 1 << feature as u32;
+                }
+                self.features = features;
+            }
+            "page_size" => {
+                self.page_size = value
+                    .parse()
+                    .ok()
+                    .filter(|n: &u32| n.is_power_of_two())
+                    .ok_or_else(invalid)?;
+            }
+            "computer" => {
+                if value.is_empty() || value.len() > MAX_COMPUTERNAME_LENGTH {
+                    return Err(invalid());
+                }
+                self.computer_name = value.to_string();
+            }
+            "user" => {
+                if value.is_empty() {
+                    return Err(invalid());
+                }
+                self.user_name = value.to_string();
+            }
+            _ => return Err(format!("unknown setting {key:?}")),
+        }
+        Ok(())
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Default)]
+pub struct SYSTEM_INFO {
+    wProcessorArchitecture: u16,
+    wReserved: u16,
+    dwPageSize: u32,
+    lpMinimumApplicationAddress: u32,
+    lpMaximumApplicationAddress: u32,
+    dwActiveProcessorMask: u32,
+    dwNumberOfProcessors: u32,
+    dwProcessorType: u32,
+    dwAllocationGranularity: u32,
+    wProcessorLevel: u16,
+    wProcessorRevision: u16,
+}
+unsafe impl ::memory::Pod for SYSTEM_INFO {}
+
+#[win32_derive::dllexport]
+pub fn GetSystemInfo(machine: &mut Machine, lpSystemInfo: Option<&mut SYSTEM_INFO>) {
+    let Some(out) = lpSystemInfo else {
+        return;
+    };
+    let info = &machine.state.kernel32.system;
+    *out = SYSTEM_INFO {
+        wProcessorArchitecture: PROCESSOR_ARCHITECTURE_INTEL,
+        wReserved: 0,
+        dwPageSize: info.page_size,
+        lpMinimumApplicationAddress: 0x1_0000,
+        lpMaximumApplicationAddress: 0x7FFE_FFFF,
+        dwActiveProcessorMask: ((1u64 << info.processors) - 1) as u32,
+        dwNumberOfProcessors: info.processors,
+        // There's no PROCESSOR_INTEL_686; later processors report as Pentiums.
+        dwProcessorType: info.processor.min(586),
+        dwAllocationGranularity: 0x1_0000,
+        wProcessorLevel: (info.processor / 100) as u16,
+        wProcessorRevision: 0,
+    };
+}
+
+#[win32_derive::dllexport]
+pub fn GetNativeSystemInfo(machine: &mut Machine, lpSystemInfo: Option<&mut SYSTEM_INFO>) {
+    GetSystemInfo(machine, lpSystemInfo)
+}
+
+#[win32_derive::dllexport]
+pub fn IsProcessorFeaturePresent(machine: &mut Machine, feature: u32) -> bool {
+    feature < 64 && machine.state.kernel32.system.features & (1 << feature) != 0
+}
+
+/// Copy a name to a nul-terminated buffer of *size chars, as GetComputerName and
+/// GetUserName do.  Returns the count of chars needed, including the nul, if it
+/// doesn't fit.
+fn copy_name<T: Copy + Default>(buf: &mut [T], name: &[T]) -> Result<(), usize> {
+    if buf.len() < name.len() + 1 {
+        return Err(name.len() + 1);
+    }
+    buf[..name.len()].copy_from_slice(name);
+    buf[name.len()] = T::default();
+    Ok(())
+}
+
+fn encode_name(machine: &Machine, name: &str) -> Vec<u8> {
+    let chars = String16::from(name).0;
+    let cp = machine.state.kernel32.code_page;
+    cp.encode(&chars, b"?", false).unwrap().0
+}
+
+#[win32_derive::dllexport]
+pub fn GetComputerNameA(machine: &mut Machine, lpBuffer: u32, nSize: Option<&mut u32>) -> bool {
+    let Some(size) = nSize else {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return false;
+    };
+    let name = encode_name(machine, &machine.state.kernel32.system.computer_name);
+    let Some(buf) = out_buf(machine, lpBuffer, *size, format_args!("GetComputerNameA")) else {
+        return false;
+    };
+    match copy_name(buf, &name) {
+        Ok(()) => {
+            *size = name.len() as u32;
+            set_last_error(machine, ERROR::SUCCESS);
+            true
+        }
+        Err(needed) => {
+            *size = needed as u32;
+            set_last_error(machine, ERROR::BUFFER_OVERFLOW);
+            false
+        }
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn GetComputerNameW(machine: &mut Machine, lpBuffer: u32, nSize: Option<&mut u32>) -> bool {
+    let Some(size) = nSize else {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return false;
+    };
+    let name = String16::from(machine.state.kernel32.system.computer_name.as_str()).0;
+    let Some(buf) = out_buf(
+        machine,
+        lpBuffer,
+        *size * 2,
+        format_args!("GetComputerNameW"),
+    ) else {
+        return false;
+    };
+    match copy_name(Str16::from_bytes_mut(buf), &name) {
+        Ok(()) => {
+            *size = name.len() as u32;
+            set_last_error(machine, ERROR::SUCCESS);
+            true
+        }
+        Err(needed) => {
+            *size = needed as u32;
+            set_last_error(machine, ERROR::BUFFER_OVERFLOW);
+            false
+        }
+    }
+}
+
+/// GetUserNameA, which lives in advapi32.  Unlike GetComputerName, the size it
+/// reports on success includes the nul.
+pub fn get_user_name_ansi(machine: &mut Machine, buf: u32, size: &mut u32) -> bool {
+    let name = encode_name(machine, &machine.state.kernel32.system.user_name);
+    let Some(out) = out_buf(machine, buf, *size, format_args!("GetUserNameA")) else {
+        return false;
+    };
+    let result = copy_name(out, &name);
+    finish_user_name(machine, result, name.len(), size)
+}
+
+/// GetUserNameW; see get_user_name_ansi.
+pub fn get_user_name_wide(machine: &mut Machine, buf: u32, size: &mut u32) -> bool {
+    let name = String16::from(machine.state.kernel32.system.user_name.as_str()).0;
+    let Some(out) = out_buf(machine, buf, *size * 2, format_args!("GetUserNameW")) else {
+        return false;
+    };
+    let result = copy_name(Str16::from_bytes_mut(out), &name);
+    finish_user_name(machine, result, name.len(), size)
+}
+
+fn finish_user_name(
+    machine: &mut Machine,
+    result: Result<(), usize>,
+    len: usize,
+    size: &mut u32,
+) -> bool {
+    match result {
+        Ok(()) => {
+            *size = len as u32 + 1;
+            set_last_error(machine, ERROR::SUCCESS);
+            true
+        }
+        Err(needed) => {
+            *size = needed as u32;
+            set_last_error(machine, ERROR::INSUFFICIENT_BUFFER);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn configure() {
+        let mut info = SystemInfo::default();
+        info.configure("cpus=2").unwrap();
+        info.configure("features=PF_XMMI_INSTRUCTIONS_AVAILABLE,RDTSC_INSTRUCTION_AVAILABLE")
+            .unwrap();
+        info.configure("computer=BOX").unwrap();
+        assert_eq!(info.processors, 2);
+        assert_eq!(info.features, 1 << 6 | 1 << 8);
+        assert_eq!(info.computer_name, "BOX");
+        assert!(info.configure("cpu=286").is_err());
+        assert!(info.configure("features=NOPE").is_err());
+        assert!(info
+            .configure("computer=A_NAME_TOO_LONG_FOR_NETBIOS")
+            .is_err());
+    }
+}
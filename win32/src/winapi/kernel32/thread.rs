@@ -1,55 +1,390 @@
-use super::{peb_mut, teb_mut};
+use super::{peb_mut, set_last_error, teb_mut, KernelObject, ObjectId, HOBJECT};
 use crate::{
     machine::Machine,
     winapi,
-    winapi::types::{Str16, HANDLE},
+    winapi::{
+        types::{Str16, HANDLE},
+        ERROR,
+    },
 };
-use memory::Pod;
+use memory::{Extensions, Pod};
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct HTHREADT;
 pub type HTHREAD = HANDLE<HTHREADT>;
 
-#[win32_derive::dllexport]
-pub fn GetCurrentThread(machine: &mut Machine) -> HTHREAD {
-    HTHREAD::from_raw(GetCurrentThreadId(machine))
+pub const MAIN_THREAD_ID: u32 = 1;
+
+/// Pseudo-handle returned by GetCurrentThread, which refers to the calling thread.
+pub(super) const CURRENT_THREAD: HTHREAD = HTHREAD::from_raw(-2i32 as u32);
+
+/// GetExitCodeThread result for a thread that hasn't exited.
+pub(super) const STILL_ACTIVE: u32 = 259;
+
+/// Stack size for threads that don't specify one.
+const DEFAULT_STACK_SIZE: u32 = 1 << 20;
+
+/// The FPU control word Windows starts threads with: like FINIT's, but at 53-bit precision.
+pub const INITIAL_FPU_CONTROL: u16 = 0x027F;
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Thread {
+    pub id: u32,
+    /// Index of the emulated CPU that runs this thread.
+    pub cpu: usize,
+    pub teb: u32,
+    /// Set once the thread has exited.
+    pub exit_code: Option<u32>,
+    pub priority: i32,
+    /// The thread only runs while this is zero.
+    pub suspend_count: u32,
+    /// APCs waiting for the thread to enter an alertable wait.
+    pub apcs: Vec<Apc>,
+    /// The handles the thread is blocked waiting on, for the debugger.
+    pub waiting_on: Vec<u32>,
+    /// Address of the C runtime's errno for the thread, once allocated.
+    pub errno: u32,
 }
 
-#[win32_derive::dllexport]
-pub fn GetCurrentThreadId(machine: &mut Machine) -> u32 {
+/// A user-mode asynchronous procedure call: a function the thread calls the next time it
+/// enters an alertable wait, e.g. an I/O completion routine.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Apc {
+    pub func: u32,
+    pub args: Vec<u32>,
+}
+
+impl Thread {
+    pub fn new(id: u32, cpu: usize, teb: u32) -> Self {
+        Thread {
+            id,
+            cpu,
+            teb,
+            exit_code: None,
+            priority: 0,
+            suspend_count: 0,
+            apcs: Vec::new(),
+            waiting_on: Vec::new(),
+            errno: 0,
+        }
+    }
+}
+
+const CREATE_SUSPENDED: u32 = 0x4;
+
+/// Propagate a thread's scheduling state to the CPU that runs it.
+fn update_cpu(machine: &mut Machine, handle: HTHREAD) {
+    #[cfg(feature = "x86-emu")]
+    {
+        let Some(thread) = get_thread(machine, handle) else {
+            return;
+        };
+        let (index, priority, suspended) = (thread.cpu, thread.priority, thread.suspend_count > 0);
+        let cpu = &mut *machine.emu.x86.cpus[index];
+        cpu.priority = priority;
+        cpu.suspended = suspended;
+    }
+
+    #[cfg(not(feature = "x86-emu"))]
+    {
+        _ = (machine, handle);
+    }
+}
+
+/// Index of the CPU running the current thread.
+fn current_cpu(machine: &Machine) -> usize {
+    #[cfg(feature = "x86-emu")]
+    {
+        machine.emu.x86.cur_cpu
+    }
+
+    #[cfg(not(feature = "x86-emu"))]
+    {
+        _ = machine;
+        0
+    }
+}
+
+fn is_live_on(obj: &KernelObject, cpu: usize) -> bool {
+    matches!(obj, KernelObject::Thread(thread) if thread.cpu == cpu && thread.exit_code.is_none())
+}
+
+/// The thread that is currently executing.
+pub fn current_thread(machine: &mut Machine) -> &mut Thread {
+    let cpu = current_cpu(machine);
+    match machine
+        .state
+        .kernel32
+        .objects
+        .iter_mut()
+        .find(|obj| is_live_on(obj, cpu))
+    {
+        Some(KernelObject::Thread(thread)) => thread,
+        _ => unreachable!("no thread for cpu {cpu}"),
+    }
+}
+
+/// The object of the thread that is currently executing, for making handles to it.
+pub(super) fn current_thread_object(machine: &Machine) -> ObjectId {
+    let cpu = current_cpu(machine);
+    let objects = &machine.state.kernel32.objects;
+    objects.find(|obj| is_live_on(obj, cpu)).unwrap()
+}
+
+/// Look up a thread handle, including the GetCurrentThread pseudo-handle.
+pub fn get_thread(machine: &mut Machine, handle: HTHREAD) -> Option<&mut Thread> {
+    if handle == CURRENT_THREAD {
+        return Some(current_thread(machine));
+    }
+    match machine
+        .state
+        .kernel32
+        .objects
+        .get_mut(HOBJECT::from_raw(handle.to_raw()))
+    {
+        Some(KernelObject::Thread(thread)) => Some(thread),
+        _ => None,
+    }
+}
+
+/// A thread's scheduling state, shown in the debugger.
+#[derive(Debug, serde::Serialize)]
+#[cfg_attr(feature = "wasm", derive(tsify::Tsify))]
+pub struct ThreadInfo {
+    pub id: u32,
+    pub cpu: usize,
+    /// Whether this is the thread that is currently executing.
+    pub current: bool,
+    /// "running", "ready", "blocked", "suspended" or "exited", or the CPU state otherwise.
+    pub state: String,
+    pub priority: i32,
+    /// The handles a blocked thread is waiting on, which are none for e.g. Sleep.
+    pub waiting_on: Vec<u32>,
+    /// When a blocked thread's wait times out, in host ticks.
+    pub deadline: Option<u32>,
+    pub eip: u32,
+}
+
+/// The scheduling state of all threads, in order of id.
+#[cfg(feature = "x86-emu")]
+pub fn thread_infos(machine: &Machine) -> Vec<ThreadInfo> {
+    let x86 = &machine.emu.x86;
+    let mut infos = machine
+        .state
+        .kernel32
+        .objects
+        .iter()
+        .filter_map(|obj| match obj {
+            KernelObject::Thread(thread) => Some(thread),
+            _ => None,
+        })
+        .map(|thread| {
+            let cpu = &x86.cpus[thread.cpu];
+            let current = thread.exit_code.is_none() && thread.cpu == x86.cur_cpu;
+            let mut deadline = None;
+            let state = match &cpu.state {
+                _ if thread.exit_code.is_some() => "exited".into(),
+                _ if cpu.suspended => "suspended".into(),
+                x86::CPUState::Running if current => "running".into(),
+                x86::CPUState::Running => "ready".into(),
+                x86::CPUState::Blocked(wait) => {
+                    deadline = *wait;
+                    "blocked".into()
+                }
+                state => format!("{state:?}"),
+            };
+            ThreadInfo {
+                id: thread.id,
+                cpu: thread.cpu,
+                current,
+                state,
+                priority: thread.priority,
+                waiting_on: thread.waiting_on.clone(),
+                deadline,
+                eip: cpu.regs.eip,
+            }
+        })
+        .collect::<Vec<_>>();
+    infos.sort_by_key(|info| info.id);
+    infos
+}
+
+/// Block the current thread until woken by wake_waiters() or the deadline (in host ticks) passes.
+/// Wakeups can be spurious, so callers must recheck whatever they were waiting for.
+pub async fn block_thread(machine: &mut Machine, deadline: Option<u32>) {
+    #[cfg(feature = "x86-emu")]
+    {
+        machine.emu.x86.cpu_mut().block(deadline).await;
+    }
+
+    #[cfg(not(feature = "x86-emu"))]
+    {
+        machine.block_host(deadline);
+    }
+}
+
+/// Block the current thread until poll produces a value, rechecking each time the thread
+/// is woken.  This is how a builtin waits on the host without holding up other threads or
+/// the host's own event loop: the host reports "not yet" and calls unblock() once ready.
+pub async fn block_until<T>(
+    machine: &mut Machine,
+    mut poll: impl FnMut(&mut Machine) -> Option<T>,
+) -> T {
+    loop {
+        if let Some(value) = poll(machine) {
+            return value;
+        }
+        block_thread(machine, None).await;
+    }
+}
+
+/// Wake all blocked threads to recheck their wait conditions, e.g. after an object is signaled.
+/// A woken thread of higher priority than the current one preempts it.
+pub fn wake_waiters(machine: &mut Machine) {
     #[cfg(feature = "x86-emu")]
     {
-        machine.emu.x86.cur_cpu as u32
+        let x86 = &mut machine.emu.x86;
+        let current = x86.cpu().priority;
+        let mut preempt = false;
+        for cpu in x86.cpus.iter_mut() {
+            if matches!(cpu.state, x86::CPUState::Blocked(_)) {
+                cpu.state = x86::CPUState::Running;
+                preempt |= !cpu.suspended && cpu.priority > current;
+            }
+        }
+        if preempt {
+            x86.preempt();
+        }
     }
 
     #[cfg(not(feature = "x86-emu"))]
     {
         _ = machine;
-        1
     }
 }
 
+/// Run the current thread's queued APCs, as done by an alertable wait.
+/// Returns false if there were none, in which case the wait proceeds.
+pub async fn deliver_apcs(machine: &mut Machine) -> bool {
+    let apcs = std::mem::take(&mut current_thread(machine).apcs);
+    if apcs.is_empty() {
+        return false;
+    }
+    for apc in apcs {
+        machine.call_x86(apc.func, apc.args).await;
+    }
+    true
+}
+
+#[win32_derive::dllexport]
+pub fn QueueUserAPC(machine: &mut Machine, pfnAPC: u32, hThread: HTHREAD, dwData: u32) -> u32 {
+    let Some(thread) = get_thread(machine, hThread) else {
+        set_last_error(machine, ERROR::INVALID_HANDLE);
+        return 0;
+    };
+    thread.apcs.push(Apc {
+        func: pfnAPC,
+        args: vec![dwData],
+    });
+    // The thread may already be in an alertable wait.
+    wake_waiters(machine);
+    1
+}
+
+/// Mark the current thread as exited, exiting the process if it was the last one.
+pub fn exit_thread(machine: &mut Machine, exit_code: u32) {
+    let thread = current_thread(machine);
+    let id = thread.id;
+    log::info!("thread {id} exiting with code {exit_code}");
+    thread.exit_code = Some(exit_code);
+    super::abandon_mutexes(machine, id);
+    // TODO: free stack and TEB.
+
+    #[cfg(feature = "x86-emu")]
+    {
+        machine.emu.x86.cpu_mut().state = x86::CPUState::Free;
+    }
+
+    let live = machine
+        .state
+        .kernel32
+        .objects
+        .iter()
+        .any(|obj| matches!(obj, KernelObject::Thread(thread) if thread.exit_code.is_none()));
+    if !live {
+        machine.exit(exit_code);
+    }
+    wake_waiters(machine);
+
+    // The program may have closed its handles to the thread while it ran.
+    let objects = &mut machine.state.kernel32.objects;
+    if let Some(object) =
+        objects.find(|obj| matches!(obj, KernelObject::Thread(thread) if thread.id == id))
+    {
+        objects.release(object);
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn GetCurrentThread(_machine: &mut Machine) -> HTHREAD {
+    CURRENT_THREAD
+}
+
+#[win32_derive::dllexport]
+pub fn GetCurrentThreadId(machine: &mut Machine) -> u32 {
+    current_thread(machine).id
+}
+
+/// Number of TLS slots, and also the number of entries in TEB.TlsSlots.
+const TLS_MINIMUM_AVAILABLE: u32 = 64;
+const TLS_OUT_OF_INDEXES: u32 = 0xFFFF_FFFF;
+
+fn tls_slot_allocated(machine: &mut Machine, index: u32) -> bool {
+    index < TLS_MINIMUM_AVAILABLE
+        && peb_mut(machine).TlsBitmapBits[index as usize / 32] & (1 << (index % 32)) != 0
+}
+
 #[win32_derive::dllexport]
 pub fn TlsAlloc(machine: &mut Machine) -> u32 {
     let peb = peb_mut(machine);
-    let slot = peb.TlsCount;
-    peb.TlsCount = slot + 1;
-    slot
+    let Some(index) = (0..TLS_MINIMUM_AVAILABLE)
+        .find(|&i| peb.TlsBitmapBits[i as usize / 32] & (1 << (i % 32)) == 0)
+    else {
+        set_last_error(machine, ERROR::NO_MORE_ITEMS);
+        return TLS_OUT_OF_INDEXES;
+    };
+    peb.TlsBitmapBits[index as usize / 32] |= 1 << (index % 32);
+    index
 }
 
 #[win32_derive::dllexport]
 pub fn TlsFree(machine: &mut Machine, dwTlsIndex: u32) -> bool {
-    let peb = peb_mut(machine);
-    if dwTlsIndex >= peb.TlsCount {
+    if !tls_slot_allocated(machine, dwTlsIndex) {
         log::warn!("TlsFree of unknown slot {dwTlsIndex}");
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
         return false;
     }
-    // TODO
+    peb_mut(machine).TlsBitmapBits[dwTlsIndex as usize / 32] &= !(1 << (dwTlsIndex % 32));
+
+    // Clear the slot in every thread, so a later TlsAlloc of the same index starts out zeroed.
+    let mem = machine.mem();
+    for obj in machine.state.kernel32.objects.iter() {
+        if let KernelObject::Thread(thread) = obj {
+            if thread.exit_code.is_none() {
+                let teb = mem.get_aligned_ref_mut::<super::TEB>(thread.teb);
+                teb.TlsSlots[dwTlsIndex as usize] = 0;
+            }
+        }
+    }
     true
 }
 
 #[win32_derive::dllexport]
 pub fn TlsSetValue(machine: &mut Machine, dwTlsIndex: u32, lpTlsValue: u32) -> bool {
+    if dwTlsIndex >= TLS_MINIMUM_AVAILABLE {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return false;
+    }
     let teb = teb_mut(machine);
     teb.TlsSlots[dwTlsIndex as usize] = lpTlsValue;
     true
@@ -57,6 +392,12 @@ pub fn TlsSetValue(machine: &mut Machine, dwTlsIndex: u32, lpTlsValue: u32) -> b
 
 #[win32_derive::dllexport]
 pub fn TlsGetValue(machine: &mut Machine, dwTlsIndex: u32) -> u32 {
+    if dwTlsIndex >= TLS_MINIMUM_AVAILABLE {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return 0;
+    }
+    // Callers distinguish a stored 0 from failure via GetLastError.
+    set_last_error(machine, ERROR::SUCCESS);
     let teb = teb_mut(machine);
     teb.TlsSlots[dwTlsIndex as usize]
 }
@@ -69,31 +410,76 @@ pub async fn CreateThread(
     lpStartAddress: u32,
     lpParameter: u32,
     dwCreationFlags: u32,
-    lpThreadId: u32,
+    lpThreadId: Option<&mut u32>,
 ) -> HTHREAD {
-    let retrowin32_thread_main =
-        winapi::kernel32::get_kernel32_builtin(machine, "retrowin32_thread_main");
-
     #[cfg(feature = "x86-emu")]
     {
-        let id = 1; // TODO
-        let stack_pointer = machine.create_stack(format!("thread{id} stack"), dwStackSize);
-        // TODO: should reuse a CPU from a previous thread that has exited
-        let cpu = machine.emu.x86.new_cpu();
+        let retrowin32_thread_start =
+            winapi::kernel32::get_kernel32_builtin(machine, "retrowin32_thread_start");
+        let retrowin32_thread_exit =
+            winapi::kernel32::get_kernel32_builtin(machine, "retrowin32_thread_exit");
+
+        let id = machine.state.kernel32.next_thread_id;
+        machine.state.kernel32.next_thread_id += 1;
+
+        let stack_size = match dwStackSize {
+            0 => DEFAULT_STACK_SIZE,
+            size => size,
+        };
+        let Some(stack_pointer) = machine.create_stack(format!("thread {id} stack"), stack_size)
+        else {
+            set_last_error(machine, ERROR::NOT_ENOUGH_MEMORY);
+            return HTHREAD::null();
+        };
+        let stack_base = stack_pointer + 4;
+        let stack_limit = stack_base - stack_size;
+        let Some(teb) = super::new_teb(machine, id, stack_base, stack_limit) else {
+            let stack = machine
+                .state
+                .kernel32
+                .mappings
+                .find_mut(stack_pointer)
+                .unwrap();
+            let addr = stack.addr;
+            super::release_mapping(machine, addr);
+            set_last_error(machine, ERROR::NOT_ENOUGH_MEMORY);
+            return HTHREAD::null();
+        };
+        if let Some(lpThreadId) = lpThreadId {
+            *lpThreadId = id;
+        }
+
+        let cpu_index = machine.emu.x86.new_cpu();
+        let cpu = &mut *machine.emu.x86.cpus[cpu_index];
         cpu.regs.set32(x86::Register::ESP, stack_pointer);
         cpu.regs.set32(x86::Register::EBP, stack_pointer);
+        cpu.regs.fs_addr = teb;
+        cpu.fpu.control = INITIAL_FPU_CONTROL;
         let mem = machine.emu.memory.mem();
+        // retrowin32_thread_start returns into the start routine, which returns into
+        // retrowin32_thread_exit.
         x86::ops::push(cpu, mem, lpParameter);
+        x86::ops::push(cpu, mem, retrowin32_thread_exit);
         x86::ops::push(cpu, mem, lpStartAddress);
-        x86::ops::push(cpu, mem, 0);
-        cpu.regs.eip = retrowin32_thread_main;
-
-        HTHREAD::from_raw(id)
+        cpu.regs.eip = retrowin32_thread_start;
+
+        let mut thread = Thread::new(id, cpu_index, teb);
+        if dwCreationFlags & CREATE_SUSPENDED != 0 {
+            thread.suspend_count = 1;
+        }
+        let handle = machine
+            .state
+            .kernel32
+            .objects
+            .add(KernelObject::Thread(thread));
+        let handle = HTHREAD::from_raw(handle.to_raw());
+        update_cpu(machine, handle);
+        handle
     }
 
     #[cfg(not(feature = "x86-emu"))]
     {
-        _ = retrowin32_thread_main;
+        _ = lpThreadId;
         log::warn!("CreateThread running thread synchronously");
         machine.call_x86(lpStartAddress, vec![lpParameter]).await;
         HTHREAD::null()
@@ -101,24 +487,54 @@ pub async fn CreateThread(
 }
 
 #[win32_derive::dllexport]
-pub fn ExitThread(machine: &mut Machine, dwExitCode: u32) {
-    if machine.emu.x86.cur_cpu == 0 {
-        panic!("ExitThread called on main thread");
-    }
+pub async fn ExitThread(machine: &mut Machine, dwExitCode: u32) {
+    super::notify_dlls_thread(machine, false).await;
+    exit_thread(machine, dwExitCode);
+}
 
-    log::warn!(
-        "thread on cpu {id} exiting with code {code}",
-        code = dwExitCode,
-        id = machine.emu.x86.cur_cpu
-    );
-    // TODO: free stack, other thread cleanup, set event to signal waiters, etc.
+#[win32_derive::dllexport]
+pub fn GetExitCodeThread(
+    machine: &mut Machine,
+    hThread: HTHREAD,
+    lpExitCode: Option<&mut u32>,
+) -> bool {
+    let Some(thread) = get_thread(machine, hThread) else {
+        return false;
+    };
+    *lpExitCode.unwrap() = thread.exit_code.unwrap_or(STILL_ACTIVE);
+    true
+}
 
-    machine.emu.x86.cpu_mut().state = x86::CPUState::Free;
+#[win32_derive::dllexport]
+pub fn SuspendThread(machine: &mut Machine, hThread: HTHREAD) -> u32 {
+    let Some(thread) = get_thread(machine, hThread) else {
+        set_last_error(machine, ERROR::INVALID_HANDLE);
+        return -1i32 as u32;
+    };
+    let prev = thread.suspend_count;
+    thread.suspend_count += 1;
+    // If this is the current thread, the scheduler switches away once we return.
+    update_cpu(machine, hThread);
+    prev
 }
 
 #[win32_derive::dllexport]
-pub fn ResumeThread(_machine: &mut Machine, hThread: HTHREAD) -> u32 {
-    1
+pub fn ResumeThread(machine: &mut Machine, hThread: HTHREAD) -> u32 {
+    let Some(thread) = get_thread(machine, hThread) else {
+        set_last_error(machine, ERROR::INVALID_HANDLE);
+        return -1i32 as u32;
+    };
+    let prev = thread.suspend_count;
+    thread.suspend_count = prev.saturating_sub(1);
+    update_cpu(machine, hThread);
+    prev
+}
+
+#[win32_derive::dllexport]
+pub async fn SwitchToThread(machine: &mut Machine) -> bool {
+    let now = machine.ticks();
+    block_thread(machine, Some(now)).await;
+    true
 }
 
 #[win32_derive::dllexport]
@@ -131,26 +547,31 @@ pub fn SetThreadDescription(
 }
 
 #[win32_derive::dllexport]
-pub fn SetThreadPriority(_machine: &mut Machine, hThread: HTHREAD, nPriority: u32) -> bool {
-    true // success
-}
-
-#[win32_derive::dllexport]
-pub fn SetThreadStackGuarantee(_machine: &mut Machine, StackSizeInBytes: Option<&mut u32>) -> bool {
-    // ignore
+pub fn SetThreadPriority(machine: &mut Machine, hThread: HTHREAD, nPriority: i32) -> bool {
+    let Some(thread) = get_thread(machine, hThread) else {
+        set_last_error(machine, ERROR::INVALID_HANDLE);
+        return false;
+    };
+    thread.priority = nPriority;
+    update_cpu(machine, hThread);
     true // success
 }
 
 #[win32_derive::dllexport]
-pub fn InterlockedIncrement(_machine: &mut Machine, addend: Option<&mut u32>) -> u32 {
-    let addend = addend.unwrap();
-    *addend += 1;
-    *addend
+pub fn GetThreadPriority(machine: &mut Machine, hThread: HTHREAD) -> i32 {
+    match get_thread(machine, hThread) {
+        Some(thread) => thread.priority,
+        None => {
+            set_last_error(machine, ERROR::INVALID_HANDLE);
+            0x7FFF_FFFF // THREAD_PRIORITY_ERROR_RETURN
+        }
+    }
 }
 
 #[win32_derive::dllexport]
-pub fn InterlockedDecrement(_machine: &mut Machine, addend: Option<&mut u32>) -> u32 {
-    todo!()
+pub fn SetThreadStackGuarantee(_machine: &mut Machine, StackSizeInBytes: Option<&mut u32>) -> bool {
+    // ignore
+    true // success
 }
 
 #[repr(C)]
@@ -189,44 +610,125 @@ pub fn InitOnceComplete(
     true
 }
 
+#[repr(C)]
+#[derive(Debug)]
+pub struct CRITICAL_SECTION {
+    pub DebugInfo: u32,
+    /// -1 when unowned, otherwise the number of threads waiting.
+    pub LockCount: i32,
+    pub RecursionCount: u32,
+    /// The id (not handle) of the owning thread.
+    pub OwningThread: u32,
+    pub LockSemaphore: u32,
+    pub SpinCount: u32,
+}
+unsafe impl Pod for CRITICAL_SECTION {}
+
+fn init_critical_section(section: &mut CRITICAL_SECTION, spin_count: u32) {
+    *section = CRITICAL_SECTION {
+        DebugInfo: 0,
+        LockCount: -1,
+        RecursionCount: 0,
+        OwningThread: 0,
+        LockSemaphore: 0,
+        SpinCount: spin_count,
+    };
+}
+
+/// Take ownership of a critical section for the given thread, if it's available.
+fn try_enter(section: &mut CRITICAL_SECTION, thread_id: u32) -> bool {
+    if section.OwningThread == 0 {
+        section.OwningThread = thread_id;
+        section.RecursionCount = 1;
+    } else if section.OwningThread == thread_id {
+        section.RecursionCount += 1;
+    } else {
+        return false;
+    }
+    section.LockCount += 1;
+    true
+}
+
 #[win32_derive::dllexport]
-pub fn InitializeCriticalSection(_machine: &mut Machine, lpCriticalSection: u32) -> bool {
+pub fn InitializeCriticalSection(
+    _machine: &mut Machine,
+    lpCriticalSection: Option<&mut CRITICAL_SECTION>,
+) -> bool {
+    init_critical_section(lpCriticalSection.unwrap(), 0);
     true
 }
 
 #[win32_derive::dllexport]
 pub fn InitializeCriticalSectionEx(
     _machine: &mut Machine,
-    lpCriticalSection: u32,
+    lpCriticalSection: Option<&mut CRITICAL_SECTION>,
     dwSpinCount: u32,
     flags: u32,
 ) -> bool {
+    init_critical_section(lpCriticalSection.unwrap(), dwSpinCount);
     true
 }
 
 #[win32_derive::dllexport]
 pub fn InitializeCriticalSectionAndSpinCount(
     _machine: &mut Machine,
-    lpCriticalSection: u32,
+    lpCriticalSection: Option<&mut CRITICAL_SECTION>,
     dwSpinCount: u32,
 ) -> bool {
     // "On single-processor systems, the spin count is ignored and the critical section spin count is set to 0 (zero)."
     // "This function always succeeds and returns a nonzero value."
+    init_critical_section(lpCriticalSection.unwrap(), 0);
     true
 }
 
 #[win32_derive::dllexport]
-pub fn DeleteCriticalSection(_machine: &mut Machine, lpCriticalSection: u32) -> u32 {
+pub fn DeleteCriticalSection(
+    _machine: &mut Machine,
+    lpCriticalSection: Option<&mut CRITICAL_SECTION>,
+) -> u32 {
     0
 }
 
 #[win32_derive::dllexport]
-pub fn EnterCriticalSection(_machine: &mut Machine, lpCriticalSection: u32) -> u32 {
-    0
+pub async fn EnterCriticalSection(machine: &mut Machine, lpCriticalSection: u32) -> u32 {
+    let thread_id = current_thread(machine).id;
+    loop {
+        let section = machine
+            .mem()
+            .get_aligned_ref_mut::<CRITICAL_SECTION>(lpCriticalSection);
+        if try_enter(section, thread_id) {
+            return 0;
+        }
+        // Owned by another thread; wait for it to be released.
+        block_thread(machine, None).await;
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn TryEnterCriticalSection(
+    machine: &mut Machine,
+    lpCriticalSection: Option<&mut CRITICAL_SECTION>,
+) -> bool {
+    let thread_id = current_thread(machine).id;
+    try_enter(lpCriticalSection.unwrap(), thread_id)
 }
 
 #[win32_derive::dllexport]
-pub fn LeaveCriticalSection(_machine: &mut Machine, lpCriticalSection: u32) -> u32 {
+pub fn LeaveCriticalSection(
+    machine: &mut Machine,
+    lpCriticalSection: Option<&mut CRITICAL_SECTION>,
+) -> u32 {
+    let section = lpCriticalSection.unwrap();
+    if section.OwningThread != current_thread(machine).id {
+        log::warn!("LeaveCriticalSection: section not owned by current thread");
+        return 0;
+    }
+    section.LockCount -= 1;
+    section.RecursionCount -= 1;
+    if section.RecursionCount == 0 {
+        section.OwningThread = 0;
+        wake_waiters(machine);
+    }
     0
 }
 
@@ -261,3 +763,82 @@ pub fn TryAcquireSRWLockExclusive(_machine: &mut Machine, SRWLock: Option<&mut S
 pub fn ReleaseSRWLockExclusive(_machine: &mut Machine, SRWLock: Option<&mut SRWLOCK>) -> u32 {
     0
 }
+
+#[cfg(all(test, feature = "x86-emu"))]
+mod tests {
+    use crate::test_guest::{builtin, new_machine, run, Code, Log};
+
+    /// Have a thread of the given priority wait on an event that the main thread then sets,
+    /// each logging a value once past that point: 1 for the main thread, 2 for the other.
+    fn wakeup_order(priority: i32) -> Vec<u32> {
+        let (mut machine, _host) = new_machine();
+        let [create_event, create_thread, set_priority, sleep, set_event, wait] = [
+            "CreateEventA",
+            "CreateThread",
+            "SetThreadPriority",
+            "Sleep",
+            "SetEvent",
+            "WaitForSingleObject",
+        ]
+        .map(|name| builtin(&mut machine, "kernel32.dll", name));
+        let log = Log::alloc(&mut machine, 0x1000);
+        let mut code = Code::alloc(&mut machine, 0x200);
+        let (thread_proc, record) = (code.base + 0x100, code.base + 0x140);
+        let (event, thread) = (code.base + 0x180, code.base + 0x184);
+        code.push(0)
+            .push(0)
+            .push(0)
+            .push(0)
+            .call(create_event)
+            .emit(&[0xA3]) // mov [event], eax
+            .emit32(event)
+            .push(0)
+            .push(0)
+            .push(0)
+            .push(thread_proc)
+            .push(0)
+            .push(0)
+            .call(create_thread)
+            .emit(&[0xA3]) // mov [thread], eax
+            .emit32(thread)
+            .push(priority as u32)
+            .emit(&[0x50]) // push eax
+            .call(set_priority)
+            // Let the thread run until it waits.
+            .push(10)
+            .call(sleep)
+            .emit(&[0xFF, 0x35]) // push [event]
+            .emit32(event)
+            .call(set_event)
+            .push(1)
+            .call(record)
+            .push(u32::MAX)
+            .emit(&[0xFF, 0x35]) // push [thread]
+            .emit32(thread)
+            .call(wait)
+            .exit_with(&mut machine, 0)
+            .at(0x100)
+            .push(u32::MAX)
+            .emit(&[0xFF, 0x35]) // push [event]
+            .emit32(event)
+            .call(wait)
+            .push(2)
+            .call(record)
+            .emit(&[0xC2, 4, 0]) // ret 4
+            .at(0x140)
+            .recorder(&log, 1, 0);
+        code.start(&mut machine);
+        run(&mut machine);
+        log.read(&machine)
+    }
+
+    #[test]
+    fn wakeup_preempts_for_higher_priority() {
+        assert_eq!(wakeup_order(2), [2, 1]);
+    }
+
+    #[test]
+    fn wakeup_waits_for_slice_at_equal_priority() {
+        assert_eq!(wakeup_order(0), [1, 2]);
+    }
+}
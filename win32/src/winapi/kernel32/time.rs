@@ -5,7 +5,7 @@ use memory::{ExtensionsMut, Pod};
 
 #[win32_derive::dllexport]
 pub fn GetTickCount(machine: &mut Machine) -> u32 {
-    machine.host.ticks()
+    machine.ticks()
 }
 
 // The number of "counts" per second, where counts are the units returned by
@@ -30,8 +30,10 @@ pub fn QueryPerformanceCounter(
     lpPerformanceCount: Option<&mut LARGE_INTEGER>,
 ) -> bool {
     let counter = lpPerformanceCount.unwrap();
-    let ms = machine.host.ticks();
-    let counts = ms as u64 * (QUERY_PERFORMANCE_FREQ as u64 / 1000);
+    let us = machine
+        .clock
+        .now_us(machine.host.ticks(), machine.instr_count());
+    let counts = us * (QUERY_PERFORMANCE_FREQ as u64 / 1_000_000);
     counter.LowPart = counts as u32;
     counter.HighPart = (counts >> 32) as u32 as i32;
     true // success
@@ -47,12 +49,60 @@ pub fn QueryPerformanceFrequency(machine: &mut Machine, lpFrequency: u32) -> boo
     true
 }
 
+/// The wall clock as seen by the guest.  Normally this follows the host, shifted by
+/// any SetLocalTime/SetSystemTime calls; a pinned clock instead starts at a fixed date
+/// in UTC (so local time is the same everywhere) and advances with GetTickCount.
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+pub struct Clock {
+    /// The pinned date, and the tick count at which it was pinned.
+    pinned: Option<(chrono::DateTime<chrono::Utc>, u32)>,
+    /// Adjustment from the host or pinned time, in nanoseconds.
+    offset: i64,
+}
+
+/// Pin the clock to `date` from now on, for deterministic runs.
+pub fn pin_clock(machine: &mut Machine, date: chrono::NaiveDateTime) {
+    let ticks = machine.ticks();
+    machine.state.kernel32.clock = Clock {
+        pinned: Some((date.and_utc(), ticks)),
+        offset: 0,
+    };
+}
+
+/// The current time, in the guest's local time zone.
+pub fn now(machine: &Machine) -> chrono::DateTime<chrono::FixedOffset> {
+    let clock = &machine.state.kernel32.clock;
+    let now = match clock.pinned {
+        Some((date, ticks)) => {
+            let elapsed = machine.ticks().wrapping_sub(ticks);
+            (date + chrono::Duration::milliseconds(elapsed as i64)).fixed_offset()
+        }
+        None => machine.host.system_time().fixed_offset(),
+    };
+    now + chrono::Duration::nanoseconds(clock.offset)
+}
+
+/// Move the clock so that it is now `date`.
+fn set_now(machine: &mut Machine, date: chrono::DateTime<chrono::Utc>) -> bool {
+    let Some(delta) = (date - now(machine).to_utc()).num_nanoseconds() else {
+        return false;
+    };
+    let clock = &mut machine.state.kernel32.clock;
+    clock.offset = clock.offset.saturating_add(delta);
+    true
+}
+
+/// Offset of the guest's local time from UTC.
+fn local_offset(machine: &Machine) -> chrono::FixedOffset {
+    *now(machine).offset()
+}
+
 #[win32_derive::dllexport]
 pub fn GetSystemTimeAsFileTime(
     machine: &mut Machine,
     lpSystemTimeAsFileTime: Option<&mut FILETIME>,
 ) -> u32 {
-    let date_time = machine.host.system_time().to_utc();
+    let date_time = now(machine);
     if let Some(time) = lpSystemTimeAsFileTime {
         let Some(nanos) = date_time.timestamp_nanos_opt() else {
             log::warn!("GetSystemTimeAsFileTime: timestamp_nanos_opt failed");
@@ -66,7 +116,7 @@ pub fn GetSystemTimeAsFileTime(
 
 #[win32_derive::dllexport]
 pub fn GetSystemTime(machine: &mut Machine, lpSystemTime: Option<&mut SYSTEMTIME>) -> u32 {
-    let date_time = machine.host.system_time().naive_utc();
+    let date_time = now(machine).naive_utc();
     if let Some(time) = lpSystemTime {
         *time = SYSTEMTIME::from_chrono(&date_time);
     }
@@ -75,13 +125,32 @@ pub fn GetSystemTime(machine: &mut Machine, lpSystemTime: Option<&mut SYSTEMTIME
 
 #[win32_derive::dllexport]
 pub fn GetLocalTime(machine: &mut Machine, lpSystemTime: Option<&mut SYSTEMTIME>) -> u32 {
-    let date_time = machine.host.system_time();
+    let date_time = now(machine).naive_local();
     if let Some(time) = lpSystemTime {
         *time = SYSTEMTIME::from_chrono(&date_time);
     }
     0
 }
 
+#[win32_derive::dllexport]
+pub fn SetSystemTime(machine: &mut Machine, lpSystemTime: Option<&SYSTEMTIME>) -> bool {
+    let Some(date_time) = lpSystemTime.and_then(SYSTEMTIME::to_chrono) else {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return false;
+    };
+    set_now(machine, date_time.and_utc())
+}
+
+#[win32_derive::dllexport]
+pub fn SetLocalTime(machine: &mut Machine, lpSystemTime: Option<&SYSTEMTIME>) -> bool {
+    let Some(date_time) = lpSystemTime.and_then(SYSTEMTIME::to_chrono) else {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return false;
+    };
+    let utc = date_time - local_offset(machine);
+    set_now(machine, utc.and_utc())
+}
+
 #[win32_derive::dllexport]
 pub fn SystemTimeToFileTime(
     machine: &mut Machine,
@@ -93,19 +162,7 @@ pub fn SystemTimeToFileTime(
         set_last_error(machine, ERROR::INVALID_DATA);
         return false;
     };
-    let date_time = match chrono::NaiveDate::from_ymd_opt(
-        lpSystemTime.wYear as i32,
-        lpSystemTime.wMonth as u32,
-        lpSystemTime.wDay as u32,
-    )
-    .and_then(|dt| {
-        dt.and_hms_milli_opt(
-            lpSystemTime.wHour as u32,
-            lpSystemTime.wMinute as u32,
-            lpSystemTime.wSecond as u32,
-            lpSystemTime.wMilliseconds as u32,
-        )
-    }) {
+    let date_time = match lpSystemTime.to_chrono() {
         Some(dt) => dt.and_utc(),
         None => {
             log::warn!("SystemTimeToFileTime: invalid SYSTEMTIME");
@@ -143,26 +200,132 @@ pub fn FileTimeToSystemTime(
     true
 }
 
+const NANOS_PER_SEC: i64 = 1_000_000_000;
+
 #[win32_derive::dllexport]
-pub async fn Sleep(machine: &mut Machine, dwMilliseconds: u32) -> u32 {
-    if dwMilliseconds == 0 {
-        return 0;
+pub fn FileTimeToLocalFileTime(
+    machine: &mut Machine,
+    lpFileTime: Option<&FILETIME>,
+    lpLocalFileTime: Option<&mut FILETIME>,
+) -> bool {
+    let (Some(utc), Some(local)) = (lpFileTime, lpLocalFileTime) else {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return false;
+    };
+    let offset = local_offset(machine).local_minus_utc() as i64 * NANOS_PER_SEC;
+    *local = FILETIME::from_unix_nanos(utc.to_unix_nanos().saturating_add(offset));
+    true
+}
+
+#[win32_derive::dllexport]
+pub fn LocalFileTimeToFileTime(
+    machine: &mut Machine,
+    lpLocalFileTime: Option<&FILETIME>,
+    lpFileTime: Option<&mut FILETIME>,
+) -> bool {
+    let (Some(local), Some(utc)) = (lpLocalFileTime, lpFileTime) else {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return false;
+    };
+    let offset = local_offset(machine).local_minus_utc() as i64 * NANOS_PER_SEC;
+    *utc = FILETIME::from_unix_nanos(local.to_unix_nanos().saturating_sub(offset));
+    true
+}
+
+#[win32_derive::dllexport]
+pub fn DosDateTimeToFileTime(
+    machine: &mut Machine,
+    wFatDate: u16,
+    wFatTime: u16,
+    lpFileTime: Option<&mut FILETIME>,
+) -> bool {
+    // Date is yyyyyyy mmmm ddddd (years since 1980), time is hhhhh mmmmmm sssss (2s units).
+    let date_time = chrono::NaiveDate::from_ymd_opt(
+        1980 + (wFatDate >> 9) as i32,
+        ((wFatDate >> 5) & 0xF) as u32,
+        (wFatDate & 0x1F) as u32,
+    )
+    .and_then(|date| {
+        date.and_hms_opt(
+            (wFatTime >> 11) as u32,
+            ((wFatTime >> 5) & 0x3F) as u32,
+            ((wFatTime & 0x1F) * 2) as u32,
+        )
+    });
+    let (Some(date_time), Some(time)) = (date_time, lpFileTime) else {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return false;
+    };
+    // DOS times carry no time zone, so like Windows this produces a "local" FILETIME.
+    *time = FILETIME::from_unix_nanos(date_time.and_utc().timestamp_nanos_opt().unwrap());
+    true
+}
+
+#[win32_derive::dllexport]
+pub fn FileTimeToDosDateTime(
+    machine: &mut Machine,
+    lpFileTime: Option<&FILETIME>,
+    lpFatDate: Option<&mut u16>,
+    lpFatTime: Option<&mut u16>,
+) -> bool {
+    let Some(file_time) = lpFileTime else {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return false;
+    };
+    let date_time = chrono::DateTime::from_timestamp_nanos(file_time.to_unix_nanos());
+    if !(1980..2108).contains(&date_time.year()) {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return false;
     }
+    if let Some(date) = lpFatDate {
+        *date = ((date_time.year() - 1980) as u16) << 9
+            | (date_time.month() as u16) << 5
+            | date_time.day() as u16;
+    }
+    if let Some(time) = lpFatTime {
+        *time = (date_time.hour() as u16) << 11
+            | (date_time.minute() as u16) << 5
+            | (date_time.second() / 2) as u16;
+    }
+    true
+}
 
-    #[cfg(feature = "x86-emu")]
-    {
-        let until = machine.host.ticks() + dwMilliseconds;
-        machine.emu.x86.cpu_mut().block(Some(until)).await;
+/// Sleep the current thread; an `alertable` sleep ends early to run queued APCs.
+async fn sleep(machine: &mut Machine, ms: u32, alertable: bool) -> u32 {
+    if alertable && super::deliver_apcs(machine).await {
+        return super::WAIT_IO_COMPLETION;
+    }
+    if ms == 0 {
+        // Yield the rest of our time slice to any other runnable threads.
+        let now = machine.ticks();
+        super::block_thread(machine, Some(now)).await;
+        return 0;
     }
 
-    #[cfg(not(feature = "x86-emu"))]
-    {
-        _ = machine;
-        log::warn!("TODO: sleep");
+    let until = match ms {
+        super::INFINITE => None,
+        ms => Some(machine.ticks() + ms),
+    };
+    // Other threads exiting etc. can wake us early.
+    while until.map_or(true, |until| machine.ticks() < until) {
+        super::block_thread(machine, until).await;
+        if alertable && super::deliver_apcs(machine).await {
+            return super::WAIT_IO_COMPLETION;
+        }
     }
     0
 }
 
+#[win32_derive::dllexport]
+pub async fn Sleep(machine: &mut Machine, dwMilliseconds: u32) -> u32 {
+    sleep(machine, dwMilliseconds, false).await
+}
+
+#[win32_derive::dllexport]
+pub async fn SleepEx(machine: &mut Machine, dwMilliseconds: u32, bAlertable: bool) -> u32 {
+    sleep(machine, dwMilliseconds, bAlertable).await
+}
+
 #[repr(C)]
 #[derive(Debug)]
 pub struct SYSTEMTIME {
@@ -191,30 +354,46 @@ impl SYSTEMTIME {
             wMilliseconds: (dt.nanosecond() / 1_000_000) as u16,
         }
     }
+
+    pub fn to_chrono(&self) -> Option<chrono::NaiveDateTime> {
+        chrono::NaiveDate::from_ymd_opt(self.wYear as i32, self.wMonth as u32, self.wDay as u32)?
+            .and_hms_milli_opt(
+                self.wHour as u32,
+                self.wMinute as u32,
+                self.wSecond as u32,
+                self.wMilliseconds as u32,
+            )
+    }
 }
 unsafe impl memory::Pod for SYSTEMTIME {}
 
 #[repr(C)]
 #[derive(Debug)]
 pub struct TIME_ZONE_INFORMATION {
-    Bias: u32,
+    Bias: i32,
     StandardName: [u16; 32],
     StandardDate: SYSTEMTIME,
-    StandardBias: u32,
+    StandardBias: i32,
     DaylightName: [u16; 32],
     DaylightDate: SYSTEMTIME,
-    DaylightBias: u32,
+    DaylightBias: i32,
 }
 unsafe impl memory::Pod for TIME_ZONE_INFORMATION {}
 
 #[win32_derive::dllexport]
 pub fn GetTimeZoneInformation(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     lpTimeZoneInformation: Option<&mut TIME_ZONE_INFORMATION>,
 ) -> u32 {
     const TIME_ZONE_ID_UNKNOWN: u32 = 0;
-    // It appears to be legal for all struct members to be zero, especially
-    // while TIME_ZONE_ID_UNKNOWN says there's no daylight savings time.
-    *lpTimeZoneInformation.unwrap() = TIME_ZONE_INFORMATION::zeroed();
+    const TIME_ZONE_ID_INVALID: u32 = 0xFFFF_FFFF;
+    let Some(info) = lpTimeZoneInformation else {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return TIME_ZONE_ID_INVALID;
+    };
+    // We only know the host's current offset, not its daylight saving rules, so report a
+    // zone without any; TIME_ZONE_ID_UNKNOWN permits leaving the rest zeroed.
+    *info = TIME_ZONE_INFORMATION::zeroed();
+    info.Bias = -local_offset(machine).local_minus_utc() / 60;
     TIME_ZONE_ID_UNKNOWN
 }
@@ -0,0 +1,129 @@
+//! Implicit thread-local storage, i.e. __declspec(thread) variables, as described by
+//! a module's TLS directory.  (The TlsAlloc() family is in thread.rs.)
+//!
+//! Each thread's TEB.ThreadLocalStoragePointer points at an array, indexed by a module's
+//! TLS index, of pointers to that thread's copy of the module's TLS block.
+
+use super::KernelObject;
+use crate::{machine::Machine, pe};
+use memory::{Extensions, ExtensionsMut};
+
+/// Number of entries in each thread's TLS array.
+/// Windows grows the array as needed, but few processes have more than a handful.
+const TLS_ARRAY_LEN: u32 = 64;
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct ModuleTls {
+    /// Base address of the module, passed to its callbacks.
+    base: u32,
+    /// Template for the initial contents of each thread's block.
+    template: std::ops::Range<u32>,
+    /// Count of zero bytes following the template in each block.
+    zero_fill: u32,
+    /// Callbacks, which take the same arguments as DllMain.
+    callbacks: Vec<u32>,
+}
+
+/// Assign a TLS index to a newly loaded module and give each thread its block.
+pub fn register_tls(machine: &mut Machine, base: u32, dir: &pe::IMAGE_TLS_DIRECTORY) {
+    let index = machine.state.kernel32.tls_modules.len() as u32;
+    if index >= TLS_ARRAY_LEN {
+        log::error!("too many modules with TLS, ignoring TLS of module at {base:x}");
+        return;
+    }
+
+    let mem = machine.mem();
+    let mut callbacks = Vec::new();
+    if dir.AddressOfCallBacks != 0 {
+        let mut addr = dir.AddressOfCallBacks;
+        loop {
+            let callback = mem.get_pod::<u32>(addr);
+            if callback == 0 {
+                break;
+            }
+            callbacks.push(callback);
+            addr += 4;
+        }
+    }
+    if dir.AddressOfIndex != 0 {
+        mem.put_pod::<u32>(dir.AddressOfIndex, index);
+    }
+
+    machine.state.kernel32.tls_modules.push(ModuleTls {
+        base,
+        template: dir.StartAddressOfRawData..dir.EndAddressOfRawData,
+        zero_fill: dir.SizeOfZeroFill,
+        callbacks,
+    });
+
+    // Threads that already exist need a block too.
+    let tebs = machine
+        .state
+        .kernel32
+        .objects
+        .iter()
+        .filter_map(|obj| match obj {
+            KernelObject::Thread(thread) if thread.exit_code.is_none() => Some(thread.teb),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    for teb in tebs {
+        init_thread_tls(machine, teb);
+    }
+}
+
+/// Give a thread a block for each module that has TLS, if it doesn't have one yet.
+pub fn init_thread_tls(machine: &mut Machine, teb_addr: u32) {
+    let blocks = machine
+        .state
+        .kernel32
+        .tls_modules
+        .iter()
+        .map(|module| (module.template.clone(), module.zero_fill))
+        .collect::<Vec<_>>();
+    if blocks.is_empty() {
+        return;
+    }
+    let heap = machine
+        .state
+        .kernel32
+        .get_process_heap(&mut machine.emu.memory);
+    let mem = machine.emu.memory.mem();
+
+    let teb = mem.get_aligned_ref_mut::<super::TEB>(teb_addr);
+    if teb.ThreadLocalStoragePointer == 0 {
+        let array = heap.alloc(mem, TLS_ARRAY_LEN * 4);
+        mem.sub32_mut(array, TLS_ARRAY_LEN * 4).fill(0);
+        teb.ThreadLocalStoragePointer = array;
+    }
+    let array = teb.ThreadLocalStoragePointer;
+
+    for (index, (template, zero_fill)) in blocks.into_iter().enumerate() {
+        let slot = array + index as u32 * 4;
+        if mem.get_pod::<u32>(slot) != 0 {
+            continue;
+        }
+        let template_len = template.end - template.start;
+        let block = heap.alloc(mem, (template_len + zero_fill).max(1));
+        mem.sub32_mut(block, template_len)
+            .copy_from_slice(mem.sub32(template.start, template_len));
+        mem.sub32_mut(block + template_len, zero_fill).fill(0);
+        mem.put_pod::<u32>(slot, block);
+    }
+}
+
+/// Call the TLS callbacks, if any, of the module at `base`.
+pub async fn call_tls_callbacks(machine: &mut Machine, base: u32, reason: u32, reserved: u32) {
+    let Some(module) = machine
+        .state
+        .kernel32
+        .tls_modules
+        .iter()
+        .find(|module| module.base == base)
+    else {
+        return;
+    };
+    for callback in module.callbacks.clone() {
+        machine.call_guest(callback, (base, reason, reserved)).await;
+    }
+}
@@ -0,0 +1,437 @@
+//! The virtual filesystem: maps guest paths onto host paths.
+//!
+//! Mounts map a guest path prefix like `C:\` to a host directory, optionally read-only.
+//! Paths outside any mount are passed to the host as-is.  Lookups are case-insensitive,
+//! as on Windows, even when the host filesystem isn't.
+//!
+//! Relative paths are resolved against the process's current directory, which starts
+//! out as the host's.
+//!
+//! Each mount is also a volume, whose drive type, label and serial number are
+//! configurable for the sake of CD checks.
+//!
+//! Hosts can also mount a scratch directory as the guest's temp directory, which is
+//! emptied when the process exits.
+
+use crate::{
+    host::{File, FileOptions, Host, ReadDir, Stat, StatKind},
+    machine::Machine,
+    winapi::ERROR,
+};
+use typed_path::{Component, WindowsComponent, WindowsPath, WindowsPathBuf};
+
+/// The kind of drive a volume appears as.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DriveKind {
+    #[default]
+    Fixed,
+    CdRom,
+}
+
+/// Volume information reported for a mount.
+#[derive(Clone, Debug, Default)]
+pub struct Volume {
+    pub kind: DriveKind,
+    pub label: String,
+    pub serial: u32,
+}
+
+#[derive(Clone)]
+struct Mount {
+    /// Guest path prefix, e.g. `C:\`.
+    guest: WindowsPathBuf,
+    /// Host directory the prefix maps to, in the form the host's file APIs accept.
+    host: WindowsPathBuf,
+    readonly: bool,
+    volume: Volume,
+}
+
+/// Guest path of the temp directory, when one is mounted.
+pub const TEMP_DIR: &str = "C:\\TEMP\\";
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+pub struct Vfs {
+    /// Mounts describe the host, so save states leave them out; see keep_mounts().
+    #[serde(skip)]
+    mounts: Vec<Mount>,
+    /// The current directory, once set by SetCurrentDirectory.
+    #[serde(with = "saved_path")]
+    cwd: Option<WindowsPathBuf>,
+    /// Whether a temp directory is mounted at TEMP_DIR.
+    #[serde(skip)]
+    temp: bool,
+    /// Whether this process mounted the temp directory, and so cleans it up on exit.
+    #[serde(skip)]
+    owns_temp: bool,
+    /// Last number used to generate a temp file name.
+    temp_unique: u16,
+}
+
+/// Compare path components, ignoring ASCII case.
+fn component_eq(a: &WindowsComponent, b: &WindowsComponent) -> bool {
+    a.as_bytes().eq_ignore_ascii_case(b.as_bytes())
+}
+
+/// The drive letter of a guest path like `C:\foo`, in uppercase.
+fn drive_letter(path: &WindowsPath) -> Option<u8> {
+    match path.as_bytes() {
+        [letter, b':', ..] if letter.is_ascii_alphabetic() => Some(letter.to_ascii_uppercase()),
+        _ => None,
+    }
+}
+
+/// Make a guest path absolute and normalized, resolving `.` and `..` and using
+/// backslash separators.
+pub fn normalize_path(cwd: &WindowsPath, path: &WindowsPath) -> WindowsPathBuf {
+    let path = cwd.join(path);
+    let mut out = WindowsPathBuf::new();
+    for component in path.components() {
+        match component {
+            WindowsComponent::CurDir => {}
+            WindowsComponent::ParentDir => {
+                // Stays at the root, as on Windows.
+                out.pop();
+            }
+            component => out.push(component.as_bytes()),
+        }
+    }
+    out
+}
+
+/// Paths as their bytes, for save states.
+mod saved_path {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use typed_path::WindowsPathBuf;
+
+    pub fn serialize<S: Serializer>(
+        path: &Option<WindowsPathBuf>,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        path.as_ref().map(|path| path.as_bytes()).serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        d: D,
+    ) -> Result<Option<WindowsPathBuf>, D::Error> {
+        Ok(Option::<Vec<u8>>::deserialize(d)?.map(WindowsPathBuf::from))
+    }
+}
+
+impl Vfs {
+    /// Take over the mounts of `current`, the Vfs a save state is being loaded over,
+    /// as those describe the host rather than the guest.
+    pub fn keep_mounts(&mut self, current: Vfs) {
+        self.mounts = current.mounts;
+        self.temp = current.temp;
+        self.owns_temp = current.owns_temp;
+    }
+
+    /// Map the guest path prefix `guest` (e.g. `C:\`) to the host directory `host`.
+    pub fn mount(&mut self, guest: &str, host: WindowsPathBuf, readonly: bool) {
+        let mut guest = normalize_path(WindowsPath::new(""), WindowsPath::new(guest));
+        // Treat a bare drive like "C:" as its root directory.
+        if !guest.has_root() {
+            guest.push("\\");
+        }
+        log::info!(
+            "mounting {} at {}{}",
+            host.to_string_lossy(),
+            guest.to_string_lossy(),
+            if readonly { " (read-only)" } else { "" }
+        );
+        self.mounts.push(Mount {
+            guest,
+            host,
+            readonly,
+            volume: Volume::default(),
+        });
+    }
+
+    /// Get the volume information of the mount at `guest`, to configure it.
+    pub fn volume_mut(&mut self, guest: &str) -> Option<&mut Volume> {
+        let mut guest = normalize_path(WindowsPath::new(""), WindowsPath::new(guest));
+        if !guest.has_root() {
+            guest.push("\\");
+        }
+        self.mounts
+            .iter_mut()
+            .find(|mount| {
+                mount.guest.components().count() == guest.components().count()
+                    && mount
+                        .guest
+                        .components()
+                        .zip(guest.components())
+                        .all(|(a, b)| component_eq(&a, &b))
+            })
+            .map(|mount| &mut mount.volume)
+    }
+
+    /// Mount the host directory `host` as the guest's temp directory.
+    /// Its contents are removed when the process exits.
+    pub fn mount_temp(&mut self, host: WindowsPathBuf) {
+        self.mount(TEMP_DIR, host, false);
+        self.temp = true;
+        self.owns_temp = true;
+    }
+
+    /// The view of the filesystem a child process starts with: the same mounts and
+    /// current directory, with the temp directory left for this process to clean up.
+    pub fn inherit(&self) -> Vfs {
+        Vfs {
+            mounts: self.mounts.clone(),
+            cwd: self.cwd.clone(),
+            temp: self.temp,
+            owns_temp: false,
+            temp_unique: self.temp_unique,
+        }
+    }
+
+    /// Generate the next number for a temp file name, which is never zero.
+    pub fn next_temp_unique(&mut self) -> u16 {
+        self.temp_unique = self.temp_unique.checked_add(1).unwrap_or(1);
+        self.temp_unique
+    }
+
+    /// Change the current directory, which must be an absolute, normalized path.
+    pub fn set_current_dir(&mut self, path: WindowsPathBuf) {
+        self.cwd = Some(path);
+    }
+
+    /// Find the mount a normalized guest path is within, preferring the longest prefix,
+    /// along with the number of path components the mount prefix covers.
+    fn find_mount(&self, path: &WindowsPath) -> Option<(&Mount, usize)> {
+        self.mounts
+            .iter()
+            .filter_map(|mount| {
+                let prefix = mount.guest.components().collect::<Vec<_>>();
+                let matches = path.components().count() >= prefix.len()
+                    && path
+                        .components()
+                        .zip(&prefix)
+                        .all(|(a, b)| component_eq(&a, b));
+                matches.then_some((mount, prefix.len()))
+            })
+            .max_by_key(|&(_, len)| len)
+    }
+}
+
+/// Find the entry of `dir` named `name` regardless of case, or if there is none,
+/// the path it would be created at.
+fn find_case_insensitive(host: &dyn Host, dir: &WindowsPath, name: &[u8]) -> WindowsPathBuf {
+    let exact = dir.join(name);
+    if host.stat(&exact).is_ok() {
+        return exact;
+    }
+    if let Ok(mut entries) = host.read_dir(dir) {
+        while let Ok(Some(entry)) = entries.next() {
+            if entry.name.as_bytes().eq_ignore_ascii_case(name) {
+                return dir.join(entry.name);
+            }
+        }
+    }
+    exact
+}
+
+/// The VFS together with the host it maps paths onto, providing the host's file APIs
+/// in terms of guest paths.
+pub struct VfsHost<'a> {
+    vfs: &'a Vfs,
+    host: &'a dyn Host,
+}
+
+pub fn vfs(machine: &Machine) -> VfsHost<'_> {
+    VfsHost {
+        vfs: &machine.state.kernel32.vfs,
+        host: &*machine.host,
+    }
+}
+
+impl<'a> VfsHost<'a> {
+    pub fn current_dir(&self) -> Result<WindowsPathBuf, ERROR> {
+        match &self.vfs.cwd {
+            Some(cwd) => Ok(cwd.clone()),
+            None => self.host.current_dir(),
+        }
+    }
+
+    /// The directory temp files go in, with a trailing backslash.
+    /// Without a mounted temp directory this is the current directory.
+    pub fn temp_dir(&self) -> Result<String, ERROR> {
+        if self.vfs.temp {
+            return Ok(TEMP_DIR.to_string());
+        }
+        let mut dir = self.current_dir()?.to_string_lossy().into_owned();
+        if !dir.ends_with('\\') {
+            dir.push('\\');
+        }
+        Ok(dir)
+    }
+
+    /// Remove the contents of the temp directory, if one is mounted.
+    pub fn remove_temp_files(&self) {
+        if !self.vfs.owns_temp {
+            return;
+        }
+        if let Err(err) = self.remove_dir_contents(WindowsPath::new(TEMP_DIR)) {
+            log::warn!("failed to remove temp files: {err:?}");
+        }
+    }
+
+    fn remove_dir_contents(&self, dir: &WindowsPath) -> Result<(), ERROR> {
+        let mut entries = Vec::new();
+        let mut read_dir = self.read_dir(dir)?;
+        while let Some(entry) = read_dir.next()? {
+            entries.push(entry);
+        }
+        for entry in entries {
+            let path = dir.join(&entry.name);
+            if entry.stat.kind == StatKind::Directory {
+                self.remove_dir_contents(&path)?;
+                self.remove_dir(&path)?;
+            } else {
+                self.remove_file(&path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Bitmask of the drive letters that exist, with bit 0 for A:.
+    /// C: always exists, along with the drives of any mounts and of the current directory.
+    pub fn drives(&self) -> u32 {
+        let mut drives = 1 << (b'C' - b'A');
+        let cwd = self.current_dir().ok();
+        let paths = self.vfs.mounts.iter().map(|mount| mount.guest.as_path());
+        for path in paths.chain(cwd.as_deref()) {
+            if let Some(letter) = drive_letter(path) {
+                drives |= 1 << (letter - b'A');
+            }
+        }
+        drives
+    }
+
+    /// Get the volume a path is on, defaulting to the current directory's volume,
+    /// along with whether it's mounted read-only.
+    pub fn volume(&self, path: Option<&str>) -> Result<(Volume, bool), ERROR> {
+        let path = self.full_path(WindowsPath::new(path.unwrap_or(".")))?;
+        let exists = match drive_letter(&path) {
+            Some(letter) => self.drives() & (1 << (letter - b'A')) != 0,
+            None => false,
+        };
+        if !exists {
+            return Err(ERROR::PATH_NOT_FOUND);
+        }
+        Ok(match self.vfs.find_mount(&path) {
+            Some((mount, _)) => (mount.volume.clone(), mount.readonly),
+            None => (Volume::default(), false),
+        })
+    }
+
+    /// Make a guest path absolute, relative to the current directory.
+    pub fn full_path(&self, path: &WindowsPath) -> Result<WindowsPathBuf, ERROR> {
+        Ok(normalize_path(&self.current_dir()?, path))
+    }
+
+    /// Map a guest path to a host path, also returning whether it's on a read-only mount.
+    fn resolve(&self, path: &WindowsPath) -> Result<(WindowsPathBuf, bool), ERROR> {
+        let path = self.full_path(path)?;
+        let (mut out, readonly, skip) = match self.vfs.find_mount(&path) {
+            Some((mount, len)) => (mount.host.clone(), mount.readonly, len),
+            None => (WindowsPathBuf::new(), false, 0),
+        };
+        for component in path.components().skip(skip) {
+            match component {
+                WindowsComponent::Normal(name) => {
+                    out = find_case_insensitive(self.host, &out, name)
+                }
+                component => out.push(component.as_bytes()),
+            }
+        }
+        Ok((out, readonly))
+    }
+
+    /// Map a guest path to a host path for modification, failing on read-only mounts.
+    fn resolve_writable(&self, path: &WindowsPath) -> Result<WindowsPathBuf, ERROR> {
+        match self.resolve(path)? {
+            (_, true) => Err(ERROR::ACCESS_DENIED),
+            (path, false) => Ok(path),
+        }
+    }
+
+    pub fn open(&self, path: &WindowsPath, options: FileOptions) -> Result<Box<dyn File>, ERROR> {
+        let modifies = options.write || options.truncate || options.create || options.create_new;
+        let path = if modifies {
+            self.resolve_writable(path)?
+        } else {
+            self.resolve(path)?.0
+        };
+        self.host.open(&path, options)
+    }
+
+    pub fn stat(&self, path: &WindowsPath) -> Result<Stat, ERROR> {
+        let (path, readonly) = self.resolve(path)?;
+        let mut stat = self.host.stat(&path)?;
+        stat.readonly |= readonly;
+        Ok(stat)
+    }
+
+    pub fn read_dir(&self, path: &WindowsPath) -> Result<Box<dyn ReadDir>, ERROR> {
+        let (path, _) = self.resolve(path)?;
+        self.host.read_dir(&path)
+    }
+
+    pub fn create_dir(&self, path: &WindowsPath) -> Result<(), ERROR> {
+        self.host.create_dir(&self.resolve_writable(path)?)
+    }
+
+    pub fn remove_file(&self, path: &WindowsPath) -> Result<(), ERROR> {
+        self.host.remove_file(&self.resolve_writable(path)?)
+    }
+
+    pub fn remove_dir(&self, path: &WindowsPath) -> Result<(), ERROR> {
+        self.host.remove_dir(&self.resolve_writable(path)?)
+    }
+
+    pub fn rename(&self, from: &WindowsPath, to: &WindowsPath) -> Result<(), ERROR> {
+        let from = self.resolve_writable(from)?;
+        let to = self.resolve_writable(to)?;
+        self.host.rename(&from, &to)
+    }
+
+    pub fn set_readonly(&self, path: &WindowsPath, readonly: bool) -> Result<(), ERROR> {
+        self.host
+            .set_readonly(&self.resolve_writable(path)?, readonly)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_path() {
+        let cwd = WindowsPath::new("C:\\games\\app");
+        let cases = [
+            ("data\\file.pak", "C:\\games\\app\\data\\file.pak"),
+            ("data/sub/../file.pak", "C:\\games\\app\\data\\file.pak"),
+            (".\\file.pak", "C:\\games\\app\\file.pak"),
+            ("D:\\cd\\..\\..\\setup.exe", "D:\\setup.exe"),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(
+                normalize_path(cwd, WindowsPath::new(path)).to_string_lossy(),
+                expected
+            );
+        }
+
+        let mut vfs = Vfs::default();
+        vfs.mount("c:", WindowsPathBuf::from("Z:\\sandbox"), false);
+        vfs.mount("C:\\Games\\CD", WindowsPathBuf::from("Z:\\cd"), true);
+        let mount = |path: &str| {
+            vfs.find_mount(&normalize_path(cwd, WindowsPath::new(path)))
+                .map(|(mount, len)| (mount.host.to_string_lossy().into_owned(), len))
+        };
+        assert_eq!(mount("C:\\x"), Some(("Z:\\sandbox".into(), 2)));
+        assert_eq!(mount("c:\\games\\cd\\x"), Some(("Z:\\cd".into(), 4)));
+        assert_eq!(mount("D:\\x"), None);
+    }
+}
@@ -0,0 +1,274 @@
+//! Drives and volumes: GetDriveType, GetVolumeInformation and friends.
+//!
+//! The volumes are those of the VFS mounts; see vfs.rs.
+
+use super::{set_last_error, vfs, DriveKind};
+use crate::{
+    machine::Machine,
+    winapi::{stack_args::ArrayWithSizeMut, types::Str16, ERROR},
+};
+
+const DRIVE_NO_ROOT_DIR: u32 = 1;
+const DRIVE_FIXED: u32 = 3;
+const DRIVE_CDROM: u32 = 5;
+
+const FILE_CASE_PRESERVED_NAMES: u32 = 0x2;
+const FILE_UNICODE_ON_DISK: u32 = 0x4;
+const FILE_READ_ONLY_VOLUME: u32 = 0x8_0000;
+
+const BYTES_PER_SECTOR: u32 = 512;
+const SECTORS_PER_CLUSTER: u32 = 8;
+/// Sizes reported for fixed drives.  These stay under 2GB because some old programs
+/// compute free space in a signed 32-bit int.
+const FIXED_TOTAL_BYTES: u64 = 2_000_000_000;
+const FIXED_FREE_BYTES: u64 = 1_000_000_000;
+/// Size reported for CD-ROMs, which are always full.
+const CDROM_TOTAL_BYTES: u64 = 650 * 1024 * 1024;
+
+#[win32_derive::dllexport]
+pub fn GetLogicalDrives(machine: &mut Machine) -> u32 {
+    vfs(machine).drives()
+}
+
+#[win32_derive::dllexport]
+pub fn GetDriveTypeA(machine: &mut Machine, lpRootPathName: Option<&str>) -> u32 {
+    match vfs(machine).volume(lpRootPathName) {
+        Ok((volume, _)) => match volume.kind {
+            DriveKind::Fixed => DRIVE_FIXED,
+            DriveKind::CdRom => DRIVE_CDROM,
+        },
+        Err(_) => DRIVE_NO_ROOT_DIR,
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn GetDriveTypeW(machine: &mut Machine, lpRootPathName: Option<&Str16>) -> u32 {
+    GetDriveTypeA(machine, lpRootPathName.map(|f| f.to_string()).as_deref())
+}
+
+/// Copy a string to an optional nul-terminated output buffer.
+fn copy_out<T: Copy + Default>(buf: Option<&mut [T]>, str: &[T]) -> Result<(), ERROR> {
+    let Some(buf) = buf else {
+        return Ok(());
+    };
+    if buf.len() < str.len() + 1 {
+        return Err(ERROR::BAD_LENGTH);
+    }
+    buf[..str.len()].copy_from_slice(str);
+    buf[str.len()] = T::default();
+    Ok(())
+}
+
+struct VolumeInformation {
+    label: String,
+    serial: u32,
+    flags: u32,
+    file_system: &'static str,
+}
+
+fn volume_information(machine: &Machine, root: Option<&str>) -> Result<VolumeInformation, ERROR> {
+    let (volume, readonly) = vfs(machine).volume(root)?;
+    let mut flags = FILE_CASE_PRESERVED_NAMES | FILE_UNICODE_ON_DISK;
+    let file_system = match volume.kind {
+        DriveKind::Fixed => "NTFS",
+        DriveKind::CdRom => {
+            flags = FILE_CASE_PRESERVED_NAMES | FILE_READ_ONLY_VOLUME;
+            "CDFS"
+        }
+    };
+    if readonly {
+        flags |= FILE_READ_ONLY_VOLUME;
+    }
+    Ok(VolumeInformation {
+        label: volume.label,
+        serial: volume.serial,
+        flags,
+        file_system,
+    })
+}
+
+#[win32_derive::dllexport]
+pub fn GetVolumeInformationA(
+    machine: &mut Machine,
+    lpRootPathName: Option<&str>,
+    lpVolumeNameBuffer: ArrayWithSizeMut<u8>,
+    lpVolumeSerialNumber: Option<&mut u32>,
+    lpMaximumComponentLength: Option<&mut u32>,
+    lpFileSystemFlags: Option<&mut u32>,
+    lpFileSystemNameBuffer: ArrayWithSizeMut<u8>,
+) -> bool {
+    let result = volume_information(machine, lpRootPathName).and_then(|info| {
+        copy_out(lpVolumeNameBuffer.to_option(), info.label.as_bytes())?;
+        copy_out(
+            lpFileSystemNameBuffer.to_option(),
+            info.file_system.as_bytes(),
+        )?;
+        Ok(info)
+    });
+    let info = match result {
+        Ok(info) => info,
+        Err(err) => {
+            log::debug!("GetVolumeInformationA({lpRootPathName:?}) failed: {err:?}");
+            set_last_error(machine, err);
+            return false;
+        }
+    };
+    if let Some(serial) = lpVolumeSerialNumber {
+        *serial = info.serial;
+    }
+    if let Some(len) = lpMaximumComponentLength {
+        *len = 255;
+    }
+    if let Some(flags) = lpFileSystemFlags {
+        *flags = info.flags;
+    }
+    set_last_error(machine, ERROR::SUCCESS);
+    true
+}
+
+#[win32_derive::dllexport]
+pub fn GetVolumeInformationW(
+    machine: &mut Machine,
+    lpRootPathName: Option<&Str16>,
+    lpVolumeNameBuffer: ArrayWithSizeMut<u16>,
+    lpVolumeSerialNumber: Option<&mut u32>,
+    lpMaximumComponentLength: Option<&mut u32>,
+    lpFileSystemFlags: Option<&mut u32>,
+    lpFileSystemNameBuffer: ArrayWithSizeMut<u16>,
+) -> bool {
+    let root = lpRootPathName.map(|f| f.to_string());
+    let result = volume_information(machine, root.as_deref()).and_then(|info| {
+        let label = info.label.encode_utf16().collect::<Vec<_>>();
+        copy_out(lpVolumeNameBuffer.to_option(), &label)?;
+        let file_system = info.file_system.encode_utf16().collect::<Vec<_>>();
+        copy_out(lpFileSystemNameBuffer.to_option(), &file_system)?;
+        Ok(info)
+    });
+    let info = match result {
+        Ok(info) => info,
+        Err(err) => {
+            log::debug!("GetVolumeInformationW({root:?}) failed: {err:?}");
+            set_last_error(machine, err);
+            return false;
+        }
+    };
+    if let Some(serial) = lpVolumeSerialNumber {
+        *serial = info.serial;
+    }
+    if let Some(len) = lpMaximumComponentLength {
+        *len = 255;
+    }
+    if let Some(flags) = lpFileSystemFlags {
+        *flags = info.flags;
+    }
+    set_last_error(machine, ERROR::SUCCESS);
+    true
+}
+
+/// Total and free bytes of the volume a path is on.
+fn disk_space(machine: &Machine, root: Option<&str>) -> Result<(u64, u64), ERROR> {
+    let (volume, _) = vfs(machine).volume(root)?;
+    Ok(match volume.kind {
+        DriveKind::Fixed => (FIXED_TOTAL_BYTES, FIXED_FREE_BYTES),
+        DriveKind::CdRom => (CDROM_TOTAL_BYTES, 0),
+    })
+}
+
+#[win32_derive::dllexport]
+pub fn GetDiskFreeSpaceA(
+    machine: &mut Machine,
+    lpRootPathName: Option<&str>,
+    lpSectorsPerCluster: Option<&mut u32>,
+    lpBytesPerSector: Option<&mut u32>,
+    lpNumberOfFreeClusters: Option<&mut u32>,
+    lpTotalNumberOfClusters: Option<&mut u32>,
+) -> bool {
+    let (total, free) = match disk_space(machine, lpRootPathName) {
+        Ok(space) => space,
+        Err(err) => {
+            log::debug!("GetDiskFreeSpaceA({lpRootPathName:?}) failed: {err:?}");
+            set_last_error(machine, err);
+            return false;
+        }
+    };
+    let cluster_size = (SECTORS_PER_CLUSTER * BYTES_PER_SECTOR) as u64;
+    if let Some(sectors) = lpSectorsPerCluster {
+        *sectors = SECTORS_PER_CLUSTER;
+    }
+    if let Some(bytes) = lpBytesPerSector {
+        *bytes = BYTES_PER_SECTOR;
+    }
+    if let Some(clusters) = lpNumberOfFreeClusters {
+        *clusters = (free / cluster_size) as u32;
+    }
+    if let Some(clusters) = lpTotalNumberOfClusters {
+        *clusters = (total / cluster_size) as u32;
+    }
+    set_last_error(machine, ERROR::SUCCESS);
+    true
+}
+
+#[win32_derive::dllexport]
+pub fn GetDiskFreeSpaceW(
+    machine: &mut Machine,
+    lpRootPathName: Option<&Str16>,
+    lpSectorsPerCluster: Option<&mut u32>,
+    lpBytesPerSector: Option<&mut u32>,
+    lpNumberOfFreeClusters: Option<&mut u32>,
+    lpTotalNumberOfClusters: Option<&mut u32>,
+) -> bool {
+    GetDiskFreeSpaceA(
+        machine,
+        lpRootPathName.map(|f| f.to_string()).as_deref(),
+        lpSectorsPerCluster,
+        lpBytesPerSector,
+        lpNumberOfFreeClusters,
+        lpTotalNumberOfClusters,
+    )
+}
+
+#[win32_derive::dllexport]
+pub fn GetDiskFreeSpaceExA(
+    machine: &mut Machine,
+    lpDirectoryName: Option<&str>,
+    lpFreeBytesAvailableToCaller: Option<&mut u64>,
+    lpTotalNumberOfBytes: Option<&mut u64>,
+    lpTotalNumberOfFreeBytes: Option<&mut u64>,
+) -> bool {
+    let (total, free) = match disk_space(machine, lpDirectoryName) {
+        Ok(space) => space,
+        Err(err) => {
+            log::debug!("GetDiskFreeSpaceExA({lpDirectoryName:?}) failed: {err:?}");
+            set_last_error(machine, err);
+            return false;
+        }
+    };
+    if let Some(bytes) = lpFreeBytesAvailableToCaller {
+        *bytes = free;
+    }
+    if let Some(bytes) = lpTotalNumberOfBytes {
+        *bytes = total;
+    }
+    if let Some(bytes) = lpTotalNumberOfFreeBytes {
+        *bytes = free;
+    }
+    set_last_error(machine, ERROR::SUCCESS);
+    true
+}
+
+#[win32_derive::dllexport]
+pub fn GetDiskFreeSpaceExW(
+    machine: &mut Machine,
+    lpDirectoryName: Option<&Str16>,
+    lpFreeBytesAvailableToCaller: Option<&mut u64>,
+    lpTotalNumberOfBytes: Option<&mut u64>,
+    lpTotalNumberOfFreeBytes: Option<&mut u64>,
+) -> bool {
+    GetDiskFreeSpaceExA(
+        machine,
+        lpDirectoryName.map(|f| f.to_string()).as_deref(),
+        lpFreeBytesAvailableToCaller,
+        lpTotalNumberOfBytes,
+        lpTotalNumberOfFreeBytes,
+    )
+}
@@ -1,6 +1,6 @@
 use crate::machine::MemImpl;
 
-mod advapi32;
+pub mod advapi32;
 mod alloc;
 mod bass;
 mod bitmap;
@@ -16,8 +16,10 @@ pub mod kernel32;
 mod ntdll;
 mod ole32;
 mod oleaut32;
+mod printf;
 mod retrowin32_test;
-mod stack_args;
+mod shell32;
+pub(crate) mod stack_args;
 pub mod types;
 mod ucrtbase;
 pub mod user32;
@@ -42,7 +44,7 @@ impl<'a> std::fmt::Display for ImportSymbol<'a> {
     }
 }
 
-pub const DLLS: [builtin::BuiltinDLL; 16] = [
+pub const DLLS: [builtin::BuiltinDLL; 17] = [
     builtin::advapi32::DLL,
     builtin::bass::DLL,
     builtin::ddraw::DLL,
@@ -52,6 +54,7 @@ pub const DLLS: [builtin::BuiltinDLL; 16] = [
     builtin::ntdll::DLL,
     builtin::ole32::DLL,
     builtin::oleaut32::DLL,
+    builtin::shell32::DLL,
     builtin::ucrtbase::DLL,
     builtin::user32::DLL,
     builtin::vcruntime140::DLL,
@@ -81,14 +84,17 @@ pub fn apiset(name: &str) -> Option<&'static str> {
     })
 }
 
+#[derive(serde::Serialize, serde::Deserialize)]
 pub struct State {
     scratch: heap::Heap,
 
+    pub advapi32: advapi32::State,
     pub ddraw: ddraw::State,
     pub dsound: dsound::State,
     pub gdi32: gdi32::State,
     pub kernel32: kernel32::State,
     pub user32: user32::State,
+    pub vcruntime140: vcruntime140::State,
     pub winmm: winmm::State,
 }
 
@@ -98,11 +104,13 @@ impl State {
 
         State {
             scratch,
+            advapi32: advapi32::State::default(),
             ddraw: ddraw::State::default(),
             dsound: dsound::State::default(),
             gdi32: gdi32::State::default(),
             kernel32,
             user32: user32::State::default(),
+            vcruntime140: vcruntime140::State::default(),
             winmm: winmm::State::default(),
         }
     }
@@ -3,7 +3,7 @@
 
 use crate::{
     machine::Machine,
-    winapi::{stack_args::ArrayWithSizeMut, types::HFILE},
+    winapi::{kernel32, stack_args::ArrayWithSizeMut, types::HFILE},
 };
 
 const STATUS_SUCCESS: u32 = 0;
@@ -28,7 +28,7 @@ pub fn NtReadFile(
     ByteOffset: Option<&mut u64>,
     Key: u32,
 ) -> u32 {
-    let file = machine.state.kernel32.files.get_mut(FileHandle).unwrap();
+    let file = machine.state.kernel32.objects.file_mut(FileHandle).unwrap();
     if Event != 0 {
         todo!();
     }
@@ -38,7 +38,7 @@ pub fn NtReadFile(
         todo!();
     }
 
-    let len = file.read(buf).unwrap();
+    let len = file.file.read(buf).unwrap();
     *status_block = IO_STATUS_BLOCK {
         Status: STATUS_SUCCESS,
         Information: len as u32,
@@ -50,3 +50,45 @@ pub fn NtReadFile(
 pub fn RtlExitUserProcess(machine: &mut Machine, exit_code: u32) {
     machine.exit(exit_code);
 }
+
+#[win32_derive::dllexport]
+pub async fn RtlUnwind(
+    machine: &mut Machine,
+    TargetFrame: u32,
+    TargetIp: u32,
+    ExceptionRecord: u32,
+    ReturnValue: u32,
+) -> u32 {
+    kernel32::unwind(machine, TargetFrame, ExceptionRecord).await;
+    // The caller resumes with ReturnValue in eax; TargetIp is unused on x86.
+    ReturnValue
+}
+
+#[win32_derive::dllexport(fastcall)]
+pub fn RtlUlongByteSwap(_machine: &mut Machine, Source: u32) -> u32 {
+    Source.swap_bytes()
+}
+
+#[win32_derive::dllexport(fastcall)]
+pub fn RtlUshortByteSwap(_machine: &mut Machine, Source: u16) -> u32 {
+    Source.swap_bytes() as u32
+}
+
+#[cfg(all(test, feature = "x86-emu"))]
+mod tests {
+    use crate::test_guest::{builtin, new_machine, run, Code};
+
+    #[test]
+    fn fastcall_takes_ecx() {
+        let (mut machine, _host) = new_machine();
+        let swap = builtin(&mut machine, "ntdll.dll", "RtlUlongByteSwap");
+        let mut code = Code::alloc(&mut machine, 0x100);
+        code.emit(&[0xB9]) // mov ecx, 0x11223344
+            .emit32(0x1122_3344)
+            .emit(&[0xBA, 0xFF, 0xFF, 0xFF, 0xFF]) // mov edx, -1
+            .call(swap)
+            .exit_with(&mut machine, 0);
+        code.start(&mut machine);
+        assert_eq!(run(&mut machine), 0x4433_2211);
+    }
+}
@@ -0,0 +1,577 @@
+//! The printf format engine, shared by user32's wsprintf family and the C runtime's
+//! printf family.
+//! This module does not become its own DLL.
+
+use super::{kernel32::CodePage, stack_args::VarArgs};
+use memory::{Extensions, Mem};
+
+/// Which printf is formatting, as they differ in what they accept.
+#[derive(Clone, Copy, Debug)]
+pub enum Dialect {
+    /// wsprintf, which has no floating point and no * widths.
+    Wsprintf,
+    /// The C runtime, per the options of ucrtbase's __stdio_common functions.
+    Crt {
+        /// In a wide format, %s takes a narrow string, as in ISO C, rather than a wide one.
+        iso_wide_specifiers: bool,
+        /// Exponents have at least three digits, as in msvcrt, rather than two.
+        three_digit_exponents: bool,
+    },
+}
+
+/// A character of a format string and its output: u8 for the ANSI functions, whose
+/// strings are in the ANSI code page, and u16 for the wide ones.
+pub trait Unit: memory::Pod + Copy + Eq {
+    const WIDE: bool;
+    fn from_ascii(c: u8) -> Self;
+    fn to_ascii(self) -> Option<u8>;
+    fn from_narrow(cp: CodePage, bytes: &[u8]) -> Vec<Self>;
+    fn from_wide(cp: CodePage, chars: &[u16]) -> Vec<Self>;
+}
+
+impl Unit for u8 {
+    const WIDE: bool = false;
+    fn from_ascii(c: u8) -> Self {
+        c
+    }
+    fn to_ascii(self) -> Option<u8> {
+        self.is_ascii().then_some(self)
+    }
+    fn from_narrow(_cp: CodePage, bytes: &[u8]) -> Vec<Self> {
+        // Copy the bytes as is, as they may be in a double-byte code page.
+        bytes.to_vec()
+    }
+    fn from_wide(cp: CodePage, chars: &[u16]) -> Vec<Self> {
+        cp.encode(chars, b"?", false).unwrap().0
+    }
+}
+
+impl Unit for u16 {
+    const WIDE: bool = true;
+    fn from_ascii(c: u8) -> Self {
+        c as u16
+    }
+    fn to_ascii(self) -> Option<u8> {
+        (self < 0x80).then_some(self as u8)
+    }
+    fn from_narrow(cp: CodePage, bytes: &[u8]) -> Vec<Self> {
+        cp.decode(bytes, false, false).unwrap()
+    }
+    fn from_wide(_cp: CodePage, chars: &[u16]) -> Vec<Self> {
+        chars.to_vec()
+    }
+}
+
+/// The bytes of formatted output, for copying to guest memory.
+pub fn as_bytes<T: Unit>(units: &[T]) -> &[u8] {
+    unsafe { std::slice::from_raw_parts(units.as_ptr() as *const u8, std::mem::size_of_val(units)) }
+}
+
+/// Formatted output, kept up to a limit beyond which it is only counted, so a format
+/// can't produce more than the destination holds.
+pub struct Output<T> {
+    pub units: Vec<T>,
+    /// The length of the whole output, including what didn't fit under the limit.
+    pub len: usize,
+    limit: usize,
+}
+
+impl<T: Unit> Output<T> {
+    fn new(limit: usize) -> Self {
+        Output {
+            units: Vec::new(),
+            len: 0,
+            limit,
+        }
+    }
+
+    fn push(&mut self, c: T) {
+        self.repeat(c, 1);
+    }
+
+    fn extend(&mut self, units: impl IntoIterator<Item = T>) {
+        for c in units {
+            self.push(c);
+        }
+    }
+
+    fn ascii(&mut self, s: &str) {
+        self.extend(s.bytes().map(T::from_ascii));
+    }
+
+    fn repeat(&mut self, c: T, n: usize) {
+        let room = self.limit.saturating_sub(self.units.len());
+        self.units.extend(std::iter::repeat_n(c, n.min(room)));
+        self.len = self.len.saturating_add(n);
+    }
+}
+
+/// Float precisions are capped at this many digits, which is past where any f64's
+/// decimal expansion ends.
+const FLOAT_DIGITS: usize = 1100;
+
+/// A parsed %-specification, other than its conversion character.
+#[derive(Default)]
+struct Spec {
+    left: bool,
+    zero: bool,
+    plus: bool,
+    space: bool,
+    alt: bool,
+    width: usize,
+    precision: Option<usize>,
+    /// Size of an integer argument, from the h/l/I64 prefixes.
+    bits: u32,
+    /// Whether a string or character argument is wide, if the prefix says.
+    wide: Option<bool>,
+}
+
+impl Spec {
+    /// Append body, preceded by prefix (a sign or 0x) and the given number of zeros,
+    /// padded out to the width.
+    fn pad<T: Unit>(
+        &self,
+        out: &mut Output<T>,
+        prefix: &str,
+        zeros: usize,
+        body: Vec<T>,
+        zero: bool,
+    ) {
+        let len = prefix
+            .len()
+            .saturating_add(zeros)
+            .saturating_add(body.len());
+        let fill = self.width.saturating_sub(len);
+        // Only what fits under the output's limit is materialized, however wide the field.
+        let (space, digit0) = (T::from_ascii(b' '), T::from_ascii(b'0'));
+        if self.left {
+            out.ascii(prefix);
+            out.repeat(digit0, zeros);
+            out.extend(body);
+            out.repeat(space, fill);
+        } else if self.zero && zero {
+            out.ascii(prefix);
+            out.repeat(digit0, fill.saturating_add(zeros));
+            out.extend(body);
+        } else {
+            out.repeat(space, fill);
+            out.ascii(prefix);
+            out.repeat(digit0, zeros);
+            out.extend(body);
+        }
+    }
+
+    fn sign(&self, negative: bool) -> &'static str {
+        if negative {
+            "-"
+        } else if self.plus {
+            "+"
+        } else if self.space {
+            " "
+        } else {
+            ""
+        }
+    }
+}
+
+type Chars<'a, T> = std::iter::Peekable<std::iter::Copied<std::slice::Iter<'a, T>>>;
+
+/// The next character of a format string, if it's ASCII.
+fn peek<T: Unit>(fmt: &mut Chars<T>) -> Option<u8> {
+    fmt.peek().and_then(|c| c.to_ascii())
+}
+
+/// Parse a width or precision: digits, or with star (for the C runtime), a * that
+/// takes the number from the arguments.
+fn number<T: Unit>(fmt: &mut Chars<T>, star: &mut Option<(&mut VarArgs, Mem)>) -> Option<i32> {
+    if let Some((args, mem)) = star {
+        if peek(fmt) == Some(b'*') {
+            fmt.next();
+            return Some(args.pop::<i32>(*mem));
+        }
+    }
+    let mut n = None;
+    while let Some(d) = peek(fmt).filter(u8::is_ascii_digit) {
+        n = Some(
+            n.unwrap_or(0i32)
+                .saturating_mul(10)
+                .saturating_add((d - b'0') as i32),
+        );
+        fmt.next();
+    }
+    n
+}
+
+/// Format fmt, taking the arguments its specifications call for from args, and
+/// returning the output without a nul terminator, kept up to limit units.
+pub fn format<T: Unit>(
+    mem: Mem,
+    cp: CodePage,
+    dialect: Dialect,
+    fmt: &[T],
+    args: &mut VarArgs,
+    limit: usize,
+) -> Output<T> {
+    let crt = matches!(dialect, Dialect::Crt { .. });
+    let mut out = Output::new(limit);
+    let mut fmt = fmt.iter().copied().peekable();
+    while let Some(c) = fmt.next() {
+        if c.to_ascii() != Some(b'%') {
+            out.push(c);
+            continue;
+        }
+
+        let mut spec = Spec {
+            bits: 32,
+            ..Default::default()
+        };
+        while let Some(c) = peek(&mut fmt) {
+            match c {
+                b'-' => spec.left = true,
+                b'0' => spec.zero = true,
+                b'+' => spec.plus = true,
+                b' ' => spec.space = true,
+                b'#' => spec.alt = true,
+                _ => break,
+            }
+            fmt.next();
+        }
+
+        let mut star = crt.then_some((&mut *args, mem));
+        if let Some(width) = number(&mut fmt, &mut star) {
+            // A negative * width means left-justify.
+            spec.left |= width < 0;
+            spec.width = width.unsigned_abs() as usize;
+        }
+        if peek(&mut fmt) == Some(b'.') {
+            fmt.next();
+            // A negative * precision is as if there were none.
+            spec.precision = match number(&mut fmt, &mut star) {
+                Some(n) if n < 0 => None,
+                n => Some(n.unwrap_or(0) as usize),
+            };
+        }
+
+        while let Some(c) = peek(&mut fmt).filter(|c| b"hlwjztLI".contains(c)) {
+            fmt.next();
+            match c {
+                b'h' if peek(&mut fmt) == Some(b'h') => {
+                    fmt.next();
+                    spec.bits = 8;
+                    spec.wide = Some(false);
+                }
+                b'h' => {
+                    spec.bits = 16;
+                    spec.wide = Some(false);
+                }
+                b'l' if peek(&mut fmt) == Some(b'l') => {
+                    fmt.next();
+                    spec.bits = 64;
+                }
+                b'l' | b'w' => spec.wide = Some(true),
+                b'j' => spec.bits = 64,
+                b'z' | b't' | b'L' => {}
+                b'I' => {
+                    // I64 and I32, or a bare I for size_t.
+                    let digits = fmt.clone().take(2).map(Unit::to_ascii).collect::<Vec<_>>();
+                    if digits == [Some(b'6'), Some(b'4')] {
+                        spec.bits = 64;
+                        fmt.nth(1);
+                    } else if digits == [Some(b'3'), Some(b'2')] {
+                        fmt.nth(1);
+                    }
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        let Some(conv) = fmt.next() else {
+            break;
+        };
+        let Some(conv) = conv.to_ascii() else {
+            out.push(conv);
+            continue;
+        };
+        match conv {
+            b'd' | b'i' | b'u' | b'x' | b'X' | b'o' => {
+                let raw = match spec.bits {
+                    64 => args.pop_u64(mem),
+                    _ => args.pop::<u32>(mem) as u64,
+                };
+                let signed = matches!(conv, b'd' | b'i');
+                let (value, negative) = match (spec.bits, signed) {
+                    (8, true) => ((raw as i8).unsigned_abs() as u64, (raw as i8) < 0),
+                    (16, true) => ((raw as i16).unsigned_abs() as u64, (raw as i16) < 0),
+                    (64, true) => ((raw as i64).unsigned_abs(), (raw as i64) < 0),
+                    (_, true) => ((raw as i32).unsigned_abs() as u64, (raw as i32) < 0),
+                    (8, false) => (raw as u8 as u64, false),
+                    (16, false) => (raw as u16 as u64, false),
+                    (64, false) => (raw, false),
+                    (_, false) => (raw as u32 as u64, false),
+                };
+                let (digits, prefix) = match conv {
+                    b'x' => (
+                        format!("{value:x}"),
+                        if spec.alt && value != 0 { "0x" } else { "" },
+                    ),
+                    b'X' => (
+                        format!("{value:X}"),
+                        if spec.alt && value != 0 { "0X" } else { "" },
+                    ),
+                    b'o' => (
+                        format!("{value:o}"),
+                        if spec.alt && value != 0 { "0" } else { "" },
+                    ),
+                    _ if signed => (value.to_string(), spec.sign(negative)),
+                    _ => (value.to_string(), ""),
+                };
+                let (digits, zeros) = match spec.precision {
+                    // An explicit zero precision prints nothing for zero.
+                    Some(0) if value == 0 => (String::new(), 0),
+                    Some(precision) => {
+                        let zeros = precision.saturating_sub(digits.len());
+                        (digits, zeros)
+                    }
+                    None => (digits, 0),
+                };
+                let body = digits.bytes().map(T::from_ascii).collect();
+                spec.pad(&mut out, prefix, zeros, body, spec.precision.is_none());
+            }
+            b'p' => {
+                let body = format!("{:08X}", args.pop::<u32>(mem));
+                spec.pad(
+                    &mut out,
+                    "",
+                    0,
+                    body.bytes().map(T::from_ascii).collect(),
+                    false,
+                );
+            }
+            b'c' | b'C' => {
+                let c = args.pop::<u32>(mem);
+                let body = if spec.wide.unwrap_or(T::WIDE == (conv == b'c')) {
+                    T::from_wide(cp, &[c as u16])
+                } else {
+                    T::from_narrow(cp, &[c as u8])
+                };
+                spec.pad(&mut out, "", 0, body, true);
+            }
+            b's' | b'S' => {
+                let addr = args.pop::<u32>(mem);
+                let native = match dialect {
+                    Dialect::Crt {
+                        iso_wide_specifiers: true,
+                        ..
+                    } => false,
+                    _ => T::WIDE,
+                };
+                let wide = spec.wide.unwrap_or(native == (conv == b's'));
+                let body = string::<T>(mem, cp, addr, wide, spec.precision);
+                spec.pad(&mut out, "", 0, body, true);
+            }
+            b'e' | b'E' | b'f' | b'F' | b'g' | b'G' if crt => {
+                let value = f64::from_bits(args.pop_u64(mem));
+                let three_digit_exponents = matches!(
+                    dialect,
+                    Dialect::Crt {
+                        three_digit_exponents: true,
+                        ..
+                    }
+                );
+                let (body, finite) = float(value, conv, &spec, three_digit_exponents);
+                let body = body.bytes().map(T::from_ascii).collect();
+                spec.pad(
+                    &mut out,
+                    spec.sign(value.is_sign_negative()),
+                    0,
+                    body,
+                    finite,
+                );
+            }
+            b'%' => out.push(T::from_ascii(b'%')),
+            _ => {
+                // Like Windows, print an unknown conversion as itself.
+                log::warn!("printf: unhandled format {:?}", conv as char);
+                out.push(T::from_ascii(conv));
+            }
+        }
+    }
+    out
+}
+
+/// Read a string argument, of at most precision characters, converting it to T.
+fn string<T: Unit>(
+    mem: Mem,
+    cp: CodePage,
+    addr: u32,
+    wide: bool,
+    precision: Option<usize>,
+) -> Vec<T> {
+    if addr == 0 {
+        return "(null)".bytes().map(T::from_ascii).collect();
+    }
+    let max = precision.map_or(u32::MAX, |n| n as u32);
+    if wide {
+        let chars = (0..max)
+            .map_while(|i| mem.read_pod::<u16>(addr.wrapping_add(i * 2)).ok())
+            .take_while(|&c| c != 0)
+            .collect::<Vec<_>>();
+        T::from_wide(cp, &chars)
+    } else {
+        let bytes = mem.strz(addr, max).unwrap_or_else(|err| {
+            log::warn!("printf: bad string: {err}");
+            &[]
+        });
+        T::from_narrow(cp, bytes)
+    }
+}
+
+/// Format a floating point conversion, without its sign, returning whether the value
+/// was finite (and so may be zero padded).
+fn float(value: f64, conv: u8, spec: &Spec, three_digit_exponents: bool) -> (String, bool) {
+    let upper = conv.is_ascii_uppercase();
+    let value = value.abs();
+    if !value.is_finite() {
+        let text = if value.is_nan() { "nan" } else { "inf" };
+        let text = if upper {
+            text.to_uppercase()
+        } else {
+            text.into()
+        };
+        return (text, false);
+    }
+
+    let exponential = |precision: usize| -> String {
+        let text = format!("{value:.precision$e}");
+        let (mantissa, exp) = text.split_once('e').unwrap();
+        let exp = exp.parse::<i32>().unwrap();
+        let digits = if three_digit_exponents { 3 } else { 2 };
+        let sign = if exp < 0 { '-' } else { '+' };
+        let e = if upper { 'E' } else { 'e' };
+        format!("{mantissa}{e}{sign}{:0digits$}", exp.unsigned_abs())
+    };
+
+    let precision = spec.precision.unwrap_or(6).min(FLOAT_DIGITS);
+    let mut text = match conv.to_ascii_lowercase() {
+        b'f' => format!("{value:.precision$}"),
+        b'e' => exponential(precision),
+        _ => {
+            // %g picks %e or %f by the exponent, then drops trailing zeros.
+            let precision = precision.max(1);
+            let exp = format!("{:.*e}", precision - 1, value)
+                .split_once('e')
+                .unwrap()
+                .1
+                .parse::<i32>()
+                .unwrap();
+            let mut text = if exp < -4 || exp >= precision as i32 {
+                exponential(precision - 1)
+            } else {
+                format!("{:.*}", (precision as i32 - 1 - exp) as usize, value)
+            };
+            if !spec.alt && text.contains('.') {
+                let exp_at = text.find(['e', 'E']).unwrap_or(text.len());
+                let (mantissa, exp) = text.split_at(exp_at);
+                let mantissa = mantissa.trim_end_matches('0').trim_end_matches('.');
+                text = format!("{mantissa}{exp}");
+            }
+            return (text, true);
+        }
+    };
+    if spec.alt && !text.contains('.') {
+        // # keeps the decimal point, even with no digits after it.
+        let at = text.find(['e', 'E']).unwrap_or(text.len());
+        text.insert(at, '.');
+    }
+    (text, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CRT: Dialect = Dialect::Crt {
+        iso_wide_specifiers: false,
+        three_digit_exponents: false,
+    };
+
+    /// Format with the arguments laid out in a little memory, with strings after them.
+    fn sprintf(dialect: Dialect, fmt: &str, args: &[u32], strings: &[&[u8]]) -> String {
+        let mut buf = args
+            .iter()
+            .flat_map(|a| a.to_le_bytes())
+            .collect::<Vec<_>>();
+        for s in strings {
+            buf.extend_from_slice(s);
+        }
+        let mem = Mem::from_slice(&buf);
+        let mut args = VarArgs::from_va_list(0);
+        let out = format(
+            mem,
+            CodePage::Windows1252,
+            dialect,
+            fmt.as_bytes(),
+            &mut args,
+            usize::MAX,
+        );
+        assert_eq!(out.len, out.units.len());
+        String::from_utf8(out.units).unwrap()
+    }
+
+    #[test]
+    fn integers() {
+        let args = [42, -7i32 as u32, 0xBEEF, 0, 5];
+        assert_eq!(
+            sprintf(Dialect::Wsprintf, "[%d|%5d|%-4x|%#X|%.3u]", &args, &[]),
+            "[42|   -7|beef|0|005]"
+        );
+        assert_eq!(
+            sprintf(CRT, "%+d %05d %.0d|", &[3, -42i32 as u32, 0], &[]),
+            "+3 -0042 |"
+        );
+        assert_eq!(
+            sprintf(CRT, "%I64d %llx", &[1, 1, 0xFF, 0], &[]),
+            "4294967297 ff"
+        );
+        assert_eq!(sprintf(CRT, "%*d|%-*d|", &[4, 1, 3, 2], &[]), "   1|2  |");
+    }
+
+    #[test]
+    fn strings() {
+        // The string is at offset 8, after the two arguments.
+        assert_eq!(
+            sprintf(Dialect::Wsprintf, "<%s %c>", &[8, b'!' as u32], &[b"hi\0"]),
+            "<hi !>"
+        );
+        assert_eq!(
+            sprintf(CRT, "<%5.1s|%s>", &[8, 0], &[b"hi\0"]),
+            "<    h|(null)>"
+        );
+        // %S takes a wide string in a narrow format.
+        assert_eq!(sprintf(CRT, "%S|%%", &[4], &[b"w\0i\0\0\0"]), "wi|%");
+    }
+
+    #[test]
+    fn floats() {
+        let arg = |f: f64| {
+            let bits = f.to_bits();
+            [bits as u32, (bits >> 32) as u32]
+        };
+        let f = |fmt: &str, v: f64| sprintf(CRT, fmt, &arg(v), &[]);
+        assert_eq!(f("%f", 1.5), "1.500000");
+        assert_eq!(f("%.2f", -0.126), "-0.13");
+        assert_eq!(f("%e", 1234.5), "1.234500e+03");
+        assert_eq!(f("%g", 0.0001), "0.0001");
+        assert_eq!(f("%g", 1e-5), "1e-05");
+        assert_eq!(f("%G", 123456789.0), "1.23457E+08");
+        assert_eq!(f("%08.3f", -3.14159), "-003.142");
+        assert_eq!(f("%f", f64::INFINITY), "inf");
+        let legacy = Dialect::Crt {
+            iso_wide_specifiers: false,
+            three_digit_exponents: true,
+        };
+        assert_eq!(sprintf(legacy, "%.1e", &arg(5.0), &[]), "5.0e+000");
+        // wsprintf has no floating point.
+        assert_eq!(sprintf(Dialect::Wsprintf, "%f", &arg(1.0), &[]), "f");
+    }
+}
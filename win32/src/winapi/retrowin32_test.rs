@@ -5,6 +5,6 @@ use crate::Machine;
 
 #[win32_derive::dllexport]
 pub async fn retrowin32_test_callback1(machine: &mut Machine, func: u32, data: u32) -> u32 {
-    machine.call_x86(func, vec![data]).await;
+    machine.call_guest(func, (data,)).await;
     1
 }
@@ -0,0 +1,48 @@
+#![allow(non_snake_case)]
+
+use super::{kernel32, types::Str16, ERROR};
+use crate::Machine;
+use memory::ExtensionsMut;
+
+#[win32_derive::dllexport]
+pub fn CommandLineToArgvW(
+    machine: &mut Machine,
+    lpCmdLine: Option<&Str16>,
+    pNumArgs: Option<&mut u32>,
+) -> u32 {
+    let Some(num_args) = pNumArgs else {
+        kernel32::set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return 0;
+    };
+    let cmdline = lpCmdLine.map(|s| s.to_string()).unwrap_or_default();
+    let args = if cmdline.is_empty() {
+        // "If this parameter is an empty string the function returns the path to the
+        // current executable file."
+        vec![machine.state.kernel32.cmdline.args[0].clone()]
+    } else {
+        kernel32::split_cmdline(&cmdline)
+    };
+    let args = args
+        .iter()
+        .map(|arg| arg.encode_utf16().chain([0]).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    // The result is a single allocation, freed by the caller with LocalFree:
+    // the null-terminated array of pointers, followed by the strings.
+    let ptrs_size = (args.len() as u32 + 1) * 4;
+    let strs_size = args.iter().map(|arg| arg.len() as u32 * 2).sum::<u32>();
+    let addr = kernel32::LocalAlloc(machine, kernel32::GMEM::empty(), ptrs_size + strs_size);
+    let mem = machine.mem();
+    let mut str_addr = addr + ptrs_size;
+    for (i, arg) in args.iter().enumerate() {
+        mem.put_pod::<u32>(addr + i as u32 * 4, str_addr);
+        for (j, &c) in arg.iter().enumerate() {
+            mem.put_pod::<u16>(str_addr + j as u32 * 2, c);
+        }
+        str_addr += arg.len() as u32 * 2;
+    }
+    mem.put_pod::<u32>(addr + args.len() as u32 * 4, 0);
+
+    *num_args = args.len() as u32;
+    addr
+}
@@ -3,6 +3,7 @@
 use super::types::Str16;
 use crate::str16::expect_ascii;
 use memory::{Extensions, ExtensionsMut, Mem};
+use std::ffi::CStr;
 
 /// ArrayWithSize<u8> matches a pair of C arguments like
 ///    const u8_t* items, size_t len,
@@ -117,6 +118,13 @@ impl<'a, T: memory::Pod> FromArg<'a> for Option<&'a mut T> {
     }
 }
 
+/// 64-bit values like LARGE_INTEGER passed by value occupy two stack slots.
+impl<'a> FromStack<'a> for u64 {
+    unsafe fn from_stack(mem: Mem<'a>, sp: u32) -> Self {
+        mem.get_pod::<u64>(sp)
+    }
+}
+
 impl<'a, T: memory::Pod> FromStack<'a> for Option<&'a [T]> {
     unsafe fn from_stack(mem: Mem<'a>, sp: u32) -> Self {
         let addr = mem.get_pod::<u32>(sp);
@@ -124,7 +132,7 @@ impl<'a, T: memory::Pod> FromStack<'a> for Option<&'a [T]> {
         if addr == 0 {
             return None;
         }
-        let slice = mem.sub32(addr, count);
+        let slice = mem.try_sub32(addr, count).unwrap_or_else(|err| err.raise());
         Some(std::slice::from_raw_parts(
             slice.as_ptr() as *const _,
             count as usize,
@@ -139,7 +147,9 @@ impl<'a, T: memory::Pod> FromStack<'a> for ArrayWithSizeMut<'a, T> {
         if addr == 0 {
             return ArrayWithSizeMut(None);
         }
-        let slice = mem.sub32_mut(addr, count);
+        let slice = mem
+            .try_sub32_mut(addr, count)
+            .unwrap_or_else(|err| err.raise());
         ArrayWithSizeMut(Some(std::slice::from_raw_parts_mut(
             slice.as_mut_ptr() as *mut _,
             count as usize,
@@ -152,8 +162,24 @@ impl<'a> FromArg<'a> for Option<&'a str> {
         if arg == 0 {
             return None;
         }
-        let strz = expect_ascii(mem.slicez(arg));
-        Some(strz)
+        let strz = mem.strz(arg, u32::MAX).unwrap_or_else(|err| err.raise());
+        Some(expect_ascii(strz))
+    }
+}
+
+/// A nul-terminated string in the ANSI code page, for functions that must preserve
+/// its bytes rather than decode them, like the DBCS-aware string functions.
+impl<'a> FromArg<'a> for Option<&'a CStr> {
+    unsafe fn from_arg(mem: Mem<'a>, arg: u32) -> Self {
+        if arg == 0 {
+            return None;
+        }
+        let len = mem
+            .strz(arg, u32::MAX)
+            .unwrap_or_else(|err| err.raise())
+            .len() as u32;
+        // strz checked the nul too.
+        Some(CStr::from_bytes_with_nul_unchecked(mem.sub32(arg, len + 1)))
     }
 }
 
@@ -167,11 +193,24 @@ impl<'a> FromArg<'a> for Option<&'a Str16> {
 #[derive(Debug)]
 pub struct VarArgs(u32);
 impl VarArgs {
+    /// A va_list, as passed to the v* printf functions, which points at the arguments
+    /// just as VarArgs does.
+    pub fn from_va_list(va_list: u32) -> Self {
+        VarArgs(va_list)
+    }
+
     pub fn pop<'a, T: FromArg<'a>>(&mut self, mem: Mem<'a>) -> T {
         let value = unsafe { T::from_stack(mem, self.0) };
         self.0 += 4; // TODO: should expose stack_consumed for use here and switch to FromStack
         value
     }
+
+    /// Pop a value that takes two slots, like a double or an __int64.
+    pub fn pop_u64(&mut self, mem: Mem) -> u64 {
+        let value = mem.get_pod::<u64>(self.0);
+        self.0 += 8;
+        value
+    }
 }
 impl<'a> FromStack<'a> for VarArgs {
     unsafe fn from_stack(_mem: Mem<'a>, sp: u32) -> Self {
@@ -207,3 +246,26 @@ impl ToX86 for () {
         0
     }
 }
+
+/// Argument lists for calls from Rust into x86 code, as tuples of ToX86 values.
+pub trait ToX86Args {
+    fn to_raw_args(&self) -> Vec<u32>;
+}
+macro_rules! impl_to_x86_args {
+    ($($arg:ident),*) => {
+        impl<$($arg: ToX86),*> ToX86Args for ($($arg,)*) {
+            #[allow(non_snake_case)]
+            fn to_raw_args(&self) -> Vec<u32> {
+                let ($($arg,)*) = self;
+                vec![$($arg.to_raw()),*]
+            }
+        }
+    };
+}
+impl_to_x86_args!();
+impl_to_x86_args!(A);
+impl_to_x86_args!(A, B);
+impl_to_x86_args!(A, B, C);
+impl_to_x86_args!(A, B, C, D);
+impl_to_x86_args!(A, B, C, D, E);
+impl_to_x86_args!(A, B, C, D, E, F);
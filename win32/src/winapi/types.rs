@@ -3,6 +3,8 @@
 pub use super::handle::HANDLE;
 pub use crate::str16::{Str16, String16};
 use memory::Extensions;
+/// ANSI strings, for functions that need their raw bytes.
+pub use std::ffi::CStr;
 
 pub type WORD = u16;
 pub type DWORD = u32;
@@ -30,7 +32,7 @@ pub struct HWNDT;
 pub type HWND = HANDLE<HWNDT>;
 
 #[repr(C, packed)]
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
 pub struct RECT {
     pub left: i32,
     pub top: i32,
@@ -40,7 +42,7 @@ pub struct RECT {
 unsafe impl memory::Pod for RECT {}
 
 #[repr(C, packed)]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct POINT {
     pub x: DWORD,
     pub y: DWORD,
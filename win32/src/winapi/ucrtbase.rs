@@ -3,15 +3,24 @@
 #![allow(non_snake_case)]
 #![allow(non_upper_case_globals)]
 
-use crate::Machine;
-use memory::Extensions;
+use crate::{
+    str16::Str16,
+    winapi::{kernel32, printf, stack_args::VarArgs},
+    Machine,
+};
+use memory::{Extensions, ExtensionsMut, Pod};
+use std::ffi::CStr;
 
 #[win32_derive::dllexport(cdecl)]
 pub async fn _initterm(machine: &mut Machine, start: u32, end: u32) -> u32 {
     if (end - start) % 4 != 0 {
         panic!("unaligned _initterm");
     }
-    let slice = machine.mem().sub32(start, end - start).to_vec();
+    let slice = machine
+        .mem()
+        .try_sub32(start, end - start)
+        .unwrap_or_else(|err| err.raise())
+        .to_vec();
     for addr in slice.into_iter_pod::<u32>() {
         if addr != 0 {
             machine.call_x86(addr, vec![]).await;
@@ -25,7 +34,11 @@ pub async fn _initterm_e(machine: &mut Machine, start: u32, end: u32) -> u32 {
     if (end - start) % 4 != 0 {
         panic!("unaligned _initterm_e");
     }
-    let slice = machine.mem().sub32(start, end - start).to_vec();
+    let slice = machine
+        .mem()
+        .try_sub32(start, end - start)
+        .unwrap_or_else(|err| err.raise())
+        .to_vec();
     for addr in slice.into_iter_pod::<u32>() {
         if addr != 0 {
             let err = machine.call_x86(addr, vec![]).await;
@@ -113,21 +126,156 @@ pub fn __dllonexit(_machine: &mut Machine, func: u32, d: u32, f: u32) -> u32 {
     0
 }
 
+/// The x87 exception bits (as both control word masks and status word flags) paired with
+/// the CRT's _EM_* / _SW_* bits, which differ.
+const FP_EXCEPTION_BITS: [(u16, u32); 6] = [
+    (0x01, 0x10),    // invalid
+    (0x02, 0x80000), // denormal
+    (0x04, 0x08),    // zero divide
+    (0x08, 0x04),    // overflow
+    (0x10, 0x02),    // underflow
+    (0x20, 0x01),    // inexact
+];
+
+/// CRT control word bits: _MCW_RC, _MCW_PC, _MCW_IC.
+const _MCW_RC: u32 = 0x300;
+const _MCW_PC: u32 = 0x30000;
+const _IC_AFFINE: u32 = 0x40000;
+const _PC_53: u32 = 0x10000;
+const _PC_24: u32 = 0x20000;
+const _EM_DENORMAL: u32 = 0x80000;
+
+/// Translate x87 exception bits to the CRT's.
+fn crt_exception_bits(x87: u16) -> u32 {
+    FP_EXCEPTION_BITS
+        .iter()
+        .filter(|&&(bit, _)| x87 & bit != 0)
+        .fold(0, |bits, &(_, crt)| bits | crt)
+}
+
+/// Translate an x87 control word to the CRT's encoding, as _control87 returns it.
+fn crt_control(cw: u16) -> u32 {
+    let rc = ((cw >> 10) & 3) as u32 * 0x100;
+    let pc = match (cw >> 8) & 3 {
+        0 => _PC_24,
+        3 => 0, // _PC_64
+        _ => _PC_53,
+    };
+    let ic = if cw & (1 << 12) != 0 { _IC_AFFINE } else { 0 };
+    crt_exception_bits(cw) | rc | pc | ic
+}
+
+/// Translate a CRT control word to x87, keeping the x87 bits it doesn't cover.
+fn x87_control(crt: u32, cw: u16) -> u16 {
+    let masks = FP_EXCEPTION_BITS
+        .iter()
+        .filter(|&&(_, bit)| crt & bit != 0)
+        .fold(0, |bits, &(x87, _)| bits | x87);
+    let rc = ((crt & _MCW_RC) >> 8) as u16;
+    let pc = match crt & _MCW_PC {
+        _PC_24 => 0,
+        _PC_53 => 2,
+        _ => 3,
+    };
+    let ic = (crt & _IC_AFFINE != 0) as u16;
+    (cw & !0x1F3F) | masks | rc << 10 | pc << 8 | ic << 12
+}
+
+/// The current thread's FPU control and status words.
+#[cfg(feature = "x86-emu")]
+fn fpu_words(machine: &Machine) -> (u16, u16) {
+    let fpu = &machine.emu.x86.cpu().fpu;
+    (fpu.control, fpu.status.bits())
+}
+
+#[cfg(feature = "x86-emu")]
+fn set_fpu_words(machine: &mut Machine, control: u16, status: u16) {
+    let fpu = &mut machine.emu.x86.cpu_mut().fpu;
+    fpu.control = control;
+    fpu.status = x86::FPUStatus::from_bits_truncate(status);
+    // Unmasking an exception whose flag is set makes it pending, as with fldcw.
+    fpu.update_summary();
+}
+
+// Without the emulator the FPU belongs to the host, so these report the Windows defaults.
+#[cfg(not(feature = "x86-emu"))]
+fn fpu_words(_machine: &Machine) -> (u16, u16) {
+    (kernel32::INITIAL_FPU_CONTROL, 0)
+}
+
+#[cfg(not(feature = "x86-emu"))]
+fn set_fpu_words(_machine: &mut Machine, control: u16, _status: u16) {
+    log::warn!("ignoring FPU control word {control:#x}");
+}
+
 #[win32_derive::dllexport(cdecl)]
-pub fn _controlfp(_machine: &mut Machine, _new: u32, _mask: u32) -> u32 {
-    0
+pub fn _control87(machine: &mut Machine, new: u32, mask: u32) -> u32 {
+    let (cw, status) = fpu_words(machine);
+    let crt = (crt_control(cw) & !mask) | (new & mask);
+    if mask != 0 {
+        set_fpu_words(machine, x87_control(crt, cw), status);
+    }
+    crt
+}
+
+#[win32_derive::dllexport(cdecl)]
+pub fn __control87_2(
+    machine: &mut Machine,
+    new: u32,
+    mask: u32,
+    x86_cw: Option<&mut u32>,
+    sse2_cw: Option<&mut u32>,
+) -> i32 {
+    if let Some(x86_cw) = x86_cw {
+        *x86_cw = _control87(machine, new, mask);
+    }
+    if let Some(sse2_cw) = sse2_cw {
+        // We don't have an MXCSR to control; report its default of everything masked.
+        *sse2_cw = crt_exception_bits(0x3F);
+    }
+    1
+}
+
+/// Like _control87, except that the denormal mask can't be changed.
+#[win32_derive::dllexport(cdecl)]
+pub fn _controlfp(machine: &mut Machine, new: u32, mask: u32) -> u32 {
+    _control87(machine, new, mask & !_EM_DENORMAL)
 }
 
 #[win32_derive::dllexport(cdecl)]
 pub fn _controlfp_s(
-    _machine: &mut Machine,
-    _currentControl: u32,
-    _newControl: u32,
-    _mask: u32,
+    machine: &mut Machine,
+    currentControl: Option<&mut u32>,
+    newControl: u32,
+    mask: u32,
 ) -> u32 {
+    let control = _controlfp(machine, newControl, mask);
+    if let Some(currentControl) = currentControl {
+        *currentControl = control;
+    }
     0
 }
 
+#[win32_derive::dllexport(cdecl)]
+pub fn _statusfp(machine: &mut Machine) -> u32 {
+    crt_exception_bits(fpu_words(machine).1)
+}
+
+/// Return and clear the exception flags.
+#[win32_derive::dllexport(cdecl)]
+pub fn _clearfp(machine: &mut Machine) -> u32 {
+    let (cw, status) = fpu_words(machine);
+    // As fnclex, clearing the exception flags, SF and ES.
+    set_fpu_words(machine, cw, status & !0xFF);
+    crt_exception_bits(status)
+}
+
+/// Reset the FPU to the state threads start with.
+#[win32_derive::dllexport(cdecl)]
+pub fn _fpreset(machine: &mut Machine) {
+    set_fpu_words(machine, kernel32::INITIAL_FPU_CONTROL, 0);
+}
+
 #[win32_derive::dllexport(cdecl)]
 pub fn _configthreadlocale(_machine: &mut Machine, per_thread_locale_type: i32) -> i32 {
     const _ENABLE_PER_THREAD_LOCALE: i32 = 1;
@@ -167,11 +315,17 @@ pub fn __getmainargs(
 
 #[win32_derive::dllexport(cdecl)]
 pub fn malloc(machine: &mut Machine, size: u32) -> u32 {
+    let caller = kernel32::guest_caller(machine);
     let heap = machine
         .state
         .kernel32
         .get_process_heap(&mut machine.emu.memory); // lazy init process_heap
-    heap.alloc(machine.emu.memory.mem(), size)
+    let addr = heap.alloc(machine.emu.memory.mem(), size);
+    if addr == 0 {
+        return 0;
+    }
+    heap.set_caller(machine.emu.memory.mem(), addr, caller);
+    addr
 }
 
 #[win32_derive::dllexport(cdecl)]
@@ -184,6 +338,209 @@ pub fn free(machine: &mut Machine, ptr: u32) -> u32 {
     0
 }
 
+const EINVAL: u32 = 22;
+
+/// The address of the calling thread's errno, allocated on first use.
+fn errno_addr(machine: &mut Machine) -> u32 {
+    let addr = kernel32::current_thread(machine).errno;
+    if addr != 0 {
+        return addr;
+    }
+    let heap = machine
+        .state
+        .kernel32
+        .get_process_heap(&mut machine.emu.memory);
+    let addr = heap.alloc(machine.emu.memory.mem(), 4);
+    machine.mem().put_pod::<u32>(addr, 0);
+    kernel32::current_thread(machine).errno = addr;
+    addr
+}
+
+#[win32_derive::dllexport(cdecl)]
+pub fn _errno(machine: &mut Machine) -> u32 {
+    errno_addr(machine)
+}
+
+/// Fail a call given a bad argument, as the CRT does when the invalid parameter
+/// handler returns: set errno to EINVAL and return ret.
+fn invalid_parameter<T>(machine: &mut Machine, ret: T) -> T {
+    log::warn!("CRT: invalid parameter");
+    let addr = errno_addr(machine);
+    machine.mem().put_pod::<u32>(addr, EINVAL);
+    ret
+}
+
+// Option bits of the __stdio_common functions, which the inline printf functions in
+// the ucrt headers pass along.
+const PRINTF_STANDARD_SNPRINTF_BEHAVIOR: u64 = 1 << 1;
+const PRINTF_LEGACY_WIDE_SPECIFIERS: u64 = 1 << 2;
+const PRINTF_LEGACY_THREE_DIGIT_EXPONENTS: u64 = 1 << 4;
+
+/// The options msvcrt's own printf functions behave as if given.
+const PRINTF_MSVCRT: u64 = PRINTF_LEGACY_WIDE_SPECIFIERS | PRINTF_LEGACY_THREE_DIGIT_EXPONENTS;
+
+/// Format into a buffer of count characters, or with a null buffer just measure.
+/// Returns the length of the output, or if it didn't fit, -1; with standard snprintf
+/// behavior, the output is instead cut short with a nul and the length still returned.
+fn format_into<T: printf::Unit>(
+    machine: &mut Machine,
+    options: u64,
+    buf: u32,
+    count: u32,
+    fmt: &[T],
+    args: &mut VarArgs,
+) -> i32 {
+    let dialect = printf::Dialect::Crt {
+        iso_wide_specifiers: options & PRINTF_LEGACY_WIDE_SPECIFIERS == 0,
+        three_digit_exponents: options & PRINTF_LEGACY_THREE_DIGIT_EXPONENTS != 0,
+    };
+    let cp = machine.state.kernel32.code_page;
+    // Keep no more than fits, while still counting the rest for the return value.
+    let limit = if buf == 0 { 0 } else { count as usize };
+    let printf::Output {
+        units: mut out,
+        len,
+        ..
+    } = printf::format(machine.mem(), cp, dialect, fmt, args, limit);
+    let Ok(ret_len) = i32::try_from(len) else {
+        log::warn!("sprintf: output of {len} characters is too long");
+        return -1;
+    };
+    if buf == 0 {
+        return ret_len;
+    }
+    let count = count as usize;
+    let ret = if len < count {
+        out.push(T::from_ascii(0));
+        ret_len
+    } else if options & PRINTF_STANDARD_SNPRINTF_BEHAVIOR != 0 {
+        if count == 0 {
+            return ret_len;
+        }
+        out.truncate(count - 1);
+        out.push(T::from_ascii(0));
+        ret_len
+    } else if len == count {
+        // As _snprintf documents: the output just fits, without a nul.
+        ret_len
+    } else {
+        // Already cut off at count.
+        -1
+    };
+    let bytes = printf::as_bytes(&out);
+    match machine.mem().try_sub32_mut(buf, bytes.len() as u32) {
+        Ok(dst) => dst.copy_from_slice(bytes),
+        Err(err) => {
+            log::warn!("sprintf: {err}");
+            return -1;
+        }
+    }
+    ret
+}
+
+#[win32_derive::dllexport(cdecl)]
+pub fn __stdio_common_vsprintf(
+    machine: &mut Machine,
+    options: u64,
+    buffer: u32,
+    buffer_count: u32,
+    format: Option<&CStr>,
+    locale: u32,
+    arglist: u32,
+) -> i32 {
+    let Some(format) = format else {
+        return invalid_parameter(machine, -1);
+    };
+    let mut args = VarArgs::from_va_list(arglist);
+    format_into(
+        machine,
+        options,
+        buffer,
+        buffer_count,
+        format.to_bytes(),
+        &mut args,
+    )
+}
+
+#[win32_derive::dllexport(cdecl)]
+pub fn __stdio_common_vswprintf(
+    machine: &mut Machine,
+    options: u64,
+    buffer: u32,
+    buffer_count: u32,
+    format: Option<&Str16>,
+    locale: u32,
+    arglist: u32,
+) -> i32 {
+    let Some(format) = format else {
+        return invalid_parameter(machine, -1);
+    };
+    let mut args = VarArgs::from_va_list(arglist);
+    format_into(
+        machine,
+        options,
+        buffer,
+        buffer_count,
+        format.buf(),
+        &mut args,
+    )
+}
+
+#[win32_derive::dllexport(cdecl)]
+pub fn sprintf(
+    machine: &mut Machine,
+    buffer: u32,
+    format: Option<&CStr>,
+    mut args: VarArgs,
+) -> i32 {
+    let Some(format) = format else {
+        return invalid_parameter(machine, -1);
+    };
+    let format = format.to_bytes();
+    format_into(machine, PRINTF_MSVCRT, buffer, u32::MAX, format, &mut args)
+}
+
+#[win32_derive::dllexport(cdecl)]
+pub fn vsprintf(machine: &mut Machine, buffer: u32, format: Option<&CStr>, arglist: u32) -> i32 {
+    let mut args = VarArgs::from_va_list(arglist);
+    let Some(format) = format else {
+        return invalid_parameter(machine, -1);
+    };
+    let format = format.to_bytes();
+    format_into(machine, PRINTF_MSVCRT, buffer, u32::MAX, format, &mut args)
+}
+
+#[win32_derive::dllexport(cdecl)]
+pub fn _snprintf(
+    machine: &mut Machine,
+    buffer: u32,
+    count: u32,
+    format: Option<&CStr>,
+    mut args: VarArgs,
+) -> i32 {
+    let Some(format) = format else {
+        return invalid_parameter(machine, -1);
+    };
+    let format = format.to_bytes();
+    format_into(machine, PRINTF_MSVCRT, buffer, count, format, &mut args)
+}
+
+#[win32_derive::dllexport(cdecl)]
+pub fn _vsnprintf(
+    machine: &mut Machine,
+    buffer: u32,
+    count: u32,
+    format: Option<&CStr>,
+    arglist: u32,
+) -> i32 {
+    let mut args = VarArgs::from_va_list(arglist);
+    let Some(format) = format else {
+        return invalid_parameter(machine, -1);
+    };
+    let format = format.to_bytes();
+    format_into(machine, PRINTF_MSVCRT, buffer, count, format, &mut args)
+}
+
 // MSDN: "Calling rand before any call to srand generates the same sequence as calling srand with seed passed as 1."
 static mut RAND_STATE: u32 = 1;
 
@@ -204,7 +561,7 @@ pub fn rand(machine: &mut Machine) -> u32 {
 }
 
 fn time64(machine: &mut Machine, destTime: Option<&mut u64>) -> u32 {
-    let time = machine.host.system_time().timestamp() as u64;
+    let time = kernel32::now(machine).timestamp() as u64;
     if let Some(destTime) = destTime {
         *destTime = time;
     }
@@ -230,17 +587,204 @@ pub fn _XcptFilter(machine: &mut Machine, xcptnum: u32, pxcptinfoptrs: u32) -> u
     todo!();
 }
 
+/// The frame registered by MSVC's __try, handled by _except_handler3/4.
+/// The function's ebp is just past it; below it are the saved esp and (during a filter)
+/// a pointer to the EXCEPTION_POINTERS.
+#[repr(C)]
+#[derive(Clone)]
+struct EH3_EXCEPTION_REGISTRATION {
+    Next: u32,
+    ExceptionHandler: u32,
+    ScopeTable: u32,
+    TryLevel: i32,
+}
+unsafe impl Pod for EH3_EXCEPTION_REGISTRATION {}
+
+/// One __try block, indexed by try level.
+#[repr(C)]
+#[derive(Clone)]
+struct SCOPETABLE_ENTRY {
+    EnclosingLevel: i32,
+    /// Zero for a __finally block.
+    FilterFunc: u32,
+    HandlerFunc: u32,
+}
+unsafe impl Pod for SCOPETABLE_ENTRY {}
+
+/// Try level outside of any __try, for _except_handler3.
+const TRYLEVEL_NONE: i32 = -1;
+
+/// Run the __finally blocks of the try levels of frame from its current one up to
+/// (not including) stop_level.
+#[cfg(feature = "x86-emu")]
+async fn local_unwind(machine: &mut Machine, frame: u32, scope_table: u32, stop_level: i32) {
+    let ebp = frame + 0x10;
+    loop {
+        let level = machine
+            .mem()
+            .get_pod::<EH3_EXCEPTION_REGISTRATION>(frame)
+            .TryLevel;
+        if level < 0 || level == stop_level {
+            break;
+        }
+        let entry = machine
+            .mem()
+            .get_pod::<SCOPETABLE_ENTRY>(scope_table + level as u32 * 12);
+        // Update the level first, so an exception in the __finally doesn't rerun it.
+        machine
+            .mem()
+            .get_aligned_ref_mut::<EH3_EXCEPTION_REGISTRATION>(frame)
+            .TryLevel = entry.EnclosingLevel;
+        if entry.FilterFunc == 0 {
+            machine
+                .call_x86_with_regs(entry.HandlerFunc, vec![], &[(x86::Register::EBP, ebp)])
+                .await;
+        }
+    }
+}
+
+/// The implementation of _except_handler3 and _except_handler4, which differ only in how
+/// they find the scope table.
+#[cfg(feature = "x86-emu")]
+pub async fn except_handler(
+    machine: &mut Machine,
+    exception_record: u32,
+    frame: u32,
+    context: u32,
+    scope_table: u32,
+) -> u32 {
+    let flags = machine
+        .mem()
+        .get_pod::<kernel32::EXCEPTION_RECORD>(exception_record)
+        .ExceptionFlags;
+    if flags & (kernel32::EXCEPTION_UNWINDING | kernel32::EXCEPTION_EXIT_UNWIND) != 0 {
+        local_unwind(machine, frame, scope_table, TRYLEVEL_NONE).await;
+        return kernel32::ExceptionContinueSearch;
+    }
+
+    // Filters find the exception via GetExceptionInformation(), which reads it from
+    // just below the frame.
+    let esp = machine.emu.x86.cpu().regs.get32(x86::Register::ESP);
+    let pointers = kernel32::push_pod(
+        machine,
+        kernel32::EXCEPTION_POINTERS {
+            ExceptionRecord: exception_record,
+            ContextRecord: context,
+        },
+    );
+    machine.mem().put_pod::<u32>(frame - 4, pointers);
+
+    let ebp = frame + 0x10;
+    let mut level = machine
+        .mem()
+        .get_pod::<EH3_EXCEPTION_REGISTRATION>(frame)
+        .TryLevel;
+    let disposition = loop {
+        if level < 0 {
+            break kernel32::ExceptionContinueSearch;
+        }
+        let entry = machine
+            .mem()
+            .get_pod::<SCOPETABLE_ENTRY>(scope_table + level as u32 * 12);
+        if entry.FilterFunc != 0 {
+            let filter = machine
+                .call_x86_with_regs(entry.FilterFunc, vec![], &[(x86::Register::EBP, ebp)])
+                .await as i32;
+            if filter == kernel32::EXCEPTION_CONTINUE_EXECUTION {
+                break kernel32::ExceptionContinueExecution;
+            }
+            if filter != kernel32::EXCEPTION_CONTINUE_SEARCH {
+                // EXCEPTION_EXECUTE_HANDLER: unwind everything within the __try and jump
+                // into its __except block, never to return here.
+                kernel32::unwind(machine, frame, exception_record).await;
+                local_unwind(machine, frame, scope_table, level).await;
+                machine
+                    .mem()
+                    .get_aligned_ref_mut::<EH3_EXCEPTION_REGISTRATION>(frame)
+                    .TryLevel = entry.EnclosingLevel;
+                let saved_esp = machine.mem().get_pod::<u32>(frame - 8);
+                let regs = &mut machine.emu.x86.cpu_mut().regs;
+                regs.set32(x86::Register::ESP, saved_esp);
+                regs.set32(x86::Register::EBP, ebp);
+                regs.eip = entry.HandlerFunc;
+                return 0;
+            }
+        }
+        level = entry.EnclosingLevel;
+    };
+    machine
+        .emu
+        .x86
+        .cpu_mut()
+        .regs
+        .set32(x86::Register::ESP, esp);
+    disposition
+}
+
 #[win32_derive::dllexport(cdecl)]
-pub fn _except_handler3(
-    _machine: &mut Machine,
+pub async fn _except_handler3(
+    machine: &mut Machine,
     exception_record: u32,
     registration: u32,
     context: u32,
-    dispatcher: u32,
-) -> i32 {
-    todo!();
+    _dispatcher: u32,
+) -> u32 {
+    #[cfg(feature = "x86-emu")]
+    {
+        let scope_table = machine
+            .mem()
+            .get_pod::<EH3_EXCEPTION_REGISTRATION>(registration)
+            .ScopeTable;
+        except_handler(
+            machine,
+            exception_record,
+            registration,
+            context,
+            scope_table,
+        )
+        .await
+    }
+
+    #[cfg(not(feature = "x86-emu"))]
+    {
+        _ = (machine, exception_record, registration, context);
+        log::warn!("_except_handler3: unimplemented");
+        kernel32::ExceptionContinueSearch
+    }
 }
 
+#[win32_derive::dllexport(cdecl)]
+pub async fn _local_unwind2(machine: &mut Machine, registration: u32, stop_level: i32) {
+    #[cfg(feature = "x86-emu")]
+    {
+        let scope_table = machine
+            .mem()
+            .get_pod::<EH3_EXCEPTION_REGISTRATION>(registration)
+            .ScopeTable;
+        local_unwind(machine, registration, scope_table, stop_level).await;
+    }
+
+    #[cfg(not(feature = "x86-emu"))]
+    {
+        _ = (machine, registration, stop_level);
+        log::warn!("_local_unwind2: unimplemented");
+    }
+}
+
+#[win32_derive::dllexport(cdecl)]
+pub async fn _global_unwind2(machine: &mut Machine, registration: u32) {
+    kernel32::unwind(machine, registration, 0).await;
+}
+
+// msvcrt.dll carried the C++ exception support that later moved to vcruntime140.dll.
+// These uses only generate the forwarding exports, so nothing refers to them here.
+#[allow(unused_imports)]
+#[win32_derive::dllexport]
+pub use crate::winapi::vcruntime140::_CxxThrowException;
+#[allow(unused_imports)]
+#[win32_derive::dllexport]
+pub use crate::winapi::vcruntime140::__CxxFrameHandler;
+
 #[win32_derive::dllexport(cdecl)]
 pub fn __setusermatherr(machine: &mut Machine, pf: u32) {
     todo!();
@@ -251,3 +795,238 @@ pub const _adjust_fdiv: &'static str = "_adjust_fdiv";
 
 #[win32_derive::dllexport]
 pub const _acmdln: &'static str = "_acmdln";
+
+#[cfg(all(test, feature = "x86-emu"))]
+mod tests {
+    use super::*;
+    use crate::test_guest::{builtin, new_machine, run, Code};
+
+    /// Run `trigger` in a function laid out as MSVC compiles
+    /// __try { __try { trigger } __finally { ... } } __except(filter) { ... },
+    /// and return the function's result: the exception code seen by the filter, plus
+    /// one if the __finally ran; or 1 if nothing was raised.
+    fn seh3(trigger: impl FnOnce(&mut Machine, &mut Code)) -> u32 {
+        let (mut machine, _host) = new_machine();
+        let handler3 = builtin(&mut machine, "ucrtbase.dll", "_except_handler3");
+        let mut code = Code::alloc(&mut machine, 0x1000);
+        let func = code.base + 0x40;
+        let except_block = code.base + 0x100;
+        let epilogue = code.base + 0x140;
+        let filter = code.base + 0x180;
+        let finally = code.base + 0x1C0;
+        let scope_table = code.base + 0x200;
+        let seen_code = code.base + 0x220;
+        let finally_ran = code.base + 0x224;
+
+        code.call(func).exit_with(&mut machine, 0);
+
+        code.at(0x40)
+            .emit(&[0x55]) // push ebp
+            .emit(&[0x8B, 0xEC]) // mov ebp, esp
+            .emit(&[0x6A, 0xFF]) // push -1  (TryLevel)
+            .push(scope_table)
+            .push(handler3)
+            .emit(&[0x64, 0xFF, 0x35, 0, 0, 0, 0]) // push dword fs:[0]
+            .emit(&[0x64, 0x89, 0x25, 0, 0, 0, 0]) // mov fs:[0], esp
+            .emit(&[0x83, 0xEC, 0x08]) // sub esp, 8  (exception pointers, saved esp)
+            .emit(&[0x53]) // push ebx
+            .emit(&[0x89, 0x65, 0xE8]) // mov [ebp-18h], esp
+            .emit(&[0xC7, 0x45, 0xFC, 1, 0, 0, 0]); // mov dword [ebp-4], 1
+        trigger(&mut machine, &mut code);
+        code.emit(&[0xC7, 0x45, 0xFC, 0xFF, 0xFF, 0xFF, 0xFF]) // mov dword [ebp-4], -1
+            .emit(&[0xB8, 1, 0, 0, 0]) // mov eax, 1
+            .emit(&[0xB9]) // mov ecx, epilogue
+            .emit32(epilogue)
+            .emit(&[0xFF, 0xE1]); // jmp ecx
+
+        code.at(0x100)
+            .emit(&[0x8B, 0x65, 0xE8]) // mov esp, [ebp-18h]
+            .emit(&[0xC7, 0x45, 0xFC, 0xFF, 0xFF, 0xFF, 0xFF]) // mov dword [ebp-4], -1
+            .emit(&[0xA1]) // mov eax, [seen_code]
+            .emit32(seen_code)
+            .emit(&[0x03, 0x05]) // add eax, [finally_ran]
+            .emit32(finally_ran)
+            .emit(&[0xB9]) // mov ecx, epilogue
+            .emit32(epilogue)
+            .emit(&[0xFF, 0xE1]); // jmp ecx
+
+        code.at(0x140)
+            .emit(&[0x8B, 0x4D, 0xF0]) // mov ecx, [ebp-10h]
+            .emit(&[0x64, 0x89, 0x0D, 0, 0, 0, 0]) // mov fs:[0], ecx
+            .emit(&[0x5B]) // pop ebx
+            .emit(&[0x8B, 0xE5]) // mov esp, ebp
+            .emit(&[0x5D]) // pop ebp
+            .emit(&[0xC3]); // ret
+
+        // The filter finds the exception via the pointers below the frame.
+        code.at(0x180)
+            .emit(&[0x8B, 0x45, 0xEC]) // mov eax, [ebp-14h]
+            .emit(&[0x8B, 0x00]) // mov eax, [eax]  (ExceptionRecord)
+            .emit(&[0x8B, 0x00]) // mov eax, [eax]  (ExceptionCode)
+            .emit(&[0xA3]) // mov [seen_code], eax
+            .emit32(seen_code)
+            .emit(&[0xB8, 1, 0, 0, 0]) // mov eax, EXCEPTION_EXECUTE_HANDLER
+            .emit(&[0xC3]); // ret
+
+        code.at(0x1C0)
+            .emit(&[0xC7, 0x05]) // mov dword [finally_ran], 1
+            .emit32(finally_ran)
+            .emit32(1)
+            .emit(&[0xC3]); // ret
+
+        code.at(0x200);
+        for entry in [
+            [TRYLEVEL_NONE as u32, filter, except_block],
+            [0, 0, finally],
+        ] {
+            for field in entry {
+                code.emit32(field);
+            }
+        }
+        code.emit32(0).emit32(0);
+
+        code.start(&mut machine);
+        run(&mut machine)
+    }
+
+    #[test]
+    fn except_handler3_catches_raised_exception() {
+        let code = seh3(|machine, code| {
+            let raise = builtin(machine, "kernel32.dll", "RaiseException");
+            code.push(0).push(0).push(0).push(0xE000_1234).call(raise);
+        });
+        assert_eq!(code, 0xE000_1234 + 1);
+    }
+
+    #[test]
+    fn except_handler3_catches_fault() {
+        let code = seh3(|_machine, code| {
+            code.emit(&[0x31, 0xC9]) // xor ecx, ecx
+                .emit(&[0xF7, 0xF1]); // div ecx
+        });
+        assert_eq!(code, kernel32::STATUS_INTEGER_DIVIDE_BY_ZERO + 1);
+    }
+
+    #[test]
+    fn except_handler3_nothing_raised() {
+        assert_eq!(seh3(|_, _| {}), 1);
+    }
+
+    #[test]
+    fn crt_control_words() {
+        // _CW_DEFAULT, what the CRT sets at startup.
+        assert_eq!(crt_control(kernel32::INITIAL_FPU_CONTROL), 0x9001F);
+        // FINIT's: 64-bit precision.
+        assert_eq!(crt_control(0x037F), 0x8001F);
+        // Round down, 24-bit precision, zero divide and overflow unmasked.
+        assert_eq!(
+            crt_control(0x0473),
+            0x20000 | 0x100 | 0x80000 | 0x10 | 0x02 | 0x01
+        );
+
+        for cw in [0x027F, 0x037F, 0x0473, 0x0E72, 0x127F] {
+            assert_eq!(x87_control(crt_control(cw), cw), cw, "{cw:#x}");
+        }
+        // Bits the CRT doesn't cover, like the reserved 0x40, are kept.
+        assert_eq!(x87_control(0x9001F, 0x0040), 0x027F);
+    }
+
+    #[test]
+    fn control87_unmasks_pending_exception() {
+        let (mut machine, _host) = new_machine();
+        assert_eq!(_control87(&mut machine, 0, 0), 0x9001F);
+        machine.emu.x86.cpu_mut().fpu.exception(x86::FPUStatus::ZE);
+        assert!(!machine
+            .emu
+            .x86
+            .cpu()
+            .fpu
+            .status
+            .contains(x86::FPUStatus::ES));
+        // Unmask _EM_ZERODIVIDE.
+        assert_eq!(_control87(&mut machine, 0, 0x08), 0x90017);
+        let fpu = &machine.emu.x86.cpu().fpu;
+        assert_eq!(fpu.control, kernel32::INITIAL_FPU_CONTROL & !0x04);
+        assert!(fpu.status.contains(x86::FPUStatus::ES));
+    }
+
+    /// Format into a buffer of count bytes, laid out with the arguments in guest memory,
+    /// returning format_into's result and the buffer, 0xAA past what was written.
+    fn snprintf(options: u64, count: u32, fmt: &str, args: &[u32]) -> (i32, Vec<u8>) {
+        let (mut machine, _host) = new_machine();
+        let base = machine
+            .state
+            .kernel32
+            .mappings
+            .alloc(0x100, "test buffers".into(), &mut machine.emu.memory)
+            .unwrap()
+            .addr;
+        let mem = machine.mem();
+        mem.sub32_mut(base, 0x40).fill(0xAA);
+        for (i, &arg) in args.iter().enumerate() {
+            mem.put_pod::<u32>(base + 0x80 + i as u32 * 4, arg);
+        }
+        let mut args = VarArgs::from_va_list(base + 0x80);
+        let buf = if count == 0 { 0 } else { base };
+        let ret = format_into(&mut machine, options, buf, count, fmt.as_bytes(), &mut args);
+        (ret, machine.mem().sub32(base, count + 2).to_vec())
+    }
+
+    const STANDARD: u64 = PRINTF_STANDARD_SNPRINTF_BEHAVIOR;
+
+    #[test]
+    fn format_into_fits() {
+        assert_eq!(
+            snprintf(0, 8, "%d", &[42]),
+            (2, b"42\0\xAA\xAA\xAA\xAA\xAA\xAA\xAA".to_vec())
+        );
+        // Just fitting leaves off the nul, unless asked for standard behavior.
+        assert_eq!(snprintf(0, 4, "abcd", &[]), (4, b"abcd\xAA\xAA".to_vec()));
+        assert_eq!(
+            snprintf(STANDARD, 4, "abcd", &[]),
+            (4, b"abc\0\xAA\xAA".to_vec())
+        );
+    }
+
+    #[test]
+    fn format_into_truncates() {
+        assert_eq!(
+            snprintf(0, 4, "abcdef", &[]),
+            (-1, b"abcd\xAA\xAA".to_vec())
+        );
+        // Standard snprintf still returns the whole length.
+        assert_eq!(
+            snprintf(STANDARD, 4, "abcdef", &[]),
+            (6, b"abc\0\xAA\xAA".to_vec())
+        );
+        // Padding stops at the buffer's end, however wide the field.
+        assert_eq!(
+            snprintf(0, 6, "%2000000000d", &[1]),
+            (-1, b"      \xAA\xAA".to_vec())
+        );
+        assert_eq!(
+            snprintf(STANDARD, 6, "%.2000000000d", &[1]),
+            (2_000_000_000, b"00000\0\xAA\xAA".to_vec())
+        );
+    }
+
+    #[test]
+    fn format_into_measures() {
+        assert_eq!(
+            snprintf(0, 0, "%d|%5d|", &[-12i32 as u32, 3]),
+            (10, vec![0xAA, 0xAA])
+        );
+        assert_eq!(snprintf(0, 0, "%2000000000d", &[1]).0, 2_000_000_000);
+        // Output too long for the int return value.
+        assert_eq!(snprintf(0, 0, "%2000000000d%2000000000d", &[1, 2]).0, -1);
+    }
+
+    #[test]
+    fn null_format_is_invalid() {
+        let (mut machine, _host) = new_machine();
+        assert_eq!(sprintf(&mut machine, 0, None, VarArgs::from_va_list(0)), -1);
+        let errno = _errno(&mut machine);
+        assert_eq!(machine.mem().get_pod::<u32>(errno), EINVAL);
+        assert_eq!(_errno(&mut machine), errno);
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
-    winapi::{stack_args::ArrayWithSizeMut, types::*},
+    host,
+    winapi::{kernel32, stack_args::ArrayWithSizeMut, types::*, ERROR},
     Machine,
 };
 
@@ -40,42 +41,85 @@ pub mod MessageBoxFlags {
 }
 */
 
+/// The buttons of each MB_* button set, as (label, ID* value) pairs.
+fn message_box_buttons(uType: u32) -> &'static [(&'static str, u32)] {
+    const IDOK: u32 = 1;
+    const IDCANCEL: u32 = 2;
+    const IDABORT: u32 = 3;
+    const IDRETRY: u32 = 4;
+    const IDIGNORE: u32 = 5;
+    const IDYES: u32 = 6;
+    const IDNO: u32 = 7;
+    const IDTRYAGAIN: u32 = 10;
+    const IDCONTINUE: u32 = 11;
+    match uType & 0xF {
+        0x1 => &[("OK", IDOK), ("Cancel", IDCANCEL)],
+        0x2 => &[("Abort", IDABORT), ("Retry", IDRETRY), ("Ignore", IDIGNORE)],
+        0x3 => &[("Yes", IDYES), ("No", IDNO), ("Cancel", IDCANCEL)],
+        0x4 => &[("Yes", IDYES), ("No", IDNO)],
+        0x5 => &[("Retry", IDRETRY), ("Cancel", IDCANCEL)],
+        0x6 => &[
+            ("Cancel", IDCANCEL),
+            ("Try Again", IDTRYAGAIN),
+            ("Continue", IDCONTINUE),
+        ],
+        _ => &[("OK", IDOK)],
+    }
+}
+
+/// Show a message box via the host, blocking the calling thread until the user answers.
+async fn message_box(machine: &mut Machine, caption: String, text: String, uType: u32) -> u32 {
+    machine
+        .host
+        .log(format!("MessageBox: {caption}\n{text}").as_bytes());
+    let buttons = message_box_buttons(uType);
+    let msgbox = host::MessageBox {
+        caption,
+        text,
+        buttons: buttons.iter().map(|&(label, _)| label).collect(),
+        default: ((uType >> 8) & 0xF).min(buttons.len() as u32 - 1) as usize,
+    };
+    machine.state.user32.message_boxes += 1;
+    let id = machine.state.user32.message_boxes;
+    // TODO: a real message box runs a modal message loop for the owner window meanwhile.
+    let choice = kernel32::block_until(machine, |machine| {
+        match machine.host.message_box(id, &msgbox) {
+            Err(ERROR::IO_PENDING) => None,
+            Ok(index) if index < buttons.len() => Some(index),
+            res => {
+                log::warn!("MessageBox: host answered {res:?}, taking the default");
+                Some(msgbox.default)
+            }
+        }
+    })
+    .await;
+    buttons[choice].1
+}
+
 #[win32_derive::dllexport]
-pub fn MessageBoxA(
+pub async fn MessageBoxA(
     machine: &mut Machine,
     hWnd: HWND,
     lpText: Option<&str>,
     lpCaption: Option<&str>,
     uType: u32,
 ) -> u32 {
-    machine.host.log(
-        format!(
-            "MessageBox: {}\n{}",
-            lpCaption.unwrap_or("Error"),
-            lpText.unwrap_or("")
-        )
-        .as_bytes(),
-    );
-    1 // IDOK
+    let caption = lpCaption.unwrap_or("Error").to_string();
+    let text = lpText.unwrap_or("").to_string();
+    message_box(machine, caption, text, uType).await
 }
 
 #[win32_derive::dllexport]
-pub fn MessageBoxW(
+pub async fn MessageBoxW(
     machine: &mut Machine,
     hWnd: HWND,
     lpText: Option<&Str16>,
     lpCaption: Option<&Str16>,
     uType: u32,
 ) -> u32 {
-    machine.host.log(
-        format!(
-            "MessageBox: {}\n{}",
-            lpCaption.unwrap().to_string(),
-            lpText.unwrap().to_string()
-        )
-        .as_bytes(),
-    );
-    1 // IDOK
+    let caption = lpCaption.map_or_else(|| "Error".to_string(), |s| s.to_string());
+    let text = lpText.map(|s| s.to_string()).unwrap_or_default();
+    message_box(machine, caption, text, uType).await
 }
 
 #[win32_derive::dllexport]
@@ -202,3 +246,78 @@ pub fn IsDlgButtonChecked(_machine: &mut Machine, hDlg: HWND, nIDButton: i32) ->
 pub fn EndDialog(_machine: &mut Machine, hDlg: HWND, nResult: Option<&mut u32>) -> bool {
     todo!();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buttons() {
+        let ids = |uType| {
+            message_box_buttons(uType)
+                .iter()
+                .map(|&(_, id)| id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(0x0), [1]);
+        assert_eq!(ids(0x1), [1, 2]);
+        assert_eq!(ids(0x3), [6, 7, 2]);
+        assert_eq!(ids(0x6), [2, 10, 11]);
+        // Icon and default button bits don't change the set.
+        assert_eq!(ids(0x234), [6, 7]);
+        assert_eq!(ids(0xF), [1]);
+    }
+
+    #[cfg(feature = "x86-emu")]
+    mod guest {
+        use crate::{
+            machine::Status,
+            test_guest::{builtin, new_machine, run, Code},
+            Machine,
+        };
+
+        /// MessageBoxA(NULL, NULL, NULL, uType), exiting with its result.
+        fn message_box(machine: &mut Machine, uType: u32) {
+            let message_box = builtin(machine, "user32.dll", "MessageBoxA");
+            let mut code = Code::alloc(machine, 0x100);
+            code.push(uType)
+                .push(0)
+                .push(0)
+                .push(0)
+                .call(message_box)
+                .exit_with(machine, 0);
+            code.start(machine);
+        }
+
+        #[test]
+        fn default_button() {
+            let (mut machine, _host) = new_machine();
+            message_box(&mut machine, 0x104); // MB_YESNO | MB_DEFBUTTON2
+            assert_eq!(run(&mut machine), 7); // IDNO
+
+            // A default past the end of the set falls back to its last button.
+            let (mut machine, _host) = new_machine();
+            message_box(&mut machine, 0x201); // MB_OKCANCEL | MB_DEFBUTTON3
+            assert_eq!(run(&mut machine), 2); // IDCANCEL
+        }
+
+        #[test]
+        fn blocks_until_answered() {
+            let (mut machine, host) = new_machine();
+            host.hold_answers(true);
+            message_box(&mut machine, 0x3); // MB_YESNOCANCEL
+            while machine.run() {}
+            assert!(matches!(machine.status, Status::Blocked));
+
+            // A wakeup before the answer is in just blocks again.
+            machine.wake_blocked();
+            while machine.run() {}
+            assert!(matches!(machine.status, Status::Blocked));
+
+            host.answer(1);
+            host.hold_answers(false);
+            machine.wake_blocked();
+            assert_eq!(run(&mut machine), 7); // IDNO
+        }
+    }
+}
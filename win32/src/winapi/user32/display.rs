@@ -0,0 +1,429 @@
+//! Display modes, as switched by ChangeDisplaySettings.
+//! There's no real display to reconfigure; the mode is recorded and reflected back
+//! through GetSystemMetrics/GetDeviceCaps, and fullscreen windows follow it.
+
+use super::*;
+use crate::{
+    winapi::{
+        handle::HANDLE,
+        types::{POINT, RECT},
+    },
+    Machine,
+};
+use bitflags::bitflags;
+use memory::{Extensions, ExtensionsMut};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct DisplayMode {
+    pub width: u32,
+    pub height: u32,
+    pub bpp: u32,
+}
+
+impl Default for DisplayMode {
+    fn default() -> Self {
+        DisplayMode {
+            width: 640,
+            height: 480,
+            bpp: 32,
+        }
+    }
+}
+
+impl std::str::FromStr for DisplayMode {
+    type Err = String;
+
+    /// Parses "WIDTHxHEIGHT" or "WIDTHxHEIGHTxBPP", with bpp defaulting to 32.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts = s
+            .split('x')
+            .map(|n| n.parse::<u32>().ok().filter(|&n| n > 0))
+            .collect::<Option<Vec<_>>>();
+        let mode = match parts.as_deref() {
+            Some(&[width, height]) => Some(DisplayMode {
+                width,
+                height,
+                ..Default::default()
+            }),
+            Some(&[width, height, bpp]) if [8, 16, 24, 32].contains(&bpp) => {
+                Some(DisplayMode { width, height, bpp })
+            }
+            _ => None,
+        };
+        mode.ok_or_else(|| format!("invalid display mode {s:?}, expected WIDTHxHEIGHT[xBPP]"))
+    }
+}
+
+/// Resolutions reported by EnumDisplaySettings, besides the desktop's.
+/// ChangeDisplaySettings accepts others too.
+const MODES: [(u32, u32); 3] = [(640, 480), (800, 600), (1024, 768)];
+const DEPTHS: [u32; 3] = [8, 16, 32];
+
+#[repr(C)]
+#[derive(Clone, Debug)]
+pub struct DEVMODEA {
+    pub dmDeviceName: [u8; 32],
+    pub dmSpecVersion: u16,
+    pub dmDriverVersion: u16,
+    pub dmSize: u16,
+    pub dmDriverExtra: u16,
+    pub dmFields: u32,
+    /// Union of printer orientation fields and display position fields.
+    pub dmPosition: [u32; 4],
+    pub dmColor: i16,
+    pub dmDuplex: i16,
+    pub dmYResolution: i16,
+    pub dmTTOption: i16,
+    pub dmCollate: i16,
+    pub dmFormName: [u8; 32],
+    pub dmLogPixels: u16,
+    pub dmBitsPerPel: u32,
+    pub dmPelsWidth: u32,
+    pub dmPelsHeight: u32,
+    pub dmDisplayFlags: u32,
+    pub dmDisplayFrequency: u32,
+    pub dmICMMethod: u32,
+    pub dmICMIntent: u32,
+    pub dmMediaType: u32,
+    pub dmDitherType: u32,
+    pub dmReserved1: u32,
+    pub dmReserved2: u32,
+    pub dmPanningWidth: u32,
+    pub dmPanningHeight: u32,
+}
+unsafe impl memory::Pod for DEVMODEA {}
+
+bitflags! {
+    pub struct DM: u32 {
+        const BITSPERPEL       = 0x0004_0000;
+        const PELSWIDTH        = 0x0008_0000;
+        const PELSHEIGHT       = 0x0010_0000;
+        const DISPLAYFLAGS     = 0x0020_0000;
+        const DISPLAYFREQUENCY = 0x0040_0000;
+    }
+}
+
+impl DEVMODEA {
+    fn set_mode(&mut self, mode: &DisplayMode) {
+        self.dmSize = std::mem::size_of::<DEVMODEA>() as u16;
+        self.dmFields =
+            (DM::BITSPERPEL | DM::PELSWIDTH | DM::PELSHEIGHT | DM::DISPLAYFREQUENCY).bits();
+        self.dmBitsPerPel = mode.bpp;
+        self.dmPelsWidth = mode.width;
+        self.dmPelsHeight = mode.height;
+        self.dmDisplayFlags = 0;
+        self.dmDisplayFrequency = 60;
+    }
+}
+
+bitflags! {
+    pub struct CDS: u32 {
+        const UPDATEREGISTRY = 0x0000_0001;
+        const TEST           = 0x0000_0002;
+        const FULLSCREEN     = 0x0000_0004;
+        const GLOBAL         = 0x0000_0008;
+        const SET_PRIMARY    = 0x0000_0010;
+        const RESET          = 0x4000_0000;
+        const NORESET        = 0x1000_0000;
+    }
+}
+impl TryFrom<u32> for CDS {
+    type Error = u32;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        CDS::from_bits(value).ok_or(value)
+    }
+}
+
+const DISP_CHANGE_SUCCESSFUL: i32 = 0;
+const DISP_CHANGE_BADMODE: i32 = -2;
+
+/// Set the mode the monitor is configured for, which is both the initial display mode
+/// and the one ChangeDisplaySettings returns to on reset.
+pub fn set_desktop_mode(machine: &mut Machine, mode: DisplayMode) {
+    machine.state.user32.desktop = mode;
+    machine.state.user32.display = mode;
+}
+
+/// Switch display mode, resizing any windows that covered the whole screen in the
+/// previous mode to cover it in the new one, and notifying windows with WM_DISPLAYCHANGE.
+pub async fn set_display_mode(machine: &mut Machine, mode: DisplayMode) {
+    let prev = std::mem::replace(&mut machine.state.user32.display, mode);
+    if prev == mode {
+        return;
+    }
+    for window in machine.state.user32.windows.iter_mut() {
+        if matches!(window.typ, WindowType::TopLevel(_))
+            && window.width >= prev.width
+            && window.height >= prev.height
+        {
+            window.set_client_size(&mut *machine.host, mode.width, mode.height);
+        }
+    }
+    send_message(
+        machine,
+        HWND_BROADCAST,
+        WM::DISPLAYCHANGE as u32,
+        mode.bpp,
+        (mode.height << 16) | mode.width,
+    )
+    .await;
+}
+
+#[win32_derive::dllexport]
+pub async fn ChangeDisplaySettingsA(
+    machine: &mut Machine,
+    lpDevMode: Option<&DEVMODEA>,
+    dwFlags: Result<CDS, u32>,
+) -> i32 {
+    // Ignore flags we don't know about rather than failing the call.
+    let flags = dwFlags.unwrap_or_else(CDS::from_bits_truncate);
+    let mode = match lpDevMode {
+        // Null means return to the registry (desktop) mode.
+        None => machine.state.user32.desktop,
+        Some(devmode) => {
+            let current = machine.state.user32.display;
+            let fields = DM::from_bits_truncate(devmode.dmFields);
+            DisplayMode {
+                width: if fields.contains(DM::PELSWIDTH) {
+                    devmode.dmPelsWidth
+                } else {
+                    current.width
+                },
+                height: if fields.contains(DM::PELSHEIGHT) {
+                    devmode.dmPelsHeight
+                } else {
+                    current.height
+                },
+                bpp: if fields.contains(DM::BITSPERPEL) {
+                    devmode.dmBitsPerPel
+                } else {
+                    current.bpp
+                },
+            }
+        }
+    };
+    if mode.width == 0 || mode.height == 0 || ![8, 16, 24, 32].contains(&mode.bpp) {
+        return DISP_CHANGE_BADMODE;
+    }
+    if !flags.contains(CDS::TEST) {
+        set_display_mode(machine, mode).await;
+    }
+    DISP_CHANGE_SUCCESSFUL
+}
+
+const ENUM_CURRENT_SETTINGS: u32 = -1i32 as u32;
+const ENUM_REGISTRY_SETTINGS: u32 = -2i32 as u32;
+
+#[win32_derive::dllexport]
+pub fn EnumDisplaySettingsA(
+    machine: &mut Machine,
+    lpszDeviceName: Option<&str>,
+    iModeNum: u32,
+    lpDevMode: Option<&mut DEVMODEA>,
+) -> bool {
+    let mode = match iModeNum {
+        ENUM_CURRENT_SETTINGS => machine.state.user32.display,
+        ENUM_REGISTRY_SETTINGS => machine.state.user32.desktop,
+        n => {
+            let n = n as usize;
+            // The desktop's resolution is listed too, after the standard ones.
+            let desktop = machine.state.user32.desktop;
+            let mut resolutions = MODES.to_vec();
+            if !resolutions.contains(&(desktop.width, desktop.height)) {
+                resolutions.push((desktop.width, desktop.height));
+            }
+            let Some(&(width, height)) = resolutions.get(n / DEPTHS.len()) else {
+                return false; // no more modes
+            };
+            DisplayMode {
+                width,
+                height,
+                bpp: DEPTHS[n % DEPTHS.len()],
+            }
+        }
+    };
+    lpDevMode.unwrap().set_mode(&mode);
+    true
+}
+
+// There's only ever one monitor, covering the current display mode.
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct HMONITORT;
+pub type HMONITOR = HANDLE<HMONITORT>;
+
+const PRIMARY_MONITOR: HMONITOR = HMONITOR::from_raw(1);
+
+fn monitor_rect(machine: &Machine) -> RECT {
+    let display = &machine.state.user32.display;
+    RECT {
+        left: 0,
+        top: 0,
+        right: display.width as i32,
+        bottom: display.height as i32,
+    }
+}
+
+#[derive(Debug, win32_derive::TryFromEnum)]
+pub enum MONITOR {
+    DEFAULTTONULL = 0,
+    DEFAULTTOPRIMARY = 1,
+    DEFAULTTONEAREST = 2,
+}
+
+/// Shared logic of the MonitorFrom* functions, given whether the target is on the monitor.
+fn monitor_from(on_monitor: bool, dwFlags: Result<MONITOR, u32>) -> HMONITOR {
+    if on_monitor {
+        return PRIMARY_MONITOR;
+    }
+    match dwFlags {
+        Ok(MONITOR::DEFAULTTONULL) => HMONITOR::null(),
+        _ => PRIMARY_MONITOR,
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn MonitorFromWindow(
+    _machine: &mut Machine,
+    hwnd: HWND,
+    dwFlags: Result<MONITOR, u32>,
+) -> HMONITOR {
+    // Windows are always positioned on screen.
+    monitor_from(true, dwFlags)
+}
+
+#[win32_derive::dllexport]
+pub fn MonitorFromPoint(
+    machine: &mut Machine,
+    pt: POINT,
+    dwFlags: Result<MONITOR, u32>,
+) -> HMONITOR {
+    let rect = monitor_rect(machine);
+    let (x, y) = (pt.x as i32, pt.y as i32);
+    let on_monitor = x >= rect.left && x < rect.right && y >= rect.top && y < rect.bottom;
+    monitor_from(on_monitor, dwFlags)
+}
+
+#[win32_derive::dllexport]
+pub fn MonitorFromRect(
+    machine: &mut Machine,
+    lprc: Option<&RECT>,
+    dwFlags: Result<MONITOR, u32>,
+) -> HMONITOR {
+    let rect = monitor_rect(machine);
+    let lprc = lprc.unwrap();
+    let on_monitor = lprc.left < rect.right
+        && lprc.right > rect.left
+        && lprc.top < rect.bottom
+        && lprc.bottom > rect.top;
+    monitor_from(on_monitor, dwFlags)
+}
+
+#[repr(C)]
+#[derive(Debug, Default, Clone)]
+pub struct MONITORINFO {
+    pub cbSize: u32,
+    pub rcMonitor: RECT,
+    pub rcWork: RECT,
+    pub dwFlags: u32,
+}
+unsafe impl memory::Pod for MONITORINFO {}
+
+const MONITORINFOF_PRIMARY: u32 = 1;
+
+/// Fill in a MONITORINFO or MONITORINFOEX, where the latter adds a device name
+/// of `char_size`-byte characters.
+fn get_monitor_info(machine: &mut Machine, hMonitor: HMONITOR, lpmi: u32, char_size: u32) -> bool {
+    if hMonitor != PRIMARY_MONITOR || lpmi == 0 {
+        return false;
+    }
+    let rect = monitor_rect(machine);
+    let mem = machine.mem();
+    let cbSize = mem.get_pod::<u32>(lpmi);
+    let info_size = std::mem::size_of::<MONITORINFO>() as u32;
+    if cbSize < info_size {
+        return false;
+    }
+    mem.put_pod::<MONITORINFO>(
+        lpmi,
+        MONITORINFO {
+            cbSize,
+            rcMonitor: rect.clone(),
+            rcWork: rect, // no taskbar
+            dwFlags: MONITORINFOF_PRIMARY,
+        },
+    );
+    const CCHDEVICENAME: u32 = 32;
+    if cbSize >= info_size + CCHDEVICENAME * char_size {
+        let Ok(name) = mem.try_sub32_mut(lpmi + info_size, CCHDEVICENAME * char_size) else {
+            return false;
+        };
+        name.fill(0);
+        for (i, &c) in b"\\\\.\\DISPLAY1".iter().enumerate() {
+            name[i * char_size as usize] = c;
+        }
+    }
+    true
+}
+
+#[win32_derive::dllexport]
+pub fn GetMonitorInfoA(machine: &mut Machine, hMonitor: HMONITOR, lpmi: u32) -> bool {
+    get_monitor_info(machine, hMonitor, lpmi, 1)
+}
+
+#[win32_derive::dllexport]
+pub fn GetMonitorInfoW(machine: &mut Machine, hMonitor: HMONITOR, lpmi: u32) -> bool {
+    get_monitor_info(machine, hMonitor, lpmi, 2)
+}
+
+#[win32_derive::dllexport]
+pub async fn EnumDisplayMonitors(
+    machine: &mut Machine,
+    hdc: HDC,
+    lprcClip: Option<&RECT>,
+    lpfnEnum: u32,
+    dwData: u32,
+) -> bool {
+    // TODO: when hdc is given, the rect should be intersected with the DC's visible region.
+    let rect = monitor_rect(machine);
+    if let Some(clip) = lprcClip {
+        if clip.left >= rect.right
+            || clip.right <= rect.left
+            || clip.top >= rect.bottom
+            || clip.bottom <= rect.top
+        {
+            return true; // no monitors intersect the clip
+        }
+    }
+
+    let mem = machine.emu.memory.mem();
+    let rect_addr = machine
+        .state
+        .scratch
+        .alloc(mem, std::mem::size_of::<RECT>() as u32);
+    mem.put_pod::<RECT>(rect_addr, rect);
+    machine
+        .call_guest(lpfnEnum, (PRIMARY_MONITOR, hdc, rect_addr, dwData))
+        .await;
+    machine
+        .state
+        .scratch
+        .free(machine.emu.memory.mem(), rect_addr);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_mode() {
+        let mode = |width, height, bpp| DisplayMode { width, height, bpp };
+        assert_eq!("800x600".parse(), Ok(mode(800, 600, 32)));
+        assert_eq!("1024x768x16".parse(), Ok(mode(1024, 768, 16)));
+        for bad in ["", "800", "800x", "0x600", "800x600x12", "800x600x16x1"] {
+            assert!(bad.parse::<DisplayMode>().is_err(), "{bad:?}");
+        }
+    }
+}
@@ -0,0 +1,194 @@
+//! Window activation and keyboard focus.
+//!
+//! The active window is the top-level window the user is working with, and the focus window
+//! (the active window or one of its children) is the one receiving keyboard input.
+//! Both change either at the guest's request (ShowWindow, SetFocus etc.) or when the host
+//! reports one of its windows gaining or losing focus.
+
+use super::*;
+use crate::Machine;
+
+const WA_INACTIVE: u32 = 0;
+const WA_ACTIVE: u32 = 1;
+
+fn notify(hwnd: HWND, message: WM, wParam: u32, lParam: u32) -> MSG {
+    MSG {
+        hwnd,
+        message: message as u32,
+        wParam,
+        lParam,
+        time: 0,
+        pt_x: 0,
+        pt_y: 0,
+    }
+}
+
+/// Make hwnd (a top-level window, or null for none) the active window.
+/// Returns the messages notifying affected windows, in the order they should be delivered.
+fn change_active(user32: &mut State, hwnd: HWND) -> Vec<MSG> {
+    let prev = user32.active;
+    let mut msgs = Vec::new();
+    if prev == hwnd {
+        return msgs;
+    }
+    if prev.is_null() {
+        // The application is becoming active.
+        msgs.push(notify(hwnd, WM::ACTIVATEAPP, true as u32, 0)); // TODO: thread id
+    } else {
+        msgs.push(notify(prev, WM::ACTIVATE, WA_INACTIVE, hwnd.to_raw()));
+        if hwnd.is_null() {
+            msgs.push(notify(prev, WM::ACTIVATEAPP, false as u32, 0));
+        }
+    }
+
+    user32.active = hwnd;
+    if !hwnd.is_null() {
+        if let Some(pos) = user32.z_order.iter().position(|&w| w == hwnd) {
+            user32.z_order.remove(pos);
+            user32.z_order.insert(0, hwnd);
+        }
+        msgs.push(notify(hwnd, WM::ACTIVATE, WA_ACTIVE, prev.to_raw()));
+    }
+
+    // DefWindowProc's WM_ACTIVATE handling moves focus to the activated window.
+    msgs.extend(change_focus(user32, hwnd));
+    msgs
+}
+
+/// Move keyboard focus to hwnd (or null for none), returning the notification messages.
+fn change_focus(user32: &mut State, hwnd: HWND) -> Vec<MSG> {
+    let prev = user32.focus;
+    let mut msgs = Vec::new();
+    if prev == hwnd {
+        return msgs;
+    }
+    user32.focus = hwnd;
+    if !prev.is_null() && user32.windows.get(prev).is_some() {
+        msgs.push(notify(prev, WM::KILLFOCUS, hwnd.to_raw(), 0));
+    }
+    if !hwnd.is_null() {
+        msgs.push(notify(hwnd, WM::SETFOCUS, prev.to_raw(), 0));
+    }
+    msgs
+}
+
+/// The most recently activated visible top-level window, other than `except`.
+fn next_active(user32: &State, except: HWND) -> HWND {
+    user32
+        .z_order
+        .iter()
+        .copied()
+        .find(|&hwnd| {
+            hwnd != except
+                && user32
+                    .windows
+                    .get(hwnd)
+                    .map_or(false, |w| w.style.contains(WindowStyle::VISIBLE))
+        })
+        .unwrap_or_default()
+}
+
+async fn dispatch_all(machine: &mut Machine, msgs: Vec<MSG>) {
+    for msg in msgs {
+        dispatch_message(machine, &msg).await;
+    }
+}
+
+/// Activate a top-level window (raising it on the host), synchronously sending the
+/// activation messages.  Returns the previously active window.
+pub async fn set_active_window(machine: &mut Machine, hwnd: HWND) -> HWND {
+    let prev = machine.state.user32.active;
+    let msgs = change_active(&mut machine.state.user32, hwnd);
+    if let Some(window) = machine.state.user32.windows.get_mut(hwnd) {
+        if let WindowType::TopLevel(top) = &mut window.typ {
+            if window.style.contains(WindowStyle::VISIBLE) {
+                top.host.show(true);
+            }
+        }
+    }
+    dispatch_all(machine, msgs).await;
+    prev
+}
+
+/// Called when a window is hidden or destroyed, to hand activation and focus elsewhere.
+pub async fn window_hidden(machine: &mut Machine, hwnd: HWND) {
+    let user32 = &mut machine.state.user32;
+    if user32.active == hwnd {
+        let next = next_active(user32, hwnd);
+        set_active_window(machine, next).await;
+    } else if user32.focus == hwnd {
+        let msgs = change_focus(user32, HWND::null());
+        dispatch_all(machine, msgs).await;
+    }
+}
+
+/// Handle a host window gaining or losing focus.
+/// Windows delivers activation messages synchronously, but these arrive while the guest
+/// is polling for messages, so instead they are returned to be queued as input.
+pub fn host_focus_changed(user32: &mut State, hwnd: HWND, focused: bool) -> Vec<MSG> {
+    if focused {
+        if !user32.z_order.contains(&hwnd) {
+            return Vec::new();
+        }
+        change_active(user32, hwnd)
+    } else if user32.active == hwnd {
+        change_active(user32, HWND::null())
+    } else {
+        Vec::new()
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn GetForegroundWindow(machine: &mut Machine) -> HWND {
+    match machine.state.user32.active.to_option() {
+        Some(hwnd) => hwnd,
+        None => GetDesktopWindow(machine),
+    }
+}
+
+#[win32_derive::dllexport]
+pub async fn SetForegroundWindow(machine: &mut Machine, hWnd: HWND) -> bool {
+    if !machine.state.user32.z_order.contains(&hWnd) {
+        return false;
+    }
+    set_active_window(machine, hWnd).await;
+    true // success
+}
+
+#[win32_derive::dllexport]
+pub fn GetActiveWindow(machine: &mut Machine) -> HWND {
+    machine.state.user32.active
+}
+
+#[win32_derive::dllexport]
+pub async fn SetActiveWindow(machine: &mut Machine, hWnd: HWND) -> HWND {
+    if !hWnd.is_null() && !machine.state.user32.z_order.contains(&hWnd) {
+        return HWND::null(); // failure
+    }
+    set_active_window(machine, hWnd).await
+}
+
+#[win32_derive::dllexport]
+pub async fn SetFocus(machine: &mut Machine, hWnd: HWND) -> HWND {
+    let prev = machine.state.user32.focus;
+    if hWnd.is_null() {
+        let msgs = change_focus(&mut machine.state.user32, hWnd);
+        dispatch_all(machine, msgs).await;
+        return prev;
+    }
+    if machine.state.user32.windows.get(hWnd).is_none() {
+        return HWND::null(); // failure
+    }
+    // TODO: child windows don't track their parent, so focusing one can't activate it.
+    if machine.state.user32.z_order.contains(&hWnd) {
+        set_active_window(machine, hWnd).await;
+    }
+    let msgs = change_focus(&mut machine.state.user32, hWnd);
+    dispatch_all(machine, msgs).await;
+    prev
+}
+
+#[win32_derive::dllexport]
+pub fn GetFocus(machine: &mut Machine) -> HWND {
+    machine.state.user32.focus
+}
@@ -0,0 +1,230 @@
+use super::{HINSTANCE, MSG};
+use crate::{winapi::types::*, Machine};
+use memory::{Extensions, ExtensionsMut};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct HOOKT;
+pub type HHOOK = HANDLE<HOOKT>;
+
+const HC_ACTION: i32 = 0;
+const WH_GETMESSAGE: i32 = 3;
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct Hook {
+    hhk: HHOOK,
+    idHook: i32,
+    /// Function to call for each event.
+    proc: u32,
+}
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct Hooks {
+    /// Installed hooks, oldest first.
+    hooks: Vec<Hook>,
+    next: u32,
+    /// The hooks currently being called, innermost last, for CallNextHookEx to continue from.
+    running: Vec<HHOOK>,
+}
+
+/// Call the newest hook of type idHook installed before `before`, if any, which is
+/// expected to pass the event on with CallNextHookEx.  Returns 0 past the end of the chain.
+async fn call_hook(
+    machine: &mut Machine,
+    idHook: i32,
+    before: Option<HHOOK>,
+    code: i32,
+    wParam: u32,
+    lParam: u32,
+) -> u32 {
+    let hooks = &machine.state.user32.hooks.hooks;
+    let end = match before {
+        Some(hhk) => match hooks.iter().position(|hook| hook.hhk == hhk) {
+            Some(index) => index,
+            None => return 0,
+        },
+        None => hooks.len(),
+    };
+    let Some(hook) = hooks[..end].iter().rev().find(|hook| hook.idHook == idHook) else {
+        return 0;
+    };
+    let (hhk, proc) = (hook.hhk, hook.proc);
+
+    machine.state.user32.hooks.running.push(hhk);
+    let ret = machine.call_guest(proc, (code, wParam, lParam)).await;
+    machine.state.user32.hooks.running.pop();
+    ret
+}
+
+/// Run the WH_GETMESSAGE hooks on a message about to be retrieved, which they may modify.
+pub async fn get_message_hooks(machine: &mut Machine, msg: MSG, remove: bool) -> MSG {
+    let hooks = &machine.state.user32.hooks.hooks;
+    if !hooks.iter().any(|hook| hook.idHook == WH_GETMESSAGE) {
+        return msg;
+    }
+    let mem = machine.emu.memory.mem();
+    let msg_addr = machine
+        .state
+        .scratch
+        .alloc(mem, std::mem::size_of::<MSG>() as u32);
+    mem.put_pod::<MSG>(msg_addr, msg);
+    call_hook(
+        machine,
+        WH_GETMESSAGE,
+        None,
+        HC_ACTION,
+        remove as u32,
+        msg_addr,
+    )
+    .await;
+    let mem = machine.emu.memory.mem();
+    let msg = mem.get_pod::<MSG>(msg_addr);
+    machine.state.scratch.free(mem, msg_addr);
+    msg
+}
+
+#[win32_derive::dllexport]
+pub fn SetWindowsHookExA(
+    machine: &mut Machine,
+    idHook: i32,
+    lpfn: u32,
+    hmod: HINSTANCE,
+    dwThreadId: u32,
+) -> HHOOK {
+    if idHook != WH_GETMESSAGE {
+        log::warn!("TODO: hook type {idHook} is never called");
+    }
+    // There is only the one process, so thread and global hooks look the same.
+    let hooks = &mut machine.state.user32.hooks;
+    hooks.next += 1;
+    let hhk = HHOOK::from_raw(hooks.next);
+    hooks.hooks.push(Hook {
+        hhk,
+        idHook,
+        proc: lpfn,
+    });
+    hhk
+}
+
+#[win32_derive::dllexport]
+pub fn SetWindowsHookExW(
+    machine: &mut Machine,
+    idHook: i32,
+    lpfn: u32,
+    hmod: HINSTANCE,
+    dwThreadId: u32,
+) -> HHOOK {
+    SetWindowsHookExA(machine, idHook, lpfn, hmod, dwThreadId)
+}
+
+#[win32_derive::dllexport]
+pub fn UnhookWindowsHookEx(machine: &mut Machine, hhk: HHOOK) -> bool {
+    let hooks = &mut machine.state.user32.hooks.hooks;
+    match hooks.iter().position(|hook| hook.hhk == hhk) {
+        Some(index) => {
+            hooks.remove(index);
+            true
+        }
+        None => false,
+    }
+}
+
+#[win32_derive::dllexport]
+pub async fn CallNextHookEx(
+    machine: &mut Machine,
+    hhk: HHOOK,
+    nCode: i32,
+    wParam: u32,
+    lParam: u32,
+) -> u32 {
+    // hhk is ignored, as in modern Windows: the chain continues from the running hook.
+    let hooks = &machine.state.user32.hooks;
+    let Some(&current) = hooks.running.last() else {
+        return 0;
+    };
+    let Some(idHook) = hooks
+        .hooks
+        .iter()
+        .find(|hook| hook.hhk == current)
+        .map(|hook| hook.idHook)
+    else {
+        return 0; // unhooked while running
+    };
+    call_hook(machine, idHook, Some(current), nCode, wParam, lParam).await
+}
+
+#[cfg(all(test, feature = "x86-emu"))]
+mod tests {
+    use super::*;
+    use crate::test_guest::{builtin, new_machine, run, Code, Log};
+
+    #[test]
+    fn get_message_hooks_chain() {
+        let (mut machine, _host) = new_machine();
+        let set_hook = builtin(&mut machine, "user32.dll", "SetWindowsHookExA");
+        let unhook = builtin(&mut machine, "user32.dll", "UnhookWindowsHookEx");
+        let call_next = builtin(&mut machine, "user32.dll", "CallNextHookEx");
+        let post = builtin(&mut machine, "user32.dll", "PostMessageW");
+        let get_message = builtin(&mut machine, "user32.dll", "GetMessageA");
+        let log = Log::alloc(&mut machine, 0x1000);
+        let mut code = Code::alloc(&mut machine, 0x300);
+        let (first, second) = (code.base + 0x180, code.base + 0x1C0);
+        let hhk = code.base + 0x200;
+        let (msg1, msg2) = (code.base + 0x240, code.base + 0x280);
+        code.push(0)
+            .push(0)
+            .push(first)
+            .push(WH_GETMESSAGE as u32)
+            .call(set_hook)
+            .push(0)
+            .push(0)
+            .push(second)
+            .push(WH_GETMESSAGE as u32)
+            .call(set_hook)
+            .emit(&[0xA3]) // mov [hhk], eax
+            .emit32(hhk);
+        for msg in [msg1, msg2] {
+            code.push(0)
+                .push(0)
+                .push(0x400)
+                .push(0)
+                .call(post)
+                .push(0)
+                .push(0)
+                .push(0)
+                .push(msg)
+                .call(get_message);
+            if msg == msg1 {
+                code.emit(&[0xFF, 0x35]) // push [hhk]
+                    .emit32(hhk)
+                    .call(unhook);
+            }
+        }
+        code.emit(&[0xA1]) // mov eax, [msg1.wParam]
+            .emit32(msg1 + 8)
+            .exit_with(&mut machine, 0)
+            // The first hook just records the call.
+            .at(0x180)
+            .recorder(&log, 3, 0)
+            // The second, newer hook runs first: it changes wParam and passes the message on.
+            .at(0x1C0)
+            .emit(&[0x8B, 0x44, 0x24, 0x0C]) // mov eax, [esp+12]
+            .emit(&[0xC7, 0x40, 0x08]) // mov dword [eax+8], 42
+            .emit32(42)
+            .emit(&[0xFF, 0x74, 0x24, 0x0C]) // push [esp+12], three times: the args
+            .emit(&[0xFF, 0x74, 0x24, 0x0C])
+            .emit(&[0xFF, 0x74, 0x24, 0x0C])
+            .push(0)
+            .call(call_next)
+            .emit(&[0xC2, 12, 0]); // ret 12
+        code.start(&mut machine);
+        assert_eq!(run(&mut machine), 42);
+
+        // Both messages reached the first hook, as HC_ACTION with PM_REMOVE, but only the
+        // first passed through the second hook before it was unhooked.
+        let calls = log.read(&machine);
+        assert_eq!(calls.len(), 6);
+        assert_eq!([calls[0], calls[1], calls[3], calls[4]], [0, 1, 0, 1]);
+        let msg2 = machine.mem().get_pod::<MSG>(msg2);
+        assert_eq!((msg2.message, msg2.wParam), (0x400, 0));
+    }
+}
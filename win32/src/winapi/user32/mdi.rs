@@ -0,0 +1,377 @@
+//! Multiple document interface: an MDICLIENT window, living inside an application's
+//! frame window, manages a set of document child windows.
+//!
+//! Child windows aren't yet drawn, so this is only the bookkeeping and message flow,
+//! which is enough for applications to open and switch between their documents.
+
+use super::*;
+use crate::{str16::expect_ascii, winapi::types::HWND, Machine};
+use memory::{Extensions, ExtensionsMut};
+
+/// State of an MDICLIENT window.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct MDIClient {
+    /// Child windows, in creation order.
+    children: Vec<HWND>,
+    active: HWND,
+    frame_menu: HMENU,
+    /// The "Window" menu, which lists the child windows.
+    /// TODO: menus are not implemented, so the child entries are never actually added to it.
+    window_menu: HMENU,
+    /// Menu command ID of the first child; subsequent children get subsequent IDs.
+    first_child_id: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Debug)]
+pub struct CLIENTCREATESTRUCT {
+    pub hWindowMenu: HMENU,
+    pub idFirstChild: u32,
+}
+unsafe impl memory::Pod for CLIENTCREATESTRUCT {}
+
+#[repr(C)]
+#[derive(Clone, Debug)]
+pub struct MDICREATESTRUCTA {
+    pub szClass: u32,
+    pub szTitle: u32,
+    pub hOwner: u32,
+    pub x: u32,
+    pub y: u32,
+    pub cx: u32,
+    pub cy: u32,
+    pub style: u32,
+    pub lParam: u32,
+}
+unsafe impl memory::Pod for MDICREATESTRUCTA {}
+
+/// Called by CreateWindowEx when creating an MDICLIENT, with the CLIENTCREATESTRUCT lpParam.
+pub fn create_mdi_client(machine: &mut Machine, hwnd: HWND, lpParam: u32) {
+    let create = machine.mem().get_pod::<CLIENTCREATESTRUCT>(lpParam);
+    machine.state.user32.mdi_clients.insert(
+        hwnd,
+        MDIClient {
+            children: Vec::new(),
+            active: HWND::null(),
+            frame_menu: 0,
+            window_menu: create.hWindowMenu,
+            first_child_id: create.idFirstChild,
+        },
+    );
+}
+
+fn send(hwnd: HWND, message: WM, wParam: u32, lParam: u32) -> MSG {
+    MSG {
+        hwnd,
+        message: message as u32,
+        wParam,
+        lParam,
+        time: 0,
+        pt_x: 0,
+        pt_y: 0,
+    }
+}
+
+/// Make `child` the active child of `client`, notifying both it and the previously active child.
+async fn mdi_activate(machine: &mut Machine, client: HWND, child: HWND) {
+    let Some(mdi) = machine.state.user32.mdi_clients.get_mut(&client) else {
+        return;
+    };
+    let prev = mdi.active;
+    if prev == child || !mdi.children.contains(&child) {
+        return;
+    }
+    mdi.active = child;
+    // Both windows receive the same message, naming the deactivated and activated windows.
+    let msg = |hwnd| send(hwnd, WM::MDIACTIVATE, prev.to_raw(), child.to_raw());
+    if !prev.is_null() {
+        dispatch_message(machine, &msg(prev)).await;
+    }
+    dispatch_message(machine, &msg(child)).await;
+    SetFocus(machine, child).await;
+}
+
+async fn mdi_create(machine: &mut Machine, client: HWND, create: MDICREATESTRUCTA) -> HWND {
+    let mem = machine.mem();
+    let class = if create.szClass <= 0xFFFF {
+        None
+    } else {
+        let class = mem.strz(create.szClass, u32::MAX);
+        Some(expect_ascii(class.unwrap_or_else(|err| err.raise())).to_string())
+    };
+    let title = match create.szTitle {
+        0 => String::new(),
+        addr => {
+            let title = mem.strz(addr, u32::MAX);
+            expect_ascii(title.unwrap_or_else(|err| err.raise())).to_string()
+        }
+    };
+    let style = WindowStyle::from_bits_truncate(create.style)
+        | WindowStyle::CHILD
+        | WindowStyle::VISIBLE
+        | WindowStyle::CLIPSIBLINGS;
+    let hwnd = CreateWindowExA(
+        machine,
+        Ok(WindowStyleEx::MDICHILD),
+        match &class {
+            Some(name) => CreateWindowClassName::Name(name),
+            None => CreateWindowClassName::Atom(create.szClass as u16),
+        },
+        Some(&title),
+        Ok(style),
+        create.x,
+        create.y,
+        create.cx,
+        create.cy,
+        client,
+        0,
+        create.hOwner,
+        create.lParam,
+    )
+    .await;
+    let Some(mdi) = machine.state.user32.mdi_clients.get_mut(&client) else {
+        return hwnd;
+    };
+    mdi.children.push(hwnd);
+    mdi_activate(machine, client, hwnd).await;
+    hwnd
+}
+
+async fn mdi_destroy(machine: &mut Machine, client: HWND, child: HWND) {
+    let Some(mdi) = machine.state.user32.mdi_clients.get_mut(&client) else {
+        return;
+    };
+    let Some(pos) = mdi.children.iter().position(|&c| c == child) else {
+        return;
+    };
+    mdi.children.remove(pos);
+    let next = if mdi.active == child {
+        mdi.active = HWND::null();
+        mdi.children.last().copied()
+    } else {
+        None
+    };
+    DestroyWindow(machine, child).await;
+    if let Some(next) = next {
+        mdi_activate(machine, client, next).await;
+    }
+}
+
+/// The child after (or before) `child` in creation order, wrapping around.
+fn mdi_next(mdi: &MDIClient, child: HWND, prev: bool) -> Option<HWND> {
+    let pos = mdi.children.iter().position(|&c| c == child)?;
+    let len = mdi.children.len();
+    let next = if prev { pos + len - 1 } else { pos + 1 };
+    Some(mdi.children[next % len])
+}
+
+/// Window procedure of the MDICLIENT window class.
+pub async fn mdi_client_proc(machine: &mut Machine, msg: &MSG) -> u32 {
+    let client = msg.hwnd;
+    let Ok(wm) = WM::try_from(msg.message) else {
+        return 0;
+    };
+    match wm {
+        WM::MDICREATE => {
+            let create = machine.mem().get_pod::<MDICREATESTRUCTA>(msg.lParam);
+            mdi_create(machine, client, create).await.to_raw()
+        }
+        WM::MDIDESTROY => {
+            mdi_destroy(machine, client, HWND::from_raw(msg.wParam)).await;
+            0
+        }
+        WM::MDIACTIVATE => {
+            mdi_activate(machine, client, HWND::from_raw(msg.wParam)).await;
+            0
+        }
+        WM::MDINEXT => {
+            let Some(mdi) = machine.state.user32.mdi_clients.get(&client) else {
+                return 0;
+            };
+            let child = match HWND::from_raw(msg.wParam) {
+                child if child.is_null() => mdi.active,
+                child => child,
+            };
+            if let Some(next) = mdi_next(mdi, child, msg.lParam != 0) {
+                mdi_activate(machine, client, next).await;
+            }
+            0
+        }
+        WM::MDIGETACTIVE => {
+            let Some(mdi) = machine.state.user32.mdi_clients.get(&client) else {
+                return 0;
+            };
+            let active = mdi.active;
+            if msg.lParam != 0 {
+                machine.mem().put_pod::<u32>(msg.lParam, 0); // not maximized
+            }
+            active.to_raw()
+        }
+        WM::MDISETMENU => {
+            let Some(mdi) = machine.state.user32.mdi_clients.get_mut(&client) else {
+                return 0;
+            };
+            let prev = mdi.frame_menu;
+            if msg.wParam != 0 {
+                mdi.frame_menu = msg.wParam;
+            }
+            if msg.lParam != 0 {
+                mdi.window_menu = msg.lParam;
+            }
+            prev
+        }
+        WM::MDICASCADE | WM::MDITILE | WM::MDIICONARRANGE => {
+            log::warn!("TODO: MDI window arrangement {wm:?}");
+            0
+        }
+        _ => def_window_proc(machine, client, Ok(wm), msg.wParam, msg.lParam).await,
+    }
+}
+
+async fn def_frame_proc(
+    machine: &mut Machine,
+    hWnd: HWND,
+    hWndMDIClient: HWND,
+    msg: Result<WM, u32>,
+    wParam: u32,
+    lParam: u32,
+) -> u32 {
+    match msg {
+        Ok(WM::COMMAND) => {
+            // Commands from the "Window" menu activate the corresponding child.
+            let id = wParam & 0xFFFF;
+            if let Some(mdi) = machine.state.user32.mdi_clients.get(&hWndMDIClient) {
+                if let Some(&child) = mdi
+                    .children
+                    .get(id.wrapping_sub(mdi.first_child_id) as usize)
+                {
+                    mdi_activate(machine, hWndMDIClient, child).await;
+                    return 0;
+                }
+            }
+        }
+        Ok(WM::SETFOCUS) => {
+            if let Some(mdi) = machine.state.user32.mdi_clients.get(&hWndMDIClient) {
+                let target = match mdi.active {
+                    active if active.is_null() => hWndMDIClient,
+                    active => active,
+                };
+                SetFocus(machine, target).await;
+                return 0;
+            }
+        }
+        Ok(WM::SIZE) => {
+            // The client area of the frame is filled by the MDI client.
+            if let Some(client) = machine.state.user32.windows.get_mut(hWndMDIClient) {
+                client.width = lParam & 0xFFFF;
+                client.height = lParam >> 16;
+            }
+        }
+        _ => {}
+    }
+    def_window_proc(machine, hWnd, msg, wParam, lParam).await
+}
+
+#[win32_derive::dllexport]
+pub async fn DefFrameProcA(
+    machine: &mut Machine,
+    hWnd: HWND,
+    hWndMDIClient: HWND,
+    uMsg: Result<WM, u32>,
+    wParam: u32,
+    lParam: u32,
+) -> u32 {
+    def_frame_proc(machine, hWnd, hWndMDIClient, uMsg, wParam, lParam).await
+}
+
+#[win32_derive::dllexport]
+pub async fn DefFrameProcW(
+    machine: &mut Machine,
+    hWnd: HWND,
+    hWndMDIClient: HWND,
+    uMsg: Result<WM, u32>,
+    wParam: u32,
+    lParam: u32,
+) -> u32 {
+    def_frame_proc(machine, hWnd, hWndMDIClient, uMsg, wParam, lParam).await
+}
+
+async fn def_mdi_child_proc(
+    machine: &mut Machine,
+    hWnd: HWND,
+    msg: Result<WM, u32>,
+    wParam: u32,
+    lParam: u32,
+) -> u32 {
+    let client = match machine.state.user32.windows.get(hWnd) {
+        Some(window) => window.parent,
+        None => return 0,
+    };
+    match msg {
+        Ok(WM::CHILDACTIVATE) | Ok(WM::SETFOCUS) => {
+            mdi_activate(machine, client, hWnd).await;
+        }
+        Ok(WM::CLOSE) => {
+            let msg = send(client, WM::MDIDESTROY, hWnd.to_raw(), 0);
+            dispatch_message(machine, &msg).await;
+            return 0;
+        }
+        _ => {}
+    }
+    def_window_proc(machine, hWnd, msg, wParam, lParam).await
+}
+
+#[win32_derive::dllexport]
+pub async fn DefMDIChildProcA(
+    machine: &mut Machine,
+    hWnd: HWND,
+    uMsg: Result<WM, u32>,
+    wParam: u32,
+    lParam: u32,
+) -> u32 {
+    def_mdi_child_proc(machine, hWnd, uMsg, wParam, lParam).await
+}
+
+#[win32_derive::dllexport]
+pub async fn DefMDIChildProcW(
+    machine: &mut Machine,
+    hWnd: HWND,
+    uMsg: Result<WM, u32>,
+    wParam: u32,
+    lParam: u32,
+) -> u32 {
+    def_mdi_child_proc(machine, hWnd, uMsg, wParam, lParam).await
+}
+
+#[win32_derive::dllexport]
+pub async fn CreateMDIWindowA(
+    machine: &mut Machine,
+    lpClassName: u32,
+    lpWindowName: u32,
+    dwStyle: u32,
+    X: u32,
+    Y: u32,
+    nWidth: u32,
+    nHeight: u32,
+    hWndParent: HWND,
+    hInstance: u32,
+    lParam: u32,
+) -> HWND {
+    let create = MDICREATESTRUCTA {
+        szClass: lpClassName,
+        szTitle: lpWindowName,
+        hOwner: hInstance,
+        x: X,
+        y: Y,
+        cx: nWidth,
+        cy: nHeight,
+        style: dwStyle,
+        lParam,
+    };
+    mdi_create(machine, hWndParent, create).await
+}
+
+#[win32_derive::dllexport]
+pub fn TranslateMDISysAccel(_machine: &mut Machine, hWndClient: HWND, lpMsg: Option<&MSG>) -> bool {
+    false // not translated
+}
@@ -41,7 +41,12 @@ pub fn LoadMenuA(_machine: &mut Machine, hInstance: u32, lpMenuName: u32) -> HME
 }
 
 #[win32_derive::dllexport]
-pub fn SetMenu(_machine: &mut Machine, hWnd: HWND, hMenu: HMENU) -> bool {
+pub fn SetMenu(machine: &mut Machine, hWnd: HWND, hMenu: HMENU) -> bool {
+    // TODO: menus aren't implemented, but whether there is one affects the window frame.
+    let Some(window) = machine.state.user32.windows.get_mut(hWnd) else {
+        return false;
+    };
+    window.menu = hMenu != 0;
     true // success
 }
 
@@ -1,15 +1,18 @@
-use std::ops::RangeInclusive;
+use std::{collections::VecDeque, ops::RangeInclusive};
 
-use super::{Timers, Window, WindowType};
+use super::{
+    client_origin, get_message_hooks, host_focus_changed, mdi_client_proc, BuiltinClass, Timers,
+    Window, WindowType,
+};
 use crate::{
     host,
-    winapi::{handle::Handles, types::*},
-    Host, Machine, MouseButton,
+    winapi::{handle::Handles, kernel32, types::*},
+    Machine, MouseButton,
 };
 use bitflags::bitflags;
 
 #[repr(C)]
-#[derive(Clone)]
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub struct MSG {
     pub hwnd: HWND,
     pub message: u32,
@@ -48,10 +51,21 @@ pub enum WM {
     MOVE = 0x0003,
     SIZE = 0x0005,
     ACTIVATE = 0x0006,
+    SETFOCUS = 0x0007,
+    KILLFOCUS = 0x0008,
     PAINT = 0x000F,
+    SETTEXT = 0x000C,
+    GETTEXT = 0x000D,
+    GETTEXTLENGTH = 0x000E,
+    CLOSE = 0x0010,
+    ERASEBKGND = 0x0014,
     QUIT = 0x0012,
     ACTIVATEAPP = 0x001C,
+    CHILDACTIVATE = 0x0022,
     WINDOWPOSCHANGED = 0x0047,
+    DISPLAYCHANGE = 0x007E,
+    COMMAND = 0x0111,
+    SYSCOMMAND = 0x0112,
     TIMER = 0x0113,
     MOUSEMOVE = 0x0200,
     LBUTTONDOWN = 0x0201,
@@ -63,10 +77,21 @@ pub enum WM {
     MBUTTONDOWN = 0x0207,
     MBUTTONUP = 0x0208,
     MBUTTONDBLCLK = 0x0209,
+    MDICREATE = 0x0220,
+    MDIDESTROY = 0x0221,
+    MDIACTIVATE = 0x0222,
+    MDIRESTORE = 0x0223,
+    MDINEXT = 0x0224,
+    MDIMAXIMIZE = 0x0225,
+    MDITILE = 0x0226,
+    MDICASCADE = 0x0227,
+    MDIICONARRANGE = 0x0228,
+    MDIGETACTIVE = 0x0229,
+    MDISETMENU = 0x0230,
     USER = 0x0400,
 }
 
-fn msg_from_message(message: host::Message) -> MSG {
+fn msg_from_message(windows: &Handles<HWND, Window>, message: host::Message) -> MSG {
     let mut msg = MSG {
         hwnd: HWND::from_raw(message.hwnd),
         message: WM::QUIT as u32, // will be overwritten
@@ -81,6 +106,7 @@ fn msg_from_message(message: host::Message) -> MSG {
         host::MessageDetail::Quit => {
             msg.message = WM::QUIT as u32;
         }
+        host::MessageDetail::Focus(_) => unreachable!("handled by pump_input"),
         host::MessageDetail::Mouse(mouse) => {
             msg.message = match (mouse.button, mouse.down) {
                 (MouseButton::None, _) => WM::MOUSEMOVE,
@@ -93,8 +119,13 @@ fn msg_from_message(message: host::Message) -> MSG {
             } as u32;
             msg.wParam = 0; // TODO:  modifiers
             msg.lParam = (mouse.y << 16) | mouse.x;
-            msg.pt_x = mouse.x;
-            msg.pt_y = mouse.y;
+            // Host mouse coordinates are relative to the client area, while pt is in screen coordinates.
+            let (x, y) = match windows.get(msg.hwnd) {
+                Some(window) => client_origin(windows, window),
+                None => (0, 0),
+            };
+            msg.pt_x = (mouse.x as i32 + x) as u32;
+            msg.pt_y = (mouse.y as i32 + y) as u32;
         }
     }
 
@@ -102,43 +133,91 @@ fn msg_from_message(message: host::Message) -> MSG {
 }
 
 /// A Windows message queue.
-/// At a high level just a queue of MSG, but there are particulars around painting and timers.
+/// At a high level just a queue of MSG, but there are particulars around input, painting and timers.
 /// https://learn.microsoft.com/en-us/windows/win32/winmsg/about-messages-and-message-queues
 /// The basic approach is that there are a few get_* methods that each can either peek or remove
 /// the requested message.  Internally, paint/timers are never actually inserted into a queue
 /// but the caller doesn't need to be aware.
 ///
+/// Messages are retrieved in priority order:
+/// 1. posted messages (PostMessage);
+/// 2. input messages from the host;
+/// 3. WM_QUIT, once the above are drained;
+/// 4. WM_TIMER;
+/// 5. WM_PAINT, synthesized from window update regions.
+/// Timers are checked ahead of paint so a window that is continually invalidated
+/// doesn't starve its timers.
+///
 /// TODO: should be per-thread.
-/// TODO: this generally doesn't support multiple HWNDs either,
-/// and will need to be revisited to make that work.
-#[derive(Default)]
+#[derive(Default, serde::Serialize, serde::Deserialize)]
 pub struct MessageQueue {
-    msgs: std::collections::VecDeque<MSG>,
+    /// Messages from PostMessage and friends.
+    posted: VecDeque<MSG>,
+    /// Input messages received from the host but not yet retrieved.
+    input: VecDeque<MSG>,
+    /// Set by PostQuitMessage, holding the exit code.
+    quit: Option<u32>,
+}
+
+/// Find and optionally remove the first message in a queue matching the filter criteria.
+fn take_matching(
+    queue: &mut VecDeque<MSG>,
+    hwnd: HWND,
+    filter: &RangeInclusive<u32>,
+    remove: bool,
+) -> Option<MSG> {
+    let pos = queue.iter().position(|msg| {
+        if !hwnd.is_null() && (!msg.hwnd.is_null() && msg.hwnd != hwnd) {
+            return false;
+        }
+        filter.contains(&msg.message)
+    })?;
+    if remove {
+        queue.remove(pos)
+    } else {
+        Some(queue[pos].clone())
+    }
 }
 
 impl MessageQueue {
-    fn push(&mut self, msg: MSG) {
-        self.msgs.push_back(msg);
+    fn post(&mut self, msg: MSG) {
+        self.posted.push_back(msg);
     }
 
-    /// Get any queued message matching the filter criteria.
-    fn get_queued(
+    /// Get any posted message matching the filter criteria.
+    fn get_posted(
         &mut self,
         hwnd: HWND,
         filter: &RangeInclusive<u32>,
         remove: bool,
     ) -> Option<MSG> {
-        let pos = self.msgs.iter().position(|msg| {
-            if !hwnd.is_null() && (!msg.hwnd.is_null() && msg.hwnd != hwnd) {
-                return false;
-            }
-            filter.contains(&msg.message)
-        })?;
-        if remove {
-            self.msgs.remove(pos)
-        } else {
-            Some(self.msgs[pos].clone())
+        take_matching(&mut self.posted, hwnd, filter, remove)
+    }
+
+    /// Get any input message matching the filter criteria.
+    fn get_input(&mut self, hwnd: HWND, filter: &RangeInclusive<u32>, remove: bool) -> Option<MSG> {
+        take_matching(&mut self.input, hwnd, filter, remove)
+    }
+
+    /// Get a WM_QUIT, if PostQuitMessage was called.
+    fn get_quit(&mut self, filter: &RangeInclusive<u32>, remove: bool) -> Option<MSG> {
+        if !filter.contains(&(WM::QUIT as u32)) {
+            return None;
         }
+        let code = if remove {
+            self.quit.take()?
+        } else {
+            self.quit?
+        };
+        Some(MSG {
+            hwnd: HWND::null(),
+            message: WM::QUIT as u32,
+            wParam: code,
+            lParam: 0,
+            time: 0,
+            pt_x: 0,
+            pt_y: 0,
+        })
     }
 
     /// Get any pending WM_PAINT matching the filter criteria.
@@ -159,10 +238,10 @@ impl MessageQueue {
                 })?
                 .hwnd
         } else {
-            if !windows.get(hwnd).unwrap().expect_toplevel().dirty.is_some() {
-                return None;
+            match &windows.get(hwnd)?.typ {
+                WindowType::TopLevel(w) if w.dirty.is_some() => hwnd,
+                _ => return None,
             }
-            hwnd
         };
         Some(MSG {
             hwnd,
@@ -179,7 +258,7 @@ impl MessageQueue {
     /// Err(wait) returns indicate how long to block until the next timer.
     fn get_timer(
         &mut self,
-        host: &dyn Host,
+        now: u32,
         timers: &mut Timers,
         remove: bool,
     ) -> Result<MSG, Option<u32>> {
@@ -188,13 +267,9 @@ impl MessageQueue {
             return Err(None);
         }
 
-        let now = host.ticks();
         // TODO: support filtering by HWND.
         if let Some(timer) = timers.find_next(HWND::null(), now) {
-            if !remove {
-                todo!("how does peeking timers work");
-            }
-            return Ok(timer.generate_wm_timer(now));
+            return Ok(timer.generate_wm_timer(now, remove));
         }
 
         let soonest = timers.soonest();
@@ -202,6 +277,23 @@ impl MessageQueue {
     }
 }
 
+/// Move any pending host input into the input queue.
+fn pump_input(machine: &mut Machine) {
+    while let Some(msg) = machine.host.get_message() {
+        let user32 = &mut machine.state.user32;
+        match msg.detail {
+            host::MessageDetail::Focus(focused) => {
+                let msgs = host_focus_changed(user32, HWND::from_raw(msg.hwnd), focused);
+                user32.messages.input.extend(msgs);
+            }
+            _ => {
+                let msg = msg_from_message(&user32.windows, msg);
+                user32.messages.input.push_back(msg);
+            }
+        }
+    }
+}
+
 /// Retrieves the next available message without blocking.
 /// Returns Err(wait) if we need to wait.
 fn poll_message(
@@ -211,45 +303,37 @@ fn poll_message(
     remove: bool,
 ) -> Result<MSG, Option<u32>> {
     let filter = filter.unwrap_or(0..=0xFFFF_FFFF);
-    if let Some(msg) = machine
-        .state
-        .user32
-        .messages
-        .get_queued(hwnd, &filter, remove)
-    {
+    let messages = &mut machine.state.user32.messages;
+    if let Some(msg) = messages.get_posted(hwnd, &filter, remove) {
         return Ok(msg);
     }
 
-    // TODO: obey filter here.
-    if let Some(msg) = machine.host.get_message() {
-        let msg = msg_from_message(msg);
-        if !remove {
-            machine.state.user32.messages.push(msg.clone());
-        }
+    pump_input(machine);
+    let now = machine.ticks();
+    let messages = &mut machine.state.user32.messages;
+    if let Some(msg) = messages.get_input(hwnd, &filter, remove) {
         return Ok(msg);
     }
 
+    if let Some(msg) = messages.get_quit(&filter, remove) {
+        return Ok(msg);
+    }
+
+    let mut wait = None;
+    if filter.contains(&(WM::TIMER as u32)) {
+        match messages.get_timer(now, &mut machine.state.user32.timers, remove) {
+            Ok(msg) => return Ok(msg),
+            Err(next) => wait = next,
+        }
+    }
+
     if filter.contains(&(WM::PAINT as u32)) {
-        if let Some(msg) =
-            machine
-                .state
-                .user32
-                .messages
-                .get_paint(hwnd, &machine.state.user32.windows, remove)
-        {
+        if let Some(msg) = messages.get_paint(hwnd, &machine.state.user32.windows, remove) {
             return Ok(msg);
         }
     }
 
-    if filter.contains(&(WM::TIMER as u32)) {
-        return machine.state.user32.messages.get_timer(
-            &*machine.host,
-            &mut machine.state.user32.timers,
-            remove,
-        );
-    } else {
-        Err(None) // block
-    }
+    Err(wait) // block
 }
 
 #[cfg(feature = "x86-emu")]
@@ -259,7 +343,7 @@ async fn await_message(machine: &mut Machine, wait: Option<u32>) {
 
 #[cfg(not(feature = "x86-emu"))]
 async fn await_message(machine: &mut Machine, wait: Option<u32>) {
-    machine.host.block(wait);
+    machine.block_host(wait);
 }
 
 bitflags! {
@@ -344,6 +428,7 @@ async fn get_message(
         }
     }
 
+    let msg = get_message_hooks(machine, msg, true).await;
     let quit = msg.message == WM::QUIT as u32;
     *lpMsg.unwrap() = msg;
     if quit {
@@ -393,74 +478,77 @@ pub fn TranslateMessage(_machine: &mut Machine, lpMsg: Option<&MSG>) -> bool {
     false // no message translated
 }
 
+/// Synchronously deliver a message to the wndproc of its target window,
+/// returning the wndproc's LRESULT.
+/// This is used both for DispatchMessage of queued messages and for SendMessage,
+/// and it may be re-entered when a wndproc itself sends a message.
 pub async fn dispatch_message(machine: &mut Machine, msg: &MSG) -> u32 {
     assert!(!msg.hwnd.is_null());
-    let wndproc = machine
-        .state
-        .user32
-        .windows
-        .get(msg.hwnd)
-        .unwrap()
-        .wndclass
-        .wndproc;
+    let (wndproc, builtin) = match machine.state.user32.windows.get(msg.hwnd) {
+        Some(window) => (window.wndclass.wndproc, window.wndclass.builtin),
+        None => {
+            log::warn!(
+                "dispatch to unknown window {:x}, dropping message",
+                msg.hwnd
+            );
+            return 0;
+        }
+    };
+    if let Some(builtin) = builtin {
+        // Boxed because builtin window procedures may themselves dispatch messages.
+        return match builtin {
+            BuiltinClass::MDIClient => Box::pin(mdi_client_proc(machine, msg)).await,
+        };
+    }
     if wndproc == 0 {
         log::error!("window has no wndproc, skipping message dispatch");
         return 0;
     }
     // TODO: SetWindowLong can change the wndproc.
     machine
-        .call_x86(
-            wndproc,
-            vec![
-                msg.hwnd.to_raw(),
-                msg.message as u32,
-                msg.wParam,
-                msg.lParam,
-            ],
-        )
-        .await;
-    // TODO: copy eax to return value
-    0
+        .call_guest(wndproc, (msg.hwnd, msg.message, msg.wParam, msg.lParam))
+        .await
 }
 
-#[win32_derive::dllexport]
-pub async fn DispatchMessageA(machine: &mut Machine, lpMsg: Option<&MSG>) -> u32 {
-    let msg = lpMsg.unwrap();
+/// DispatchMessage, which unlike SendMessage also runs the TIMERPROC of a WM_TIMER
+/// in place of the window procedure.
+async fn dispatch_queued_message(machine: &mut Machine, msg: &MSG) -> u32 {
+    if msg.message == WM::TIMER as u32 && msg.lParam != 0 {
+        let now = machine.ticks();
+        return machine
+            .call_guest(msg.lParam, (msg.hwnd, msg.message, msg.wParam, now))
+            .await;
+    }
     if msg.hwnd.is_null() {
         // No associated hwnd.
         return 0;
     }
-    dispatch_message(machine, msg).await;
-    0
+    dispatch_message(machine, msg).await
+}
+
+#[win32_derive::dllexport]
+pub async fn DispatchMessageA(machine: &mut Machine, lpMsg: Option<&MSG>) -> u32 {
+    dispatch_queued_message(machine, lpMsg.unwrap()).await
 }
 
 #[win32_derive::dllexport]
 pub async fn DispatchMessageW(machine: &mut Machine, lpMsg: Option<&MSG>) -> u32 {
-    let msg = lpMsg.unwrap();
-    if msg.hwnd.is_null() {
-        // No associated hwnd.
-        return 0;
-    }
-    dispatch_message(machine, msg).await;
-    0
+    dispatch_queued_message(machine, lpMsg.unwrap()).await
 }
 
 #[win32_derive::dllexport]
 pub fn PostQuitMessage(machine: &mut Machine, nExitCode: i32) {
-    machine.state.user32.messages.push(MSG {
-        hwnd: HWND::null(),
-        message: WM::QUIT as u32,
-        wParam: 0,
-        lParam: 0,
-        time: 0,
-        pt_x: 0,
-        pt_y: 0,
-    });
+    machine.state.user32.messages.quit = Some(nExitCode as u32);
 }
 
 #[win32_derive::dllexport]
 pub fn PostMessageW(machine: &mut Machine, hWnd: HWND, Msg: u32, wParam: u32, lParam: u32) -> bool {
-    machine.state.user32.messages.push(MSG {
+    if Msg == WM::PAINT as u32 {
+        // WM_PAINT is generated from the update region, never queued.
+        log::warn!("ignoring posted WM_PAINT");
+        return true;
+    }
+    machine.state.user32.messages.post(MSG {
         hwnd: hWnd,
         message: Msg,
         wParam,
@@ -482,26 +570,66 @@ pub fn TranslateAcceleratorW(
     0 // success
 }
 
-#[win32_derive::dllexport]
-pub async fn SendMessageA(
+pub const HWND_BROADCAST: HWND = HWND::from_raw(0xFFFF);
+
+/// Deliver a message synchronously, bypassing the message queue.
+pub async fn send_message(
     machine: &mut Machine,
-    hWnd: HWND,
-    Msg: Result<WM, u32>,
+    hwnd: HWND,
+    message: u32,
     wParam: u32,
     lParam: u32,
 ) -> u32 {
-    let msg = MSG {
-        hwnd: hWnd,
-        message: Msg.unwrap() as u32,
+    if hwnd.is_null() {
+        log::warn!("SendMessage to null hwnd");
+        return 0;
+    }
+    let mut msg = MSG {
+        hwnd,
+        message,
         wParam,
         lParam,
         time: 0,
         pt_x: 0,
         pt_y: 0,
     };
+    if hwnd == HWND_BROADCAST {
+        let hwnds = machine
+            .state
+            .user32
+            .windows
+            .iter()
+            .filter(|w| matches!(w.typ, WindowType::TopLevel(_)))
+            .map(|w| w.hwnd)
+            .collect::<Vec<_>>();
+        for hwnd in hwnds {
+            msg.hwnd = hwnd;
+            dispatch_message(machine, &msg).await;
+        }
+        return 0;
+    }
     dispatch_message(machine, &msg).await
 }
 
+/// Messages are passed as Result<WM, u32> for nicer traces, but any value is legal.
+fn wm_to_raw(msg: Result<WM, u32>) -> u32 {
+    match msg {
+        Ok(wm) => wm as u32,
+        Err(raw) => raw,
+    }
+}
+
+#[win32_derive::dllexport]
+pub async fn SendMessageA(
+    machine: &mut Machine,
+    hWnd: HWND,
+    Msg: Result<WM, u32>,
+    wParam: u32,
+    lParam: u32,
+) -> u32 {
+    send_message(machine, hWnd, wm_to_raw(Msg), wParam, lParam).await
+}
+
 #[win32_derive::dllexport]
 pub async fn SendMessageW(
     machine: &mut Machine,
@@ -510,18 +638,110 @@ pub async fn SendMessageW(
     wParam: u32,
     lParam: u32,
 ) -> u32 {
-    todo!()
+    // TODO: messages carrying strings need A<->W conversion.
+    send_message(machine, hWnd, wm_to_raw(Msg), wParam, lParam).await
+}
+
+/// MsgWaitForMultipleObjects message check: is there a message in the queue?
+fn has_message(machine: &mut Machine) -> Result<(), Option<u32>> {
+    poll_message(machine, HWND::null(), None, false).map(|_| ())
 }
 
 #[win32_derive::dllexport]
-pub fn MsgWaitForMultipleObjects(
-    _machine: &mut Machine,
+pub async fn MsgWaitForMultipleObjects(
+    machine: &mut Machine,
     nCount: u32,
     pHandles: u32,
     fWaitAll: bool,
     dwMilliseconds: u32,
     dwWakeMask: u32,
 ) -> u32 {
-    // TODO: implement me
-    258 // WAIT_TIMEOUT
+    // TODO: dwWakeMask is ignored; any queued message ends the wait.
+    // One of the MAXIMUM_WAIT_OBJECTS slots goes to the message queue.
+    let counts = 0..=kernel32::MAXIMUM_WAIT_OBJECTS - 1;
+    let Some(handles) = kernel32::wait_handles(machine, nCount, pHandles, counts) else {
+        return kernel32::WAIT_FAILED;
+    };
+    kernel32::wait_for_objects(
+        machine,
+        &handles,
+        fWaitAll,
+        dwMilliseconds,
+        false,
+        Some(has_message),
+    )
+    .await
+}
+
+const MWMO_WAITALL: u32 = 0x1;
+const MWMO_ALERTABLE: u32 = 0x2;
+
+#[win32_derive::dllexport]
+pub async fn MsgWaitForMultipleObjectsEx(
+    machine: &mut Machine,
+    nCount: u32,
+    pHandles: u32,
+    dwMilliseconds: u32,
+    dwWakeMask: u32,
+    dwFlags: u32,
+) -> u32 {
+    // TODO: dwWakeMask and MWMO_INPUTAVAILABLE are ignored; any queued message ends the wait.
+    // One of the MAXIMUM_WAIT_OBJECTS slots goes to the message queue.
+    let counts = 0..=kernel32::MAXIMUM_WAIT_OBJECTS - 1;
+    let Some(handles) = kernel32::wait_handles(machine, nCount, pHandles, counts) else {
+        return kernel32::WAIT_FAILED;
+    };
+    kernel32::wait_for_objects(
+        machine,
+        &handles,
+        dwFlags & MWMO_WAITALL != 0,
+        dwMilliseconds,
+        dwFlags & MWMO_ALERTABLE != 0,
+        Some(has_message),
+    )
+    .await
+}
+
+#[cfg(all(test, feature = "x86-emu"))]
+mod tests {
+    use super::*;
+    use crate::test_guest::{builtin, new_machine, run, Code, Log};
+    use memory::Extensions;
+
+    #[test]
+    fn dispatch_runs_timer_proc() {
+        let (mut machine, _host) = new_machine();
+        let set_timer = builtin(&mut machine, "user32.dll", "SetTimer");
+        let get_message = builtin(&mut machine, "user32.dll", "GetMessageA");
+        let dispatch = builtin(&mut machine, "user32.dll", "DispatchMessageA");
+        let log = Log::alloc(&mut machine, 0x1000);
+        let mut code = Code::alloc(&mut machine, 0x200);
+        let timer_proc = code.base + 0x100;
+        let msg = code.base + 0x180;
+        code.push(timer_proc)
+            .push(20)
+            .push(7)
+            .push(0)
+            .call(set_timer)
+            .push(0)
+            .push(0)
+            .push(0)
+            .push(msg)
+            .call(get_message)
+            .push(msg)
+            .call(dispatch)
+            .exit_with(&mut machine, 0)
+            .at(0x100)
+            .recorder(&log, 4, 0x55);
+        code.start(&mut machine);
+        // DispatchMessage returns what the timer proc did.
+        assert_eq!(run(&mut machine), 0x55);
+
+        let msg = machine.mem().get_pod::<MSG>(msg);
+        let args = log.read(&machine);
+        assert_eq!(args[..3], [0, WM::TIMER as u32, msg.wParam]);
+        // The proc gets the current time, after the timer's period.
+        assert!(args[3] >= 20);
+        assert_eq!(args.len(), 4);
+    }
 }
@@ -1,10 +1,10 @@
 use crate::{
     str16::Str16,
-    winapi::{stack_args::VarArgs, types::HWND},
+    winapi::{printf, stack_args::VarArgs, types::HWND},
     Machine,
 };
-use memory::{Extensions, ExtensionsMut};
-use std::io::{Cursor, Write};
+use memory::ExtensionsMut;
+use std::ffi::CStr;
 
 pub type HINSTANCE = u32;
 
@@ -15,15 +15,35 @@ pub enum SystemMetric {
     CYCAPTION = 4,
     CXBORDER = 5,
     CYBORDER = 6,
+    CXDLGFRAME = 7,
+    CYDLGFRAME = 8,
     CYMENU = 15,
     CXFRAME = 32,
     CYFRAME = 33,
+    CXEDGE = 45,
+    CYEDGE = 46,
+    CYSMCAPTION = 51,
     CXVIRTUALSCREEN = 78,
     CYVIRTUALSCREEN = 79,
 }
 
+/// Sizes of the parts of a window's non-client area, in pixels.
+/// These are what GetSystemMetrics reports and what window frame sizes are computed from.
+pub mod metrics {
+    pub const CAPTION: i32 = 19;
+    /// Caption of a WS_EX_TOOLWINDOW window.
+    pub const SMCAPTION: i32 = 15;
+    pub const MENU: i32 = 19;
+    pub const BORDER: i32 = 1;
+    pub const DLGFRAME: i32 = 3;
+    /// Resizable (WS_THICKFRAME) border.
+    pub const FRAME: i32 = 4;
+    /// 3d edge, as drawn for WS_EX_CLIENTEDGE.
+    pub const EDGE: i32 = 2;
+}
+
 #[win32_derive::dllexport]
-pub fn GetSystemMetrics(_machine: &mut Machine, nIndex: Result<SystemMetric, u32>) -> u32 {
+pub fn GetSystemMetrics(machine: &mut Machine, nIndex: Result<SystemMetric, u32>) -> u32 {
     let metric = match nIndex {
         Ok(metric) => metric,
         Err(val) => {
@@ -31,17 +51,19 @@ pub fn GetSystemMetrics(_machine: &mut Machine, nIndex: Result<SystemMetric, u32
             return 0;
         }
     };
+    let display = &machine.state.user32.display;
     match metric {
-        SystemMetric::CXSCREEN => 640,
-        SystemMetric::CYSCREEN => 480,
-        SystemMetric::CYCAPTION => 19,
-        SystemMetric::CXBORDER => 1,
-        SystemMetric::CYBORDER => 1,
-        SystemMetric::CYMENU => 19,
-        SystemMetric::CXFRAME => 4,
-        SystemMetric::CYFRAME => 4,
-        SystemMetric::CXVIRTUALSCREEN => 640,
-        SystemMetric::CYVIRTUALSCREEN => 480,
+        SystemMetric::CXSCREEN => display.width,
+        SystemMetric::CYSCREEN => display.height,
+        SystemMetric::CYCAPTION => metrics::CAPTION as u32,
+        SystemMetric::CXBORDER | SystemMetric::CYBORDER => metrics::BORDER as u32,
+        SystemMetric::CXDLGFRAME | SystemMetric::CYDLGFRAME => metrics::DLGFRAME as u32,
+        SystemMetric::CYMENU => metrics::MENU as u32,
+        SystemMetric::CXFRAME | SystemMetric::CYFRAME => metrics::FRAME as u32,
+        SystemMetric::CXEDGE | SystemMetric::CYEDGE => metrics::EDGE as u32,
+        SystemMetric::CYSMCAPTION => metrics::SMCAPTION as u32,
+        SystemMetric::CXVIRTUALSCREEN => display.width,
+        SystemMetric::CYVIRTUALSCREEN => display.height,
     }
 }
 
@@ -50,81 +72,50 @@ pub fn GetSysColor(_machine: &mut Machine, nIndex: i32) -> u32 {
     todo!();
 }
 
-#[win32_derive::dllexport(cdecl)]
-pub fn wsprintfA(machine: &mut Machine, buf: u32, fmt: Option<&str>, mut args: VarArgs) -> u32 {
-    const BUF_LEN: u32 = 1024;
-    let mem = machine.mem();
-    let buf = mem.sub32_mut(buf, BUF_LEN);
-    let mut out = Cursor::new(buf);
-
-    fn read_number(c: u8) -> usize {
-        // TODO: multiple digits, error handling, etc.
-        assert!(c >= b'0' && c <= b'9');
-        (c - b'0') as usize
+/// Format into buf, which wsprintf assumes holds 1024 characters.
+fn wsprintf<T: printf::Unit>(
+    machine: &mut Machine,
+    buf: u32,
+    fmt: &[T],
+    args: &mut VarArgs,
+) -> u32 {
+    const BUF_LEN: usize = 1024;
+    let cp = machine.state.kernel32.code_page;
+    let dialect = printf::Dialect::Wsprintf;
+    let mut out = printf::format(machine.mem(), cp, dialect, fmt, args, BUF_LEN - 1).units;
+    let len = out.len() as u32;
+    out.push(T::from_ascii(0));
+    let bytes = printf::as_bytes(&out);
+    match machine.mem().try_sub32_mut(buf, bytes.len() as u32) {
+        Ok(dst) => dst.copy_from_slice(bytes),
+        Err(err) => {
+            log::warn!("wsprintf: {err}");
+            return 0;
+        }
     }
+    len
+}
 
-    let mut i = fmt.unwrap().bytes();
-    while let Some(c) = i.next() {
-        if c == b'%' {
-            let mut c = i.next().unwrap();
-            let mut width = 0;
-            if c >= b'0' && c <= b'9' {
-                width = read_number(c);
-                c = i.next().unwrap();
-            }
-            let mut precision = 0;
-            if c == b'.' {
-                c = i.next().unwrap();
-                precision = read_number(c);
-                c = i.next().unwrap();
-            }
+#[win32_derive::dllexport(cdecl)]
+pub fn wsprintfA(machine: &mut Machine, buf: u32, fmt: Option<&CStr>, mut args: VarArgs) -> u32 {
+    wsprintf(machine, buf, fmt.unwrap().to_bytes(), &mut args)
+}
 
-            let mut long = false;
-            if c == b'l' {
-                long = true;
-                c = i.next().unwrap();
-            }
-            _ = long; // currently ignored
+#[win32_derive::dllexport(cdecl)]
+pub fn wsprintfW(machine: &mut Machine, buf: u32, fmt: Option<&Str16>, mut args: VarArgs) -> u32 {
+    wsprintf(machine, buf, fmt.unwrap().buf(), &mut args)
+}
 
-            match c {
-                b'u' => write!(
-                    out,
-                    "{:width$.precision$}",
-                    args.pop::<u32>(mem),
-                    width = width,
-                    precision = precision
-                )
-                .unwrap(),
-                b'd' => write!(
-                    out,
-                    "{:width$.precision$}",
-                    args.pop::<i32>(mem),
-                    width = width,
-                    precision = precision
-                )
-                .unwrap(),
-                b's' => {
-                    let addr = args.pop::<u32>(mem);
-                    let str = mem.slicez(addr);
-                    write!(out, "{}", std::str::from_utf8(str).unwrap()).unwrap();
-                }
-                _ => todo!("format string character {:?}", c as char),
-            }
-        } else {
-            out.write(&[c]).unwrap();
-        }
-    }
-    out.write(&[0]).unwrap();
-    // let len = out.position() as usize;
-    // let buf = &out.into_inner()[..len];
-    // log::info!("=> {}", std::str::from_utf8(buf).unwrap());
-    // len as u32 - 1
-    out.position() as u32 - 1
+#[win32_derive::dllexport]
+pub fn wvsprintfA(machine: &mut Machine, buf: u32, fmt: Option<&CStr>, arglist: u32) -> u32 {
+    let mut args = VarArgs::from_va_list(arglist);
+    wsprintf(machine, buf, fmt.unwrap().to_bytes(), &mut args)
 }
 
-#[win32_derive::dllexport(cdecl)]
-pub fn wsprintfW(machine: &mut Machine, buf: u32, fmt: Option<&Str16>, args: VarArgs) -> u32 {
-    todo!()
+#[win32_derive::dllexport]
+pub fn wvsprintfW(machine: &mut Machine, buf: u32, fmt: Option<&Str16>, arglist: u32) -> u32 {
+    let mut args = VarArgs::from_va_list(arglist);
+    wsprintf(machine, buf, fmt.unwrap().buf(), &mut args)
 }
 
 #[win32_derive::dllexport]
@@ -133,8 +124,19 @@ pub fn GetKeyState(_machine: &mut Machine, nVirtKey: u32) -> u32 {
 }
 
 #[win32_derive::dllexport]
-pub fn IsIconic(_machine: &mut Machine, hwnd: HWND) -> bool {
-    false
+pub fn IsIconic(machine: &mut Machine, hwnd: HWND) -> bool {
+    match machine.state.user32.windows.get(hwnd) {
+        Some(window) => window.show_state == super::ShowState::Minimized,
+        None => false,
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn IsZoomed(machine: &mut Machine, hwnd: HWND) -> bool {
+    match machine.state.user32.windows.get(hwnd) {
+        Some(window) => window.show_state == super::ShowState::Maximized,
+        None => false,
+    }
 }
 
 #[win32_derive::dllexport]
@@ -1,35 +1,60 @@
 #![allow(non_snake_case)]
 
 mod dialog;
+mod display;
+mod focus;
+mod hook;
+mod mdi;
 mod menu;
 mod message;
 mod misc;
 mod paint;
 mod rect;
 mod resource;
+mod string;
 mod timer;
 mod window;
 
 use super::handle::Handles;
 use super::types::HWND;
 
-pub use super::gdi32::HDC;
+pub use super::gdi32::{COLORREF, HDC};
 pub use super::kernel32::ResourceKey;
 pub use dialog::*;
+pub use display::*;
+pub use focus::*;
+pub use hook::*;
+pub use mdi::*;
 pub use menu::*;
 pub use message::*;
 pub use misc::*;
 pub use paint::*;
 pub use rect::*;
 pub use resource::*;
+pub use string::*;
 pub use timer::*;
 pub use window::*;
 
-#[derive(Default)]
+#[derive(Default, serde::Serialize, serde::Deserialize)]
 pub struct State {
+    #[serde(with = "crate::snapshot::shared_vec")]
     wndclasses: Vec<std::rc::Rc<WndClass>>,
     pub user_window_message_count: u32,
     pub windows: Handles<HWND, Window>,
+    /// Top-level windows, most recently activated first.
+    z_order: Vec<HWND>,
+    /// The active top-level window, or null if the application is inactive.
+    active: HWND,
+    /// The window receiving keyboard input.
+    focus: HWND,
+    /// The current display mode, as set by ChangeDisplaySettings.
+    pub display: DisplayMode,
+    /// The mode the monitor is configured for; see set_desktop_mode.
+    desktop: DisplayMode,
+    mdi_clients: std::collections::HashMap<HWND, MDIClient>,
     messages: MessageQueue,
     timers: Timers,
+    hooks: Hooks,
+    /// Message boxes shown so far, which numbers them for the host.
+    message_boxes: u32,
 }
@@ -1,4 +1,4 @@
-use super::{UpdateRegion, WindowType, HBRUSH, HDC};
+use super::{dispatch_message, get_window_dc, UpdateRegion, WindowType, HBRUSH, HDC, MSG, WM};
 use crate::str16::Str16;
 use crate::{
     winapi::{
@@ -80,7 +80,11 @@ pub struct PAINTSTRUCT {
 unsafe impl memory::Pod for PAINTSTRUCT {}
 
 #[win32_derive::dllexport]
-pub fn BeginPaint(machine: &mut Machine, hWnd: HWND, lpPaint: Option<&mut PAINTSTRUCT>) -> HDC {
+pub async fn BeginPaint(
+    machine: &mut Machine,
+    hWnd: HWND,
+    lpPaint: Option<&mut PAINTSTRUCT>,
+) -> HDC {
     let window = machine.state.user32.windows.get_mut(hWnd).unwrap();
     // TODO: take from update region
     let dirty_rect = RECT {
@@ -90,29 +94,42 @@ pub fn BeginPaint(machine: &mut Machine, hWnd: HWND, lpPaint: Option<&mut PAINTS
         bottom: window.height as i32,
     };
 
-    let mut background_drawn = false;
-
     let WindowType::TopLevel(toplevel) = &window.typ else {
         log::warn!("TODO: BeginPaint for child windows");
         return HDC::null();
     };
-    let hdc = machine.state.gdi32.new_window_dc(hWnd);
-    let update = toplevel.dirty.as_ref().unwrap();
-
-    if update.erase_background {
-        if let Some(hbrush) = window.wndclass.background.to_option() {
-            if let gdi32::Object::Brush(brush) = machine.state.gdi32.objects.get(hbrush).unwrap() {
-                if let Some(color) = brush.color {
-                    gdi32::fill_rect(machine, hdc, &dirty_rect, color);
-                    background_drawn = true;
-                }
+    let erase_background = match &toplevel.dirty {
+        Some(update) => update.erase_background,
+        None => false,
+    };
+    let hdc = get_window_dc(machine, hWnd);
+
+    // The erase is done via WM_ERASEBKGND, whose default handling fills with the class brush.
+    // If the wndproc returns 0 the background remains to be erased, as reported via fErase.
+    let mut background_erased = !erase_background;
+    if erase_background {
+        let msg = MSG {
+            hwnd: hWnd,
+            message: WM::ERASEBKGND as u32,
+            wParam: hdc.to_raw(),
+            lParam: 0,
+            time: 0,
+            pt_x: 0,
+            pt_y: 0,
+        };
+        background_erased = dispatch_message(machine, &msg).await != 0;
+    }
+    if let Some(window) = machine.state.user32.windows.get_mut(hWnd) {
+        if let WindowType::TopLevel(toplevel) = &mut window.typ {
+            if let Some(update) = &mut toplevel.dirty {
+                update.erase_background = false;
             }
         }
     }
 
     *lpPaint.unwrap() = PAINTSTRUCT {
         hdc,
-        fErase: (!background_drawn).into(),
+        fErase: (!background_erased).into(),
         rcPaint: dirty_rect,
         fRestore: 0,          // reserved
         fIncUpdate: 0,        // reserved
@@ -133,6 +150,11 @@ pub fn EndPaint(machine: &mut Machine, hWnd: HWND, lpPaint: Option<&PAINTSTRUCT>
             log::warn!("TODO: EndPaint for child windows");
         }
     }
+    if let Some(paint) = lpPaint {
+        if window.dc.map(|dc| dc.to_raw()) != Some(paint.hdc.to_raw()) {
+            machine.state.gdi32.dcs.remove(paint.hdc);
+        }
+    }
     true
 }
 
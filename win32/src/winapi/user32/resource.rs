@@ -4,8 +4,10 @@ use crate::{
     winapi::{
         bitmap::BitmapRGBA32,
         gdi32::{self, HGDIOBJ},
-        kernel32::ResourceKey,
+        kernel32::{out_buf, set_last_error, ResourceKey},
+        stack_args::ArrayWithSizeMut,
         types::*,
+        ERROR,
     },
     Machine,
 };
@@ -156,7 +158,11 @@ pub fn LoadBitmapA(
 }
 
 fn find_string(machine: &Machine, hInstance: HINSTANCE, uID: u32) -> Option<Range<u32>> {
-    // Strings are stored as blocks of 16 consecutive strings.
+    let hInstance = if hInstance == 0 {
+        machine.state.kernel32.image_base
+    } else {
+        hInstance
+    };
     let (resource_id, index) = ((uID >> 4) + 1, uID & 0xF);
 
     let block = crate::winapi::kernel32::find_resource(
@@ -166,22 +172,12 @@ fn find_string(machine: &Machine, hInstance: HINSTANCE, uID: u32) -> Option<Rang
         ResourceKey::Id(pe::RT::STRING as u32),
         ResourceKey::Id(resource_id),
     )?;
-    let block_ofs = block.start;
-    let block = machine.mem().slice(block);
-
-    // Each block is a sequence of two byte length-prefixed strings.
-    // Iterate through them to find the requested index.
-    let mut ofs = 0;
-    for _ in 0..index {
-        let len = block.get_pod::<u16>(ofs) as u32;
-        ofs += (1 + len) * 2;
+    let str = pe::find_string_in_bundle(machine.mem().slice(block.clone()), index)?;
+    if str.is_empty() {
+        // Missing strings within a bundle are stored as empty.
+        return None;
     }
-    let len = block.get_pod::<u16>(ofs) as u32;
-    let start = block_ofs + ofs + 2;
-    Some(Range {
-        start,
-        end: start + len * 2,
-    })
+    Some((block.start + str.start)..(block.start + str.end))
 }
 
 #[win32_derive::dllexport]
@@ -189,19 +185,25 @@ pub fn LoadStringA(
     machine: &mut Machine,
     hInstance: u32,
     uID: u32,
-    lpBuffer: u32,
-    cchBufferMax: u32,
+    lpBuffer: ArrayWithSizeMut<u8>,
 ) -> u32 {
-    let str = match find_string(machine, hInstance, uID) {
-        Some(str) => Str16::from_bytes(machine.mem().slice(str)),
-        None => return 0,
+    let Some(dst) = lpBuffer.to_option() else {
+        return 0;
     };
-    assert!(cchBufferMax != 0); // MSDN claims this is invalid
+    if dst.is_empty() {
+        return 0; // MSDN claims this is invalid
+    }
+    let Some(str) = find_string(machine, hInstance, uID) else {
+        dst[0] = 0;
+        set_last_error(machine, ERROR::RESOURCE_NAME_NOT_FOUND);
+        return 0;
+    };
+    let str = Str16::from_bytes(machine.mem().slice(str));
 
-    let dst = machine.mem().sub32_mut(lpBuffer, cchBufferMax);
-    let copy_len = std::cmp::min(dst.len() as usize - 1, str.len());
-    for i in 0..copy_len {
-        dst[i] = str.buf()[i] as u8;
+    // TODO: convert via the ANSI code page.
+    let copy_len = std::cmp::min(dst.len() - 1, str.len());
+    for (d, &c) in dst.iter_mut().zip(&str.buf()[..copy_len]) {
+        *d = if c < 0x100 { c as u8 } else { b'?' };
     }
     dst[copy_len] = 0;
     copy_len as u32
@@ -216,18 +218,31 @@ pub fn LoadStringW(
     cchBufferMax: u32,
 ) -> u32 {
     let Some(str) = find_string(machine, hInstance, uID) else {
+        if cchBufferMax != 0 {
+            machine.mem().put_pod::<u16>(lpBuffer, 0);
+        }
+        set_last_error(machine, ERROR::RESOURCE_NAME_NOT_FOUND);
         return 0;
     };
+    let len = str.len() as u32 / 2;
     let mem = machine.mem();
     if cchBufferMax == 0 {
+        // Caller gets a read-only pointer to the string in the resource itself.
         mem.put_pod::<u32>(lpBuffer, str.start);
-        str.len() as u32
+        len
     } else {
-        let dst = machine.mem().sub32_mut(lpBuffer, cchBufferMax * 2);
-        let copy_len = std::cmp::min(dst.len() - 2, str.len()) as u32;
-        dst[..copy_len as usize].copy_from_slice(mem.sub32(str.start, copy_len));
-        dst.put_pod::<u16>(copy_len as u32, 0);
-        copy_len as u32
+        let Some(dst) = out_buf(
+            machine,
+            lpBuffer,
+            cchBufferMax * 2,
+            format_args!("LoadStringW"),
+        ) else {
+            return 0;
+        };
+        let copy_len = std::cmp::min(cchBufferMax - 1, len);
+        dst[..(copy_len * 2) as usize].copy_from_slice(mem.sub32(str.start, copy_len * 2));
+        dst.put_pod::<u16>(copy_len * 2, 0);
+        copy_len
     }
 }
 
@@ -0,0 +1,191 @@
+//! String functions that understand double-byte character sets.
+//!
+//! In a DBCS code page like Shift-JIS the second byte of a character can look like
+//! an ASCII character, so walking or case-mapping an ANSI string a byte at a time
+//! corrupts it.
+
+use crate::{
+    str16::Str16,
+    winapi::kernel32::{set_last_error, CodePage},
+    winapi::ERROR,
+    Machine,
+};
+use memory::{Extensions, ExtensionsMut};
+
+#[win32_derive::dllexport]
+pub fn CharNextA(machine: &mut Machine, lpsz: u32) -> u32 {
+    let cp = machine.state.kernel32.code_page;
+    char_next(machine, cp, lpsz)
+}
+
+#[win32_derive::dllexport]
+pub fn CharNextExA(machine: &mut Machine, CodePage: u16, lpCurrentChar: u32, dwFlags: u32) -> u32 {
+    let Some(cp) = CodePage::resolve(machine, CodePage as u32) else {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return lpCurrentChar;
+    };
+    char_next(machine, cp, lpCurrentChar)
+}
+
+fn char_next(machine: &Machine, cp: CodePage, addr: u32) -> u32 {
+    if addr == 0 {
+        return 0;
+    }
+    // Stops at the nul rather than stepping past it.
+    let str = machine.mem().strz(addr, u32::MAX);
+    addr + cp.next_char(str.unwrap_or_else(|err| err.raise()), 0) as u32
+}
+
+#[win32_derive::dllexport]
+pub fn CharPrevA(machine: &mut Machine, lpszStart: u32, lpszCurrent: u32) -> u32 {
+    let cp = machine.state.kernel32.code_page;
+    char_prev(machine, cp, lpszStart, lpszCurrent)
+}
+
+#[win32_derive::dllexport]
+pub fn CharPrevExA(
+    machine: &mut Machine,
+    CodePage: u16,
+    lpStart: u32,
+    lpCurrentChar: u32,
+    dwFlags: u32,
+) -> u32 {
+    let Some(cp) = CodePage::resolve(machine, CodePage as u32) else {
+        set_last_error(machine, ERROR::INVALID_PARAMETER);
+        return lpCurrentChar;
+    };
+    char_prev(machine, cp, lpStart, lpCurrentChar)
+}
+
+fn char_prev(machine: &Machine, cp: CodePage, start: u32, current: u32) -> u32 {
+    if current <= start {
+        return start;
+    }
+    let str = machine
+        .mem()
+        .try_sub32(start, current - start)
+        .unwrap_or_else(|err| err.raise());
+    start + cp.prev_char(str, str.len()) as u32
+}
+
+#[win32_derive::dllexport]
+pub fn CharNextW(machine: &mut Machine, lpsz: u32) -> u32 {
+    if lpsz == 0 || machine.mem().get_pod::<u16>(lpsz) == 0 {
+        return lpsz;
+    }
+    lpsz + 2
+}
+
+#[win32_derive::dllexport]
+pub fn CharPrevW(_machine: &mut Machine, lpszStart: u32, lpszCurrent: u32) -> u32 {
+    if lpszCurrent <= lpszStart {
+        return lpszStart;
+    }
+    lpszCurrent - 2
+}
+
+/// CharUpper/CharLower take either a string pointer or, if the high word is zero, a
+/// single character, which they return converted.
+fn is_single_char(lpsz: u32) -> bool {
+    lpsz >> 16 == 0
+}
+
+fn change_case_ansi(machine: &mut Machine, lpsz: u32, len: Option<u32>, upper: bool) -> u32 {
+    let cp = machine.state.kernel32.code_page;
+    if is_single_char(lpsz) && len.is_none() {
+        let mut c = [lpsz as u8];
+        cp.change_case(&mut c, upper);
+        return c[0] as u32;
+    }
+    let len = match len {
+        Some(len) => len,
+        None => match machine.mem().strz(lpsz, u32::MAX) {
+            Ok(str) => str.len() as u32,
+            Err(_) => return 0,
+        },
+    };
+    let Ok(buf) = machine.mem().try_sub32_mut(lpsz, len) else {
+        return 0;
+    };
+    cp.change_case(buf, upper);
+    lpsz
+}
+
+fn change_case_char(c: u16, upper: bool) -> u16 {
+    let Some(ch) = char::from_u32(c as u32) else {
+        // A surrogate.
+        return c;
+    };
+    let mapped = if upper {
+        ch.to_uppercase().collect::<String>()
+    } else {
+        ch.to_lowercase().collect::<String>()
+    };
+    let mut utf16 = mapped.encode_utf16();
+    match (utf16.next(), utf16.next()) {
+        (Some(mapped), None) => mapped,
+        _ => c,
+    }
+}
+
+fn change_case_wide(machine: &mut Machine, lpsz: u32, len: Option<u32>, upper: bool) -> u32 {
+    if is_single_char(lpsz) && len.is_none() {
+        return change_case_char(lpsz as u16, upper) as u32;
+    }
+    let len = len.unwrap_or_else(|| {
+        unsafe { Str16::from_nul_term_ptr(machine.mem(), lpsz) }
+            .unwrap()
+            .len() as u32
+    });
+    let Ok(buf) = machine.mem().try_sub32_mut(lpsz, len * 2) else {
+        return 0;
+    };
+    for c in Str16::from_bytes_mut(buf).iter_mut() {
+        *c = change_case_char(*c, upper);
+    }
+    lpsz
+}
+
+#[win32_derive::dllexport]
+pub fn CharUpperA(machine: &mut Machine, lpsz: u32) -> u32 {
+    change_case_ansi(machine, lpsz, None, true)
+}
+
+#[win32_derive::dllexport]
+pub fn CharLowerA(machine: &mut Machine, lpsz: u32) -> u32 {
+    change_case_ansi(machine, lpsz, None, false)
+}
+
+#[win32_derive::dllexport]
+pub fn CharUpperW(machine: &mut Machine, lpsz: u32) -> u32 {
+    change_case_wide(machine, lpsz, None, true)
+}
+
+#[win32_derive::dllexport]
+pub fn CharLowerW(machine: &mut Machine, lpsz: u32) -> u32 {
+    change_case_wide(machine, lpsz, None, false)
+}
+
+#[win32_derive::dllexport]
+pub fn CharUpperBuffA(machine: &mut Machine, lpsz: u32, cchLength: u32) -> u32 {
+    change_case_ansi(machine, lpsz, Some(cchLength), true);
+    cchLength
+}
+
+#[win32_derive::dllexport]
+pub fn CharLowerBuffA(machine: &mut Machine, lpsz: u32, cchLength: u32) -> u32 {
+    change_case_ansi(machine, lpsz, Some(cchLength), false);
+    cchLength
+}
+
+#[win32_derive::dllexport]
+pub fn CharUpperBuffW(machine: &mut Machine, lpsz: u32, cchLength: u32) -> u32 {
+    change_case_wide(machine, lpsz, Some(cchLength), true);
+    cchLength
+}
+
+#[win32_derive::dllexport]
+pub fn CharLowerBuffW(machine: &mut Machine, lpsz: u32, cchLength: u32) -> u32 {
+    change_case_wide(machine, lpsz, Some(cchLength), false);
+    cchLength
+}
@@ -2,7 +2,7 @@ use crate::{winapi::types::*, Machine};
 
 use super::{MSG, WM};
 
-#[derive(Debug)]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct Timer {
     id: u32,
     /// Associated window, if any.
@@ -16,8 +16,11 @@ pub struct Timer {
 }
 
 impl Timer {
-    pub fn generate_wm_timer(&mut self, now: u32) -> MSG {
-        self.next = now + self.period;
+    /// Generate the WM_TIMER for this timer, rescheduling it if the message is being removed.
+    pub fn generate_wm_timer(&mut self, now: u32, remove: bool) -> MSG {
+        if remove {
+            self.next = now + self.period;
+        }
         MSG {
             hwnd: self.hwnd,
             message: WM::TIMER as u32,
@@ -30,7 +33,7 @@ impl Timer {
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
 pub struct Timers(Vec<Timer>);
 
 impl Timers {
@@ -79,10 +82,7 @@ pub fn SetTimer(
     const USER_TIMER_MAXIMUM: u32 = 0x7FFF_FFFF;
     let uElapse = num_traits::clamp(uElapse, USER_TIMER_MINIMUM, USER_TIMER_MAXIMUM);
 
-    if lpTimerFunc != 0 {
-        log::warn!("timer callbacks unimplemented");
-    }
-
+    let now = machine.ticks();
     let id = match machine
         .state
         .user32
@@ -93,7 +93,7 @@ pub fn SetTimer(
     {
         Some(timer) => {
             timer.period = uElapse;
-            timer.next = machine.host.ticks() + uElapse;
+            timer.next = now + uElapse;
             timer.func = lpTimerFunc;
             timer.id
         }
@@ -103,7 +103,7 @@ pub fn SetTimer(
                 id,
                 hwnd: hWnd,
                 period: uElapse,
-                next: machine.host.ticks() + uElapse,
+                next: now + uElapse,
                 func: lpTimerFunc,
             };
             machine.state.user32.timers.0.push(timer);
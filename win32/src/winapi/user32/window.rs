@@ -6,8 +6,10 @@ use crate::{
         self,
         bitmap::{self, BitmapRGBA32},
         gdi32::HDC,
-        stack_args::{ArrayWithSize, FromArg},
+        kernel32::{set_last_error, CodePage},
+        stack_args::{ArrayWithSizeMut, FromArg},
         types::{Str16, String16, HWND, POINT, RECT},
+        ERROR,
     },
     Host, Machine, SurfaceOptions,
 };
@@ -43,6 +45,7 @@ ShowWindow() {
 
 */
 
+#[derive(serde::Serialize, serde::Deserialize)]
 pub struct WindowPixels {
     pub bitmap: BitmapRGBA32,
 }
@@ -64,36 +67,85 @@ impl WindowPixels {
     }
 }
 
+#[derive(serde::Serialize, serde::Deserialize)]
 pub struct UpdateRegion {
     /// Whether to erase background in BeginPaint.
     pub erase_background: bool,
     // TODO: rect
 }
 
+#[derive(serde::Serialize, serde::Deserialize)]
 pub struct Window {
     pub hwnd: HWND,
     pub typ: WindowType,
+    /// Position of the window (including its frame), relative to the parent's client area
+    /// for child windows or the screen for top-level windows.
+    pub x: i32,
+    pub y: i32,
     /// Client area width (not total window width).
     pub width: u32,
     /// Client area height (not total window height).
     pub height: u32,
+    #[serde(with = "crate::snapshot::shared")]
     pub wndclass: Rc<WndClass>,
     pub style: WindowStyle,
-}
-
+    pub style_ex: WindowStyleEx,
+    /// Window text, shown as the title of top-level windows.
+    pub text: String,
+    /// Whether the window has a menu bar, which takes space from the non-client area.
+    pub menu: bool,
+    pub parent: HWND,
+    /// For CS_OWNDC windows, the window's persistent DC, created on first use.
+    pub dc: Option<HDC>,
+    pub show_state: ShowState,
+    /// Window rect to return to when restored from minimized or maximized.
+    normal_rect: RECT,
+}
+
+/// Whether a window is minimized, maximized, or neither.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum ShowState {
+    #[default]
+    Normal,
+    Minimized,
+    Maximized,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
 pub enum WindowType {
     TopLevel(WindowTopLevel),
     Child,
 }
 
 /// Properties of only top-level windows.
+#[derive(serde::Serialize, serde::Deserialize)]
 pub struct WindowTopLevel {
+    #[serde(skip, default = "crate::snapshot::Detach::detached")]
     pub host: Box<dyn host::Window>,
+    #[serde(skip, default = "crate::snapshot::Detach::detached")]
     pub surface: Box<dyn host::Surface>,
-    // TODO: CS_OWNDC windows do own a DC, but otherwise they don't.
-    // pub hdc: HDC,
     pub pixels: Option<WindowPixels>,
     pub dirty: Option<UpdateRegion>,
+    /// For WS_EX_LAYERED windows, how the window is composited.
+    pub layered: Option<Layered>,
+}
+
+/// Attributes of a layered window, as set by SetLayeredWindowAttributes.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Layered {
+    /// Pixels of this color are fully transparent.
+    pub color_key: Option<COLORREF>,
+    /// Constant opacity of the whole window.
+    pub alpha: u8,
+}
+
+impl Default for Layered {
+    fn default() -> Self {
+        Layered {
+            color_key: None,
+            alpha: 0xFF,
+        }
+    }
 }
 
 impl Window {
@@ -120,6 +172,84 @@ impl Window {
             .bitmap
     }
 
+    pub fn set_text(&mut self, text: String) {
+        if let WindowType::TopLevel(top) = &mut self.typ {
+            top.host.set_title(&text);
+        }
+        self.text = text;
+    }
+
+    /// Window rect (including the frame) in parent coordinates.
+    pub fn frame_rect(&self) -> RECT {
+        let mut rect = RECT {
+            left: 0,
+            top: 0,
+            right: self.width as i32,
+            bottom: self.height as i32,
+        };
+        window_rect(&mut rect, self.style, self.style_ex, self.menu);
+        RECT {
+            left: self.x,
+            top: self.y,
+            right: self.x + (rect.right - rect.left),
+            bottom: self.y + (rect.bottom - rect.top),
+        }
+    }
+
+    /// Move and resize the window to the given window rect, in parent coordinates.
+    fn set_frame_rect(&mut self, host: &mut dyn Host, rect: &RECT) {
+        self.x = rect.left;
+        self.y = rect.top;
+        let (width, height) = client_size_from_window_size(
+            self.style,
+            self.style_ex,
+            self.menu,
+            (rect.right - rect.left) as u32,
+            (rect.bottom - rect.top) as u32,
+        );
+        if (width, height) != (self.width, self.height) {
+            self.set_client_size(host, width, height);
+        }
+    }
+
+    /// Switch between normal, minimized and maximized, where `bounds` is the area
+    /// a maximized window fills.
+    fn set_show_state(&mut self, host: &mut dyn Host, state: ShowState, bounds: (u32, u32)) {
+        if state == self.show_state {
+            return;
+        }
+        if self.show_state == ShowState::Normal {
+            self.normal_rect = self.frame_rect();
+        }
+        self.show_state = state;
+        self.style
+            .remove(WindowStyle::MINIMIZE | WindowStyle::MAXIMIZE);
+        match state {
+            ShowState::Normal => {
+                let rect = self.normal_rect.clone();
+                self.set_frame_rect(host, &rect);
+            }
+            ShowState::Minimized => {
+                // There's no taskbar to iconify to, so the window keeps its size but is hidden.
+                self.style.insert(WindowStyle::MINIMIZE);
+            }
+            ShowState::Maximized => {
+                self.style.insert(WindowStyle::MAXIMIZE);
+                // The frame's sides and bottom hang off the edges, leaving the caption on screen.
+                let mut frame = RECT::default();
+                window_rect(&mut frame, self.style, self.style_ex, self.menu);
+                let border = frame.right;
+                let rect = RECT {
+                    left: -border,
+                    top: -border,
+                    right: bounds.0 as i32 + border,
+                    bottom: bounds.1 as i32 + border,
+                };
+                self.set_frame_rect(host, &rect);
+            }
+        }
+    }
+
     pub fn set_client_size(&mut self, host: &mut dyn Host, width: u32, height: u32) {
         self.width = width;
         self.height = height;
@@ -154,25 +284,92 @@ impl WindowTopLevel {
 
     pub fn flush_pixels(&mut self, mem: Mem) {
         if let Some(pixels) = &mut self.pixels {
-            self.surface
-                .write_pixels(&pixels.bitmap.pixels.as_slice(mem));
+            let pixels = pixels.bitmap.pixels.as_slice(mem);
+            match self.layered.as_ref().and_then(|l| l.color_key) {
+                Some(key) => {
+                    // Color keying is implemented by making the keyed pixels transparent,
+                    // leaving it to the host to composite the surface.
+                    let mut keyed = vec![[0; 4]; pixels.len()];
+                    memory::simd::copy_color_keyed(&mut keyed, pixels, key.to_pixel());
+                    self.surface.write_pixels(&keyed);
+                }
+                None => self.surface.write_pixels(pixels),
+            }
             self.surface.show();
         }
     }
 }
 
+#[derive(serde::Serialize, serde::Deserialize)]
 pub struct WndClass {
     pub name: String,
+    pub style: CS,
     pub wndproc: u32,
+    /// For classes implemented by retrowin32, used instead of wndproc.
+    pub builtin: Option<BuiltinClass>,
+    /// Classes registered with the *W functions get messages with UTF-16 strings.
+    pub wide: bool,
     pub background: HBRUSH,
 }
 
+/// Window classes that are implemented here rather than registered by the guest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum BuiltinClass {
+    MDIClient,
+}
+
+impl BuiltinClass {
+    fn from_name(name: &str) -> Option<Self> {
+        if name.eq_ignore_ascii_case("MDICLIENT") {
+            Some(BuiltinClass::MDIClient)
+        } else {
+            None
+        }
+    }
+}
+
+bitflags! {
+    /// Window class styles.
+    #[derive(serde::Serialize, serde::Deserialize)]
+    pub struct CS: u32 {
+        const VREDRAW         = 0x0001;
+        const HREDRAW         = 0x0002;
+        const DBLCLKS         = 0x0008;
+        const OWNDC           = 0x0020;
+        const CLASSDC         = 0x0040;
+        const PARENTDC        = 0x0080;
+        const NOCLOSE         = 0x0200;
+        const SAVEBITS        = 0x0800;
+        const BYTEALIGNCLIENT = 0x1000;
+        const BYTEALIGNWINDOW = 0x2000;
+        const GLOBALCLASS     = 0x4000;
+        const DROPSHADOW      = 0x0002_0000;
+    }
+}
+
+impl CS {
+    fn from_raw(style: u32) -> CS {
+        let cs = CS::from_bits_truncate(style);
+        if cs.bits() != style {
+            log::warn!("unhandled class style {:#x}", style & !cs.bits());
+        }
+        cs
+    }
+}
+
 fn register_class(machine: &mut Machine, wndclass: WndClass) -> u32 {
     let atom = machine.state.user32.wndclasses.len() as u32 + 1;
     machine.state.user32.wndclasses.push(Rc::new(wndclass));
     atom
 }
 
+impl super::State {
+    /// The class that register_class returned the given atom for.
+    fn class_by_atom(&self, atom: u16) -> Option<&Rc<WndClass>> {
+        self.wndclasses.get((atom as usize).checked_sub(1)?)
+    }
+}
+
 #[repr(C, packed)]
 #[derive(Clone, Debug)]
 pub struct WNDCLASSA {
@@ -211,14 +408,16 @@ pub fn RegisterClassA(machine: &mut Machine, lpWndClass: Option<&WNDCLASSA>) ->
 
 #[win32_derive::dllexport]
 pub fn RegisterClassW(machine: &mut Machine, lpWndClass: Option<&WNDCLASSA>) -> u32 {
-    // TODO: calling the *W variants tags the windows as expecting wide messages(!).
     let lpWndClass = lpWndClass.unwrap();
     let name =
         unsafe { Str16::from_nul_term_ptr(machine.mem(), lpWndClass.lpszClassName) }.unwrap();
     let background = unsafe { BrushOrColor::from_arg(machine.mem(), lpWndClass.hbrBackground) };
     let wndclass = WndClass {
         name: name.to_string(),
+        style: CS::from_raw(lpWndClass.style),
         wndproc: lpWndClass.lpfnWndProc,
+        builtin: None,
+        wide: true,
         background: background.to_brush(machine),
     };
     register_class(machine, wndclass)
@@ -263,10 +462,19 @@ unsafe impl memory::Pod for WNDCLASSEXW {}
 #[win32_derive::dllexport]
 pub fn RegisterClassExA(machine: &mut Machine, lpWndClassEx: Option<&WNDCLASSEXA>) -> u32 {
     let lpWndClassEx = lpWndClassEx.unwrap();
-    let name = expect_ascii(machine.mem().slicez(lpWndClassEx.lpszClassName)).to_string();
+    let name = match machine.mem().strz(lpWndClassEx.lpszClassName, u32::MAX) {
+        Ok(name) => expect_ascii(name).to_string(),
+        Err(err) => {
+            set_last_error(machine, err.into());
+            return 0;
+        }
+    };
     let wndclass = WndClass {
         name,
+        style: CS::from_raw(lpWndClassEx.style),
         wndproc: lpWndClassEx.lpfnWndProc,
+        builtin: None,
+        wide: false,
         background: unsafe { BrushOrColor::from_arg(machine.mem(), lpWndClassEx.hbrBackground) }
             .to_brush(machine),
     };
@@ -281,7 +489,10 @@ pub fn RegisterClassExW(machine: &mut Machine, lpWndClassEx: Option<&WNDCLASSEXW
         .to_string();
     let wndclass = WndClass {
         name,
+        style: CS::from_raw(lpWndClassEx.style),
         wndproc: lpWndClassEx.lpfnWndProc,
+        builtin: None,
+        wide: true,
         background: unsafe { BrushOrColor::from_arg(machine.mem(), lpWndClassEx.hbrBackground) }
             .to_brush(machine),
     };
@@ -289,6 +500,7 @@ pub fn RegisterClassExW(machine: &mut Machine, lpWndClassEx: Option<&WNDCLASSEXW
 }
 
 bitflags! {
+    #[derive(serde::Serialize, serde::Deserialize)]
     pub struct WindowStyle: u32 {
         const POPUP           = 0x80000000;
         const CHILD           = 0x40000000;
@@ -298,6 +510,7 @@ bitflags! {
         const CLIPSIBLINGS    = 0x04000000;
         const CLIPCHILDREN    = 0x02000000;
         const MAXIMIZE        = 0x01000000;
+        const CAPTION         = 0x00C00000; // BORDER | DLGFRAME
         const BORDER          = 0x00800000;
         const DLGFRAME        = 0x00400000;
         const VSCROLL         = 0x00200000;
@@ -319,8 +532,21 @@ impl TryFrom<u32> for WindowStyle {
 }
 
 bitflags! {
+    #[derive(serde::Serialize, serde::Deserialize)]
     pub struct WindowStyleEx: u32 {
-        // todo
+        const DLGMODALFRAME  = 0x00000001;
+        const NOPARENTNOTIFY = 0x00000004;
+        const TOPMOST        = 0x00000008;
+        const ACCEPTFILES    = 0x00000010;
+        const TRANSPARENT    = 0x00000020;
+        const MDICHILD       = 0x00000040;
+        const TOOLWINDOW     = 0x00000080;
+        const WINDOWEDGE     = 0x00000100;
+        const CLIENTEDGE     = 0x00000200;
+        const CONTEXTHELP    = 0x00000400;
+        const STATICEDGE     = 0x00020000;
+        const APPWINDOW      = 0x00040000;
+        const LAYERED        = 0x00080000;
     }
 }
 impl TryFrom<u32> for WindowStyleEx {
@@ -415,7 +641,13 @@ pub async fn CreateWindowExW(
     lpParam: u32,
 ) -> HWND {
     let class_name = match lpClassName {
-        CreateWindowClassName::Atom(_) => unimplemented!(),
+        CreateWindowClassName::Atom(atom) => match machine.state.user32.class_by_atom(atom) {
+            Some(wndclass) => wndclass.name.clone(),
+            None => {
+                set_last_error(machine, ERROR::CANNOT_FIND_WND_CLASS);
+                return HWND::null();
+            }
+        },
         CreateWindowClassName::Name(name) => name.to_string(),
     };
     let wndclass = match machine
@@ -427,14 +659,21 @@ pub async fn CreateWindowExW(
     {
         Some(wndclass) => wndclass.clone(),
         None => {
-            log::warn!("unknown wndclass {class_name:?}, using empty");
+            let builtin = BuiltinClass::from_name(&class_name);
+            if builtin.is_none() {
+                log::warn!("unknown wndclass {class_name:?}, using empty");
+            }
             Rc::new(WndClass {
                 name: class_name,
+                style: CS::empty(),
                 wndproc: 0,
+                builtin,
+                wide: false,
                 background: HBRUSH::null(),
             })
         }
     };
+    let builtin = wndclass.builtin;
 
     let style = dwStyle.unwrap();
     const CW_USEDEFAULT: u32 = 0x8000_0000;
@@ -443,6 +682,15 @@ pub async fn CreateWindowExW(
     //   https://devblogs.microsoft.com/oldnewthing/20050418-59/?p=35873
 
     let hwnd = machine.state.user32.windows.reserve();
+    let text = lpWindowName
+        .map(|name| name.to_string())
+        .unwrap_or_default();
+    // If X is CW_USEDEFAULT then Y is ignored too.
+    let (x, y) = if X == CW_USEDEFAULT {
+        (0, 0)
+    } else {
+        (X as i32, Y as i32)
+    };
     let width = if nWidth == CW_USEDEFAULT { 640 } else { nWidth };
     let height = if nHeight == CW_USEDEFAULT {
         480
@@ -451,14 +699,19 @@ pub async fn CreateWindowExW(
     };
 
     let style = dwStyle.unwrap();
-    let menu = false; // TODO
-    let (width, height) = client_size_from_window_size(style, menu, width, height);
+    let style_ex = dwExStyle.unwrap_or_else(|bits| {
+        log::warn!("unhandled extended window style {bits:#x}");
+        WindowStyleEx::from_bits_truncate(bits)
+    });
+    // For child windows, hMenu is instead the child window identifier.
+    let menu = hMenu != 0 && !style.contains(WindowStyle::CHILD);
+    let (width, height) = client_size_from_window_size(style, style_ex, menu, width, height);
 
     let typ = if style.contains(WindowStyle::CHILD) {
         WindowType::Child
     } else {
         let mut host_win = machine.host.create_window(hwnd.to_raw());
-        host_win.set_title(&lpWindowName.unwrap().to_string());
+        host_win.set_title(&text);
         host_win.set_size(width, height);
         let surface = machine.host.create_surface(
             hwnd.to_raw(),
@@ -468,6 +721,10 @@ pub async fn CreateWindowExW(
                 primary: true,
             },
         );
+        if style.contains(WindowStyle::VISIBLE) {
+            host_win.show(true);
+        }
+        machine.state.user32.z_order.push(hwnd);
         WindowType::TopLevel(WindowTopLevel {
             host: host_win,
             surface,
@@ -475,18 +732,35 @@ pub async fn CreateWindowExW(
             dirty: Some(UpdateRegion {
                 erase_background: true,
             }),
+            layered: if style_ex.contains(WindowStyleEx::LAYERED) {
+                Some(Layered::default())
+            } else {
+                None
+            },
         })
     };
 
     let window = Window {
         hwnd,
         typ,
+        x,
+        y,
         width,
         height,
         wndclass,
         style,
+        style_ex,
+        text,
+        menu,
+        parent: hWndParent,
+        dc: None,
+        show_state: ShowState::default(),
+        normal_rect: RECT::default(),
     };
     machine.state.user32.windows.set(hwnd, window);
+    if builtin == Some(BuiltinClass::MDIClient) {
+        create_mdi_client(machine, hwnd, lpParam);
+    }
 
     // Synchronously dispatch WM_CREATE.
     let msg = MSG {
@@ -500,11 +774,25 @@ pub async fn CreateWindowExW(
     };
     dispatch_message(machine, &msg).await;
 
+    if style.contains(WindowStyle::VISIBLE) && !style.contains(WindowStyle::CHILD) {
+        set_active_window(machine, hwnd).await;
+    }
+
     hwnd
 }
 
 #[win32_derive::dllexport]
-pub fn DestroyWindow(_machine: &mut Machine, hWnd: HWND) -> bool {
+pub async fn DestroyWindow(machine: &mut Machine, hWnd: HWND) -> bool {
+    let Some(window) = machine.state.user32.windows.get_mut(hWnd) else {
+        set_last_error(machine, ERROR::INVALID_WINDOW_HANDLE);
+        return false;
+    };
+    window.style.remove(WindowStyle::VISIBLE);
+    if let WindowType::TopLevel(top) = &mut window.typ {
+        top.host.show(false);
+    }
+    window_hidden(machine, hWnd).await;
+    machine.state.user32.z_order.retain(|&w| w != hWnd);
     true // success
 }
 
@@ -513,27 +801,6 @@ pub fn GetDesktopWindow(_machine: &mut Machine) -> HWND {
     HWND::null()
 }
 
-#[win32_derive::dllexport]
-pub fn GetForegroundWindow(machine: &mut Machine) -> HWND {
-    if let Some(window) = machine.state.user32.windows.iter().next() {
-        return window.hwnd;
-    }
-    GetDesktopWindow(machine)
-}
-
-#[win32_derive::dllexport]
-pub fn SetForegroundWindow(_machine: &mut Machine, hWnd: HWND) -> bool {
-    true // success
-}
-
-#[win32_derive::dllexport]
-pub fn GetActiveWindow(machine: &mut Machine) -> HWND {
-    match machine.state.user32.windows.iter().next() {
-        Some(w) => w.hwnd,
-        None => HWND::null(),
-    }
-}
-
 #[win32_derive::dllexport]
 pub fn GetLastActivePopup(machine: &mut Machine) -> HWND {
     machine.state.user32.windows.iter().next().unwrap().hwnd
@@ -554,6 +821,55 @@ pub fn FindWindowA(
     }
 }
 
+/// Call lpEnumFunc(hwnd, lParam) for each of hwnds until it returns false.
+async fn enum_windows(machine: &mut Machine, hwnds: Vec<HWND>, lpEnumFunc: u32, lParam: u32) {
+    for hwnd in hwnds {
+        if machine.call_guest(lpEnumFunc, (hwnd, lParam)).await == 0 {
+            break;
+        }
+    }
+}
+
+#[win32_derive::dllexport]
+pub async fn EnumWindows(machine: &mut Machine, lpEnumFunc: u32, lParam: u32) -> bool {
+    // Enumerated top to bottom.
+    let hwnds = machine.state.user32.z_order.clone();
+    enum_windows(machine, hwnds, lpEnumFunc, lParam).await;
+    true
+}
+
+#[win32_derive::dllexport]
+pub async fn EnumChildWindows(
+    machine: &mut Machine,
+    hWndParent: HWND,
+    lpEnumFunc: u32,
+    lParam: u32,
+) -> bool {
+    if hWndParent.is_null() {
+        // The desktop's children are the top-level windows.
+        return EnumWindows(machine, lpEnumFunc, lParam).await;
+    }
+    let windows = &machine.state.user32.windows;
+    let is_descendant = |mut hwnd: HWND| loop {
+        match windows.get(hwnd) {
+            Some(window) if !window.parent.is_null() => hwnd = window.parent,
+            _ => return false,
+        }
+        if hwnd == hWndParent {
+            return true;
+        }
+    };
+    // Children are kept unordered, so go by creation order.
+    let mut hwnds = windows
+        .iter()
+        .map(|window| window.hwnd)
+        .filter(|&hwnd| is_descendant(hwnd))
+        .collect::<Vec<_>>();
+    hwnds.sort_by_key(|hwnd| hwnd.to_raw());
+    enum_windows(machine, hwnds, lpEnumFunc, lParam).await;
+    true
+}
+
 #[win32_derive::dllexport]
 pub async fn UpdateWindow(machine: &mut Machine, hWnd: HWND) -> bool {
     let window = machine.state.user32.windows.get(hWnd).unwrap();
@@ -601,72 +917,103 @@ pub enum SW {
 
 #[win32_derive::dllexport]
 pub async fn ShowWindow(machine: &mut Machine, hWnd: HWND, nCmdShow: Result<SW, u32>) -> bool {
-    dispatch_message(
-        machine,
-        &MSG {
-            hwnd: hWnd,
-            message: WM::ACTIVATEAPP as u32,
-            wParam: true as u32, // activating
-            lParam: 0,           // TODO: thread id
-            time: 0,
-            pt_x: 0,
-            pt_y: 0,
-        },
-    )
-    .await;
+    let cmd = nCmdShow.unwrap();
+    let user32 = &mut machine.state.user32;
+    let Some(window) = user32.windows.get(hWnd) else {
+        set_last_error(machine, ERROR::INVALID_WINDOW_HANDLE);
+        return false;
+    };
+    let previously_visible = window.style.contains(WindowStyle::VISIBLE);
+    let bounds = match user32.windows.get(window.parent) {
+        Some(parent) if window.style.contains(WindowStyle::CHILD) => (parent.width, parent.height),
+        _ => (user32.display.width, user32.display.height),
+    };
 
-    const WA_ACTIVE: u32 = 1;
-    dispatch_message(
-        machine,
-        &MSG {
-            hwnd: hWnd,
-            message: WM::ACTIVATE as u32,
-            wParam: WA_ACTIVE,
-            lParam: 0, // TODO: previous window hwnd
-            time: 0,
-            pt_x: 0,
-            pt_y: 0,
-        },
-    )
-    .await;
+    let window = user32.windows.get_mut(hWnd).unwrap();
+    let state = match cmd {
+        SW::HIDE => {
+            window.style.remove(WindowStyle::VISIBLE);
+            if let WindowType::TopLevel(top) = &mut window.typ {
+                top.host.show(false);
+            }
+            window_hidden(machine, hWnd).await;
+            return previously_visible;
+        }
+        SW::SHOWMINIMIZED | SW::MINIMIZE | SW::SHOWMINNOACTIVE | SW::FORCEMINIMIZE => {
+            ShowState::Minimized
+        }
+        SW::SHOWMAXIMIZED => ShowState::Maximized,
+        SW::NORMAL | SW::RESTORE | SW::SHOWDEFAULT => ShowState::Normal,
+        SW::SHOW | SW::SHOWNOACTIVATE | SW::SHOWNA => window.show_state,
+    };
+    let prev_state = window.show_state;
+    window.style.insert(WindowStyle::VISIBLE);
+    window.set_show_state(&mut *machine.host, state, bounds);
+    let toplevel = match &mut window.typ {
+        WindowType::TopLevel(top) => {
+            top.host.show(state != ShowState::Minimized);
+            true
+        }
+        WindowType::Child => false,
+    };
 
-    // TODO: WM_WINDOWPOSCHANGED should pass a WINDOWPOS struct,
-    // but the DefWindowProc we provide ignores it and calls WM_MOVE/WM_SIZE directly.
-    let windowpos_addr = 0;
-    dispatch_message(
-        machine,
-        &MSG {
-            hwnd: hWnd,
-            message: WM::WINDOWPOSCHANGED as u32,
-            wParam: 0,
-            lParam: windowpos_addr,
-            time: 0,
-            pt_x: 0,
-            pt_y: 0,
-        },
-    )
-    .await;
+    if matches!(cmd, SW::MINIMIZE | SW::FORCEMINIMIZE) {
+        // Minimizing hands activation to the next window.
+        window_hidden(machine, hWnd).await;
+    } else if toplevel && !matches!(cmd, SW::SHOWNOACTIVATE | SW::SHOWNA | SW::SHOWMINNOACTIVE) {
+        set_active_window(machine, hWnd).await;
+    }
 
-    let previously_visible = true;
-    previously_visible
-}
+    let mut flags = SWP::NOMOVE | SWP::NOSIZE | SWP::NOZORDER | SWP::SHOWWINDOW;
+    if state != prev_state {
+        flags.remove(SWP::NOMOVE | SWP::NOSIZE);
+    }
+    send_windowposchanged(machine, hWnd, flags).await;
 
-#[win32_derive::dllexport]
-pub fn SetFocus(_machine: &mut Machine, hWnd: HWND) -> HWND {
-    let prev_focused = HWND::null();
-    prev_focused
+    previously_visible
 }
 
-#[win32_derive::dllexport]
-pub fn GetFocus(machine: &mut Machine) -> HWND {
-    machine.state.user32.windows.iter().next().unwrap().hwnd
+/// Synchronously send WM_WINDOWPOSCHANGED describing the window's current position.
+async fn send_windowposchanged(machine: &mut Machine, hwnd: HWND, flags: SWP) {
+    let window = machine.state.user32.windows.get(hwnd).unwrap();
+    let rect = window.frame_rect();
+    let windowpos_addr = machine.state.scratch.alloc(
+        machine.emu.memory.mem(),
+        std::mem::size_of::<WINDOWPOS>() as u32,
+    );
+    machine.mem().put_pod::<WINDOWPOS>(
+        windowpos_addr,
+        WINDOWPOS {
+            hwnd,
+            hwndInsertAfter: HWND::null(),
+            x: rect.left,
+            y: rect.top,
+            cx: rect.right - rect.left,
+            cy: rect.bottom - rect.top,
+            flags,
+        },
+    );
+    let msg = MSG {
+        hwnd,
+        message: WM::WINDOWPOSCHANGED as u32,
+        wParam: 0,
+        lParam: windowpos_addr,
+        time: 0,
+        pt_x: 0,
+        pt_y: 0,
+    };
+    dispatch_message(machine, &msg).await;
+    machine
+        .state
+        .scratch
+        .free(machine.emu.memory.mem(), windowpos_addr);
 }
 
-async fn def_window_proc(
+pub async fn def_window_proc(
     machine: &mut Machine,
     hWnd: HWND,
     msg: Result<WM, u32>,
-    _wParam: u32,
+    wParam: u32,
     lParam: u32,
 ) -> u32 {
     let msg = match msg {
@@ -684,17 +1031,105 @@ async fn def_window_proc(
                 .expect_toplevel_mut();
             window.dirty = None;
         }
+        WM::ERASEBKGND => {
+            let hdc = HDC::from_raw(wParam);
+            let window = machine.state.user32.windows.get(hWnd).unwrap();
+            let Some(hbrush) = window.wndclass.background.to_option() else {
+                return 0; // not erased
+            };
+            let rect = RECT {
+                left: 0,
+                top: 0,
+                right: window.width as i32,
+                bottom: window.height as i32,
+            };
+            let winapi::gdi32::Object::Brush(brush) =
+                machine.state.gdi32.objects.get(hbrush).unwrap()
+            else {
+                return 0;
+            };
+            let Some(color) = brush.color else {
+                return 0;
+            };
+            winapi::gdi32::fill_rect(machine, hdc, &rect, color);
+            return 1; // erased
+        }
+        WM::SYSCOMMAND => {
+            const SC_MINIMIZE: u32 = 0xF020;
+            const SC_MAXIMIZE: u32 = 0xF030;
+            const SC_CLOSE: u32 = 0xF060;
+            const SC_RESTORE: u32 = 0xF120;
+            // The low four bits are used internally by Windows.
+            let cmd = match wParam & 0xFFF0 {
+                SC_MINIMIZE => SW::MINIMIZE,
+                SC_MAXIMIZE => SW::SHOWMAXIMIZED,
+                SC_RESTORE => SW::RESTORE,
+                SC_CLOSE => {
+                    let msg = MSG {
+                        hwnd: hWnd,
+                        message: WM::CLOSE as u32,
+                        wParam: 0,
+                        lParam: 0,
+                        time: 0,
+                        pt_x: 0,
+                        pt_y: 0,
+                    };
+                    dispatch_message(machine, &msg).await;
+                    return 0;
+                }
+                cmd => {
+                    log::warn!("unhandled WM_SYSCOMMAND {cmd:#x}");
+                    return 0;
+                }
+            };
+            Box::pin(ShowWindow(machine, hWnd, Ok(cmd))).await;
+        }
+        WM::CLOSE => {
+            Box::pin(DestroyWindow(machine, hWnd)).await;
+        }
+        WM::SETTEXT => {
+            let window = machine.state.user32.windows.get_mut(hWnd).unwrap();
+            let text = get_text(machine.emu.memory.mem(), lParam, window.wndclass.wide);
+            window.set_text(text);
+            return 1; // success
+        }
+        WM::GETTEXT => {
+            let cp = machine.state.kernel32.code_page;
+            let window = machine.state.user32.windows.get(hWnd).unwrap();
+            let mem = machine.emu.memory.mem();
+            return put_text(mem, cp, &window.text, window.wndclass.wide, lParam, wParam);
+        }
+        WM::GETTEXTLENGTH => {
+            let cp = machine.state.kernel32.code_page;
+            let window = machine.state.user32.windows.get(hWnd).unwrap();
+            return text_len(cp, &window.text, window.wndclass.wide);
+        }
         WM::WINDOWPOSCHANGED => {
-            let Window { width, height, .. } = *machine.state.user32.windows.get_mut(hWnd).unwrap();
+            let windows = &machine.state.user32.windows;
+            let window = windows.get(hWnd).unwrap();
+            let (width, height, show_state) = (window.width, window.height, window.show_state);
+            // WM_MOVE reports the client area position, relative to the parent.
+            let (x, y) = {
+                let (x, y) = client_origin(windows, window);
+                let (parent_x, parent_y) = parent_origin(windows, window);
+                (x - parent_x, y - parent_y)
+            };
             let WINDOWPOS { flags, .. } = machine.mem().get_pod::<WINDOWPOS>(lParam);
 
             if !flags.contains(SWP::NOSIZE) {
                 const SIZE_RESTORED: u32 = 0;
+                const SIZE_MINIMIZED: u32 = 1;
+                const SIZE_MAXIMIZED: u32 = 2;
+                let (kind, size) = match show_state {
+                    ShowState::Normal => (SIZE_RESTORED, (height << 16) | width),
+                    ShowState::Minimized => (SIZE_MINIMIZED, 0),
+                    ShowState::Maximized => (SIZE_MAXIMIZED, (height << 16) | width),
+                };
                 let msg = MSG {
                     hwnd: hWnd,
                     message: WM::SIZE as u32,
-                    wParam: SIZE_RESTORED, // TODO: SIZE_* flags
-                    lParam: (height << 16) | width,
+                    wParam: kind,
+                    lParam: size,
                     time: 0,
                     pt_x: 0,
                     pt_y: 0,
@@ -703,13 +1138,11 @@ async fn def_window_proc(
             }
 
             if !flags.contains(SWP::NOMOVE) {
-                let x = 0; // TODO
-                let y = 0;
                 let msg = MSG {
                     hwnd: hWnd,
                     message: WM::MOVE as u32,
                     wParam: 0,
-                    lParam: (y << 16) | x,
+                    lParam: ((y as u16 as u32) << 16) | (x as u16 as u32),
                     time: 0,
                     pt_x: 0,
                     pt_y: 0,
@@ -744,40 +1177,59 @@ pub async fn DefWindowProcW(
     def_window_proc(machine, hWnd, msg, wParam, lParam).await
 }
 
-/// Compute window rectangle from client rectangle.
-fn window_rect(rect: &mut RECT, style: WindowStyle, menu: bool) {
-    const CAPTION: i32 = 19;
-    rect.top = rect.top - CAPTION;
-    rect.left = rect.left;
-    rect.right = rect.right;
-    rect.bottom = rect.bottom;
-    if menu {
-        rect.top -= 19;
-    }
-    if style.contains(WindowStyle::BORDER) {
-        const BORDER: i32 = 1;
-        rect.top -= BORDER;
-        rect.left -= BORDER;
-        rect.right += BORDER;
-        rect.bottom += BORDER;
+/// Compute window rectangle from client rectangle, by adding the frame, caption, and menu bar
+/// that make up the non-client area.
+fn window_rect(rect: &mut RECT, style: WindowStyle, style_ex: WindowStyleEx, menu: bool) {
+    // Overlapped windows always have a caption.
+    let style = if style.intersects(WindowStyle::POPUP | WindowStyle::CHILD) {
+        style
+    } else {
+        style | WindowStyle::CAPTION
+    };
+    let modal_frame = style_ex.contains(WindowStyleEx::DLGMODALFRAME);
+
+    // The frame is built up from an outer edge, the resizing border, and an inner line.
+    let mut frame = 0;
+    if style_ex.contains(WindowStyleEx::STATICEDGE) && !modal_frame {
+        frame += metrics::BORDER;
+    } else if modal_frame || style.intersects(WindowStyle::THICKFRAME | WindowStyle::DLGFRAME) {
+        frame += metrics::DLGFRAME - metrics::BORDER;
     }
     if style.contains(WindowStyle::THICKFRAME) {
-        const FRAME: i32 = 4;
-        rect.top -= FRAME;
-        rect.left -= FRAME;
-        rect.right += FRAME;
-        rect.bottom += FRAME;
+        frame += metrics::FRAME - metrics::DLGFRAME;
+    }
+    if modal_frame || style.intersects(WindowStyle::BORDER | WindowStyle::DLGFRAME) {
+        frame += metrics::BORDER;
+    }
+    if style_ex.contains(WindowStyleEx::CLIENTEDGE) {
+        frame += metrics::EDGE;
+    }
+    rect.left -= frame;
+    rect.top -= frame;
+    rect.right += frame;
+    rect.bottom += frame;
+
+    if style.contains(WindowStyle::CAPTION) {
+        rect.top -= if style_ex.contains(WindowStyleEx::TOOLWINDOW) {
+            metrics::SMCAPTION
+        } else {
+            metrics::CAPTION
+        };
+    }
+    if menu {
+        rect.top -= metrics::MENU;
     }
 }
 
 fn client_size_from_window_size(
     style: WindowStyle,
+    style_ex: WindowStyleEx,
     menu: bool,
     width: u32,
     height: u32,
 ) -> (u32, u32) {
     let mut r = RECT::default();
-    window_rect(&mut r, style, menu);
+    window_rect(&mut r, style, style_ex, menu);
     (
         std::cmp::max(width as i32 - (r.right - r.left), 64) as u32,
         std::cmp::max(height as i32 - (r.bottom - r.top), 64) as u32,
@@ -808,7 +1260,8 @@ pub fn AdjustWindowRectEx(
     bMenu: bool,
     dwExStyle: Result<WindowStyleEx, u32>,
 ) -> bool {
-    window_rect(lpRect.unwrap(), dwStyle.unwrap(), bMenu);
+    let style_ex = dwExStyle.unwrap_or_else(WindowStyleEx::from_bits_truncate);
+    window_rect(lpRect.unwrap(), dwStyle.unwrap(), style_ex, bMenu);
     true
 }
 
@@ -863,43 +1316,32 @@ pub async fn SetWindowPos(
     cy: i32,
     uFlags: Result<SWP, u32>,
 ) -> bool {
-    let windowpos_addr = machine.state.scratch.alloc(
-        machine.emu.memory.mem(),
-        std::mem::size_of::<WINDOWPOS>() as u32,
-    );
-    machine.mem().put_pod::<WINDOWPOS>(
-        windowpos_addr,
-        WINDOWPOS {
-            hwnd: hWnd,
-            hwndInsertAfter: hWndInsertAfter,
-            x: X,
-            y: Y,
-            cx,
-            cy,
-            flags: uFlags.unwrap(),
-        },
-    );
+    let flags = uFlags.unwrap();
+    let Some(window) = machine.state.user32.windows.get_mut(hWnd) else {
+        set_last_error(machine, ERROR::INVALID_WINDOW_HANDLE);
+        return false;
+    };
+    if !flags.contains(SWP::NOMOVE) {
+        window.x = X;
+        window.y = Y;
+    }
+    if !flags.contains(SWP::NOSIZE) {
+        let (width, height) = client_size_from_window_size(
+            window.style,
+            window.style_ex,
+            window.menu,
+            cx as u32,
+            cy as u32,
+        );
+        window.set_client_size(&mut *machine.host, width, height);
+    }
 
     // A trace of winstream.exe had this sequence of synchronous messages:
     // WM_WINDOWPOSCHANGING
     // (WM_ACTIVATEAPP, WM_NCACTIVATE, WM_ACTIVATE)
     // WM_WINDOWPOSCHANGED
     // -> DefWindowProc calls WM_SIZE and WM_MOVE
-    let msg = MSG {
-        hwnd: hWnd,
-        message: WM::WINDOWPOSCHANGED as u32,
-        wParam: 0,
-        lParam: windowpos_addr,
-        time: 0,
-        pt_x: 0,
-        pt_y: 0,
-    };
-    dispatch_message(machine, &msg).await;
-
-    let window = machine.state.user32.windows.get_mut(hWnd).unwrap();
-    let menu = true; // TODO
-    let (width, height) = client_size_from_window_size(window.style, menu, cx as u32, cy as u32);
-    window.set_client_size(&mut *machine.host, width, height);
+    send_windowposchanged(machine, hWnd, flags).await;
 
     true
 }
@@ -915,8 +1357,10 @@ pub fn MoveWindow(
     bRepaint: bool,
 ) -> bool {
     let window = machine.state.user32.windows.get_mut(hWnd).unwrap();
-    let menu = true; // TODO
-    let (width, height) = client_size_from_window_size(window.style, menu, nWidth, nHeight);
+    window.x = X as i32;
+    window.y = Y as i32;
+    let (width, height) =
+        client_size_from_window_size(window.style, window.style_ex, window.menu, nWidth, nHeight);
     window.set_client_size(&mut *machine.host, width, height);
     true // success
 }
@@ -945,12 +1389,12 @@ pub fn GetWindowRect(machine: &mut Machine, hWnd: HWND, lpRect: Option<&mut RECT
         bottom: window.height as i32,
     };
 
-    let menu = true; // TODO
-    window_rect(&mut result, window.style, menu);
+    window_rect(&mut result, window.style, window.style_ex, window.menu);
 
-    // TODO: this pretends that the window is at 0,0
-    let offset_x = -result.left;
-    let offset_y = -result.top;
+    // Move the rect to the window's position on screen.
+    let (parent_x, parent_y) = parent_origin(&machine.state.user32.windows, window);
+    let offset_x = parent_x + window.x - result.left;
+    let offset_y = parent_y + window.y - result.top;
 
     result.left += offset_x;
     result.right += offset_x;
@@ -963,13 +1407,125 @@ pub fn GetWindowRect(machine: &mut Machine, hWnd: HWND, lpRect: Option<&mut RECT
     true
 }
 
+#[repr(C)]
+#[derive(Debug, Clone)]
+pub struct WINDOWPLACEMENT {
+    pub length: u32,
+    pub flags: u32,
+    pub showCmd: u32,
+    pub ptMinPosition: POINT,
+    pub ptMaxPosition: POINT,
+    pub rcNormalPosition: RECT,
+}
+unsafe impl memory::Pod for WINDOWPLACEMENT {}
+
+#[win32_derive::dllexport]
+pub fn GetWindowPlacement(
+    machine: &mut Machine,
+    hWnd: HWND,
+    lpwndpl: Option<&mut WINDOWPLACEMENT>,
+) -> bool {
+    let Some(window) = machine.state.user32.windows.get(hWnd) else {
+        set_last_error(machine, ERROR::INVALID_WINDOW_HANDLE);
+        return false;
+    };
+    let placement = lpwndpl.unwrap();
+    placement.flags = 0;
+    placement.showCmd = match window.show_state {
+        ShowState::Normal => SW::NORMAL,
+        ShowState::Minimized => SW::SHOWMINIMIZED,
+        ShowState::Maximized => SW::SHOWMAXIMIZED,
+    } as u32;
+    placement.ptMinPosition = POINT {
+        x: -1i32 as u32,
+        y: -1i32 as u32,
+    };
+    placement.ptMaxPosition = POINT {
+        x: -1i32 as u32,
+        y: -1i32 as u32,
+    };
+    placement.rcNormalPosition = match window.show_state {
+        ShowState::Normal => window.frame_rect(),
+        _ => window.normal_rect.clone(),
+    };
+    true
+}
+
+#[win32_derive::dllexport]
+pub async fn SetWindowPlacement(
+    machine: &mut Machine,
+    hWnd: HWND,
+    lpwndpl: Option<&WINDOWPLACEMENT>,
+) -> bool {
+    let placement = lpwndpl.unwrap().clone();
+    let Some(window) = machine.state.user32.windows.get_mut(hWnd) else {
+        set_last_error(machine, ERROR::INVALID_WINDOW_HANDLE);
+        return false;
+    };
+    if window.show_state == ShowState::Normal {
+        window.set_frame_rect(&mut *machine.host, &placement.rcNormalPosition);
+    } else {
+        window.normal_rect = placement.rcNormalPosition.clone();
+    }
+    let cmd = SW::try_from(placement.showCmd).unwrap_or(SW::NORMAL);
+    ShowWindow(machine, hWnd, Ok(cmd)).await;
+    true
+}
+
+/// Screen position of the origin of the client area of the window's parent,
+/// which is what child window positions are relative to.
+fn parent_origin(windows: &Handles<HWND, Window>, window: &Window) -> (i32, i32) {
+    if !window.style.contains(WindowStyle::CHILD) {
+        return (0, 0);
+    }
+    match windows.get(window.parent) {
+        Some(parent) => client_origin(windows, parent),
+        None => (0, 0),
+    }
+}
+
+/// Screen position of the origin of the window's client area.
+pub fn client_origin(windows: &Handles<HWND, Window>, window: &Window) -> (i32, i32) {
+    let mut frame = RECT::default();
+    window_rect(&mut frame, window.style, window.style_ex, window.menu);
+    let (parent_x, parent_y) = parent_origin(windows, window);
+    (
+        parent_x + window.x - frame.left,
+        parent_y + window.y - frame.top,
+    )
+}
+
+/// Like client_origin, but where a null (or unknown) hwnd means the screen.
+fn hwnd_origin(windows: &Handles<HWND, Window>, hwnd: HWND) -> (i32, i32) {
+    match windows.get(hwnd) {
+        Some(window) => client_origin(windows, window),
+        None => (0, 0),
+    }
+}
+
 #[win32_derive::dllexport]
-pub fn GetWindowPlacement(_machine: &mut Machine, hWnd: HWND, lpwndpl: Option<&mut u32>) -> bool {
-    false
+pub fn ClientToScreen(machine: &mut Machine, hWnd: HWND, lpPoint: Option<&mut POINT>) -> bool {
+    let Some(window) = machine.state.user32.windows.get(hWnd) else {
+        set_last_error(machine, ERROR::INVALID_WINDOW_HANDLE);
+        return false;
+    };
+    let (x, y) = client_origin(&machine.state.user32.windows, window);
+    let point = lpPoint.unwrap();
+    point.x = (point.x as i32 + x) as u32;
+    point.y = (point.y as i32 + y) as u32;
+    true
 }
 
 #[win32_derive::dllexport]
-pub fn ClientToScreen(_machine: &mut Machine, hWnd: HWND, lpPoint: Option<&mut POINT>) -> bool {
+pub fn ScreenToClient(machine: &mut Machine, hWnd: HWND, lpPoint: Option<&mut POINT>) -> bool {
+    let Some(window) = machine.state.user32.windows.get(hWnd) else {
+        set_last_error(machine, ERROR::INVALID_WINDOW_HANDLE);
+        return false;
+    };
+    let (x, y) = client_origin(&machine.state.user32.windows, window);
+    let point = lpPoint.unwrap();
+    point.x = (point.x as i32 - x) as u32;
+    point.y = (point.y as i32 - y) as u32;
     true
 }
 
@@ -981,6 +1537,11 @@ pub fn GetWindowDC(_machine: &mut Machine, hWnd: HWND) -> HDC {
 #[win32_derive::dllexport]
 pub fn ReleaseDC(machine: &mut Machine, hwnd: HWND, hdc: HDC) -> bool {
     // Note: there is also DeleteDC; this one is specifically for GetWindowDC/GetDC.
+    if let Some(window) = machine.state.user32.windows.get(hwnd) {
+        if window.dc.map(|dc| dc.to_raw()) == Some(hdc.to_raw()) {
+            return true; // CS_OWNDC DCs live as long as the window
+        }
+    }
     if let Some(dc) = machine.state.gdi32.dcs.remove(hdc) {
         match dc.target {
             winapi::gdi32::DCTarget::Window(dc_hwnd) => {
@@ -1015,13 +1576,28 @@ pub fn GetWindowLongA(_machine: &mut Machine, hWnd: HWND, nIndex: i32) -> i32 {
     }
 }
 
+/// Get a DC for drawing to a window: the window's own DC for CS_OWNDC windows,
+/// which retains its state across calls, or otherwise a fresh one.
+pub fn get_window_dc(machine: &mut Machine, hwnd: HWND) -> HDC {
+    let window = machine.state.user32.windows.get_mut(hwnd).unwrap();
+    if let Some(hdc) = window.dc {
+        return hdc;
+    }
+    let own_dc = window.wndclass.style.contains(CS::OWNDC);
+    let hdc = machine.state.gdi32.new_window_dc(hwnd);
+    if own_dc {
+        machine.state.user32.windows.get_mut(hwnd).unwrap().dc = Some(hdc);
+    }
+    hdc
+}
+
 #[win32_derive::dllexport]
 pub fn GetDC(machine: &mut Machine, hWnd: HWND) -> HDC {
     match hWnd.to_option() {
         Some(hwnd) => {
             let window = machine.state.user32.windows.get(hwnd).unwrap();
             match &window.typ {
-                WindowType::TopLevel(_) => machine.state.gdi32.new_window_dc(hwnd),
+                WindowType::TopLevel(_) => get_window_dc(machine, hwnd),
                 _ => {
                     log::warn!("GetDC for non-top-level window");
                     HDC::null()
@@ -1034,18 +1610,21 @@ pub fn GetDC(machine: &mut Machine, hWnd: HWND) -> HDC {
 
 #[win32_derive::dllexport]
 pub fn MapWindowPoints(
-    _machine: &mut Machine,
+    machine: &mut Machine,
     hWndFrom: HWND,
     hWndTo: HWND,
-    lpPoints: ArrayWithSize<POINT>,
+    lpPoints: ArrayWithSizeMut<POINT>,
 ) -> i32 {
-    if !(hWndFrom.is_null() || hWndTo.is_null()) {
-        todo!()
+    let windows = &machine.state.user32.windows;
+    let (from_x, from_y) = hwnd_origin(windows, hWndFrom);
+    let (to_x, to_y) = hwnd_origin(windows, hWndTo);
+    let delta_x = from_x - to_x;
+    let delta_y = from_y - to_y;
+    for point in lpPoints.to_option().unwrap_or_default() {
+        point.x = (point.x as i32 + delta_x) as u32;
+        point.y = (point.y as i32 + delta_y) as u32;
     }
-    // Mapping a window to/from desktop coords.
-    let delta_x = 0;
-    let delta_y = 0;
-    (delta_y << 16) | delta_x
+    ((delta_y as u16 as i32) << 16) | (delta_x as u16 as i32)
 }
 
 #[win32_derive::dllexport]
@@ -1058,23 +1637,153 @@ pub fn ReleaseCapture(_machine: &mut Machine) -> bool {
     true
 }
 
+/// Read a nul-terminated guest string, UTF-16 if wide.
+fn get_text(mem: Mem, addr: u32, wide: bool) -> String {
+    if addr == 0 {
+        String::new()
+    } else if wide {
+        unsafe { Str16::from_nul_term_ptr(mem, addr) }
+            .unwrap()
+            .to_string()
+    } else {
+        let str = mem.strz(addr, u32::MAX).unwrap_or_else(|err| err.raise());
+        expect_ascii(str).to_string()
+    }
+}
+
+/// Encode window text as the W (UTF-16) or A (ANSI code page) APIs see it, without a
+/// nul, returning the guest bytes and the size of one character unit in them.
+fn encode_text(cp: CodePage, text: &str, wide: bool) -> (Vec<u8>, usize) {
+    let chars = text.encode_utf16().collect::<Vec<_>>();
+    if wide {
+        (chars.iter().flat_map(|c| c.to_le_bytes()).collect(), 2)
+    } else {
+        (cp.encode(&chars, b"?", false).unwrap().0, 1)
+    }
+}
+
+/// The length of window text in characters (UTF-16 units or code page bytes), as
+/// WM_GETTEXTLENGTH and GetWindowTextLength report it.
+fn text_len(cp: CodePage, text: &str, wide: bool) -> u32 {
+    let (bytes, unit) = encode_text(cp, text, wide);
+    (bytes.len() / unit) as u32
+}
+
+/// Copy text into a guest buffer of `max` characters (including the nul terminator),
+/// truncating if needed.  Returns the number of characters copied, excluding the nul.
+fn put_text(mem: Mem, cp: CodePage, text: &str, wide: bool, buf: u32, max: u32) -> u32 {
+    if buf == 0 || max == 0 {
+        return 0;
+    }
+    let (bytes, unit) = encode_text(cp, text, wide);
+    let len = std::cmp::min(bytes.len() / unit, max as usize - 1);
+    let Ok(dst) = mem.try_sub32_mut(buf, ((len + 1) * unit) as u32) else {
+        return 0;
+    };
+    dst[..len * unit].copy_from_slice(&bytes[..len * unit]);
+    dst[len * unit..].fill(0);
+    len as u32
+}
+
+/// Send WM_SETTEXT, which the window procedure passes on to DefWindowProc to store the text.
+async fn set_window_text(machine: &mut Machine, hwnd: HWND, text: &str) -> bool {
+    let Some(window) = machine.state.user32.windows.get_mut(hwnd) else {
+        set_last_error(machine, ERROR::INVALID_WINDOW_HANDLE);
+        return false;
+    };
+    if window.wndclass.wndproc == 0 && window.wndclass.builtin.is_none() {
+        // No window procedure to send the message to.
+        window.set_text(text.to_string());
+        return true;
+    }
+    let wide = window.wndclass.wide;
+    let cp = machine.state.kernel32.code_page;
+    let len = text_len(cp, text, wide) + 1;
+    let size = len * if wide { 2 } else { 1 };
+    let addr = machine.state.scratch.alloc(machine.emu.memory.mem(), size);
+    put_text(machine.emu.memory.mem(), cp, text, wide, addr, len);
+
+    let msg = MSG {
+        hwnd,
+        message: WM::SETTEXT as u32,
+        wParam: 0,
+        lParam: addr,
+        time: 0,
+        pt_x: 0,
+        pt_y: 0,
+    };
+    let ret = dispatch_message(machine, &msg).await;
+    machine.state.scratch.free(machine.emu.memory.mem(), addr);
+    ret != 0
+}
+
 #[win32_derive::dllexport]
-pub fn SetWindowTextA(machine: &mut Machine, hWnd: HWND, lpString: Option<&str>) -> bool {
-    match machine.state.user32.windows.get_mut(hWnd) {
-        Some(window) => {
-            window
-                .expect_toplevel_mut()
-                .host
-                .set_title(lpString.unwrap());
-            true
-        }
+pub async fn SetWindowTextA(machine: &mut Machine, hWnd: HWND, lpString: Option<&str>) -> bool {
+    set_window_text(machine, hWnd, lpString.unwrap_or("")).await
+}
+
+#[win32_derive::dllexport]
+pub async fn SetWindowTextW(machine: &mut Machine, hWnd: HWND, lpString: Option<&Str16>) -> bool {
+    let text = lpString.map(|s| s.to_string()).unwrap_or_default();
+    set_window_text(machine, hWnd, &text).await
+}
+
+// GetWindowText reads the stored text directly, as Windows does for windows of other processes,
+// rather than sending WM_GETTEXT (whose buffer would be in the window's character set).
+
+#[win32_derive::dllexport]
+pub fn GetWindowTextA(machine: &mut Machine, hWnd: HWND, lpString: u32, nMaxCount: i32) -> i32 {
+    let Some(window) = machine.state.user32.windows.get(hWnd) else {
+        set_last_error(machine, ERROR::INVALID_WINDOW_HANDLE);
+        return 0;
+    };
+    put_text(
+        machine.emu.memory.mem(),
+        machine.state.kernel32.code_page,
+        &window.text,
+        false,
+        lpString,
+        nMaxCount as u32,
+    ) as i32
+}
+
+#[win32_derive::dllexport]
+pub fn GetWindowTextW(machine: &mut Machine, hWnd: HWND, lpString: u32, nMaxCount: i32) -> i32 {
+    let Some(window) = machine.state.user32.windows.get(hWnd) else {
+        set_last_error(machine, ERROR::INVALID_WINDOW_HANDLE);
+        return 0;
+    };
+    put_text(
+        machine.emu.memory.mem(),
+        machine.state.kernel32.code_page,
+        &window.text,
+        true,
+        lpString,
+        nMaxCount as u32,
+    ) as i32
+}
+
+fn get_window_text_length(machine: &mut Machine, hWnd: HWND, wide: bool) -> i32 {
+    let cp = machine.state.kernel32.code_page;
+    match machine.state.user32.windows.get(hWnd) {
+        Some(window) => text_len(cp, &window.text, wide) as i32,
         None => {
-            log::error!("SetWindowText of non-window?");
-            false
+            set_last_error(machine, ERROR::INVALID_WINDOW_HANDLE);
+            0
         }
     }
 }
 
+#[win32_derive::dllexport]
+pub fn GetWindowTextLengthA(machine: &mut Machine, hWnd: HWND) -> i32 {
+    get_window_text_length(machine, hWnd, false)
+}
+
+#[win32_derive::dllexport]
+pub fn GetWindowTextLengthW(machine: &mut Machine, hWnd: HWND) -> i32 {
+    get_window_text_length(machine, hWnd, true)
+}
+
 #[win32_derive::dllexport]
 pub fn RegisterWindowMessageW(machine: &mut Machine, lpString: Option<&Str16>) -> u32 {
     let name = lpString.unwrap().to_string();
@@ -1091,3 +1800,210 @@ pub fn GetCapture(machine: &mut Machine) -> HWND {
 pub fn EnableWindow(_machine: &mut Machine, hWnd: HWND, bEnable: bool) -> bool {
     todo!();
 }
+
+bitflags! {
+    pub struct LWA: u32 {
+        const COLORKEY = 0x1;
+        const ALPHA    = 0x2;
+    }
+}
+impl TryFrom<u32> for LWA {
+    type Error = u32;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        LWA::from_bits(value).ok_or(value)
+    }
+}
+
+fn layered_mut(windows: &mut Handles<HWND, Window>, hwnd: HWND) -> Option<&mut WindowTopLevel> {
+    let window = windows.get_mut(hwnd)?;
+    match &mut window.typ {
+        WindowType::TopLevel(top) if top.layered.is_some() => Some(top),
+        _ => None,
+    }
+}
+
+#[win32_derive::dllexport]
+pub fn SetLayeredWindowAttributes(
+    machine: &mut Machine,
+    hwnd: HWND,
+    crKey: COLORREF,
+    bAlpha: u8,
+    dwFlags: Result<LWA, u32>,
+) -> bool {
+    let flags = dwFlags.unwrap();
+    let Some(top) = layered_mut(&mut machine.state.user32.windows, hwnd) else {
+        return false; // not a layered window
+    };
+    let layered = top.layered.as_mut().unwrap();
+    layered.color_key = if flags.contains(LWA::COLORKEY) {
+        Some(crKey)
+    } else {
+        None
+    };
+    layered.alpha = if flags.contains(LWA::ALPHA) {
+        bAlpha
+    } else {
+        0xFF
+    };
+    let alpha = layered.alpha;
+    top.host.set_alpha(alpha);
+    top.flush_pixels(machine.emu.memory.mem());
+    true
+}
+
+#[win32_derive::dllexport]
+pub fn GetLayeredWindowAttributes(
+    machine: &mut Machine,
+    hwnd: HWND,
+    pcrKey: Option<&mut u32>,
+    pbAlpha: Option<&mut u8>,
+    pdwFlags: Option<&mut u32>,
+) -> bool {
+    let Some(top) = layered_mut(&mut machine.state.user32.windows, hwnd) else {
+        return false; // not a layered window
+    };
+    let layered = top.layered.as_ref().unwrap();
+    let mut flags = LWA::empty();
+    if let Some(key) = layered.color_key {
+        flags |= LWA::COLORKEY;
+        if let Some(pcrKey) = pcrKey {
+            let [r, g, b, _] = key.to_pixel();
+            *pcrKey = u32::from_le_bytes([r, g, b, 0]);
+        }
+    }
+    if layered.alpha != 0xFF {
+        flags |= LWA::ALPHA;
+    }
+    if let Some(pbAlpha) = pbAlpha {
+        *pbAlpha = layered.alpha;
+    }
+    if let Some(pdwFlags) = pdwFlags {
+        *pdwFlags = flags.bits();
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_lengths_count_characters() {
+        let cp = CodePage::Windows1252;
+        assert_eq!(text_len(cp, "héllo", false), 5);
+        assert_eq!(text_len(cp, "héllo", true), 5);
+        // Outside the BMP: a surrogate pair in UTF-16, and the default char in ANSI.
+        assert_eq!(text_len(cp, "a😀", true), 3);
+        assert_eq!(text_len(cp, "a😀", false), 2);
+    }
+
+    #[test]
+    fn put_text_truncates_in_characters() {
+        let cp = CodePage::Windows1252;
+        let buf = [0xFFu8; 16];
+        let mem = Mem::from_slice(&buf);
+        // Address 0 would be a null buffer.
+        assert_eq!(put_text(mem, cp, "ééé", false, 4, 3), 2);
+        assert_eq!(&buf[4..8], &[0xE9, 0xE9, 0, 0xFF]);
+
+        let buf = [0xFFu8; 16];
+        let mem = Mem::from_slice(&buf);
+        assert_eq!(put_text(mem, cp, "ééé", true, 4, 3), 2);
+        assert_eq!(&buf[4..12], &[0xE9, 0, 0xE9, 0, 0, 0, 0xFF, 0xFF]);
+    }
+
+    #[cfg(feature = "x86-emu")]
+    mod guest {
+        use super::*;
+        use crate::test_guest::{builtin, new_machine, run, Code, Log};
+        use memory::Pod;
+
+        #[test]
+        fn enum_windows() {
+            let (mut machine, _host) = new_machine();
+            let create = builtin(&mut machine, "user32.dll", "CreateWindowExA");
+            let enum_windows = builtin(&mut machine, "user32.dll", "EnumWindows");
+            let enum_children = builtin(&mut machine, "user32.dll", "EnumChildWindows");
+            let log = Log::alloc(&mut machine, 0x1000);
+            let mut code = Code::alloc(&mut machine, 0x400);
+            let record = code.base + 0x300;
+            let stop = code.base + 0x340;
+            let class_name = code.base + 0x380;
+            // Created windows' handles, in order.
+            let hwnds = code.base + 0x3C0;
+
+            // Two top-level windows, the first with a child that has a child of its own,
+            // the second with a child.
+            for (i, parent) in [None, Some(0), Some(1), None, Some(3)]
+                .into_iter()
+                .enumerate()
+            {
+                code.push(0).push(0).push(0);
+                match parent {
+                    Some(parent) => code.emit(&[0xFF, 0x35]).emit32(hwnds + parent * 4), // push [hwnd]
+                    None => code.push(0),
+                };
+                code.push(10).push(10).push(0).push(0);
+                code.push(if parent.is_some() {
+                    WindowStyle::CHILD.bits()
+                } else {
+                    0
+                })
+                .push(0)
+                .push(class_name)
+                .push(0)
+                .call(create)
+                .emit(&[0xA3]) // mov [hwnd], eax
+                .emit32(hwnds + i as u32 * 4);
+            }
+            code.push(1)
+                .push(record)
+                .emit(&[0xFF, 0x35]) // push [hwnd]
+                .emit32(hwnds)
+                .call(enum_children)
+                .push(2)
+                .push(record)
+                .call(enum_windows)
+                .push(3)
+                .push(record)
+                .push(0)
+                .call(enum_children)
+                .push(4)
+                .push(stop)
+                .emit(&[0xFF, 0x35]) // push [hwnd]
+                .emit32(hwnds)
+                .call(enum_children)
+                .exit_with(&mut machine, 0)
+                .at(0x300)
+                .recorder(&log, 2, 1)
+                .at(0x340)
+                .recorder(&log, 2, 0)
+                .at(0x380)
+                .emit(b"test\0");
+            code.start(&mut machine);
+            let wndclass = WNDCLASSA {
+                lpfnWndProc: builtin(&mut machine, "user32.dll", "DefWindowProcA"),
+                lpszClassName: class_name,
+                ..WNDCLASSA::zeroed()
+            };
+            assert_ne!(RegisterClassA(&mut machine, Some(&wndclass)), 0);
+            assert_eq!(run(&mut machine), 1);
+
+            let mem = machine.mem();
+            let w = (0..5)
+                .map(|i| mem.get_pod::<u32>(hwnds + i * 4))
+                .collect::<Vec<_>>();
+            assert!(w.iter().all(|&hwnd| hwnd != 0));
+            assert_eq!(
+                log.read(&machine),
+                [
+                    w[1], 1, w[2], 1, // children of the first window, down to grandchildren
+                    w[0], 2, w[3], 2, // top-level windows
+                    w[0], 3, w[3], 3, // children of the desktop, the same
+                    w[1], 4, // stopped after the first
+                ]
+            );
+        }
+    }
+}
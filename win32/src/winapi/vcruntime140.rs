@@ -1,28 +1,45 @@
+//! The Visual C++ runtime: memory functions and C++ exception handling.
+//!
+//! MSVC C++ exceptions are SEH exceptions with code EH_EXCEPTION_NUMBER that carry the
+//! thrown object and its type information.  Each function with try blocks or objects to
+//! destroy registers a frame whose handler (a thunk passing the function's FuncInfo)
+//! calls __CxxFrameHandler.
+
 #![allow(non_snake_case)]
+#![allow(non_upper_case_globals)]
 
-use crate::machine::Machine;
-use memory::{Extensions, ExtensionsMut};
+use crate::{
+    machine::Machine,
+    winapi::{kernel32, ucrtbase},
+};
+use memory::{Extensions, ExtensionsMut, Pod};
 
 #[win32_derive::dllexport(cdecl)]
 pub fn memcpy(machine: &mut Machine, dst: u32, src: u32, len: u32) -> u32 {
     // TODO: this probably violates Rust rules around aliasing, if callers expect memmove.
-    machine
-        .mem()
-        .sub32_mut(dst, len)
-        .copy_from_slice(machine.mem().sub32(src, len));
+    let mem = machine.mem();
+    let src = mem.try_sub32(src, len).unwrap_or_else(|err| err.raise());
+    mem.try_sub32_mut(dst, len)
+        .unwrap_or_else(|err| err.raise())
+        .copy_from_slice(src);
     dst
 }
 
 #[win32_derive::dllexport(cdecl)]
 pub fn memset(machine: &mut Machine, dst: u32, val: u32, len: u32) -> u32 {
-    machine.mem().sub32_mut(dst, len).fill(val as u8);
+    machine
+        .mem()
+        .try_sub32_mut(dst, len)
+        .unwrap_or_else(|err| err.raise())
+        .fill(val as u8);
     dst
 }
 
 #[win32_derive::dllexport(cdecl)]
 pub fn memcmp(machine: &mut Machine, lhs: u32, rhs: u32, len: u32) -> u32 {
-    let left = machine.mem().sub32(lhs, len);
-    let right = machine.mem().sub32(rhs, len);
+    let mem = machine.mem();
+    let left = mem.try_sub32(lhs, len).unwrap_or_else(|err| err.raise());
+    let right = mem.try_sub32(rhs, len).unwrap_or_else(|err| err.raise());
     match left.cmp(right) {
         std::cmp::Ordering::Less => -1i32 as u32,
         std::cmp::Ordering::Equal => 0,
@@ -30,7 +47,633 @@ pub fn memcmp(machine: &mut Machine, lhs: u32, rhs: u32, len: u32) -> u32 {
     }
 }
 
+/// Exception code of C++ exceptions: 'msc' | 0xE0000000.
+pub const EH_EXCEPTION_NUMBER: u32 = 0xE06D_7363;
+/// ExceptionInformation[0] of C++ exceptions, identifying the compiler version.
+const EH_MAGIC_NUMBER1: u32 = 0x1993_0520;
+
+/// The frame registered by a function with C++ exception handling.
+/// The function's ebp is just past it, and the saved esp just below it.
+#[repr(C)]
+#[derive(Clone)]
+struct EHRegistrationNode {
+    pNext: u32,
+    frameHandler: u32,
+    /// Index into the unwind map of the objects constructed so far.
+    state: i32,
+}
+unsafe impl Pod for EHRegistrationNode {}
+
+#[repr(C)]
+#[derive(Clone)]
+struct FuncInfo {
+    magicNumber: u32,
+    maxState: i32,
+    pUnwindMap: u32,
+    nTryBlocks: u32,
+    pTryBlockMap: u32,
+    nIPMapEntries: u32,
+    pIPtoStateMap: u32,
+}
+unsafe impl Pod for FuncInfo {}
+
+#[repr(C)]
+#[derive(Clone)]
+struct UnwindMapEntry {
+    toState: i32,
+    /// Funclet destroying the object constructed in this state, if any.
+    action: u32,
+}
+unsafe impl Pod for UnwindMapEntry {}
+
+#[repr(C)]
+#[derive(Clone)]
+struct TryBlockMapEntry {
+    tryLow: i32,
+    tryHigh: i32,
+    catchHigh: i32,
+    nCatches: u32,
+    pHandlerArray: u32,
+}
+unsafe impl Pod for TryBlockMapEntry {}
+
+/// One catch clause.
+#[repr(C)]
+#[derive(Clone)]
+struct HandlerType {
+    adjectives: u32,
+    /// TypeDescriptor of the caught type, or 0 for catch(...).
+    pType: u32,
+    /// Offset from ebp of the catch object.
+    dispCatchObj: i32,
+    /// Funclet of the catch block, returning the address to continue at.
+    addressOfHandler: u32,
+}
+unsafe impl Pod for HandlerType {}
+
+const HT_IsReference: u32 = 0x8;
+
+#[repr(C)]
+#[derive(Clone)]
+struct ThrowInfo {
+    attributes: u32,
+    /// Destructor of the thrown object.
+    pmfnUnwind: u32,
+    pForwardCompat: u32,
+    pCatchableTypeArray: u32,
+}
+unsafe impl Pod for ThrowInfo {}
+
+/// One of the types (the thrown type and its bases) that a thrown object can be caught as.
+#[repr(C)]
+#[derive(Clone)]
+struct CatchableType {
+    properties: u32,
+    pType: u32,
+    /// Adjustment of the object pointer to this type: a pointer-to-member displacement.
+    mdisp: i32,
+    pdisp: i32,
+    vdisp: i32,
+    sizeOrOffset: u32,
+    copyFunction: u32,
+}
+unsafe impl Pod for CatchableType {}
+
+const CT_IsSimpleType: u32 = 0x1;
+const CT_HasVirtualBase: u32 = 0x4;
+
+/// An exception whose catch block is running, which `throw;` rethrows.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CaughtException {
+    object: u32,
+    throw_info: u32,
+    /// esp when the catch block was entered.
+    esp: u32,
+}
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+pub struct State {
+    /// Innermost last.
+    caught: Vec<CaughtException>,
+}
+
+#[win32_derive::dllexport(cdecl)]
+pub async fn _CxxThrowException(machine: &mut Machine, pExceptionObject: u32, pThrowInfo: u32) {
+    #[cfg(feature = "x86-emu")]
+    {
+        let (object, throw_info) = if pThrowInfo != 0 {
+            (pExceptionObject, pThrowInfo)
+        } else {
+            // `throw;` rethrows the exception being handled.
+            let esp = machine.emu.x86.cpu().regs.get32(x86::Register::ESP);
+            let caught = &mut machine.state.vcruntime140.caught;
+            // Catch blocks that were left by an exception never finished.
+            while matches!(caught.last(), Some(c) if c.esp < esp) {
+                caught.pop();
+            }
+            let Some(current) = caught.last() else {
+                // "If no exception is currently being handled ... terminate() is called."
+                log::error!("rethrow with no current exception");
+                machine.exit(3);
+                return;
+            };
+            (current.object, current.throw_info)
+        };
+        let context = kernel32::caller_context(machine, 0);
+        let record = kernel32::EXCEPTION_RECORD::new(
+            EH_EXCEPTION_NUMBER,
+            kernel32::EXCEPTION_NONCONTINUABLE,
+            context.Eip,
+            &[EH_MAGIC_NUMBER1, object, throw_info],
+        );
+        kernel32::dispatch_exception(machine, record, context).await;
+    }
+
+    #[cfg(not(feature = "x86-emu"))]
+    {
+        // Nothing can catch it, so it's as if nothing tried: terminate() is called.
+        _ = (pExceptionObject, pThrowInfo);
+        log::error!("_CxxThrowException: unimplemented; terminating");
+        machine.exit(3);
+    }
+}
+
+/// Call a funclet of the function whose frame is at frame, which expects its ebp.
+#[cfg(feature = "x86-emu")]
+async fn call_funclet(machine: &mut Machine, funclet: u32, frame: u32) -> u32 {
+    let ebp = frame + 12;
+    machine
+        .call_x86_with_regs(funclet, vec![], &[(x86::Register::EBP, ebp)])
+        .await
+}
+
+/// Destroy the objects of the frame's function until it reaches target_state.
+#[cfg(feature = "x86-emu")]
+async fn unwind_to_state(
+    machine: &mut Machine,
+    frame: u32,
+    func_info: &FuncInfo,
+    target_state: i32,
+) {
+    loop {
+        let state = machine.mem().get_pod::<EHRegistrationNode>(frame).state;
+        if state == target_state || state < 0 {
+            break;
+        }
+        if state >= func_info.maxState {
+            log::error!("bad C++ EH state {state} in frame {frame:x}");
+            break;
+        }
+        let entry = machine
+            .mem()
+            .get_pod::<UnwindMapEntry>(func_info.pUnwindMap + state as u32 * 8);
+        // Update the state first, so an exception in the destructor doesn't rerun it.
+        machine
+            .mem()
+            .get_aligned_ref_mut::<EHRegistrationNode>(frame)
+            .state = entry.toState;
+        if entry.action != 0 {
+            call_funclet(machine, entry.action, frame).await;
+        }
+    }
+}
+
+/// Adjust a pointer to a thrown object to point at the base class given by a CatchableType.
+#[cfg(feature = "x86-emu")]
+fn adjust_pointer(machine: &Machine, object: u32, ct: &CatchableType) -> u32 {
+    let mem = machine.mem();
+    let mut ptr = object.wrapping_add(ct.mdisp as u32);
+    if ct.pdisp >= 0 {
+        let vbtable = mem.get_pod::<u32>(object.wrapping_add(ct.pdisp as u32));
+        let vbase = mem.get_pod::<i32>(vbtable.wrapping_add(ct.vdisp as u32));
+        ptr = ptr.wrapping_add(vbase as u32).wrapping_add(ct.pdisp as u32);
+    }
+    ptr
+}
+
+/// Compare two TypeDescriptors, which may be duplicated across modules.
+#[cfg(feature = "x86-emu")]
+fn same_type(machine: &Machine, a: u32, b: u32) -> bool {
+    // The decorated name follows the vftable pointer and a spare field.
+    let mem = machine.mem();
+    let name = |addr: u32| {
+        mem.strz(addr + 8, u32::MAX)
+            .unwrap_or_else(|err| err.raise())
+    };
+    a == b || name(a) == name(b)
+}
+
+/// Find the CatchableType of the thrown object, if any, that a catch clause accepts.
+#[cfg(feature = "x86-emu")]
+fn match_catch(machine: &Machine, handler: &HandlerType, throw_info: u32) -> Option<CatchableType> {
+    let mem = machine.mem();
+    let throw_info = mem.get_pod::<ThrowInfo>(throw_info);
+    let count = mem.get_pod::<u32>(throw_info.pCatchableTypeArray);
+    (0..count)
+        .map(|i| mem.get_pod::<u32>(throw_info.pCatchableTypeArray + 4 + i * 4))
+        .map(|addr| mem.get_pod::<CatchableType>(addr))
+        .find(|ct| same_type(machine, handler.pType, ct.pType))
+}
+
+/// Initialize a catch clause's catch object from the thrown object.
+#[cfg(feature = "x86-emu")]
+async fn copy_catch_object(
+    machine: &mut Machine,
+    frame: u32,
+    handler: &HandlerType,
+    object: u32,
+    ct: &CatchableType,
+) {
+    let dest = (frame + 12).wrapping_add(handler.dispCatchObj as u32);
+    let src = adjust_pointer(machine, object, ct);
+    if handler.adjectives & HT_IsReference != 0 {
+        machine.mem().put_pod::<u32>(dest, src);
+    } else if ct.properties & CT_IsSimpleType != 0 || ct.copyFunction == 0 {
+        let mem = machine.mem();
+        let src = mem
+            .try_sub32(src, ct.sizeOrOffset)
+            .unwrap_or_else(|err| err.raise());
+        mem.try_sub32_mut(dest, ct.sizeOrOffset)
+            .unwrap_or_else(|err| err.raise())
+            .copy_from_slice(src);
+    } else {
+        // The copy constructor of a class with virtual bases takes a flag to construct them.
+        let args = if ct.properties & CT_HasVirtualBase != 0 {
+            vec![src, 1]
+        } else {
+            vec![src]
+        };
+        machine
+            .call_x86_with_regs(ct.copyFunction, args, &[(x86::Register::ECX, dest)])
+            .await;
+    }
+}
+
+/// Run a catch block for an exception thrown from within its try block, then continue
+/// after the try/catch, never returning here.
+#[cfg(feature = "x86-emu")]
+async fn catch_exception(
+    machine: &mut Machine,
+    exception_record: u32,
+    frame: u32,
+    func_info: &FuncInfo,
+    try_block: &TryBlockMapEntry,
+    handler: &HandlerType,
+    thrown: Option<(u32, u32, CatchableType)>,
+) {
+    // Unwind the frames within this one, then this function's objects within the try block.
+    kernel32::unwind(machine, frame, exception_record).await;
+    unwind_to_state(machine, frame, func_info, try_block.tryLow).await;
+
+    if let Some((object, _, ct)) = &thrown {
+        if handler.pType != 0 && handler.dispCatchObj != 0 {
+            copy_catch_object(machine, frame, handler, *object, ct).await;
+        }
+    }
+
+    machine
+        .mem()
+        .get_aligned_ref_mut::<EHRegistrationNode>(frame)
+        .state = try_block.tryHigh + 1;
+    let esp = machine.emu.x86.cpu().regs.get32(x86::Register::ESP);
+    if let Some((object, throw_info, _)) = thrown {
+        machine.state.vcruntime140.caught.push(CaughtException {
+            object,
+            throw_info,
+            esp,
+        });
+    }
+    let continuation = call_funclet(machine, handler.addressOfHandler, frame).await;
+    if let Some((object, throw_info, _)) = thrown {
+        machine.state.vcruntime140.caught.pop();
+        let unwind = machine.mem().get_pod::<ThrowInfo>(throw_info).pmfnUnwind;
+        if unwind != 0 {
+            machine
+                .call_x86_with_regs(unwind, vec![], &[(x86::Register::ECX, object)])
+                .await;
+        }
+    }
+
+    let saved_esp = machine.mem().get_pod::<u32>(frame - 4);
+    let regs = &mut machine.emu.x86.cpu_mut().regs;
+    regs.set32(x86::Register::ESP, saved_esp);
+    regs.set32(x86::Register::EBP, frame + 12);
+    regs.eip = continuation;
+}
+
+#[cfg(feature = "x86-emu")]
+async fn frame_handler(
+    machine: &mut Machine,
+    exception_record: u32,
+    frame: u32,
+    func_info: u32,
+) -> u32 {
+    let record = machine
+        .mem()
+        .get_pod::<kernel32::EXCEPTION_RECORD>(exception_record);
+    let func_info = machine.mem().get_pod::<FuncInfo>(func_info);
+    if record.ExceptionFlags & (kernel32::EXCEPTION_UNWINDING | kernel32::EXCEPTION_EXIT_UNWIND)
+        != 0
+    {
+        unwind_to_state(machine, frame, &func_info, -1).await;
+        return kernel32::ExceptionContinueSearch;
+    }
+
+    // Non-C++ exceptions are only caught by catch(...).
+    let throw_info = if record.ExceptionCode == EH_EXCEPTION_NUMBER
+        && record.NumberParameters >= 3
+        && record.ExceptionInformation[0] & !0x3 == EH_MAGIC_NUMBER1
+    {
+        Some((
+            record.ExceptionInformation[1],
+            record.ExceptionInformation[2],
+        ))
+    } else {
+        None
+    };
+
+    let state = machine.mem().get_pod::<EHRegistrationNode>(frame).state;
+    for i in 0..func_info.nTryBlocks {
+        let try_block = machine
+            .mem()
+            .get_pod::<TryBlockMapEntry>(func_info.pTryBlockMap + i * 20);
+        if state < try_block.tryLow || state > try_block.tryHigh {
+            continue;
+        }
+        for j in 0..try_block.nCatches {
+            let handler = machine
+                .mem()
+                .get_pod::<HandlerType>(try_block.pHandlerArray + j * 16);
+            let thrown = match throw_info {
+                Some((object, throw_info)) => {
+                    let ct = if handler.pType == 0 {
+                        // catch(...) doesn't look at the type, so any will do.
+                        let throw_info = machine.mem().get_pod::<ThrowInfo>(throw_info);
+                        let first = machine
+                            .mem()
+                            .get_pod::<u32>(throw_info.pCatchableTypeArray + 4);
+                        Some(machine.mem().get_pod::<CatchableType>(first))
+                    } else {
+                        match_catch(machine, &handler, throw_info)
+                    };
+                    match ct {
+                        Some(ct) => Some((object, throw_info, ct)),
+                        None => continue,
+                    }
+                }
+                None if handler.pType == 0 => None,
+                None => continue,
+            };
+            catch_exception(
+                machine,
+                exception_record,
+                frame,
+                &func_info,
+                &try_block,
+                &handler,
+                thrown,
+            )
+            .await;
+            return kernel32::ExceptionContinueSearch;
+        }
+    }
+    kernel32::ExceptionContinueSearch
+}
+
+#[win32_derive::dllexport(cdecl)]
+pub async fn __CxxFrameHandler(
+    machine: &mut Machine,
+    pExcept: u32,
+    pRN: u32,
+    _pContext: u32,
+    _pDC: u32,
+) -> u32 {
+    #[cfg(feature = "x86-emu")]
+    {
+        // The function's handler thunk passes its FuncInfo in eax.
+        let func_info = machine.emu.x86.cpu().regs.get32(x86::Register::EAX);
+        frame_handler(machine, pExcept, pRN, func_info).await
+    }
+
+    #[cfg(not(feature = "x86-emu"))]
+    {
+        _ = (machine, pExcept, pRN);
+        log::warn!("__CxxFrameHandler: unimplemented");
+        kernel32::ExceptionContinueSearch
+    }
+}
+
+#[win32_derive::dllexport(cdecl)]
+pub async fn __CxxFrameHandler3(
+    machine: &mut Machine,
+    pExcept: u32,
+    pRN: u32,
+    pContext: u32,
+    pDC: u32,
+) -> u32 {
+    __CxxFrameHandler(machine, pExcept, pRN, pContext, pDC).await
+}
+
+/// _except_handler4, which is linked into the exe, calls this with its security cookie.
 #[win32_derive::dllexport(cdecl)]
-pub fn _CxxThrowException(_machine: &mut Machine, pExceptionObject: u32, pThrowInfo: u32) -> u32 {
-    panic!("exception");
+pub async fn _except_handler4_common(
+    machine: &mut Machine,
+    CookiePointer: u32,
+    _CookieCheckFunction: u32,
+    ExceptionRecord: u32,
+    EstablisherFrame: u32,
+    ContextRecord: u32,
+    _DispatcherContext: u32,
+) -> u32 {
+    #[cfg(feature = "x86-emu")]
+    {
+        // The scope table pointer is xored with the cookie, and its entries follow
+        // a header of GS cookie offsets (which we don't check).
+        let mem = machine.mem();
+        let scope_table =
+            mem.get_pod::<u32>(EstablisherFrame + 8) ^ mem.get_pod::<u32>(CookiePointer);
+        ucrtbase::except_handler(
+            machine,
+            ExceptionRecord,
+            EstablisherFrame,
+            ContextRecord,
+            scope_table + 16,
+        )
+        .await
+    }
+
+    #[cfg(not(feature = "x86-emu"))]
+    {
+        _ = (
+            machine,
+            CookiePointer,
+            ExceptionRecord,
+            EstablisherFrame,
+            ContextRecord,
+        );
+        log::warn!("_except_handler4_common: unimplemented");
+        kernel32::ExceptionContinueSearch
+    }
+}
+
+#[cfg(all(test, feature = "x86-emu"))]
+mod tests {
+    use super::*;
+    use crate::test_guest::{builtin, new_machine, run, Code};
+
+    /// The exit code when the catch block didn't initialize its catch object.
+    const UNSET: u32 = 0x77;
+
+    /// Throw the int 42 from a function laid out as MSVC compiles
+    /// try { throw 42; } catch (T x) { return x; }, for T given by its decorated name
+    /// (None for catch(...)), and return the process's exit code.
+    fn throw_int(catch_type: Option<&str>) -> u32 {
+        let (mut machine, _host) = new_machine();
+        let throw = builtin(&mut machine, "vcruntime140.dll", "_CxxThrowException");
+        let frame_handler = builtin(&mut machine, "vcruntime140.dll", "__CxxFrameHandler3");
+        let mut code = Code::alloc(&mut machine, 0x1000);
+        let func = code.base + 0x40;
+        let catch_block = code.base + 0x100;
+        let continuation = code.base + 0x140;
+        let epilogue = code.base + 0x160;
+        let thunk = code.base + 0x1C0;
+        let object = code.base + 0x200;
+        let caught_type = code.base + 0x210;
+        let thrown_type = code.base + 0x230;
+        let catchable_type = code.base + 0x250;
+        let catchable_types = code.base + 0x270;
+        let throw_info = code.base + 0x280;
+        let handler = code.base + 0x290;
+        let try_block = code.base + 0x2A0;
+        let unwind_map = code.base + 0x2C0;
+        let func_info = code.base + 0x2D0;
+        let result = code.base + 0x2F0;
+
+        code.call(func).exit_with(&mut machine, 0);
+
+        code.at(0x40)
+            .emit(&[0x55]) // push ebp
+            .emit(&[0x8B, 0xEC]) // mov ebp, esp
+            .emit(&[0x6A, 0xFF]) // push -1  (state)
+            .push(thunk)
+            .emit(&[0x64, 0xFF, 0x35, 0, 0, 0, 0]) // push dword fs:[0]
+            .emit(&[0x64, 0x89, 0x25, 0, 0, 0, 0]) // mov fs:[0], esp
+            .emit(&[0x83, 0xEC, 0x08]) // sub esp, 8  (saved esp, catch object)
+            .emit(&[0xC7, 0x45, 0xEC]) // mov dword [ebp-14h], UNSET
+            .emit32(UNSET)
+            .emit(&[0x53]) // push ebx
+            .emit(&[0x89, 0x65, 0xF0]) // mov [ebp-10h], esp
+            .emit(&[0xC7, 0x45, 0xFC, 0, 0, 0, 0]) // mov dword [ebp-4], 0
+            .push(throw_info)
+            .push(object)
+            .call(throw)
+            // Not reached.
+            .emit(&[0xB8, 1, 0, 0, 0]) // mov eax, 1
+            .emit(&[0xB9]) // mov ecx, epilogue
+            .emit32(epilogue)
+            .emit(&[0xFF, 0xE1]); // jmp ecx
+
+        // The catch block funclet returns where to continue.
+        code.at(0x100)
+            .emit(&[0x8B, 0x45, 0xEC]) // mov eax, [ebp-14h]
+            .emit(&[0xA3]) // mov [result], eax
+            .emit32(result)
+            .emit(&[0xB8]) // mov eax, continuation
+            .emit32(continuation)
+            .emit(&[0xC3]); // ret
+
+        code.at(0x140)
+            .emit(&[0xC7, 0x45, 0xFC, 0xFF, 0xFF, 0xFF, 0xFF]) // mov dword [ebp-4], -1
+            .emit(&[0xA1]) // mov eax, [result]
+            .emit32(result)
+            .emit(&[0xB9]) // mov ecx, epilogue
+            .emit32(epilogue)
+            .emit(&[0xFF, 0xE1]); // jmp ecx
+
+        code.at(0x160)
+            .emit(&[0x8B, 0x4D, 0xF4]) // mov ecx, [ebp-0Ch]
+            .emit(&[0x64, 0x89, 0x0D, 0, 0, 0, 0]) // mov fs:[0], ecx
+            .emit(&[0x5B]) // pop ebx
+            .emit(&[0x8B, 0xE5]) // mov esp, ebp
+            .emit(&[0x5D]) // pop ebp
+            .emit(&[0xC3]); // ret
+
+        code.at(0x1C0)
+            .emit(&[0xB8]) // mov eax, func_info
+            .emit32(func_info)
+            .emit(&[0xB9]) // mov ecx, __CxxFrameHandler3
+            .emit32(frame_handler)
+            .emit(&[0xFF, 0xE1]); // jmp ecx
+
+        code.at(0x200).emit32(42);
+        // TypeDescriptors: vftable, spare, decorated name.
+        code.at(0x210)
+            .emit32(0)
+            .emit32(0)
+            .emit(catch_type.unwrap_or("").as_bytes())
+            .emit(&[0]);
+        code.at(0x230).emit32(0).emit32(0).emit(b".H\0");
+        code.at(0x250)
+            .emit32(CT_IsSimpleType)
+            .emit32(thrown_type)
+            .emit32(0) // mdisp
+            .emit32(-1i32 as u32) // pdisp
+            .emit32(0) // vdisp
+            .emit32(4) // size
+            .emit32(0); // copyFunction
+        code.at(0x270).emit32(1).emit32(catchable_type);
+        code.at(0x280)
+            .emit32(0)
+            .emit32(0)
+            .emit32(0)
+            .emit32(catchable_types);
+        let (ptype, disp) = match catch_type {
+            Some(_) => (caught_type, -0x14i32),
+            None => (0, 0),
+        };
+        code.at(0x290)
+            .emit32(0)
+            .emit32(ptype)
+            .emit32(disp as u32)
+            .emit32(catch_block);
+        code.at(0x2A0)
+            .emit32(0) // tryLow
+            .emit32(0) // tryHigh
+            .emit32(1) // catchHigh
+            .emit32(1)
+            .emit32(handler);
+        code.at(0x2C0)
+            .emit32(-1i32 as u32)
+            .emit32(0)
+            .emit32(-1i32 as u32)
+            .emit32(0);
+        code.at(0x2D0)
+            .emit32(EH_MAGIC_NUMBER1)
+            .emit32(2)
+            .emit32(unwind_map)
+            .emit32(1)
+            .emit32(try_block)
+            .emit32(0)
+            .emit32(0);
+        code.at(0x2F0).emit32(0);
+
+        code.start(&mut machine);
+        run(&mut machine)
+    }
+
+    #[test]
+    fn catch_by_type() {
+        assert_eq!(throw_int(Some(".H")), 42);
+    }
+
+    #[test]
+    fn catch_all() {
+        assert_eq!(throw_int(None), UNSET);
+    }
+
+    #[test]
+    fn uncaught_type() {
+        // A catch(double) doesn't match, so the process dies of the exception.
+        assert_eq!(throw_int(Some(".N")), EH_EXCEPTION_NUMBER);
+    }
 }
@@ -17,6 +17,7 @@ use crate::host;
 #[derive(Copy, Clone, Debug)]
 pub enum MMRESULT {
     MMSYSERR_NOERROR = 0,
+    MMSYSERR_INVALPARAM = 11,
 }
 impl super::stack_args::ToX86 for MMRESULT {
     fn to_raw(&self) -> u32 {
@@ -24,7 +25,10 @@ impl super::stack_args::ToX86 for MMRESULT {
     }
 }
 
-#[derive(Default)]
+#[derive(Default, serde::Serialize, serde::Deserialize)]
 pub struct State {
+    #[serde(skip)]
     pub audio: Option<Box<dyn host::Audio>>,
+    /// The sample rate audio was opened with, to reopen it after loading a save state.
+    pub audio_rate: u32,
 }
@@ -19,7 +19,7 @@ pub fn timeKillEvent(_machine: &mut Machine, uTimerID: u32) -> u32 {
 
 #[win32_derive::dllexport]
 pub fn timeGetTime(machine: &mut Machine) -> u32 {
-    machine.host.ticks()
+    machine.ticks()
 }
 
 const TIMERR_NOERROR: u32 = 0;
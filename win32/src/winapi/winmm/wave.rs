@@ -96,6 +96,7 @@ pub fn waveOutOpen(
 
     let fmt = pwfx.unwrap();
     machine.state.winmm.audio = Some(machine.host.init_audio(fmt.nSamplesPerSec));
+    machine.state.winmm.audio_rate = fmt.nSamplesPerSec;
 
     MMRESULT::MMSYSERR_NOERROR
 }
@@ -249,11 +250,14 @@ pub fn waveOutWrite(
 ) -> MMRESULT {
     assert_eq!(cbwh, std::mem::size_of::<WAVEHDR>() as u32);
     let hdr = pwh.unwrap();
-    let buf = machine
+    let Ok(buf) = machine
         .emu
         .memory
         .mem()
-        .sub32(hdr.lpData, hdr.dwBufferLength);
+        .try_sub32(hdr.lpData, hdr.dwBufferLength)
+    else {
+        return MMRESULT::MMSYSERR_INVALPARAM;
+    };
     machine.state.winmm.audio.as_mut().unwrap().write(buf);
     MMRESULT::MMSYSERR_NOERROR
 }
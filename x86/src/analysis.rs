@@ -0,0 +1,72 @@
+//! Static analysis of guest code, used to find function boundaries before execution.
+
+use iced_x86::{FlowControl, OpKind};
+use memory::Mem;
+use std::{collections::BTreeSet, collections::HashSet, ops::Range};
+
+/// Recursive-descent pass over code, starting from the given entry points.
+/// Follows jumps and calls that land within `code`, returning the set of addresses
+/// that are the targets of direct calls (plus the entry points themselves).
+///
+/// This is necessarily approximate: indirect calls/jumps are not followed, and
+/// data mixed into code (or packed executables) may produce spurious results.
+pub fn find_functions(mem: Mem, code: &[Range<u32>], entry_points: &[u32]) -> BTreeSet<u32> {
+    let in_code = |addr: u32| code.iter().any(|r| r.contains(&addr));
+
+    let mut functions = BTreeSet::new();
+    let mut visited = HashSet::new();
+    let mut todo = Vec::new();
+    for &addr in entry_points {
+        if in_code(addr) {
+            functions.insert(addr);
+            todo.push(addr);
+        }
+    }
+
+    while let Some(start) = todo.pop() {
+        let Some(range) = code.iter().find(|r| r.contains(&start)) else {
+            continue;
+        };
+        let mut decoder = iced_x86::Decoder::with_ip(
+            32,
+            mem.slice(start..range.end),
+            start as u64,
+            iced_x86::DecoderOptions::NONE,
+        );
+        let mut instr = iced_x86::Instruction::default();
+        while decoder.can_decode() {
+            decoder.decode_out(&mut instr);
+            if instr.is_invalid() || !visited.insert(instr.ip32()) {
+                break;
+            }
+            let target = match instr.op0_kind() {
+                OpKind::NearBranch32 => Some(instr.near_branch32()),
+                _ => None,
+            };
+            match instr.flow_control() {
+                FlowControl::Next | FlowControl::Interrupt => {}
+                FlowControl::Call => {
+                    if let Some(target) = target.filter(|&t| in_code(t)) {
+                        if functions.insert(target) {
+                            todo.push(target);
+                        }
+                    }
+                }
+                FlowControl::ConditionalBranch => {
+                    if let Some(target) = target.filter(|&t| in_code(t)) {
+                        todo.push(target);
+                    }
+                }
+                FlowControl::UnconditionalBranch => {
+                    if let Some(target) = target.filter(|&t| in_code(t)) {
+                        todo.push(target);
+                    }
+                    break;
+                }
+                _ => break,
+            }
+        }
+    }
+
+    functions
+}
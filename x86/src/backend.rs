@@ -0,0 +1,216 @@
+//! The part of the emulator that actually executes guest instructions, behind a trait so
+//! that it can be swapped out.
+//!
+//! X86 owns everything around execution: the CPUs and their registers, scheduling, time
+//! slices, hardware breakpoints and tracking writes to code.  A Backend only runs
+//! instructions on a CPU, and is told when code it may have cached is overwritten.
+//! The interpreter over the InstrCache is the default backend, jit/ has one that compiles
+//! hot code on x86-64 hosts, and an external engine (e.g. unicorn, to cross-check
+//! against) would slot in here too; compare() runs two of them in lockstep.
+
+use crate::{
+    icache::InstrCache,
+    x86::{CPUState, CPU},
+    Register,
+};
+use memory::Mem;
+
+pub trait Backend {
+    /// Execute at most budget instructions starting from cpu.regs.eip, returning how many ran.
+    /// Like CPU::execute_block, this may stop early, at the end of a basic block or when
+    /// cpu.state is no longer Running; on a fault eip must point at the faulting instruction.
+    fn run(&mut self, cpu: &mut CPU, mem: Mem, budget: usize) -> usize;
+
+    /// Execute a single instruction.
+    fn step(&mut self, cpu: &mut CPU, mem: Mem) -> usize {
+        self.run(cpu, mem, 1)
+    }
+
+    /// Discard anything derived from the code in [start, end), because it was written to.
+    fn invalidate(&mut self, mem: Mem, start: u32, end: u32);
+
+    /// A summary of performance counters, for printing at exit.
+    fn stats(&self) -> String {
+        String::new()
+    }
+}
+
+/// The interpreter: decodes into basic blocks (see icache.rs) and runs each op's function.
+#[derive(Default)]
+pub struct Interpreter {
+    icache: InstrCache,
+}
+
+impl Backend for Interpreter {
+    fn run(&mut self, cpu: &mut CPU, mem: Mem, budget: usize) -> usize {
+        let block = self.icache.get_block(mem, cpu.regs.eip);
+        cpu.execute_block(mem, block, budget)
+    }
+
+    fn step(&mut self, cpu: &mut CPU, mem: Mem) -> usize {
+        let block = self.icache.get_single(mem, cpu.regs.eip);
+        cpu.execute_block(mem, block, 1)
+    }
+
+    fn invalidate(&mut self, mem: Mem, start: u32, end: u32) {
+        self.icache.invalidate(mem, start, end);
+    }
+
+    fn stats(&self) -> String {
+        format!("icache: {}", self.icache.stats())
+    }
+}
+
+/// Where compare() found two backends disagreeing.
+#[derive(Debug)]
+pub struct Divergence {
+    /// Number of instructions both executed before the difference showed.
+    pub steps: usize,
+    /// The address of the instruction that diverged.
+    pub eip: u32,
+    pub what: String,
+}
+
+const COMPARED_REGS: [Register; 8] = [
+    Register::EAX,
+    Register::ECX,
+    Register::EDX,
+    Register::EBX,
+    Register::ESP,
+    Register::EBP,
+    Register::ESI,
+    Register::EDI,
+];
+
+pub(crate) fn diff_cpus(a: &CPU, b: &CPU) -> Option<String> {
+    if a.regs.eip != b.regs.eip {
+        return Some(format!("eip {:x} vs {:x}", a.regs.eip, b.regs.eip));
+    }
+    for reg in COMPARED_REGS {
+        let (x, y) = (a.regs.get32(reg), b.regs.get32(reg));
+        if x != y {
+            return Some(format!("{:?} {:x} vs {:x}", reg, x, y));
+        }
+    }
+    if a.flags.bits() != b.flags.bits() {
+        return Some(format!("flags {:?} vs {:?}", a.flags.get(), b.flags.get()));
+    }
+    if a.state != b.state {
+        return Some(format!("state {:?} vs {:?}", a.state, b.state));
+    }
+    None
+}
+
+/// Run two backends in lockstep, an instruction at a time, for differential testing.
+/// Each gets its own CPU and memory, which must start out identical.  Stops after steps
+/// instructions or when the CPUs stop Running, comparing registers after each instruction
+/// and memory at the end, and returns the number of instructions run.
+pub fn compare(
+    (a, cpu_a, mem_a): (&mut dyn Backend, &mut CPU, Mem),
+    (b, cpu_b, mem_b): (&mut dyn Backend, &mut CPU, Mem),
+    steps: usize,
+) -> Result<usize, Divergence> {
+    let mut count = 0;
+    while count < steps && cpu_a.state == CPUState::Running {
+        let eip = cpu_a.regs.eip;
+        let ran = a.step(cpu_a, mem_a);
+        if b.step(cpu_b, mem_b) != ran {
+            return Err(Divergence {
+                steps: count,
+                eip,
+                what: "instruction count".into(),
+            });
+        }
+        if let Some(what) = diff_cpus(cpu_a, cpu_b) {
+            return Err(Divergence {
+                steps: count,
+                eip,
+                what,
+            });
+        }
+        count += ran;
+        // Keep both in sync with code they overwrote, as X86 would.
+        if let Some((start, end)) = cpu_a.written.take() {
+            a.invalidate(mem_a, start, end);
+        }
+        if let Some((start, end)) = cpu_b.written.take() {
+            b.invalidate(mem_b, start, end);
+        }
+        if ran == 0 {
+            break;
+        }
+    }
+    let (bytes_a, bytes_b) = (mem_a.slice(..), mem_b.slice(..));
+    if let Some(addr) = bytes_a.iter().zip(bytes_b).position(|(x, y)| x != y) {
+        return Err(Divergence {
+            steps: count,
+            eip: cpu_a.regs.eip,
+            what: format!(
+                "memory at {:x}: {:x} vs {:x}",
+                addr, bytes_a[addr], bytes_b[addr]
+            ),
+        });
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An interpreter with a bug: inc eax adds two.
+    #[derive(Default)]
+    struct Broken(Interpreter);
+
+    impl Backend for Broken {
+        fn run(&mut self, cpu: &mut CPU, mem: Mem, budget: usize) -> usize {
+            let inc = mem.slice(cpu.regs.eip..)[0] == 0x40;
+            let count = self.0.run(cpu, mem, budget);
+            if inc {
+                let eax = cpu.regs.get32(Register::EAX);
+                cpu.regs.set32(Register::EAX, eax.wrapping_add(1));
+            }
+            count
+        }
+
+        fn invalidate(&mut self, mem: Mem, start: u32, end: u32) {
+            self.0.invalidate(mem, start, end);
+        }
+    }
+
+    /// mov ecx,3; loop: inc eax; mov [1800h],eax; dec ecx; jnz loop; hlt
+    const CODE: &[u8] = &[
+        0xb9, 3, 0, 0, 0, 0x40, 0xa3, 0x00, 0x18, 0, 0, 0x49, 0x75, 0xf7, 0xf4,
+    ];
+
+    fn run_compare(a: &mut dyn Backend, b: &mut dyn Backend) -> Result<usize, Divergence> {
+        let mut bufs = [vec![0u8; 0x2000], vec![0u8; 0x2000]];
+        for buf in &mut bufs {
+            buf[0x1000..0x1000 + CODE.len()].copy_from_slice(CODE);
+        }
+        let [buf_a, buf_b] = &mut bufs;
+        let mem = |buf: &mut Vec<u8>| {
+            let range = buf.as_mut_ptr_range();
+            Mem::from_ptrs(range.start as *const u8..range.end as *const u8)
+        };
+        let (mem_a, mem_b) = (mem(buf_a), mem(buf_b));
+        let (mut cpu_a, mut cpu_b) = (CPU::new(), CPU::new());
+        cpu_a.regs.eip = 0x1000;
+        cpu_b.regs.eip = 0x1000;
+        compare((a, &mut cpu_a, mem_a), (b, &mut cpu_b, mem_b), 100)
+    }
+
+    #[test]
+    fn interpreters_agree() {
+        let steps = run_compare(&mut Interpreter::default(), &mut Interpreter::default());
+        // mov, then four instructions three times, then hlt.
+        assert_eq!(steps.unwrap(), 14);
+    }
+
+    #[test]
+    fn finds_divergence() {
+        let err = run_compare(&mut Interpreter::default(), &mut Broken::default()).unwrap_err();
+        assert_eq!((err.steps, err.eip), (1, 0x1005));
+        assert_eq!(err.what, "EAX 1 vs 2");
+    }
+}
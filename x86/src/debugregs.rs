@@ -0,0 +1,322 @@
+//! The debug registers, DR0-DR7, which set hardware instruction breakpoints and data
+//! watchpoints.
+//!
+//! The guest sets them with mov to DRn or through a CONTEXT, and the debugger can set
+//! breakpoints of its own that work the same way but stop the emulator instead of
+//! raising an exception.  While any of them is enabled, X86 runs the CPU an instruction
+//! at a time, checking the instruction's address before it runs and the memory it
+//! accessed after.
+
+use crate::x86::CPU;
+use iced_x86::{
+    Decoder, DecoderOptions, Instruction, InstructionInfoFactory, InstructionInfoOptions, OpAccess,
+    Register,
+};
+use memory::Mem;
+
+/// What a breakpoint fires on, from the R/W bits of DR7.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakOn {
+    /// Executing the instruction at the address.
+    Execute,
+    /// Writes to the range.
+    Write,
+    /// Reads or writes of the range.
+    ReadWrite,
+}
+
+/// A hardware breakpoint, as set by a debug register or by the debugger.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HwBreakpoint {
+    pub addr: u32,
+    pub len: u32,
+    pub on: BreakOn,
+}
+
+impl HwBreakpoint {
+    pub fn hits_execute(&self, eip: u32) -> bool {
+        self.on == BreakOn::Execute && self.addr == eip
+    }
+
+    pub fn hits_access(&self, access: &Access) -> bool {
+        match self.on {
+            BreakOn::Execute => false,
+            BreakOn::Write if !access.write => false,
+            _ => {
+                access.addr < self.addr.saturating_add(self.len)
+                    && self.addr < access.addr.saturating_add(access.len)
+            }
+        }
+    }
+}
+
+/// A memory access made by an instruction.
+#[derive(Debug)]
+pub struct Access {
+    pub addr: u32,
+    pub len: u32,
+    pub write: bool,
+}
+
+/// DR6 bit: the debug exception came from single-stepping.
+pub const DR6_BS: u32 = 1 << 14;
+/// DR6 after reset; the reserved bits read as ones.
+const DR6_DEFAULT: u32 = 0xFFFF_0FF0;
+/// DR7 after reset; bit 10 is reserved and reads as one.
+const DR7_DEFAULT: u32 = 0x400;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct DebugRegs {
+    /// DR0-DR3, the breakpoint addresses.
+    pub dr: [u32; 4],
+    /// Status: which breakpoints fired (bits 0-3), and DR6_BS.
+    pub dr6: u32,
+    /// Control: enable bits (bits 0-7) and the kind and length of each breakpoint.
+    pub dr7: u32,
+}
+
+impl Default for DebugRegs {
+    fn default() -> Self {
+        DebugRegs {
+            dr: [0; 4],
+            dr6: DR6_DEFAULT,
+            dr7: DR7_DEFAULT,
+        }
+    }
+}
+
+impl DebugRegs {
+    /// Read DRn, where DR4 and DR5 are aliases of DR6 and DR7.
+    pub fn get(&self, n: usize) -> u32 {
+        match n {
+            0..=3 => self.dr[n],
+            4 | 6 => self.dr6,
+            _ => self.dr7,
+        }
+    }
+
+    pub fn set(&mut self, n: usize, value: u32) {
+        match n {
+            0..=3 => self.dr[n] = value,
+            4 | 6 => self.dr6 = value | DR6_DEFAULT,
+            _ => self.dr7 = value | DR7_DEFAULT,
+        }
+    }
+
+    /// Whether DR7 enables any breakpoint, locally or globally.
+    pub fn enabled(&self) -> bool {
+        self.dr7 & 0xFF != 0
+    }
+
+    /// The breakpoint in DRn, if DR7 enables it.
+    pub fn breakpoint(&self, n: usize) -> Option<HwBreakpoint> {
+        if self.dr7 & (3 << (n * 2)) == 0 {
+            return None;
+        }
+        let bits = self.dr7 >> (16 + n * 4);
+        let on = match bits & 3 {
+            0 => BreakOn::Execute,
+            1 => BreakOn::Write,
+            3 => BreakOn::ReadWrite,
+            // I/O breakpoints, which need CR4.DE and in/out, neither of which we have.
+            _ => return None,
+        };
+        let len = match on {
+            // The length of instruction breakpoints must be 1.
+            BreakOn::Execute => 1,
+            _ => [1, 2, 8, 4][(bits >> 2) as usize & 3],
+        };
+        // The processor ignores the low bits of the address, aligning it to the length.
+        Some(HwBreakpoint {
+            addr: self.dr[n] & !(len - 1),
+            len,
+            on,
+        })
+    }
+
+    /// The DR6 bits of the enabled breakpoints that match.
+    pub fn hits(&self, matches: impl Fn(&HwBreakpoint) -> bool) -> u32 {
+        (0..4)
+            .filter(|&n| self.breakpoint(n).is_some_and(|bp| matches(&bp)))
+            .fold(0, |bits, n| bits | 1 << n)
+    }
+}
+
+/// Decode the instruction at eip, independently of the backend's caches.
+pub fn decode(mem: Mem, eip: u32) -> Instruction {
+    Decoder::with_ip(32, mem.slice(eip..), eip as u64, DecoderOptions::NONE).decode()
+}
+
+/// The memory an instruction accesses, computed before it runs from the current registers.
+pub fn accesses(cpu: &CPU, instr: &Instruction) -> Vec<Access> {
+    let mut factory = InstructionInfoFactory::new();
+    let info = factory.info_options(instr, InstructionInfoOptions::NO_REGISTER_USAGE);
+    info.used_memory()
+        .iter()
+        .filter_map(|m| {
+            let write = match m.access() {
+                OpAccess::NoMemAccess => return None,
+                OpAccess::Read | OpAccess::CondRead => false,
+                _ => true,
+            };
+            let addr = m.virtual_address(0, |reg, _, _| {
+                Some(match reg {
+                    // iced wants the base of segment registers rather than their value.
+                    Register::ES
+                    | Register::CS
+                    | Register::SS
+                    | Register::DS
+                    | Register::FS
+                    | Register::GS => cpu.regs.segment_base(reg),
+                    _ if reg.is_gpr32() => cpu.regs.get32(reg),
+                    _ if reg.is_gpr16() => cpu.regs.get16(reg) as u32,
+                    _ => return None,
+                } as u64)
+            })?;
+            // rep string instructions have no size of their own; use their element's.
+            let len = match m.memory_size().size() {
+                0 => instr.memory_size().size(),
+                len => len,
+            };
+            Some(Access {
+                addr: addr as u32,
+                len: len.max(1) as u32,
+                write,
+            })
+        })
+        .collect()
+}
+
+/// Whether an instruction is a string instruction repeated ecx times, for which
+/// accesses() describes only the first element.
+pub fn is_rep_string(instr: &Instruction) -> bool {
+    instr.is_string_instruction() && (instr.has_rep_prefix() || instr.has_repne_prefix())
+}
+
+/// Widen the accesses of a rep string instruction from its first element to the count
+/// elements it went through, which run downwards when DF is set.
+pub fn widen_rep(accesses: &mut Vec<Access>, count: u32, backward: bool) {
+    if count == 0 {
+        accesses.clear();
+        return;
+    }
+    for access in accesses {
+        let len = access.len.saturating_mul(count);
+        if backward {
+            access.addr = access.addr.wrapping_sub(len - access.len);
+        }
+        access.len = len;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn access(addr: u32, len: u32, write: bool) -> Access {
+        Access { addr, len, write }
+    }
+
+    #[test]
+    fn aliases_and_reserved_bits() {
+        let mut debug = DebugRegs::default();
+        debug.set(4, 0b10);
+        assert_eq!(debug.get(6), 0b10 | DR6_DEFAULT);
+        debug.set(5, 0b1);
+        assert_eq!(debug.get(7), 0b1 | DR7_DEFAULT);
+        assert!(debug.enabled());
+    }
+
+    #[test]
+    fn breakpoint_from_dr7() {
+        let mut debug = DebugRegs::default();
+        debug.dr = [0x1000, 0x2003, 0x3007, 0x4000];
+        assert_eq!(debug.breakpoint(0), None);
+        // G0 execute; L1 write of 4 bytes; L2 read/write of 8 bytes; G3 I/O.
+        debug.set(
+            7,
+            0b10 | 0b100 | 0b10000 | 0b1000_0000 | 0b0010_1011_1101_0000 << 16,
+        );
+        assert_eq!(
+            debug.breakpoint(0),
+            Some(HwBreakpoint {
+                addr: 0x1000,
+                len: 1,
+                on: BreakOn::Execute
+            })
+        );
+        assert_eq!(
+            debug.breakpoint(1),
+            Some(HwBreakpoint {
+                addr: 0x2000,
+                len: 4,
+                on: BreakOn::Write
+            })
+        );
+        assert_eq!(
+            debug.breakpoint(2),
+            Some(HwBreakpoint {
+                addr: 0x3000,
+                len: 8,
+                on: BreakOn::ReadWrite
+            })
+        );
+        assert_eq!(debug.breakpoint(3), None);
+    }
+
+    #[test]
+    fn matching() {
+        let write = HwBreakpoint {
+            addr: 0x2000,
+            len: 4,
+            on: BreakOn::Write,
+        };
+        assert!(write.hits_access(&access(0x2003, 1, true)));
+        assert!(write.hits_access(&access(0x1FFE, 4, true)));
+        assert!(!write.hits_access(&access(0x2004, 4, true)));
+        assert!(!write.hits_access(&access(0x1FFC, 4, true)));
+        assert!(!write.hits_access(&access(0x2000, 4, false)));
+        assert!(!write.hits_execute(0x2000));
+
+        let rw = HwBreakpoint {
+            on: BreakOn::ReadWrite,
+            ..write
+        };
+        assert!(rw.hits_access(&access(0x2000, 4, false)));
+
+        let exec = HwBreakpoint {
+            addr: 0x1000,
+            len: 1,
+            on: BreakOn::Execute,
+        };
+        assert!(exec.hits_execute(0x1000));
+        assert!(!exec.hits_execute(0x1001));
+        assert!(!exec.hits_access(&access(0x1000, 1, false)));
+
+        let mut debug = DebugRegs::default();
+        debug.dr = [0x1000, 0x2000, 0, 0];
+        // L0 execute, L1 write of 4 bytes.
+        debug.set(7, 0b101 | 0b1101_0000 << 16);
+        assert_eq!(debug.hits(|bp| bp.hits_execute(0x1000)), 0b01);
+        assert_eq!(
+            debug.hits(|bp| bp.hits_access(&access(0x2002, 2, true))),
+            0b10
+        );
+        assert_eq!(debug.hits(|bp| bp.hits_access(&access(0x3000, 2, true))), 0);
+    }
+
+    #[test]
+    fn widen_rep_covers_elements() {
+        let mut accesses = vec![access(0x1000, 4, true)];
+        widen_rep(&mut accesses, 3, false);
+        assert_eq!((accesses[0].addr, accesses[0].len), (0x1000, 12));
+
+        let mut accesses = vec![access(0x1008, 4, true)];
+        widen_rep(&mut accesses, 3, true);
+        assert_eq!((accesses[0].addr, accesses[0].len), (0x1000, 12));
+
+        let mut accesses = vec![access(0x1000, 4, true)];
+        widen_rep(&mut accesses, 0, false);
+        assert!(accesses.is_empty());
+    }
+}
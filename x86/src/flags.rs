@@ -0,0 +1,208 @@
+//! Lazily evaluated EFLAGS.
+//!
+//! Most arithmetic instructions set the flags but most of those flags are never read
+//! before the next instruction overwrites them.  So rather than computing them up front,
+//! the common arithmetic ops record their operands and result, and the flags are only
+//! derived from those when something asks for them.
+//!
+//! With the "check-flags" feature, each lazy op is also checked against an eager
+//! computation of the same flags, to catch the two disagreeing.
+
+use crate::registers::Flags;
+
+/// The operation whose operands are recorded in LazyFlags.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[repr(u8)]
+pub enum FlagOp {
+    /// Flags are all in LazyFlags::flags.
+    #[default]
+    None,
+    /// add/adc: result = x + y + carry.
+    Add,
+    /// sub/sbb/cmp: result = x - y - carry.
+    Sub,
+    /// and/or/xor/test.
+    Logic,
+    /// inc/dec, which leave CF alone: result = x + y, where y is 1 or -1.
+    IncDec,
+}
+
+impl FlagOp {
+    /// The flags derived from the recorded operation.
+    fn derives(self) -> Flags {
+        match self {
+            FlagOp::None => Flags::empty(),
+            FlagOp::Add | FlagOp::Sub => {
+                Flags::CF | Flags::ZF | Flags::SF | Flags::OF | Flags::PF | Flags::AF
+            }
+            // AF is undefined after the logic ops, so we leave it alone.
+            FlagOp::Logic => Flags::CF | Flags::ZF | Flags::SF | Flags::OF | Flags::PF,
+            FlagOp::IncDec => Flags::ZF | Flags::SF | Flags::OF | Flags::PF | Flags::AF,
+        }
+    }
+}
+
+// The fields are pub(crate) for the JIT, whose generated code records ops itself.
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+pub struct LazyFlags {
+    /// The flags not derived from op.
+    pub(crate) flags: Flags,
+    pub(crate) op: FlagOp,
+    /// Operand width in bits.
+    pub(crate) bits: u8,
+    /// Whether the op had a carry or borrow in, from adc/sbb.
+    pub(crate) carry: bool,
+    pub(crate) x: u32,
+    pub(crate) y: u32,
+    pub(crate) result: u32,
+}
+
+impl From<Flags> for LazyFlags {
+    fn from(flags: Flags) -> Self {
+        LazyFlags {
+            flags,
+            op: FlagOp::None,
+            bits: 32,
+            carry: false,
+            x: 0,
+            y: 0,
+            result: 0,
+        }
+    }
+}
+
+impl Default for LazyFlags {
+    fn default() -> Self {
+        Flags::empty().into()
+    }
+}
+
+/// PF: whether the low byte of a result has an even number of bits set.
+#[inline]
+pub fn parity(result: u32) -> bool {
+    (result as u8).count_ones() & 1 == 0
+}
+
+impl LazyFlags {
+    /// Record an op whose flags are derived later.  Values are zero-extended from bits.
+    #[inline]
+    pub fn record(&mut self, op: FlagOp, bits: usize, x: u32, y: u32, result: u32, carry: bool) {
+        if op == FlagOp::IncDec {
+            self.before_inc_dec();
+        }
+        self.op = op;
+        self.bits = bits as u8;
+        self.x = x;
+        self.y = y;
+        self.result = result;
+        self.carry = carry;
+    }
+
+    /// Keep the CF derived by the previous op, before recording an inc/dec, which doesn't
+    /// derive one.
+    #[inline]
+    pub(crate) fn before_inc_dec(&mut self) {
+        if self.op != FlagOp::IncDec {
+            self.flags = self.get();
+        }
+    }
+
+    fn sign(&self, x: u32) -> bool {
+        (x >> (self.bits - 1)) & 1 != 0
+    }
+
+    /// Compute the flags derived from the recorded op.
+    fn derived(&self) -> Flags {
+        let (x, y, result) = (self.x, self.y, self.result);
+        let mut flags = Flags::empty();
+        flags.set(Flags::ZF, result == 0);
+        flags.set(Flags::SF, self.sign(result));
+        flags.set(Flags::PF, parity(result));
+        match self.op {
+            FlagOp::None => unreachable!(),
+            FlagOp::Add => {
+                flags.set(Flags::CF, result < x || (self.carry && result == x));
+                flags.set(Flags::OF, self.sign((x ^ result) & (y ^ result)));
+                flags.set(Flags::AF, (x ^ y ^ result) & 0x10 != 0);
+            }
+            FlagOp::Sub => {
+                flags.set(Flags::CF, x < y || (self.carry && x == y));
+                flags.set(Flags::OF, self.sign((x ^ y) & (x ^ result)));
+                flags.set(Flags::AF, (x ^ y ^ result) & 0x10 != 0);
+            }
+            FlagOp::Logic => {}
+            FlagOp::IncDec => {
+                flags.set(Flags::OF, self.sign((x ^ result) & (y ^ result)));
+                flags.set(Flags::AF, (x ^ result) & 0x10 != 0);
+            }
+        }
+        flags
+    }
+
+    /// The current value of all the flags.
+    #[inline]
+    pub fn get(&self) -> Flags {
+        match self.op {
+            FlagOp::None => self.flags,
+            op => (self.flags - op.derives()) | self.derived(),
+        }
+    }
+
+    /// Fold any recorded op into self.flags, as needed before modifying them directly.
+    #[inline]
+    fn materialize(&mut self) {
+        if self.op != FlagOp::None {
+            self.flags = self.get();
+            self.op = FlagOp::None;
+        }
+    }
+
+    #[inline]
+    pub fn contains(&self, other: Flags) -> bool {
+        if self.op.derives().intersects(other) {
+            self.get().contains(other)
+        } else {
+            self.flags.contains(other)
+        }
+    }
+
+    pub fn intersects(&self, other: Flags) -> bool {
+        self.get().intersects(other)
+    }
+
+    pub fn bits(&self) -> u32 {
+        self.get().bits()
+    }
+
+    #[inline]
+    pub fn set(&mut self, other: Flags, value: bool) {
+        self.materialize();
+        self.flags.set(other, value);
+    }
+
+    #[inline]
+    pub fn insert(&mut self, other: Flags) {
+        self.materialize();
+        self.flags.insert(other);
+    }
+
+    #[inline]
+    pub fn remove(&mut self, other: Flags) {
+        self.materialize();
+        self.flags.remove(other);
+    }
+
+    /// Panic if the flags differ from those computed eagerly, for the "check-flags" feature.
+    #[cfg(feature = "check-flags")]
+    pub fn check(&self, eager: Flags) {
+        assert_eq!(
+            self.get(),
+            eager,
+            "lazy flags disagree with eager for {:?} x={:#x} y={:#x} result={:#x}",
+            self.op,
+            self.x,
+            self.y,
+            self.result
+        );
+    }
+}
@@ -3,21 +3,61 @@
 use bitflags::bitflags;
 
 bitflags! {
+    #[derive(serde::Serialize, serde::Deserialize)]
     pub struct Status: u16 {
         const C3 = 1 << 14;
         const C2 = 1 << 10;
         const C1 = 1 << 9;
         const C0 = 1 << 8;
+        /// Exception summary: some unmasked exception flag is set.
+        const ES = 1 << 7;
+        /// Stack fault, alongside IE when the stack over- or underflows.
+        const SF = 1 << 6;
+        /// Exception flags, sticky until cleared by FCLEX.
+        const PE = 1 << 5;
+        const UE = 1 << 4;
+        const OE = 1 << 3;
+        const ZE = 1 << 2;
+        const DE = 1 << 1;
+        const IE = 1 << 0;
     }
 }
 
+impl Status {
+    /// The exception flags, which share their bit positions with the control word's masks.
+    pub const EXCEPTIONS: Status = Status::from_bits_truncate(0x3F);
+}
+
+/// Control word rounding control, bits 10-11.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rounding {
+    Nearest,
+    Down,
+    Up,
+    Zero,
+}
+
+/// Control word precision control, bits 8-9.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Precision {
+    Single,
+    Double,
+    Extended,
+}
+
+/// The control word after FINIT: all exceptions masked, 64-bit precision, round to nearest.
+pub const DEFAULT_CONTROL: u16 = 0x037F;
+
+#[derive(serde::Serialize, serde::Deserialize)]
 pub struct FPU {
     /// FPU ST0 through ST7 registers.
     pub st: [f64; 8],
     /// Index of top of FPU stack; 8 when stack empty.
     pub st_top: usize,
-    /// FPU status word (TODO fold st_top in here?)
+    /// FPU status word, except for the stack top, which is st_top; see status_word().
     pub status: Status,
+    /// FPU control word.
+    pub control: u16,
 }
 
 impl Default for FPU {
@@ -26,29 +66,206 @@ impl Default for FPU {
             st: [0.; 8],
             st_top: 8,
             status: Status::empty(),
+            control: DEFAULT_CONTROL,
         }
     }
 }
 
 impl FPU {
-    fn exception(_msg: &str) {
-        // TODO: modify state bits etc.
-        // At least ignoring these may allow programs to make some progress.
-        // See note in https://github.com/joncampbell123/dosbox-x/issues/94 ,
-        // "I've seen DOSBox SVN bail out on perfectly good demoscene programs because
-        // of [not allowing underflow]."
-        // Don't log because anatyda underflows thousands of times, eek.
-        // log::warn!("{}", msg);
+    /// Record an exception in the status word.
+    /// The instruction always completes as if the exception were masked, substituting the
+    /// default result.  If the control word unmasks it, ES is set and the next waiting FPU
+    /// instruction raises #MF instead of running (see checks_pending()), as on a 486 and
+    /// later; masked, it just leaves its flag for FNSTSW to find.
+    /// See note in https://github.com/joncampbell123/dosbox-x/issues/94 ,
+    /// "I've seen DOSBox SVN bail out on perfectly good demoscene programs because
+    /// of [not allowing underflow]."
+    /// Don't log because anatyda underflows thousands of times, eek.
+    pub fn exception(&mut self, flags: Status) {
+        self.status.insert(flags);
+        self.update_summary();
+    }
+
+    /// Set ES from whether any exception flag is unmasked, e.g. after loading a control word.
+    pub fn update_summary(&mut self) {
+        let unmasked = self.status.bits() & !self.control & Status::EXCEPTIONS.bits() != 0;
+        self.status.set(Status::ES, unmasked);
+    }
+
+    /// The exception flags that are set and unmasked, which a pending #MF reports.
+    pub fn unmasked_exceptions(&self) -> Status {
+        self.status & Status::from_bits_truncate(!self.control) & (Status::EXCEPTIONS | Status::SF)
+    }
+
+    /// The status word as stored by FNSTSW, including the stack top.
+    pub fn status_word(&self) -> u16 {
+        self.status.bits() | (((self.st_top & 7) as u16) << 11)
+    }
+
+    pub fn rounding(&self) -> Rounding {
+        match (self.control >> 10) & 3 {
+            0 => Rounding::Nearest,
+            1 => Rounding::Down,
+            2 => Rounding::Up,
+            _ => Rounding::Zero,
+        }
+    }
+
+    pub fn precision(&self) -> Precision {
+        match (self.control >> 8) & 3 {
+            0 => Precision::Single,
+            2 => Precision::Double,
+            // 1 is reserved, and behaves as extended.
+            _ => Precision::Extended,
+        }
+    }
+
+    /// Round to an integer according to the rounding control.
+    pub fn round_int(&self, x: f64) -> f64 {
+        match self.rounding() {
+            Rounding::Nearest => x.round_ties_even(),
+            Rounding::Down => x.floor(),
+            Rounding::Up => x.ceil(),
+            Rounding::Zero => x.trunc(),
+        }
+    }
+
+    /// Round an arithmetic result according to the precision and rounding controls.
+    /// x is the result rounded to the nearest double, and err() approximates how far the
+    /// exact result is above it; only its sign matters, and it's only needed to round in
+    /// other directions, so that the common case of rounding to nearest stays cheap.
+    ///
+    /// We keep values as f64, so extended precision is the same as double: results have
+    /// 53 bits of precision as after MSVC's CRT startup (or Windows itself) sets the
+    /// control word, rather than the 64 bits of FINIT.
+    pub fn round_result(&mut self, x: f64, err: impl FnOnce() -> f64) -> f64 {
+        if !x.is_finite() {
+            return x;
+        }
+        let rounding = self.rounding();
+        let err = match rounding {
+            Rounding::Nearest => 0.0,
+            _ => err(),
+        };
+        let single = self.precision() == Precision::Single;
+        let y = if single { x as f32 as f64 } else { x };
+        let diff = (x - y) + err;
+        if diff == 0.0 {
+            return y;
+        }
+        self.exception(Status::PE);
+        if !steps_toward(rounding, y, diff) {
+            return y;
+        }
+        match (single, diff > 0.0) {
+            (true, true) => (y as f32).next_up() as f64,
+            (true, false) => (y as f32).next_down() as f64,
+            (false, true) => y.next_up(),
+            (false, false) => y.next_down(),
+        }
+    }
+
+    /// Convert a value to f32 for FST m32fp, rounding per the rounding control.
+    pub fn to_f32(&mut self, x: f64) -> f32 {
+        let y = x as f32;
+        if !x.is_finite() {
+            return y;
+        }
+        if y.is_infinite() {
+            self.exception(Status::OE | Status::PE);
+            return y;
+        }
+        let diff = x - y as f64;
+        if diff == 0.0 {
+            return y;
+        }
+        self.exception(Status::PE);
+        if !steps_toward(self.rounding(), y as f64, diff) {
+            return y;
+        }
+        if diff > 0.0 {
+            y.next_up()
+        } else {
+            y.next_down()
+        }
+    }
+
+    /// The tag word: for each physical register, 3 if empty, else what it holds.
+    /// We only track the stack top, so the tags of registers in use follow their values.
+    pub fn tag_word(&self) -> u16 {
+        let mut tags = 0;
+        for (i, &x) in self.st.iter().enumerate() {
+            let tag = if i < self.st_top {
+                3
+            } else if x == 0.0 {
+                1
+            } else if !x.is_normal() {
+                // NaN, infinity or denormal.
+                2
+            } else {
+                0
+            };
+            tags |= tag << (i * 2);
+        }
+        tags
+    }
+
+    /// The 28-byte environment stored by FNSTENV, in the 32-bit protected mode layout.
+    /// We don't track the last instruction and operand pointers, which read as zero.
+    pub fn env(&self) -> [u8; 28] {
+        let mut env = [0u8; 28];
+        env[0..2].copy_from_slice(&self.control.to_le_bytes());
+        env[4..6].copy_from_slice(&self.status_word().to_le_bytes());
+        env[8..10].copy_from_slice(&self.tag_word().to_le_bytes());
+        env
+    }
+
+    /// Load an environment as stored by env(), for FLDENV.
+    pub fn load_env(&mut self, env: &[u8]) {
+        let word = |i: usize| u16::from_le_bytes([env[i], env[i + 1]]);
+        self.control = word(0) | 0x40;
+        let status = word(4);
+        self.status = Status::from_bits_truncate(status);
+        let top = ((status >> 11) & 7) as usize;
+        // The registers in use are the ones from the top on; see tag_word().  With none
+        // in use, the stack is empty, which we can only represent as st_top 8, so the
+        // TOP field is lost and reads back as 0.
+        self.st_top = if word(8) == 0xFFFF { 8 } else { top };
+    }
+
+    /// The 108-byte image stored by FNSAVE: the environment, then ST0-ST7 as 80-bit floats.
+    pub fn save(&self) -> [u8; 108] {
+        let mut image = [0u8; 108];
+        image[..28].copy_from_slice(&self.env());
+        for i in 0..8 {
+            let x = self.st[(self.st_top + i) & 7];
+            image[28 + i * 10..][..10].copy_from_slice(&f64_to_f80(x));
+        }
+        image
+    }
+
+    /// Load an image as stored by save(), for FRSTOR.
+    pub fn restore(&mut self, image: &[u8]) {
+        self.load_env(&image[..28]);
+        for i in 0..8 {
+            let x = f80_to_f64(image[28 + i * 10..][..10].try_into().unwrap());
+            self.st[(self.st_top + i) & 7] = x;
+        }
     }
 
     /// Get st(0), the current top of the FPU stack.
     pub fn st0(&mut self) -> &mut f64 {
+        if self.st_top == 8 {
+            self.exception(Status::IE | Status::SF);
+            return &mut self.st[7];
+        }
         &mut self.st[self.st_top]
     }
 
     pub fn push(&mut self, val: f64) {
         if self.st_top == 0 {
-            Self::exception("fpu stack overflow");
+            // C1 distinguishes overflow from underflow.
+            self.exception(Status::IE | Status::SF | Status::C1);
             return;
         }
         self.st_top -= 1;
@@ -57,15 +274,20 @@ impl FPU {
 
     pub fn pop(&mut self) {
         if self.st_top == 8 {
-            Self::exception("fpu stack underflow");
+            self.status.remove(Status::C1);
+            self.exception(Status::IE | Status::SF);
             return;
         }
         self.st_top += 1;
     }
 
-    /// Index in self.st for a given ST0, ST1 etc reg.
-    fn st_offset(&self, reg: iced_x86::Register) -> usize {
-        let ofs = match reg {
+    /// Whether a register is empty, for FXAM and FFREE.
+    pub fn is_empty(&self, reg: iced_x86::Register) -> bool {
+        self.st_top + Self::st_index(reg) >= 8
+    }
+
+    fn st_index(reg: iced_x86::Register) -> usize {
+        match reg {
             iced_x86::Register::ST0 => 0,
             iced_x86::Register::ST1 => 1,
             iced_x86::Register::ST2 => 2,
@@ -75,10 +297,14 @@ impl FPU {
             iced_x86::Register::ST6 => 6,
             iced_x86::Register::ST7 => 7,
             _ => unreachable!("invalid reg {reg:?}"),
-        };
-        let new = self.st_top + ofs;
+        }
+    }
+
+    /// Index in self.st for a given ST0, ST1 etc reg.
+    fn st_offset(&mut self, reg: iced_x86::Register) -> usize {
+        let new = self.st_top + Self::st_index(reg);
         if new >= 8 {
-            Self::exception("fpu stack underflow");
+            self.exception(Status::IE | Status::SF);
             return 7;
         }
         new
@@ -91,6 +317,210 @@ impl FPU {
     }
 
     pub fn get(&mut self, reg: iced_x86::Register) -> &mut f64 {
-        &mut self.st[self.st_offset(reg)]
+        let ofs = self.st_offset(reg);
+        &mut self.st[ofs]
+    }
+}
+
+/// Whether an instruction first raises any pending unmasked FPU exception (#MF), as
+/// WAIT and every x87 instruction do except the "no-wait" control ones.
+pub fn checks_pending(instr: &iced_x86::Instruction) -> bool {
+    use iced_x86::{Code, CpuidFeature};
+    match instr.code() {
+        Code::Fninit
+        | Code::Fnclex
+        | Code::Fnstsw_AX
+        | Code::Fnstsw_m2byte
+        | Code::Fnstcw_m2byte
+        | Code::Fnstenv_m14byte
+        | Code::Fnstenv_m28byte
+        | Code::Fnsave_m94byte
+        | Code::Fnsave_m108byte => false,
+        Code::Wait => true,
+        _ => instr.cpuid_features().iter().any(|feature| {
+            matches!(
+                feature,
+                CpuidFeature::FPU | CpuidFeature::FPU287 | CpuidFeature::FPU387
+            )
+        }),
+    }
+}
+
+/// Whether a result y, which the exact result exceeds by diff, must step one unit in the
+/// last place towards the exact result to round in the given direction.  y is rounded to
+/// nearest, so that step is all any other direction needs.
+fn steps_toward(rounding: Rounding, y: f64, diff: f64) -> bool {
+    match rounding {
+        Rounding::Nearest => false,
+        Rounding::Up => diff > 0.0,
+        Rounding::Down => diff < 0.0,
+        // Only if y was rounded away from zero.
+        Rounding::Zero => (y > 0.0 && diff < 0.0) || (y < 0.0 && diff > 0.0),
+    }
+}
+
+/// Convert an 80-bit extended precision float, as stored by FSTP m80fp, to f64.
+pub fn f80_to_f64(bytes: [u8; 10]) -> f64 {
+    let mantissa = u64::from_le_bytes(bytes[..8].try_into().unwrap());
+    let se = u16::from_le_bytes([bytes[8], bytes[9]]);
+    let sign = if se & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exp = (se & 0x7FFF) as i32;
+    let val = if exp == 0x7FFF {
+        if mantissa << 1 == 0 {
+            f64::INFINITY
+        } else {
+            f64::NAN
+        }
+    } else {
+        // The explicit integer bit means the mantissa is a 1.63 fixed point number.
+        // Scale it in two steps, as 2^exp alone overflows for results that are denormal.
+        let exp = exp - 16383;
+        mantissa as f64 / (1u64 << 63) as f64 * 2f64.powi(exp / 2) * 2f64.powi(exp - exp / 2)
+    };
+    sign * val
+}
+
+/// Convert an f64 to an 80-bit extended precision float, which represents it exactly.
+pub fn f64_to_f80(val: f64) -> [u8; 10] {
+    let bits = val.to_bits();
+    let sign = ((bits >> 63) as u16) << 15;
+    let exp = ((bits >> 52) & 0x7FF) as i32;
+    let frac = bits & ((1 << 52) - 1);
+    let (exp, mantissa) = match exp {
+        0 if frac == 0 => (0, 0),
+        0 => {
+            // Denormal in f64, but normal in the wider exponent range.
+            let shift = frac.leading_zeros();
+            (15372 - shift as i32, frac << shift)
+        }
+        0x7FF => (0x7FFF, (1 << 63) | (frac << 11)),
+        _ => (exp - 1023 + 16383, (1 << 63) | (frac << 11)),
+    };
+    let mut bytes = [0u8; 10];
+    bytes[..8].copy_from_slice(&mantissa.to_le_bytes());
+    bytes[8..].copy_from_slice(&(sign | exp as u16).to_le_bytes());
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fpu(control: u16) -> FPU {
+        FPU {
+            control,
+            ..FPU::default()
+        }
+    }
+
+    const SINGLE: u16 = 0x007F;
+    const DOWN: u16 = 0x0400;
+    const UP: u16 = 0x0800;
+    const ZERO: u16 = 0x0C00;
+
+    #[test]
+    fn round_result_single() {
+        let x = 1.0 + 2f64.powi(-30);
+        let mut f = fpu(SINGLE);
+        assert_eq!(f.round_result(x, || 0.0), 1.0);
+        assert!(f.status.contains(Status::PE));
+        assert_eq!(
+            fpu(SINGLE | UP).round_result(x, || 0.0),
+            1f32.next_up() as f64
+        );
+        assert_eq!(fpu(SINGLE | DOWN).round_result(x, || 0.0), 1.0);
+        assert_eq!(fpu(SINGLE | ZERO).round_result(-x, || 0.0), -1.0);
+        assert_eq!(
+            fpu(SINGLE | DOWN).round_result(-x, || 0.0),
+            -(1f32.next_up() as f64)
+        );
+    }
+
+    #[test]
+    fn round_result_double() {
+        // Exact results are left alone, without PE.
+        let mut f = fpu(DEFAULT_CONTROL | UP);
+        assert_eq!(f.round_result(1.0, || 0.0), 1.0);
+        assert!(!f.status.contains(Status::PE));
+        // Otherwise err() says which way the exact result lies.
+        assert_eq!(f.round_result(1.0, || 1e-300), 1f64.next_up());
+        assert!(f.status.contains(Status::PE));
+        assert_eq!(
+            fpu(DEFAULT_CONTROL | DOWN).round_result(1.0, || 1e-300),
+            1.0
+        );
+        assert_eq!(
+            fpu(DEFAULT_CONTROL | DOWN).round_result(1.0, || -1e-300),
+            1f64.next_down()
+        );
+        // Rounding to nearest never asks.
+        assert_eq!(
+            fpu(DEFAULT_CONTROL).round_result(1.0, || unreachable!()),
+            1.0
+        );
+    }
+
+    #[test]
+    fn unmasked_exceptions_pend() {
+        let mut f = fpu(DEFAULT_CONTROL);
+        f.exception(Status::ZE);
+        assert!(!f.status.contains(Status::ES));
+        // Unmasking a flag that's already set makes it pending, as fldcw does.
+        f.control &= !0x4;
+        f.update_summary();
+        assert!(f.status.contains(Status::ES));
+        assert_eq!(f.unmasked_exceptions(), Status::ZE);
+    }
+
+    #[test]
+    fn checks_pending_skips_no_wait_forms() {
+        let decode = |bytes: &[u8]| {
+            iced_x86::Decoder::with_ip(32, bytes, 0, iced_x86::DecoderOptions::NONE).decode()
+        };
+        assert!(!checks_pending(&decode(&[0xdb, 0xe3]))); // fninit
+        assert!(!checks_pending(&decode(&[0xdb, 0xe2]))); // fnclex
+        assert!(!checks_pending(&decode(&[0xdf, 0xe0]))); // fnstsw ax
+        assert!(!checks_pending(&decode(&[0xd9, 0x38]))); // fnstcw [eax]
+        assert!(checks_pending(&decode(&[0x9b]))); // wait
+        assert!(checks_pending(&decode(&[0xd8, 0xc1]))); // fadd st,st(1)
+        assert!(checks_pending(&decode(&[0xd9, 0x28]))); // fldcw [eax]
+        assert!(!checks_pending(&decode(&[0xb8, 1, 0, 0, 0]))); // mov eax,1
+    }
+
+    #[test]
+    fn save_restore() {
+        let mut f = fpu(0x027F);
+        f.push(1.5);
+        f.push(-2.25);
+        f.exception(Status::PE);
+        let image = f.save();
+
+        let mut g = FPU::default();
+        g.restore(&image);
+        assert_eq!(g.control, f.control);
+        assert_eq!(g.status_word(), f.status_word());
+        assert_eq!(g.st_top, 6);
+        assert_eq!((g.st[6], g.st[7]), (-2.25, 1.5));
+        assert_eq!(g.save(), image);
+    }
+
+    #[test]
+    fn restore_empty() {
+        let mut f = FPU::default();
+        f.push(1.0);
+        let mut image = FPU::default().save();
+        // An empty stack with TOP=5 still comes back empty.
+        image[5] |= 5 << 3;
+        f.restore(&image);
+        assert_eq!(f.st_top, 8);
+        assert_eq!(f.tag_word(), 0xFFFF);
+    }
+
+    #[test]
+    fn f80_round_trip() {
+        for x in [0.0, -1.0, 1.5e300, f64::MIN_POSITIVE / 4.0, f64::INFINITY] {
+            assert_eq!(f80_to_f64(f64_to_f80(x)), x);
+        }
+        assert!(f80_to_f64(f64_to_f80(f64::NAN)).is_nan());
     }
 }
@@ -2,15 +2,18 @@
 //! so when executing we always know we'll execute a full basic block in
 //! linear order before making any jumps.
 //!
-//! Because of this, we cannot represent a CPU state that is partway through
-//! a basic block.  To implement single-stepping and breakpoints, we break
-//! any affected basic block into smaller pieces to maintain the invariant of
-//! always executing through a basic block's end.
+//! Single-stepping and breakpoints run one instruction at a time, taking it from a
+//! cached block that starts there if there is one, and otherwise decoding just that
+//! instruction, so that stepping through a block doesn't fill the cache with blocks
+//! starting partway through it.
+//!
+//! The pages holding cached code are marked with PAGE_TRAP_CODE, so that when the
+//! guest writes to its own code the affected blocks are discarded and redecoded.
 //!
 //! Some good notes on how to make this kind of thing perform well:
 //! http://www.emulators.com/docs/nx25_nostradamus.htm
 
-use memory::Mem;
+use memory::{Mem, PAGE_SHIFT, PAGE_TRAP_CODE};
 
 const CACHE_LINES: usize = 2 << 10;
 
@@ -19,6 +22,8 @@ pub struct Op {
     /// The function that implements instr.  Cached here to avoid looking it up;
     /// this was worth about 10% performance in a quick test.
     pub op: crate::ops::Op,
+    /// Whether it first raises any pending FPU exception; see fpu::checks_pending().
+    pub waits: bool,
 }
 
 #[derive(Default)]
@@ -26,15 +31,18 @@ pub struct BasicBlock {
     /// Number of x86 instruction bytes covered by this block.
     pub len: u32,
     pub ops: Vec<Op>,
+    /// Whether any op waits, so that running the block must check for a pending #MF.
+    pub waits: bool,
 }
 
 impl BasicBlock {
-    fn decode(buf: &[u8], ip: u32, single_step: bool) -> Option<Self> {
+    /// Decode a block of at most max_ops instructions.
+    pub(crate) fn decode(buf: &[u8], ip: u32, max_ops: usize) -> Self {
         let mut ops = Vec::new();
         let mut decoder =
             iced_x86::Decoder::with_ip(32, buf, ip as u64, iced_x86::DecoderOptions::NONE);
         let mut len = 0;
-        while decoder.can_decode() {
+        while decoder.can_decode() && ops.len() < max_ops {
             let instr = decoder.decode();
             if instr.code() == iced_x86::Code::INVALID {
                 // We can hit invalid instruction when decoding confusing control flows.
@@ -51,20 +59,35 @@ impl BasicBlock {
                     // logic a chance to generate smaller basic blocks that will be able to toggle between
                     // the two interpretations.
                     break;
-                } else {
-                    // Otherwise the caller must repair this.
-                    return None;
                 }
+                // Otherwise the instruction really is invalid, which includes a LOCK prefix on an
+                // instruction that can't be locked; executing it raises #UD.
+                ops.push(Op {
+                    op: crate::ops::invalid,
+                    instr,
+                    waits: false,
+                });
+                len = instr.len().max(1) as u32;
+                break;
             }
-            let op =
-                crate::ops::decode(&instr).unwrap_or_else(|| todo!("{instr} ({:?})", instr.code()));
-            ops.push(Op { op, instr });
+            let op = crate::ops::decode(&instr).unwrap_or(crate::ops::unimplemented);
+            let waits = crate::fpu::checks_pending(&instr);
+            ops.push(Op { op, instr, waits });
             len += instr.len() as u32;
-            if instr.flow_control() != iced_x86::FlowControl::Next || single_step {
+            if instr.flow_control() != iced_x86::FlowControl::Next {
+                break;
+            }
+            // popf may set TF, and mov to DR7 may enable breakpoints, which X86 only checks
+            // between blocks.
+            if matches!(
+                instr.code(),
+                iced_x86::Code::Popfd | iced_x86::Code::Popfw | iced_x86::Code::Mov_dr_r32
+            ) {
                 break;
             }
         }
-        Some(BasicBlock { ops, len })
+        let waits = ops.iter().any(|op| op.waits);
+        BasicBlock { ops, len, waits }
     }
 }
 
@@ -77,6 +100,8 @@ struct CacheLine {
 /// Cache of decoded instructions as basic blocks.
 pub struct InstrCache {
     lines: Box<[CacheLine; CACHE_LINES]>,
+    /// The uncached instruction of the last get_single().
+    single: BasicBlock,
     hit: usize,
     miss: usize,
 }
@@ -87,6 +112,7 @@ impl Default for InstrCache {
         lines.resize_with(CACHE_LINES, || Default::default());
         InstrCache {
             lines: lines.try_into().unwrap_or_else(|_| panic!()),
+            single: BasicBlock::default(),
             hit: 0,
             miss: 0,
         }
@@ -103,22 +129,24 @@ impl InstrCache {
         )
     }
 
-    /// Remove any cache line that covers ip.
-    pub fn clear_cache(&mut self, ip: u32) {
+    /// Remove any cache lines overlapping [start, end), after a write to that memory.
+    /// Works at page granularity, because that's what the PAGE_TRAP_CODE bits track.
+    pub fn invalidate(&mut self, mem: Mem, start: u32, end: u32) {
+        let page_mask = (1 << PAGE_SHIFT) - 1;
+        let start = start & !page_mask;
+        let end = end.saturating_add(page_mask) & !page_mask;
         for line in self.lines.iter_mut() {
-            if line.ip <= ip && line.ip + line.block.len > ip {
+            if line.ip != 0 && line.ip < end && line.ip + line.block.len > start {
                 line.ip = 0;
-                return;
             }
         }
+        mem.set_page_traps(start, end - start, PAGE_TRAP_CODE, false);
     }
 
     /// Decode the instructions starting at ip and save in self.lines.
-    fn decode_block(&mut self, mem: Mem, ip: u32, single_step: bool) -> &BasicBlock {
-        let block = match BasicBlock::decode(mem.slice(ip..), ip, single_step) {
-            Some(block) => block,
-            None => unreachable!(),
-        };
+    fn decode_block(&mut self, mem: Mem, ip: u32) -> &BasicBlock {
+        let block = BasicBlock::decode(mem.slice(ip..), ip, usize::MAX);
+        mem.set_page_traps(ip, block.len, PAGE_TRAP_CODE, true);
         // log::info!("added block {:x}..{:x}", ip, ip + block.len);
         // if block.len == 1 {
         //     log::info!(
@@ -144,13 +172,46 @@ impl InstrCache {
             return &self.lines[index].block;
         } else {
             self.miss += 1;
-            self.decode_block(mem, ip, false)
+            self.decode_block(mem, ip)
+        }
+    }
+
+    /// Gets a block whose first op is the instruction at ip, for running just that one:
+    /// the cached block starting there, or else the instruction decoded on its own.
+    pub fn get_single<'a>(&'a mut self, mem: Mem, ip: u32) -> &'a BasicBlock {
+        let index = ip as usize % self.lines.len();
+        if self.lines[index].ip == ip {
+            self.hit += 1;
+            return &self.lines[index].block;
         }
+        self.single = BasicBlock::decode(mem.slice(ip..), ip, 1);
+        &self.single
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_doesnt_cache_midblock() {
+        let mut buf = vec![0u8; 0x2000];
+        // mov eax,1; mov ecx,2; jmp $
+        buf[0x1000..0x100C].copy_from_slice(&[0xb8, 1, 0, 0, 0, 0xb9, 2, 0, 0, 0, 0xeb, 0xfe]);
+        let pages = memory::page_table(buf.len() as u32);
+        let range = buf.as_mut_ptr_range();
+        let mem =
+            Mem::from_ptrs(range.start as *const u8..range.end as *const u8).with_pages(&pages);
 
-    /// Change cache such that there's a single basic block at ip.
-    /// This means the next get_block() will get a block with a single instruction.
-    pub fn make_single_step(&mut self, mem: Mem, ip: u32) {
-        self.decode_block(mem, ip, true);
+        let mut icache = InstrCache::default();
+        assert_eq!(icache.get_block(mem, 0x1000).ops.len(), 3);
+        // The cached block serves stepping from its start...
+        assert_eq!(icache.get_single(mem, 0x1000).ops.len(), 3);
+        assert_eq!(icache.hit, 1);
+        // ...and from partway through, the one instruction is decoded but not cached.
+        let single = icache.get_single(mem, 0x1005);
+        assert_eq!(single.ops.len(), 1);
+        assert_eq!(single.ops[0].instr.ip(), 0x1005);
+        assert!(icache.lines.iter().all(|line| line.ip != 0x1005));
     }
 }
@@ -0,0 +1,523 @@
+//! A minimal x86-64 assembler, covering just the instructions the JIT emits.
+
+/// Host registers, numbered as in their encoding.
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum R {
+    Rax = 0,
+    Rcx,
+    Rdx,
+    Rbx,
+    Rsp,
+    Rbp,
+    Rsi,
+    Rdi,
+    R8,
+    R9,
+    R10,
+    R11,
+    R12,
+    R13,
+    R14,
+    R15,
+}
+
+impl R {
+    fn low(self) -> u8 {
+        self as u8 & 7
+    }
+}
+
+/// A memory operand, [base + index*scale + disp].
+#[derive(Clone, Copy, Debug)]
+pub struct M {
+    base: R,
+    index: Option<(R, u8)>,
+    disp: i32,
+}
+
+/// [base + disp]
+pub fn at(base: R, disp: i32) -> M {
+    M {
+        base,
+        index: None,
+        disp,
+    }
+}
+
+/// [base + index*scale + disp]
+pub fn at_index(base: R, index: R, scale: u32, disp: i32) -> M {
+    debug_assert!(index != R::Rsp);
+    let scale = match scale {
+        1 => 0,
+        2 => 1,
+        4 => 2,
+        8 => 3,
+        _ => unreachable!("scale {scale}"),
+    };
+    M {
+        base,
+        index: Some((index, scale)),
+        disp,
+    }
+}
+
+/// The arithmetic ops sharing the classic encodings, numbered as in their /digit.
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Alu {
+    Add = 0,
+    Or,
+    Adc,
+    Sbb,
+    And,
+    Sub,
+    Xor,
+    Cmp,
+}
+
+/// A position in the code that jumps can target before it's known.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Label(usize);
+
+/// Where a rel32 jump goes.
+enum Target {
+    Label(Label),
+    /// An absolute host address, outside the code being assembled.
+    Addr(usize),
+}
+
+#[derive(Default)]
+pub struct Asm {
+    pub buf: Vec<u8>,
+    /// The offset each label was bound to, if it has been.
+    labels: Vec<Option<usize>>,
+    /// The offset of each rel32 to fill in, and where it jumps.
+    fixups: Vec<(usize, Target)>,
+}
+
+impl Asm {
+    pub fn pos(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub fn label(&mut self) -> Label {
+        self.labels.push(None);
+        Label(self.labels.len() - 1)
+    }
+
+    pub fn bind(&mut self, label: Label) {
+        debug_assert!(self.labels[label.0].is_none());
+        self.labels[label.0] = Some(self.pos());
+    }
+
+    /// Resolve all jumps, for the code placed at host address `at`.
+    pub fn finish(mut self, at: usize) -> Vec<u8> {
+        for (pos, target) in std::mem::take(&mut self.fixups) {
+            let dest = match target {
+                Target::Label(label) => at + self.labels[label.0].expect("unbound label"),
+                Target::Addr(addr) => addr,
+            };
+            let rel = rel32(at + pos + 4, dest);
+            self.buf[pos..pos + 4].copy_from_slice(&rel.to_le_bytes());
+        }
+        self.buf
+    }
+
+    fn byte(&mut self, b: u8) {
+        self.buf.push(b);
+    }
+
+    fn bytes(&mut self, bs: &[u8]) {
+        self.buf.extend_from_slice(bs);
+    }
+
+    fn imm32(&mut self, imm: u32) {
+        self.bytes(&imm.to_le_bytes());
+    }
+
+    /// Emit a REX prefix if any of its bits are needed.  byte_reg is for an 8-bit
+    /// register operand in the reg field, where spl..dil need a REX to be addressed.
+    fn rex(&mut self, w: bool, reg: u8, index: u8, base: u8, byte_reg: bool) {
+        let rex = 0x40 | (w as u8) << 3 | (reg >> 3) << 2 | (index >> 3) << 1 | (base >> 3);
+        if rex != 0x40 || (byte_reg && (4..8).contains(&reg)) {
+            self.byte(rex);
+        }
+    }
+
+    fn modrm_mem(&mut self, reg: u8, m: M) {
+        let base = m.base.low();
+        let (mode, disp_len) = if m.disp == 0 && base != 5 {
+            (0, 0)
+        } else if m.disp as i8 as i32 == m.disp {
+            (1, 1)
+        } else {
+            (2, 4)
+        };
+        let reg = reg & 7;
+        if m.index.is_some() || base == 4 {
+            self.byte(mode << 6 | reg << 3 | 4);
+            let (index, scale) = m
+                .index
+                .map_or((4, 0), |(index, scale)| (index.low(), scale));
+            self.byte(scale << 6 | index << 3 | base);
+        } else {
+            self.byte(mode << 6 | reg << 3 | base);
+        }
+        self.bytes(&m.disp.to_le_bytes()[..disp_len]);
+    }
+
+    /// An instruction with a register and a memory operand.
+    fn op_mem(&mut self, prefix: &[u8], opcode: &[u8], w: bool, reg: u8, m: M, byte_reg: bool) {
+        self.bytes(prefix);
+        let index = m.index.map_or(0, |(index, _)| index as u8);
+        self.rex(w, reg, index, m.base as u8, byte_reg);
+        self.bytes(opcode);
+        self.modrm_mem(reg, m);
+    }
+
+    /// An instruction with two register operands, reg in the ModRM reg field.
+    fn op_reg(&mut self, opcode: &[u8], w: bool, reg: u8, rm: R) {
+        self.rex(w, reg, 0, rm as u8, false);
+        self.bytes(opcode);
+        self.byte(0xC0 | (reg & 7) << 3 | rm.low());
+    }
+
+    /// mov r32, [m]
+    pub fn load32(&mut self, r: R, m: M) {
+        self.op_mem(&[], &[0x8B], false, r as u8, m, false);
+    }
+
+    /// movzx r32, byte [m]
+    pub fn load8(&mut self, r: R, m: M) {
+        self.op_mem(&[], &[0x0F, 0xB6], false, r as u8, m, false);
+    }
+
+    /// movzx r32, word [m]
+    pub fn load16(&mut self, r: R, m: M) {
+        self.op_mem(&[], &[0x0F, 0xB7], false, r as u8, m, false);
+    }
+
+    /// movsx r32, byte [m]
+    pub fn load8s(&mut self, r: R, m: M) {
+        self.op_mem(&[], &[0x0F, 0xBE], false, r as u8, m, false);
+    }
+
+    /// movsx r32, word [m]
+    pub fn load16s(&mut self, r: R, m: M) {
+        self.op_mem(&[], &[0x0F, 0xBF], false, r as u8, m, false);
+    }
+
+    /// mov r64, [m]
+    pub fn load64(&mut self, r: R, m: M) {
+        self.op_mem(&[], &[0x8B], true, r as u8, m, false);
+    }
+
+    /// mov [m], r32
+    pub fn store32(&mut self, m: M, r: R) {
+        self.op_mem(&[], &[0x89], false, r as u8, m, false);
+    }
+
+    /// mov [m], r8
+    pub fn store8(&mut self, m: M, r: R) {
+        self.op_mem(&[], &[0x88], false, r as u8, m, true);
+    }
+
+    /// mov [m], r64
+    pub fn store64(&mut self, m: M, r: R) {
+        self.op_mem(&[], &[0x89], true, r as u8, m, false);
+    }
+
+    /// mov dword [m], imm32
+    pub fn store32_imm(&mut self, m: M, imm: u32) {
+        self.op_mem(&[], &[0xC7], false, 0, m, false);
+        self.imm32(imm);
+    }
+
+    /// mov byte [m], imm8
+    pub fn store8_imm(&mut self, m: M, imm: u8) {
+        self.op_mem(&[], &[0xC6], false, 0, m, false);
+        self.byte(imm);
+    }
+
+    /// mov r32, imm32
+    pub fn mov32_imm(&mut self, r: R, imm: u32) {
+        self.rex(false, 0, 0, r as u8, false);
+        self.byte(0xB8 + r.low());
+        self.imm32(imm);
+    }
+
+    /// mov r64, imm64
+    pub fn mov64_imm(&mut self, r: R, imm: u64) {
+        self.rex(true, 0, 0, r as u8, false);
+        self.byte(0xB8 + r.low());
+        self.bytes(&imm.to_le_bytes());
+    }
+
+    /// mov dst, src (32-bit)
+    pub fn mov32(&mut self, dst: R, src: R) {
+        self.op_reg(&[0x89], false, src as u8, dst);
+    }
+
+    /// mov dst, src (64-bit)
+    pub fn mov64(&mut self, dst: R, src: R) {
+        self.op_reg(&[0x89], true, src as u8, dst);
+    }
+
+    /// op dst, src (32-bit)
+    pub fn alu32(&mut self, op: Alu, dst: R, src: R) {
+        self.op_reg(&[(op as u8) << 3 | 1], false, src as u8, dst);
+    }
+
+    /// op dst, src (8-bit, of the low bytes of rax..rbx)
+    pub fn alu8(&mut self, op: Alu, dst: R, src: R) {
+        debug_assert!((dst as u8) < 4 && (src as u8) < 4);
+        self.op_reg(&[(op as u8) << 3], false, src as u8, dst);
+    }
+
+    /// op r32, [m]
+    pub fn alu32_mem(&mut self, op: Alu, r: R, m: M) {
+        self.op_mem(&[], &[(op as u8) << 3 | 3], false, r as u8, m, false);
+    }
+
+    /// op r8, byte [m]
+    pub fn alu8_mem(&mut self, op: Alu, r: R, m: M) {
+        self.op_mem(&[], &[(op as u8) << 3 | 2], false, r as u8, m, true);
+    }
+
+    /// op r32, imm32
+    pub fn alu32_imm(&mut self, op: Alu, r: R, imm: u32) {
+        self.op_reg(&[0x81], false, op as u8, r);
+        self.imm32(imm);
+    }
+
+    /// op r64, imm32 (sign-extended)
+    pub fn alu64_imm(&mut self, op: Alu, r: R, imm: i32) {
+        self.op_reg(&[0x81], true, op as u8, r);
+        self.imm32(imm as u32);
+    }
+
+    /// op dst, src (64-bit)
+    pub fn alu64(&mut self, op: Alu, dst: R, src: R) {
+        self.op_reg(&[(op as u8) << 3 | 1], true, src as u8, dst);
+    }
+
+    /// op byte [m], imm8
+    pub fn alu8_mem_imm(&mut self, op: Alu, m: M, imm: u8) {
+        self.op_mem(&[], &[0x80], false, op as u8, m, false);
+        self.byte(imm);
+    }
+
+    /// test a, b (32-bit)
+    pub fn test32(&mut self, a: R, b: R) {
+        self.op_reg(&[0x85], false, b as u8, a);
+    }
+
+    /// test a, b (8-bit, of the low bytes of rax..rbx)
+    pub fn test8(&mut self, a: R, b: R) {
+        debug_assert!((a as u8) < 4 && (b as u8) < 4);
+        self.op_reg(&[0x84], false, b as u8, a);
+    }
+
+    /// test a, b (64-bit)
+    pub fn test64(&mut self, a: R, b: R) {
+        self.op_reg(&[0x85], true, b as u8, a);
+    }
+
+    /// test r8, imm8 (of the low bytes of rax..rbx)
+    pub fn test8_imm(&mut self, r: R, imm: u8) {
+        debug_assert!((r as u8) < 4);
+        self.op_reg(&[0xF6], false, 0, r);
+        self.byte(imm);
+    }
+
+    /// lea r32, [m]
+    pub fn lea32(&mut self, r: R, m: M) {
+        self.op_mem(&[], &[0x8D], false, r as u8, m, false);
+    }
+
+    /// shr r32, imm8
+    pub fn shr32_imm(&mut self, r: R, imm: u8) {
+        self.op_reg(&[0xC1], false, 5, r);
+        self.byte(imm);
+    }
+
+    /// movzx dst, src8 (of the low bytes of rax..rbx)
+    pub fn movzx8(&mut self, dst: R, src: R) {
+        debug_assert!((src as u8) < 4);
+        self.rex(false, dst as u8, 0, src as u8, false);
+        self.bytes(&[0x0F, 0xB6]);
+        self.byte(0xC0 | dst.low() << 3 | src.low());
+    }
+
+    pub fn push(&mut self, r: R) {
+        self.rex(false, 0, 0, r as u8, false);
+        self.byte(0x50 + r.low());
+    }
+
+    pub fn pop(&mut self, r: R) {
+        self.rex(false, 0, 0, r as u8, false);
+        self.byte(0x58 + r.low());
+    }
+
+    /// call r64
+    pub fn call(&mut self, r: R) {
+        self.op_reg(&[0xFF], false, 2, r);
+    }
+
+    /// Call a host function, clobbering rax.
+    pub fn call_abs(&mut self, func: usize) {
+        self.mov64_imm(R::Rax, func as u64);
+        self.call(R::Rax);
+    }
+
+    /// jmp r64
+    pub fn jmp_reg(&mut self, r: R) {
+        self.op_reg(&[0xFF], false, 4, r);
+    }
+
+    pub fn ret(&mut self) {
+        self.byte(0xC3);
+    }
+
+    pub fn jmp(&mut self, label: Label) {
+        self.byte(0xE9);
+        self.fixup(Target::Label(label));
+    }
+
+    /// jmp to a host address outside of this code.  Returns the offset of the rel32,
+    /// for patching to go elsewhere later.
+    pub fn jmp_abs(&mut self, addr: usize) -> usize {
+        self.byte(0xE9);
+        let pos = self.pos();
+        self.fixup(Target::Addr(addr));
+        pos
+    }
+
+    /// j<cc> label, where cc is the condition's encoding (as in 0F 80+cc).
+    pub fn jcc(&mut self, cc: u8, label: Label) {
+        self.bytes(&[0x0F, 0x80 | cc]);
+        self.fixup(Target::Label(label));
+    }
+
+    fn fixup(&mut self, target: Target) {
+        self.fixups.push((self.pos(), target));
+        self.imm32(0);
+    }
+}
+
+/// The rel32 of a jump whose next instruction is at `from`, to `to`.
+pub fn rel32(from: usize, to: usize) -> i32 {
+    let rel = to as i64 - from as i64;
+    i32::try_from(rel).expect("jump out of range")
+}
+
+/// The condition codes of jcc/setcc/cmovcc.
+pub mod cc {
+    pub const O: u8 = 0x0;
+    pub const NO: u8 = 0x1;
+    pub const B: u8 = 0x2;
+    pub const AE: u8 = 0x3;
+    pub const E: u8 = 0x4;
+    pub const NE: u8 = 0x5;
+    pub const BE: u8 = 0x6;
+    pub const A: u8 = 0x7;
+    pub const S: u8 = 0x8;
+    pub const NS: u8 = 0x9;
+    pub const P: u8 = 0xA;
+    pub const NP: u8 = 0xB;
+    pub const L: u8 = 0xC;
+    pub const GE: u8 = 0xD;
+    pub const LE: u8 = 0xE;
+    pub const G: u8 = 0xF;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encodings checked against a disassembler.
+    #[test]
+    fn encodings() {
+        let cases: &[(&dyn Fn(&mut Asm), &[u8])] = &[
+            // mov eax, [rbx+0x10]
+            (&|a| a.load32(R::Rax, at(R::Rbx, 0x10)), &[0x8B, 0x43, 0x10]),
+            // mov ecx, [r12+rsi]
+            (
+                &|a| a.load32(R::Rcx, at_index(R::R12, R::Rsi, 1, 0)),
+                &[0x41, 0x8B, 0x0C, 0x34],
+            ),
+            // movzx r8d, byte [r14+r8]
+            (
+                &|a| a.load8(R::R8, at_index(R::R14, R::R8, 1, 0)),
+                &[0x47, 0x0F, 0xB6, 0x04, 0x06],
+            ),
+            // mov [r13+0], eax: r13 as a base needs a displacement.
+            (
+                &|a| a.store32(at(R::R13, 0), R::Rax),
+                &[0x41, 0x89, 0x45, 0x00],
+            ),
+            // mov [rsp+8], rax
+            (
+                &|a| a.store64(at(R::Rsp, 8), R::Rax),
+                &[0x48, 0x89, 0x44, 0x24, 0x08],
+            ),
+            // mov byte [rbx+0x200], 7
+            (
+                &|a| a.store8_imm(at(R::Rbx, 0x200), 7),
+                &[0xC6, 0x83, 0x00, 0x02, 0x00, 0x00, 0x07],
+            ),
+            // lea eax, [rax+rcx*4-8]
+            (
+                &|a| a.lea32(R::Rax, at_index(R::Rax, R::Rcx, 4, -8)),
+                &[0x8D, 0x44, 0x88, 0xF8],
+            ),
+            // sub r13, 5
+            (
+                &|a| a.alu64_imm(Alu::Sub, R::R13, 5),
+                &[0x49, 0x81, 0xED, 5, 0, 0, 0],
+            ),
+            // cmp al, dl
+            (&|a| a.alu8(Alu::Cmp, R::Rax, R::Rdx), &[0x38, 0xD0]),
+            // xor ecx, [rbx+4]
+            (
+                &|a| a.alu32_mem(Alu::Xor, R::Rcx, at(R::Rbx, 4)),
+                &[0x33, 0x4B, 0x04],
+            ),
+            // mov [rsi+r12], cl, spelled with r12 as the base
+            (
+                &|a| a.store8(at_index(R::R12, R::Rsi, 1, 0), R::Rcx),
+                &[0x41, 0x88, 0x0C, 0x34],
+            ),
+            // mov r15, rsi
+            (&|a| a.mov64(R::R15, R::Rsi), &[0x49, 0x89, 0xF7]),
+            // push r12; pop rbx
+            (&|a| a.push(R::R12), &[0x41, 0x54]),
+            (&|a| a.pop(R::Rbx), &[0x5B]),
+            // shr edx, 12
+            (&|a| a.shr32_imm(R::Rdx, 12), &[0xC1, 0xEA, 0x0C]),
+            // test dl, 0x12
+            (&|a| a.test8_imm(R::Rdx, 0x12), &[0xF6, 0xC2, 0x12]),
+            // jmp rax
+            (&|a| a.jmp_reg(R::Rax), &[0xFF, 0xE0]),
+        ];
+        for (i, (emit, bytes)) in cases.iter().enumerate() {
+            let mut asm = Asm::default();
+            emit(&mut asm);
+            assert_eq!(asm.finish(0), *bytes, "case {i}");
+        }
+    }
+
+    #[test]
+    fn jumps() {
+        let mut asm = Asm::default();
+        let label = asm.label();
+        asm.jcc(cc::NE, label);
+        asm.ret();
+        asm.bind(label);
+        asm.jmp_abs(0x1000);
+        let code = asm.finish(0x100);
+        // jne +1; ret; jmp 0x1000 from 0x10c.
+        assert_eq!(code, [0x0F, 0x85, 1, 0, 0, 0, 0xC3, 0xE9, 0xF4, 0x0E, 0, 0]);
+    }
+}
@@ -0,0 +1,102 @@
+//! Host memory for generated code.
+//!
+//! The region is mapped once and filled from the front; when it's full, or when the code
+//! in it goes stale, the JIT starts over from empty.  Pages are writable only while the
+//! JIT is writing to them, and executable otherwise.
+
+pub struct CodeMem {
+    ptr: *mut u8,
+    len: usize,
+    /// The offset of the first free byte.
+    used: usize,
+}
+
+impl CodeMem {
+    pub fn new(len: usize) -> std::io::Result<Self> {
+        // Safety: a fresh anonymous mapping doesn't alias anything.
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_EXEC,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(CodeMem {
+            ptr: ptr as *mut u8,
+            len,
+            used: 0,
+        })
+    }
+
+    /// The host address of the next code to be added.
+    pub fn next(&self) -> usize {
+        self.ptr as usize + self.used
+    }
+
+    pub fn free(&self) -> usize {
+        self.len - self.used
+    }
+
+    /// Forget all the code, to fill the region afresh.
+    pub fn clear(&mut self) {
+        self.used = 0;
+    }
+
+    fn protect(&mut self, writable: bool) {
+        let prot = if writable {
+            libc::PROT_READ | libc::PROT_WRITE
+        } else {
+            libc::PROT_READ | libc::PROT_EXEC
+        };
+        // Safety: changes the protection of our own mapping only.
+        if unsafe { libc::mprotect(self.ptr as *mut libc::c_void, self.len, prot) } != 0 {
+            panic!("mprotect: {}", std::io::Error::last_os_error());
+        }
+    }
+
+    /// Make the code writable for the duration of f, which may add() and patch().
+    pub fn write<T>(&mut self, f: impl FnOnce(&mut Writer) -> T) -> T {
+        self.protect(true);
+        let ret = f(&mut Writer(self));
+        self.protect(false);
+        ret
+    }
+}
+
+/// Access to CodeMem while it's writable.
+pub struct Writer<'a>(&'a mut CodeMem);
+
+impl Writer<'_> {
+    /// Append code that was assembled for the address next() returned, returning that.
+    pub fn add(&mut self, code: &[u8]) -> usize {
+        let at = self.0.next();
+        assert!(code.len() <= self.0.free());
+        // Safety: in bounds of the mapping, per the assert, which is writable.
+        unsafe { std::ptr::copy_nonoverlapping(code.as_ptr(), at as *mut u8, code.len()) };
+        self.0.used += code.len();
+        at
+    }
+
+    /// Point the rel32 of a jump at `pos` to `to`.
+    pub fn patch(&mut self, pos: usize, to: usize) {
+        debug_assert!((self.0.ptr as usize..self.0.next()).contains(&pos));
+        let rel = super::asm::rel32(pos + 4, to);
+        // Safety: within code previously added, which is writable.
+        unsafe { std::ptr::write_unaligned(pos as *mut i32, rel) };
+    }
+}
+
+impl Drop for CodeMem {
+    fn drop(&mut self) {
+        // Safety: we own the mapping, and the code in it is no longer running.
+        unsafe {
+            libc::munmap(self.ptr as *mut libc::c_void, self.len);
+        }
+    }
+}
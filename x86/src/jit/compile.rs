@@ -0,0 +1,715 @@
+//! Translation of a basic block to host code.
+//!
+//! The generated code keeps no guest state in host registers between instructions: each
+//! instruction loads its operands from the CPU and stores its results back.  So every
+//! instruction boundary is a point where the code can hand over to the interpreter, which
+//! is how anything out of the ordinary is dealt with.  Instructions not translated here
+//! call the interpreter's function for the op, and an access to a page with trap bits
+//! leaves the block just before the instruction, for the interpreter to run it.
+
+use super::asm::{at, at_index, cc, Alu, Asm, Label, M, R};
+use crate::{flags::FlagOp, icache::Op, x86::CPU};
+use iced_x86::{Code, ConditionCode, FlowControl, Instruction, Mnemonic, OpKind, Register};
+use memory::{PAGE_SHIFT, PAGE_TRAP_GUARD, PAGE_TRAP_READ};
+use std::mem::offset_of;
+
+// Host registers holding state for the whole of the generated code, as set up by the
+// entry code in mod.rs.  All are callee-saved, so they survive calls out to Rust.
+/// The CPU.
+pub const CPU_REG: R = R::Rbx;
+/// The host address of guest address 0.
+pub const MEM: R = R::R12;
+/// The number of further guest instructions that may run.
+pub const BUDGET: R = R::R13;
+/// The page trap table.
+pub const PAGES: R = R::R14;
+/// The Ctx.
+pub const CTX: R = R::R15;
+
+// Scratch registers, for the duration of an instruction.
+/// The guest address of the memory operand.
+const ADDR: R = R::Rsi;
+/// The first operand, which the flags record as x.
+const X: R = R::Rcx;
+/// The second operand, which the flags record as y.
+const Y: R = R::Rdx;
+const RESULT: R = R::Rax;
+
+const R32: i32 = offset_of!(CPU, regs.r32) as i32;
+const EIP: i32 = offset_of!(CPU, regs.eip) as i32;
+const FLAGS_OP: i32 = offset_of!(CPU, flags.op) as i32;
+const FLAGS_BITS: i32 = offset_of!(CPU, flags.bits) as i32;
+const FLAGS_CARRY: i32 = offset_of!(CPU, flags.carry) as i32;
+const FLAGS_X: i32 = offset_of!(CPU, flags.x) as i32;
+const FLAGS_Y: i32 = offset_of!(CPU, flags.y) as i32;
+const FLAGS_RESULT: i32 = offset_of!(CPU, flags.result) as i32;
+
+/// The trap bits that make reads leave for the interpreter, as in ops/helpers.rs; any
+/// trap bit does for writes.
+const READ_TRAPS: u8 = PAGE_TRAP_READ | PAGE_TRAP_GUARD;
+const WRITE_TRAPS: u8 = !0;
+
+/// The slot of a 32-bit register in the CPU.
+fn reg32(reg: Register) -> M {
+    at(CPU_REG, R32 + 4 * (reg as i32 - Register::EAX as i32))
+}
+
+/// The slot of an 8-bit register in the CPU: the low or second byte of a 32-bit one.
+fn reg8(reg: Register) -> M {
+    let index = reg as i32 - Register::AL as i32;
+    if index < 4 {
+        at(CPU_REG, R32 + 4 * index)
+    } else {
+        at(CPU_REG, R32 + 4 * (index - 4) + 1)
+    }
+}
+
+fn eip() -> M {
+    at(CPU_REG, EIP)
+}
+
+/// The guest memory at ADDR.
+fn guest() -> M {
+    at_index(MEM, ADDR, 1, 0)
+}
+
+/// Where an operand is.
+#[derive(Clone, Copy)]
+enum Loc {
+    /// A register, at its slot in the CPU.
+    Reg(M),
+    /// Memory, at the address the instruction computes.
+    Mem,
+    Imm(u32),
+}
+
+/// Whether a jump to addr can be taken without faulting, as CPU::jmp checks.
+fn jumpable(addr: u32, mem_len: u32) -> bool {
+    addr >= 0x1000 && addr < mem_len
+}
+
+/// The host condition code testing the same flags as a guest one.
+fn condition(code: ConditionCode) -> Option<u8> {
+    Some(match code {
+        ConditionCode::None => return None,
+        ConditionCode::o => cc::O,
+        ConditionCode::no => cc::NO,
+        ConditionCode::b => cc::B,
+        ConditionCode::ae => cc::AE,
+        ConditionCode::e => cc::E,
+        ConditionCode::ne => cc::NE,
+        ConditionCode::be => cc::BE,
+        ConditionCode::a => cc::A,
+        ConditionCode::s => cc::S,
+        ConditionCode::ns => cc::NS,
+        ConditionCode::p => cc::P,
+        ConditionCode::np => cc::NP,
+        ConditionCode::l => cc::L,
+        ConditionCode::ge => cc::GE,
+        ConditionCode::le => cc::LE,
+        ConditionCode::g => cc::G,
+    })
+}
+
+pub struct Compiled {
+    pub code: Vec<u8>,
+    /// Jumps to blocks that aren't compiled yet, to patch when they are: the offset in
+    /// code of each jump's rel32, and the address of the block.
+    pub links: Vec<(usize, u32)>,
+    /// How many of the instructions were translated, rather than calling the interpreter.
+    pub native: usize,
+}
+
+/// Translate the ops of a block, for code placed at host address `at`.  `exit` is the
+/// host code that returns from generated code, and `compiled` finds the host code of
+/// other blocks, by guest address.
+pub fn compile(
+    ops: &[Op],
+    mem_len: u32,
+    at: usize,
+    exit: usize,
+    compiled: impl Fn(u32) -> Option<usize>,
+) -> Compiled {
+    let mut asm = Asm::default();
+    let to_exit = asm.label();
+    let mut compiler = Compiler {
+        asm,
+        count: ops.len() as u32,
+        mem_len,
+        exit,
+        to_exit,
+        compiled,
+        links: Vec::new(),
+        stubs: Vec::new(),
+        known: None,
+        recorded: None,
+        native: 0,
+    };
+    compiler.block(ops);
+    let Compiler {
+        asm, links, native, ..
+    } = compiler;
+    Compiled {
+        code: asm.finish(at),
+        links,
+        native,
+    }
+}
+
+/// Code placed after a block, off its main path.
+enum Stub {
+    /// Leave before the instruction at index, for the interpreter to run it.
+    Before { index: u32, ip: u32 },
+    /// Leave after the interpreter's op at index said to stop, with rax holding what it
+    /// returned.
+    Stopped { index: u32 },
+}
+
+struct Compiler<F> {
+    asm: Asm,
+    /// The number of instructions in the block.
+    count: u32,
+    mem_len: u32,
+    exit: usize,
+    /// A jump to exit, shared by the block.
+    to_exit: Label,
+    compiled: F,
+    links: Vec<(usize, u32)>,
+    stubs: Vec<(Label, Stub)>,
+    /// The op and width of the flags last recorded by translated code in this block, if
+    /// nothing since has changed the flags.
+    known: Option<(FlagOp, u8)>,
+    /// What the instruction being translated recorded in the flags.
+    recorded: Option<(FlagOp, u8)>,
+    native: usize,
+}
+
+impl<F: Fn(u32) -> Option<usize>> Compiler<F> {
+    fn block(&mut self, ops: &[Op]) {
+        // Blocks only run when the budget covers all of them, leaving the rest to the
+        // interpreter; see Jit::run().
+        let short = self.asm.label();
+        self.asm.alu64_imm(Alu::Cmp, BUDGET, self.count as i32);
+        self.asm.jcc(cc::B, short);
+        self.asm.alu64_imm(Alu::Sub, BUDGET, self.count as i32);
+
+        for (index, op) in ops.iter().enumerate() {
+            let index = index as u32;
+            let native = self.translate(index, &op.instr);
+            if native {
+                self.native += 1;
+            } else {
+                self.call_op(index, op);
+            }
+            // Ops may also rearrange how the flags are recorded without changing them.
+            self.known = match self.recorded.take() {
+                Some(recorded) => Some(recorded),
+                None if !native || op.instr.rflags_modified() != 0 => None,
+                None => self.known,
+            };
+        }
+
+        // A block that doesn't end in a jump goes on to the next instruction.
+        let last = &ops[ops.len() - 1].instr;
+        if last.flow_control() == FlowControl::Next {
+            if matches!(last.code(), Code::Popfd | Code::Popfw | Code::Mov_dr_r32) {
+                // X86 checks for the TF or breakpoints these may enable between blocks.
+                self.asm.store32_imm(eip(), last.next_ip32());
+                self.asm.jmp(self.to_exit);
+            } else {
+                self.link(last.next_ip32());
+            }
+        }
+
+        self.asm.bind(short);
+        self.asm.store32_imm(eip(), ops[0].instr.ip32());
+        self.asm.bind(self.to_exit);
+        self.asm.jmp_abs(self.exit);
+        for (label, stub) in std::mem::take(&mut self.stubs) {
+            self.asm.bind(label);
+            match stub {
+                Stub::Before { index, ip } => {
+                    self.asm
+                        .alu64_imm(Alu::Add, BUDGET, (self.count - index) as i32);
+                    self.asm.store32_imm(eip(), ip);
+                }
+                Stub::Stopped { index } => {
+                    // 1 means the instruction ran and 2 that it didn't, which counts.
+                    self.asm.shr32_imm(RESULT, 1);
+                    self.asm.alu64(Alu::Add, BUDGET, RESULT);
+                    self.asm
+                        .alu64_imm(Alu::Add, BUDGET, (self.count - index - 1) as i32);
+                }
+            }
+            self.asm.jmp(self.to_exit);
+        }
+    }
+
+    /// Jump to the block at target, or if it isn't compiled, return to run it.
+    fn link(&mut self, target: u32) {
+        self.asm.store32_imm(eip(), target);
+        match (self.compiled)(target) {
+            Some(code) => {
+                self.asm.jmp_abs(code);
+            }
+            None => {
+                let pos = self.asm.jmp_abs(self.exit);
+                self.links.push((pos, target));
+            }
+        }
+    }
+
+    /// Go on to the block at eip, wherever that is, if it's compiled.
+    fn link_dynamic(&mut self) {
+        self.asm.mov64(R::Rdi, CPU_REG);
+        self.asm.mov64(R::Rsi, CTX);
+        self.asm.call_abs(super::next as *const () as usize);
+        self.asm.test64(RESULT, RESULT);
+        self.asm.jcc(cc::E, self.to_exit);
+        self.asm.jmp_reg(RESULT);
+    }
+
+    /// Run an instruction by calling the interpreter's op.
+    fn call_op(&mut self, index: u32, op: &Op) {
+        self.asm.mov64(R::Rdi, CPU_REG);
+        self.asm.mov64(R::Rsi, CTX);
+        self.asm.mov64_imm(R::Rdx, op as *const Op as u64);
+        self.asm.call_abs(super::call_op as *const () as usize);
+        self.asm.test32(RESULT, RESULT);
+        let stopped = self.asm.label();
+        self.stubs.push((stopped, Stub::Stopped { index }));
+        self.asm.jcc(cc::NE, stopped);
+        if op.instr.flow_control() != FlowControl::Next {
+            self.link_dynamic();
+        }
+    }
+
+    /// A label that leaves before the instruction at index, for the interpreter to run it.
+    fn before(&mut self, index: u32, instr: &Instruction) -> Label {
+        if let Some((label, _)) = self
+            .stubs
+            .iter()
+            .find(|(_, stub)| matches!(stub, Stub::Before { index: i, .. } if *i == index))
+        {
+            return *label;
+        }
+        let label = self.asm.label();
+        let ip = instr.ip32();
+        self.stubs.push((label, Stub::Before { index, ip }));
+        label
+    }
+
+    /// Translate an instruction into host code, or return false, having emitted nothing,
+    /// if it isn't one of those handled here.
+    fn translate(&mut self, index: u32, instr: &Instruction) -> bool {
+        if instr.has_lock_prefix() {
+            return false;
+        }
+        match instr.mnemonic() {
+            Mnemonic::Nop => true,
+            Mnemonic::Mov => self.mov(index, instr),
+            Mnemonic::Movzx | Mnemonic::Movsx => self.extend(index, instr),
+            Mnemonic::Lea => self.lea(instr),
+            Mnemonic::Add
+            | Mnemonic::Or
+            | Mnemonic::And
+            | Mnemonic::Sub
+            | Mnemonic::Xor
+            | Mnemonic::Cmp
+            | Mnemonic::Test => self.alu(index, instr),
+            Mnemonic::Inc | Mnemonic::Dec => self.inc_dec(index, instr),
+            Mnemonic::Push => self.push(index, instr),
+            Mnemonic::Pop => self.pop(index, instr),
+            Mnemonic::Jmp => self.jmp(instr),
+            Mnemonic::Call => self.call(index, instr),
+            Mnemonic::Ret => self.ret(index, instr),
+            _ if instr.is_jcc_short_or_near() => self.jcc(instr),
+            _ => false,
+        }
+    }
+
+    /// Where operand n is and its width in bits, if it's somewhere handled here.
+    /// Immediates have no width of their own.
+    fn operand(&self, instr: &Instruction, n: u32) -> Option<(Loc, u32)> {
+        match instr.op_kind(n) {
+            OpKind::Register => {
+                let reg = instr.op_register(n);
+                if reg.is_gpr32() {
+                    Some((Loc::Reg(reg32(reg)), 32))
+                } else if reg.is_gpr8() {
+                    Some((Loc::Reg(reg8(reg)), 8))
+                } else {
+                    None
+                }
+            }
+            OpKind::Memory => {
+                let plain = |reg: Register| reg == Register::None || reg.is_gpr32();
+                if !plain(instr.memory_base())
+                    || !plain(instr.memory_index())
+                    || matches!(instr.segment_prefix(), Register::FS | Register::GS)
+                {
+                    return None;
+                }
+                Some((Loc::Mem, instr.memory_size().size() as u32 * 8))
+            }
+            OpKind::Immediate8 | OpKind::Immediate32 | OpKind::Immediate8to32 => {
+                Some((Loc::Imm(instr.immediate(n) as u32), 0))
+            }
+            _ => None,
+        }
+    }
+
+    /// Compute the address of the memory operand into ADDR, as ops::x86_addr() does.
+    fn address(&mut self, instr: &Instruction) {
+        let base = instr.memory_base();
+        let index = instr.memory_index();
+        let mut disp = instr.memory_displacement32() as i32;
+        if base != Register::None {
+            self.asm.load32(ADDR, reg32(base));
+        } else {
+            self.asm.mov32_imm(ADDR, disp as u32);
+            disp = 0;
+        }
+        if index != Register::None {
+            self.asm.load32(RESULT, reg32(index));
+            let scale = instr.memory_index_scale();
+            self.asm.lea32(ADDR, at_index(ADDR, RESULT, scale, disp));
+        } else if disp != 0 {
+            self.asm.lea32(ADDR, at(ADDR, disp));
+        }
+    }
+
+    /// Leave for the interpreter if an access of len bytes at ADDR touches a page with
+    /// any of the trap bits in mask, as ops::page_traps() would find.
+    fn check(&mut self, index: u32, instr: &Instruction, len: u32, mask: u8) {
+        let before = self.before(index, instr);
+        self.asm.mov32(RESULT, ADDR);
+        self.asm.shr32_imm(RESULT, PAGE_SHIFT as u8);
+        self.asm.load8(RESULT, at_index(PAGES, RESULT, 1, 0));
+        if len > 1 {
+            // An access that wraps around the top of the address space always faults.
+            self.asm.alu32_imm(Alu::Cmp, ADDR, len.wrapping_neg());
+            self.asm.jcc(cc::A, before);
+            self.asm.lea32(Y, at(ADDR, len as i32 - 1));
+            self.asm.shr32_imm(Y, PAGE_SHIFT as u8);
+            self.asm.alu8_mem(Alu::Or, RESULT, at_index(PAGES, Y, 1, 0));
+        }
+        self.asm.test8_imm(RESULT, mask);
+        self.asm.jcc(cc::NE, before);
+    }
+
+    /// Compute the address of the memory operand and check an access of bits to it.
+    fn access(&mut self, index: u32, instr: &Instruction, bits: u32, write: bool) {
+        self.address(instr);
+        let mask = if write { WRITE_TRAPS } else { READ_TRAPS };
+        self.check(index, instr, bits / 8, mask);
+    }
+
+    fn load(&mut self, r: R, loc: Loc, bits: u32) {
+        let m = match loc {
+            Loc::Reg(m) => m,
+            Loc::Mem => guest(),
+            Loc::Imm(imm) => {
+                let imm = if bits == 8 { imm & 0xFF } else { imm };
+                self.asm.mov32_imm(r, imm);
+                return;
+            }
+        };
+        match bits {
+            8 => self.asm.load8(r, m),
+            32 => self.asm.load32(r, m),
+            _ => unreachable!(),
+        }
+    }
+
+    fn store(&mut self, loc: Loc, r: R, bits: u32) {
+        let m = match loc {
+            Loc::Reg(m) => m,
+            Loc::Mem => guest(),
+            Loc::Imm(_) => unreachable!(),
+        };
+        match bits {
+            8 => self.asm.store8(m, r),
+            32 => self.asm.store32(m, r),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Record X, Y and RESULT as the operands of op, as LazyFlags::record() does.
+    fn record(&mut self, op: FlagOp, bits: u32) {
+        let flags = |offset| at(CPU_REG, offset);
+        self.asm.store8_imm(flags(FLAGS_OP), op as u8);
+        self.asm.store8_imm(flags(FLAGS_BITS), bits as u8);
+        self.asm.store8_imm(flags(FLAGS_CARRY), 0);
+        self.asm.store32(flags(FLAGS_X), X);
+        self.asm.store32(flags(FLAGS_Y), Y);
+        self.asm.store32(flags(FLAGS_RESULT), RESULT);
+        self.recorded = Some((op, bits as u8));
+    }
+
+    fn mov(&mut self, index: u32, instr: &Instruction) -> bool {
+        let (Some((dst, bits)), Some((src, _))) = (self.operand(instr, 0), self.operand(instr, 1))
+        else {
+            return false;
+        };
+        if !matches!(bits, 8 | 32) {
+            return false;
+        }
+        if matches!(dst, Loc::Mem) {
+            self.access(index, instr, bits, true);
+        } else if matches!(src, Loc::Mem) {
+            self.access(index, instr, bits, false);
+        }
+        self.load(X, src, bits);
+        self.store(dst, X, bits);
+        true
+    }
+
+    fn extend(&mut self, index: u32, instr: &Instruction) -> bool {
+        let dst = instr.op0_register();
+        if !dst.is_gpr32() {
+            return false;
+        }
+        let (src, bits) = match instr.op1_kind() {
+            OpKind::Register => {
+                let reg = instr.op1_register();
+                if reg.is_gpr8() {
+                    (reg8(reg), 8)
+                } else if reg.is_gpr16() {
+                    (reg32(reg.full_register32()), 16)
+                } else {
+                    return false;
+                }
+            }
+            OpKind::Memory => {
+                let Some((_, bits)) = self.operand(instr, 1) else {
+                    return false;
+                };
+                self.access(index, instr, bits, false);
+                (guest(), bits)
+            }
+            _ => return false,
+        };
+        match (instr.mnemonic() == Mnemonic::Movsx, bits) {
+            (false, 8) => self.asm.load8(X, src),
+            (false, 16) => self.asm.load16(X, src),
+            (true, 8) => self.asm.load8s(X, src),
+            (true, 16) => self.asm.load16s(X, src),
+            _ => unreachable!(),
+        }
+        self.asm.store32(reg32(dst), X);
+        true
+    }
+
+    fn lea(&mut self, instr: &Instruction) -> bool {
+        let dst = instr.op0_register();
+        if !dst.is_gpr32() || self.operand(instr, 1).is_none() {
+            return false;
+        }
+        self.address(instr);
+        self.asm.store32(reg32(dst), ADDR);
+        true
+    }
+
+    fn alu(&mut self, index: u32, instr: &Instruction) -> bool {
+        let (alu, op, writes) = match instr.mnemonic() {
+            Mnemonic::Add => (Alu::Add, FlagOp::Add, true),
+            Mnemonic::Or => (Alu::Or, FlagOp::Logic, true),
+            Mnemonic::And => (Alu::And, FlagOp::Logic, true),
+            Mnemonic::Sub => (Alu::Sub, FlagOp::Sub, true),
+            Mnemonic::Xor => (Alu::Xor, FlagOp::Logic, true),
+            Mnemonic::Cmp => (Alu::Sub, FlagOp::Sub, false),
+            Mnemonic::Test => (Alu::And, FlagOp::Logic, false),
+            _ => unreachable!(),
+        };
+        let (Some((dst, bits)), Some((src, _))) = (self.operand(instr, 0), self.operand(instr, 1))
+        else {
+            return false;
+        };
+        if !matches!(bits, 8 | 32) || matches!(dst, Loc::Imm(_)) {
+            return false;
+        }
+        if matches!(dst, Loc::Mem) {
+            self.access(index, instr, bits, writes);
+        } else if matches!(src, Loc::Mem) {
+            self.access(index, instr, bits, false);
+        }
+        self.load(X, dst, bits);
+        self.load(Y, src, bits);
+        self.asm.mov32(RESULT, X);
+        if bits == 8 {
+            self.asm.alu8(alu, RESULT, Y);
+            self.asm.movzx8(RESULT, RESULT);
+        } else {
+            self.asm.alu32(alu, RESULT, Y);
+        }
+        self.record(op, bits);
+        if writes {
+            self.store(dst, RESULT, bits);
+        }
+        true
+    }
+
+    fn inc_dec(&mut self, index: u32, instr: &Instruction) -> bool {
+        let Some((dst, 32)) = self.operand(instr, 0) else {
+            return false;
+        };
+        let inc = instr.mnemonic() == Mnemonic::Inc;
+        // Keep the CF of the op before, unless that was an inc/dec that already did.
+        let recorded = self.asm.label();
+        self.asm
+            .alu8_mem_imm(Alu::Cmp, at(CPU_REG, FLAGS_OP), FlagOp::IncDec as u8);
+        self.asm.jcc(cc::E, recorded);
+        self.asm.mov64(R::Rdi, CPU_REG);
+        self.asm
+            .call_abs(super::before_inc_dec as *const () as usize);
+        self.asm.bind(recorded);
+
+        if matches!(dst, Loc::Mem) {
+            self.access(index, instr, 32, true);
+        }
+        self.load(X, dst, 32);
+        self.asm.lea32(RESULT, at(X, if inc { 1 } else { -1 }));
+        self.asm.mov32_imm(Y, if inc { 1 } else { !0 });
+        self.record(FlagOp::IncDec, 32);
+        self.store(dst, RESULT, 32);
+        true
+    }
+
+    /// Push X, as ops::push() does.
+    fn push_x(&mut self, index: u32, instr: &Instruction) {
+        self.asm.load32(ADDR, reg32(Register::ESP));
+        self.asm.lea32(ADDR, at(ADDR, -4));
+        self.check(index, instr, 4, WRITE_TRAPS);
+        self.asm.store32(reg32(Register::ESP), ADDR);
+        self.asm.store32(guest(), X);
+    }
+
+    fn push(&mut self, index: u32, instr: &Instruction) -> bool {
+        let value = match instr.op0_kind() {
+            OpKind::Register if instr.op0_register().is_gpr32() => {
+                Loc::Reg(reg32(instr.op0_register()))
+            }
+            OpKind::Immediate8to32 | OpKind::Immediate32 => Loc::Imm(instr.immediate(0) as u32),
+            _ => return false,
+        };
+        self.load(X, value, 32);
+        self.push_x(index, instr);
+        true
+    }
+
+    /// Pop into X, as ops::pop() does, leaving the new esp, plus extra, in ADDR.
+    fn pop_x(&mut self, index: u32, instr: &Instruction, extra: i32) {
+        self.asm.load32(ADDR, reg32(Register::ESP));
+        self.check(index, instr, 4, READ_TRAPS);
+        self.asm.load32(X, guest());
+        self.asm.lea32(ADDR, at(ADDR, 4 + extra));
+    }
+
+    fn pop(&mut self, index: u32, instr: &Instruction) -> bool {
+        let dst = instr.op0_register();
+        if instr.op0_kind() != OpKind::Register || !dst.is_gpr32() {
+            return false;
+        }
+        self.pop_x(index, instr, 0);
+        self.asm.store32(reg32(Register::ESP), ADDR);
+        self.asm.store32(reg32(dst), X);
+        true
+    }
+
+    fn jmp(&mut self, instr: &Instruction) -> bool {
+        if !matches!(instr.code(), Code::Jmp_rel8_32 | Code::Jmp_rel32_32)
+            || !jumpable(instr.near_branch32(), self.mem_len)
+        {
+            return false;
+        }
+        self.link(instr.near_branch32());
+        true
+    }
+
+    fn call(&mut self, index: u32, instr: &Instruction) -> bool {
+        if instr.code() != Code::Call_rel32_32 || !jumpable(instr.near_branch32(), self.mem_len) {
+            return false;
+        }
+        self.asm.mov32_imm(X, instr.next_ip32());
+        self.push_x(index, instr);
+        self.link(instr.near_branch32());
+        true
+    }
+
+    fn ret(&mut self, index: u32, instr: &Instruction) -> bool {
+        let extra = match instr.code() {
+            Code::Retnd => 0,
+            Code::Retnd_imm16 => instr.immediate16() as i32,
+            _ => return false,
+        };
+        self.pop_x(index, instr, extra);
+        // A return address that would fault is left to the interpreter, as is one into
+        // retrowin32 itself, which isn't guest memory.
+        let before = self.before(index, instr);
+        self.asm.alu32_imm(Alu::Cmp, X, 0x1000);
+        self.asm.jcc(cc::B, before);
+        self.asm.alu32_mem(Alu::Cmp, X, at(CTX, super::CTX_MEM_LEN));
+        self.asm.jcc(cc::AE, before);
+        self.asm.store32(reg32(Register::ESP), ADDR);
+        self.asm.store32(eip(), X);
+        self.link_dynamic();
+        true
+    }
+
+    fn jcc(&mut self, instr: &Instruction) -> bool {
+        let Some(cc) = condition(instr.condition_code()) else {
+            return false;
+        };
+        let target = instr.near_branch32();
+        if !jumpable(target, self.mem_len) {
+            return false;
+        }
+        let taken = self.asm.label();
+        let reads_cf = matches!(cc, cc::B | cc::AE | cc::BE | cc::A);
+        match self.known {
+            // The flags came from translated code earlier in the block, so redo its op on
+            // the host for the host flags to match.  AF is the only flag that might not,
+            // and no condition reads it.  An inc/dec leaves CF to an earlier op.
+            Some((op, bits)) if op != FlagOp::IncDec || !reads_cf => {
+                let flags = |offset| at(CPU_REG, offset);
+                let (alu, first) = match op {
+                    FlagOp::Add | FlagOp::IncDec => (Some(Alu::Add), FLAGS_X),
+                    FlagOp::Sub => (Some(Alu::Cmp), FLAGS_X),
+                    FlagOp::Logic => (None, FLAGS_RESULT),
+                    FlagOp::None => unreachable!(),
+                };
+                match (alu, bits) {
+                    (Some(alu), 8) => {
+                        self.asm.load8(RESULT, flags(first));
+                        self.asm.alu8_mem(alu, RESULT, flags(FLAGS_Y));
+                    }
+                    (Some(alu), _) => {
+                        self.asm.load32(RESULT, flags(first));
+                        self.asm.alu32_mem(alu, RESULT, flags(FLAGS_Y));
+                    }
+                    (None, 8) => {
+                        self.asm.load8(RESULT, flags(first));
+                        self.asm.test8(RESULT, RESULT);
+                    }
+                    (None, _) => {
+                        self.asm.load32(RESULT, flags(first));
+                        self.asm.test32(RESULT, RESULT);
+                    }
+                }
+                self.asm.jcc(cc, taken);
+            }
+            _ => {
+                self.asm.mov64(R::Rdi, CPU_REG);
+                self.asm.mov32_imm(R::Rsi, cc as u32);
+                self.asm.call_abs(super::condition as *const () as usize);
+                self.asm.test32(RESULT, RESULT);
+                self.asm.jcc(cc::NE, taken);
+            }
+        }
+        self.link(instr.next_ip32());
+        self.asm.bind(taken);
+        self.link(target);
+        true
+    }
+}
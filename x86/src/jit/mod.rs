@@ -0,0 +1,497 @@
+//! A backend that translates hot basic blocks to x86-64 host code.
+//!
+//! Blocks start out in the interpreter, and once one has run enough times it's compiled
+//! (see compile.rs).  Compiled blocks jump straight to each other where their successor
+//! is known, so hot loops run without coming back out to Rust.  Whatever the generated
+//! code doesn't handle itself, it leaves to the interpreter: instructions it doesn't
+//! translate call the interpreter's op, and memory accesses to pages with any trap bits
+//! leave the block for the interpreter to redo the access.  So faults, page protection
+//! and tracking writes to code all work as they do in the interpreter.
+//!
+//! Writes to code flush all compiled code, rather than tracking what links to what.
+//!
+//! Only built for x86-64 unix hosts; see X86::enable_jit().
+
+mod asm;
+mod code;
+mod compile;
+
+use self::{
+    asm::{at, cc, Asm, R},
+    code::CodeMem,
+    compile::{BUDGET, CPU_REG, CTX, MEM, PAGES},
+};
+use crate::{
+    backend::{Backend, Interpreter},
+    icache::{BasicBlock, Op},
+    registers::Flags,
+    x86::{CPUState, Fault, CPU},
+};
+use memory::{Mem, PAGE_SHIFT, PAGE_TRAP_CODE};
+use std::{any::Any, collections::HashMap, mem::offset_of};
+
+/// Size of the host memory holding generated code.
+const CODE_SIZE: usize = 32 << 20;
+/// Number of times a block runs in the interpreter before it's compiled.
+const THRESHOLD: u32 = 16;
+/// The most instructions compiled as one block.  Blocks only run when the budget covers
+/// all of them, and the interpreter's blocks run on through whatever follows e.g. a hlt.
+const MAX_OPS: usize = 100;
+
+/// State shared with the generated code while it runs.
+#[repr(C)]
+struct Ctx<'a> {
+    /// The number of instructions that may yet run, kept in a register while in
+    /// generated code and stored here on leaving it.
+    budget: usize,
+    mem_len: u32,
+    mem: Mem<'a>,
+    blocks: &'a HashMap<u32, Block>,
+    /// A panic caught in an op, to resume once out of the generated code.
+    panic: Option<Box<dyn Any + Send>>,
+}
+
+const CTX_BUDGET: i32 = offset_of!(Ctx<'static>, budget) as i32;
+const CTX_MEM_LEN: i32 = offset_of!(Ctx<'static>, mem_len) as i32;
+
+/// The code of enter(): fn(cpu, ctx, code, membase, pages).
+type Enter = extern "C" fn(*mut CPU, *mut Ctx, usize, *mut u8, *const u8);
+
+struct Block {
+    /// The host code.
+    entry: usize,
+    /// The end of the guest code.
+    end: u32,
+    /// The number of instructions.
+    count: usize,
+}
+
+#[derive(Default)]
+struct Stats {
+    blocks: usize,
+    /// Instructions in compiled blocks, and how many of those were translated.
+    instrs: usize,
+    native: usize,
+    /// Instructions run in compiled code.
+    ran: usize,
+    flushes: usize,
+}
+
+pub struct Jit {
+    /// For code that isn't hot, and for single steps.
+    interp: Interpreter,
+    code: CodeMem,
+    /// The code that enters and leaves generated code, at the start of code.
+    enter: usize,
+    exit: usize,
+    blocks: HashMap<u32, Block>,
+    /// The decoded blocks, whose Ops the generated code calls by address.
+    decoded: Vec<BasicBlock>,
+    /// Jumps to the blocks at these addresses, to patch when they're compiled.
+    pending: HashMap<u32, Vec<usize>>,
+    /// The number of runs in the interpreter of blocks at these addresses.
+    heat: HashMap<u32, u32>,
+    pub(crate) threshold: u32,
+    /// The memory size the code was compiled for, as jump targets are checked against it.
+    mem_len: u32,
+    stats: Stats,
+}
+
+impl Jit {
+    pub fn new() -> std::io::Result<Self> {
+        let mut jit = Jit {
+            interp: Interpreter::default(),
+            code: CodeMem::new(CODE_SIZE)?,
+            enter: 0,
+            exit: 0,
+            blocks: HashMap::new(),
+            decoded: Vec::new(),
+            pending: HashMap::new(),
+            heat: HashMap::new(),
+            threshold: THRESHOLD,
+            mem_len: 0,
+            stats: Stats::default(),
+        };
+        jit.add_stubs();
+        Ok(jit)
+    }
+
+    /// Add the code entering and leaving generated code, which keeps the state it needs
+    /// in callee-saved registers; see compile.rs.
+    fn add_stubs(&mut self) {
+        let mut asm = Asm::default();
+        for r in [R::Rbp, CPU_REG, MEM, BUDGET, PAGES, CTX] {
+            asm.push(r);
+        }
+        // Keep the stack aligned for calls out of generated code.
+        asm.alu64_imm(asm::Alu::Sub, R::Rsp, 8);
+        asm.mov64(CPU_REG, R::Rdi);
+        asm.mov64(CTX, R::Rsi);
+        asm.mov64(MEM, R::Rcx);
+        asm.mov64(PAGES, R::R8);
+        asm.load64(BUDGET, at(CTX, CTX_BUDGET));
+        asm.jmp_reg(R::Rdx);
+
+        let exit = asm.pos();
+        asm.store64(at(CTX, CTX_BUDGET), BUDGET);
+        asm.alu64_imm(asm::Alu::Add, R::Rsp, 8);
+        for r in [CTX, PAGES, BUDGET, MEM, CPU_REG, R::Rbp] {
+            asm.pop(r);
+        }
+        asm.ret();
+
+        let at = self.code.next();
+        let code = asm.finish(at);
+        self.enter = self.code.write(|w| w.add(&code));
+        self.exit = self.enter + exit;
+    }
+
+    /// Discard all compiled code.
+    fn flush(&mut self) {
+        self.blocks.clear();
+        self.decoded.clear();
+        self.pending.clear();
+        self.heat.clear();
+        self.code.clear();
+        self.add_stubs();
+        self.stats.flushes += 1;
+    }
+
+    /// Compile the block at eip, returning its code, or None if there wasn't room.
+    fn compile(&mut self, mem: Mem, eip: u32) -> Option<usize> {
+        let block = BasicBlock::decode(mem.slice(eip..), eip, MAX_OPS);
+        let blocks = &self.blocks;
+        let compiled = compile::compile(
+            &block.ops,
+            mem.len(),
+            self.code.next(),
+            self.exit,
+            |target| blocks.get(&target).map(|block| block.entry),
+        );
+        if compiled.code.len() > self.code.free() {
+            self.flush();
+            return None;
+        }
+
+        let pending = &mut self.pending;
+        let entry = self.code.write(|w| {
+            let entry = w.add(&compiled.code);
+            for &(pos, target) in &compiled.links {
+                pending.entry(target).or_default().push(entry + pos);
+            }
+            // Including jumps from this block to itself.
+            for pos in pending.remove(&eip).unwrap_or_default() {
+                w.patch(pos, entry);
+            }
+            entry
+        });
+
+        mem.set_page_traps(eip, block.len, PAGE_TRAP_CODE, true);
+        self.blocks.insert(
+            eip,
+            Block {
+                entry,
+                end: eip + block.len,
+                count: block.ops.len(),
+            },
+        );
+        self.stats.blocks += 1;
+        self.stats.instrs += block.ops.len();
+        self.stats.native += compiled.native;
+        self.decoded.push(block);
+        Some(entry)
+    }
+
+    /// Run generated code from entry, returning the number of instructions run.
+    fn enter(&mut self, cpu: &mut CPU, mem: Mem, entry: usize, budget: usize) -> usize {
+        let mut ctx = Ctx {
+            budget,
+            mem_len: mem.len(),
+            mem,
+            blocks: &self.blocks,
+            panic: None,
+        };
+        // Safety: self.enter holds the code of an Enter, from add_stubs().
+        let enter: Enter = unsafe { std::mem::transmute(self.enter) };
+        enter(
+            cpu,
+            &mut ctx,
+            entry,
+            mem.get_ptr_mut_unchecked::<u8>(0),
+            mem.page_table_ptr(),
+        );
+        if let Some(panic) = ctx.panic {
+            std::panic::resume_unwind(panic);
+        }
+        let ran = budget - ctx.budget;
+        self.stats.ran += ran;
+        ran
+    }
+}
+
+impl Backend for Jit {
+    fn run(&mut self, cpu: &mut CPU, mem: Mem, budget: usize) -> usize {
+        // The generated code checks accesses against the page table, so without one
+        // there is only the interpreter.
+        if mem.page_table_ptr().is_null() {
+            return self.interp.run(cpu, mem, budget);
+        }
+        if mem.len() != self.mem_len {
+            // Jump targets were checked against the old size.
+            if self.mem_len != 0 {
+                self.flush();
+            }
+            self.mem_len = mem.len();
+        }
+        let eip = cpu.regs.eip;
+        let entry = match self.blocks.get(&eip) {
+            Some(block) if block.count > budget => None,
+            Some(block) => Some(block.entry),
+            None => {
+                let heat = self.heat.entry(eip).or_default();
+                *heat += 1;
+                if *heat > self.threshold {
+                    self.heat.remove(&eip);
+                    self.compile(mem, eip)
+                } else {
+                    None
+                }
+            }
+        };
+        if let Some(entry) = entry {
+            let ran = self.enter(cpu, mem, entry, budget);
+            // The first instruction may have left for the interpreter, to fault or
+            // to access a page with traps.
+            if ran > 0 || cpu.state != CPUState::Running {
+                return ran;
+            }
+        }
+        self.interp.run(cpu, mem, budget)
+    }
+
+    fn step(&mut self, cpu: &mut CPU, mem: Mem) -> usize {
+        self.interp.step(cpu, mem)
+    }
+
+    fn invalidate(&mut self, mem: Mem, start: u32, end: u32) {
+        let page_mask = (1 << PAGE_SHIFT) - 1;
+        let (start, end) = (
+            start & !page_mask,
+            end.saturating_add(page_mask) & !page_mask,
+        );
+        if self
+            .blocks
+            .iter()
+            .any(|(&ip, block)| ip < end && block.end > start)
+        {
+            self.flush();
+        }
+        self.interp.invalidate(mem, start, end);
+    }
+
+    fn stats(&self) -> String {
+        let stats = &self.stats;
+        format!(
+            "{}\njit: {} blocks, {}/{} instructions translated, {} instructions run, {} flushes",
+            self.interp.stats(),
+            stats.blocks,
+            stats.native,
+            stats.instrs,
+            stats.ran,
+            stats.flushes
+        )
+    }
+}
+
+// The functions below are called from generated code.
+
+/// Run an op, as CPU::execute_ops() does.  Returns 0 to go on, or to leave the generated
+/// code, 1 when the op ran and 2 when it didn't.
+extern "C" fn call_op(cpu: &mut CPU, ctx: &mut Ctx, op: &Op) -> u32 {
+    if op.waits && cpu.fpu.status.contains(crate::fpu::Status::ES) {
+        cpu.regs.eip = op.instr.ip32();
+        cpu.fault(Fault::FloatingPoint);
+        return 2;
+    }
+    cpu.regs.eip = op.instr.next_ip32();
+    // Unwinding out of here would be undefined, so it waits until we're back in Rust.
+    let mem = ctx.mem;
+    if let Err(panic) = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        (op.op)(cpu, mem, &op.instr)
+    })) {
+        ctx.panic = Some(panic);
+        return 1;
+    }
+    match cpu.state {
+        CPUState::Running if cpu.written.is_some() => 1,
+        CPUState::Running => 0,
+        CPUState::Fault(Fault::SingleStep) => 1,
+        CPUState::Fault(_) | CPUState::Error(_) => {
+            cpu.regs.eip = op.instr.ip32();
+            1
+        }
+        _ => 1,
+    }
+}
+
+/// The code of the block at eip, after a jump to somewhere not known when compiling, or
+/// 0 to leave the generated code.
+extern "C" fn next(cpu: &CPU, ctx: &Ctx) -> usize {
+    // X86 checks for a TF set by e.g. iret between blocks.
+    if cpu.state != CPUState::Running || cpu.flags.contains(Flags::TF) {
+        return 0;
+    }
+    ctx.blocks.get(&cpu.regs.eip).map_or(0, |block| block.entry)
+}
+
+/// Whether the condition of a jcc with condition code cc holds.
+extern "C" fn condition(cpu: &CPU, cc: u32) -> u32 {
+    let flags = cpu.flags.get();
+    let has = |flag| flags.contains(flag);
+    let holds = match cc as u8 & !1 {
+        cc::O => has(Flags::OF),
+        cc::B => has(Flags::CF),
+        cc::E => has(Flags::ZF),
+        cc::BE => has(Flags::CF) || has(Flags::ZF),
+        cc::S => has(Flags::SF),
+        cc::P => has(Flags::PF),
+        cc::L => has(Flags::SF) != has(Flags::OF),
+        cc::LE => has(Flags::ZF) || has(Flags::SF) != has(Flags::OF),
+        _ => unreachable!(),
+    };
+    // Odd codes are the negations.
+    (holds != (cc & 1 != 0)) as u32
+}
+
+extern "C" fn before_inc_dec(cpu: &mut CPU) {
+    cpu.flags.before_inc_dec();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{backend::diff_cpus, Register};
+
+    /// Run code placed at 0x1000 until the CPU stops, in runs of at most budget
+    /// instructions, returning the CPU, memory and instruction count.
+    fn run(backend: &mut dyn Backend, code: &[u8], budget: usize) -> (CPU, Vec<u8>, usize) {
+        let mut buf = vec![0u8; 0x4000];
+        buf[0x1000..][..code.len()].copy_from_slice(code);
+        let pages = memory::page_table(buf.len() as u32);
+        let range = buf.as_mut_ptr_range();
+        let mem =
+            Mem::from_ptrs(range.start as *const u8..range.end as *const u8).with_pages(&pages);
+        let mut cpu = CPU::new();
+        cpu.regs.eip = 0x1000;
+        cpu.regs.set32(Register::ESP, 0x3F00);
+        let mut count = 0;
+        while cpu.state == CPUState::Running {
+            let ran = backend.run(&mut cpu, mem, budget);
+            assert!(ran <= budget);
+            count += ran;
+            if let Some((start, end)) = cpu.written.take() {
+                backend.invalidate(mem, start, end);
+            }
+            assert!(count < 100_000, "runaway at {:x}", cpu.regs.eip);
+        }
+        (cpu, buf, count)
+    }
+
+    /// Run code with the interpreter and with the JIT compiling every block, check that
+    /// they end up the same, and return the JIT.
+    fn check(code: &[u8], budget: usize) -> Jit {
+        let (want_cpu, want_mem, want_count) = run(&mut Interpreter::default(), code, budget);
+        let mut jit = Jit::new().unwrap();
+        jit.threshold = 0;
+        let (cpu, mem, count) = run(&mut jit, code, budget);
+        assert_eq!(diff_cpus(&want_cpu, &cpu), None);
+        if let Some(addr) = want_mem.iter().zip(&mem).position(|(x, y)| x != y) {
+            panic!(
+                "memory at {addr:x}: {:x} vs {:x}",
+                want_mem[addr], mem[addr]
+            );
+        }
+        assert_eq!(count, want_count);
+        assert!(jit.stats.blocks > 0 && jit.stats.ran > 0);
+        jit
+    }
+
+    /// mov ecx,100; xor eax,eax; mov ebx,2000h
+    /// loop: add eax,ecx; mov [ebx+ecx*4],eax; xor [ebx],eax; dec ecx; jnz loop
+    /// hlt
+    const LOOP: &[u8] = &[
+        0xb9, 0x64, 0x00, 0x00, 0x00, 0x31, 0xc0, 0xbb, 0x00, 0x20, 0x00, 0x00, 0x01, 0xc8, 0x89,
+        0x04, 0x8b, 0x31, 0x03, 0x49, 0x75, 0xf6, 0xf4,
+    ];
+
+    #[test]
+    fn runs_loop() {
+        let jit = check(LOOP, 1000);
+        // All of the 3 + 100*5 + 1 instructions.
+        assert_eq!(jit.stats.ran, 504);
+    }
+
+    #[test]
+    fn stops_within_budget() {
+        // Budgets that end runs partway through blocks.
+        for budget in [2, 3, 7] {
+            check(LOOP, budget);
+        }
+    }
+
+    #[test]
+    fn runs_calls_and_conditions() {
+        // Calls and returns, 8-bit registers and memory, extensions, jcc after each kind
+        // of flags op, and ops left to the interpreter in between.
+        //   mov ecx,50; xor ebx,ebx; xor esi,esi
+        // 1: push ecx; call f; pop ecx; cmp eax,40; jl 2f; inc esi
+        // 2: test al,1; jz 3f; add ebx,eax
+        // 3: cmp ecx,25; inc edx; jb 5f; add bh,cl
+        // 5: dec ecx; jnz 1b; mov [2000h],ebx; mov [2004h],esi; hlt
+        // f: mov eax,[esp+4]; lea eax,[eax+eax*2-7]; sub eax,30; jg 4f; neg eax
+        // 4: movzx edx,al; movsx edi,byte [esp+4]; or dl,80h; mov [ecx+2010h],dl
+        //    xor eax,edi; and eax,7fh; adc eax,0; ret
+        let code = &[
+            0xb9, 0x32, 0x00, 0x00, 0x00, 0x31, 0xdb, 0x31, 0xf6, 0x51, 0xe8, 0x25, 0x00, 0x00,
+            0x00, 0x59, 0x83, 0xf8, 0x28, 0x7c, 0x01, 0x46, 0xa8, 0x01, 0x74, 0x02, 0x01, 0xc3,
+            0x83, 0xf9, 0x19, 0x42, 0x72, 0x02, 0x00, 0xcf, 0x49, 0x75, 0xe2, 0x89, 0x1d, 0x00,
+            0x20, 0x00, 0x00, 0x89, 0x35, 0x04, 0x20, 0x00, 0x00, 0xf4, 0x8b, 0x44, 0x24, 0x04,
+            0x8d, 0x44, 0x40, 0xf9, 0x83, 0xe8, 0x1e, 0x7f, 0x02, 0xf7, 0xd8, 0x0f, 0xb6, 0xd0,
+            0x0f, 0xbe, 0x7c, 0x24, 0x04, 0x80, 0xca, 0x80, 0x88, 0x91, 0x10, 0x20, 0x00, 0x00,
+            0x31, 0xf8, 0x83, 0xe0, 0x7f, 0x83, 0xd0, 0x00, 0xc3,
+        ];
+        check(code, 1000);
+        check(code, 5);
+    }
+
+    #[test]
+    fn faults_midway() {
+        // Reads run off the end of memory on the twelfth time around.
+        //   xor ecx,ecx
+        // 1: mov eax,[ecx*4+3fd0h]; add [2000h],eax; inc ecx; cmp ecx,20; jnz 1b
+        //   hlt
+        let code = &[
+            0x31, 0xc9, 0x8b, 0x04, 0x8d, 0xd0, 0x3f, 0x00, 0x00, 0x01, 0x05, 0x00, 0x20, 0x00,
+            0x00, 0x41, 0x83, 0xf9, 0x14, 0x75, 0xed, 0xf4,
+        ];
+        check(code, 1000);
+
+        // An op left to the interpreter faults: xor edx,edx; mov eax,7; div ecx; hlt
+        let code = &[0x31, 0xd2, 0xb8, 0x07, 0x00, 0x00, 0x00, 0xf7, 0xf1, 0xf4];
+        check(code, 1000);
+    }
+
+    #[test]
+    fn flushes_modified_code() {
+        // The loop patches the immediate of its own mov.
+        //   mov ecx,3
+        // 1: mov eax,1; add [2000h],eax; mov byte [1006h],5; dec ecx; jnz 1b
+        //   hlt
+        let code = &[
+            0xb9, 0x03, 0x00, 0x00, 0x00, 0xb8, 0x01, 0x00, 0x00, 0x00, 0x01, 0x05, 0x00, 0x20,
+            0x00, 0x00, 0xc6, 0x05, 0x06, 0x10, 0x00, 0x00, 0x05, 0x49, 0x75, 0xeb, 0xf4,
+        ];
+        let jit = check(code, 1000);
+        assert!(jit.stats.flushes > 0);
+    }
+}
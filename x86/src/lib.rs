@@ -1,10 +1,22 @@
+pub mod analysis;
+mod backend;
 pub mod debug;
+mod debugregs;
+mod flags;
 mod fpu;
 mod icache;
+#[cfg(all(target_arch = "x86_64", unix))]
+mod jit;
 pub mod ops;
+pub mod real;
 mod registers;
 mod x86;
 
-pub use crate::x86::{CPUState, CPU, X86};
+pub use crate::backend::{compare, Backend, Divergence, Interpreter};
+pub use crate::debugregs::{BreakOn, DebugRegs, HwBreakpoint, DR6_BS};
+pub use crate::flags::LazyFlags;
+pub use crate::fpu::{Status as FPUStatus, FPU};
+pub use crate::x86::{CPUState, Fault, CPU, X86};
 pub use iced_x86::Register;
-pub use ops::set_edx_eax;
+pub use ops::{set_edx_eax, CpuProfile, EDXFeatures};
+pub use registers::{Flags, Registers, CODE_SELECTOR, DATA_SELECTOR, TEB_SELECTOR};
@@ -1,15 +1,68 @@
-use crate::{registers::Flags, x86::CPU, CPUState};
+use crate::{
+    registers::{Flags, CODE_SELECTOR, DATA_SELECTOR, TEB_SELECTOR},
+    x86::CPU,
+    CPUState, Fault,
+};
 use iced_x86::{Instruction, Register};
-use memory::{Extensions, ExtensionsMut, Mem};
+use memory::Mem;
 
 use super::helpers::*;
+use super::math::sub;
 
 pub fn nop(_cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {}
 
+/// enter, with width the operand size in bytes.  A nonzero nesting level, as used for
+/// Pascal-style nested procedures, also copies the enclosing frame pointers to the stack.
+fn enter(cpu: &mut CPU, mem: Mem, instr: &Instruction, width: u32) {
+    let size = instr.immediate16() as u32;
+    let level = (instr.immediate8_2nd() % 32) as u32;
+    let ebp = cpu.regs.get32(Register::EBP);
+    let pushed = width * if level > 0 { level + 1 } else { 1 };
+    // Check the whole push up front, so a fault leaves the stack untouched.
+    let esp = cpu.regs.get32(Register::ESP);
+    if write_traps(cpu, mem, esp - pushed, pushed) {
+        return;
+    }
+    let push_width = |cpu: &mut CPU, value: u32| {
+        if width == 4 {
+            push(cpu, mem, value)
+        } else {
+            push16(cpu, mem, value as u16)
+        }
+    };
+    push_width(cpu, ebp);
+    let frame = cpu.regs.get32(Register::ESP);
+    if level > 0 {
+        for i in 1..level {
+            let addr = ebp.wrapping_sub(width * i);
+            let outer = if width == 4 {
+                read_pod::<u32>(cpu, mem, addr)
+            } else {
+                read_pod::<u16>(cpu, mem, addr) as u32
+            };
+            if !cpu.state.is_running() {
+                cpu.regs.set32(Register::ESP, esp);
+                return;
+            }
+            push_width(cpu, outer);
+        }
+        push_width(cpu, frame);
+    }
+    if width == 4 {
+        cpu.regs.set32(Register::EBP, frame);
+    } else {
+        cpu.regs.set16(Register::BP, frame as u16);
+    }
+    cpu.regs
+        .set32(Register::ESP, cpu.regs.get32(Register::ESP) - size);
+}
+
 pub fn enterd_imm16_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    push(cpu, mem, cpu.regs.get32(Register::EBP));
-    cpu.regs.set32(Register::EBP, cpu.regs.get32(Register::ESP));
-    *cpu.regs.get32_mut(Register::ESP) -= instr.immediate16() as u32;
+    enter(cpu, mem, instr, 4);
+}
+
+pub fn enterw_imm16_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    enter(cpu, mem, instr, 2);
 }
 
 pub fn leaved(cpu: &mut CPU, mem: Mem, _instr: &Instruction) {
@@ -18,6 +71,13 @@ pub fn leaved(cpu: &mut CPU, mem: Mem, _instr: &Instruction) {
     cpu.regs.set32(Register::EBP, ebp);
 }
 
+pub fn leavew(cpu: &mut CPU, mem: Mem, _instr: &Instruction) {
+    // The stack is still 32-bit, so this moves all of ebp but pops only bp.
+    cpu.regs.set32(Register::ESP, cpu.regs.get32(Register::EBP));
+    let bp = pop16(cpu, mem);
+    cpu.regs.set16(Register::BP, bp);
+}
+
 pub fn pushd_r16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     // Pushing segment registers is subtle:
     // "If the source operand is a segment register (16 bits) and [...]
@@ -43,86 +103,130 @@ pub fn push_r32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 }
 
 pub fn push_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let value = rm32(cpu, mem, instr).get();
+    let Some(value) = rm32(cpu, mem, instr) else {
+        return;
+    };
+    let value = value.get();
     push(cpu, mem, value);
 }
 
 pub fn push_rm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let value = rm16(cpu, mem, instr).get();
+    let Some(value) = rm16(cpu, mem, instr) else {
+        return;
+    };
+    let value = value.get();
     push16(cpu, mem, value);
 }
 
 pub fn popd_r16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     // See discussion in pushd_r16.
-    let value = pop(cpu, mem);
-    cpu.regs.set16(instr.op0_register(), value as u16);
+    // Only pop once the load succeeds, so a fault leaves esp alone.
+    let esp = cpu.regs.get32(Register::ESP);
+    let value = read_pod::<u32>(cpu, mem, esp);
+    if cpu.state.is_running() && load_segment(cpu, instr.op0_register(), value as u16) {
+        cpu.regs.set32(Register::ESP, esp.wrapping_add(4));
+    }
 }
 
 pub fn pop_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    // The destination address is computed after esp is incremented, but a fault leaves
+    // esp as it was.
+    let esp = cpu.regs.get32(Register::ESP);
     let value = pop(cpu, mem);
-    let x = rm32(cpu, mem, instr);
+    if !cpu.state.is_running() {
+        return;
+    }
+    let Some(x) = rm32(cpu, mem, instr) else {
+        cpu.regs.set32(Register::ESP, esp);
+        return;
+    };
     x.set(value);
 }
 
 pub fn pop_rm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let esp = cpu.regs.get32(Register::ESP);
     let value = pop16(cpu, mem);
-    let x = rm16(cpu, mem, instr);
+    if !cpu.state.is_running() {
+        return;
+    }
+    let Some(x) = rm16(cpu, mem, instr) else {
+        cpu.regs.set32(Register::ESP, esp);
+        return;
+    };
     x.set(value);
 }
 
 pub fn mov_rm32_imm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let x = rm32(cpu, mem, instr);
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     x.set(instr.immediate32());
 }
 
 pub fn mov_rm32_r32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let value = cpu.regs.get32(instr.op1_register());
-    let x = rm32(cpu, mem, instr);
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     x.set(value);
 }
 
 pub fn mov_r32_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let value = op1_rm32(cpu, mem, instr);
+    let Some(value) = op1_rm32(cpu, mem, instr) else {
+        return;
+    };
     cpu.regs.set32(instr.op0_register(), value);
 }
 
 pub fn mov_r16_rm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let value = op1_rm16(cpu, mem, instr);
+    let Some(value) = op1_rm16(cpu, mem, instr) else {
+        return;
+    };
     cpu.regs.set16(instr.op0_register(), value);
 }
 
 pub fn mov_rm16_r16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = cpu.regs.get16(instr.op1_register());
-    let x = rm16(cpu, mem, instr);
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
     x.set(y);
 }
 
 pub fn mov_rm16_imm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = instr.immediate16();
-    let x = rm16(cpu, mem, instr);
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
     x.set(y);
 }
 
 pub fn mov_r8_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let value = op1_rm8(cpu, mem, instr);
+    let Some(value) = op1_rm8(cpu, mem, instr) else {
+        return;
+    };
     cpu.regs.set8(instr.op0_register(), value);
 }
 
 pub fn mov_rm8_r8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = cpu.regs.get8(instr.op1_register());
-    let x = rm8(cpu, mem, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     x.set(y);
 }
 
 pub fn mov_rm8_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = instr.immediate8();
-    let x = rm8(cpu, mem, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     x.set(y);
 }
 
 pub fn mov_moffs8_al(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let addr = x86_addr(cpu, instr);
-    mem.put_pod::<u8>(addr, cpu.regs.get8(Register::AL));
+    write_pod::<u8>(cpu, mem, addr, cpu.regs.get8(Register::AL));
 }
 
 pub fn mov_r32m16_sreg(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
@@ -132,70 +236,163 @@ pub fn mov_r32m16_sreg(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
         iced_x86::OpKind::Register => cpu.regs.set32(instr.op0_register(), y as u32),
         iced_x86::OpKind::Memory => {
             let addr = x86_addr(cpu, instr);
-            mem.put_pod::<u16>(addr, y)
+            write_pod::<u16>(cpu, mem, addr, y)
         }
         _ => unimplemented!(),
     }
 }
 
+pub fn mov_rm16_sreg(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = cpu.regs.get16(instr.op1_register());
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
+    x.set(y);
+}
+
 pub fn mov_sreg_r32m16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     // This weirdly is either a 16-bit or 32-write, so we must match to determine.
-    // TODO: this is supposed to do segment selector validation stuff.
     let y = match instr.op1_kind() {
         iced_x86::OpKind::Register => cpu.regs.get32(instr.op1_register()) as u16,
-        iced_x86::OpKind::Memory => mem.get_pod::<u16>(x86_addr(cpu, instr)),
+        iced_x86::OpKind::Memory => match read_operand::<u16>(cpu, mem, instr) {
+            Some(value) => value,
+            None => return,
+        },
         _ => unimplemented!(),
     };
-    cpu.regs.set16(instr.op0_register(), y);
+    load_segment(cpu, instr.op0_register(), y);
+}
+
+pub fn mov_sreg_rm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = op1_rm16(cpu, mem, instr) else {
+        return;
+    };
+    load_segment(cpu, instr.op0_register(), y);
+}
+
+// The debug register movs are privileged, so on Windows the guest can only set the
+// debug registers through a CONTEXT.  We let it execute them anyway, as the simplest way
+// for test code to set a breakpoint.
+
+pub fn mov_r32_dr(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+    let n = instr.op1_register() as usize - Register::DR0 as usize;
+    cpu.regs.set32(instr.op0_register(), cpu.debug.get(n));
+}
+
+pub fn mov_dr_r32(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+    let n = instr.op0_register() as usize - Register::DR0 as usize;
+    cpu.debug.set(n, cpu.regs.get32(instr.op1_register()));
+}
+
+/// Load a segment register, checking the selector as the CPU would against the
+/// descriptors Windows provides.  Returns false if the load faulted.
+fn load_segment(cpu: &mut CPU, reg: Register, selector: u16) -> bool {
+    if reg == Register::CS {
+        // mov to cs doesn't exist; far jumps and calls are how to load it.
+        cpu.fault(Fault::InvalidOpcode);
+        return false;
+    }
+    let valid = match selector & !3 {
+        // The null selector is fine to load, just not to access through; but ss
+        // must always be usable.
+        0 => reg != Register::SS,
+        index => [CODE_SELECTOR, DATA_SELECTOR, TEB_SELECTOR]
+            .iter()
+            .any(|&s| s & !3 == index),
+    };
+    if !valid {
+        cpu.fault(Fault::GeneralProtection);
+        return false;
+    }
+    cpu.regs.set16(reg, selector);
+    true
 }
 
 pub fn movsx_r32_rm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_rm16(cpu, mem, instr) as i16 as u32;
-    let x = rm32(cpu, mem, instr);
+    let Some(y) = op1_rm16(cpu, mem, instr) else {
+        return;
+    };
+    let y = y as i16 as u32;
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     x.set(y);
 }
 
 pub fn movsx_r32_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_rm8(cpu, mem, instr) as i8 as u32;
-    let x = rm32(cpu, mem, instr);
+    let Some(y) = op1_rm8(cpu, mem, instr) else {
+        return;
+    };
+    let y = y as i8 as u32;
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     x.set(y);
 }
 
 pub fn movsx_r16_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_rm8(cpu, mem, instr) as i8 as u16;
-    let x = rm16(cpu, mem, instr);
+    let Some(y) = op1_rm8(cpu, mem, instr) else {
+        return;
+    };
+    let y = y as i8 as u16;
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
     x.set(y);
 }
 
 pub fn movzx_r32_rm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_rm16(cpu, mem, instr) as u32;
-    let x = rm32(cpu, mem, instr);
+    let Some(y) = op1_rm16(cpu, mem, instr) else {
+        return;
+    };
+    let y = y as u32;
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     x.set(y);
 }
 
 pub fn movzx_r32_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_rm8(cpu, mem, instr) as u32;
-    let x = rm32(cpu, mem, instr);
+    let Some(y) = op1_rm8(cpu, mem, instr) else {
+        return;
+    };
+    let y = y as u32;
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     x.set(y);
 }
 
 pub fn movzx_r16_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_rm8(cpu, mem, instr) as u16;
-    let x = rm16(cpu, mem, instr);
+    let Some(y) = op1_rm8(cpu, mem, instr) else {
+        return;
+    };
+    let y = y as u16;
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
     x.set(y);
 }
 
 pub fn cmovb_r32_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_rm32(cpu, mem, instr);
-    let x = rm32(cpu, mem, instr);
+    let Some(y) = op1_rm32(cpu, mem, instr) else {
+        return;
+    };
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     if cpu.flags.contains(Flags::CF) {
         x.set(y);
     }
 }
 
 pub fn cmovne_r32_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_rm32(cpu, mem, instr);
-    let x = rm32(cpu, mem, instr);
+    let Some(y) = op1_rm32(cpu, mem, instr) else {
+        return;
+    };
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     if !cpu.flags.contains(Flags::ZF) {
         x.set(y);
     }
@@ -204,7 +401,9 @@ pub fn cmovne_r32_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 pub fn xchg_rm32_r32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let r1 = instr.op1_register();
     let y = cpu.regs.get32(r1);
-    let x = rm32(cpu, mem, instr);
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     let tmp = x.get();
     x.set(y);
     cpu.regs.set32(r1, tmp);
@@ -213,7 +412,9 @@ pub fn xchg_rm32_r32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 pub fn xchg_rm16_r16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let r1 = instr.op1_register();
     let y = cpu.regs.get16(r1);
-    let x = rm16(cpu, mem, instr);
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
     let tmp = x.get();
     x.set(y);
     cpu.regs.set16(r1, tmp);
@@ -222,59 +423,90 @@ pub fn xchg_rm16_r16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 pub fn xchg_rm8_r8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let r1 = instr.op1_register();
     let y = cpu.regs.get8(r1);
-    let x = rm8(cpu, mem, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     let tmp = x.get();
     x.set(y);
     cpu.regs.set8(r1, tmp);
 }
 
+// cmpxchg and cmpxchg8b are what lock-free code is built on, so they must behave
+// exactly: the comparison sets flags like cmp, and on mismatch the accumulator gets
+// the destination's value.
+
 pub fn cmpxchg_rm32_r32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = cpu.regs.get32(instr.op1_register());
-    match instr.op0_kind() {
-        iced_x86::OpKind::Register => todo!(),
-        iced_x86::OpKind::Memory => {
-            let addr = x86_addr(cpu, instr);
-            let x = mem.get_pod::<u32>(addr);
-            if cpu.regs.get32(Register::EAX) == x {
-                cpu.flags.insert(Flags::ZF);
-                mem.put_pod::<u32>(addr, y);
-            } else {
-                cpu.flags.remove(Flags::ZF);
-                cpu.regs.set32(Register::EAX, y);
-            }
-        }
-        _ => unreachable!(),
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
     };
+    if !cpu.state.is_running() {
+        return;
+    }
+    let eax = cpu.regs.get32(Register::EAX);
+    sub(eax, x.get(), &mut cpu.flags);
+    if cpu.flags.contains(Flags::ZF) {
+        x.set(y);
+    } else {
+        cpu.regs.set32(Register::EAX, x.get());
+    }
 }
 
-pub fn cmpxchg8b_m64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let addr = x86_addr(cpu, instr);
-    let m64 = mem.get_pod::<u64>(addr);
-    let test = get_edx_eax(cpu);
-    if test == m64 {
-        cpu.flags.insert(Flags::ZF);
-        let val =
-            ((cpu.regs.get32(Register::ECX) as u64) << 32) | (cpu.regs.get32(Register::EBX) as u64);
-        mem.put_pod::<u64>(addr, val);
+pub fn cmpxchg_rm16_r16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = cpu.regs.get16(instr.op1_register());
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
+    if !cpu.state.is_running() {
+        return;
+    }
+    let ax = cpu.regs.get16(Register::AX);
+    sub(ax, x.get(), &mut cpu.flags);
+    if cpu.flags.contains(Flags::ZF) {
+        x.set(y);
     } else {
-        cpu.flags.remove(Flags::ZF);
-        set_edx_eax(cpu, m64);
+        cpu.regs.set16(Register::AX, x.get());
     }
 }
 
 pub fn cmpxchg_rm8_r8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_rm8(cpu, mem, instr);
-    let x = rm8(cpu, mem, instr);
+    let y = cpu.regs.get8(instr.op1_register());
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
+    if !cpu.state.is_running() {
+        return;
+    }
     let al = cpu.regs.get8(Register::AL);
-    if al == x.get() {
-        cpu.flags.insert(Flags::ZF);
+    sub(al, x.get(), &mut cpu.flags);
+    if cpu.flags.contains(Flags::ZF) {
         x.set(y);
     } else {
-        cpu.flags.remove(Flags::ZF);
         cpu.regs.set8(Register::AL, x.get());
     }
 }
 
+pub fn cmpxchg8b_m64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let addr = x86_addr(cpu, instr);
+    let m64 = read_pod::<u64>(cpu, mem, addr);
+    if !cpu.state.is_running() {
+        return;
+    }
+    let test = get_edx_eax(cpu);
+    if test == m64 {
+        let val =
+            ((cpu.regs.get32(Register::ECX) as u64) << 32) | (cpu.regs.get32(Register::EBX) as u64);
+        write_pod::<u64>(cpu, mem, addr, val);
+        if !cpu.state.is_running() {
+            return;
+        }
+        cpu.flags.insert(Flags::ZF);
+    } else {
+        cpu.flags.remove(Flags::ZF);
+        set_edx_eax(cpu, m64);
+    }
+}
+
 pub fn lea_r32_m(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
     // lea eax,[esp+10h]
     cpu.regs.set32(instr.op0_register(), x86_addr(cpu, instr));
@@ -282,69 +514,107 @@ pub fn lea_r32_m(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
 
 pub fn seta_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let value = (!cpu.flags.contains(Flags::CF) && !cpu.flags.contains(Flags::ZF)) as u8;
-    let x = rm8(cpu, mem, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     x.set(value);
 }
 
 pub fn setae_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let value = !cpu.flags.contains(Flags::CF) as u8;
-    let x = rm8(cpu, mem, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     x.set(value);
 }
 
 pub fn setb_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let value = cpu.flags.contains(Flags::CF) as u8;
-    let x = rm8(cpu, mem, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
+    x.set(value);
+}
+
+pub fn setp_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let value = cpu.flags.contains(Flags::PF) as u8;
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
+    x.set(value);
+}
+
+pub fn setnp_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let value = !cpu.flags.contains(Flags::PF) as u8;
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     x.set(value);
 }
 
 pub fn setbe_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let value = (cpu.flags.contains(Flags::CF) || cpu.flags.contains(Flags::ZF)) as u8;
-    let x = rm8(cpu, mem, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     x.set(value);
 }
 
 pub fn sete_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let value = cpu.flags.contains(Flags::ZF) as u8;
-    let x = rm8(cpu, mem, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     x.set(value);
 }
 
 pub fn setg_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let value = (!cpu.flags.contains(Flags::ZF)
         && (cpu.flags.contains(Flags::SF) == cpu.flags.contains(Flags::OF))) as u8;
-    let x = rm8(cpu, mem, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     x.set(value);
 }
 
 pub fn setl_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let value = (cpu.flags.contains(Flags::SF) != cpu.flags.contains(Flags::OF)) as u8;
-    let x = rm8(cpu, mem, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     x.set(value);
 }
 
 pub fn setle_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let value = (cpu.flags.contains(Flags::ZF)
         || (cpu.flags.contains(Flags::SF) != cpu.flags.contains(Flags::OF))) as u8;
-    let x = rm8(cpu, mem, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     x.set(value);
 }
 
 pub fn setne_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let value = !cpu.flags.contains(Flags::ZF) as u8;
-    let x = rm8(cpu, mem, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     x.set(value);
 }
 
 pub fn setge_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let value = (cpu.flags.contains(Flags::SF) == cpu.flags.contains(Flags::OF)) as u8;
-    let x = rm8(cpu, mem, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     x.set(value);
 }
 
 pub fn sets_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let value = cpu.flags.contains(Flags::SF) as u8;
-    let x = rm8(cpu, mem, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     x.set(value);
 }
 
@@ -390,18 +660,27 @@ pub fn pushfw(cpu: &mut CPU, mem: Mem, _instr: &Instruction) {
 
 pub fn popfd(cpu: &mut CPU, mem: Mem, _instr: &Instruction) {
     let value = pop(cpu, mem);
-    cpu.flags = Flags::from_bits(value).unwrap_or_else(|| panic!("invalid flags {:#x}", value));
+    // Reserved bit 1 is always set, and we don't model the others, like IF and IOPL.
+    cpu.flags = Flags::from_bits_truncate(value).into();
 }
 
 pub fn popfw(cpu: &mut CPU, mem: Mem, _instr: &Instruction) {
-    let prev = Flags::from_bits(cpu.flags.bits() & 0xFFFF_0000).unwrap();
-    let new = Flags::from_bits(pop16(cpu, mem) as u32).unwrap();
-    cpu.flags = prev.union(new);
+    let prev = Flags::from_bits_truncate(cpu.flags.bits() & 0xFFFF_0000);
+    let new = Flags::from_bits_truncate(pop16(cpu, mem) as u32);
+    cpu.flags = prev.union(new).into();
+}
+
+pub fn lahf(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
+    let flags =
+        cpu.flags.bits() & (Flags::SF | Flags::ZF | Flags::AF | Flags::PF | Flags::CF).bits();
+    // Bit 1 of EFLAGS is reserved and always reads as set.
+    cpu.regs.set8(Register::AH, (flags | 2) as u8);
 }
 
 pub fn sahf(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
+    // AH often holds the FPU status word, from FNSTSW AX, with bits we don't track.
     let ah = cpu.regs.get8(Register::AH);
-    cpu.flags = Flags::from_bits((cpu.flags.bits() & 0xFFFF_FF00) | ah as u32).unwrap();
+    cpu.flags = Flags::from_bits_truncate((cpu.flags.bits() & 0xFFFF_FF00) | ah as u32).into();
 }
 
 pub fn salc(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
@@ -450,15 +729,20 @@ pub fn cdq(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
 }
 
 pub fn int3(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
-    log::warn!("debugger interrupt");
-    cpu.state = CPUState::DebugBreak;
-    cpu.regs.eip -= 1;
+    // The machine decides whether this is one of the debugger's breakpoints or the
+    // guest's own, which goes to its exception handlers.
+    cpu.fault(Fault::Breakpoint);
 }
 
 pub fn sysenter(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
     cpu.state = CPUState::SysCall;
 }
 
+pub fn rdtsc(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
+    // The count follows the emulated clock, which only the machine knows.
+    cpu.state = CPUState::ReadTsc;
+}
+
 pub fn bswap_r32(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
     let reg = instr.op0_register();
     let val = cpu.regs.get32(reg);
@@ -471,22 +755,59 @@ pub fn bswap_r32(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
     );
 }
 
-pub fn xlat_m8(cpu: &mut CPU, mem: Mem, _instr: &Instruction) {
-    let addr = cpu.regs.get32(Register::EBX) + (cpu.regs.get8(Register::AL) as u32);
-    cpu.regs.set8(Register::AL, mem.get_pod::<u8>(addr));
+pub fn xlat_m8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    // The table can be addressed through a segment override, e.g. xlat fs:[ebx].
+    let addr = cpu
+        .regs
+        .segment_base(instr.memory_segment())
+        .wrapping_add(cpu.regs.get32(Register::EBX))
+        .wrapping_add(cpu.regs.get8(Register::AL) as u32);
+    let value = read_pod::<u8>(cpu, mem, addr);
+    if cpu.state.is_running() {
+        cpu.regs.set8(Register::AL, value);
+    }
+}
+
+pub fn bound_r32_m3232(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let addr = x86_addr(cpu, instr);
+    let index = cpu.regs.get32(instr.op0_register()) as i32;
+    let lower = read_pod::<u32>(cpu, mem, addr) as i32;
+    let upper = read_pod::<u32>(cpu, mem, addr.wrapping_add(4)) as i32;
+    if cpu.state.is_running() && (index < lower || index > upper) {
+        cpu.fault(Fault::BoundRange);
+    }
+}
+
+pub fn bound_r16_m1616(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let addr = x86_addr(cpu, instr);
+    let index = cpu.regs.get16(instr.op0_register()) as i16;
+    let lower = read_pod::<u16>(cpu, mem, addr) as i16;
+    let upper = read_pod::<u16>(cpu, mem, addr.wrapping_add(2)) as i16;
+    if cpu.state.is_running() && (index < lower || index > upper) {
+        cpu.fault(Fault::BoundRange);
+    }
 }
 
 pub fn bts_rm32_r32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_rm32(cpu, mem, instr) % 32;
-    let x = rm32(cpu, mem, instr);
+    let Some(y) = op1_rm32(cpu, mem, instr) else {
+        return;
+    };
+    let y = y % 32;
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     let mask = 1u32 << y;
     cpu.flags.set(Flags::CF, x.get() & mask != 0);
     x.set(x.get() | mask);
 }
 
 pub fn tzcnt_r32_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_rm32(cpu, mem, instr);
-    let x = rm32(cpu, mem, instr);
+    let Some(y) = op1_rm32(cpu, mem, instr) else {
+        return;
+    };
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     let count = y.trailing_zeros();
     cpu.flags.set(Flags::CF, count == 32);
     cpu.flags.set(Flags::ZF, count == 0);
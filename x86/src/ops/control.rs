@@ -1,9 +1,27 @@
-use crate::{registers::Flags, x86::CPU, Register};
+use crate::{registers::Flags, x86::CPU, Fault, Register};
 use iced_x86::Instruction;
 use memory::Mem;
 
 use super::helpers::*;
 
+/// Stands in for an undefined opcode, including a LOCK prefix on an instruction that
+/// doesn't allow one.
+pub fn invalid(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
+    cpu.fault(Fault::InvalidOpcode);
+}
+
+/// Stands in for an instruction we don't implement yet, stopping the emulator if it runs.
+/// Decoding happens a block at a time, so this can't be an error at decode time: the
+/// bytes may never execute, e.g. past a call to a function that never returns.
+pub fn unimplemented(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+    cpu.err(format!("unimplemented: {instr} ({:?})", instr.code()));
+}
+
+/// ICEBP, the undocumented one-byte int 1.
+pub fn int1(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
+    cpu.fault(Fault::SingleStep);
+}
+
 pub fn call(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     push(cpu, mem, cpu.regs.eip);
     cpu.jmp(mem, instr.near_branch32())
@@ -11,7 +29,10 @@ pub fn call(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 
 pub fn call_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     // call dword ptr [addr]
-    let target = rm32(cpu, mem, instr).get();
+    let Some(target) = rm32(cpu, mem, instr) else {
+        return;
+    };
+    let target = target.get();
     push(cpu, mem, cpu.regs.eip);
     cpu.jmp(mem, target)
 }
@@ -32,7 +53,10 @@ pub fn jmp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 }
 
 pub fn jmp_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let target = rm32(cpu, mem, instr).get();
+    let Some(target) = rm32(cpu, mem, instr) else {
+        return;
+    };
+    let target = target.get();
     cpu.jmp(mem, target)
 }
 
@@ -66,6 +90,18 @@ pub fn je(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     }
 }
 
+pub fn jp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    if cpu.flags.contains(Flags::PF) {
+        cpu.jmp(mem, instr.near_branch32());
+    }
+}
+
+pub fn jnp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    if !cpu.flags.contains(Flags::PF) {
+        cpu.jmp(mem, instr.near_branch32());
+    }
+}
+
 pub fn jecxz(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     if cpu.regs.get32(Register::ECX) == 0 {
         cpu.jmp(mem, instr.near_branch32());
@@ -5,27 +5,173 @@ use memory::Mem;
 
 bitflags! {
     pub struct EDXFeatures: u32 {
+        const FPU = 1 << 0;
+        const TSC = 1 << 4;
+        const CX8 = 1 << 8;
         const MMX = 1 << 23;
+        const SSE = 1 << 25;
+        const SSE2 = 1 << 26;
     }
 }
 
+/// The processor that CPUID describes.  Guests use CPUID to pick code paths, so each
+/// profile only reports features the emulator implements, even where the real chip
+/// had more (e.g. CMOV and FXSR on the P6 family).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum CpuProfile {
+    Pentium,
+    #[default]
+    PentiumMMX,
+    PentiumII,
+    PentiumIII,
+    Pentium4,
+    K6,
+}
+
+impl std::str::FromStr for CpuProfile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "pentium" => CpuProfile::Pentium,
+            "pentium-mmx" => CpuProfile::PentiumMMX,
+            "pentium2" => CpuProfile::PentiumII,
+            "pentium3" => CpuProfile::PentiumIII,
+            "pentium4" => CpuProfile::Pentium4,
+            "k6" => CpuProfile::K6,
+            _ => {
+                return Err(format!(
+                    "unknown cpu {s:?}, expected one of pentium, pentium-mmx, pentium2, pentium3, pentium4, k6"
+                ))
+            }
+        })
+    }
+}
+
+impl CpuProfile {
+    pub fn vendor(self) -> &'static [u8; 12] {
+        match self {
+            CpuProfile::K6 => b"AuthenticAMD",
+            _ => b"GenuineIntel",
+        }
+    }
+
+    /// Family, model and stepping, as reported in EAX by CPUID leaf 1.
+    fn signature(self) -> (u32, u32, u32) {
+        match self {
+            CpuProfile::Pentium => (5, 2, 12),
+            CpuProfile::PentiumMMX => (5, 4, 3),
+            CpuProfile::PentiumII => (6, 5, 2),
+            CpuProfile::PentiumIII => (6, 7, 3),
+            CpuProfile::Pentium4 => (15, 2, 9),
+            CpuProfile::K6 => (5, 6, 2),
+        }
+    }
+
+    /// The processor family, e.g. 5 for Pentium or 15 for Pentium 4.
+    pub fn family(self) -> u32 {
+        self.signature().0
+    }
+
+    pub fn features(self) -> EDXFeatures {
+        let base = EDXFeatures::FPU | EDXFeatures::TSC | EDXFeatures::CX8;
+        match self {
+            CpuProfile::Pentium => base,
+            CpuProfile::PentiumMMX | CpuProfile::PentiumII | CpuProfile::K6 => {
+                base | EDXFeatures::MMX
+            }
+            CpuProfile::PentiumIII => base | EDXFeatures::MMX | EDXFeatures::SSE,
+            CpuProfile::Pentium4 => base | EDXFeatures::MMX | EDXFeatures::SSE | EDXFeatures::SSE2,
+        }
+    }
+
+    /// The clock rate of a typical chip, which is the rate RDTSC counts at.
+    pub fn mhz(self) -> u32 {
+        match self {
+            CpuProfile::Pentium => 100,
+            CpuProfile::PentiumMMX | CpuProfile::K6 => 200,
+            CpuProfile::PentiumII => 300,
+            CpuProfile::PentiumIII => 500,
+            CpuProfile::Pentium4 => 2000,
+        }
+    }
+
+    /// The highest basic leaf.  Leaf 2 describes caches, which the P6 family added.
+    fn max_leaf(self) -> u32 {
+        match self.signature().0 {
+            5 => 1,
+            _ => 2,
+        }
+    }
+
+    /// The brand string of the extended leaves, on processors that have them.
+    fn brand(self) -> Option<&'static str> {
+        match self {
+            CpuProfile::Pentium4 => Some("Intel(R) Pentium(R) 4 CPU 2.00GHz"),
+            CpuProfile::K6 => Some("AMD-K6tm w/ multimedia extensions"),
+            _ => None,
+        }
+    }
+}
+
+fn set_regs(cpu: &mut CPU, eax: u32, ebx: u32, ecx: u32, edx: u32) {
+    cpu.regs.set32(Register::EAX, eax);
+    cpu.regs.set32(Register::EBX, ebx);
+    cpu.regs.set32(Register::ECX, ecx);
+    cpu.regs.set32(Register::EDX, edx);
+}
+
 pub fn cpuid(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
-    match cpu.regs.get32(Register::EAX) {
+    let profile = cpu.cpuid;
+    let leaf = cpu.regs.get32(Register::EAX);
+    match leaf {
         0 => {
             // basic information
-            cpu.regs.set32(Register::EAX, /* Pentium */ 0x2);
-            // "GenuineIntel"
-            cpu.regs.set32(Register::EBX, u32::from_le_bytes(*b"Genu"));
-            cpu.regs.set32(Register::EDX, u32::from_le_bytes(*b"ineI"));
-            cpu.regs.set32(Register::ECX, u32::from_le_bytes(*b"ntel"));
+            let vendor = profile.vendor();
+            let word = |i: usize| u32::from_le_bytes(vendor[i * 4..][..4].try_into().unwrap());
+            set_regs(cpu, profile.max_leaf(), word(0), word(2), word(1));
         }
         1 => {
             // CPUID_GETFEATURES
-            // Just enough to convince heaven7 that we support MMX.
-            cpu.regs.set32(Register::EAX, 0);
-            cpu.regs.set32(Register::ECX, 0);
-            cpu.regs.set32(Register::EDX, EDXFeatures::MMX.bits());
+            let (family, model, stepping) = profile.signature();
+            let (family, ext_family) = match family {
+                15.. => (15, family - 15),
+                _ => (family, 0),
+            };
+            let eax = (ext_family << 20) | (family << 8) | (model << 4) | stepping;
+            set_regs(cpu, eax, 0, 0, profile.features().bits());
+        }
+        2 if profile.max_leaf() >= 2 => {
+            // Cache and TLB descriptors, as reported by a Pentium II: 32KB L1, 512KB L2.
+            set_regs(cpu, 0x0302_0101, 0, 0, 0x0C04_0843);
+        }
+        0x8000_0000.. if profile.brand().is_some() => {
+            let brand = profile.brand().unwrap();
+            match leaf - 0x8000_0000 {
+                0 => set_regs(cpu, 0x8000_0004, 0, 0, 0),
+                1 => {
+                    // Extended features; AMD duplicates the basic ones here.
+                    let edx = match profile.vendor() {
+                        b"AuthenticAMD" => profile.features().bits(),
+                        _ => 0,
+                    };
+                    set_regs(cpu, 0, 0, 0, edx);
+                }
+                n @ 2..=4 => {
+                    let mut bytes = [0u8; 48];
+                    bytes[..brand.len()].copy_from_slice(brand.as_bytes());
+                    let chunk = &bytes[(n as usize - 2) * 16..][..16];
+                    let word =
+                        |i: usize| u32::from_le_bytes(chunk[i * 4..][..4].try_into().unwrap());
+                    set_regs(cpu, word(0), word(1), word(2), word(3));
+                }
+                _ => set_regs(cpu, 0, 0, 0, 0),
+            }
+        }
+        _ => {
+            // Leaves beyond the maximum are undefined; zeros at least say "nothing here".
+            log::warn!("cpuid: unsupported leaf {leaf:#x}");
+            set_regs(cpu, 0, 0, 0, 0);
         }
-        mode => todo!("cpuid {mode}"),
     }
 }
@@ -1,32 +1,158 @@
 use super::helpers::*;
-use crate::{fpu, registers::Flags, x86::CPU};
+use crate::{
+    fpu::{self, Status},
+    registers::Flags,
+    x86::CPU,
+};
 use iced_x86::{Instruction, Register};
-use memory::{Extensions, ExtensionsMut, Mem};
+use memory::Mem;
 
 pub fn finit(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
     cpu.fpu.st_top = 8;
-    cpu.fpu.status = fpu::Status::empty();
+    cpu.fpu.status = Status::empty();
+    cpu.fpu.control = fpu::DEFAULT_CONTROL;
+}
+
+pub fn fnclex(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
+    cpu.fpu
+        .status
+        .remove(Status::EXCEPTIONS | Status::SF | Status::ES);
+}
+
+/// Whether a NaN is signaling, which the FPU treats as an invalid operand even where
+/// it lets quiet NaNs pass.
+fn is_snan(x: f64) -> bool {
+    x.is_nan() && x.to_bits() & (1 << 51) == 0
+}
+
+/// Read an f32 operand, flagging denormals as the FPU does on loading them.
+fn load_m32fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) -> Option<f64> {
+    let x = read_operand::<f32>(cpu, mem, instr)?;
+    if x.is_subnormal() {
+        cpu.fpu.exception(Status::DE);
+    }
+    Some(x as f64)
+}
+
+/// Read an f64 operand, flagging denormals as the FPU does on loading them.
+fn load_m64fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) -> Option<f64> {
+    let x = read_operand::<f64>(cpu, mem, instr)?;
+    if x.is_subnormal() {
+        cpu.fpu.exception(Status::DE);
+    }
+    Some(x)
 }
 
 /// Compare two values and set floating-point comparison flags.
-fn fcom<T: std::cmp::PartialOrd>(cpu: &mut CPU, x: T, y: T) {
-    if x > y {
-        cpu.fpu.status.set(fpu::Status::C3, false);
-        cpu.fpu.status.set(fpu::Status::C2, false);
-        cpu.fpu.status.set(fpu::Status::C0, false);
-    } else if x < y {
-        cpu.fpu.status.set(fpu::Status::C3, false);
-        cpu.fpu.status.set(fpu::Status::C2, false);
-        cpu.fpu.status.set(fpu::Status::C0, true);
-    } else if x == y {
-        cpu.fpu.status.set(fpu::Status::C3, true);
-        cpu.fpu.status.set(fpu::Status::C2, false);
-        cpu.fpu.status.set(fpu::Status::C0, false);
-    } else {
-        cpu.fpu.status.set(fpu::Status::C3, true);
-        cpu.fpu.status.set(fpu::Status::C2, true);
-        cpu.fpu.status.set(fpu::Status::C0, true);
+/// Unordered comparisons are invalid unless `quiet`, as in FUCOM, which only objects
+/// to signaling NaNs.
+fn fcom(cpu: &mut CPU, x: f64, y: f64, quiet: bool) {
+    let (c3, c2, c0) = match x.partial_cmp(&y) {
+        Some(std::cmp::Ordering::Greater) => (false, false, false),
+        Some(std::cmp::Ordering::Less) => (false, false, true),
+        Some(std::cmp::Ordering::Equal) => (true, false, false),
+        None => {
+            if !quiet || is_snan(x) || is_snan(y) {
+                cpu.fpu.exception(Status::IE);
+            }
+            (true, true, true)
+        }
+    };
+    cpu.fpu.status.set(Status::C3, c3);
+    cpu.fpu.status.set(Status::C2, c2);
+    cpu.fpu.status.remove(Status::C1);
+    cpu.fpu.status.set(Status::C0, c0);
+}
+
+/// Compare two values and set the integer flags, as FCOMI does.
+fn fcomi(cpu: &mut CPU, x: f64, y: f64, quiet: bool) {
+    let (zf, pf, cf) = match x.partial_cmp(&y) {
+        Some(std::cmp::Ordering::Greater) => (false, false, false),
+        Some(std::cmp::Ordering::Less) => (false, false, true),
+        Some(std::cmp::Ordering::Equal) => (true, false, false),
+        None => {
+            if !quiet || is_snan(x) || is_snan(y) {
+                cpu.fpu.exception(Status::IE);
+            }
+            (true, true, true)
+        }
     };
+    cpu.flags.set(Flags::ZF, zf);
+    cpu.flags.set(Flags::PF, pf);
+    cpu.flags.set(Flags::CF, cf);
+    cpu.flags.set(Flags::OF, false);
+    cpu.flags.set(Flags::SF, false);
+    cpu.fpu.status.remove(Status::C1);
+}
+
+/// The rounding error of s = a + b, i.e. (a + b) - s computed exactly (Knuth's TwoSum).
+fn sum_error(a: f64, b: f64, s: f64) -> f64 {
+    let bb = s - a;
+    (a - (s - bb)) + (b - bb)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Arith {
+    Add,
+    Sub,
+    SubR,
+    Mul,
+    Div,
+    DivR,
+}
+
+/// Compute reg = reg <op> y, rounded per the precision control, noting exceptions.
+fn arith(cpu: &mut CPU, reg: Register, op: Arith, y: f64) {
+    let x = *cpu.fpu.get(reg);
+    let (a, b) = match op {
+        Arith::SubR | Arith::DivR => (y, x),
+        _ => (x, y),
+    };
+    let val = match op {
+        Arith::Add => a + b,
+        Arith::Sub | Arith::SubR => a - b,
+        Arith::Mul => a * b,
+        Arith::Div | Arith::DivR => a / b,
+    };
+    let val = cpu.fpu.round_result(val, || match op {
+        Arith::Add => sum_error(a, b, val),
+        Arith::Sub | Arith::SubR => sum_error(a, -b, val),
+        // The fused multiply-add computes the rounding error of a product exactly,
+        // and the remainder of a quotient.
+        Arith::Mul => a.mul_add(b, -val),
+        Arith::Div | Arith::DivR => -val.mul_add(b, -a) / b,
+    });
+    if a.is_nan() || b.is_nan() {
+        if is_snan(a) || is_snan(b) {
+            cpu.fpu.exception(Status::IE);
+        }
+    } else if val.is_nan() {
+        // inf - inf, 0 * inf, 0 / 0 and the like.
+        cpu.fpu.exception(Status::IE);
+    } else if matches!(op, Arith::Div | Arith::DivR) && b == 0.0 {
+        cpu.fpu.exception(Status::ZE);
+    } else if val.is_infinite() && a.is_finite() && b.is_finite() {
+        cpu.fpu.exception(Status::OE | Status::PE);
+    } else if val.is_subnormal() {
+        cpu.fpu.exception(Status::UE);
+    }
+    *cpu.fpu.get(reg) = val;
+}
+
+/// Round st0 to an integer of the given width per the rounding control, for FIST.
+/// Values out of range become the "integer indefinite", the most negative integer.
+fn st0_int(cpu: &mut CPU, bits: u32) -> i64 {
+    let x = *cpu.fpu.st0();
+    let int = cpu.fpu.round_int(x);
+    let limit = 2f64.powi(bits as i32 - 1);
+    if int.is_nan() || int < -limit || int >= limit {
+        cpu.fpu.exception(Status::IE);
+        return i64::MIN >> (64 - bits);
+    }
+    if int != x {
+        cpu.fpu.exception(Status::PE);
+    }
+    int as i64
 }
 
 pub fn fld1(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
@@ -45,81 +171,146 @@ pub fn fldl2e(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
     cpu.fpu.push(std::f64::consts::LOG2_E);
 }
 
+pub fn fldl2t(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
+    cpu.fpu.push(std::f64::consts::LOG2_10);
+}
+
+pub fn fldlg2(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
+    cpu.fpu.push(std::f64::consts::LOG10_2);
+}
+
+pub fn fldln2(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
+    cpu.fpu.push(std::f64::consts::LN_2);
+}
+
 pub fn fld_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
     let x = *cpu.fpu.get(instr.op0_register());
     cpu.fpu.push(x);
 }
 
+pub fn fld_m80fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(bytes) = read_operand::<[u8; 10]>(cpu, mem, instr) else {
+        return;
+    };
+    cpu.fpu.push(fpu::f80_to_f64(bytes));
+}
+
 pub fn fld_m64fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    cpu.fpu.push(mem.get_pod::<f64>(x86_addr(cpu, instr)));
+    let Some(x) = load_m64fp(cpu, mem, instr) else {
+        return;
+    };
+    cpu.fpu.push(x);
 }
 
 pub fn fld_m32fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    cpu.fpu
-        .push(mem.get_pod::<f32>(x86_addr(cpu, instr)) as f64);
+    let Some(x) = load_m32fp(cpu, mem, instr) else {
+        return;
+    };
+    cpu.fpu.push(x);
 }
 
 pub fn fild_m64int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    cpu.fpu
-        .push(mem.get_pod::<i64>(x86_addr(cpu, instr)) as f64);
+    if let Some(x) = read_operand::<i64>(cpu, mem, instr) {
+        cpu.fpu.push(x as f64);
+    }
 }
 
 pub fn fild_m32int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    cpu.fpu
-        .push(mem.get_pod::<i32>(x86_addr(cpu, instr)) as f64);
+    if let Some(x) = read_operand::<i32>(cpu, mem, instr) {
+        cpu.fpu.push(x as f64);
+    }
 }
 
 pub fn fild_m16int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    cpu.fpu
-        .push(mem.get_pod::<i16>(x86_addr(cpu, instr)) as f64);
+    if let Some(x) = read_operand::<i16>(cpu, mem, instr) {
+        cpu.fpu.push(x as f64);
+    }
+}
+
+pub fn fst_m80fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let f = *cpu.fpu.st0();
+    let addr = x86_addr(cpu, instr);
+    write_pod::<[u8; 10]>(cpu, mem, addr, fpu::f64_to_f80(f));
 }
 
 pub fn fst_m64fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let f = *cpu.fpu.st0();
-    mem.put_pod::<f64>(x86_addr(cpu, instr), f);
+    let addr = x86_addr(cpu, instr);
+    write_pod::<f64>(cpu, mem, addr, f);
 }
 
 pub fn fst_m32fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let f = *cpu.fpu.st0();
-    mem.put_pod::<f32>(x86_addr(cpu, instr), f as f32);
+    let addr = x86_addr(cpu, instr);
+    let f = cpu.fpu.to_f32(f);
+    write_pod::<f32>(cpu, mem, addr, f);
+}
+
+pub fn fstp_m80fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    fst_m80fp(cpu, mem, instr);
+    if cpu.state.is_running() {
+        cpu.fpu.pop();
+    }
 }
 
 pub fn fstp_m64fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     fst_m64fp(cpu, mem, instr);
-    cpu.fpu.pop();
+    // A store that faulted had no effect, so mustn't pop either.
+    if cpu.state.is_running() {
+        cpu.fpu.pop();
+    }
 }
 
 pub fn fstp_m32fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     fst_m32fp(cpu, mem, instr);
-    cpu.fpu.pop();
+    if cpu.state.is_running() {
+        cpu.fpu.pop();
+    }
 }
 
-pub fn fstp_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+pub fn fst_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
     let f = *cpu.fpu.st0();
     *cpu.fpu.get(instr.op0_register()) = f;
+}
+
+pub fn fstp_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    fst_sti(cpu, mem, instr);
     cpu.fpu.pop();
 }
 
 pub fn fistp_m64int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let f = *cpu.fpu.st0();
-    mem.put_pod::<i64>(x86_addr(cpu, instr), f as i64);
-    cpu.fpu.pop();
+    let x = st0_int(cpu, 64);
+    let addr = x86_addr(cpu, instr);
+    write_pod::<i64>(cpu, mem, addr, x);
+    if cpu.state.is_running() {
+        cpu.fpu.pop();
+    }
 }
 
 pub fn fist_m32int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let f = *cpu.fpu.st0();
-    mem.put_pod::<i32>(x86_addr(cpu, instr), f as i32);
+    let x = st0_int(cpu, 32);
+    let addr = x86_addr(cpu, instr);
+    write_pod::<i32>(cpu, mem, addr, x as i32);
 }
 
 pub fn fistp_m32int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     fist_m32int(cpu, mem, instr);
-    cpu.fpu.pop();
+    if cpu.state.is_running() {
+        cpu.fpu.pop();
+    }
+}
+
+pub fn fist_m16int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let x = st0_int(cpu, 16);
+    let addr = x86_addr(cpu, instr);
+    write_pod::<i16>(cpu, mem, addr, x as i16);
 }
 
 pub fn fistp_m16int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let f = *cpu.fpu.st0();
-    mem.put_pod::<i16>(x86_addr(cpu, instr), f as i16);
-    cpu.fpu.pop();
+    fist_m16int(cpu, mem, instr);
+    if cpu.state.is_running() {
+        cpu.fpu.pop();
+    }
 }
 
 pub fn fchs(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
@@ -130,20 +321,49 @@ pub fn fabs(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
     *cpu.fpu.st0() = cpu.fpu.st0().abs();
 }
 
+/// The trigonometric instructions only accept operands below 2^63 in magnitude,
+/// setting C2 and leaving the operand alone otherwise so the program can reduce it.
+fn trig_operand(cpu: &mut CPU) -> Option<f64> {
+    let x = *cpu.fpu.st0();
+    if x.is_infinite() {
+        cpu.fpu.exception(Status::IE);
+        cpu.fpu.status.remove(Status::C2);
+        *cpu.fpu.st0() = f64::NAN;
+        return None;
+    }
+    if x.abs() >= 2f64.powi(63) {
+        cpu.fpu.status.insert(Status::C2);
+        return None;
+    }
+    cpu.fpu.status.remove(Status::C2);
+    Some(x)
+}
+
 pub fn fcos(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
-    let reg = cpu.fpu.st0();
-    *reg = reg.cos();
+    if let Some(x) = trig_operand(cpu) {
+        *cpu.fpu.st0() = x.cos();
+    }
 }
 
 pub fn fsin(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
-    let reg = cpu.fpu.st0();
-    *reg = reg.sin();
+    if let Some(x) = trig_operand(cpu) {
+        *cpu.fpu.st0() = x.sin();
+    }
 }
 
 pub fn fsincos(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
-    let val = *cpu.fpu.st0();
-    *cpu.fpu.st0() = val.sin();
-    cpu.fpu.push(val.cos());
+    if let Some(x) = trig_operand(cpu) {
+        *cpu.fpu.st0() = x.sin();
+        cpu.fpu.push(x.cos());
+    }
+}
+
+pub fn fptan(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
+    if let Some(x) = trig_operand(cpu) {
+        *cpu.fpu.st0() = x.tan();
+        // Pushes 1.0 so that a following FDIVR computes the cotangent.
+        cpu.fpu.push(1.0);
+    }
 }
 
 pub fn fpatan(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
@@ -154,14 +374,17 @@ pub fn fpatan(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
 }
 
 pub fn fsqrt(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
-    let reg = cpu.fpu.st0();
-    *reg = reg.sqrt();
+    let x = *cpu.fpu.st0();
+    if x < 0.0 {
+        cpu.fpu.exception(Status::IE);
+    }
+    let root = x.sqrt();
+    *cpu.fpu.st0() = cpu.fpu.round_result(root, || -root.mul_add(root, -x));
 }
 
 pub fn fadd_sti_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
     let y = *cpu.fpu.get(instr.op1_register());
-    let x = cpu.fpu.get(instr.op0_register());
-    *x += y;
+    arith(cpu, instr.op0_register(), Arith::Add, y);
 }
 
 pub fn faddp_sti_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
@@ -170,41 +393,50 @@ pub fn faddp_sti_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 }
 
 pub fn fadd_m64fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<f64>(x86_addr(cpu, instr));
-    *cpu.fpu.st0() += y;
+    let Some(y) = load_m64fp(cpu, mem, instr) else {
+        return;
+    };
+    arith(cpu, Register::ST0, Arith::Add, y);
 }
 
 pub fn fadd_m32fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<f32>(x86_addr(cpu, instr)) as f64;
-    *cpu.fpu.st0() += y;
+    let Some(y) = load_m32fp(cpu, mem, instr) else {
+        return;
+    };
+    arith(cpu, Register::ST0, Arith::Add, y);
 }
 
 pub fn fiadd_m32int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<i32>(x86_addr(cpu, instr)) as f64;
-    *cpu.fpu.st0() += y;
+    let Some(y) = read_operand::<i32>(cpu, mem, instr).map(f64::from) else {
+        return;
+    };
+    arith(cpu, Register::ST0, Arith::Add, y);
 }
 
 pub fn fiadd_m16int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<i16>(x86_addr(cpu, instr)) as f64;
-    *cpu.fpu.st0() += y;
+    let Some(y) = read_operand::<i16>(cpu, mem, instr).map(f64::from) else {
+        return;
+    };
+    arith(cpu, Register::ST0, Arith::Add, y);
 }
 
 pub fn fsub_m64fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<f64>(x86_addr(cpu, instr));
-    let x = cpu.fpu.st0();
-    *x -= y;
+    let Some(y) = load_m64fp(cpu, mem, instr) else {
+        return;
+    };
+    arith(cpu, Register::ST0, Arith::Sub, y);
 }
 
 pub fn fsub_m32fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<f32>(x86_addr(cpu, instr)) as f64;
-    let x = cpu.fpu.st0();
-    *x -= y;
+    let Some(y) = load_m32fp(cpu, mem, instr) else {
+        return;
+    };
+    arith(cpu, Register::ST0, Arith::Sub, y);
 }
 
 pub fn fsub_sti_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
     let y = *cpu.fpu.get(instr.op1_register());
-    let x = cpu.fpu.get(instr.op0_register());
-    *x -= y;
+    arith(cpu, instr.op0_register(), Arith::Sub, y);
 }
 
 pub fn fsubp_sti_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
@@ -213,27 +445,36 @@ pub fn fsubp_sti_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 }
 
 pub fn fisub_m32int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<i32>(x86_addr(cpu, instr)) as f64;
-    let x = cpu.fpu.st0();
-    *x = *x - y;
+    let Some(y) = read_operand::<i32>(cpu, mem, instr).map(f64::from) else {
+        return;
+    };
+    arith(cpu, Register::ST0, Arith::Sub, y);
+}
+
+pub fn fisub_m16int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = read_operand::<i16>(cpu, mem, instr).map(f64::from) else {
+        return;
+    };
+    arith(cpu, Register::ST0, Arith::Sub, y);
 }
 
 pub fn fsubr_m64fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<f64>(x86_addr(cpu, instr));
-    let x = cpu.fpu.st0();
-    *x = y - *x;
+    let Some(y) = load_m64fp(cpu, mem, instr) else {
+        return;
+    };
+    arith(cpu, Register::ST0, Arith::SubR, y);
 }
 
 pub fn fsubr_m32fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<f32>(x86_addr(cpu, instr)) as f64;
-    let x = cpu.fpu.st0();
-    *x = y - *x;
+    let Some(y) = load_m32fp(cpu, mem, instr) else {
+        return;
+    };
+    arith(cpu, Register::ST0, Arith::SubR, y);
 }
 
 pub fn fsubr_sti_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
     let y = *cpu.fpu.get(instr.op1_register());
-    let x = cpu.fpu.get(instr.op0_register());
-    *x = y - *x;
+    arith(cpu, instr.op0_register(), Arith::SubR, y);
 }
 
 pub fn fsubrp_sti_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
@@ -241,30 +482,51 @@ pub fn fsubrp_sti_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     cpu.fpu.pop();
 }
 
+pub fn fisubr_m32int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = read_operand::<i32>(cpu, mem, instr).map(f64::from) else {
+        return;
+    };
+    arith(cpu, Register::ST0, Arith::SubR, y);
+}
+
+pub fn fisubr_m16int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = read_operand::<i16>(cpu, mem, instr).map(f64::from) else {
+        return;
+    };
+    arith(cpu, Register::ST0, Arith::SubR, y);
+}
+
 pub fn fmul_m64fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<f64>(x86_addr(cpu, instr));
-    *cpu.fpu.st0() *= y;
+    let Some(y) = load_m64fp(cpu, mem, instr) else {
+        return;
+    };
+    arith(cpu, Register::ST0, Arith::Mul, y);
 }
 
 pub fn fmul_m32fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<f32>(x86_addr(cpu, instr)) as f64;
-    *cpu.fpu.st0() *= y;
+    let Some(y) = load_m32fp(cpu, mem, instr) else {
+        return;
+    };
+    arith(cpu, Register::ST0, Arith::Mul, y);
 }
 
 pub fn fimul_m32int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<i32>(x86_addr(cpu, instr)) as f64;
-    *cpu.fpu.st0() *= y;
+    let Some(y) = read_operand::<i32>(cpu, mem, instr).map(f64::from) else {
+        return;
+    };
+    arith(cpu, Register::ST0, Arith::Mul, y);
 }
 
 pub fn fimul_m16int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<i16>(x86_addr(cpu, instr)) as f64;
-    *cpu.fpu.st0() *= y;
+    let Some(y) = read_operand::<i16>(cpu, mem, instr).map(f64::from) else {
+        return;
+    };
+    arith(cpu, Register::ST0, Arith::Mul, y);
 }
 
 pub fn fmul_sti_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
     let y = *cpu.fpu.get(instr.op1_register());
-    let x = cpu.fpu.get(instr.op0_register());
-    *x *= y;
+    arith(cpu, instr.op0_register(), Arith::Mul, y);
 }
 
 pub fn fmulp_sti_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
@@ -274,29 +536,78 @@ pub fn fmulp_sti_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 
 pub fn f2xm1(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
     let x = cpu.fpu.st0();
-    *x = 2.0_f64.powf(*x) - 1.0;
+    // Computed this way to stay precise for x near 0, which is the point of the instruction.
+    *x = (*x * std::f64::consts::LN_2).exp_m1();
+}
+
+pub fn fyl2x(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
+    let x = *cpu.fpu.st0();
+    cpu.fpu.pop();
+    let y = *cpu.fpu.st0();
+    if x < 0.0 || (x == 0.0 && y == 0.0) {
+        cpu.fpu.exception(Status::IE);
+    } else if x == 0.0 && y.is_finite() {
+        cpu.fpu.exception(Status::ZE);
+    }
+    *cpu.fpu.st0() = y * x.log2();
+}
+
+pub fn fyl2xp1(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
+    let x = *cpu.fpu.st0();
+    cpu.fpu.pop();
+    let y = cpu.fpu.st0();
+    *y *= x.ln_1p() / std::f64::consts::LN_2;
 }
 
 pub fn fscale(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
     let y = *cpu.fpu.get(iced_x86::Register::ST1);
     let x = cpu.fpu.st0();
-    *x = *x * 2f64.powf(y.round());
+    *x *= 2f64.powf(y.trunc());
+}
+
+pub fn fxtract(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
+    let x = *cpu.fpu.st0();
+    if x == 0.0 {
+        cpu.fpu.exception(Status::ZE);
+        *cpu.fpu.st0() = f64::NEG_INFINITY;
+        cpu.fpu.push(x);
+        return;
+    }
+    if !x.is_finite() {
+        *cpu.fpu.st0() = x.abs();
+        cpu.fpu.push(x);
+        return;
+    }
+    // Scale denormals up first so the exponent field is meaningful.
+    let (x, bias) = if x.is_subnormal() {
+        (x * 2f64.powi(64), 64)
+    } else {
+        (x, 0)
+    };
+    let bits = x.to_bits();
+    let exp = ((bits >> 52) & 0x7FF) as i32 - 1023;
+    let significand = f64::from_bits((bits & !(0x7FF << 52)) | (1023 << 52));
+    *cpu.fpu.st0() = (exp - bias) as f64;
+    cpu.fpu.push(significand);
 }
 
 pub fn fdiv_m64fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<f64>(x86_addr(cpu, instr));
-    *cpu.fpu.st0() /= y;
+    let Some(y) = load_m64fp(cpu, mem, instr) else {
+        return;
+    };
+    arith(cpu, Register::ST0, Arith::Div, y);
 }
 
 pub fn fdiv_m32fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<f32>(x86_addr(cpu, instr)) as f64;
-    *cpu.fpu.st0() /= y;
+    let Some(y) = load_m32fp(cpu, mem, instr) else {
+        return;
+    };
+    arith(cpu, Register::ST0, Arith::Div, y);
 }
 
 pub fn fdiv_sti_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
     let y = *cpu.fpu.get(instr.op1_register());
-    let x = cpu.fpu.get(instr.op0_register());
-    *x = *x / y;
+    arith(cpu, instr.op0_register(), Arith::Div, y);
 }
 
 pub fn fdivp_sti_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
@@ -305,31 +616,36 @@ pub fn fdivp_sti_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 }
 
 pub fn fidiv_m32int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<i32>(x86_addr(cpu, instr)) as f64;
-    *cpu.fpu.st0() /= y;
+    let Some(y) = read_operand::<i32>(cpu, mem, instr).map(f64::from) else {
+        return;
+    };
+    arith(cpu, Register::ST0, Arith::Div, y);
 }
 
 pub fn fidiv_m16int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<i16>(x86_addr(cpu, instr)) as f64;
-    *cpu.fpu.st0() /= y;
+    let Some(y) = read_operand::<i16>(cpu, mem, instr).map(f64::from) else {
+        return;
+    };
+    arith(cpu, Register::ST0, Arith::Div, y);
 }
 
 pub fn fdivr_m64fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<f64>(x86_addr(cpu, instr));
-    let x = cpu.fpu.st0();
-    *x = y / *x;
+    let Some(y) = load_m64fp(cpu, mem, instr) else {
+        return;
+    };
+    arith(cpu, Register::ST0, Arith::DivR, y);
 }
 
 pub fn fdivr_m32fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<f32>(x86_addr(cpu, instr)) as f64;
-    let x = cpu.fpu.st0();
-    *x = y / *x;
+    let Some(y) = load_m32fp(cpu, mem, instr) else {
+        return;
+    };
+    arith(cpu, Register::ST0, Arith::DivR, y);
 }
 
 pub fn fdivr_sti_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
     let y = *cpu.fpu.get(instr.op1_register());
-    let x = cpu.fpu.get(instr.op0_register());
-    *x = y / *x;
+    arith(cpu, instr.op0_register(), Arith::DivR, y);
 }
 
 pub fn fdivrp_sti_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
@@ -338,15 +654,58 @@ pub fn fdivrp_sti_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 }
 
 pub fn fidivr_m32int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = mem.get_pod::<i32>(x86_addr(cpu, instr)) as f64;
-    let x = cpu.fpu.st0();
-    *x = y / *x;
+    let Some(y) = read_operand::<i32>(cpu, mem, instr).map(f64::from) else {
+        return;
+    };
+    arith(cpu, Register::ST0, Arith::DivR, y);
 }
 
-pub fn fprem(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
+pub fn fidivr_m16int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = read_operand::<i16>(cpu, mem, instr).map(f64::from) else {
+        return;
+    };
+    arith(cpu, Register::ST0, Arith::DivR, y);
+}
+
+/// Shared by FPREM and FPREM1, which differ in whether the quotient is truncated or
+/// rounded to nearest.  Real hardware may need several iterations to reduce a large operand, signaled by C2,
+/// but fmod is exact, so we always finish in one.
+fn fprem_common(cpu: &mut CPU, ieee: bool) {
     let y = *cpu.fpu.get(iced_x86::Register::ST1);
-    let x = cpu.fpu.st0();
-    *x = *x % y;
+    let x = *cpu.fpu.st0();
+    if x.is_infinite() || y == 0.0 || x.is_nan() || y.is_nan() {
+        if !x.is_nan() && !y.is_nan() {
+            cpu.fpu.exception(Status::IE);
+        }
+        *cpu.fpu.st0() = f64::NAN;
+        cpu.fpu.status.remove(Status::C2);
+        return;
+    }
+    let mut r = x % y;
+    // The low bits of the quotient, which programs use to find the octant for trig.
+    let mut q = ((x - r) / y).abs() % 8.0;
+    if ieee {
+        // Round the quotient to nearest (even) rather than truncating.
+        let half = y.abs() / 2.0;
+        if r.abs() > half || (r.abs() == half && q % 2.0 == 1.0) {
+            r -= y.abs().copysign(r);
+            q = (q + 1.0) % 8.0;
+        }
+    }
+    let q = q as u8;
+    *cpu.fpu.st0() = r;
+    cpu.fpu.status.remove(Status::C2);
+    cpu.fpu.status.set(Status::C0, q & 4 != 0);
+    cpu.fpu.status.set(Status::C3, q & 2 != 0);
+    cpu.fpu.status.set(Status::C1, q & 1 != 0);
+}
+
+pub fn fprem(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
+    fprem_common(cpu, false);
+}
+
+pub fn fprem1(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
+    fprem_common(cpu, true);
 }
 
 pub fn fxch_st0_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
@@ -355,14 +714,18 @@ pub fn fxch_st0_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
 
 pub fn fcom_m64fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let x = *cpu.fpu.st0();
-    let y = mem.get_pod::<f64>(x86_addr(cpu, instr));
-    fcom(cpu, x, y);
+    let Some(y) = load_m64fp(cpu, mem, instr) else {
+        return;
+    };
+    fcom(cpu, x, y, false);
 }
 
 pub fn fcom_m32fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let x = *cpu.fpu.st0();
-    let y = mem.get_pod::<f32>(x86_addr(cpu, instr)) as f64;
-    fcom(cpu, x, y);
+    let Some(y) = load_m32fp(cpu, mem, instr) else {
+        return;
+    };
+    fcom(cpu, x, y, false);
 }
 
 pub fn fcomp_m32fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
@@ -375,40 +738,110 @@ pub fn fcomp_m64fp(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     cpu.fpu.pop();
 }
 
-pub fn fcomp_st0_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+pub fn ficom_m32int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let x = *cpu.fpu.st0();
+    let Some(y) = read_operand::<i32>(cpu, mem, instr).map(f64::from) else {
+        return;
+    };
+    fcom(cpu, x, y, false);
+}
+
+pub fn ficom_m16int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let x = *cpu.fpu.st0();
+    let Some(y) = read_operand::<i16>(cpu, mem, instr).map(f64::from) else {
+        return;
+    };
+    fcom(cpu, x, y, false);
+}
+
+pub fn ficomp_m32int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    ficom_m32int(cpu, mem, instr);
+    cpu.fpu.pop();
+}
+
+pub fn ficomp_m16int(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    ficom_m16int(cpu, mem, instr);
+    cpu.fpu.pop();
+}
+
+pub fn fcom_st0_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
     let x = *cpu.fpu.st0();
     let y = *cpu.fpu.get(instr.op1_register());
-    fcom(cpu, x, y);
+    fcom(cpu, x, y, false);
+}
+
+pub fn fcomp_st0_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    fcom_st0_sti(cpu, mem, instr);
     cpu.fpu.pop();
 }
 
-pub fn fucomp_st0_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    fcomp_st0_sti(cpu, mem, instr);
-    // TODO: raise the invalid-arithmetic-operand exception when appropriate.
+pub fn fcompp(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
+    let x = *cpu.fpu.st0();
+    let y = *cpu.fpu.get(iced_x86::Register::ST1);
+    fcom(cpu, x, y, false);
+    cpu.fpu.pop();
+    cpu.fpu.pop();
 }
 
-pub fn fcomi_st0_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+pub fn fucom_st0_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
     let x = *cpu.fpu.st0();
     let y = *cpu.fpu.get(instr.op1_register());
-    //cpu.flags.set(Flags::PF, false);
-    if x > y {
-        cpu.flags.set(Flags::ZF, false);
-        cpu.flags.set(Flags::CF, false);
-    } else if x < y {
-        cpu.flags.set(Flags::ZF, false);
-        cpu.flags.set(Flags::CF, true);
+    fcom(cpu, x, y, true);
+}
+
+pub fn fucomp_st0_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    fucom_st0_sti(cpu, mem, instr);
+    cpu.fpu.pop();
+}
+
+pub fn fucompp(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
+    let x = *cpu.fpu.st0();
+    let y = *cpu.fpu.get(iced_x86::Register::ST1);
+    fcom(cpu, x, y, true);
+    cpu.fpu.pop();
+    cpu.fpu.pop();
+}
+
+pub fn ftst(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
+    let x = *cpu.fpu.st0();
+    fcom(cpu, x, 0.0, false);
+}
+
+pub fn fxam(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
+    // Classes are encoded in C3, C2, C0.
+    let (c3, c2, c0) = if cpu.fpu.is_empty(Register::ST0) {
+        (true, false, true)
     } else {
-        cpu.flags.set(Flags::ZF, true);
-        cpu.flags.set(Flags::CF, false);
-    }
+        let x = *cpu.fpu.st0();
+        cpu.fpu.status.set(Status::C1, x.is_sign_negative());
+        match x.classify() {
+            std::num::FpCategory::Nan => (false, false, true),
+            std::num::FpCategory::Normal => (false, true, false),
+            std::num::FpCategory::Infinite => (false, true, true),
+            std::num::FpCategory::Zero => (true, false, false),
+            std::num::FpCategory::Subnormal => (true, true, false),
+        }
+    };
+    cpu.fpu.status.set(Status::C3, c3);
+    cpu.fpu.status.set(Status::C2, c2);
+    cpu.fpu.status.set(Status::C0, c0);
+}
 
-    cpu.flags.set(Flags::OF, false);
-    cpu.flags.set(Flags::SF, false);
+pub fn fcomi_st0_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+    let x = *cpu.fpu.st0();
+    let y = *cpu.fpu.get(instr.op1_register());
+    fcomi(cpu, x, y, false);
 }
 
-pub fn fucomi_st0_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+pub fn fcomip_st0_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     fcomi_st0_sti(cpu, mem, instr);
-    // TODO: raise the invalid-arithmetic-operand exception when appropriate.
+    cpu.fpu.pop();
+}
+
+pub fn fucomi_st0_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+    let x = *cpu.fpu.st0();
+    let y = *cpu.fpu.get(instr.op1_register());
+    fcomi(cpu, x, y, true);
 }
 
 pub fn fucomip_st0_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
@@ -417,29 +850,134 @@ pub fn fucomip_st0_sti(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 }
 
 pub fn frndint(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
-    let x = cpu.fpu.st0();
-    *x = x.round();
+    let x = *cpu.fpu.st0();
+    let int = cpu.fpu.round_int(x);
+    if int != x && x.is_finite() {
+        cpu.fpu.exception(Status::PE);
+    }
+    *cpu.fpu.st0() = int;
 }
 
 pub fn fnstsw_ax(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
-    // TODO: does this need stack top in it?
-    cpu.regs.set32(Register::EAX, cpu.fpu.status.bits() as u32);
+    cpu.regs.set16(Register::AX, cpu.fpu.status_word());
+}
+
+pub fn fnstsw_m2byte(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let sw = cpu.fpu.status_word();
+    let addr = x86_addr(cpu, instr);
+    write_pod::<u16>(cpu, mem, addr, sw);
 }
 
 pub fn fnstcw_m2byte(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    // TODO: control word
-    let cw = 0x37u16; // default value
+    let cw = cpu.fpu.control;
     let addr = x86_addr(cpu, instr);
-    mem.put_pod::<u16>(addr, cw);
+    write_pod::<u16>(cpu, mem, addr, cw);
 }
 
 pub fn fldcw_m2byte(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    // TODO: control word
-    mem.get_pod::<u16>(x86_addr(cpu, instr));
+    let Some(cw) = read_operand::<u16>(cpu, mem, instr) else {
+        return;
+    };
+    // Bit 6 is reserved and always reads as set.
+    cpu.fpu.control = cw | 0x40;
+    // Unmasking an exception whose flag is already set leaves it pending.
+    cpu.fpu.update_summary();
+}
+
+pub fn fnstenv_m28byte(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let env = cpu.fpu.env();
+    let addr = x86_addr(cpu, instr);
+    write_pod::<[u8; 28]>(cpu, mem, addr, env);
+    if cpu.state.is_running() {
+        // FNSTENV then masks all exceptions, typically for an exception handler.
+        cpu.fpu.control |= Status::EXCEPTIONS.bits();
+    }
+}
+
+pub fn fldenv_m28byte(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    if let Some(env) = read_operand::<[u8; 28]>(cpu, mem, instr) {
+        cpu.fpu.load_env(&env);
+    }
+}
+
+pub fn fnsave_m108byte(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let image = cpu.fpu.save();
+    let addr = x86_addr(cpu, instr);
+    write_pod::<[u8; 108]>(cpu, mem, addr, image);
+    if cpu.state.is_running() {
+        // FNSAVE then reinitializes the FPU, as FNINIT.
+        finit(cpu, mem, instr);
+    }
+}
+
+pub fn frstor_m108byte(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    if let Some(image) = read_operand::<[u8; 108]>(cpu, mem, instr) {
+        cpu.fpu.restore(&image);
+    }
+}
+
+pub fn ffree_sti(_cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
+    // We don't track empty registers other than by the stack top.  This is usually
+    // followed by FINCSTP, which together pop the stack.
+}
+
+pub fn ffreep_sti(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
+    cpu.fpu.pop();
+}
+
+pub fn fincstp(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
+    // Unlike a pop, this doesn't check for an empty stack.
+    cpu.fpu.st_top = (cpu.fpu.st_top + 1).min(8);
+    cpu.fpu.status.remove(Status::C1);
+}
+
+pub fn fdecstp(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
+    cpu.fpu.st_top = cpu.fpu.st_top.saturating_sub(1);
+    cpu.fpu.status.remove(Status::C1);
+}
+
+pub fn fcmovb_st0_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+    let cond = cpu.flags.contains(Flags::CF);
+    fcmov(cpu, instr, cond);
+}
+
+pub fn fcmove_st0_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+    let cond = cpu.flags.contains(Flags::ZF);
+    fcmov(cpu, instr, cond);
+}
+
+pub fn fcmovbe_st0_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+    let cond = cpu.flags.intersects(Flags::CF | Flags::ZF);
+    fcmov(cpu, instr, cond);
+}
+
+pub fn fcmovu_st0_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+    let cond = cpu.flags.contains(Flags::PF);
+    fcmov(cpu, instr, cond);
+}
+
+pub fn fcmovnb_st0_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+    let cond = !cpu.flags.contains(Flags::CF);
+    fcmov(cpu, instr, cond);
+}
+
+pub fn fcmovne_st0_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+    let cond = !cpu.flags.contains(Flags::ZF);
+    fcmov(cpu, instr, cond);
 }
 
 pub fn fcmovnbe_st0_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
-    if !cpu.flags.contains(Flags::CF) && !cpu.flags.contains(Flags::ZF) {
+    let cond = !cpu.flags.intersects(Flags::CF | Flags::ZF);
+    fcmov(cpu, instr, cond);
+}
+
+pub fn fcmovnu_st0_sti(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+    let cond = !cpu.flags.contains(Flags::PF);
+    fcmov(cpu, instr, cond);
+}
+
+fn fcmov(cpu: &mut CPU, instr: &Instruction, cond: bool) {
+    if cond {
         let y = *cpu.fpu.get(instr.op1_register());
         *cpu.fpu.st0() = y;
     }
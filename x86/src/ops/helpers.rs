@@ -1,7 +1,13 @@
 //! Functions for common behaviors across all operations.
 
-use crate::{x86::CPU, Register};
-use memory::{Extensions, ExtensionsMut, Mem};
+use crate::{
+    x86::{Fault, CPU},
+    Register,
+};
+use memory::{Mem, Pod, PAGE_TRAPS_RECORD, PAGE_TRAP_GUARD, PAGE_TRAP_READ, PAGE_TRAP_WRITE};
+
+/// The trap bits that make reads fault.
+const READ_TRAPS: u8 = PAGE_TRAP_READ | PAGE_TRAP_GUARD;
 
 // TODO: maybe there are no 64-bit memory reads needed (?)
 pub fn rm64_x(
@@ -18,10 +24,12 @@ pub fn rm64_x(
             cpu.regs.set64(reg, value);
         }
         iced_x86::OpKind::Memory => {
-            let addr = x86_addr(cpu, instr);
-            let x = mem.get_pod::<u64>(addr);
-            let value = op(cpu, x);
-            mem.put_pod::<u64>(addr, value);
+            let Some(ptr) = rm_ptr::<u64>(cpu, mem, instr) else {
+                return;
+            };
+            let arg = Arg(ptr);
+            let value = op(cpu, arg.get());
+            arg.set(value);
         }
         _ => unimplemented!(),
     }
@@ -48,111 +56,263 @@ impl<T> Arg<T> {
     }
 }
 
-pub fn rm32<'a>(cpu: &'a mut CPU, mem: Mem, instr: &iced_x86::Instruction) -> Arg<u32> {
+/// Pointer to the memory operand of an rm* accessor.  Its only check is a lookup in the
+/// page trap table, which is usually zero; the pages past the end of memory are guard
+/// pages there, so that also covers the bounds of memory.  Anything else is left to
+/// the slow path.  If the access fails, the CPU is left in a fault state and this
+/// returns None; the op must then return without touching any other state.
+#[inline]
+fn rm_ptr<T: Pod>(cpu: &mut CPU, mem: Mem, instr: &iced_x86::Instruction) -> Option<*mut T> {
+    let addr = x86_addr(cpu, instr);
+    let len = std::mem::size_of::<T>() as u32;
+    if mem.page_traps(addr, len) != 0 && operand_traps(cpu, mem, instr, addr, len) {
+        return None;
+    }
+    Some(mem.get_ptr_mut_unchecked::<T>(addr))
+}
+
+/// Read the memory operand of an op1_rm* accessor, or None if it faults; see rm_ptr.
+#[inline]
+pub fn op1_mem<T: Clone + Pod>(
+    cpu: &mut CPU,
+    mem: Mem,
+    instr: &iced_x86::Instruction,
+) -> Option<T> {
+    let addr = x86_addr(cpu, instr);
+    let len = std::mem::size_of::<T>() as u32;
+    if mem.page_traps(addr, len) & READ_TRAPS != 0 && read_page_traps(cpu, mem, addr, len) {
+        return None;
+    }
+    // Safety: just checked.
+    Some(unsafe { mem.get_pod_unchecked::<T>(addr) })
+}
+
+pub fn rm32<'a>(cpu: &'a mut CPU, mem: Mem, instr: &iced_x86::Instruction) -> Option<Arg<u32>> {
     match instr.op0_kind() {
         iced_x86::OpKind::Register => {
             let reg = instr.op0_register();
-            Arg(cpu.regs.get32_mut(reg))
-        }
-        iced_x86::OpKind::Memory => {
-            let addr = x86_addr(cpu, instr);
-            Arg(mem.get_ptr_mut::<u32>(addr))
+            Some(Arg(cpu.regs.get32_mut(reg)))
         }
+        iced_x86::OpKind::Memory => rm_ptr(cpu, mem, instr).map(Arg),
         _ => unimplemented!(),
     }
 }
 
-pub fn rm16<'a>(cpu: &'a mut CPU, mem: Mem, instr: &iced_x86::Instruction) -> Arg<u16> {
+pub fn rm16<'a>(cpu: &'a mut CPU, mem: Mem, instr: &iced_x86::Instruction) -> Option<Arg<u16>> {
     match instr.op0_kind() {
         iced_x86::OpKind::Register => {
             let reg = instr.op0_register();
-            Arg(cpu.regs.get16_mut(reg))
-        }
-        iced_x86::OpKind::Memory => {
-            let addr = x86_addr(cpu, instr);
-            Arg(mem.get_ptr_mut::<u16>(addr))
+            Some(Arg(cpu.regs.get16_mut(reg)))
         }
+        iced_x86::OpKind::Memory => rm_ptr(cpu, mem, instr).map(Arg),
         _ => unimplemented!(),
     }
 }
 
-pub fn rm8<'a>(cpu: &'a mut CPU, mem: Mem, instr: &iced_x86::Instruction) -> Arg<u8> {
+pub fn rm8<'a>(cpu: &'a mut CPU, mem: Mem, instr: &iced_x86::Instruction) -> Option<Arg<u8>> {
     match instr.op0_kind() {
         iced_x86::OpKind::Register => {
             let reg = instr.op0_register();
-            Arg(cpu.regs.get8_mut(reg))
-        }
-        iced_x86::OpKind::Memory => {
-            let mut addr = x86_addr(cpu, instr);
-            if mem.is_oob::<u8>(addr) {
-                cpu.err(format!("oob at {addr:x}"));
-                addr = 0;
-            }
-            Arg(mem.get_ptr_mut::<u8>(addr))
+            Some(Arg(cpu.regs.get8_mut(reg)))
         }
+        iced_x86::OpKind::Memory => rm_ptr(cpu, mem, instr).map(Arg),
         _ => unimplemented!(),
     }
 }
 
-pub fn op1_rm32(cpu: &mut CPU, mem: Mem, instr: &iced_x86::Instruction) -> u32 {
+pub fn op1_rm32(cpu: &mut CPU, mem: Mem, instr: &iced_x86::Instruction) -> Option<u32> {
     match instr.op1_kind() {
-        iced_x86::OpKind::Register => cpu.regs.get32(instr.op1_register()),
-        iced_x86::OpKind::Memory => {
-            let addr = x86_addr(cpu, instr);
-            if mem.is_oob::<u32>(addr) {
-                cpu.err(format!("oob at {addr:x}"));
-                return 0;
-            }
-            mem.get_pod::<u32>(addr)
-        }
+        iced_x86::OpKind::Register => Some(cpu.regs.get32(instr.op1_register())),
+        iced_x86::OpKind::Memory => op1_mem(cpu, mem, instr),
         _ => unreachable!(),
     }
 }
 
-pub fn op1_rm16(cpu: &mut CPU, mem: Mem, instr: &iced_x86::Instruction) -> u16 {
+pub fn op1_rm16(cpu: &mut CPU, mem: Mem, instr: &iced_x86::Instruction) -> Option<u16> {
     match instr.op1_kind() {
-        iced_x86::OpKind::Register => cpu.regs.get16(instr.op1_register()),
-        iced_x86::OpKind::Memory => mem.get_pod::<u16>(x86_addr(cpu, instr)),
+        iced_x86::OpKind::Register => Some(cpu.regs.get16(instr.op1_register())),
+        iced_x86::OpKind::Memory => op1_mem(cpu, mem, instr),
         _ => unreachable!(),
     }
 }
 
-pub fn op1_rm8(cpu: &mut CPU, mem: Mem, instr: &iced_x86::Instruction) -> u8 {
+pub fn op1_rm8(cpu: &mut CPU, mem: Mem, instr: &iced_x86::Instruction) -> Option<u8> {
     match instr.op1_kind() {
-        iced_x86::OpKind::Register => cpu.regs.get8(instr.op1_register()),
-        iced_x86::OpKind::Memory => mem.get_pod::<u8>(x86_addr(cpu, instr)),
+        iced_x86::OpKind::Register => Some(cpu.regs.get8(instr.op1_register())),
+        iced_x86::OpKind::Memory => op1_mem(cpu, mem, instr),
         _ => unreachable!(),
     }
 }
 
+/// Slow path of the rm* accessors, for an operand on a page with trap bits.
+/// Any access to a guard or no-access page faults, as does a write to a read-only page; the rm*
+/// accessors can't tell whether the caller will write, so we ask the decoder.
+/// Returns true if the instruction faulted.
+#[cold]
+fn operand_traps(
+    cpu: &mut CPU,
+    mem: Mem,
+    instr: &iced_x86::Instruction,
+    addr: u32,
+    len: u32,
+) -> bool {
+    let traps = mem.page_traps(addr, len);
+    let write = writes_memory(instr);
+    if traps & PAGE_TRAP_GUARD != 0 {
+        cpu.fault(Fault::GuardPage { addr, write });
+        true
+    } else if traps & PAGE_TRAP_READ != 0 || (traps & PAGE_TRAP_WRITE != 0 && write) {
+        cpu.fault(Fault::AccessViolation { addr, write });
+        true
+    } else {
+        if traps & PAGE_TRAPS_RECORD != 0 && write {
+            cpu.record_write(addr, len);
+        }
+        false
+    }
+}
+
+/// Whether an instruction writes to its memory operand.  Other memory it uses, like the
+/// stack slot of `push [mem]`, doesn't count.
+#[cold]
+fn writes_memory(instr: &iced_x86::Instruction) -> bool {
+    iced_x86::InstructionInfoFactory::new()
+        .info_options(instr, iced_x86::InstructionInfoOptions::NO_REGISTER_USAGE)
+        .used_memory()
+        .iter()
+        .filter(|m| {
+            m.segment() == instr.memory_segment()
+                && m.base() == instr.memory_base()
+                && m.index() == instr.memory_index()
+                && m.scale() == instr.memory_index_scale()
+                && m.displacement() == instr.memory_displacement64()
+        })
+        .any(|m| {
+            !matches!(
+                m.access(),
+                iced_x86::OpAccess::Read
+                    | iced_x86::OpAccess::CondRead
+                    | iced_x86::OpAccess::NoMemAccess
+            )
+        })
+}
+
+/// Check a read of len bytes at addr against page protection and the bounds of memory,
+/// faulting if it traps.  Returns true if the read must not happen.
+pub fn read_traps(cpu: &mut CPU, mem: Mem, addr: u32, len: u32) -> bool {
+    mem.page_traps(addr, len) & READ_TRAPS != 0 && read_page_traps(cpu, mem, addr, len)
+}
+
+/// Slow path of read checks, for a read from a page that faults on reads.
+#[cold]
+fn read_page_traps(cpu: &mut CPU, mem: Mem, addr: u32, len: u32) -> bool {
+    if mem.page_traps(addr, len) & PAGE_TRAP_GUARD != 0 {
+        cpu.fault(Fault::GuardPage { addr, write: false });
+    } else {
+        cpu.fault(Fault::AccessViolation { addr, write: false });
+    }
+    true
+}
+
+/// Read a T at addr, which read_traps() has let through.
+#[inline]
+fn load<T: Clone + Pod>(mem: Mem, addr: u32) -> T {
+    // Safety: read_traps() checked the access against the page table.
+    unsafe { mem.get_pod_unchecked::<T>(addr) }
+}
+
+/// Write a T at addr, which write_traps() has let through.
+#[inline]
+fn store<T: Pod>(mem: Mem, addr: u32, value: T) {
+    Arg(mem.get_ptr_mut_unchecked::<T>(addr)).set(value)
+}
+
+/// Read an instruction's memory operand, or None if the read faults.
+pub fn read_operand<T: Clone + Pod>(
+    cpu: &mut CPU,
+    mem: Mem,
+    instr: &iced_x86::Instruction,
+) -> Option<T> {
+    let addr = x86_addr(cpu, instr);
+    if read_traps(cpu, mem, addr, std::mem::size_of::<T>() as u32) {
+        return None;
+    }
+    Some(load::<T>(mem, addr))
+}
+
+/// Read a value from memory, or if the read faults, return zero for the op to finish
+/// with harmlessly; callers check cpu.state before changing anything else.
+pub fn read_pod<T: Clone + Pod>(cpu: &mut CPU, mem: Mem, addr: u32) -> T {
+    if read_traps(cpu, mem, addr, std::mem::size_of::<T>() as u32) {
+        return T::zeroed();
+    }
+    load::<T>(mem, addr)
+}
+
+/// Check a write of len bytes at addr against page protection and the bounds of memory,
+/// faulting if it traps.  Returns true if the write must not happen.
+pub fn write_traps(cpu: &mut CPU, mem: Mem, addr: u32, len: u32) -> bool {
+    let traps = mem.page_traps(addr, len);
+    if traps == 0 {
+        return false;
+    }
+    if traps & PAGE_TRAP_GUARD != 0 {
+        cpu.fault(Fault::GuardPage { addr, write: true });
+    } else if traps & PAGE_TRAP_WRITE != 0 {
+        cpu.fault(Fault::AccessViolation { addr, write: true });
+    } else {
+        cpu.record_write(addr, len);
+        return false;
+    }
+    true
+}
+
+/// Write a value to memory, unless page protection makes the write fault.
+pub fn write_pod<T: Clone + Pod>(cpu: &mut CPU, mem: Mem, addr: u32, value: T) {
+    if write_traps(cpu, mem, addr, std::mem::size_of::<T>() as u32) {
+        return;
+    }
+    store::<T>(mem, addr, value);
+}
+
 /// Push a u32 on the x86 stack.
 pub fn push(cpu: &mut CPU, mem: Mem, value: u32) {
-    let esp = cpu.regs.get32_mut(Register::ESP);
-    *esp -= 4;
-    mem.put_pod::<u32>(*esp, value);
+    let esp = cpu.regs.get32(Register::ESP).wrapping_sub(4);
+    if write_traps(cpu, mem, esp, 4) {
+        return;
+    }
+    cpu.regs.set32(Register::ESP, esp);
+    store::<u32>(mem, esp, value);
 }
 
 /// Push a u16 on the x86 stack.
 pub fn push16(cpu: &mut CPU, mem: Mem, value: u16) {
-    let esp = cpu.regs.get32_mut(Register::ESP);
-    *esp -= 2;
-    mem.put_pod::<u16>(*esp, value);
+    let esp = cpu.regs.get32(Register::ESP).wrapping_sub(2);
+    if write_traps(cpu, mem, esp, 2) {
+        return;
+    }
+    cpu.regs.set32(Register::ESP, esp);
+    store::<u16>(mem, esp, value);
 }
 
-/// Pop a u32 from the x86 stack.
+/// Pop a u32 from the x86 stack.  If the read faults, returns 0 and leaves esp alone.
 pub fn pop(cpu: &mut CPU, mem: Mem) -> u32 {
-    let esp = cpu.regs.get32_mut(Register::ESP);
-    let value = mem.get_pod::<u32>(*esp);
-    *esp += 4;
-    value
+    let esp = cpu.regs.get32(Register::ESP);
+    if read_traps(cpu, mem, esp, 4) {
+        return 0;
+    }
+    cpu.regs.set32(Register::ESP, esp.wrapping_add(4));
+    load::<u32>(mem, esp)
 }
 
-/// Pop a u16 from the x86 stack.
+/// Pop a u16 from the x86 stack.  If the read faults, returns 0 and leaves esp alone.
 pub fn pop16(cpu: &mut CPU, mem: Mem) -> u16 {
-    let esp = cpu.regs.get32_mut(Register::ESP);
-    let value = mem.get_pod::<u16>(*esp);
-    *esp += 2;
-    value
+    let esp = cpu.regs.get32(Register::ESP);
+    if read_traps(cpu, mem, esp, 2) {
+        return 0;
+    }
+    cpu.regs.set32(Register::ESP, esp.wrapping_add(2));
+    load::<u16>(mem, esp)
 }
 
 /// Compute the address found in instructions that reference memory, e.g.
@@ -169,10 +329,11 @@ pub fn x86_addr(cpu: &CPU, instr: &iced_x86::Instruction) -> u32 {
     // wrap, but in some cases the components are negative which is implemented
     // in two's complement by a wrapping add.
 
-    // TODO: see comments on regs.fs_addr.
+    // Only the TEB segment has a nonzero base, and only FS and GS ever select it in practice,
+    // so we can skip the lookup for the other prefixes.
     match instr.segment_prefix() {
-        iced_x86::Register::FS => {
-            let seg = cpu.regs.fs_addr;
+        iced_x86::Register::FS | iced_x86::Register::GS => {
+            let seg = cpu.regs.segment_base(instr.segment_prefix());
             addr = addr.wrapping_add(seg);
         }
         _ => {}
@@ -211,3 +372,53 @@ pub fn set_dx_ax(cpu: &mut CPU, value: u32) {
 pub fn get_dx_ax(cpu: &mut CPU) -> u32 {
     ((cpu.regs.get16(Register::DX) as u32) << 16) | (cpu.regs.get16(Register::AX) as u32)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::decode;
+    use crate::{x86::CPUState, CPU};
+    use iced_x86::{Code, Instruction, MemoryOperand, Register};
+    use memory::Mem;
+
+    /// Run one instruction against a 16-byte memory, so any operand at 0x1000 faults.
+    fn run(cpu: &mut CPU, buf: &[u8], instr: &Instruction) {
+        let func = decode(instr).unwrap();
+        func(cpu, Mem::from_slice(buf), instr);
+    }
+
+    fn out_of_bounds() -> MemoryOperand {
+        MemoryOperand::with_displ(0x1000, 4)
+    }
+
+    #[test]
+    fn faulting_operand_skips_op() {
+        let buf = [0x11u8; 16];
+        let mut cpu = CPU::new();
+        cpu.regs.set32(Register::EBX, 1);
+        let flags = cpu.flags.bits();
+        let instr = Instruction::with2(Code::Add_rm32_r32, out_of_bounds(), Register::EBX).unwrap();
+        run(&mut cpu, &buf, &instr);
+        assert!(matches!(cpu.state, CPUState::Fault(_)));
+        assert_eq!(buf, [0x11; 16]);
+        assert_eq!(cpu.flags.bits(), flags);
+
+        let mut cpu = CPU::new();
+        let instr = Instruction::with2(Code::Add_r32_rm32, Register::EBX, out_of_bounds()).unwrap();
+        run(&mut cpu, &buf, &instr);
+        assert!(matches!(cpu.state, CPUState::Fault(_)));
+        assert_eq!(cpu.regs.get32(Register::EBX), 0);
+        assert_eq!(cpu.flags.bits(), flags);
+    }
+
+    #[test]
+    fn faulting_pop_keeps_esp() {
+        let buf = [0x11u8; 16];
+        let mut cpu = CPU::new();
+        cpu.regs.set32(Register::ESP, 4);
+        let instr = Instruction::with1(Code::Pop_rm32, out_of_bounds()).unwrap();
+        run(&mut cpu, &buf, &instr);
+        assert!(matches!(cpu.state, CPUState::Fault(_)));
+        assert_eq!(cpu.regs.get32(Register::ESP), 4);
+        assert_eq!(buf, [0x11; 16]);
+    }
+}
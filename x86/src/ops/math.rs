@@ -1,5 +1,10 @@
 use super::helpers::*;
-use crate::{registers::Flags, x86::CPU};
+use crate::{
+    flags::{parity, FlagOp, LazyFlags},
+    registers::Flags,
+    x86::CPU,
+    Fault,
+};
 use iced_x86::{Instruction, Register};
 use memory::Mem;
 use num_traits::ops::overflowing::OverflowingSub;
@@ -11,11 +16,11 @@ use num_traits::ops::overflowing::OverflowingSub;
 /// (which is x.shr(I::bits() - 1))
 /// that math optimizes down to the appropriate constant.
 pub(crate) trait Int: num_traits::PrimInt {
-    fn as_usize(self) -> usize;
+    fn as_u32(self) -> u32;
     fn bits() -> usize;
 }
 impl Int for u64 {
-    fn as_usize(self) -> usize {
+    fn as_u32(self) -> u32 {
         unimplemented!()
     }
     fn bits() -> usize {
@@ -23,377 +28,450 @@ impl Int for u64 {
     }
 }
 impl Int for u32 {
-    fn as_usize(self) -> usize {
-        self as usize
+    fn as_u32(self) -> u32 {
+        self
     }
     fn bits() -> usize {
         32
     }
 }
 impl Int for u16 {
-    fn as_usize(self) -> usize {
-        self as usize
+    fn as_u32(self) -> u32 {
+        self as u32
     }
     fn bits() -> usize {
         16
     }
 }
 impl Int for u8 {
-    fn as_usize(self) -> usize {
-        self as usize
+    fn as_u32(self) -> u32 {
+        self as u32
     }
     fn bits() -> usize {
         8
     }
 }
 
+/// Record the flags of and/or/xor, which are set according to the result, except for
+/// OF and CF which are cleared.
+fn logic<I: Int>(x: I, y: I, result: I, flags: &mut LazyFlags) {
+    #[cfg(feature = "check-flags")]
+    let mut eager = flags.get();
+    flags.record(
+        FlagOp::Logic,
+        I::bits(),
+        x.as_u32(),
+        y.as_u32(),
+        result.as_u32(),
+        false,
+    );
+    #[cfg(feature = "check-flags")]
+    {
+        eager.set(Flags::ZF, result.is_zero());
+        eager.set(Flags::SF, (result >> (I::bits() - 1)).is_one());
+        eager.set(Flags::PF, parity(result.as_u32()));
+        eager.remove(Flags::OF | Flags::CF);
+        flags.check(eager);
+    }
+}
+
 // pub(crate) for use in the test opcode impl.
-pub(crate) fn and<I: Int>(x: I, y: I, flags: &mut Flags) -> I {
+pub(crate) fn and<I: Int>(x: I, y: I, flags: &mut LazyFlags) -> I {
     let result = x & y;
-    flags.set(Flags::ZF, result.is_zero());
-    flags.set(Flags::SF, (result >> (I::bits() - 1)).is_one());
-    flags.set(Flags::OF, false);
-    flags.set(Flags::CF, false);
+    logic(x, y, result, flags);
     result
 }
 
 pub fn and_rm32_imm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = instr.immediate32();
-    let x = rm32(cpu, mem, instr);
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     x.set(and(x.get(), y, &mut cpu.flags));
 }
 
 pub fn and_rm32_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = instr.immediate8to32() as u32;
-    let x = rm32(cpu, mem, instr);
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     x.set(and(x.get(), y, &mut cpu.flags));
 }
 
 pub fn and_rm32_r32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = cpu.regs.get32(instr.op1_register());
-    let x = rm32(cpu, mem, instr);
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     x.set(and(x.get(), y, &mut cpu.flags));
 }
 
 pub fn and_r32_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_rm32(cpu, mem, instr);
-    let x = rm32(cpu, mem, instr);
+    let Some(y) = op1_rm32(cpu, mem, instr) else {
+        return;
+    };
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     x.set(and(x.get(), y, &mut cpu.flags));
 }
 
 pub fn and_rm16_r16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_rm16(cpu, mem, instr);
-    let x = rm16(cpu, mem, instr);
+    let Some(y) = op1_rm16(cpu, mem, instr) else {
+        return;
+    };
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
     x.set(and(x.get(), y, &mut cpu.flags));
 }
 
 pub fn and_r16_rm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_rm16(cpu, mem, instr);
-    let x = rm16(cpu, mem, instr);
+    let Some(y) = op1_rm16(cpu, mem, instr) else {
+        return;
+    };
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
     x.set(and(x.get(), y, &mut cpu.flags));
 }
 
 pub fn and_rm16_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = instr.immediate8to16() as u16;
-    let x = rm16(cpu, mem, instr);
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
     x.set(and(x.get(), y, &mut cpu.flags));
 }
 
 pub fn and_rm16_imm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = instr.immediate16();
-    let x = rm16(cpu, mem, instr);
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
     x.set(and(x.get(), y, &mut cpu.flags));
 }
 
 pub fn and_rm8_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_rm8(cpu, mem, instr);
-    let x = rm8(cpu, mem, instr);
+    let Some(y) = op1_rm8(cpu, mem, instr) else {
+        return;
+    };
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     x.set(and(x.get(), y, &mut cpu.flags));
 }
 
 pub fn and_rm8_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = instr.immediate8();
-    let x = rm8(cpu, mem, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     x.set(and(x.get(), y, &mut cpu.flags));
 }
 
-fn or<I: Int>(x: I, y: I, flags: &mut Flags) -> I {
+fn or<I: Int>(x: I, y: I, flags: &mut LazyFlags) -> I {
     let result = x | y;
-    flags.remove(Flags::OF | Flags::CF);
-    flags.set(Flags::SF, result.shr(I::bits() - 1).is_one());
-    flags.set(Flags::ZF, result.is_zero());
+    logic(x, y, result, flags);
     result
 }
 
 pub fn or_rm32_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_rm32(cpu, mem, instr);
-    let x = rm32(cpu, mem, instr);
+    let Some(y) = op1_rm32(cpu, mem, instr) else {
+        return;
+    };
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     x.set(or(x.get(), y, &mut cpu.flags));
 }
 
 pub fn or_rm32_imm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = instr.immediate32();
-    let x = rm32(cpu, mem, instr);
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     x.set(or(x.get(), y, &mut cpu.flags));
 }
 
 pub fn or_rm32_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = instr.immediate8to32() as u32;
-    let x = rm32(cpu, mem, instr);
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     x.set(or(x.get(), y, &mut cpu.flags));
 }
 
 pub fn or_rm16_imm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = instr.immediate16();
-    let x = rm16(cpu, mem, instr);
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
     x.set(or(x.get(), y, &mut cpu.flags));
 }
 
 pub fn or_rm16_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = instr.immediate8to16() as u16;
-    let x = rm16(cpu, mem, instr);
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
     x.set(or(x.get(), y, &mut cpu.flags));
 }
 
 pub fn or_rm16_rm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_rm16(cpu, mem, instr);
-    let x = rm16(cpu, mem, instr);
+    let Some(y) = op1_rm16(cpu, mem, instr) else {
+        return;
+    };
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
     x.set(or(x.get(), y, &mut cpu.flags));
 }
 
 pub fn or_rm8_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_rm8(cpu, mem, instr);
-    let x = rm8(cpu, mem, instr);
+    let Some(y) = op1_rm8(cpu, mem, instr) else {
+        return;
+    };
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     x.set(or(x.get(), y, &mut cpu.flags));
 }
 
 pub fn or_rm8_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = instr.immediate8();
-    let x = rm8(cpu, mem, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     x.set(or(x.get(), y, &mut cpu.flags));
 }
 
 pub fn or_r8_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_rm8(cpu, mem, instr);
-    let x = rm8(cpu, mem, instr);
+    let Some(y) = op1_rm8(cpu, mem, instr) else {
+        return;
+    };
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     x.set(or(x.get(), y, &mut cpu.flags));
 }
 
-fn shl<I: Int + num_traits::WrappingShl>(x: I, y: u8, flags: &mut Flags) -> I {
-    let y = y % 32;
-    if y == 0 {
-        return x;
-    }
+/// The count operand of a shift or rotate, which is CL, an immediate, or an implicit 1,
+/// masked to 5 bits as the CPU does.  A count of 0 leaves the flags alone.
+fn shift_count(cpu: &CPU, instr: &Instruction) -> u8 {
+    let count = match instr.op1_kind() {
+        iced_x86::OpKind::Register => cpu.regs.get8(Register::CL),
+        _ => instr.immediate8(),
+    };
+    count & 0x1F
+}
 
-    // Carry is the highest bit that will be shifted out.
-    let cf = (x.shr(I::bits() - y as usize) & I::one()).is_one();
-    let val = x.wrapping_shl(y.as_usize() as u32);
+/// Set the flags shared by the shifts, from their result.
+fn shift_flags<I: Int>(result: I, cf: bool, of: bool, flags: &mut LazyFlags) {
     flags.set(Flags::CF, cf);
-    let msb = val.shr(I::bits() - 1).is_one();
-    flags.set(Flags::SF, msb);
-    // Note: OF only defined for 1-bit rotates.
-    // "For left shifts, the OF flag is set to 0 if the mostsignificant bit of the result is the
-    // same as the CF flag (that is, the top two bits of the original operand were the same) [...]"
-    flags.set(
-        Flags::OF,
-        x.shr(I::bits() - 1).is_one() ^ (x.shr(I::bits() - 2) & I::one()).is_one(),
-    );
-    flags.set(Flags::ZF, val.is_zero());
-
-    val
+    // Note: OF only defined for 1-bit shifts.
+    flags.set(Flags::OF, of);
+    flags.set(Flags::SF, (result >> (I::bits() - 1)).is_one());
+    flags.set(Flags::ZF, result.is_zero());
+    flags.set(Flags::PF, parity(result.as_u32()));
 }
 
-pub fn shl_rm32_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = instr.immediate8();
-    let x = rm32(cpu, mem, instr);
-    x.set(shl(x.get(), y, &mut cpu.flags));
+/// Truncate a shift or rotate result computed in a wider type back to I.
+fn narrow<I: Int>(x: u64) -> I {
+    I::from(x & (u64::MAX >> (64 - I::bits()))).unwrap()
 }
 
-pub fn shl_rm32_cl(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = cpu.regs.get8(Register::CL);
-    let x = rm32(cpu, mem, instr);
-    x.set(shl(x.get(), y, &mut cpu.flags));
+fn shl<I: Int>(x: I, count: u8, flags: &mut LazyFlags) -> I {
+    if count == 0 {
+        return x;
+    }
+    // Shift in a wider type, so that counts past the width work out to 0.
+    let wide = (x.as_u32() as u64) << count;
+    let result: I = narrow(wide);
+    // Carry is the last bit shifted out.
+    let cf = (wide >> I::bits()) & 1 != 0;
+    // "For left shifts, the OF flag is set to 0 if the mostsignificant bit of the result is the
+    // same as the CF flag (that is, the top two bits of the original operand were the same) [...]"
+    let of = (result >> (I::bits() - 1)).is_one() != cf;
+    shift_flags(result, cf, of, flags);
+    result
 }
 
-pub fn shl_rm16_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = instr.immediate8();
-    let x = rm16(cpu, mem, instr);
-    x.set(shl(x.get(), y, &mut cpu.flags));
+pub fn shl_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let count = shift_count(cpu, instr);
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
+    x.set(shl(x.get(), count, &mut cpu.flags));
 }
 
-pub fn shl_rm8_cl(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = cpu.regs.get8(Register::CL);
-    let x = rm8(cpu, mem, instr);
-    x.set(shl(x.get(), y, &mut cpu.flags));
+pub fn shl_rm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let count = shift_count(cpu, instr);
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
+    x.set(shl(x.get(), count, &mut cpu.flags));
 }
 
-pub fn shl_rm8_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = instr.immediate8();
-    let x = rm8(cpu, mem, instr);
-    x.set(shl(x.get(), y, &mut cpu.flags));
+pub fn shl_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let count = shift_count(cpu, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
+    x.set(shl(x.get(), count, &mut cpu.flags));
 }
 
-fn shld(x: Arg<u32>, y: u32, count: u8, flags: &mut Flags) {
+fn shld(x: Arg<u32>, y: u32, count: u8, flags: &mut LazyFlags) {
     let count = count % 32;
     if count == 0 {
         return;
     }
     let val = x.get();
+    let result = (val << count) | (y >> (32 - count));
     // "CF flag is filled with the last bit shifted out of the destination operand"
-    flags.set(Flags::CF, ((val >> (32 - count)) & 1) != 0);
-    if count == 1 {
-        // "OF flag is set if a sign change occurred"
-        flags.set(Flags::OF, (val >> 31) != ((val >> 30) & 1));
-    }
-    x.set((val << count) | (y >> (32 - count)));
+    let cf = ((val >> (32 - count)) & 1) != 0;
+    // "OF flag is set if a sign change occurred"
+    shift_flags(result, cf, (val ^ result) >> 31 != 0, flags);
+    x.set(result);
 }
 
 pub fn shld_rm32_r32_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let count = instr.immediate8();
-    let y = op1_rm32(cpu, mem, instr);
-    let x = rm32(cpu, mem, instr);
+    let Some(y) = op1_rm32(cpu, mem, instr) else {
+        return;
+    };
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     shld(x, y, count, &mut cpu.flags);
 }
 
 pub fn shld_rm32_r32_cl(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let count = cpu.regs.get8(Register::CL);
-    let y = op1_rm32(cpu, mem, instr);
-    let x = rm32(cpu, mem, instr);
+    let Some(y) = op1_rm32(cpu, mem, instr) else {
+        return;
+    };
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     shld(x, y, count, &mut cpu.flags);
 }
 
-fn shr<I: Int>(x: I, y: u8, flags: &mut Flags) -> I {
-    // In all modes but 64 it is correct to mask to 32 bits.
-    assert!(I::bits() < 64); // 64 not implemented
-    let y = y % 32;
-
-    if y == 0 {
+fn shr<I: Int>(x: I, count: u8, flags: &mut LazyFlags) -> I {
+    if count == 0 {
         return x; // Don't affect flags.
     }
-
-    flags.set(Flags::CF, ((x >> (y - 1) as usize) & I::one()).is_one());
-    let val = x >> y as usize;
-    flags.set(Flags::SF, false); // ?
-    flags.set(Flags::ZF, val.is_zero());
-
-    // Note: OF state undefined for shifts > 1 bit.
-    flags.set(Flags::OF, (x >> (I::bits() - 1)).is_one());
-    val
-}
-
-pub fn shr_rm32_cl(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = cpu.regs.get8(Register::CL);
-    let x = rm32(cpu, mem, instr);
-    x.set(shr(x.get(), y, &mut cpu.flags));
-}
-
-pub fn shr_rm32_1(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let x = rm32(cpu, mem, instr);
-    x.set(shr(x.get(), 1, &mut cpu.flags));
-}
-
-pub fn shr_rm32_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = instr.immediate8();
-    let x = rm32(cpu, mem, instr);
-    x.set(shr(x.get(), y, &mut cpu.flags));
-}
-
-pub fn shr_rm16_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = instr.immediate8();
-    let x = rm16(cpu, mem, instr);
-    x.set(shr(x.get(), y, &mut cpu.flags));
+    let wide = x.as_u32() as u64;
+    let result: I = narrow(wide >> count);
+    let cf = (wide >> (count - 1)) & 1 != 0;
+    // OF is the sign of the original operand.
+    shift_flags(result, cf, (x >> (I::bits() - 1)).is_one(), flags);
+    result
 }
 
-pub fn shr_rm16_1(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let x = rm16(cpu, mem, instr);
-    x.set(shr(x.get(), 1, &mut cpu.flags));
+pub fn shr_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let count = shift_count(cpu, instr);
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
+    x.set(shr(x.get(), count, &mut cpu.flags));
 }
 
-pub fn shr_rm8_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = instr.immediate8();
-    let x = rm8(cpu, mem, instr);
-    x.set(shr(x.get(), y, &mut cpu.flags));
+pub fn shr_rm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let count = shift_count(cpu, instr);
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
+    x.set(shr(x.get(), count, &mut cpu.flags));
 }
 
-pub fn shr_rm8_cl(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = cpu.regs.get8(Register::CL);
-    let x = rm8(cpu, mem, instr);
-    x.set(shr(x.get(), y, &mut cpu.flags));
+pub fn shr_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let count = shift_count(cpu, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
+    x.set(shr(x.get(), count, &mut cpu.flags));
 }
 
-fn shrd(x: Arg<u32>, y: u32, count: u8, flags: &mut Flags) {
+fn shrd(x: Arg<u32>, y: u32, count: u8, flags: &mut LazyFlags) {
     let count = count % 32;
     if count == 0 {
         return;
     }
     let val = x.get();
+    let result = (val >> count) | (y << (32 - count));
     // "CF flag is filled with the last bit shifted out of the destination operand"
-    flags.set(Flags::CF, ((val >> (count - 1)) & 1) != 0);
-    if count == 1 {
-        // "OF flag is set if a sign change occurred"
-        flags.set(Flags::OF, (val >> 31) != (y & 1));
-    }
-    x.set((val >> count) | (y << (32 - count)));
+    let cf = ((val >> (count - 1)) & 1) != 0;
+    // "OF flag is set if a sign change occurred"
+    shift_flags(result, cf, (val ^ result) >> 31 != 0, flags);
+    x.set(result);
 }
 
 pub fn shrd_rm32_r32_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let count = instr.immediate8();
-    let y = op1_rm32(cpu, mem, instr);
-    let x = rm32(cpu, mem, instr);
+    let Some(y) = op1_rm32(cpu, mem, instr) else {
+        return;
+    };
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     shrd(x, y, count, &mut cpu.flags);
 }
 
 pub fn shrd_rm32_r32_cl(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let count = cpu.regs.get8(Register::CL);
-    let y = op1_rm32(cpu, mem, instr);
-    let x = rm32(cpu, mem, instr);
+    let Some(y) = op1_rm32(cpu, mem, instr) else {
+        return;
+    };
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     shrd(x, y, count, &mut cpu.flags);
 }
 
-fn sar<I: Int>(x: I, y: I, flags: &mut Flags) -> I {
-    if y.is_zero() {
+fn sar<I: Int>(x: I, count: u8, flags: &mut LazyFlags) -> I {
+    if count == 0 {
         return x;
     }
-    flags.set(Flags::CF, x.shr(y.as_usize() - 1).bitand(I::one()).is_one());
-    // Note: OF only defined for 1-bit rotates.
-    flags.set(Flags::OF, false);
-    // There's a random "u32" type in the num-traits signed_shr signature, so cast here.
-    let result = x.signed_shr(y.as_usize() as u32);
-
-    flags.set(Flags::SF, result.shr(I::bits() - 1).is_one());
-    flags.set(Flags::ZF, result.is_zero());
+    // Sign extend, so that counts past the width fill with the sign bit.
+    let wide = ((x.as_u32() as u64) << (64 - I::bits())) as i64 >> (64 - I::bits());
+    let result: I = narrow((wide >> count) as u64);
+    let cf = (wide >> (count - 1)) & 1 != 0;
+    shift_flags(result, cf, false, flags);
     result
 }
 
-pub fn sar_rm32_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = instr.immediate8() as u32;
-    let x = rm32(cpu, mem, instr);
-    x.set(sar(x.get(), y, &mut cpu.flags));
+pub fn sar_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let count = shift_count(cpu, instr);
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
+    x.set(sar(x.get(), count, &mut cpu.flags));
 }
 
-pub fn sar_rm32_cl(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = cpu.regs.get8(Register::CL) as u32;
-    let x = rm32(cpu, mem, instr);
-    x.set(sar(x.get(), y, &mut cpu.flags));
+pub fn sar_rm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let count = shift_count(cpu, instr);
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
+    x.set(sar(x.get(), count, &mut cpu.flags));
 }
 
-pub fn sar_rm8_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = instr.immediate8() as u8;
-    let x = rm8(cpu, mem, instr);
-    x.set(sar(x.get(), y, &mut cpu.flags));
+pub fn sar_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let count = shift_count(cpu, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
+    x.set(sar(x.get(), count, &mut cpu.flags));
 }
 
-pub fn sar_rm8_cl(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = cpu.regs.get8(Register::CL);
-    let x = rm8(cpu, mem, instr);
-    x.set(sar(x.get(), y, &mut cpu.flags));
-}
+// The rotates only affect CF and OF.
 
-fn rol<I: Int>(x: I, y: u8, flags: &mut Flags) -> I {
-    if y == 0 {
+fn rol<I: Int>(x: I, count: u8, flags: &mut LazyFlags) -> I {
+    if count == 0 {
         return x;
     }
-    let result = x.rotate_left(y as u32);
+    let result = x.rotate_left(count as u32 % I::bits() as u32);
     let carry = (result & I::one()).is_one();
     flags.set(Flags::CF, carry);
     // Note: OF only defined for 1-bit rotates.
@@ -401,230 +479,446 @@ fn rol<I: Int>(x: I, y: u8, flags: &mut Flags) -> I {
     result
 }
 
-pub fn rol_rm32_cl(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = cpu.regs.get8(Register::CL);
-    let x = rm32(cpu, mem, instr);
-    x.set(rol(x.get(), y, &mut cpu.flags));
-}
-
-pub fn rol_rm32_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = instr.immediate8();
-    let x = rm32(cpu, mem, instr);
-    x.set(rol(x.get(), y, &mut cpu.flags));
+pub fn rol_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let count = shift_count(cpu, instr);
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
+    x.set(rol(x.get(), count, &mut cpu.flags));
 }
 
-pub fn rol_rm8_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = instr.immediate8();
-    let x = rm8(cpu, mem, instr);
-    x.set(rol(x.get(), y, &mut cpu.flags));
+pub fn rol_rm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let count = shift_count(cpu, instr);
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
+    x.set(rol(x.get(), count, &mut cpu.flags));
 }
 
-pub fn rol_rm8_cl(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = cpu.regs.get8(Register::CL);
-    let x = rm8(cpu, mem, instr);
-    x.set(rol(x.get(), y, &mut cpu.flags));
+pub fn rol_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let count = shift_count(cpu, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
+    x.set(rol(x.get(), count, &mut cpu.flags));
 }
 
-fn ror<I: Int>(x: I, y: u8, flags: &mut Flags) -> I {
-    if y == 0 {
+fn ror<I: Int>(x: I, count: u8, flags: &mut LazyFlags) -> I {
+    if count == 0 {
         return x;
     }
-    let result = x.rotate_right(y as u32);
+    let result = x.rotate_right(count as u32 % I::bits() as u32);
     let msb = (result >> (I::bits() - 1)).is_one();
     flags.set(Flags::CF, msb);
     // Note: OF only defined for 1-bit rotates.
-    flags.set(Flags::OF, msb ^ (result >> (I::bits() - 2)).is_one());
+    flags.set(
+        Flags::OF,
+        msb ^ (result >> (I::bits() - 2) & I::one()).is_one(),
+    );
     result
 }
 
-pub fn ror_rm32_cl(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = cpu.regs.get8(Register::CL);
-    let x = rm32(cpu, mem, instr);
-    x.set(ror(x.get(), y, &mut cpu.flags));
+pub fn ror_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let count = shift_count(cpu, instr);
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
+    x.set(ror(x.get(), count, &mut cpu.flags));
 }
 
-pub fn ror_rm32_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = instr.immediate8();
-    let x = rm32(cpu, mem, instr);
-    x.set(ror(x.get(), y, &mut cpu.flags));
+pub fn ror_rm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let count = shift_count(cpu, instr);
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
+    x.set(ror(x.get(), count, &mut cpu.flags));
 }
 
-pub fn ror_rm8_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = instr.immediate8();
-    let x = rm8(cpu, mem, instr);
-    x.set(ror(x.get(), y, &mut cpu.flags));
+pub fn ror_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let count = shift_count(cpu, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
+    x.set(ror(x.get(), count, &mut cpu.flags));
 }
 
-pub fn ror_rm8_cl(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = cpu.regs.get8(Register::CL);
-    let x = rm8(cpu, mem, instr);
-    x.set(ror(x.get(), y, &mut cpu.flags));
+/// rcl and rcr rotate through CF, as if it were an extra high bit of the operand.
+fn rcl<I: Int>(x: I, count: u8, flags: &mut LazyFlags) -> I {
+    if count == 0 {
+        return x;
+    }
+    let bits = I::bits() + 1;
+    let count = count as usize % bits;
+    let wide = ((flags.contains(Flags::CF) as u64) << I::bits()) | x.as_u32() as u64;
+    let rotated = ((wide << count) | (wide >> (bits - count))) & (u64::MAX >> (64 - bits));
+    let result: I = narrow(rotated);
+    let cf = rotated >> I::bits() != 0;
+    flags.set(Flags::CF, cf);
+    // Note: OF only defined for 1-bit rotates.
+    flags.set(Flags::OF, cf ^ (result >> (I::bits() - 1)).is_one());
+    result
+}
+
+pub fn rcl_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let count = shift_count(cpu, instr);
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
+    x.set(rcl(x.get(), count, &mut cpu.flags));
+}
+
+pub fn rcl_rm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let count = shift_count(cpu, instr);
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
+    x.set(rcl(x.get(), count, &mut cpu.flags));
+}
+
+pub fn rcl_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let count = shift_count(cpu, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
+    x.set(rcl(x.get(), count, &mut cpu.flags));
+}
+
+fn rcr<I: Int>(x: I, count: u8, flags: &mut LazyFlags) -> I {
+    if count == 0 {
+        return x;
+    }
+    let bits = I::bits() + 1;
+    let count = count as usize % bits;
+    let carry = flags.contains(Flags::CF);
+    let wide = ((carry as u64) << I::bits()) | x.as_u32() as u64;
+    let rotated = ((wide >> count) | (wide << (bits - count))) & (u64::MAX >> (64 - bits));
+    // Note: OF only defined for 1-bit rotates, where it's from before the rotate.
+    flags.set(Flags::OF, carry ^ (x >> (I::bits() - 1)).is_one());
+    flags.set(Flags::CF, rotated >> I::bits() != 0);
+    narrow(rotated)
+}
+
+pub fn rcr_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let count = shift_count(cpu, instr);
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
+    x.set(rcr(x.get(), count, &mut cpu.flags));
+}
+
+pub fn rcr_rm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let count = shift_count(cpu, instr);
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
+    x.set(rcr(x.get(), count, &mut cpu.flags));
+}
+
+pub fn rcr_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let count = shift_count(cpu, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
+    x.set(rcr(x.get(), count, &mut cpu.flags));
 }
 
-fn xor<I: Int>(x: I, y: I, flags: &mut Flags) -> I {
+fn xor<I: Int>(x: I, y: I, flags: &mut LazyFlags) -> I {
     let result = x ^ y;
     // The OF and CF flags are cleared; the SF, ZF, and PF flags are set according to the result. The state of the AF flag is undefined.
-    flags.remove(Flags::OF);
-    flags.remove(Flags::CF);
-    flags.set(Flags::ZF, result.is_zero());
-    flags.set(Flags::SF, (result >> (I::bits() - 1)).is_one());
+    logic(x, y, result, flags);
     result
 }
 
 pub fn xor_rm32_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_rm32(cpu, mem, instr);
-    let x = rm32(cpu, mem, instr);
+    let Some(y) = op1_rm32(cpu, mem, instr) else {
+        return;
+    };
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     x.set(xor(x.get(), y, &mut cpu.flags));
 }
 
 pub fn xor_rm32_imm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = instr.immediate32();
-    let x = rm32(cpu, mem, instr);
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     x.set(xor(x.get(), y, &mut cpu.flags));
 }
 
 pub fn xor_rm32_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = instr.immediate8to32() as u32;
-    let x = rm32(cpu, mem, instr);
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     x.set(xor(x.get(), y, &mut cpu.flags));
 }
 
 pub fn xor_rm8_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = instr.immediate8();
-    let x = rm8(cpu, mem, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     x.set(xor(x.get(), y, &mut cpu.flags));
 }
 
 pub fn xor_rm16_rm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_rm16(cpu, mem, instr);
-    let x = rm16(cpu, mem, instr);
+    let Some(y) = op1_rm16(cpu, mem, instr) else {
+        return;
+    };
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
     x.set(xor(x.get(), y, &mut cpu.flags));
 }
 
 pub fn xor_r8_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_rm8(cpu, mem, instr);
-    let x = rm8(cpu, mem, instr);
+    let Some(y) = op1_rm8(cpu, mem, instr) else {
+        return;
+    };
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     x.set(xor(x.get(), y, &mut cpu.flags));
 }
 
 pub fn xor_rm8_r8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = cpu.regs.get8(instr.op1_register());
-    let x = rm8(cpu, mem, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     x.set(xor(x.get(), y, &mut cpu.flags));
 }
 
-fn add<I: Int + num_traits::ops::wrapping::WrappingAdd>(x: I, y: I, flags: &mut Flags) -> I {
+fn add<I: Int + num_traits::ops::wrapping::WrappingAdd>(x: I, y: I, flags: &mut LazyFlags) -> I {
     addc(x, y, I::zero(), flags)
 }
 
-fn addc<I: Int + num_traits::ops::wrapping::WrappingAdd>(x: I, y: I, z: I, flags: &mut Flags) -> I {
-    // TODO "The CF, OF, SF, ZF, AF, and PF flags are set according to the result."
-    let y = y.wrapping_add(&z);
-    let result = x.wrapping_add(&y);
-    flags.set(Flags::CF, result < x || (y.is_zero() && !z.is_zero()));
-    flags.set(Flags::ZF, result.is_zero());
-    flags.set(Flags::SF, (result >> (I::bits() - 1)).is_one());
-    // Overflow is true exactly when the high (sign) bits are like:
-    //   x  y  result
-    //   0  0  1
-    //   1  1  0
-    let of = !(((x ^ !y) & (x ^ result)) >> (I::bits() - 1)).is_zero();
-    flags.set(Flags::OF, of);
+fn addc<I: Int + num_traits::ops::wrapping::WrappingAdd>(
+    x: I,
+    y: I,
+    z: I,
+    flags: &mut LazyFlags,
+) -> I {
+    let result = x.wrapping_add(&y).wrapping_add(&z);
+    #[cfg(feature = "check-flags")]
+    let mut eager = flags.get();
+    flags.record(
+        FlagOp::Add,
+        I::bits(),
+        x.as_u32(),
+        y.as_u32(),
+        result.as_u32(),
+        !z.is_zero(),
+    );
+    #[cfg(feature = "check-flags")]
+    {
+        let wide = x.as_u32() as u64 + y.as_u32() as u64 + z.as_u32() as u64;
+        eager.set(Flags::CF, wide >> I::bits() != 0);
+        eager.set(Flags::ZF, result.is_zero());
+        eager.set(Flags::SF, (result >> (I::bits() - 1)).is_one());
+        // Overflow is true exactly when the high (sign) bits are like:
+        //   x  y  result
+        //   0  0  1
+        //   1  1  0
+        let of = !(((x ^ !y) & (x ^ result)) >> (I::bits() - 1)).is_zero();
+        eager.set(Flags::OF, of);
+        eager.set(Flags::PF, parity(result.as_u32()));
+        eager.set(
+            Flags::AF,
+            (x.as_u32() & 0xF) + (y.as_u32() & 0xF) + z.as_u32() > 0xF,
+        );
+        flags.check(eager);
+    }
     result
 }
 
 pub fn add_r32_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_rm32(cpu, mem, instr);
-    let x = rm32(cpu, mem, instr);
+    let Some(y) = op1_rm32(cpu, mem, instr) else {
+        return;
+    };
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     x.set(add(x.get(), y, &mut cpu.flags));
 }
 
 pub fn add_rm32_r32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = cpu.regs.get32(instr.op1_register());
-    let x = rm32(cpu, mem, instr);
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     x.set(add(x.get(), y, &mut cpu.flags));
 }
 
 pub fn add_rm32_imm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = instr.immediate32();
-    let x = rm32(cpu, mem, instr);
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     x.set(add(x.get(), y, &mut cpu.flags));
 }
 
 pub fn add_rm32_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = instr.immediate8to32() as u32;
-    let x = rm32(cpu, mem, instr);
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     x.set(add(x.get(), y, &mut cpu.flags));
 }
 
 pub fn add_rm16_imm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = instr.immediate16();
-    let x = rm16(cpu, mem, instr);
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
     x.set(add(x.get(), y, &mut cpu.flags));
 }
 
 pub fn add_rm16_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = instr.immediate8to16() as u16;
-    let x = rm16(cpu, mem, instr);
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
     x.set(add(x.get(), y, &mut cpu.flags));
 }
 
 pub fn add_rm16_rm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_rm16(cpu, mem, instr);
-    let x = rm16(cpu, mem, instr);
+    let Some(y) = op1_rm16(cpu, mem, instr) else {
+        return;
+    };
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
     x.set(add(x.get(), y, &mut cpu.flags));
 }
 
 pub fn add_rm8_r8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = cpu.regs.get8(instr.op1_register());
-    let x = rm8(cpu, mem, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     x.set(add(x.get(), y, &mut cpu.flags));
 }
 
 pub fn add_rm8_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = instr.immediate8();
-    let x = rm8(cpu, mem, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     x.set(add(x.get(), y, &mut cpu.flags));
 }
 
 pub fn add_r8_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_rm8(cpu, mem, instr);
-    let x = rm8(cpu, mem, instr);
+    let Some(y) = op1_rm8(cpu, mem, instr) else {
+        return;
+    };
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     x.set(add(x.get(), y, &mut cpu.flags));
 }
 
+pub fn xadd_rm32_r32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = cpu.regs.get32(instr.op1_register());
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
+    if !cpu.state.is_running() {
+        return;
+    }
+    let old = x.get();
+    let sum = add(old, y, &mut cpu.flags);
+    cpu.regs.set32(instr.op1_register(), old);
+    x.set(sum);
+}
+
+pub fn xadd_rm16_r16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = cpu.regs.get16(instr.op1_register());
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
+    if !cpu.state.is_running() {
+        return;
+    }
+    let old = x.get();
+    let sum = add(old, y, &mut cpu.flags);
+    cpu.regs.set16(instr.op1_register(), old);
+    x.set(sum);
+}
+
+pub fn xadd_rm8_r8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = cpu.regs.get8(instr.op1_register());
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
+    if !cpu.state.is_running() {
+        return;
+    }
+    let old = x.get();
+    let sum = add(old, y, &mut cpu.flags);
+    cpu.regs.set8(instr.op1_register(), old);
+    x.set(sum);
+}
+
 pub fn adc_rm32_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_rm32(cpu, mem, instr);
+    let Some(y) = op1_rm32(cpu, mem, instr) else {
+        return;
+    };
     let carry = cpu.flags.contains(Flags::CF);
-    let x = rm32(cpu, mem, instr);
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     x.set(addc(x.get(), y, carry as u32, &mut cpu.flags));
 }
 
 pub fn adc_rm32_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = instr.immediate8() as u32;
     let carry = cpu.flags.contains(Flags::CF);
-    let x = rm32(cpu, mem, instr);
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     x.set(addc(x.get(), y, carry as u32, &mut cpu.flags));
 }
 
 pub fn adc_rm16_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = instr.immediate8() as u16;
     let carry = cpu.flags.contains(Flags::CF);
-    let x = rm16(cpu, mem, instr);
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
+    x.set(addc(x.get(), y, carry as u16, &mut cpu.flags));
+}
+
+pub fn adc_rm16_rm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = op1_rm16(cpu, mem, instr) else {
+        return;
+    };
+    let carry = cpu.flags.contains(Flags::CF);
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
     x.set(addc(x.get(), y, carry as u16, &mut cpu.flags));
 }
 
 pub fn adc_rm8_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_rm8(cpu, mem, instr);
+    let Some(y) = op1_rm8(cpu, mem, instr) else {
+        return;
+    };
     let carry = cpu.flags.contains(Flags::CF);
-    let x = rm8(cpu, mem, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     x.set(addc(x.get(), y, carry as u8, &mut cpu.flags));
 }
 
 pub fn adc_rm8_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = instr.immediate8();
     let carry = cpu.flags.contains(Flags::CF);
-    let x = rm8(cpu, mem, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     x.set(addc(x.get(), y, carry as u8, &mut cpu.flags));
 }
 
@@ -632,23 +926,38 @@ fn sbb<I: Int + num_traits::ops::overflowing::OverflowingSub + num_traits::Wrapp
     x: I,
     y: I,
     b: bool,
-    flags: &mut Flags,
+    flags: &mut LazyFlags,
 ) -> I {
-    let mut y = y;
-    if b {
-        y = y.wrapping_add(&I::one());
+    let (result, _carry) = x.overflowing_sub(&y);
+    let (result, _borrow) = result.overflowing_sub(&if b { I::one() } else { I::zero() });
+    #[cfg(feature = "check-flags")]
+    let mut eager = flags.get();
+    flags.record(
+        FlagOp::Sub,
+        I::bits(),
+        x.as_u32(),
+        y.as_u32(),
+        result.as_u32(),
+        b,
+    );
+    #[cfg(feature = "check-flags")]
+    {
+        eager.set(Flags::CF, _carry || _borrow);
+        eager.set(Flags::ZF, result.is_zero());
+        eager.set(Flags::SF, (result >> (I::bits() - 1)).is_one());
+        // Overflow is true exactly when the high (sign) bits are like:
+        //   x  y  result
+        //   0  1  1
+        //   1  0  0
+        let of = !(((x ^ y) & (x ^ result)) >> (I::bits() - 1)).is_zero();
+        eager.set(Flags::OF, of);
+        eager.set(Flags::PF, parity(result.as_u32()));
+        eager.set(
+            Flags::AF,
+            (x.as_u32() & 0xF) < (y.as_u32() & 0xF) + b as u32,
+        );
+        flags.check(eager);
     }
-    let (result, carry) = x.overflowing_sub(&y);
-    // TODO "The CF, OF, SF, ZF, AF, and PF flags are set according to the result."
-    flags.set(Flags::CF, carry || (b && y == I::zero()));
-    flags.set(Flags::ZF, result.is_zero());
-    flags.set(Flags::SF, (result >> (I::bits() - 1)).is_one());
-    // Overflow is true exactly when the high (sign) bits are like:
-    //   x  y  result
-    //   0  1  1
-    //   1  0  0
-    let of = !(((x ^ y) & (x ^ result)) >> (I::bits() - 1)).is_zero();
-    flags.set(Flags::OF, of);
     result
 }
 
@@ -658,104 +967,153 @@ pub(crate) fn sub<
 >(
     x: I,
     y: I,
-    flags: &mut Flags,
+    flags: &mut LazyFlags,
 ) -> I {
     sbb(x, y, false, flags)
 }
 
 pub fn sub_rm32_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = instr.immediate8to32() as u32;
-    let x = rm32(cpu, mem, instr);
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     x.set(sub(x.get(), y, &mut cpu.flags));
 }
 
 pub fn sub_rm32_imm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = instr.immediate32();
-    let x = rm32(cpu, mem, instr);
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     x.set(sub(x.get(), y, &mut cpu.flags));
 }
 
 pub fn sub_rm32_r32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = cpu.regs.get32(instr.op1_register());
-    let x = rm32(cpu, mem, instr);
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     x.set(sub(x.get(), y, &mut cpu.flags));
 }
 
 pub fn sub_r32_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_rm32(cpu, mem, instr);
-    let x = rm32(cpu, mem, instr);
+    let Some(y) = op1_rm32(cpu, mem, instr) else {
+        return;
+    };
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     x.set(sub(x.get(), y, &mut cpu.flags));
 }
 
 pub fn sub_rm16_rm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_rm16(cpu, mem, instr);
-    let x = rm16(cpu, mem, instr);
+    let Some(y) = op1_rm16(cpu, mem, instr) else {
+        return;
+    };
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
     x.set(sub(x.get(), y, &mut cpu.flags));
 }
 
 pub fn sub_rm16_imm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = instr.immediate16();
-    let x = rm16(cpu, mem, instr);
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
     x.set(sub(x.get(), y, &mut cpu.flags));
 }
 
 pub fn sub_rm8_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_rm8(cpu, mem, instr);
-    let x = rm8(cpu, mem, instr);
+    let Some(y) = op1_rm8(cpu, mem, instr) else {
+        return;
+    };
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     x.set(sub(x.get(), y, &mut cpu.flags));
 }
 
 pub fn sub_rm8_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = instr.immediate8();
-    let x = rm8(cpu, mem, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     x.set(sub(x.get(), y, &mut cpu.flags));
 }
 
 pub fn sbb_r32_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let carry = cpu.flags.contains(Flags::CF);
-    let y = op1_rm32(cpu, mem, instr);
-    let x = rm32(cpu, mem, instr);
+    let Some(y) = op1_rm32(cpu, mem, instr) else {
+        return;
+    };
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     x.set(sbb(x.get(), y, carry, &mut cpu.flags));
 }
 
 pub fn sbb_rm32_r32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let carry = cpu.flags.contains(Flags::CF);
     let y = cpu.regs.get32(instr.op1_register());
-    let x = rm32(cpu, mem, instr);
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     x.set(sbb(x.get(), y, carry, &mut cpu.flags));
 }
 
 pub fn sbb_rm32_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let carry = cpu.flags.contains(Flags::CF);
     let y = instr.immediate8to32() as u32;
-    let x = rm32(cpu, mem, instr);
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     x.set(sbb(x.get(), y, carry, &mut cpu.flags));
 }
 
 pub fn sbb_rm32_imm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let carry = cpu.flags.contains(Flags::CF);
     let y = instr.immediate32();
-    let x = rm32(cpu, mem, instr);
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
+    x.set(sbb(x.get(), y, carry, &mut cpu.flags));
+}
+
+pub fn sbb_rm16_rm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let carry = cpu.flags.contains(Flags::CF);
+    let Some(y) = op1_rm16(cpu, mem, instr) else {
+        return;
+    };
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
     x.set(sbb(x.get(), y, carry, &mut cpu.flags));
 }
 
 pub fn sbb_r8_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let carry = cpu.flags.contains(Flags::CF);
-    let y = op1_rm8(cpu, mem, instr);
-    let x = rm8(cpu, mem, instr);
+    let Some(y) = op1_rm8(cpu, mem, instr) else {
+        return;
+    };
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     x.set(sbb(x.get(), y, carry, &mut cpu.flags));
 }
 
 pub fn sbb_r8_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let carry = cpu.flags.contains(Flags::CF);
     let y = instr.immediate8();
-    let x = rm8(cpu, mem, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     x.set(sbb(x.get(), y, carry, &mut cpu.flags));
 }
 
 /// Shared impl of mul_rmXX.  The trick is to pass in a higher width int,
 /// e.g. x as u32 for the 16-bit mul, so there is enough space in the result.
-fn mul<I: Int>(x: I, y: I, flags: &mut Flags) -> I {
+fn mul<I: Int>(x: I, y: I, flags: &mut LazyFlags) -> I {
     let res = x.mul(y);
     let tophalf = res.shr(I::bits() / 2);
     flags.set(Flags::OF, !tophalf.is_zero());
@@ -764,212 +1122,442 @@ fn mul<I: Int>(x: I, y: I, flags: &mut Flags) -> I {
 }
 
 pub fn mul_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = rm32(cpu, mem, instr).get();
+    let Some(y) = rm32(cpu, mem, instr) else {
+        return;
+    };
+    let y = y.get();
     let x = cpu.regs.get32(Register::EAX);
     let res = mul(x as u64, y as u64, &mut cpu.flags);
     set_edx_eax(cpu, res);
 }
 
 pub fn mul_rm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = rm16(cpu, mem, instr).get();
+    let Some(y) = rm16(cpu, mem, instr) else {
+        return;
+    };
+    let y = y.get();
     let x = cpu.regs.get16(Register::AX);
     let res = mul(x as u32, y as u32, &mut cpu.flags);
     set_dx_ax(cpu, res);
 }
 
 pub fn mul_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = rm8(cpu, mem, instr).get();
+    let Some(y) = rm8(cpu, mem, instr) else {
+        return;
+    };
+    let y = y.get();
     let x = cpu.regs.get8(Register::AL);
     let res = mul(x as u16, y as u16, &mut cpu.flags);
     cpu.regs.set16(Register::AX, res);
 }
 
+/// Set the flags of imul: CF and OF are set when the product doesn't fit in the low
+/// half, sign extended.  The others are undefined.
+fn imul_flags(fits: bool, flags: &mut LazyFlags) {
+    flags.set(Flags::CF, !fits);
+    flags.set(Flags::OF, !fits);
+}
+
 pub fn imul_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let x = rm32(cpu, mem, instr).get() as i32;
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
+    let x = x.get() as i32;
     let y = cpu.regs.get32(Register::EAX) as i32;
-    let res = (x as i64).wrapping_mul(y as i64) as u64;
-    // TODO: flags.
-    set_edx_eax(cpu, res);
+    let res = (x as i64).wrapping_mul(y as i64);
+    imul_flags(res == res as i32 as i64, &mut cpu.flags);
+    set_edx_eax(cpu, res as u64);
 }
 
 pub fn imul_rm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let x = rm16(cpu, mem, instr).get() as i16;
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
+    let x = x.get() as i16;
     let y = cpu.regs.get16(Register::AX) as i16;
-    let res = (x as i32).wrapping_mul(y as i32) as u32;
-    // TODO: flags.
-    set_dx_ax(cpu, res);
+    let res = (x as i32).wrapping_mul(y as i32);
+    imul_flags(res == res as i16 as i32, &mut cpu.flags);
+    set_dx_ax(cpu, res as u32);
 }
 
 pub fn imul_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let x = rm8(cpu, mem, instr).get() as i8;
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
+    let x = x.get() as i8;
     let y = cpu.regs.get8(Register::AL) as i8;
-    let res = (x as i16).wrapping_mul(y as i16) as u16;
-    // TODO: flags.
-    cpu.regs.set16(Register::AX, res);
+    let res = (x as i16).wrapping_mul(y as i16);
+    imul_flags(res == res as i8 as i16, &mut cpu.flags);
+    cpu.regs.set16(Register::AX, res as u16);
 }
 
-fn imul_trunc(x: i32, y: i32, _flags: &mut Flags) -> i32 {
-    // TODO: flags.
-    x.wrapping_mul(y)
+fn imul_trunc(x: i32, y: i32, flags: &mut LazyFlags) -> i32 {
+    let res = (x as i64) * (y as i64);
+    imul_flags(res == res as i32 as i64, flags);
+    res as i32
 }
 
 pub fn imul_r32_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let x = cpu.regs.get32(instr.op0_register()) as i32;
-    let y = op1_rm32(cpu, mem, instr) as i32;
+    let Some(y) = op1_rm32(cpu, mem, instr) else {
+        return;
+    };
+    let y = y as i32;
     let value = imul_trunc(x, y, &mut cpu.flags);
     cpu.regs.set32(instr.op0_register(), value as u32);
 }
 
 pub fn imul_r32_rm32_imm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let x = op1_rm32(cpu, mem, instr) as i32;
+    let Some(x) = op1_rm32(cpu, mem, instr) else {
+        return;
+    };
+    let x = x as i32;
     let y = instr.immediate32() as i32;
     let value = imul_trunc(x, y, &mut cpu.flags);
     cpu.regs.set32(instr.op0_register(), value as u32);
 }
 
 pub fn imul_r32_rm32_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let x = op1_rm32(cpu, mem, instr) as i32;
+    let Some(x) = op1_rm32(cpu, mem, instr) else {
+        return;
+    };
+    let x = x as i32;
     let y = instr.immediate8to32();
     let value = imul_trunc(x, y, &mut cpu.flags);
     cpu.regs.set32(instr.op0_register(), value as u32);
 }
 
+/// Whether a divide can go ahead, given None for a zero divisor or else whether the
+/// quotient fits, raising #DE if it can't.  A fault reading the divisor also stops it.
+fn divide_ok(cpu: &mut CPU, quotient_fits: Option<bool>) -> bool {
+    if !cpu.state.is_running() {
+        return false;
+    }
+    match quotient_fits {
+        Some(true) => true,
+        fits => {
+            let overflow = fits.is_some();
+            cpu.fault(Fault::DivideError { overflow });
+            false
+        }
+    }
+}
+
 pub fn idiv_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let x = get_edx_eax(cpu) as i64;
-    let y = rm32(cpu, mem, instr).get() as i32 as i64;
-    cpu.regs.set32(Register::EAX, (x / y) as i32 as u32);
+    let Some(y) = rm32(cpu, mem, instr) else {
+        return;
+    };
+    let y = y.get() as i32 as i64;
+    let quotient = x.checked_div(y);
+    if !divide_ok(
+        cpu,
+        (y != 0).then(|| quotient.is_some_and(|q| i32::try_from(q).is_ok())),
+    ) {
+        return;
+    }
+    cpu.regs.set32(Register::EAX, quotient.unwrap() as u32);
     cpu.regs.set32(Register::EDX, (x % y) as i32 as u32);
 }
 
 pub fn idiv_rm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let x = get_dx_ax(cpu) as i32;
-    let y = rm16(cpu, mem, instr).get() as i16 as i32;
-    let quotient = x / y;
-    if quotient > 0x7FFF || quotient < -0x8000 {
-        panic!("divide error");
+    let Some(y) = rm16(cpu, mem, instr) else {
+        return;
+    };
+    let y = y.get() as i16 as i32;
+    let quotient = x.checked_div(y);
+    if !divide_ok(
+        cpu,
+        (y != 0).then(|| quotient.is_some_and(|q| i16::try_from(q).is_ok())),
+    ) {
+        return;
     }
-    cpu.regs.set16(Register::AX, quotient as i16 as u16);
+    cpu.regs.set16(Register::AX, quotient.unwrap() as u16);
     cpu.regs.set16(Register::DX, (x % y) as u16);
 }
 
 pub fn idiv_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let x = cpu.regs.get16(Register::AX) as i16;
-    let y = rm8(cpu, mem, instr).get() as i8 as i16;
-    let quotient = x / y;
-    if quotient > 0x7F || quotient < -0x80 {
-        panic!("divide error");
+    let Some(y) = rm8(cpu, mem, instr) else {
+        return;
+    };
+    let y = y.get() as i8 as i16;
+    let quotient = x.checked_div(y);
+    if !divide_ok(
+        cpu,
+        (y != 0).then(|| quotient.is_some_and(|q| i8::try_from(q).is_ok())),
+    ) {
+        return;
     }
     let rem = x % y;
-    cpu.regs
-        .set16(Register::AX, ((rem << 8) as u16) | (quotient as i8 as u16));
+    cpu.regs.set16(
+        Register::AX,
+        ((rem << 8) as u16) | (quotient.unwrap() as i8 as u8 as u16),
+    );
 }
 
 pub fn div_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let x = get_edx_eax(cpu);
-    let y = rm32(cpu, mem, instr).get() as u64;
-    cpu.regs.set32(Register::EAX, (x / y) as u32);
+    let Some(y) = rm32(cpu, mem, instr) else {
+        return;
+    };
+    let y = y.get() as u64;
+    let quotient = x.checked_div(y);
+    if !divide_ok(cpu, quotient.map(|q| q <= u32::MAX as u64)) {
+        return;
+    }
+    cpu.regs.set32(Register::EAX, quotient.unwrap() as u32);
     cpu.regs.set32(Register::EDX, (x % y) as u32);
     // No flags.
 }
 
 pub fn div_rm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let x = get_dx_ax(cpu);
-    let y = rm16(cpu, mem, instr).get() as u32;
-    cpu.regs.set32(Register::EAX, ((x / y) as u16) as u32);
-    cpu.regs.set32(Register::EDX, ((x % y) as u16) as u32);
+    let Some(y) = rm16(cpu, mem, instr) else {
+        return;
+    };
+    let y = y.get() as u32;
+    let quotient = x.checked_div(y);
+    if !divide_ok(cpu, quotient.map(|q| q <= u16::MAX as u32)) {
+        return;
+    }
+    cpu.regs.set16(Register::AX, quotient.unwrap() as u16);
+    cpu.regs.set16(Register::DX, (x % y) as u16);
     // No flags.
 }
 
 pub fn div_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let x = cpu.regs.get16(Register::AX);
-    let y = rm8(cpu, mem, instr).get() as u16;
+    let Some(y) = rm8(cpu, mem, instr) else {
+        return;
+    };
+    let y = y.get() as u16;
+    let quotient = x.checked_div(y);
+    if !divide_ok(cpu, quotient.map(|q| q <= u8::MAX as u16)) {
+        return;
+    }
     cpu.regs
-        .set32(Register::EAX, (((x % y) as u32) << 16) | ((x / y) as u32));
+        .set16(Register::AX, ((x % y) << 8) | quotient.unwrap());
     // No flags.
 }
 
-fn dec<I: Int + num_traits::WrappingSub>(x: I, flags: &mut Flags) -> I {
+/// Record the flags of inc/dec, which leave CF alone; y is 1 or -1.
+fn inc_dec_flags<I: Int>(x: I, y: I, result: I, flags: &mut LazyFlags) {
+    #[cfg(feature = "check-flags")]
+    let mut eager = flags.get();
+    flags.record(
+        FlagOp::IncDec,
+        I::bits(),
+        x.as_u32(),
+        y.as_u32(),
+        result.as_u32(),
+        false,
+    );
+    #[cfg(feature = "check-flags")]
+    {
+        // Overflow is going from the largest positive value to the smallest negative
+        // one, or the reverse.
+        let sign = |v: I| (v >> (I::bits() - 1)).is_one();
+        eager.set(
+            Flags::OF,
+            sign(x) != sign(result) && sign(y) != sign(result),
+        );
+        eager.set(Flags::SF, sign(result));
+        eager.set(Flags::ZF, result.is_zero());
+        eager.set(Flags::PF, parity(result.as_u32()));
+        eager.set(Flags::AF, (x.as_u32() ^ result.as_u32()) & 0x10 != 0);
+        flags.check(eager);
+    }
+}
+
+fn dec<I: Int + num_traits::WrappingSub>(x: I, flags: &mut LazyFlags) -> I {
     // Note this is not sub(1) because CF should be preserved.
     let result = x.wrapping_sub(&I::one());
-    flags.set(Flags::OF, result.is_zero());
-    flags.set(Flags::SF, (result >> (I::bits() - 1)).is_one());
-    flags.set(Flags::ZF, result.is_zero());
+    inc_dec_flags(x, !I::zero(), result, flags);
     result
 }
 
 pub fn dec_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let x = rm32(cpu, mem, instr);
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     x.set(dec(x.get(), &mut cpu.flags));
 }
 
 pub fn dec_rm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let x = rm16(cpu, mem, instr);
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
     x.set(dec(x.get(), &mut cpu.flags));
 }
 
 pub fn dec_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let x = rm8(cpu, mem, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     x.set(dec(x.get(), &mut cpu.flags));
 }
 
-fn inc<I: Int + num_traits::WrappingAdd>(x: I, flags: &mut Flags) -> I {
+fn inc<I: Int + num_traits::WrappingAdd>(x: I, flags: &mut LazyFlags) -> I {
     // Note this is not add(1) because CF should be preserved.
     let result = x.wrapping_add(&I::one());
-    flags.set(Flags::OF, result.is_zero());
-    flags.set(Flags::SF, (result >> (I::bits() - 1)).is_one());
-    flags.set(Flags::ZF, result.is_zero());
+    inc_dec_flags(x, I::one(), result, flags);
     result
 }
 
 pub fn inc_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let x = rm32(cpu, mem, instr);
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     x.set(inc(x.get(), &mut cpu.flags));
 }
 
 pub fn inc_rm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let x = rm16(cpu, mem, instr);
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
     x.set(inc(x.get(), &mut cpu.flags));
 }
 
 pub fn inc_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let x = rm8(cpu, mem, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     x.set(inc(x.get(), &mut cpu.flags));
 }
 
-fn neg<I: Int + OverflowingSub>(x: I, flags: &mut Flags) -> I {
-    let (res, of) = I::zero().overflowing_sub(&x);
-    flags.set(Flags::ZF, res.is_zero());
-    flags.set(Flags::CF, !res.is_zero());
-    flags.set(Flags::OF, of);
-    res
+fn neg<I: Int + OverflowingSub + num_traits::WrappingAdd>(x: I, flags: &mut LazyFlags) -> I {
+    // The flags are exactly those of 0 - x.
+    sub(I::zero(), x, flags)
 }
 
 pub fn neg_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let x = rm32(cpu, mem, instr);
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     x.set(neg(x.get(), &mut cpu.flags));
 }
 
 pub fn neg_rm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let x = rm16(cpu, mem, instr);
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
     x.set(neg(x.get(), &mut cpu.flags));
 }
 
 pub fn neg_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let x = rm8(cpu, mem, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     x.set(neg(x.get(), &mut cpu.flags));
 }
 
 pub fn not_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let x = rm32(cpu, mem, instr);
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     x.set(!x.get())
 }
 
 pub fn not_rm16(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let x = rm16(cpu, mem, instr);
+    let Some(x) = rm16(cpu, mem, instr) else {
+        return;
+    };
     x.set(!x.get())
 }
 
 pub fn not_rm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let x = rm8(cpu, mem, instr);
+    let Some(x) = rm8(cpu, mem, instr) else {
+        return;
+    };
     x.set(!x.get())
 }
+
+/// Set ZF, SF and PF from the AL left by a BCD adjustment.
+fn bcd_flags(cpu: &mut CPU, al: u8) {
+    cpu.flags.set(Flags::ZF, al == 0);
+    cpu.flags.set(Flags::SF, al & 0x80 != 0);
+    cpu.flags.set(Flags::PF, al.count_ones() & 1 == 0);
+}
+
+pub fn aaa(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
+    let mut ax = cpu.regs.get16(Register::AX);
+    let adjust = (ax & 0xF) > 9 || cpu.flags.contains(Flags::AF);
+    if adjust {
+        ax = ax.wrapping_add(0x106);
+    }
+    cpu.regs.set16(Register::AX, ax & 0xFF0F);
+    cpu.flags.set(Flags::AF | Flags::CF, adjust);
+}
+
+pub fn aas(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
+    let mut ax = cpu.regs.get16(Register::AX);
+    let adjust = (ax & 0xF) > 9 || cpu.flags.contains(Flags::AF);
+    if adjust {
+        ax = ax.wrapping_sub(6).wrapping_sub(0x100);
+    }
+    cpu.regs.set16(Register::AX, ax & 0xFF0F);
+    cpu.flags.set(Flags::AF | Flags::CF, adjust);
+}
+
+pub fn daa(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
+    let old_al = cpu.regs.get8(Register::AL);
+    let old_cf = cpu.flags.contains(Flags::CF);
+    let mut al = old_al;
+    let af = (al & 0xF) > 9 || cpu.flags.contains(Flags::AF);
+    if af {
+        al = al.wrapping_add(6);
+    }
+    let cf = old_al > 0x99 || old_cf;
+    if cf {
+        al = al.wrapping_add(0x60);
+    }
+    cpu.regs.set8(Register::AL, al);
+    cpu.flags.set(Flags::AF, af);
+    cpu.flags.set(Flags::CF, cf);
+    bcd_flags(cpu, al);
+}
+
+pub fn das(cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
+    let old_al = cpu.regs.get8(Register::AL);
+    let old_cf = cpu.flags.contains(Flags::CF);
+    let mut al = old_al;
+    let af = (al & 0xF) > 9 || cpu.flags.contains(Flags::AF);
+    let mut cf = false;
+    if af {
+        cf = old_cf || al < 6;
+        al = al.wrapping_sub(6);
+    }
+    if old_al > 0x99 || old_cf {
+        al = al.wrapping_sub(0x60);
+        cf = true;
+    }
+    cpu.regs.set8(Register::AL, al);
+    cpu.flags.set(Flags::AF, af);
+    cpu.flags.set(Flags::CF, cf);
+    bcd_flags(cpu, al);
+}
+
+pub fn aam_imm8(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+    let base = instr.immediate8();
+    if base == 0 {
+        cpu.fault(Fault::DivideError { overflow: false });
+        return;
+    }
+    let al = cpu.regs.get8(Register::AL);
+    cpu.regs.set8(Register::AH, al / base);
+    cpu.regs.set8(Register::AL, al % base);
+    bcd_flags(cpu, al % base);
+}
+
+pub fn aad_imm8(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+    let base = instr.immediate8();
+    let ah = cpu.regs.get8(Register::AH);
+    let al = cpu
+        .regs
+        .get8(Register::AL)
+        .wrapping_add(ah.wrapping_mul(base));
+    cpu.regs.set16(Register::AX, al as u16);
+    bcd_flags(cpu, al);
+}
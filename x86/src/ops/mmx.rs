@@ -1,20 +1,20 @@
 use super::helpers::*;
 use crate::CPU;
 use iced_x86::Instruction;
-use memory::{Extensions, Mem};
+use memory::Mem;
 
-fn op1_mmm64(cpu: &mut CPU, mem: Mem, instr: &iced_x86::Instruction) -> u64 {
+fn op1_mmm64(cpu: &mut CPU, mem: Mem, instr: &iced_x86::Instruction) -> Option<u64> {
     match instr.op1_kind() {
-        iced_x86::OpKind::Register => cpu.regs.get64(instr.op1_register()),
-        iced_x86::OpKind::Memory => mem.get_pod::<u64>(x86_addr(cpu, instr)),
+        iced_x86::OpKind::Register => Some(cpu.regs.get64(instr.op1_register())),
+        iced_x86::OpKind::Memory => op1_mem(cpu, mem, instr),
         _ => unreachable!(),
     }
 }
 
-fn op1_mmm32(cpu: &mut CPU, mem: Mem, instr: &iced_x86::Instruction) -> u32 {
+fn op1_mmm32(cpu: &mut CPU, mem: Mem, instr: &iced_x86::Instruction) -> Option<u32> {
     match instr.op1_kind() {
-        iced_x86::OpKind::Register => cpu.regs.get64(instr.op1_register()) as u32,
-        iced_x86::OpKind::Memory => mem.get_pod::<u32>(x86_addr(cpu, instr)),
+        iced_x86::OpKind::Register => Some(cpu.regs.get64(instr.op1_register()) as u32),
+        iced_x86::OpKind::Memory => op1_mem(cpu, mem, instr),
         _ => unreachable!(),
     }
 }
@@ -23,7 +23,7 @@ fn op1_mmm32(cpu: &mut CPU, mem: Mem, instr: &iced_x86::Instruction) -> u32 {
 // array, and reassembling the output all gets inlined by the compiler into the same code
 // as doing it with a bunch of bitshift expressions.
 
-trait Unpack<T> {
+pub trait Unpack<T> {
     fn unpack(self) -> T;
 }
 
@@ -75,7 +75,7 @@ impl Unpack<[u8; 4]> for u32 {
     }
 }
 
-trait Pack {
+pub trait Pack {
     type Target;
     fn pack(self) -> Self::Target;
 }
@@ -119,28 +119,39 @@ impl Pack for [u8; 4] {
 }
 
 pub fn pxor_mm_mmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_mmm64(cpu, mem, instr);
+    let Some(y) = op1_mmm64(cpu, mem, instr) else {
+        return;
+    };
     rm64_x(cpu, mem, instr, |_cpu, x| x ^ y);
 }
 
 pub fn movq_mmm64_mmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_mmm64(cpu, mem, instr);
+    let Some(y) = op1_mmm64(cpu, mem, instr) else {
+        return;
+    };
     rm64_x(cpu, mem, instr, |_cpu, _x| y);
 }
 
 pub fn movd_mm_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_rm32(cpu, mem, instr) as u64; // zero extend
+    let Some(y) = op1_rm32(cpu, mem, instr) else {
+        return;
+    };
+    let y = y as u64; // zero extend
     rm64_x(cpu, mem, instr, |_cpu, _x| y);
 }
 
 pub fn movd_rm32_mm(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
     let y = cpu.regs.get64(instr.op1_register()) as u32;
-    let x = rm32(cpu, mem, instr);
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
     x.set(y);
 }
 
 pub fn punpcklwd_mm_mmm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_mmm32(cpu, mem, instr);
+    let Some(y) = op1_mmm32(cpu, mem, instr) else {
+        return;
+    };
     rm64_x(cpu, mem, instr, |_cpu, x| {
         let x: [u16; 2] = (x as u32).unpack(); // instr only uses low 32 bits of x
         let y: [u16; 2] = y.unpack();
@@ -149,7 +160,9 @@ pub fn punpcklwd_mm_mmm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 }
 
 pub fn punpcklbw_mm_mmm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_mmm32(cpu, mem, instr);
+    let Some(y) = op1_mmm32(cpu, mem, instr) else {
+        return;
+    };
     rm64_x(cpu, mem, instr, |_cpu, x| {
         let x: [u8; 4] = (x as u32).unpack(); // instr only uses low 32 bits of x
         let y: [u8; 4] = y.unpack();
@@ -158,7 +171,9 @@ pub fn punpcklbw_mm_mmm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 }
 
 pub fn pmullw_mm_mmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_mmm64(cpu, mem, instr);
+    let Some(y) = op1_mmm64(cpu, mem, instr) else {
+        return;
+    };
     rm64_x(cpu, mem, instr, |_cpu, x| {
         let x: [i16; 4] = x.unpack();
         let y: [i16; 4] = y.unpack();
@@ -172,7 +187,9 @@ pub fn pmullw_mm_mmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 }
 
 pub fn pmulhw_mm_mmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_mmm64(cpu, mem, instr);
+    let Some(y) = op1_mmm64(cpu, mem, instr) else {
+        return;
+    };
     rm64_x(cpu, mem, instr, |_cpu, x| {
         let x: [i16; 4] = x.unpack();
         let y: [i16; 4] = y.unpack();
@@ -227,7 +244,9 @@ pub fn packuswb_mm_mmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
             x as u8
         }
     }
-    let y = op1_mmm64(cpu, mem, instr);
+    let Some(y) = op1_mmm64(cpu, mem, instr) else {
+        return;
+    };
     rm64_x(cpu, mem, instr, |_cpu, x| {
         let x: [i16; 4] = x.unpack();
         let y: [i16; 4] = y.unpack();
@@ -243,7 +262,9 @@ pub fn emms(_cpu: &mut CPU, _mem: Mem, _instr: &Instruction) {
 }
 
 pub fn psubusb_mm_mmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_mmm64(cpu, mem, instr);
+    let Some(y) = op1_mmm64(cpu, mem, instr) else {
+        return;
+    };
     rm64_x(cpu, mem, instr, |_cpu, x| {
         let x: [u8; 8] = x.unpack();
         let y: [u8; 8] = y.unpack();
@@ -253,7 +274,9 @@ pub fn psubusb_mm_mmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 }
 
 pub fn paddusb_mm_mmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_mmm64(cpu, mem, instr);
+    let Some(y) = op1_mmm64(cpu, mem, instr) else {
+        return;
+    };
     rm64_x(cpu, mem, instr, |_cpu, x| {
         let x: [u8; 8] = x.unpack();
         let y: [u8; 8] = y.unpack();
@@ -272,7 +295,9 @@ pub fn psllw_mm_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 }
 
 pub fn paddsb_mm_mmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_mmm64(cpu, mem, instr);
+    let Some(y) = op1_mmm64(cpu, mem, instr) else {
+        return;
+    };
     rm64_x(cpu, mem, instr, |_cpu, x| {
         let x: [u8; 8] = x.unpack();
         let y: [u8; 8] = y.unpack();
@@ -282,7 +307,9 @@ pub fn paddsb_mm_mmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 }
 
 pub fn paddw_mm_mmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_mmm64(cpu, mem, instr);
+    let Some(y) = op1_mmm64(cpu, mem, instr) else {
+        return;
+    };
     rm64_x(cpu, mem, instr, |_cpu, x| {
         let x: [u16; 4] = x.unpack();
         let y: [u16; 4] = y.unpack();
@@ -292,7 +319,9 @@ pub fn paddw_mm_mmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 }
 
 pub fn paddd_mm_mmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_mmm64(cpu, mem, instr);
+    let Some(y) = op1_mmm64(cpu, mem, instr) else {
+        return;
+    };
     rm64_x(cpu, mem, instr, |_cpu, x| {
         let x: [u32; 2] = x.unpack();
         let y: [u32; 2] = y.unpack();
@@ -302,7 +331,9 @@ pub fn paddd_mm_mmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 }
 
 pub fn paddsw_mm_mmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_mmm64(cpu, mem, instr);
+    let Some(y) = op1_mmm64(cpu, mem, instr) else {
+        return;
+    };
     rm64_x(cpu, mem, instr, |_cpu, x| {
         let x: [i16; 4] = x.unpack();
         let y: [i16; 4] = y.unpack();
@@ -312,7 +343,9 @@ pub fn paddsw_mm_mmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 }
 
 pub fn pmaddwd_mm_mmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_mmm64(cpu, mem, instr);
+    let Some(y) = op1_mmm64(cpu, mem, instr) else {
+        return;
+    };
     rm64_x(cpu, mem, instr, |_cpu, x| {
         let x: [i16; 4] = x.unpack();
         let y: [i16; 4] = y.unpack();
@@ -323,7 +356,9 @@ pub fn pmaddwd_mm_mmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 }
 
 pub fn psubw_mm_mmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_mmm64(cpu, mem, instr);
+    let Some(y) = op1_mmm64(cpu, mem, instr) else {
+        return;
+    };
     rm64_x(cpu, mem, instr, |_cpu, x| {
         let x: [u16; 4] = x.unpack();
         let y: [u16; 4] = y.unpack();
@@ -333,7 +368,9 @@ pub fn psubw_mm_mmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 }
 
 pub fn pcmpeqb_mm_mmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
-    let y = op1_mmm64(cpu, mem, instr);
+    let Some(y) = op1_mmm64(cpu, mem, instr) else {
+        return;
+    };
     rm64_x(cpu, mem, instr, |_cpu, x| {
         let x: [u8; 8] = x.unpack();
         let y: [u8; 8] = y.unpack();
@@ -1,13 +1,18 @@
 mod basic;
+#[cfg(test)]
+mod conformance;
 mod control;
 mod cpuid;
 mod fpu;
 mod helpers;
 mod math;
 mod mmx;
+mod sse;
 mod string;
 mod table;
 mod test;
 
+pub use control::{invalid, unimplemented};
+pub use cpuid::{CpuProfile, EDXFeatures};
 pub use helpers::{pop, push, set_edx_eax};
 pub use table::{decode, Op};
@@ -0,0 +1,1261 @@
+//! SSE and SSE2: XMM registers holding packed floats or integers.
+//!
+//! Alignment faults for MOVAPS etc. aren't modeled; misaligned accesses just work.
+
+use super::helpers::*;
+use super::mmx::{Pack, Unpack};
+use crate::{registers::Flags, CPU};
+use iced_x86::Instruction;
+use memory::Mem;
+use std::cmp::Ordering;
+
+fn op1_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) -> Option<u128> {
+    match instr.op1_kind() {
+        iced_x86::OpKind::Register => Some(cpu.regs.get128(instr.op1_register())),
+        iced_x86::OpKind::Memory => op1_mem(cpu, mem, instr),
+        _ => unreachable!(),
+    }
+}
+
+fn op1_xmmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) -> Option<u64> {
+    match instr.op1_kind() {
+        iced_x86::OpKind::Register => Some(cpu.regs.get128(instr.op1_register()) as u64),
+        iced_x86::OpKind::Memory => op1_mem(cpu, mem, instr),
+        _ => unreachable!(),
+    }
+}
+
+fn op1_xmmm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) -> Option<u32> {
+    match instr.op1_kind() {
+        iced_x86::OpKind::Register => Some(cpu.regs.get128(instr.op1_register()) as u32),
+        iced_x86::OpKind::Memory => op1_mem(cpu, mem, instr),
+        _ => unreachable!(),
+    }
+}
+
+/// Replace the op0 xmm register with op(its value).
+fn xmm_x(cpu: &mut CPU, instr: &Instruction, op: impl FnOnce(u128) -> u128) {
+    let reg = instr.op0_register();
+    let x = cpu.regs.get128(reg);
+    cpu.regs.set128(reg, op(x));
+}
+
+/// Write an xmm/m128 destination operand.
+fn set_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction, value: u128) {
+    match instr.op0_kind() {
+        iced_x86::OpKind::Register => cpu.regs.set128(instr.op0_register(), value),
+        iced_x86::OpKind::Memory => write_pod(cpu, mem, x86_addr(cpu, instr), value),
+        _ => unreachable!(),
+    }
+}
+
+macro_rules! lanes128 {
+    ($t:ty, $n:literal) => {
+        impl Unpack<[$t; $n]> for u128 {
+            fn unpack(self) -> [$t; $n] {
+                const SIZE: usize = 16 / $n;
+                let bytes = self.to_le_bytes();
+                std::array::from_fn(|i| {
+                    <$t>::from_le_bytes(bytes[i * SIZE..][..SIZE].try_into().unwrap())
+                })
+            }
+        }
+
+        impl Pack for [$t; $n] {
+            type Target = u128;
+            fn pack(self) -> u128 {
+                const SIZE: usize = 16 / $n;
+                let mut bytes = [0u8; 16];
+                for (i, lane) in self.iter().enumerate() {
+                    bytes[i * SIZE..][..SIZE].copy_from_slice(&lane.to_le_bytes());
+                }
+                u128::from_le_bytes(bytes)
+            }
+        }
+    };
+}
+lanes128!(u8, 16);
+lanes128!(i8, 16);
+lanes128!(u16, 8);
+lanes128!(i16, 8);
+lanes128!(u32, 4);
+lanes128!(i32, 4);
+lanes128!(u64, 2);
+lanes128!(i64, 2);
+lanes128!(f32, 4);
+lanes128!(f64, 2);
+
+/// Apply op to each pair of lanes of the op0 register and op1.
+fn lanes<T: Copy, const N: usize>(
+    cpu: &mut CPU,
+    mem: Mem,
+    instr: &Instruction,
+    op: impl Fn(T, T) -> T,
+) where
+    u128: Unpack<[T; N]>,
+    [T; N]: Pack<Target = u128>,
+{
+    let Some(y) = op1_xmmm128(cpu, mem, instr) else {
+        return;
+    };
+    let y: [T; N] = y.unpack();
+    xmm_x(cpu, instr, |x| {
+        let x: [T; N] = x.unpack();
+        std::array::from_fn(|i| op(x[i], y[i])).pack()
+    });
+}
+
+const MXCSR_DAZ: u32 = 1 << 6;
+const MXCSR_FTZ: u32 = 1 << 15;
+
+trait Float: Copy {
+    fn is_subnormal(self) -> bool;
+    fn zero_like(self) -> Self;
+}
+
+impl Float for f32 {
+    fn is_subnormal(self) -> bool {
+        f32::is_subnormal(self)
+    }
+    fn zero_like(self) -> Self {
+        0f32.copysign(self)
+    }
+}
+
+impl Float for f64 {
+    fn is_subnormal(self) -> bool {
+        f64::is_subnormal(self)
+    }
+    fn zero_like(self) -> Self {
+        0f64.copysign(self)
+    }
+}
+
+/// Wrap a float op to honor MXCSR's denormals-are-zero and flush-to-zero modes.
+fn flushing<T: Float>(mxcsr: u32, op: impl Fn(T, T) -> T) -> impl Fn(T, T) -> T {
+    let daz = move |x: T| {
+        if mxcsr & MXCSR_DAZ != 0 && x.is_subnormal() {
+            x.zero_like()
+        } else {
+            x
+        }
+    };
+    move |x, y| {
+        let z = op(daz(x), daz(y));
+        if mxcsr & MXCSR_FTZ != 0 && z.is_subnormal() {
+            z.zero_like()
+        } else {
+            z
+        }
+    }
+}
+
+fn packed_ps(cpu: &mut CPU, mem: Mem, instr: &Instruction, op: impl Fn(f32, f32) -> f32) {
+    lanes::<f32, 4>(cpu, mem, instr, flushing(cpu.mxcsr, op));
+}
+
+fn packed_pd(cpu: &mut CPU, mem: Mem, instr: &Instruction, op: impl Fn(f64, f64) -> f64) {
+    lanes::<f64, 2>(cpu, mem, instr, flushing(cpu.mxcsr, op));
+}
+
+/// Apply op to the low float of op0 and op1, leaving the upper lanes of op0 alone.
+fn scalar_ss(cpu: &mut CPU, mem: Mem, instr: &Instruction, op: impl Fn(f32, f32) -> f32) {
+    let op = flushing(cpu.mxcsr, op);
+    let Some(y) = op1_xmmm32(cpu, mem, instr) else {
+        return;
+    };
+    let y = f32::from_bits(y);
+    xmm_x(cpu, instr, |x| {
+        let z = op(f32::from_bits(x as u32), y);
+        (x & !0xFFFF_FFFF) | z.to_bits() as u128
+    });
+}
+
+fn scalar_sd(cpu: &mut CPU, mem: Mem, instr: &Instruction, op: impl Fn(f64, f64) -> f64) {
+    let op = flushing(cpu.mxcsr, op);
+    let Some(y) = op1_xmmm64(cpu, mem, instr) else {
+        return;
+    };
+    let y = f64::from_bits(y);
+    xmm_x(cpu, instr, |x| {
+        let z = op(f64::from_bits(x as u64), y);
+        (x & !(u64::MAX as u128)) | z.to_bits() as u128
+    });
+}
+
+// MIN and MAX return the second operand if either is NaN or both are zero,
+// which is what these comparisons naturally do.
+
+fn min<T: PartialOrd>(x: T, y: T) -> T {
+    if x < y {
+        x
+    } else {
+        y
+    }
+}
+
+fn max<T: PartialOrd>(x: T, y: T) -> T {
+    if x > y {
+        x
+    } else {
+        y
+    }
+}
+
+// Moves.
+
+pub fn movaps_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = op1_xmmm128(cpu, mem, instr) else {
+        return;
+    };
+    cpu.regs.set128(instr.op0_register(), y);
+}
+
+pub fn movaps_xmmm128_xmm(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = cpu.regs.get128(instr.op1_register());
+    set_xmmm128(cpu, mem, instr, y);
+}
+
+pub fn movss_xmm_xmmm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = op1_xmmm32(cpu, mem, instr) else {
+        return;
+    };
+    let y = y as u128;
+    match instr.op1_kind() {
+        // Loads from memory clear the upper lanes.
+        iced_x86::OpKind::Memory => cpu.regs.set128(instr.op0_register(), y),
+        _ => xmm_x(cpu, instr, |x| (x & !0xFFFF_FFFF) | y),
+    }
+}
+
+pub fn movss_xmmm32_xmm(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = cpu.regs.get128(instr.op1_register()) as u32;
+    match instr.op0_kind() {
+        iced_x86::OpKind::Memory => write_pod(cpu, mem, x86_addr(cpu, instr), y),
+        _ => xmm_x(cpu, instr, |x| (x & !0xFFFF_FFFF) | y as u128),
+    }
+}
+
+pub fn movsd_xmm_xmmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = op1_xmmm64(cpu, mem, instr) else {
+        return;
+    };
+    let y = y as u128;
+    match instr.op1_kind() {
+        iced_x86::OpKind::Memory => cpu.regs.set128(instr.op0_register(), y),
+        _ => xmm_x(cpu, instr, |x| (x & !(u64::MAX as u128)) | y),
+    }
+}
+
+pub fn movsd_xmmm64_xmm(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = cpu.regs.get128(instr.op1_register()) as u64;
+    match instr.op0_kind() {
+        iced_x86::OpKind::Memory => write_pod(cpu, mem, x86_addr(cpu, instr), y),
+        _ => xmm_x(cpu, instr, |x| (x & !(u64::MAX as u128)) | y as u128),
+    }
+}
+
+pub fn movd_xmm_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = op1_rm32(cpu, mem, instr) else {
+        return;
+    };
+    let y = y as u128; // zero extend
+    cpu.regs.set128(instr.op0_register(), y);
+}
+
+pub fn movd_rm32_xmm(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = cpu.regs.get128(instr.op1_register()) as u32;
+    let Some(x) = rm32(cpu, mem, instr) else {
+        return;
+    };
+    x.set(y);
+}
+
+pub fn movq_xmm_xmmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = op1_xmmm64(cpu, mem, instr) else {
+        return;
+    };
+    let y = y as u128; // zero extend
+    cpu.regs.set128(instr.op0_register(), y);
+}
+
+pub fn movq_xmmm64_xmm(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = cpu.regs.get128(instr.op1_register()) as u64;
+    match instr.op0_kind() {
+        iced_x86::OpKind::Memory => write_pod(cpu, mem, x86_addr(cpu, instr), y),
+        _ => cpu.regs.set128(instr.op0_register(), y as u128),
+    }
+}
+
+pub fn movq2dq_xmm_mm(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+    let y = cpu.regs.get64(instr.op1_register()) as u128;
+    cpu.regs.set128(instr.op0_register(), y);
+}
+
+pub fn movdq2q_mm_xmm(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+    let y = cpu.regs.get128(instr.op1_register()) as u64;
+    cpu.regs.set64(instr.op0_register(), y);
+}
+
+/// MOVLPS/MOVLPD from memory, replacing the low quadword.
+pub fn movlps_xmm_m64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = op1_xmmm64(cpu, mem, instr) else {
+        return;
+    };
+    let y = y as u128;
+    xmm_x(cpu, instr, |x| (x & !(u64::MAX as u128)) | y);
+}
+
+pub fn movlps_m64_xmm(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = cpu.regs.get128(instr.op1_register()) as u64;
+    write_pod(cpu, mem, x86_addr(cpu, instr), y);
+}
+
+/// MOVHPS/MOVHPD from memory, replacing the high quadword.
+pub fn movhps_xmm_m64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = op1_xmmm64(cpu, mem, instr) else {
+        return;
+    };
+    let y = y as u128;
+    xmm_x(cpu, instr, |x| (x & u64::MAX as u128) | (y << 64));
+}
+
+pub fn movhps_m64_xmm(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = (cpu.regs.get128(instr.op1_register()) >> 64) as u64;
+    write_pod(cpu, mem, x86_addr(cpu, instr), y);
+}
+
+pub fn movhlps_xmm_xmm(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+    let y = cpu.regs.get128(instr.op1_register()) >> 64;
+    xmm_x(cpu, instr, |x| (x & !(u64::MAX as u128)) | y);
+}
+
+pub fn movlhps_xmm_xmm(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+    let y = cpu.regs.get128(instr.op1_register()) as u64 as u128;
+    xmm_x(cpu, instr, |x| (x & u64::MAX as u128) | (y << 64));
+}
+
+pub fn movmskps_r32_xmm(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+    let y: [u32; 4] = cpu.regs.get128(instr.op1_register()).unpack();
+    let mask = y
+        .iter()
+        .enumerate()
+        .fold(0, |mask, (i, &lane)| mask | ((lane >> 31) << i));
+    cpu.regs.set32(instr.op0_register(), mask);
+}
+
+pub fn movmskpd_r32_xmm(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+    let y: [u64; 2] = cpu.regs.get128(instr.op1_register()).unpack();
+    let mask = ((y[0] >> 63) | ((y[1] >> 63) << 1)) as u32;
+    cpu.regs.set32(instr.op0_register(), mask);
+}
+
+pub fn pmovmskb_r32_xmm(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+    let y: [u8; 16] = cpu.regs.get128(instr.op1_register()).unpack();
+    let mask = y
+        .iter()
+        .enumerate()
+        .fold(0, |mask, (i, &lane)| mask | (((lane >> 7) as u32) << i));
+    cpu.regs.set32(instr.op0_register(), mask);
+}
+
+// Floating point arithmetic.
+
+pub fn addps_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed_ps(cpu, mem, instr, |x, y| x + y);
+}
+
+pub fn addss_xmm_xmmm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    scalar_ss(cpu, mem, instr, |x, y| x + y);
+}
+
+pub fn addpd_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed_pd(cpu, mem, instr, |x, y| x + y);
+}
+
+pub fn addsd_xmm_xmmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    scalar_sd(cpu, mem, instr, |x, y| x + y);
+}
+
+pub fn subps_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed_ps(cpu, mem, instr, |x, y| x - y);
+}
+
+pub fn subss_xmm_xmmm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    scalar_ss(cpu, mem, instr, |x, y| x - y);
+}
+
+pub fn subpd_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed_pd(cpu, mem, instr, |x, y| x - y);
+}
+
+pub fn subsd_xmm_xmmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    scalar_sd(cpu, mem, instr, |x, y| x - y);
+}
+
+pub fn mulps_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed_ps(cpu, mem, instr, |x, y| x * y);
+}
+
+pub fn mulss_xmm_xmmm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    scalar_ss(cpu, mem, instr, |x, y| x * y);
+}
+
+pub fn mulpd_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed_pd(cpu, mem, instr, |x, y| x * y);
+}
+
+pub fn mulsd_xmm_xmmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    scalar_sd(cpu, mem, instr, |x, y| x * y);
+}
+
+pub fn divps_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed_ps(cpu, mem, instr, |x, y| x / y);
+}
+
+pub fn divss_xmm_xmmm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    scalar_ss(cpu, mem, instr, |x, y| x / y);
+}
+
+pub fn divpd_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed_pd(cpu, mem, instr, |x, y| x / y);
+}
+
+pub fn divsd_xmm_xmmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    scalar_sd(cpu, mem, instr, |x, y| x / y);
+}
+
+pub fn minps_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed_ps(cpu, mem, instr, min);
+}
+
+pub fn minss_xmm_xmmm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    scalar_ss(cpu, mem, instr, min);
+}
+
+pub fn minpd_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed_pd(cpu, mem, instr, min);
+}
+
+pub fn minsd_xmm_xmmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    scalar_sd(cpu, mem, instr, min);
+}
+
+pub fn maxps_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed_ps(cpu, mem, instr, max);
+}
+
+pub fn maxss_xmm_xmmm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    scalar_ss(cpu, mem, instr, max);
+}
+
+pub fn maxpd_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed_pd(cpu, mem, instr, max);
+}
+
+pub fn maxsd_xmm_xmmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    scalar_sd(cpu, mem, instr, max);
+}
+
+pub fn sqrtps_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed_ps(cpu, mem, instr, |_, y| y.sqrt());
+}
+
+pub fn sqrtss_xmm_xmmm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    scalar_ss(cpu, mem, instr, |_, y| y.sqrt());
+}
+
+pub fn sqrtpd_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed_pd(cpu, mem, instr, |_, y| y.sqrt());
+}
+
+pub fn sqrtsd_xmm_xmmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    scalar_sd(cpu, mem, instr, |_, y| y.sqrt());
+}
+
+// The reciprocal approximations are only specified to 12 bits, so exact results are fine.
+
+pub fn rcpps_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed_ps(cpu, mem, instr, |_, y| 1.0 / y);
+}
+
+pub fn rcpss_xmm_xmmm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    scalar_ss(cpu, mem, instr, |_, y| 1.0 / y);
+}
+
+pub fn rsqrtps_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    packed_ps(cpu, mem, instr, |_, y| 1.0 / y.sqrt());
+}
+
+pub fn rsqrtss_xmm_xmmm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    scalar_ss(cpu, mem, instr, |_, y| 1.0 / y.sqrt());
+}
+
+// Bitwise logic, shared by the ps/pd/integer variants which differ only in their domain.
+
+pub fn andps_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = op1_xmmm128(cpu, mem, instr) else {
+        return;
+    };
+    xmm_x(cpu, instr, |x| x & y);
+}
+
+pub fn andnps_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = op1_xmmm128(cpu, mem, instr) else {
+        return;
+    };
+    xmm_x(cpu, instr, |x| !x & y);
+}
+
+pub fn orps_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = op1_xmmm128(cpu, mem, instr) else {
+        return;
+    };
+    xmm_x(cpu, instr, |x| x | y);
+}
+
+pub fn xorps_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = op1_xmmm128(cpu, mem, instr) else {
+        return;
+    };
+    xmm_x(cpu, instr, |x| x ^ y);
+}
+
+// Comparisons.
+
+/// Evaluate a CMPPS etc. predicate, given as the instruction's immediate.
+fn cmp_predicate<T: PartialOrd>(x: T, y: T, imm: u8) -> bool {
+    match imm & 7 {
+        0 => x == y,
+        1 => x < y,
+        2 => x <= y,
+        3 => x.partial_cmp(&y).is_none(),
+        4 => x != y,
+        5 => !matches!(x.partial_cmp(&y), Some(Ordering::Less)),
+        6 => !matches!(x.partial_cmp(&y), Some(Ordering::Less | Ordering::Equal)),
+        7 => x.partial_cmp(&y).is_some(),
+        _ => unreachable!(),
+    }
+}
+
+pub fn cmpps_xmm_xmmm128_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let imm = instr.immediate8();
+    lanes::<u32, 4>(cpu, mem, instr, |x, y| {
+        if cmp_predicate(f32::from_bits(x), f32::from_bits(y), imm) {
+            u32::MAX
+        } else {
+            0
+        }
+    });
+}
+
+pub fn cmppd_xmm_xmmm128_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let imm = instr.immediate8();
+    lanes::<u64, 2>(cpu, mem, instr, |x, y| {
+        if cmp_predicate(f64::from_bits(x), f64::from_bits(y), imm) {
+            u64::MAX
+        } else {
+            0
+        }
+    });
+}
+
+pub fn cmpss_xmm_xmmm32_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let imm = instr.immediate8();
+    let Some(y) = op1_xmmm32(cpu, mem, instr) else {
+        return;
+    };
+    let y = f32::from_bits(y);
+    xmm_x(cpu, instr, |x| {
+        let z = if cmp_predicate(f32::from_bits(x as u32), y, imm) {
+            u32::MAX
+        } else {
+            0
+        };
+        (x & !0xFFFF_FFFF) | z as u128
+    });
+}
+
+pub fn cmpsd_xmm_xmmm64_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let imm = instr.immediate8();
+    let Some(y) = op1_xmmm64(cpu, mem, instr) else {
+        return;
+    };
+    let y = f64::from_bits(y);
+    xmm_x(cpu, instr, |x| {
+        let z = if cmp_predicate(f64::from_bits(x as u64), y, imm) {
+            u64::MAX
+        } else {
+            0
+        };
+        (x & !(u64::MAX as u128)) | z as u128
+    });
+}
+
+/// Set flags for COMISS etc., like FCOMI does.
+fn comis<T: PartialOrd>(cpu: &mut CPU, x: T, y: T) {
+    let (zf, pf, cf) = match x.partial_cmp(&y) {
+        Some(Ordering::Greater) => (false, false, false),
+        Some(Ordering::Less) => (false, false, true),
+        Some(Ordering::Equal) => (true, false, false),
+        None => (true, true, true),
+    };
+    cpu.flags.set(Flags::ZF, zf);
+    cpu.flags.set(Flags::PF, pf);
+    cpu.flags.set(Flags::CF, cf);
+    cpu.flags.set(Flags::OF, false);
+    cpu.flags.set(Flags::SF, false);
+}
+
+/// COMISS and UCOMISS, which differ only in exceptions on QNaN.
+pub fn comiss_xmm_xmmm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let x = f32::from_bits(cpu.regs.get128(instr.op0_register()) as u32);
+    let Some(y) = op1_xmmm32(cpu, mem, instr) else {
+        return;
+    };
+    let y = f32::from_bits(y);
+    comis(cpu, x, y);
+}
+
+/// COMISD and UCOMISD.
+pub fn comisd_xmm_xmmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let x = f64::from_bits(cpu.regs.get128(instr.op0_register()) as u64);
+    let Some(y) = op1_xmmm64(cpu, mem, instr) else {
+        return;
+    };
+    let y = f64::from_bits(y);
+    comis(cpu, x, y);
+}
+
+// Shuffles.
+
+pub fn shufps_xmm_xmmm128_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let imm = instr.immediate8() as usize;
+    let Some(y) = op1_xmmm128(cpu, mem, instr) else {
+        return;
+    };
+    let y: [u32; 4] = y.unpack();
+    xmm_x(cpu, instr, |x| {
+        let x: [u32; 4] = x.unpack();
+        [
+            x[imm & 3],
+            x[(imm >> 2) & 3],
+            y[(imm >> 4) & 3],
+            y[(imm >> 6) & 3],
+        ]
+        .pack()
+    });
+}
+
+pub fn shufpd_xmm_xmmm128_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let imm = instr.immediate8() as usize;
+    let Some(y) = op1_xmmm128(cpu, mem, instr) else {
+        return;
+    };
+    let y: [u64; 2] = y.unpack();
+    xmm_x(cpu, instr, |x| {
+        let x: [u64; 2] = x.unpack();
+        [x[imm & 1], y[(imm >> 1) & 1]].pack()
+    });
+}
+
+pub fn pshufd_xmm_xmmm128_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let imm = instr.immediate8() as usize;
+    let Some(y) = op1_xmmm128(cpu, mem, instr) else {
+        return;
+    };
+    let y: [u32; 4] = y.unpack();
+    let z: [u32; 4] = std::array::from_fn(|i| y[(imm >> (i * 2)) & 3]);
+    cpu.regs.set128(instr.op0_register(), z.pack());
+}
+
+pub fn pshuflw_xmm_xmmm128_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let imm = instr.immediate8() as usize;
+    let Some(y) = op1_xmmm128(cpu, mem, instr) else {
+        return;
+    };
+    let y: [u16; 8] = y.unpack();
+    let z: [u16; 8] = std::array::from_fn(|i| match i {
+        0..=3 => y[(imm >> (i * 2)) & 3],
+        _ => y[i],
+    });
+    cpu.regs.set128(instr.op0_register(), z.pack());
+}
+
+pub fn pshufhw_xmm_xmmm128_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let imm = instr.immediate8() as usize;
+    let Some(y) = op1_xmmm128(cpu, mem, instr) else {
+        return;
+    };
+    let y: [u16; 8] = y.unpack();
+    let z: [u16; 8] = std::array::from_fn(|i| match i {
+        0..=3 => y[i],
+        _ => y[4 + ((imm >> ((i - 4) * 2)) & 3)],
+    });
+    cpu.regs.set128(instr.op0_register(), z.pack());
+}
+
+/// Interleave lanes from the low (high=false) or high halves of op0 and op1.
+fn interleave<T: Copy, const N: usize>(cpu: &mut CPU, mem: Mem, instr: &Instruction, high: bool)
+where
+    u128: Unpack<[T; N]>,
+    [T; N]: Pack<Target = u128>,
+{
+    let base = if high { N / 2 } else { 0 };
+    let Some(y) = op1_xmmm128(cpu, mem, instr) else {
+        return;
+    };
+    let y: [T; N] = y.unpack();
+    xmm_x(cpu, instr, |x| {
+        let x: [T; N] = x.unpack();
+        std::array::from_fn(|i| {
+            let src = if i % 2 == 0 { &x } else { &y };
+            src[base + i / 2]
+        })
+        .pack()
+    });
+}
+
+pub fn unpcklps_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    interleave::<u32, 4>(cpu, mem, instr, false);
+}
+
+pub fn unpckhps_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    interleave::<u32, 4>(cpu, mem, instr, true);
+}
+
+pub fn unpcklpd_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    interleave::<u64, 2>(cpu, mem, instr, false);
+}
+
+pub fn unpckhpd_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    interleave::<u64, 2>(cpu, mem, instr, true);
+}
+
+pub fn punpcklbw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    interleave::<u8, 16>(cpu, mem, instr, false);
+}
+
+pub fn punpcklwd_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    interleave::<u16, 8>(cpu, mem, instr, false);
+}
+
+pub fn punpckhbw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    interleave::<u8, 16>(cpu, mem, instr, true);
+}
+
+pub fn punpckhwd_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    interleave::<u16, 8>(cpu, mem, instr, true);
+}
+
+// Conversions.
+
+/// Round to an integer according to MXCSR's rounding control.
+fn round_mxcsr(cpu: &CPU, x: f64) -> f64 {
+    match (cpu.mxcsr >> 13) & 3 {
+        0 => x.round_ties_even(),
+        1 => x.floor(),
+        2 => x.ceil(),
+        _ => x.trunc(),
+    }
+}
+
+/// Convert an already-rounded float to i32, giving the "integer indefinite" value
+/// 0x8000_0000 if it's out of range.
+fn to_i32(x: f64) -> u32 {
+    if x.is_nan() || x < i32::MIN as f64 || x > i32::MAX as f64 {
+        0x8000_0000
+    } else {
+        x as i32 as u32
+    }
+}
+
+pub fn cvtsi2ss_xmm_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = op1_rm32(cpu, mem, instr) else {
+        return;
+    };
+    let y = y as i32 as f32;
+    xmm_x(cpu, instr, |x| (x & !0xFFFF_FFFF) | y.to_bits() as u128);
+}
+
+pub fn cvtsi2sd_xmm_rm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = op1_rm32(cpu, mem, instr) else {
+        return;
+    };
+    let y = y as i32 as f64;
+    xmm_x(cpu, instr, |x| {
+        (x & !(u64::MAX as u128)) | y.to_bits() as u128
+    });
+}
+
+pub fn cvtss2si_r32_xmmm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = op1_xmmm32(cpu, mem, instr) else {
+        return;
+    };
+    let y = f32::from_bits(y) as f64;
+    let value = to_i32(round_mxcsr(cpu, y));
+    cpu.regs.set32(instr.op0_register(), value);
+}
+
+pub fn cvttss2si_r32_xmmm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = op1_xmmm32(cpu, mem, instr) else {
+        return;
+    };
+    let y = f32::from_bits(y) as f64;
+    cpu.regs.set32(instr.op0_register(), to_i32(y.trunc()));
+}
+
+pub fn cvtsd2si_r32_xmmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = op1_xmmm64(cpu, mem, instr) else {
+        return;
+    };
+    let y = f64::from_bits(y);
+    let value = to_i32(round_mxcsr(cpu, y));
+    cpu.regs.set32(instr.op0_register(), value);
+}
+
+pub fn cvttsd2si_r32_xmmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = op1_xmmm64(cpu, mem, instr) else {
+        return;
+    };
+    let y = f64::from_bits(y);
+    cpu.regs.set32(instr.op0_register(), to_i32(y.trunc()));
+}
+
+pub fn cvtss2sd_xmm_xmmm32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = op1_xmmm32(cpu, mem, instr) else {
+        return;
+    };
+    let y = f32::from_bits(y) as f64;
+    xmm_x(cpu, instr, |x| {
+        (x & !(u64::MAX as u128)) | y.to_bits() as u128
+    });
+}
+
+pub fn cvtsd2ss_xmm_xmmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = op1_xmmm64(cpu, mem, instr) else {
+        return;
+    };
+    let y = f64::from_bits(y) as f32;
+    xmm_x(cpu, instr, |x| (x & !0xFFFF_FFFF) | y.to_bits() as u128);
+}
+
+pub fn cvtps2pd_xmm_xmmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = op1_xmmm64(cpu, mem, instr) else {
+        return;
+    };
+    let y: [u32; 2] = y.unpack();
+    let z = [f32::from_bits(y[0]) as f64, f32::from_bits(y[1]) as f64];
+    cpu.regs.set128(instr.op0_register(), z.pack());
+}
+
+pub fn cvtpd2ps_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = op1_xmmm128(cpu, mem, instr) else {
+        return;
+    };
+    let y: [f64; 2] = y.unpack();
+    let z = [y[0] as f32, y[1] as f32, 0.0, 0.0];
+    cpu.regs.set128(instr.op0_register(), z.pack());
+}
+
+pub fn cvtdq2ps_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = op1_xmmm128(cpu, mem, instr) else {
+        return;
+    };
+    let y: [i32; 4] = y.unpack();
+    let z = y.map(|y| y as f32);
+    cpu.regs.set128(instr.op0_register(), z.pack());
+}
+
+pub fn cvtps2dq_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = op1_xmmm128(cpu, mem, instr) else {
+        return;
+    };
+    let y: [f32; 4] = y.unpack();
+    let z = y.map(|y| to_i32(round_mxcsr(cpu, y as f64)));
+    cpu.regs.set128(instr.op0_register(), z.pack());
+}
+
+pub fn cvttps2dq_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = op1_xmmm128(cpu, mem, instr) else {
+        return;
+    };
+    let y: [f32; 4] = y.unpack();
+    let z = y.map(|y| to_i32((y as f64).trunc()));
+    cpu.regs.set128(instr.op0_register(), z.pack());
+}
+
+pub fn cvtdq2pd_xmm_xmmm64(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = op1_xmmm64(cpu, mem, instr) else {
+        return;
+    };
+    let y: [i32; 2] = y.unpack();
+    let z = y.map(|y| y as f64);
+    cpu.regs.set128(instr.op0_register(), z.pack());
+}
+
+pub fn cvtpd2dq_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = op1_xmmm128(cpu, mem, instr) else {
+        return;
+    };
+    let y: [f64; 2] = y.unpack();
+    let z = [
+        to_i32(round_mxcsr(cpu, y[0])),
+        to_i32(round_mxcsr(cpu, y[1])),
+        0,
+        0,
+    ];
+    cpu.regs.set128(instr.op0_register(), z.pack());
+}
+
+pub fn cvttpd2dq_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = op1_xmmm128(cpu, mem, instr) else {
+        return;
+    };
+    let y: [f64; 2] = y.unpack();
+    let z = [to_i32(y[0].trunc()), to_i32(y[1].trunc()), 0, 0];
+    cpu.regs.set128(instr.op0_register(), z.pack());
+}
+
+// SSE2 integer ops.
+
+pub fn paddb_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    lanes::<u8, 16>(cpu, mem, instr, u8::wrapping_add);
+}
+
+pub fn paddw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    lanes::<u16, 8>(cpu, mem, instr, u16::wrapping_add);
+}
+
+pub fn paddd_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    lanes::<u32, 4>(cpu, mem, instr, u32::wrapping_add);
+}
+
+pub fn paddq_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    lanes::<u64, 2>(cpu, mem, instr, u64::wrapping_add);
+}
+
+pub fn psubb_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    lanes::<u8, 16>(cpu, mem, instr, u8::wrapping_sub);
+}
+
+pub fn psubw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    lanes::<u16, 8>(cpu, mem, instr, u16::wrapping_sub);
+}
+
+pub fn psubd_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    lanes::<u32, 4>(cpu, mem, instr, u32::wrapping_sub);
+}
+
+pub fn psubq_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    lanes::<u64, 2>(cpu, mem, instr, u64::wrapping_sub);
+}
+
+pub fn paddusb_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    lanes::<u8, 16>(cpu, mem, instr, u8::saturating_add);
+}
+
+pub fn paddusw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    lanes::<u16, 8>(cpu, mem, instr, u16::saturating_add);
+}
+
+pub fn paddsb_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    lanes::<i8, 16>(cpu, mem, instr, i8::saturating_add);
+}
+
+pub fn paddsw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    lanes::<i16, 8>(cpu, mem, instr, i16::saturating_add);
+}
+
+pub fn psubusb_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    lanes::<u8, 16>(cpu, mem, instr, u8::saturating_sub);
+}
+
+pub fn psubusw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    lanes::<u16, 8>(cpu, mem, instr, u16::saturating_sub);
+}
+
+pub fn psubsb_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    lanes::<i8, 16>(cpu, mem, instr, i8::saturating_sub);
+}
+
+pub fn psubsw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    lanes::<i16, 8>(cpu, mem, instr, i16::saturating_sub);
+}
+
+pub fn pmullw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    lanes::<u16, 8>(cpu, mem, instr, u16::wrapping_mul);
+}
+
+pub fn pmulhw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    lanes::<i16, 8>(cpu, mem, instr, |x, y| ((x as i32 * y as i32) >> 16) as i16);
+}
+
+pub fn pmulhuw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    lanes::<u16, 8>(cpu, mem, instr, |x, y| ((x as u32 * y as u32) >> 16) as u16);
+}
+
+pub fn pmuludq_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    lanes::<u64, 2>(cpu, mem, instr, |x, y| {
+        (x as u32 as u64) * (y as u32 as u64)
+    });
+}
+
+pub fn pmaddwd_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = op1_xmmm128(cpu, mem, instr) else {
+        return;
+    };
+    let y: [i16; 8] = y.unpack();
+    xmm_x(cpu, instr, |x| {
+        let x: [i16; 8] = x.unpack();
+        let z: [u32; 4] = std::array::from_fn(|i| {
+            let lo = x[i * 2] as i32 * y[i * 2] as i32;
+            let hi = x[i * 2 + 1] as i32 * y[i * 2 + 1] as i32;
+            lo.wrapping_add(hi) as u32
+        });
+        z.pack()
+    });
+}
+
+pub fn pavgb_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    lanes::<u8, 16>(cpu, mem, instr, |x, y| {
+        ((x as u16 + y as u16 + 1) >> 1) as u8
+    });
+}
+
+pub fn pavgw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    lanes::<u16, 8>(cpu, mem, instr, |x, y| {
+        ((x as u32 + y as u32 + 1) >> 1) as u16
+    });
+}
+
+pub fn pminub_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    lanes::<u8, 16>(cpu, mem, instr, u8::min);
+}
+
+pub fn pmaxub_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    lanes::<u8, 16>(cpu, mem, instr, u8::max);
+}
+
+pub fn pminsw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    lanes::<i16, 8>(cpu, mem, instr, i16::min);
+}
+
+pub fn pmaxsw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    lanes::<i16, 8>(cpu, mem, instr, i16::max);
+}
+
+pub fn psadbw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = op1_xmmm128(cpu, mem, instr) else {
+        return;
+    };
+    let y: [u8; 16] = y.unpack();
+    xmm_x(cpu, instr, |x| {
+        let x: [u8; 16] = x.unpack();
+        let z: [u64; 2] = std::array::from_fn(|half| {
+            (0..8)
+                .map(|i| x[half * 8 + i].abs_diff(y[half * 8 + i]) as u64)
+                .sum()
+        });
+        z.pack()
+    });
+}
+
+pub fn pcmpeqb_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    lanes::<u8, 16>(cpu, mem, instr, |x, y| if x == y { u8::MAX } else { 0 });
+}
+
+pub fn pcmpeqw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    lanes::<u16, 8>(cpu, mem, instr, |x, y| if x == y { u16::MAX } else { 0 });
+}
+
+pub fn pcmpeqd_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    lanes::<u32, 4>(cpu, mem, instr, |x, y| if x == y { u32::MAX } else { 0 });
+}
+
+pub fn pcmpgtb_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    lanes::<i8, 16>(cpu, mem, instr, |x, y| if x > y { -1 } else { 0 });
+}
+
+pub fn pcmpgtw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    lanes::<i16, 8>(cpu, mem, instr, |x, y| if x > y { -1 } else { 0 });
+}
+
+pub fn pcmpgtd_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    lanes::<i32, 4>(cpu, mem, instr, |x, y| if x > y { -1 } else { 0 });
+}
+
+pub fn punpckldq_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    interleave::<u32, 4>(cpu, mem, instr, false);
+}
+
+pub fn punpckhdq_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    interleave::<u32, 4>(cpu, mem, instr, true);
+}
+
+pub fn punpcklqdq_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    interleave::<u64, 2>(cpu, mem, instr, false);
+}
+
+pub fn punpckhqdq_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    interleave::<u64, 2>(cpu, mem, instr, true);
+}
+
+pub fn packsswb_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = op1_xmmm128(cpu, mem, instr) else {
+        return;
+    };
+    let y: [i16; 8] = y.unpack();
+    xmm_x(cpu, instr, |x| {
+        let x: [i16; 8] = x.unpack();
+        let z: [i8; 16] = std::array::from_fn(|i| {
+            let v = if i < 8 { x[i] } else { y[i - 8] };
+            v.clamp(i8::MIN as i16, i8::MAX as i16) as i8
+        });
+        z.pack()
+    });
+}
+
+pub fn packuswb_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = op1_xmmm128(cpu, mem, instr) else {
+        return;
+    };
+    let y: [i16; 8] = y.unpack();
+    xmm_x(cpu, instr, |x| {
+        let x: [i16; 8] = x.unpack();
+        let z: [u8; 16] = std::array::from_fn(|i| {
+            let v = if i < 8 { x[i] } else { y[i - 8] };
+            v.clamp(0, u8::MAX as i16) as u8
+        });
+        z.pack()
+    });
+}
+
+pub fn packssdw_xmm_xmmm128(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let Some(y) = op1_xmmm128(cpu, mem, instr) else {
+        return;
+    };
+    let y: [i32; 4] = y.unpack();
+    xmm_x(cpu, instr, |x| {
+        let x: [i32; 4] = x.unpack();
+        let z: [i16; 8] = std::array::from_fn(|i| {
+            let v = if i < 4 { x[i] } else { y[i - 4] };
+            v.clamp(i16::MIN as i32, i16::MAX as i32) as i16
+        });
+        z.pack()
+    });
+}
+
+pub fn pextrw_r32_xmm_imm8(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+    let y: [u16; 8] = cpu.regs.get128(instr.op1_register()).unpack();
+    let value = y[instr.immediate8() as usize & 7] as u32;
+    cpu.regs.set32(instr.op0_register(), value);
+}
+
+pub fn pinsrw_xmm_r32m16_imm8(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let y = match instr.op1_kind() {
+        iced_x86::OpKind::Register => cpu.regs.get32(instr.op1_register()) as u16,
+        _ => match op1_mem(cpu, mem, instr) {
+            Some(y) => y,
+            None => return,
+        },
+    };
+    let index = instr.immediate8() as usize & 7;
+    xmm_x(cpu, instr, |x| {
+        let mut x: [u16; 8] = x.unpack();
+        x[index] = y;
+        x.pack()
+    });
+}
+
+// Shifts, by an immediate or by the low quadword of op1.
+
+/// Shift each lane of op0 by count, with logical shifts of too many bits giving zero
+/// and arithmetic ones filling with the sign.
+fn shift<T: Copy, const N: usize>(
+    cpu: &mut CPU,
+    instr: &Instruction,
+    count: u64,
+    op: fn(T, u32) -> T,
+) where
+    u128: Unpack<[T; N]>,
+    [T; N]: Pack<Target = u128>,
+{
+    let bits = (128 / N) as u64;
+    let count = count.min(bits) as u32;
+    xmm_x(cpu, instr, |x| {
+        let x: [T; N] = x.unpack();
+        x.map(|x| op(x, count)).pack()
+    });
+}
+
+fn shift_count(cpu: &mut CPU, mem: Mem, instr: &Instruction) -> Option<u64> {
+    match instr.op1_kind() {
+        iced_x86::OpKind::Immediate8 => Some(instr.immediate8() as u64),
+        _ => op1_xmmm128(cpu, mem, instr).map(|x| x as u64),
+    }
+}
+
+macro_rules! shift_ops {
+    ($name_imm:ident, $name_xmm:ident, $t:ty, $n:literal, $op:expr) => {
+        pub fn $name_imm(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+            let Some(count) = shift_count(cpu, mem, instr) else {
+                return;
+            };
+            shift::<$t, $n>(cpu, instr, count, $op);
+        }
+
+        pub fn $name_xmm(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+            let Some(count) = shift_count(cpu, mem, instr) else {
+                return;
+            };
+            shift::<$t, $n>(cpu, instr, count, $op);
+        }
+    };
+}
+
+shift_ops!(psllw_xmm_imm8, psllw_xmm_xmmm128, u16, 8, |x, n| x
+    .checked_shl(n)
+    .unwrap_or(0));
+shift_ops!(pslld_xmm_imm8, pslld_xmm_xmmm128, u32, 4, |x, n| x
+    .checked_shl(n)
+    .unwrap_or(0));
+shift_ops!(psllq_xmm_imm8, psllq_xmm_xmmm128, u64, 2, |x, n| x
+    .checked_shl(n)
+    .unwrap_or(0));
+shift_ops!(psrlw_xmm_imm8, psrlw_xmm_xmmm128, u16, 8, |x, n| x
+    .checked_shr(n)
+    .unwrap_or(0));
+shift_ops!(psrld_xmm_imm8, psrld_xmm_xmmm128, u32, 4, |x, n| x
+    .checked_shr(n)
+    .unwrap_or(0));
+shift_ops!(psrlq_xmm_imm8, psrlq_xmm_xmmm128, u64, 2, |x, n| x
+    .checked_shr(n)
+    .unwrap_or(0));
+shift_ops!(psraw_xmm_imm8, psraw_xmm_xmmm128, i16, 8, |x, n| x
+    >> n.min(15));
+shift_ops!(psrad_xmm_imm8, psrad_xmm_xmmm128, i32, 4, |x, n| x
+    >> n.min(31));
+
+pub fn pslldq_xmm_imm8(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+    let bytes = instr.immediate8() as u32;
+    xmm_x(cpu, instr, |x| x.checked_shl(bytes * 8).unwrap_or(0));
+}
+
+pub fn psrldq_xmm_imm8(cpu: &mut CPU, _mem: Mem, instr: &Instruction) {
+    let bytes = instr.immediate8() as u32;
+    xmm_x(cpu, instr, |x| x.checked_shr(bytes * 8).unwrap_or(0));
+}
+
+// Control and cache management.
+
+pub fn ldmxcsr_m32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    if let Some(value) = read_operand::<u32>(cpu, mem, instr) {
+        cpu.mxcsr = value;
+    }
+}
+
+pub fn stmxcsr_m32(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
+    let value = cpu.mxcsr;
+    write_pod(cpu, mem, x86_addr(cpu, instr), value);
+}
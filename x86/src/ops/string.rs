@@ -1,9 +1,18 @@
 //! Ops that tend to loop with 'rep' prefix, e.g. movs, stos.
-
-use super::math::sub;
+//!
+//! The common rep loops, which are how CRT memcpy/memset/memchr/memcmp are written,
+//! are run as a single bulk operation on the host when that gives the same result as
+//! looping, and fall back to looping an instruction at a time otherwise.
+
+use super::{
+    helpers::{read_pod, write_traps},
+    math::sub,
+};
 use crate::{registers::Flags, x86::CPU};
 use iced_x86::{Instruction, Register};
-use memory::{Extensions, ExtensionsMut, Mem};
+use memory::{
+    simd, Extensions, ExtensionsMut, Mem, PAGE_TRAPS_RECORD, PAGE_TRAP_GUARD, PAGE_TRAP_READ,
+};
 
 /// Width of an operation, e.g. movsb/w/d.
 #[derive(Clone, Copy)]
@@ -51,6 +60,10 @@ impl Rep {
 fn rep(cpu: &mut CPU, mem: Mem, rep: Rep, size: Size, func: impl Fn(&mut CPU, Mem, Size)) {
     while cpu.regs.get32(Register::ECX) > 0 {
         func(cpu, mem, size);
+        if !cpu.state.is_running() {
+            // Faulted, leaving the registers at the iteration to resume from.
+            break;
+        }
         *cpu.regs.get32_mut(Register::ECX) -= 1;
         match rep {
             Rep::REPE if !cpu.flags.contains(Flags::ZF) => break,
@@ -60,23 +73,104 @@ fn rep(cpu: &mut CPU, mem: Mem, rep: Rep, size: Size, func: impl Fn(&mut CPU, Me
     }
 }
 
+/// The memory [start, start+len) covered by count elements of size starting at addr and
+/// stepping in the direction of DF, if it's all within bounds.
+fn span(cpu: &CPU, mem: Mem, addr: u32, count: u32, size: Size) -> Option<(u32, u32)> {
+    let len = count.checked_mul(size as u32)?;
+    let start = if cpu.flags.contains(Flags::DF) {
+        addr.checked_add(size as u32)?.checked_sub(len)?
+    } else {
+        addr
+    };
+    if start as u64 + len as u64 > mem.len() as u64 {
+        return None;
+    }
+    Some((start, len))
+}
+
+/// Whether a bulk write to [start, start+len) can go ahead.  Writes that would fault
+/// are left to the looping path, which faults at the right iteration.
+fn bulk_write(cpu: &mut CPU, mem: Mem, start: u32, len: u32) -> bool {
+    let traps = mem.page_traps_span(start, len);
+    if traps & !PAGE_TRAPS_RECORD != 0 {
+        return false;
+    }
+    if traps != 0 {
+        cpu.record_write(start, len);
+    }
+    true
+}
+
+/// Whether a bulk read of [start, start+len) can go ahead, as with bulk_write.
+fn bulk_read(mem: Mem, start: u32, len: u32) -> bool {
+    mem.page_traps_span(start, len) & (PAGE_TRAP_READ | PAGE_TRAP_GUARD) == 0
+}
+
+/// Advance a string register by count elements in the direction of DF.
+fn advance(cpu: &mut CPU, reg: Register, len: u32) {
+    let value = cpu.regs.get32(reg);
+    let value = if cpu.flags.contains(Flags::DF) {
+        value.wrapping_sub(len)
+    } else {
+        value.wrapping_add(len)
+    };
+    cpu.regs.set32(reg, value);
+}
+
+/// repe/repne cmpsb as a single scan, i.e. memcmp.  Returns false if it must loop instead.
+fn cmpsb_bulk(cpu: &mut CPU, mem: Mem, rep: &Rep) -> bool {
+    let count = cpu.regs.get32(Register::ECX);
+    let (esi, edi) = (cpu.regs.get32(Register::ESI), cpu.regs.get32(Register::EDI));
+    if count == 0 || cpu.flags.contains(Flags::DF) {
+        return false;
+    }
+    if span(cpu, mem, esi, count, Size::Byte).is_none()
+        || span(cpu, mem, edi, count, Size::Byte).is_none()
+        || !bulk_read(mem, esi, count)
+        || !bulk_read(mem, edi, count)
+    {
+        return false;
+    }
+    let x = mem.sub32(esi, count);
+    let y = mem.sub32(edi, count);
+    let equal = matches!(rep, Rep::REPE);
+    let last = x
+        .iter()
+        .zip(y)
+        .position(|(a, b)| (a == b) != equal)
+        .unwrap_or(count as usize - 1);
+    sub(x[last], y[last], &mut cpu.flags);
+    let n = last as u32 + 1;
+    cpu.regs.set32(Register::ECX, count - n);
+    advance(cpu, Register::ESI, n);
+    advance(cpu, Register::EDI, n);
+    true
+}
+
 fn cmps_single(cpu: &mut CPU, mem: Mem, size: Size) {
+    let (esi, edi) = (cpu.regs.get32(Register::ESI), cpu.regs.get32(Register::EDI));
+    // Read both before comparing, so a fault leaves the flags alone.
+    let (x, y) = match size {
+        Size::Dword => (
+            read_pod::<u32>(cpu, mem, esi),
+            read_pod::<u32>(cpu, mem, edi),
+        ),
+        Size::Word => (
+            read_pod::<u16>(cpu, mem, esi) as u32,
+            read_pod::<u16>(cpu, mem, edi) as u32,
+        ),
+        Size::Byte => (
+            read_pod::<u8>(cpu, mem, esi) as u32,
+            read_pod::<u8>(cpu, mem, edi) as u32,
+        ),
+    };
+    if !cpu.state.is_running() {
+        return;
+    }
     match size {
-        Size::Dword => {
-            let x = mem.get_pod::<u32>(cpu.regs.get32(Register::ESI));
-            let y = mem.get_pod::<u32>(cpu.regs.get32(Register::EDI));
-            sub(x, y, &mut cpu.flags);
-        }
-        Size::Word => {
-            let x = mem.get_pod::<u16>(cpu.regs.get32(Register::ESI));
-            let y = mem.get_pod::<u16>(cpu.regs.get32(Register::EDI));
-            sub(x, y, &mut cpu.flags);
-        }
-        Size::Byte => {
-            let x = mem.get_pod::<u8>(cpu.regs.get32(Register::ESI));
-            let y = mem.get_pod::<u8>(cpu.regs.get32(Register::EDI));
-            sub(x, y, &mut cpu.flags);
-        }
+        Size::Dword => _ = sub(x, y, &mut cpu.flags),
+        Size::Word => _ = sub(x as u16, y as u16, &mut cpu.flags),
+        Size::Byte => _ = sub(x as u8, y as u8, &mut cpu.flags),
     }
     if cpu.flags.contains(Flags::DF) {
         *cpu.regs.get32_mut(Register::EDI) -= size as u32;
@@ -89,6 +183,9 @@ fn cmps_single(cpu: &mut CPU, mem: Mem, size: Size) {
 
 fn cmps(cpu: &mut CPU, mem: Mem, instr: &Instruction, size: Size) {
     if let Some(r) = Rep::from_instr(instr) {
+        if matches!(size, Size::Byte) && cmpsb_bulk(cpu, mem, &r) {
+            return;
+        }
         rep(cpu, mem, r, size, cmps_single);
     } else {
         cmps_single(cpu, mem, size);
@@ -108,20 +205,34 @@ pub fn cmpsb(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 }
 
 fn movs_single(cpu: &mut CPU, mem: Mem, size: Size) {
+    let edi = cpu.regs.get32(Register::EDI);
+    if write_traps(cpu, mem, edi, size as u32) {
+        return;
+    }
+    let esi = cpu.regs.get32(Register::ESI);
     match size {
         Size::Dword => {
-            let src = mem.get_pod::<u32>(cpu.regs.get32(Register::ESI));
-            mem.put_pod::<u32>(cpu.regs.get32(Register::EDI), src);
+            let src = read_pod::<u32>(cpu, mem, esi);
+            if cpu.state.is_running() {
+                mem.put_pod::<u32>(edi, src);
+            }
         }
         Size::Word => {
-            let src = mem.get_pod::<u16>(cpu.regs.get32(Register::ESI));
-            mem.put_pod::<u16>(cpu.regs.get32(Register::EDI), src);
+            let src = read_pod::<u16>(cpu, mem, esi);
+            if cpu.state.is_running() {
+                mem.put_pod::<u16>(edi, src);
+            }
         }
         Size::Byte => {
-            let src = mem.get_pod::<u8>(cpu.regs.get32(Register::ESI));
-            mem.put_pod::<u8>(cpu.regs.get32(Register::EDI), src);
+            let src = read_pod::<u8>(cpu, mem, esi);
+            if cpu.state.is_running() {
+                mem.put_pod::<u8>(edi, src);
+            }
         }
     }
+    if !cpu.state.is_running() {
+        return;
+    }
     if cpu.flags.contains(Flags::DF) {
         *cpu.regs.get32_mut(Register::EDI) -= size as u32;
         *cpu.regs.get32_mut(Register::ESI) -= size as u32;
@@ -131,8 +242,46 @@ fn movs_single(cpu: &mut CPU, mem: Mem, size: Size) {
     };
 }
 
+/// rep movs as a single copy, i.e. memmove.  Returns false if it must loop instead.
+fn movs_bulk(cpu: &mut CPU, mem: Mem, size: Size) -> bool {
+    let count = cpu.regs.get32(Register::ECX);
+    if count == 0 {
+        return false;
+    }
+    let (esi, edi) = (cpu.regs.get32(Register::ESI), cpu.regs.get32(Register::EDI));
+    let (Some((src, len)), Some((dst, _))) = (
+        span(cpu, mem, esi, count, size),
+        span(cpu, mem, edi, count, size),
+    ) else {
+        return false;
+    };
+    // Copying an element at a time matches memmove, except when the destination
+    // overlaps source that's yet to be read, which some decompressors use to repeat
+    // a pattern.
+    let overlap = dst < src + len && src < dst + len;
+    let ahead = if cpu.flags.contains(Flags::DF) {
+        dst < src
+    } else {
+        dst > src
+    };
+    if overlap && ahead {
+        return false;
+    }
+    if !bulk_read(mem, src, len) || !bulk_write(cpu, mem, dst, len) {
+        return false;
+    }
+    mem.copy(src, dst, len);
+    cpu.regs.set32(Register::ECX, 0);
+    advance(cpu, Register::ESI, len);
+    advance(cpu, Register::EDI, len);
+    true
+}
+
 fn movs(cpu: &mut CPU, mem: Mem, instr: &Instruction, size: Size) {
     if Rep::is_rep(instr) {
+        if movs_bulk(cpu, mem, size) {
+            return;
+        }
         rep(cpu, mem, Rep::REP, size, movs_single);
     } else {
         movs_single(cpu, mem, size);
@@ -152,20 +301,31 @@ pub fn movsb(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 }
 
 fn scas_single(cpu: &mut CPU, mem: Mem, size: Size) {
+    let edi = cpu.regs.get32(Register::EDI);
+    let eax = cpu.regs.get32(Register::EAX);
     match size {
         Size::Dword => {
-            let src = mem.get_pod::<u32>(cpu.regs.get32(Register::EDI));
-            sub(cpu.regs.get32(Register::EAX), src, &mut cpu.flags);
+            let src = read_pod::<u32>(cpu, mem, edi);
+            if cpu.state.is_running() {
+                sub(eax, src, &mut cpu.flags);
+            }
         }
         Size::Word => {
-            let src = mem.get_pod::<u16>(cpu.regs.get32(Register::EDI));
-            sub(cpu.regs.get32(Register::EAX) as u16, src, &mut cpu.flags);
+            let src = read_pod::<u16>(cpu, mem, edi);
+            if cpu.state.is_running() {
+                sub(eax as u16, src, &mut cpu.flags);
+            }
         }
         Size::Byte => {
-            let src = mem.get_pod::<u8>(cpu.regs.get32(Register::EDI));
-            sub(cpu.regs.get32(Register::EAX) as u8, src, &mut cpu.flags);
+            let src = read_pod::<u8>(cpu, mem, edi);
+            if cpu.state.is_running() {
+                sub(eax as u8, src, &mut cpu.flags);
+            }
         }
     }
+    if !cpu.state.is_running() {
+        return;
+    }
     if cpu.flags.contains(Flags::DF) {
         *cpu.regs.get32_mut(Register::EDI) -= size as u32;
     } else {
@@ -173,8 +333,36 @@ fn scas_single(cpu: &mut CPU, mem: Mem, size: Size) {
     };
 }
 
+/// repe/repne scasb as a single scan, i.e. memchr.  Returns false if it must loop instead.
+fn scasb_bulk(cpu: &mut CPU, mem: Mem, rep: &Rep) -> bool {
+    let count = cpu.regs.get32(Register::ECX);
+    let edi = cpu.regs.get32(Register::EDI);
+    if count == 0
+        || cpu.flags.contains(Flags::DF)
+        || span(cpu, mem, edi, count, Size::Byte).is_none()
+        || !bulk_read(mem, edi, count)
+    {
+        return false;
+    }
+    let al = cpu.regs.get8(Register::AL);
+    let bytes = mem.sub32(edi, count);
+    let equal = matches!(rep, Rep::REPE);
+    let last = bytes
+        .iter()
+        .position(|&b| (b == al) != equal)
+        .unwrap_or(count as usize - 1);
+    sub(al, bytes[last], &mut cpu.flags);
+    let n = last as u32 + 1;
+    cpu.regs.set32(Register::ECX, count - n);
+    advance(cpu, Register::EDI, n);
+    true
+}
+
 fn scas(cpu: &mut CPU, mem: Mem, instr: &Instruction, size: Size) {
     if let Some(r) = Rep::from_instr(instr) {
+        if matches!(size, Size::Byte) && scasb_bulk(cpu, mem, &r) {
+            return;
+        }
         rep(cpu, mem, r, size, scas_single);
     } else {
         scas_single(cpu, mem, size);
@@ -194,6 +382,10 @@ pub fn scasb(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 }
 
 fn stos_single(cpu: &mut CPU, mem: Mem, size: Size) {
+    let edi = cpu.regs.get32(Register::EDI);
+    if write_traps(cpu, mem, edi, size as u32) {
+        return;
+    }
     match size {
         Size::Byte => mem.put_pod::<u8>(
             cpu.regs.get32(Register::EDI),
@@ -214,8 +406,36 @@ fn stos_single(cpu: &mut CPU, mem: Mem, size: Size) {
     };
 }
 
+/// rep stos as a single fill, i.e. memset.  Returns false if it must loop instead.
+fn stos_bulk(cpu: &mut CPU, mem: Mem, size: Size) -> bool {
+    let count = cpu.regs.get32(Register::ECX);
+    let edi = cpu.regs.get32(Register::EDI);
+    if count == 0 {
+        return false;
+    }
+    let Some((start, len)) = span(cpu, mem, edi, count, size) else {
+        return false;
+    };
+    if !bulk_write(cpu, mem, start, len) {
+        return false;
+    }
+    let eax = cpu.regs.get32(Register::EAX);
+    let buf = mem.sub32_mut(start, len);
+    match size {
+        Size::Byte => buf.fill(eax as u8),
+        Size::Word => simd::fill_repeat(buf, (eax & 0xFFFF) * 0x1_0001),
+        Size::Dword => simd::fill_repeat(buf, eax),
+    }
+    cpu.regs.set32(Register::ECX, 0);
+    advance(cpu, Register::EDI, len);
+    true
+}
+
 fn stos(cpu: &mut CPU, mem: Mem, instr: &Instruction, size: Size) {
     if Rep::is_rep(instr) {
+        if stos_bulk(cpu, mem, size) {
+            return;
+        }
         rep(cpu, mem, Rep::REP, size, stos_single);
     } else {
         stos_single(cpu, mem, size);
@@ -235,21 +455,19 @@ pub fn stosb(cpu: &mut CPU, mem: Mem, instr: &Instruction) {
 }
 
 fn lods_single(cpu: &mut CPU, mem: Mem, size: Size) {
+    let esi = cpu.regs.get32(Register::ESI);
+    let value = match size {
+        Size::Byte => read_pod::<u8>(cpu, mem, esi) as u32,
+        Size::Word => read_pod::<u16>(cpu, mem, esi) as u32,
+        Size::Dword => read_pod::<u32>(cpu, mem, esi),
+    };
+    if !cpu.state.is_running() {
+        return;
+    }
     match size {
-        Size::Byte => {
-            let value = mem.get_pod::<u8>(cpu.regs.get32(Register::ESI));
-            cpu.regs.set8(iced_x86::Register::AL, value)
-        }
-        Size::Word => {
-            let value = mem.get_pod::<u16>(cpu.regs.get32(Register::ESI));
-            cpu.regs.set16(iced_x86::Register::AX, value)
-        }
-        Size::Dword => {
-            cpu.regs.set32(
-                Register::EAX,
-                mem.get_pod::<u32>(cpu.regs.get32(Register::ESI)),
-            );
-        }
+        Size::Byte => cpu.regs.set8(iced_x86::Register::AL, value as u8),
+        Size::Word => cpu.regs.set16(iced_x86::Register::AX, value as u16),
+        Size::Dword => cpu.regs.set32(Register::EAX, value),
     }
     if cpu.flags.contains(Flags::DF) {
         *cpu.regs.get32_mut(Register::ESI) -= size as u32;
@@ -7,8 +7,11 @@ use memory::Mem;
 /// The type of all operations defined in the ops module.
 pub type Op = fn(&mut CPU, Mem, &Instruction);
 
-const OP_TAB: [Option<Op>; 2553] = {
-    let mut tab: [Option<Op>; 2553] = [None; 2553];
+/// Covers every op we implement; higher codes are SSSE3 and later extensions.
+const OP_TAB_SIZE: usize = 2560;
+
+const OP_TAB: [Option<Op>; OP_TAB_SIZE] = {
+    let mut tab: [Option<Op>; OP_TAB_SIZE] = [None; OP_TAB_SIZE];
 
     use super::basic::*;
     use super::control::*;
@@ -20,7 +23,9 @@ const OP_TAB: [Option<Op>; 2553] = {
     use super::test::*;
 
     tab[iced_x86::Code::Enterd_imm16_imm8 as usize] = Some(enterd_imm16_imm8);
+    tab[iced_x86::Code::Enterw_imm16_imm8 as usize] = Some(enterw_imm16_imm8);
     tab[iced_x86::Code::Leaved as usize] = Some(leaved);
+    tab[iced_x86::Code::Leavew as usize] = Some(leavew);
     tab[iced_x86::Code::Call_rel32_32 as usize] = Some(call);
     tab[iced_x86::Code::Call_rm32 as usize] = Some(call_rm32);
     tab[iced_x86::Code::Retnd as usize] = Some(retnd);
@@ -36,6 +41,10 @@ const OP_TAB: [Option<Op>; 2553] = {
     tab[iced_x86::Code::Jb_rel8_32 as usize] = Some(jb);
     tab[iced_x86::Code::Jbe_rel32_32 as usize] = Some(jbe);
     tab[iced_x86::Code::Jbe_rel8_32 as usize] = Some(jbe);
+    tab[iced_x86::Code::Jp_rel32_32 as usize] = Some(jp);
+    tab[iced_x86::Code::Jp_rel8_32 as usize] = Some(jp);
+    tab[iced_x86::Code::Jnp_rel32_32 as usize] = Some(jnp);
+    tab[iced_x86::Code::Jnp_rel8_32 as usize] = Some(jnp);
     tab[iced_x86::Code::Je_rel32_32 as usize] = Some(je);
     tab[iced_x86::Code::Je_rel8_32 as usize] = Some(je);
     tab[iced_x86::Code::Jecxz_rel8_32 as usize] = Some(jecxz);
@@ -56,6 +65,8 @@ const OP_TAB: [Option<Op>; 2553] = {
 
     tab[iced_x86::Code::Loop_rel8_32_ECX as usize] = Some(loop_);
 
+    tab[iced_x86::Code::Pushd_CS as usize] = Some(pushd_r16);
+    tab[iced_x86::Code::Pushd_SS as usize] = Some(pushd_r16);
     tab[iced_x86::Code::Pushd_DS as usize] = Some(pushd_r16);
     tab[iced_x86::Code::Pushd_ES as usize] = Some(pushd_r16);
     tab[iced_x86::Code::Pushd_FS as usize] = Some(pushd_r16);
@@ -67,6 +78,7 @@ const OP_TAB: [Option<Op>; 2553] = {
     tab[iced_x86::Code::Push_rm16 as usize] = Some(push_rm16);
     tab[iced_x86::Code::Push_r16 as usize] = Some(push_rm16);
 
+    tab[iced_x86::Code::Popd_SS as usize] = Some(popd_r16);
     tab[iced_x86::Code::Popd_DS as usize] = Some(popd_r16);
     tab[iced_x86::Code::Popd_ES as usize] = Some(popd_r16);
     tab[iced_x86::Code::Popd_FS as usize] = Some(popd_r16);
@@ -96,6 +108,10 @@ const OP_TAB: [Option<Op>; 2553] = {
     tab[iced_x86::Code::Mov_moffs8_AL as usize] = Some(mov_moffs8_al);
     tab[iced_x86::Code::Mov_r32m16_Sreg as usize] = Some(mov_r32m16_sreg);
     tab[iced_x86::Code::Mov_Sreg_r32m16 as usize] = Some(mov_sreg_r32m16);
+    tab[iced_x86::Code::Mov_rm16_Sreg as usize] = Some(mov_rm16_sreg);
+    tab[iced_x86::Code::Mov_Sreg_rm16 as usize] = Some(mov_sreg_rm16);
+    tab[iced_x86::Code::Mov_r32_dr as usize] = Some(mov_r32_dr);
+    tab[iced_x86::Code::Mov_dr_r32 as usize] = Some(mov_dr_r32);
 
     tab[iced_x86::Code::Movsx_r32_rm16 as usize] = Some(movsx_r32_rm16);
     tab[iced_x86::Code::Movsx_r32_rm8 as usize] = Some(movsx_r32_rm8);
@@ -116,6 +132,10 @@ const OP_TAB: [Option<Op>; 2553] = {
     tab[iced_x86::Code::Cmpxchg_rm32_r32 as usize] = Some(cmpxchg_rm32_r32);
     tab[iced_x86::Code::Cmpxchg8b_m64 as usize] = Some(cmpxchg8b_m64);
     tab[iced_x86::Code::Cmpxchg_rm8_r8 as usize] = Some(cmpxchg_rm8_r8);
+    tab[iced_x86::Code::Cmpxchg_rm16_r16 as usize] = Some(cmpxchg_rm16_r16);
+    tab[iced_x86::Code::Xadd_rm32_r32 as usize] = Some(xadd_rm32_r32);
+    tab[iced_x86::Code::Xadd_rm16_r16 as usize] = Some(xadd_rm16_r16);
+    tab[iced_x86::Code::Xadd_rm8_r8 as usize] = Some(xadd_rm8_r8);
 
     tab[iced_x86::Code::Cmpsd_m32_m32 as usize] = Some(cmpsd);
     tab[iced_x86::Code::Cmpsw_m16_m16 as usize] = Some(cmpsw);
@@ -163,46 +183,91 @@ const OP_TAB: [Option<Op>; 2553] = {
     tab[iced_x86::Code::Or_AL_imm8 as usize] = Some(or_rm8_imm8);
     tab[iced_x86::Code::Or_AX_imm16 as usize] = Some(or_rm16_imm16);
 
-    tab[iced_x86::Code::Shl_rm32_imm8 as usize] = Some(shl_rm32_imm8);
-    tab[iced_x86::Code::Shl_rm32_1 as usize] = Some(shl_rm32_imm8);
-    tab[iced_x86::Code::Shl_rm32_CL as usize] = Some(shl_rm32_cl);
-    tab[iced_x86::Code::Shl_rm16_imm8 as usize] = Some(shl_rm16_imm8);
-    tab[iced_x86::Code::Shl_rm16_1 as usize] = Some(shl_rm16_imm8);
-    tab[iced_x86::Code::Shl_rm8_CL as usize] = Some(shl_rm8_cl);
-    tab[iced_x86::Code::Shl_rm8_imm8 as usize] = Some(shl_rm8_imm8);
+    tab[iced_x86::Code::Shl_rm32_imm8 as usize] = Some(shl_rm32);
+    tab[iced_x86::Code::Shl_rm32_1 as usize] = Some(shl_rm32);
+    tab[iced_x86::Code::Shl_rm32_CL as usize] = Some(shl_rm32);
+    tab[iced_x86::Code::Shl_rm16_imm8 as usize] = Some(shl_rm16);
+    tab[iced_x86::Code::Shl_rm16_1 as usize] = Some(shl_rm16);
+    tab[iced_x86::Code::Shl_rm16_CL as usize] = Some(shl_rm16);
+    tab[iced_x86::Code::Shl_rm8_imm8 as usize] = Some(shl_rm8);
+    tab[iced_x86::Code::Shl_rm8_1 as usize] = Some(shl_rm8);
+    tab[iced_x86::Code::Shl_rm8_CL as usize] = Some(shl_rm8);
+
+    tab[iced_x86::Code::Sal_rm32_imm8 as usize] = Some(shl_rm32);
+    tab[iced_x86::Code::Sal_rm32_1 as usize] = Some(shl_rm32);
+    tab[iced_x86::Code::Sal_rm32_CL as usize] = Some(shl_rm32);
+    tab[iced_x86::Code::Sal_rm16_imm8 as usize] = Some(shl_rm16);
+    tab[iced_x86::Code::Sal_rm16_1 as usize] = Some(shl_rm16);
+    tab[iced_x86::Code::Sal_rm16_CL as usize] = Some(shl_rm16);
+    tab[iced_x86::Code::Sal_rm8_imm8 as usize] = Some(shl_rm8);
+    tab[iced_x86::Code::Sal_rm8_1 as usize] = Some(shl_rm8);
+    tab[iced_x86::Code::Sal_rm8_CL as usize] = Some(shl_rm8);
+
+    tab[iced_x86::Code::Shr_rm32_imm8 as usize] = Some(shr_rm32);
+    tab[iced_x86::Code::Shr_rm32_1 as usize] = Some(shr_rm32);
+    tab[iced_x86::Code::Shr_rm32_CL as usize] = Some(shr_rm32);
+    tab[iced_x86::Code::Shr_rm16_imm8 as usize] = Some(shr_rm16);
+    tab[iced_x86::Code::Shr_rm16_1 as usize] = Some(shr_rm16);
+    tab[iced_x86::Code::Shr_rm16_CL as usize] = Some(shr_rm16);
+    tab[iced_x86::Code::Shr_rm8_imm8 as usize] = Some(shr_rm8);
+    tab[iced_x86::Code::Shr_rm8_1 as usize] = Some(shr_rm8);
+    tab[iced_x86::Code::Shr_rm8_CL as usize] = Some(shr_rm8);
+
+    tab[iced_x86::Code::Sar_rm32_imm8 as usize] = Some(sar_rm32);
+    tab[iced_x86::Code::Sar_rm32_1 as usize] = Some(sar_rm32);
+    tab[iced_x86::Code::Sar_rm32_CL as usize] = Some(sar_rm32);
+    tab[iced_x86::Code::Sar_rm16_imm8 as usize] = Some(sar_rm16);
+    tab[iced_x86::Code::Sar_rm16_1 as usize] = Some(sar_rm16);
+    tab[iced_x86::Code::Sar_rm16_CL as usize] = Some(sar_rm16);
+    tab[iced_x86::Code::Sar_rm8_imm8 as usize] = Some(sar_rm8);
+    tab[iced_x86::Code::Sar_rm8_1 as usize] = Some(sar_rm8);
+    tab[iced_x86::Code::Sar_rm8_CL as usize] = Some(sar_rm8);
+
+    tab[iced_x86::Code::Rol_rm32_imm8 as usize] = Some(rol_rm32);
+    tab[iced_x86::Code::Rol_rm32_1 as usize] = Some(rol_rm32);
+    tab[iced_x86::Code::Rol_rm32_CL as usize] = Some(rol_rm32);
+    tab[iced_x86::Code::Rol_rm16_imm8 as usize] = Some(rol_rm16);
+    tab[iced_x86::Code::Rol_rm16_1 as usize] = Some(rol_rm16);
+    tab[iced_x86::Code::Rol_rm16_CL as usize] = Some(rol_rm16);
+    tab[iced_x86::Code::Rol_rm8_imm8 as usize] = Some(rol_rm8);
+    tab[iced_x86::Code::Rol_rm8_1 as usize] = Some(rol_rm8);
+    tab[iced_x86::Code::Rol_rm8_CL as usize] = Some(rol_rm8);
+
+    tab[iced_x86::Code::Ror_rm32_imm8 as usize] = Some(ror_rm32);
+    tab[iced_x86::Code::Ror_rm32_1 as usize] = Some(ror_rm32);
+    tab[iced_x86::Code::Ror_rm32_CL as usize] = Some(ror_rm32);
+    tab[iced_x86::Code::Ror_rm16_imm8 as usize] = Some(ror_rm16);
+    tab[iced_x86::Code::Ror_rm16_1 as usize] = Some(ror_rm16);
+    tab[iced_x86::Code::Ror_rm16_CL as usize] = Some(ror_rm16);
+    tab[iced_x86::Code::Ror_rm8_imm8 as usize] = Some(ror_rm8);
+    tab[iced_x86::Code::Ror_rm8_1 as usize] = Some(ror_rm8);
+    tab[iced_x86::Code::Ror_rm8_CL as usize] = Some(ror_rm8);
+
+    tab[iced_x86::Code::Rcl_rm32_imm8 as usize] = Some(rcl_rm32);
+    tab[iced_x86::Code::Rcl_rm32_1 as usize] = Some(rcl_rm32);
+    tab[iced_x86::Code::Rcl_rm32_CL as usize] = Some(rcl_rm32);
+    tab[iced_x86::Code::Rcl_rm16_imm8 as usize] = Some(rcl_rm16);
+    tab[iced_x86::Code::Rcl_rm16_1 as usize] = Some(rcl_rm16);
+    tab[iced_x86::Code::Rcl_rm16_CL as usize] = Some(rcl_rm16);
+    tab[iced_x86::Code::Rcl_rm8_imm8 as usize] = Some(rcl_rm8);
+    tab[iced_x86::Code::Rcl_rm8_1 as usize] = Some(rcl_rm8);
+    tab[iced_x86::Code::Rcl_rm8_CL as usize] = Some(rcl_rm8);
+
+    tab[iced_x86::Code::Rcr_rm32_imm8 as usize] = Some(rcr_rm32);
+    tab[iced_x86::Code::Rcr_rm32_1 as usize] = Some(rcr_rm32);
+    tab[iced_x86::Code::Rcr_rm32_CL as usize] = Some(rcr_rm32);
+    tab[iced_x86::Code::Rcr_rm16_imm8 as usize] = Some(rcr_rm16);
+    tab[iced_x86::Code::Rcr_rm16_1 as usize] = Some(rcr_rm16);
+    tab[iced_x86::Code::Rcr_rm16_CL as usize] = Some(rcr_rm16);
+    tab[iced_x86::Code::Rcr_rm8_imm8 as usize] = Some(rcr_rm8);
+    tab[iced_x86::Code::Rcr_rm8_1 as usize] = Some(rcr_rm8);
+    tab[iced_x86::Code::Rcr_rm8_CL as usize] = Some(rcr_rm8);
 
     tab[iced_x86::Code::Shld_rm32_r32_imm8 as usize] = Some(shld_rm32_r32_imm8);
     tab[iced_x86::Code::Shld_rm32_r32_CL as usize] = Some(shld_rm32_r32_cl);
-
-    tab[iced_x86::Code::Shr_rm32_CL as usize] = Some(shr_rm32_cl);
-    tab[iced_x86::Code::Shr_rm32_1 as usize] = Some(shr_rm32_1);
-    tab[iced_x86::Code::Shr_rm32_imm8 as usize] = Some(shr_rm32_imm8);
-    tab[iced_x86::Code::Shr_rm16_imm8 as usize] = Some(shr_rm16_imm8);
-    tab[iced_x86::Code::Shr_rm16_1 as usize] = Some(shr_rm16_1);
-    tab[iced_x86::Code::Shr_rm8_imm8 as usize] = Some(shr_rm8_imm8);
-    tab[iced_x86::Code::Shr_rm8_1 as usize] = Some(shr_rm8_imm8);
-    tab[iced_x86::Code::Shr_rm8_CL as usize] = Some(shr_rm8_cl);
-
     tab[iced_x86::Code::Shrd_rm32_r32_imm8 as usize] = Some(shrd_rm32_r32_imm8);
     tab[iced_x86::Code::Shrd_rm32_r32_CL as usize] = Some(shrd_rm32_r32_cl);
 
-    tab[iced_x86::Code::Sar_rm32_imm8 as usize] = Some(sar_rm32_imm8);
-    tab[iced_x86::Code::Sar_rm32_1 as usize] = Some(sar_rm32_imm8);
-    tab[iced_x86::Code::Sar_rm32_CL as usize] = Some(sar_rm32_cl);
-    tab[iced_x86::Code::Sar_rm8_1 as usize] = Some(sar_rm8_imm8);
-    tab[iced_x86::Code::Sar_rm8_CL as usize] = Some(sar_rm8_cl);
-
-    tab[iced_x86::Code::Rol_rm32_CL as usize] = Some(rol_rm32_cl);
-    tab[iced_x86::Code::Rol_rm32_imm8 as usize] = Some(rol_rm32_imm8);
-    tab[iced_x86::Code::Rol_rm8_imm8 as usize] = Some(rol_rm8_imm8);
-    tab[iced_x86::Code::Rol_rm8_1 as usize] = Some(rol_rm8_imm8);
-    tab[iced_x86::Code::Rol_rm8_CL as usize] = Some(rol_rm8_cl);
-    tab[iced_x86::Code::Ror_rm32_CL as usize] = Some(ror_rm32_cl);
-    tab[iced_x86::Code::Ror_rm32_imm8 as usize] = Some(ror_rm32_imm8);
-    tab[iced_x86::Code::Ror_rm8_imm8 as usize] = Some(ror_rm8_imm8);
-    tab[iced_x86::Code::Ror_rm8_1 as usize] = Some(ror_rm8_imm8);
-    tab[iced_x86::Code::Ror_rm8_CL as usize] = Some(ror_rm8_cl);
-
     tab[iced_x86::Code::Xor_rm32_r32 as usize] = Some(xor_rm32_rm32);
     tab[iced_x86::Code::Xor_r32_rm32 as usize] = Some(xor_rm32_rm32);
     tab[iced_x86::Code::Xor_rm32_imm32 as usize] = Some(xor_rm32_imm32);
@@ -232,6 +297,8 @@ const OP_TAB: [Option<Op>; 2553] = {
     tab[iced_x86::Code::Adc_r32_rm32 as usize] = Some(adc_rm32_rm32);
     tab[iced_x86::Code::Adc_rm32_imm8 as usize] = Some(adc_rm32_imm8);
     tab[iced_x86::Code::Adc_rm16_imm8 as usize] = Some(adc_rm16_imm8);
+    tab[iced_x86::Code::Adc_rm16_r16 as usize] = Some(adc_rm16_rm16);
+    tab[iced_x86::Code::Adc_r16_rm16 as usize] = Some(adc_rm16_rm16);
     tab[iced_x86::Code::Adc_rm8_r8 as usize] = Some(adc_rm8_rm8);
     tab[iced_x86::Code::Adc_r8_rm8 as usize] = Some(adc_rm8_rm8);
     tab[iced_x86::Code::Adc_rm8_imm8 as usize] = Some(adc_rm8_imm8);
@@ -253,7 +320,10 @@ const OP_TAB: [Option<Op>; 2553] = {
     tab[iced_x86::Code::Sbb_rm32_r32 as usize] = Some(sbb_rm32_r32);
     tab[iced_x86::Code::Sbb_rm32_imm8 as usize] = Some(sbb_rm32_imm8);
     tab[iced_x86::Code::Sbb_rm32_imm32 as usize] = Some(sbb_rm32_imm32);
+    tab[iced_x86::Code::Sbb_rm16_r16 as usize] = Some(sbb_rm16_rm16);
+    tab[iced_x86::Code::Sbb_r16_rm16 as usize] = Some(sbb_rm16_rm16);
     tab[iced_x86::Code::Sbb_r8_rm8 as usize] = Some(sbb_r8_rm8);
+    tab[iced_x86::Code::Sbb_rm8_r8 as usize] = Some(sbb_r8_rm8);
     tab[iced_x86::Code::Sbb_AL_imm8 as usize] = Some(sbb_r8_imm8);
     tab[iced_x86::Code::Mul_rm32 as usize] = Some(mul_rm32);
     tab[iced_x86::Code::Mul_rm16 as usize] = Some(mul_rm16);
@@ -270,6 +340,12 @@ const OP_TAB: [Option<Op>; 2553] = {
     tab[iced_x86::Code::Div_rm32 as usize] = Some(div_rm32);
     tab[iced_x86::Code::Div_rm16 as usize] = Some(div_rm16);
     tab[iced_x86::Code::Div_rm8 as usize] = Some(div_rm8);
+    tab[iced_x86::Code::Aaa as usize] = Some(aaa);
+    tab[iced_x86::Code::Aas as usize] = Some(aas);
+    tab[iced_x86::Code::Daa as usize] = Some(daa);
+    tab[iced_x86::Code::Das as usize] = Some(das);
+    tab[iced_x86::Code::Aam_imm8 as usize] = Some(aam_imm8);
+    tab[iced_x86::Code::Aad_imm8 as usize] = Some(aad_imm8);
     tab[iced_x86::Code::Dec_r32 as usize] = Some(dec_rm32);
     tab[iced_x86::Code::Dec_rm32 as usize] = Some(dec_rm32);
     tab[iced_x86::Code::Dec_r16 as usize] = Some(dec_rm16);
@@ -320,6 +396,10 @@ const OP_TAB: [Option<Op>; 2553] = {
     tab[iced_x86::Code::Bt_rm32_r32 as usize] = Some(bt_rm32_r32);
     tab[iced_x86::Code::Bt_rm32_imm8 as usize] = Some(bt_rm32_imm8);
     tab[iced_x86::Code::Btr_rm32_imm8 as usize] = Some(btr_rm32_imm8);
+    tab[iced_x86::Code::Btr_rm32_r32 as usize] = Some(btr_rm32_r32);
+    tab[iced_x86::Code::Bts_rm32_imm8 as usize] = Some(bts_rm32_imm8);
+    tab[iced_x86::Code::Btc_rm32_r32 as usize] = Some(btc_rm32_r32);
+    tab[iced_x86::Code::Btc_rm32_imm8 as usize] = Some(btc_rm32_imm8);
     tab[iced_x86::Code::Bsr_r32_rm32 as usize] = Some(bsr_r32_rm32);
 
     tab[iced_x86::Code::Cmove_r32_rm32 as usize] = Some(cmove_r32_rm32);
@@ -329,6 +409,8 @@ const OP_TAB: [Option<Op>; 2553] = {
     tab[iced_x86::Code::Setb_rm8 as usize] = Some(setb_rm8);
     tab[iced_x86::Code::Setbe_rm8 as usize] = Some(setbe_rm8);
     tab[iced_x86::Code::Sete_rm8 as usize] = Some(sete_rm8);
+    tab[iced_x86::Code::Setp_rm8 as usize] = Some(setp_rm8);
+    tab[iced_x86::Code::Setnp_rm8 as usize] = Some(setnp_rm8);
     tab[iced_x86::Code::Setg_rm8 as usize] = Some(setg_rm8);
     tab[iced_x86::Code::Setl_rm8 as usize] = Some(setl_rm8);
     tab[iced_x86::Code::Setle_rm8 as usize] = Some(setle_rm8);
@@ -343,8 +425,12 @@ const OP_TAB: [Option<Op>; 2553] = {
     tab[iced_x86::Code::Fldz as usize] = Some(fldz);
     tab[iced_x86::Code::Fldpi as usize] = Some(fldpi);
     tab[iced_x86::Code::Fldl2e as usize] = Some(fldl2e);
+    tab[iced_x86::Code::Fldl2t as usize] = Some(fldl2t);
+    tab[iced_x86::Code::Fldlg2 as usize] = Some(fldlg2);
+    tab[iced_x86::Code::Fldln2 as usize] = Some(fldln2);
 
     tab[iced_x86::Code::Fld_sti as usize] = Some(fld_sti);
+    tab[iced_x86::Code::Fld_m80fp as usize] = Some(fld_m80fp);
     tab[iced_x86::Code::Fld_m64fp as usize] = Some(fld_m64fp);
     tab[iced_x86::Code::Fld_m32fp as usize] = Some(fld_m32fp);
     tab[iced_x86::Code::Fild_m64int as usize] = Some(fild_m64int);
@@ -352,21 +438,25 @@ const OP_TAB: [Option<Op>; 2553] = {
     tab[iced_x86::Code::Fild_m16int as usize] = Some(fild_m16int);
     tab[iced_x86::Code::Fst_m64fp as usize] = Some(fst_m64fp);
     tab[iced_x86::Code::Fst_m32fp as usize] = Some(fst_m32fp);
+    tab[iced_x86::Code::Fst_sti as usize] = Some(fst_sti);
+    tab[iced_x86::Code::Fstp_m80fp as usize] = Some(fstp_m80fp);
     tab[iced_x86::Code::Fstp_m64fp as usize] = Some(fstp_m64fp);
     tab[iced_x86::Code::Fstp_m32fp as usize] = Some(fstp_m32fp);
     tab[iced_x86::Code::Fstp_sti as usize] = Some(fstp_sti);
+    tab[iced_x86::Code::Fstp_sti_DFD0 as usize] = Some(fstp_sti);
+    tab[iced_x86::Code::Fstp_sti_DFD8 as usize] = Some(fstp_sti);
     tab[iced_x86::Code::Fistp_m64int as usize] = Some(fistp_m64int);
     tab[iced_x86::Code::Fistp_m32int as usize] = Some(fistp_m32int);
     tab[iced_x86::Code::Fistp_m16int as usize] = Some(fistp_m16int);
-    // tab[iced_x86::Code::Fisp_m64int as usize] = Some(fist_m64int);
     tab[iced_x86::Code::Fist_m32int as usize] = Some(fist_m32int);
-    // tab[iced_x86::Code::Fist_m16int as usize] = Some(fist_m16int);
+    tab[iced_x86::Code::Fist_m16int as usize] = Some(fist_m16int);
 
     tab[iced_x86::Code::Fchs as usize] = Some(fchs);
     tab[iced_x86::Code::Fabs as usize] = Some(fabs);
     tab[iced_x86::Code::Fcos as usize] = Some(fcos);
     tab[iced_x86::Code::Fsin as usize] = Some(fsin);
     tab[iced_x86::Code::Fsincos as usize] = Some(fsincos);
+    tab[iced_x86::Code::Fptan as usize] = Some(fptan);
     tab[iced_x86::Code::Fpatan as usize] = Some(fpatan);
     tab[iced_x86::Code::Fsqrt as usize] = Some(fsqrt);
 
@@ -381,14 +471,18 @@ const OP_TAB: [Option<Op>; 2553] = {
     tab[iced_x86::Code::Fsub_m64fp as usize] = Some(fsub_m64fp);
     tab[iced_x86::Code::Fsub_m32fp as usize] = Some(fsub_m32fp);
     tab[iced_x86::Code::Fsub_st0_sti as usize] = Some(fsub_sti_sti);
+    tab[iced_x86::Code::Fsub_sti_st0 as usize] = Some(fsub_sti_sti);
     tab[iced_x86::Code::Fsubp_sti_st0 as usize] = Some(fsubp_sti_sti);
     tab[iced_x86::Code::Fisub_m32int as usize] = Some(fisub_m32int);
+    tab[iced_x86::Code::Fisub_m16int as usize] = Some(fisub_m16int);
 
     tab[iced_x86::Code::Fsubr_m64fp as usize] = Some(fsubr_m64fp);
     tab[iced_x86::Code::Fsubr_m32fp as usize] = Some(fsubr_m32fp);
     tab[iced_x86::Code::Fsubr_st0_sti as usize] = Some(fsubr_sti_sti);
     tab[iced_x86::Code::Fsubr_sti_st0 as usize] = Some(fsubr_sti_sti);
     tab[iced_x86::Code::Fsubrp_sti_st0 as usize] = Some(fsubrp_sti_sti);
+    tab[iced_x86::Code::Fisubr_m32int as usize] = Some(fisubr_m32int);
+    tab[iced_x86::Code::Fisubr_m16int as usize] = Some(fisubr_m16int);
 
     tab[iced_x86::Code::Fmul_m64fp as usize] = Some(fmul_m64fp);
     tab[iced_x86::Code::Fmul_m32fp as usize] = Some(fmul_m32fp);
@@ -399,7 +493,10 @@ const OP_TAB: [Option<Op>; 2553] = {
     tab[iced_x86::Code::Fmulp_sti_st0 as usize] = Some(fmulp_sti_sti);
 
     tab[iced_x86::Code::F2xm1 as usize] = Some(f2xm1);
+    tab[iced_x86::Code::Fyl2x as usize] = Some(fyl2x);
+    tab[iced_x86::Code::Fyl2xp1 as usize] = Some(fyl2xp1);
     tab[iced_x86::Code::Fscale as usize] = Some(fscale);
+    tab[iced_x86::Code::Fxtract as usize] = Some(fxtract);
 
     tab[iced_x86::Code::Fdiv_m64fp as usize] = Some(fdiv_m64fp);
     tab[iced_x86::Code::Fdiv_m32fp as usize] = Some(fdiv_m32fp);
@@ -412,31 +509,73 @@ const OP_TAB: [Option<Op>; 2553] = {
     tab[iced_x86::Code::Fdivr_m64fp as usize] = Some(fdivr_m64fp);
     tab[iced_x86::Code::Fdivr_m32fp as usize] = Some(fdivr_m32fp);
     tab[iced_x86::Code::Fdivr_st0_sti as usize] = Some(fdivr_sti_sti);
+    tab[iced_x86::Code::Fdivr_sti_st0 as usize] = Some(fdivr_sti_sti);
     tab[iced_x86::Code::Fdivrp_sti_st0 as usize] = Some(fdivrp_sti_sti);
     tab[iced_x86::Code::Fidivr_m32int as usize] = Some(fidivr_m32int);
+    tab[iced_x86::Code::Fidivr_m16int as usize] = Some(fidivr_m16int);
 
     tab[iced_x86::Code::Fprem as usize] = Some(fprem);
+    tab[iced_x86::Code::Fprem1 as usize] = Some(fprem1);
 
     tab[iced_x86::Code::Fxch_st0_sti as usize] = Some(fxch_st0_sti);
-    tab[iced_x86::Code::Fcom_m64fp as usize] = Some(fcom_m32fp);
+    tab[iced_x86::Code::Fxch_st0_sti_DDC8 as usize] = Some(fxch_st0_sti);
+    tab[iced_x86::Code::Fxch_st0_sti_DFC8 as usize] = Some(fxch_st0_sti);
+    tab[iced_x86::Code::Fcom_m64fp as usize] = Some(fcom_m64fp);
     tab[iced_x86::Code::Fcom_m32fp as usize] = Some(fcom_m32fp);
     tab[iced_x86::Code::Fcomp_m32fp as usize] = Some(fcomp_m32fp);
     tab[iced_x86::Code::Fcomp_m64fp as usize] = Some(fcomp_m64fp);
+    tab[iced_x86::Code::Ficom_m32int as usize] = Some(ficom_m32int);
+    tab[iced_x86::Code::Ficom_m16int as usize] = Some(ficom_m16int);
+    tab[iced_x86::Code::Ficomp_m32int as usize] = Some(ficomp_m32int);
+    tab[iced_x86::Code::Ficomp_m16int as usize] = Some(ficomp_m16int);
+    tab[iced_x86::Code::Fcom_st0_sti as usize] = Some(fcom_st0_sti);
+    tab[iced_x86::Code::Fcom_st0_sti_DCD0 as usize] = Some(fcom_st0_sti);
     tab[iced_x86::Code::Fcomp_st0_sti as usize] = Some(fcomp_st0_sti);
+    tab[iced_x86::Code::Fcomp_st0_sti_DCD8 as usize] = Some(fcomp_st0_sti);
+    tab[iced_x86::Code::Fcomp_st0_sti_DED0 as usize] = Some(fcomp_st0_sti);
+    tab[iced_x86::Code::Fcompp as usize] = Some(fcompp);
+    tab[iced_x86::Code::Fucom_st0_sti as usize] = Some(fucom_st0_sti);
     tab[iced_x86::Code::Fucomp_st0_sti as usize] = Some(fucomp_st0_sti);
+    tab[iced_x86::Code::Fucompp as usize] = Some(fucompp);
+    tab[iced_x86::Code::Ftst as usize] = Some(ftst);
+    tab[iced_x86::Code::Fxam as usize] = Some(fxam);
     tab[iced_x86::Code::Fcomi_st0_sti as usize] = Some(fcomi_st0_sti);
+    tab[iced_x86::Code::Fcomip_st0_sti as usize] = Some(fcomip_st0_sti);
     tab[iced_x86::Code::Fucomi_st0_sti as usize] = Some(fucomi_st0_sti);
     tab[iced_x86::Code::Fucomip_st0_sti as usize] = Some(fucomip_st0_sti);
 
     tab[iced_x86::Code::Frndint as usize] = Some(frndint);
     tab[iced_x86::Code::Fnstsw_AX as usize] = Some(fnstsw_ax);
+    tab[iced_x86::Code::Fstsw_AX as usize] = Some(fnstsw_ax);
+    tab[iced_x86::Code::Fnstsw_m2byte as usize] = Some(fnstsw_m2byte);
+    tab[iced_x86::Code::Fstsw_m2byte as usize] = Some(fnstsw_m2byte);
     tab[iced_x86::Code::Fnstcw_m2byte as usize] = Some(fnstcw_m2byte);
+    tab[iced_x86::Code::Fstcw_m2byte as usize] = Some(fnstcw_m2byte);
     tab[iced_x86::Code::Fldcw_m2byte as usize] = Some(fldcw_m2byte);
-    tab[iced_x86::Code::Fclex as usize] = Some(nop);
-    tab[iced_x86::Code::Fnclex as usize] = Some(nop);
-
+    tab[iced_x86::Code::Fnstenv_m28byte as usize] = Some(fnstenv_m28byte);
+    tab[iced_x86::Code::Fstenv_m28byte as usize] = Some(fnstenv_m28byte);
+    tab[iced_x86::Code::Fldenv_m28byte as usize] = Some(fldenv_m28byte);
+    tab[iced_x86::Code::Fnsave_m108byte as usize] = Some(fnsave_m108byte);
+    tab[iced_x86::Code::Fsave_m108byte as usize] = Some(fnsave_m108byte);
+    tab[iced_x86::Code::Frstor_m108byte as usize] = Some(frstor_m108byte);
+    tab[iced_x86::Code::Fclex as usize] = Some(fnclex);
+    tab[iced_x86::Code::Fnclex as usize] = Some(fnclex);
+    tab[iced_x86::Code::Fnop as usize] = Some(nop);
+    tab[iced_x86::Code::Ffree_sti as usize] = Some(ffree_sti);
+    tab[iced_x86::Code::Ffreep_sti as usize] = Some(ffreep_sti);
+    tab[iced_x86::Code::Fincstp as usize] = Some(fincstp);
+    tab[iced_x86::Code::Fdecstp as usize] = Some(fdecstp);
+
+    tab[iced_x86::Code::Fcmovb_st0_sti as usize] = Some(fcmovb_st0_sti);
+    tab[iced_x86::Code::Fcmove_st0_sti as usize] = Some(fcmove_st0_sti);
+    tab[iced_x86::Code::Fcmovbe_st0_sti as usize] = Some(fcmovbe_st0_sti);
+    tab[iced_x86::Code::Fcmovu_st0_sti as usize] = Some(fcmovu_st0_sti);
+    tab[iced_x86::Code::Fcmovnb_st0_sti as usize] = Some(fcmovnb_st0_sti);
+    tab[iced_x86::Code::Fcmovne_st0_sti as usize] = Some(fcmovne_st0_sti);
     tab[iced_x86::Code::Fcmovnbe_st0_sti as usize] = Some(fcmovnbe_st0_sti);
+    tab[iced_x86::Code::Fcmovnu_st0_sti as usize] = Some(fcmovnu_st0_sti);
 
+    // Waiting for the FPU is only visible as raising a pending exception; see CPU::execute_block.
     tab[iced_x86::Code::Wait as usize] = Some(nop);
 
     tab[iced_x86::Code::Pushad as usize] = Some(pushad);
@@ -445,6 +584,7 @@ const OP_TAB: [Option<Op>; 2553] = {
     tab[iced_x86::Code::Pushfw as usize] = Some(pushfw);
     tab[iced_x86::Code::Popfd as usize] = Some(popfd);
     tab[iced_x86::Code::Popfw as usize] = Some(popfw);
+    tab[iced_x86::Code::Lahf as usize] = Some(lahf);
     tab[iced_x86::Code::Sahf as usize] = Some(sahf);
 
     tab[iced_x86::Code::Salc as usize] = Some(salc);
@@ -482,16 +622,218 @@ const OP_TAB: [Option<Op>; 2553] = {
     tab[iced_x86::Code::Psubw_mm_mmm64 as usize] = Some(psubw_mm_mmm64);
     tab[iced_x86::Code::Pcmpeqb_mm_mmm64 as usize] = Some(pcmpeqb_mm_mmm64);
 
+    use super::sse::*;
+    tab[iced_x86::Code::Movaps_xmm_xmmm128 as usize] = Some(movaps_xmm_xmmm128);
+    tab[iced_x86::Code::Movups_xmm_xmmm128 as usize] = Some(movaps_xmm_xmmm128);
+    tab[iced_x86::Code::Movapd_xmm_xmmm128 as usize] = Some(movaps_xmm_xmmm128);
+    tab[iced_x86::Code::Movupd_xmm_xmmm128 as usize] = Some(movaps_xmm_xmmm128);
+    tab[iced_x86::Code::Movdqa_xmm_xmmm128 as usize] = Some(movaps_xmm_xmmm128);
+    tab[iced_x86::Code::Movdqu_xmm_xmmm128 as usize] = Some(movaps_xmm_xmmm128);
+    tab[iced_x86::Code::Movaps_xmmm128_xmm as usize] = Some(movaps_xmmm128_xmm);
+    tab[iced_x86::Code::Movups_xmmm128_xmm as usize] = Some(movaps_xmmm128_xmm);
+    tab[iced_x86::Code::Movapd_xmmm128_xmm as usize] = Some(movaps_xmmm128_xmm);
+    tab[iced_x86::Code::Movupd_xmmm128_xmm as usize] = Some(movaps_xmmm128_xmm);
+    tab[iced_x86::Code::Movdqa_xmmm128_xmm as usize] = Some(movaps_xmmm128_xmm);
+    tab[iced_x86::Code::Movdqu_xmmm128_xmm as usize] = Some(movaps_xmmm128_xmm);
+    tab[iced_x86::Code::Movntps_m128_xmm as usize] = Some(movaps_xmmm128_xmm);
+    tab[iced_x86::Code::Movntpd_m128_xmm as usize] = Some(movaps_xmmm128_xmm);
+    tab[iced_x86::Code::Movntdq_m128_xmm as usize] = Some(movaps_xmmm128_xmm);
+    tab[iced_x86::Code::Movss_xmm_xmmm32 as usize] = Some(movss_xmm_xmmm32);
+    tab[iced_x86::Code::Movss_xmmm32_xmm as usize] = Some(movss_xmmm32_xmm);
+    tab[iced_x86::Code::Movsd_xmm_xmmm64 as usize] = Some(movsd_xmm_xmmm64);
+    tab[iced_x86::Code::Movsd_xmmm64_xmm as usize] = Some(movsd_xmmm64_xmm);
+    tab[iced_x86::Code::Movd_xmm_rm32 as usize] = Some(movd_xmm_rm32);
+    tab[iced_x86::Code::Movd_rm32_xmm as usize] = Some(movd_rm32_xmm);
+    tab[iced_x86::Code::Movq_xmm_xmmm64 as usize] = Some(movq_xmm_xmmm64);
+    tab[iced_x86::Code::Movq_xmmm64_xmm as usize] = Some(movq_xmmm64_xmm);
+    tab[iced_x86::Code::Movq2dq_xmm_mm as usize] = Some(movq2dq_xmm_mm);
+    tab[iced_x86::Code::Movdq2q_mm_xmm as usize] = Some(movdq2q_mm_xmm);
+    tab[iced_x86::Code::Movlps_xmm_m64 as usize] = Some(movlps_xmm_m64);
+    tab[iced_x86::Code::Movlpd_xmm_m64 as usize] = Some(movlps_xmm_m64);
+    tab[iced_x86::Code::Movlps_m64_xmm as usize] = Some(movlps_m64_xmm);
+    tab[iced_x86::Code::Movlpd_m64_xmm as usize] = Some(movlps_m64_xmm);
+    tab[iced_x86::Code::Movhps_xmm_m64 as usize] = Some(movhps_xmm_m64);
+    tab[iced_x86::Code::Movhpd_xmm_m64 as usize] = Some(movhps_xmm_m64);
+    tab[iced_x86::Code::Movhps_m64_xmm as usize] = Some(movhps_m64_xmm);
+    tab[iced_x86::Code::Movhpd_m64_xmm as usize] = Some(movhps_m64_xmm);
+    tab[iced_x86::Code::Movhlps_xmm_xmm as usize] = Some(movhlps_xmm_xmm);
+    tab[iced_x86::Code::Movlhps_xmm_xmm as usize] = Some(movlhps_xmm_xmm);
+    tab[iced_x86::Code::Movmskps_r32_xmm as usize] = Some(movmskps_r32_xmm);
+    tab[iced_x86::Code::Movmskpd_r32_xmm as usize] = Some(movmskpd_r32_xmm);
+    tab[iced_x86::Code::Pmovmskb_r32_xmm as usize] = Some(pmovmskb_r32_xmm);
+
+    tab[iced_x86::Code::Addps_xmm_xmmm128 as usize] = Some(addps_xmm_xmmm128);
+    tab[iced_x86::Code::Addss_xmm_xmmm32 as usize] = Some(addss_xmm_xmmm32);
+    tab[iced_x86::Code::Addpd_xmm_xmmm128 as usize] = Some(addpd_xmm_xmmm128);
+    tab[iced_x86::Code::Addsd_xmm_xmmm64 as usize] = Some(addsd_xmm_xmmm64);
+    tab[iced_x86::Code::Subps_xmm_xmmm128 as usize] = Some(subps_xmm_xmmm128);
+    tab[iced_x86::Code::Subss_xmm_xmmm32 as usize] = Some(subss_xmm_xmmm32);
+    tab[iced_x86::Code::Subpd_xmm_xmmm128 as usize] = Some(subpd_xmm_xmmm128);
+    tab[iced_x86::Code::Subsd_xmm_xmmm64 as usize] = Some(subsd_xmm_xmmm64);
+    tab[iced_x86::Code::Mulps_xmm_xmmm128 as usize] = Some(mulps_xmm_xmmm128);
+    tab[iced_x86::Code::Mulss_xmm_xmmm32 as usize] = Some(mulss_xmm_xmmm32);
+    tab[iced_x86::Code::Mulpd_xmm_xmmm128 as usize] = Some(mulpd_xmm_xmmm128);
+    tab[iced_x86::Code::Mulsd_xmm_xmmm64 as usize] = Some(mulsd_xmm_xmmm64);
+    tab[iced_x86::Code::Divps_xmm_xmmm128 as usize] = Some(divps_xmm_xmmm128);
+    tab[iced_x86::Code::Divss_xmm_xmmm32 as usize] = Some(divss_xmm_xmmm32);
+    tab[iced_x86::Code::Divpd_xmm_xmmm128 as usize] = Some(divpd_xmm_xmmm128);
+    tab[iced_x86::Code::Divsd_xmm_xmmm64 as usize] = Some(divsd_xmm_xmmm64);
+    tab[iced_x86::Code::Minps_xmm_xmmm128 as usize] = Some(minps_xmm_xmmm128);
+    tab[iced_x86::Code::Minss_xmm_xmmm32 as usize] = Some(minss_xmm_xmmm32);
+    tab[iced_x86::Code::Minpd_xmm_xmmm128 as usize] = Some(minpd_xmm_xmmm128);
+    tab[iced_x86::Code::Minsd_xmm_xmmm64 as usize] = Some(minsd_xmm_xmmm64);
+    tab[iced_x86::Code::Maxps_xmm_xmmm128 as usize] = Some(maxps_xmm_xmmm128);
+    tab[iced_x86::Code::Maxss_xmm_xmmm32 as usize] = Some(maxss_xmm_xmmm32);
+    tab[iced_x86::Code::Maxpd_xmm_xmmm128 as usize] = Some(maxpd_xmm_xmmm128);
+    tab[iced_x86::Code::Maxsd_xmm_xmmm64 as usize] = Some(maxsd_xmm_xmmm64);
+    tab[iced_x86::Code::Sqrtps_xmm_xmmm128 as usize] = Some(sqrtps_xmm_xmmm128);
+    tab[iced_x86::Code::Sqrtss_xmm_xmmm32 as usize] = Some(sqrtss_xmm_xmmm32);
+    tab[iced_x86::Code::Sqrtpd_xmm_xmmm128 as usize] = Some(sqrtpd_xmm_xmmm128);
+    tab[iced_x86::Code::Sqrtsd_xmm_xmmm64 as usize] = Some(sqrtsd_xmm_xmmm64);
+    tab[iced_x86::Code::Rcpps_xmm_xmmm128 as usize] = Some(rcpps_xmm_xmmm128);
+    tab[iced_x86::Code::Rcpss_xmm_xmmm32 as usize] = Some(rcpss_xmm_xmmm32);
+    tab[iced_x86::Code::Rsqrtps_xmm_xmmm128 as usize] = Some(rsqrtps_xmm_xmmm128);
+    tab[iced_x86::Code::Rsqrtss_xmm_xmmm32 as usize] = Some(rsqrtss_xmm_xmmm32);
+
+    tab[iced_x86::Code::Andps_xmm_xmmm128 as usize] = Some(andps_xmm_xmmm128);
+    tab[iced_x86::Code::Andpd_xmm_xmmm128 as usize] = Some(andps_xmm_xmmm128);
+    tab[iced_x86::Code::Pand_xmm_xmmm128 as usize] = Some(andps_xmm_xmmm128);
+    tab[iced_x86::Code::Andnps_xmm_xmmm128 as usize] = Some(andnps_xmm_xmmm128);
+    tab[iced_x86::Code::Andnpd_xmm_xmmm128 as usize] = Some(andnps_xmm_xmmm128);
+    tab[iced_x86::Code::Pandn_xmm_xmmm128 as usize] = Some(andnps_xmm_xmmm128);
+    tab[iced_x86::Code::Orps_xmm_xmmm128 as usize] = Some(orps_xmm_xmmm128);
+    tab[iced_x86::Code::Orpd_xmm_xmmm128 as usize] = Some(orps_xmm_xmmm128);
+    tab[iced_x86::Code::Por_xmm_xmmm128 as usize] = Some(orps_xmm_xmmm128);
+    tab[iced_x86::Code::Xorps_xmm_xmmm128 as usize] = Some(xorps_xmm_xmmm128);
+    tab[iced_x86::Code::Xorpd_xmm_xmmm128 as usize] = Some(xorps_xmm_xmmm128);
+    tab[iced_x86::Code::Pxor_xmm_xmmm128 as usize] = Some(xorps_xmm_xmmm128);
+
+    tab[iced_x86::Code::Cmpps_xmm_xmmm128_imm8 as usize] = Some(cmpps_xmm_xmmm128_imm8);
+    tab[iced_x86::Code::Cmppd_xmm_xmmm128_imm8 as usize] = Some(cmppd_xmm_xmmm128_imm8);
+    tab[iced_x86::Code::Cmpss_xmm_xmmm32_imm8 as usize] = Some(cmpss_xmm_xmmm32_imm8);
+    tab[iced_x86::Code::Cmpsd_xmm_xmmm64_imm8 as usize] = Some(cmpsd_xmm_xmmm64_imm8);
+    tab[iced_x86::Code::Comiss_xmm_xmmm32 as usize] = Some(comiss_xmm_xmmm32);
+    tab[iced_x86::Code::Ucomiss_xmm_xmmm32 as usize] = Some(comiss_xmm_xmmm32);
+    tab[iced_x86::Code::Comisd_xmm_xmmm64 as usize] = Some(comisd_xmm_xmmm64);
+    tab[iced_x86::Code::Ucomisd_xmm_xmmm64 as usize] = Some(comisd_xmm_xmmm64);
+
+    tab[iced_x86::Code::Shufps_xmm_xmmm128_imm8 as usize] = Some(shufps_xmm_xmmm128_imm8);
+    tab[iced_x86::Code::Shufpd_xmm_xmmm128_imm8 as usize] = Some(shufpd_xmm_xmmm128_imm8);
+    tab[iced_x86::Code::Pshufd_xmm_xmmm128_imm8 as usize] = Some(pshufd_xmm_xmmm128_imm8);
+    tab[iced_x86::Code::Pshuflw_xmm_xmmm128_imm8 as usize] = Some(pshuflw_xmm_xmmm128_imm8);
+    tab[iced_x86::Code::Pshufhw_xmm_xmmm128_imm8 as usize] = Some(pshufhw_xmm_xmmm128_imm8);
+    tab[iced_x86::Code::Unpcklps_xmm_xmmm128 as usize] = Some(unpcklps_xmm_xmmm128);
+    tab[iced_x86::Code::Unpckhps_xmm_xmmm128 as usize] = Some(unpckhps_xmm_xmmm128);
+    tab[iced_x86::Code::Unpcklpd_xmm_xmmm128 as usize] = Some(unpcklpd_xmm_xmmm128);
+    tab[iced_x86::Code::Unpckhpd_xmm_xmmm128 as usize] = Some(unpckhpd_xmm_xmmm128);
+
+    tab[iced_x86::Code::Cvtsi2ss_xmm_rm32 as usize] = Some(cvtsi2ss_xmm_rm32);
+    tab[iced_x86::Code::Cvtsi2sd_xmm_rm32 as usize] = Some(cvtsi2sd_xmm_rm32);
+    tab[iced_x86::Code::Cvtss2si_r32_xmmm32 as usize] = Some(cvtss2si_r32_xmmm32);
+    tab[iced_x86::Code::Cvttss2si_r32_xmmm32 as usize] = Some(cvttss2si_r32_xmmm32);
+    tab[iced_x86::Code::Cvtsd2si_r32_xmmm64 as usize] = Some(cvtsd2si_r32_xmmm64);
+    tab[iced_x86::Code::Cvttsd2si_r32_xmmm64 as usize] = Some(cvttsd2si_r32_xmmm64);
+    tab[iced_x86::Code::Cvtss2sd_xmm_xmmm32 as usize] = Some(cvtss2sd_xmm_xmmm32);
+    tab[iced_x86::Code::Cvtsd2ss_xmm_xmmm64 as usize] = Some(cvtsd2ss_xmm_xmmm64);
+    tab[iced_x86::Code::Cvtps2pd_xmm_xmmm64 as usize] = Some(cvtps2pd_xmm_xmmm64);
+    tab[iced_x86::Code::Cvtpd2ps_xmm_xmmm128 as usize] = Some(cvtpd2ps_xmm_xmmm128);
+    tab[iced_x86::Code::Cvtdq2ps_xmm_xmmm128 as usize] = Some(cvtdq2ps_xmm_xmmm128);
+    tab[iced_x86::Code::Cvtps2dq_xmm_xmmm128 as usize] = Some(cvtps2dq_xmm_xmmm128);
+    tab[iced_x86::Code::Cvttps2dq_xmm_xmmm128 as usize] = Some(cvttps2dq_xmm_xmmm128);
+    tab[iced_x86::Code::Cvtdq2pd_xmm_xmmm64 as usize] = Some(cvtdq2pd_xmm_xmmm64);
+    tab[iced_x86::Code::Cvtpd2dq_xmm_xmmm128 as usize] = Some(cvtpd2dq_xmm_xmmm128);
+    tab[iced_x86::Code::Cvttpd2dq_xmm_xmmm128 as usize] = Some(cvttpd2dq_xmm_xmmm128);
+
+    tab[iced_x86::Code::Paddb_xmm_xmmm128 as usize] = Some(paddb_xmm_xmmm128);
+    tab[iced_x86::Code::Paddw_xmm_xmmm128 as usize] = Some(paddw_xmm_xmmm128);
+    tab[iced_x86::Code::Paddd_xmm_xmmm128 as usize] = Some(paddd_xmm_xmmm128);
+    tab[iced_x86::Code::Paddq_xmm_xmmm128 as usize] = Some(paddq_xmm_xmmm128);
+    tab[iced_x86::Code::Psubb_xmm_xmmm128 as usize] = Some(psubb_xmm_xmmm128);
+    tab[iced_x86::Code::Psubw_xmm_xmmm128 as usize] = Some(psubw_xmm_xmmm128);
+    tab[iced_x86::Code::Psubd_xmm_xmmm128 as usize] = Some(psubd_xmm_xmmm128);
+    tab[iced_x86::Code::Psubq_xmm_xmmm128 as usize] = Some(psubq_xmm_xmmm128);
+    tab[iced_x86::Code::Paddusb_xmm_xmmm128 as usize] = Some(paddusb_xmm_xmmm128);
+    tab[iced_x86::Code::Paddusw_xmm_xmmm128 as usize] = Some(paddusw_xmm_xmmm128);
+    tab[iced_x86::Code::Paddsb_xmm_xmmm128 as usize] = Some(paddsb_xmm_xmmm128);
+    tab[iced_x86::Code::Paddsw_xmm_xmmm128 as usize] = Some(paddsw_xmm_xmmm128);
+    tab[iced_x86::Code::Psubusb_xmm_xmmm128 as usize] = Some(psubusb_xmm_xmmm128);
+    tab[iced_x86::Code::Psubusw_xmm_xmmm128 as usize] = Some(psubusw_xmm_xmmm128);
+    tab[iced_x86::Code::Psubsb_xmm_xmmm128 as usize] = Some(psubsb_xmm_xmmm128);
+    tab[iced_x86::Code::Psubsw_xmm_xmmm128 as usize] = Some(psubsw_xmm_xmmm128);
+    tab[iced_x86::Code::Pmullw_xmm_xmmm128 as usize] = Some(pmullw_xmm_xmmm128);
+    tab[iced_x86::Code::Pmulhw_xmm_xmmm128 as usize] = Some(pmulhw_xmm_xmmm128);
+    tab[iced_x86::Code::Pmulhuw_xmm_xmmm128 as usize] = Some(pmulhuw_xmm_xmmm128);
+    tab[iced_x86::Code::Pmuludq_xmm_xmmm128 as usize] = Some(pmuludq_xmm_xmmm128);
+    tab[iced_x86::Code::Pmaddwd_xmm_xmmm128 as usize] = Some(pmaddwd_xmm_xmmm128);
+    tab[iced_x86::Code::Pavgb_xmm_xmmm128 as usize] = Some(pavgb_xmm_xmmm128);
+    tab[iced_x86::Code::Pavgw_xmm_xmmm128 as usize] = Some(pavgw_xmm_xmmm128);
+    tab[iced_x86::Code::Pminub_xmm_xmmm128 as usize] = Some(pminub_xmm_xmmm128);
+    tab[iced_x86::Code::Pmaxub_xmm_xmmm128 as usize] = Some(pmaxub_xmm_xmmm128);
+    tab[iced_x86::Code::Pminsw_xmm_xmmm128 as usize] = Some(pminsw_xmm_xmmm128);
+    tab[iced_x86::Code::Pmaxsw_xmm_xmmm128 as usize] = Some(pmaxsw_xmm_xmmm128);
+    tab[iced_x86::Code::Psadbw_xmm_xmmm128 as usize] = Some(psadbw_xmm_xmmm128);
+    tab[iced_x86::Code::Pcmpeqb_xmm_xmmm128 as usize] = Some(pcmpeqb_xmm_xmmm128);
+    tab[iced_x86::Code::Pcmpeqw_xmm_xmmm128 as usize] = Some(pcmpeqw_xmm_xmmm128);
+    tab[iced_x86::Code::Pcmpeqd_xmm_xmmm128 as usize] = Some(pcmpeqd_xmm_xmmm128);
+    tab[iced_x86::Code::Pcmpgtb_xmm_xmmm128 as usize] = Some(pcmpgtb_xmm_xmmm128);
+    tab[iced_x86::Code::Pcmpgtw_xmm_xmmm128 as usize] = Some(pcmpgtw_xmm_xmmm128);
+    tab[iced_x86::Code::Pcmpgtd_xmm_xmmm128 as usize] = Some(pcmpgtd_xmm_xmmm128);
+    tab[iced_x86::Code::Punpcklbw_xmm_xmmm128 as usize] = Some(punpcklbw_xmm_xmmm128);
+    tab[iced_x86::Code::Punpcklwd_xmm_xmmm128 as usize] = Some(punpcklwd_xmm_xmmm128);
+    tab[iced_x86::Code::Punpckldq_xmm_xmmm128 as usize] = Some(punpckldq_xmm_xmmm128);
+    tab[iced_x86::Code::Punpcklqdq_xmm_xmmm128 as usize] = Some(punpcklqdq_xmm_xmmm128);
+    tab[iced_x86::Code::Punpckhbw_xmm_xmmm128 as usize] = Some(punpckhbw_xmm_xmmm128);
+    tab[iced_x86::Code::Punpckhwd_xmm_xmmm128 as usize] = Some(punpckhwd_xmm_xmmm128);
+    tab[iced_x86::Code::Punpckhdq_xmm_xmmm128 as usize] = Some(punpckhdq_xmm_xmmm128);
+    tab[iced_x86::Code::Punpckhqdq_xmm_xmmm128 as usize] = Some(punpckhqdq_xmm_xmmm128);
+    tab[iced_x86::Code::Packsswb_xmm_xmmm128 as usize] = Some(packsswb_xmm_xmmm128);
+    tab[iced_x86::Code::Packssdw_xmm_xmmm128 as usize] = Some(packssdw_xmm_xmmm128);
+    tab[iced_x86::Code::Packuswb_xmm_xmmm128 as usize] = Some(packuswb_xmm_xmmm128);
+    tab[iced_x86::Code::Pextrw_r32_xmm_imm8 as usize] = Some(pextrw_r32_xmm_imm8);
+    tab[iced_x86::Code::Pinsrw_xmm_r32m16_imm8 as usize] = Some(pinsrw_xmm_r32m16_imm8);
+    tab[iced_x86::Code::Psllw_xmm_imm8 as usize] = Some(psllw_xmm_imm8);
+    tab[iced_x86::Code::Pslld_xmm_imm8 as usize] = Some(pslld_xmm_imm8);
+    tab[iced_x86::Code::Psllq_xmm_imm8 as usize] = Some(psllq_xmm_imm8);
+    tab[iced_x86::Code::Psrlw_xmm_imm8 as usize] = Some(psrlw_xmm_imm8);
+    tab[iced_x86::Code::Psrld_xmm_imm8 as usize] = Some(psrld_xmm_imm8);
+    tab[iced_x86::Code::Psrlq_xmm_imm8 as usize] = Some(psrlq_xmm_imm8);
+    tab[iced_x86::Code::Psraw_xmm_imm8 as usize] = Some(psraw_xmm_imm8);
+    tab[iced_x86::Code::Psrad_xmm_imm8 as usize] = Some(psrad_xmm_imm8);
+    tab[iced_x86::Code::Psllw_xmm_xmmm128 as usize] = Some(psllw_xmm_xmmm128);
+    tab[iced_x86::Code::Pslld_xmm_xmmm128 as usize] = Some(pslld_xmm_xmmm128);
+    tab[iced_x86::Code::Psllq_xmm_xmmm128 as usize] = Some(psllq_xmm_xmmm128);
+    tab[iced_x86::Code::Psrlw_xmm_xmmm128 as usize] = Some(psrlw_xmm_xmmm128);
+    tab[iced_x86::Code::Psrld_xmm_xmmm128 as usize] = Some(psrld_xmm_xmmm128);
+    tab[iced_x86::Code::Psrlq_xmm_xmmm128 as usize] = Some(psrlq_xmm_xmmm128);
+    tab[iced_x86::Code::Psraw_xmm_xmmm128 as usize] = Some(psraw_xmm_xmmm128);
+    tab[iced_x86::Code::Psrad_xmm_xmmm128 as usize] = Some(psrad_xmm_xmmm128);
+    tab[iced_x86::Code::Pslldq_xmm_imm8 as usize] = Some(pslldq_xmm_imm8);
+    tab[iced_x86::Code::Psrldq_xmm_imm8 as usize] = Some(psrldq_xmm_imm8);
+
+    tab[iced_x86::Code::Ldmxcsr_m32 as usize] = Some(ldmxcsr_m32);
+    tab[iced_x86::Code::Stmxcsr_m32 as usize] = Some(stmxcsr_m32);
+    tab[iced_x86::Code::Prefetchnta_m8 as usize] = Some(nop);
+    tab[iced_x86::Code::Prefetcht0_m8 as usize] = Some(nop);
+    tab[iced_x86::Code::Prefetcht1_m8 as usize] = Some(nop);
+    tab[iced_x86::Code::Prefetcht2_m8 as usize] = Some(nop);
+    tab[iced_x86::Code::Sfence as usize] = Some(nop);
+    tab[iced_x86::Code::Lfence as usize] = Some(nop);
+    tab[iced_x86::Code::Mfence as usize] = Some(nop);
+
     tab[iced_x86::Code::Nopd as usize] = Some(nop);
     tab[iced_x86::Code::Nopw as usize] = Some(nop);
     tab[iced_x86::Code::Nop_rm16 as usize] = Some(nop);
     tab[iced_x86::Code::Nop_rm32 as usize] = Some(nop);
 
     tab[iced_x86::Code::Int3 as usize] = Some(int3);
+    tab[iced_x86::Code::Int1 as usize] = Some(int1);
     tab[iced_x86::Code::Sysenter as usize] = Some(sysenter);
+    tab[iced_x86::Code::Rdtsc as usize] = Some(rdtsc);
 
     tab[iced_x86::Code::Bswap_r32 as usize] = Some(bswap_r32);
     tab[iced_x86::Code::Xlat_m8 as usize] = Some(xlat_m8);
+    tab[iced_x86::Code::Bound_r32_m3232 as usize] = Some(bound_r32_m3232);
+    tab[iced_x86::Code::Bound_r16_m1616 as usize] = Some(bound_r16_m1616);
     tab[iced_x86::Code::Bts_rm32_r32 as usize] = Some(bts_rm32_r32);
     tab[iced_x86::Code::Tzcnt_r32_rm32 as usize] = Some(tzcnt_r32_rm32);
 
@@ -505,5 +847,5 @@ const OP_TAB: [Option<Op>; 2553] = {
 };
 
 pub fn decode(instr: &Instruction) -> Option<Op> {
-    OP_TAB[instr.code() as usize]
+    OP_TAB.get(instr.code() as usize).copied().flatten()
 }
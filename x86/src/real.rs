@@ -0,0 +1,778 @@
+//! A minimal real-mode (16-bit) interpreter, enough to run the DOS stub of an executable
+//! or a small DOS loader.
+//!
+//! This is separate from CPU, which only knows flat 32-bit code.  Nothing here is hot,
+//! so it decodes each instruction as it reaches it and computes flags eagerly.
+//!
+//! Interrupts go through the interrupt vector table at address 0.  Vectors the program
+//! hasn't replaced point into BIOS_SEGMENT, and reaching one of those stops execution
+//! so the caller can provide the service.
+
+use crate::registers::{Flags, Registers};
+use iced_x86::{
+    ConditionCode, Decoder, DecoderOptions, Instruction, MemorySize, Mnemonic, OpKind, Register,
+};
+
+/// Segment holding the default interrupt handlers; the handler for interrupt n is at
+/// offset 4*n, and is a `retf 2` that runs after the caller has provided the service.
+/// Returning with `retf 2` rather than `iret` keeps the flags the service set, e.g. CF
+/// for DOS errors.
+pub const BIOS_SEGMENT: u16 = 0xF000;
+
+/// Size of the real-mode address space.
+const MEM_SIZE: usize = 1 << 20;
+
+pub struct RealModeCPU {
+    /// Registers; eip holds the 16-bit IP.
+    pub regs: Registers,
+    pub flags: Flags,
+    pub mem: Vec<u8>,
+}
+
+impl RealModeCPU {
+    pub fn new() -> Self {
+        let mut cpu = RealModeCPU {
+            regs: Registers::default(),
+            flags: Flags::empty(),
+            mem: vec![0; MEM_SIZE],
+        };
+        for seg in [
+            Register::ES,
+            Register::CS,
+            Register::SS,
+            Register::DS,
+            Register::FS,
+            Register::GS,
+        ] {
+            cpu.regs.set16(seg, 0);
+        }
+        for n in 0..=0xFFu16 {
+            cpu.put16(0, n * 4, n * 4);
+            cpu.put16(0, n * 4 + 2, BIOS_SEGMENT);
+            let handler = linear(BIOS_SEGMENT, n * 4);
+            cpu.mem[handler..][..4].copy_from_slice(&[0xCA, 0x02, 0x00, 0x90]); // retf 2; nop
+        }
+        cpu
+    }
+
+    pub fn get8(&self, seg: u16, off: u16) -> u8 {
+        self.mem[linear(seg, off)]
+    }
+
+    pub fn get16(&self, seg: u16, off: u16) -> u16 {
+        u16::from_le_bytes([self.get8(seg, off), self.get8(seg, off.wrapping_add(1))])
+    }
+
+    pub fn put8(&mut self, seg: u16, off: u16, value: u8) {
+        self.mem[linear(seg, off)] = value;
+    }
+
+    pub fn put16(&mut self, seg: u16, off: u16, value: u16) {
+        let [lo, hi] = value.to_le_bytes();
+        self.put8(seg, off, lo);
+        self.put8(seg, off.wrapping_add(1), hi);
+    }
+
+    /// The len bytes at seg:off, clipped to the end of memory.
+    pub fn slice_mut(&mut self, seg: u16, off: u16, len: usize) -> &mut [u8] {
+        let start = linear(seg, off);
+        let end = (start + len).min(MEM_SIZE);
+        &mut self.mem[start..end]
+    }
+
+    pub fn push16(&mut self, value: u16) {
+        let sp = self.regs.get16(Register::SP).wrapping_sub(2);
+        self.regs.set16(Register::SP, sp);
+        self.put16(self.regs.get16(Register::SS), sp, value);
+    }
+
+    pub fn pop16(&mut self) -> u16 {
+        let sp = self.regs.get16(Register::SP);
+        let value = self.get16(self.regs.get16(Register::SS), sp);
+        self.regs.set16(Register::SP, sp.wrapping_add(2));
+        value
+    }
+
+    /// Run until execution reaches a default interrupt handler, returning the interrupt
+    /// number.  Calling run() again then returns from the interrupt.
+    pub fn run(&mut self) -> Result<u8, String> {
+        let mut first = true;
+        loop {
+            let cs = self.regs.get16(Register::CS);
+            let ip = self.regs.eip as u16;
+            if cs == BIOS_SEGMENT && ip & 3 == 0 && ip < 0x400 && !first {
+                return Ok((ip / 4) as u8);
+            }
+            first = false;
+
+            let addr = linear(cs, ip);
+            let code = &self.mem[addr..(addr + 16).min(MEM_SIZE)];
+            let instr = Decoder::with_ip(16, code, ip as u64, DecoderOptions::NONE).decode();
+            if instr.is_invalid() {
+                return Err(format!("{cs:04x}:{ip:04x}: invalid instruction"));
+            }
+            self.regs.eip = instr.next_ip() as u16 as u32;
+            if let Err(err) = self.execute(&instr) {
+                self.regs.eip = ip as u32;
+                return Err(format!("{cs:04x}:{ip:04x} {instr}: {err}"));
+            }
+        }
+    }
+
+    /// Enter an interrupt handler, as the int instruction does.
+    pub fn interrupt(&mut self, n: u8) {
+        self.push16(self.flags.bits() as u16 | 0x0202);
+        self.push16(self.regs.get16(Register::CS));
+        self.push16(self.regs.eip as u16);
+        self.flags.remove(Flags::TF);
+        let off = self.get16(0, n as u16 * 4);
+        let seg = self.get16(0, n as u16 * 4 + 2);
+        self.jmp_far(seg, off);
+    }
+
+    fn jmp_far(&mut self, seg: u16, off: u16) {
+        self.regs.set16(Register::CS, seg);
+        self.regs.eip = off as u32;
+    }
+
+    /// The offset of a memory operand, without its segment.
+    fn offset(&self, instr: &Instruction) -> u16 {
+        let mut off = instr.memory_displacement32() as u16;
+        if instr.memory_base() != Register::None {
+            off = off.wrapping_add(self.regs.get16(instr.memory_base()));
+        }
+        if instr.memory_index() != Register::None {
+            off = off.wrapping_add(self.regs.get16(instr.memory_index()));
+        }
+        off
+    }
+
+    /// The segment and offset of a memory operand.
+    fn mem_operand(&self, instr: &Instruction) -> (u16, u16) {
+        (self.regs.get16(instr.memory_segment()), self.offset(instr))
+    }
+
+    /// The width of operand n in bits.
+    fn bits(&self, instr: &Instruction, n: u32) -> u32 {
+        match instr.op_kind(n) {
+            OpKind::Register => instr.op_register(n).size() as u32 * 8,
+            OpKind::Immediate8 => 8,
+            _ if instr.memory_size() == MemorySize::UInt8
+                || instr.memory_size() == MemorySize::Int8 =>
+            {
+                8
+            }
+            _ => 16,
+        }
+    }
+
+    fn read(&self, instr: &Instruction, n: u32) -> Result<u32, String> {
+        Ok(match instr.op_kind(n) {
+            OpKind::Register => {
+                let reg = instr.op_register(n);
+                if reg.is_gpr8() {
+                    self.regs.get8(reg) as u32
+                } else {
+                    self.regs.get16(reg) as u32
+                }
+            }
+            OpKind::Memory => {
+                let (seg, off) = self.mem_operand(instr);
+                match self.bits(instr, n) {
+                    8 => self.get8(seg, off) as u32,
+                    _ => self.get16(seg, off) as u32,
+                }
+            }
+            OpKind::Immediate8 | OpKind::Immediate16 | OpKind::Immediate8to16 => {
+                instr.immediate(n) as u16 as u32
+            }
+            kind => return Err(format!("unsupported operand {kind:?}")),
+        })
+    }
+
+    fn write(&mut self, instr: &Instruction, n: u32, value: u32) -> Result<(), String> {
+        match instr.op_kind(n) {
+            OpKind::Register => {
+                let reg = instr.op_register(n);
+                if reg.is_gpr8() {
+                    self.regs.set8(reg, value as u8);
+                } else {
+                    self.regs.set16(reg, value as u16);
+                }
+            }
+            OpKind::Memory => {
+                let (seg, off) = self.mem_operand(instr);
+                match self.bits(instr, n) {
+                    8 => self.put8(seg, off, value as u8),
+                    _ => self.put16(seg, off, value as u16),
+                }
+            }
+            kind => return Err(format!("unsupported operand {kind:?}")),
+        }
+        Ok(())
+    }
+
+    /// Read a far pointer, as segment and offset, from a memory operand.
+    fn read_far(&self, instr: &Instruction) -> (u16, u16) {
+        let (seg, off) = self.mem_operand(instr);
+        (self.get16(seg, off.wrapping_add(2)), self.get16(seg, off))
+    }
+
+    /// Set ZF, SF and PF from a result.
+    fn set_result_flags(&mut self, result: u32, bits: u32) {
+        self.flags.set(Flags::ZF, result == 0);
+        self.flags.set(Flags::SF, (result >> (bits - 1)) & 1 != 0);
+        self.flags
+            .set(Flags::PF, (result as u8).count_ones() & 1 == 0);
+    }
+
+    /// Compute an arithmetic or logic op, setting flags.
+    fn arith(&mut self, op: Mnemonic, x: u32, y: u32, bits: u32) -> u32 {
+        let mask = (1u32 << bits) - 1;
+        let sign = 1u32 << (bits - 1);
+        let carry = self.flags.contains(Flags::CF) as u32;
+        let result = match op {
+            Mnemonic::Add | Mnemonic::Adc => {
+                let c = if op == Mnemonic::Adc { carry } else { 0 };
+                let result = x + y + c;
+                self.flags.set(Flags::CF, result > mask);
+                self.flags
+                    .set(Flags::OF, (x ^ result) & (y ^ result) & sign != 0);
+                result
+            }
+            Mnemonic::Sub | Mnemonic::Sbb | Mnemonic::Cmp => {
+                let c = if op == Mnemonic::Sbb { carry } else { 0 };
+                let result = x.wrapping_sub(y).wrapping_sub(c);
+                self.flags.set(Flags::CF, x < y + c);
+                self.flags
+                    .set(Flags::OF, (x ^ y) & (x ^ result) & sign != 0);
+                result
+            }
+            _ => {
+                self.flags.remove(Flags::CF | Flags::OF);
+                match op {
+                    Mnemonic::And | Mnemonic::Test => x & y,
+                    Mnemonic::Or => x | y,
+                    Mnemonic::Xor => x ^ y,
+                    _ => unreachable!("{op:?}"),
+                }
+            }
+        } & mask;
+        self.set_result_flags(result, bits);
+        result
+    }
+
+    fn shift(&mut self, op: Mnemonic, x: u32, count: u32, bits: u32) -> u32 {
+        let mask = (1u32 << bits) - 1;
+        let msb = |v: u32| (v >> (bits - 1)) & 1 != 0;
+        let result = match op {
+            Mnemonic::Shl | Mnemonic::Sal => {
+                let result = (x << count) & mask;
+                self.flags
+                    .set(Flags::CF, count <= bits && (x >> (bits - count)) & 1 != 0);
+                self.flags
+                    .set(Flags::OF, msb(result) != self.flags.contains(Flags::CF));
+                result
+            }
+            Mnemonic::Shr => {
+                self.flags
+                    .set(Flags::CF, count <= bits && (x >> (count - 1)) & 1 != 0);
+                self.flags.set(Flags::OF, msb(x));
+                x.checked_shr(count).unwrap_or(0)
+            }
+            Mnemonic::Sar => {
+                let signed = ((x << (32 - bits)) as i32) >> (32 - bits);
+                let count = count.min(bits);
+                self.flags.set(Flags::CF, (signed >> (count - 1)) & 1 != 0);
+                self.flags.remove(Flags::OF);
+                (signed >> count) as u32 & mask
+            }
+            Mnemonic::Rol | Mnemonic::Ror => {
+                let count = count % bits;
+                let result = if count == 0 {
+                    x
+                } else if op == Mnemonic::Rol {
+                    ((x << count) | (x >> (bits - count))) & mask
+                } else {
+                    ((x >> count) | (x << (bits - count))) & mask
+                };
+                let cf = if op == Mnemonic::Rol {
+                    result & 1 != 0
+                } else {
+                    msb(result)
+                };
+                self.flags.set(Flags::CF, cf);
+                // Rotates leave the other flags alone.
+                return result;
+            }
+            _ => unreachable!("{op:?}"),
+        };
+        self.set_result_flags(result, bits);
+        result
+    }
+
+    fn condition(&self, cc: ConditionCode) -> bool {
+        let f = |flag| self.flags.contains(flag);
+        match cc {
+            ConditionCode::None => true,
+            ConditionCode::o => f(Flags::OF),
+            ConditionCode::no => !f(Flags::OF),
+            ConditionCode::b => f(Flags::CF),
+            ConditionCode::ae => !f(Flags::CF),
+            ConditionCode::e => f(Flags::ZF),
+            ConditionCode::ne => !f(Flags::ZF),
+            ConditionCode::be => f(Flags::CF) || f(Flags::ZF),
+            ConditionCode::a => !f(Flags::CF) && !f(Flags::ZF),
+            ConditionCode::s => f(Flags::SF),
+            ConditionCode::ns => !f(Flags::SF),
+            ConditionCode::p => f(Flags::PF),
+            ConditionCode::np => !f(Flags::PF),
+            ConditionCode::l => f(Flags::SF) != f(Flags::OF),
+            ConditionCode::ge => f(Flags::SF) == f(Flags::OF),
+            ConditionCode::le => f(Flags::ZF) || f(Flags::SF) != f(Flags::OF),
+            ConditionCode::g => !f(Flags::ZF) && f(Flags::SF) == f(Flags::OF),
+        }
+    }
+
+    /// The target of a jmp or call, as segment and offset.
+    fn branch_target(&self, instr: &Instruction) -> Result<(u16, u16), String> {
+        let cs = self.regs.get16(Register::CS);
+        Ok(match instr.op0_kind() {
+            OpKind::NearBranch16 => (cs, instr.near_branch16()),
+            OpKind::FarBranch16 => (instr.far_branch_selector(), instr.far_branch16()),
+            OpKind::Memory if instr.memory_size() == MemorySize::SegPtr16 => self.read_far(instr),
+            _ => (cs, self.read(instr, 0)? as u16),
+        })
+    }
+
+    fn divide_error(&mut self, instr: &Instruction) {
+        // #DE is a fault, so the handler sees the ip of the div.
+        self.regs.eip = instr.ip() as u32;
+        self.interrupt(0);
+    }
+
+    fn string_op(&mut self, instr: &Instruction) {
+        use Register::{AL, AX, CX, DI, ES, SI};
+        let op = instr.mnemonic();
+        let bits = match op {
+            Mnemonic::Lodsb
+            | Mnemonic::Stosb
+            | Mnemonic::Movsb
+            | Mnemonic::Cmpsb
+            | Mnemonic::Scasb => 8,
+            _ => 16,
+        };
+        let step = if self.flags.contains(Flags::DF) {
+            ((bits / 8) as u16).wrapping_neg()
+        } else {
+            (bits / 8) as u16
+        };
+        let rep = instr.has_rep_prefix() || instr.has_repne_prefix();
+        // The DS:SI operand, whose segment may be overridden; unused by stos and scas.
+        let src_seg = self.regs.get16(instr.memory_segment());
+        let es = self.regs.get16(ES);
+        let load = |cpu: &Self, seg: u16, off: u16| match bits {
+            8 => cpu.get8(seg, off) as u32,
+            _ => cpu.get16(seg, off) as u32,
+        };
+        let acc = if bits == 8 { AL } else { AX };
+        loop {
+            if rep && self.regs.get16(CX) == 0 {
+                break;
+            }
+            let (si, di) = (self.regs.get16(SI), self.regs.get16(DI));
+            match op {
+                Mnemonic::Lodsb | Mnemonic::Lodsw => {
+                    let value = load(self, src_seg, si);
+                    self.write_acc(acc, value);
+                    self.regs.set16(SI, si.wrapping_add(step));
+                }
+                Mnemonic::Stosb | Mnemonic::Stosw => {
+                    let value = self.read_acc(acc);
+                    self.store(bits, es, di, value);
+                    self.regs.set16(DI, di.wrapping_add(step));
+                }
+                Mnemonic::Movsb | Mnemonic::Movsw => {
+                    let value = load(self, src_seg, si);
+                    self.store(bits, es, di, value);
+                    self.regs.set16(SI, si.wrapping_add(step));
+                    self.regs.set16(DI, di.wrapping_add(step));
+                }
+                Mnemonic::Cmpsb | Mnemonic::Cmpsw => {
+                    let (x, y) = (load(self, src_seg, si), load(self, es, di));
+                    self.arith(Mnemonic::Cmp, x, y, bits);
+                    self.regs.set16(SI, si.wrapping_add(step));
+                    self.regs.set16(DI, di.wrapping_add(step));
+                }
+                Mnemonic::Scasb | Mnemonic::Scasw => {
+                    let (x, y) = (self.read_acc(acc), load(self, es, di));
+                    self.arith(Mnemonic::Cmp, x, y, bits);
+                    self.regs.set16(DI, di.wrapping_add(step));
+                }
+                _ => unreachable!("{op:?}"),
+            }
+            if !rep {
+                break;
+            }
+            self.regs.set16(CX, self.regs.get16(CX).wrapping_sub(1));
+            if matches!(
+                op,
+                Mnemonic::Cmpsb | Mnemonic::Cmpsw | Mnemonic::Scasb | Mnemonic::Scasw
+            ) {
+                let zf = self.flags.contains(Flags::ZF);
+                if (instr.has_repe_prefix() && !zf) || (instr.has_repne_prefix() && zf) {
+                    break;
+                }
+            }
+        }
+    }
+
+    fn read_acc(&self, reg: Register) -> u32 {
+        match reg {
+            Register::AL => self.regs.get8(reg) as u32,
+            _ => self.regs.get16(reg) as u32,
+        }
+    }
+
+    fn write_acc(&mut self, reg: Register, value: u32) {
+        match reg {
+            Register::AL => self.regs.set8(reg, value as u8),
+            _ => self.regs.set16(reg, value as u16),
+        }
+    }
+
+    fn store(&mut self, bits: u32, seg: u16, off: u16, value: u32) {
+        match bits {
+            8 => self.put8(seg, off, value as u8),
+            _ => self.put16(seg, off, value as u16),
+        }
+    }
+
+    fn execute(&mut self, instr: &Instruction) -> Result<(), String> {
+        use Register::{AH, AL, AX, CS, CX, DS, DX, ES, SP};
+        if instr.is_jcc_short_or_near() {
+            if self.condition(instr.condition_code()) {
+                self.regs.eip = instr.near_branch16() as u32;
+            }
+            return Ok(());
+        }
+        let op = instr.mnemonic();
+        match op {
+            Mnemonic::Nop => {}
+            Mnemonic::Mov => {
+                let value = self.read(instr, 1)?;
+                self.write(instr, 0, value)?;
+            }
+            Mnemonic::Add
+            | Mnemonic::Adc
+            | Mnemonic::Sub
+            | Mnemonic::Sbb
+            | Mnemonic::Cmp
+            | Mnemonic::And
+            | Mnemonic::Or
+            | Mnemonic::Xor
+            | Mnemonic::Test => {
+                let bits = self.bits(instr, 0);
+                let (x, y) = (self.read(instr, 0)?, self.read(instr, 1)?);
+                let result = self.arith(op, x, y, bits);
+                if !matches!(op, Mnemonic::Cmp | Mnemonic::Test) {
+                    self.write(instr, 0, result)?;
+                }
+            }
+            Mnemonic::Inc | Mnemonic::Dec => {
+                let bits = self.bits(instr, 0);
+                let x = self.read(instr, 0)?;
+                let cf = self.flags.contains(Flags::CF);
+                let op = if op == Mnemonic::Inc {
+                    Mnemonic::Add
+                } else {
+                    Mnemonic::Sub
+                };
+                let result = self.arith(op, x, 1, bits);
+                self.flags.set(Flags::CF, cf);
+                self.write(instr, 0, result)?;
+            }
+            Mnemonic::Neg => {
+                let bits = self.bits(instr, 0);
+                let x = self.read(instr, 0)?;
+                let result = self.arith(Mnemonic::Sub, 0, x, bits);
+                self.write(instr, 0, result)?;
+            }
+            Mnemonic::Not => {
+                let x = self.read(instr, 0)?;
+                self.write(instr, 0, !x)?;
+            }
+            Mnemonic::Shl
+            | Mnemonic::Sal
+            | Mnemonic::Shr
+            | Mnemonic::Sar
+            | Mnemonic::Rol
+            | Mnemonic::Ror => {
+                let bits = self.bits(instr, 0);
+                let count = self.read(instr, 1)? & 0x1F;
+                if count != 0 {
+                    let x = self.read(instr, 0)?;
+                    let result = self.shift(op, x, count, bits);
+                    self.write(instr, 0, result)?;
+                }
+            }
+            Mnemonic::Mul | Mnemonic::Imul if instr.op_count() == 1 => {
+                let signed = op == Mnemonic::Imul;
+                let x = self.read(instr, 0)?;
+                let overflow = if self.bits(instr, 0) == 8 {
+                    let al = self.regs.get8(AL);
+                    let result = if signed {
+                        (al as i8 as i16 * x as u8 as i8 as i16) as u16
+                    } else {
+                        al as u16 * x as u16
+                    };
+                    self.regs.set16(AX, result);
+                    if signed {
+                        result as i16 != result as i8 as i16
+                    } else {
+                        result >> 8 != 0
+                    }
+                } else {
+                    let ax = self.regs.get16(AX);
+                    let result = if signed {
+                        (ax as i16 as i32 * x as u16 as i16 as i32) as u32
+                    } else {
+                        ax as u32 * x
+                    };
+                    self.regs.set16(AX, result as u16);
+                    self.regs.set16(DX, (result >> 16) as u16);
+                    if signed {
+                        result as i32 != result as i16 as i32
+                    } else {
+                        result >> 16 != 0
+                    }
+                };
+                self.flags.set(Flags::CF | Flags::OF, overflow);
+            }
+            Mnemonic::Imul => {
+                let x = self.read(instr, 1)? as u16 as i16 as i32;
+                let y = match instr.op_count() {
+                    3 => self.read(instr, 2)?,
+                    _ => self.read(instr, 0)?,
+                } as u16 as i16 as i32;
+                let result = x * y;
+                self.flags
+                    .set(Flags::CF | Flags::OF, result != result as i16 as i32);
+                self.write(instr, 0, result as u32)?;
+            }
+            Mnemonic::Div | Mnemonic::Idiv => {
+                let signed = op == Mnemonic::Idiv;
+                let y = self.read(instr, 0)?;
+                if self.bits(instr, 0) == 8 {
+                    let x = self.regs.get16(AX);
+                    let result = if signed {
+                        let (x, y) = (x as i16 as i32, y as u8 as i8 as i32);
+                        match (x.checked_div(y), x.checked_rem(y)) {
+                            (Some(q), Some(r)) if q == q as i8 as i32 => Some((q as u8, r as u8)),
+                            _ => None,
+                        }
+                    } else {
+                        let (x, y) = (x as u32, y);
+                        match (x.checked_div(y), x.checked_rem(y)) {
+                            (Some(q), Some(r)) if q <= 0xFF => Some((q as u8, r as u8)),
+                            _ => None,
+                        }
+                    };
+                    match result {
+                        Some((q, r)) => {
+                            self.regs.set8(AL, q);
+                            self.regs.set8(AH, r);
+                        }
+                        None => self.divide_error(instr),
+                    }
+                } else {
+                    let x = (self.regs.get16(DX) as u32) << 16 | self.regs.get16(AX) as u32;
+                    let result = if signed {
+                        let (x, y) = (x as i32 as i64, y as u16 as i16 as i64);
+                        match (x.checked_div(y), x.checked_rem(y)) {
+                            (Some(q), Some(r)) if q == q as i16 as i64 => {
+                                Some((q as u16, r as u16))
+                            }
+                            _ => None,
+                        }
+                    } else {
+                        match (x.checked_div(y), x.checked_rem(y)) {
+                            (Some(q), Some(r)) if q <= 0xFFFF => Some((q as u16, r as u16)),
+                            _ => None,
+                        }
+                    };
+                    match result {
+                        Some((q, r)) => {
+                            self.regs.set16(AX, q);
+                            self.regs.set16(DX, r);
+                        }
+                        None => self.divide_error(instr),
+                    }
+                }
+            }
+            Mnemonic::Cbw => {
+                self.regs.set16(AX, self.regs.get8(AL) as i8 as i16 as u16);
+            }
+            Mnemonic::Cwd => {
+                let sign = (self.regs.get16(AX) as i16) < 0;
+                self.regs.set16(DX, if sign { 0xFFFF } else { 0 });
+            }
+            Mnemonic::Xchg => {
+                let (x, y) = (self.read(instr, 0)?, self.read(instr, 1)?);
+                self.write(instr, 0, y)?;
+                self.write(instr, 1, x)?;
+            }
+            Mnemonic::Lea => {
+                let off = self.offset(instr);
+                self.write(instr, 0, off as u32)?;
+            }
+            Mnemonic::Lds | Mnemonic::Les => {
+                let (seg, off) = self.read_far(instr);
+                self.write(instr, 0, off as u32)?;
+                self.regs
+                    .set16(if op == Mnemonic::Lds { DS } else { ES }, seg);
+            }
+            Mnemonic::Push => {
+                let value = self.read(instr, 0)?;
+                self.push16(value as u16);
+            }
+            Mnemonic::Pop => {
+                let value = self.pop16();
+                self.write(instr, 0, value as u32)?;
+            }
+            Mnemonic::Pushf => {
+                self.push16(self.flags.bits() as u16 | 0x0202);
+            }
+            Mnemonic::Popf => {
+                let value = self.pop16();
+                self.flags = Flags::from_bits_truncate(value as u32);
+            }
+            Mnemonic::Clc => self.flags.remove(Flags::CF),
+            Mnemonic::Stc => self.flags.insert(Flags::CF),
+            Mnemonic::Cmc => self.flags.toggle(Flags::CF),
+            Mnemonic::Cld => self.flags.remove(Flags::DF),
+            Mnemonic::Std => self.flags.insert(Flags::DF),
+            // Interrupts from hardware never arrive, so the interrupt flag doesn't matter.
+            Mnemonic::Cli | Mnemonic::Sti => {}
+            Mnemonic::Jmp => {
+                let (seg, off) = self.branch_target(instr)?;
+                self.jmp_far(seg, off);
+            }
+            Mnemonic::Call => {
+                let (seg, off) = self.branch_target(instr)?;
+                let far = matches!(instr.op0_kind(), OpKind::FarBranch16)
+                    || instr.memory_size() == MemorySize::SegPtr16;
+                if far {
+                    self.push16(self.regs.get16(CS));
+                }
+                self.push16(self.regs.eip as u16);
+                self.jmp_far(seg, off);
+            }
+            Mnemonic::Ret | Mnemonic::Retf => {
+                let off = self.pop16();
+                if op == Mnemonic::Retf {
+                    let seg = self.pop16();
+                    self.regs.set16(CS, seg);
+                }
+                self.regs.eip = off as u32;
+                if instr.op_count() == 1 {
+                    let sp = self.regs.get16(SP).wrapping_add(instr.immediate16());
+                    self.regs.set16(SP, sp);
+                }
+            }
+            Mnemonic::Iret => {
+                let off = self.pop16();
+                let seg = self.pop16();
+                let flags = self.pop16();
+                self.jmp_far(seg, off);
+                self.flags = Flags::from_bits_truncate(flags as u32);
+            }
+            Mnemonic::Loop | Mnemonic::Loope | Mnemonic::Loopne => {
+                let cx = self.regs.get16(CX).wrapping_sub(1);
+                self.regs.set16(CX, cx);
+                let zf = self.flags.contains(Flags::ZF);
+                let taken = cx != 0
+                    && match op {
+                        Mnemonic::Loope => zf,
+                        Mnemonic::Loopne => !zf,
+                        _ => true,
+                    };
+                if taken {
+                    self.regs.eip = instr.near_branch16() as u32;
+                }
+            }
+            Mnemonic::Jcxz => {
+                if self.regs.get16(CX) == 0 {
+                    self.regs.eip = instr.near_branch16() as u32;
+                }
+            }
+            Mnemonic::Int => self.interrupt(instr.immediate8()),
+            Mnemonic::Int3 => self.interrupt(3),
+            Mnemonic::Lodsb
+            | Mnemonic::Lodsw
+            | Mnemonic::Stosb
+            | Mnemonic::Stosw
+            | Mnemonic::Movsb
+            | Mnemonic::Movsw
+            | Mnemonic::Cmpsb
+            | Mnemonic::Cmpsw
+            | Mnemonic::Scasb
+            | Mnemonic::Scasw => self.string_op(instr),
+            _ => return Err("unimplemented".into()),
+        }
+        Ok(())
+    }
+}
+
+impl Default for RealModeCPU {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The linear address of seg:off, wrapping at 1MB as with the A20 line disabled.
+pub fn linear(seg: u16, off: u16) -> usize {
+    (((seg as usize) << 4) + off as usize) & (MEM_SIZE - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_until_interrupt() {
+        let mut cpu = RealModeCPU::new();
+        #[rustfmt::skip]
+        let code = [
+            0xB8, 0x34, 0x12,       // mov ax, 1234h
+            0xBB, 0x10, 0x00,       // mov bx, 0010h
+            0x01, 0xD8,             // add ax, bx
+            0x50,                   // push ax
+            0x59,                   // pop cx
+            0xCD, 0x21,             // int 21h
+            0x83, 0xD1, 0x00,       // adc cx, 0
+            0xCD, 0x20,             // int 20h
+        ];
+        cpu.slice_mut(0x100, 0x100, code.len())
+            .copy_from_slice(&code);
+        cpu.regs.set16(Register::CS, 0x100);
+        cpu.regs.set16(Register::SS, 0x100);
+        cpu.regs.set16(Register::SP, 0xFFFE);
+        cpu.regs.eip = 0x100;
+
+        assert_eq!(cpu.run(), Ok(0x21));
+        assert_eq!(cpu.regs.get16(Register::CX), 0x1244);
+        // The handler's result flags survive its return, as DOS calls rely on.
+        cpu.flags.insert(Flags::CF);
+        assert_eq!(cpu.run(), Ok(0x20));
+        assert_eq!(cpu.regs.get16(Register::CX), 0x1245);
+        // Only the int 20h frame is left on the stack.
+        assert_eq!(cpu.regs.get16(Register::SP), 0xFFFE - 6);
+    }
+
+    #[test]
+    fn linear_wraps_at_1mb() {
+        assert_eq!(linear(0x1234, 0x0010), 0x12350);
+        assert_eq!(linear(0xFFFF, 0x0010), 0);
+    }
+}
@@ -2,23 +2,38 @@ use bitflags::bitflags;
 use iced_x86::Register::{self, *};
 
 bitflags! {
+    #[derive(serde::Serialize, serde::Deserialize)]
     pub struct Flags: u32 {
         /// carry
         const CF = 1 << 0;
+        /// parity
+        const PF = 1 << 2;
+        /// auxiliary carry, out of the low nibble
+        const AF = 1 << 4;
         /// zero
         const ZF = 1 << 6;
         /// sign
         const SF = 1 << 7;
+        /// trap: raise a single-step exception after each instruction
+        const TF = 1 << 8;
         /// direction
         const DF = 1 << 10;
         /// overflow
         const OF = 1 << 11;
+        /// resume: skip instruction breakpoints for the next instruction
+        const RF = 1 << 16;
         /// cpuid
         const ID = 1 << 21;
     }
 }
 
-#[derive(Default)]
+/// Selectors Windows sets up for a 32-bit process.  All but the TEB selector are flat,
+/// based at 0; the low two bits of each are the requested privilege level.
+pub const CODE_SELECTOR: u16 = 0x1b;
+pub const DATA_SELECTOR: u16 = 0x23;
+pub const TEB_SELECTOR: u16 = 0x3b;
+
+#[derive(serde::Serialize, serde::Deserialize)]
 pub struct Registers {
     /// 32-bit registers, in order:
     ///   eax ecx edx ebx esp ebp esi edi,
@@ -29,7 +44,7 @@ pub struct Registers {
     // llvm doesn't seem to optimize it to the obvious math.
     // I tried the equivalent in C++ and that didn't optimize either.
     // So instead we represent these as an array internally.
-    r32: [u32; 8],
+    pub(crate) r32: [u32; 8],
 
     pub eip: u32,
 
@@ -37,15 +52,17 @@ pub struct Registers {
     ///   es cs ss ds fs gs
     segment: [u16; 6],
 
-    // TODO: segment registers are actually 16-bit indexes into the GDT/LDT,
-    // but for our purposes all we ever care about is making FS-relative accesses point
-    // at the Windows TEB.
-    /// Address that FS-relative accesses point to.
+    /// Base address of the segment TEB_SELECTOR selects, which is the thread's TEB.
+    // Segment registers are really indexes into the GDT/LDT, but we only model the
+    // selectors Windows provides, where every other segment is flat.
     pub fs_addr: u32,
 
     /// MMX registers.
     // TODO: officially these should alias the FPU registers(!).
     mm: [u64; 8],
+
+    /// SSE registers.
+    xmm: [u128; 8],
 }
 
 #[allow(dead_code)]
@@ -83,6 +100,14 @@ const fn assert_enums_as_expected() {
     assert!(MM5 as u8 == MM0 as u8 + 5);
     assert!(MM6 as u8 == MM0 as u8 + 6);
     assert!(MM7 as u8 == MM0 as u8 + 7);
+
+    assert!(XMM1 as u8 == XMM0 as u8 + 1);
+    assert!(XMM2 as u8 == XMM0 as u8 + 2);
+    assert!(XMM3 as u8 == XMM0 as u8 + 3);
+    assert!(XMM4 as u8 == XMM0 as u8 + 4);
+    assert!(XMM5 as u8 == XMM0 as u8 + 5);
+    assert!(XMM6 as u8 == XMM0 as u8 + 6);
+    assert!(XMM7 as u8 == XMM0 as u8 + 7);
 }
 const _: () = assert_enums_as_expected();
 
@@ -104,7 +129,37 @@ fn r8h_to_32(r8: Register) -> Register {
     unsafe { std::mem::transmute((r8 as u8 - AH as u8) + EAX as u8) }
 }
 
+impl Default for Registers {
+    fn default() -> Self {
+        Registers {
+            r32: [0; 8],
+            eip: 0,
+            segment: [
+                DATA_SELECTOR,
+                CODE_SELECTOR,
+                DATA_SELECTOR,
+                DATA_SELECTOR,
+                TEB_SELECTOR,
+                0,
+            ],
+            fs_addr: 0,
+            mm: [0; 8],
+            xmm: [0; 8],
+        }
+    }
+}
+
 impl Registers {
+    /// The base address of the segment currently loaded in a segment register.
+    #[inline]
+    pub fn segment_base(&self, reg: Register) -> u32 {
+        if self.get16(reg) & !3 == TEB_SELECTOR & !3 {
+            self.fs_addr
+        } else {
+            0
+        }
+    }
+
     pub fn get32_mut(&mut self, reg: Register) -> &mut u32 {
         let idx = reg as usize - Register::EAX as usize;
         // See check in assert_enums_as_expected() -- the registers we can fetch are always < 8.
@@ -175,7 +230,7 @@ impl Registers {
 
     pub fn set16(&mut self, reg: Register, value: u16) {
         match reg {
-            AX | CX | DX | BX | SI | DI | BP => {
+            AX | CX | DX | BX | SP | BP | SI | DI => {
                 let r32 = r16_to_32(reg);
                 self.set32(r32, (self.get32(r32) & 0xFFFF_0000) | value as u32);
             }
@@ -216,4 +271,20 @@ impl Registers {
         }
         self.mm[index] = value;
     }
+
+    pub fn get128(&self, reg: Register) -> u128 {
+        let index = reg as usize - XMM0 as usize;
+        if index >= 8 {
+            unreachable!("{reg:?}");
+        }
+        self.xmm[index]
+    }
+
+    pub fn set128(&mut self, reg: Register, value: u128) {
+        let index = reg as usize - XMM0 as usize;
+        if index >= 8 {
+            unreachable!("{reg:?}");
+        }
+        self.xmm[index] = value;
+    }
 }
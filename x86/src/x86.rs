@@ -1,24 +1,59 @@
 //! The central x86 machine object.
 
 use crate::{
+    backend::{Backend, Interpreter},
+    debugregs::{self, DebugRegs, HwBreakpoint, DR6_BS},
+    flags::LazyFlags,
     fpu::FPU,
-    icache::{BasicBlock, InstrCache},
-    ops,
+    icache::{BasicBlock, Op},
+    ops::{self, CpuProfile},
     registers::{Flags, Registers},
     Register,
 };
-use memory::Mem;
+use memory::{Mem, PAGE_TRAP_CODE, PAGE_TRAP_WATCH};
 use std::future::Future;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 
-#[derive(Debug, Default, PartialEq, Eq)]
+/// A processor exception caused by the guest code, which the OS delivers back to the guest
+/// (as opposed to CPUState::Error, which is a failure of the emulator).
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Fault {
+    /// #DE, from division by zero or (with overflow) a quotient that doesn't fit.
+    DivideError { overflow: bool },
+    /// #UD, from an undefined opcode.
+    InvalidOpcode,
+    /// #BR, from bound with an index outside the bounds.
+    BoundRange,
+    /// #PF, from an access to memory that isn't mapped, or a write to a read-only page.
+    AccessViolation { addr: u32, write: bool },
+    /// #GP, e.g. from loading a segment register with a selector that doesn't exist.
+    GeneralProtection,
+    /// #PF, from the first access to a guard page.
+    GuardPage { addr: u32, write: bool },
+    /// #MF, from an FPU instruction or WAIT with an unmasked FPU exception pending from an
+    /// earlier instruction; fpu.status says which.
+    FloatingPoint,
+    /// #BP, from int3.
+    Breakpoint,
+    /// #DB, after an instruction executed with TF set, from ICEBP, or from a breakpoint in
+    /// the debug registers, with DR6 saying which.  Unlike the others this is a trap, with
+    /// eip pointing after the instruction, except for instruction breakpoints.
+    SingleStep,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum CPUState {
     #[default]
     Running,
     Blocked(Option<u32>),
     DebugBreak,
     SysCall,
+    /// RDTSC executed; the machine loads EDX:EAX from its clock and resumes.
+    ReadTsc,
+    /// The instruction at eip faulted, without having had any effect,
+    /// or for a trap, the instruction before eip completed.
+    Fault(Fault),
     Error(String),
     Free,
 }
@@ -29,34 +64,65 @@ impl CPUState {
     }
 }
 
+/// MXCSR after reset: all exceptions masked, round to nearest.
+pub const MXCSR_DEFAULT: u32 = 0x1F80;
+
 /// When eip==MAGIC_ADDR, the CPU executes futures (async tasks) rather than x86 code.
 const MAGIC_ADDR: u32 = 0xFFFF_FFF0;
 
 // Similar to futures::future::BoxFuture, but 'static + !Send.
 pub type BoxFuture<T> = Pin<Box<dyn Future<Output = T>>>;
 
+#[derive(serde::Serialize, serde::Deserialize)]
 pub struct CPU {
     pub regs: Registers,
     // Flags are in principle a register but we moved it outside of regs for lifetime reasons,
     // because there are operations we want to do over mut regs and flags at the same time.
     // TODO: this may no longer be necessary (?)
-    pub flags: Flags,
+    pub flags: LazyFlags,
     pub fpu: FPU,
+    /// SSE control and status register.
+    pub mxcsr: u32,
+    /// The processor CPUID describes.
+    pub cpuid: CpuProfile,
+    pub debug: DebugRegs,
 
     pub state: CPUState,
+    /// Suspended CPUs are skipped by the scheduler, whatever their state.
+    pub suspended: bool,
+    /// Scheduling priority, following Windows THREAD_PRIORITY_* values.
+    pub priority: i32,
+    /// Number of times the scheduler passed over this CPU while it was ready to run,
+    /// which counts towards its priority so that it eventually gets a turn.
+    pub starved: u32,
+    /// The range of memory the current block wrote to in pages with PAGE_TRAPS_RECORD
+    /// bits, which X86 must act on before executing further.
+    #[serde(skip)]
+    pub(crate) written: Option<(u32, u32)>,
 
     /// If eip==MAGIC_ADDR, then the next step is to poll a future rather than
     /// executing a basic block.
-    futures: Vec<BoxFuture<()>>,
+    /// Each future is paired with the esp at the time it was started.
+    /// Futures can't be saved, so a save state is only possible while there are none;
+    /// see X86::has_futures().
+    #[serde(skip)]
+    futures: Vec<(u32, BoxFuture<()>)>,
 }
 
 impl CPU {
     pub fn new() -> Self {
         CPU {
             regs: Registers::default(),
-            flags: Flags::empty(),
+            flags: LazyFlags::default(),
             fpu: FPU::default(),
+            mxcsr: MXCSR_DEFAULT,
+            cpuid: CpuProfile::default(),
+            debug: DebugRegs::default(),
             state: Default::default(),
+            suspended: false,
+            priority: 0,
+            starved: 0,
+            written: None,
             futures: Default::default(),
         }
     }
@@ -65,14 +131,24 @@ impl CPU {
         self.state = CPUState::Error(msg);
     }
 
-    /// Jump to an address, verifying it's within valid bounds.
+    pub fn fault(&mut self, fault: Fault) {
+        self.state = CPUState::Fault(fault);
+    }
+
+    /// Note a write to [addr, addr+len) in a page with PAGE_TRAPS_RECORD bits.
+    pub fn record_write(&mut self, addr: u32, len: u32) {
+        let end = addr.saturating_add(len);
+        self.written = Some(match self.written {
+            Some((start, prev_end)) => (start.min(addr), prev_end.max(end)),
+            None => (addr, end),
+        });
+    }
+
+    /// Jump to an address, faulting as the fetch from it would if it's in the null page
+    /// or out of bounds.
     pub fn jmp(&mut self, mem: Mem, addr: u32) {
-        if addr < 0x1000 {
-            self.err(format!("jmp to null page addr={addr:x}"));
-            return;
-        }
-        if mem.is_oob::<u8>(addr) && addr != MAGIC_ADDR {
-            self.err(format!("jmp to oob addr={addr:x}"));
+        if addr < 0x1000 || (mem.is_oob::<u8>(addr) && addr != MAGIC_ADDR) {
+            self.fault(Fault::AccessViolation { addr, write: false });
             return;
         }
         self.regs.eip = addr;
@@ -80,6 +156,7 @@ impl CPU {
 
     /// Set up the CPU such that we are making a Rust->x86 call, returning a Future
     /// that completes when the x86 call returns.
+    /// The callee may be either stdcall or cdecl.
     pub fn call_x86(&mut self, mem: Mem, func: u32, args: Vec<u32>) -> X86Future {
         // Save original esp, as that's the marker that we use to know when the call is done.
         let esp = self.regs.get32(Register::ESP);
@@ -87,6 +164,7 @@ impl CPU {
         for &arg in args.iter().rev() {
             ops::push(self, mem, arg);
         }
+        let args_esp = self.regs.get32(Register::ESP);
         ops::push(self, mem, MAGIC_ADDR); // return address
         self.jmp(mem, func);
 
@@ -96,7 +174,11 @@ impl CPU {
         self.regs.set32(Register::ECX, 0);
         self.regs.set32(Register::EDX, 0);
 
-        X86Future { cpu: self, esp }
+        X86Future {
+            cpu: self,
+            esp,
+            args_esp,
+        }
     }
 
     /// Set up the CPU such that we are making an x86->async call, enqueuing a Future
@@ -104,22 +186,35 @@ impl CPU {
     pub fn call_async(&mut self, future: BoxFuture<u32>, return_address: u32) {
         self.regs.eip = MAGIC_ADDR;
         let cpu = self as *mut CPU;
-        self.futures.push(Box::pin(async move {
-            let cpu = unsafe { &mut *cpu };
-            let ret = future.await;
-            cpu.regs.set32(Register::EAX, ret);
-            cpu.regs.eip = return_address;
-        }));
+        let esp = self.regs.get32(Register::ESP);
+        self.futures.push((
+            esp,
+            Box::pin(async move {
+                let cpu = unsafe { &mut *cpu };
+                let ret = future.await;
+                // The future may have redirected execution itself, e.g. by restoring the
+                // context of an exception, in which case there's nothing to return to.
+                if cpu.regs.eip == MAGIC_ADDR {
+                    cpu.regs.set32(Register::EAX, ret);
+                    cpu.regs.eip = return_address;
+                }
+            }),
+        ));
     }
 
     fn async_executor(&mut self) {
-        let future = self.futures.last_mut().unwrap();
-        // TODO: we don't use the waker at all.  Rust doesn't like us passing a random null pointer
-        // here but it seems like nothing accesses it(?).
-        //let c = unsafe { std::task::Context::from_waker(&Waker::from_raw(std::task::RawWaker::)) };
-        #[allow(deref_nullptr)]
-        let context: &mut std::task::Context = unsafe { &mut *std::ptr::null_mut() };
-        let poll = future.as_mut().poll(context);
+        // Code run from within a future may never return to it, as when an exception handler
+        // unwinds the stack past it.  Such futures were started deeper in the stack than
+        // where we are now, and will never complete.
+        let esp = self.regs.get32(Register::ESP);
+        while matches!(self.futures.last(), Some(&(start_esp, _)) if start_esp < esp) {
+            self.futures.pop();
+        }
+        let (_, future) = self.futures.last_mut().unwrap();
+        // We don't use the waker at all: futures are polled again whenever the CPU gets
+        // back to them.
+        let mut context = std::task::Context::from_waker(std::task::Waker::noop());
+        let poll = future.as_mut().poll(&mut context);
         match poll {
             Poll::Ready(()) => {
                 self.futures.pop();
@@ -133,9 +228,20 @@ impl CPU {
         BlockFuture { cpu: self }
     }
 
-    // Useful to disassemble this function (see misc/dump-fn.sh):
+    /// Execute at most budget instructions of a block, returning how many ran.
+    pub fn execute_block(&mut self, mem: Mem, block: &BasicBlock, budget: usize) -> usize {
+        // Only blocks with x87 instructions pay for checking for a pending #MF.
+        if block.waits {
+            self.execute_ops::<true>(mem, &block.ops, budget)
+        } else {
+            self.execute_ops::<false>(mem, &block.ops, budget)
+        }
+    }
+
+    // Useful to disassemble this function (see misc/dump-fn.sh), in place of inline(always):
     // #[inline(never)]
-    pub fn execute_block(&mut self, mem: Mem, block: &BasicBlock) -> usize {
+    #[inline(always)]
+    fn execute_ops<const WAITS: bool>(&mut self, mem: Mem, ops: &[Op], budget: usize) -> usize {
         // Performance note: this function is the central hottest loop in the emulator.
         // Some things I've tried:
         // - changing eip to be a usize: worth a few percent when usize!=u32
@@ -145,14 +251,21 @@ impl CPU {
         //   2) macro paste the block: macro_rules! unroll { ($code:tt) => { $code $code $code $code } }
 
         let mut count = 0;
-        for op in block.ops.iter() {
+        for op in ops.iter().take(budget) {
+            if WAITS && op.waits && self.fpu.status.contains(crate::fpu::Status::ES) {
+                self.fault(Fault::FloatingPoint);
+                break;
+            }
             let prev_ip = self.regs.eip;
             self.regs.eip = op.instr.next_ip() as u32;
             count += 1;
             (op.op)(self, mem, &op.instr);
             match self.state {
+                // The rest of the block may have been overwritten, or a watchpoint hit.
+                CPUState::Running if self.written.is_some() => break,
                 CPUState::Running => continue,
-                CPUState::Error(_) => {
+                CPUState::Fault(Fault::SingleStep) => break,
+                CPUState::Fault(_) | CPUState::Error(_) => {
                     // Point the debugger at the failed instruction.
                     self.regs.eip = prev_ip;
                     break;
@@ -169,6 +282,8 @@ pub struct X86Future {
     // https://github.com/rust-lang/futures-rs/issues/316
     cpu: *mut CPU,
     esp: u32,
+    /// The esp after a cdecl callee returns, leaving its arguments for the caller to pop.
+    args_esp: u32,
 }
 impl Future for X86Future {
     type Output = u32;
@@ -176,7 +291,11 @@ impl Future for X86Future {
     fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
         let cpu = self.cpu;
         let cpu = unsafe { &mut *cpu };
-        if cpu.regs.get32(Register::ESP) == self.esp {
+        let esp = cpu.regs.get32(Register::ESP);
+        if esp == self.esp {
+            Poll::Ready(cpu.regs.get32(Register::EAX))
+        } else if esp == self.args_esp {
+            cpu.regs.set32(Register::ESP, self.esp);
             Poll::Ready(cpu.regs.get32(Register::EAX))
         } else {
             Poll::Pending
@@ -205,15 +324,84 @@ impl Future for BlockFuture {
     }
 }
 
+/// The default number of instructions a CPU runs before the scheduler switches away.
+pub const DEFAULT_QUANTUM: u32 = 1 << 9;
+
+fn default_quantum() -> u32 {
+    DEFAULT_QUANTUM
+}
+
+/// Whether the scheduler may pick a CPU in this state: one that can run, or that needs
+/// the machine to act on it (e.g. handle a syscall) before it can.
+fn is_ready(state: &CPUState) -> bool {
+    matches!(
+        state,
+        CPUState::Running
+            | CPUState::SysCall
+            | CPUState::ReadTsc
+            | CPUState::Fault(_)
+            | CPUState::Error(_)
+    )
+}
+
+fn default_backend() -> Box<dyn Backend> {
+    Box::new(Interpreter::default())
+}
+
+mod pinned_cpus {
+    use super::CPU;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::pin::Pin;
+
+    pub fn serialize<S: Serializer>(cpus: &[Pin<Box<CPU>>], s: S) -> Result<S::Ok, S::Error> {
+        s.collect_seq(cpus.iter().map(|cpu| &**cpu))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<Pin<Box<CPU>>>, D::Error> {
+        Ok(Vec::<CPU>::deserialize(d)?
+            .into_iter()
+            .map(Box::pin)
+            .collect())
+    }
+}
+
+/// Serialization of X86 covers the CPUs and scheduling, for save states; the backend and
+/// the debugger's breakpoints and watches are left out.
+#[derive(serde::Serialize, serde::Deserialize)]
 pub struct X86 {
     /// CPUs are boxed because their futures take pointers to self.
+    #[serde(with = "pinned_cpus")]
     pub cpus: Vec<Pin<Box<CPU>>>,
     pub cur_cpu: usize,
+    /// Number of further instructions the current CPU may run before switching to another.
+    /// Slices are counted in instructions rather than blocks so that thread switches land
+    /// on the same instruction however blocks happen to be split, e.g. by single-stepping,
+    /// which makes runs reproducible.
+    slice: u32,
+    /// The length of a full time slice, in instructions.  This is a setting rather than
+    /// state, so save states don't carry it.
+    #[serde(skip, default = "default_quantum")]
+    pub quantum: u32,
 
     /// Total number of instructions executed.
     pub instr_count: usize,
 
-    pub icache: InstrCache,
+    /// What executes instructions; see backend.rs.
+    #[serde(skip, default = "default_backend")]
+    backend: Box<dyn Backend>,
+    /// Run only a single instruction on the next execute_block().
+    #[serde(skip)]
+    step_next: bool,
+
+    /// Memory ranges that stop the CPU with DebugBreak when written to.
+    #[serde(skip)]
+    watches: Vec<std::ops::Range<u32>>,
+    /// The debugger's hardware breakpoints, which stop any CPU with DebugBreak.
+    #[serde(skip)]
+    hw_breakpoints: Vec<HwBreakpoint>,
+
+    /// The processor CPUID describes, for all CPUs.
+    cpu_profile: CpuProfile,
 }
 
 impl X86 {
@@ -221,8 +409,25 @@ impl X86 {
         X86 {
             cpus: vec![Box::pin(CPU::new())],
             cur_cpu: 0,
+            slice: 0,
+            quantum: DEFAULT_QUANTUM,
             instr_count: 0,
-            icache: InstrCache::default(),
+            backend: default_backend(),
+            step_next: false,
+            watches: Vec::new(),
+            hw_breakpoints: Vec::new(),
+            cpu_profile: CpuProfile::default(),
+        }
+    }
+
+    pub fn cpu_profile(&self) -> CpuProfile {
+        self.cpu_profile
+    }
+
+    pub fn set_cpu_profile(&mut self, profile: CpuProfile) {
+        self.cpu_profile = profile;
+        for cpu in self.cpus.iter_mut() {
+            cpu.cpuid = profile;
         }
     }
 
@@ -234,20 +439,140 @@ impl X86 {
         &mut *self.cpus[self.cur_cpu]
     }
 
-    pub fn new_cpu(&mut self) -> &mut CPU {
-        self.cpus.push(Box::pin(CPU::new()));
-        self.cpus.last_mut().unwrap()
+    /// Allocate a CPU for a new thread, reusing the slot of an exited one if possible.
+    /// Returns the index of the CPU.
+    pub fn new_cpu(&mut self) -> usize {
+        let mut cpu = Box::pin(CPU::new());
+        cpu.cpuid = self.cpu_profile;
+        match self
+            .cpus
+            .iter()
+            .position(|cpu| matches!(cpu.state, CPUState::Free))
+        {
+            Some(i) => {
+                self.cpus[i] = cpu;
+                i
+            }
+            None => {
+                self.cpus.push(cpu);
+                self.cpus.len() - 1
+            }
+        }
     }
 
-    pub fn single_step_next_block(&mut self, mem: Mem) {
-        let ip = self.cpu().regs.eip;
-        if ip == MAGIC_ADDR {
-            return;
+    /// Stop with DebugBreak after any instruction that writes to [addr, addr+len).
+    /// Returns false if there already was such a watch.
+    pub fn add_watch(&mut self, mem: Mem, addr: u32, len: u32) -> bool {
+        let range = addr..addr.saturating_add(len);
+        if self.watches.contains(&range) {
+            return false;
+        }
+        mem.set_page_traps(addr, len, PAGE_TRAP_WATCH, true);
+        self.watches.push(range);
+        true
+    }
+
+    /// Undo an add_watch().
+    pub fn clear_watch(&mut self, mem: Mem, addr: u32, len: u32) -> bool {
+        let range = addr..addr.saturating_add(len);
+        let Some(index) = self.watches.iter().position(|w| *w == range) else {
+            return false;
+        };
+        self.watches.remove(index);
+        // Other watches may share the pages.
+        mem.set_page_traps(addr, len, PAGE_TRAP_WATCH, false);
+        for w in self.watches.iter() {
+            mem.set_page_traps(w.start, w.end - w.start, PAGE_TRAP_WATCH, true);
+        }
+        true
+    }
+
+    /// Stop with DebugBreak on the hardware breakpoint, unlike add_watch() catching reads
+    /// and execution too, at the cost of running an instruction at a time.
+    /// Returns false if there already was such a breakpoint.
+    pub fn add_hw_breakpoint(&mut self, bp: HwBreakpoint) -> bool {
+        if self.hw_breakpoints.contains(&bp) {
+            return false;
         }
-        self.icache.make_single_step(mem, ip);
+        self.hw_breakpoints.push(bp);
+        true
+    }
+
+    /// Undo an add_hw_breakpoint().
+    pub fn clear_hw_breakpoint(&mut self, bp: &HwBreakpoint) -> bool {
+        let Some(index) = self.hw_breakpoints.iter().position(|b| b == bp) else {
+            return false;
+        };
+        self.hw_breakpoints.remove(index);
+        true
+    }
+
+    /// Whether any CPU is partway through a call into retrowin32, which can't be saved.
+    pub fn has_futures(&self) -> bool {
+        self.cpus.iter().any(|cpu| !cpu.futures.is_empty())
+    }
+
+    /// Take on the CPUs and scheduling of a deserialized X86, as loaded from a save state,
+    /// keeping this one's backend and debugger state.  The backend forgets all code, as
+    /// memory was replaced too.
+    pub fn restore(&mut self, mem: Mem, saved: X86) {
+        self.cpus = saved.cpus;
+        self.cur_cpu = saved.cur_cpu;
+        self.slice = saved.slice;
+        self.instr_count = saved.instr_count;
+        self.cpu_profile = saved.cpu_profile;
+        self.step_next = false;
+        self.backend.invalidate(mem, 0, mem.len());
+    }
+
+    pub fn backend_stats(&self) -> String {
+        self.backend.stats()
+    }
+
+    /// Run hot code through the JIT (see jit/mod.rs) rather than only the interpreter,
+    /// returning false if this host can't.  Only x86-64 unix hosts can.
+    pub fn enable_jit(&mut self) -> bool {
+        #[cfg(all(target_arch = "x86_64", unix))]
+        match crate::jit::Jit::new() {
+            Ok(jit) => {
+                self.backend = Box::new(jit);
+                true
+            }
+            Err(err) => {
+                log::warn!("jit: {err}");
+                false
+            }
+        }
+        #[cfg(not(all(target_arch = "x86_64", unix)))]
+        false
+    }
+
+    /// Discard any code the backend derived from [addr, addr+len), after the debugger
+    /// patched it or the guest asked with FlushInstructionCache.
+    pub fn clear_cache(&mut self, mem: Mem, addr: u32, len: u32) {
+        self.backend
+            .invalidate(mem, addr, addr.saturating_add(len.max(1)));
+    }
+
+    /// Make the next execute_block() run only one instruction.
+    pub fn single_step_next_block(&mut self) {
+        self.step_next = true;
+    }
+
+    /// End the current time slice early, so that the next schedule() picks again, e.g.
+    /// because a thread of higher priority became ready.
+    pub fn preempt(&mut self) {
+        self.slice = 0;
     }
 
     /// Schedule the next runnable thread to run.
+    /// CPUs only ever switch between instructions, so every instruction, and in particular
+    /// every LOCK-prefixed read-modify-write, is atomic with respect to the other CPUs.
+    ///
+    /// The current CPU keeps running until it blocks or its slice of `quantum` instructions
+    /// runs out; then the ready CPU of highest priority runs next, taking turns with equals.
+    /// Every CPU passed over gains a step of priority until it's picked, so that lower
+    /// priority threads are delayed rather than starved.
     pub fn schedule(&mut self) {
         // log::info!(
         //     "cpustate {:?}",
@@ -258,54 +583,524 @@ impl X86 {
         // );
         // let prev = self.cur_cpu;
 
-        // Common perf-sensitive case: find next runnable CPU.
+        // Common perf-sensitive case: keep running the current CPU until its slice is used up.
+        if self.slice > 0 {
+            let cpu = self.cpu();
+            if !cpu.suspended && cpu.state.is_running() {
+                return;
+            }
+        }
+
+        // Otherwise pick the ready CPU of highest priority, searching from the one after
+        // the current CPU so that equals take turns.
+        let mut best: Option<(usize, i64)> = None;
         for i in 0..self.cpus.len() {
             let i = (self.cur_cpu + i + 1) % self.cpus.len();
-            match self.cpus[i].state {
-                CPUState::Running | CPUState::SysCall | CPUState::Error(_) => {
-                    self.cur_cpu = i;
-                    return;
+            let cpu = &self.cpus[i];
+            if cpu.suspended || !is_ready(&cpu.state) {
+                continue;
+            }
+            let priority = cpu.priority as i64 + cpu.starved as i64;
+            if best.is_none_or(|(_, best)| priority > best) {
+                best = Some((i, priority));
+            }
+        }
+        if let Some((next, _)) = best {
+            for (i, cpu) in self.cpus.iter_mut().enumerate() {
+                if i == next {
+                    cpu.starved = 0;
+                } else if !cpu.suspended && is_ready(&cpu.state) {
+                    cpu.starved += 1;
                 }
-                _ => {}
             }
+            self.cur_cpu = next;
+            self.slice = self.quantum;
+            return;
         }
 
         // Otherwise, find the CPU that will unblock soonest.
         let mut soonest = None;
         for (i, cpu) in self.cpus.iter().enumerate() {
+            if cpu.suspended {
+                continue;
+            }
             match cpu.state {
                 CPUState::Running | CPUState::Free => {}
-                CPUState::DebugBreak | CPUState::Error(_) | CPUState::SysCall => {
+                CPUState::DebugBreak
+                | CPUState::Error(_)
+                | CPUState::SysCall
+                | CPUState::ReadTsc
+                | CPUState::Fault(_) => {
                     self.cur_cpu = i;
                     return;
                 }
                 CPUState::Blocked(wait) => match soonest {
                     None => soonest = Some((i, wait)),
                     Some((_, soonest_wait)) => {
-                        if wait < soonest_wait {
+                        // A wait with no deadline comes after any deadline.
+                        let sooner = match (wait, soonest_wait) {
+                            (Some(wait), Some(soonest_wait)) => wait < soonest_wait,
+                            (Some(_), None) => true,
+                            (None, _) => false,
+                        };
+                        if sooner {
                             soonest = Some((i, wait));
                         }
                     }
                 },
             }
         }
-        self.cur_cpu = soonest.unwrap().0;
+        self.cur_cpu = soonest.expect("no runnable threads").0;
+        self.slice = 0;
 
         // if self.cur_cpu != prev || !self.cpu().state.is_running() {
         //     log::info!("cpu {prev}=>{} {:?}", self.cur_cpu, self.cpu().state);
         // }
     }
 
-    /// Execute one basic block starting at current ip.
+    /// Execute one basic block starting at current ip, or as much of it as fits in the
+    /// current time slice.
     pub fn execute_block(&mut self, mem: Mem) {
+        // Builtins may have written to code since the last block.
+        if let Some((start, end)) = memory::take_builtin_writes() {
+            if self.note_writes(mem, start, end) {
+                self.cpus[self.cur_cpu].state = CPUState::DebugBreak;
+                return;
+            }
+        }
         let cpu = &mut *self.cpus[self.cur_cpu];
         debug_assert!(cpu.state.is_running());
+        let step = std::mem::take(&mut self.step_next);
         if cpu.regs.eip == MAGIC_ADDR {
             cpu.async_executor();
+            // Polling counts as an instruction, so that it too uses up the slice.
+            self.slice = self.slice.saturating_sub(1);
             return;
         }
-        let block = self.icache.get_block(mem, cpu.regs.eip);
-        let count = cpu.execute_block(mem, block);
+        // With TF set, or with hardware breakpoints, run one instruction at a time and trap
+        // after each.
+        let trap = cpu.flags.contains(Flags::TF);
+        let debug = cpu.debug.enabled() || !self.hw_breakpoints.is_empty();
+        let mut accesses = Vec::new();
+        let mut rep_ecx = None;
+        if debug {
+            // Instruction breakpoints are faults, checked before the instruction runs.
+            let eip = cpu.regs.eip;
+            if !cpu.flags.contains(Flags::RF) {
+                let hits = cpu.debug.hits(|bp| bp.hits_execute(eip));
+                if hits != 0 {
+                    cpu.debug.dr6 |= hits;
+                    cpu.fault(Fault::SingleStep);
+                    return;
+                }
+                if self.hw_breakpoints.iter().any(|bp| bp.hits_execute(eip)) {
+                    // Set RF so that continuing runs the instruction.
+                    cpu.flags.insert(Flags::RF);
+                    cpu.state = CPUState::DebugBreak;
+                    return;
+                }
+            }
+            let instr = debugregs::decode(mem, eip);
+            accesses = debugregs::accesses(cpu, &instr);
+            if debugregs::is_rep_string(&instr) {
+                rep_ecx = Some(cpu.regs.get32(Register::ECX));
+            }
+        }
+        // Without a slice from schedule(), e.g. when driven by hand, run one instruction.
+        let budget = if step || trap || debug {
+            1
+        } else {
+            self.slice.max(1) as usize
+        };
+        let count = if budget == 1 {
+            self.backend.step(cpu, mem)
+        } else {
+            self.backend.run(cpu, mem, budget)
+        };
         self.instr_count = self.instr_count.wrapping_add(count);
+        self.slice = self.slice.saturating_sub(count as u32);
+        if cpu.flags.contains(Flags::RF)
+            && matches!(
+                cpu.state,
+                CPUState::Running | CPUState::Fault(Fault::SingleStep)
+            )
+        {
+            cpu.flags.remove(Flags::RF);
+        }
+        if cpu.state.is_running() {
+            // Data breakpoints and single steps are traps, after the instruction.
+            let mut dr6 = 0;
+            if let Some(ecx) = rep_ecx {
+                let count = ecx.wrapping_sub(cpu.regs.get32(Register::ECX));
+                debugregs::widen_rep(&mut accesses, count, cpu.flags.contains(Flags::DF));
+            }
+            if debug {
+                dr6 = cpu
+                    .debug
+                    .hits(|bp| accesses.iter().any(|a| bp.hits_access(a)));
+            }
+            if trap {
+                dr6 |= DR6_BS;
+            }
+            if dr6 != 0 {
+                cpu.debug.dr6 |= dr6;
+                cpu.fault(Fault::SingleStep);
+            } else if debug
+                && self
+                    .hw_breakpoints
+                    .iter()
+                    .any(|bp| accesses.iter().any(|a| bp.hits_access(a)))
+            {
+                cpu.state = CPUState::DebugBreak;
+            }
+        }
+        if let Some((start, end)) = cpu.written.take() {
+            if self.note_writes(mem, start, end) && self.cpus[self.cur_cpu].state.is_running() {
+                self.cpus[self.cur_cpu].state = CPUState::DebugBreak;
+            }
+        }
+    }
+
+    /// Act on a write to [start, end) in pages with PAGE_TRAPS_RECORD bits, returning
+    /// whether it hit a watch.  Only writes to pages holding code need the backend to
+    /// forget any; pages merely watched, or data sharing a watched page, don't.
+    fn note_writes(&mut self, mem: Mem, start: u32, end: u32) -> bool {
+        if mem.page_traps_span(start, end - start) & PAGE_TRAP_CODE != 0 {
+            self.backend.invalidate(mem, start, end);
+        }
+        self.watches.iter().any(|w| w.start < end && start < w.end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use memory::ExtensionsMut;
+
+    #[test]
+    fn builtin_writes_invalidate_code() {
+        let mut buf = vec![0u8; 0x2000];
+        // mov eax,1; jmp $
+        buf[0x1000..0x1007].copy_from_slice(&[0xb8, 1, 0, 0, 0, 0xeb, 0xfe]);
+        let pages = memory::page_table(buf.len() as u32);
+        let range = buf.as_mut_ptr_range();
+        let mem =
+            Mem::from_ptrs(range.start as *const u8..range.end as *const u8).with_pages(&pages);
+
+        let mut x86 = X86::new();
+        x86.cpu_mut().regs.eip = 0x1000;
+        x86.execute_block(mem);
+        assert_eq!(x86.cpu().regs.get32(Register::EAX), 1);
+
+        // As a builtin like memcpy would, outside of the CPU.
+        mem.put_pod::<u32>(0x1001, 2);
+        x86.cpu_mut().regs.eip = 0x1000;
+        x86.execute_block(mem);
+        assert_eq!(x86.cpu().regs.get32(Register::EAX), 2);
+        assert_eq!(memory::take_builtin_writes(), None);
+    }
+
+    #[test]
+    fn clear_cache_discards_range() {
+        let mut buf = vec![0u8; 0x2000];
+        buf[0x1000..0x1007].copy_from_slice(&[0xb8, 1, 0, 0, 0, 0xeb, 0xfe]);
+        let range = buf.as_mut_ptr_range();
+        // Without a page table nothing records writes, so only a flush can notice them.
+        let mem = Mem::from_ptrs(range.start as *const u8..range.end as *const u8);
+
+        let mut x86 = X86::new();
+        x86.cpu_mut().regs.eip = 0x1000;
+        x86.execute_block(mem);
+        unsafe { *range.start.add(0x1001) = 3 };
+        x86.clear_cache(mem, 0x1000, 7);
+        x86.cpu_mut().regs.eip = 0x1000;
+        x86.execute_block(mem);
+        assert_eq!(x86.cpu().regs.get32(Register::EAX), 3);
+    }
+
+    /// Run one instruction at 0x2000 with esi and edi as given, where the page at 0x1000
+    /// is inaccessible, and return the resulting CPU state.
+    fn run_by_noaccess_page(code: &[u8], esi: u32, edi: u32) -> CPUState {
+        let mut buf = vec![0u8; 0x3000];
+        buf[0x2000..][..code.len()].copy_from_slice(code);
+        let mut pages = memory::page_table(buf.len() as u32);
+        pages[1] = memory::PAGE_TRAPS_NO_ACCESS;
+        let range = buf.as_mut_ptr_range();
+        let mem =
+            Mem::from_ptrs(range.start as *const u8..range.end as *const u8).with_pages(&pages);
+
+        let mut x86 = X86::new();
+        let cpu = x86.cpu_mut();
+        cpu.regs.eip = 0x2000;
+        cpu.regs.set32(Register::ESP, 0x2F00);
+        cpu.regs.set32(Register::ESI, esi);
+        cpu.regs.set32(Register::EDI, edi);
+        x86.execute_block(mem);
+        let cpu = x86.cpu_mut();
+        if matches!(cpu.state, CPUState::Fault(_)) {
+            assert_eq!(cpu.regs.eip, 0x2000, "fault points at the instruction");
+        }
+        std::mem::take(&mut cpu.state)
+    }
+
+    #[test]
+    fn reads_of_noaccess_pages_fault() {
+        let read = CPUState::Fault(Fault::AccessViolation {
+            addr: 0x1000,
+            write: false,
+        });
+        let ok = (0x2800, 0x2800);
+        let cases: &[(&str, &[u8], (u32, u32))] = &[
+            ("mov eax, [0x1000]", &[0x8B, 0x05, 0, 0x10, 0, 0], ok),
+            ("add eax, [0x1000]", &[0x03, 0x05, 0, 0x10, 0, 0], ok),
+            (
+                "movzx eax, byte [0x1000]",
+                &[0x0F, 0xB6, 0x05, 0, 0x10, 0, 0],
+                ok,
+            ),
+            ("push dword [0x1000]", &[0xFF, 0x35, 0, 0x10, 0, 0], ok),
+            ("fld dword [0x1000]", &[0xD9, 0x05, 0, 0x10, 0, 0], ok),
+            (
+                "movups xmm0, [0x1000]",
+                &[0x0F, 0x10, 0x05, 0, 0x10, 0, 0],
+                ok,
+            ),
+            ("lodsd", &[0xAD], (0x1000, 0x2800)),
+            ("movsd", &[0xA5], (0x1000, 0x2800)),
+            ("cmpsd", &[0xA7], (0x2800, 0x1000)),
+            ("scasd", &[0xAF], (0x2800, 0x1000)),
+        ];
+        for &(name, code, (esi, edi)) in cases {
+            assert_eq!(run_by_noaccess_page(code, esi, edi), read, "{name}");
+        }
+        // Stores fault as writes.
+        assert_eq!(
+            run_by_noaccess_page(&[0x89, 0x05, 0, 0x10, 0, 0], ok.0, ok.1),
+            CPUState::Fault(Fault::AccessViolation {
+                addr: 0x1000,
+                write: true,
+            })
+        );
+        // The same reads elsewhere run.
+        assert!(!matches!(
+            run_by_noaccess_page(&[0x8B, 0x05, 0, 0x28, 0, 0], ok.0, ok.1),
+            CPUState::Fault(_)
+        ));
+    }
+
+    #[test]
+    fn accesses_past_memory_fault() {
+        // Memory ends at 0x3000, so everything past it is a guard page.
+        let cases: &[(&str, &[u8], u32, u32, bool)] = &[
+            ("mov eax, [esi]", &[0x8B, 0x06], 0x2FFE, 0x2FFE, false),
+            (
+                "mov eax, [esi]",
+                &[0x8B, 0x06],
+                0x8000_0000,
+                0x8000_0000,
+                false,
+            ),
+            ("mov [edi], eax", &[0x89, 0x07], 0x2FFE, 0x2FFE, true),
+            (
+                "mov [edi], eax",
+                &[0x89, 0x07],
+                0xFFFF_FFFE,
+                0xFFFF_FFFE,
+                true,
+            ),
+            ("lodsd", &[0xAD], 0xFFFF_FFFC, 0xFFFF_FFFC, false),
+        ];
+        for &(name, code, reg, addr, write) in cases {
+            assert_eq!(
+                run_by_noaccess_page(code, reg, reg),
+                CPUState::Fault(Fault::AccessViolation { addr, write }),
+                "{name} at {reg:#x}"
+            );
+        }
+    }
+
+    /// Run `rep stosd` over the four dwords at 0x1800 with a write watchpoint on the
+    /// dword at addr, and return DR6.
+    fn rep_stosd_watching(addr: u32, backward: bool) -> u32 {
+        let mut buf = vec![0u8; 0x2000];
+        buf[0x1000..0x1002].copy_from_slice(&[0xf3, 0xab]);
+        let range = buf.as_mut_ptr_range();
+        let mem = Mem::from_ptrs(range.start as *const u8..range.end as *const u8);
+
+        let mut x86 = X86::new();
+        let cpu = x86.cpu_mut();
+        cpu.regs.eip = 0x1000;
+        cpu.regs.set32(Register::ECX, 4);
+        cpu.regs
+            .set32(Register::EDI, if backward { 0x180C } else { 0x1800 });
+        if backward {
+            cpu.flags.insert(Flags::DF);
+        }
+        cpu.debug.set(0, addr);
+        // L0, writes, 4 bytes.
+        cpu.debug.set(7, 0b1 | 0b1101 << 16);
+        x86.execute_block(mem);
+        assert_eq!(x86.cpu().regs.get32(Register::ECX), 0);
+        x86.cpu().debug.dr6 & 0xF
+    }
+
+    #[test]
+    fn rep_string_hits_every_element() {
+        assert_eq!(rep_stosd_watching(0x1800, false), 1);
+        assert_eq!(rep_stosd_watching(0x180C, false), 1);
+        assert_eq!(rep_stosd_watching(0x1810, false), 0);
+        assert_eq!(rep_stosd_watching(0x1800, true), 1);
+        assert_eq!(rep_stosd_watching(0x17FC, true), 0);
+    }
+
+    /// The interpreter, counting the calls to invalidate().
+    struct CountInvalidates(Interpreter, std::rc::Rc<std::cell::Cell<usize>>);
+
+    impl Backend for CountInvalidates {
+        fn run(&mut self, cpu: &mut CPU, mem: Mem, budget: usize) -> usize {
+            self.0.run(cpu, mem, budget)
+        }
+
+        fn invalidate(&mut self, mem: Mem, start: u32, end: u32) {
+            self.1.set(self.1.get() + 1);
+            self.0.invalidate(mem, start, end);
+        }
+    }
+
+    #[test]
+    fn watched_writes_keep_code() {
+        let mut buf = vec![0u8; 0x3000];
+        // loop: mov [2000h],eax; mov [1800h],eax; jmp loop
+        buf[0x1000..0x100C]
+            .copy_from_slice(&[0xa3, 0x00, 0x20, 0, 0, 0xa3, 0x00, 0x18, 0, 0, 0xeb, 0xf4]);
+        let pages = memory::page_table(buf.len() as u32);
+        let range = buf.as_mut_ptr_range();
+        let mem =
+            Mem::from_ptrs(range.start as *const u8..range.end as *const u8).with_pages(&pages);
+
+        let mut x86 = X86::new();
+        let invalidates = std::rc::Rc::new(std::cell::Cell::new(0));
+        x86.backend = Box::new(CountInvalidates(
+            Interpreter::default(),
+            invalidates.clone(),
+        ));
+        // Elsewhere in the page written, so the writes trap but don't hit it.
+        x86.add_watch(mem, 0x2800, 4);
+        x86.cpu_mut().regs.eip = 0x1000;
+        x86.execute_block(mem);
+        assert_eq!(x86.cpu().regs.eip, 0x1005);
+        assert_eq!(invalidates.get(), 0);
+
+        // But data sharing a page with code might be code.
+        x86.execute_block(mem);
+        assert_eq!(x86.cpu().regs.eip, 0x100A);
+        assert_eq!(invalidates.get(), 1);
+        assert_eq!(x86.cpu().state, CPUState::Running);
+    }
+
+    #[test]
+    fn pending_fpu_exception_faults_next_wait() {
+        let mut buf = vec![0u8; 0x2000];
+        // fld1; fldz; fdivp st(1),st; fld1; jmp $
+        let code = [0xd9, 0xe8, 0xd9, 0xee, 0xde, 0xf9, 0xd9, 0xe8, 0xeb, 0xfe];
+        buf[0x1000..0x1000 + code.len()].copy_from_slice(&code);
+        let range = buf.as_mut_ptr_range();
+        let mem = Mem::from_ptrs(range.start as *const u8..range.end as *const u8);
+
+        let mut x86 = X86::new();
+        let cpu = x86.cpu_mut();
+        cpu.regs.eip = 0x1000;
+        // Unmask zero divide.
+        cpu.fpu.control = crate::fpu::DEFAULT_CONTROL & !0x4;
+        x86.slice = 100;
+        x86.execute_block(mem);
+        // The divide completes and leaves the exception pending, for the fld1 after it.
+        let cpu = x86.cpu();
+        assert_eq!(cpu.state, CPUState::Fault(Fault::FloatingPoint));
+        assert_eq!(cpu.regs.eip, 0x1006);
+        assert!(cpu
+            .fpu
+            .status
+            .contains(crate::fpu::Status::ZE | crate::fpu::Status::ES));
+        assert_eq!(cpu.fpu.st[cpu.fpu.st_top], f64::INFINITY);
+    }
+
+    /// An X86 with a CPU for each of the given priorities.
+    fn cpus_with_priorities(priorities: &[i32]) -> X86 {
+        let mut x86 = X86::new();
+        while x86.cpus.len() < priorities.len() {
+            x86.new_cpu();
+        }
+        for (cpu, &priority) in x86.cpus.iter_mut().zip(priorities) {
+            cpu.priority = priority;
+        }
+        x86
+    }
+
+    /// The CPUs schedule() picks for n slices in a row, each used up in full.
+    fn picks(x86: &mut X86, n: usize) -> Vec<usize> {
+        (0..n)
+            .map(|_| {
+                x86.preempt();
+                x86.schedule();
+                x86.cur_cpu
+            })
+            .collect()
+    }
+
+    #[test]
+    fn equal_priorities_take_turns() {
+        let mut x86 = cpus_with_priorities(&[0, 0, 0]);
+        assert_eq!(picks(&mut x86, 6), [1, 2, 0, 1, 2, 0]);
+    }
+
+    #[test]
+    fn priority_wins_but_lower_priorities_age_in() {
+        let mut x86 = cpus_with_priorities(&[0, 2, 0]);
+        // CPU 1 runs until the others have waited out the difference in priority, and
+        // from then on gets every other slice.
+        assert_eq!(picks(&mut x86, 8), [1, 1, 2, 0, 1, 2, 1, 0]);
+
+        // However large the gap, a ready CPU waits a bounded number of slices.
+        let mut x86 = cpus_with_priorities(&[-15, 15]);
+        let picks = picks(&mut x86, 100);
+        let low = picks.iter().filter(|&&cpu| cpu == 0).count();
+        assert!((3..=4).contains(&low), "{picks:?}");
+    }
+
+    #[test]
+    fn blocked_and_suspended_cpus_are_skipped() {
+        let mut x86 = cpus_with_priorities(&[0, 2, 1]);
+        x86.cpus[1].state = CPUState::Blocked(None);
+        x86.cpus[2].suspended = true;
+        assert_eq!(picks(&mut x86, 3), [0, 0, 0]);
+        // Starvation isn't counted while a CPU can't run.
+        assert_eq!((x86.cpus[1].starved, x86.cpus[2].starved), (0, 0));
+
+        // With nothing ready, the blocked CPU with the soonest deadline is current.
+        x86.cpus[0].state = CPUState::Blocked(Some(50));
+        x86.cpus[1].state = CPUState::Blocked(Some(20));
+        x86.preempt();
+        x86.schedule();
+        assert_eq!(x86.cur_cpu, 1);
+    }
+
+    #[test]
+    fn wakeup_of_higher_priority_preempts() {
+        let mut x86 = cpus_with_priorities(&[0, 2]);
+        x86.cpus[1].state = CPUState::Blocked(None);
+        x86.preempt();
+        x86.schedule();
+        assert_eq!(x86.cur_cpu, 0);
+        assert_eq!(x86.slice, DEFAULT_QUANTUM);
+
+        // Readying a CPU alone leaves the current one its slice...
+        x86.cpus[1].state = CPUState::Running;
+        x86.schedule();
+        assert_eq!(x86.cur_cpu, 0);
+        // ...until it's preempted, as wake_waiters() does for higher priorities.
+        x86.preempt();
+        x86.schedule();
+        assert_eq!(x86.cur_cpu, 1);
     }
 }